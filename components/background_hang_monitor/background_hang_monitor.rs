@@ -283,6 +283,13 @@ impl SignalToExitOnDrop {
         }
     }
 
+    /// Call `BackgroundHangMonitorExitSignal::signal_to_interrupt` now.
+    fn signal_to_interrupt(&self) {
+        if let Some(signal) = self.0.as_ref() {
+            signal.signal_to_interrupt();
+        }
+    }
+
     /// Disassociate `BackgroundHangMonitorExitSignal` from itself, preventing
     /// `BackgroundHangMonitorExitSignal::signal_to_exit` from being called in
     /// the future.
@@ -454,6 +461,18 @@ impl BackgroundHangMonitorWorker {
                         // Also exit the BHM.
                         return false;
                     },
+                    Ok(BackgroundHangMonitorControlMsg::InterruptComponent(component_id)) => {
+                        if let Some(component) = self.monitored_components.get(&component_id) {
+                            component.exit_signal.signal_to_interrupt();
+                        }
+                        None
+                    },
+                    Ok(BackgroundHangMonitorControlMsg::ExitComponent(component_id)) => {
+                        if let Some(component) = self.monitored_components.get_mut(&component_id) {
+                            component.exit_signal.signal_to_exit();
+                        }
+                        None
+                    },
                     Err(_) => return false,
                 }
             }
@@ -529,6 +548,11 @@ impl BackgroundHangMonitorWorker {
                     .monitored_components
                     .get_mut(&component_id)
                     .expect("Received NotifyActivity for an unknown component");
+                if component.sent_permanent_alert {
+                    let _ = self
+                        .constellation_chan
+                        .send(HangMonitorAlert::HangEnded(component_id));
+                }
                 component.last_activity = Instant::now();
                 component.last_annotation = Some(annotation);
                 component.sent_transient_alert = false;
@@ -540,6 +564,11 @@ impl BackgroundHangMonitorWorker {
                     .monitored_components
                     .get_mut(&component_id)
                     .expect("Received NotifyWait for an unknown component");
+                if component.sent_permanent_alert {
+                    let _ = self
+                        .constellation_chan
+                        .send(HangMonitorAlert::HangEnded(component_id));
+                }
                 component.last_activity = Instant::now();
                 component.sent_transient_alert = false;
                 component.sent_permanent_alert = false;
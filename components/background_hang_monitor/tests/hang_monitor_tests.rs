@@ -40,7 +40,7 @@ fn test_hang_monitoring() {
     );
 
     // Start an activity.
-    let hang_annotation = HangAnnotation::Script(ScriptHangAnnotation::AttachLayout);
+    let hang_annotation = HangAnnotation::Script(ScriptHangAnnotation::AttachLayout, None);
     background_hang_monitor.notify_activity(hang_annotation);
 
     // Sleep until the "transient" timeout has been reached.
@@ -69,7 +69,13 @@ fn test_hang_monitoring() {
 
     // Now the component is not hanging anymore.
     background_hang_monitor.notify_activity(hang_annotation);
-    assert!(background_hang_monitor_receiver.try_recv().is_err());
+    match background_hang_monitor_receiver.recv().unwrap() {
+        HangMonitorAlert::HangEnded(component_id) => {
+            let expected = MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script);
+            assert_eq!(expected, component_id);
+        },
+        _ => unreachable!(),
+    }
 
     // Sleep for a while.
     thread::sleep(Duration::from_millis(10));
@@ -144,7 +150,7 @@ fn test_hang_monitoring_unregister() {
     );
 
     // Start an activity.
-    let hang_annotation = HangAnnotation::Script(ScriptHangAnnotation::AttachLayout);
+    let hang_annotation = HangAnnotation::Script(ScriptHangAnnotation::AttachLayout, None);
     background_hang_monitor.notify_activity(hang_annotation);
 
     // Unregister the component.
@@ -258,3 +264,56 @@ fn test_hang_monitoring_exit_signal_inner(op_order: fn(&mut dyn FnMut(), &mut dy
         thread::sleep(Duration::from_millis(10));
     }
 }
+
+#[test]
+fn test_hang_monitoring_interrupt_component() {
+    let _lock = SERIAL.lock().unwrap();
+
+    let (background_hang_monitor_ipc_sender, _background_hang_monitor_receiver) =
+        ipc::channel().expect("ipc channel failure");
+    let (control_sender, control_receiver) = ipc::channel().expect("ipc channel failure");
+
+    struct BHMExitSignal {
+        closing: Arc<AtomicBool>,
+        interrupted: Arc<AtomicBool>,
+    }
+
+    impl BackgroundHangMonitorExitSignal for BHMExitSignal {
+        fn signal_to_exit(&self) {
+            self.closing.store(true, Ordering::SeqCst);
+        }
+
+        fn signal_to_interrupt(&self) {
+            self.interrupted.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let closing = Arc::new(AtomicBool::new(false));
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let background_hang_monitor_register = HangMonitorRegister::init(
+        background_hang_monitor_ipc_sender.clone(),
+        control_receiver,
+        false,
+    );
+    let _background_hang_monitor = background_hang_monitor_register.register_component(
+        MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script),
+        Duration::from_millis(10),
+        Duration::from_millis(1000),
+        Some(Box::new(BHMExitSignal {
+            closing: closing.clone(),
+            interrupted: interrupted.clone(),
+        })),
+    );
+
+    control_sender
+        .send(BackgroundHangMonitorControlMsg::InterruptComponent(
+            MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script),
+        ))
+        .unwrap();
+
+    // Assert we get the interrupt signal, but not the exit signal.
+    while !interrupted.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!closing.load(Ordering::SeqCst));
+}
@@ -17,9 +17,9 @@ use std::borrow::ToOwned;
 use std::collections::{HashMap, HashSet};
 use std::string::String;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use base::id::WebViewId;
+use base::id::{PipelineId, WebViewId};
 use bitflags::bitflags;
 use bluetooth_traits::blocklist::{Blocklist, uuid_is_blocklisted};
 use bluetooth_traits::scanfilter::{
@@ -27,11 +27,11 @@ use bluetooth_traits::scanfilter::{
 };
 use bluetooth_traits::{
     BluetoothCharacteristicMsg, BluetoothDescriptorMsg, BluetoothDeviceMsg, BluetoothError,
-    BluetoothRequest, BluetoothResponse, BluetoothResponseResult, BluetoothResult,
-    BluetoothServiceMsg, GATTType,
+    BluetoothNotificationRequest, BluetoothRequest, BluetoothResponse, BluetoothResponseResult,
+    BluetoothResult, BluetoothServiceMsg, GATTType,
 };
 use embedder_traits::{EmbedderMsg, EmbedderProxy};
-use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender, TryRecvError};
 use log::warn;
 use servo_config::pref;
 use servo_rand::{self, Rng};
@@ -47,6 +47,10 @@ const MAXIMUM_TRANSACTION_TIME: u8 = 30;
 const CONNECTION_TIMEOUT_MS: u64 = 1000;
 // The discovery session needs some time to find any nearby devices
 const DISCOVERY_TIMEOUT_MS: u64 = 1500;
+// The adapters do not report new values of characteristics, so the characteristics that have
+// started notifications are checked for new values this often, whether or not requests arrive
+// in the meantime.
+const NOTIFICATION_POLL_INTERVAL_MS: u64 = 100;
 
 bitflags! {
     struct Flags: u32 {
@@ -209,6 +213,13 @@ pub struct BluetoothManager {
     cached_characteristics: HashMap<String, BluetoothGATTCharacteristic>,
     cached_descriptors: HashMap<String, BluetoothGATTDescriptor>,
     allowed_services: HashMap<String, HashSet<String>>,
+    /// For each characteristic that has started notifications, the channels of the documents
+    /// that receive its new values.
+    notification_subscribers: HashMap<String, HashMap<PipelineId, IpcSender<Vec<u8>>>>,
+    /// The last value of each characteristic that has started notifications.
+    notified_values: HashMap<String, Vec<u8>>,
+    /// When the characteristics that have started notifications are next checked for new values.
+    next_notification_poll: Instant,
     embedder_proxy: EmbedderProxy,
 }
 
@@ -230,12 +241,15 @@ impl BluetoothManager {
             cached_characteristics: HashMap::new(),
             cached_descriptors: HashMap::new(),
             allowed_services: HashMap::new(),
+            notification_subscribers: HashMap::new(),
+            notified_values: HashMap::new(),
+            next_notification_poll: Instant::now(),
             embedder_proxy,
         }
     }
 
     fn start(&mut self) {
-        while let Ok(msg) = self.receiver.recv() {
+        while let Some(msg) = self.next_request() {
             match msg {
                 BluetoothRequest::RequestDevice(options, sender) => {
                     let _ = sender.send(self.request_device(options));
@@ -255,8 +269,8 @@ impl BluetoothManager {
                 BluetoothRequest::WriteValue(id, value, sender) => {
                     let _ = sender.send(self.write_value(id, value));
                 },
-                BluetoothRequest::EnableNotification(id, enable, sender) => {
-                    let _ = sender.send(self.enable_notification(id, enable));
+                BluetoothRequest::EnableNotification(id, request, sender) => {
+                    let _ = sender.send(self.enable_notification(id, request));
                 },
                 BluetoothRequest::WatchAdvertisements(id, sender) => {
                     let _ = sender.send(self.watch_advertisements(id));
@@ -278,11 +292,38 @@ impl BluetoothManager {
                 BluetoothRequest::MatchesFilter(id, filters, sender) => {
                     let _ = sender.send(self.device_matches_filter(&id, &filters));
                 },
+                BluetoothRequest::PipelineExited(pipeline_id) => {
+                    self.remove_notification_subscriber(pipeline_id)
+                },
                 BluetoothRequest::Exit => break,
             }
         }
     }
 
+    /// Wait for the next request, while sending the new values of the characteristics that
+    /// have started notifications to the documents that receive them.
+    fn next_request(&mut self) -> Option<BluetoothRequest> {
+        loop {
+            if self.notification_subscribers.is_empty() {
+                return self.receiver.recv().ok();
+            }
+            let now = Instant::now();
+            if now >= self.next_notification_poll {
+                self.send_notifications();
+                self.next_notification_poll =
+                    now + Duration::from_millis(NOTIFICATION_POLL_INTERVAL_MS);
+            }
+            match self
+                .receiver
+                .try_recv_timeout(self.next_notification_poll.saturating_duration_since(now))
+            {
+                Ok(msg) => return Some(msg),
+                Err(TryRecvError::Empty) => {},
+                Err(TryRecvError::IpcError(_)) => return None,
+            }
+        }
+    }
+
     // Test
 
     fn test(&mut self, data_set_name: String) -> BluetoothResult<()> {
@@ -295,6 +336,8 @@ impl BluetoothManager {
         self.cached_characteristics.clear();
         self.cached_descriptors.clear();
         self.allowed_services.clear();
+        self.notification_subscribers.clear();
+        self.notified_values.clear();
         self.adapter = BluetoothAdapter::new_mock().ok();
         match test::test(self, data_set_name) {
             Ok(_) => Ok(()),
@@ -316,6 +359,8 @@ impl BluetoothManager {
         for id in characteristic_ids {
             self.cached_characteristics.remove(&id);
             self.characteristic_to_service.remove(&id);
+            self.notification_subscribers.remove(&id);
+            self.notified_values.remove(&id);
         }
 
         for id in descriptor_ids {
@@ -942,7 +987,11 @@ impl BluetoothManager {
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
-    fn enable_notification(&mut self, id: String, enable: bool) -> BluetoothResponseResult {
+    fn enable_notification(
+        &mut self,
+        id: String,
+        request: BluetoothNotificationRequest,
+    ) -> BluetoothResponseResult {
         // (StartNotifications) Step 3 - 4.
         // (StopNotifications) Step 1 - 2.
         if !self.characteristic_is_cached(&id) {
@@ -951,27 +1000,116 @@ impl BluetoothManager {
 
         // (StartNotification) TODO: Step 7: Missing because it is optional.
         let mut adapter = self.get_adapter()?;
-        match self.get_gatt_characteristic(&mut adapter, &id) {
-            Some(c) => {
-                let result = if enable {
+        let Some(characteristic) = self.get_gatt_characteristic(&mut adapter, &id).cloned() else {
+            // (StartNotification) Step 4.
+            return Err(BluetoothError::InvalidState);
+        };
+        let is_notifying = self.notification_subscribers.contains_key(&id);
+        let is_started = matches!(request, BluetoothNotificationRequest::Start(..));
+        match request {
+            BluetoothNotificationRequest::Start(pipeline_id, sender) => {
+                if !is_notifying {
                     // (StartNotification) Step 8.
                     // TODO: Handle all the errors returned from the start_notify call.
-                    c.start_notify()
-                } else {
-                    // (StopNotification) Step 4.
-                    c.stop_notify()
+                    if characteristic.start_notify().is_err() {
+                        // (StartNotification) Step 5.
+                        return Err(BluetoothError::NotSupported);
+                    }
+                    self.notified_values
+                        .insert(id.clone(), characteristic.get_value().unwrap_or_default());
+                }
+                self.notification_subscribers
+                    .entry(id)
+                    .or_default()
+                    .insert(pipeline_id, sender);
+            },
+            BluetoothNotificationRequest::Stop(pipeline_id) => {
+                let Some(subscribers) = self.notification_subscribers.get_mut(&id) else {
+                    return Ok(BluetoothResponse::EnableNotification(false));
                 };
-                match result {
-                    // (StartNotification) Step 11.
-                    // (StopNotification)  Step 5.
-                    Ok(_) => Ok(BluetoothResponse::EnableNotification(())),
-
-                    // (StartNotification) Step 5.
-                    Err(_) => Err(BluetoothError::NotSupported),
+                subscribers.remove(&pipeline_id);
+
+                // (StopNotification) Step 4.
+                // The notifications are only stopped once no document receives them.
+                if subscribers.is_empty() {
+                    self.notification_subscribers.remove(&id);
+                    self.notified_values.remove(&id);
+                    if characteristic.stop_notify().is_err() {
+                        return Err(BluetoothError::NotSupported);
+                    }
                 }
             },
-            // (StartNotification) Step 4.
-            None => Err(BluetoothError::InvalidState),
+        }
+
+        // (StartNotification) Step 11.
+        // (StopNotification)  Step 5.
+        Ok(BluetoothResponse::EnableNotification(is_started))
+    }
+
+    /// Stop sending the new values of characteristics to a document that went away, and stop
+    /// the notifications of the characteristics that no other document receives.
+    fn remove_notification_subscriber(&mut self, pipeline_id: PipelineId) {
+        let mut unsubscribed_ids = vec![];
+        self.notification_subscribers.retain(|id, subscribers| {
+            subscribers.remove(&pipeline_id);
+            if subscribers.is_empty() {
+                unsubscribed_ids.push(id.clone());
+                return false;
+            }
+            true
+        });
+        if unsubscribed_ids.is_empty() {
+            return;
+        }
+        for id in &unsubscribed_ids {
+            self.notified_values.remove(id);
+        }
+
+        let Ok(mut adapter) = self.get_adapter() else {
+            return;
+        };
+        for id in unsubscribed_ids {
+            if let Some(characteristic) = self.get_gatt_characteristic(&mut adapter, &id) {
+                let _ = characteristic.stop_notify();
+            }
+        }
+    }
+
+    /// Send the new value of each characteristic that has started notifications to the
+    /// documents that receive them. A value is only sent when it differs from the previous
+    /// one, as the adapters only keep the last value that the device notified.
+    ///
+    /// <https://webbluetoothcg.github.io/web-bluetooth/#notification-events>
+    fn send_notifications(&mut self) {
+        let Ok(mut adapter) = self.get_adapter() else {
+            return;
+        };
+        let ids: Vec<String> = self.notification_subscribers.keys().cloned().collect();
+        for id in ids {
+            let Some(characteristic) = self.get_gatt_characteristic(&mut adapter, &id).cloned()
+            else {
+                continue;
+            };
+            let Ok(value) = characteristic.get_value() else {
+                continue;
+            };
+            if self.notified_values.get(&id) == Some(&value) {
+                continue;
+            }
+            let Some(subscribers) = self.notification_subscribers.get_mut(&id) else {
+                continue;
+            };
+
+            // Documents that went away no longer receive the values, and the notifications
+            // are stopped once none is left.
+            subscribers.retain(|_, sender| sender.send(value.clone()).is_ok());
+            if subscribers.is_empty() {
+                self.notification_subscribers.remove(&id);
+                self.notified_values.remove(&id);
+                let _ = characteristic.stop_notify();
+                continue;
+            }
+            self.notified_values.insert(id, value);
         }
     }
 
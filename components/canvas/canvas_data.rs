@@ -16,8 +16,10 @@ use fonts::{
 use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
 use log::warn;
 use num_traits::ToPrimitive;
+use pixels::ColorSpace;
 use range::Range;
 use servo_arc::Arc as ServoArc;
+use servo_config::pref;
 use style::color::AbsoluteColor;
 use style::properties::style_structs::Font as FontStyleStruct;
 use unicode_script::Script;
@@ -589,6 +591,19 @@ fn create_backend() -> Box<dyn Backend> {
     Box::new(crate::raqote_backend::RaqoteBackend)
 }
 
+/// The contents of `draw_target` as an image for WebRender. The canvas draws in sRGB, so its
+/// contents are converted when WebRender renders in Display P3.
+fn webrender_image_data(draw_target: &dyn GenericDrawTarget) -> SerializableImageData {
+    let mut data = draw_target.snapshot_data_owned();
+    if pref!(gfx_display_p3_enabled) {
+        pixels::bgra8_premultiplied_convert_to_color_space_inplace(
+            &mut data,
+            ColorSpace::DisplayP3,
+        );
+    }
+    SerializableImageData::Raw(IpcSharedMemory::from_bytes(&data))
+}
+
 impl<'a> CanvasData<'a> {
     pub fn new(
         size: Size2D<u64>,
@@ -605,9 +620,7 @@ impl<'a> CanvasData<'a> {
             offset: 0,
             flags: ImageDescriptorFlags::empty(),
         };
-        let data = SerializableImageData::Raw(IpcSharedMemory::from_bytes(
-            &draw_target.snapshot_data_owned(),
-        ));
+        let data = webrender_image_data(&*draw_target);
         compositor_api.update_images(vec![ImageUpdate::AddImage(image_key, descriptor, data)]);
         CanvasData {
             backend,
@@ -1421,9 +1434,7 @@ impl<'a> CanvasData<'a> {
             offset: 0,
             flags: ImageDescriptorFlags::empty(),
         };
-        let data = SerializableImageData::Raw(IpcSharedMemory::from_bytes(
-            &self.drawtarget.snapshot_data_owned(),
-        ));
+        let data = webrender_image_data(&*self.drawtarget);
 
         self.compositor_api
             .update_images(vec![ImageUpdate::UpdateImage(
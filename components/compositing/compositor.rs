@@ -22,29 +22,33 @@ use compositing_traits::{
 };
 use constellation_traits::{
     AnimationTickType, CompositorHitTestResult, ConstellationMsg, PaintMetricEvent,
-    UntrustedNodeAddress, WindowSizeData, WindowSizeType,
+    UntrustedNodeAddress, VisualViewportState, WindowSizeData, WindowSizeType,
 };
 use crossbeam_channel::Sender;
 use dpi::PhysicalSize;
 use embedder_traits::{
-    Cursor, InputEvent, MouseButtonEvent, MouseMoveEvent, ShutdownState, TouchEventType,
+    Cursor, InputEvent, MouseButtonEvent, MouseMoveEvent, ScreenId, ScreenInfo, ScreenTopology,
+    ShutdownState, TouchEventType, WheelDelta, WheelEvent, WheelMode,
 };
-use euclid::{Box2D, Point2D, Rect, Scale, Size2D, Transform3D};
+use euclid::{Box2D, Point2D, Rect, Scale, Size2D, Transform3D, Vector2D};
 use fnv::FnvHashMap;
+use image::{RgbaImage, imageops};
 use ipc_channel::ipc::{self, IpcSharedMemory};
+use ipc_channel::router::ROUTER;
 use libc::c_void;
 use log::{debug, info, trace, warn};
 use pixels::{CorsStatus, Image, ImageFrame, PixelFormat};
+use profile_traits::mem::{self as profile_mem, Report, ReportKind, Reporter, ReportsChan};
 use profile_traits::time::{self as profile_time, ProfilerCategory};
-use profile_traits::time_profile;
+use profile_traits::{path, time_profile};
 use script_traits::AnimationState;
-use servo_config::opts;
+use servo_config::{opts, pref};
 use servo_geometry::DeviceIndependentPixel;
 use style_traits::{CSSPixel, PinchZoomFactor};
-use webrender::{CaptureBits, RenderApi, Transaction};
+use webrender::{CaptureBits, MemoryReport, RenderApi, Transaction};
 use webrender_api::units::{
-    DeviceIntPoint, DeviceIntRect, DevicePixel, DevicePoint, DeviceRect, LayoutPoint, LayoutRect,
-    LayoutSize, LayoutVector2D, WorldPoint,
+    DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePixel, DevicePoint, DeviceRect,
+    LayoutPoint, LayoutRect, LayoutSize, LayoutVector2D, WorldPoint,
 };
 use webrender_api::{
     self, BuiltDisplayList, DirtyRect, DisplayListPayload, DocumentId, Epoch as WebRenderEpoch,
@@ -53,11 +57,14 @@ use webrender_api::{
     RenderReasons, SampledScrollOffset, ScrollLocation, SpaceAndClipInfo, SpatialId,
     SpatialTreeItemKey, TransformStyle,
 };
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use webrender_api::{FontHinting, FontInstancePlatformOptions};
 use webrender_traits::display_list::{HitTestInfo, ScrollTree};
-use webrender_traits::rendering_context::RenderingContext;
-use webrender_traits::{CrossProcessCompositorMessage, ImageUpdate};
+use webrender_traits::rendering_context::{OffscreenRenderTarget, RenderingContext};
+use webrender_traits::{CrossProcessCompositorMessage, ImageUpdate, ScrollAnimation};
 
 use crate::InitialCompositorState;
+use crate::screenshot::PageScreenshot;
 use crate::webview::{UnknownWebView, WebView, WebViewManager};
 use crate::windowing::{self, EmbedderCoordinates, WebRenderDebugOption, WindowMethods};
 
@@ -73,10 +80,22 @@ enum NotReadyToPaint {
     WaitingOnConstellation,
 }
 
+/// The name that the compositor registers its memory reporter with.
+const COMPOSITOR_MEMORY_REPORTER_NAME: &str = "compositor";
+
 // Default viewport constraints
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// The pinch zoom level that a double tap zooms in to.
+const DOUBLE_TAP_ZOOM_FACTOR: f32 = 2.0;
+
+/// The pipeline of the root display list, which holds those of the `WebView`s. Every display
+/// list needs a pipeline, but we'd like to choose one that is unlikely to conflict with our
+/// content pipelines, which start at (1, 1). (0, 0) is WebRender's dummy pipeline, so we
+/// choose (0, 1).
+const ROOT_PIPELINE_ID: WebRenderPipelineId = WebRenderPipelineId(0, 1);
+
 /// Holds the state when running reftests that determines when it is
 /// safe to save the output image.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -105,6 +124,9 @@ pub struct ServoRenderer {
     /// The channel on which messages can be sent to the time profiler.
     time_profiler_chan: profile_time::ProfilerChan,
 
+    /// The channel on which messages can be sent to the memory profiler.
+    mem_profiler_chan: profile_mem::ProfilerChan,
+
     /// The WebRender [`RenderApi`] interface used to communicate with WebRender.
     pub(crate) webrender_api: RenderApi,
 
@@ -150,8 +172,9 @@ pub struct IOCompositor {
     min_viewport_zoom: Option<PinchZoomFactor>,
     max_viewport_zoom: Option<PinchZoomFactor>,
 
-    /// "Desktop-style" zoom that resizes the viewport to fit the window.
-    page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
+    /// The offset of the visual viewport, the part of the window that is visible when it is
+    /// pinch-zoomed, from the origin of the window, in the coordinates of the root display list.
+    visual_viewport_offset: LayoutVector2D,
 
     /// Tracks whether or not the view needs to be repainted.
     needs_repaint: Cell<RepaintReason>,
@@ -175,12 +198,36 @@ pub struct IOCompositor {
     /// The coordinates of the native window, its view and the screen.
     embedder_coordinates: EmbedderCoordinates,
 
+    /// The screen that the native window was on the last time that the screen topology
+    /// was queried. This is used to detect when the window moves between screens.
+    current_screen: Option<ScreenId>,
+
     /// The number of frames pending to receive from WebRender.
     pending_frames: usize,
 
     /// The [`Instant`] of the last animation tick, used to avoid flooding the Constellation and
     /// ScriptThread with a deluge of animation ticks.
     last_animation_tick: Instant,
+
+    /// Whether the embedder notifies us of the vsync of the display. When it does, animations
+    /// and `requestAnimationFrame` callbacks are ticked once per vsync instead of after every
+    /// frame that is rendered, so that they stay aligned with the refresh of the display.
+    vsync_driven: bool,
+
+    /// When the caches of WebRender were last trimmed because of memory pressure.
+    last_memory_pressure: Option<Instant>,
+
+    /// The parts of the rendering context that WebRender drew when it last rendered, if
+    /// partial present is enabled.
+    damage: Vec<DeviceIntRect>,
+
+    /// Whether WebRender rendered to an off-screen target since it last rendered to the
+    /// rendering context. Its history of damage then no longer matches the buffers of the
+    /// rendering context, so the next frame must be rendered in full.
+    rendered_offscreen: bool,
+
+    /// The screenshot of a page that is being taken, a part of it at a time.
+    page_screenshot: Option<PageScreenshot>,
 }
 
 /// Why we need to be repainted. This is used for debugging.
@@ -242,6 +289,10 @@ pub(crate) struct PipelineDetails {
     /// information to the full information necessary for Servo.
     pub hit_test_items: Vec<HitTestInfo>,
 
+    /// The pipelines of the iframes of this pipeline that have `pointer-events: none`, so
+    /// that nothing in them is hit by hit tests.
+    pub iframes_without_pointer_events: Vec<PipelineId>,
+
     /// The compositor-side [ScrollTree]. This is used to allow finding and scrolling
     /// nodes in the compositor before forwarding new offsets to WebRender.
     pub scroll_tree: ScrollTree,
@@ -300,6 +351,7 @@ impl PipelineDetails {
             animation_callbacks_running: false,
             throttled: false,
             hit_test_items: Vec::new(),
+            iframes_without_pointer_events: Vec::new(),
             scroll_tree: ScrollTree::default(),
             first_paint_metric: PaintMetricState::Waiting,
             first_contentful_paint_metric: PaintMetricState::Waiting,
@@ -330,6 +382,27 @@ impl PipelineDetails {
     }
 }
 
+/// Whether the pipeline of `details` is in an iframe that has `pointer-events: none`, or is
+/// nested in one, in which case nothing in it is hit by hit tests.
+fn is_in_iframe_without_pointer_events<'a>(
+    details: &PipelineDetails,
+    details_for_pipeline: &impl Fn(PipelineId) -> Option<&'a PipelineDetails>,
+) -> bool {
+    let mut pipeline_id = details.id;
+    let mut parent_pipeline_id = details.parent_pipeline_id;
+    while let Some(parent_details) = parent_pipeline_id.and_then(details_for_pipeline) {
+        if parent_details
+            .iframes_without_pointer_events
+            .contains(&pipeline_id)
+        {
+            return true;
+        }
+        pipeline_id = parent_details.id;
+        parent_pipeline_id = parent_details.parent_pipeline_id;
+    }
+    false
+}
+
 impl ServoRenderer {
     pub fn shutdown_state(&self) -> ShutdownState {
         self.shutdown_state.get()
@@ -360,11 +433,19 @@ impl ServoRenderer {
     ) -> Vec<CompositorHitTestResult> {
         // DevicePoint and WorldPoint are the same for us.
         let world_point = WorldPoint::from_untyped(point.to_untyped());
-        let results =
-            self.webrender_api
-                .hit_test(self.webrender_document, pipeline_id, world_point, flags);
 
-        results
+        // WebRender returns the items that were hit in the order in which they are painted,
+        // which is not the order of the items of a 3D rendering context, and it does not know
+        // which iframes are not hit testable. All items are needed to find the topmost one.
+        let results = self.webrender_api.hit_test(
+            self.webrender_document,
+            pipeline_id,
+            world_point,
+            flags | HitTestFlags::FIND_ALL,
+        );
+        let hit_test_time = CrossProcessInstant::now();
+
+        let mut results: Vec<_> = results
             .items
             .iter()
             .filter_map(|item| {
@@ -379,17 +460,47 @@ impl ServoRenderer {
                     _ => return None,
                 }
 
+                if is_in_iframe_without_pointer_events(details, &details_for_pipeline) {
+                    return None;
+                }
+
                 let info = &details.hit_test_items[item.tag.0 as usize];
-                Some(CompositorHitTestResult {
+                let plane = info.plane.as_ref().map(|plane| {
+                    let depth = plane
+                        .depth_at(item.point_relative_to_item)
+                        .unwrap_or(f32::NEG_INFINITY);
+                    ((pipeline_id, plane.context), depth)
+                });
+                let result = CompositorHitTestResult {
                     pipeline_id,
                     point_in_viewport: item.point_in_viewport.to_untyped(),
                     point_relative_to_item: item.point_relative_to_item.to_untyped(),
                     node: UntrustedNodeAddress(info.node as *const c_void),
                     cursor: info.cursor,
                     scroll_tree_node: info.scroll_tree_node,
-                })
+                    hit_test_time,
+                };
+                Some((result, plane))
             })
-            .collect()
+            .collect();
+
+        // Within a 3D rendering context, the item that is closest to the viewer at the hit
+        // point is on top.
+        for items_in_context in results.chunk_by_mut(|(_, first_plane), (_, second_plane)| {
+            first_plane.is_some() &&
+                first_plane.map(|(context, _)| context) ==
+                    second_plane.map(|(context, _)| context)
+        }) {
+            items_in_context.sort_by(|(_, first_plane), (_, second_plane)| {
+                let depth = |plane: &Option<(_, f32)>| plane.map_or(0., |(_, depth)| depth);
+                depth(second_plane).total_cmp(&depth(first_plane))
+            });
+        }
+
+        if !flags.contains(HitTestFlags::FIND_ALL) {
+            results.truncate(1);
+        }
+        results.into_iter().map(|(result, _)| result).collect()
     }
 
     pub(crate) fn send_transaction(&mut self, transaction: Transaction) {
@@ -431,6 +542,26 @@ impl IOCompositor {
         convert_mouse_to_touch: bool,
         version_string: String,
     ) -> Self {
+        // Memory reports are collected on the compositor thread, which owns the WebRender
+        // renderer that knows about the textures that it allocated.
+        let (reporter_sender, reporter_receiver) = ipc::channel().unwrap();
+        let compositor_proxy = state.sender.clone();
+        ROUTER.add_typed_route(
+            reporter_receiver,
+            Box::new(move |message| {
+                if let Ok(request) = message {
+                    compositor_proxy
+                        .send(CompositorMsg::CollectMemoryReport(request.reports_channel));
+                }
+            }),
+        );
+        state
+            .mem_profiler_chan
+            .send(profile_mem::ProfilerMsg::RegisterReporter(
+                COMPOSITOR_MEMORY_REPORTER_NAME.into(),
+                Reporter(reporter_sender),
+            ));
+
         let compositor = IOCompositor {
             global: Rc::new(RefCell::new(ServoRenderer {
                 shutdown_state: state.shutdown_state,
@@ -438,6 +569,7 @@ impl IOCompositor {
                 compositor_receiver: state.receiver,
                 constellation_sender: state.constellation_chan,
                 time_profiler_chan: state.time_profiler_chan,
+                mem_profiler_chan: state.mem_profiler_chan.clone(),
                 webrender_api: state.webrender_api,
                 webrender_document: state.webrender_document,
                 webrender_gl: state.webrender_gl,
@@ -450,12 +582,13 @@ impl IOCompositor {
             })),
             webviews: WebViewManager::default(),
             embedder_coordinates: window.get_coordinates(),
+            current_screen: None,
             window,
             needs_repaint: Cell::default(),
-            page_zoom: Scale::new(1.0),
             viewport_zoom: PinchZoomFactor::new(1.0),
             min_viewport_zoom: Some(PinchZoomFactor::new(1.0)),
             max_viewport_zoom: None,
+            visual_viewport_offset: LayoutVector2D::zero(),
             zoom_action: false,
             zoom_time: 0f64,
             ready_to_save_state: ReadyState::Unknown,
@@ -463,6 +596,11 @@ impl IOCompositor {
             rendering_context: state.rendering_context,
             pending_frames: 0,
             last_animation_tick: Instant::now(),
+            vsync_driven: false,
+            last_memory_pressure: None,
+            damage: Vec::new(),
+            rendered_offscreen: false,
+            page_screenshot: None,
         };
 
         {
@@ -504,6 +642,13 @@ impl IOCompositor {
             .is_some()
         {}
 
+        self.global
+            .borrow()
+            .mem_profiler_chan
+            .send(profile_mem::ProfilerMsg::UnregisterReporter(
+                COMPOSITOR_MEMORY_REPORTER_NAME.into(),
+            ));
+
         // Tell the profiler, memory profiler, and scrolling timer to shut down.
         if let Ok((sender, receiver)) = ipc::channel() {
             self.global
@@ -548,8 +693,13 @@ impl IOCompositor {
                 }
 
                 if !throttled && animation_state == AnimationState::AnimationCallbacksPresent {
-                    // We need to fetch the WebView again in order to avoid a double borrow.
-                    if let Some(webview) = self.webviews.get(webview_id) {
+                    if self.vsync_driven {
+                        // The callbacks will run on the next vsync, which only needs the
+                        // embedder to keep notifying us of it.
+                        self.window
+                            .set_animation_state(windowing::AnimationState::Animating);
+                    } else if let Some(webview) = self.webviews.get(webview_id) {
+                        // We need to fetch the WebView again in order to avoid a double borrow.
                         webview.tick_animations_for_pipeline(pipeline_id, self);
                     }
                 }
@@ -582,6 +732,10 @@ impl IOCompositor {
                 }
             },
 
+            CompositorMsg::CreatePagePng(webview_id, rect, reply) => {
+                self.start_page_screenshot(webview_id, rect, reply);
+            },
+
             CompositorMsg::IsReadyToSaveImageReply(is_ready) => {
                 assert_eq!(
                     self.ready_to_save_state,
@@ -595,6 +749,10 @@ impl IOCompositor {
                 self.set_needs_repaint(RepaintReason::ReadyForScreenshot);
             },
 
+            CompositorMsg::CollectMemoryReport(reports_chan) => {
+                self.collect_memory_report(reports_chan);
+            },
+
             CompositorMsg::SetThrottled(webview_id, pipeline_id, throttled) => {
                 if let Some(webview) = self.webviews.get_mut(webview_id) {
                     webview.set_throttled(pipeline_id, throttled);
@@ -615,6 +773,17 @@ impl IOCompositor {
 
             CompositorMsg::NewWebRenderFrameReady(_document_id, recomposite_needed) => {
                 self.pending_frames -= 1;
+
+                // Once WebRender has built all the frames that were asked for, the one for the
+                // scene of a screenshot is among them.
+                if self.pending_frames == 0 &&
+                    self.page_screenshot
+                        .as_ref()
+                        .is_some_and(PageScreenshot::is_waiting_for_frame)
+                {
+                    self.take_page_screenshot();
+                }
+
                 let point: DevicePoint = self.global.borrow().cursor_pos;
 
                 if recomposite_needed {
@@ -640,27 +809,53 @@ impl IOCompositor {
             },
 
             CompositorMsg::WebDriverMouseButtonEvent(webview_id, action, button, x, y) => {
-                let dppx = self.device_pixels_per_page_pixel();
-                let point = dppx.transform_point(Point2D::new(x, y));
+                let device_pixels_per_root_pixel = self.device_pixels_per_root_pixel();
                 let Some(webview) = self.webviews.get_mut(webview_id) else {
                     warn!("Handling input event for unknown webview: {webview_id}");
                     return;
                 };
-                webview.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent {
-                    point,
-                    action,
-                    button,
-                }));
+                let dppx = webview.page_zoom * device_pixels_per_root_pixel;
+                let point = dppx.transform_point(Point2D::new(x, y));
+                webview.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+                    action, button, point,
+                )));
             },
 
             CompositorMsg::WebDriverMouseMoveEvent(webview_id, x, y) => {
-                let dppx = self.device_pixels_per_page_pixel();
+                let device_pixels_per_root_pixel = self.device_pixels_per_root_pixel();
+                let Some(webview) = self.webviews.get_mut(webview_id) else {
+                    warn!("Handling input event for unknown webview: {webview_id}");
+                    return;
+                };
+                let dppx = webview.page_zoom * device_pixels_per_root_pixel;
                 let point = dppx.transform_point(Point2D::new(x, y));
+                webview.dispatch_input_event(InputEvent::MouseMove(MouseMoveEvent::new(point)));
+            },
+
+            CompositorMsg::WebDriverWheelScrollEvent(webview_id, x, y, delta_x, delta_y) => {
+                let device_pixels_per_root_pixel = self.device_pixels_per_root_pixel();
                 let Some(webview) = self.webviews.get_mut(webview_id) else {
                     warn!("Handling input event for unknown webview: {webview_id}");
                     return;
                 };
-                webview.dispatch_input_event(InputEvent::MouseMove(MouseMoveEvent { point }));
+                let dppx = webview.page_zoom * device_pixels_per_root_pixel;
+                let point = dppx.transform_point(Point2D::new(x, y));
+                let scroll_delta =
+                    dppx.transform_vector(Vector2D::new(delta_x as f32, delta_y as f32));
+                webview.dispatch_input_event(InputEvent::Wheel(WheelEvent {
+                    delta: WheelDelta {
+                        x: delta_x,
+                        y: delta_y,
+                        z: 0.0,
+                        mode: WheelMode::DeltaPixel,
+                    },
+                    point,
+                }));
+                // Positive wheel deltas scroll towards the end of the page, which is the
+                // opposite of the direction of scroll deltas.
+                let scroll_location =
+                    ScrollLocation::Delta(LayoutVector2D::new(-scroll_delta.x, -scroll_delta.y));
+                webview.notify_scroll_event(scroll_location, point.to_i32(), TouchEventType::Move);
             },
 
             CompositorMsg::CrossProcess(cross_proces_message) => {
@@ -689,17 +884,25 @@ impl IOCompositor {
                 pipeline_id,
                 point,
                 external_scroll_id,
+                animation,
             ) => {
                 let Some(webview) = self.webviews.get_mut(webview_id) else {
                     return;
                 };
 
                 let pipeline_id = pipeline_id.into();
+                let offset = LayoutVector2D::new(point.x, point.y);
+                if animation == ScrollAnimation::Smooth {
+                    webview.start_smooth_scroll(pipeline_id, external_scroll_id, offset);
+                    self.process_animations(true);
+                    return;
+                }
+
+                webview.stop_smooth_scroll(pipeline_id, external_scroll_id);
                 let Some(pipeline_details) = webview.pipelines.get_mut(&pipeline_id) else {
                     return;
                 };
 
-                let offset = LayoutVector2D::new(point.x, point.y);
                 if !pipeline_details
                     .scroll_tree
                     .set_scroll_offsets_for_node_with_external_scroll_id(
@@ -719,6 +922,11 @@ impl IOCompositor {
                         generation: 0,
                     }],
                 );
+                txn.append_dynamic_transform_properties(
+                    pipeline_details
+                        .scroll_tree
+                        .scrollbar_thumb_transforms(external_scroll_id),
+                );
                 self.generate_frame(&mut txn, RenderReasons::APZ);
                 self.global.borrow_mut().send_transaction(txn);
             },
@@ -778,6 +986,11 @@ impl IOCompositor {
                 let details = webview.ensure_pipeline_details(pipeline_id.into());
                 details.most_recent_display_list_epoch = Some(display_list_info.epoch);
                 details.hit_test_items = display_list_info.hit_test_info;
+                details.iframes_without_pointer_events = display_list_info
+                    .iframes_without_pointer_events
+                    .into_iter()
+                    .map(PipelineId::from)
+                    .collect();
                 details.install_new_scroll_tree(display_list_info.scroll_tree);
 
                 let epoch = display_list_info.epoch;
@@ -920,6 +1133,11 @@ impl IOCompositor {
                     );
                 }
             },
+            CrossProcessCompositorMessage::GetScreenTopology(req) => {
+                if let Err(e) = req.send(self.screen_topology()) {
+                    warn!("Sending response to get screen topology failed ({:?}).", e);
+                }
+            },
         }
     }
 
@@ -989,6 +1207,15 @@ impl IOCompositor {
                     );
                 }
             },
+            CompositorMsg::CrossProcess(CrossProcessCompositorMessage::GetScreenTopology(req)) => {
+                if let Err(e) = req.send(self.screen_topology()) {
+                    warn!("Sending response to get screen topology failed ({:?}).", e);
+                }
+            },
+            CompositorMsg::CollectMemoryReport(reports_chan) => {
+                // The memory profiler waits for a reply from every reporter.
+                reports_chan.send(Vec::new());
+            },
             CompositorMsg::NewWebRenderFrameReady(..) => {
                 // Subtract from the number of pending frames, but do not do any compositing.
                 self.pending_frames -= 1;
@@ -1017,26 +1244,33 @@ impl IOCompositor {
 
     /// Set the root pipeline for our WebRender scene to a display list that consists of an iframe
     /// for each visible top-level browsing context, applying a transformation on the root for
-    /// pinch zoom, page zoom, and HiDPI scaling.
+    /// pinch zoom and HiDPI scaling, and one on each iframe for the page zoom of its `WebView`.
     pub(crate) fn send_root_pipeline_display_list_in_transaction(
         &self,
         transaction: &mut Transaction,
     ) {
-        // Every display list needs a pipeline, but we'd like to choose one that is unlikely
-        // to conflict with our content pipelines, which start at (1, 1). (0, 0) is WebRender's
-        // dummy pipeline, so we choose (0, 1).
-        let root_pipeline = WebRenderPipelineId(0, 1);
+        let root_pipeline = ROOT_PIPELINE_ID;
         transaction.set_root_pipeline(root_pipeline);
 
         let mut builder = webrender_api::DisplayListBuilder::new(root_pipeline);
         builder.begin();
 
-        let zoom_factor = self.device_pixels_per_page_pixel().0;
+        // The pinch zoom transform scales the window around the visual viewport. It applies
+        // to the whole layout viewport of the `WebView`s, so that boxes that are fixed to
+        // their layout viewport move along with it when the visual viewport is panned.
+        let zoom_factor = self.device_pixels_per_root_pixel().get();
+        let offset = self.visual_viewport_offset;
         let zoom_reference_frame = builder.push_reference_frame(
             LayoutPoint::zero(),
             SpatialId::root_reference_frame(root_pipeline),
             TransformStyle::Flat,
-            PropertyBinding::Value(Transform3D::scale(zoom_factor, zoom_factor, 1.)),
+            PropertyBinding::Value(
+                Transform3D::translation(-offset.x, -offset.y, 0.).then_scale(
+                    zoom_factor,
+                    zoom_factor,
+                    1.,
+                ),
+            ),
             ReferenceFrameKind::Transform {
                 is_2d_scale_translation: true,
                 should_snap: true,
@@ -1045,29 +1279,44 @@ impl IOCompositor {
             SpatialTreeItemKey::new(0, 0),
         );
 
-        let scaled_viewport_size =
-            self.rendering_context.size2d().to_f32().to_untyped() / zoom_factor;
-        let scaled_viewport_rect = LayoutRect::from_origin_and_size(
-            LayoutPoint::zero(),
-            LayoutSize::from_untyped(scaled_viewport_size),
-        );
+        let visual_viewport_rect =
+            LayoutRect::from_origin_and_size(offset.to_point(), self.visual_viewport_size());
 
-        let root_clip_id = builder.define_clip_rect(zoom_reference_frame, scaled_viewport_rect);
+        let root_clip_id = builder.define_clip_rect(zoom_reference_frame, visual_viewport_rect);
         let clip_chain_id = builder.define_clip_chain(None, [root_clip_id]);
-        for (_, webview) in self.webviews.painting_order() {
-            if let Some(pipeline_id) = webview.root_pipeline_id {
-                let scaled_webview_rect = webview.rect / zoom_factor;
-                builder.push_iframe(
-                    LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
-                    LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
-                    &SpaceAndClipInfo {
-                        spatial_id: zoom_reference_frame,
-                        clip_chain_id,
-                    },
-                    pipeline_id.into(),
-                    true,
-                );
-            }
+        for (index, (_, webview)) in self.webviews.painting_order().enumerate() {
+            let Some(pipeline_id) = webview.root_pipeline_id else {
+                continue;
+            };
+
+            // Page zoom scales the contents of each `WebView` separately, so that its layout
+            // viewport still fills the rectangle of the `WebView`.
+            let page_zoom = webview.page_zoom.get();
+            let page_zoom_reference_frame = builder.push_reference_frame(
+                LayoutPoint::zero(),
+                zoom_reference_frame,
+                TransformStyle::Flat,
+                PropertyBinding::Value(Transform3D::scale(page_zoom, page_zoom, 1.)),
+                ReferenceFrameKind::Transform {
+                    is_2d_scale_translation: true,
+                    should_snap: true,
+                    paired_with_perspective: false,
+                },
+                SpatialTreeItemKey::new(1, index as u64),
+            );
+            let scaled_webview_rect = webview.rect /
+                self.device_pixels_per_page_pixel_not_including_pinch_zoom(webview.page_zoom);
+            builder.push_iframe(
+                LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
+                LayoutRect::from_untyped(&scaled_webview_rect.to_untyped()),
+                &SpaceAndClipInfo {
+                    spatial_id: page_zoom_reference_frame,
+                    clip_chain_id,
+                },
+                pipeline_id.into(),
+                true,
+            );
+            builder.pop_reference_frame();
         }
 
         let built_display_list = builder.end();
@@ -1087,6 +1336,8 @@ impl IOCompositor {
     ///
     /// TODO(mrobinson): Could we only send offsets for the branch being modified
     /// and not the entire scene?
+    /// Scroll all of the scroll nodes that WebRender knows about to their offsets in the
+    /// compositor, and move the scrollbar thumbs of those nodes along.
     fn update_transaction_with_all_scroll_offsets(&self, transaction: &mut Transaction) {
         for webview in self.webviews.iter() {
             for details in webview.pipelines.values() {
@@ -1095,6 +1346,8 @@ impl IOCompositor {
                     else {
                         continue;
                     };
+                    transaction
+                        .append_dynamic_transform_properties(node.scrollbar_thumb_transforms());
 
                     let offset = LayoutVector2D::new(-offset.x, -offset.y);
                     transaction.set_scroll_offsets(
@@ -1131,6 +1384,11 @@ impl IOCompositor {
 
         webview.set_frame_tree(frame_tree);
         self.send_root_pipeline_display_list();
+
+        // A new top-level document starts out knowing nothing about the visual viewport.
+        if self.pinch_zoom_level().get() != 1. {
+            self.send_visual_viewport_to_script();
+        }
     }
 
     fn remove_webview(&mut self, webview_id: WebViewId) {
@@ -1161,7 +1419,9 @@ impl IOCompositor {
 
         if rect_changed {
             if size_changed {
-                self.send_window_size_message_for_top_level_browser_context(rect, webview_id);
+                if let Some(webview) = self.webviews.get(webview_id) {
+                    self.send_window_size_message_for_top_level_browser_context(webview);
+                }
             }
 
             self.send_root_pipeline_display_list();
@@ -1224,20 +1484,18 @@ impl IOCompositor {
         Ok(())
     }
 
-    fn send_window_size_message_for_top_level_browser_context(
-        &self,
-        rect: DeviceRect,
-        webview_id: WebViewId,
-    ) {
+    fn send_window_size_message_for_top_level_browser_context(&self, webview: &WebView) {
         // The device pixel ratio used by the style system should include the scale from page pixels
         // to device pixels, but not including any pinch zoom.
-        let device_pixel_ratio = self.device_pixels_per_page_pixel_not_including_page_zoom();
-        let initial_viewport = rect.size().to_f32() / device_pixel_ratio;
+        let device_pixel_ratio =
+            self.device_pixels_per_page_pixel_not_including_pinch_zoom(webview.page_zoom);
+        let initial_viewport = webview.rect.size().to_f32() / device_pixel_ratio;
         let msg = ConstellationMsg::WindowSize(
-            webview_id,
+            webview.id,
             WindowSizeData {
                 device_pixel_ratio,
                 initial_viewport,
+                text_zoom: webview.text_zoom,
             },
             WindowSizeType::Resize,
         );
@@ -1247,7 +1505,73 @@ impl IOCompositor {
     }
 
     pub fn on_embedder_window_moved(&mut self) {
+        self.update_embedder_coordinates();
+
+        // Moving the window may have moved it to a different screen, which is observable
+        // via `ScreenDetails.currentScreen`.
+        let current_screen = self.screen_topology().current_screen;
+        if self.current_screen.is_some() && self.current_screen != current_screen {
+            self.on_screens_changed();
+        }
+        self.current_screen = current_screen;
+    }
+
+    /// The embedder has informed us that screens have been connected, disconnected or had
+    /// their properties changed. Let all pipelines know so that they can update any
+    /// `ScreenDetails` objects and fire the appropriate events.
+    pub fn on_screens_changed(&mut self) {
+        self.update_embedder_coordinates();
+        self.current_screen = self.screen_topology().current_screen;
+        if let Err(error) = self
+            .global
+            .borrow()
+            .constellation_sender
+            .send(ConstellationMsg::ScreensChanged)
+        {
+            warn!("Sending screens changed to constellation failed ({error:?}).");
+        }
+    }
+
+    /// The embedder has informed us that the device pixel ratio of the window changed, for
+    /// instance because it was moved to a screen with a different pixel density.
+    pub fn on_hidpi_factor_changed(&mut self) {
+        self.update_embedder_coordinates();
+    }
+
+    /// Get the current [`EmbedderCoordinates`] from the embedder. If the device pixel ratio
+    /// of the window changed, which happens when it moves between screens with different
+    /// pixel densities, webviews are laid out and rasterized again at the new ratio. Returns
+    /// whether it changed.
+    fn update_embedder_coordinates(&mut self) -> bool {
+        let old_hidpi_factor = self.embedder_coordinates.hidpi_factor;
         self.embedder_coordinates = self.window.get_coordinates();
+        if self.embedder_coordinates.hidpi_factor == old_hidpi_factor {
+            return false;
+        }
+        self.update_after_zoom_or_hidpi_change();
+        self.set_needs_repaint(RepaintReason::Resize);
+        true
+    }
+
+    /// Get the current [`ScreenTopology`] from the embedder. If the embedder does not
+    /// provide details about the connected screens, fall back to a single primary screen
+    /// built from the [`EmbedderCoordinates`].
+    fn screen_topology(&self) -> ScreenTopology {
+        let mut screens = self.window.get_screens();
+        if screens.is_empty() {
+            let coordinates = &self.embedder_coordinates;
+            screens.push(ScreenInfo {
+                id: ScreenId(0),
+                label: String::new(),
+                rect: Box2D::from_size(coordinates.screen_size),
+                available_rect: Box2D::from_size(coordinates.available_screen_size),
+                device_pixel_ratio: coordinates.hidpi_factor.get(),
+                color_depth: 24,
+                is_primary: true,
+                is_internal: false,
+            });
+        }
+        ScreenTopology::new(screens, self.embedder_coordinates.window_rect)
     }
 
     pub fn resize_rendering_context(&mut self, new_size: PhysicalSize<u32>) -> bool {
@@ -1255,12 +1579,9 @@ impl IOCompositor {
             return false;
         }
 
-        let old_hidpi_factor = self.embedder_coordinates.hidpi_factor;
-        self.embedder_coordinates = self.window.get_coordinates();
-        if self.embedder_coordinates.hidpi_factor == old_hidpi_factor &&
-            self.rendering_context.size() == new_size
-        {
-            return false;
+        let hidpi_factor_changed = self.update_embedder_coordinates();
+        if self.rendering_context.size() == new_size {
+            return hidpi_factor_changed;
         }
 
         self.rendering_context.resize(new_size);
@@ -1279,13 +1600,12 @@ impl IOCompositor {
     }
 
     /// If there are any animations running, dispatches appropriate messages to the constellation.
+    /// Only the pipelines of webviews that are painted are ticked, so that hidden webviews don't
+    /// use resources for animations that cannot be seen.
     fn process_animations(&mut self, force: bool) {
         // When running animations in order to dump a screenshot (not after a full composite), don't send
-        // animation ticks faster than about 60Hz.
-        //
-        // TODO: This should be based on the refresh rate of the screen and also apply to all
-        // animation ticks, not just ones sent while waiting to dump screenshots. This requires
-        // something like a refresh driver concept though.
+        // animation ticks faster than about 60Hz. Otherwise, animations are ticked after each frame
+        // is rendered, or on vsync if the embedder notifies us of it.
         if !force && (Instant::now() - self.last_animation_tick) < Duration::from_millis(16) {
             return;
         }
@@ -1296,10 +1616,19 @@ impl IOCompositor {
         #[cfg(not(feature = "webxr"))]
         let webxr_running = false;
 
+        let mut webviews = take(&mut self.webviews);
+        let any_webviews_scrolling = webviews.iter_mut().fold(false, |scrolling, webview| {
+            let scrolling_smoothly = webview.tick_smooth_scrolls(self);
+            let scrolling_with_momentum = webview.tick_momentum_scroll(self);
+            scrolling_smoothly || scrolling_with_momentum || scrolling
+        });
+        self.webviews = webviews;
+
         let any_webviews_animating = !self
             .webviews
-            .iter()
-            .all(|webview| !webview.tick_all_animations(self));
+            .painting_order()
+            .all(|(_, webview)| !webview.tick_all_animations(self)) ||
+            any_webviews_scrolling;
 
         let animation_state = if !any_webviews_animating && !webxr_running {
             windowing::AnimationState::Idle
@@ -1314,38 +1643,88 @@ impl IOCompositor {
         self.embedder_coordinates.hidpi_factor
     }
 
-    pub(crate) fn device_pixels_per_page_pixel(&self) -> Scale<f32, CSSPixel, DevicePixel> {
-        self.device_pixels_per_page_pixel_not_including_page_zoom() * self.pinch_zoom_level()
+    /// The scale from the coordinates of the root display list to device pixels, which
+    /// includes HiDPI scaling and pinch zoom, but not the page zoom of any `WebView`.
+    pub(crate) fn device_pixels_per_root_pixel(
+        &self,
+    ) -> Scale<f32, DeviceIndependentPixel, DevicePixel> {
+        self.hidpi_factor() * self.pinch_zoom_level()
     }
 
-    fn device_pixels_per_page_pixel_not_including_page_zoom(
+    fn device_pixels_per_page_pixel_not_including_pinch_zoom(
         &self,
+        page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
     ) -> Scale<f32, CSSPixel, DevicePixel> {
-        self.page_zoom * self.hidpi_factor()
+        page_zoom * self.hidpi_factor()
+    }
+
+    /// The "desktop-style" zoom of the given `WebView`, which resizes its viewport to fit
+    /// the window.
+    pub fn page_zoom(&self, webview_id: WebViewId) -> f32 {
+        self.webviews
+            .get(webview_id)
+            .map_or(1.0, |webview| webview.page_zoom.get())
     }
 
-    pub fn on_zoom_reset_window_event(&mut self) {
+    pub fn set_page_zoom(&mut self, webview_id: WebViewId, page_zoom: f32) {
         if self.global.borrow().shutdown_state() != ShutdownState::NotShuttingDown {
             return;
         }
 
-        self.page_zoom = Scale::new(1.0);
-        self.update_after_zoom_or_hidpi_change();
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            warn!("{webview_id}: Setting page zoom of unknown webview");
+            return;
+        };
+        let page_zoom = Scale::new(page_zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        if std::mem::replace(&mut webview.page_zoom, page_zoom) == page_zoom {
+            return;
+        }
+
+        if let Some(webview) = self.webviews.get(webview_id) {
+            self.send_window_size_message_for_top_level_browser_context(webview);
+        }
+        // The page zoom changes where the visual viewport is within the page.
+        if self.pinch_zoom_level().get() != 1. {
+            self.send_visual_viewport_to_script();
+        }
+        self.send_root_pipeline_display_list();
+    }
+
+    /// The factor by which the size of the text of the pages in the given `WebView` is
+    /// multiplied, without affecting the size of anything else.
+    pub fn text_zoom(&self, webview_id: WebViewId) -> f32 {
+        self.webviews
+            .get(webview_id)
+            .map_or(1.0, |webview| webview.text_zoom)
     }
 
-    pub fn on_zoom_window_event(&mut self, magnification: f32) {
+    pub fn set_text_zoom(&mut self, webview_id: WebViewId, text_zoom: f32) {
         if self.global.borrow().shutdown_state() != ShutdownState::NotShuttingDown {
             return;
         }
 
-        self.page_zoom =
-            Scale::new((self.page_zoom.get() * magnification).clamp(MIN_ZOOM, MAX_ZOOM));
-        self.update_after_zoom_or_hidpi_change();
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            warn!("{webview_id}: Setting text zoom of unknown webview");
+            return;
+        };
+        let text_zoom = text_zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        if std::mem::replace(&mut webview.text_zoom, text_zoom) == text_zoom {
+            return;
+        }
+
+        if let Some(webview) = self.webviews.get(webview_id) {
+            self.send_window_size_message_for_top_level_browser_context(webview);
+        }
     }
 
     fn update_after_zoom_or_hidpi_change(&mut self) {
-        for (webview_id, webview) in self.webviews.painting_order() {
-            self.send_window_size_message_for_top_level_browser_context(webview.rect, *webview_id);
+        for (_, webview) in self.webviews.painting_order() {
+            self.send_window_size_message_for_top_level_browser_context(webview);
+        }
+
+        // The window may have become too small for the visual viewport to stay where it is.
+        if self.set_visual_viewport_offset(self.visual_viewport_offset) {
+            self.send_visual_viewport_to_script();
         }
 
         // Update the root transform in WebRender to reflect the new zoom.
@@ -1427,8 +1806,15 @@ impl IOCompositor {
     }
 
     /// Render the WebRender scene to the active `RenderingContext`. If successful, trigger
-    /// the next round of animations.
+    /// the next round of animations. Returns false if nothing was rendered, in which case
+    /// there is nothing to present.
     pub fn render(&mut self) -> bool {
+        // The scene of a page screenshot replaces the one of the `WebView`s until it has been
+        // rendered, and it must not be presented. The scene is painted once it is back.
+        if self.page_screenshot.is_some() {
+            return false;
+        }
+
         if let Err(error) = self.render_inner() {
             warn!("Unable to render: {error:?}");
             return false;
@@ -1438,10 +1824,16 @@ impl IOCompositor {
         // the scene no longer needs to be repainted.
         self.needs_repaint.set(RepaintReason::empty());
 
-        // Queue up any subsequent paints for animations.
-        self.process_animations(true);
+        self.enforce_texture_memory_limit();
 
-        true
+        // Queue up any subsequent paints for animations, unless they are ticked on vsync.
+        if !self.vsync_driven {
+            self.process_animations(true);
+        }
+
+        // With partial present, WebRender reports the parts of the screen that it drew. If
+        // none changed, the buffer still holds the previous frame and should not be presented.
+        !pref!(gfx_partial_present_enabled) || !self.damage.is_empty()
     }
 
     /// Render the WebRender scene to the shared memory, without updating other state of this
@@ -1454,7 +1846,13 @@ impl IOCompositor {
 
         let size = self.rendering_context.size2d().to_i32();
         let rect = if let Some(rect) = page_rect {
-            let rect = self.device_pixels_per_page_pixel().transform_rect(&rect);
+            // The page rectangle is in the coordinates of the topmost `WebView`.
+            let page_zoom = self
+                .webviews
+                .painting_order()
+                .last()
+                .map_or(Scale::new(1.0), |(_, webview)| webview.page_zoom);
+            let rect = (page_zoom * self.device_pixels_per_root_pixel()).transform_rect(&rect);
 
             let x = rect.origin.x as i32;
             // We need to convert to the bottom-left origin coordinate
@@ -1486,6 +1884,218 @@ impl IOCompositor {
             }))
     }
 
+    /// Render the WebRender scene and read back the pixels of the [`WebView`] with
+    /// `webview_id`, without presenting them. If `rect` is given, only that part of the
+    /// WebView, relative to its origin, is read back. The image is scaled by `scale`, for
+    /// instance to produce a thumbnail. Returns `None` if there is no such [`WebView`], if
+    /// `rect` is outside of it, or if the scene could not be rendered.
+    pub fn capture_pixels(
+        &mut self,
+        webview_id: WebViewId,
+        rect: Option<DeviceRect>,
+        scale: f32,
+    ) -> Option<RgbaImage> {
+        let webview_rect = self.webviews.get(webview_id)?.rect;
+        let capture_rect = match rect {
+            Some(rect) => rect
+                .translate(webview_rect.min.to_vector())
+                .intersection(&webview_rect)?,
+            None => webview_rect,
+        }
+        .round_out()
+        .to_i32();
+        if capture_rect.is_empty() {
+            return None;
+        }
+
+        // Render to a separate target, so that the buffers of the rendering context, and
+        // what is known of them for partial present, are left untouched.
+        let target = self.render_offscreen(self.rendering_context.size())?;
+
+        // We need to convert to the bottom-left origin coordinate system used by OpenGL.
+        let size = self.rendering_context.size2d().to_i32();
+        let source_rectangle = DeviceIntRect::from_origin_and_size(
+            Point2D::new(capture_rect.min.x, size.height - capture_rect.max.y),
+            capture_rect.size(),
+        );
+        let image = target.read_to_image(source_rectangle)?;
+        if scale == 1.0 {
+            return Some(image);
+        }
+        let width = ((image.width() as f32 * scale).round() as u32).max(1);
+        let height = ((image.height() as f32 * scale).round() as u32).max(1);
+        Some(imageops::resize(
+            &image,
+            width,
+            height,
+            imageops::FilterType::Triangle,
+        ))
+    }
+
+    /// Start taking a screenshot of `rect` of the page of the [`WebView`] with `webview_id`,
+    /// relative to its viewport, or of the whole page, by rendering it off-screen in a scene
+    /// of its own.
+    fn start_page_screenshot(
+        &mut self,
+        webview_id: WebViewId,
+        rect: Option<Rect<f32, CSSPixel>>,
+        reply: ipc::IpcSender<Option<Image>>,
+    ) {
+        if self.page_screenshot.is_some() {
+            warn!("Page screenshot requested while another one is being taken");
+            let _ = reply.send(None);
+            return;
+        }
+
+        let Some(webview) = self.webviews.get(webview_id) else {
+            let _ = reply.send(None);
+            return;
+        };
+        let root_scroll_info = webview.root_pipeline_id.and_then(|pipeline_id| {
+            let external_id = ExternalScrollId(0, pipeline_id.into());
+            let scroll_info = webview
+                .pipelines
+                .get(&pipeline_id)?
+                .scroll_tree
+                .nodes
+                .iter()
+                .filter_map(|node| node.scroll_info.as_ref())
+                .find(|scroll_info| scroll_info.external_id == external_id)?;
+            Some((pipeline_id, scroll_info))
+        });
+        let Some((pipeline_id, scroll_info)) = root_scroll_info else {
+            let _ = reply.send(None);
+            return;
+        };
+
+        // The scroll offsets of the scroll tree are negated.
+        let scroll_offset = -scroll_info.offset;
+        let scale = (webview.page_zoom * self.hidpi_factor()).get();
+        let viewport_size = webview.rect.size() / scale;
+        let rect = rect.map(|rect| rect.translate(Vector2D::new(scroll_offset.x, scroll_offset.y)));
+        match PageScreenshot::new(
+            webview_id,
+            pipeline_id,
+            rect,
+            LayoutSize::new(viewport_size.width, viewport_size.height),
+            scroll_info.scrollable_size,
+            scale,
+            reply,
+        ) {
+            Ok(screenshot) => {
+                self.page_screenshot = Some(screenshot);
+                self.send_page_screenshot_scene();
+            },
+            Err(reply) => {
+                let _ = reply.send(None);
+            },
+        }
+    }
+
+    /// Replace the WebRender scene with one that holds only the page of the screenshot that
+    /// is being taken. Its viewport covers the whole part of the page that the screenshot is
+    /// of, and the root scroll node of the page is scrolled to the start of that part.
+    fn send_page_screenshot_scene(&mut self) {
+        let Some(screenshot) = self.page_screenshot.as_ref() else {
+            return;
+        };
+        let device_size = screenshot.device_size();
+        let page_rect = LayoutRect::from_size(screenshot.page_size());
+
+        let mut transaction = Transaction::new();
+        transaction.set_document_view(DeviceIntRect::from_size(DeviceIntSize::new(
+            device_size.width as i32,
+            device_size.height as i32,
+        )));
+        transaction.set_root_pipeline(ROOT_PIPELINE_ID);
+
+        let mut builder = webrender_api::DisplayListBuilder::new(ROOT_PIPELINE_ID);
+        builder.begin();
+        let scale_reference_frame = builder.push_reference_frame(
+            LayoutPoint::zero(),
+            SpatialId::root_reference_frame(ROOT_PIPELINE_ID),
+            TransformStyle::Flat,
+            PropertyBinding::Value(Transform3D::scale(screenshot.scale, screenshot.scale, 1.)),
+            ReferenceFrameKind::Transform {
+                is_2d_scale_translation: true,
+                should_snap: true,
+                paired_with_perspective: false,
+            },
+            SpatialTreeItemKey::new(0, 0),
+        );
+        let clip_id = builder.define_clip_rect(scale_reference_frame, page_rect);
+        let clip_chain_id = builder.define_clip_chain(None, [clip_id]);
+        builder.push_iframe(
+            page_rect,
+            page_rect,
+            &SpaceAndClipInfo {
+                spatial_id: scale_reference_frame,
+                clip_chain_id,
+            },
+            screenshot.pipeline_id.into(),
+            true,
+        );
+        builder.pop_reference_frame();
+        transaction.set_display_list(WebRenderEpoch(0), builder.end());
+
+        // The other scroll nodes keep the offsets that the pages know of.
+        self.update_transaction_with_all_scroll_offsets(&mut transaction);
+        transaction.set_scroll_offsets(
+            ExternalScrollId(0, screenshot.pipeline_id.into()),
+            vec![SampledScrollOffset {
+                offset: screenshot.scroll_offset(),
+                generation: 0,
+            }],
+        );
+        self.generate_frame(&mut transaction, RenderReasons::SCENE);
+        self.global.borrow_mut().send_transaction(transaction);
+
+        if let Some(screenshot) = self.page_screenshot.as_mut() {
+            screenshot.set_waiting_for_frame();
+        }
+    }
+
+    /// Render the scene of the screenshot that is being taken into an off-screen target of
+    /// the size of the screenshot, and send the screenshot.
+    fn take_page_screenshot(&mut self) {
+        let Some(device_size) = self
+            .page_screenshot
+            .as_ref()
+            .map(PageScreenshot::device_size)
+        else {
+            return;
+        };
+        let rendering = self.render_offscreen(device_size).and_then(|target| {
+            target.read_to_image(DeviceIntRect::from_size(DeviceIntSize::new(
+                device_size.width as i32,
+                device_size.height as i32,
+            )))
+        });
+        if rendering.is_none() {
+            warn!("Unable to render page screenshot");
+        }
+        self.finish_page_screenshot(rendering);
+    }
+
+    /// Put the scene of the `WebView`s back in place, and send the screenshot.
+    fn finish_page_screenshot(&mut self, rendering: Option<RgbaImage>) {
+        let Some(screenshot) = self.page_screenshot.take() else {
+            return;
+        };
+
+        let mut transaction = Transaction::new();
+        transaction.set_document_view(DeviceIntRect::from_size(
+            self.rendering_context.size2d().to_i32(),
+        ));
+        self.send_root_pipeline_display_list_in_transaction(&mut transaction);
+        // Scroll the root scroll node of the page back to where the page knows it is.
+        self.update_transaction_with_all_scroll_offsets(&mut transaction);
+        self.generate_frame(&mut transaction, RenderReasons::SCENE);
+        self.global.borrow_mut().send_transaction(transaction);
+
+        screenshot.finish(rendering);
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
@@ -1525,12 +2135,26 @@ impl IOCompositor {
             || {
                 trace!("Compositing");
 
-                // Paint the scene.
+                // Paint the scene. With partial present, WebRender only draws the parts of the
+                // scene that changed since the frame that the buffer holds, so the rest of the
+                // buffer must not be cleared.
                 // TODO(gw): Take notice of any errors the renderer returns!
-                self.clear_background();
+                let buffer_age = if pref!(gfx_partial_present_enabled) &&
+                    !std::mem::take(&mut self.rendered_offscreen)
+                {
+                    self.rendering_context.buffer_age()
+                } else {
+                    0
+                };
+                if buffer_age == 0 {
+                    self.clear_background();
+                }
+                self.damage.clear();
                 if let Some(webrender) = self.webrender.as_mut() {
                     let size = self.rendering_context.size2d().to_i32();
-                    webrender.render(size, 0 /* buffer_age */).ok();
+                    if let Ok(results) = webrender.render(size, buffer_age) {
+                        self.damage = results.dirty_rects;
+                    }
                 }
             },
         );
@@ -1539,6 +2163,29 @@ impl IOCompositor {
         Ok(())
     }
 
+    /// Render the WebRender scene to a new [`OffscreenRenderTarget`] of `size`, without
+    /// presenting it or updating other state of this [`IOCompositor`].
+    fn render_offscreen(&mut self, size: PhysicalSize<u32>) -> Option<OffscreenRenderTarget> {
+        if let Err(error) = self.rendering_context.make_current() {
+            warn!("Failed to make the rendering context current: {error:?}");
+            return None;
+        }
+        self.webrender.as_mut()?.update();
+
+        let target = OffscreenRenderTarget::new(&*self.rendering_context, size);
+        target.bind();
+        self.clear_background();
+        let rendered = self
+            .webrender
+            .as_mut()?
+            .render(DeviceIntSize::new(size.width as i32, size.height as i32), 0)
+            .is_ok();
+        self.rendered_offscreen = true;
+
+        self.rendering_context.prepare_for_rendering();
+        rendered.then_some(target)
+    }
+
     /// Send all pending paint metrics messages after a composite operation, which may advance
     /// the epoch for pipelines in the WebRender scene.
     ///
@@ -1608,7 +2255,7 @@ impl IOCompositor {
         // Always clear the entire RenderingContext, regardless of how many WebViews there are
         // or where they are positioned. This is so WebView actually clears even before the
         // first WebView is ready.
-        let color = servo_config::pref!(shell_background_color_rgba);
+        let color = pref!(shell_background_color_rgba);
         gl.clear_color(
             color[0] as f32,
             color[1] as f32,
@@ -1722,10 +2369,87 @@ impl IOCompositor {
             zoom = f32::min(max.get(), zoom);
         }
 
+        // Zoom around the center of the visual viewport.
+        let center = self.visual_viewport_offset + self.visual_viewport_size().to_vector() / 2.;
         let old_zoom = std::mem::replace(&mut self.viewport_zoom, PinchZoomFactor::new(zoom));
+        self.set_visual_viewport_offset(center - self.visual_viewport_size().to_vector() / 2.);
         old_zoom != self.viewport_zoom
     }
 
+    /// Zoom in on `point` for a double tap, centering it in the visual viewport, or zoom back
+    /// out if the view is already zoomed in. Returns whether the view changed.
+    pub(crate) fn double_tap_zoom(&mut self, point: DevicePoint) -> bool {
+        if self.pinch_zoom_level().get() > 1. {
+            return self.set_pinch_zoom_level(1.);
+        }
+
+        let tapped_offset = self.visual_viewport_offset +
+            LayoutVector2D::from_untyped(
+                (point.to_vector() / self.device_pixels_per_root_pixel()).to_untyped(),
+            );
+        let zoom_changed = self.set_pinch_zoom_level(DOUBLE_TAP_ZOOM_FACTOR);
+        let panned = self.set_visual_viewport_offset(
+            tapped_offset - self.visual_viewport_size().to_vector() / 2.,
+        );
+        zoom_changed || panned
+    }
+
+    /// The size of the window, in the coordinates of the root display list.
+    fn layout_viewport_size(&self) -> LayoutSize {
+        LayoutSize::from_untyped(
+            self.rendering_context.size2d().to_f32().to_untyped() / self.hidpi_factor().get(),
+        )
+    }
+
+    /// The size of the visual viewport, in the coordinates of the root display list.
+    fn visual_viewport_size(&self) -> LayoutSize {
+        self.layout_viewport_size() / self.pinch_zoom_level().get()
+    }
+
+    /// Move the visual viewport to `offset`, keeping it inside of the window. Returns whether
+    /// it moved.
+    pub(crate) fn set_visual_viewport_offset(&mut self, offset: LayoutVector2D) -> bool {
+        let max_offset = (self.layout_viewport_size() - self.visual_viewport_size()).to_vector();
+        let offset = offset.max(LayoutVector2D::zero()).min(max_offset);
+        let old_offset = std::mem::replace(&mut self.visual_viewport_offset, offset);
+        old_offset != self.visual_viewport_offset
+    }
+
+    /// Pan the visual viewport by a scroll delta in the coordinates of the root display list,
+    /// which moves towards the start of the window when positive like that of scroll nodes.
+    /// Returns whether the visual viewport moved.
+    pub(crate) fn pan_visual_viewport(&mut self, delta: LayoutVector2D) -> bool {
+        self.set_visual_viewport_offset(self.visual_viewport_offset - delta)
+    }
+
+    /// Let the top-level document of every `WebView` know where its visual viewport is.
+    pub(crate) fn send_visual_viewport_to_script(&self) {
+        for webview in self.webviews.iter() {
+            let Some(pipeline_id) = webview.root_pipeline_id else {
+                continue;
+            };
+            let webview_origin = webview.rect.min.to_vector() / self.hidpi_factor();
+            let offset = (Vector2D::from_untyped(self.visual_viewport_offset.to_untyped()) -
+                webview_origin) /
+                webview.page_zoom;
+            let visual_viewport = VisualViewportState {
+                offset: offset.max(Vector2D::zero()),
+                scale: self.pinch_zoom_level().get(),
+            };
+            if let Err(error) =
+                self.global
+                    .borrow()
+                    .constellation_sender
+                    .send(ConstellationMsg::SetVisualViewport(
+                        pipeline_id,
+                        visual_viewport,
+                    ))
+            {
+                warn!("Sending visual viewport to constellation failed ({error:?}).");
+            }
+        }
+    }
+
     pub fn toggle_webrender_debug(&mut self, option: WebRenderDebugOption) {
         let Some(webrender) = self.webrender.as_mut() else {
             return;
@@ -1795,12 +2519,25 @@ impl IOCompositor {
             flags,
             ..Default::default()
         };
+
+        // Hinting depends on the configuration of the system, so glyphs are not hinted when
+        // rendering deterministically.
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let platform_options = opts::get()
+            .deterministic
+            .then(|| FontInstancePlatformOptions {
+                hinting: FontHinting::None,
+                ..Default::default()
+            });
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        let platform_options = None;
+
         transaction.add_font_instance(
             instance_key,
             font_key,
             size,
             Some(font_instance_options),
-            None,
+            platform_options,
             Vec::new(),
         );
 
@@ -1826,15 +2563,105 @@ impl IOCompositor {
         cursor: DeviceIntPoint,
         event_type: TouchEventType,
     ) {
-        if let Some(webview) = self.webviews.get_mut(webview_id) {
-            webview.notify_scroll_event(scroll_location, cursor, event_type);
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return;
+        };
+        let was_scrolling_with_momentum = webview.scrolling_with_momentum();
+        webview.notify_scroll_event(scroll_location, cursor, event_type);
+        if !was_scrolling_with_momentum && webview.scrolling_with_momentum() {
+            self.process_animations(true);
         }
     }
 
+    /// The memory report of WebRender. Its heap sizes are measured with the `size_of_op`
+    /// that the renderer was created with, and its texture sizes are tracked by the renderer
+    /// itself. The argument of `report_memory` is a SWGL context, which Servo does not use.
+    fn webrender_memory_report(&self) -> Option<MemoryReport> {
+        Some(self.webrender.as_ref()?.report_memory(std::ptr::null_mut()))
+    }
+
+    fn collect_memory_report(&self, reports_chan: ReportsChan) {
+        let Some(report) = self.webrender_memory_report() else {
+            return reports_chan.send(Vec::new());
+        };
+        let heap = [
+            ("clip-stores", report.clip_stores),
+            ("gpu-cache-metadata", report.gpu_cache_metadata),
+            ("gpu-cache-cpu-mirror", report.gpu_cache_cpu_mirror),
+            ("render-tasks", report.render_tasks),
+            ("hit-testers", report.hit_testers),
+            ("fonts", report.fonts),
+            ("images", report.images),
+            ("interning", report.interning),
+            ("display-lists", report.display_list),
+        ]
+        .into_iter()
+        .map(|(kind, size)| Report {
+            path: path!["webrender", "heap", kind],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size,
+            attribution: None,
+        });
+        let textures = [
+            ("atlas", report.atlas_textures),
+            ("standalone", report.standalone_textures),
+            ("picture-tiles", report.picture_tile_textures),
+            ("render-targets", report.render_target_textures),
+            ("depth-targets", report.depth_target_textures),
+            ("gpu-cache", report.gpu_cache_textures),
+            ("vertex-data", report.vertex_data_textures),
+            ("texture-cache-structures", report.texture_cache_structures),
+        ]
+        .into_iter()
+        .map(|(kind, size)| Report {
+            path: path!["webrender", "textures", kind],
+            kind: ReportKind::ExplicitUnknownLocationSize,
+            size,
+            attribution: None,
+        });
+        reports_chan.send(heap.chain(textures).collect());
+    }
+
+    /// The embedder has informed us that the system is low on memory. Ask WebRender to free
+    /// the memory of its caches, such as the texture cache and the glyph cache, which are
+    /// filled again as content is rendered.
+    pub fn notify_memory_pressure(&mut self) {
+        self.global.borrow().webrender_api.notify_memory_pressure();
+        self.last_memory_pressure = Some(Instant::now());
+    }
+
+    /// Trim the caches of WebRender if its textures use more memory than the limit that the
+    /// `gfx_texture_memory_limit_mb` preference sets. This is not done more than once per
+    /// second, so that content that needs more texture memory than that can still render.
+    fn enforce_texture_memory_limit(&mut self) {
+        let limit_mb = pref!(gfx_texture_memory_limit_mb);
+        if limit_mb <= 0 ||
+            self.last_memory_pressure
+                .is_some_and(|time| time.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+        let Some(report) = self.webrender_memory_report() else {
+            return;
+        };
+        let usage = report.total_gpu_bytes_allocated();
+        if usage > limit_mb as usize * 1024 * 1024 {
+            debug!("Texture memory usage of {usage} bytes exceeds limit, trimming caches");
+            self.notify_memory_pressure();
+        }
+    }
+
+    /// The embedder has informed us of a vsync of the display that `webview_id` is shown on.
+    /// From then on, animations are ticked on vsync, aligning the frames that they produce with
+    /// the refresh of the display.
     pub fn on_vsync(&mut self, webview_id: WebViewId) {
         if let Some(webview) = self.webviews.get_mut(webview_id) {
             webview.on_vsync();
         }
+        self.vsync_driven = true;
+        if self.global.borrow().shutdown_state() == ShutdownState::NotShuttingDown {
+            self.process_animations(true);
+        }
     }
 
     pub fn set_pinch_zoom(&mut self, webview_id: WebViewId, magnification: f32) {
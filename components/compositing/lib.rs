@@ -22,6 +22,7 @@ pub use crate::compositor::IOCompositor;
 mod tracing;
 
 mod compositor;
+mod screenshot;
 mod touch;
 pub mod webview;
 pub mod windowing;
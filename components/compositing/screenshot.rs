@@ -0,0 +1,132 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Screenshots of the parts of a page that do not fit in its viewport.
+
+use base::id::{PipelineId, WebViewId};
+use dpi::PhysicalSize;
+use euclid::{Point2D, Rect, Size2D};
+use image::RgbaImage;
+use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
+use log::warn;
+use pixels::{CorsStatus, Image, ImageFrame, PixelFormat};
+use style_traits::CSSPixel;
+use webrender_api::units::{LayoutSize, LayoutVector2D};
+
+/// The largest width or height of a page screenshot, in device pixels. Larger parts of a page
+/// are cut off.
+const MAX_SCREENSHOT_SIZE: f32 = 16384.;
+
+/// A screenshot of a part of a page, which may be larger than its viewport. It is taken in a
+/// single off-screen rendering of a scene of its own, whose viewport covers all of that part
+/// and in which the root scroll node of the page is scrolled to its start. Content that is
+/// fixed to the viewport is rendered once, relative to the start of the part. The page is not
+/// told about this scene, so neither its scroll position nor its layout change, and the scene
+/// is never presented.
+pub(crate) struct PageScreenshot {
+    pub webview_id: WebViewId,
+    pub pipeline_id: PipelineId,
+    /// The part of the page to take a screenshot of, in page pixels from its origin.
+    rect: Rect<f32, CSSPixel>,
+    /// The number of device pixels per page pixel.
+    pub scale: f32,
+    /// Whether the scene of the screenshot was sent to WebRender, and has yet to be rendered.
+    waiting_for_frame: bool,
+    reply: IpcSender<Option<Image>>,
+}
+
+impl PageScreenshot {
+    /// Prepare a screenshot of `rect` of a page, or of all of it, given the size of its
+    /// viewport and how far it can scroll. Gives `reply` back if there is nothing to take a
+    /// screenshot of.
+    pub(crate) fn new(
+        webview_id: WebViewId,
+        pipeline_id: PipelineId,
+        rect: Option<Rect<f32, CSSPixel>>,
+        viewport_size: LayoutSize,
+        scrollable_size: LayoutSize,
+        scale: f32,
+        reply: IpcSender<Option<Image>>,
+    ) -> Result<Self, IpcSender<Option<Image>>> {
+        let scrollable_size = scrollable_size.max(LayoutSize::zero());
+        let content_rect = Rect::new(
+            Point2D::origin(),
+            Size2D::new(
+                viewport_size.width + scrollable_size.width,
+                viewport_size.height + scrollable_size.height,
+            ),
+        );
+        let Some(rect) = rect
+            .unwrap_or(content_rect)
+            .intersection(&content_rect)
+            .filter(|rect| !rect.is_empty() && !viewport_size.is_empty())
+        else {
+            return Err(reply);
+        };
+        let rect = Rect::new(
+            rect.origin,
+            rect.size.min(Size2D::splat(MAX_SCREENSHOT_SIZE / scale)),
+        );
+
+        Ok(Self {
+            webview_id,
+            pipeline_id,
+            rect,
+            scale,
+            waiting_for_frame: false,
+            reply,
+        })
+    }
+
+    /// The size of the part of the page to take a screenshot of, in page pixels.
+    pub(crate) fn page_size(&self) -> LayoutSize {
+        LayoutSize::new(self.rect.size.width, self.rect.size.height)
+    }
+
+    /// The size of the screenshot, in device pixels.
+    pub(crate) fn device_size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(
+            ((self.rect.size.width * self.scale).round() as u32).max(1),
+            ((self.rect.size.height * self.scale).round() as u32).max(1),
+        )
+    }
+
+    /// The offset that the root scroll node of the page is scrolled to in the scene of the
+    /// screenshot.
+    pub(crate) fn scroll_offset(&self) -> LayoutVector2D {
+        LayoutVector2D::new(self.rect.min_x(), self.rect.min_y())
+    }
+
+    /// Whether the scene of the screenshot was sent to WebRender, and has yet to be rendered.
+    pub(crate) fn is_waiting_for_frame(&self) -> bool {
+        self.waiting_for_frame
+    }
+
+    pub(crate) fn set_waiting_for_frame(&mut self) {
+        self.waiting_for_frame = true;
+    }
+
+    /// Send the screenshot, or `None` if it could not be taken.
+    pub(crate) fn finish(self, rendering: Option<RgbaImage>) {
+        let image = rendering.map(|rendering| {
+            let (width, height) = rendering.dimensions();
+            Image {
+                width,
+                height,
+                format: PixelFormat::RGBA8,
+                frames: vec![ImageFrame {
+                    delay: None,
+                    bytes: IpcSharedMemory::from_bytes(&rendering),
+                    width,
+                    height,
+                }],
+                id: None,
+                cors_status: CorsStatus::Safe,
+            }
+        });
+        if let Err(error) = self.reply.send(image) {
+            warn!("Sending page screenshot failed ({error:?}).");
+        }
+    }
+}
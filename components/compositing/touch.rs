@@ -3,10 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use embedder_traits::{TouchId, TouchSequenceId};
 use euclid::{Point2D, Scale, Vector2D};
 use log::{debug, error, warn};
+use servo_config::pref;
 use webrender_api::units::{DeviceIntPoint, DevicePixel, DevicePoint, LayoutVector2D};
 
 use self::TouchSequenceState::*;
@@ -14,19 +16,38 @@ use self::TouchSequenceState::*;
 // TODO: All `_SCREEN_PX` units below are currently actually used as `DevicePixel`
 // without multiplying with the `hidpi_factor`. This should be fixed and the
 // constants adjusted accordingly.
-/// Minimum number of `DeviceIndependentPixel` to begin touch scrolling.
+/// Minimum number of `DeviceIndependentPixel` to begin touch scrolling. This is the touch slop,
+/// the distance that a single touch point can move from where it went down and still be a tap.
 const TOUCH_PAN_MIN_SCREEN_PX: f32 = 20.0;
-/// Factor by which the flinging velocity changes on each tick.
-const FLING_SCALING_FACTOR: f32 = 0.95;
-/// Minimum velocity required for transitioning to fling when panning ends.
-const FLING_MIN_SCREEN_PX: f32 = 3.0;
+/// Maximum distance between two taps that make up a double tap.
+const DOUBLE_TAP_MAX_SCREEN_PX: f32 = 40.0;
+/// Maximum time between two taps that make up a double tap.
+const DOUBLE_TAP_TIMEOUT: Duration = Duration::from_millis(300);
 /// Maximum velocity when flinging.
 const FLING_MAX_SCREEN_PX: f32 = 4000.0;
 
+/// Factor by which the flinging velocity changes on each tick.
+fn fling_scaling_factor() -> f32 {
+    pref!(layout_momentum_scrolling_friction) as f32
+}
+
+/// Minimum velocity required for transitioning to fling when panning ends, and below which
+/// flinging stops.
+fn fling_min_screen_px() -> f32 {
+    pref!(layout_momentum_scrolling_minimum_velocity) as f32
+}
+
 pub struct TouchHandler {
     pub current_sequence_id: TouchSequenceId,
     // todo: VecDeque + modulo arithmetic would be more efficient.
     touch_sequence_map: HashMap<TouchSequenceId, TouchSequenceInfo>,
+    /// When and where the last tap was, which a second tap turns into a double tap.
+    last_tap: Option<(Instant, DevicePoint)>,
+}
+
+/// Whether a touch point that moved by `delta` since it went down has left its touch slop.
+fn exceeds_touch_slop(delta: Vector2D<f32, DevicePixel>) -> bool {
+    delta.length() > TOUCH_PAN_MIN_SCREEN_PX
 }
 
 /// Whether the default move action is allowed or not.
@@ -127,7 +148,11 @@ impl TouchSequenceInfo {
     fn is_finished(&self) -> bool {
         matches!(
             self.state,
-            Finished | Flinging { .. } | PendingFling { .. } | PendingClick(_)
+            Finished |
+                Flinging { .. } |
+                PendingFling { .. } |
+                PendingClick(_) |
+                PendingDoubleTap(_)
         )
     }
 }
@@ -184,6 +209,9 @@ pub(crate) enum TouchSequenceState {
     },
     /// The touch sequence is finished, but a click is still pending, waiting on script.
     PendingClick(DevicePoint),
+    /// The touch sequence is finished as the second tap of a double tap, which zooms in on
+    /// the given point instead of clicking, unless script prevents it.
+    PendingDoubleTap(DevicePoint),
     /// touch sequence finished.
     Finished,
 }
@@ -209,9 +237,18 @@ impl TouchHandler {
             // so that we always have one element in the map, which simplifies creating
             // a new touch sequence on touch_down.
             touch_sequence_map: HashMap::from([(TouchSequenceId::new(), finished_info)]),
+            last_tap: None,
         }
     }
 
+    /// Whether the current touch sequence is a single touch point that has not left its touch
+    /// slop, so that it may still turn out to be a tap.
+    pub(crate) fn is_within_touch_slop(&self) -> bool {
+        self.touch_sequence_map
+            .get(&self.current_sequence_id)
+            .is_some_and(|sequence| sequence.state == Touching)
+    }
+
     pub(crate) fn set_handling_touch_move(&mut self, sequence_id: TouchSequenceId, flag: bool) {
         if let Some(sequence) = self.touch_sequence_map.get_mut(&sequence_id) {
             sequence.handling_touch_move = flag;
@@ -347,7 +384,7 @@ impl TouchHandler {
         let Flinging { velocity, cursor } = &mut touch_sequence.state else {
             return None;
         };
-        if velocity.length().abs() < FLING_MIN_SCREEN_PX {
+        if velocity.length().abs() < fling_min_screen_px() {
             touch_sequence.state = Finished;
             // If we were flinging previously, there could still be a touch_up event result
             // coming in after we stopped flinging
@@ -356,7 +393,7 @@ impl TouchHandler {
         } else {
             // TODO: Probably we should multiply with the current refresh rate (and divide on each frame)
             // or save a timestamp to account for a potentially changing display refresh rate.
-            *velocity *= FLING_SCALING_FACTOR;
+            *velocity *= fling_scaling_factor();
             debug_assert!(velocity.length() <= FLING_MAX_SCREEN_PX);
             Some(FlingAction {
                 delta: LayoutVector2D::new(velocity.x, velocity.y),
@@ -394,9 +431,7 @@ impl TouchHandler {
                     // update the touch point every time when panning.
                     touch_sequence.active_touch_points[idx].point = point;
                     TouchMoveAction::Scroll(delta, point)
-                } else if delta.x.abs() > TOUCH_PAN_MIN_SCREEN_PX ||
-                    delta.y.abs() > TOUCH_PAN_MIN_SCREEN_PX
-                {
+                } else if exceeds_touch_slop(delta) {
                     touch_sequence.state = Panning {
                         velocity: Vector2D::new(delta.x, delta.y),
                     };
@@ -412,10 +447,7 @@ impl TouchHandler {
                 }
             },
             2 => {
-                if touch_sequence.state == Pinching ||
-                    delta.x.abs() > TOUCH_PAN_MIN_SCREEN_PX ||
-                    delta.y.abs() > TOUCH_PAN_MIN_SCREEN_PX
-                {
+                if touch_sequence.state == Pinching || exceeds_touch_slop(delta) {
                     touch_sequence.state = Pinching;
                     let (d0, c0) = touch_sequence.pinch_distance_and_center();
                     // update the touch point with the enough distance or pinching.
@@ -448,6 +480,8 @@ impl TouchHandler {
     }
 
     pub fn on_touch_up(&mut self, id: TouchId, point: Point2D<f32, DevicePixel>) {
+        let last_tap = self.last_tap.take();
+        let mut tap = None;
         let touch_sequence = self.get_current_touch_sequence_mut();
         let old = match touch_sequence
             .active_touch_points
@@ -462,14 +496,21 @@ impl TouchHandler {
         };
         match touch_sequence.state {
             Touching => {
+                let is_double_tap = last_tap.is_some_and(|(time, last_point)| {
+                    time.elapsed() <= DOUBLE_TAP_TIMEOUT &&
+                        (point - last_point).length() <= DOUBLE_TAP_MAX_SCREEN_PX
+                });
                 if touch_sequence.prevent_click {
                     touch_sequence.state = Finished;
+                } else if is_double_tap {
+                    touch_sequence.state = PendingDoubleTap(point);
                 } else {
                     touch_sequence.state = PendingClick(point);
+                    tap = Some(point);
                 }
             },
             Panning { velocity } => {
-                if velocity.length().abs() >= FLING_MIN_SCREEN_PX {
+                if velocity.length().abs() >= fling_min_screen_px() {
                     // TODO: point != old. Not sure which one is better to take as cursor for flinging.
                     debug!(
                         "Transitioning to Fling. Cursor is {point:?}. Old cursor was {old:?}. \
@@ -505,7 +546,11 @@ impl TouchHandler {
                     touch_sequence.state = Finished;
                 }
             },
-            PendingFling { .. } | Flinging { .. } | PendingClick(_) | Finished => {
+            PendingFling { .. } |
+            Flinging { .. } |
+            PendingClick(_) |
+            PendingDoubleTap(_) |
+            Finished => {
                 error!("Touch-up received, but touch handler already in post-touchup state.")
             },
         }
@@ -522,6 +567,7 @@ impl TouchHandler {
             touch_sequence.active_touch_points.len(),
             self.current_sequence_id
         );
+        self.last_tap = tap.map(|point| (Instant::now(), point));
     }
 
     pub fn on_touch_cancel(&mut self, id: TouchId, _point: Point2D<f32, DevicePixel>) {
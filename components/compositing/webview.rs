@@ -6,6 +6,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::hash_map::{Entry, Keys, Values, ValuesMut};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use base::id::{PipelineId, WebViewId};
 use compositing_traits::SendableFrameTree;
@@ -18,6 +19,9 @@ use euclid::{Point2D, Scale, Vector2D};
 use fnv::FnvHashSet;
 use log::{debug, warn};
 use script_traits::{AnimationState, TouchEventResult};
+use servo_config::pref;
+use servo_geometry::DeviceIndependentPixel;
+use style_traits::CSSPixel;
 use webrender::Transaction;
 use webrender_api::units::{DeviceIntPoint, DevicePoint, DeviceRect, LayoutVector2D};
 use webrender_api::{
@@ -38,10 +42,52 @@ struct ScrollEvent {
     event_count: u32,
 }
 
+/// How long it takes to scroll smoothly to a new scroll position.
+const SMOOTH_SCROLL_DURATION: Duration = Duration::from_millis(300);
+
+/// A scroll node that is being scrolled smoothly to a new scroll position.
+struct SmoothScroll {
+    pipeline_id: PipelineId,
+    external_id: ExternalScrollId,
+    start_position: LayoutVector2D,
+    end_position: LayoutVector2D,
+    start_time: Instant,
+}
+
+impl SmoothScroll {
+    /// The progress of the scroll at `time`, from 0 to 1.
+    fn progress_at(&self, time: Instant) -> f32 {
+        (time
+            .saturating_duration_since(self.start_time)
+            .as_secs_f32() /
+            SMOOTH_SCROLL_DURATION.as_secs_f32())
+        .min(1.)
+    }
+
+    /// The scroll position at `time`, which eases out towards the end position.
+    fn position_at(&self, time: Instant) -> LayoutVector2D {
+        let eased_progress = 1. - (1. - self.progress_at(time)).powi(3);
+        self.start_position.lerp(self.end_position, eased_progress)
+    }
+}
+
+/// A scroll with the mouse wheel that is still moving with momentum. Every frame, it scrolls
+/// by part of the distance that is left, so that it slows down as it gets closer to its end.
+struct MomentumScroll {
+    /// Scroll the content at this location.
+    cursor: DeviceIntPoint,
+    /// The part of the scroll that has not been applied yet, in device pixels.
+    remaining_delta: LayoutVector2D,
+    /// When the scroll was last moved.
+    last_tick: Instant,
+}
+
 #[derive(Clone, Copy)]
 enum ScrollZoomEvent {
     /// An pinch zoom event that magnifies the view by the given factor.
     PinchZoom(f32),
+    /// A double tap at the given point, which zooms in on it or back out.
+    DoubleTapZoom(DevicePoint),
     /// A scroll event that scrolls the scroll node at the given location by the
     /// given amount.
     Scroll(ScrollEvent),
@@ -53,12 +99,21 @@ pub(crate) struct WebView {
     /// The root [`PipelineId`] of the currently displayed page in this WebView.
     pub root_pipeline_id: Option<PipelineId>,
     pub rect: DeviceRect,
+    /// "Desktop-style" zoom that resizes the viewport to fit the window.
+    pub page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
+    /// The factor by which the size of the text of the pages in this `WebView` is
+    /// multiplied.
+    pub text_zoom: f32,
     /// Tracks details about each active pipeline that the compositor knows about.
     pub pipelines: HashMap<PipelineId, PipelineDetails>,
     /// Data that is shared by all WebView renderers.
     pub(crate) global: Rc<RefCell<ServoRenderer>>,
     /// Pending scroll/zoom events.
     pending_scroll_zoom_events: Vec<ScrollZoomEvent>,
+    /// The scroll nodes that are being scrolled smoothly.
+    smooth_scrolls: Vec<SmoothScroll>,
+    /// The scroll with the mouse wheel that is still moving with momentum, if any.
+    momentum_scroll: Option<MomentumScroll>,
     /// Touch input state machine
     touch_handler: TouchHandler,
 }
@@ -78,17 +133,23 @@ impl WebView {
             id,
             root_pipeline_id: None,
             rect,
+            page_zoom: Scale::new(1.0),
+            text_zoom: 1.0,
             pipelines: Default::default(),
             touch_handler: TouchHandler::new(),
             global,
             pending_scroll_zoom_events: Default::default(),
+            smooth_scrolls: Default::default(),
+            momentum_scroll: None,
         }
     }
 
     pub(crate) fn animations_or_animation_callbacks_running(&self) -> bool {
-        self.pipelines
-            .values()
-            .any(PipelineDetails::animations_or_animation_callbacks_running)
+        !self.smooth_scrolls.is_empty() ||
+            self.momentum_scroll.is_some() ||
+            self.pipelines
+                .values()
+                .any(PipelineDetails::animations_or_animation_callbacks_running)
     }
 
     pub(crate) fn animation_callbacks_running(&self) -> bool {
@@ -256,6 +317,159 @@ impl WebView {
         }
     }
 
+    /// Start scrolling the scroll node with the given [`ExternalScrollId`] smoothly to
+    /// `position`, replacing any smooth scroll of that node that is in progress.
+    pub(crate) fn start_smooth_scroll(
+        &mut self,
+        pipeline_id: PipelineId,
+        external_id: ExternalScrollId,
+        position: LayoutVector2D,
+    ) {
+        let Some(start_position) = self.pipelines.get(&pipeline_id).and_then(|details| {
+            details
+                .scroll_tree
+                .nodes
+                .iter()
+                .find(|node| node.external_id() == Some(external_id))?
+                .offset()
+        }) else {
+            return;
+        };
+        self.stop_smooth_scroll(pipeline_id, external_id);
+        self.smooth_scrolls.push(SmoothScroll {
+            pipeline_id,
+            external_id,
+            // Scroll tree nodes store the negation of the scroll position.
+            start_position: -start_position,
+            end_position: position,
+            start_time: Instant::now(),
+        });
+    }
+
+    /// Stop scrolling the scroll node with the given [`ExternalScrollId`] smoothly, if it is.
+    pub(crate) fn stop_smooth_scroll(
+        &mut self,
+        pipeline_id: PipelineId,
+        external_id: ExternalScrollId,
+    ) {
+        self.smooth_scrolls.retain(|smooth_scroll| {
+            smooth_scroll.pipeline_id != pipeline_id || smooth_scroll.external_id != external_id
+        });
+    }
+
+    /// Move the scroll nodes that are being scrolled smoothly to their scroll position for
+    /// the next frame. Returns whether any smooth scroll is still in progress.
+    pub(crate) fn tick_smooth_scrolls(&mut self, compositor: &mut IOCompositor) -> bool {
+        if self.smooth_scrolls.is_empty() {
+            return false;
+        }
+
+        let now = Instant::now();
+        let mut transaction = Transaction::new();
+        let mut scrolled_pipelines = FnvHashSet::default();
+        let pipelines = &mut self.pipelines;
+        self.smooth_scrolls.retain(|smooth_scroll| {
+            let Some(details) = pipelines.get_mut(&smooth_scroll.pipeline_id) else {
+                return false;
+            };
+            let position = smooth_scroll.position_at(now);
+            if !details
+                .scroll_tree
+                .set_scroll_offsets_for_node_with_external_scroll_id(
+                    smooth_scroll.external_id,
+                    -position,
+                )
+            {
+                return false;
+            }
+            transaction.set_scroll_offsets(
+                smooth_scroll.external_id,
+                vec![SampledScrollOffset {
+                    offset: position,
+                    generation: 0,
+                }],
+            );
+            transaction.append_dynamic_transform_properties(
+                details
+                    .scroll_tree
+                    .scrollbar_thumb_transforms(smooth_scroll.external_id),
+            );
+            scrolled_pipelines.insert(smooth_scroll.pipeline_id);
+            smooth_scroll.progress_at(now) < 1.
+        });
+
+        for pipeline_id in scrolled_pipelines {
+            self.send_scroll_positions_to_layout_for_pipeline(pipeline_id);
+        }
+        compositor.generate_frame(&mut transaction, RenderReasons::APZ);
+        self.global.borrow_mut().send_transaction(transaction);
+        !self.smooth_scrolls.is_empty()
+    }
+
+    /// Whether a scroll with the mouse wheel is still moving with momentum.
+    pub(crate) fn scrolling_with_momentum(&self) -> bool {
+        self.momentum_scroll.is_some()
+    }
+
+    /// Add `delta` to the scroll with momentum of the content under `cursor`, starting one if
+    /// there is none. A scroll in the opposite direction replaces the one in progress.
+    fn add_scroll_momentum(&mut self, delta: LayoutVector2D, cursor: DeviceIntPoint) {
+        match self.momentum_scroll.as_mut() {
+            Some(momentum_scroll) if momentum_scroll.remaining_delta.dot(delta) >= 0. => {
+                momentum_scroll.cursor = cursor;
+                momentum_scroll.remaining_delta += delta;
+            },
+            _ => {
+                self.momentum_scroll = Some(MomentumScroll {
+                    cursor,
+                    remaining_delta: delta,
+                    last_tick: Instant::now(),
+                })
+            },
+        }
+    }
+
+    /// Move the scroll with momentum, if any, for the next frame. Returns whether it is still
+    /// in progress.
+    pub(crate) fn tick_momentum_scroll(&mut self, compositor: &mut IOCompositor) -> bool {
+        let Some(momentum_scroll) = self.momentum_scroll.as_mut() else {
+            return false;
+        };
+
+        // The distance that is left decays exponentially with the friction, so the velocity
+        // of the scroll is proportional to it.
+        let now = Instant::now();
+        let frames = now
+            .saturating_duration_since(momentum_scroll.last_tick)
+            .as_secs_f32() *
+            60.;
+        momentum_scroll.last_tick = now;
+        let friction = (pref!(layout_momentum_scrolling_friction) as f32).clamp(0., 0.999);
+        let mut delta = momentum_scroll.remaining_delta * (1. - friction.powf(frames));
+        if (momentum_scroll.remaining_delta - delta).length() < 1. {
+            delta = momentum_scroll.remaining_delta;
+        }
+        momentum_scroll.remaining_delta -= delta;
+        if delta == LayoutVector2D::zero() {
+            return true;
+        }
+
+        let finished = momentum_scroll.remaining_delta == LayoutVector2D::zero();
+        self.pending_scroll_zoom_events
+            .push(ScrollZoomEvent::Scroll(ScrollEvent {
+                scroll_location: ScrollLocation::Delta(delta),
+                cursor: momentum_scroll.cursor,
+                event_count: 1,
+            }));
+
+        // The scroll also stops when it reaches the end of the content that it scrolls.
+        let scrolled = self.process_pending_scroll_events(compositor);
+        if finished || !scrolled {
+            self.momentum_scroll = None;
+        }
+        self.momentum_scroll.is_some()
+    }
+
     /// On a Window refresh tick (e.g. vsync)
     pub fn on_vsync(&mut self) {
         if let Some(fling_action) = self.touch_handler.on_vsync() {
@@ -385,59 +599,66 @@ impl WebView {
     }
 
     fn on_touch_down(&mut self, event: TouchEvent) {
+        self.momentum_scroll = None;
         self.touch_handler.on_touch_down(event.id, event.point);
         self.send_touch_event(event);
     }
 
     fn on_touch_move(&mut self, mut event: TouchEvent) {
         let action: TouchMoveAction = self.touch_handler.on_touch_move(event.id, event.point);
-        if TouchMoveAction::NoAction != action {
-            // if first move processed and allowed, we directly process the move event,
-            // without waiting for the script handler.
-            if self
-                .touch_handler
-                .move_allowed(self.touch_handler.current_sequence_id)
-            {
-                // https://w3c.github.io/touch-events/#cancelability
-                event.disable_cancelable();
-                match action {
-                    TouchMoveAction::Scroll(delta, point) => self.on_scroll_window_event(
-                        ScrollLocation::Delta(LayoutVector2D::from_untyped(delta.to_untyped())),
-                        point.cast(),
-                    ),
-                    TouchMoveAction::Zoom(magnification, scroll_delta) => {
-                        let cursor = Point2D::new(-1, -1); // Make sure this hits the base layer.
-
-                        // The order of these events doesn't matter, because zoom is handled by
-                        // a root display list and the scroll event here is handled by the scroll
-                        // applied to the content display list.
-                        self.pending_scroll_zoom_events
-                            .push(ScrollZoomEvent::PinchZoom(magnification));
-                        self.pending_scroll_zoom_events
-                            .push(ScrollZoomEvent::Scroll(ScrollEvent {
-                                scroll_location: ScrollLocation::Delta(
-                                    LayoutVector2D::from_untyped(scroll_delta.to_untyped()),
-                                ),
-                                cursor,
-                                event_count: 1,
-                            }));
-                    },
-                    _ => {},
-                }
-            }
-            // When the event is touchmove, if the script thread is processing the touch
-            // move event, we skip sending the event to the script thread.
-            // This prevents the script thread from stacking up for a large amount of time.
-            if !self
-                .touch_handler
-                .is_handling_touch_move(self.touch_handler.current_sequence_id) &&
-                self.send_touch_event(event) &&
-                event.is_cancelable()
-            {
-                self.touch_handler
-                    .set_handling_touch_move(self.touch_handler.current_sequence_id, true);
+        // Moves of a single touch point within its touch slop are not sent to script, as the
+        // touch may still turn out to be a tap.
+        if TouchMoveAction::NoAction == action && self.touch_handler.is_within_touch_slop() {
+            return;
+        }
+
+        // if first move processed and allowed, we directly process the move event,
+        // without waiting for the script handler.
+        if self
+            .touch_handler
+            .move_allowed(self.touch_handler.current_sequence_id)
+        {
+            // Once the first move of the sequence was allowed, it is scrolling, and none of
+            // its following moves can be canceled.
+            // https://w3c.github.io/touch-events/#cancelability
+            event.disable_cancelable();
+            match action {
+                TouchMoveAction::Scroll(delta, point) => self.on_scroll_window_event(
+                    ScrollLocation::Delta(LayoutVector2D::from_untyped(delta.to_untyped())),
+                    point.cast(),
+                ),
+                TouchMoveAction::Zoom(magnification, scroll_delta) => {
+                    let cursor = Point2D::new(-1, -1); // Make sure this hits the base layer.
+
+                    // The order of these events doesn't matter, because zoom is handled by
+                    // a root display list and the scroll event here is handled by the scroll
+                    // applied to the content display list.
+                    self.pending_scroll_zoom_events
+                        .push(ScrollZoomEvent::PinchZoom(magnification));
+                    self.pending_scroll_zoom_events
+                        .push(ScrollZoomEvent::Scroll(ScrollEvent {
+                            scroll_location: ScrollLocation::Delta(LayoutVector2D::from_untyped(
+                                scroll_delta.to_untyped(),
+                            )),
+                            cursor,
+                            event_count: 1,
+                        }));
+                },
+                _ => {},
             }
         }
+        // When the event is touchmove, if the script thread is processing the touch
+        // move event, we skip sending the event to the script thread.
+        // This prevents the script thread from stacking up for a large amount of time.
+        if !self
+            .touch_handler
+            .is_handling_touch_move(self.touch_handler.current_sequence_id) &&
+            self.send_touch_event(event) &&
+            event.is_cancelable()
+        {
+            self.touch_handler
+                .set_handling_touch_move(self.touch_handler.current_sequence_id, true);
+        }
     }
 
     fn on_touch_up(&mut self, event: TouchEvent) {
@@ -495,7 +716,8 @@ impl WebView {
                             return;
                         };
                         match info.state {
-                            TouchSequenceState::PendingClick(_) => {
+                            TouchSequenceState::PendingClick(_) |
+                            TouchSequenceState::PendingDoubleTap(_) => {
                                 info.state = TouchSequenceState::Finished;
                                 self.touch_handler.remove_touch_sequence(sequence_id);
                             },
@@ -600,6 +822,14 @@ impl WebView {
                                 }
                                 self.touch_handler.remove_touch_sequence(sequence_id);
                             },
+                            TouchSequenceState::PendingDoubleTap(point) => {
+                                info.state = TouchSequenceState::Finished;
+                                if !info.prevent_click {
+                                    self.pending_scroll_zoom_events
+                                        .push(ScrollZoomEvent::DoubleTapZoom(point));
+                                }
+                                self.touch_handler.remove_touch_sequence(sequence_id);
+                            },
                             TouchSequenceState::Flinging { .. } => {
                                 // We can't remove the touch sequence yet
                             },
@@ -632,22 +862,22 @@ impl WebView {
     /// <http://w3c.github.io/touch-events/#mouse-events>
     fn simulate_mouse_click(&mut self, point: DevicePoint) {
         let button = MouseButton::Left;
-        self.dispatch_input_event(InputEvent::MouseMove(MouseMoveEvent { point }));
-        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent {
+        self.dispatch_input_event(InputEvent::MouseMove(MouseMoveEvent::new(point)));
+        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+            MouseButtonAction::Down,
             button,
-            action: MouseButtonAction::Down,
             point,
-        }));
-        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent {
+        )));
+        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+            MouseButtonAction::Up,
             button,
-            action: MouseButtonAction::Up,
             point,
-        }));
-        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent {
+        )));
+        self.dispatch_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+            MouseButtonAction::Click,
             button,
-            action: MouseButtonAction::Click,
             point,
-        }));
+        )));
     }
 
     pub fn notify_scroll_event(
@@ -660,6 +890,14 @@ impl WebView {
             return;
         }
 
+        if pref!(layout_momentum_scrolling_enabled) {
+            if let ScrollLocation::Delta(delta) = scroll_location {
+                self.add_scroll_momentum(delta, cursor);
+                return;
+            }
+            self.momentum_scroll = None;
+        }
+
         match event_type {
             TouchEventType::Move => self.on_scroll_window_event(scroll_location, cursor),
             TouchEventType::Up | TouchEventType::Cancel => {
@@ -680,19 +918,23 @@ impl WebView {
             }));
     }
 
-    pub(crate) fn process_pending_scroll_events(&mut self, compositor: &mut IOCompositor) {
+    /// Apply the pending scroll and zoom events. Returns whether they scrolled or zoomed
+    /// anything.
+    pub(crate) fn process_pending_scroll_events(&mut self, compositor: &mut IOCompositor) -> bool {
         if self.pending_scroll_zoom_events.is_empty() {
-            return;
+            return false;
         }
 
         // Batch up all scroll events into one, or else we'll do way too much painting.
         let mut combined_scroll_event: Option<ScrollEvent> = None;
         let mut combined_magnification = 1.0;
+        let mut double_tap_point = None;
         for scroll_event in self.pending_scroll_zoom_events.drain(..) {
             match scroll_event {
                 ScrollZoomEvent::PinchZoom(magnification) => {
                     combined_magnification *= magnification
                 },
+                ScrollZoomEvent::DoubleTapZoom(point) => double_tap_point = Some(point),
                 ScrollZoomEvent::Scroll(scroll_event_info) => {
                     let combined_event = match combined_scroll_event.as_mut() {
                         None => {
@@ -733,8 +975,11 @@ impl WebView {
             }
         }
 
-        let zoom_changed = compositor
+        let mut zoom_changed = compositor
             .set_pinch_zoom_level(compositor.pinch_zoom_level().get() * combined_magnification);
+        if let Some(point) = double_tap_point {
+            zoom_changed |= compositor.double_tap_zoom(point);
+        }
         let scroll_result = combined_scroll_event.and_then(|combined_event| {
             self.scroll_node_at_device_point(
                 combined_event.cursor.to_f32(),
@@ -742,13 +987,28 @@ impl WebView {
                 compositor,
             )
         });
-        if !zoom_changed && scroll_result.is_none() {
-            return;
+
+        // When the content under the cursor cannot scroll any further, the scroll pans the
+        // visual viewport instead.
+        let visual_viewport_panned = scroll_result.is_none() &&
+            combined_scroll_event.is_some_and(|combined_event| {
+                let ScrollLocation::Delta(delta) = combined_event.scroll_location else {
+                    return false;
+                };
+                let device_pixels_per_root_pixel = compositor.device_pixels_per_root_pixel();
+                compositor.pan_visual_viewport(LayoutVector2D::from_untyped(
+                    (Vector2D::from_untyped(delta.to_untyped()) / device_pixels_per_root_pixel)
+                        .to_untyped(),
+                ))
+            });
+        if !zoom_changed && !visual_viewport_panned && scroll_result.is_none() {
+            return false;
         }
 
         let mut transaction = Transaction::new();
-        if zoom_changed {
+        if zoom_changed || visual_viewport_panned {
             compositor.send_root_pipeline_display_list_in_transaction(&mut transaction);
+            compositor.send_visual_viewport_to_script();
         }
 
         if let Some((pipeline_id, external_id, offset)) = scroll_result {
@@ -760,11 +1020,17 @@ impl WebView {
                     generation: 0,
                 }],
             );
+            if let Some(details) = self.pipelines.get(&pipeline_id) {
+                transaction.append_dynamic_transform_properties(
+                    details.scroll_tree.scrollbar_thumb_transforms(external_id),
+                );
+            }
             self.send_scroll_positions_to_layout_for_pipeline(pipeline_id);
         }
 
         compositor.generate_frame(&mut transaction, RenderReasons::APZ);
         self.global.borrow_mut().send_transaction(transaction);
+        true
     }
 
     /// Perform a hit test at the given [`DevicePoint`] and apply the [`ScrollLocation`]
@@ -779,7 +1045,8 @@ impl WebView {
     ) -> Option<(PipelineId, ExternalScrollId, LayoutVector2D)> {
         let scroll_location = match scroll_location {
             ScrollLocation::Delta(delta) => {
-                let device_pixels_per_page = compositor.device_pixels_per_page_pixel();
+                let device_pixels_per_page =
+                    self.page_zoom * compositor.device_pixels_per_root_pixel();
                 let scaled_delta = (Vector2D::from_untyped(delta.to_untyped()) /
                     device_pixels_per_page)
                     .to_untyped();
@@ -830,7 +1097,6 @@ impl WebView {
             return;
         }
 
-        // TODO: Scroll to keep the center in view?
         self.pending_scroll_zoom_events
             .push(ScrollZoomEvent::PinchZoom(magnification));
     }
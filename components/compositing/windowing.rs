@@ -6,7 +6,7 @@
 
 use std::fmt::Debug;
 
-use embedder_traits::{EventLoopWaker, MouseButton};
+use embedder_traits::{EventLoopWaker, MouseButton, ScreenInfo};
 use euclid::Scale;
 use net::protocols::ProtocolRegistry;
 use servo_geometry::{DeviceIndependentIntRect, DeviceIndependentIntSize, DeviceIndependentPixel};
@@ -44,6 +44,12 @@ pub trait WindowMethods {
     /// will want to avoid blocking on UI events, and just
     /// run the event loop at the vsync interval.
     fn set_animation_state(&self, _state: AnimationState);
+    /// Get a description of every screen connected to the system. Embedders that do not know
+    /// about the screen topology may leave this unimplemented, in which case a single screen
+    /// is derived from the [`EmbedderCoordinates`].
+    fn get_screens(&self) -> Vec<ScreenInfo> {
+        Vec::new()
+    }
 }
 
 pub trait EmbedderMethods {
@@ -9,8 +9,9 @@ use synstructure::decl_derive;
 
 decl_derive!([ServoPreferences] => servo_preferences_derive);
 
-/// A derive macro that adds string-based getter and setter for each field of this struct
-/// (enums and other types are not supported). Each field must be able to be convertable
+/// A derive macro that adds string-based getter and setter for each field of this struct,
+/// and a way to list the fields that differ between two instances (enums and other types
+/// are not supported). Each field must be able to be convertable
 /// (with `into()`) into a `PrefValue`.
 fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
     let ast = input.ast();
@@ -34,6 +35,30 @@ fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
         set_match_cases.extend(quote!(stringify!(#name) => self.#name = value.try_into().unwrap(),))
     }
 
+    let mut try_set_match_cases = quote!();
+    for field in named_fields.named.iter() {
+        let name = field.ident.as_ref().unwrap();
+        try_set_match_cases.extend(quote!(stringify!(#name) => {
+            self.#name = value.try_into().map_err(PreferenceError::InvalidValue)?
+        },))
+    }
+
+    let mut changes_cases = quote!();
+    for field in named_fields.named.iter() {
+        let name = field.ident.as_ref().unwrap();
+        changes_cases.extend(quote!(
+            if self.#name != other.#name {
+                changes.push((stringify!(#name), self.#name.clone().into()));
+            }
+        ))
+    }
+
+    let mut values_cases = quote!();
+    for field in named_fields.named.iter() {
+        let name = field.ident.as_ref().unwrap();
+        values_cases.extend(quote!((stringify!(#name), self.#name.clone().into()),))
+    }
+
     let structure_name = &ast.ident;
     quote! {
         impl #structure_name {
@@ -50,6 +75,28 @@ fn servo_preferences_derive(input: synstructure::Structure) -> TokenStream {
                     _ => { panic!("Unknown preference: {:?}", name); }
                 }
             }
+
+            /// Like `set_value`, but fails instead of panicking when there is no preference
+            /// called `name`, or when `value` does not have the type of the preference.
+            pub fn try_set_value(&mut self, name: &str, value: PrefValue) -> Result<(), PreferenceError> {
+                match name {
+                    #try_set_match_cases
+                    _ => return Err(PreferenceError::UnknownPreference(name.to_owned())),
+                }
+                Ok(())
+            }
+
+            /// The names and values of all of the fields.
+            pub fn values(&self) -> Vec<(&'static str, PrefValue)> {
+                vec![#values_cases]
+            }
+
+            /// The names and values of the fields that differ from those of `other`.
+            pub fn changes_from(&self, other: &Self) -> Vec<(&'static str, PrefValue)> {
+                let mut changes = Vec::new();
+                #changes_cases
+                changes
+            }
         }
     }
 }
@@ -32,6 +32,10 @@ pub struct Opts {
     /// visualizing the traces as a timeline.
     pub time_profiler_trace_path: Option<String>,
 
+    /// An optional path to record the traces of the time profiler into, in the Chrome trace
+    /// event format that Perfetto can load. This does not require the profiler to be enabled.
+    pub time_profiler_chrome_trace_path: Option<String>,
+
     /// True to turn off incremental layout.
     pub nonincremental_layout: bool,
 
@@ -90,6 +94,12 @@ pub struct Opts {
 
     /// Print Progressive Web Metrics to console.
     pub print_pwm: bool,
+
+    /// Whether to render reproducibly, for automated testing. Timers, animation frames,
+    /// `performance.now()` and animations of documents follow a clock that only advances when
+    /// a test asks for it, and text is rendered without subpixel antialiasing and hinting,
+    /// using only the configured generic font families.
+    pub deterministic: bool,
 }
 
 /// Debug options for Servo, currently set on the command line with -Z
@@ -185,6 +195,7 @@ impl Default for Opts {
             wait_for_stable_image: false,
             time_profiling: None,
             time_profiler_trace_path: None,
+            time_profiler_chrome_trace_path: None,
             nonincremental_layout: false,
             user_stylesheets: Vec::new(),
             hard_fail: true,
@@ -203,6 +214,7 @@ impl Default for Opts {
             local_script_source: None,
             unminify_css: false,
             print_pwm: false,
+            deterministic: false,
         }
     }
 }
@@ -103,30 +103,26 @@ impl From<[f64; 4]> for PrefValue {
     }
 }
 
-impl From<PrefValue> for [f64; 4] {
-    fn from(other: PrefValue) -> [f64; 4] {
-        match other {
-            PrefValue::Array(values) if values.len() == 4 => {
-                let values: Vec<f64> = values
-                    .into_iter()
-                    .map(TryFrom::try_from)
-                    .filter_map(Result::ok)
-                    .collect();
-                if values.len() == 4 {
-                    [values[0], values[1], values[2], values[3]]
-                } else {
-                    panic!(
-                        "Cannot convert PrefValue to {:?}",
-                        std::any::type_name::<[f64; 4]>()
-                    )
-                }
-            },
-            _ => panic!(
+impl TryFrom<PrefValue> for [f64; 4] {
+    type Error = String;
+    fn try_from(other: PrefValue) -> Result<Self, Self::Error> {
+        let error = || {
+            format!(
                 "Cannot convert {:?} to {:?}",
                 other,
                 std::any::type_name::<[f64; 4]>()
-            ),
-        }
+            )
+        };
+        let PrefValue::Array(values) = &other else {
+            return Err(error());
+        };
+        let values: Vec<f64> = values
+            .iter()
+            .cloned()
+            .map(TryFrom::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|_| error())?;
+        values.try_into().map_err(|_| error())
     }
 }
 
@@ -91,11 +91,13 @@ use std::marker::PhantomData;
 use std::mem::replace;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::{process, thread};
 
 use background_hang_monitor::HangMonitorRegister;
 use background_hang_monitor_api::{
-    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangMonitorAlert,
+    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangAlert, HangAnnotation,
+    HangMonitorAlert, MonitoredComponentId,
 };
 use base::Epoch;
 use base::id::{
@@ -111,7 +113,8 @@ use canvas_traits::webgl::WebGLThreads;
 use compositing_traits::{CompositorMsg, CompositorProxy, SendableFrameTree};
 use constellation_traits::{
     AnimationTickType, CompositorHitTestResult, ConstellationMsg as FromCompositorMsg, LogEntry,
-    PaintMetricEvent, ScrollState, TraversalDirection, WindowSizeData, WindowSizeType,
+    PaintMetricEvent, ScrollState, TraversalDirection, VisualViewportState, WindowSizeData,
+    WindowSizeType,
 };
 use crossbeam_channel::{Receiver, Sender, select, unbounded};
 use devtools_traits::{
@@ -121,9 +124,11 @@ use devtools_traits::{
 use embedder_traits::resources::{self, Resource};
 use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    Cursor, EmbedderMsg, EmbedderProxy, ImeEvent, InputEvent, MediaSessionActionType,
-    MediaSessionEvent, MediaSessionPlaybackState, MouseButton, MouseButtonAction, MouseButtonEvent,
-    Theme, WebDriverCommandMsg, WebDriverLoadStatus,
+    AnimationClockRequest, AutofillRequest, AuxiliaryWebViewFeatures, CrashReport, Cursor,
+    EmbedderMsg, EmbedderProxy, EmulatedMediaFeatures, FindInPageRequest, ImeEvent, InputEvent,
+    MediaSessionActionType, MediaSessionEvent, MediaSessionPlaybackState, MouseButton,
+    MouseButtonAction, MouseButtonEvent, NetworkStatus, Theme, UnresponsiveScriptAction,
+    WebDriverBiDiEvent, WebDriverCommandMsg, WebDriverLoadStatus,
 };
 use euclid::Size2D;
 use euclid::default::Size2D as UntypedSize2D;
@@ -135,7 +140,9 @@ use keyboard_types::webdriver::Event as WebDriverInputEvent;
 use keyboard_types::{Key, KeyState, KeyboardEvent, Modifiers};
 use log::{debug, error, info, trace, warn};
 use media::WindowGLContext;
-use net_traits::pub_domains::reg_host;
+use net::about;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
+use net_traits::pub_domains::{reg_host, site};
 use net_traits::request::Referrer;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
 use net_traits::{self, IpcSend, ReferrerPolicy, ResourceThreads};
@@ -146,11 +153,13 @@ use script_traits::{
     BroadcastMsg, ConstellationInputEvent, DiscardBrowsingContext, DocumentActivity, DocumentState,
     IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, IFrameSizeMsg, Job, LoadData,
     LoadOrigin, MessagePortMsg, NavigationHistoryBehavior, PortMessageTask,
-    ProgressiveWebMetricType, SWManagerMsg, SWManagerSenders, ScriptMsg as FromScriptMsg,
-    ScriptThreadMessage, ScriptToConstellationChan, ServiceWorkerManagerFactory, ServiceWorkerMsg,
-    StructuredSerializedData, UpdatePipelineIdReason,
+    ProgressiveWebMetricType, SWManagerMsg, SWManagerSenders, SandboxingFlagSet,
+    ScriptMsg as FromScriptMsg, ScriptThreadMessage, ScriptToConstellationChan,
+    ServiceWorkerManagerFactory, ServiceWorkerMsg, StructuredSerializedData,
+    UpdatePipelineIdReason,
 };
 use serde::{Deserialize, Serialize};
+use servo_config::prefs::{self, PrefValue, Preferences, SitePreferences};
 use servo_config::{opts, pref};
 use servo_rand::{Rng, ServoRng, SliceRandom, random};
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
@@ -170,6 +179,7 @@ use crate::browsingcontext::{
     NewBrowsingContextInfo,
 };
 use crate::event_loop::EventLoop;
+use crate::memory_report::memory_report;
 use crate::pipeline::{InitialPipelineState, Pipeline};
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 use crate::session_history::{
@@ -179,6 +189,9 @@ use crate::webview::WebViewManager;
 
 type PendingApprovalNavigations = HashMap<PipelineId, (LoadData, NavigationHistoryBehavior)>;
 
+/// The maximum number of prerendered pipelines a single document may keep alive at once.
+const MAX_PRERENDERS_PER_DOCUMENT: usize = 2;
+
 #[derive(Debug)]
 /// The state used by MessagePortInfo to represent the various states the port can be in.
 enum TransferState {
@@ -215,6 +228,31 @@ struct MessagePortInfo {
     entangled_with: Option<MessagePortId>,
 }
 
+/// A pipeline that is being loaded in the background on behalf of a document's speculation rules,
+/// so that a later navigation of that document to the same URL can be activated without waiting
+/// for the network.
+///
+/// <https://wicg.github.io/nav-speculation/prerendering.html>
+struct PrerenderedPipeline {
+    /// The pipeline whose speculation rules requested this prerender.
+    source_pipeline_id: PipelineId,
+
+    /// The URL being prerendered.
+    url: ServoUrl,
+
+    /// Whether the prerendered document has been created and could become the active document
+    /// of its browsing context.
+    ready_to_activate: bool,
+
+    /// Messages sent by the prerendered document to the embedder, which are held back until the
+    /// document is activated.
+    deferred_embedder_messages: Vec<EmbedderMsg>,
+
+    /// Whether this is the document of a `<portal>` element, which is shown in place by its
+    /// source and only activated explicitly, rather than by navigating to its URL.
+    is_portal: bool,
+}
+
 #[cfg(feature = "webgpu")]
 /// Webrender related objects required by WebGPU threads
 struct WebrenderWGPU {
@@ -238,6 +276,22 @@ struct WebView {
 
     /// The joint session history for this webview.
     session_history: JointSessionHistory,
+
+    /// Whether the embedder muted the audio of this webview. Event loops that start hosting
+    /// one of its pipelines are told about it.
+    muted: bool,
+
+    /// The media features that the embedder emulates for this webview. Event loops that
+    /// start hosting one of its pipelines are told about them.
+    emulated_media_features: EmulatedMediaFeatures,
+
+    /// Whether this webview is visible to the user, as reported by the embedder. Event loops
+    /// that start hosting one of its pipelines are told when it is hidden.
+    visible: bool,
+
+    /// The size of this webview, along with its zoom factors, which new pipelines in it
+    /// start out with.
+    window_size: WindowSizeData,
 }
 
 /// A browsing context group.
@@ -299,6 +353,10 @@ pub struct Constellation<STF, SWF> {
     /// so that they could be controlled on a "per-tab/event-loop" basis.
     background_monitor_control_senders: Vec<IpcSender<BackgroundHangMonitorControlMsg>>,
 
+    /// The pipelines whose script is reported to be permanently hanging, and the
+    /// script-thread that is running it, for as long as the hang lasts.
+    unresponsive_pipelines: HashMap<PipelineId, MonitoredComponentId>,
+
     /// A channel for the background hang monitor to send messages
     /// to the constellation.
     background_hang_monitor_sender: IpcSender<HangMonitorAlert>,
@@ -452,6 +510,10 @@ pub struct Constellation<STF, SWF> {
     /// Navigation requests from script awaiting approval from the embedder.
     pending_approval_navigations: PendingApprovalNavigations,
 
+    /// Pipelines that are being prerendered in the background, keyed by their id. These
+    /// pipelines are not part of any session history until a navigation activates them.
+    prerendered_pipelines: HashMap<PipelineId, PrerenderedPipeline>,
+
     /// Bitmask which indicates which combination of mouse buttons are
     /// currently being pressed.
     pressed_mouse_buttons: u16,
@@ -475,6 +537,13 @@ pub struct Constellation<STF, SWF> {
 
     /// User content manager
     user_content_manager: UserContentManager,
+
+    /// The connectivity and quality of the network, as last reported by the embedder.
+    network_status: NetworkStatus,
+
+    /// The platform theme, as last reported by the embedder. Event loops that start hosting
+    /// a pipeline are told about it.
+    theme: Theme,
 }
 
 /// State needed to construct a constellation.
@@ -536,6 +605,9 @@ pub struct InitialConstellationState {
 struct WebDriverData {
     load_channel: Option<(PipelineId, IpcSender<WebDriverLoadStatus>)>,
     resize_channel: Option<IpcSender<Size2D<f32, CSSPixel>>>,
+    /// Where to send the events of the top-level browsing contexts, while the WebDriver BiDi
+    /// server runs.
+    bidi_event_sender: Option<IpcSender<WebDriverBiDiEvent>>,
 }
 
 impl WebDriverData {
@@ -543,6 +615,17 @@ impl WebDriverData {
         WebDriverData {
             load_channel: None,
             resize_channel: None,
+            bidi_event_sender: None,
+        }
+    }
+
+    fn notify_bidi(&mut self, event: WebDriverBiDiEvent) {
+        let Some(sender) = &self.bidi_event_sender else {
+            return;
+        };
+        if sender.send(event).is_err() {
+            warn!("WebDriver BiDi server is gone");
+            self.bidi_event_sender = None;
         }
     }
 }
@@ -659,7 +742,8 @@ where
                             Some(HangMonitorRegister::init(
                                 background_hang_monitor_ipc_sender.clone(),
                                 background_hang_monitor_control_ipc_receiver,
-                                opts::get().background_hang_monitor,
+                                opts::get().background_hang_monitor ||
+                                    pref!(dom_script_watchdog_enabled),
                             )),
                             vec![background_hang_monitor_control_ipc_sender],
                         )
@@ -690,6 +774,7 @@ where
                     background_hang_monitor_receiver,
                     background_monitor_register,
                     background_monitor_control_senders: background_hang_monitor_control_ipc_senders,
+                    unresponsive_pipelines: HashMap::new(),
                     script_receiver,
                     compositor_receiver,
                     layout_factory,
@@ -740,6 +825,7 @@ where
                     canvas_sender: canvas_create_sender,
                     canvas_ipc_sender,
                     pending_approval_navigations: HashMap::new(),
+                    prerendered_pipelines: HashMap::new(),
                     pressed_mouse_buttons: 0,
                     active_keyboard_modifiers: Modifiers::empty(),
                     hard_fail,
@@ -747,6 +833,8 @@ where
                     user_agent: state.user_agent,
                     rippy_data,
                     user_content_manager: state.user_content_manager,
+                    network_status: NetworkStatus::default(),
+                    theme: Theme::Light,
                 };
 
                 constellation.run();
@@ -889,7 +977,7 @@ where
         // here, because it will be send on an ipc channel,
         // and ipc channels take onership of their data.
         // https://github.com/servo/ipc-channel/issues/138
-        load_data: LoadData,
+        mut load_data: LoadData,
         sandbox: IFrameSandboxState,
         is_private: bool,
         throttled: bool,
@@ -897,6 +985,18 @@ where
         if self.shutting_down {
             return;
         }
+
+        // Nested documents inherit the origin of the top-level document from their parent,
+        // which partitions and blocks their third-party cookies.
+        load_data.top_level_origin = parent_pipeline_id
+            .and_then(|parent_pipeline_id| self.pipelines.get(&parent_pipeline_id))
+            .map(|parent| {
+                parent
+                    .load_data
+                    .top_level_origin
+                    .clone()
+                    .unwrap_or_else(|| parent.url.origin())
+            });
         debug!(
             "{}: Creating new pipeline in {}",
             pipeline_id, browsing_context_id
@@ -976,7 +1076,7 @@ where
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: WindowSizeData {
                 initial_viewport: initial_window_size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                ..self.webview_window_size(webview_id)
             },
             event_loop,
             load_data,
@@ -991,6 +1091,7 @@ where
             user_agent: self.user_agent.clone(),
             rippy_data: self.rippy_data.clone(),
             user_content_manager: self.user_content_manager.clone(),
+            network_status: self.network_status,
         });
 
         let pipeline = match result {
@@ -1012,6 +1113,47 @@ where
             );
         }
 
+        if self
+            .webviews
+            .get(webview_id)
+            .is_some_and(|webview| webview.muted)
+        {
+            let msg = ScriptThreadMessage::SetWebViewMuted(webview_id, true);
+            if let Err(err) = pipeline.pipeline.event_loop.send(msg) {
+                warn!("{pipeline_id}: Failed to send webview muting ({err:?}).");
+            }
+        }
+
+        if self
+            .webviews
+            .get(webview_id)
+            .is_some_and(|webview| !webview.visible)
+        {
+            let msg = ScriptThreadMessage::SetWebViewVisibility(webview_id, false);
+            if let Err(err) = pipeline.pipeline.event_loop.send(msg) {
+                warn!("{pipeline_id}: Failed to send webview visibility ({err:?}).");
+            }
+        }
+
+        if let Some(features) = self
+            .webviews
+            .get(webview_id)
+            .map(|webview| webview.emulated_media_features)
+            .filter(|features| *features != EmulatedMediaFeatures::default())
+        {
+            let msg = ScriptThreadMessage::SetEmulatedMediaFeatures(webview_id, features);
+            if let Err(err) = pipeline.pipeline.event_loop.send(msg) {
+                warn!("{pipeline_id}: Failed to send emulated media features ({err:?}).");
+            }
+        }
+
+        if self.theme != Theme::Light {
+            let msg = ScriptThreadMessage::ThemeChange(pipeline_id, self.theme);
+            if let Err(err) = pipeline.pipeline.event_loop.send(msg) {
+                warn!("{pipeline_id}: Failed to send theme change ({err:?}).");
+            }
+        }
+
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
     }
@@ -1200,19 +1342,136 @@ where
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
     )]
-    fn handle_request_from_background_hang_monitor(&self, message: HangMonitorAlert) {
+    fn handle_request_from_background_hang_monitor(&mut self, message: HangMonitorAlert) {
         match message {
             HangMonitorAlert::Profile(bytes) => {
                 self.embedder_proxy.send(EmbedderMsg::ReportProfile(bytes))
             },
             HangMonitorAlert::Hang(hang) => {
-                // TODO: In case of a permanent hang being reported, add a "kill script" workflow,
-                // via the embedder?
                 warn!("Component hang alert: {:?}", hang);
+                if let HangAlert::Permanent(
+                    component_id,
+                    HangAnnotation::Script(_, Some(pipeline_id)),
+                    _,
+                ) = hang
+                {
+                    self.handle_script_unresponsive(component_id, pipeline_id);
+                }
+            },
+            HangMonitorAlert::HangEnded(component_id) => {
+                self.handle_script_responsive(component_id);
             },
         }
     }
 
+    /// Ask the memory profiler for the reports of every process, and send them to the
+    /// embedder once they are summed by origin and by pipeline.
+    fn handle_create_memory_report(&self) {
+        let Ok((sender, receiver)) = ipc::channel::<mem::MemoryReportResult>() else {
+            return warn!("Failed to create IPC channel for memory report");
+        };
+        let pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .map(|pipeline| (pipeline.webview_id, pipeline.id))
+            .collect();
+        let embedder_proxy = self.embedder_proxy.clone();
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |result| {
+                let reports = result
+                    .ok()
+                    .and_then(|result| serde_json::from_str(&result.content).ok())
+                    .unwrap_or_default();
+                embedder_proxy.send(EmbedderMsg::MemoryReportCreated(memory_report(
+                    reports, &pipelines,
+                )));
+            }),
+        );
+        self.mem_profiler_chan
+            .send(mem::ProfilerMsg::Report(sender));
+    }
+
+    /// Let the embedder decide what to do about a script that has been hanging for long
+    /// enough to be considered permanently stuck.
+    fn handle_script_unresponsive(
+        &mut self,
+        component_id: MonitoredComponentId,
+        pipeline_id: PipelineId,
+    ) {
+        if !pref!(dom_script_watchdog_enabled) {
+            return;
+        }
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            return warn!("{pipeline_id}: Unresponsive script after closure");
+        };
+        self.unresponsive_pipelines
+            .insert(pipeline_id, component_id);
+        self.embedder_proxy.send(EmbedderMsg::ScriptUnresponsive(
+            pipeline.webview_id,
+            pipeline_id,
+        ));
+    }
+
+    fn handle_script_responsive(&mut self, component_id: MonitoredComponentId) {
+        let pipeline_ids: Vec<_> = self
+            .unresponsive_pipelines
+            .iter()
+            .filter(|(_, id)| **id == component_id)
+            .map(|(pipeline_id, _)| *pipeline_id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            self.unresponsive_pipelines.remove(&pipeline_id);
+            if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                self.embedder_proxy.send(EmbedderMsg::ScriptResponsive(
+                    pipeline.webview_id,
+                    pipeline_id,
+                ));
+            }
+        }
+    }
+
+    /// Stop the unresponsive script running in `pipeline_id`, as decided by the embedder.
+    fn handle_stop_unresponsive_script(
+        &mut self,
+        pipeline_id: PipelineId,
+        action: UnresponsiveScriptAction,
+    ) {
+        let Some(component_id) = self.unresponsive_pipelines.remove(&pipeline_id) else {
+            return warn!("{pipeline_id}: Tried to stop a script that is not unresponsive");
+        };
+        // The monitor of the script-thread is not known, but only one of them monitors it.
+        for background_monitor_control_sender in &self.background_monitor_control_senders {
+            let message = match action {
+                UnresponsiveScriptAction::Interrupt => {
+                    BackgroundHangMonitorControlMsg::InterruptComponent(component_id.clone())
+                },
+                UnresponsiveScriptAction::Kill => {
+                    BackgroundHangMonitorControlMsg::ExitComponent(component_id.clone())
+                },
+            };
+            if let Err(e) = background_monitor_control_sender.send(message) {
+                warn!("error communicating with background hang monitor: {}", e);
+            }
+        }
+        if action == UnresponsiveScriptAction::Interrupt {
+            return;
+        }
+
+        // The killed event-loop can't run any more script, so none of the pages it hosts
+        // will work anymore: they are replaced with crash pages, as after a panic.
+        let webview_id = self
+            .pipelines
+            .get(&pipeline_id)
+            .map(|pipeline| pipeline.webview_id);
+        self.handle_panic(
+            webview_id,
+            Some(pipeline_id),
+            "The page was stopped because it was unresponsive".into(),
+            None,
+        );
+    }
+
     fn handle_request_from_swmanager(&mut self, message: SWManagerMsg) {
         match message {
             SWManagerMsg::PostMessageToClient => {
@@ -1289,6 +1548,11 @@ where
                 self.public_resource_threads.clear_cache();
                 self.private_resource_threads.clear_cache();
             },
+            FromCompositorMsg::ClearStorageForSite(url) => {
+                self.public_resource_threads
+                    .clear_storage_for_site(url.clone());
+                self.private_resource_threads.clear_storage_for_site(url);
+            },
             // Load a new page from a typed url
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
@@ -1341,7 +1605,7 @@ where
                 if webview_id.is_none() {
                     warn!("constellation got a SendError message without top level id");
                 }
-                self.handle_panic(webview_id, error, None);
+                self.handle_panic(webview_id, None, error, None);
             },
             FromCompositorMsg::FocusWebView(webview_id) => {
                 self.handle_focus_web_view(webview_id);
@@ -1360,6 +1624,18 @@ where
             FromCompositorMsg::ThemeChange(theme) => {
                 self.handle_theme_change(theme);
             },
+            FromCompositorMsg::NetworkStatusChanged(network_status) => {
+                self.handle_network_status_changed(network_status);
+            },
+            FromCompositorMsg::SetCookiePolicy(webview_id, policy) => {
+                self.public_resource_threads
+                    .set_cookie_policy(webview_id, policy);
+                self.private_resource_threads
+                    .set_cookie_policy(webview_id, policy);
+            },
+            FromCompositorMsg::ScreensChanged => {
+                self.handle_screens_changed();
+            },
             FromCompositorMsg::TickAnimation(pipeline_id, tick_type) => {
                 self.handle_tick_animation(pipeline_id, tick_type)
             },
@@ -1369,8 +1645,8 @@ where
             FromCompositorMsg::Reload(webview_id) => {
                 self.handle_reload_msg(webview_id);
             },
-            FromCompositorMsg::LogEntry(webview_id, thread_name, entry) => {
-                self.handle_log_entry(webview_id, thread_name, entry);
+            FromCompositorMsg::LogEntry(webview_id, pipeline_id, thread_name, entry) => {
+                self.handle_log_entry(webview_id, pipeline_id, thread_name, entry);
             },
             FromCompositorMsg::ForwardInputEvent(webview_id, event, hit_test) => {
                 self.forward_input_event(webview_id, event, hit_test);
@@ -1396,12 +1672,43 @@ where
             FromCompositorMsg::SetWebViewThrottled(webview_id, throttled) => {
                 self.set_webview_throttled(webview_id, throttled);
             },
+            FromCompositorMsg::SetWebViewVisibility(webview_id, visible) => {
+                self.set_webview_visibility(webview_id, visible);
+            },
+            FromCompositorMsg::SetUserContentManager(user_content_manager) => {
+                self.set_user_content_manager(user_content_manager);
+            },
+            FromCompositorMsg::PreferencesUpdated(preferences, site_preferences) => {
+                self.handle_preferences_updated(preferences, site_preferences);
+            },
+            FromCompositorMsg::CreateMemoryReport => self.handle_create_memory_report(),
+            FromCompositorMsg::StopUnresponsiveScript(pipeline_id, action) => {
+                self.handle_stop_unresponsive_script(pipeline_id, action);
+            },
+            FromCompositorMsg::SetWebViewMuted(webview_id, muted) => {
+                self.set_webview_muted(webview_id, muted);
+            },
+            FromCompositorMsg::SetEmulatedMediaFeatures(webview_id, features) => {
+                self.set_emulated_media_features(webview_id, features);
+            },
             FromCompositorMsg::SetScrollStates(pipeline_id, scroll_states) => {
                 self.handle_set_scroll_states(pipeline_id, scroll_states)
             },
+            FromCompositorMsg::SetVisualViewport(pipeline_id, visual_viewport) => {
+                self.handle_set_visual_viewport(pipeline_id, visual_viewport)
+            },
             FromCompositorMsg::PaintMetric(pipeline_id, paint_metric_event) => {
                 self.handle_paint_metric(pipeline_id, paint_metric_event);
             },
+            FromCompositorMsg::ControlAnimationClock(webview_id, request) => {
+                self.handle_control_animation_clock(webview_id, request);
+            },
+            FromCompositorMsg::FindInPage(webview_id, request) => {
+                self.handle_find_in_page(webview_id, request);
+            },
+            FromCompositorMsg::Autofill(webview_id, request) => {
+                self.handle_autofill(webview_id, request);
+            },
         }
     }
 
@@ -1482,7 +1789,14 @@ where
                 self.handle_schedule_broadcast(source_pipeline_id, router_id, message);
             },
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
-                self.embedder_proxy.send(embedder_msg);
+                // Prerendered documents stay hidden from the embedder until they are activated.
+                // Script blocks on the response to some messages, so those cannot wait.
+                match self.prerendered_pipelines.get_mut(&source_pipeline_id) {
+                    Some(prerender) if !embedder_msg.expects_response() => {
+                        prerender.deferred_embedder_messages.push(embedder_msg)
+                    },
+                    _ => self.embedder_proxy.send(embedder_msg),
+                }
             },
             FromScriptMsg::PipelineExited => {
                 self.handle_pipeline_exited(source_pipeline_id);
@@ -1502,6 +1816,11 @@ where
             FromScriptMsg::CreateAuxiliaryWebView(load_info) => {
                 self.handle_script_new_auxiliary(load_info);
             },
+            FromScriptMsg::DisownOpener => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.opener = None;
+                }
+            },
             FromScriptMsg::ChangeRunningAnimationsState(animation_state) => {
                 self.handle_change_running_animations_state(source_pipeline_id, animation_state)
             },
@@ -1514,6 +1833,48 @@ where
                     history_handling,
                 );
             },
+            FromScriptMsg::Prerender(load_data) => {
+                self.handle_prerender_msg(webview_id, source_pipeline_id, load_data);
+            },
+            FromScriptMsg::CreatePortal(portal_pipeline_id, mut load_data) => {
+                load_data.is_portal = true;
+                self.start_prerendered_pipeline(
+                    webview_id,
+                    source_pipeline_id,
+                    portal_pipeline_id,
+                    load_data,
+                    true,
+                );
+            },
+            FromScriptMsg::ActivatePortal(portal_pipeline_id, data, response_sender) => {
+                let activated = self.handle_activate_portal_msg(
+                    webview_id,
+                    source_pipeline_id,
+                    portal_pipeline_id,
+                    data,
+                );
+                if let Err(error) = response_sender.send(activated) {
+                    warn!("Failed to respond to portal activation: {error}");
+                }
+            },
+            FromScriptMsg::DiscardPortal(portal_pipeline_id) => {
+                self.handle_discard_portal_msg(source_pipeline_id, portal_pipeline_id);
+            },
+            FromScriptMsg::PostMessageToPortal(portal_pipeline_id, source_origin, data) => {
+                self.handle_post_message_to_portal_msg(
+                    source_pipeline_id,
+                    portal_pipeline_id,
+                    source_origin,
+                    data,
+                );
+            },
+            FromScriptMsg::PostMessageToPortalHost(source_origin, data) => {
+                self.handle_post_message_to_portal_host_msg(
+                    source_pipeline_id,
+                    source_origin,
+                    data,
+                );
+            },
             FromScriptMsg::AbortLoadUrl => {
                 self.handle_abort_load_url_msg(source_pipeline_id);
             },
@@ -1521,6 +1882,15 @@ where
             FromScriptMsg::LoadComplete => {
                 self.handle_load_complete_msg(webview_id, source_pipeline_id)
             },
+            FromScriptMsg::DOMContentLoaded => {
+                self.handle_dom_content_loaded_msg(webview_id, source_pipeline_id)
+            },
+            FromScriptMsg::WebDriverChannelMessage(channel, message) => {
+                self.webdriver
+                    .notify_bidi(WebDriverBiDiEvent::ChannelMessage(
+                        webview_id, channel, message,
+                    ));
+            },
             // Handle navigating to a fragment
             FromScriptMsg::NavigatedToFragment(new_url, replacement_enabled) => {
                 self.handle_navigated_to_fragment(source_pipeline_id, new_url, replacement_enabled);
@@ -1536,6 +1906,16 @@ where
             FromScriptMsg::ReplaceHistoryState(history_state_id, url) => {
                 self.handle_replace_history_state_msg(source_pipeline_id, history_state_id, url);
             },
+            FromScriptMsg::SetSessionHistoryEntryState(history_state_id, state) => {
+                match self.pipelines.get_mut(&source_pipeline_id) {
+                    Some(pipeline) => {
+                        pipeline
+                            .session_history_entry_states
+                            .insert(history_state_id, state);
+                    },
+                    None => warn!("{source_pipeline_id}: Set history entry state after closure"),
+                }
+            },
             // Handle a joint session history length request.
             FromScriptMsg::JointSessionHistoryLength(response_sender) => {
                 self.handle_joint_session_history_length(webview_id, response_sender);
@@ -1593,8 +1973,8 @@ where
 
                 response_sender.send(true).unwrap_or_default();
             },
-            FromScriptMsg::LogEntry(thread_name, entry) => {
-                self.handle_log_entry(Some(webview_id), thread_name, entry);
+            FromScriptMsg::LogEntry(pipeline_id, thread_name, entry) => {
+                self.handle_log_entry(Some(webview_id), pipeline_id, thread_name, entry);
             },
             FromScriptMsg::TouchEventProcessed(result) => self
                 .compositor_proxy
@@ -1707,6 +2087,9 @@ where
                 self.mem_profiler_chan
                     .send(mem::ProfilerMsg::Report(sender));
             },
+            FromScriptMsg::SetPreference(name, value) => {
+                self.handle_set_preference_msg(source_pipeline_id, name, value);
+            },
         }
     }
 
@@ -2432,9 +2815,22 @@ where
         old_value: Option<String>,
         new_value: Option<String>,
     ) {
+        // Only pipelines that share the storage of the source pipeline, which is partitioned
+        // by the site of the top-level document, see the change.
+        let top_level_site = |pipeline: &Pipeline| {
+            let top_level_origin = pipeline.load_data.top_level_origin.clone();
+            site(&top_level_origin.unwrap_or_else(|| pipeline.url.origin()))
+        };
+        let Some(source_top_level_site) = self.pipelines.get(&pipeline_id).map(top_level_site)
+        else {
+            return warn!("{}: Storage event from closed pipeline", pipeline_id);
+        };
         let origin = url.origin();
         for pipeline in self.pipelines.values() {
-            if (pipeline.id != pipeline_id) && (pipeline.url.origin() == origin) {
+            if (pipeline.id != pipeline_id) &&
+                (pipeline.url.origin() == origin) &&
+                top_level_site(pipeline) == source_top_level_site
+            {
                 let msg = ScriptThreadMessage::DispatchStorageEvent(
                     pipeline.id,
                     storage,
@@ -2678,7 +3074,7 @@ where
             .get(&pipeline_id)
             .map(|pipeline| pipeline.webview_id);
         let reason = format!("Send failed ({})", err);
-        self.handle_panic(webview_id, reason, None);
+        self.handle_panic(webview_id, Some(pipeline_id), reason, None);
     }
 
     #[cfg_attr(
@@ -2688,6 +3084,7 @@ where
     fn handle_panic(
         &mut self,
         webview_id: Option<WebViewId>,
+        pipeline_id: Option<PipelineId>,
         reason: String,
         backtrace: Option<String>,
     ) {
@@ -2698,32 +3095,108 @@ where
             process::exit(1);
         }
 
-        let webview_id = match webview_id {
-            Some(id) => id,
-            None => return,
+        // The panic is attributed to the pipeline whose task was running when it happened, if
+        // it is known.
+        let failed_pipeline = pipeline_id.and_then(|id| self.pipelines.get(&id));
+        let failed_event_loop = failed_pipeline.map(|pipeline| pipeline.event_loop.clone());
+        let webview_id = failed_pipeline
+            .map(|pipeline| pipeline.webview_id)
+            .or(webview_id);
+        let url = failed_pipeline
+            .or_else(|| {
+                let browsing_context = self
+                    .browsing_contexts
+                    .get(&BrowsingContextId::from(webview_id?))?;
+                self.pipelines.get(&browsing_context.pipeline_id)
+            })
+            .map(|pipeline| pipeline.url.clone());
+
+        let report = CrashReport {
+            time: SystemTime::now(),
+            webview_id,
+            pipeline_id,
+            url,
+            reason: reason.clone(),
+            backtrace: backtrace.clone(),
         };
+        about::record_crash(report.clone());
+        self.embedder_proxy.send(EmbedderMsg::Panic(report));
 
-        debug!(
-            "{}: Panic handler for top-level browsing context: {}",
-            webview_id, reason
-        );
+        let crash = backtrace
+            .map(|b| format!("{}\n{}", reason, b))
+            .unwrap_or(reason);
 
-        let browsing_context_id = BrowsingContextId::from(webview_id);
+        // A panic while running a task of a pipeline kills the script thread of that pipeline,
+        // along with all of the other pipelines that it hosts. If the pipeline is not known,
+        // the whole webview is replaced by the crash page.
+        if let Some(event_loop) = failed_event_loop {
+            return self.replace_pipelines_of_dead_event_loop(&event_loop, crash);
+        }
+        if let Some(webview_id) = webview_id {
+            self.replace_with_crash_page(webview_id.into(), crash);
+        }
+    }
 
-        self.embedder_proxy.send(EmbedderMsg::Panic(
-            webview_id,
-            reason.clone(),
-            backtrace.clone(),
-        ));
+    /// Replace the documents hosted by an event loop that can't run any more script with
+    /// crash pages, which are loaded in a new event loop.
+    fn replace_pipelines_of_dead_event_loop(&mut self, event_loop: &Rc<EventLoop>, crash: String) {
+        let dead_event_loop = Rc::downgrade(event_loop);
+        for bc_group in self.browsing_context_group_set.values_mut() {
+            bc_group
+                .event_loops
+                .retain(|_, other| !Weak::ptr_eq(other, &dead_event_loop));
+        }
+
+        let hosted_pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| Rc::ptr_eq(&pipeline.event_loop, event_loop))
+            .map(|pipeline| {
+                let is_active = self
+                    .browsing_contexts
+                    .get(&pipeline.browsing_context_id)
+                    .is_some_and(|browsing_context| browsing_context.pipeline_id == pipeline.id);
+                (
+                    pipeline.webview_id,
+                    pipeline.browsing_context_id,
+                    pipeline.id,
+                    is_active,
+                )
+            })
+            .collect();
+
+        // The documents in the session history are loaded again when they are traversed to.
+        for (webview_id, _, pipeline_id, is_active) in &hosted_pipelines {
+            if !is_active {
+                self.handle_discard_document(*webview_id, *pipeline_id);
+            }
+        }
+        for (_, browsing_context_id, pipeline_id, is_active) in hosted_pipelines {
+            // Replacing a document also closes the documents of its descendant browsing
+            // contexts, which don't need to be replaced anymore.
+            if is_active && self.pipelines.contains_key(&pipeline_id) {
+                self.replace_with_crash_page(browsing_context_id, crash.clone());
+            }
+        }
+    }
 
+    /// Replace the document of a browsing context with a crash page that shows `crash`.
+    fn replace_with_crash_page(&mut self, browsing_context_id: BrowsingContextId, crash: String) {
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(context) => context,
             None => return warn!("failed browsing context is missing"),
         };
+        let webview_id = browsing_context.top_level_id;
         let window_size = browsing_context.size;
         let pipeline_id = browsing_context.pipeline_id;
+        let parent_pipeline_id = browsing_context.parent_pipeline_id;
         let throttled = browsing_context.throttled;
 
+        debug!(
+            "{}: Panic handler for browsing context {}: {}",
+            webview_id, browsing_context_id, crash
+        );
+
         let pipeline = match self.pipelines.get(&pipeline_id) {
             Some(p) => p,
             None => return warn!("failed pipeline is missing"),
@@ -2749,11 +3222,7 @@ where
 
         let new_pipeline_id = PipelineId::new();
         let new_load_data = LoadData {
-            crash: Some(
-                backtrace
-                    .map(|b| format!("{}\n{}", reason, b))
-                    .unwrap_or(reason),
-            ),
+            crash: Some(crash),
             ..old_load_data.clone()
         };
 
@@ -2763,7 +3232,7 @@ where
             new_pipeline_id,
             browsing_context_id,
             webview_id,
-            None,
+            parent_pipeline_id,
             opener,
             window_size,
             new_load_data,
@@ -2803,11 +3272,17 @@ where
     fn handle_log_entry(
         &mut self,
         webview_id: Option<WebViewId>,
+        pipeline_id: Option<PipelineId>,
         thread_name: Option<String>,
         entry: LogEntry,
     ) {
         if let LogEntry::Panic(ref reason, ref backtrace) = entry {
-            self.handle_panic(webview_id, reason.clone(), Some(backtrace.clone()));
+            self.handle_panic(
+                webview_id,
+                pipeline_id,
+                reason.clone(),
+                Some(backtrace.clone()),
+            );
         }
 
         match entry {
@@ -2933,6 +3408,23 @@ where
             return;
         };
 
+        // Script may only read the files that the user dropped, so the file manager learns
+        // about them from here, before script does.
+        if let InputEvent::FileDrop(event) = &event {
+            for resource_threads in [
+                &self.public_resource_threads,
+                &self.private_resource_threads,
+            ] {
+                let message =
+                    FileManagerThreadMsg::AllowDroppedFiles(webview_id, event.paths.clone());
+                if let Err(error) =
+                    resource_threads.send(net_traits::CoreResourceMsg::ToFileManager(message))
+                {
+                    warn!("Failed to allow dropped files ({error:?}).");
+                }
+            }
+        }
+
         let event = ConstellationInputEvent {
             hit_test_result,
             pressed_mouse_buttons,
@@ -2981,6 +3473,10 @@ where
             WebView {
                 focused_browsing_context_id: browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                muted: false,
+                emulated_media_features: EmulatedMediaFeatures::default(),
+                visible: true,
+                window_size: self.window_size,
             },
         );
 
@@ -3022,6 +3518,8 @@ where
         if let Some(response_sender) = response_sender {
             self.webdriver.load_channel = Some((pipeline_id, response_sender));
         }
+        self.webdriver
+            .notify_bidi(WebDriverBiDiEvent::ContextCreated(webview_id));
     }
 
     #[cfg_attr(
@@ -3041,6 +3539,8 @@ where
             .send(CompositorMsg::RemoveWebView(webview_id));
         self.embedder_proxy
             .send(EmbedderMsg::WebViewClosed(webview_id));
+        self.webdriver
+            .notify_bidi(WebDriverBiDiEvent::ContextDestroyed(webview_id));
 
         let Some(browsing_context) = browsing_context else {
             return;
@@ -3073,9 +3573,16 @@ where
             type_,
         } in iframe_sizes
         {
+            let Some(webview_id) = self
+                .browsing_contexts
+                .get(&browsing_context_id)
+                .map(|browsing_context| browsing_context.top_level_id)
+            else {
+                continue;
+            };
             let window_size = WindowSizeData {
                 initial_viewport: size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                ..self.webview_window_size(webview_id)
             };
 
             self.resize_browsing_context(window_size, type_, browsing_context_id);
@@ -3145,6 +3652,13 @@ where
             ..
         } = load_info.info;
 
+        if self.is_forbidden_privileged_load(parent_pipeline_id, &load_info.load_data.url) {
+            return warn!(
+                "{}: Refusing to load {} in iframe {}",
+                parent_pipeline_id, load_info.load_data.url, browsing_context_id,
+            );
+        }
+
         // If no url is specified, reload.
         let old_pipeline = load_info
             .old_pipeline_id
@@ -3314,9 +3828,28 @@ where
             load_data,
             opener_webview_id,
             opener_pipeline_id,
+            features,
+            noopener,
             response_sender,
         } = load_info;
 
+        // Documents sandboxed without `allow-popups` may not open auxiliary browsing contexts,
+        // even if a compromised content process asks for one.
+        if self
+            .pipelines
+            .get(&opener_pipeline_id)
+            .is_some_and(|pipeline| {
+                pipeline
+                    .load_data
+                    .sandboxing_flag_set
+                    .contains(SandboxingFlagSet::AUXILIARY_NAVIGATION)
+            })
+        {
+            warn!("{opener_pipeline_id}: Sandboxed document tried to open an auxiliary webview");
+            let _ = response_sender.send(None);
+            return;
+        }
+
         let (webview_id_sender, webview_id_receiver) = match ipc::channel() {
             Ok(result) => result,
             Err(error) => {
@@ -3327,6 +3860,7 @@ where
         };
         self.embedder_proxy.send(EmbedderMsg::AllowOpeningWebView(
             opener_webview_id,
+            features,
             webview_id_sender,
         ));
         let new_webview_id = match webview_id_receiver.recv() {
@@ -3359,11 +3893,14 @@ where
                 },
             };
         let new_pipeline_id = PipelineId::new();
+        // With `noopener` the new document must not be able to reach its opener, not even
+        // after it navigates to a document in another event loop.
+        let opener = (!noopener).then_some(opener_browsing_context_id);
         let pipeline = Pipeline::new(
             new_pipeline_id,
             new_browsing_context_id,
             new_webview_id,
-            Some(opener_browsing_context_id),
+            opener,
             script_sender,
             self.compositor_proxy.clone(),
             is_opener_throttled,
@@ -3381,6 +3918,10 @@ where
             WebView {
                 focused_browsing_context_id: new_browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                muted: false,
+                emulated_media_features: EmulatedMediaFeatures::default(),
+                visible: true,
+                window_size: self.window_size,
             },
         );
 
@@ -3473,6 +4014,16 @@ where
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
     )]
+    /// Whether script in `source_id` is trying to load one of the privileged `about:` pages,
+    /// which only the user or another privileged page may do.
+    fn is_forbidden_privileged_load(&self, source_id: PipelineId, url: &ServoUrl) -> bool {
+        about::is_privileged_url(url) &&
+            !self
+                .pipelines
+                .get(&source_id)
+                .is_some_and(|pipeline| about::is_privileged_url(&pipeline.url))
+    }
+
     fn schedule_navigation(
         &mut self,
         webview_id: WebViewId,
@@ -3480,6 +4031,24 @@ where
         load_data: LoadData,
         history_handling: NavigationHistoryBehavior,
     ) {
+        if self.is_forbidden_privileged_load(source_id, &load_data.url) {
+            return warn!("{}: Refusing to navigate to {}", source_id, load_data.url);
+        }
+
+        // Prerendered documents are not allowed to navigate themselves, so give up on the
+        // prerender instead.
+        if self.prerendered_pipelines.remove(&source_id).is_some() {
+            debug!(
+                "{}: Discarding prerender that attempted to navigate",
+                source_id
+            );
+            return self.close_pipeline(
+                source_id,
+                DiscardBrowsingContext::Yes,
+                ExitPipelineMode::Normal,
+            );
+        }
+
         match self.pending_approval_navigations.entry(source_id) {
             Entry::Occupied(_) => {
                 return warn!(
@@ -3508,7 +4077,7 @@ where
         &mut self,
         webview_id: WebViewId,
         source_id: PipelineId,
-        load_data: LoadData,
+        mut load_data: LoadData,
         history_handling: NavigationHistoryBehavior,
     ) -> Option<PipelineId> {
         debug!(
@@ -3598,7 +4167,11 @@ where
                 // Being here means either there are no pending changes, or none of the pending
                 // changes would be overridden by changing the subframe associated with source_id.
 
-                // Create the new pipeline
+                self.webdriver
+                    .notify_bidi(WebDriverBiDiEvent::NavigationStarted(
+                        webview_id,
+                        load_data.url.clone(),
+                    ));
 
                 let replace = if history_handling == NavigationHistoryBehavior::Replace {
                     Some(NeedsToReload::No(pipeline_id))
@@ -3606,6 +4179,36 @@ where
                     None
                 };
 
+                // A top-level browsing context that was sandboxed when it was opened stays
+                // sandboxed across navigations.
+                if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                    load_data.sandboxing_flag_set = pipeline.load_data.sandboxing_flag_set;
+                }
+
+                // If the navigating document prerendered this URL, use the prerendered pipeline
+                // instead of starting a new load. Any other prerenders are no longer useful.
+                // Prerendered pipelines are unsandboxed, so sandboxed contexts can't use them.
+                let prerendered_pipeline = if load_data.sandboxing_flag_set.is_empty() {
+                    self.take_prerendered_pipeline(source_id, &load_data)
+                } else {
+                    None
+                };
+                self.discard_prerendered_pipelines(source_id);
+                if let Some((new_pipeline_id, prerender)) = prerendered_pipeline {
+                    debug!("{}: Activating prerendered {}", source_id, new_pipeline_id);
+                    self.activate_prerendered_pipeline(
+                        webview_id,
+                        browsing_context_id,
+                        new_pipeline_id,
+                        prerender,
+                        replace,
+                        None,
+                    );
+                    return Some(new_pipeline_id);
+                }
+
+                // Create the new pipeline
+
                 let new_pipeline_id = PipelineId::new();
                 let sandbox = IFrameSandboxState::IFrameUnsandboxed;
                 self.new_pipeline(
@@ -3634,18 +4237,354 @@ where
         }
     }
 
+    /// Start loading a same-origin document in a hidden pipeline, so that a later navigation of
+    /// `source_id` to the same URL can activate it immediately.
+    ///
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#start-user-agent-initiated-prerendering>
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
     )]
-    fn handle_abort_load_url_msg(&mut self, new_pipeline_id: PipelineId) {
-        let pending_index = self
-            .pending_changes
+    fn handle_prerender_msg(
+        &mut self,
+        webview_id: WebViewId,
+        source_id: PipelineId,
+        load_data: LoadData,
+    ) {
+        if self.is_forbidden_privileged_load(source_id, &load_data.url) {
+            return warn!("{}: Refusing to prerender {}", source_id, load_data.url);
+        }
+        let existing_prerenders: Vec<&PrerenderedPipeline> = self
+            .prerendered_pipelines
+            .values()
+            .filter(|prerender| prerender.source_pipeline_id == source_id && !prerender.is_portal)
+            .collect();
+        if existing_prerenders
             .iter()
-            .rposition(|change| change.new_pipeline_id == new_pipeline_id);
-
-        // If it is found, remove it from the pending changes.
-        if let Some(pending_index) = pending_index {
+            .any(|prerender| prerender.url == load_data.url)
+        {
+            return;
+        }
+        if existing_prerenders.len() >= MAX_PRERENDERS_PER_DOCUMENT {
+            return debug!(
+                "{}: Too many prerenders, ignoring {}",
+                source_id, load_data.url
+            );
+        }
+
+        self.start_prerendered_pipeline(webview_id, source_id, PipelineId::new(), load_data, false);
+    }
+
+    /// Start loading `load_data` in a hidden pipeline, which can later replace `source_id` as the
+    /// active document of its top-level browsing context. The documents of `<portal>` elements are
+    /// shown in place by their source, so they are not throttled.
+    ///
+    /// The new pipeline gets a browsing context of its own, which is not part of the session
+    /// history, so that the `WindowProxy` of the source keeps pointing at the source until the
+    /// prerendered document is activated.
+    fn start_prerendered_pipeline(
+        &mut self,
+        webview_id: WebViewId,
+        source_id: PipelineId,
+        new_pipeline_id: PipelineId,
+        mut load_data: LoadData,
+        is_portal: bool,
+    ) {
+        if self.prerendered_pipelines.contains_key(&source_id) {
+            return warn!("{}: Prerendered documents cannot prerender", source_id);
+        }
+        if self.pipelines.contains_key(&new_pipeline_id) {
+            return warn!("{}: Prerendered pipeline already exists", new_pipeline_id);
+        }
+        let browsing_context_id = match self.pipelines.get(&source_id) {
+            Some(pipeline) => pipeline.browsing_context_id,
+            None => return warn!("{}: Prerender requested after closure", source_id),
+        };
+        let (window_size, is_private) = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(ctx) if ctx.parent_pipeline_id.is_none() => (ctx.size, ctx.is_private),
+            Some(_) => {
+                return warn!(
+                    "{}: Prerendering is only supported in top-level browsing contexts",
+                    source_id
+                );
+            },
+            None => return warn!("{}: Prerender requested in closed context", source_id),
+        };
+        if self.get_activity(source_id) != DocumentActivity::FullyActive {
+            return;
+        }
+
+        let url = load_data.url.clone();
+        load_data.is_prerendering = true;
+        self.new_pipeline(
+            new_pipeline_id,
+            BrowsingContextId::new(),
+            webview_id,
+            None,
+            None,
+            window_size,
+            load_data,
+            IFrameSandboxState::IFrameUnsandboxed,
+            is_private,
+            // Prerendered pipelines are hidden until they are activated.
+            !is_portal,
+        );
+        self.prerendered_pipelines.insert(
+            new_pipeline_id,
+            PrerenderedPipeline {
+                source_pipeline_id: source_id,
+                url,
+                ready_to_activate: false,
+                deferred_embedder_messages: Vec::new(),
+                is_portal,
+            },
+        );
+    }
+
+    /// Make a prerendered pipeline the active document of `browsing_context_id`, releasing the
+    /// messages it sent to the embedder while it was hidden. The pipeline leaves its hidden
+    /// browsing context, and its document takes over the `WindowProxy` of the new one.
+    fn activate_prerendered_pipeline(
+        &mut self,
+        webview_id: WebViewId,
+        browsing_context_id: BrowsingContextId,
+        new_pipeline_id: PipelineId,
+        prerender: PrerenderedPipeline,
+        replace: Option<NeedsToReload>,
+        portal_activation_data: Option<StructuredSerializedData>,
+    ) {
+        let Some(ctx) = self.browsing_contexts.get(&browsing_context_id) else {
+            return warn!(
+                "{}: Activating prerender in closed context",
+                new_pipeline_id
+            );
+        };
+        let (window_size, is_throttled) = (ctx.size, ctx.throttled);
+        if let Some(pipeline) = self.pipelines.get_mut(&new_pipeline_id) {
+            pipeline.browsing_context_id = browsing_context_id;
+            // A document that is reloaded from the session history is no longer in a portal,
+            // nor hidden.
+            pipeline.load_data.is_portal = false;
+            pipeline.load_data.is_prerendering = false;
+            let msg = ScriptThreadMessage::ActivatePrerenderedDocument(
+                new_pipeline_id,
+                browsing_context_id,
+                portal_activation_data,
+            );
+            if let Err(err) = pipeline.event_loop.send(msg) {
+                warn!("{new_pipeline_id}: Failed to activate prerender ({err:?}).");
+            }
+            pipeline.set_throttled(is_throttled);
+        }
+        self.add_pending_change(SessionHistoryChange {
+            webview_id,
+            browsing_context_id,
+            new_pipeline_id,
+            replace,
+            new_browsing_context_info: None,
+            window_size,
+        });
+        if prerender.ready_to_activate {
+            self.handle_activate_document_msg(new_pipeline_id);
+        }
+        for embedder_msg in prerender.deferred_embedder_messages {
+            self.embedder_proxy.send(embedder_msg);
+        }
+    }
+
+    /// Make the document of a `<portal>` element of `source_id` the active document of the
+    /// top-level browsing context, as if `source_id` had navigated to it. `data` is given to the
+    /// `portalactivate` event of the activated document. Returns whether the activation
+    /// started.
+    ///
+    /// <https://wicg.github.io/portals/#activate-a-portal-browsing-context>
+    fn handle_activate_portal_msg(
+        &mut self,
+        webview_id: WebViewId,
+        source_id: PipelineId,
+        portal_pipeline_id: PipelineId,
+        data: StructuredSerializedData,
+    ) -> bool {
+        if !self.is_portal_of(portal_pipeline_id, source_id) {
+            warn!(
+                "{}: Activating unknown portal {}",
+                source_id, portal_pipeline_id
+            );
+            return false;
+        }
+        let Some(browsing_context_id) = self
+            .pipelines
+            .get(&source_id)
+            .map(|pipeline| pipeline.browsing_context_id)
+        else {
+            warn!("{}: Portal activated after closure", source_id);
+            return false;
+        };
+        if self.get_activity(source_id) != DocumentActivity::FullyActive ||
+            self.pending_changes
+                .iter()
+                .any(|change| change.browsing_context_id == browsing_context_id)
+        {
+            debug!(
+                "{}: Ignoring portal activation during navigation",
+                source_id
+            );
+            return false;
+        }
+
+        let Some(prerender) = self.prerendered_pipelines.remove(&portal_pipeline_id) else {
+            return false;
+        };
+        debug!("{}: Activating portal {}", source_id, portal_pipeline_id);
+        self.discard_prerendered_pipelines(source_id);
+        self.activate_prerendered_pipeline(
+            webview_id,
+            browsing_context_id,
+            portal_pipeline_id,
+            prerender,
+            None,
+            Some(data),
+        );
+        true
+    }
+
+    /// Post a message from `source_id` to the document of one of its `<portal>` elements.
+    fn handle_post_message_to_portal_msg(
+        &mut self,
+        source_id: PipelineId,
+        portal_pipeline_id: PipelineId,
+        source_origin: ImmutableOrigin,
+        data: StructuredSerializedData,
+    ) {
+        if !self.is_portal_of(portal_pipeline_id, source_id) {
+            return warn!(
+                "{}: PostMessage to unknown portal {}",
+                source_id, portal_pipeline_id
+            );
+        }
+        self.post_portal_message(portal_pipeline_id, portal_pipeline_id, source_origin, data);
+    }
+
+    /// Post a message from the document of a `<portal>` element to the document that hosts it.
+    fn handle_post_message_to_portal_host_msg(
+        &mut self,
+        portal_pipeline_id: PipelineId,
+        source_origin: ImmutableOrigin,
+        data: StructuredSerializedData,
+    ) {
+        let Some(host_pipeline_id) = self
+            .prerendered_pipelines
+            .get(&portal_pipeline_id)
+            .filter(|prerender| prerender.is_portal)
+            .map(|prerender| prerender.source_pipeline_id)
+        else {
+            return warn!("{}: PostMessage to missing portal host", portal_pipeline_id);
+        };
+        self.post_portal_message(host_pipeline_id, portal_pipeline_id, source_origin, data);
+    }
+
+    /// Send a message between the document of a `<portal>` element, `portal_pipeline_id`, and
+    /// the document that hosts it. Either one can be `target_pipeline_id`.
+    fn post_portal_message(
+        &mut self,
+        target_pipeline_id: PipelineId,
+        portal_pipeline_id: PipelineId,
+        source_origin: ImmutableOrigin,
+        data: StructuredSerializedData,
+    ) {
+        let msg = ScriptThreadMessage::PostPortalMessage {
+            target: target_pipeline_id,
+            portal: portal_pipeline_id,
+            source_origin,
+            data,
+        };
+        let result = match self.pipelines.get(&target_pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return warn!("{}: PostMessage to closed pipeline", target_pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(target_pipeline_id, e);
+        }
+    }
+
+    /// Whether `portal_pipeline_id` hosts the document of a `<portal>` element of `source_id`.
+    fn is_portal_of(&self, portal_pipeline_id: PipelineId, source_id: PipelineId) -> bool {
+        self.prerendered_pipelines
+            .get(&portal_pipeline_id)
+            .is_some_and(|prerender| {
+                prerender.is_portal && prerender.source_pipeline_id == source_id
+            })
+    }
+
+    /// Close the document of a `<portal>` element of `source_id`.
+    fn handle_discard_portal_msg(&mut self, source_id: PipelineId, portal_pipeline_id: PipelineId) {
+        if !self.is_portal_of(portal_pipeline_id, source_id) {
+            return;
+        }
+        self.prerendered_pipelines.remove(&portal_pipeline_id);
+        self.close_pipeline(
+            portal_pipeline_id,
+            DiscardBrowsingContext::Yes,
+            ExitPipelineMode::Normal,
+        );
+    }
+
+    /// Remove and return the prerendered pipeline started by `source_id` that can be used for
+    /// `load_data`.
+    fn take_prerendered_pipeline(
+        &mut self,
+        source_id: PipelineId,
+        load_data: &LoadData,
+    ) -> Option<(PipelineId, PrerenderedPipeline)> {
+        // Only simple GET navigations can reuse a prerendered document.
+        if load_data.method.as_str() != "GET" || load_data.data.is_some() {
+            return None;
+        }
+        let prerendered_pipeline_id =
+            self.prerendered_pipelines
+                .iter()
+                .find_map(|(pipeline_id, prerender)| {
+                    (prerender.source_pipeline_id == source_id &&
+                        !prerender.is_portal &&
+                        prerender.url == load_data.url)
+                        .then_some(*pipeline_id)
+                })?;
+        let prerender = self
+            .prerendered_pipelines
+            .remove(&prerendered_pipeline_id)?;
+        Some((prerendered_pipeline_id, prerender))
+    }
+
+    /// Close every prerendered pipeline started by `source_id`.
+    fn discard_prerendered_pipelines(&mut self, source_id: PipelineId) {
+        let prerendered_pipeline_ids: Vec<PipelineId> = self
+            .prerendered_pipelines
+            .iter()
+            .filter(|(_, prerender)| prerender.source_pipeline_id == source_id)
+            .map(|(pipeline_id, _)| *pipeline_id)
+            .collect();
+        for pipeline_id in prerendered_pipeline_ids {
+            self.prerendered_pipelines.remove(&pipeline_id);
+            self.close_pipeline(
+                pipeline_id,
+                DiscardBrowsingContext::Yes,
+                ExitPipelineMode::Normal,
+            );
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_abort_load_url_msg(&mut self, new_pipeline_id: PipelineId) {
+        let pending_index = self
+            .pending_changes
+            .iter()
+            .rposition(|change| change.new_pipeline_id == new_pipeline_id);
+
+        // If it is found, remove it from the pending changes.
+        if let Some(pending_index) = pending_index {
             self.pending_changes.remove(pending_index);
             self.close_pipeline(
                 new_pipeline_id,
@@ -3697,12 +4636,36 @@ where
                 // Notify embedder and compositor top level document finished loading.
                 self.compositor_proxy
                     .send(CompositorMsg::LoadComplete(webview_id));
+                if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                    let url = pipeline.url.clone();
+                    self.webdriver
+                        .notify_bidi(WebDriverBiDiEvent::Load(webview_id, url));
+                }
             }
         } else {
             self.handle_subframe_loaded(pipeline_id);
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_dom_content_loaded_msg(&mut self, webview_id: WebViewId, pipeline_id: PipelineId) {
+        let is_top_level_pipeline = self
+            .browsing_contexts
+            .get(&BrowsingContextId::from(webview_id))
+            .is_some_and(|ctx| ctx.pipeline_id == pipeline_id);
+        if !is_top_level_pipeline {
+            return;
+        }
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let url = pipeline.url.clone();
+            self.webdriver
+                .notify_bidi(WebDriverBiDiEvent::DomContentLoaded(webview_id, url));
+        }
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
@@ -4028,10 +4991,16 @@ where
                 return warn!("{}: History state updated after closure", pipeline_id);
             },
             Some(pipeline) => {
+                let entry_state = pipeline
+                    .session_history_entry_states
+                    .get(&history_state_id)
+                    .copied()
+                    .unwrap_or_default();
                 let msg = ScriptThreadMessage::UpdateHistoryState(
                     pipeline_id,
                     history_state_id,
                     url.clone(),
+                    entry_state,
                 );
                 pipeline.history_state_id = history_state_id;
                 pipeline.url = url;
@@ -4352,8 +5321,11 @@ where
                     Ok(result) => result,
                     Err(error) => return warn!("Failed to create channel: {error:?}"),
                 };
-                self.embedder_proxy
-                    .send(EmbedderMsg::AllowOpeningWebView(webview_id, chan));
+                self.embedder_proxy.send(EmbedderMsg::AllowOpeningWebView(
+                    webview_id,
+                    AuxiliaryWebViewFeatures::default(),
+                    chan,
+                ));
                 let webview_id = match port.recv() {
                     Ok(Some(webview_id)) => webview_id,
                     Ok(None) => return warn!("Embedder refused to allow opening webview"),
@@ -4369,6 +5341,15 @@ where
             WebDriverCommandMsg::FocusWebView(webview_id) => {
                 self.handle_focus_web_view(webview_id);
             },
+            WebDriverCommandMsg::SetBiDiEventSender(sender) => {
+                self.webdriver.bidi_event_sender = sender;
+            },
+            WebDriverCommandMsg::SetNetworkInterceptor(sender) => {
+                self.public_resource_threads
+                    .set_webdriver_interceptor(sender.clone());
+                self.private_resource_threads
+                    .set_webdriver_interceptor(sender);
+            },
             WebDriverCommandMsg::GetWindowSize(_, response_sender) => {
                 let _ = response_sender.send(self.window_size.initial_viewport);
             },
@@ -4505,10 +5486,23 @@ where
                 self.compositor_proxy
                     .send(CompositorMsg::WebDriverMouseMoveEvent(webview_id, x, y));
             },
+            WebDriverCommandMsg::WheelScrollAction(webview_id, x, y, delta_x, delta_y) => {
+                self.compositor_proxy
+                    .send(CompositorMsg::WebDriverWheelScrollEvent(
+                        webview_id, x, y, delta_x, delta_y,
+                    ));
+            },
             WebDriverCommandMsg::TakeScreenshot(_, rect, response_sender) => {
                 self.compositor_proxy
                     .send(CompositorMsg::CreatePng(rect, response_sender));
             },
+            WebDriverCommandMsg::TakePageScreenshot(webview_id, rect, response_sender) => {
+                self.compositor_proxy.send(CompositorMsg::CreatePagePng(
+                    webview_id,
+                    rect,
+                    response_sender,
+                ));
+            },
         }
     }
 
@@ -4530,6 +5524,254 @@ where
         }
     }
 
+    /// Forward a find-in-page request to the top-level document of a `WebView`. The documents
+    /// of nested browsing contexts are not searched.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_find_in_page(&mut self, webview_id: WebViewId, request: FindInPageRequest) {
+        let browsing_context_id = BrowsingContextId::from(webview_id);
+        let Some(pipeline_id) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map(|browsing_context| browsing_context.pipeline_id)
+        else {
+            return warn!("{browsing_context_id}: Tried to FindInPage after closure");
+        };
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            return warn!("{pipeline_id}: Tried to FindInPage after closure");
+        };
+        let msg = ScriptThreadMessage::FindInPage(pipeline_id, request);
+        if let Err(err) = pipeline.event_loop.send(msg) {
+            warn!("{pipeline_id}: Failed to send find-in-page request ({err:?}).");
+        }
+    }
+
+    /// Forward an autofill request to the top-level document of a `WebView`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_autofill(&mut self, webview_id: WebViewId, request: AutofillRequest) {
+        let browsing_context_id = BrowsingContextId::from(webview_id);
+        let Some(pipeline_id) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map(|browsing_context| browsing_context.pipeline_id)
+        else {
+            return warn!("{browsing_context_id}: Tried to Autofill after closure");
+        };
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            return warn!("{pipeline_id}: Tried to Autofill after closure");
+        };
+        let msg = ScriptThreadMessage::Autofill(pipeline_id, request);
+        if let Err(err) = pipeline.event_loop.send(msg) {
+            warn!("{pipeline_id}: Failed to send autofill request ({err:?}).");
+        }
+    }
+
+    /// Forward a request to control the animation clock to the top-level document of a
+    /// `WebView`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_control_animation_clock(
+        &mut self,
+        webview_id: WebViewId,
+        request: AnimationClockRequest,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(webview_id);
+        let Some(pipeline_id) = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map(|browsing_context| browsing_context.pipeline_id)
+        else {
+            return warn!("{browsing_context_id}: Tried to ControlAnimationClock after closure");
+        };
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            return warn!("{pipeline_id}: Tried to ControlAnimationClock after closure");
+        };
+        let msg = ScriptThreadMessage::ControlAnimationClock(pipeline_id, request);
+        if let Err(err) = pipeline.event_loop.send(msg) {
+            warn!("{pipeline_id}: Failed to send animation clock request ({err:?}).");
+        }
+    }
+
+    /// Forward a change in the visibility of a `WebView` to every event loop that hosts one
+    /// of its pipelines, including those of nested browsing contexts.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn set_webview_visibility(&mut self, webview_id: WebViewId, visible: bool) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return warn!("{webview_id}: Tried to SetWebViewVisibility after closure");
+        };
+        if webview.visible == visible {
+            return;
+        }
+        webview.visible = visible;
+
+        for event_loop in self.event_loops_hosting_webview(webview_id) {
+            let msg = ScriptThreadMessage::SetWebViewVisibility(webview_id, visible);
+            if let Err(err) = event_loop.send(msg) {
+                warn!("{webview_id}: Failed to send webview visibility ({err:?}).");
+            }
+        }
+    }
+
+    /// Replace the user content that new pipelines are created with, and let every script
+    /// thread know about it for the documents that they load from now on.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn set_user_content_manager(&mut self, user_content_manager: UserContentManager) {
+        for event_loop in self.event_loops_hosting_pipelines() {
+            let msg = ScriptThreadMessage::SetUserContentManager(user_content_manager.clone());
+            if let Err(err) = event_loop.send(msg) {
+                warn!("Failed to send user content to script thread ({err:?}).");
+            }
+        }
+        self.user_content_manager = user_content_manager;
+    }
+
+    /// Let every script thread know about preferences that the embedder changed at runtime,
+    /// so that content processes can update their copy of them.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_preferences_updated(
+        &mut self,
+        preferences: Box<Preferences>,
+        site_preferences: SitePreferences,
+    ) {
+        for event_loop in self.event_loops_hosting_pipelines() {
+            let msg = ScriptThreadMessage::PreferencesUpdated(
+                preferences.clone(),
+                site_preferences.clone(),
+            );
+            if let Err(err) = event_loop.send(msg) {
+                warn!("Failed to send preferences to script thread ({err:?}).");
+            }
+        }
+    }
+
+    /// Change a preference on behalf of `about:config`, and let every script thread know.
+    /// `value` is parsed according to the type of the preference's current value.
+    fn handle_set_preference_msg(&mut self, source_id: PipelineId, name: String, value: String) {
+        if !self
+            .pipelines
+            .get(&source_id)
+            .is_some_and(|pipeline| about::is_privileged_url(&pipeline.url))
+        {
+            return warn!("{}: Refusing to set preference {name}", source_id);
+        }
+
+        let mut preferences = prefs::get().clone();
+        let Some((_, current_value)) = preferences
+            .values()
+            .into_iter()
+            .find(|(pref_name, _)| *pref_name == name)
+        else {
+            return warn!("Tried to set unknown preference {name}");
+        };
+        let new_value = match current_value {
+            PrefValue::Bool(_) => value.parse().ok().map(PrefValue::Bool),
+            PrefValue::Int(_) => value.parse().ok().map(PrefValue::Int),
+            PrefValue::Float(_) => value.parse().ok().map(PrefValue::Float),
+            PrefValue::Str(_) => Some(PrefValue::Str(value.clone())),
+            PrefValue::Array(_) => None,
+        };
+        let Some(new_value) = new_value else {
+            return warn!("Invalid value {value:?} for preference {name}");
+        };
+
+        preferences.set_value(&name, new_value);
+        prefs::set(preferences.clone());
+        self.handle_preferences_updated(Box::new(preferences), prefs::site_preferences());
+    }
+
+    /// The distinct event loops that host at least one pipeline.
+    fn event_loops_hosting_pipelines(&self) -> Vec<Rc<EventLoop>> {
+        let mut event_loops: Vec<Rc<EventLoop>> = Vec::new();
+        for pipeline in self.pipelines.values() {
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        event_loops
+    }
+
+    /// Mute or unmute the audio of a `WebView`, in every event loop that hosts one of its
+    /// pipelines.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn set_webview_muted(&mut self, webview_id: WebViewId, muted: bool) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return warn!("{webview_id}: Tried to SetWebViewMuted after closure");
+        };
+        webview.muted = muted;
+
+        for event_loop in self.event_loops_hosting_webview(webview_id) {
+            let msg = ScriptThreadMessage::SetWebViewMuted(webview_id, muted);
+            if let Err(err) = event_loop.send(msg) {
+                warn!("{webview_id}: Failed to send webview muting ({err:?}).");
+            }
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn set_emulated_media_features(
+        &mut self,
+        webview_id: WebViewId,
+        features: EmulatedMediaFeatures,
+    ) {
+        let Some(webview) = self.webviews.get_mut(webview_id) else {
+            return warn!("{webview_id}: Tried to SetEmulatedMediaFeatures after closure");
+        };
+        if webview.emulated_media_features == features {
+            return;
+        }
+        webview.emulated_media_features = features;
+
+        for event_loop in self.event_loops_hosting_webview(webview_id) {
+            let msg = ScriptThreadMessage::SetEmulatedMediaFeatures(webview_id, features);
+            if let Err(err) = event_loop.send(msg) {
+                warn!("{webview_id}: Failed to send emulated media features ({err:?}).");
+            }
+        }
+    }
+
+    /// The distinct event loops that host at least one pipeline of the given webview.
+    fn event_loops_hosting_webview(&self, webview_id: WebViewId) -> Vec<Rc<EventLoop>> {
+        let mut event_loops: Vec<Rc<EventLoop>> = Vec::new();
+        for pipeline in self
+            .pipelines
+            .values()
+            .filter(|pipeline| pipeline.webview_id == webview_id)
+        {
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        event_loops
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
@@ -4804,15 +6046,21 @@ where
 
                 if let Some(states_to_close) = states_to_close {
                     for (pipeline_id, states) in states_to_close {
-                        let msg = ScriptThreadMessage::RemoveHistoryStates(pipeline_id, states);
-                        let result = match self.pipelines.get(&pipeline_id) {
+                        let result = match self.pipelines.get_mut(&pipeline_id) {
                             None => {
                                 return warn!(
                                     "{}: Removed history states after closure",
                                     pipeline_id
                                 );
                             },
-                            Some(pipeline) => pipeline.event_loop.send(msg),
+                            Some(pipeline) => {
+                                for state in &states {
+                                    pipeline.session_history_entry_states.remove(&Some(*state));
+                                }
+                                let msg =
+                                    ScriptThreadMessage::RemoveHistoryStates(pipeline_id, states);
+                                pipeline.event_loop.send(msg)
+                            },
                         };
                         if let Err(e) = result {
                             self.handle_send_error(pipeline_id, e);
@@ -4902,6 +6150,8 @@ where
                 Some(pipeline) => {
                     let mut load_data = pipeline.load_data.clone();
                     load_data.url = pipeline.url.clone();
+                    load_data.session_history_entry_state =
+                        pipeline.current_session_history_entry_state();
                     load_data
                 },
                 None => continue,
@@ -4967,6 +6217,9 @@ where
                 }
             }
             self.change_session_history(change);
+        } else if let Some(prerender) = self.prerendered_pipelines.get_mut(&pipeline_id) {
+            // A prerendered document is only activated once its source navigates to it.
+            prerender.ready_to_activate = true;
         }
     }
 
@@ -4993,9 +6246,19 @@ where
             let _ = response_sender.send(new_size.initial_viewport);
         }
 
+        if let Some(webview) = self.webviews.get_mut(webview_id) {
+            webview.window_size = new_size;
+        }
         self.window_size = new_size;
     }
 
+    /// The size and zoom factors that new pipelines in the given webview start out with.
+    fn webview_window_size(&self, webview_id: WebViewId) -> WindowSizeData {
+        self.webviews
+            .get(webview_id)
+            .map_or(self.window_size, |webview| webview.window_size)
+    }
+
     /// Called when the window exits from fullscreen mode
     #[cfg_attr(
         feature = "tracing",
@@ -5218,6 +6481,7 @@ where
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
     )]
     fn handle_theme_change(&mut self, theme: Theme) {
+        self.theme = theme;
         for pipeline in self.pipelines.values() {
             let msg = ScriptThreadMessage::ThemeChange(pipeline.id, theme);
             if let Err(err) = pipeline.event_loop.send(msg) {
@@ -5229,6 +6493,47 @@ where
         }
     }
 
+    /// Handle changes to the network from the embedder and forward them to the resource
+    /// threads and to all script threads.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_network_status_changed(&mut self, network_status: NetworkStatus) {
+        self.network_status = network_status;
+        self.public_resource_threads
+            .set_online(network_status.online);
+        self.private_resource_threads
+            .set_online(network_status.online);
+        for pipeline in self.pipelines.values() {
+            let msg = ScriptThreadMessage::NetworkStatusChanged(pipeline.id, network_status);
+            if let Err(err) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to send network status change to pipeline ({:?}).",
+                    pipeline.id, err
+                );
+            }
+        }
+    }
+
+    /// Handle changes to the screen topology from the embedder and forward them to all
+    /// script threads.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_screens_changed(&mut self) {
+        for pipeline in self.pipelines.values() {
+            let msg = ScriptThreadMessage::ScreensChanged(pipeline.id);
+            if let Err(err) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to send screens changed event to pipeline ({:?}).",
+                    pipeline.id, err
+                );
+            }
+        }
+    }
+
     // Handle switching from fullscreen mode
     #[cfg_attr(
         feature = "tracing",
@@ -5338,7 +6643,10 @@ where
         match self.webviews.get_mut(webview_id) {
             Some(webview) => {
                 let load_data = match self.pipelines.get(&pipeline_id) {
-                    Some(pipeline) => pipeline.load_data.clone(),
+                    Some(pipeline) => LoadData {
+                        session_history_entry_state: pipeline.current_session_history_entry_state(),
+                        ..pipeline.load_data.clone()
+                    },
                     None => return warn!("{}: Discarding closed pipeline", pipeline_id),
                 };
                 webview.session_history.replace_reloader(
@@ -5415,6 +6723,11 @@ where
             self.close_browsing_context(*child_browsing_context, exit_mode);
         }
 
+        // Discard any prerenders this pipeline started, and forget about it if it was itself
+        // being prerendered.
+        self.prerendered_pipelines.remove(&pipeline_id);
+        self.discard_prerendered_pipelines(pipeline_id);
+
         // Note, we don't remove the pipeline now, we wait for the message to come back from
         // the pipeline.
         let pipeline = match self.pipelines.get(&pipeline_id) {
@@ -5589,6 +6902,30 @@ where
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn handle_set_visual_viewport(
+        &self,
+        pipeline_id: PipelineId,
+        visual_viewport: VisualViewportState,
+    ) {
+        let Some(pipeline) = self.pipelines.get(&pipeline_id) else {
+            warn!("Discarding visual viewport update for unknown pipeline");
+            return;
+        };
+        if let Err(error) = pipeline
+            .event_loop
+            .send(ScriptThreadMessage::SetVisualViewport(
+                pipeline_id,
+                visual_viewport,
+            ))
+        {
+            warn!("Could not send visual viewport to pipeline: {pipeline_id:?}: {error:?}");
+        }
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
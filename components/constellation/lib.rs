@@ -11,6 +11,7 @@ mod browsingcontext;
 mod constellation;
 mod event_loop;
 mod logging;
+mod memory_report;
 mod pipeline;
 mod sandboxing;
 mod serviceworker;
@@ -11,7 +11,7 @@ use std::sync::Arc;
 use std::thread;
 
 use backtrace::Backtrace;
-use base::id::WebViewId;
+use base::id::{PipelineId, WebViewId};
 use constellation_traits::{ConstellationMsg as FromCompositorMsg, LogEntry};
 use crossbeam_channel::Sender;
 use log::{Level, LevelFilter, Log, Metadata, Record};
@@ -50,7 +50,7 @@ impl Log for FromScriptLogger {
     fn log(&self, record: &Record) {
         if let Some(entry) = log_entry(record) {
             let thread_name = thread::current().name().map(ToOwned::to_owned);
-            let msg = FromScriptMsg::LogEntry(thread_name, entry);
+            let msg = FromScriptMsg::LogEntry(PipelineId::installed(), thread_name, entry);
             let chan = self.script_to_constellation_chan.lock();
             let _ = chan.send(msg);
         }
@@ -89,7 +89,12 @@ impl Log for FromCompositorLogger {
         if let Some(entry) = log_entry(record) {
             let top_level_id = WebViewId::installed();
             let thread_name = thread::current().name().map(ToOwned::to_owned);
-            let msg = FromCompositorMsg::LogEntry(top_level_id, thread_name, entry);
+            let msg = FromCompositorMsg::LogEntry(
+                top_level_id,
+                PipelineId::installed(),
+                thread_name,
+                entry,
+            );
             let chan = self.constellation_chan.lock();
             let _ = chan.send(msg);
         }
@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Turn the reports of the memory profiler into the per-origin and per-pipeline memory usage
+//! that is given to the embedder.
+
+use std::collections::BTreeMap;
+
+use base::id::{PipelineId, WebViewId};
+use embedder_traits::{
+    MemoryReport, OriginMemoryUsage, PipelineMemoryUsage, SharedJavaScriptMemoryUsage,
+};
+use profile_traits::mem::{PAGES_PATH_SEGMENT, Report, ReportAttribution, ReportKind};
+use servo_url::ServoUrl;
+
+/// Sum the memory `reports` by origin and by pipeline. `pipelines` are the pipelines known
+/// to the constellation, which are used to find the webview of each document.
+pub(crate) fn memory_report(
+    reports: Vec<Report>,
+    pipelines: &[(WebViewId, PipelineId)],
+) -> MemoryReport {
+    let mut memory_report = MemoryReport::default();
+    let mut origins: BTreeMap<String, OriginMemoryUsage> = BTreeMap::new();
+    let mut shared_javascript: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+    let mut documents: BTreeMap<PipelineId, (String, PipelineMemoryUsage)> = BTreeMap::new();
+
+    for report in reports {
+        if let ReportKind::NonExplicitSize = report.kind {
+            if report.path == ["resident"] {
+                *memory_report.resident.get_or_insert(0) += report.size;
+            }
+            continue;
+        }
+
+        match report.attribution {
+            None => memory_report.unattributed += report.size,
+            // Everything that isn't measured for a single document, like the JavaScript
+            // heaps of event loops and workers, is attributed to its origins as a whole.
+            Some(ReportAttribution::Origins(report_origins)) => match report_origins.as_slice() {
+                [] => memory_report.unattributed += report.size,
+                [origin] => origin_usage(&mut origins, origin).javascript += report.size,
+                _ => *shared_javascript.entry(report_origins).or_default() += report.size,
+            },
+            Some(ReportAttribution::Pipeline {
+                origin,
+                pipeline_id,
+                url,
+            }) => {
+                origin_usage(&mut origins, &origin);
+                let (_, document_usage) = documents.entry(pipeline_id).or_insert_with(|| {
+                    let webview_id = pipelines
+                        .iter()
+                        .find(|(_, id)| *id == pipeline_id)
+                        .map(|(webview_id, _)| *webview_id);
+                    let document_usage = PipelineMemoryUsage {
+                        // The pipeline might have been closed since the report was requested.
+                        webview_id,
+                        pipeline_id: Some(pipeline_id),
+                        url: ServoUrl::parse(&url).ok(),
+                        ..Default::default()
+                    };
+                    (origin, document_usage)
+                });
+                match document_path(&report.path).as_slice() {
+                    ["dom", "computed-styles", ..] => document_usage.styles += report.size,
+                    ["dom", ..] => document_usage.dom += report.size,
+                    ["images", ..] => document_usage.images += report.size,
+                    _ => document_usage.layout += report.size,
+                }
+            },
+        }
+    }
+
+    for (origin, document_usage) in documents.into_values() {
+        if let Some(origin_usage) = origins.get_mut(&origin) {
+            origin_usage.pipelines.push(document_usage);
+        }
+    }
+    memory_report.origins = origins.into_values().collect();
+    memory_report.shared_javascript = shared_javascript
+        .into_iter()
+        .map(|(origins, size)| SharedJavaScriptMemoryUsage { origins, size })
+        .collect();
+    memory_report
+}
+
+fn origin_usage<'a>(
+    origins: &'a mut BTreeMap<String, OriginMemoryUsage>,
+    origin: &str,
+) -> &'a mut OriginMemoryUsage {
+    origins
+        .entry(origin.to_owned())
+        .or_insert_with(|| OriginMemoryUsage {
+            origin: origin.to_owned(),
+            ..Default::default()
+        })
+}
+
+/// The segments of the path of a report about a document that follow the segments that
+/// identify the document.
+fn document_path(path: &[String]) -> Vec<&str> {
+    match path {
+        [first, _origin, _pipeline, rest @ ..] if first == PAGES_PATH_SEGMENT => {
+            rest.iter().map(String::as_str).collect()
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use base::id::{PipelineId, PipelineNamespace, PipelineNamespaceId, WebViewId};
+    use profile_traits::mem::{Report, ReportAttribution, ReportKind};
+    use profile_traits::path;
+
+    use crate::memory_report::memory_report;
+
+    fn report(attribution: Option<&ReportAttribution>, suffix: Vec<String>, size: usize) -> Report {
+        let mut path = attribution.map(ReportAttribution::path).unwrap_or_default();
+        path.extend(suffix);
+        Report {
+            path,
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size,
+            attribution: attribution.cloned(),
+        }
+    }
+
+    #[test]
+    fn test_memory_report_by_origin() {
+        let single_origin = ReportAttribution::Origins(vec!["https://a.example.com".to_owned()]);
+        let shared_origins = ReportAttribution::Origins(vec![
+            "https://a.example.com".to_owned(),
+            "https://b.example.com".to_owned(),
+        ]);
+        let reports = vec![
+            report(Some(&single_origin), path!["js", "gc-heap", "used"], 1),
+            report(Some(&single_origin), path!["js", "malloc-heap"], 2),
+            report(Some(&shared_origins), path!["js", "gc-heap", "used"], 4),
+            report(None, path!["memory-cache", "public"], 8),
+            Report {
+                path: path!["resident"],
+                kind: ReportKind::NonExplicitSize,
+                size: 16,
+                attribution: None,
+            },
+        ];
+
+        let memory_report = memory_report(reports, &[]);
+        assert_eq!(memory_report.origins.len(), 1);
+        assert_eq!(memory_report.origins[0].origin, "https://a.example.com");
+        assert_eq!(memory_report.origins[0].javascript, 3);
+        // A heap shared by several origins is not attributed to a made-up origin.
+        assert_eq!(memory_report.shared_javascript.len(), 1);
+        assert_eq!(
+            memory_report.shared_javascript[0].origins,
+            ["https://a.example.com", "https://b.example.com"]
+        );
+        assert_eq!(memory_report.shared_javascript[0].size, 4);
+        assert_eq!(memory_report.unattributed, 8);
+        assert_eq!(memory_report.resident, Some(16));
+    }
+
+    #[test]
+    fn test_memory_report_by_pipeline() {
+        PipelineNamespace::install(PipelineNamespaceId(0));
+        let webview_id = WebViewId::new();
+        let pipeline_id = PipelineId::new();
+        let closed_pipeline_id = PipelineId::new();
+        let document = ReportAttribution::Pipeline {
+            origin: "https://example.com".to_owned(),
+            pipeline_id,
+            url: "https://example.com/index.html".to_owned(),
+        };
+        let closed_document = ReportAttribution::Pipeline {
+            origin: "https://example.com".to_owned(),
+            pipeline_id: closed_pipeline_id,
+            url: "https://example.com/closed.html".to_owned(),
+        };
+        let reports = vec![
+            report(Some(&document), path!["dom", "nodes"], 1),
+            report(Some(&document), path!["dom", "computed-styles"], 2),
+            report(Some(&document), path!["images"], 4),
+            report(Some(&document), path!["layout", "fragment-tree"], 8),
+            report(Some(&document), path!["layout", "stylist"], 16),
+            report(Some(&closed_document), path!["dom", "nodes"], 32),
+        ];
+
+        let memory_report = memory_report(reports, &[(webview_id, pipeline_id)]);
+        assert_eq!(memory_report.origins.len(), 1);
+        let origin = &memory_report.origins[0];
+        assert_eq!(origin.javascript, 0);
+        assert_eq!(origin.total(), 63);
+        assert_eq!(origin.pipelines.len(), 2);
+
+        let document = &origin.pipelines[0];
+        assert_eq!(document.webview_id, Some(webview_id));
+        assert_eq!(document.pipeline_id, Some(pipeline_id));
+        assert_eq!(
+            document.url.as_ref().map(|url| url.as_str()),
+            Some("https://example.com/index.html")
+        );
+        assert_eq!(
+            (
+                document.dom,
+                document.styles,
+                document.images,
+                document.layout
+            ),
+            (1, 2, 4, 24)
+        );
+
+        let closed_document = &origin.pipelines[1];
+        assert_eq!(closed_document.webview_id, None);
+        assert_eq!(closed_document.pipeline_id, Some(closed_pipeline_id));
+        assert_eq!(closed_document.dom, 32);
+    }
+}
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -23,6 +23,7 @@ use compositing_traits::{CompositionPipeline, CompositorMsg, CompositorProxy};
 use constellation_traits::WindowSizeData;
 use crossbeam_channel::{Sender, unbounded};
 use devtools_traits::{DevtoolsControlMsg, ScriptToDevtoolsControlMsg};
+use embedder_traits::NetworkStatus;
 use embedder_traits::user_content_manager::UserContentManager;
 use fonts::{SystemFontServiceProxy, SystemFontServiceProxySender};
 use ipc_channel::Error;
@@ -38,10 +39,12 @@ use script_layout_interface::{LayoutFactory, ScriptThreadFactory};
 use script_traits::{
     AnimationState, DiscardBrowsingContext, DocumentActivity, InitialScriptState, LoadData,
     NewLayoutInfo, SWManagerMsg, ScriptThreadMessage, ScriptToConstellationChan,
+    SessionHistoryEntryState,
 };
 use serde::{Deserialize, Serialize};
 use servo_config::opts::{self, Opts};
-use servo_config::prefs::{self, Preferences};
+use servo_config::pref;
+use servo_config::prefs::{self, Preferences, SitePreferences};
 use servo_url::ServoUrl;
 use webrender_api::DocumentId;
 use webrender_traits::CrossProcessCompositorApi;
@@ -90,6 +93,10 @@ pub struct Pipeline {
     /// The history states owned by this pipeline.
     pub history_states: HashSet<HistoryStateId>,
 
+    /// The persisted state of the session history entries of this pipeline, by the history
+    /// state of the entry. The entry that the document was loaded in has no history state.
+    pub session_history_entry_states: HashMap<Option<HistoryStateId>, SessionHistoryEntryState>,
+
     /// Has this pipeline received a notification that it is completely loaded?
     pub completely_loaded: bool,
 
@@ -200,6 +207,9 @@ pub struct InitialPipelineState {
 
     /// User content manager
     pub user_content_manager: UserContentManager,
+
+    /// The connectivity and quality of the network.
+    pub network_status: NetworkStatus,
 }
 
 pub struct NewPipeline {
@@ -285,6 +295,8 @@ impl Pipeline {
                     script_port,
                     opts: (*opts::get()).clone(),
                     prefs: Box::new(prefs::get().clone()),
+                    site_preferences: prefs::site_preferences(),
+                    secure_schemes: servo_url::secure_schemes(),
                     pipeline_namespace_id: state.pipeline_namespace_id,
                     webrender_document: state.webrender_document,
                     cross_process_compositor_api: state
@@ -297,6 +309,7 @@ impl Pipeline {
                     user_agent: state.user_agent,
                     rippy_data: state.rippy_data,
                     user_content_manager: state.user_content_manager,
+                    network_status: state.network_status,
                 };
 
                 // Spawn the child process.
@@ -341,6 +354,14 @@ impl Pipeline {
         })
     }
 
+    /// The persisted state of the current session history entry of this pipeline.
+    pub fn current_session_history_entry_state(&self) -> SessionHistoryEntryState {
+        self.session_history_entry_states
+            .get(&self.history_state_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Creates a new `Pipeline`, after the script has been spawned.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -353,6 +374,9 @@ impl Pipeline {
         throttled: bool,
         load_data: LoadData,
     ) -> Pipeline {
+        // A document that reloads a discarded entry restores the state persisted for it.
+        let session_history_entry_states =
+            HashMap::from([(None, load_data.session_history_entry_state)]);
         let pipeline = Pipeline {
             id,
             browsing_context_id,
@@ -366,6 +390,7 @@ impl Pipeline {
             load_data,
             history_state_id: None,
             history_states: HashSet::new(),
+            session_history_entry_states,
             completely_loaded: false,
             title: String::new(),
             layout_epoch: Epoch(0),
@@ -495,6 +520,8 @@ pub struct UnprivilegedPipelineContent {
     script_port: IpcReceiver<ScriptThreadMessage>,
     opts: Opts,
     prefs: Box<Preferences>,
+    site_preferences: SitePreferences,
+    secure_schemes: Vec<String>,
     pipeline_namespace_id: PipelineNamespaceId,
     cross_process_compositor_api: CrossProcessCompositorApi,
     webrender_document: DocumentId,
@@ -504,6 +531,7 @@ pub struct UnprivilegedPipelineContent {
     user_agent: Cow<'static, str>,
     rippy_data: Vec<u8>,
     user_content_manager: UserContentManager,
+    network_status: NetworkStatus,
 }
 
 impl UnprivilegedPipelineContent {
@@ -550,6 +578,7 @@ impl UnprivilegedPipelineContent {
                 player_context: self.player_context.clone(),
                 inherited_secure_context: self.load_data.inherited_secure_context,
                 user_content_manager: self.user_content_manager,
+                network_status: self.network_status,
             },
             layout_factory,
             Arc::new(self.system_font_service.to_proxy()),
@@ -575,7 +604,7 @@ impl UnprivilegedPipelineContent {
         HangMonitorRegister::init(
             self.background_hang_monitor_to_constellation_chan.clone(),
             self.bhm_control_port.take().expect("no sampling profiler?"),
-            opts::get().background_hang_monitor,
+            opts::get().background_hang_monitor || pref!(dom_script_watchdog_enabled),
         )
     }
 
@@ -590,4 +619,12 @@ impl UnprivilegedPipelineContent {
     pub fn prefs(&self) -> &Preferences {
         &self.prefs
     }
+
+    pub fn site_preferences(&self) -> &SitePreferences {
+        &self.site_preferences
+    }
+
+    pub fn secure_schemes(&self) -> &[String] {
+        &self.secure_schemes
+    }
 }
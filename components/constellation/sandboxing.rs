@@ -22,7 +22,7 @@ use gaol::profile::{Operation, PathPattern, Profile};
 use ipc_channel::Error;
 use serde::{Deserialize, Serialize};
 use servo_config::opts::Opts;
-use servo_config::prefs::Preferences;
+use servo_config::prefs::{Preferences, SitePreferences};
 
 use crate::pipeline::UnprivilegedPipelineContent;
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
@@ -48,6 +48,20 @@ impl UnprivilegedContent {
             UnprivilegedContent::ServiceWorker(content) => content.prefs(),
         }
     }
+
+    pub fn site_preferences(&self) -> &SitePreferences {
+        match self {
+            UnprivilegedContent::Pipeline(content) => content.site_preferences(),
+            UnprivilegedContent::ServiceWorker(content) => content.site_preferences(),
+        }
+    }
+
+    pub fn secure_schemes(&self) -> &[String] {
+        match self {
+            UnprivilegedContent::Pipeline(content) => content.secure_schemes(),
+            UnprivilegedContent::ServiceWorker(content) => content.secure_schemes(),
+        }
+    }
 }
 
 /// Our content process sandbox profile on Mac. As restrictive as possible.
@@ -7,7 +7,7 @@ use script_traits::{SWManagerSenders, ServiceWorkerManagerFactory};
 use serde::{Deserialize, Serialize};
 use servo_config::opts::{self, Opts};
 use servo_config::prefs;
-use servo_config::prefs::Preferences;
+use servo_config::prefs::{Preferences, SitePreferences};
 use servo_url::ImmutableOrigin;
 
 use crate::sandboxing::{UnprivilegedContent, spawn_multiprocess};
@@ -18,6 +18,8 @@ use crate::sandboxing::{UnprivilegedContent, spawn_multiprocess};
 pub struct ServiceWorkerUnprivilegedContent {
     opts: Opts,
     prefs: Box<Preferences>,
+    site_preferences: SitePreferences,
+    secure_schemes: Vec<String>,
     senders: SWManagerSenders,
     origin: ImmutableOrigin,
 }
@@ -30,6 +32,8 @@ impl ServiceWorkerUnprivilegedContent {
         ServiceWorkerUnprivilegedContent {
             opts: (*opts::get()).clone(),
             prefs: Box::new(prefs::get().clone()),
+            site_preferences: prefs::site_preferences(),
+            secure_schemes: servo_url::secure_schemes(),
             senders,
             origin,
         }
@@ -55,4 +59,12 @@ impl ServiceWorkerUnprivilegedContent {
     pub fn prefs(&self) -> &Preferences {
         &self.prefs
     }
+
+    pub fn site_preferences(&self) -> &SitePreferences {
+        &self.site_preferences
+    }
+
+    pub fn secure_schemes(&self) -> &[String] {
+        &self.secure_schemes
+    }
 }
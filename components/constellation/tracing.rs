@@ -57,9 +57,13 @@ mod from_compositor {
                 Self::AllowNavigationResponse(..) => target!("AllowNavigationResponse"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::ClearCache => target!("ClearCache"),
+                Self::ClearStorageForSite(..) => target!("ClearStorageForSite"),
                 Self::TraverseHistory(..) => target!("TraverseHistory"),
                 Self::WindowSize(..) => target!("WindowSize"),
                 Self::ThemeChange(..) => target!("ThemeChange"),
+                Self::ScreensChanged => target!("ScreensChanged"),
+                Self::NetworkStatusChanged(..) => target!("NetworkStatusChanged"),
+                Self::SetCookiePolicy(..) => target!("SetCookiePolicy"),
                 Self::TickAnimation(..) => target!("TickAnimation"),
                 Self::WebDriverCommand(..) => target!("WebDriverCommand"),
                 Self::Reload(..) => target!("Reload"),
@@ -75,8 +79,19 @@ mod from_compositor {
                 Self::ExitFullScreen(_) => target!("ExitFullScreen"),
                 Self::MediaSessionAction(_) => target!("MediaSessionAction"),
                 Self::SetWebViewThrottled(_, _) => target!("SetWebViewThrottled"),
+                Self::SetWebViewVisibility(_, _) => target!("SetWebViewVisibility"),
+                Self::SetWebViewMuted(_, _) => target!("SetWebViewMuted"),
+                Self::SetEmulatedMediaFeatures(..) => target!("SetEmulatedMediaFeatures"),
+                Self::SetUserContentManager(_) => target!("SetUserContentManager"),
+                Self::PreferencesUpdated(..) => target!("PreferencesUpdated"),
+                Self::CreateMemoryReport => target!("CreateMemoryReport"),
+                Self::StopUnresponsiveScript(..) => target!("StopUnresponsiveScript"),
                 Self::SetScrollStates(..) => target!("SetScrollStates"),
+                Self::SetVisualViewport(..) => target!("SetVisualViewport"),
                 Self::PaintMetric(..) => target!("PaintMetric"),
+                Self::FindInPage(..) => target!("FindInPage"),
+                Self::Autofill(..) => target!("Autofill"),
+                Self::ControlAnimationClock(..) => target!("ControlAnimationClock"),
             }
         }
     }
@@ -90,6 +105,7 @@ mod from_compositor {
             }
             match self {
                 InputEvent::EditingAction(..) => target_variant!("EditingAction"),
+                InputEvent::FileDrop(..) => target_variant!("FileDrop"),
                 InputEvent::Gamepad(..) => target_variant!("Gamepad"),
                 InputEvent::Ime(..) => target_variant!("Ime"),
                 InputEvent::Keyboard(..) => target_variant!("Keyboard"),
@@ -143,19 +159,29 @@ mod from_script {
                 Self::GetBrowsingContextInfo(..) => target!("GetBrowsingContextInfo"),
                 Self::GetChildBrowsingContextId(..) => target!("GetChildBrowsingContextId"),
                 Self::LoadComplete => target!("LoadComplete"),
+                Self::DOMContentLoaded => target!("DOMContentLoaded"),
+                Self::WebDriverChannelMessage(..) => target!("WebDriverChannelMessage"),
                 Self::LoadUrl(..) => target!("LoadUrl"),
                 Self::AbortLoadUrl => target!("AbortLoadUrl"),
+                Self::Prerender(..) => target!("Prerender"),
+                Self::CreatePortal(..) => target!("CreatePortal"),
+                Self::ActivatePortal(..) => target!("ActivatePortal"),
+                Self::DiscardPortal(..) => target!("DiscardPortal"),
+                Self::PostMessageToPortal(..) => target!("PostMessageToPortal"),
+                Self::PostMessageToPortalHost(..) => target!("PostMessageToPortalHost"),
                 Self::PostMessage { .. } => target!("PostMessage"),
                 Self::NavigatedToFragment(..) => target!("NavigatedToFragment"),
                 Self::TraverseHistory(..) => target!("TraverseHistory"),
                 Self::PushHistoryState(..) => target!("PushHistoryState"),
                 Self::ReplaceHistoryState(..) => target!("ReplaceHistoryState"),
+                Self::SetSessionHistoryEntryState(..) => target!("SetSessionHistoryEntryState"),
                 Self::JointSessionHistoryLength(..) => target!("JointSessionHistoryLength"),
                 Self::RemoveIFrame(..) => target!("RemoveIFrame"),
                 Self::SetThrottledComplete(..) => target!("SetThrottledComplete"),
                 Self::ScriptLoadedURLInIFrame(..) => target!("ScriptLoadedURLInIFrame"),
                 Self::ScriptNewIFrame(..) => target!("ScriptNewIFrame"),
                 Self::CreateAuxiliaryWebView(..) => target!("ScriptNewAuxiliary"),
+                Self::DisownOpener => target!("DisownOpener"),
                 Self::ActivateDocument => target!("ActivateDocument"),
                 Self::SetDocumentState(..) => target!("SetDocumentState"),
                 Self::SetLayoutEpoch(..) => target!("SetLayoutEpoch"),
@@ -175,6 +201,7 @@ mod from_script {
                 Self::TitleChanged(..) => target!("TitleChanged"),
                 Self::IFrameSizes(..) => target!("IFrameSizes"),
                 Self::ReportMemory(..) => target!("ReportMemory"),
+                Self::SetPreference(..) => target!("SetPreference"),
             }
         }
     }
@@ -200,11 +227,17 @@ mod from_script {
                 Self::WebViewFocused(..) => target_variant!("WebViewFocused"),
                 Self::WebViewBlurred => target_variant!("WebViewBlurred"),
                 Self::WebResourceRequested(..) => target_variant!("WebResourceRequested"),
+                Self::WebResourceResponseReceived(..) => {
+                    target_variant!("WebResourceResponseReceived")
+                },
+                Self::DownloadStarted(..) => target_variant!("DownloadStarted"),
+                Self::DownloadStateChanged(..) => target_variant!("DownloadStateChanged"),
                 Self::AllowUnload(..) => target_variant!("AllowUnload"),
                 Self::Keyboard(..) => target_variant!("Keyboard"),
                 Self::ClearClipboard(..) => target_variant!("ClearClipboard"),
                 Self::GetClipboardText(..) => target_variant!("GetClipboardText"),
                 Self::SetClipboardText(..) => target_variant!("SetClipboardText"),
+                Self::SetClipboardImage(..) => target_variant!("SetClipboardImage"),
                 Self::SetCursor(..) => target_variant!("SetCursor"),
                 Self::NewFavicon(..) => target_variant!("NewFavicon"),
                 Self::HistoryChanged(..) => target_variant!("HistoryChanged"),
@@ -221,6 +254,9 @@ mod from_script {
                     target_variant!("NotifyLoadStatusChanged(LoadStatus::Complete")
                 },
                 Self::Panic(..) => target_variant!("Panic"),
+                Self::MemoryReportCreated(..) => target_variant!("MemoryReportCreated"),
+                Self::ScriptUnresponsive(..) => target_variant!("ScriptUnresponsive"),
+                Self::ScriptResponsive(..) => target_variant!("ScriptResponsive"),
                 Self::GetSelectedBluetoothDevice(..) => {
                     target_variant!("GetSelectedBluetoothDevice")
                 },
@@ -228,6 +264,7 @@ mod from_script {
                 Self::PromptPermission(..) => target_variant!("PromptPermission"),
                 Self::ShowIME(..) => target_variant!("ShowIME"),
                 Self::HideIME(..) => target_variant!("HideIME"),
+                Self::UpdateImeCaretRect(..) => target_variant!("UpdateImeCaretRect"),
                 Self::ReportProfile(..) => target_variant!("ReportProfile"),
                 Self::MediaSessionEvent(..) => target_variant!("MediaSessionEvent"),
                 Self::OnDevtoolsStarted(..) => target_variant!("OnDevtoolsStarted"),
@@ -236,6 +273,15 @@ mod from_script {
                 Self::StopGamepadHapticEffect(..) => target_variant!("StopGamepadHapticEffect"),
                 Self::ShutdownComplete => target_variant!("ShutdownComplete"),
                 Self::ShowNotification(..) => target_variant!("ShowNotification"),
+                Self::AccessibilityTreeUpdate(..) => target_variant!("AccessibilityTreeUpdate"),
+                Self::FindInPageResult(..) => target_variant!("FindInPageResult"),
+                Self::CheckSpelling(..) => target_variant!("CheckSpelling"),
+                Self::GetSpellingSuggestions(..) => target_variant!("GetSpellingSuggestions"),
+                Self::AutofillFields(..) => target_variant!("AutofillFields"),
+                Self::AutofillFieldFocused(..) => target_variant!("AutofillFieldFocused"),
+                Self::GetKeyboardLayoutMap(..) => target_variant!("GetKeyboardLayoutMap"),
+                Self::SetKeyboardLock(..) => target_variant!("SetKeyboardLock"),
+                Self::ShowPrintDialog(..) => target_variant!("ShowPrintDialog"),
             }
         }
     }
@@ -195,6 +195,13 @@ impl ActorRegistry {
                 }
             },
         }
+        self.flush_pending();
+        Ok(())
+    }
+
+    /// Insert the actors passed to [`Self::register_later`] and remove the ones passed to
+    /// [`Self::drop_actor_later`].
+    pub(crate) fn flush_pending(&mut self) {
         let new_actors = mem::take(&mut *self.new_actors.borrow_mut());
         for actor in new_actors.into_iter() {
             self.actors.insert(actor.name().to_owned(), actor);
@@ -204,7 +211,6 @@ impl ActorRegistry {
         for name in old_actors {
             self.drop_actor(name);
         }
-        Ok(())
     }
 
     pub fn drop_actor(&mut self, name: String) {
@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Keeps the breakpoints of a browsing context, which apply to every source with the URL that
+//! they name.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/breakpoint-list.js
+
+use std::net::TcpStream;
+
+use devtools_traits::{BreakpointLocation, DevtoolScriptControlMsg};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
+use crate::protocol::JsonPacketStream;
+use crate::{EmptyReplyMsg, StreamId};
+
+#[derive(Serialize)]
+pub struct BreakpointListActorMsg {
+    actor: String,
+}
+
+pub struct BreakpointListActor {
+    name: String,
+    browsing_context: String,
+}
+
+impl Actor for BreakpointListActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The breakpoint list actor can handle the following messages:
+    ///
+    /// - `setBreakpoint`: Sets a breakpoint at a location given by source URL, line and column
+    ///
+    /// - `removeBreakpoint`: Removes the breakpoint at a location
+    ///
+    /// - `setActiveEventBreakpoints`: Event breakpoints are not supported yet, so this only
+    ///   replies
+    fn handle_message(
+        &self,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "setBreakpoint" | "removeBreakpoint" => {
+                let location = msg.get("location").ok_or(())?;
+                let Some(url) = location.get("sourceUrl").and_then(Value::as_str) else {
+                    // TODO: Support breakpoints in sources without a URL, given by source actor
+                    return Ok(ActorMessageStatus::Ignored);
+                };
+                let line = location.get("line").and_then(Value::as_u64).ok_or(())?;
+                let column = location.get("column").and_then(Value::as_u64);
+                let breakpoint = BreakpointLocation {
+                    url: url.to_owned(),
+                    line: line as u32,
+                    column: column.map(|column| column as u32),
+                };
+
+                let browsing_context =
+                    registry.find::<BrowsingContextActor>(&self.browsing_context);
+                let message = if msg_type == "setBreakpoint" {
+                    DevtoolScriptControlMsg::SetBreakpoint(breakpoint)
+                } else {
+                    DevtoolScriptControlMsg::ClearBreakpoint(breakpoint)
+                };
+                browsing_context.script_chan.send(message).map_err(|_| ())?;
+
+                let _ = stream.write_json_packet(&EmptyReplyMsg { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "setActiveEventBreakpoints" => {
+                let _ = stream.write_json_packet(&EmptyReplyMsg { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl BreakpointListActor {
+    pub fn new(name: String, browsing_context: String) -> Self {
+        Self {
+            name,
+            browsing_context,
+        }
+    }
+
+    pub fn encodable(&self) -> BreakpointListActorMsg {
+        BreakpointListActorMsg { actor: self.name() }
+    }
+}
@@ -14,6 +14,7 @@ use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use devtools_traits::DevtoolScriptControlMsg::{self, GetCssDatabase, WantsLiveNotifications};
 use devtools_traits::{DevtoolsPageInfo, NavigationState};
 use ipc_channel::ipc::{self, IpcSender};
+use profile_traits::mem;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
@@ -21,6 +22,7 @@ use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::actors::inspector::InspectorActor;
 use crate::actors::inspector::accessibility::AccessibilityActor;
 use crate::actors::inspector::css_properties::CssPropertiesActor;
+use crate::actors::memory::MemoryActor;
 use crate::actors::reflow::ReflowActor;
 use crate::actors::stylesheets::StyleSheetsActor;
 use crate::actors::tab::TabDescriptorActor;
@@ -98,6 +100,7 @@ pub struct BrowsingContextActorMsg {
     console_actor: String,
     css_properties_actor: String,
     inspector_actor: String,
+    memory_actor: String,
     reflow_actor: String,
     style_sheets_actor: String,
     thread_actor: String,
@@ -106,7 +109,6 @@ pub struct BrowsingContextActorMsg {
     // changes_actor: String,
     // framerate_actor: String,
     // manifest_actor: String,
-    // memory_actor: String,
     // network_content_actor: String,
     // objects_manager: String,
     // performance_actor: String,
@@ -132,6 +134,7 @@ pub(crate) struct BrowsingContextActor {
     pub console: String,
     pub css_properties: String,
     pub inspector: String,
+    pub memory: String,
     pub reflow: String,
     pub style_sheets: String,
     pub thread: String,
@@ -183,6 +186,7 @@ impl BrowsingContextActor {
         page_info: DevtoolsPageInfo,
         pipeline_id: PipelineId,
         script_sender: IpcSender<DevtoolScriptControlMsg>,
+        mem_profiler_chan: mem::ProfilerChan,
         actors: &mut ActorRegistry,
     ) -> BrowsingContextActor {
         let name = actors.new_name("target");
@@ -206,18 +210,20 @@ impl BrowsingContextActor {
             name: actors.new_name("inspector"),
             walker: RefCell::new(None),
             page_style: RefCell::new(None),
-            highlighter: RefCell::new(None),
+            highlighters: RefCell::new(HashMap::new()),
             script_chan: script_sender.clone(),
             browsing_context: name.clone(),
         };
 
+        let memory = MemoryActor::new(actors.new_name("memory"), name.clone(), mem_profiler_chan);
+
         let reflow = ReflowActor::new(actors.new_name("reflow"));
 
         let style_sheets = StyleSheetsActor::new(actors.new_name("stylesheets"));
 
         let tabdesc = TabDescriptorActor::new(actors, name.clone(), is_top_level_global);
 
-        let thread = ThreadActor::new(actors.new_name("thread"));
+        let thread = ThreadActor::new(actors.new_name("thread"), script_sender.clone());
 
         let watcher = WatcherActor::new(
             actors,
@@ -237,6 +243,7 @@ impl BrowsingContextActor {
             console,
             css_properties: css_properties.name(),
             inspector: inspector.name(),
+            memory: memory.name(),
             reflow: reflow.name(),
             streams: RefCell::new(HashMap::new()),
             style_sheets: style_sheets.name(),
@@ -248,6 +255,7 @@ impl BrowsingContextActor {
         actors.register(Box::new(accessibility));
         actors.register(Box::new(css_properties));
         actors.register(Box::new(inspector));
+        actors.register(Box::new(memory));
         actors.register(Box::new(reflow));
         actors.register(Box::new(style_sheets));
         actors.register(Box::new(tabdesc));
@@ -280,6 +288,7 @@ impl BrowsingContextActor {
             console_actor: self.console.clone(),
             css_properties_actor: self.css_properties.clone(),
             inspector_actor: self.inspector.clone(),
+            memory_actor: self.memory.clone(),
             reflow_actor: self.reflow.clone(),
             style_sheets_actor: self.style_sheets.clone(),
             thread_actor: self.thread.clone(),
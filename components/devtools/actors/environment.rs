@@ -0,0 +1,99 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A scope of a paused frame, along with the variables that it binds.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/environment.js
+
+use std::net::TcpStream;
+
+use devtools_traits::ScopeInfo;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::StreamId;
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::object::encode_js_value;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EnvironmentForm {
+    actor: String,
+    #[serde(rename = "type")]
+    type_: String,
+    scope_kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<EnvironmentFunction>,
+    bindings: EnvironmentBindings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<Box<EnvironmentForm>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentFunction {
+    display_name: String,
+}
+
+#[derive(Serialize)]
+struct EnvironmentBindings {
+    arguments: Vec<Value>,
+    variables: Map<String, Value>,
+}
+
+pub(crate) struct EnvironmentActor {
+    name: String,
+}
+
+impl Actor for EnvironmentActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        _msg_type: &str,
+        _msg: &Map<String, Value>,
+        _stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        // TODO: Handle assign to change the value of a binding
+        Ok(ActorMessageStatus::Ignored)
+    }
+}
+
+impl EnvironmentActor {
+    /// Register an actor for each of the given scopes, innermost first, and return the form of
+    /// the innermost one, which links to its parents.
+    pub fn register(registry: &ActorRegistry, scopes: &[ScopeInfo]) -> Option<EnvironmentForm> {
+        let (scope, parents) = scopes.split_first()?;
+        let name = registry.new_name("environment");
+        registry.register_later(Box::new(EnvironmentActor { name: name.clone() }));
+
+        let mut variables = Map::new();
+        for (binding, value) in &scope.bindings {
+            let mut descriptor = Map::new();
+            descriptor.insert("value".to_owned(), encode_js_value(registry, value.clone()));
+            variables.insert(binding.clone(), Value::Object(descriptor));
+        }
+
+        Some(EnvironmentForm {
+            actor: name,
+            type_: scope.kind.clone(),
+            scope_kind: scope.kind.clone(),
+            function: scope
+                .function_name
+                .clone()
+                .map(|display_name| EnvironmentFunction { display_name }),
+            bindings: EnvironmentBindings {
+                arguments: vec![],
+                variables,
+            },
+            parent: Self::register(registry, parents).map(Box::new),
+        })
+    }
+}
@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A frame on the stack of a paused script thread. It lives until the thread resumes.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/frame.js
+
+use std::net::TcpStream;
+
+use devtools_traits::FrameInfo;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::StreamId;
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::environment::{EnvironmentActor, EnvironmentForm};
+use crate::protocol::JsonPacketStream;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FrameForm {
+    actor: String,
+    #[serde(rename = "type")]
+    type_: String,
+    display_name: String,
+    arguments: Vec<Value>,
+    #[serde(rename = "where")]
+    where_: FrameWhere,
+    oldest: bool,
+    state: String,
+}
+
+#[derive(Serialize)]
+struct FrameWhere {
+    actor: String,
+    line: u32,
+    column: u32,
+}
+
+#[derive(Serialize)]
+struct GetEnvironmentReply {
+    from: String,
+    #[serde(flatten)]
+    environment: Option<EnvironmentForm>,
+}
+
+pub(crate) struct FrameActor {
+    name: String,
+    info: FrameInfo,
+    /// The source actor of the source that the frame executes.
+    source: String,
+    oldest: bool,
+}
+
+impl Actor for FrameActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The frame actor can handle the following messages:
+    ///
+    /// - `getEnvironment`: Returns the scopes of the frame, with the values that they bind
+    fn handle_message(
+        &self,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getEnvironment" => {
+                let msg = GetEnvironmentReply {
+                    from: self.name(),
+                    environment: EnvironmentActor::register(registry, &self.info.scopes),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl FrameActor {
+    pub fn new(name: String, info: FrameInfo, source: String, oldest: bool) -> Self {
+        Self {
+            name,
+            info,
+            source,
+            oldest,
+        }
+    }
+
+    pub fn encodable(&self) -> FrameForm {
+        // Global code runs in the frame of a script rather than of a function call.
+        let type_ = if self.info.display_name.is_empty() && self.oldest {
+            "global"
+        } else {
+            "call"
+        };
+        FrameForm {
+            actor: self.name(),
+            type_: type_.into(),
+            display_name: self.info.display_name.clone(),
+            arguments: vec![],
+            where_: FrameWhere {
+                actor: self.source.clone(),
+                line: self.info.location.line,
+                column: self.info.location.column,
+            },
+            oldest: self.oldest,
+            state: "on-stack".into(),
+        }
+    }
+}
@@ -8,8 +8,8 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::net::TcpStream;
 
-use devtools_traits::DevtoolScriptControlMsg;
-use devtools_traits::DevtoolScriptControlMsg::GetRootNode;
+use devtools_traits::DevtoolScriptControlMsg::{GetRootNode, WatchDomMutations};
+use devtools_traits::{DevtoolScriptControlMsg, HighlighterKind};
 use ipc_channel::ipc::{self, IpcSender};
 use serde::Serialize;
 use serde_json::{self, Map, Value};
@@ -61,7 +61,8 @@ pub struct InspectorActor {
     pub name: String,
     pub walker: RefCell<Option<String>>,
     pub page_style: RefCell<Option<String>>,
-    pub highlighter: RefCell<Option<String>>,
+    /// The highlighter actors, keyed by their type name.
+    pub highlighters: RefCell<HashMap<String, String>>,
     pub script_chan: IpcSender<DevtoolScriptControlMsg>,
     pub browsing_context: String,
 }
@@ -75,7 +76,7 @@ impl Actor for InspectorActor {
         &self,
         registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
@@ -109,6 +110,8 @@ impl Actor for InspectorActor {
                         root_node: root.clone(),
                         mutations: RefCell::new(vec![]),
                     };
+                    // Have the walker follow the changes to the DOM as they happen.
+                    let _ = self.script_chan.send(WatchDomMutations(pipeline, true));
                     let mut walker_name = self.walker.borrow_mut();
                     *walker_name = Some(walker.name());
                     registry.register_later(Box::new(walker));
@@ -163,20 +166,27 @@ impl Actor for InspectorActor {
             },
 
             "getHighlighterByType" => {
-                if self.highlighter.borrow().is_none() {
-                    let highlighter_actor = HighlighterActor {
-                        name: registry.new_name("highlighter"),
-                    };
-                    let mut highlighter = self.highlighter.borrow_mut();
-                    *highlighter = Some(highlighter_actor.name());
-                    registry.register_later(Box::new(highlighter_actor));
-                }
+                let type_name = msg.get("typeName").and_then(Value::as_str).ok_or(())?;
+                let actor = self
+                    .highlighters
+                    .borrow_mut()
+                    .entry(type_name.into())
+                    .or_insert_with(|| {
+                        let highlighter_actor = HighlighterActor {
+                            name: registry.new_name("highlighter"),
+                            kind: highlighter_kind(type_name),
+                            script_chan: self.script_chan.clone(),
+                            browsing_context: self.browsing_context.clone(),
+                        };
+                        let name = highlighter_actor.name();
+                        registry.register_later(Box::new(highlighter_actor));
+                        name
+                    })
+                    .clone();
 
                 let msg = GetHighlighterReply {
                     from: self.name(),
-                    highlighter: HighlighterMsg {
-                        actor: self.highlighter.borrow().clone().unwrap(),
-                    },
+                    highlighter: HighlighterMsg { actor },
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -186,3 +196,13 @@ impl Actor for InspectorActor {
         })
     }
 }
+
+/// What the highlighters with the given type name draw over the page.
+fn highlighter_kind(type_name: &str) -> Option<HighlighterKind> {
+    match type_name {
+        "BoxModelHighlighter" => Some(HighlighterKind::BoxModel),
+        "FlexboxHighlighter" => Some(HighlighterKind::Flexbox),
+        "CssGridHighlighter" => Some(HighlighterKind::Grid),
+        _ => None,
+    }
+}
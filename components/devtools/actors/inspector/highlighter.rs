@@ -2,15 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-//! Handles highlighting selected DOM nodes in the inspector. At the moment it only replies and
-//! changes nothing on Servo's side.
+//! Handles highlighting selected DOM nodes in the inspector. Layout draws the box model of the
+//! highlighted node, or the lines of its flex or grid container, over the page. The highlighters
+//! of other types only reply and change nothing on Servo's side.
 
 use std::net::TcpStream;
 
+use devtools_traits::DevtoolScriptControlMsg::HighlightDomNode;
+use devtools_traits::{DevtoolScriptControlMsg, HighlighterKind};
+use ipc_channel::ipc::IpcSender;
 use serde::Serialize;
 use serde_json::{self, Map, Value};
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
 use crate::protocol::JsonPacketStream;
 use crate::{EmptyReplyMsg, StreamId};
 
@@ -21,6 +26,11 @@ pub struct HighlighterMsg {
 
 pub struct HighlighterActor {
     pub name: String,
+    /// What is drawn over the highlighted node, or `None` for the types of highlighters that
+    /// are not supported.
+    pub kind: Option<HighlighterKind>,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pub browsing_context: String,
 }
 
 #[derive(Serialize)]
@@ -41,23 +51,29 @@ impl Actor for HighlighterActor {
     /// - `hide`: Disables highlighting for the selected node
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
+        registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "show" => {
+                let node = msg.get("node").and_then(Value::as_str).ok_or(())?;
+                let node_id = registry.actor_to_script(node.into());
+                self.highlight(registry, Some(node_id));
+
                 let msg = ShowReply {
                     from: self.name(),
-                    value: true,
+                    value: self.kind.is_some(),
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
 
             "hide" => {
+                self.highlight(registry, None);
+
                 let msg = EmptyReplyMsg { from: self.name() };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -67,3 +83,19 @@ impl Actor for HighlighterActor {
         })
     }
 }
+
+impl HighlighterActor {
+    /// Highlight the node with the given script id, or stop highlighting if there is none.
+    fn highlight(&self, registry: &ActorRegistry, node_id: Option<String>) {
+        let Some(kind) = self.kind else {
+            return;
+        };
+        let pipeline = registry
+            .find::<BrowsingContextActor>(&self.browsing_context)
+            .active_pipeline
+            .get();
+        let _ = self
+            .script_chan
+            .send(HighlightDomNode(pipeline, kind, node_id));
+    }
+}
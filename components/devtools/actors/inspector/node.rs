@@ -10,14 +10,15 @@ use std::collections::HashMap;
 use std::net::TcpStream;
 
 use base::id::PipelineId;
-use devtools_traits::DevtoolScriptControlMsg::{GetChildren, GetDocumentElement, ModifyAttribute};
+use devtools_traits::DevtoolScriptControlMsg::{
+    GetChildren, GetDocumentElement, GetNodeValue, ModifyAttribute, ModifyNodeValue,
+};
 use devtools_traits::{DevtoolScriptControlMsg, NodeInfo, ShadowRootMode};
 use ipc_channel::ipc::{self, IpcSender};
 use serde::Serialize;
 use serde_json::{self, Map, Value};
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
-use crate::actors::inspector::walker::WalkerActor;
 use crate::protocol::JsonPacketStream;
 use crate::{EmptyReplyMsg, StreamId};
 
@@ -28,6 +29,12 @@ const TEXT_NODE: u16 = 3;
 /// The maximum length of a text node for it to appear as an inline child in the inspector.
 const MAX_INLINE_LENGTH: usize = 50;
 
+#[derive(Serialize)]
+struct GetNodeValueReply {
+    from: String,
+    value: Option<String>,
+}
+
 #[derive(Serialize)]
 struct GetUniqueSelectorReply {
     from: String,
@@ -98,6 +105,10 @@ impl Actor for NodeActor {
     /// - `modifyAttributes`: Asks the script to change a value in the attribute of the
     ///   corresponding node
     ///
+    /// - `getNodeValue`: Returns the text of this node, if it is a text or comment node
+    ///
+    /// - `setNodeValue`: Asks the script to replace the text of the corresponding node
+    ///
     /// - `getUniqueSelector`: Returns the display name of this node
     fn handle_message(
         &self,
@@ -117,9 +128,6 @@ impl Actor for NodeActor {
                     })
                     .collect();
 
-                let walker = registry.find::<WalkerActor>(&self.walker);
-                walker.new_mutations(stream, &self.name, &modifications);
-
                 self.script_chan
                     .send(ModifyAttribute(
                         self.pipeline,
@@ -133,6 +141,39 @@ impl Actor for NodeActor {
                 ActorMessageStatus::Processed
             },
 
+            "getNodeValue" => {
+                let (tx, rx) = ipc::channel().map_err(|_| ())?;
+                self.script_chan
+                    .send(GetNodeValue(
+                        self.pipeline,
+                        registry.actor_to_script(self.name()),
+                        tx,
+                    ))
+                    .map_err(|_| ())?;
+
+                let msg = GetNodeValueReply {
+                    from: self.name(),
+                    value: rx.recv().map_err(|_| ())?,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "setNodeValue" => {
+                let value = msg.get("value").ok_or(())?.as_str().ok_or(())?;
+                self.script_chan
+                    .send(ModifyNodeValue(
+                        self.pipeline,
+                        registry.actor_to_script(self.name()),
+                        value.into(),
+                    ))
+                    .map_err(|_| ())?;
+
+                let reply = EmptyReplyMsg { from: self.name() };
+                let _ = stream.write_json_packet(&reply);
+                ActorMessageStatus::Processed
+            },
+
             "getUniqueSelector" => {
                 let (tx, rx) = ipc::channel().unwrap();
                 self.script_chan
@@ -95,7 +95,8 @@ impl Actor for StyleRuleActor {
     /// - `setRuleText`: Applies a set of modifications to the css rules that this actor manages.
     ///   There is also `modifyProperties`, which has a slightly different API to do the same, but
     ///   this is preferred. Which one the devtools client sends is decided by the `traits` defined
-    ///   when returning the list of rules.
+    ///   when returning the list of rules. Both are handled, and the modifications apply either
+    ///   to the style attribute of the node or to the rules of the stylesheet with the selector.
     fn handle_message(
         &self,
         registry: &ActorRegistry,
@@ -105,7 +106,7 @@ impl Actor for StyleRuleActor {
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
-            "setRuleText" => {
+            "setRuleText" | "modifyProperties" => {
                 // Parse the modifications sent from the client
                 let mods = msg.get("modifications").ok_or(())?.as_array().ok_or(())?;
                 let modifications: Vec<_> = mods
@@ -123,6 +124,7 @@ impl Actor for StyleRuleActor {
                     .send(ModifyRule(
                         walker.pipeline,
                         registry.actor_to_script(self.node.clone()),
+                        self.selector.clone(),
                         modifications,
                     ))
                     .map_err(|_| ())?;
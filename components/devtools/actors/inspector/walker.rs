@@ -9,7 +9,7 @@ use std::net::TcpStream;
 
 use base::id::PipelineId;
 use devtools_traits::DevtoolScriptControlMsg::{GetChildren, GetDocumentElement};
-use devtools_traits::{AttrModification, DevtoolScriptControlMsg};
+use devtools_traits::{DevtoolScriptControlMsg, DomMutation, NodeInfo};
 use ipc_channel::ipc::{self, IpcSender};
 use serde::Serialize;
 use serde_json::{self, Map, Value};
@@ -31,7 +31,8 @@ pub struct WalkerActor {
     pub script_chan: IpcSender<DevtoolScriptControlMsg>,
     pub pipeline: PipelineId,
     pub root_node: NodeActorMsg,
-    pub mutations: RefCell<Vec<(AttrModification, String)>>,
+    /// The mutations of the DOM that the client has not asked for yet.
+    pub(crate) mutations: RefCell<Vec<MutationMsg>>,
 }
 
 #[derive(Serialize)]
@@ -72,13 +73,23 @@ struct WatchRootNodeReply {
 }
 
 #[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct MutationMsg {
-    attribute_name: String,
-    new_value: Option<String>,
-    target: String,
-    #[serde(rename = "type")]
-    type_: String,
+#[serde(rename_all = "camelCase", tag = "type")]
+pub(crate) enum MutationMsg {
+    #[serde(rename_all = "camelCase")]
+    Attributes {
+        target: String,
+        attribute_name: String,
+        new_value: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    CharacterData { target: String, new_value: String },
+    #[serde(rename_all = "camelCase")]
+    ChildList {
+        target: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+        num_children: usize,
+    },
 }
 
 #[derive(Serialize)]
@@ -93,6 +104,21 @@ struct GetOffsetParentReply {
     node: Option<()>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DisconnectedNodeMsg {
+    node: NodeActorMsg,
+    new_parents: Vec<NodeActorMsg>,
+}
+
+#[derive(Serialize)]
+struct PickerNodePickedReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    node: DisconnectedNodeMsg,
+}
+
 #[derive(Serialize)]
 struct NewMutationsReply {
     from: String,
@@ -115,7 +141,7 @@ impl Actor for WalkerActor {
     ///
     /// - `getLayoutInspector`: Returns the Layout inspector actor, placeholder
     ///
-    /// - `getMutations`: Returns the list of attribute changes since it was last called
+    /// - `getMutations`: Returns the list of DOM changes since it was last called
     ///
     /// - `getOffsetParent`: Placeholder
     ///
@@ -204,17 +230,7 @@ impl Actor for WalkerActor {
             "getMutations" => {
                 let msg = GetMutationsReply {
                     from: self.name(),
-                    mutations: self
-                        .mutations
-                        .borrow_mut()
-                        .drain(..)
-                        .map(|(mutation, target)| MutationMsg {
-                            attribute_name: mutation.attribute_name,
-                            new_value: mutation.new_value,
-                            target,
-                            type_: "attributes".into(),
-                        })
-                        .collect(),
+                    mutations: self.mutations.take(),
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -269,20 +285,99 @@ impl Actor for WalkerActor {
 }
 
 impl WalkerActor {
-    pub(crate) fn new_mutations(
+    /// Remember the mutations of the DOM for the next `getMutations` message, and tell the
+    /// clients that there are new ones if there weren't already. Mutations of nodes that the
+    /// clients have never seen are left out.
+    pub(crate) fn new_mutations<'a>(
         &self,
-        stream: &mut TcpStream,
-        target: &str,
-        modifications: &[AttrModification],
+        registry: &ActorRegistry,
+        mutations: Vec<DomMutation>,
+        streams: impl Iterator<Item = &'a mut TcpStream>,
     ) {
-        {
-            let mut mutations = self.mutations.borrow_mut();
-            mutations.extend(modifications.iter().cloned().map(|m| (m, target.into())));
+        let known_actor = |script_id: String| {
+            registry
+                .script_actor_registered(script_id.clone())
+                .then(|| registry.script_to_actor(script_id))
+        };
+        let known_actors =
+            |script_ids: Vec<String>| script_ids.into_iter().filter_map(known_actor).collect();
+
+        let mut pending_mutations = self.mutations.borrow_mut();
+        let had_mutations = !pending_mutations.is_empty();
+        pending_mutations.extend(mutations.into_iter().filter_map(|mutation| {
+            Some(match mutation {
+                DomMutation::Attribute {
+                    target,
+                    attribute_name,
+                    new_value,
+                } => MutationMsg::Attributes {
+                    target: known_actor(target)?,
+                    attribute_name,
+                    new_value,
+                },
+                DomMutation::CharacterData { target, new_value } => MutationMsg::CharacterData {
+                    target: known_actor(target)?,
+                    new_value,
+                },
+                DomMutation::ChildList {
+                    target,
+                    added,
+                    removed,
+                    num_children,
+                } => MutationMsg::ChildList {
+                    target: known_actor(target)?,
+                    added: known_actors(added),
+                    removed: known_actors(removed),
+                    num_children,
+                },
+            })
+        }));
+
+        if had_mutations || pending_mutations.is_empty() {
+            return;
         }
-        let _ = stream.write_json_packet(&NewMutationsReply {
+        for stream in streams {
+            let _ = stream.write_json_packet(&NewMutationsReply {
+                from: self.name(),
+                type_: "newMutations".into(),
+            });
+        }
+    }
+
+    /// Select a node in the inspector of the clients, as if they had picked it. `nodes` are the
+    /// inclusive ancestors of the node, from the document down to the node itself.
+    pub(crate) fn pick_node<'a>(
+        &self,
+        registry: &ActorRegistry,
+        nodes: Vec<NodeInfo>,
+        streams: impl Iterator<Item = &'a mut TcpStream>,
+    ) {
+        let mut nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|node| {
+                node.encode(
+                    registry,
+                    true,
+                    self.script_chan.clone(),
+                    self.pipeline,
+                    self.name(),
+                )
+            })
+            .collect();
+        let Some(node) = nodes.pop() else {
+            return;
+        };
+        let msg = PickerNodePickedReply {
             from: self.name(),
-            type_: "newMutations".into(),
-        });
+            type_: "pickerNodePicked".into(),
+            node: DisconnectedNodeMsg {
+                node,
+                new_parents: nodes,
+            },
+        };
+        for stream in streams {
+            let _ = stream.write_json_packet(&msg);
+        }
     }
 }
 
@@ -2,31 +2,69 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+//! Measures the memory used on behalf of the document of a target, using the reports of the
+//! memory profiler.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/memory.js
+
+use std::cell::Cell;
 use std::net::TcpStream;
+use std::time::Instant;
 
+use base::id::PipelineId;
+use ipc_channel::ipc;
+use profile_traits::mem::{self, Report, ReportAttribution, ReportKind};
 use serde::Serialize;
 use serde_json::{Map, Value};
 
 use crate::StreamId;
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
+use crate::protocol::JsonPacketStream;
 
-#[derive(Serialize)]
+/// The memory used by a document, in bytes, as shown by the memory panel.
+#[derive(Default, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct TimelineMemoryReply {
-    js_object_size: u64,
-    js_string_size: u64,
-    js_other_size: u64,
-    dom_size: u64,
-    style_size: u64,
-    other_size: u64,
-    total_size: u64,
+pub struct MemoryMeasurement {
+    total: usize,
+    dom_size: usize,
+    style_size: usize,
+    js_objects_size: usize,
+    js_strings_size: usize,
+    js_other_size: usize,
+    other_size: usize,
     js_milliseconds: f64,
     #[serde(rename = "nonJSMilliseconds")]
     non_js_milliseconds: f64,
 }
 
+#[derive(Serialize)]
+struct MeasureReply {
+    from: String,
+    #[serde(flatten)]
+    measurement: MemoryMeasurement,
+}
+
+#[derive(Serialize)]
+struct AttachReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Serialize)]
+struct GetStateReply {
+    from: String,
+    state: String,
+}
+
 pub struct MemoryActor {
     pub name: String,
+    browsing_context: String,
+    mem_profiler_chan: mem::ProfilerChan,
+    attached: Cell<bool>,
 }
 
 impl Actor for MemoryActor {
@@ -34,41 +72,142 @@ impl Actor for MemoryActor {
         self.name.clone()
     }
 
+    /// The memory actor can handle the following messages:
+    ///
+    /// - `attach`, `detach`: Start and stop following the memory of the target, which does not
+    ///   need any setup
+    ///
+    /// - `getState`: Returns whether the actor is attached
+    ///
+    /// - `measure`: Returns the memory used by the document of the target
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
-        _msg_type: &str,
+        registry: &ActorRegistry,
+        msg_type: &str,
         _msg: &Map<String, Value>,
-        _stream: &mut TcpStream,
+        stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
-        Ok(ActorMessageStatus::Ignored)
+        Ok(match msg_type {
+            "attach" | "detach" => {
+                self.attached.set(msg_type == "attach");
+                let msg = AttachReply {
+                    from: self.name(),
+                    type_: self.state(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getState" => {
+                let msg = GetStateReply {
+                    from: self.name(),
+                    state: self.state(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "measure" => {
+                let msg = MeasureReply {
+                    from: self.name(),
+                    measurement: self.measure(registry)?,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
     }
 }
 
 impl MemoryActor {
-    /// return name of actor
-    pub fn create(registry: &ActorRegistry) -> String {
-        let actor_name = registry.new_name("memory");
-        let actor = MemoryActor {
-            name: actor_name.clone(),
-        };
-
-        registry.register_later(Box::new(actor));
-        actor_name
+    pub fn new(
+        name: String,
+        browsing_context: String,
+        mem_profiler_chan: mem::ProfilerChan,
+    ) -> Self {
+        Self {
+            name,
+            browsing_context,
+            mem_profiler_chan,
+            attached: Cell::new(false),
+        }
     }
 
-    pub fn measure(&self) -> TimelineMemoryReply {
-        TimelineMemoryReply {
-            js_object_size: 1,
-            js_string_size: 1,
-            js_other_size: 1,
-            dom_size: 1,
-            style_size: 1,
-            other_size: 1,
-            total_size: 1,
-            js_milliseconds: 1.1,
-            non_js_milliseconds: 1.1,
+    fn state(&self) -> String {
+        if self.attached.get() {
+            "attached".into()
+        } else {
+            "detached".into()
+        }
+    }
+
+    /// Ask the memory profiler for a report of every process, and sum the parts of it that are
+    /// about the active document of the target.
+    pub fn measure(&self, registry: &ActorRegistry) -> Result<MemoryMeasurement, ()> {
+        let pipeline = registry
+            .find::<BrowsingContextActor>(&self.browsing_context)
+            .active_pipeline
+            .get();
+
+        let start = Instant::now();
+        let (sender, receiver) = ipc::channel().map_err(|_| ())?;
+        self.mem_profiler_chan
+            .send(mem::ProfilerMsg::Report(sender));
+        let result = receiver.recv().map_err(|_| ())?;
+        let reports: Vec<Report> = serde_json::from_str(&result.content).map_err(|_| ())?;
+
+        let mut measurement = measurement_of_pipeline(&reports, pipeline);
+        measurement.non_js_milliseconds = start.elapsed().as_secs_f64() * 1000.;
+        Ok(measurement)
+    }
+}
+
+/// Sum the `reports` about the document of `pipeline`. The JavaScript heap of an event loop is
+/// shared by the documents of the origins that it runs, so it is counted for each of them.
+fn measurement_of_pipeline(reports: &[Report], pipeline: PipelineId) -> MemoryMeasurement {
+    let mut measurement = MemoryMeasurement::default();
+
+    let Some(origin) = reports.iter().find_map(|report| match &report.attribution {
+        Some(ReportAttribution::Pipeline {
+            origin,
+            pipeline_id,
+            ..
+        }) if *pipeline_id == pipeline => Some(origin),
+        _ => None,
+    }) else {
+        return measurement;
+    };
+
+    for report in reports {
+        if matches!(report.kind, ReportKind::NonExplicitSize) {
+            continue;
+        }
+        // The paths of the reports start with segments that identify their attribution.
+        let path: Vec<&str> = report.path.iter().skip(2).map(String::as_str).collect();
+        match (&report.attribution, path.as_slice()) {
+            (Some(ReportAttribution::Origins(origins)), ["js", rest @ ..])
+                if origins.contains(origin) =>
+            {
+                match rest {
+                    ["gc-heap", "used", ..] => measurement.js_objects_size += report.size,
+                    _ => measurement.js_other_size += report.size,
+                }
+            },
+            (Some(ReportAttribution::Pipeline { pipeline_id, .. }), [_, rest @ ..])
+                if *pipeline_id == pipeline =>
+            {
+                match rest {
+                    ["dom", "computed-styles", ..] => measurement.style_size += report.size,
+                    ["dom", ..] => measurement.dom_size += report.size,
+                    _ => measurement.other_size += report.size,
+                }
+            },
+            _ => continue,
         }
+        measurement.total += report.size;
     }
+    measurement
 }
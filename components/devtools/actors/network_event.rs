@@ -8,9 +8,12 @@
 use std::net::TcpStream;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use base64::Engine;
 use chrono::{Local, LocalResult, TimeZone};
-use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
-use headers::{ContentType, Cookie, HeaderMapExt};
+use devtools_traits::{
+    HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse, HttpResponseEnd,
+};
+use headers::{ContentLength, ContentType, Cookie, HeaderMapExt};
 use http::{HeaderMap, Method, header};
 use net_traits::http_status::HttpStatus;
 use serde::Serialize;
@@ -34,13 +37,14 @@ struct HttpRequest {
 struct HttpResponse {
     headers: Option<HeaderMap>,
     status: HttpStatus,
-    body: Option<Vec<u8>>,
 }
 
 pub struct NetworkEventActor {
     pub name: String,
     request: HttpRequest,
     response: HttpResponse,
+    /// The body and timings of the response, once it has been received completely.
+    response_end: Option<HttpResponseEnd>,
     is_xhr: bool,
 }
 
@@ -126,11 +130,22 @@ struct GetResponseHeadersReply {
     raw_headers: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseContent {
+    mime_type: String,
+    text: String,
+    size: usize,
+    /// Set to `base64` when the body isn't UTF-8 text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GetResponseContentReply {
     from: String,
-    content: Option<Vec<u8>>,
+    content: Option<ResponseContent>,
     content_discarded: bool,
 }
 
@@ -154,14 +169,16 @@ struct GetResponseCookiesReply {
     cookies: Vec<u8>,
 }
 
+/// The duration of each phase of the fetch, in milliseconds.
 #[derive(Serialize)]
 struct Timings {
-    blocked: u32,
-    dns: u32,
+    blocked: u64,
+    dns: u64,
     connect: u64,
+    ssl: u64,
     send: u64,
-    wait: u32,
-    receive: u32,
+    wait: u64,
+    receive: u64,
 }
 
 #[derive(Serialize)]
@@ -288,30 +305,67 @@ impl Actor for NetworkEventActor {
                 ActorMessageStatus::Processed
             },
             "getResponseContent" => {
+                let content = self.response_end.as_ref().map(|response_end| {
+                    let (text, encoding) = match std::str::from_utf8(&response_end.body_preview) {
+                        Ok(text) => (text.to_owned(), None),
+                        // The preview may end in the middle of a character.
+                        Err(error) if error.error_len().is_none() => (
+                            String::from_utf8_lossy(
+                                &response_end.body_preview[..error.valid_up_to()],
+                            )
+                            .into_owned(),
+                            None,
+                        ),
+                        Err(_) => (
+                            base64::engine::general_purpose::STANDARD
+                                .encode(&response_end.body_preview),
+                            Some("base64"),
+                        ),
+                    };
+                    ResponseContent {
+                        mime_type: self.mime_type(),
+                        text,
+                        size: response_end.body_size,
+                        encoding,
+                    }
+                });
                 let msg = GetResponseContentReply {
                     from: self.name(),
-                    content: self.response.body.clone(),
-                    content_discarded: self.response.body.is_none(),
+                    content_discarded: content.is_none(),
+                    content,
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
             "getEventTimings" => {
-                // TODO: This is a fake timings msg
-                let timings_obj = Timings {
-                    blocked: 0,
-                    dns: 0,
-                    connect: self.request.connect_time.as_millis() as u64,
-                    send: self.request.send_time.as_millis() as u64,
-                    wait: 0,
-                    receive: 0,
+                let timings_obj = match self.response_end {
+                    Some(ref response_end) => {
+                        let timings = &response_end.timings;
+                        Timings {
+                            blocked: timings.blocked.as_millis() as u64,
+                            dns: timings.dns.as_millis() as u64,
+                            connect: timings.connect.as_millis() as u64,
+                            ssl: timings.ssl.as_millis() as u64,
+                            send: timings.send.as_millis() as u64,
+                            wait: timings.wait.as_millis() as u64,
+                            receive: timings.receive.as_millis() as u64,
+                        }
+                    },
+                    // Until the response has ended, only the request's own timings are known.
+                    None => Timings {
+                        blocked: 0,
+                        dns: 0,
+                        connect: self.request.connect_time.as_millis() as u64,
+                        ssl: 0,
+                        send: self.request.send_time.as_millis() as u64,
+                        wait: 0,
+                        receive: 0,
+                    },
                 };
-                let total = timings_obj.connect + timings_obj.send;
-                // TODO: Send the correct values for all these fields.
                 let msg = GetEventTimingsReply {
                     from: self.name(),
                     timings: timings_obj,
-                    total_time: total,
+                    total_time: self.total_time().as_millis() as u64,
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -352,8 +406,8 @@ impl NetworkEventActor {
             response: HttpResponse {
                 headers: None,
                 status: HttpStatus::default(),
-                body: None,
             },
+            response_end: None,
             is_xhr: false,
         }
     }
@@ -374,7 +428,10 @@ impl NetworkEventActor {
     pub fn add_response(&mut self, response: DevtoolsHttpResponse) {
         self.response.headers.clone_from(&response.headers);
         self.response.status = response.status;
-        self.response.body = response.body;
+    }
+
+    pub fn add_response_end(&mut self, response_end: HttpResponseEnd) {
+        self.response_end = Some(response_end);
     }
 
     pub fn event_actor(&self) -> EventActor {
@@ -420,20 +477,33 @@ impl NetworkEventActor {
         }
     }
 
+    fn mime_type(&self) -> String {
+        self.response
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.typed_get::<ContentType>())
+            .map(|content_type| content_type.to_string())
+            .unwrap_or_default()
+    }
+
     pub fn response_content(&self) -> ResponseContentMsg {
-        let mut m_string = "".to_owned();
-        if let Some(ref headers) = self.response.headers {
-            m_string = match headers.typed_get::<ContentType>() {
-                Some(ct) => ct.to_string(),
-                _ => "".to_owned(),
-            };
-        }
-        // TODO: Set correct values when response's body is sent to the devtools in http_loader.
+        let content_size = self
+            .response_end
+            .as_ref()
+            .map_or(0, |response_end| response_end.body_size);
+        // The body may have been compressed on the wire, which only the Content-Length
+        // header tells.
+        let transferred_size = self
+            .response
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.typed_get::<ContentLength>())
+            .map_or(content_size, |content_length| content_length.0 as usize);
         ResponseContentMsg {
-            mime_type: m_string,
-            content_size: 0,
-            transferred_size: 0,
-            discard_response_body: true,
+            mime_type: self.mime_type(),
+            content_size: content_size as u32,
+            transferred_size: transferred_size as u32,
+            discard_response_body: self.response_end.is_none(),
         }
     }
 
@@ -486,6 +556,9 @@ impl NetworkEventActor {
     }
 
     pub fn total_time(&self) -> Duration {
-        self.request.connect_time + self.request.send_time
+        match self.response_end {
+            Some(ref response_end) => response_end.timings.total(),
+            None => self.request.connect_time + self.request.send_time,
+        }
     }
 }
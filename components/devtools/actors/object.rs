@@ -4,7 +4,8 @@
 
 use std::net::TcpStream;
 
-use serde_json::{Map, Value};
+use devtools_traits::EvaluateJSReply;
+use serde_json::{Map, Number, Value};
 
 use crate::StreamId;
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
@@ -49,3 +50,47 @@ impl ObjectActor {
         }
     }
 }
+
+/// Encode a JS value sent by the script thread as a grip, registering an [`ObjectActor`] for
+/// objects.
+pub(crate) fn encode_js_value(registry: &ActorRegistry, value: EvaluateJSReply) -> Value {
+    let type_grip = |type_: &str| {
+        let mut m = Map::new();
+        m.insert("type".to_owned(), Value::String(type_.to_owned()));
+        Value::Object(m)
+    };
+    match value {
+        EvaluateJSReply::VoidValue => type_grip("undefined"),
+        EvaluateJSReply::NullValue => type_grip("null"),
+        EvaluateJSReply::BooleanValue(val) => Value::Bool(val),
+        EvaluateJSReply::NumberValue(val) => {
+            if val.is_nan() {
+                type_grip("NaN")
+            } else if val.is_infinite() {
+                if val < 0. {
+                    type_grip("-Infinity")
+                } else {
+                    type_grip("Infinity")
+                }
+            } else if val == 0. && val.is_sign_negative() {
+                type_grip("-0")
+            } else {
+                Value::Number(Number::from_f64(val).unwrap())
+            }
+        },
+        EvaluateJSReply::StringValue(s) => Value::String(s),
+        EvaluateJSReply::ActorValue { class, uuid } => {
+            // TODO: Make initial ActorValue message include these properties?
+            let mut m = Map::new();
+            let actor = ObjectActor::register(registry, uuid);
+
+            m.insert("type".to_owned(), Value::String("object".to_owned()));
+            m.insert("class".to_owned(), Value::String(class));
+            m.insert("actor".to_owned(), Value::String(actor));
+            m.insert("extensible".to_owned(), Value::Bool(true));
+            m.insert("frozen".to_owned(), Value::Bool(false));
+            m.insert("sealed".to_owned(), Value::Bool(false));
+            Value::Object(m)
+        },
+    }
+}
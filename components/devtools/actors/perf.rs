@@ -0,0 +1,233 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Records performance profiles of the whole browser for the performance panel. The work of
+//! every process is recorded by the time profiler as a Chrome trace, which the Firefox profiler
+//! is able to import.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/perf.js
+
+use std::cell::RefCell;
+use std::fs;
+use std::net::TcpStream;
+use std::path::PathBuf;
+
+use ipc_channel::ipc;
+use log::warn;
+use profile_traits::time::{ProfilerChan, ProfilerMsg};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use crate::{EmptyReplyMsg, StreamId};
+
+#[derive(Serialize)]
+struct ValueReply<T> {
+    from: String,
+    value: T,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilerStartedEvent {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    entries: Option<Value>,
+    interval: Option<Value>,
+    features: Option<Value>,
+    duration: Option<Value>,
+    #[serde(rename = "activeTabID")]
+    active_tab_id: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct ProfilerStoppedEvent {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+pub struct PerfActor {
+    name: String,
+    time_profiler_chan: ProfilerChan,
+    /// The file that the recording in progress, if any, is written to.
+    trace_path: RefCell<Option<PathBuf>>,
+}
+
+impl Actor for PerfActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The perf actor can handle the following messages:
+    ///
+    /// - `isActive`: Returns whether a profile is being recorded
+    ///
+    /// - `isSupportedPlatform`: Returns whether profiles can be recorded at all
+    ///
+    /// - `isLockedForPrivateBrowsing`: Returns whether recording is disabled because of private
+    ///   browsing, which never happens here
+    ///
+    /// - `getSupportedFeatures`: Returns the optional features of the Gecko profiler that can be
+    ///   enabled, none of which are supported
+    ///
+    /// - `startProfiler`: Starts recording a profile, replacing the recording in progress, if any
+    ///
+    /// - `stopProfilerAndDiscardProfile`: Stops the recording and forgets about it
+    ///
+    /// - `getProfileAndStopProfiler`: Stops the recording and returns the profile
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "isActive" => {
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: self.trace_path.borrow().is_some(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "isSupportedPlatform" => {
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: true,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "isLockedForPrivateBrowsing" => {
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: false,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getSupportedFeatures" => {
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: Vec::<String>::new(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "startProfiler" => {
+                // The settings are not used by the time profiler, but the client expects to be
+                // told about them.
+                let options = recording_options(msg);
+                self.start();
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: true,
+                };
+                let _ = stream.write_json_packet(&msg);
+
+                let event = ProfilerStartedEvent {
+                    from: self.name(),
+                    type_: "profiler-started".into(),
+                    entries: options.get("entries").cloned(),
+                    interval: options.get("interval").cloned(),
+                    features: options.get("features").cloned(),
+                    duration: options.get("duration").cloned(),
+                    active_tab_id: options.get("activeTabID").cloned(),
+                };
+                let _ = stream.write_json_packet(&event);
+                ActorMessageStatus::Processed
+            },
+
+            "stopProfilerAndDiscardProfile" => {
+                if let Some(path) = self.stop() {
+                    let _ = fs::remove_file(path);
+                }
+                let _ = stream.write_json_packet(&EmptyReplyMsg { from: self.name() });
+                self.notify_stopped(stream);
+                ActorMessageStatus::Processed
+            },
+
+            "getProfileAndStopProfiler" => {
+                let path = self.stop().ok_or(())?;
+                let profile = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|trace| serde_json::from_str::<Value>(&trace).ok());
+                let _ = fs::remove_file(&path);
+                let Some(profile) = profile else {
+                    warn!("Failed to read the recorded profile at {}", path.display());
+                    return Err(());
+                };
+                let msg = ValueReply {
+                    from: self.name(),
+                    value: profile,
+                };
+                let _ = stream.write_json_packet(&msg);
+                self.notify_stopped(stream);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+/// The settings of the recording, which are given in the `options` of `startProfiler`.
+fn recording_options(msg: &Map<String, Value>) -> Map<String, Value> {
+    msg.get("options")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+impl PerfActor {
+    pub fn new(name: String, time_profiler_chan: ProfilerChan) -> Self {
+        Self {
+            name,
+            time_profiler_chan,
+            trace_path: RefCell::new(None),
+        }
+    }
+
+    /// Start recording into a temporary file. Starting again while recording overwrites the
+    /// file of the recording in progress.
+    fn start(&self) {
+        let path = std::env::temp_dir().join(format!(
+            "servo-devtools-profile-{}-{}.json",
+            std::process::id(),
+            self.name
+        ));
+        self.time_profiler_chan
+            .send(ProfilerMsg::StartChromeTrace(path.clone()));
+        *self.trace_path.borrow_mut() = Some(path);
+    }
+
+    /// Stop the recording in progress and return the path of its complete file.
+    fn stop(&self) -> Option<PathBuf> {
+        let path = self.trace_path.borrow_mut().take()?;
+        let (sender, receiver) = ipc::channel().ok()?;
+        self.time_profiler_chan
+            .send(ProfilerMsg::StopChromeTrace(sender));
+        receiver.recv().ok()?;
+        Some(path)
+    }
+
+    fn notify_stopped(&self, stream: &mut TcpStream) {
+        let event = ProfilerStoppedEvent {
+            from: self.name(),
+            type_: "profiler-stopped".into(),
+        };
+        let _ = stream.write_json_packet(&event);
+    }
+}
@@ -48,6 +48,7 @@ struct GetRootReply {
     from: String,
     selected: u32,
     performance_actor: String,
+    perf_actor: String,
     device_actor: String,
     preference_actor: String,
 }
@@ -127,6 +128,7 @@ pub struct RootActor {
     pub tabs: Vec<String>,
     pub workers: Vec<String>,
     pub performance: String,
+    pub perf: String,
     pub device: String,
     pub preference: String,
     pub process: String,
@@ -189,6 +191,7 @@ impl Actor for RootActor {
                     from: "root".to_owned(),
                     selected: 0,
                     performance_actor: self.performance.clone(),
+                    perf_actor: self.perf.clone(),
                     device_actor: self.device.clone(),
                     preference_actor: self.preference.clone(),
                 };
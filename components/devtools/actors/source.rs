@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A script source seen by the debugger. It serves the text of the source, which the client
+//! pretty-prints and maps through the source map on its own, and the positions where breakpoints
+//! can be set.
+//!
+//! Liberally derived from the [Firefox JS implementation].
+//!
+//! [Firefox JS implementation]: https://searchfox.org/mozilla-central/source/devtools/server/actors/source.js
+
+use std::collections::BTreeMap;
+use std::net::TcpStream;
+
+use base::id::PipelineId;
+use devtools_traits::{DevtoolScriptControlMsg, SourceInfo, SourceLocation};
+use ipc_channel::ipc::{self, IpcSender};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::StreamId;
+use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SourceForm {
+    actor: String,
+    url: String,
+    is_black_boxed: bool,
+    introduction_type: Option<String>,
+    #[serde(rename = "sourceMapBaseURL")]
+    source_map_base_url: String,
+    #[serde(rename = "sourceMapURL")]
+    source_map_url: Option<String>,
+    extension_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceContentReply {
+    from: String,
+    source: String,
+    content_type: String,
+}
+
+#[derive(Serialize)]
+struct BreakpointPositionsReply {
+    from: String,
+    positions: BTreeMap<u32, Vec<u32>>,
+}
+
+#[derive(Serialize)]
+struct BreakableLinesReply {
+    from: String,
+    lines: Vec<u32>,
+}
+
+pub(crate) struct SourceActor {
+    name: String,
+    pub pipeline: PipelineId,
+    info: SourceInfo,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl Actor for SourceActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The source actor can handle the following messages:
+    ///
+    /// - `source`: Returns the text of the source
+    ///
+    /// - `getBreakpointPositionsCompressed`: Returns the columns where breakpoints can be set,
+    ///   grouped by line
+    ///
+    /// - `getBreakableLines`: Returns the lines where breakpoints can be set
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+        _id: StreamId,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "source" => {
+                let msg = SourceContentReply {
+                    from: self.name(),
+                    source: self.info.content.clone(),
+                    content_type: "text/javascript".into(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getBreakpointPositionsCompressed" => {
+                let mut positions = BTreeMap::<u32, Vec<u32>>::new();
+                for location in self.possible_breakpoints()? {
+                    positions
+                        .entry(location.line)
+                        .or_default()
+                        .push(location.column);
+                }
+                let msg = BreakpointPositionsReply {
+                    from: self.name(),
+                    positions,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "getBreakableLines" => {
+                let mut lines: Vec<u32> = self
+                    .possible_breakpoints()?
+                    .into_iter()
+                    .map(|location| location.line)
+                    .collect();
+                lines.dedup();
+                let msg = BreakableLinesReply {
+                    from: self.name(),
+                    lines,
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl SourceActor {
+    pub fn new(
+        name: String,
+        pipeline: PipelineId,
+        info: SourceInfo,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+    ) -> Self {
+        Self {
+            name,
+            pipeline,
+            info,
+            script_chan,
+        }
+    }
+
+    pub fn spidermonkey_id(&self) -> u32 {
+        self.info.spidermonkey_id
+    }
+
+    /// Ask the script thread where breakpoints can be set, sorted by line and column.
+    fn possible_breakpoints(&self) -> Result<Vec<SourceLocation>, ()> {
+        let (tx, rx) = ipc::channel().map_err(|_| ())?;
+        self.script_chan
+            .send(DevtoolScriptControlMsg::GetPossibleBreakpoints(
+                self.info.spidermonkey_id,
+                tx,
+            ))
+            .map_err(|_| ())?;
+        rx.recv().map_err(|_| ())
+    }
+
+    pub fn encodable(&self) -> SourceForm {
+        SourceForm {
+            actor: self.name(),
+            url: self.info.url.to_string(),
+            is_black_boxed: false,
+            introduction_type: self.info.introduction_type.clone(),
+            source_map_base_url: self.info.url.to_string(),
+            source_map_url: self.info.source_map_url.clone(),
+            extension_name: None,
+        }
+    }
+}
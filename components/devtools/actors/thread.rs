@@ -2,12 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::RefCell;
 use std::net::TcpStream;
 
+use base::id::PipelineId;
+use devtools_traits::{DevtoolScriptControlMsg, PauseInfo, PauseReason, ResumeLimit};
+use ipc_channel::ipc::IpcSender;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::frame::{FrameActor, FrameForm};
+use crate::actors::source::{SourceActor, SourceForm};
 use crate::protocol::JsonPacketStream;
 use crate::{EmptyReplyMsg, StreamId};
 
@@ -29,6 +35,16 @@ struct ThreadAttached {
 #[derive(Serialize)]
 enum PoppedFrameMsg {}
 
+#[derive(Serialize)]
+struct ThreadPaused {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    actor: String,
+    frame: Option<FrameForm>,
+    why: WhyMsg,
+}
+
 #[derive(Serialize)]
 struct WhyMsg {
     #[serde(rename = "type")]
@@ -52,19 +68,116 @@ struct ThreadInterruptedReply {
 #[derive(Serialize)]
 struct SourcesReply {
     from: String,
-    sources: Vec<Source>,
+    sources: Vec<SourceForm>,
 }
 
 #[derive(Serialize)]
-enum Source {}
+struct FramesReply {
+    from: String,
+    frames: Vec<FrameForm>,
+}
 
 pub struct ThreadActor {
     name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    /// The source actors of the sources seen by the debugger, in the order they were compiled.
+    sources: RefCell<Vec<String>>,
+    /// The frame actors of the stack while the thread is paused, youngest first.
+    frames: RefCell<Vec<String>>,
 }
 
 impl ThreadActor {
-    pub fn new(name: String) -> ThreadActor {
-        ThreadActor { name }
+    pub fn new(name: String, script_chan: IpcSender<DevtoolScriptControlMsg>) -> ThreadActor {
+        ThreadActor {
+            name,
+            script_chan,
+            sources: Default::default(),
+            frames: Default::default(),
+        }
+    }
+
+    pub(crate) fn add_source(&self, source: String) {
+        self.sources.borrow_mut().push(source);
+    }
+
+    /// The forms of the sources compiled in the given pipeline.
+    pub(crate) fn source_forms(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+    ) -> Vec<SourceForm> {
+        self.sources
+            .borrow()
+            .iter()
+            .map(|name| registry.find::<SourceActor>(name))
+            .filter(|source| source.pipeline == pipeline)
+            .map(SourceActor::encodable)
+            .collect()
+    }
+
+    /// Create actors for the stack of the paused script thread, and let the clients know that
+    /// the thread paused.
+    pub(crate) fn paused<'a>(
+        &self,
+        registry: &ActorRegistry,
+        pipeline: PipelineId,
+        info: PauseInfo,
+        streams: impl Iterator<Item = &'a mut TcpStream>,
+    ) {
+        self.clear_frames(registry);
+
+        let frame_count = info.frames.len();
+        let frames: Vec<FrameActor> = info
+            .frames
+            .into_iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let source = self
+                    .sources
+                    .borrow()
+                    .iter()
+                    .find(|name| {
+                        let source = registry.find::<SourceActor>(name);
+                        source.pipeline == pipeline && source.spidermonkey_id() == frame.source_id
+                    })
+                    .cloned()
+                    .unwrap_or_default();
+                let oldest = index + 1 == frame_count;
+                FrameActor::new(registry.new_name("frame"), frame, source, oldest)
+            })
+            .collect();
+
+        let msg = ThreadPaused {
+            from: self.name(),
+            type_: "paused".to_owned(),
+            actor: registry.new_name("pause"),
+            frame: frames.first().map(FrameActor::encodable),
+            why: WhyMsg {
+                type_: match info.reason {
+                    PauseReason::Breakpoint => "breakpoint",
+                    PauseReason::DebuggerStatement => "debuggerStatement",
+                    PauseReason::Interrupted => "interrupted",
+                    PauseReason::ResumeLimit => "resumeLimit",
+                }
+                .to_owned(),
+            },
+        };
+
+        let mut frame_names = self.frames.borrow_mut();
+        for frame in frames {
+            frame_names.push(frame.name());
+            registry.register_later(Box::new(frame));
+        }
+
+        for stream in streams {
+            let _ = stream.write_json_packet(&msg);
+        }
+    }
+
+    fn clear_frames(&self, registry: &ActorRegistry) {
+        for frame in self.frames.borrow_mut().drain(..) {
+            registry.drop_actor_later(frame);
+        }
     }
 }
 
@@ -77,7 +190,7 @@ impl Actor for ThreadActor {
         &self,
         registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
@@ -102,6 +215,21 @@ impl Actor for ThreadActor {
             },
 
             "resume" => {
+                let limit = match msg
+                    .get("resumeLimit")
+                    .and_then(|limit| limit.get("type"))
+                    .and_then(Value::as_str)
+                {
+                    Some("next") => ResumeLimit::StepOver,
+                    Some("step") => ResumeLimit::StepIn,
+                    Some("finish") => ResumeLimit::StepOut,
+                    _ => ResumeLimit::None,
+                };
+                self.clear_frames(registry);
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::Resume(limit))
+                    .map_err(|_| ())?;
+
                 let msg = ThreadResumedReply {
                     from: self.name(),
                     type_: "resumed".to_owned(),
@@ -112,6 +240,10 @@ impl Actor for ThreadActor {
             },
 
             "interrupt" => {
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::Interrupt)
+                    .map_err(|_| ())?;
+
                 let msg = ThreadInterruptedReply {
                     from: self.name(),
                     type_: "interrupted".to_owned(),
@@ -128,7 +260,33 @@ impl Actor for ThreadActor {
             "sources" => {
                 let msg = SourcesReply {
                     from: self.name(),
-                    sources: vec![], // TODO: Add sources for the debugger here
+                    sources: self
+                        .sources
+                        .borrow()
+                        .iter()
+                        .map(|name| registry.find::<SourceActor>(name).encodable())
+                        .collect(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "frames" => {
+                let start = msg.get("start").and_then(Value::as_u64).unwrap_or(0) as usize;
+                let count = msg
+                    .get("count")
+                    .and_then(Value::as_u64)
+                    .map_or(usize::MAX, |count| count as usize);
+                let msg = FramesReply {
+                    from: self.name(),
+                    frames: self
+                        .frames
+                        .borrow()
+                        .iter()
+                        .skip(start)
+                        .take(count)
+                        .map(|name| registry.find::<FrameActor>(name).encodable())
+                        .collect(),
                 };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -138,3 +296,115 @@ impl Actor for ThreadActor {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::net::{TcpListener, TcpStream};
+
+    use devtools_traits::{DevtoolScriptControlMsg, ResumeLimit};
+    use ipc_channel::ipc;
+    use serde_json::{Value, json};
+
+    use crate::StreamId;
+    use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+    use crate::actors::thread::ThreadActor;
+    use crate::protocol::JsonPacketStream;
+
+    fn connected_streams() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    fn handle_message(
+        actor: &ThreadActor,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        msg: Value,
+        stream: &mut TcpStream,
+    ) -> ActorMessageStatus {
+        actor
+            .handle_message(
+                registry,
+                msg_type,
+                msg.as_object().unwrap(),
+                stream,
+                StreamId(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resume_limits() {
+        let registry = ActorRegistry::new();
+        let (script_sender, script_receiver) = ipc::channel().unwrap();
+        let actor = ThreadActor::new(registry.new_name("thread"), script_sender);
+        let (mut server, mut client) = connected_streams();
+
+        for (resume_limit, expected) in [
+            (json!(null), ResumeLimit::None),
+            (json!({ "type": "next" }), ResumeLimit::StepOver),
+            (json!({ "type": "step" }), ResumeLimit::StepIn),
+            (json!({ "type": "finish" }), ResumeLimit::StepOut),
+        ] {
+            let msg = json!({ "resumeLimit": resume_limit });
+            assert!(matches!(
+                handle_message(&actor, &registry, "resume", msg, &mut server),
+                ActorMessageStatus::Processed
+            ));
+            let Ok(DevtoolScriptControlMsg::Resume(limit)) = script_receiver.recv() else {
+                panic!("The script thread should be resumed");
+            };
+            assert_eq!(limit, expected);
+            assert_eq!(
+                client.read_json_packet().unwrap(),
+                Some(json!({ "from": actor.name(), "type": "resumed" }))
+            );
+            assert_eq!(
+                client.read_json_packet().unwrap(),
+                Some(json!({ "from": actor.name() }))
+            );
+        }
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let registry = ActorRegistry::new();
+        let (script_sender, script_receiver) = ipc::channel().unwrap();
+        let actor = ThreadActor::new(registry.new_name("thread"), script_sender);
+        let (mut server, mut client) = connected_streams();
+
+        assert!(matches!(
+            handle_message(&actor, &registry, "interrupt", json!({}), &mut server),
+            ActorMessageStatus::Processed
+        ));
+        assert!(matches!(
+            script_receiver.recv(),
+            Ok(DevtoolScriptControlMsg::Interrupt)
+        ));
+        assert_eq!(
+            client.read_json_packet().unwrap(),
+            Some(json!({ "from": actor.name(), "type": "interrupted" }))
+        );
+    }
+
+    #[test]
+    fn test_frames_without_pause() {
+        let registry = ActorRegistry::new();
+        let (script_sender, _script_receiver) = ipc::channel().unwrap();
+        let actor = ThreadActor::new(registry.new_name("thread"), script_sender);
+        let (mut server, mut client) = connected_streams();
+
+        handle_message(&actor, &registry, "frames", json!({}), &mut server);
+        assert_eq!(
+            client.read_json_packet().unwrap(),
+            Some(json!({ "from": actor.name(), "frames": [] }))
+        );
+        handle_message(&actor, &registry, "sources", json!({}), &mut server);
+        assert_eq!(
+            client.read_json_packet().unwrap(),
+            Some(json!({ "from": actor.name(), "sources": [] }))
+        );
+    }
+}
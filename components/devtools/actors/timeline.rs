@@ -23,7 +23,7 @@ use serde_json::{Map, Value};
 use crate::StreamId;
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::actors::framerate::FramerateActor;
-use crate::actors::memory::{MemoryActor, TimelineMemoryReply};
+use crate::actors::memory::{MemoryActor, MemoryMeasurement};
 use crate::protocol::JsonPacketStream;
 
 pub struct TimelineActor {
@@ -35,6 +35,8 @@ pub struct TimelineActor {
     stream: RefCell<Option<TcpStream>>,
 
     framerate_actor: RefCell<Option<String>>,
+    /// The memory actor of the target, which measures its memory while recording with memory.
+    target_memory_actor: String,
     memory_actor: RefCell<Option<String>>,
 }
 
@@ -92,7 +94,7 @@ struct MemoryEmitterReply {
     type_: String,
     from: String,
     delta: HighResolutionStamp,
-    measurement: TimelineMemoryReply,
+    measurement: MemoryMeasurement,
 }
 
 #[derive(Serialize)]
@@ -134,6 +136,7 @@ impl TimelineActor {
         name: String,
         pipeline_id: PipelineId,
         script_sender: IpcSender<DevtoolScriptControlMsg>,
+        target_memory_actor: String,
     ) -> TimelineActor {
         let marker_types = vec![TimelineMarkerType::Reflow, TimelineMarkerType::DOMEvent];
 
@@ -146,6 +149,7 @@ impl TimelineActor {
             stream: RefCell::new(None),
 
             framerate_actor: RefCell::new(None),
+            target_memory_actor,
             memory_actor: RefCell::new(None),
         }
     }
@@ -213,10 +217,10 @@ impl Actor for TimelineActor {
                 //TODO: support multiple connections by using root actor's streams instead.
                 *self.stream.borrow_mut() = stream.try_clone().ok();
 
-                // init memory actor
+                // use the memory actor of the target
                 if let Some(with_memory) = msg.get("withMemory") {
                     if let Some(true) = with_memory.as_bool() {
-                        *self.memory_actor.borrow_mut() = Some(MemoryActor::create(registry));
+                        *self.memory_actor.borrow_mut() = Some(self.target_memory_actor.clone());
                     }
                 }
 
@@ -276,9 +280,7 @@ impl Actor for TimelineActor {
                     registry.drop_actor_later(actor_name.clone());
                 }
 
-                if let Some(ref actor_name) = *self.memory_actor.borrow() {
-                    registry.drop_actor_later(actor_name.clone());
-                }
+                self.memory_actor.borrow_mut().take();
 
                 **self.is_recording.lock().as_mut().unwrap() = false;
                 self.stream.borrow_mut().take();
@@ -356,13 +358,15 @@ impl Emitter {
         if let Some(ref actor_name) = self.memory_actor {
             let registry = self.registry.lock().unwrap();
             let memory_actor = registry.find::<MemoryActor>(actor_name);
-            let memory_reply = MemoryEmitterReply {
-                type_: "memory".to_owned(),
-                from: memory_actor.name(),
-                delta: HighResolutionStamp::new(self.start_stamp, end_time),
-                measurement: memory_actor.measure(),
-            };
-            self.stream.write_json_packet(&memory_reply)?;
+            if let Ok(measurement) = memory_actor.measure(&registry) {
+                let memory_reply = MemoryEmitterReply {
+                    type_: "memory".to_owned(),
+                    from: memory_actor.name(),
+                    delta: HighResolutionStamp::new(self.start_stamp, end_time),
+                    measurement,
+                };
+                self.stream.write_json_packet(&memory_reply)?;
+            }
         }
 
         Ok(())
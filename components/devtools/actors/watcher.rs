@@ -14,13 +14,16 @@ use std::collections::HashMap;
 use std::net::TcpStream;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use devtools_traits::DevtoolScriptControlMsg;
 use log::warn;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
 use self::network_parent::{NetworkParentActor, NetworkParentActorMsg};
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::breakpoint::{BreakpointListActor, BreakpointListActorMsg};
 use crate::actors::browsing_context::{BrowsingContextActor, BrowsingContextActorMsg};
+use crate::actors::thread::ThreadActor;
 use crate::actors::watcher::target_configuration::{
     TargetConfigurationActor, TargetConfigurationActorMsg,
 };
@@ -78,7 +81,7 @@ impl SessionContext {
                 ("network-event-stacktrace", false),
                 ("reflow", false),
                 ("stylesheet", false),
-                ("source", false),
+                ("source", true),
                 ("thread-state", false),
                 ("server-sent-event", false),
                 ("websocket", false),
@@ -121,6 +124,13 @@ struct GetNetworkParentActorReply {
     network: NetworkParentActorMsg,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBreakpointListActorReply {
+    from: String,
+    breakpoint_list: BreakpointListActorMsg,
+}
+
 #[derive(Serialize)]
 struct GetTargetConfigurationActorReply {
     from: String,
@@ -162,6 +172,7 @@ pub struct WatcherActorMsg {
 pub struct WatcherActor {
     name: String,
     browsing_context_actor: String,
+    breakpoint_list: String,
     network_parent: String,
     target_configuration: String,
     thread_configuration: String,
@@ -189,6 +200,9 @@ impl Actor for WatcherActor {
     ///   that the server can update its settings.
     ///
     /// - `getThreadConfigurationActor`: The same but with the configuration actor for the thread
+    ///
+    /// - `getBreakpointListActor`: Returns the actor that sets breakpoints in the sources of the
+    ///   target
     fn handle_message(
         &self,
         registry: &ActorRegistry,
@@ -249,6 +263,17 @@ impl Actor for WatcherActor {
                                 target.resource_available(event, "document-event".into());
                             }
                         },
+                        "source" => {
+                            // Documents are only debugged once a client watches their sources,
+                            // which are then reported as they are found.
+                            let pipeline = target.active_pipeline.get();
+                            let attach = DevtoolScriptControlMsg::AttachDebugger(pipeline);
+                            let _ = target.script_chan.send(attach);
+                            let thread = registry.find::<ThreadActor>(&target.thread);
+                            for source in thread.source_forms(registry, pipeline) {
+                                target.resource_available(source, "source".into());
+                            }
+                        },
                         "console-message" | "error-message" => {},
                         _ => warn!("resource {} not handled yet", resource),
                     }
@@ -296,6 +321,15 @@ impl Actor for WatcherActor {
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
+            "getBreakpointListActor" => {
+                let breakpoint_list = registry.find::<BreakpointListActor>(&self.breakpoint_list);
+                let msg = GetBreakpointListActorReply {
+                    from: self.name(),
+                    breakpoint_list: breakpoint_list.encodable(),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
             _ => ActorMessageStatus::Ignored,
         })
     }
@@ -307,6 +341,10 @@ impl WatcherActor {
         browsing_context_actor: String,
         session_context: SessionContext,
     ) -> Self {
+        let breakpoint_list = BreakpointListActor::new(
+            actors.new_name("breakpoint-list"),
+            browsing_context_actor.clone(),
+        );
         let network_parent = NetworkParentActor::new(actors.new_name("network-parent"));
         let target_configuration =
             TargetConfigurationActor::new(actors.new_name("target-configuration"));
@@ -316,12 +354,14 @@ impl WatcherActor {
         let watcher = Self {
             name: actors.new_name("watcher"),
             browsing_context_actor,
+            breakpoint_list: breakpoint_list.name(),
             network_parent: network_parent.name(),
             target_configuration: target_configuration.name(),
             thread_configuration: thread_configuration.name(),
             session_context,
         };
 
+        actors.register(Box::new(breakpoint_list));
         actors.register(Box::new(network_parent));
         actors.register(Box::new(target_configuration));
         actors.register(Box::new(thread_configuration));
@@ -0,0 +1,1507 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A server for the [Chrome DevTools Protocol], which lets automation libraries such as Puppeteer
+//! and Playwright drive Servo. It runs alongside the Firefox devtools server and is told about the
+//! same pages, but it listens on a port of its own, and only on the loopback interface.
+//!
+//! Clients find the targets over HTTP, at `/json/version` and `/json/list`, and then open a
+//! WebSocket to the browser or to one of its pages. The commands of the `Target`, `Browser`,
+//! `Page`, `Runtime`, `DOM` and `Network` domains are supported as far as Servo is able to answer
+//! them, and the other commands fail with a "method not found" error. Only the "flatten" mode of
+//! sessions is supported, in which the messages of every session share the connection of the
+//! client and carry the id of their session.
+//!
+//! [Chrome DevTools Protocol]: https://chromedevtools.github.io/devtools-protocol/
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base::id::{BrowsingContextId, PipelineId};
+use crossbeam_channel::{Sender, unbounded};
+use devtools_traits::{
+    ConsoleMessage, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState, NetworkEvent,
+    PageError, ReadyState,
+};
+use ipc_channel::ipc::{self, IpcSender};
+use log::{info, warn};
+use net_traits::local_server::{self, RejectedRequest};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+use uuid::Uuid;
+
+use crate::cdp::dom::NodeIds;
+use crate::cdp::network::NetworkRequests;
+
+mod dom;
+mod network;
+mod page;
+mod runtime;
+mod target;
+
+/// The version of the protocol that is implemented.
+const PROTOCOL_VERSION: &str = "1.3";
+
+/// How long reading from a WebSocket may block before the queued events are sent.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The error codes of JSON-RPC that are used by the protocol.
+const SERVER_ERROR: i64 = -32000;
+const SESSION_NOT_FOUND: i64 = -32001;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A command sent by a client.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Command {
+    id: u64,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    session_id: Option<String>,
+}
+
+/// The reply to a command, which has either a result or an error.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandReply {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<CommandError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Event<'a, T> {
+    method: &'a str,
+    params: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct CommandError {
+    code: i64,
+    message: String,
+}
+
+impl CommandError {
+    pub(crate) fn server_error(message: impl Into<String>) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+
+    fn method_not_found(method: &str) -> Self {
+        Self {
+            code: METHOD_NOT_FOUND,
+            message: format!("'{}' wasn't found", method),
+        }
+    }
+}
+
+pub(crate) type CommandResult = Result<Value, CommandError>;
+
+/// Read the parameters of a command, which may be left out when none of them are required.
+pub(crate) fn parse_params<T: DeserializeOwned>(params: &Value) -> Result<T, CommandError> {
+    let params = match params {
+        Value::Null => Value::Object(Map::new()),
+        params => params.clone(),
+    };
+    serde_json::from_value(params).map_err(|error| CommandError::invalid_params(error.to_string()))
+}
+
+pub(crate) fn command_result<T: Serialize>(result: T) -> CommandResult {
+    serde_json::to_value(result).map_err(|error| CommandError::server_error(error.to_string()))
+}
+
+pub(crate) fn empty_result() -> CommandResult {
+    Ok(Value::Object(Map::new()))
+}
+
+/// A new id for a target, a session or a navigation, in the format that Chrome uses.
+fn new_id() -> String {
+    Uuid::new_v4().simple().to_string().to_uppercase()
+}
+
+/// The time of an event, in seconds since the Unix epoch.
+pub(crate) fn timestamp() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+fn send_event<T: Serialize>(
+    sender: &Sender<String>,
+    session_id: Option<&str>,
+    method: &str,
+    params: T,
+) {
+    let event = Event {
+        method,
+        params,
+        session_id,
+    };
+    if let Ok(message) = serde_json::to_string(&event) {
+        let _ = sender.send(message);
+    }
+}
+
+/// A top-level browsing context, which is a target of the `page` type.
+pub(crate) struct PageTarget {
+    /// The id of the target, which is also the id of its main frame.
+    pub id: String,
+    browsing_context: BrowsingContextId,
+    pub pipeline: PipelineId,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pub url: String,
+    pub title: String,
+    /// The pipeline of the document that the clients were told about.
+    announced_pipeline: Option<PipelineId>,
+    /// The id of the navigation to the current document.
+    pub loader_id: String,
+    /// The id to give to the next navigation, which was requested with `Page.navigate`.
+    pub pending_loader_id: Option<String>,
+    /// The id of the JavaScript execution context of the current document.
+    pub execution_context_id: u32,
+    /// The nodes of the current document that the clients were told about.
+    pub nodes: NodeIds,
+    pub requests: NetworkRequests,
+}
+
+impl PageTarget {
+    /// Ask the script thread of the page for something, and wait for its reply.
+    pub(crate) fn request<T>(
+        &self,
+        message: impl FnOnce(PipelineId, IpcSender<T>) -> DevtoolScriptControlMsg,
+    ) -> Result<T, CommandError>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let (sender, receiver) =
+            ipc::channel().map_err(|error| CommandError::server_error(error.to_string()))?;
+        self.script_chan
+            .send(message(self.pipeline, sender))
+            .map_err(|_| CommandError::server_error("The page is gone"))?;
+        receiver
+            .recv()
+            .map_err(|_| CommandError::server_error("The page is gone"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct ClientId(u32);
+
+/// A WebSocket connection.
+struct Client {
+    /// Where the messages for the client are queued until they are sent.
+    sender: Sender<String>,
+    /// The page that the connection was opened for, or `None` for a connection to the browser.
+    page: Option<String>,
+    /// Whether the client is told about the targets as they are created and changed.
+    discover_targets: bool,
+    /// Whether the client is attached to every page as soon as it is created.
+    auto_attach: bool,
+}
+
+/// The domains that a session enabled, and whose events it is sent.
+#[derive(Default)]
+pub(crate) struct EnabledDomains {
+    pub page: bool,
+    pub lifecycle_events: bool,
+    pub runtime: bool,
+    pub dom: bool,
+    pub network: bool,
+}
+
+/// A client attached to a page. The session of a connection that was opened for a page has no
+/// id, and it is the one that the commands without a session id are for.
+struct Session {
+    id: Option<String>,
+    client: ClientId,
+    target: String,
+    domains: EnabledDomains,
+}
+
+/// A session and the page that it is attached to, for the commands of the page domains.
+pub(crate) struct PageSession<'a> {
+    id: Option<String>,
+    pub domains: &'a mut EnabledDomains,
+    pub target: &'a mut PageTarget,
+    events: &'a Sender<String>,
+}
+
+impl PageSession<'_> {
+    /// Send an event to the client of the session.
+    pub(crate) fn emit<T: Serialize>(&self, method: &str, params: T) {
+        send_event(self.events, self.id.as_deref(), method, params);
+    }
+}
+
+/// The state that is shared by the connections and the devtools thread.
+struct CdpState {
+    /// The id of the target of the browser itself.
+    browser_id: String,
+    targets: Vec<PageTarget>,
+    clients: HashMap<ClientId, Client>,
+    sessions: Vec<Session>,
+    next_client_id: u32,
+    next_execution_context_id: u32,
+}
+
+impl CdpState {
+    fn new(browser_id: String) -> Self {
+        Self {
+            browser_id,
+            targets: vec![],
+            clients: HashMap::new(),
+            sessions: vec![],
+            next_client_id: 0,
+            next_execution_context_id: 0,
+        }
+    }
+
+    fn add_client(&mut self, sender: Sender<String>, page: Option<String>) -> ClientId {
+        let id = ClientId(self.next_client_id);
+        self.next_client_id += 1;
+        if let Some(target) = &page {
+            self.sessions.push(Session {
+                id: None,
+                client: id,
+                target: target.clone(),
+                domains: Default::default(),
+            });
+        }
+        self.clients.insert(
+            id,
+            Client {
+                sender,
+                page,
+                discover_targets: false,
+                auto_attach: false,
+            },
+        );
+        id
+    }
+
+    fn remove_client(&mut self, client: ClientId) {
+        self.clients.remove(&client);
+        self.sessions.retain(|session| session.client != client);
+    }
+
+    fn target(&self, id: &str) -> Option<&PageTarget> {
+        self.targets.iter().find(|target| target.id == id)
+    }
+
+    fn next_execution_context_id(&mut self) -> u32 {
+        self.next_execution_context_id += 1;
+        self.next_execution_context_id
+    }
+
+    /// Reply to a message of a client.
+    fn handle_message(&mut self, client: ClientId, message: &str) -> String {
+        let reply = match serde_json::from_str::<Command>(message) {
+            Ok(command) => {
+                let (result, error) = match self.handle_command(client, &command) {
+                    Ok(result) => (Some(result), None),
+                    Err(error) => (None, Some(error)),
+                };
+                CommandReply {
+                    id: Some(command.id),
+                    result,
+                    error,
+                    session_id: command.session_id,
+                }
+            },
+            Err(error) => CommandReply {
+                id: None,
+                result: None,
+                error: Some(CommandError {
+                    code: INVALID_REQUEST,
+                    message: error.to_string(),
+                }),
+                session_id: None,
+            },
+        };
+        serde_json::to_string(&reply).unwrap_or_default()
+    }
+
+    fn handle_command(&mut self, client: ClientId, command: &Command) -> CommandResult {
+        let method = command.method.as_str();
+        let params = &command.params;
+        let session = match &command.session_id {
+            Some(id) => Some(
+                self.sessions
+                    .iter()
+                    .position(|session| {
+                        session.client == client && session.id.as_deref() == Some(id)
+                    })
+                    .ok_or(CommandError {
+                        code: SESSION_NOT_FOUND,
+                        message: "Session with given id not found.".into(),
+                    })?,
+            ),
+            None => self
+                .sessions
+                .iter()
+                .position(|session| session.client == client && session.id.is_none()),
+        };
+
+        let Some((domain, _)) = method.split_once('.') else {
+            return Err(CommandError::method_not_found(method));
+        };
+        match domain {
+            "Browser" => target::handle_browser(method),
+            "Target" => target::handle(self, client, session, method, params),
+            "Page" | "Runtime" | "DOM" | "Network" => {
+                let Some(index) = session else {
+                    return Err(CommandError::method_not_found(method));
+                };
+                let session = &mut self.sessions[index];
+                let target = self
+                    .targets
+                    .iter_mut()
+                    .find(|target| target.id == session.target)
+                    .ok_or_else(|| CommandError::server_error("No target with given id found"))?;
+                let events = &self
+                    .clients
+                    .get(&session.client)
+                    .ok_or_else(|| CommandError::server_error("The client is gone"))?
+                    .sender;
+                let mut session = PageSession {
+                    id: session.id.clone(),
+                    domains: &mut session.domains,
+                    target,
+                    events,
+                };
+                match domain {
+                    "Page" => page::handle(&mut session, method, params),
+                    "Runtime" => runtime::handle(&mut session, method, params),
+                    "DOM" => dom::handle(&mut session, method, params),
+                    _ => network::handle(&mut session, method, params),
+                }
+            },
+            _ => Err(CommandError::method_not_found(method)),
+        }
+    }
+
+    /// Send an event to the sessions attached to a page that enabled the domain of the event.
+    fn emit_to_sessions<T: Serialize>(
+        &self,
+        target: &str,
+        enabled: impl Fn(&EnabledDomains) -> bool,
+        method: &str,
+        params: &T,
+    ) {
+        for session in &self.sessions {
+            if session.target != target || !enabled(&session.domains) {
+                continue;
+            }
+            if let Some(client) = self.clients.get(&session.client) {
+                send_event(&client.sender, session.id.as_deref(), method, params);
+            }
+        }
+    }
+
+    /// Send an event to the clients that discover targets.
+    fn emit_to_discovering_clients<T: Serialize>(&self, method: &str, params: &T) {
+        for client in self.clients.values() {
+            if client.discover_targets {
+                send_event(&client.sender, None, method, params);
+            }
+        }
+    }
+}
+
+/// The Chrome DevTools Protocol server, as seen by the devtools thread, which tells it about the
+/// pages and what happens in them.
+pub(crate) struct CdpServer {
+    state: Arc<Mutex<CdpState>>,
+}
+
+impl CdpServer {
+    /// Listen for connections on the given port of the loopback interface.
+    pub(crate) fn start(port: u16) -> Option<Self> {
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(error) => {
+                warn!(
+                    "Failed to start the Chrome DevTools Protocol server: {}",
+                    error
+                );
+                return None;
+            },
+        };
+        let port = listener.local_addr().ok()?.port();
+
+        let browser_id = new_id();
+        // Automation tools that launch the browser look for this line to find the server.
+        eprintln!(
+            "DevTools listening on ws://127.0.0.1:{}/devtools/browser/{}",
+            port, browser_id
+        );
+
+        let state = Arc::new(Mutex::new(CdpState::new(browser_id)));
+        let server_state = state.clone();
+        thread::Builder::new()
+            .name("DevtoolsCdpAcceptor".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let state = server_state.clone();
+                    thread::Builder::new()
+                        .name("DevtoolsCdpClient".to_owned())
+                        .spawn(move || handle_connection(&state, stream))
+                        .expect("Thread spawning failed");
+                }
+            })
+            .expect("Thread spawning failed");
+
+        Some(Self { state })
+    }
+
+    /// A global was created for the document of a top-level browsing context.
+    pub(crate) fn page_created(
+        &self,
+        browsing_context: BrowsingContextId,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        page_info: &DevtoolsPageInfo,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(target) = state
+            .targets
+            .iter_mut()
+            .find(|target| target.browsing_context == browsing_context)
+        {
+            // The page is navigating; the clients are told about it once it is done.
+            target.pipeline = pipeline;
+            target.script_chan = script_chan;
+            return;
+        }
+
+        let execution_context_id = state.next_execution_context_id();
+        let target = PageTarget {
+            id: new_id(),
+            browsing_context,
+            pipeline,
+            script_chan,
+            url: page_info.url.as_str().to_owned(),
+            title: page_info.title.clone(),
+            announced_pipeline: Some(pipeline),
+            loader_id: new_id(),
+            pending_loader_id: None,
+            execution_context_id,
+            nodes: Default::default(),
+            requests: Default::default(),
+        };
+        let id = target.id.clone();
+        state.targets.push(target);
+
+        target::target_created(&mut state, &id);
+    }
+
+    pub(crate) fn navigate(
+        &self,
+        browsing_context: BrowsingContextId,
+        navigation: &NavigationState,
+    ) {
+        // Navigations are announced once the new document exists.
+        let NavigationState::Stop(pipeline, page_info) = navigation else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        let execution_context_id = state.next_execution_context_id();
+        let Some(target) = state
+            .targets
+            .iter_mut()
+            .find(|target| target.browsing_context == browsing_context)
+        else {
+            return;
+        };
+        target.pipeline = *pipeline;
+        page_info.url.as_str().clone_into(&mut target.url);
+        target.title.clone_from(&page_info.title);
+        let new_document = target.announced_pipeline != Some(*pipeline);
+        if new_document {
+            target.announced_pipeline = Some(*pipeline);
+            target.loader_id = target.pending_loader_id.take().unwrap_or_else(new_id);
+            target.execution_context_id = execution_context_id;
+            target.nodes = Default::default();
+        }
+        let id = target.id.clone();
+
+        if new_document {
+            page::frame_navigated(&state, &id);
+            runtime::execution_context_changed(&state, &id);
+            dom::document_updated(&state, &id);
+        }
+        target::target_info_changed(&state, &id);
+    }
+
+    pub(crate) fn title_changed(&self, pipeline: PipelineId, title: String) {
+        let mut state = self.state.lock().unwrap();
+        let Some(target) = state
+            .targets
+            .iter_mut()
+            .find(|target| target.pipeline == pipeline)
+        else {
+            return;
+        };
+        target.title = title;
+        let id = target.id.clone();
+        target::target_info_changed(&state, &id);
+    }
+
+    pub(crate) fn ready_state_changed(&self, pipeline: PipelineId, ready_state: ReadyState) {
+        let state = self.state.lock().unwrap();
+        if let Some(target) = state
+            .targets
+            .iter()
+            .find(|target| target.pipeline == pipeline)
+        {
+            page::ready_state_changed(&state, target, ready_state);
+        }
+    }
+
+    pub(crate) fn console_message(&self, pipeline: PipelineId, message: &ConsoleMessage) {
+        let state = self.state.lock().unwrap();
+        if let Some(target) = state
+            .targets
+            .iter()
+            .find(|target| target.pipeline == pipeline)
+        {
+            runtime::console_api_called(&state, target, message);
+        }
+    }
+
+    pub(crate) fn page_error(&self, pipeline: PipelineId, error: &PageError) {
+        let state = self.state.lock().unwrap();
+        if let Some(target) = state
+            .targets
+            .iter()
+            .find(|target| target.pipeline == pipeline)
+        {
+            runtime::exception_thrown(&state, target, error);
+        }
+    }
+
+    pub(crate) fn network_event(
+        &self,
+        pipeline: PipelineId,
+        request_id: &str,
+        event: &NetworkEvent,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(index) = state
+            .targets
+            .iter()
+            .position(|target| target.pipeline == pipeline)
+        {
+            network::network_event(&mut state, index, request_id, event);
+        }
+    }
+}
+
+/// A request for the list of targets, or to open a WebSocket.
+struct Request {
+    path: String,
+    /// The headers of the request, with lowercase names.
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let path = line.split_whitespace().nth(1)?.to_owned();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+    }
+    Some(Request { path, headers })
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn respond_with_json<T: Serialize>(stream: &mut TcpStream, body: &T) {
+    let body = serde_json::to_string_pretty(body).unwrap_or_default();
+    respond(stream, "200 OK", "application/json; charset=UTF-8", &body);
+}
+
+#[derive(Serialize)]
+struct VersionDescription {
+    #[serde(rename = "Browser")]
+    browser: String,
+    #[serde(rename = "Protocol-Version")]
+    protocol_version: &'static str,
+    #[serde(rename = "webSocketDebuggerUrl")]
+    web_socket_debugger_url: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetDescription {
+    description: String,
+    id: String,
+    title: String,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    url: String,
+    web_socket_debugger_url: String,
+}
+
+/// Serve a connection: either a request for the list of targets, or a WebSocket.
+fn handle_connection(state: &Mutex<CdpState>, mut stream: TcpStream) {
+    let Ok(read_stream) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_stream);
+    let Some(request) = read_request(&mut reader) else {
+        return;
+    };
+
+    let is_websocket = request
+        .header("upgrade")
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+    let host = request.header("host");
+    let origin = request.header("origin").filter(|_| is_websocket);
+    if let Err(rejection) = local_server::check_request(host, origin) {
+        let status = match rejection {
+            RejectedRequest::Host => "500 Internal Server Error",
+            RejectedRequest::Origin(_) => "403 Forbidden",
+        };
+        return respond(&mut stream, status, "text/plain", &rejection.to_string());
+    }
+    let host = host.unwrap_or_default();
+
+    if is_websocket {
+        let page = {
+            let state = state.lock().unwrap();
+            let browser_path = format!("/devtools/browser/{}", state.browser_id);
+            match request.path.strip_prefix("/devtools/page/") {
+                Some(id) if state.target(id).is_some() => Some(Some(id.to_owned())),
+                _ if request.path == browser_path => Some(None),
+                _ => None,
+            }
+        };
+        let (Some(page), Some(key)) = (page, request.header("sec-websocket-key")) else {
+            return respond(&mut stream, "404 Not Found", "text/plain", "No such target");
+        };
+        let handshake = write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            derive_accept_key(key.as_bytes())
+        );
+        if handshake.is_err() {
+            return;
+        }
+        let socket =
+            WebSocket::from_partially_read(stream, reader.buffer().to_vec(), Role::Server, None);
+        return run_client(state, socket, page);
+    }
+
+    let state = state.lock().unwrap();
+    match request.path.as_str() {
+        "/json/version" => respond_with_json(
+            &mut stream,
+            &VersionDescription {
+                browser: format!("Servo/{}", env!("CARGO_PKG_VERSION")),
+                protocol_version: PROTOCOL_VERSION,
+                web_socket_debugger_url: format!(
+                    "ws://{}/devtools/browser/{}",
+                    host, state.browser_id
+                ),
+            },
+        ),
+        "/json" | "/json/list" => {
+            let targets: Vec<_> = state
+                .targets
+                .iter()
+                .map(|target| TargetDescription {
+                    description: String::new(),
+                    id: target.id.clone(),
+                    title: target.title.clone(),
+                    type_: "page",
+                    url: target.url.clone(),
+                    web_socket_debugger_url: format!("ws://{}/devtools/page/{}", host, target.id),
+                })
+                .collect();
+            respond_with_json(&mut stream, &targets)
+        },
+        _ => respond(
+            &mut stream,
+            "404 Not Found",
+            "text/plain",
+            "Unknown command",
+        ),
+    }
+}
+
+/// Exchange messages with a client until it goes away.
+fn run_client(state: &Mutex<CdpState>, mut socket: WebSocket<TcpStream>, page: Option<String>) {
+    info!("Chrome DevTools Protocol connection established");
+    // Reading times out regularly, so that the events that happen in the meantime are sent.
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(EVENT_POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+    let (sender, receiver) = unbounded();
+    let client = state.lock().unwrap().add_client(sender.clone(), page);
+
+    loop {
+        if receiver
+            .try_iter()
+            .any(|message| socket.send(Message::text(message)).is_err())
+        {
+            break;
+        }
+        match socket.read() {
+            Ok(Message::Text(message)) => {
+                // The reply is queued after the events that the command caused.
+                let reply = state.lock().unwrap().handle_message(client, &message);
+                let _ = sender.send(reply);
+            },
+            Ok(_) => {},
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+            Err(_) => break,
+        }
+    }
+
+    info!("Chrome DevTools Protocol connection closed");
+    state.lock().unwrap().remove_client(client);
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use base::id::{BrowsingContextId, PipelineId, PipelineNamespace, TEST_NAMESPACE};
+    use crossbeam_channel::{Receiver, unbounded};
+    use devtools_traits::{
+        ConsoleMessage, ConsoleMessageArgument, DevtoolScriptControlMsg, DevtoolsPageInfo,
+        EvaluateJSReply, LogLevel, NavigationState,
+    };
+    use ipc_channel::ipc::{self, IpcReceiver};
+    use serde_json::{Value, json};
+    use servo_url::ServoUrl;
+
+    use super::{
+        CdpServer, CdpState, ClientId, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND,
+        SERVER_ERROR, SESSION_NOT_FOUND,
+    };
+
+    struct Page {
+        id: String,
+        browsing_context: BrowsingContextId,
+        script_receiver: IpcReceiver<DevtoolScriptControlMsg>,
+    }
+
+    fn test_server() -> CdpServer {
+        PipelineNamespace::install(TEST_NAMESPACE);
+        CdpServer {
+            state: Arc::new(Mutex::new(CdpState::new("browser".to_owned()))),
+        }
+    }
+
+    fn page_info(url: &str) -> DevtoolsPageInfo {
+        DevtoolsPageInfo {
+            title: "Title".to_owned(),
+            url: ServoUrl::parse(url).unwrap(),
+            is_top_level_global: true,
+        }
+    }
+
+    fn create_page(server: &CdpServer, url: &str) -> Page {
+        let browsing_context = BrowsingContextId::new();
+        let (script_sender, script_receiver) = ipc::channel().unwrap();
+        server.page_created(
+            browsing_context,
+            PipelineId::new(),
+            script_sender,
+            &page_info(url),
+        );
+        let id = server
+            .state
+            .lock()
+            .unwrap()
+            .targets
+            .last()
+            .unwrap()
+            .id
+            .clone();
+        Page {
+            id,
+            browsing_context,
+            script_receiver,
+        }
+    }
+
+    fn current_pipeline(server: &CdpServer, page: &Page) -> PipelineId {
+        server
+            .state
+            .lock()
+            .unwrap()
+            .target(&page.id)
+            .unwrap()
+            .pipeline
+    }
+
+    fn connect(server: &CdpServer, page: Option<&Page>) -> (ClientId, Receiver<String>) {
+        let (sender, receiver) = unbounded();
+        let client = server
+            .state
+            .lock()
+            .unwrap()
+            .add_client(sender, page.map(|page| page.id.clone()));
+        (client, receiver)
+    }
+
+    /// Send a command, and return its reply.
+    fn send(server: &CdpServer, client: ClientId, command: Value) -> Value {
+        let reply = server
+            .state
+            .lock()
+            .unwrap()
+            .handle_message(client, &command.to_string());
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    /// Send a command that must succeed, and return its result.
+    fn result(server: &CdpServer, client: ClientId, command: Value) -> Value {
+        let reply = send(server, client, command);
+        assert_eq!(reply.get("error"), None, "{reply}");
+        reply["result"].clone()
+    }
+
+    /// Send a command that must fail, and return the code of its error.
+    fn error_code(server: &CdpServer, client: ClientId, command: Value) -> i64 {
+        let reply = send(server, client, command);
+        assert_eq!(reply.get("result"), None, "{reply}");
+        reply["error"]["code"].as_i64().unwrap()
+    }
+
+    fn next_event(events: &Receiver<String>) -> Value {
+        serde_json::from_str(&events.try_recv().expect("An event should have been sent")).unwrap()
+    }
+
+    /// Answer the evaluations of the page with the expression that was evaluated.
+    fn echo_evaluations(page: Page) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            while let Ok(message) = page.script_receiver.recv() {
+                if let DevtoolScriptControlMsg::EvaluateJS(_, expression, reply) = message {
+                    let _ = reply.send(EvaluateJSReply::StringValue(expression));
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        let server = test_server();
+        let (client, _events) = connect(&server, None);
+
+        let reply: Value = serde_json::from_str(
+            &server
+                .state
+                .lock()
+                .unwrap()
+                .handle_message(client, "not json"),
+        )
+        .unwrap();
+        assert_eq!(reply.get("id"), None);
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+
+        let reply = send(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Unknown.method" }),
+        );
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(
+            error_code(&server, client, json!({ "id": 2, "method": "noDomain" })),
+            METHOD_NOT_FOUND
+        );
+        // The commands of a page cannot be sent to the browser.
+        assert_eq!(
+            error_code(&server, client, json!({ "id": 3, "method": "Page.enable" })),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 4, "method": "Page.enable", "sessionId": "unknown" })
+            ),
+            SESSION_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_browser_and_target_info() {
+        let server = test_server();
+        let page = create_page(&server, "https://example.com/");
+        let (client, _events) = connect(&server, None);
+
+        let version = result(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Browser.getVersion" }),
+        );
+        assert_eq!(version["protocolVersion"], "1.3");
+        assert!(version["product"].as_str().unwrap().starts_with("Servo/"));
+
+        let targets = result(
+            &server,
+            client,
+            json!({ "id": 2, "method": "Target.getTargets" }),
+        );
+        assert_eq!(
+            targets["targetInfos"],
+            json!([{
+                "targetId": page.id,
+                "type": "page",
+                "title": "Title",
+                "url": "https://example.com/",
+                "attached": false,
+                "canAccessOpener": false,
+            }])
+        );
+
+        let browser = result(
+            &server,
+            client,
+            json!({ "id": 3, "method": "Target.getTargetInfo" }),
+        );
+        assert_eq!(browser["targetInfo"]["targetId"], "browser");
+        assert_eq!(browser["targetInfo"]["type"], "browser");
+        let info = result(
+            &server,
+            client,
+            json!({ "id": 4, "method": "Target.getTargetInfo", "params": { "targetId": page.id } }),
+        );
+        assert_eq!(info["targetInfo"]["type"], "page");
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 5, "method": "Target.getTargetInfo", "params": { "targetId": "unknown" } })
+            ),
+            INVALID_PARAMS
+        );
+
+        let contexts = result(
+            &server,
+            client,
+            json!({ "id": 6, "method": "Target.getBrowserContexts" }),
+        );
+        assert_eq!(contexts, json!({ "browserContextIds": [] }));
+    }
+
+    #[test]
+    fn test_target_discovery() {
+        let server = test_server();
+        let first = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, None);
+
+        // Nothing is sent about the pages until they are discovered.
+        create_page(&server, "https://example.org/");
+        assert!(events.try_recv().is_err());
+
+        result(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Target.setDiscoverTargets", "params": { "discover": true } }),
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.targetCreated");
+        assert_eq!(event["params"]["targetInfo"]["targetId"], first.id);
+        assert_eq!(event["params"]["targetInfo"]["url"], "https://example.com/");
+        assert_eq!(
+            next_event(&events)["params"]["targetInfo"]["url"],
+            "https://example.org/"
+        );
+
+        let third = create_page(&server, "https://example.net/");
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.targetCreated");
+        assert_eq!(event["params"]["targetInfo"]["targetId"], third.id);
+
+        server.title_changed(current_pipeline(&server, &first), "New title".to_owned());
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.targetInfoChanged");
+        assert_eq!(event["params"]["targetInfo"]["title"], "New title");
+
+        result(
+            &server,
+            client,
+            json!({ "id": 2, "method": "Target.setDiscoverTargets", "params": { "discover": false } }),
+        );
+        create_page(&server, "https://example.com/");
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_attach_and_detach() {
+        let server = test_server();
+        let page = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, None);
+
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 1, "method": "Target.attachToTarget", "params": { "targetId": page.id } })
+            ),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({
+                    "id": 2,
+                    "method": "Target.attachToTarget",
+                    "params": { "targetId": "unknown", "flatten": true },
+                })
+            ),
+            INVALID_PARAMS
+        );
+
+        let attached = result(
+            &server,
+            client,
+            json!({
+                "id": 3,
+                "method": "Target.attachToTarget",
+                "params": { "targetId": page.id, "flatten": true },
+            }),
+        );
+        let session_id = attached["sessionId"].as_str().unwrap().to_owned();
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.attachedToTarget");
+        assert_eq!(event.get("sessionId"), None);
+        assert_eq!(event["params"]["sessionId"], session_id);
+        assert_eq!(event["params"]["targetInfo"]["attached"], true);
+        assert_eq!(event["params"]["waitingForDebugger"], false);
+
+        // The commands of the session carry its id, and so do their replies.
+        let reply = send(
+            &server,
+            client,
+            json!({ "id": 4, "method": "Page.enable", "sessionId": session_id }),
+        );
+        assert_eq!(reply["sessionId"], session_id);
+        assert_eq!(reply["result"], json!({}));
+
+        result(
+            &server,
+            client,
+            json!({
+                "id": 5,
+                "method": "Target.detachFromTarget",
+                "params": { "sessionId": session_id },
+            }),
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.detachedFromTarget");
+        assert_eq!(event["params"]["sessionId"], session_id);
+        assert_eq!(event["params"]["targetId"], page.id);
+
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 6, "method": "Page.enable", "sessionId": session_id })
+            ),
+            SESSION_NOT_FOUND
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({
+                    "id": 7,
+                    "method": "Target.detachFromTarget",
+                    "params": { "sessionId": session_id },
+                })
+            ),
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn test_auto_attach() {
+        let server = test_server();
+        let first = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, None);
+
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 1, "method": "Target.setAutoAttach", "params": { "autoAttach": true } })
+            ),
+            INVALID_PARAMS
+        );
+        result(
+            &server,
+            client,
+            json!({
+                "id": 2,
+                "method": "Target.setAutoAttach",
+                "params": { "autoAttach": true, "flatten": true },
+            }),
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.attachedToTarget");
+        assert_eq!(event["params"]["targetInfo"]["targetId"], first.id);
+
+        let second = create_page(&server, "https://example.org/");
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Target.attachedToTarget");
+        assert_eq!(event["params"]["targetInfo"]["targetId"], second.id);
+
+        // Asking again attaches to nothing new.
+        result(
+            &server,
+            client,
+            json!({
+                "id": 3,
+                "method": "Target.setAutoAttach",
+                "params": { "autoAttach": true, "flatten": true },
+            }),
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_page_commands() {
+        let server = test_server();
+        let page = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, Some(&page));
+
+        let tree = result(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Page.getFrameTree" }),
+        );
+        let frame = &tree["frameTree"]["frame"];
+        assert_eq!(frame["id"], page.id);
+        assert_eq!(frame["url"], "https://example.com/");
+        assert_eq!(frame["securityOrigin"], "https://example.com");
+        assert_eq!(frame["secureContextType"], "Secure");
+        let loader_id = frame["loaderId"].clone();
+
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 2, "method": "Page.navigate", "params": { "url": "not a url" } })
+            ),
+            SERVER_ERROR
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 3, "method": "Page.navigate" })
+            ),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({ "id": 4, "method": "Page.unknown" })
+            ),
+            METHOD_NOT_FOUND
+        );
+
+        result(&server, client, json!({ "id": 5, "method": "Page.enable" }));
+        result(
+            &server,
+            client,
+            json!({
+                "id": 6,
+                "method": "Page.setLifecycleEventsEnabled",
+                "params": { "enabled": true },
+            }),
+        );
+        let navigation = result(
+            &server,
+            client,
+            json!({
+                "id": 7,
+                "method": "Page.navigate",
+                "params": { "url": "http://example.org/" },
+            }),
+        );
+        assert_eq!(navigation["frameId"], page.id);
+        assert_ne!(navigation["loaderId"], loader_id);
+        let Ok(DevtoolScriptControlMsg::NavigateTo(_, url)) = page.script_receiver.recv() else {
+            panic!("The page should be navigated");
+        };
+        assert_eq!(url.as_str(), "http://example.org/");
+        assert!(events.try_recv().is_err());
+
+        // The navigation is announced once the new document exists, with the id it was given.
+        server.navigate(
+            page.browsing_context,
+            &NavigationState::Stop(PipelineId::new(), page_info("http://example.org/")),
+        );
+        assert_eq!(next_event(&events)["method"], "Page.frameStartedLoading");
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Page.frameNavigated");
+        assert_eq!(event["params"]["frame"]["url"], "http://example.org/");
+        assert_eq!(event["params"]["frame"]["loaderId"], navigation["loaderId"]);
+        assert_eq!(
+            event["params"]["frame"]["secureContextType"],
+            "InsecureScheme"
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Page.lifecycleEvent");
+        assert_eq!(event["params"]["name"], "init");
+        assert_eq!(event["params"]["loaderId"], navigation["loaderId"]);
+
+        result(&server, client, json!({ "id": 8, "method": "Page.reload" }));
+        assert!(matches!(
+            page.script_receiver.recv(),
+            Ok(DevtoolScriptControlMsg::Reload(_))
+        ));
+    }
+
+    #[test]
+    fn test_runtime_execution_contexts() {
+        let server = test_server();
+        let page = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, Some(&page));
+
+        result(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Runtime.enable" }),
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Runtime.executionContextCreated");
+        let context = &event["params"]["context"];
+        assert_eq!(context["origin"], "https://example.com");
+        assert_eq!(context["auxData"]["frameId"], page.id);
+        assert_eq!(context["auxData"]["isDefault"], true);
+        let context_id = context["id"].clone();
+
+        server.console_message(
+            current_pipeline(&server, &page),
+            &ConsoleMessage {
+                log_level: LogLevel::Warn,
+                filename: "https://example.com/".to_owned(),
+                line_number: 1,
+                column_number: 1,
+                arguments: vec![
+                    ConsoleMessageArgument::String("text".to_owned()),
+                    ConsoleMessageArgument::Number(f64::NAN),
+                ],
+                stacktrace: None,
+            },
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Runtime.consoleAPICalled");
+        assert_eq!(event["params"]["type"], "warning");
+        assert_eq!(event["params"]["executionContextId"], context_id);
+        assert_eq!(
+            event["params"]["args"],
+            json!([
+                { "type": "string", "value": "text" },
+                { "type": "number", "unserializableValue": "NaN", "description": "NaN" },
+            ])
+        );
+
+        // A new document has a new execution context.
+        server.navigate(
+            page.browsing_context,
+            &NavigationState::Stop(PipelineId::new(), page_info("https://example.org/")),
+        );
+        assert_eq!(
+            next_event(&events)["method"],
+            "Runtime.executionContextsCleared"
+        );
+        let event = next_event(&events);
+        assert_eq!(event["method"], "Runtime.executionContextCreated");
+        assert_eq!(event["params"]["context"]["origin"], "https://example.org");
+        assert_ne!(event["params"]["context"]["id"], context_id);
+
+        result(
+            &server,
+            client,
+            json!({ "id": 2, "method": "Runtime.disable" }),
+        );
+        server.console_message(
+            current_pipeline(&server, &page),
+            &ConsoleMessage {
+                log_level: LogLevel::Log,
+                filename: String::new(),
+                line_number: 1,
+                column_number: 1,
+                arguments: vec![],
+                stacktrace: None,
+            },
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_runtime_evaluation() {
+        let server = test_server();
+        let page = create_page(&server, "https://example.com/");
+        let (client, events) = connect(&server, Some(&page));
+        result(
+            &server,
+            client,
+            json!({ "id": 1, "method": "Runtime.enable" }),
+        );
+        let context_id = next_event(&events)["params"]["context"]["id"].clone();
+        let script_thread = echo_evaluations(page);
+
+        let evaluation = result(
+            &server,
+            client,
+            json!({
+                "id": 2,
+                "method": "Runtime.evaluate",
+                "params": { "expression": "1 + 1", "contextId": context_id },
+            }),
+        );
+        assert_eq!(
+            evaluation,
+            json!({ "result": { "type": "string", "value": "1 + 1" } })
+        );
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({
+                    "id": 3,
+                    "method": "Runtime.evaluate",
+                    "params": { "expression": "1", "contextId": 1000 },
+                })
+            ),
+            SERVER_ERROR
+        );
+
+        let call = result(
+            &server,
+            client,
+            json!({
+                "id": 4,
+                "method": "Runtime.callFunctionOn",
+                "params": {
+                    "functionDeclaration": "(a, b, c, d) => a",
+                    "arguments": [
+                        { "value": { "key": [1, "two"] } },
+                        { "unserializableValue": "-0" },
+                        { "unserializableValue": "-12n" },
+                        {},
+                    ],
+                },
+            }),
+        );
+        assert_eq!(
+            call["result"]["value"],
+            r#"((a, b, c, d) => a).apply(undefined, [{"key":[1,"two"]}, -0, -12n, undefined])"#
+        );
+        for argument in [
+            json!({ "unserializableValue": "alert(1)" }),
+            json!({ "unserializableValue": "n" }),
+        ] {
+            assert_eq!(
+                error_code(
+                    &server,
+                    client,
+                    json!({
+                        "id": 5,
+                        "method": "Runtime.callFunctionOn",
+                        "params": { "functionDeclaration": "() => {}", "arguments": [argument] },
+                    })
+                ),
+                INVALID_PARAMS
+            );
+        }
+        assert_eq!(
+            error_code(
+                &server,
+                client,
+                json!({
+                    "id": 6,
+                    "method": "Runtime.callFunctionOn",
+                    "params": { "functionDeclaration": "() => {}", "objectId": "1" },
+                })
+            ),
+            SERVER_ERROR
+        );
+
+        // The page goes away with its script thread.
+        server.state.lock().unwrap().targets.clear();
+        script_thread.join().unwrap();
+    }
+}
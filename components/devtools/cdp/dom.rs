@@ -0,0 +1,401 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `DOM` domain, which reads and edits the document of the page.
+//!
+//! <https://chromedevtools.github.io/devtools-protocol/tot/DOM/>
+
+use std::collections::HashMap;
+
+use devtools_traits::DevtoolScriptControlMsg::{
+    GetChildren, GetOuterHtml, GetRootNode, ModifyAttribute, ModifyNodeValue, QuerySelectorAll,
+};
+use devtools_traits::{AttrModification, NodeInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cdp::{
+    CdpState, CommandError, CommandResult, PageSession, PageTarget, command_result, empty_result,
+    parse_params,
+};
+
+/// The type of element nodes.
+const ELEMENT_NODE: u16 = 1;
+
+/// The type of document nodes.
+const DOCUMENT_NODE: u16 = 9;
+
+/// A node that the clients were told about.
+struct KnownNode {
+    unique_id: String,
+    /// The unique id of the parent of the node, which is empty for the document.
+    parent: String,
+}
+
+/// The ids by which the clients know the nodes of a document. These are numbers in this
+/// protocol, and strings in the script thread. Node ids start at 1, so that 0 is able to mean
+/// that there is no node.
+#[derive(Default)]
+pub(crate) struct NodeIds {
+    nodes: Vec<KnownNode>,
+    ids: HashMap<String, i64>,
+}
+
+impl NodeIds {
+    fn node_id(&mut self, info: &NodeInfo) -> i64 {
+        if let Some(id) = self.ids.get(&info.unique_id) {
+            return *id;
+        }
+        self.nodes.push(KnownNode {
+            unique_id: info.unique_id.clone(),
+            parent: info.parent.clone(),
+        });
+        let id = self.nodes.len() as i64;
+        self.ids.insert(info.unique_id.clone(), id);
+        id
+    }
+
+    fn get(&self, node_id: i64) -> Result<&KnownNode, CommandError> {
+        usize::try_from(node_id - 1)
+            .ok()
+            .and_then(|index| self.nodes.get(index))
+            .ok_or_else(|| CommandError::server_error("Could not find node with given id"))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Node {
+    node_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<i64>,
+    backend_node_id: i64,
+    node_type: u16,
+    node_name: String,
+    local_name: String,
+    node_value: String,
+    child_node_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<Node>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<Vec<String>>,
+    #[serde(rename = "documentURL", skip_serializing_if = "Option::is_none")]
+    document_url: Option<String>,
+    #[serde(rename = "baseURL", skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GetDocumentReply {
+    root: Node,
+}
+
+#[derive(Serialize)]
+struct DescribeNodeReply {
+    node: Node,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuerySelectorReply {
+    node_id: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuerySelectorAllReply {
+    node_ids: Vec<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetOuterHtmlReply {
+    #[serde(rename = "outerHTML")]
+    outer_html: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SetChildNodesEvent {
+    parent_id: i64,
+    nodes: Vec<Node>,
+}
+
+#[derive(Serialize)]
+struct EmptyEvent {}
+
+#[derive(Deserialize)]
+struct DepthParams {
+    depth: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeParams {
+    node_id: Option<i64>,
+    backend_node_id: Option<i64>,
+    depth: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuerySelectorParams {
+    node_id: i64,
+    selector: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetAttributeValueParams {
+    node_id: i64,
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoveAttributeParams {
+    node_id: i64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetNodeValueParams {
+    node_id: i64,
+    value: String,
+}
+
+fn children(target: &PageTarget, unique_id: &str) -> Result<Vec<NodeInfo>, CommandError> {
+    target
+        .request(|pipeline, sender| GetChildren(pipeline, unique_id.to_owned(), sender))?
+        .ok_or_else(|| CommandError::server_error("Could not find node with given id"))
+}
+
+/// What the script thread knows about a node that the clients were told about.
+fn node_info(target: &PageTarget, node_id: i64) -> Result<NodeInfo, CommandError> {
+    let node = target.nodes.get(node_id)?;
+    let info = if node.parent.is_empty() {
+        target.request(GetRootNode)?
+    } else {
+        children(target, &node.parent)?
+            .into_iter()
+            .find(|child| child.unique_id == node.unique_id)
+    };
+    info.ok_or_else(|| CommandError::server_error("Could not find node with given id"))
+}
+
+/// The depth of the children of a node, given the depth of its descendants.
+fn child_depth(depth: i64) -> i64 {
+    if depth < 0 { depth } else { (depth - 1).max(0) }
+}
+
+/// Describe a node, and its descendants up to the given depth, or all of them if it is negative.
+fn describe(target: &mut PageTarget, info: NodeInfo, depth: i64) -> Result<Node, CommandError> {
+    let node_id = target.nodes.node_id(&info);
+    let parent_id = target.nodes.ids.get(&info.parent).copied();
+    let children = if depth != 0 && info.num_children > 0 {
+        let children = children(target, &info.unique_id)?
+            .into_iter()
+            .map(|child| describe(target, child, child_depth(depth)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Some(children)
+    } else {
+        None
+    };
+    let is_element = info.node_type == ELEMENT_NODE;
+    let is_document = info.node_type == DOCUMENT_NODE;
+    Ok(Node {
+        node_id,
+        parent_id,
+        backend_node_id: node_id,
+        node_type: info.node_type,
+        local_name: if is_element {
+            info.node_name.to_lowercase()
+        } else {
+            String::new()
+        },
+        node_name: info.node_name,
+        node_value: info.node_value.unwrap_or_default(),
+        child_node_count: info.num_children,
+        children,
+        attributes: is_element.then(|| {
+            info.attrs
+                .into_iter()
+                .flat_map(|attr| [attr.name, attr.value])
+                .collect()
+        }),
+        document_url: is_document.then(|| target.url.clone()),
+        base_url: is_document.then_some(info.base_uri),
+    })
+}
+
+fn query_selector_all(
+    target: &mut PageTarget,
+    node_id: i64,
+    selector: String,
+) -> Result<Vec<i64>, CommandError> {
+    let unique_id = target.nodes.get(node_id)?.unique_id.clone();
+    let matches = target
+        .request(|pipeline, sender| QuerySelectorAll(pipeline, unique_id, selector, sender))?
+        .ok_or_else(|| CommandError::server_error("DOM Error while querying"))?;
+    Ok(matches
+        .iter()
+        .map(|info| target.nodes.node_id(info))
+        .collect())
+}
+
+fn modify_attribute(
+    target: &PageTarget,
+    node_id: i64,
+    name: String,
+    value: Option<String>,
+) -> CommandResult {
+    let unique_id = target.nodes.get(node_id)?.unique_id.clone();
+    let modification = AttrModification {
+        attribute_name: name,
+        new_value: value,
+    };
+    target
+        .script_chan
+        .send(ModifyAttribute(
+            target.pipeline,
+            unique_id,
+            vec![modification],
+        ))
+        .map_err(|_| CommandError::server_error("The page is gone"))?;
+    empty_result()
+}
+
+/// Tell the sessions of a page that its document was replaced, and forget about the nodes of
+/// the previous one.
+pub(super) fn document_updated(state: &CdpState, id: &str) {
+    state.emit_to_sessions(
+        id,
+        |domains| domains.dom,
+        "DOM.documentUpdated",
+        &EmptyEvent {},
+    );
+}
+
+/// The DOM commands that are supported:
+///
+/// - `enable`, `disable`: Start and stop sending the events of the domain
+///
+/// - `getDocument`: Returns the document, and its descendants up to the given depth
+///
+/// - `requestChildNodes`: Sends the children of a node, and their descendants up to the given
+///   depth, in a `setChildNodes` event
+///
+/// - `describeNode`: Returns a node, and its descendants up to the given depth
+///
+/// - `querySelector`, `querySelectorAll`: Return the descendants of a node that match a selector
+///
+/// - `getOuterHTML`: Returns the markup of a node
+///
+/// - `setAttributeValue`, `removeAttribute`, `setNodeValue`: Edit a node
+pub(super) fn handle(session: &mut PageSession, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "DOM.enable" | "DOM.disable" => {
+            session.domains.dom = method == "DOM.enable";
+            empty_result()
+        },
+
+        "DOM.getDocument" => {
+            let params: DepthParams = parse_params(params)?;
+            let root = session
+                .target
+                .request(GetRootNode)?
+                .ok_or_else(|| CommandError::server_error("Document is not available"))?;
+            let root = describe(session.target, root, params.depth.unwrap_or(1))?;
+            command_result(GetDocumentReply { root })
+        },
+
+        "DOM.requestChildNodes" => {
+            let params: NodeParams = parse_params(params)?;
+            let node_id = params
+                .node_id
+                .ok_or_else(|| CommandError::invalid_params("Missing nodeId"))?;
+            let unique_id = session.target.nodes.get(node_id)?.unique_id.clone();
+            let depth = params.depth.unwrap_or(1);
+            let nodes = children(session.target, &unique_id)?
+                .into_iter()
+                .map(|child| describe(session.target, child, child_depth(depth)))
+                .collect::<Result<Vec<_>, _>>()?;
+            session.emit(
+                "DOM.setChildNodes",
+                SetChildNodesEvent {
+                    parent_id: node_id,
+                    nodes,
+                },
+            );
+            empty_result()
+        },
+
+        "DOM.describeNode" => {
+            let params: NodeParams = parse_params(params)?;
+            let node_id = params.node_id.or(params.backend_node_id).ok_or_else(|| {
+                CommandError::invalid_params("Either nodeId or backendNodeId must be specified")
+            })?;
+            let info = node_info(session.target, node_id)?;
+            let node = describe(session.target, info, params.depth.unwrap_or(0))?;
+            command_result(DescribeNodeReply { node })
+        },
+
+        "DOM.querySelector" => {
+            let params: QuerySelectorParams = parse_params(params)?;
+            let node_ids = query_selector_all(session.target, params.node_id, params.selector)?;
+            command_result(QuerySelectorReply {
+                node_id: node_ids.first().copied().unwrap_or(0),
+            })
+        },
+
+        "DOM.querySelectorAll" => {
+            let params: QuerySelectorParams = parse_params(params)?;
+            let node_ids = query_selector_all(session.target, params.node_id, params.selector)?;
+            command_result(QuerySelectorAllReply { node_ids })
+        },
+
+        "DOM.getOuterHTML" => {
+            let params: NodeParams = parse_params(params)?;
+            let node_id = params.node_id.or(params.backend_node_id).ok_or_else(|| {
+                CommandError::invalid_params("Either nodeId or backendNodeId must be specified")
+            })?;
+            let unique_id = session.target.nodes.get(node_id)?.unique_id.clone();
+            let outer_html = session
+                .target
+                .request(|pipeline, sender| GetOuterHtml(pipeline, unique_id, sender))?
+                .ok_or_else(|| CommandError::server_error("Could not find node with given id"))?;
+            command_result(GetOuterHtmlReply { outer_html })
+        },
+
+        "DOM.setAttributeValue" => {
+            let params: SetAttributeValueParams = parse_params(params)?;
+            modify_attribute(
+                session.target,
+                params.node_id,
+                params.name,
+                Some(params.value),
+            )
+        },
+
+        "DOM.removeAttribute" => {
+            let params: RemoveAttributeParams = parse_params(params)?;
+            modify_attribute(session.target, params.node_id, params.name, None)
+        },
+
+        "DOM.setNodeValue" => {
+            let params: SetNodeValueParams = parse_params(params)?;
+            let target = &session.target;
+            let unique_id = target.nodes.get(params.node_id)?.unique_id.clone();
+            target
+                .script_chan
+                .send(ModifyNodeValue(target.pipeline, unique_id, params.value))
+                .map_err(|_| CommandError::server_error("The page is gone"))?;
+            empty_result()
+        },
+
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
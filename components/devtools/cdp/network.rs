@@ -0,0 +1,333 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `Network` domain, which tells about the requests of the page and their responses.
+//!
+//! <https://chromedevtools.github.io/devtools-protocol/tot/Network/>
+
+use std::collections::{HashMap, VecDeque};
+use std::time::UNIX_EPOCH;
+
+use base64::Engine;
+use devtools_traits::{HttpRequest, HttpResponse, HttpResponseEnd, NetworkEvent};
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cdp::{
+    CdpState, CommandError, CommandResult, PageSession, command_result, empty_result, parse_params,
+    timestamp,
+};
+
+/// How many requests of a page are remembered, so that their bodies can be asked for.
+const MAX_REMEMBERED_REQUESTS: usize = 1000;
+
+struct RequestRecord {
+    url: String,
+    resource_type: &'static str,
+    body: Option<Vec<u8>>,
+}
+
+/// The recent requests of a page.
+#[derive(Default)]
+pub(crate) struct NetworkRequests {
+    requests: HashMap<String, RequestRecord>,
+    /// The ids of the requests, from the oldest to the newest.
+    order: VecDeque<String>,
+}
+
+impl NetworkRequests {
+    fn insert(&mut self, request_id: &str, record: RequestRecord) {
+        if self
+            .requests
+            .insert(request_id.to_owned(), record)
+            .is_none()
+        {
+            self.order.push_back(request_id.to_owned());
+        }
+        while self.order.len() > MAX_REMEMBERED_REQUESTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.requests.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    url: String,
+    method: String,
+    headers: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<String>,
+    has_post_data: bool,
+    initial_priority: &'static str,
+    referrer_policy: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Initiator {
+    #[serde(rename = "type")]
+    type_: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestWillBeSentEvent {
+    request_id: String,
+    loader_id: String,
+    #[serde(rename = "documentURL")]
+    document_url: String,
+    request: Request,
+    timestamp: f64,
+    wall_time: f64,
+    initiator: Initiator,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    frame_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    url: String,
+    status: u16,
+    status_text: String,
+    headers: HashMap<String, String>,
+    mime_type: String,
+    connection_reused: bool,
+    connection_id: u32,
+    encoded_data_length: usize,
+    security_state: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseReceivedEvent {
+    request_id: String,
+    loader_id: String,
+    timestamp: f64,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    response: Response,
+    frame_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LoadingFinishedEvent {
+    request_id: String,
+    timestamp: f64,
+    encoded_data_length: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetResponseBodyReply {
+    body: String,
+    base64_encoded: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetResponseBodyParams {
+    request_id: String,
+}
+
+/// The headers of a request or response, with the values of repeated headers joined by newlines.
+fn headers(header_map: Option<&HeaderMap>) -> HashMap<String, String> {
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for (name, value) in header_map.into_iter().flatten() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        headers
+            .entry(name.as_str().to_owned())
+            .and_modify(|values| {
+                values.push('\n');
+                values.push_str(&value);
+            })
+            .or_insert_with(|| value.into_owned());
+    }
+    headers
+}
+
+fn request_will_be_sent(
+    state: &mut CdpState,
+    target_index: usize,
+    request_id: &str,
+    request: &HttpRequest,
+) {
+    let resource_type = if request.is_xhr { "XHR" } else { "Other" };
+    let target = &mut state.targets[target_index];
+    target.requests.insert(
+        request_id,
+        RequestRecord {
+            url: request.url.to_string(),
+            resource_type,
+            body: None,
+        },
+    );
+    let event = RequestWillBeSentEvent {
+        request_id: request_id.to_owned(),
+        loader_id: target.loader_id.clone(),
+        document_url: target.url.clone(),
+        request: Request {
+            url: request.url.to_string(),
+            method: request.method.to_string(),
+            headers: headers(Some(&request.headers)),
+            post_data: request
+                .body
+                .as_ref()
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+            has_post_data: request.body.is_some(),
+            initial_priority: "High",
+            referrer_policy: "strict-origin-when-cross-origin",
+        },
+        timestamp: timestamp(),
+        wall_time: request
+            .started_date_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64(),
+        initiator: Initiator { type_: "other" },
+        type_: resource_type,
+        frame_id: target.id.clone(),
+    };
+    let target = &state.targets[target_index];
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.network,
+        "Network.requestWillBeSent",
+        &event,
+    );
+}
+
+fn response_received(
+    state: &CdpState,
+    target_index: usize,
+    request_id: &str,
+    response: &HttpResponse,
+) {
+    let target = &state.targets[target_index];
+    let Some(record) = target.requests.requests.get(request_id) else {
+        return;
+    };
+    let headers = headers(response.headers.as_ref());
+    let mime_type = headers
+        .get("content-type")
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime_type| mime_type.trim().to_owned())
+        .unwrap_or_default();
+    let event = ResponseReceivedEvent {
+        request_id: request_id.to_owned(),
+        loader_id: target.loader_id.clone(),
+        timestamp: timestamp(),
+        type_: record.resource_type,
+        response: Response {
+            url: record.url.clone(),
+            status: response.status.raw_code(),
+            status_text: String::from_utf8_lossy(response.status.message()).into_owned(),
+            headers,
+            mime_type,
+            connection_reused: false,
+            connection_id: 0,
+            encoded_data_length: 0,
+            security_state: "unknown",
+        },
+        frame_id: target.id.clone(),
+    };
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.network,
+        "Network.responseReceived",
+        &event,
+    );
+}
+
+fn loading_finished(
+    state: &mut CdpState,
+    target_index: usize,
+    request_id: &str,
+    response_end: &HttpResponseEnd,
+) {
+    let target = &mut state.targets[target_index];
+    let Some(record) = target.requests.requests.get_mut(request_id) else {
+        return;
+    };
+    record.body = Some(response_end.body_preview.clone());
+    let event = LoadingFinishedEvent {
+        request_id: request_id.to_owned(),
+        timestamp: timestamp(),
+        encoded_data_length: response_end.body_size,
+    };
+    let target = &state.targets[target_index];
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.network,
+        "Network.loadingFinished",
+        &event,
+    );
+}
+
+/// Tell the sessions of a page about the progress of one of its requests.
+pub(super) fn network_event(
+    state: &mut CdpState,
+    target_index: usize,
+    request_id: &str,
+    event: &NetworkEvent,
+) {
+    match event {
+        NetworkEvent::HttpRequest(request) => {
+            request_will_be_sent(state, target_index, request_id, request)
+        },
+        NetworkEvent::HttpResponse(response) => {
+            response_received(state, target_index, request_id, response)
+        },
+        NetworkEvent::HttpResponseEnd(response_end) => {
+            loading_finished(state, target_index, request_id, response_end)
+        },
+    }
+}
+
+/// The network commands that are supported:
+///
+/// - `enable`, `disable`: Start and stop sending the events of the domain
+///
+/// - `getResponseBody`: Returns the body of a response, up to the size that is kept for the
+///   network monitor
+pub(super) fn handle(session: &mut PageSession, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "Network.enable" | "Network.disable" => {
+            session.domains.network = method == "Network.enable";
+            empty_result()
+        },
+
+        "Network.getResponseBody" => {
+            let params: GetResponseBodyParams = parse_params(params)?;
+            let body = session
+                .target
+                .requests
+                .requests
+                .get(&params.request_id)
+                .and_then(|record| record.body.as_ref())
+                .ok_or_else(|| {
+                    CommandError::server_error("No resource with given identifier found")
+                })?;
+            let reply = match std::str::from_utf8(body) {
+                Ok(text) => GetResponseBodyReply {
+                    body: text.to_owned(),
+                    base64_encoded: false,
+                },
+                Err(_) => GetResponseBodyReply {
+                    body: base64::engine::general_purpose::STANDARD.encode(body),
+                    base64_encoded: true,
+                },
+            };
+            command_result(reply)
+        },
+
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
@@ -0,0 +1,240 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `Page` domain, which navigates the page and tells about the loading of its documents.
+//! The frames of a page are not reported, only its main frame.
+//!
+//! <https://chromedevtools.github.io/devtools-protocol/tot/Page/>
+
+use devtools_traits::DevtoolScriptControlMsg::{NavigateTo, Reload};
+use devtools_traits::ReadyState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use servo_url::ServoUrl;
+
+use crate::cdp::{
+    CdpState, CommandError, CommandResult, PageSession, PageTarget, command_result, empty_result,
+    new_id, parse_params, timestamp,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Frame {
+    id: String,
+    loader_id: String,
+    url: String,
+    domain_and_registry: String,
+    security_origin: String,
+    mime_type: &'static str,
+    secure_context_type: &'static str,
+    cross_origin_isolated_context_type: &'static str,
+    gated_api_features: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct FrameTree {
+    frame: Frame,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetFrameTreeReply {
+    frame_tree: FrameTree,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NavigateReply {
+    frame_id: String,
+    loader_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameEvent {
+    frame_id: String,
+}
+
+#[derive(Serialize)]
+struct FrameNavigatedEvent {
+    frame: Frame,
+    #[serde(rename = "type")]
+    type_: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LifecycleEvent {
+    frame_id: String,
+    loader_id: String,
+    name: &'static str,
+    timestamp: f64,
+}
+
+#[derive(Serialize)]
+struct TimestampEvent {
+    timestamp: f64,
+}
+
+#[derive(Deserialize)]
+struct EnabledParams {
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct NavigateParams {
+    url: String,
+}
+
+fn frame(target: &PageTarget) -> Frame {
+    let url = ServoUrl::parse(&target.url).ok();
+    let secure = url
+        .as_ref()
+        .is_some_and(ServoUrl::is_potentially_trustworthy);
+    Frame {
+        id: target.id.clone(),
+        loader_id: target.loader_id.clone(),
+        url: target.url.clone(),
+        domain_and_registry: String::new(),
+        security_origin: url
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_default(),
+        mime_type: "text/html",
+        secure_context_type: if secure { "Secure" } else { "InsecureScheme" },
+        cross_origin_isolated_context_type: "NotIsolated",
+        gated_api_features: vec![],
+    }
+}
+
+fn lifecycle_event(target: &PageTarget, name: &'static str) -> LifecycleEvent {
+    LifecycleEvent {
+        frame_id: target.id.clone(),
+        loader_id: target.loader_id.clone(),
+        name,
+        timestamp: timestamp(),
+    }
+}
+
+/// Tell the sessions of a page that it navigated to a new document.
+pub(super) fn frame_navigated(state: &CdpState, id: &str) {
+    let Some(target) = state.target(id) else {
+        return;
+    };
+    let frame_event = FrameEvent {
+        frame_id: target.id.clone(),
+    };
+    state.emit_to_sessions(
+        id,
+        |domains| domains.page,
+        "Page.frameStartedLoading",
+        &frame_event,
+    );
+    let navigated = FrameNavigatedEvent {
+        frame: frame(target),
+        type_: "Navigation",
+    };
+    state.emit_to_sessions(
+        id,
+        |domains| domains.page,
+        "Page.frameNavigated",
+        &navigated,
+    );
+    state.emit_to_sessions(
+        id,
+        |domains| domains.lifecycle_events,
+        "Page.lifecycleEvent",
+        &lifecycle_event(target, "init"),
+    );
+}
+
+/// Tell the sessions of a page that its document was parsed or loaded.
+pub(super) fn ready_state_changed(state: &CdpState, target: &PageTarget, ready_state: ReadyState) {
+    let (event, lifecycle) = match ready_state {
+        ReadyState::Loading => return,
+        ReadyState::Interactive => ("Page.domContentEventFired", "DOMContentLoaded"),
+        ReadyState::Complete => ("Page.loadEventFired", "load"),
+    };
+    let event_params = TimestampEvent {
+        timestamp: timestamp(),
+    };
+    state.emit_to_sessions(&target.id, |domains| domains.page, event, &event_params);
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.lifecycle_events,
+        "Page.lifecycleEvent",
+        &lifecycle_event(target, lifecycle),
+    );
+    if ready_state == ReadyState::Complete {
+        let frame_event = FrameEvent {
+            frame_id: target.id.clone(),
+        };
+        state.emit_to_sessions(
+            &target.id,
+            |domains| domains.page,
+            "Page.frameStoppedLoading",
+            &frame_event,
+        );
+    }
+}
+
+/// The page commands that are supported:
+///
+/// - `enable`, `disable`: Start and stop sending the events of the domain
+///
+/// - `setLifecycleEventsEnabled`: Start or stop sending the `lifecycleEvent` events
+///
+/// - `getFrameTree`: Returns the main frame of the page
+///
+/// - `navigate`: Starts navigating the page to a URL, and returns the id that the navigation
+///   will have once the new document is created
+///
+/// - `reload`: Reloads the page
+pub(super) fn handle(session: &mut PageSession, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "Page.enable" | "Page.disable" => {
+            session.domains.page = method == "Page.enable";
+            empty_result()
+        },
+
+        "Page.setLifecycleEventsEnabled" => {
+            let params: EnabledParams = parse_params(params)?;
+            session.domains.lifecycle_events = params.enabled;
+            empty_result()
+        },
+
+        "Page.getFrameTree" => command_result(GetFrameTreeReply {
+            frame_tree: FrameTree {
+                frame: frame(session.target),
+            },
+        }),
+
+        "Page.navigate" => {
+            let params: NavigateParams = parse_params(params)?;
+            let url = ServoUrl::parse(&params.url)
+                .map_err(|_| CommandError::server_error("Cannot navigate to invalid URL"))?;
+            let target = &mut *session.target;
+            target
+                .script_chan
+                .send(NavigateTo(target.pipeline, url))
+                .map_err(|_| CommandError::server_error("The page is gone"))?;
+            let loader_id = new_id();
+            target.pending_loader_id = Some(loader_id.clone());
+            command_result(NavigateReply {
+                frame_id: target.id.clone(),
+                loader_id,
+            })
+        },
+
+        "Page.reload" => {
+            let target = &session.target;
+            target
+                .script_chan
+                .send(Reload(target.pipeline))
+                .map_err(|_| CommandError::server_error("The page is gone"))?;
+            empty_result()
+        },
+
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
@@ -0,0 +1,387 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `Runtime` domain, which evaluates JavaScript in the page and reports the messages of its
+//! console. Objects are described by their class, but they are not kept alive for the client, so
+//! they have no id.
+//!
+//! <https://chromedevtools.github.io/devtools-protocol/tot/Runtime/>
+
+use devtools_traits::DevtoolScriptControlMsg::EvaluateJS;
+use devtools_traits::{
+    ConsoleMessage, ConsoleMessageArgument, EvaluateJSReply, LogLevel, PageError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use servo_url::ServoUrl;
+
+use crate::cdp::{
+    CdpState, CommandError, CommandResult, PageSession, PageTarget, command_result, empty_result,
+    parse_params, timestamp,
+};
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteObject {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subtype: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unserializable_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+impl RemoteObject {
+    fn number(number: f64) -> Self {
+        // Numbers that JSON is unable to represent are sent as their JavaScript source.
+        let unserializable = if number.is_nan() {
+            Some("NaN")
+        } else if number == f64::INFINITY {
+            Some("Infinity")
+        } else if number == f64::NEG_INFINITY {
+            Some("-Infinity")
+        } else if number == 0. && number.is_sign_negative() {
+            Some("-0")
+        } else {
+            None
+        };
+        Self {
+            type_: "number",
+            value: unserializable.is_none().then(|| number.into()),
+            unserializable_value: unserializable.map(str::to_owned),
+            description: Some(unserializable.map_or_else(|| number.to_string(), str::to_owned)),
+            ..Default::default()
+        }
+    }
+
+    fn string(string: String) -> Self {
+        Self {
+            type_: "string",
+            value: Some(string.into()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<EvaluateJSReply> for RemoteObject {
+    fn from(reply: EvaluateJSReply) -> Self {
+        match reply {
+            EvaluateJSReply::VoidValue => Self {
+                type_: "undefined",
+                ..Default::default()
+            },
+            EvaluateJSReply::NullValue => Self {
+                type_: "object",
+                subtype: Some("null"),
+                value: Some(Value::Null),
+                ..Default::default()
+            },
+            EvaluateJSReply::BooleanValue(boolean) => Self {
+                type_: "boolean",
+                value: Some(boolean.into()),
+                ..Default::default()
+            },
+            EvaluateJSReply::NumberValue(number) => Self::number(number),
+            EvaluateJSReply::StringValue(string) => Self::string(string),
+            EvaluateJSReply::ActorValue { class, .. } => Self {
+                type_: "object",
+                description: Some(class.clone()),
+                class_name: Some(class),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl From<&ConsoleMessageArgument> for RemoteObject {
+    fn from(argument: &ConsoleMessageArgument) -> Self {
+        match argument {
+            ConsoleMessageArgument::String(string) => Self::string(string.clone()),
+            ConsoleMessageArgument::Integer(integer) => Self::number(*integer as f64),
+            ConsoleMessageArgument::Number(number) => Self::number(*number),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuxData {
+    is_default: bool,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    frame_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecutionContextDescription {
+    id: u32,
+    origin: String,
+    name: String,
+    unique_id: String,
+    aux_data: AuxData,
+}
+
+#[derive(Serialize)]
+struct ExecutionContextCreatedEvent {
+    context: ExecutionContextDescription,
+}
+
+#[derive(Serialize)]
+struct EmptyEvent {}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConsoleApiCalledEvent {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    args: Vec<RemoteObject>,
+    execution_context_id: u32,
+    timestamp: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExceptionDetails {
+    exception_id: u32,
+    text: String,
+    line_number: u32,
+    column_number: u32,
+    url: String,
+    execution_context_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExceptionThrownEvent {
+    timestamp: f64,
+    exception_details: ExceptionDetails,
+}
+
+#[derive(Serialize)]
+struct EvaluateReply {
+    result: RemoteObject,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvaluateParams {
+    expression: String,
+    context_id: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallArgument {
+    value: Option<Value>,
+    unserializable_value: Option<String>,
+    object_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallFunctionOnParams {
+    function_declaration: String,
+    object_id: Option<String>,
+    #[serde(default)]
+    arguments: Vec<CallArgument>,
+    execution_context_id: Option<u32>,
+}
+
+fn execution_context(target: &PageTarget) -> ExecutionContextDescription {
+    ExecutionContextDescription {
+        id: target.execution_context_id,
+        origin: ServoUrl::parse(&target.url)
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_default(),
+        name: String::new(),
+        unique_id: format!("{}.{}", target.loader_id, target.execution_context_id),
+        aux_data: AuxData {
+            is_default: true,
+            type_: "default",
+            frame_id: target.id.clone(),
+        },
+    }
+}
+
+/// Tell the sessions of a page that the document, and so its execution context, was replaced.
+pub(super) fn execution_context_changed(state: &CdpState, id: &str) {
+    let Some(target) = state.target(id) else {
+        return;
+    };
+    state.emit_to_sessions(
+        id,
+        |domains| domains.runtime,
+        "Runtime.executionContextsCleared",
+        &EmptyEvent {},
+    );
+    state.emit_to_sessions(
+        id,
+        |domains| domains.runtime,
+        "Runtime.executionContextCreated",
+        &ExecutionContextCreatedEvent {
+            context: execution_context(target),
+        },
+    );
+}
+
+pub(super) fn console_api_called(state: &CdpState, target: &PageTarget, message: &ConsoleMessage) {
+    let type_ = match message.log_level {
+        LogLevel::Log => "log",
+        LogLevel::Debug => "debug",
+        LogLevel::Info => "info",
+        LogLevel::Warn => "warning",
+        LogLevel::Error => "error",
+        LogLevel::Clear => "clear",
+        LogLevel::Trace => "trace",
+    };
+    let event = ConsoleApiCalledEvent {
+        type_,
+        args: message.arguments.iter().map(RemoteObject::from).collect(),
+        execution_context_id: target.execution_context_id,
+        timestamp: timestamp(),
+    };
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.runtime,
+        "Runtime.consoleAPICalled",
+        &event,
+    );
+}
+
+pub(super) fn exception_thrown(state: &CdpState, target: &PageTarget, error: &PageError) {
+    if !error.error && !error.exception {
+        return;
+    }
+    let event = ExceptionThrownEvent {
+        timestamp: timestamp(),
+        exception_details: ExceptionDetails {
+            exception_id: 0,
+            text: error.error_message.clone(),
+            // Lines and columns are zero-based in this protocol.
+            line_number: error.line_number.saturating_sub(1),
+            column_number: error.column_number.saturating_sub(1),
+            url: error.source_name.clone(),
+            execution_context_id: target.execution_context_id,
+        },
+    };
+    state.emit_to_sessions(
+        &target.id,
+        |domains| domains.runtime,
+        "Runtime.exceptionThrown",
+        &event,
+    );
+}
+
+fn check_execution_context(target: &PageTarget, id: Option<u32>) -> Result<(), CommandError> {
+    match id {
+        Some(id) if id != target.execution_context_id => Err(CommandError::server_error(
+            "Cannot find context with specified id",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn evaluate(target: &PageTarget, expression: String) -> CommandResult {
+    let reply = target.request(|pipeline, sender| EvaluateJS(pipeline, expression, sender))?;
+    command_result(EvaluateReply {
+        result: reply.into(),
+    })
+}
+
+/// The JavaScript source of an argument of `callFunctionOn`.
+fn argument_source(argument: CallArgument) -> Result<String, CommandError> {
+    if argument.object_id.is_some() {
+        return Err(CommandError::server_error(
+            "Could not find object with given id",
+        ));
+    }
+    if let Some(value) = argument.unserializable_value {
+        let is_number = matches!(value.as_str(), "NaN" | "Infinity" | "-Infinity" | "-0");
+        let is_bigint = value
+            .strip_prefix('-')
+            .unwrap_or(&value)
+            .strip_suffix('n')
+            .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()));
+        if !is_number && !is_bigint {
+            return Err(CommandError::invalid_params("Invalid unserializable value"));
+        }
+        return Ok(value);
+    }
+    // JSON is valid JavaScript.
+    Ok(argument
+        .value
+        .map_or_else(|| "undefined".to_owned(), |value| value.to_string()))
+}
+
+/// The runtime commands that are supported:
+///
+/// - `enable`, `disable`: Start and stop sending the events of the domain, starting with the
+///   execution context of the current document
+///
+/// - `evaluate`: Evaluates an expression in the page
+///
+/// - `callFunctionOn`: Calls a function with arguments that are given by value
+///
+/// - `runIfWaitingForDebugger`: Lets a page run, which they always do
+///
+/// - `releaseObject`, `releaseObjectGroup`: Forget about objects, which are never kept
+pub(super) fn handle(session: &mut PageSession, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "Runtime.enable" => {
+            session.domains.runtime = true;
+            session.emit(
+                "Runtime.executionContextCreated",
+                ExecutionContextCreatedEvent {
+                    context: execution_context(session.target),
+                },
+            );
+            empty_result()
+        },
+
+        "Runtime.disable" => {
+            session.domains.runtime = false;
+            empty_result()
+        },
+
+        "Runtime.evaluate" => {
+            let params: EvaluateParams = parse_params(params)?;
+            check_execution_context(session.target, params.context_id)?;
+            evaluate(session.target, params.expression)
+        },
+
+        "Runtime.callFunctionOn" => {
+            let params: CallFunctionOnParams = parse_params(params)?;
+            check_execution_context(session.target, params.execution_context_id)?;
+            if params.object_id.is_some() {
+                return Err(CommandError::server_error(
+                    "Could not find object with given id",
+                ));
+            }
+            let arguments = params
+                .arguments
+                .into_iter()
+                .map(argument_source)
+                .collect::<Result<Vec<_>, _>>()?;
+            let expression = format!(
+                "({}).apply(undefined, [{}])",
+                params.function_declaration,
+                arguments.join(", ")
+            );
+            evaluate(session.target, expression)
+        },
+
+        "Runtime.runIfWaitingForDebugger" |
+        "Runtime.releaseObject" |
+        "Runtime.releaseObjectGroup" => empty_result(),
+
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
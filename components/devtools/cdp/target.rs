@@ -0,0 +1,351 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `Target` domain, which lists the pages and attaches sessions to them, and the `Browser`
+//! domain, which describes the browser.
+//!
+//! <https://chromedevtools.github.io/devtools-protocol/tot/Target/>
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cdp::{
+    CdpState, ClientId, CommandError, CommandResult, EnabledDomains, PROTOCOL_VERSION, Session,
+    command_result, empty_result, new_id, parse_params, send_event,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetInfo {
+    target_id: String,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    title: String,
+    url: String,
+    attached: bool,
+    can_access_opener: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetInfoEvent {
+    target_info: TargetInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachedToTargetEvent {
+    session_id: String,
+    target_info: TargetInfo,
+    waiting_for_debugger: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DetachedFromTargetEvent {
+    session_id: String,
+    target_id: String,
+}
+
+#[derive(Deserialize)]
+struct SetDiscoverTargetsParams {
+    discover: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetAutoAttachParams {
+    auto_attach: bool,
+    #[serde(default)]
+    flatten: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetParams {
+    target_id: String,
+    #[serde(default)]
+    flatten: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTargetInfoParams {
+    target_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DetachFromTargetParams {
+    session_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTargetsReply {
+    target_infos: Vec<TargetInfo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTargetInfoReply {
+    target_info: TargetInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachToTargetReply {
+    session_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBrowserContextsReply {
+    browser_context_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetVersionReply {
+    protocol_version: &'static str,
+    product: String,
+    revision: String,
+    user_agent: String,
+    js_version: String,
+}
+
+/// The description of a page, or `None` if there is no page with the given id.
+fn target_info(state: &CdpState, id: &str) -> Option<TargetInfo> {
+    let target = state.target(id)?;
+    Some(TargetInfo {
+        target_id: target.id.clone(),
+        type_: "page",
+        title: target.title.clone(),
+        url: target.url.clone(),
+        attached: state.sessions.iter().any(|session| session.target == id),
+        can_access_opener: false,
+    })
+}
+
+/// Attach a client to a page, and tell it about the new session with an event sent to the
+/// session that it asked from, if any.
+fn attach(
+    state: &mut CdpState,
+    client: ClientId,
+    target: &str,
+    parent_session: Option<&str>,
+) -> Option<String> {
+    let session_id = new_id();
+    state.sessions.push(Session {
+        id: Some(session_id.clone()),
+        client,
+        target: target.to_owned(),
+        domains: EnabledDomains::default(),
+    });
+    let event = AttachedToTargetEvent {
+        session_id: session_id.clone(),
+        target_info: target_info(state, target)?,
+        waiting_for_debugger: false,
+    };
+    let sender = &state.clients.get(&client)?.sender;
+    send_event(sender, parent_session, "Target.attachedToTarget", event);
+    Some(session_id)
+}
+
+/// Tell the clients about a new page, and attach the ones that asked to be to it.
+pub(super) fn target_created(state: &mut CdpState, id: &str) {
+    if let Some(target_info) = target_info(state, id) {
+        state.emit_to_discovering_clients("Target.targetCreated", &TargetInfoEvent { target_info });
+    }
+
+    let auto_attaching: Vec<_> = state
+        .clients
+        .iter()
+        .filter(|(_, client)| client.auto_attach)
+        .map(|(client, _)| *client)
+        .collect();
+    for client in auto_attaching {
+        attach(state, client, id, None);
+    }
+}
+
+pub(super) fn target_info_changed(state: &CdpState, id: &str) {
+    if let Some(target_info) = target_info(state, id) {
+        state.emit_to_discovering_clients(
+            "Target.targetInfoChanged",
+            &TargetInfoEvent { target_info },
+        );
+    }
+}
+
+/// The browser commands that are supported:
+///
+/// - `getVersion`: Returns the name and version of the browser
+pub(super) fn handle_browser(method: &str) -> CommandResult {
+    match method {
+        "Browser.getVersion" => command_result(GetVersionReply {
+            protocol_version: PROTOCOL_VERSION,
+            product: format!("Servo/{}", env!("CARGO_PKG_VERSION")),
+            revision: String::new(),
+            user_agent: String::new(),
+            js_version: String::new(),
+        }),
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
+
+/// The target commands that are supported:
+///
+/// - `setDiscoverTargets`: Starts or stops telling the client about the pages as they are
+///   created and changed, starting with the existing ones
+///
+/// - `setAutoAttach`: Starts or stops attaching the client to every page, starting with the
+///   existing ones. Pages have no targets of their own, so this does nothing for a session.
+///
+/// - `getTargets`, `getTargetInfo`: Describe the pages, or the browser
+///
+/// - `attachToTarget`, `detachFromTarget`: Open or close a session with a page
+///
+/// - `getBrowserContexts`: Returns the browser contexts other than the default one, of which
+///   there are none
+pub(super) fn handle(
+    state: &mut CdpState,
+    client: ClientId,
+    session: Option<usize>,
+    method: &str,
+    params: &Value,
+) -> CommandResult {
+    let session_id = session.and_then(|index| state.sessions[index].id.clone());
+    match method {
+        "Target.setDiscoverTargets" => {
+            let params: SetDiscoverTargetsParams = parse_params(params)?;
+            let client_state = state
+                .clients
+                .get_mut(&client)
+                .ok_or_else(|| CommandError::server_error("The client is gone"))?;
+            client_state.discover_targets = params.discover;
+            if params.discover {
+                let sender = client_state.sender.clone();
+                for target in &state.targets {
+                    if let Some(target_info) = target_info(state, &target.id) {
+                        send_event(
+                            &sender,
+                            None,
+                            "Target.targetCreated",
+                            TargetInfoEvent { target_info },
+                        );
+                    }
+                }
+            }
+            empty_result()
+        },
+
+        "Target.setAutoAttach" => {
+            let params: SetAutoAttachParams = parse_params(params)?;
+            if session.is_some() {
+                return empty_result();
+            }
+            if params.auto_attach && !params.flatten {
+                return Err(CommandError::invalid_params(
+                    "Only flatten mode is supported",
+                ));
+            }
+            let client_state = state
+                .clients
+                .get_mut(&client)
+                .ok_or_else(|| CommandError::server_error("The client is gone"))?;
+            let was_auto_attaching = client_state.auto_attach;
+            client_state.auto_attach = params.auto_attach;
+            if params.auto_attach && !was_auto_attaching {
+                let targets: Vec<_> = state
+                    .targets
+                    .iter()
+                    .map(|target| target.id.clone())
+                    .collect();
+                for target in targets {
+                    let attached = state
+                        .sessions
+                        .iter()
+                        .any(|session| session.client == client && session.target == target);
+                    if !attached {
+                        attach(state, client, &target, None);
+                    }
+                }
+            }
+            empty_result()
+        },
+
+        "Target.getTargets" => command_result(GetTargetsReply {
+            target_infos: state
+                .targets
+                .iter()
+                .filter_map(|target| target_info(state, &target.id))
+                .collect(),
+        }),
+
+        "Target.getTargetInfo" => {
+            let params: GetTargetInfoParams = parse_params(params)?;
+            let target_info = match params.target_id {
+                Some(id) if id != state.browser_id => target_info(state, &id)
+                    .ok_or_else(|| CommandError::invalid_params("No target with given id found"))?,
+                _ => TargetInfo {
+                    target_id: state.browser_id.clone(),
+                    type_: "browser",
+                    title: String::new(),
+                    url: String::new(),
+                    attached: true,
+                    can_access_opener: false,
+                },
+            };
+            command_result(GetTargetInfoReply { target_info })
+        },
+
+        "Target.attachToTarget" => {
+            let params: TargetParams = parse_params(params)?;
+            if !params.flatten {
+                return Err(CommandError::invalid_params(
+                    "Only flatten mode is supported",
+                ));
+            }
+            if state.target(&params.target_id).is_none() {
+                return Err(CommandError::invalid_params(
+                    "No target with given id found",
+                ));
+            }
+            let session_id = attach(state, client, &params.target_id, session_id.as_deref())
+                .ok_or_else(|| CommandError::server_error("The client is gone"))?;
+            command_result(AttachToTargetReply { session_id })
+        },
+
+        "Target.detachFromTarget" => {
+            let params: DetachFromTargetParams = parse_params(params)?;
+            let index = state
+                .sessions
+                .iter()
+                .position(|session| {
+                    session.client == client && session.id.as_ref() == Some(&params.session_id)
+                })
+                .ok_or_else(|| CommandError::invalid_params("No session with given id found"))?;
+            let detached = state.sessions.remove(index);
+            if let Some(client) = state.clients.get(&client) {
+                send_event(
+                    &client.sender,
+                    session_id.as_deref(),
+                    "Target.detachedFromTarget",
+                    DetachedFromTargetEvent {
+                        session_id: params.session_id,
+                        target_id: detached.target,
+                    },
+                );
+            }
+            empty_result()
+        },
+
+        "Target.getBrowserContexts" => command_result(GetBrowserContextsReply {
+            browser_context_ids: vec![],
+        }),
+
+        _ => Err(CommandError::method_not_found(method)),
+    }
+}
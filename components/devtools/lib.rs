@@ -23,13 +23,15 @@ use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use crossbeam_channel::{Receiver, Sender, unbounded};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, ConsoleMessage, ConsoleMessageBuilder, DevtoolScriptControlMsg,
-    DevtoolsControlMsg, DevtoolsPageInfo, LogLevel, NavigationState, NetworkEvent, PageError,
-    ScriptToDevtoolsControlMsg, WorkerId,
+    DevtoolsControlMsg, DevtoolsPageInfo, DomMutation, LogLevel, NavigationState, NetworkEvent,
+    NodeInfo, PageError, PauseInfo, ReadyState, ScriptToDevtoolsControlMsg, SourceInfo, WorkerId,
 };
 use embedder_traits::{AllowOrDeny, EmbedderMsg, EmbedderProxy};
 use ipc_channel::ipc::{self, IpcSender};
 use log::trace;
+use profile_traits::{mem, time};
 use serde::Serialize;
+use servo_config::pref;
 use servo_rand::RngCore;
 
 use crate::actor::{Actor, ActorRegistry};
@@ -37,32 +39,42 @@ use crate::actors::browsing_context::BrowsingContextActor;
 use crate::actors::console::{ConsoleActor, Root};
 use crate::actors::device::DeviceActor;
 use crate::actors::framerate::FramerateActor;
+use crate::actors::inspector::InspectorActor;
+use crate::actors::inspector::walker::WalkerActor;
 use crate::actors::network_event::NetworkEventActor;
+use crate::actors::perf::PerfActor;
 use crate::actors::performance::PerformanceActor;
 use crate::actors::preference::PreferenceActor;
 use crate::actors::process::ProcessActor;
 use crate::actors::root::RootActor;
+use crate::actors::source::SourceActor;
 use crate::actors::thread::ThreadActor;
 use crate::actors::worker::{WorkerActor, WorkerType};
+use crate::cdp::CdpServer;
 use crate::network_handler::handle_network_event;
 use crate::protocol::JsonPacketStream;
 
 mod actor;
 /// <https://searchfox.org/mozilla-central/source/devtools/server/actors>
 mod actors {
+    pub mod breakpoint;
     pub mod browsing_context;
     pub mod console;
     pub mod device;
+    pub mod environment;
+    pub mod frame;
     pub mod framerate;
     pub mod inspector;
     pub mod memory;
     pub mod network_event;
     pub mod object;
+    pub mod perf;
     pub mod performance;
     pub mod preference;
     pub mod process;
     pub mod reflow;
     pub mod root;
+    pub mod source;
     pub mod stylesheets;
     pub mod tab;
     pub mod thread;
@@ -70,6 +82,7 @@ mod actors {
     pub mod watcher;
     pub mod worker;
 }
+mod cdp;
 mod network_handler;
 mod protocol;
 
@@ -84,15 +97,28 @@ pub struct EmptyReplyMsg {
     pub from: String,
 }
 
-/// Spin up a devtools server that listens for connections on the specified port.
-pub fn start_server(port: u16, embedder: EmbedderProxy) -> Sender<DevtoolsControlMsg> {
+/// Spin up a devtools server that listens for connections on the specified port. The profilers
+/// are used to record performance profiles and to measure the memory of pages.
+pub fn start_server(
+    port: u16,
+    embedder: EmbedderProxy,
+    time_profiler_chan: time::ProfilerChan,
+    mem_profiler_chan: mem::ProfilerChan,
+) -> Sender<DevtoolsControlMsg> {
     let (sender, receiver) = unbounded();
     {
         let sender = sender.clone();
         thread::Builder::new()
             .name("Devtools".to_owned())
             .spawn(move || {
-                if let Some(instance) = DevtoolsInstance::create(sender, receiver, port, embedder) {
+                if let Some(instance) = DevtoolsInstance::create(
+                    sender,
+                    receiver,
+                    port,
+                    embedder,
+                    time_profiler_chan,
+                    mem_profiler_chan,
+                ) {
                     instance.run()
                 }
             })
@@ -112,6 +138,9 @@ struct DevtoolsInstance {
     actor_workers: HashMap<WorkerId, String>,
     actor_requests: HashMap<String, String>,
     connections: HashMap<StreamId, TcpStream>,
+    mem_profiler_chan: mem::ProfilerChan,
+    /// The Chrome DevTools Protocol server, if it is enabled.
+    cdp: Option<CdpServer>,
 }
 
 impl DevtoolsInstance {
@@ -120,6 +149,8 @@ impl DevtoolsInstance {
         receiver: Receiver<DevtoolsControlMsg>,
         port: u16,
         embedder: EmbedderProxy,
+        time_profiler_chan: time::ProfilerChan,
+        mem_profiler_chan: mem::ProfilerChan,
     ) -> Option<Self> {
         let bound = TcpListener::bind(("0.0.0.0", port)).ok().and_then(|l| {
             l.local_addr()
@@ -143,6 +174,7 @@ impl DevtoolsInstance {
         // Create basic actors
         let mut registry = ActorRegistry::new();
         let performance = PerformanceActor::new(registry.new_name("performance"));
+        let perf = PerfActor::new(registry.new_name("perf"), time_profiler_chan);
         let device = DeviceActor::new(registry.new_name("device"));
         let preference = PreferenceActor::new(registry.new_name("preference"));
         let process = ProcessActor::new(registry.new_name("process"));
@@ -151,6 +183,7 @@ impl DevtoolsInstance {
             workers: vec![],
             device: device.name(),
             performance: performance.name(),
+            perf: perf.name(),
             preference: preference.name(),
             process: process.name(),
             active_tab: None.into(),
@@ -158,6 +191,7 @@ impl DevtoolsInstance {
 
         registry.register(root);
         registry.register(Box::new(performance));
+        registry.register(Box::new(perf));
         registry.register(Box::new(device));
         registry.register(Box::new(preference));
         registry.register(Box::new(process));
@@ -165,6 +199,12 @@ impl DevtoolsInstance {
 
         let actors = registry.create_shareable();
 
+        let cdp = if pref!(devtools_cdp_server_enabled) {
+            CdpServer::start(pref!(devtools_cdp_server_port) as u16)
+        } else {
+            None
+        };
+
         let instance = Self {
             actors,
             browsing_contexts: HashMap::new(),
@@ -173,6 +213,8 @@ impl DevtoolsInstance {
             actor_requests: HashMap::new(),
             actor_workers: HashMap::new(),
             connections: HashMap::new(),
+            mem_profiler_chan,
+            cdp,
         };
 
         thread::Builder::new()
@@ -229,6 +271,26 @@ impl DevtoolsInstance {
                     pipeline,
                     title,
                 )) => self.handle_title_changed(pipeline, title),
+                DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReadyStateChanged(
+                    pipeline,
+                    ready_state,
+                )) => self.handle_ready_state_changed(pipeline, ready_state),
+                DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::DomMutations(
+                    pipeline,
+                    mutations,
+                )) => self.handle_dom_mutations(pipeline, mutations),
+                DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::CreateSourceActor(
+                    pipeline,
+                    source,
+                )) => self.handle_create_source_actor(pipeline, source),
+                DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::DebuggerPaused(
+                    pipeline,
+                    pause,
+                )) => self.handle_debugger_paused(pipeline, pause),
+                DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::InspectNode(
+                    pipeline,
+                    nodes,
+                )) => self.handle_inspect_node(pipeline, nodes),
                 DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::NewGlobal(
                     ids,
                     script_sender,
@@ -274,6 +336,7 @@ impl DevtoolsInstance {
                     let pipeline_id = match network_event {
                         NetworkEvent::HttpResponse(ref response) => response.pipeline_id,
                         NetworkEvent::HttpRequest(ref request) => request.pipeline_id,
+                        NetworkEvent::HttpResponseEnd(ref response_end) => response_end.pipeline_id,
                     };
                     self.handle_network_event(connections, pipeline_id, request_id, network_event);
                 },
@@ -294,6 +357,9 @@ impl DevtoolsInstance {
     }
 
     fn handle_navigate(&self, browsing_context_id: BrowsingContextId, state: NavigationState) {
+        if let Some(cdp) = &self.cdp {
+            cdp.navigate(browsing_context_id, &state);
+        }
         let actor_name = self.browsing_contexts.get(&browsing_context_id).unwrap();
         self.actors
             .lock()
@@ -321,7 +387,7 @@ impl DevtoolsInstance {
             assert!(self.pipelines.contains_key(&pipeline_id));
             assert!(self.browsing_contexts.contains_key(&browsing_context_id));
 
-            let thread = ThreadActor::new(actors.new_name("context"));
+            let thread = ThreadActor::new(actors.new_name("context"), script_sender.clone());
             let thread_name = thread.name();
             actors.register(Box::new(thread));
 
@@ -344,6 +410,14 @@ impl DevtoolsInstance {
 
             Root::DedicatedWorker(worker_name)
         } else {
+            if let Some(cdp) = self.cdp.as_ref().filter(|_| page_info.is_top_level_global) {
+                cdp.page_created(
+                    browsing_context_id,
+                    pipeline_id,
+                    script_sender.clone(),
+                    &page_info,
+                );
+            }
             self.pipelines.insert(pipeline_id, browsing_context_id);
             let name = self
                 .browsing_contexts
@@ -356,6 +430,7 @@ impl DevtoolsInstance {
                         page_info,
                         pipeline_id,
                         script_sender,
+                        self.mem_profiler_chan.clone(),
                         &mut actors,
                     );
                     let name = browsing_context_actor.name();
@@ -383,6 +458,9 @@ impl DevtoolsInstance {
     }
 
     fn handle_title_changed(&self, pipeline_id: PipelineId, title: String) {
+        if let Some(cdp) = &self.cdp {
+            cdp.title_changed(pipeline_id, title.clone());
+        }
         let bc = match self.pipelines.get(&pipeline_id) {
             Some(bc) => bc,
             None => return,
@@ -396,12 +474,118 @@ impl DevtoolsInstance {
         browsing_context.title_changed(pipeline_id, title);
     }
 
+    fn handle_ready_state_changed(&self, pipeline_id: PipelineId, ready_state: ReadyState) {
+        if let Some(cdp) = &self.cdp {
+            cdp.ready_state_changed(pipeline_id, ready_state);
+        }
+    }
+
+    fn handle_dom_mutations(&self, pipeline_id: PipelineId, mutations: Vec<DomMutation>) {
+        let Some(name) = self
+            .pipelines
+            .get(&pipeline_id)
+            .and_then(|bc| self.browsing_contexts.get(bc))
+        else {
+            return;
+        };
+        let actors = self.actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(name);
+        let inspector = actors.find::<InspectorActor>(&browsing_context.inspector);
+        let Some(walker) = inspector.walker.borrow().clone() else {
+            return;
+        };
+        let walker = actors.find::<WalkerActor>(&walker);
+        if walker.pipeline != pipeline_id {
+            return;
+        }
+        walker.new_mutations(
+            &actors,
+            mutations,
+            browsing_context.streams.borrow_mut().values_mut(),
+        );
+    }
+
+    fn handle_inspect_node(&self, pipeline_id: PipelineId, nodes: Vec<NodeInfo>) {
+        let Some(name) = self
+            .pipelines
+            .get(&pipeline_id)
+            .and_then(|bc| self.browsing_contexts.get(bc))
+        else {
+            return;
+        };
+        let actors = self.actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(name);
+        let inspector = actors.find::<InspectorActor>(&browsing_context.inspector);
+        let Some(walker) = inspector.walker.borrow().clone() else {
+            return;
+        };
+        let walker = actors.find::<WalkerActor>(&walker);
+        if walker.pipeline != pipeline_id {
+            return;
+        }
+        walker.pick_node(
+            &actors,
+            nodes,
+            browsing_context.streams.borrow_mut().values_mut(),
+        );
+    }
+
+    fn handle_create_source_actor(&self, pipeline_id: PipelineId, source: SourceInfo) {
+        let Some(name) = self
+            .pipelines
+            .get(&pipeline_id)
+            .and_then(|bc| self.browsing_contexts.get(bc))
+        else {
+            return;
+        };
+        let mut actors = self.actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(name);
+        let source = SourceActor::new(
+            actors.new_name("source"),
+            pipeline_id,
+            source,
+            browsing_context.script_chan.clone(),
+        );
+        let source_name = source.name();
+        let form = source.encodable();
+        actors.register(Box::new(source));
+
+        let browsing_context = actors.find::<BrowsingContextActor>(name);
+        actors
+            .find::<ThreadActor>(&browsing_context.thread)
+            .add_source(source_name);
+        browsing_context.resource_available(form, "source".into());
+    }
+
+    fn handle_debugger_paused(&self, pipeline_id: PipelineId, pause: PauseInfo) {
+        let Some(name) = self
+            .pipelines
+            .get(&pipeline_id)
+            .and_then(|bc| self.browsing_contexts.get(bc))
+        else {
+            return;
+        };
+        let mut actors = self.actors.lock().unwrap();
+        let browsing_context = actors.find::<BrowsingContextActor>(name);
+        let thread = actors.find::<ThreadActor>(&browsing_context.thread);
+        thread.paused(
+            &actors,
+            pipeline_id,
+            pause,
+            browsing_context.streams.borrow_mut().values_mut(),
+        );
+        actors.flush_pending();
+    }
+
     fn handle_page_error(
         &self,
         pipeline_id: PipelineId,
         worker_id: Option<WorkerId>,
         page_error: PageError,
     ) {
+        if let Some(cdp) = self.cdp.as_ref().filter(|_| worker_id.is_none()) {
+            cdp.page_error(pipeline_id, &page_error);
+        }
         let console_actor_name = match self.find_console_actor(pipeline_id, worker_id) {
             Some(name) => name,
             None => return,
@@ -418,6 +602,9 @@ impl DevtoolsInstance {
         worker_id: Option<WorkerId>,
         console_message: ConsoleMessage,
     ) {
+        if let Some(cdp) = self.cdp.as_ref().filter(|_| worker_id.is_none()) {
+            cdp.console_message(pipeline_id, &console_message);
+        }
         let console_actor_name = match self.find_console_actor(pipeline_id, worker_id) {
             Some(name) => name,
             None => return,
@@ -456,6 +643,9 @@ impl DevtoolsInstance {
         request_id: String,
         network_event: NetworkEvent,
     ) {
+        if let Some(cdp) = &self.cdp {
+            cdp.network_event(pipeline_id, &request_id, &network_event);
+        }
         let console_actor_name = match self.find_console_actor(pipeline_id, None) {
             Some(name) => name,
             None => return,
@@ -109,13 +109,14 @@ pub(crate) fn handle_network_event(
             for stream in &mut connections {
                 let _ = stream.write_json_packet(&msg);
             }
+
             let msg = NetworkEventUpdateMsg {
                 from: netevent_actor_name.clone(),
                 type_: "networkEventUpdate".to_owned(),
-                update_type: "eventTimings".to_owned(),
+                update_type: "securityInfo".to_owned(),
             };
-            let extra = EventTimingsUpdateMsg {
-                total_time: actor.total_time().as_millis() as u64,
+            let extra = SecurityInfoUpdateMsg {
+                state: "insecure".to_owned(),
             };
             for stream in &mut connections {
                 let _ = stream.write_merged_json_packet(&msg, &extra);
@@ -124,40 +125,44 @@ pub(crate) fn handle_network_event(
             let msg = NetworkEventUpdateMsg {
                 from: netevent_actor_name.clone(),
                 type_: "networkEventUpdate".to_owned(),
-                update_type: "securityInfo".to_owned(),
-            };
-            let extra = SecurityInfoUpdateMsg {
-                state: "insecure".to_owned(),
+                update_type: "responseCookies".to_owned(),
             };
             for stream in &mut connections {
-                let _ = stream.write_merged_json_packet(&msg, &extra);
+                let _ = stream.write_merged_json_packet(&msg, &actor.response_cookies());
             }
 
             let msg = NetworkEventUpdateMsg {
-                from: netevent_actor_name.clone(),
+                from: netevent_actor_name,
                 type_: "networkEventUpdate".to_owned(),
-                update_type: "responseContent".to_owned(),
+                update_type: "responseHeaders".to_owned(),
             };
             for stream in &mut connections {
-                let _ = stream.write_merged_json_packet(&msg, &actor.response_content());
+                let _ = stream.write_merged_json_packet(&msg, &actor.response_headers());
             }
+        },
+        NetworkEvent::HttpResponseEnd(response_end) => {
+            // The body and timings are only known once the whole body has been received.
+            actor.add_response_end(response_end);
 
             let msg = NetworkEventUpdateMsg {
                 from: netevent_actor_name.clone(),
                 type_: "networkEventUpdate".to_owned(),
-                update_type: "responseCookies".to_owned(),
+                update_type: "responseContent".to_owned(),
             };
             for stream in &mut connections {
-                let _ = stream.write_merged_json_packet(&msg, &actor.response_cookies());
+                let _ = stream.write_merged_json_packet(&msg, &actor.response_content());
             }
 
             let msg = NetworkEventUpdateMsg {
                 from: netevent_actor_name,
                 type_: "networkEventUpdate".to_owned(),
-                update_type: "responseHeaders".to_owned(),
+                update_type: "eventTimings".to_owned(),
+            };
+            let extra = EventTimingsUpdateMsg {
+                total_time: actor.total_time().as_millis() as u64,
             };
             for stream in &mut connections {
-                let _ = stream.write_merged_json_packet(&msg, &actor.response_headers());
+                let _ = stream.write_merged_json_packet(&msg, &extra);
             }
         },
     }
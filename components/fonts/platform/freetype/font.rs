@@ -18,6 +18,7 @@ use freetype_sys::{
 };
 use log::debug;
 use parking_lot::ReentrantMutex;
+use servo_config::opts;
 use style::Zero;
 use style::computed_values::font_stretch::T as FontStretch;
 use style::computed_values::font_weight::T as FontWeight;
@@ -510,9 +511,14 @@ impl FreeTypeFaceHelpers for FT_Face {
 
         // Default to slight hinting, which is what most
         // Linux distros use by default, and is a better
-        // default than no hinting.
+        // default than no hinting. Deterministic rendering
+        // uses no hinting, like WebRender does then.
         // TODO(gw): Make this configurable.
-        load_flags |= FT_LOAD_TARGET_LIGHT as i32;
+        if opts::get().deterministic {
+            load_flags |= FT_LOAD_NO_HINTING;
+        } else {
+            load_flags |= FT_LOAD_TARGET_LIGHT as i32;
+        }
 
         let face_flags = unsafe { (*self).face_flags };
         if (face_flags & (FT_FACE_FLAG_FIXED_SIZES as FT_Long)) != 0 {
@@ -16,7 +16,7 @@ use log::debug;
 use malloc_size_of_derive::MallocSizeOf;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use servo_config::pref;
+use servo_config::{opts, pref};
 use servo_url::ServoUrl;
 use style::font_face::{FontFaceRuleData, FontStyle as FontFaceStyle};
 use style::values::computed::font::{
@@ -309,6 +309,20 @@ impl SystemFontService {
             SingleFontFamily::Generic(generic) => generic,
         };
 
+        // Deterministic rendering only uses the generic font families that can be configured,
+        // so that it does not depend on the fonts that the system picks for the others.
+        let generic = match generic {
+            GenericFontFamily::Cursive | GenericFontFamily::Fantasy
+                if opts::get().deterministic =>
+            {
+                &GenericFontFamily::None
+            },
+            GenericFontFamily::SystemUi if opts::get().deterministic => {
+                &GenericFontFamily::SansSerif
+            },
+            generic => generic,
+        };
+
         let resolved_font = match generic {
             GenericFontFamily::None => &self.generic_fonts.default,
             GenericFontFamily::Serif => &self.generic_fonts.serif,
@@ -0,0 +1,599 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Construction of the accessibility tree of a document from its DOM and fragment tree.
+//!
+//! The tree is expressed in terms of [AccessKit](https://accesskit.dev) nodes, which the
+//! embedder can hand to a platform accessibility adapter. Layout keeps the nodes that it
+//! last sent, so that after each reflow only the nodes that changed need to be sent, and so
+//! that scrolling only needs to move the bounds of the nodes that it moved.
+
+use std::collections::HashMap;
+
+use accesskit::{Node, NodeId, Rect, Role, Toggled, Tree, TreeUpdate};
+use app_units::Au;
+use euclid::default::{Rect as AuRect, Vector2D};
+use fxhash::FxHashMap;
+use html5ever::{local_name, namespace_url, ns};
+use script_layout_interface::wrapper_traits::{
+    LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
+};
+use script_layout_interface::{LayoutElementType, LayoutNodeType};
+use selectors::Element as _;
+use style::dom::{OpaqueNode, TElement, TNode};
+use stylo_dom::ElementState;
+use webrender_api::units::LayoutVector2D;
+use webrender_api::{ExternalScrollId, PipelineId};
+
+use crate::FragmentTree;
+use crate::context::LayoutContext;
+use crate::dom::NodeExt;
+use crate::dom_traversal::iter_child_nodes;
+use crate::scroll_anchoring::scroll_translations;
+use crate::style_ext::Display;
+
+fn node_id(node: OpaqueNode) -> NodeId {
+    NodeId(node.0 as u64)
+}
+
+fn to_accesskit_rect(rect: &AuRect<Au>) -> Rect {
+    Rect {
+        x0: rect.min_x().to_f64_px(),
+        y0: rect.min_y().to_f64_px(),
+        x1: rect.max_x().to_f64_px(),
+        y1: rect.max_y().to_f64_px(),
+    }
+}
+
+/// The accessibility tree of a document, as last sent to the embedder.
+#[derive(Default)]
+pub struct AccessibilityTree {
+    nodes: FxHashMap<NodeId, Node>,
+    root: Option<NodeId>,
+    focus: Option<NodeId>,
+    /// The scroll offsets that the bounds of the nodes account for.
+    scroll_offsets: HashMap<ExternalScrollId, LayoutVector2D>,
+    /// The border boxes of the nodes in the fragment tree that the tree was built from,
+    /// which scrolling moves without changing the fragment tree.
+    border_boxes: FxHashMap<OpaqueNode, AuRect<Au>>,
+    /// How far scrolling moved the nodes that are scrolled, for `scroll_offsets`.
+    scroll_translations: FxHashMap<OpaqueNode, Vector2D<Au>>,
+}
+
+impl AccessibilityTree {
+    /// Update the accessibility tree rooted at `root_element` after a reflow and return a
+    /// [`TreeUpdate`] with the nodes that changed since the last update, or `None` if
+    /// nothing changed. The tree is only rebuilt if `layout_changed`. When only the scroll
+    /// offsets changed, the bounds of the nodes that scrolling moved are updated instead.
+    pub fn update<'dom>(
+        &mut self,
+        context: &LayoutContext,
+        root_element: impl LayoutNode<'dom> + 'dom,
+        fragment_tree: &FragmentTree,
+        scroll_offsets: &HashMap<ExternalScrollId, LayoutVector2D>,
+        pipeline_id: PipelineId,
+        layout_changed: bool,
+    ) -> Option<TreeUpdate> {
+        if !layout_changed && self.root.is_some() {
+            if self.scroll_offsets == *scroll_offsets {
+                return None;
+            }
+            return self.update_for_scroll(fragment_tree, scroll_offsets, pipeline_id);
+        }
+        self.scroll_offsets = scroll_offsets.clone();
+
+        let mut builder = AccessibilityTreeBuilder {
+            context,
+            border_boxes: fragment_tree.get_border_boxes_for_all_nodes(),
+            scroll_translations: scroll_translations(fragment_tree, scroll_offsets, pipeline_id),
+            nodes: FxHashMap::default(),
+            focus: None,
+        };
+        let root = node_id(root_element.opaque());
+
+        // While a modal dialog is open, the rest of the document is inert, so the dialog is
+        // exposed as the only child of the document.
+        let modal_dialog = root_element.traverse_preorder().find(|node| {
+            node.as_element()
+                .is_some_and(|element| element.state().contains(ElementState::MODAL))
+        });
+        let children = match modal_dialog {
+            Some(modal_dialog) => builder.build_element(modal_dialog).into_iter().collect(),
+            None => builder.build_children(root_element),
+        };
+        builder.build_root(root_element, children);
+        let AccessibilityTreeBuilder {
+            nodes,
+            focus,
+            mut border_boxes,
+            scroll_translations,
+            ..
+        } = builder;
+        let focus = focus.unwrap_or(root);
+        border_boxes.retain(|node, _| nodes.contains_key(&node_id(*node)));
+        self.border_boxes = border_boxes;
+        self.scroll_translations = scroll_translations;
+
+        let tree_changed = self.root != Some(root);
+        let changed_nodes: Vec<(NodeId, Node)> = nodes
+            .iter()
+            .filter(|(id, node)| tree_changed || self.nodes.get(id) != Some(node))
+            .map(|(id, node)| (*id, node.clone()))
+            .collect();
+        if changed_nodes.is_empty() && self.focus == Some(focus) {
+            return None;
+        }
+
+        self.nodes = nodes;
+        self.root = Some(root);
+        self.focus = Some(focus);
+        Some(TreeUpdate {
+            nodes: changed_nodes,
+            tree: tree_changed.then(|| Tree::new(root)),
+            focus,
+        })
+    }
+
+    /// Move the bounds of the nodes that scrolling moved since the last update, without
+    /// rebuilding the tree, and return a [`TreeUpdate`] with those nodes.
+    fn update_for_scroll(
+        &mut self,
+        fragment_tree: &FragmentTree,
+        scroll_offsets: &HashMap<ExternalScrollId, LayoutVector2D>,
+        pipeline_id: PipelineId,
+    ) -> Option<TreeUpdate> {
+        self.scroll_offsets = scroll_offsets.clone();
+        let scroll_translations = scroll_translations(fragment_tree, scroll_offsets, pipeline_id);
+        let translation = |translations: &FxHashMap<OpaqueNode, Vector2D<Au>>, node| {
+            translations
+                .get(node)
+                .copied()
+                .unwrap_or_else(Vector2D::zero)
+        };
+
+        let mut changed_nodes = Vec::new();
+        for (node, border_box) in &self.border_boxes {
+            let new_translation = translation(&scroll_translations, node);
+            if translation(&self.scroll_translations, node) == new_translation {
+                continue;
+            }
+            let id = node_id(*node);
+            let Some(accessibility_node) = self.nodes.get_mut(&id) else {
+                continue;
+            };
+            accessibility_node
+                .set_bounds(to_accesskit_rect(&border_box.translate(new_translation)));
+            changed_nodes.push((id, accessibility_node.clone()));
+        }
+        self.scroll_translations = scroll_translations;
+
+        if changed_nodes.is_empty() {
+            return None;
+        }
+        Some(TreeUpdate {
+            nodes: changed_nodes,
+            tree: None,
+            focus: self.focus.or(self.root)?,
+        })
+    }
+}
+
+struct AccessibilityTreeBuilder<'a> {
+    context: &'a LayoutContext<'a>,
+    border_boxes: FxHashMap<OpaqueNode, AuRect<Au>>,
+    /// How far scrolling moved the nodes that are scrolled.
+    scroll_translations: FxHashMap<OpaqueNode, Vector2D<Au>>,
+    nodes: FxHashMap<NodeId, Node>,
+    focus: Option<NodeId>,
+}
+
+impl AccessibilityTreeBuilder<'_> {
+    /// The bounds of `node` where it is currently visible, accounting for the scroll offsets
+    /// of the scrollers that it is in.
+    fn bounds(&self, node: OpaqueNode) -> Option<Rect> {
+        let border_box = self.border_boxes.get(&node)?;
+        let translation = self
+            .scroll_translations
+            .get(&node)
+            .copied()
+            .unwrap_or_else(Vector2D::zero);
+        Some(to_accesskit_rect(&border_box.translate(translation)))
+    }
+
+    /// Build the accessibility nodes for the children of `parent`, returning their ids in
+    /// tree order. Nodes that are not rendered or hidden from assistive technologies don't
+    /// get accessibility nodes, while the children of elements with `display: contents` are
+    /// included as children of their closest included ancestor.
+    fn build_children<'dom>(&mut self, parent: impl NodeExt<'dom>) -> Vec<NodeId> {
+        let mut children = Vec::new();
+        for child in iter_child_nodes(parent) {
+            if child.is_text_node() {
+                let text = child.to_threadsafe().node_text_content();
+                let text = text.trim();
+                if text.is_empty() {
+                    continue;
+                }
+                let mut node = Node::new(Role::Label);
+                node.set_value(text);
+                let id = node_id(child.opaque());
+                self.nodes.insert(id, node);
+                children.push(id);
+                continue;
+            }
+
+            if !child.is_element() {
+                continue;
+            }
+            match Display::from(child.style(self.context).get_box().display) {
+                Display::None => continue,
+                Display::Contents => {
+                    children.extend(self.build_children(child));
+                    continue;
+                },
+                Display::GeneratingBox(_) => {},
+            }
+            if let Some(id) = self.build_element(child) {
+                children.push(id);
+            }
+        }
+        children
+    }
+
+    fn build_root<'dom>(&mut self, element: impl NodeExt<'dom>, children: Vec<NodeId>) {
+        let mut node = Node::new(Role::Document);
+        node.set_children(children);
+        if let Some(bounds) = self.bounds(element.opaque()) {
+            node.set_bounds(bounds);
+        }
+        self.nodes.insert(node_id(element.opaque()), node);
+    }
+
+    fn build_element<'dom>(&mut self, node: impl NodeExt<'dom>) -> Option<NodeId> {
+        let threadsafe_node = node.to_threadsafe();
+        let element = threadsafe_node.as_element()?;
+        if element.get_attr(&ns!(), &local_name!("aria-hidden")) == Some("true") ||
+            element.get_attr(&ns!(), &local_name!("inert")).is_some()
+        {
+            return None;
+        }
+
+        let id = node_id(node.opaque());
+        let role = role_for_element(node, &element);
+        let mut accessibility_node = Node::new(role);
+
+        // Text controls render their own contents, which are exposed through their value.
+        let is_text_control = matches!(
+            node.type_id(),
+            LayoutNodeType::Element(
+                LayoutElementType::HTMLInputElement | LayoutElementType::HTMLTextAreaElement
+            )
+        );
+        if !is_text_control {
+            accessibility_node.set_children(self.build_children(node));
+        } else if !matches!(role, Role::Button | Role::CheckBox | Role::RadioButton) {
+            accessibility_node.set_value(&*threadsafe_node.node_text_content());
+            if let Some(placeholder) = element.get_attr(&ns!(), &local_name!("placeholder")) {
+                accessibility_node.set_placeholder(placeholder);
+            }
+        }
+
+        if let Some(label) = self.name_for_element(&element, role, &accessibility_node) {
+            accessibility_node.set_label(label);
+        }
+        if let Some(bounds) = self.bounds(node.opaque()) {
+            accessibility_node.set_bounds(bounds);
+        }
+        if let Some(level) = heading_level(&element) {
+            accessibility_node.set_level(level);
+        }
+        if role == Role::Link {
+            if let Some(href) = element.get_attr(&ns!(), &local_name!("href")) {
+                accessibility_node.set_url(href);
+            }
+        }
+
+        let state = node.as_element().map(|element| element.state());
+        if let Some(state) = state {
+            if state.contains(ElementState::DISABLED) {
+                accessibility_node.set_disabled();
+            }
+            if matches!(role, Role::CheckBox | Role::RadioButton) {
+                accessibility_node.set_toggled(if state.contains(ElementState::CHECKED) {
+                    Toggled::True
+                } else if state.contains(ElementState::INDETERMINATE) {
+                    Toggled::Mixed
+                } else {
+                    Toggled::False
+                });
+            }
+            if state.contains(ElementState::FOCUS) {
+                self.focus = Some(id);
+            }
+        }
+        if element.get_attr(&ns!(), &local_name!("required")).is_some() {
+            accessibility_node.set_required();
+        }
+
+        self.nodes.insert(id, accessibility_node);
+        Some(id)
+    }
+
+    /// Compute the accessible name of an element, following a simplified version of
+    /// <https://w3c.github.io/accname/#computation-steps>.
+    fn name_for_element<'dom>(
+        &self,
+        element: &impl ThreadSafeLayoutElement<'dom>,
+        role: Role,
+        node: &Node,
+    ) -> Option<String> {
+        if let Some(label) = element.get_attr(&ns!(), &local_name!("aria-label")) {
+            if !label.trim().is_empty() {
+                return Some(label.trim().to_owned());
+            }
+        }
+        if role == Role::Image {
+            if let Some(alt) = element.get_attr(&ns!(), &local_name!("alt")) {
+                return Some(alt.trim().to_owned());
+            }
+        }
+        if role == Role::Button && *element.get_local_name() == local_name!("input") {
+            if let Some(value) = element.get_attr(&ns!(), &local_name!("value")) {
+                return Some(value.to_owned());
+            }
+        }
+
+        // <https://w3c.github.io/aria/#namefromcontent>
+        let name_from_content = matches!(
+            role,
+            Role::Button |
+                Role::Link |
+                Role::Heading |
+                Role::Cell |
+                Role::ColumnHeader |
+                Role::RowHeader |
+                Role::CheckBox |
+                Role::RadioButton |
+                Role::MenuItem |
+                Role::Tab |
+                Role::ListBoxOption |
+                Role::Label
+        );
+        if name_from_content {
+            let mut text = String::new();
+            self.collect_text(node, &mut text);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                return Some(text);
+            }
+        }
+
+        element
+            .get_attr(&ns!(), &local_name!("title"))
+            .map(|title| title.trim().to_owned())
+            .filter(|title| !title.is_empty())
+    }
+
+    fn collect_text(&self, node: &Node, text: &mut String) {
+        for child in node.children() {
+            let Some(child) = self.nodes.get(child) else {
+                continue;
+            };
+            if child.role() == Role::Label && child.children().is_empty() {
+                if let Some(value) = child.value() {
+                    text.push(' ');
+                    text.push_str(value);
+                }
+            } else if let Some(label) = child.label() {
+                text.push(' ');
+                text.push_str(label);
+            } else {
+                self.collect_text(child, text);
+            }
+        }
+    }
+}
+
+/// The level of a heading element, from its `aria-level` attribute or its local name.
+fn heading_level<'dom>(element: &impl ThreadSafeLayoutElement<'dom>) -> Option<usize> {
+    if let Some(level) = element
+        .get_attr(&ns!(), &local_name!("aria-level"))
+        .and_then(|level| level.trim().parse().ok())
+    {
+        return Some(level);
+    }
+    if !element.has_namespace(&ns!(html)) {
+        return None;
+    }
+    Some(match *element.get_local_name() {
+        local_name!("h1") => 1,
+        local_name!("h2") => 2,
+        local_name!("h3") => 3,
+        local_name!("h4") => 4,
+        local_name!("h5") => 5,
+        local_name!("h6") => 6,
+        _ => return None,
+    })
+}
+
+/// The role of an element, from its `role` attribute or its implicit ARIA semantics.
+///
+/// <https://w3c.github.io/html-aam/#html-element-role-mappings>
+fn role_for_element<'dom>(
+    node: impl NodeExt<'dom>,
+    element: &impl ThreadSafeLayoutElement<'dom>,
+) -> Role {
+    if let Some(role) = element
+        .get_attr(&ns!(), &local_name!("role"))
+        .and_then(|roles| roles.split_ascii_whitespace().find_map(role_for_aria_role))
+    {
+        return role;
+    }
+
+    if let LayoutNodeType::Element(LayoutElementType::SVGSVGElement) = node.type_id() {
+        return Role::SvgRoot;
+    }
+    if !element.has_namespace(&ns!(html)) {
+        return Role::GenericContainer;
+    }
+
+    match *element.get_local_name() {
+        local_name!("a") | local_name!("area")
+            if element.get_attr(&ns!(), &local_name!("href")).is_some() =>
+        {
+            Role::Link
+        },
+        local_name!("article") => Role::Article,
+        local_name!("aside") => Role::Complementary,
+        local_name!("blockquote") => Role::Blockquote,
+        local_name!("body") => Role::GenericContainer,
+        local_name!("button") => Role::Button,
+        local_name!("caption") => Role::Caption,
+        local_name!("code") => Role::Code,
+        local_name!("dd") => Role::DescriptionListDetail,
+        local_name!("details") => Role::Details,
+        local_name!("dialog") => Role::Dialog,
+        local_name!("dl") => Role::DescriptionList,
+        local_name!("dt") => Role::DescriptionListTerm,
+        local_name!("em") => Role::Emphasis,
+        local_name!("figcaption") => Role::FigureCaption,
+        local_name!("figure") => Role::Figure,
+        local_name!("footer") => Role::ContentInfo,
+        local_name!("form") => Role::Form,
+        local_name!("h1") |
+        local_name!("h2") |
+        local_name!("h3") |
+        local_name!("h4") |
+        local_name!("h5") |
+        local_name!("h6") => Role::Heading,
+        local_name!("header") => Role::Banner,
+        local_name!("hr") => Role::Splitter,
+        local_name!("iframe") => Role::Iframe,
+        local_name!("img") => Role::Image,
+        local_name!("input") => {
+            match element
+                .get_attr(&ns!(), &local_name!("type"))
+                .map(|input_type| input_type.to_ascii_lowercase())
+                .as_deref()
+            {
+                Some("button" | "submit" | "reset" | "image") => Role::Button,
+                Some("checkbox") => Role::CheckBox,
+                Some("radio") => Role::RadioButton,
+                Some("range") => Role::Slider,
+                Some("number") => Role::SpinButton,
+                Some("search") => Role::SearchInput,
+                Some("email") => Role::EmailInput,
+                Some("tel") => Role::PhoneNumberInput,
+                Some("url") => Role::UrlInput,
+                Some("password") => Role::PasswordInput,
+                Some("color") => Role::ColorWell,
+                Some("date") => Role::DateInput,
+                Some("datetime-local") => Role::DateTimeInput,
+                Some("month") => Role::MonthInput,
+                Some("time") => Role::TimeInput,
+                Some("week") => Role::WeekInput,
+                Some("hidden") => Role::GenericContainer,
+                _ => Role::TextInput,
+            }
+        },
+        local_name!("label") => Role::Label,
+        local_name!("legend") => Role::Legend,
+        local_name!("li") => Role::ListItem,
+        local_name!("main") => Role::Main,
+        local_name!("mark") => Role::Mark,
+        local_name!("meter") => Role::Meter,
+        local_name!("nav") => Role::Navigation,
+        local_name!("ol") | local_name!("ul") | local_name!("menu") => Role::List,
+        local_name!("option") => Role::ListBoxOption,
+        local_name!("p") => Role::Paragraph,
+        local_name!("pre") => Role::Pre,
+        local_name!("progress") => Role::ProgressIndicator,
+        local_name!("section") => Role::Section,
+        local_name!("select") => Role::ComboBox,
+        local_name!("strong") => Role::Strong,
+        local_name!("table") => Role::Table,
+        local_name!("tbody") | local_name!("thead") | local_name!("tfoot") => Role::RowGroup,
+        local_name!("td") => Role::Cell,
+        local_name!("textarea") => Role::MultilineTextInput,
+        local_name!("th") => Role::ColumnHeader,
+        local_name!("time") => Role::Time,
+        local_name!("tr") => Role::Row,
+        local_name!("video") => Role::Video,
+        local_name!("audio") => Role::Audio,
+        local_name!("canvas") => Role::Canvas,
+        _ => Role::GenericContainer,
+    }
+}
+
+/// <https://w3c.github.io/aria/#role_definitions>
+fn role_for_aria_role(role: &str) -> Option<Role> {
+    Some(match &*role.to_ascii_lowercase() {
+        "alert" => Role::Alert,
+        "alertdialog" => Role::AlertDialog,
+        "application" => Role::Application,
+        "article" => Role::Article,
+        "banner" => Role::Banner,
+        "button" => Role::Button,
+        "cell" => Role::Cell,
+        "checkbox" => Role::CheckBox,
+        "columnheader" => Role::ColumnHeader,
+        "combobox" => Role::ComboBox,
+        "complementary" => Role::Complementary,
+        "contentinfo" => Role::ContentInfo,
+        "dialog" => Role::Dialog,
+        "document" => Role::Document,
+        "feed" => Role::Feed,
+        "figure" => Role::Figure,
+        "form" => Role::Form,
+        "generic" | "none" | "presentation" => Role::GenericContainer,
+        "grid" => Role::Grid,
+        "group" => Role::Group,
+        "heading" => Role::Heading,
+        "img" | "image" => Role::Image,
+        "link" => Role::Link,
+        "list" => Role::List,
+        "listbox" => Role::ListBox,
+        "listitem" => Role::ListItem,
+        "log" => Role::Log,
+        "main" => Role::Main,
+        "marquee" => Role::Marquee,
+        "math" => Role::Math,
+        "menu" => Role::Menu,
+        "menubar" => Role::MenuBar,
+        "menuitem" => Role::MenuItem,
+        "menuitemcheckbox" => Role::MenuItemCheckBox,
+        "menuitemradio" => Role::MenuItemRadio,
+        "meter" => Role::Meter,
+        "navigation" => Role::Navigation,
+        "note" => Role::Note,
+        "option" => Role::ListBoxOption,
+        "paragraph" => Role::Paragraph,
+        "progressbar" => Role::ProgressIndicator,
+        "radio" => Role::RadioButton,
+        "radiogroup" => Role::RadioGroup,
+        "region" => Role::Region,
+        "row" => Role::Row,
+        "rowgroup" => Role::RowGroup,
+        "rowheader" => Role::RowHeader,
+        "scrollbar" => Role::ScrollBar,
+        "search" => Role::Search,
+        "searchbox" => Role::SearchInput,
+        "separator" => Role::Splitter,
+        "slider" => Role::Slider,
+        "spinbutton" => Role::SpinButton,
+        "status" => Role::Status,
+        "switch" => Role::Switch,
+        "tab" => Role::Tab,
+        "table" => Role::Table,
+        "tablist" => Role::TabList,
+        "tabpanel" => Role::TabPanel,
+        "textbox" => Role::TextInput,
+        "timer" => Role::Timer,
+        "toolbar" => Role::Toolbar,
+        "tooltip" => Role::Tooltip,
+        "tree" => Role::Tree,
+        "treegrid" => Role::TreeGrid,
+        "treeitem" => Role::TreeItem,
+        _ => return None,
+    })
+}
@@ -0,0 +1,258 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Caret browsing, where the user moves a caret through the rendered text of a document
+//! with the keyboard and selects text by extending the selection from the caret.
+//!
+//! Script keeps the caret and the selection as positions in the text of text nodes. These
+//! are mapped onto the text content of inline formatting contexts by the [`TextSource`]s of
+//! text fragments, so that layout can paint them and find the position of the caret on the
+//! line above or below it. The same mapping finds the position in the text that is closest
+//! to a point, for `document.caretPositionFromPoint()`.
+
+use std::iter::once;
+use std::ops::Range;
+use std::sync::Arc;
+
+use app_units::{Au, MAX_AU};
+use euclid::default::Rect;
+use script_layout_interface::{CaretBrowsingSelection, CaretLineDirection};
+use style::Zero;
+use style::dom::OpaqueNode;
+
+use crate::FragmentTree;
+use crate::cell::ArcRefCell;
+use crate::fragment_tree::{Fragment, TextFragment};
+use crate::geom::{PhysicalPoint, PhysicalRect};
+
+/// The text of a text node, as added to the text content of an inline formatting context.
+#[derive(Debug)]
+pub(crate) struct TextSource {
+    /// The text node.
+    pub node: OpaqueNode,
+    /// For each character of the text added to the text content, and for its end, the byte
+    /// offset in the text content and the byte offset in the text of the node.
+    offsets: Vec<(usize, usize)>,
+}
+
+impl TextSource {
+    /// Map `text`, the text of `node`, onto `processed_text`, which is what white space
+    /// processing and `text-transform` made of it, added to the text content of an inline
+    /// formatting context at `offset`.
+    ///
+    /// Collapsed white space maps onto the first white space character that it replaces. Text
+    /// transforms that change the number of characters make the mapping approximate.
+    pub(crate) fn new(node: OpaqueNode, text: &str, processed_text: &str, offset: usize) -> Self {
+        let mut text_characters = text.char_indices().peekable();
+        let mut offsets = Vec::with_capacity(processed_text.len() + 1);
+        for (index, character) in processed_text.char_indices() {
+            if !character.is_whitespace() {
+                // Skip the white space of the text that was collapsed away.
+                while text_characters
+                    .next_if(|(_, text_character)| text_character.is_whitespace())
+                    .is_some()
+                {}
+            }
+            let text_offset = text_characters
+                .next()
+                .map_or(text.len(), |(text_offset, _)| text_offset);
+            offsets.push((offset + index, text_offset));
+        }
+        offsets.push((offset + processed_text.len(), text.len()));
+        Self { node, offsets }
+    }
+
+    /// The range of bytes of the text content of the inline formatting context that this
+    /// text was added as.
+    pub(crate) fn text_content_range(&self) -> Range<usize> {
+        self.offsets[0].0..self.offsets[self.offsets.len() - 1].0
+    }
+
+    /// Map a byte offset in the text of the node to the text content of the inline
+    /// formatting context.
+    pub(crate) fn to_text_content_offset(&self, node_offset: usize) -> usize {
+        let index = self
+            .offsets
+            .partition_point(|(_, text_offset)| *text_offset < node_offset);
+        self.offsets[index.min(self.offsets.len() - 1)].0
+    }
+
+    /// Map a byte offset in the text content of the inline formatting context to the text
+    /// of the node.
+    pub(crate) fn to_node_offset(&self, text_content_offset: usize) -> usize {
+        let index = self
+            .offsets
+            .partition_point(|(offset, _)| *offset < text_content_offset);
+        self.offsets[index.min(self.offsets.len() - 1)].1
+    }
+}
+
+/// The parts of `fragment`, positioned at `rect`, that display the selected text of
+/// `selection`.
+pub(crate) fn selection_rects_for_fragment(
+    selection: &CaretBrowsingSelection,
+    fragment: &TextFragment,
+    rect: &PhysicalRect<Au>,
+) -> Vec<PhysicalRect<Au>> {
+    fragment
+        .text_sources
+        .iter()
+        .filter_map(|source| {
+            let selected = selection.selected_text.get(&source.node)?;
+            let range = source.to_text_content_offset(selected.start)..
+                source.to_text_content_offset(selected.end);
+            fragment.rect_for_text_range(rect, &range)
+        })
+        .collect()
+}
+
+/// The area of the caret of `selection`, if it is displayed in `fragment`, positioned at
+/// `rect`.
+pub(crate) fn caret_rect_for_fragment(
+    selection: &CaretBrowsingSelection,
+    fragment: &TextFragment,
+    rect: &PhysicalRect<Au>,
+) -> Option<PhysicalRect<Au>> {
+    let (node, offset) = selection.caret?;
+    caret_rect_in_fragment(fragment, rect, node, offset)
+}
+
+fn caret_rect_in_fragment(
+    fragment: &TextFragment,
+    rect: &PhysicalRect<Au>,
+    node: OpaqueNode,
+    offset: usize,
+) -> Option<PhysicalRect<Au>> {
+    let source = fragment
+        .text_sources
+        .iter()
+        .find(|source| source.node == node)?;
+    fragment.caret_rect_for_text_offset(rect, source.to_text_content_offset(offset))
+}
+
+/// The text fragments of `fragment_tree` that display the text of text nodes, along with
+/// their position in the tree.
+fn text_fragments(
+    fragment_tree: &FragmentTree,
+) -> Vec<(ArcRefCell<TextFragment>, PhysicalRect<Au>)> {
+    let mut text_fragments = Vec::new();
+    fragment_tree.find(|fragment, _, containing_block| {
+        if let Fragment::Text(text_fragment) = fragment {
+            if !text_fragment.borrow().text_sources.is_empty() {
+                let rect = text_fragment
+                    .borrow()
+                    .rect
+                    .translate(containing_block.origin.to_vector());
+                text_fragments.push((text_fragment.clone(), rect));
+            }
+        }
+        None::<()>
+    });
+    text_fragments
+}
+
+pub fn process_caret_rect_request(
+    fragment_tree: Option<Arc<FragmentTree>>,
+    node: OpaqueNode,
+    offset: usize,
+) -> Option<Rect<Au>> {
+    text_fragments(&fragment_tree?)
+        .iter()
+        .find_map(|(fragment, rect)| caret_rect_in_fragment(&fragment.borrow(), rect, node, offset))
+        .map(|rect| rect.to_untyped())
+}
+
+pub fn process_caret_in_adjacent_line_request(
+    fragment_tree: Option<Arc<FragmentTree>>,
+    node: OpaqueNode,
+    offset: usize,
+    direction: CaretLineDirection,
+) -> Option<(OpaqueNode, usize)> {
+    let text_fragments = text_fragments(&fragment_tree?);
+    let caret = text_fragments.iter().find_map(|(fragment, rect)| {
+        caret_rect_in_fragment(&fragment.borrow(), rect, node, offset)
+    })?;
+
+    // The adjacent line is the one of the fragment above or below the caret that is closest
+    // to it vertically.
+    let vertical_center = |rect: &PhysicalRect<Au>| rect.min_y() + rect.size.height / 2;
+    let candidates: Vec<_> = text_fragments
+        .iter()
+        .filter(|(_, rect)| match direction {
+            CaretLineDirection::Up => vertical_center(rect) < caret.min_y(),
+            CaretLineDirection::Down => vertical_center(rect) > caret.max_y(),
+        })
+        .collect();
+    let line = candidates
+        .iter()
+        .map(|(_, rect)| *rect)
+        .min_by_key(|rect| (vertical_center(rect) - vertical_center(&caret)).abs())?;
+
+    // On that line, the caret moves to the position that is closest to it horizontally.
+    let (fragment, rect) = candidates
+        .into_iter()
+        .filter(|(_, rect)| rect.min_y() < line.max_y() && line.min_y() < rect.max_y())
+        .min_by_key(|(_, rect)| distance_to_range(caret.origin.x, rect.min_x(), rect.max_x()))?;
+    node_position_closest_to(&fragment.borrow(), rect, caret.origin.x)
+}
+
+pub fn process_caret_position_from_point_request(
+    fragment_tree: Option<Arc<FragmentTree>>,
+    point: PhysicalPoint<Au>,
+) -> Option<(OpaqueNode, usize)> {
+    let text_fragments = text_fragments(&fragment_tree?);
+
+    // The position is on the line of the text fragment that is closest to the point
+    // vertically, in the fragment of that line that is closest to it horizontally.
+    let (_, line) = text_fragments
+        .iter()
+        .min_by_key(|(_, rect)| distance_to_range(point.y, rect.min_y(), rect.max_y()))?;
+    let (fragment, rect) = text_fragments
+        .iter()
+        .filter(|(_, rect)| rect.min_y() < line.max_y() && line.min_y() < rect.max_y())
+        .min_by_key(|(_, rect)| distance_to_range(point.x, rect.min_x(), rect.max_x()))?;
+    node_position_closest_to(&fragment.borrow(), rect, point.x)
+}
+
+/// The distance from `value` to the range from `min` to `max`, which is zero inside of it.
+fn distance_to_range(value: Au, min: Au, max: Au) -> Au {
+    if value < min {
+        min - value
+    } else if value > max {
+        value - max
+    } else {
+        Au::zero()
+    }
+}
+
+/// The position in the text of a text node of the position in `fragment`, positioned at
+/// `rect`, that is closest to `x` horizontally, as the text node and a byte offset in its text.
+fn node_position_closest_to(
+    fragment: &TextFragment,
+    rect: &PhysicalRect<Au>,
+    x: Au,
+) -> Option<(OpaqueNode, usize)> {
+    let text_content_offset = text_offset_closest_to(fragment, rect, x);
+    let source = fragment.text_sources.iter().find(|source| {
+        let range = source.text_content_range();
+        range.start <= text_content_offset && text_content_offset <= range.end
+    })?;
+    Some((source.node, source.to_node_offset(text_content_offset)))
+}
+
+/// The byte offset in the text content of the inline formatting context of the position in
+/// `fragment`, positioned at `rect`, that is closest to `x` horizontally.
+fn text_offset_closest_to(fragment: &TextFragment, rect: &PhysicalRect<Au>, x: Au) -> usize {
+    let text_range = fragment.text_range.clone();
+    fragment.formatting_context_text[text_range.clone()]
+        .char_indices()
+        .map(|(index, _)| text_range.start + index)
+        .chain(once(text_range.end))
+        .min_by_key(|offset| {
+            fragment
+                .caret_rect_for_text_offset(rect, *offset)
+                .map_or(MAX_AU, |caret| (caret.origin.x - x).abs())
+        })
+        .unwrap_or(text_range.start)
+}
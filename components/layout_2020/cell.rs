@@ -6,6 +6,7 @@ use std::fmt;
 use std::ops::Deref;
 
 use atomic_refcell::AtomicRefCell;
+use malloc_size_of::{MallocConditionalShallowSizeOf, MallocSizeOfOps};
 use servo_arc::Arc;
 
 pub(crate) struct ArcRefCell<T> {
@@ -18,6 +19,12 @@ impl<T> ArcRefCell<T> {
             value: Arc::new(AtomicRefCell::new(value)),
         }
     }
+
+    /// The size of the allocation holding the value, or zero if it was already measured
+    /// with `ops`.
+    pub(crate) fn conditional_shallow_size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.value.conditional_shallow_size_of(ops)
+    }
 }
 
 impl<T> Clone for ArcRefCell<T> {
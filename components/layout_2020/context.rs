@@ -17,6 +17,7 @@ use script_layout_interface::{IFrameSizes, ImageAnimationState, PendingImage, Pe
 use servo_url::{ImmutableOrigin, ServoUrl};
 use style::context::SharedStyleContext;
 use style::dom::OpaqueNode;
+use style::selector_parser::RestyleDamage;
 use style::values::computed::image::{Gradient, Image};
 
 use crate::display_list::WebRenderImageInfo;
@@ -41,6 +42,16 @@ pub struct LayoutContext<'a> {
     /// A collection of `<iframe>` sizes to send back to script.
     pub iframe_sizes: Mutex<IFrameSizes>,
 
+    /// The elements that were damaged by the style traversal, with their damage.
+    pub damaged_nodes: Mutex<Vec<(OpaqueNode, RestyleDamage)>>,
+
+    /// The factor by which the used size of every font, and of the line heights that depend
+    /// on it, is multiplied for text-only zoom.
+    pub text_zoom: f32,
+
+    /// Whether the document is painted with the colors of forced colors mode.
+    pub forced_colors: bool,
+
     pub webrender_image_cache:
         Arc<RwLock<FnvHashMap<(ServoUrl, UsePlaceholder), WebRenderImageInfo>>>,
 
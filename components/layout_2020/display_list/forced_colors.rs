@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Forced colors mode, in which pages are painted with a small palette of system colors
+//! instead of the colors of their style, so that they have the contrast that the user needs.
+//! Text, borders and outlines are painted in `CanvasText`, and backgrounds in `Canvas` with
+//! the alpha of their own color, while shadows and gradients are left out.
+//!
+//! Stylo does not support `forced-color-adjust` nor the `forced-colors` media feature in
+//! stylesheets, so no element can opt out and the palette is applied while painting.
+//! <https://drafts.csswg.org/css-color-adjust/#forced>
+
+use style::color::AbsoluteColor;
+
+/// The `Canvas` system color of forced colors mode, the background of pages and controls.
+pub(crate) const CANVAS: AbsoluteColor = AbsoluteColor::BLACK;
+
+/// The `CanvasText` system color of forced colors mode, the color of text, borders and
+/// outlines.
+pub(crate) const CANVAS_TEXT: AbsoluteColor = AbsoluteColor::WHITE;
+
+/// The color that a background of the given color is painted with, which keeps its alpha so
+/// that transparent backgrounds stay transparent.
+pub(crate) fn background_color(color: AbsoluteColor) -> AbsoluteColor {
+    CANVAS.with_alpha(color.alpha)
+}
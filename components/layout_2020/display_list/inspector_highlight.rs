@@ -0,0 +1,300 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The overlays that the devtools inspector draws over the nodes that it highlights, to show
+//! their box model or the layout of their flex or grid container. The geometry of the overlays
+//! is taken from the fragments of the nodes while the display list is built, and the overlays
+//! are painted once all of the content has been, in a stacking context of their own. They are
+//! not clipped by the ancestors of the nodes, but they move with them when they scroll.
+
+use app_units::Au;
+use devtools_traits::HighlighterKind;
+use euclid::{Point2D, SideOffsets2D};
+use script_layout_interface::InspectorHighlight;
+use style::values::specified::box_::DisplayInside;
+use webrender_api::units::{LayoutPoint, LayoutRect};
+use webrender_api::{self as wr, ClipChainId};
+
+use crate::display_list::DisplayList;
+use crate::display_list::conversions::ToWebRender;
+use crate::fragment_tree::{BoxFragment, Fragment, SpecificLayoutInfo};
+use crate::geom::{PhysicalPoint, PhysicalRect, PhysicalSize};
+
+/// The color of the content box in the box model overlay.
+const CONTENT_BOX_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.4,
+    g: 0.8,
+    b: 1.0,
+    a: 0.5,
+};
+
+/// The color of the padding in the box model overlay.
+const PADDING_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.42,
+    g: 0.35,
+    b: 0.8,
+    a: 0.5,
+};
+
+/// The color of the border in the box model overlay.
+const BORDER_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.27,
+    g: 0.27,
+    b: 0.27,
+    a: 0.5,
+};
+
+/// The color of the margin in the box model overlay.
+const MARGIN_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.93,
+    g: 1.0,
+    b: 0.39,
+    a: 0.5,
+};
+
+/// The color of the lines of the flex and grid overlays.
+const LAYOUT_LINE_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.58,
+    g: 0.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+/// The color of the gaps between the tracks in the grid overlay.
+const GRID_GAP_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.58,
+    g: 0.0,
+    b: 1.0,
+    a: 0.15,
+};
+
+/// What is painted by an overlay.
+enum OverlayShape {
+    /// A filled rectangle.
+    Rect(LayoutRect, wr::ColorF),
+    /// The outline of a rectangle, one pixel wide.
+    Outline(LayoutRect, wr::BorderStyle),
+    /// A line, one pixel wide, that spans the given rectangle.
+    Line(LayoutRect, wr::LineOrientation),
+}
+
+/// A shape, in the coordinates of the spatial node of the fragment that it is drawn over.
+struct Overlay {
+    spatial_id: wr::SpatialId,
+    shape: OverlayShape,
+}
+
+/// The nodes highlighted by the devtools inspector, and the overlays drawn over the fragments
+/// of these nodes that were found so far.
+pub(crate) struct InspectorOverlays<'a> {
+    highlights: &'a [InspectorHighlight],
+    overlays: Vec<Overlay>,
+}
+
+impl<'a> InspectorOverlays<'a> {
+    pub(crate) fn new(highlights: &'a [InspectorHighlight]) -> Self {
+        Self {
+            highlights,
+            overlays: Vec::new(),
+        }
+    }
+
+    /// Add the overlays of `fragment`, which is positioned in `containing_block` and painted
+    /// with `spatial_id`, if it is the fragment of a highlighted node.
+    pub(crate) fn add_box_fragment(
+        &mut self,
+        fragment: &BoxFragment,
+        containing_block: &PhysicalRect<Au>,
+        spatial_id: wr::SpatialId,
+    ) {
+        let Some(tag) = fragment.base.tag.filter(|tag| tag.pseudo.is_none()) else {
+            return;
+        };
+        let origin = containing_block.origin.to_vector();
+        for highlight in self.highlights {
+            if highlight.node != tag.node {
+                continue;
+            }
+            let mut push = |shape| self.overlays.push(Overlay { spatial_id, shape });
+            match highlight.kind {
+                HighlighterKind::BoxModel => {
+                    let content = fragment.content_rect.translate(origin).to_webrender();
+                    let padding = fragment.padding_rect().translate(origin).to_webrender();
+                    let border = fragment.border_rect().translate(origin).to_webrender();
+                    let margin = fragment.margin_rect().translate(origin).to_webrender();
+                    push(OverlayShape::Rect(content, CONTENT_BOX_COLOR));
+                    for (outer, inner, color) in [
+                        (padding, content, PADDING_COLOR),
+                        (border, padding, BORDER_COLOR),
+                        (margin, border, MARGIN_COLOR),
+                    ] {
+                        for side in ring(outer, inner) {
+                            push(OverlayShape::Rect(side, color));
+                        }
+                    }
+                },
+                HighlighterKind::Flexbox => {
+                    if fragment.style.get_box().display.inside() != DisplayInside::Flex {
+                        continue;
+                    }
+                    let border = fragment.border_rect().translate(origin).to_webrender();
+                    push(OverlayShape::Outline(border, wr::BorderStyle::Dashed));
+
+                    // The items are positioned relative to the content box of the container.
+                    let content_origin = fragment.content_rect.translate(origin).origin;
+                    for child in &fragment.children {
+                        let Fragment::Box(item) = child else {
+                            continue;
+                        };
+                        let item = item
+                            .borrow()
+                            .border_rect()
+                            .translate(content_origin.to_vector())
+                            .to_webrender();
+                        push(OverlayShape::Outline(item, wr::BorderStyle::Solid));
+                    }
+                },
+                HighlighterKind::Grid => {
+                    let Some(SpecificLayoutInfo::Grid(grid_info)) = &fragment.specific_layout_info
+                    else {
+                        continue;
+                    };
+                    let content = fragment.content_rect.translate(origin);
+                    let border = fragment.border_rect().translate(origin).to_webrender();
+                    push(OverlayShape::Outline(border, wr::BorderStyle::Dashed));
+
+                    let columns: Vec<_> = grid_info.columns.track_ranges().collect();
+                    let rows: Vec<_> = grid_info.rows.track_ranges().collect();
+                    let column_rect = |start: Au, end: Au| {
+                        PhysicalRect::new(
+                            PhysicalPoint::new(content.origin.x + start, content.origin.y),
+                            PhysicalSize::new(end - start, content.size.height),
+                        )
+                        .to_webrender()
+                    };
+                    let row_rect = |start: Au, end: Au| {
+                        PhysicalRect::new(
+                            PhysicalPoint::new(content.origin.x, content.origin.y + start),
+                            PhysicalSize::new(content.size.width, end - start),
+                        )
+                        .to_webrender()
+                    };
+
+                    for gap in columns.windows(2) {
+                        push(OverlayShape::Rect(
+                            column_rect(gap[0].1, gap[1].0),
+                            GRID_GAP_COLOR,
+                        ));
+                    }
+                    for gap in rows.windows(2) {
+                        push(OverlayShape::Rect(
+                            row_rect(gap[0].1, gap[1].0),
+                            GRID_GAP_COLOR,
+                        ));
+                    }
+                    for (start, end) in columns {
+                        for position in [start, end] {
+                            push(OverlayShape::Line(
+                                column_rect(position, position),
+                                wr::LineOrientation::Vertical,
+                            ));
+                        }
+                    }
+                    for (start, end) in rows {
+                        for position in [start, end] {
+                            push(OverlayShape::Line(
+                                row_rect(position, position),
+                                wr::LineOrientation::Horizontal,
+                            ));
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Paint the overlays that were found above everything else in `display_list`.
+    pub(crate) fn paint(&mut self, display_list: &mut DisplayList) {
+        if self.overlays.is_empty() {
+            return;
+        }
+
+        let root_spatial_id = display_list
+            .compositor_info
+            .root_reference_frame_id
+            .spatial_id;
+        display_list.wr.push_simple_stacking_context(
+            LayoutPoint::zero(),
+            root_spatial_id,
+            wr::PrimitiveFlags::empty(),
+        );
+        for overlay in self.overlays.drain(..) {
+            let common = |clip_rect: LayoutRect| wr::CommonItemProperties {
+                clip_rect,
+                spatial_id: overlay.spatial_id,
+                clip_chain_id: ClipChainId::INVALID,
+                flags: wr::PrimitiveFlags::empty(),
+            };
+            match overlay.shape {
+                OverlayShape::Rect(rect, color) => {
+                    if !rect.is_empty() {
+                        display_list.wr.push_rect(&common(rect), rect, color);
+                    }
+                },
+                OverlayShape::Outline(rect, style) => {
+                    let side = wr::BorderSide {
+                        color: LAYOUT_LINE_COLOR,
+                        style,
+                    };
+                    let details = wr::BorderDetails::Normal(wr::NormalBorder {
+                        top: side,
+                        right: side,
+                        bottom: side,
+                        left: side,
+                        radius: wr::BorderRadius::zero(),
+                        do_aa: true,
+                    });
+                    display_list.wr.push_border(
+                        &common(rect),
+                        rect,
+                        SideOffsets2D::new_all_same(1.0),
+                        details,
+                    );
+                },
+                OverlayShape::Line(mut rect, orientation) => {
+                    match orientation {
+                        wr::LineOrientation::Vertical => rect.max.x = rect.min.x + 1.0,
+                        wr::LineOrientation::Horizontal => rect.max.y = rect.min.y + 1.0,
+                    }
+                    display_list.wr.push_line(
+                        &common(rect),
+                        &rect,
+                        1.0,
+                        orientation,
+                        &LAYOUT_LINE_COLOR,
+                        wr::LineStyle::Dashed,
+                    );
+                },
+            }
+        }
+        display_list.wr.pop_stacking_context();
+    }
+}
+
+/// The four rectangles that are inside of `outer` but outside of `inner`, or empty rectangles
+/// where `inner` is not inside of `outer`.
+fn ring(outer: LayoutRect, inner: LayoutRect) -> [LayoutRect; 4] {
+    [
+        LayoutRect::new(outer.min, Point2D::new(outer.max.x, inner.min.y)),
+        LayoutRect::new(Point2D::new(outer.min.x, inner.max.y), outer.max),
+        LayoutRect::new(
+            Point2D::new(outer.min.x, inner.min.y),
+            Point2D::new(inner.min.x, inner.max.y),
+        ),
+        LayoutRect::new(
+            Point2D::new(inner.max.x, inner.min.y),
+            Point2D::new(outer.max.x, inner.max.y),
+        ),
+    ]
+}
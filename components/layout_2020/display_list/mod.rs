@@ -2,7 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::cell::{OnceCell, RefCell};
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use app_units::Au;
@@ -11,11 +12,15 @@ use base::id::ScrollTreeNodeId;
 use embedder_traits::Cursor;
 use euclid::{Point2D, SideOffsets2D, Size2D, UnknownUnit};
 use fonts::GlyphStore;
+use fxhash::FxHashMap;
 use gradient::WebRenderGradient;
+use script_layout_interface::{CaretBrowsingSelection, InspectorHighlight, TopLayerElement};
+use servo_config::pref;
 use servo_geometry::MaxRect;
 use style::Zero;
 use style::color::{AbsoluteColor, ColorSpace};
 use style::computed_values::border_image_outset::T as BorderImageOutset;
+use style::computed_values::pointer_events::T as PointerEvents;
 use style::computed_values::text_decoration_style::T as ComputedTextDecorationStyle;
 use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
@@ -34,12 +39,16 @@ use webrender_api::{
     self as wr, BorderDetails, BoxShadowClipMode, ClipChainId, CommonItemProperties,
     ImageRendering, NinePatchBorder, NinePatchBorderSource, units,
 };
-use webrender_traits::display_list::{AxesScrollSensitivity, CompositorDisplayListInfo};
+use webrender_traits::display_list::{
+    AxesScrollSensitivity, CompositorDisplayListInfo, HitTestPlane,
+};
 use wr::units::LayoutVector2D;
 
+use crate::caret_browsing::{caret_rect_for_fragment, selection_rects_for_fragment};
 use crate::context::{LayoutContext, ResolvedImage};
 use crate::display_list::conversions::ToWebRender;
-use crate::display_list::stacking_context::StackingContextSection;
+use crate::display_list::stacking_context::{StackingContextSection, TopLayerStackingContext};
+use crate::find_in_page::FindInPageHighlights;
 use crate::fragment_tree::{
     BackgroundMode, BoxFragment, Fragment, FragmentFlags, FragmentTree, SpecificLayoutInfo, Tag,
     TextFragment,
@@ -53,11 +62,21 @@ use crate::style_ext::{BorderStyleColor, ComputedValuesExt};
 mod background;
 mod clip_path;
 mod conversions;
+mod forced_colors;
 mod gradient;
+mod inspector_highlight;
+mod retained_items;
+mod scrollbar;
 mod stacking_context;
+mod theme;
 
 use background::BackgroundPainter;
+use inspector_highlight::InspectorOverlays;
+pub use retained_items::{DisplayListInvalidation, RetainedDisplayItems};
+use scrollbar::Scrollbars;
+pub(crate) use scrollbar::scrollbar_thickness;
 pub use stacking_context::*;
+pub(crate) use theme::{Widget, WidgetKind};
 
 #[derive(Clone, Copy)]
 pub struct WebRenderImageInfo {
@@ -66,6 +85,46 @@ pub struct WebRenderImageInfo {
     pub key: Option<wr::ImageKey>,
 }
 
+/// The color of the highlights of find-in-page matches.
+const FIND_IN_PAGE_MATCH_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The color of the highlight of the active find-in-page match.
+const ACTIVE_FIND_IN_PAGE_MATCH_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 0.6,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The color of the highlights of the text selected in caret browsing mode.
+const CARET_BROWSING_SELECTION_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.2,
+    g: 0.5,
+    b: 1.0,
+    a: 0.4,
+};
+
+/// The color of the highlight of the text selected in a focused text control.
+const TEXT_CONTROL_SELECTION_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.2,
+    g: 0.5,
+    b: 1.0,
+    a: 0.4,
+};
+
+/// The color of the wavy lines that mark misspelled words.
+const SPELLING_ERROR_COLOR: wr::ColorF = wr::ColorF {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
 // webrender's `ItemTag` is private.
 type ItemTag = (u64, u16);
 type HitInfo = Option<ItemTag>;
@@ -88,6 +147,28 @@ pub struct DisplayList {
     /// list. This is merely to ensure that the currently-unused SpatialTreeItemKey
     /// produced for every SpatialTree node is unique.
     pub spatial_tree_count: u64,
+
+    /// A count of the number of clip chains defined in the WebRender display list, which
+    /// is used to find out whether the display items of a stacking context define any.
+    pub clip_chain_count: u64,
+
+    /// The planes of the reference frames that are part of 3D rendering contexts, by the
+    /// index of their [ScrollTreeNodeId].
+    three_d_planes: FxHashMap<usize, ThreeDPlane>,
+
+    /// A count of the number of 3D rendering contexts in the display list, which is used
+    /// to give each of them a unique id.
+    three_d_rendering_context_count: u32,
+
+    /// The scroll containers whose scrollbars are painted over the content.
+    scrollbars: Scrollbars,
+
+    /// The elements in the top layer of the document, from the bottom to the top.
+    top_layer: Vec<TopLayerElement>,
+
+    /// The stacking contexts of the elements in the top layer, which are collected while
+    /// building the stacking context tree and then given to the root stacking context.
+    top_layer_stacking_contexts: Vec<TopLayerStackingContext>,
 }
 
 impl DisplayList {
@@ -101,6 +182,13 @@ impl DisplayList {
         viewport_scroll_sensitivity: AxesScrollSensitivity,
         first_reflow: bool,
     ) -> Self {
+        let color_space = if pref!(gfx_display_p3_enabled) {
+            ColorSpace::DisplayP3
+        } else {
+            ColorSpace::Srgb
+        };
+        OUTPUT_COLOR_SPACE.with(|output_color_space| output_color_space.set(color_space));
+
         Self {
             wr: wr::DisplayListBuilder::new(pipeline_id),
             compositor_info: CompositorDisplayListInfo::new(
@@ -112,6 +200,12 @@ impl DisplayList {
                 first_reflow,
             ),
             spatial_tree_count: 0,
+            clip_chain_count: 0,
+            three_d_planes: FxHashMap::default(),
+            three_d_rendering_context_count: 0,
+            scrollbars: Scrollbars::default(),
+            top_layer: Vec::new(),
+            top_layer_stacking_contexts: Vec::new(),
         }
     }
 
@@ -128,6 +222,7 @@ impl DisplayList {
             ClipChainId::INVALID => None,
             parent => Some(parent),
         };
+        self.clip_chain_count += 1;
         self.wr.define_clip_chain(parent, clips)
     }
 }
@@ -160,18 +255,51 @@ pub(crate) struct DisplayListBuilder<'a> {
 
     /// The [DisplayList] used to collect display list items and metadata.
     pub display_list: &'a mut DisplayList,
+
+    /// The matches of the current find-in-page search, which are highlighted.
+    find_in_page: Option<&'a FindInPageHighlights>,
+
+    /// The caret and selected text to paint in caret browsing mode.
+    caret_browsing_selection: Option<&'a CaretBrowsingSelection>,
+
+    /// The overlays of the nodes highlighted by the devtools inspector, which are painted
+    /// once the rest of the display list is built.
+    inspector_overlays: InspectorOverlays<'a>,
+
+    /// The display items that WebRender retained from the previous display list.
+    retained_items: &'a mut RetainedDisplayItems,
+
+    /// Whether display items are being built into an item group that WebRender retains.
+    in_item_group: bool,
 }
 
 impl DisplayList {
-    /// Build the display list, returning true if it was contentful.
+    /// Build the display list, returning true if it was contentful. Scrollbars are painted
+    /// at the given scroll offsets.
+    #[allow(clippy::too_many_arguments)]
     pub fn build(
         &mut self,
         context: &LayoutContext,
         fragment_tree: &FragmentTree,
         root_stacking_context: &StackingContext,
+        find_in_page: Option<&FindInPageHighlights>,
+        caret_browsing_selection: Option<&CaretBrowsingSelection>,
+        inspector_highlights: &[InspectorHighlight],
+        retained_items: &mut RetainedDisplayItems,
+        scroll_offsets: &HashMap<wr::ExternalScrollId, LayoutVector2D>,
     ) {
         #[cfg(feature = "tracing")]
         let _span = tracing::trace_span!("display_list::build", servo_profiling = true).entered();
+
+        // Find-in-page highlights and the caret are painted into the items of stacking
+        // contexts, but are not part of the fragment tree, so those items cannot be reused.
+        // Neither can they when the fragments of highlighted nodes need to be found.
+        retained_items.begin(
+            self,
+            find_in_page.is_none() &&
+                caret_browsing_selection.is_none() &&
+                inspector_highlights.is_empty(),
+        );
         let mut builder = DisplayListBuilder {
             current_scroll_node_id: self.compositor_info.root_reference_frame_id,
             current_reference_frame_scroll_node_id: self.compositor_info.root_reference_frame_id,
@@ -179,8 +307,16 @@ impl DisplayList {
             element_for_canvas_background: fragment_tree.canvas_background.from_element,
             context,
             display_list: self,
+            find_in_page,
+            caret_browsing_selection,
+            inspector_overlays: InspectorOverlays::new(inspector_highlights),
+            retained_items: &mut *retained_items,
+            in_item_group: false,
         };
         fragment_tree.build_display_list(&mut builder, root_stacking_context);
+        builder.inspector_overlays.paint(builder.display_list);
+        std::mem::take(&mut self.scrollbars).paint(self, context, scroll_offsets);
+        retained_items.end(self);
     }
 }
 
@@ -214,18 +350,26 @@ impl DisplayListBuilder<'_> {
         style: &ComputedValues,
         tag: Option<Tag>,
         auto_cursor: Cursor,
+        rect: &LayoutRect,
     ) -> HitInfo {
-        use style::computed_values::pointer_events::T as PointerEvents;
-
         let inherited_ui = style.get_inherited_ui();
         if inherited_ui.pointer_events == PointerEvents::None {
             return None;
         }
 
+        let plane = self
+            .display_list
+            .three_d_plane(&self.current_scroll_node_id)
+            .map(|plane| HitTestPlane {
+                context: plane.context,
+                transform: plane.transform,
+                origin: rect.min,
+            });
         let hit_test_index = self.display_list.compositor_info.add_hit_test_info(
             tag?.node.0 as u64,
             Some(cursor(inherited_ui.cursor.keyword, auto_cursor)),
             self.current_scroll_node_id,
+            plane,
         );
         Some((
             hit_test_index as u64,
@@ -317,6 +461,16 @@ impl Fragment {
                         builder.mark_is_contentful();
                         let rect = iframe.rect.translate(containing_block.origin.to_vector());
 
+                        if iframe.is_portal ||
+                            iframe.style.get_inherited_ui().pointer_events == PointerEvents::None
+                        {
+                            builder
+                                .display_list
+                                .compositor_info
+                                .iframes_without_pointer_events
+                                .push(iframe.pipeline_id.into());
+                        }
+
                         let common = builder.common_properties(rect.to_webrender(), &iframe.style);
                         builder.wr().push_iframe(
                             rect.to_webrender(),
@@ -354,7 +508,8 @@ impl Fragment {
         rect: PhysicalRect<Au>,
         cursor: Cursor,
     ) {
-        let hit_info = builder.hit_info(style, tag, cursor);
+        let rect = rect.to_webrender();
+        let hit_info = builder.hit_info(style, tag, cursor, &rect);
         let hit_info = match hit_info {
             Some(hit_info) => hit_info,
             None => return,
@@ -363,7 +518,7 @@ impl Fragment {
         let clip_chain_id = builder.current_clip_chain_id;
         let spatial_id = builder.current_scroll_node_id.spatial_id;
         builder.wr().push_hit_test(
-            rect.to_webrender(),
+            rect,
             clip_chain_id,
             spatial_id,
             style.get_webrender_primitive_flags(),
@@ -402,15 +557,62 @@ impl Fragment {
             Cursor::Text,
         );
 
-        let color = fragment.parent_style.clone_color();
+        let color = if builder.context.forced_colors {
+            forced_colors::CANVAS_TEXT
+        } else {
+            fragment.parent_style.clone_color()
+        };
         let font_metrics = &fragment.font_metrics;
         let dppx = builder.context.style_context.device_pixel_ratio().get();
         let common = builder.common_properties(rect.to_webrender(), &fragment.parent_style);
 
+        // Find-in-page highlights are painted below the text and its shadows.
+        if let Some(find_in_page) = builder.find_in_page {
+            for (highlight, active) in find_in_page.highlights_for_fragment(fragment, &rect) {
+                let color = if active {
+                    ACTIVE_FIND_IN_PAGE_MATCH_COLOR
+                } else {
+                    FIND_IN_PAGE_MATCH_COLOR
+                };
+                builder
+                    .wr()
+                    .push_rect(&common, highlight.to_webrender(), color);
+            }
+        }
+
+        // So is the text selected in caret browsing mode.
+        if let Some(selection) = builder.caret_browsing_selection {
+            for highlight in selection_rects_for_fragment(selection, fragment, &rect) {
+                builder.wr().push_rect(
+                    &common,
+                    highlight.to_webrender(),
+                    CARET_BROWSING_SELECTION_COLOR,
+                );
+            }
+        }
+
+        // And the text selected in a focused text control.
+        if let Some(highlight) = fragment
+            .selection
+            .as_ref()
+            .filter(|selection| !selection.is_empty())
+            .and_then(|selection| fragment.rect_for_text_range(&rect, selection))
+        {
+            builder.wr().push_rect(
+                &common,
+                highlight.to_webrender(),
+                TEXT_CONTROL_SELECTION_COLOR,
+            );
+        }
+
         // Shadows. According to CSS-BACKGROUNDS, text shadows render in *reverse* order (front to
-        // back).
-        let shadows = &fragment.parent_style.get_inherited_text().text_shadow;
-        for shadow in shadows.0.iter().rev() {
+        // back). Forced colors mode leaves them out.
+        let shadows: &[_] = if builder.context.forced_colors {
+            &[]
+        } else {
+            &*fragment.parent_style.get_inherited_text().text_shadow.0
+        };
+        for shadow in shadows.iter().rev() {
             builder.wr().push_shadow(
                 &wr::SpaceAndClipInfo {
                     spatial_id: common.spatial_id,
@@ -467,9 +669,95 @@ impl Fragment {
             self.build_display_list_for_text_decoration(fragment, builder, &rect, &color);
         }
 
-        if !shadows.0.is_empty() {
+        if !shadows.is_empty() {
             builder.wr().pop_all_shadows();
         }
+
+        self.build_display_list_for_spelling_errors(fragment, builder, &rect);
+        self.build_display_list_for_composition(fragment, builder, &rect, &color);
+
+        // The caret of caret browsing mode, and that of a focused text control, are painted
+        // above everything else.
+        if let Some(selection) = builder.caret_browsing_selection {
+            if let Some(mut caret) = caret_rect_for_fragment(selection, fragment, &rect) {
+                caret.size.width = Au::from_f32_px(1. / dppx);
+                builder
+                    .wr()
+                    .push_rect(&common, caret.to_webrender(), rgba(color));
+            }
+        }
+        if let Some(mut caret) = fragment
+            .selection
+            .as_ref()
+            .filter(|selection| selection.is_empty())
+            .and_then(|selection| fragment.caret_rect_for_text_offset(&rect, selection.start))
+        {
+            // The caret can be at the end of the text, outside of the area of the fragment.
+            caret.size.width = Au::from_f32_px(1. / dppx);
+            let caret = caret.to_webrender();
+            let common = builder.common_properties(caret, &fragment.parent_style);
+            builder.wr().push_rect(&common, caret, rgba(color));
+        }
+    }
+
+    /// Underline the part of the IME composition string of a text control that a text
+    /// fragment, positioned at `rect`, displays, in the color of its text.
+    fn build_display_list_for_composition(
+        &self,
+        fragment: &TextFragment,
+        builder: &mut DisplayListBuilder,
+        rect: &PhysicalRect<Au>,
+        color: &AbsoluteColor,
+    ) {
+        let Some(composition) = &fragment.composition else {
+            return;
+        };
+        let Some(mut underline_rect) = fragment.rect_for_text_range(rect, composition) else {
+            return;
+        };
+        let dppx = builder.context.style_context.device_pixel_ratio().get();
+        let font_metrics = &fragment.font_metrics;
+        let thickness = font_metrics.underline_size.to_nearest_pixel(dppx).max(1.);
+        underline_rect.origin.y += font_metrics.ascent - font_metrics.underline_offset;
+        underline_rect.size.height = Au::from_f32_px(thickness);
+        let underline_rect = underline_rect.to_webrender();
+        builder.display_list.wr.push_line(
+            &builder.common_properties(underline_rect, &fragment.parent_style),
+            &underline_rect,
+            thickness,
+            wr::LineOrientation::Horizontal,
+            &rgba(*color),
+            wr::LineStyle::Solid,
+        );
+    }
+
+    /// Mark the misspelled words of a text fragment, positioned at `rect`, with wavy lines
+    /// below them. These are painted above the text and without its shadows.
+    fn build_display_list_for_spelling_errors(
+        &self,
+        fragment: &TextFragment,
+        builder: &mut DisplayListBuilder,
+        rect: &PhysicalRect<Au>,
+    ) {
+        let dppx = builder.context.style_context.device_pixel_ratio().get();
+        let font_metrics = &fragment.font_metrics;
+        let thickness = font_metrics.underline_size.to_nearest_pixel(dppx).max(1.);
+        for spelling_error in &fragment.spelling_errors {
+            let Some(mut error_rect) = fragment.rect_for_text_range(rect, spelling_error) else {
+                continue;
+            };
+            error_rect.origin.y += font_metrics.ascent - font_metrics.underline_offset;
+            error_rect.size.height = Au::from_f32_px(thickness * 3.);
+            let error_rect = error_rect.to_webrender();
+            builder.display_list.wr.push_line(
+                &builder.common_properties(error_rect, &fragment.parent_style),
+                &error_rect,
+                thickness,
+                wr::LineOrientation::Horizontal,
+                &SPELLING_ERROR_COLOR,
+                wr::LineStyle::Wavy,
+            );
+        }
     }
 
     fn build_display_list_for_text_decoration(
@@ -481,10 +769,14 @@ impl Fragment {
     ) {
         let rect = rect.to_webrender();
         let wavy_line_thickness = (0.33 * rect.size().height).ceil();
-        let text_decoration_color = fragment
-            .parent_style
-            .clone_text_decoration_color()
-            .resolve_to_absolute(color);
+        let text_decoration_color = if builder.context.forced_colors {
+            *color
+        } else {
+            fragment
+                .parent_style
+                .clone_text_decoration_color()
+                .resolve_to_absolute(color)
+        };
         let text_decoration_style = fragment.parent_style.clone_text_decoration_style();
         if text_decoration_style == ComputedTextDecorationStyle::MozNone {
             return;
@@ -664,6 +956,11 @@ impl<'a> BuilderForBoxFragment<'a> {
         }
 
         self.build_hit_test(builder, self.border_rect);
+        builder.inspector_overlays.add_box_fragment(
+            self.fragment,
+            self.containing_block,
+            builder.current_scroll_node_id.spatial_id,
+        );
         if self
             .fragment
             .base
@@ -672,9 +969,26 @@ impl<'a> BuilderForBoxFragment<'a> {
         {
             return;
         }
-        self.build_background(builder);
-        self.build_box_shadow(builder);
-        self.build_border(builder);
+        match self.fragment.base.widget {
+            Some(widget) if widget.replaces_background_and_border() => {
+                self.build_box_shadow(builder);
+                widget.paint(builder, &self.fragment.style, *self.content_rect(), None);
+            },
+            widget => {
+                self.build_background(builder);
+                self.build_box_shadow(builder);
+                self.build_border(builder);
+                if let Some(widget) = widget {
+                    let clip_chain_id = self.padding_edge_clip(builder, false);
+                    widget.paint(
+                        builder,
+                        &self.fragment.style,
+                        *self.padding_rect(),
+                        clip_chain_id,
+                    );
+                }
+            },
+        }
     }
 
     fn build_hit_test(&self, builder: &mut DisplayListBuilder, rect: LayoutRect) {
@@ -682,6 +996,7 @@ impl<'a> BuilderForBoxFragment<'a> {
             &self.fragment.style,
             self.fragment.base.tag,
             Cursor::Default,
+            &rect,
         );
         let hit_info = match hit_info {
             Some(hit_info) => hit_info,
@@ -707,7 +1022,10 @@ impl<'a> BuilderForBoxFragment<'a> {
         painter: &BackgroundPainter,
     ) {
         let b = painter.style.get_background();
-        let background_color = painter.style.resolve_color(&b.background_color);
+        let mut background_color = painter.style.resolve_color(&b.background_color);
+        if builder.context.forced_colors {
+            background_color = forced_colors::background_color(background_color);
+        }
         if background_color.alpha > 0.0 {
             // https://drafts.csswg.org/css-backgrounds/#background-color
             // “The background color is clipped according to the background-clip
@@ -777,6 +1095,8 @@ impl<'a> BuilderForBoxFragment<'a> {
         for (index, image) in b.background_image.0.iter().enumerate().rev() {
             match builder.context.resolve_image(node, image) {
                 None => {},
+                // Forced colors mode leaves out gradients, whose colors it cannot replace.
+                Some(ResolvedImage::Gradient(_)) if builder.context.forced_colors => {},
                 Some(ResolvedImage::Gradient(gradient)) => {
                     let intrinsic = NaturalSizes::empty();
                     let Some(layer) =
@@ -854,9 +1174,19 @@ impl<'a> BuilderForBoxFragment<'a> {
         }
     }
 
-    fn build_border_side(&mut self, style_color: BorderStyleColor) -> wr::BorderSide {
+    fn build_border_side(
+        &mut self,
+        builder: &DisplayListBuilder,
+        style_color: BorderStyleColor,
+    ) -> wr::BorderSide {
+        // Forced colors mode paints all borders and outlines in `CanvasText`.
+        let color = if builder.context.forced_colors {
+            forced_colors::CANVAS_TEXT
+        } else {
+            style_color.color
+        };
         wr::BorderSide {
-            color: rgba(style_color.color),
+            color: rgba(color),
             style: match style_color.style {
                 BorderStyle::None => wr::BorderStyle::None,
                 BorderStyle::Solid => wr::BorderStyle::Solid,
@@ -890,10 +1220,10 @@ impl<'a> BuilderForBoxFragment<'a> {
                 let top_border = &table_info.collapsed_borders.y[y][x];
                 let bottom_border = &table_info.collapsed_borders.y[y + 1][x];
                 let details = wr::BorderDetails::Normal(wr::NormalBorder {
-                    left: self.build_border_side(left_border.style_color.clone()),
-                    right: self.build_border_side(right_border.style_color.clone()),
-                    top: self.build_border_side(top_border.style_color.clone()),
-                    bottom: self.build_border_side(bottom_border.style_color.clone()),
+                    left: self.build_border_side(builder, left_border.style_color.clone()),
+                    right: self.build_border_side(builder, right_border.style_color.clone()),
+                    top: self.build_border_side(builder, top_border.style_color.clone()),
+                    bottom: self.build_border_side(builder, bottom_border.style_color.clone()),
                     radius,
                     do_aa: true,
                 });
@@ -959,10 +1289,10 @@ impl<'a> BuilderForBoxFragment<'a> {
         let current_color = self.fragment.style.get_inherited_text().clone_color();
         let style_color = BorderStyleColor::from_border(border, &current_color);
         let details = wr::BorderDetails::Normal(wr::NormalBorder {
-            top: self.build_border_side(style_color.top),
-            right: self.build_border_side(style_color.right),
-            bottom: self.build_border_side(style_color.bottom),
-            left: self.build_border_side(style_color.left),
+            top: self.build_border_side(builder, style_color.top),
+            right: self.build_border_side(builder, style_color.right),
+            bottom: self.build_border_side(builder, style_color.bottom),
+            left: self.build_border_side(builder, style_color.left),
             radius: self.border_radius,
             do_aa: true,
         });
@@ -1062,22 +1392,33 @@ impl<'a> BuilderForBoxFragment<'a> {
         let outline_rect = self.border_rect.inflate(offset, offset);
         let common = builder.common_properties(outline_rect, &self.fragment.style);
         let widths = SideOffsets2D::new_all_same(width);
-        let border_style = match outline.outline_style {
-            // TODO: treating 'auto' as 'solid' is allowed by the spec,
-            // but we should do something better.
-            OutlineStyle::Auto => BorderStyle::Solid,
-            OutlineStyle::BorderStyle(s) => s,
+        let mut radius = offset_radii(self.border_radius, offset);
+        let side = match outline.outline_style {
+            // `auto` outlines are the focus rings of the theme, which are rounded even around
+            // boxes with square corners.
+            OutlineStyle::Auto => {
+                if radius.is_zero() {
+                    radius = theme::focus_ring_radius(self.fragment.base.widget, &outline_rect);
+                }
+                wr::BorderSide {
+                    color: theme::focus_ring_color(builder, style, &outline.outline_color),
+                    style: wr::BorderStyle::Solid,
+                }
+            },
+            OutlineStyle::BorderStyle(border_style) => self.build_border_side(
+                builder,
+                BorderStyleColor {
+                    style: border_style,
+                    color: style.resolve_color(&outline.outline_color),
+                },
+            ),
         };
-        let side = self.build_border_side(BorderStyleColor {
-            style: border_style,
-            color: style.resolve_color(&outline.outline_color),
-        });
         let details = wr::BorderDetails::Normal(wr::NormalBorder {
             top: side,
             right: side,
             bottom: side,
             left: side,
-            radius: offset_radii(self.border_radius, offset),
+            radius,
             do_aa: true,
         });
         builder
@@ -1086,8 +1427,9 @@ impl<'a> BuilderForBoxFragment<'a> {
     }
 
     fn build_box_shadow(&self, builder: &mut DisplayListBuilder<'_>) {
+        // Forced colors mode leaves out box shadows.
         let box_shadows = &self.fragment.style.get_effects().box_shadow.0;
-        if box_shadows.is_empty() {
+        if box_shadows.is_empty() || builder.context.forced_colors {
             return;
         }
 
@@ -1117,8 +1459,16 @@ impl<'a> BuilderForBoxFragment<'a> {
     }
 }
 
+thread_local! {
+    /// The color space that the colors of the display list being built are converted to,
+    /// which is read from the preferences once per display list.
+    static OUTPUT_COLOR_SPACE: Cell<ColorSpace> = const { Cell::new(ColorSpace::Srgb) };
+}
+
+/// Convert `color` to the color space that the rendering context presents its contents in, so
+/// that wide gamut colors are only clipped when it cannot display them.
 fn rgba(color: AbsoluteColor) -> wr::ColorF {
-    let rgba = color.to_color_space(ColorSpace::Srgb);
+    let rgba = color.to_color_space(OUTPUT_COLOR_SPACE.with(Cell::get));
     wr::ColorF::new(
         rgba.components.0.clamp(0.0, 1.0),
         rgba.components.1.clamp(0.0, 1.0),
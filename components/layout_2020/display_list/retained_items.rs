@@ -0,0 +1,268 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Retained display items, which let a display list reuse the display items of stacking
+//! contexts that WebRender kept from the previous display list of the pipeline.
+//!
+//! The display items of a stacking context are built into an item group, which WebRender
+//! caches under an [`wr::ItemKey`]. When the next display list is built after changes that
+//! only need a repaint, stacking contexts that paint none of the changed nodes refer to
+//! their cached items instead of building them again. Item groups may only contain
+//! primitives, so the items of stacking contexts that define clips or that contain nested
+//! WebRender stacking contexts are never retained.
+
+use std::mem;
+
+use fxhash::{FxHashMap, FxHashSet};
+use style::dom::OpaqueNode;
+use style::selector_parser::RestyleDamage;
+use webrender_api as wr;
+use webrender_traits::display_list::HitTestInfo;
+
+use super::DisplayList;
+
+/// What changed since the last display list was built.
+pub enum DisplayListInvalidation {
+    /// Anything may have changed, including the geometry of the fragment tree.
+    All,
+    /// Only the way that these nodes are painted changed.
+    Nodes(FxHashSet<OpaqueNode>),
+}
+
+impl Default for DisplayListInvalidation {
+    fn default() -> Self {
+        Self::Nodes(FxHashSet::default())
+    }
+}
+
+impl DisplayListInvalidation {
+    /// The invalidation for the damage of the elements restyled by a style traversal. Damage
+    /// that needs more than a repaint invalidates everything, because the fragment tree is
+    /// laid out again.
+    pub fn for_damaged_nodes(damaged_nodes: Vec<(OpaqueNode, RestyleDamage)>) -> Self {
+        if damaged_nodes
+            .iter()
+            .any(|(_, damage)| !RestyleDamage::REPAINT.contains(*damage))
+        {
+            return Self::All;
+        }
+        Self::Nodes(damaged_nodes.into_iter().map(|(node, _)| node).collect())
+    }
+
+    fn add(&mut self, other: Self) {
+        match (&mut *self, other) {
+            (Self::All, _) => {},
+            (_, Self::All) => *self = Self::All,
+            (Self::Nodes(nodes), Self::Nodes(other_nodes)) => nodes.extend(other_nodes),
+        }
+    }
+}
+
+/// The parts of a display list that display items refer to by index: the nodes of the
+/// spatial tree and of the scroll tree, and the clip chains that are defined before the
+/// display items are built. Retained items can only be reused if these do not change.
+#[derive(Clone, Copy, PartialEq)]
+struct DisplayListStructure {
+    spatial_tree_count: u64,
+    scroll_tree_node_count: usize,
+    clip_chain_count: u64,
+}
+
+impl DisplayListStructure {
+    fn of(display_list: &DisplayList) -> Self {
+        Self {
+            spatial_tree_count: display_list.spatial_tree_count,
+            scroll_tree_node_count: display_list.compositor_info.scroll_tree.nodes.len(),
+            clip_chain_count: display_list.clip_chain_count,
+        }
+    }
+}
+
+/// The display items of a stacking context that WebRender retained.
+struct RetainedItemGroup {
+    key: wr::ItemKey,
+    /// The index of the first [`HitTestInfo`] of the group in the display list that it was
+    /// built in, and the [`HitTestInfo`] before it, which the first item of the group may
+    /// refer to when they are equal.
+    hit_test_info_start: usize,
+    previous_hit_test_info: Option<HitTestInfo>,
+    /// The [`HitTestInfo`] that the items of the group refer to.
+    hit_test_info: Vec<HitTestInfo>,
+    /// Whether the items of the group are contentful.
+    is_contentful: bool,
+}
+
+/// An item group that is being built.
+pub(crate) struct PendingItemGroup {
+    id: u64,
+    key: wr::ItemKey,
+    hit_test_info_start: usize,
+    previous_hit_test_info: Option<HitTestInfo>,
+    clip_chain_count: u64,
+    iframes_without_pointer_events_count: usize,
+    was_contentful: bool,
+}
+
+/// The display items that WebRender retained from the display lists of a pipeline, by the
+/// id of the stacking context that they were built for.
+#[derive(Default)]
+pub struct RetainedDisplayItems {
+    groups: FxHashMap<u64, RetainedItemGroup>,
+    /// The groups of the display list that is being built.
+    new_groups: FxHashMap<u64, RetainedItemGroup>,
+    /// The key of the next item group that is created.
+    next_key: wr::ItemKey,
+    /// The structure of the display list that the groups were built for.
+    structure: Option<DisplayListStructure>,
+    /// What changed since the last display list was built.
+    invalidation: DisplayListInvalidation,
+    /// Whether the items of the display list that is being built may be retained.
+    cacheable: bool,
+}
+
+impl RetainedDisplayItems {
+    /// Note that `invalidation` changed since the last display list was built. This must
+    /// also be done for layouts that do not build a display list.
+    pub fn invalidate(&mut self, invalidation: DisplayListInvalidation) {
+        self.invalidation.add(invalidation);
+    }
+
+    /// Start building the items of `display_list`, after its stacking context tree was built.
+    /// If `cacheable` is false, for instance because the display list paints things that are
+    /// not part of the fragment tree, nothing is reused or retained.
+    pub(crate) fn begin(&mut self, display_list: &DisplayList, cacheable: bool) {
+        let structure = DisplayListStructure::of(display_list);
+        if !cacheable || self.structure != Some(structure) {
+            self.invalidation = DisplayListInvalidation::All;
+        }
+        if matches!(self.invalidation, DisplayListInvalidation::All) {
+            self.groups.clear();
+            self.next_key = 0;
+        }
+        self.structure = Some(structure);
+        self.cacheable = cacheable;
+    }
+
+    /// Finish building the items of `display_list`, keeping the groups that it retained for
+    /// the next one.
+    pub(crate) fn end(&mut self, display_list: &mut DisplayList) {
+        self.groups = mem::take(&mut self.new_groups);
+        self.invalidation = DisplayListInvalidation::default();
+        display_list.wr.set_cache_size(self.next_key as usize);
+    }
+
+    /// Refer to the retained items of the stacking context with `id` instead of building
+    /// them, unless `paints_any_of` says that it paints one of the nodes that changed.
+    /// Returns whether the items were reused.
+    pub(crate) fn reuse_group(
+        &mut self,
+        id: u64,
+        display_list: &mut DisplayList,
+        paints_any_of: impl FnOnce(&FxHashSet<OpaqueNode>) -> bool,
+    ) -> bool {
+        let DisplayListInvalidation::Nodes(changed_nodes) = &self.invalidation else {
+            return false;
+        };
+        let Some(group) = self.groups.get(&id) else {
+            return false;
+        };
+        let hit_test_info = &display_list.compositor_info.hit_test_info;
+        if hit_test_info.len() != group.hit_test_info_start ||
+            hit_test_info.last() != group.previous_hit_test_info.as_ref() ||
+            paints_any_of(changed_nodes)
+        {
+            return false;
+        }
+
+        let Some(group) = self.groups.remove(&id) else {
+            return false;
+        };
+        display_list.wr.push_reuse_items(group.key);
+        display_list
+            .compositor_info
+            .hit_test_info
+            .extend(group.hit_test_info.iter().cloned());
+        display_list.compositor_info.is_contentful |= group.is_contentful;
+        self.new_groups.insert(id, group);
+        true
+    }
+
+    /// Start building the items of the stacking context with `id` into a group that
+    /// WebRender retains, if possible.
+    pub(crate) fn start_group(
+        &mut self,
+        id: u64,
+        display_list: &mut DisplayList,
+    ) -> Option<PendingItemGroup> {
+        if !self.cacheable {
+            return None;
+        }
+        let key = match self.groups.remove(&id) {
+            Some(group) => group.key,
+            None if self.next_key == wr::ItemKey::MAX => return None,
+            None => {
+                self.next_key += 1;
+                self.next_key - 1
+            },
+        };
+
+        display_list.wr.start_item_group();
+        let hit_test_info = &display_list.compositor_info.hit_test_info;
+        let pending_group = PendingItemGroup {
+            id,
+            key,
+            hit_test_info_start: hit_test_info.len(),
+            previous_hit_test_info: hit_test_info.last().cloned(),
+            clip_chain_count: display_list.clip_chain_count,
+            iframes_without_pointer_events_count: display_list
+                .compositor_info
+                .iframes_without_pointer_events
+                .len(),
+            was_contentful: display_list.compositor_info.is_contentful,
+        };
+        display_list.compositor_info.is_contentful = false;
+        Some(pending_group)
+    }
+
+    /// Finish building the items of `pending_group`, retaining them unless they defined
+    /// clips, which cannot be reused, or unless they have hit testing information that is
+    /// not kept with the group: the planes of 3D rendering contexts, which may move without
+    /// the items being invalidated, and iframes without pointer events.
+    pub(crate) fn finish_group(
+        &mut self,
+        pending_group: PendingItemGroup,
+        display_list: &mut DisplayList,
+    ) {
+        let is_contentful = display_list.compositor_info.is_contentful;
+        display_list.compositor_info.is_contentful |= pending_group.was_contentful;
+        let hit_test_info =
+            &display_list.compositor_info.hit_test_info[pending_group.hit_test_info_start..];
+        if display_list.clip_chain_count != pending_group.clip_chain_count ||
+            hit_test_info.iter().any(|info| info.plane.is_some()) ||
+            display_list
+                .compositor_info
+                .iframes_without_pointer_events
+                .len() !=
+                pending_group.iframes_without_pointer_events_count
+        {
+            display_list.wr.cancel_item_group(false /* discard */);
+            return;
+        }
+        if !display_list.wr.finish_item_group(pending_group.key) {
+            return;
+        }
+        self.new_groups.insert(
+            pending_group.id,
+            RetainedItemGroup {
+                key: pending_group.key,
+                hit_test_info_start: pending_group.hit_test_info_start,
+                previous_hit_test_info: pending_group.previous_hit_test_info,
+                hit_test_info: display_list.compositor_info.hit_test_info
+                    [pending_group.hit_test_info_start..]
+                    .to_vec(),
+                is_contentful,
+            },
+        );
+    }
+}
@@ -0,0 +1,376 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The overlay scrollbars that layout paints over the viewport and over scroll containers, to
+//! show which part of their content is in view. The scroll containers are collected while the
+//! stacking context tree is built, because the display items of stacking contexts may be
+//! retained from a previous display list, and their scrollbars are painted once all of the
+//! content has been. The compositor moves the thumbs as it scrolls, so scrolling does not
+//! need a new display list.
+//!
+//! `scrollbar-width` sets the thickness of the scrollbars, or hides them, and `scrollbar-color`
+//! their colors. Otherwise their colors follow the used color scheme of the scroll container,
+//! or forced colors mode. Scrollbars do not take up space, unless `scrollbar-gutter` reserves
+//! it.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use app_units::Au;
+use base::id::ScrollTreeNodeId;
+use fxhash::FxHasher;
+use servo_arc::Arc as ServoArc;
+use style::computed_values::scrollbar_width::T as ScrollbarWidth;
+use style::properties::ComputedValues;
+use style::values::computed::ui::ScrollbarColor;
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSize, LayoutTransform, LayoutVector2D};
+use webrender_api::{self as wr, ClipChainId};
+use webrender_traits::display_list::{
+    AxesScrollSensitivity, ScrollSensitivity, ScrollbarAxis, ScrollbarThumb,
+};
+
+use super::{DisplayList, forced_colors, rgba};
+use crate::context::LayoutContext;
+use crate::style_ext::ComputedValuesExt;
+
+/// The thickness of scrollbars with `scrollbar-width: auto`, in CSS pixels.
+const SCROLLBAR_THICKNESS: f32 = 12.0;
+
+/// The thickness of scrollbars with `scrollbar-width: thin`, in CSS pixels.
+const THIN_SCROLLBAR_THICKNESS: f32 = 8.0;
+
+/// The space between the thumb and the edges of its scrollbar, in CSS pixels.
+const THUMB_INSET: f32 = 2.0;
+
+/// The shortest length of a thumb, in CSS pixels, so that it stays visible for long content.
+const MINIMUM_THUMB_LENGTH: f32 = 20.0;
+
+/// The color of the thumb with `scrollbar-color: auto`.
+const THUMB_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 0.7,
+};
+
+/// The color of the thumb with `scrollbar-color: auto` in the dark color scheme.
+const DARK_THUMB_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.75,
+    g: 0.75,
+    b: 0.75,
+    a: 0.7,
+};
+
+/// The thickness of the scrollbars of boxes with the given style, or `None` when their
+/// scrollbars are hidden with `scrollbar-width: none`.
+pub(crate) fn scrollbar_thickness(style: &ComputedValues) -> Option<Au> {
+    let thickness = match style.get_ui().scrollbar_width {
+        ScrollbarWidth::Auto => SCROLLBAR_THICKNESS,
+        ScrollbarWidth::Thin => THIN_SCROLLBAR_THICKNESS,
+        ScrollbarWidth::None => return None,
+    };
+    Some(Au::from_f32_px(thickness))
+}
+
+/// The thickness of the scrollbars of a scroll container, and the style that gives their
+/// colors.
+struct ScrollbarStyle {
+    thickness: f32,
+    style: ServoArc<ComputedValues>,
+}
+
+impl ScrollbarStyle {
+    fn new(style: &ServoArc<ComputedValues>) -> Option<Self> {
+        Some(Self {
+            thickness: scrollbar_thickness(style)?.to_f32_px(),
+            style: style.clone(),
+        })
+    }
+
+    /// The colors of the thumbs and of the tracks of the scrollbars. Scrollbars without a
+    /// `scrollbar-color` have no track, and a thumb in the color of the used color scheme,
+    /// or in `CanvasText` in forced colors mode, which overrides `scrollbar-color` too.
+    fn colors(&self, context: &LayoutContext) -> (wr::ColorF, Option<wr::ColorF>) {
+        if context.forced_colors {
+            return (rgba(forced_colors::CANVAS_TEXT), None);
+        }
+        match &self.style.get_inherited_ui().scrollbar_color {
+            ScrollbarColor::Auto => {
+                let preferred_color_scheme = context.style_context.stylist.device().color_scheme();
+                if self.style.uses_dark_color_scheme(preferred_color_scheme) {
+                    (DARK_THUMB_COLOR, None)
+                } else {
+                    (THUMB_COLOR, None)
+                }
+            },
+            ScrollbarColor::Colors { thumb, track } => (
+                rgba(self.style.resolve_color(thumb)),
+                Some(rgba(self.style.resolve_color(track))),
+            ),
+        }
+    }
+}
+
+/// A scroll container, or the viewport, whose scrollbars are painted.
+struct ScrollContainer {
+    /// The scroll node that the scroll container is positioned in, which does not move
+    /// when its content scrolls.
+    parent_scroll_node_id: ScrollTreeNodeId,
+    /// The scroll node of the content of the scroll container.
+    scroll_node_id: ScrollTreeNodeId,
+    /// The clip chain of the scroll container, which clips its scrollbars as well.
+    clip_chain_id: ClipChainId,
+    /// The rectangle that shows the content of the scroll container.
+    scrollport: LayoutRect,
+    /// The size of the scrollable overflow of the scroll container.
+    content_size: LayoutSize,
+    scroll_sensitivity: AxesScrollSensitivity,
+    style: ScrollbarStyle,
+}
+
+/// A scrollbar of a scroll container, with its thumb at the start of its track.
+struct Scrollbar {
+    axis: ScrollbarAxis,
+    track: LayoutRect,
+    thumb: LayoutRect,
+    /// How far the thumb moves along the track when the content is scrolled from its start
+    /// to its end.
+    travel: f32,
+}
+
+/// The scroll containers of a display list whose scrollbars are painted.
+#[derive(Default)]
+pub(crate) struct Scrollbars {
+    scroll_containers: Vec<ScrollContainer>,
+}
+
+impl Scrollbars {
+    /// Add the scroll container with the given scroll frame, whose scrollbars are painted
+    /// unless its style hides them.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add(
+        &mut self,
+        style: &ServoArc<ComputedValues>,
+        parent_scroll_node_id: ScrollTreeNodeId,
+        scroll_node_id: ScrollTreeNodeId,
+        clip_chain_id: ClipChainId,
+        scrollport: LayoutRect,
+        content_size: LayoutSize,
+        scroll_sensitivity: AxesScrollSensitivity,
+    ) {
+        let Some(style) = ScrollbarStyle::new(style) else {
+            return;
+        };
+        self.scroll_containers.push(ScrollContainer {
+            parent_scroll_node_id,
+            scroll_node_id,
+            clip_chain_id,
+            scrollport,
+            content_size,
+            scroll_sensitivity,
+            style,
+        });
+    }
+
+    /// Paint the scrollbars of all of the scroll containers that were added over all of the
+    /// content of the display list. Each thumb is painted at the start of its track, in a
+    /// reference frame whose transform the compositor updates as it scrolls the content, so
+    /// that the thumb follows scrolling without a new display list. Until then, the thumb
+    /// is placed at the given scroll offsets.
+    pub(crate) fn paint(
+        self,
+        display_list: &mut DisplayList,
+        context: &LayoutContext,
+        scroll_offsets: &HashMap<wr::ExternalScrollId, LayoutVector2D>,
+    ) {
+        if self.scroll_containers.is_empty() {
+            return;
+        }
+
+        let root_spatial_id = display_list
+            .compositor_info
+            .root_reference_frame_id
+            .spatial_id;
+        display_list.wr.push_simple_stacking_context(
+            LayoutPoint::zero(),
+            root_spatial_id,
+            wr::PrimitiveFlags::empty(),
+        );
+        for scroll_container in self.scroll_containers {
+            let Some(external_id) = display_list
+                .compositor_info
+                .scroll_tree
+                .get_node(&scroll_container.scroll_node_id)
+                .external_id()
+            else {
+                continue;
+            };
+            // Scroll offsets are stored negated, as the offsets of the content.
+            let scroll_position = scroll_offsets
+                .get(&external_id)
+                .map_or(LayoutVector2D::zero(), |offset| -*offset);
+            let scrollable_size =
+                scroll_container.content_size - scroll_container.scrollport.size();
+            let (thumb_color, track_color) = scroll_container.style.colors(context);
+
+            let mut thumbs = Vec::new();
+            for scrollbar in scroll_container.scrollbars() {
+                if let Some(track_color) = track_color {
+                    let common = wr::CommonItemProperties {
+                        clip_rect: scrollbar.track,
+                        spatial_id: scroll_container.parent_scroll_node_id.spatial_id,
+                        clip_chain_id: scroll_container.clip_chain_id,
+                        flags: wr::PrimitiveFlags::empty(),
+                    };
+                    display_list
+                        .wr
+                        .push_rect(&common, scrollbar.track, track_color);
+                }
+
+                let thumb = ScrollbarThumb {
+                    transform_key: thumb_transform_key(external_id, scrollbar.axis),
+                    axis: scrollbar.axis,
+                    travel: scrollbar.travel,
+                };
+                let thumb_frame = display_list.push_reference_frame(
+                    LayoutPoint::zero(),
+                    &scroll_container.parent_scroll_node_id,
+                    wr::TransformStyle::Flat,
+                    wr::PropertyBinding::Binding(
+                        thumb.transform_key,
+                        thumb.transform(scroll_position, scrollable_size),
+                    ),
+                    wr::ReferenceFrameKind::Transform {
+                        is_2d_scale_translation: true,
+                        should_snap: false,
+                        paired_with_perspective: false,
+                    },
+                );
+                let common = wr::CommonItemProperties {
+                    clip_rect: scrollbar.thumb,
+                    spatial_id: thumb_frame.spatial_id,
+                    clip_chain_id: scroll_container.clip_chain_id,
+                    flags: wr::PrimitiveFlags::empty(),
+                };
+                display_list
+                    .wr
+                    .push_rect(&common, scrollbar.thumb, thumb_color);
+                display_list.wr.pop_reference_frame();
+                thumbs.push(thumb);
+            }
+
+            if let Some(scroll_info) = display_list
+                .compositor_info
+                .scroll_tree
+                .get_node_mut(&scroll_container.scroll_node_id)
+                .scroll_info
+                .as_mut()
+            {
+                scroll_info.scrollbar_thumbs = thumbs;
+            }
+        }
+        display_list.wr.pop_stacking_context();
+    }
+}
+
+/// The key of the transform of the thumb of the scrollbar of a scroll node along `axis`,
+/// which has to be unique among the scrollbars of all pipelines.
+fn thumb_transform_key(
+    external_id: wr::ExternalScrollId,
+    axis: ScrollbarAxis,
+) -> wr::PropertyBindingKey<LayoutTransform> {
+    let mut hasher = FxHasher::default();
+    external_id.hash(&mut hasher);
+    (axis == ScrollbarAxis::Vertical).hash(&mut hasher);
+    wr::PropertyBindingKey::new(hasher.finish())
+}
+
+impl ScrollContainer {
+    /// The scrollbars of this scroll container, in the axes in which the user can scroll
+    /// its content.
+    fn scrollbars(&self) -> Vec<Scrollbar> {
+        let thickness = self.style.thickness;
+        let scrollport = self.scrollport;
+        let can_scroll = |sensitivity, content_length: f32, scrollport_length: f32| {
+            sensitivity == ScrollSensitivity::ScriptAndInputEvents &&
+                content_length > scrollport_length
+        };
+        let scrolls_vertically = can_scroll(
+            self.scroll_sensitivity.y,
+            self.content_size.height,
+            scrollport.height(),
+        );
+        let scrolls_horizontally = can_scroll(
+            self.scroll_sensitivity.x,
+            self.content_size.width,
+            scrollport.width(),
+        );
+
+        // When there are two scrollbars, they leave the corner between them empty.
+        let corner = if scrolls_vertically && scrolls_horizontally {
+            thickness
+        } else {
+            0.0
+        };
+
+        let mut scrollbars = Vec::new();
+        if scrolls_vertically {
+            let track = LayoutRect::new(
+                LayoutPoint::new(scrollport.max.x - thickness, scrollport.min.y),
+                LayoutPoint::new(scrollport.max.x, scrollport.max.y - corner),
+            );
+            let (length, travel) = thumb_length_and_travel(
+                track.height(),
+                scrollport.height(),
+                self.content_size.height,
+            );
+            let start = track.min.y + THUMB_INSET;
+            let thumb = LayoutRect::new(
+                LayoutPoint::new(track.min.x + THUMB_INSET, start),
+                LayoutPoint::new(track.max.x - THUMB_INSET, start + length),
+            );
+            scrollbars.push(Scrollbar {
+                axis: ScrollbarAxis::Vertical,
+                track,
+                thumb,
+                travel,
+            });
+        }
+        if scrolls_horizontally {
+            let track = LayoutRect::new(
+                LayoutPoint::new(scrollport.min.x, scrollport.max.y - thickness),
+                LayoutPoint::new(scrollport.max.x - corner, scrollport.max.y),
+            );
+            let (length, travel) =
+                thumb_length_and_travel(track.width(), scrollport.width(), self.content_size.width);
+            let start = track.min.x + THUMB_INSET;
+            let thumb = LayoutRect::new(
+                LayoutPoint::new(start, track.min.y + THUMB_INSET),
+                LayoutPoint::new(start + length, track.max.y - THUMB_INSET),
+            );
+            scrollbars.push(Scrollbar {
+                axis: ScrollbarAxis::Horizontal,
+                track,
+                thumb,
+                travel,
+            });
+        }
+        scrollbars
+    }
+}
+
+/// The length of the thumb in a track of the given length, along the axis of the scrollbar,
+/// and how far it moves along the track as the content scrolls from its start to its end.
+/// The thumb is as long, relative to its track, as the scrollport is relative to the content.
+fn thumb_length_and_travel(
+    track_length: f32,
+    scrollport_length: f32,
+    content_length: f32,
+) -> (f32, f32) {
+    let track_length = (track_length - 2.0 * THUMB_INSET).max(0.0);
+    let thumb_length = (track_length * scrollport_length / content_length)
+        .max(MINIMUM_THUMB_LENGTH)
+        .min(track_length);
+    (thumb_length, track_length - thumb_length)
+}
@@ -11,7 +11,9 @@ use base::id::ScrollTreeNodeId;
 use base::print_tree::PrintTree;
 use euclid::SideOffsets2D;
 use euclid::default::{Point2D, Rect, Size2D};
+use fxhash::FxHashSet;
 use log::warn;
+use script_layout_interface::TopLayerElement;
 use servo_arc::Arc as ServoArc;
 use servo_config::opts::DebugOptions;
 use style::Zero;
@@ -19,6 +21,7 @@ use style::computed_values::float::T as ComputedFloat;
 use style::computed_values::mix_blend_mode::T as ComputedMixBlendMode;
 use style::computed_values::overflow_x::T as ComputedOverflow;
 use style::computed_values::position::T as ComputedPosition;
+use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::angle::Angle;
 use style::values::computed::basic_shape::ClipPath;
@@ -32,17 +35,63 @@ use webrender_traits::display_list::{AxesScrollSensitivity, ScrollableNodeInfo};
 use wr::units::{LayoutPixel, LayoutSize};
 use wr::{ClipChainId, SpatialTreeItemKey, StickyOffsetBounds};
 
-use super::DisplayList;
 use super::clip_path::build_clip_path_clip_chain_if_necessary;
+use super::{DisplayList, forced_colors};
 use crate::display_list::conversions::{FilterToWebRender, ToWebRender};
 use crate::display_list::{BuilderForBoxFragment, DisplayListBuilder, offset_radii};
 use crate::fragment_tree::{
     BoxFragment, ContainingBlockManager, Fragment, FragmentFlags, FragmentTree,
-    PositioningFragment, SpecificLayoutInfo,
+    PositioningFragment, SpecificLayoutInfo, Tag,
 };
 use crate::geom::{AuOrAuto, PhysicalRect, PhysicalSides};
 use crate::style_ext::{ComputedValuesExt, TransformExt};
 
+/// The `Canvas` system color of the dark color scheme, which is painted behind documents
+/// whose root element uses that scheme and has no background of its own.
+const DARK_CANVAS_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.071,
+    g: 0.071,
+    b: 0.071,
+    a: 1.0,
+};
+
+/// The color of the `::backdrop` of fullscreen elements.
+///
+/// <https://fullscreen.spec.whatwg.org/#user-agent-level-style-sheet-defaults>
+const FULLSCREEN_BACKDROP_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The color of the `::backdrop` of modal dialogs.
+///
+/// <https://html.spec.whatwg.org/multipage/#flow-content-3>
+const MODAL_DIALOG_BACKDROP_COLOR: wr::ColorF = wr::ColorF {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.1,
+};
+
+/// The plane that the contents of a reference frame lie in, when it is part of a 3D rendering
+/// context. These are used to order hit test results by depth.
+///
+/// This is only tracked for 3D rendering contexts that are established by elements with a
+/// transform, as other elements do not create a reference frame.
+#[derive(Clone)]
+pub(crate) struct ThreeDPlane {
+    /// The id of the 3D rendering context, which is unique within the display list.
+    pub context: u32,
+    /// The transform from the coordinate space of the reference frame to the one of the
+    /// element that establishes the 3D rendering context.
+    pub transform: LayoutTransform,
+    /// Whether the reference frame preserves 3D, in which case the reference frames nested
+    /// in it are part of the same 3D rendering context rather than flattened into its plane.
+    preserves_3d: bool,
+}
+
 #[derive(Clone)]
 pub(crate) struct ContainingBlock {
     /// The SpatialId of the spatial node that contains the children
@@ -116,8 +165,11 @@ impl DisplayList {
     pub fn build_stacking_context_tree(
         &mut self,
         fragment_tree: &FragmentTree,
+        top_layer: &[TopLayerElement],
         debug: &DebugOptions,
     ) -> StackingContext {
+        self.top_layer = top_layer.to_vec();
+
         let cb_for_non_fixed_descendants = ContainingBlock::new(
             fragment_tree.initial_containing_block,
             self.compositor_info.root_scroll_node_id,
@@ -143,6 +195,19 @@ impl DisplayList {
             for_absolute_and_fixed_descendants: &cb_for_fixed_descendants,
         };
 
+        // The scrollbars of the viewport take their style from the root element.
+        if let Some(Fragment::Box(root_fragment)) = fragment_tree.root_fragments.first() {
+            self.scrollbars.add(
+                &root_fragment.borrow().style,
+                self.compositor_info.root_reference_frame_id,
+                self.compositor_info.root_scroll_node_id,
+                ClipChainId::INVALID,
+                LayoutRect::from_size(self.compositor_info.viewport_size),
+                self.compositor_info.content_size,
+                fragment_tree.viewport_scroll_sensitivity,
+            );
+        }
+
         let mut root_stacking_context = StackingContext::create_root(&self.wr, debug);
         for fragment in &fragment_tree.root_fragments {
             fragment.build_stacking_context_tree(
@@ -153,10 +218,14 @@ impl DisplayList {
             );
         }
         root_stacking_context.sort();
+
+        let mut top_layer = mem::take(&mut self.top_layer_stacking_contexts);
+        top_layer.sort_by_key(|top_layer_stacking_context| top_layer_stacking_context.index);
+        root_stacking_context.top_layer = top_layer;
         root_stacking_context
     }
 
-    fn push_reference_frame(
+    pub(super) fn push_reference_frame(
         &mut self,
         origin: LayoutPoint,
         parent_scroll_node_id: &ScrollTreeNodeId,
@@ -173,11 +242,67 @@ impl DisplayList {
             kind,
             spatial_tree_item_key,
         );
-        self.compositor_info.scroll_tree.add_scroll_tree_node(
+        let new_scroll_node_id = self.compositor_info.scroll_tree.add_scroll_tree_node(
             Some(parent_scroll_node_id),
             new_spatial_id,
             None,
-        )
+        );
+
+        let preserves_3d = transform_style == wr::TransformStyle::Preserve3D;
+        let plane = match self.three_d_plane(parent_scroll_node_id) {
+            // Reference frames nested in an element that preserves 3D are part of its 3D
+            // rendering context, in their own plane.
+            Some(parent_plane) if parent_plane.preserves_3d => {
+                let transform = match transform {
+                    wr::PropertyBinding::Value(transform) => transform,
+                    wr::PropertyBinding::Binding(_, transform) => transform,
+                };
+                Some(ThreeDPlane {
+                    context: parent_plane.context,
+                    transform: transform
+                        .then_translate(origin.to_vector().to_3d())
+                        .then(&parent_plane.transform),
+                    preserves_3d,
+                })
+            },
+            // Those nested in a flat element are flattened into its plane.
+            Some(parent_plane) => Some(ThreeDPlane {
+                preserves_3d: false,
+                ..parent_plane.clone()
+            }),
+            None if preserves_3d => {
+                self.three_d_rendering_context_count += 1;
+                Some(ThreeDPlane {
+                    context: self.three_d_rendering_context_count,
+                    transform: LayoutTransform::identity(),
+                    preserves_3d,
+                })
+            },
+            None => None,
+        };
+        if let Some(plane) = plane {
+            self.three_d_planes.insert(new_scroll_node_id.index, plane);
+        }
+        new_scroll_node_id
+    }
+
+    /// The plane that the contents of the spatial node `scroll_node_id` lie in, if they are
+    /// part of a 3D rendering context. Nodes that are not reference frames lie in the plane
+    /// of their closest ancestor reference frame.
+    pub(crate) fn three_d_plane(&self, scroll_node_id: &ScrollTreeNodeId) -> Option<&ThreeDPlane> {
+        if self.three_d_planes.is_empty() {
+            return None;
+        }
+        let mut index = Some(scroll_node_id.index);
+        while let Some(current_index) = index {
+            if let Some(plane) = self.three_d_planes.get(&current_index) {
+                return Some(plane);
+            }
+            index = self.compositor_info.scroll_tree.nodes[current_index]
+                .parent
+                .map(|parent| parent.index);
+        }
+        None
     }
 
     fn pop_reference_frame(&mut self) {
@@ -237,6 +362,7 @@ impl DisplayList {
                 scrollable_size: content_rect.size() - clip_rect.size(),
                 scroll_sensitivity,
                 offset: LayoutVector2D::zero(),
+                scrollbar_thumbs: Vec::new(),
             }),
         )
     }
@@ -360,6 +486,9 @@ pub struct StackingContext {
     /// The [`FragmentFlags`] of the [`Fragment`] that established this stacking context.
     initializing_fragment_flags: FragmentFlags,
 
+    /// The [`Tag`] of the [`Fragment`] that established this stacking context, if any.
+    initializing_fragment_tag: Option<Tag>,
+
     /// The type of this stacking context. Used for collecting and sorting.
     context_type: StackingContextType,
 
@@ -392,10 +521,50 @@ pub struct StackingContext {
     /// <https://drafts.csswg.org/css-position-4/#paint-a-box-in-a-line-box>
     atomic_inline_stacking_containers: Vec<StackingContext>,
 
+    /// The stacking contexts of the elements in the top layer, in the order of the top layer.
+    /// These are painted above everything else, so only the root stacking context has any.
+    /// <https://drafts.csswg.org/css-position-4/#top-layer>
+    top_layer: Vec<TopLayerStackingContext>,
+
     /// Information gathered about the painting order, for [Self::debug_print].
     debug_print_items: Option<RefCell<Vec<DebugPrintItem>>>,
 }
 
+/// The stacking context of an element in the top layer, which is painted over its
+/// `::backdrop`.
+pub(crate) struct TopLayerStackingContext {
+    /// The position of the element in the top layer.
+    index: usize,
+    /// The color of the `::backdrop` of the element, which covers the viewport.
+    backdrop_color: wr::ColorF,
+    /// A stacking context that contains the one established by the element.
+    stacking_context: StackingContext,
+}
+
+impl TopLayerStackingContext {
+    fn build_display_list(&self, builder: &mut DisplayListBuilder) {
+        // <https://drafts.csswg.org/css-position-4/#backdrop>
+        // TODO: Style the `::backdrop` pseudo-element, which Stylo cannot match yet.
+        let viewport_rect =
+            LayoutRect::from_size(builder.display_list.compositor_info.viewport_size);
+        let common = wr::CommonItemProperties {
+            clip_rect: viewport_rect,
+            spatial_id: builder
+                .display_list
+                .compositor_info
+                .root_reference_frame_id
+                .spatial_id,
+            clip_chain_id: ClipChainId::INVALID,
+            flags: wr::PrimitiveFlags::empty(),
+        };
+        builder
+            .wr()
+            .push_rect(&common, viewport_rect, self.backdrop_color);
+
+        self.stacking_context.build_display_list(builder);
+    }
+}
+
 /// Refers to one of the child contents or stacking contexts of a [StackingContext].
 #[derive(Clone, Copy)]
 pub struct DebugPrintItem {
@@ -410,6 +579,7 @@ pub enum DebugPrintField {
     RealStackingContextsAndPositionedStackingContainers,
     FloatStackingContainers,
     AtomicInlineStackingContainers,
+    TopLayer,
 }
 
 impl StackingContext {
@@ -419,6 +589,7 @@ impl StackingContext {
         clip_chain_id: wr::ClipChainId,
         initializing_fragment_style: ServoArc<ComputedValues>,
         initializing_fragment_flags: FragmentFlags,
+        initializing_fragment_tag: Option<Tag>,
         context_type: StackingContextType,
     ) -> Self {
         // WebRender has two different ways of expressing "no clip." ClipChainId::INVALID should be
@@ -434,11 +605,32 @@ impl StackingContext {
             clip_chain_id,
             initializing_fragment_style: Some(initializing_fragment_style),
             initializing_fragment_flags,
+            initializing_fragment_tag,
             context_type,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
             float_stacking_containers: vec![],
             atomic_inline_stacking_containers: vec![],
+            top_layer: vec![],
+            debug_print_items: self.debug_print_items.is_some().then(|| vec![].into()),
+        }
+    }
+
+    /// Create a stacking context that contains the stacking context of an element in the top
+    /// layer, which is painted above the root stacking context rather than inside its parent.
+    fn create_for_top_layer(&self) -> Self {
+        Self {
+            spatial_id: self.spatial_id,
+            clip_chain_id: None,
+            initializing_fragment_style: None,
+            initializing_fragment_flags: FragmentFlags::empty(),
+            initializing_fragment_tag: None,
+            context_type: StackingContextType::RealStackingContext,
+            contents: vec![],
+            real_stacking_contexts_and_positioned_stacking_containers: vec![],
+            float_stacking_containers: vec![],
+            atomic_inline_stacking_containers: vec![],
+            top_layer: vec![],
             debug_print_items: self.debug_print_items.is_some().then(|| vec![].into()),
         }
     }
@@ -449,11 +641,13 @@ impl StackingContext {
             clip_chain_id: None,
             initializing_fragment_style: None,
             initializing_fragment_flags: FragmentFlags::empty(),
+            initializing_fragment_tag: None,
             context_type: StackingContextType::RealStackingContext,
             contents: vec![],
             real_stacking_contexts_and_positioned_stacking_containers: vec![],
             float_stacking_containers: vec![],
             atomic_inline_stacking_containers: vec![],
+            top_layer: vec![],
             debug_print_items: debug.dump_stacking_context_tree.then(|| vec![].into()),
         }
     }
@@ -515,26 +709,63 @@ impl StackingContext {
         );
     }
 
+    /// Whether this stacking context needs a WebRender stacking context. WebRender only uses
+    /// stacking contexts to apply certain effects.
+    fn needs_webrender_stacking_context(&self) -> bool {
+        let Some(style) = self.initializing_fragment_style.as_ref() else {
+            return false;
+        };
+        let effects = style.get_effects();
+        !effects.filter.0.is_empty() ||
+            effects.opacity != 1.0 ||
+            effects.mix_blend_mode != ComputedMixBlendMode::Normal ||
+            style.has_transform_or_perspective(FragmentFlags::empty()) ||
+            style.clone_clip_path() != ClipPath::None
+    }
+
+    /// The stacking contexts and stacking containers that are painted as part of this one.
+    fn children(&self) -> impl Iterator<Item = &StackingContext> {
+        self.real_stacking_contexts_and_positioned_stacking_containers
+            .iter()
+            .chain(&self.float_stacking_containers)
+            .chain(&self.atomic_inline_stacking_containers)
+            .chain(
+                self.top_layer
+                    .iter()
+                    .map(|top_layer_stacking_context| &top_layer_stacking_context.stacking_context),
+            )
+    }
+
+    /// Whether any of the stacking contexts painted as part of this one needs a WebRender
+    /// stacking context.
+    fn contains_webrender_stacking_contexts(&self) -> bool {
+        self.children().any(|child| {
+            child.needs_webrender_stacking_context() || child.contains_webrender_stacking_contexts()
+        })
+    }
+
+    /// Whether this stacking context paints any fragment of one of `nodes`.
+    fn paints_any_of(&self, nodes: &FxHashSet<OpaqueNode>) -> bool {
+        self.contents.iter().any(|content| match content {
+            StackingContextContent::Fragment { fragment, .. } => {
+                fragment.tag().is_some_and(|tag| nodes.contains(&tag.node))
+            },
+            StackingContextContent::AtomicInlineStackingContainer { .. } => false,
+        }) || self.children().any(|child| child.paints_any_of(nodes))
+    }
+
     fn push_webrender_stacking_context_if_necessary(
         &self,
         builder: &mut DisplayListBuilder,
     ) -> bool {
-        let style = match self.initializing_fragment_style.as_ref() {
-            Some(style) => style,
-            None => return false,
-        };
-
-        // WebRender only uses the stacking context to apply certain effects. If we don't
-        // actually need to create a stacking context, just avoid creating one.
-        let effects = style.get_effects();
-        if effects.filter.0.is_empty() &&
-            effects.opacity == 1.0 &&
-            effects.mix_blend_mode == ComputedMixBlendMode::Normal &&
-            !style.has_transform_or_perspective(FragmentFlags::empty()) &&
-            style.clone_clip_path() == ClipPath::None
-        {
+        // If we don't actually need to create a stacking context, just avoid creating one.
+        if !self.needs_webrender_stacking_context() {
             return false;
         }
+        let Some(style) = self.initializing_fragment_style.as_ref() else {
+            return false;
+        };
+        let effects = style.get_effects();
 
         // Create the filter pipeline.
         let current_color = style.clone_color();
@@ -604,13 +835,37 @@ impl StackingContext {
             .to_webrender();
 
         let background_color = style.resolve_color(&style.get_background().background_color);
-        if background_color.alpha > 0.0 {
+        if builder.context.forced_colors {
+            // Forced colors mode paints the canvas in its `Canvas` color, whether the root
+            // element has a background or not.
+            let common = builder.common_properties(painting_area, style);
+            builder.display_list.wr.push_rect(
+                &common,
+                painting_area,
+                super::rgba(forced_colors::CANVAS),
+            )
+        } else if background_color.alpha > 0.0 {
             let common = builder.common_properties(painting_area, style);
             let color = super::rgba(background_color);
             builder
                 .display_list
                 .wr
                 .push_rect(&common, painting_area, color)
+        } else if style.uses_dark_color_scheme(
+            builder
+                .context
+                .style_context
+                .stylist
+                .device()
+                .color_scheme(),
+        ) {
+            // A transparent canvas shows the `Canvas` system color of the used color scheme
+            // of the root element. The light one is the white that the page is cleared to.
+            let common = builder.common_properties(painting_area, style);
+            builder
+                .display_list
+                .wr
+                .push_rect(&common, painting_area, DARK_CANVAS_COLOR)
         }
 
         // `background-color` was comparatively easy,
@@ -713,6 +968,42 @@ impl StackingContext {
     pub(crate) fn build_display_list(&self, builder: &mut DisplayListBuilder) {
         let pushed_context = self.push_webrender_stacking_context_if_necessary(builder);
 
+        // The items of real stacking contexts are retained by WebRender, so that they can be
+        // reused by the next display list if nothing painted in them changes.
+        let item_group_id = self
+            .initializing_fragment_tag
+            .filter(|_| {
+                self.context_type == StackingContextType::RealStackingContext &&
+                    !builder.in_item_group &&
+                    self.debug_print_items.is_none() &&
+                    !self.contains_webrender_stacking_contexts()
+            })
+            .map(|tag| tag.to_display_list_fragment_id());
+        match item_group_id {
+            Some(id)
+                if builder
+                    .retained_items
+                    .reuse_group(id, builder.display_list, |nodes| self.paints_any_of(nodes)) => {},
+            Some(id) => {
+                let pending_group = builder.retained_items.start_group(id, builder.display_list);
+                builder.in_item_group = pending_group.is_some();
+                self.build_display_list_contents(builder);
+                builder.in_item_group = false;
+                if let Some(pending_group) = pending_group {
+                    builder
+                        .retained_items
+                        .finish_group(pending_group, builder.display_list);
+                }
+            },
+            None => self.build_display_list_contents(builder),
+        }
+
+        if pushed_context {
+            builder.display_list.wr.pop_stacking_context();
+        }
+    }
+
+    fn build_display_list_contents(&self, builder: &mut DisplayListBuilder) {
         // Properly order display items that make up a stacking context.
         // “Steps” here refer to the steps in CSS 2.1 Appendix E.
         // Note that “positioned descendants” is generalised to include all descendants that
@@ -795,8 +1086,11 @@ impl StackingContext {
             child.build_display_list(builder, &self.atomic_inline_stacking_containers);
         }
 
-        if pushed_context {
-            builder.display_list.wr.pop_stacking_context();
+        // The top layer, from the bottom to the top, each element over its `::backdrop`.
+        // <https://drafts.csswg.org/css-position-4/#top-layer>
+        for (i, child) in self.top_layer.iter().enumerate() {
+            self.debug_push_print_item(DebugPrintField::TopLayer, i);
+            child.build_display_list(builder);
         }
     }
 
@@ -857,6 +1151,13 @@ impl StackingContext {
                 DebugPrintField::AtomicInlineStackingContainers => {
                     // do nothing; we print these in DebugPrintField::Contents
                 },
+                DebugPrintField::TopLayer => {
+                    tree.new_level(format!("TopLayer #{index}"));
+                    self.top_layer[*index]
+                        .stacking_context
+                        .debug_print_with_tree(tree);
+                    tree.end_level();
+                },
             }
         }
         match self.context_type {
@@ -905,6 +1206,47 @@ impl Fragment {
                     return;
                 }
 
+                // Elements in the top layer are painted above the root stacking context, in
+                // the order of the top layer, rather than by their parent stacking context.
+                let top_layer_element = fragment
+                    .base
+                    .flags
+                    .contains(FragmentFlags::IS_IN_TOP_LAYER)
+                    .then(|| {
+                        display_list
+                            .top_layer
+                            .iter()
+                            .enumerate()
+                            .find(|(_, element)| {
+                                fragment.base.tag.map(|tag| tag.node) == Some(element.node)
+                            })
+                    })
+                    .flatten()
+                    .map(|(index, element)| (index, element.is_fullscreen));
+                if let Some((index, is_fullscreen)) = top_layer_element {
+                    let mut top_layer_stacking_context = stacking_context.create_for_top_layer();
+                    fragment.build_stacking_context_tree(
+                        fragment_clone,
+                        display_list,
+                        containing_block,
+                        containing_block_info,
+                        &mut top_layer_stacking_context,
+                    );
+                    top_layer_stacking_context.sort();
+                    display_list
+                        .top_layer_stacking_contexts
+                        .push(TopLayerStackingContext {
+                            index,
+                            backdrop_color: if is_fullscreen {
+                                FULLSCREEN_BACKDROP_COLOR
+                            } else {
+                                MODAL_DIALOG_BACKDROP_COLOR
+                            },
+                            stacking_context: top_layer_stacking_context,
+                        });
+                    return;
+                }
+
                 fragment.build_stacking_context_tree(
                     fragment_clone,
                     display_list,
@@ -1142,6 +1484,7 @@ impl BoxFragment {
             stacking_context_clip_chain_id,
             self.style.clone(),
             self.base.flags,
+            self.base.tag,
             context_type,
         );
         self.build_stacking_context_tree_for_children(
@@ -1486,6 +1829,15 @@ impl BoxFragment {
             scroll_frame_rect,
             sensitivity,
         );
+        display_list.scrollbars.add(
+            &self.style,
+            *parent_scroll_node_id,
+            scroll_tree_node_id,
+            clip_chain_id,
+            scroll_frame_rect,
+            content_rect.size(),
+            sensitivity,
+        );
 
         Some(OverflowFrameData {
             clip_chain_id,
@@ -0,0 +1,431 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The theme that paints form controls the way that the platform would, rather than with
+//! the plain boxes and text that their style alone would give them.
+//!
+//! Checkboxes and radio buttons are replaced elements that the theme draws entirely, at the
+//! natural size of the platform. Buttons and drop-down selects keep the background and border
+//! of their style, and the theme shades them while they are hovered, pressed or disabled, and
+//! draws the arrow of selects. The theme also draws the focus rings of `outline-style: auto`.
+//!
+//! Widgets are painted with the palette of the used color scheme of their element, which is
+//! dark when `color-scheme` supports dark and either the user prefers it or light is not
+//! supported, or with that of forced colors mode.
+
+use style::properties::ComputedValues;
+use style::values::computed::Color;
+use stylo_dom::ElementState;
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSize};
+use webrender_api::{self as wr, ClipChainId};
+
+use super::{DisplayListBuilder, create_clip_chain, forced_colors, rgba};
+use crate::geom::PhysicalSize;
+use crate::style_ext::ComputedValuesExt;
+
+/// The size of checkboxes, in CSS pixels.
+#[cfg(target_os = "macos")]
+const CHECKBOX_SIZE: f64 = 14.0;
+#[cfg(not(target_os = "macos"))]
+const CHECKBOX_SIZE: f64 = 13.0;
+
+/// The size of radio buttons, in CSS pixels.
+#[cfg(target_os = "macos")]
+const RADIO_SIZE: f64 = 16.0;
+#[cfg(not(target_os = "macos"))]
+const RADIO_SIZE: f64 = 13.0;
+
+/// The radius of the corners of checkboxes and of focus rings around square boxes.
+const CORNER_RADIUS: f32 = 2.0;
+
+/// The width of the arrow of selects, in CSS pixels.
+const SELECT_ARROW_WIDTH: f32 = 7.0;
+
+/// The space between the arrow of selects and the end of their padding box.
+const SELECT_ARROW_INSET: f32 = 4.0;
+
+/// The colors that the theme paints widgets with.
+struct Palette {
+    accent: wr::ColorF,
+    hovered_accent: wr::ColorF,
+    active_accent: wr::ColorF,
+    field: wr::ColorF,
+    active_field: wr::ColorF,
+    disabled_field: wr::ColorF,
+    field_border: wr::ColorF,
+    hovered_field_border: wr::ColorF,
+    disabled: wr::ColorF,
+    hovered_shade: wr::ColorF,
+    active_shade: wr::ColorF,
+    disabled_shade: wr::ColorF,
+}
+
+static LIGHT_PALETTE: Palette = Palette {
+    accent: color(0.0, 0.459, 1.0, 1.0),
+    hovered_accent: color(0.0, 0.388, 0.882, 1.0),
+    active_accent: color(0.0, 0.31, 0.737, 1.0),
+    field: color(1.0, 1.0, 1.0, 1.0),
+    active_field: color(0.914, 0.914, 0.914, 1.0),
+    disabled_field: color(0.937, 0.937, 0.937, 1.0),
+    field_border: color(0.463, 0.463, 0.463, 1.0),
+    hovered_field_border: color(0.31, 0.31, 0.31, 1.0),
+    disabled: color(0.463, 0.463, 0.463, 0.3),
+    hovered_shade: color(0.0, 0.0, 0.0, 0.05),
+    active_shade: color(0.0, 0.0, 0.0, 0.12),
+    disabled_shade: color(1.0, 1.0, 1.0, 0.4),
+};
+
+static DARK_PALETTE: Palette = Palette {
+    accent: color(0.6, 0.784, 1.0, 1.0),
+    hovered_accent: color(0.82, 0.902, 1.0, 1.0),
+    active_accent: color(0.38, 0.663, 1.0, 1.0),
+    field: color(0.231, 0.231, 0.231, 1.0),
+    active_field: color(0.329, 0.329, 0.329, 1.0),
+    disabled_field: color(0.169, 0.169, 0.169, 1.0),
+    field_border: color(0.522, 0.522, 0.522, 1.0),
+    hovered_field_border: color(0.675, 0.675, 0.675, 1.0),
+    disabled: color(0.522, 0.522, 0.522, 0.3),
+    hovered_shade: color(1.0, 1.0, 1.0, 0.08),
+    active_shade: color(1.0, 1.0, 1.0, 0.16),
+    disabled_shade: color(0.0, 0.0, 0.0, 0.4),
+};
+
+/// The palette of forced colors mode, which only uses its system colors and does not shade
+/// buttons.
+static FORCED_COLORS_PALETTE: Palette = Palette {
+    accent: CANVAS_TEXT,
+    hovered_accent: CANVAS_TEXT,
+    active_accent: CANVAS_TEXT,
+    field: CANVAS,
+    active_field: CANVAS,
+    disabled_field: CANVAS,
+    field_border: CANVAS_TEXT,
+    hovered_field_border: CANVAS_TEXT,
+    disabled: color(0.5, 0.5, 0.5, 1.0),
+    hovered_shade: TRANSPARENT,
+    active_shade: TRANSPARENT,
+    disabled_shade: TRANSPARENT,
+};
+
+/// [`forced_colors::CANVAS`] and [`forced_colors::CANVAS_TEXT`], as WebRender colors.
+const CANVAS: wr::ColorF = color(0.0, 0.0, 0.0, 1.0);
+const CANVAS_TEXT: wr::ColorF = color(1.0, 1.0, 1.0, 1.0);
+const TRANSPARENT: wr::ColorF = color(0.0, 0.0, 0.0, 0.0);
+
+const fn color(r: f32, g: f32, b: f32, a: f32) -> wr::ColorF {
+    wr::ColorF { r, g, b, a }
+}
+
+impl Palette {
+    /// The palette that the widgets of elements with the given style are painted with.
+    fn for_style(builder: &DisplayListBuilder, style: &ComputedValues) -> &'static Self {
+        let preferred_color_scheme = builder
+            .context
+            .style_context
+            .stylist
+            .device()
+            .color_scheme();
+        if builder.context.forced_colors {
+            &FORCED_COLORS_PALETTE
+        } else if style.uses_dark_color_scheme(preferred_color_scheme) {
+            &DARK_PALETTE
+        } else {
+            &LIGHT_PALETTE
+        }
+    }
+}
+
+/// The kinds of form controls that the theme paints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WidgetKind {
+    Checkbox,
+    Radio,
+    Button,
+    Select,
+}
+
+/// A form control that the theme paints, with the state of its element when it was laid out.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Widget {
+    pub kind: WidgetKind,
+    pub state: ElementState,
+}
+
+impl Widget {
+    /// The natural size of the widget, for those that the theme draws entirely and which are
+    /// laid out as replaced elements.
+    pub(crate) fn natural_size(&self) -> Option<PhysicalSize<f64>> {
+        let size = match self.kind {
+            WidgetKind::Checkbox => CHECKBOX_SIZE,
+            WidgetKind::Radio => RADIO_SIZE,
+            WidgetKind::Button | WidgetKind::Select => return None,
+        };
+        Some(PhysicalSize::new(size, size))
+    }
+
+    /// Whether the theme paints this widget in place of the background and border of its box.
+    pub(crate) fn replaces_background_and_border(&self) -> bool {
+        self.natural_size().is_some()
+    }
+
+    fn is(&self, state: ElementState) -> bool {
+        self.state.contains(state)
+    }
+
+    /// The colors of the fill and border of checkboxes and radio buttons, and of the mark that
+    /// shows that they are checked.
+    fn toggle_colors(&self, palette: &Palette) -> (wr::ColorF, wr::ColorF, wr::ColorF) {
+        let is_on = self.is(ElementState::CHECKED) ||
+            (self.kind == WidgetKind::Checkbox && self.is(ElementState::INDETERMINATE));
+        match (is_on, self.is(ElementState::DISABLED)) {
+            (true, true) => (palette.disabled, palette.disabled, palette.field),
+            (false, true) => (palette.disabled_field, palette.disabled, palette.disabled),
+            (true, false) => {
+                let accent = if self.is(ElementState::ACTIVE) {
+                    palette.active_accent
+                } else if self.is(ElementState::HOVER) {
+                    palette.hovered_accent
+                } else {
+                    palette.accent
+                };
+                (accent, accent, palette.field)
+            },
+            (false, false) => {
+                if self.is(ElementState::ACTIVE) {
+                    (
+                        palette.active_field,
+                        palette.hovered_field_border,
+                        palette.field,
+                    )
+                } else if self.is(ElementState::HOVER) {
+                    (palette.field, palette.hovered_field_border, palette.field)
+                } else {
+                    (palette.field, palette.field_border, palette.field)
+                }
+            },
+        }
+    }
+
+    /// Paint the widget of a box. `rect` is the content box of checkboxes and radio buttons,
+    /// and the padding box of buttons and selects, clipped by `clip_chain_id` to the rounded
+    /// corners of their border.
+    pub(crate) fn paint(
+        &self,
+        builder: &mut DisplayListBuilder,
+        style: &ComputedValues,
+        rect: LayoutRect,
+        clip_chain_id: Option<ClipChainId>,
+    ) {
+        match self.kind {
+            WidgetKind::Checkbox => self.paint_checkbox(builder, style, rect),
+            WidgetKind::Radio => self.paint_radio(builder, style, rect),
+            WidgetKind::Button => self.paint_shade(builder, style, rect, clip_chain_id),
+            WidgetKind::Select => {
+                self.paint_shade(builder, style, rect, clip_chain_id);
+                self.paint_select_arrow(builder, style, rect);
+            },
+        }
+    }
+
+    fn paint_checkbox(
+        &self,
+        builder: &mut DisplayListBuilder,
+        style: &ComputedValues,
+        rect: LayoutRect,
+    ) {
+        let (fill, border, mark) = self.toggle_colors(Palette::for_style(builder, style));
+        push_rounded_rect(builder, style, rect, CORNER_RADIUS, border);
+        push_rounded_rect(
+            builder,
+            style,
+            rect.inflate(-1.0, -1.0),
+            CORNER_RADIUS - 1.0,
+            fill,
+        );
+
+        let size = rect.size();
+        let stroke = (size.width.min(size.height) / 6.5).max(1.0);
+        if self.is(ElementState::INDETERMINATE) {
+            let bar = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(
+                    rect.min.x + size.width * 0.25,
+                    rect.center().y - stroke / 2.0,
+                ),
+                LayoutSize::new(size.width * 0.5, stroke),
+            );
+            push_rect(builder, style, bar, mark);
+        } else if self.is(ElementState::CHECKED) {
+            // The check mark, as a polyline in the unit square of the checkbox.
+            let point = |x: f32, y: f32| {
+                LayoutPoint::new(rect.min.x + x * size.width, rect.min.y + y * size.height)
+            };
+            let check_mark = [point(0.22, 0.5), point(0.42, 0.7), point(0.78, 0.3)];
+            for segment in check_mark.windows(2) {
+                stroke_line(builder, style, segment[0], segment[1], stroke, mark);
+            }
+        }
+    }
+
+    fn paint_radio(
+        &self,
+        builder: &mut DisplayListBuilder,
+        style: &ComputedValues,
+        rect: LayoutRect,
+    ) {
+        // Radio buttons are round even when their content box is not square.
+        let diameter = rect.width().min(rect.height());
+        let rect = LayoutRect::from_origin_and_size(
+            rect.center() - LayoutSize::new(diameter, diameter).to_vector() / 2.0,
+            LayoutSize::new(diameter, diameter),
+        );
+        let (fill, border, mark) = self.toggle_colors(Palette::for_style(builder, style));
+        let circle = |builder: &mut DisplayListBuilder, inset: f32, color| {
+            let circle = rect.inflate(-inset, -inset);
+            push_rounded_rect(builder, style, circle, circle.width() / 2.0, color);
+        };
+        circle(builder, 0.0, border);
+        if self.is(ElementState::CHECKED) {
+            let gap = (diameter / 6.5).max(1.0);
+            circle(builder, gap, mark);
+            circle(builder, gap * 2.0, fill);
+        } else {
+            circle(builder, 1.0, fill);
+        }
+    }
+
+    /// Shade buttons and selects while they are hovered, pressed or disabled, over their
+    /// own background.
+    fn paint_shade(
+        &self,
+        builder: &mut DisplayListBuilder,
+        style: &ComputedValues,
+        rect: LayoutRect,
+        clip_chain_id: Option<ClipChainId>,
+    ) {
+        let palette = Palette::for_style(builder, style);
+        let shade = if self.is(ElementState::DISABLED) {
+            palette.disabled_shade
+        } else if self.is(ElementState::ACTIVE) {
+            palette.active_shade
+        } else if self.is(ElementState::HOVER) {
+            palette.hovered_shade
+        } else {
+            return;
+        };
+        if shade.a == 0.0 {
+            return;
+        }
+        let mut common = builder.common_properties(rect, style);
+        if let Some(clip_chain_id) = clip_chain_id {
+            common.clip_chain_id = clip_chain_id;
+        }
+        builder.wr().push_rect(&common, rect, shade);
+    }
+
+    /// Draw the downward arrow of selects at the end of their padding box, as a triangle of
+    /// rows that get narrower towards its tip.
+    fn paint_select_arrow(
+        &self,
+        builder: &mut DisplayListBuilder,
+        style: &ComputedValues,
+        rect: LayoutRect,
+    ) {
+        let color = if self.is(ElementState::DISABLED) {
+            Palette::for_style(builder, style).disabled
+        } else if builder.context.forced_colors {
+            rgba(forced_colors::CANVAS_TEXT)
+        } else {
+            rgba(style.get_inherited_text().clone_color())
+        };
+        let rows = (SELECT_ARROW_WIDTH / 2.0).ceil();
+        let center_x = if style.writing_mode.is_bidi_ltr() {
+            rect.max.x - SELECT_ARROW_INSET - SELECT_ARROW_WIDTH / 2.0
+        } else {
+            rect.min.x + SELECT_ARROW_INSET + SELECT_ARROW_WIDTH / 2.0
+        };
+        let top = rect.center().y - rows / 2.0;
+        for row in 0..rows as usize {
+            let width = SELECT_ARROW_WIDTH - 2.0 * row as f32;
+            let row = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(center_x - width / 2.0, top + row as f32),
+                LayoutSize::new(width, 1.0),
+            );
+            push_rect(builder, style, row, color);
+        }
+    }
+}
+
+/// The color of `outline-style: auto` focus rings, which is the accent color of the theme
+/// unless the style gives one. Forced colors mode paints them in `CanvasText`.
+pub(crate) fn focus_ring_color(
+    builder: &DisplayListBuilder,
+    style: &ComputedValues,
+    outline_color: &Color,
+) -> wr::ColorF {
+    if builder.context.forced_colors || outline_color.is_currentcolor() {
+        Palette::for_style(builder, style).accent
+    } else {
+        rgba(style.resolve_color(outline_color))
+    }
+}
+
+/// The radii of the corners of `outline-style: auto` focus rings that are drawn around
+/// `outline_rect`, when the box they surround has square corners.
+pub(crate) fn focus_ring_radius(
+    widget: Option<Widget>,
+    outline_rect: &LayoutRect,
+) -> wr::BorderRadius {
+    match widget.map(|widget| widget.kind) {
+        Some(WidgetKind::Radio) => {
+            wr::BorderRadius::uniform(outline_rect.width().min(outline_rect.height()) / 2.0)
+        },
+        _ => wr::BorderRadius::uniform(CORNER_RADIUS),
+    }
+}
+
+fn push_rect(
+    builder: &mut DisplayListBuilder,
+    style: &ComputedValues,
+    rect: LayoutRect,
+    color: wr::ColorF,
+) {
+    let common = builder.common_properties(rect, style);
+    builder.wr().push_rect(&common, rect, color);
+}
+
+fn push_rounded_rect(
+    builder: &mut DisplayListBuilder,
+    style: &ComputedValues,
+    rect: LayoutRect,
+    radius: f32,
+    color: wr::ColorF,
+) {
+    let mut common = builder.common_properties(rect, style);
+    let radii = wr::BorderRadius::uniform(radius.max(0.0));
+    if let Some(clip_chain_id) = create_clip_chain(radii, rect, builder, false) {
+        common.clip_chain_id = clip_chain_id;
+    }
+    builder.wr().push_rect(&common, rect, color);
+}
+
+/// Stroke the line between two points with squares of the given size, which WebRender can
+/// draw without a transform. The steps are small enough that the line looks straight.
+fn stroke_line(
+    builder: &mut DisplayListBuilder,
+    style: &ComputedValues,
+    from: LayoutPoint,
+    to: LayoutPoint,
+    stroke: f32,
+    color: wr::ColorF,
+) {
+    let steps = ((to - from).length() * 2.0).ceil().max(1.0) as usize;
+    for step in 0..=steps {
+        let center = from.lerp(to, step as f32 / steps as f32);
+        let square = LayoutRect::from_origin_and_size(
+            center - LayoutSize::new(stroke, stroke).to_vector() / 2.0,
+            LayoutSize::new(stroke, stroke),
+        );
+        push_rect(builder, style, square, color);
+    }
+}
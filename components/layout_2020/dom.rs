@@ -16,11 +16,16 @@ use script_layout_interface::{
     HTMLCanvasDataSource, LayoutElementType, LayoutNodeType as ScriptLayoutNodeType,
 };
 use servo_arc::Arc as ServoArc;
+use servo_url::ServoUrl;
+use style::dom::{TElement, TNode};
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 
+use crate::PropagatedBoxTreeData;
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
+use crate::display_list::{Widget, WidgetKind};
+use crate::dom_traversal::iter_child_nodes;
 use crate::flexbox::FlexLevelBox;
 use crate::flow::BlockLevelBox;
 use crate::flow::inline::InlineItem;
@@ -34,6 +39,11 @@ pub struct InnerDOMLayoutData {
     pub(super) self_box: ArcRefCell<Option<LayoutBox>>,
     pub(super) pseudo_before_box: ArcRefCell<Option<LayoutBox>>,
     pub(super) pseudo_after_box: ArcRefCell<Option<LayoutBox>>,
+    /// Whether the last restyle of this node damaged it, in which case its boxes can't be
+    /// reused when it's moved.
+    pub(super) style_changed: bool,
+    /// The data propagated from the ancestors of this node when its block-level box was built.
+    pub(super) propagated_data: Option<PropagatedBoxTreeData>,
 }
 
 /// A box that is stored in one of the `DOMLayoutData` slots.
@@ -101,8 +111,14 @@ pub(crate) trait NodeExt<'dom>: 'dom + LayoutNode<'dom> {
     fn as_image(self) -> Option<(Option<Arc<Image>>, PhysicalSize<f64>)>;
     fn as_canvas(self) -> Option<(CanvasInfo, PhysicalSize<f64>)>;
     fn as_iframe(self) -> Option<(PipelineId, BrowsingContextId)>;
+    fn as_portal(self) -> Option<PipelineId>;
     fn as_video(self) -> Option<(Option<webrender_api::ImageKey>, Option<PhysicalSize<f64>>)>;
+    /// Returns the URL of the serialized subtree of an `<svg>` element, if it has been
+    /// serialized, and the size of the element.
+    fn as_svg(self) -> Option<(Option<ServoUrl>, PhysicalSize<f64>)>;
     fn as_typeless_object_with_data_attribute(self) -> Option<String>;
+    /// Returns the form control that the theme paints for this node, if it is one.
+    fn as_widget(self) -> Option<Widget>;
     fn style(self, context: &LayoutContext) -> ServoArc<ComputedValues>;
 
     fn layout_data_mut(self) -> AtomicRefMut<'dom, InnerDOMLayoutData>;
@@ -113,6 +129,14 @@ pub(crate) trait NodeExt<'dom>: 'dom + LayoutNode<'dom> {
 
     /// Remove boxes for the element itself, and its `:before` and `:after` if any.
     fn unset_all_boxes(self);
+
+    /// Returns the block-level box of this element if it was moved and can be placed in its
+    /// new position as is: the box was built with the same propagated data, and neither the
+    /// element nor its descendants were restyled in a way that changes their boxes.
+    fn reusable_block_level_box(
+        self,
+        propagated_data: PropagatedBoxTreeData,
+    ) -> Option<ArcRefCell<BlockLevelBox>>;
 }
 
 impl<'dom, LayoutNodeType> NodeExt<'dom> for LayoutNodeType
@@ -150,6 +174,15 @@ where
         ))
     }
 
+    fn as_svg(self) -> Option<(Option<ServoUrl>, PhysicalSize<f64>)> {
+        let node = self.to_threadsafe();
+        let data = node.svg_data()?;
+        Some((
+            data.source,
+            PhysicalSize::new(data.width.into(), data.height.into()),
+        ))
+    }
+
     fn as_canvas(self) -> Option<(CanvasInfo, PhysicalSize<f64>)> {
         let node = self.to_threadsafe();
         let canvas_data = node.canvas_data()?;
@@ -175,6 +208,10 @@ where
         }
     }
 
+    fn as_portal(self) -> Option<PipelineId> {
+        self.to_threadsafe().portal_pipeline_id()
+    }
+
     fn as_typeless_object_with_data_attribute(self) -> Option<String> {
         if self.type_id() != ScriptLayoutNodeType::Element(LayoutElementType::HTMLObjectElement) {
             return None;
@@ -192,6 +229,46 @@ where
             .map(|string| string.to_owned())
     }
 
+    fn as_widget(self) -> Option<Widget> {
+        let element = self.to_threadsafe().as_html_element()?;
+        let kind = match element.get_local_name() {
+            &local_name!("button") => WidgetKind::Button,
+            // Selects that show more than one option are list boxes, not drop-down menus.
+            &local_name!("select") => {
+                let size = element
+                    .get_attr(&ns!(), &local_name!("size"))
+                    .and_then(|size| size.trim().parse::<u32>().ok())
+                    .unwrap_or(1);
+                if element.get_attr(&ns!(), &local_name!("multiple")).is_some() || size > 1 {
+                    return None;
+                }
+                WidgetKind::Select
+            },
+            &local_name!("input") => {
+                let input_type = element
+                    .get_attr(&ns!(), &local_name!("type"))
+                    .unwrap_or_default();
+                if input_type.eq_ignore_ascii_case("checkbox") {
+                    WidgetKind::Checkbox
+                } else if input_type.eq_ignore_ascii_case("radio") {
+                    WidgetKind::Radio
+                } else if ["button", "submit", "reset"]
+                    .iter()
+                    .any(|button_type| input_type.eq_ignore_ascii_case(button_type))
+                {
+                    WidgetKind::Button
+                } else {
+                    return None;
+                }
+            },
+            _ => return None,
+        };
+        Some(Widget {
+            kind,
+            state: self.as_element()?.state(),
+        })
+    }
+
     fn style(self, context: &LayoutContext) -> ServoArc<ComputedValues> {
         self.to_threadsafe().style(context.shared_context())
     }
@@ -251,4 +328,57 @@ where
         // Stylo already takes care of removing all layout data
         // for DOM descendants of elements with `display: none`.
     }
+
+    fn reusable_block_level_box(
+        self,
+        propagated_data: PropagatedBoxTreeData,
+    ) -> Option<ArcRefCell<BlockLevelBox>> {
+        let data = self.layout_data()?;
+        if data.propagated_data != Some(propagated_data) {
+            return None;
+        }
+        let block_level_box = match &*data.self_box.borrow() {
+            Some(LayoutBox::BlockLevel(block_level_box)) => block_level_box.clone(),
+            _ => return None,
+        };
+        if !matches!(
+            &*block_level_box.borrow(),
+            BlockLevelBox::SameFormattingContextBlock { .. } | BlockLevelBox::Independent(_)
+        ) {
+            return None;
+        }
+        boxes_are_reusable(self).then_some(block_level_box)
+    }
+}
+
+/// Whether the boxes of this node and its descendants stay valid when it is moved. Boxes that
+/// depend on other parts of the tree, like list markers and generated content, which may use
+/// counters and quotes, are rebuilt.
+fn boxes_are_reusable<'dom>(node: impl NodeExt<'dom>) -> bool {
+    if !node.is_element() {
+        return true;
+    }
+    let Some(data) = node.layout_data() else {
+        return false;
+    };
+    if data.style_changed ||
+        data.pseudo_before_box.borrow().is_some() ||
+        data.pseudo_after_box.borrow().is_some()
+    {
+        return false;
+    }
+    let Some(display) = node.style_data().and_then(|style_data| {
+        let element_data = style_data.element_data.borrow();
+        Some(element_data.styles.get_primary()?.get_box().display)
+    }) else {
+        return false;
+    };
+    // Elements with `display: none` have no boxes, nor do their descendants.
+    if display.is_none() {
+        return true;
+    }
+    if display.is_list_item() || node.as_widget().is_some() {
+        return false;
+    }
+    iter_child_nodes(node).all(boxes_are_reusable)
 }
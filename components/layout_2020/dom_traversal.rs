@@ -15,6 +15,7 @@ use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 use style::values::generics::counters::{Content, ContentItem};
 use style::values::specified::Quotes;
+use stylo_dom::ElementState;
 
 use crate::context::LayoutContext;
 use crate::dom::{BoxSlot, LayoutBox, NodeExt};
@@ -125,9 +126,25 @@ where
             }
         };
 
+        // <https://drafts.csswg.org/css-position-4/#top-layer>
+        if pseudo.is_none() &&
+            node.as_element().is_some_and(|element| {
+                element
+                    .state()
+                    .intersects(ElementState::FULLSCREEN | ElementState::MODAL)
+            })
+        {
+            flags.insert(FragmentFlags::IS_IN_TOP_LAYER);
+        }
+
         Self {
             tag: Some(Tag::new_pseudo(threadsafe_node.opaque(), pseudo)),
             flags,
+            widget: if pseudo.is_none() {
+                node.as_widget()
+            } else {
+                None
+            },
         }
     }
 }
@@ -173,6 +190,17 @@ where
         contents: Contents,
         box_slot: BoxSlot<'dom>,
     );
+
+    /// Called for an element that was moved, before [`Self::handle_element`]. Returns true if
+    /// the handler placed the boxes that the element had before it was moved, in which case
+    /// they are not built again.
+    fn reuse_boxes_of_moved_element(
+        &mut self,
+        _info: &NodeAndStyleInfo<Node>,
+        _display: DisplayGeneratingBox,
+    ) -> bool {
+        false
+    }
 }
 
 fn traverse_children_of<'dom, Node>(
@@ -184,10 +212,7 @@ fn traverse_children_of<'dom, Node>(
 {
     traverse_eager_pseudo_element(PseudoElement::Before, parent_element, context, handler);
 
-    let is_text_input_element = matches!(
-        parent_element.type_id(),
-        LayoutNodeType::Element(LayoutElementType::HTMLInputElement)
-    );
+    let is_text_input_element = is_text_input_element(parent_element);
 
     let is_textarea_element = matches!(
         parent_element.type_id(),
@@ -224,6 +249,21 @@ fn traverse_children_of<'dom, Node>(
     traverse_eager_pseudo_element(PseudoElement::After, parent_element, context, handler);
 }
 
+/// Whether the element is an `<input>` that shows its value as text. Inputs that have a UA
+/// shadow tree, like range inputs, show the contents of the shadow tree instead.
+fn is_text_input_element<'dom, Node>(element: Node) -> bool
+where
+    Node: NodeExt<'dom>,
+{
+    matches!(
+        element.type_id(),
+        LayoutNodeType::Element(LayoutElementType::HTMLInputElement)
+    ) && element
+        .as_element()
+        .is_none_or(|element| element.shadow_root().is_none())
+}
+
+#[allow(unsafe_code)]
 fn traverse_element<'dom, Node>(
     element: Node,
     context: &LayoutContext,
@@ -231,6 +271,9 @@ fn traverse_element<'dom, Node>(
 ) where
     Node: NodeExt<'dom>,
 {
+    // Safety: Each element is only visited once during box tree construction, and nothing
+    // else reads its flags meanwhile.
+    let was_moved = unsafe { element.take_was_moved() };
     let replaced = ReplacedContents::for_element(element, context);
     let style = element.style(context);
     match Display::from(style.get_box().display) {
@@ -248,19 +291,22 @@ fn traverse_element<'dom, Node>(
         Display::GeneratingBox(display) => {
             let contents = if let Some(replaced) = replaced {
                 Contents::Replaced(replaced)
-            } else if matches!(
-                element.type_id(),
-                LayoutNodeType::Element(
-                    LayoutElementType::HTMLInputElement | LayoutElementType::HTMLTextAreaElement
+            } else if is_text_input_element(element) ||
+                matches!(
+                    element.type_id(),
+                    LayoutNodeType::Element(LayoutElementType::HTMLTextAreaElement)
                 )
-            ) {
+            {
                 NonReplacedContents::OfTextControl.into()
             } else {
                 NonReplacedContents::OfElement.into()
             };
             let display = display.used_value_for_contents(&contents);
-            let box_slot = element.element_box_slot();
             let info = NodeAndStyleInfo::new(element, style);
+            if was_moved && handler.reuse_boxes_of_moved_element(&info, display) {
+                return;
+            }
+            let box_slot = element.element_box_slot();
             handler.handle_element(&info, display, contents, box_slot);
         },
     }
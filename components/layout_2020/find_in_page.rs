@@ -0,0 +1,187 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Searching the rendered text of a document for find-in-page, and the geometry of the
+//! matches that is used to highlight them in the display list.
+//!
+//! Text is searched per inline formatting context, so a match can span several text
+//! fragments and lines, as well as the boundaries of inline elements and shadow trees,
+//! but not the boundary between two blocks.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use app_units::Au;
+use euclid::default::Rect;
+use fxhash::FxHashMap;
+use script_layout_interface::{FindInPageMatches, FindInPageQuery};
+
+use crate::FragmentTree;
+use crate::cell::ArcRefCell;
+use crate::fragment_tree::{Fragment, TextFragment};
+use crate::geom::PhysicalRect;
+
+/// A match of a find-in-page query in the text of an inline formatting context.
+struct TextMatch {
+    range: Range<usize>,
+    active: bool,
+}
+
+/// The matches of a [`FindInPageQuery`] in a [`FragmentTree`].
+pub struct FindInPageHighlights {
+    /// The matches in the text of each inline formatting context, keyed by the address of
+    /// that text, which is shared by all of the [`TextFragment`]s laid out from it.
+    matches: FxHashMap<usize, Vec<TextMatch>>,
+    number_of_matches: usize,
+    active_match_index: Option<usize>,
+    active_match_rect: Option<Rect<Au>>,
+}
+
+fn text_key(text: &Arc<str>) -> usize {
+    Arc::as_ptr(text) as *const u8 as usize
+}
+
+impl FindInPageHighlights {
+    pub fn new(fragment_tree: &FragmentTree, query: &FindInPageQuery) -> Self {
+        // Gather the text fragments of the tree in order, grouped by the inline formatting
+        // context that they belong to, along with their position in the tree.
+        let mut texts: Vec<Arc<str>> = Vec::new();
+        let mut fragments: FxHashMap<usize, Vec<(ArcRefCell<TextFragment>, PhysicalRect<Au>)>> =
+            FxHashMap::default();
+        fragment_tree.find(|fragment, _, containing_block| {
+            if let Fragment::Text(text_fragment) = fragment {
+                let text = text_fragment.borrow().formatting_context_text.clone();
+                let rect = text_fragment
+                    .borrow()
+                    .rect
+                    .translate(containing_block.origin.to_vector());
+                fragments
+                    .entry(text_key(&text))
+                    .or_insert_with(|| {
+                        texts.push(text);
+                        Vec::new()
+                    })
+                    .push((text_fragment.clone(), rect));
+            }
+            None::<()>
+        });
+
+        // Only text that was actually laid out can match, which excludes collapsed white
+        // space and text that was trimmed at the start or end of a line.
+        let mut all_matches: Vec<(usize, Range<usize>)> = Vec::new();
+        for text in &texts {
+            let key = text_key(text);
+            let text_fragments = &fragments[&key];
+            all_matches.extend(
+                find_occurrences(text, &query.text, query.case_sensitive)
+                    .into_iter()
+                    .filter(|range| {
+                        text_fragments.iter().any(|(fragment, _)| {
+                            let fragment_range = &fragment.borrow().text_range;
+                            range.start < fragment_range.end && fragment_range.start < range.end
+                        })
+                    })
+                    .map(|range| (key, range)),
+            );
+        }
+
+        let number_of_matches = all_matches.len();
+        let active_match_index =
+            (number_of_matches > 0).then(|| query.active_match_index % number_of_matches);
+
+        let mut active_match_rect: Option<Rect<Au>> = None;
+        let mut matches: FxHashMap<usize, Vec<TextMatch>> = FxHashMap::default();
+        for (index, (key, range)) in all_matches.into_iter().enumerate() {
+            let active = Some(index) == active_match_index;
+            if active {
+                for (fragment, rect) in &fragments[&key] {
+                    let Some(highlight) = fragment.borrow().rect_for_text_range(rect, &range)
+                    else {
+                        continue;
+                    };
+                    let highlight = highlight.to_untyped();
+                    active_match_rect = Some(match active_match_rect {
+                        Some(active_match_rect) => active_match_rect.union(&highlight),
+                        None => highlight,
+                    });
+                }
+            }
+            matches
+                .entry(key)
+                .or_default()
+                .push(TextMatch { range, active });
+        }
+
+        Self {
+            matches,
+            number_of_matches,
+            active_match_index,
+            active_match_rect,
+        }
+    }
+
+    pub fn to_matches(&self) -> FindInPageMatches {
+        FindInPageMatches {
+            number_of_matches: self.number_of_matches,
+            active_match_index: self.active_match_index,
+            active_match_rect: self.active_match_rect,
+        }
+    }
+
+    /// The highlights to paint for the matches in `fragment`, which is positioned at `rect`,
+    /// and whether each of them is the active match.
+    pub(crate) fn highlights_for_fragment(
+        &self,
+        fragment: &TextFragment,
+        rect: &PhysicalRect<Au>,
+    ) -> Vec<(PhysicalRect<Au>, bool)> {
+        let Some(matches) = self
+            .matches
+            .get(&text_key(&fragment.formatting_context_text))
+        else {
+            return Vec::new();
+        };
+        matches
+            .iter()
+            .filter_map(|text_match| {
+                let highlight = fragment.rect_for_text_range(rect, &text_match.range)?;
+                Some((highlight, text_match.active))
+            })
+            .collect()
+    }
+}
+
+/// Find the non-overlapping occurrences of `query` in `text`, returning their byte ranges.
+fn find_occurrences(text: &str, query: &str, case_sensitive: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    if case_sensitive {
+        return text
+            .match_indices(query)
+            .map(|(start, found)| start..start + found.len())
+            .collect();
+    }
+
+    // Case-insensitive matching searches lowercased copies of the text and the query,
+    // mapping every byte of the lowercased text back to the character that it came from.
+    let mut folded_text = String::with_capacity(text.len());
+    let mut original_offsets = Vec::with_capacity(text.len());
+    for (offset, character) in text.char_indices() {
+        folded_text.extend(character.to_lowercase());
+        original_offsets.resize(folded_text.len(), offset);
+    }
+    folded_text
+        .match_indices(&query.to_lowercase())
+        .map(|(start, found)| {
+            let last_character = original_offsets[start + found.len() - 1];
+            let end = last_character +
+                text[last_character..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+            original_offsets[start]..end
+        })
+        .collect()
+}
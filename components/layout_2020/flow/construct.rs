@@ -89,6 +89,10 @@ enum BlockLevelCreator {
     AnonymousTable {
         table_block: ArcRefCell<BlockLevelBox>,
     },
+    /// The box of a moved element, which is placed as is in its new position.
+    Reused {
+        block_level_box: ArcRefCell<BlockLevelBox>,
+    },
 }
 
 /// A block container that may still have to be constructed.
@@ -358,6 +362,38 @@ where
         }
     }
 
+    fn reuse_boxes_of_moved_element(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        display: DisplayGeneratingBox,
+    ) -> bool {
+        // Only in-flow block-level boxes are reused, as they don't depend on the inline
+        // formatting context or the containing block chain that they end up in.
+        let DisplayGeneratingBox::OutsideInside {
+            outside: DisplayOutside::Block,
+            ..
+        } = display
+        else {
+            return false;
+        };
+        let box_style = info.style.get_box();
+        if box_style.position.is_absolutely_positioned() || box_style.float.is_floating() {
+            return false;
+        }
+        let Some(block_level_box) = info.node.reusable_block_level_box(self.propagated_data) else {
+            return false;
+        };
+
+        self.finish_anonymous_table_if_needed();
+        let box_slot = info.node.element_box_slot();
+        self.push_in_flow_block_level_job(
+            info,
+            box_slot,
+            BlockLevelCreator::Reused { block_level_box },
+        );
+        true
+    }
+
     fn handle_text(&mut self, info: &NodeAndStyleInfo<Node>, text: Cow<'dom, str>) {
         if text.is_empty() {
             return;
@@ -474,24 +510,6 @@ where
         contents: Contents,
         box_slot: BoxSlot<'dom>,
     ) {
-        // We just found a block level element, all ongoing inline level boxes
-        // need to be split around it.
-        //
-        // After calling `split_around_block_and_finish`,
-        // `self.inline_formatting_context_builder` is set up with the state
-        // that we want to have after we push the block below.
-        if let Some(inline_formatting_context) = self
-            .inline_formatting_context_builder
-            .split_around_block_and_finish(
-                self.context,
-                self.propagated_data,
-                !self.have_already_seen_first_line_for_text_indent,
-                self.info.style.writing_mode.to_bidi_level(),
-            )
-        {
-            self.push_block_level_job_for_inline_formatting_context(inline_formatting_context);
-        }
-
         let propagated_data = self.propagated_data;
         let kind = match contents {
             Contents::NonReplaced(contents) => match display_inside {
@@ -523,11 +541,41 @@ where
                 }
             },
         };
+        if info.pseudo_element_type.is_none() {
+            info.node.layout_data_mut().propagated_data = Some(propagated_data);
+        }
+        self.push_in_flow_block_level_job(info, box_slot, kind);
+    }
+
+    fn push_in_flow_block_level_job(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        box_slot: BoxSlot<'dom>,
+        kind: BlockLevelCreator,
+    ) {
+        // We just found a block level element, all ongoing inline level boxes
+        // need to be split around it.
+        //
+        // After calling `split_around_block_and_finish`,
+        // `self.inline_formatting_context_builder` is set up with the state
+        // that we want to have after we push the block below.
+        if let Some(inline_formatting_context) = self
+            .inline_formatting_context_builder
+            .split_around_block_and_finish(
+                self.context,
+                self.propagated_data,
+                !self.have_already_seen_first_line_for_text_indent,
+                self.info.style.writing_mode.to_bidi_level(),
+            )
+        {
+            self.push_block_level_job_for_inline_formatting_context(inline_formatting_context);
+        }
+
         self.block_level_boxes.push(BlockLevelJob {
             info: info.clone(),
             box_slot,
             kind,
-            propagated_data,
+            propagated_data: self.propagated_data,
         });
 
         // Any block also counts as the first line for the purposes of text indent. Even if
@@ -712,6 +760,7 @@ where
                 }))
             },
             BlockLevelCreator::AnonymousTable { table_block } => table_block,
+            BlockLevelCreator::Reused { block_level_box } => block_level_box,
         };
         self.box_slot
             .set(LayoutBox::BlockLevel(block_level_box.clone()));
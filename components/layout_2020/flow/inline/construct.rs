@@ -4,9 +4,13 @@
 
 use std::borrow::Cow;
 use std::char::{ToLowercase, ToUppercase};
+use std::ops::Range;
 
 use icu_segmenter::WordSegmenter;
+use script_layout_interface::LayoutNodeType;
+use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
 use servo_arc::Arc;
+use servo_config::pref;
 use style::computed_values::white_space_collapse::T as WhiteSpaceCollapse;
 use style::values::specified::text::TextTransformCase;
 use unicode_bidi::Level;
@@ -14,6 +18,7 @@ use unicode_bidi::Level;
 use super::text_run::TextRun;
 use super::{InlineBox, InlineBoxIdentifier, InlineBoxes, InlineFormattingContext, InlineItem};
 use crate::PropagatedBoxTreeData;
+use crate::caret_browsing::TextSource;
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::dom::NodeExt;
@@ -253,18 +258,41 @@ impl InlineFormattingContextBuilder {
 
         let new_range = self.current_text_offset..self.current_text_offset + new_text.len();
         self.current_text_offset = new_range.end;
+        let (spelling_errors, composition, selection) =
+            text_control_value_ranges(info, &text, &new_text, new_range.start);
+        let node = info.node.to_threadsafe();
+        let text_source = (pref!(accessibility_caret_browsing_enabled) &&
+            node.type_id() == Some(LayoutNodeType::Text))
+        .then(|| {
+            std::sync::Arc::new(TextSource::new(
+                node.opaque(),
+                &text,
+                &new_text,
+                new_range.start,
+            ))
+        });
         self.text_segments.push(new_text);
 
         if let Some(inline_item) = self.inline_items.last() {
             if let InlineItem::TextRun(text_run) = &mut *inline_item.borrow_mut() {
-                text_run.borrow_mut().text_range.end = new_range.end;
+                let mut text_run = text_run.borrow_mut();
+                text_run.text_range.end = new_range.end;
+                text_run.spelling_errors.extend(spelling_errors);
+                text_run.composition = text_run.composition.take().or(composition);
+                text_run.selection = text_run.selection.take().or(selection);
+                text_run.text_sources.extend(text_source);
                 return;
             }
         }
 
+        let mut text_run = TextRun::new(info.into(), info.style.clone(), new_range);
+        text_run.spelling_errors = spelling_errors;
+        text_run.composition = composition;
+        text_run.selection = selection;
+        text_run.text_sources.extend(text_source);
         self.inline_items
             .push(ArcRefCell::new(InlineItem::TextRun(ArcRefCell::new(
-                TextRun::new(info.into(), info.style.clone(), new_range),
+                text_run,
             ))));
     }
 
@@ -594,6 +622,46 @@ where
     }
 }
 
+/// The misspelled words, the IME composition string and the selection in `text`, if it is
+/// the value of a text control, as ranges of bytes in the text content of the inline
+/// formatting context, where `text` was added as `processed_text` at `offset`. The selection
+/// is empty when the text control only has a caret.
+///
+/// All of them are found by script in the value of the text control, so they can only be
+/// mapped onto text that white space processing and `text-transform` left unchanged.
+fn text_control_value_ranges<'dom, Node: NodeExt<'dom>>(
+    info: &NodeAndStyleInfo<Node>,
+    text: &str,
+    processed_text: &str,
+    offset: usize,
+) -> (
+    Vec<Range<usize>>,
+    Option<Range<usize>>,
+    Option<Range<usize>>,
+) {
+    let node = info.node.to_threadsafe();
+    let spelling_errors = node.spelling_errors();
+    let composition = node.composition();
+    let selection = node
+        .selection()
+        .map(|selection| selection.begin().to_usize()..selection.end().to_usize());
+    if (spelling_errors.is_empty() && composition.is_none() && selection.is_none()) ||
+        text != processed_text ||
+        text != node.node_text_content()
+    {
+        return (Vec::new(), None, None);
+    }
+    let to_text_content_range = |range: Range<usize>| range.start + offset..range.end + offset;
+    (
+        spelling_errors
+            .into_iter()
+            .map(to_text_content_range)
+            .collect(),
+        composition.map(to_text_content_range),
+        selection.map(to_text_content_range),
+    )
+}
+
 /// Given a string and whether the start of the string represents a word boundary, create a copy of
 /// the string with letters after word boundaries capitalized.
 fn capitalize_string(string: &str, allow_word_at_start: bool) -> String {
@@ -235,6 +235,7 @@ impl InlineBoxContainerState {
                 Some(parent_container),
                 parent_container.text_decoration_line,
                 font_metrics,
+                layout_context.text_zoom,
             ),
             identifier: inline_box.identifier,
             base_fragment_info: inline_box.base_fragment_info,
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::ops::Range;
+
 use app_units::Au;
 use bitflags::bitflags;
 use fonts::{FontMetrics, GlyphStore};
@@ -20,7 +22,9 @@ use unicode_bidi::{BidiInfo, Level};
 use webrender_api::FontInstanceKey;
 
 use super::inline_box::{InlineBoxContainerState, InlineBoxIdentifier, InlineBoxTreePathToken};
+use super::text_run::used_font_size;
 use super::{InlineFormattingContextLayout, LineBlockSizes};
+use crate::caret_browsing::TextSource;
 use crate::cell::ArcRefCell;
 use crate::fragment_tree::{
     BaseFragmentInfo, BoxFragment, CollapsedBlockMargins, Fragment, TextFragment,
@@ -506,17 +510,18 @@ impl LineItemLayout<'_, '_> {
     ) -> Au {
         let font_metrics = &inline_box_state.base.font_metrics;
         let style = &inline_box_state.base.style;
+        let text_zoom = inline_box_state.base.text_zoom;
         let line_gap = font_metrics.line_gap;
 
         // The baseline offset that we have in `Self::baseline_offset` is relative to the line
         // baseline, so we need to make it relative to the line block start.
         match inline_box_state.base.style.clone_vertical_align() {
             GenericVerticalAlign::Keyword(VerticalAlignKeyword::Top) => {
-                let line_height: Au = line_height(style, font_metrics);
+                let line_height: Au = line_height(style, font_metrics, text_zoom);
                 (line_height - line_gap).scale_by(0.5)
             },
             GenericVerticalAlign::Keyword(VerticalAlignKeyword::Bottom) => {
-                let line_height: Au = line_height(style, font_metrics);
+                let line_height: Au = line_height(style, font_metrics, text_zoom);
                 let half_leading = (line_height - line_gap).scale_by(0.5);
                 self.line_metrics.block_size - line_height + half_leading
             },
@@ -566,6 +571,26 @@ impl LineItemLayout<'_, '_> {
         };
 
         self.current_state.inline_advance += inline_advance;
+        let text_range = text_item.text_range;
+        let spelling_errors = text_item
+            .spelling_errors
+            .into_iter()
+            .filter(|error| error.start < text_range.end && text_range.start < error.end)
+            .collect();
+        let composition = text_item.composition.filter(|composition| {
+            composition.start <= text_range.end && text_range.start <= composition.end
+        });
+        let selection = text_item.selection.filter(|selection| {
+            selection.start <= text_range.end && text_range.start <= selection.end
+        });
+        let text_sources = text_item
+            .text_sources
+            .into_iter()
+            .filter(|source| {
+                let source_range = source.text_content_range();
+                source_range.start < text_range.end && text_range.start < source_range.end
+            })
+            .collect();
         self.current_state.fragments.push((
             Fragment::Text(ArcRefCell::new(TextFragment {
                 base: text_item.base_fragment_info.into(),
@@ -574,6 +599,12 @@ impl LineItemLayout<'_, '_> {
                 font_metrics: text_item.font_metrics,
                 font_key: text_item.font_key,
                 glyphs: text_item.text,
+                formatting_context_text: self.layout.ifc.text_content.clone(),
+                text_range,
+                spelling_errors,
+                composition,
+                selection,
+                text_sources,
                 text_decoration_line: text_item.text_decoration_line,
                 justification_adjustment: self.justification_adjustment,
             })),
@@ -763,6 +794,21 @@ pub(super) struct TextRunLineItem {
     pub base_fragment_info: BaseFragmentInfo,
     pub parent_style: Arc<ComputedValues>,
     pub text: Vec<std::sync::Arc<GlyphStore>>,
+    /// The range of bytes in the parent [`super::InlineFormattingContext`]'s text content
+    /// that is covered by the glyphs of this [`TextRunLineItem`].
+    pub text_range: Range<usize>,
+    /// The misspelled words of the [`super::text_run::TextRun`] that this item was laid out
+    /// from, as ranges of bytes in the text content.
+    pub spelling_errors: Vec<Range<usize>>,
+    /// The IME composition string of the [`super::text_run::TextRun`] that this item was
+    /// laid out from, as a range of bytes in the text content.
+    pub composition: Option<Range<usize>>,
+    /// The selection in the text control of the [`super::text_run::TextRun`] that this item
+    /// was laid out from, as a range of bytes in the text content.
+    pub selection: Option<Range<usize>>,
+    /// The text nodes whose text the [`super::text_run::TextRun`] that this item was laid out
+    /// from displays, if caret browsing is enabled.
+    pub text_sources: Vec<std::sync::Arc<TextSource>>,
     pub font_metrics: FontMetrics,
     pub font_key: FontInstanceKey,
     pub text_decoration_line: TextDecorationLine,
@@ -787,11 +833,10 @@ impl TextRunLineItem {
             .map(|offset_from_end| self.text.len() - offset_from_end);
 
         let first_whitespace_index = index_of_last_non_whitespace.unwrap_or(0);
-        *whitespace_trimmed += self
-            .text
-            .drain(first_whitespace_index..)
-            .map(|glyph| glyph.total_advance())
-            .sum();
+        for glyph in self.text.drain(first_whitespace_index..) {
+            *whitespace_trimmed += glyph.total_advance();
+            self.text_range.end -= glyph.len().to_usize();
+        }
 
         // Only keep going if we only encountered whitespace.
         index_of_last_non_whitespace.is_none()
@@ -811,11 +856,10 @@ impl TextRunLineItem {
             .position(|glyph| !glyph.is_whitespace())
             .unwrap_or(self.text.len());
 
-        *whitespace_trimmed += self
-            .text
-            .drain(0..index_of_first_non_whitespace)
-            .map(|glyph| glyph.total_advance())
-            .sum();
+        for glyph in self.text.drain(0..index_of_first_non_whitespace) {
+            *whitespace_trimmed += glyph.total_advance();
+            self.text_range.start += glyph.len().to_usize();
+        }
 
         // Only keep going if we only encountered whitespace.
         self.text.is_empty()
@@ -874,13 +918,12 @@ pub(super) struct FloatLineItem {
     pub needs_placement: bool,
 }
 
-fn line_height(parent_style: &ComputedValues, font_metrics: &FontMetrics) -> Au {
+fn line_height(parent_style: &ComputedValues, font_metrics: &FontMetrics, text_zoom: f32) -> Au {
     let font = parent_style.get_font();
-    let font_size = font.font_size.computed_size();
     match font.line_height {
         LineHeight::Normal => font_metrics.line_gap,
-        LineHeight::Number(number) => (font_size * number.0).into(),
-        LineHeight::Length(length) => length.0.into(),
+        LineHeight::Number(number) => used_font_size(parent_style, text_zoom).scale_by(number.0),
+        LineHeight::Length(length) => Au::from(length.0).scale_by(text_zoom),
     }
 }
 
@@ -76,12 +76,15 @@ pub mod text_run;
 
 use std::cell::{OnceCell, RefCell};
 use std::mem;
+use std::ops::Range;
 use std::rc::Rc;
 
 use app_units::{Au, MAX_AU};
 use bitflags::bitflags;
 use construct::InlineFormattingContextBuilder;
 use fonts::{FontMetrics, GlyphStore};
+use icu_properties::LineBreak;
+use icu_properties::maps::line_break;
 use inline_box::{InlineBox, InlineBoxContainerState, InlineBoxIdentifier, InlineBoxes};
 use line::{
     AbsolutelyPositionedLineItem, AtomicLineItem, FloatLineItem, LineItem, LineItemLayout,
@@ -101,13 +104,9 @@ use style::values::generics::font::LineHeight;
 use style::values::specified::box_::BaselineSource;
 use style::values::specified::text::{TextAlignKeyword, TextDecorationLine};
 use style::values::specified::{TextAlignLast, TextJustify};
-use text_run::{
-    TextRun, XI_LINE_BREAKING_CLASS_GL, XI_LINE_BREAKING_CLASS_WJ, XI_LINE_BREAKING_CLASS_ZWJ,
-    add_or_get_font, get_font_for_first_font_for_style,
-};
+use text_run::{TextRun, add_or_get_font, get_font_for_first_font_for_style, used_font_size};
 use unicode_bidi::{BidiInfo, Level};
 use webrender_api::FontInstanceKey;
-use xi_unicode::linebreak_property;
 
 use super::float::{Clear, PlacementAmongFloats};
 use super::{
@@ -147,8 +146,9 @@ pub(crate) struct InlineFormattingContext {
     /// a flat array with each being given a [`InlineBoxIdentifier`].
     pub(super) inline_boxes: InlineBoxes,
 
-    /// The text content of this inline formatting context.
-    pub(super) text_content: String,
+    /// The text content of this inline formatting context. This is shared with the
+    /// [`TextFragment`]s laid out from it, so that their text can be searched.
+    pub(super) text_content: std::sync::Arc<str>,
 
     /// A store of font information for all the shaped segments in this formatting
     /// context in order to avoid duplicating this information.
@@ -551,6 +551,10 @@ pub(super) struct InlineContainerState {
 
     /// The font metrics of the non-fallback font for this container.
     font_metrics: FontMetrics,
+
+    /// The factor by which the used font size, and the line height, are multiplied for
+    /// text-only zoom.
+    text_zoom: f32,
 }
 
 pub(super) struct InlineFormattingContextLayout<'layout_data> {
@@ -1261,6 +1265,7 @@ impl InlineFormattingContextLayout<'_> {
     pub(super) fn push_glyph_store_to_unbreakable_segment(
         &mut self,
         glyph_store: std::sync::Arc<GlyphStore>,
+        text_range: Range<usize>,
         text_run: &TextRun,
         font_index: usize,
         bidi_level: Level,
@@ -1315,6 +1320,7 @@ impl InlineFormattingContextLayout<'_> {
                     line_item.can_merge(ifc_font_info.key, bidi_level) =>
             {
                 line_item.text.push(glyph_store);
+                line_item.text_range.end = text_range.end;
                 return;
             },
             _ => {},
@@ -1324,6 +1330,11 @@ impl InlineFormattingContextLayout<'_> {
             current_inline_box_identifier,
             TextRunLineItem {
                 text: vec![glyph_store],
+                text_range,
+                spelling_errors: text_run.spelling_errors.clone(),
+                composition: text_run.composition.clone(),
+                selection: text_run.selection.clone(),
+                text_sources: text_run.text_sources.clone(),
                 base_fragment_info: text_run.base_fragment_info,
                 parent_style: text_run.parent_style.clone(),
                 font_metrics,
@@ -1540,6 +1551,7 @@ impl InlineFormattingContext {
                     text_run.borrow_mut().segment_and_shape(
                         &text_content,
                         &layout_context.font_context,
+                        layout_context.text_zoom,
                         &mut new_linebreaker,
                         &mut font_metrics,
                         &bidi_info,
@@ -1550,6 +1562,7 @@ impl InlineFormattingContext {
                     if let Some(font) = get_font_for_first_font_for_style(
                         &inline_box.style,
                         &layout_context.font_context,
+                        layout_context.text_zoom,
                     ) {
                         inline_box.default_font_index = Some(add_or_get_font(
                             &font,
@@ -1568,7 +1581,7 @@ impl InlineFormattingContext {
         }
 
         InlineFormattingContext {
-            text_content,
+            text_content: text_content.into(),
             inline_items: builder.inline_items,
             inline_boxes: builder.inline_boxes,
             font_metrics,
@@ -1603,9 +1616,12 @@ impl InlineFormattingContext {
 
         // It's unfortunate that it isn't possible to get this during IFC text processing, but in
         // that situation the style of the containing block is unknown.
-        let default_font_metrics =
-            get_font_for_first_font_for_style(style, &layout_context.font_context)
-                .map(|font| font.metrics.clone());
+        let default_font_metrics = get_font_for_first_font_for_style(
+            style,
+            &layout_context.font_context,
+            layout_context.text_zoom,
+        )
+        .map(|font| font.metrics.clone());
 
         let style_text = containing_block.style.get_inherited_text();
         let mut inline_container_state_flags = InlineContainerStateFlags::empty();
@@ -1634,6 +1650,7 @@ impl InlineFormattingContext {
                 None, /* parent_container */
                 self.text_decoration_line,
                 default_font_metrics.as_ref(),
+                layout_context.text_zoom,
             ),
             inline_box_state_stack: Vec::new(),
             inline_box_states: Vec::with_capacity(self.inline_boxes.len()),
@@ -1732,6 +1749,7 @@ impl InlineContainerState {
         parent_container: Option<&InlineContainerState>,
         parent_text_decoration_line: TextDecorationLine,
         font_metrics: Option<&FontMetrics>,
+        text_zoom: f32,
     ) -> Self {
         let text_decoration_line = parent_text_decoration_line | style.clone_text_decoration_line();
         let font_metrics = font_metrics.cloned().unwrap_or_else(FontMetrics::empty);
@@ -1739,6 +1757,7 @@ impl InlineContainerState {
             &style,
             &font_metrics,
             flags.contains(InlineContainerStateFlags::IS_SINGLE_LINE_TEXT_INPUT),
+            text_zoom,
         );
 
         let mut baseline_offset = Au::zero();
@@ -1775,6 +1794,7 @@ impl InlineContainerState {
             strut_block_sizes,
             baseline_offset,
             font_metrics,
+            text_zoom,
         }
     }
 
@@ -1865,6 +1885,7 @@ impl InlineContainerState {
                 font_metrics,
                 self.flags
                     .contains(InlineContainerStateFlags::IS_SINGLE_LINE_TEXT_INPUT),
+                self.text_zoom,
             ),
         )
     }
@@ -2102,13 +2123,13 @@ fn line_height(
     parent_style: &ComputedValues,
     font_metrics: &FontMetrics,
     is_single_line_text_input: bool,
+    text_zoom: f32,
 ) -> Au {
     let font = parent_style.get_font();
-    let font_size = font.font_size.computed_size();
     let mut line_height = match font.line_height {
         LineHeight::Normal => font_metrics.line_gap,
-        LineHeight::Number(number) => (font_size * number.0).into(),
-        LineHeight::Length(length) => length.0.into(),
+        LineHeight::Number(number) => used_font_size(parent_style, text_zoom).scale_by(number.0),
+        LineHeight::Length(length) => Au::from(length.0).scale_by(text_zoom),
     };
 
     // Single line text inputs line height is clamped to the size of `normal`. See
@@ -2429,8 +2450,8 @@ fn char_prevents_soft_wrap_opportunity_when_before_or_after_atomic(character: ch
     if character == '\u{00A0}' {
         return false;
     }
-    let class = linebreak_property(character);
-    class == XI_LINE_BREAKING_CLASS_GL ||
-        class == XI_LINE_BREAKING_CLASS_WJ ||
-        class == XI_LINE_BREAKING_CLASS_ZWJ
+    matches!(
+        line_break().get(character),
+        LineBreak::Glue | LineBreak::WordJoiner | LineBreak::ZWJ
+    )
 }
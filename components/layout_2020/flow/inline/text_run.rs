@@ -11,6 +11,8 @@ use fonts::{
     FontContext, FontRef, GlyphRun, LAST_RESORT_GLYPH_ADVANCE, ShapingFlags, ShapingOptions,
 };
 use fonts_traits::ByteIndex;
+use icu_properties::LineBreak;
+use icu_properties::maps::line_break;
 use log::warn;
 use range::Range as ServoRange;
 use servo_arc::Arc;
@@ -22,20 +24,12 @@ use style::str::char_is_whitespace;
 use style::values::computed::OverflowWrap;
 use unicode_bidi::{BidiInfo, Level};
 use unicode_script::Script;
-use xi_unicode::linebreak_property;
 
 use super::line_breaker::LineBreaker;
 use super::{FontKeyAndMetrics, InlineFormattingContextLayout};
+use crate::caret_browsing::TextSource;
 use crate::fragment_tree::BaseFragmentInfo;
 
-// These constants are the xi-unicode line breaking classes that are defined in
-// `table.rs`. Unfortunately, they are only identified by number.
-pub(crate) const XI_LINE_BREAKING_CLASS_CM: u8 = 9;
-pub(crate) const XI_LINE_BREAKING_CLASS_GL: u8 = 12;
-pub(crate) const XI_LINE_BREAKING_CLASS_ZW: u8 = 28;
-pub(crate) const XI_LINE_BREAKING_CLASS_WJ: u8 = 30;
-pub(crate) const XI_LINE_BREAKING_CLASS_ZWJ: u8 = 42;
-
 /// <https://www.w3.org/TR/css-display-3/#css-text-run>
 #[derive(Debug)]
 pub(crate) struct TextRun {
@@ -43,6 +37,22 @@ pub(crate) struct TextRun {
     pub parent_style: Arc<ComputedValues>,
     pub text_range: Range<usize>,
 
+    /// The ranges of bytes in the parent [`super::InlineFormattingContext`]'s text content
+    /// that are misspelled words in the value of a text control.
+    pub spelling_errors: Vec<Range<usize>>,
+
+    /// The range of bytes in the parent [`super::InlineFormattingContext`]'s text content
+    /// that is the IME composition string in the value of a text control, if any.
+    pub composition: Option<Range<usize>>,
+
+    /// The range of bytes in the parent [`super::InlineFormattingContext`]'s text content
+    /// that is selected in the value of a focused text control, which is empty when there is
+    /// only a caret.
+    pub selection: Option<Range<usize>>,
+
+    /// The text nodes whose text this [`TextRun`] displays, if caret browsing is enabled.
+    pub text_sources: Vec<std::sync::Arc<TextSource>>,
+
     /// The text of this [`TextRun`] with a font selected, broken into unbreakable
     /// segments, and shaped.
     pub shaped_text: Vec<TextRunSegment>,
@@ -157,6 +167,7 @@ impl TextRunSegment {
             }
             ifc.push_glyph_store_to_unbreakable_segment(
                 run.glyph_store.clone(),
+                run.range.begin().to_usize()..run.range.end().to_usize(),
                 text_run,
                 self.font_index,
                 self.bidi_level,
@@ -332,6 +343,10 @@ impl TextRun {
             base_fragment_info,
             parent_style,
             text_range,
+            spelling_errors: Vec::new(),
+            composition: None,
+            selection: None,
+            text_sources: Vec::new(),
             shaped_text: Vec::new(),
         }
     }
@@ -340,6 +355,7 @@ impl TextRun {
         &mut self,
         formatting_context_text: &str,
         font_context: &FontContext,
+        text_zoom: f32,
         linebreaker: &mut LineBreaker,
         font_cache: &mut Vec<FontKeyAndMetrics>,
         bidi_info: &BidiInfo,
@@ -348,7 +364,7 @@ impl TextRun {
         let letter_spacing = inherited_text_style
             .letter_spacing
             .0
-            .resolve(self.parent_style.clone_font().font_size.computed_size());
+            .resolve(used_font_size(&self.parent_style, text_zoom).into());
         let letter_spacing = if letter_spacing.px() != 0. {
             Some(app_units::Au::from(letter_spacing))
         } else {
@@ -368,7 +384,13 @@ impl TextRun {
         let style_word_spacing: Option<Au> = specified_word_spacing.to_length().map(|l| l.into());
 
         let segments = self
-            .segment_text_by_font(formatting_context_text, font_context, font_cache, bidi_info)
+            .segment_text_by_font(
+                formatting_context_text,
+                font_context,
+                text_zoom,
+                font_cache,
+                bidi_info,
+            )
             .into_iter()
             .map(|(mut segment, font)| {
                 let word_spacing = style_word_spacing.unwrap_or_else(|| {
@@ -412,10 +434,14 @@ impl TextRun {
         &mut self,
         formatting_context_text: &str,
         font_context: &FontContext,
+        text_zoom: f32,
         font_cache: &mut Vec<FontKeyAndMetrics>,
         bidi_info: &BidiInfo,
     ) -> Vec<(TextRunSegment, FontRef)> {
-        let font_group = font_context.font_group(self.parent_style.clone_font());
+        let font_group = font_context.font_group_with_size(
+            self.parent_style.clone_font(),
+            used_font_size(&self.parent_style, text_zoom),
+        );
         let mut current: Option<(TextRunSegment, FontRef)> = None;
         let mut results = Vec::new();
 
@@ -548,12 +574,14 @@ fn char_does_not_change_font(character: char) -> bool {
         return false;
     }
 
-    let class = linebreak_property(character);
-    class == XI_LINE_BREAKING_CLASS_CM ||
-        class == XI_LINE_BREAKING_CLASS_GL ||
-        class == XI_LINE_BREAKING_CLASS_ZW ||
-        class == XI_LINE_BREAKING_CLASS_WJ ||
-        class == XI_LINE_BREAKING_CLASS_ZWJ
+    matches!(
+        line_break().get(character),
+        LineBreak::CombiningMark |
+            LineBreak::Glue |
+            LineBreak::ZWSpace |
+            LineBreak::WordJoiner |
+            LineBreak::ZWJ
+    )
 }
 
 pub(super) fn add_or_get_font(
@@ -577,12 +605,20 @@ pub(super) fn add_or_get_font(
     ifc_fonts.len() - 1
 }
 
+/// The size at which the font of `style` is used, which is its computed size multiplied by the
+/// text-only zoom factor. Stylo has no notion of text zoom in Servo, so unlike in Gecko it
+/// doesn't change the computed font size, but it applies to sizes in every unit.
+pub(super) fn used_font_size(style: &ComputedValues, text_zoom: f32) -> Au {
+    Au::from(style.get_font().font_size.computed_size()).scale_by(text_zoom)
+}
+
 pub(super) fn get_font_for_first_font_for_style(
     style: &ComputedValues,
     font_context: &FontContext,
+    text_zoom: f32,
 ) -> Option<FontRef> {
     let font = font_context
-        .font_group(style.clone_font())
+        .font_group_with_size(style.clone_font(), used_font_size(style, text_zoom))
         .write()
         .first(font_context);
     if font.is_none() {
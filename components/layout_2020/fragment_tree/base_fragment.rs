@@ -7,6 +7,8 @@ use script_layout_interface::combine_id_with_fragment_type;
 use style::dom::OpaqueNode;
 use style::selector_parser::PseudoElement;
 
+use crate::display_list::Widget;
+
 /// This data structure stores fields that are common to all non-base
 /// Fragment types and should generally be the first member of all
 /// concrete fragments.
@@ -20,6 +22,9 @@ pub(crate) struct BaseFragment {
     /// Flags which various information about this fragment used during
     /// layout.
     pub flags: FragmentFlags,
+
+    /// The form control that the theme paints for this fragment, if any.
+    pub widget: Option<Widget>,
 }
 
 impl BaseFragment {
@@ -27,6 +32,7 @@ impl BaseFragment {
         BaseFragment {
             tag: None,
             flags: FragmentFlags::empty(),
+            widget: None,
         }
     }
 
@@ -45,6 +51,9 @@ pub(crate) struct BaseFragmentInfo {
 
     /// The flags to use for the new BaseFragment.
     pub flags: FragmentFlags,
+
+    /// The form control that the theme paints for the new BaseFragment, if any.
+    pub widget: Option<Widget>,
 }
 
 impl BaseFragmentInfo {
@@ -52,6 +61,7 @@ impl BaseFragmentInfo {
         Self {
             tag: Some(Tag::new(node)),
             flags: FragmentFlags::empty(),
+            widget: None,
         }
     }
 
@@ -59,6 +69,7 @@ impl BaseFragmentInfo {
         Self {
             tag: None,
             flags: FragmentFlags::empty(),
+            widget: None,
         }
     }
 }
@@ -68,6 +79,7 @@ impl From<BaseFragmentInfo> for BaseFragment {
         Self {
             tag: info.tag,
             flags: info.flags,
+            widget: info.widget,
         }
     }
 }
@@ -102,6 +114,10 @@ bitflags! {
         /// and the fragment can be a flex item. This flag is used to cache items during flex
         /// layout.
         const SIZE_DEPENDS_ON_BLOCK_CONSTRAINTS_AND_CAN_BE_CHILD_OF_FLEX_ITEM = 1 << 8;
+        /// Whether or not the node that created this Fragment is an element in the top layer,
+        /// either because it is fullscreen or because it is a modal dialog. These are laid out
+        /// in the initial containing block and painted above the rest of the document.
+        const IS_IN_TOP_LAYER = 1 << 9;
     }
 }
 
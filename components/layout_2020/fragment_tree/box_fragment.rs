@@ -13,7 +13,7 @@ use style::computed_values::position::T as ComputedPosition;
 use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 
-use super::{BaseFragment, BaseFragmentInfo, CollapsedBlockMargins, Fragment};
+use super::{BaseFragment, BaseFragmentInfo, CollapsedBlockMargins, Fragment, FragmentFlags};
 use crate::formatting_contexts::Baselines;
 use crate::geom::{
     AuOrAuto, LengthPercentageOrAuto, PhysicalPoint, PhysicalRect, PhysicalSides, ToLogical,
@@ -348,6 +348,16 @@ impl BoxFragment {
         self.style.is_inline_box(self.base.flags)
     }
 
+    /// Whether this box is a scroll container. The overflow of the body can be propagated to
+    /// the viewport, in which case the body is not a scroll container.
+    pub(crate) fn is_scroll_container(&self) -> bool {
+        self.style.establishes_scroll_container(self.base.flags) &&
+            !self
+                .base
+                .flags
+                .contains(FragmentFlags::IS_BODY_ELEMENT_OF_HTML_ELEMENT_ROOT)
+    }
+
     /// Whether this is a table wrapper box.
     /// <https://www.w3.org/TR/css-tables-3/#table-wrapper-box>
     pub(crate) fn is_table_wrapper(&self) -> bool {
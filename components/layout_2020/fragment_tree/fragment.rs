@@ -2,12 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::ops::Range;
 use std::sync::Arc;
 
 use app_units::Au;
 use base::id::PipelineId;
 use base::print_tree::PrintTree;
 use fonts::{FontMetrics, GlyphStore};
+use fonts_traits::ByteIndex;
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOfOps};
+use range::Range as ServoRange;
 use servo_arc::Arc as ServoArc;
 use style::Zero;
 use style::properties::ComputedValues;
@@ -18,6 +22,7 @@ use super::{
     BaseFragment, BoxFragment, ContainingBlockManager, HoistedSharedFragment, PositioningFragment,
     Tag,
 };
+use crate::caret_browsing::TextSource;
 use crate::cell::ArcRefCell;
 use crate::geom::{LogicalSides, PhysicalRect};
 use crate::style_ext::ComputedValuesExt;
@@ -65,6 +70,26 @@ pub(crate) struct TextFragment {
     pub font_metrics: FontMetrics,
     pub font_key: FontInstanceKey,
     pub glyphs: Vec<Arc<GlyphStore>>,
+    /// The text content of the inline formatting context that this fragment was laid
+    /// out from, and the range of bytes in it that [`Self::glyphs`] cover.
+    pub formatting_context_text: Arc<str>,
+    pub text_range: Range<usize>,
+
+    /// The misspelled words that this fragment displays, as ranges of bytes in
+    /// [`Self::formatting_context_text`].
+    pub spelling_errors: Vec<Range<usize>>,
+
+    /// The part of the IME composition string of a text control that this fragment
+    /// displays, as a range of bytes in [`Self::formatting_context_text`].
+    pub composition: Option<Range<usize>>,
+
+    /// The part of the selection in a focused text control that this fragment displays, as
+    /// a range of bytes in [`Self::formatting_context_text`]. It is empty when the text
+    /// control only has a caret.
+    pub selection: Option<Range<usize>>,
+
+    /// The text nodes whose text this fragment displays, if caret browsing is enabled.
+    pub text_sources: Vec<Arc<TextSource>>,
 
     /// A flag that represents the _used_ value of the text-decoration property.
     pub text_decoration_line: TextDecorationLine,
@@ -86,6 +111,9 @@ pub(crate) struct IFrameFragment {
     pub pipeline_id: PipelineId,
     pub rect: PhysicalRect<Au>,
     pub style: ServoArc<ComputedValues>,
+    /// Whether this fragment shows the document of a `<portal>`, which is rendered in place
+    /// but never receives input.
+    pub is_portal: bool,
 }
 
 impl Fragment {
@@ -157,6 +185,29 @@ impl Fragment {
         }
     }
 
+    /// The memory used by this fragment and the list of its children, not counting the
+    /// children themselves, or zero if it was already measured with `ops`.
+    pub(crate) fn conditional_shallow_size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        match self {
+            Fragment::Box(fragment) | Fragment::Float(fragment) => {
+                match fragment.conditional_shallow_size_of(ops) {
+                    0 => 0,
+                    size => size + fragment.borrow().children.shallow_size_of(ops),
+                }
+            },
+            Fragment::Positioning(fragment) => match fragment.conditional_shallow_size_of(ops) {
+                0 => 0,
+                size => size + fragment.borrow().children.shallow_size_of(ops),
+            },
+            Fragment::AbsoluteOrFixedPositioned(fragment) => {
+                fragment.conditional_shallow_size_of(ops)
+            },
+            Fragment::Text(fragment) => fragment.conditional_shallow_size_of(ops),
+            Fragment::Image(fragment) => fragment.conditional_shallow_size_of(ops),
+            Fragment::IFrame(fragment) => fragment.conditional_shallow_size_of(ops),
+        }
+    }
+
     pub(crate) fn find<T>(
         &self,
         manager: &ContainingBlockManager<PhysicalRect<Au>>,
@@ -221,6 +272,69 @@ impl TextFragment {
             self.rect,
         ));
     }
+
+    /// The part of this fragment, positioned at `rect`, that displays the given range of
+    /// bytes of its inline formatting context's text, or `None` if it displays none of them.
+    pub(crate) fn rect_for_text_range(
+        &self,
+        rect: &PhysicalRect<Au>,
+        range: &Range<usize>,
+    ) -> Option<PhysicalRect<Au>> {
+        let mut offset = self.text_range.start;
+        let mut advance = Au::zero();
+        let mut covered: Option<Range<Au>> = None;
+        for glyph_store in &self.glyphs {
+            let length = glyph_store.len().to_usize();
+            let start = range.start.clamp(offset, offset + length) - offset;
+            let end = range.end.clamp(offset, offset + length) - offset;
+            let advance_for = |range: Range<usize>| {
+                if range.is_empty() {
+                    return Au::zero();
+                }
+                glyph_store.advance_for_byte_range(
+                    &ServoRange::new(
+                        ByteIndex(range.start as isize),
+                        ByteIndex(range.len() as isize),
+                    ),
+                    self.justification_adjustment,
+                )
+            };
+            if start < end {
+                let covered_start = advance + advance_for(0..start);
+                let covered_end = covered_start + advance_for(start..end);
+                covered = Some(match covered {
+                    Some(covered) => covered.start..covered_end,
+                    None => covered_start..covered_end,
+                });
+            }
+            advance += advance_for(0..length);
+            offset += length;
+        }
+
+        let covered = covered?;
+        let mut covered_rect = *rect;
+        covered_rect.origin.x += covered.start;
+        covered_rect.size.width = covered.end - covered.start;
+        Some(covered_rect)
+    }
+
+    /// A zero-width rectangle at the given byte offset of the inline formatting context's
+    /// text in this fragment, positioned at `rect`, or `None` if the offset is outside of it.
+    pub(crate) fn caret_rect_for_text_offset(
+        &self,
+        rect: &PhysicalRect<Au>,
+        offset: usize,
+    ) -> Option<PhysicalRect<Au>> {
+        if offset < self.text_range.start || self.text_range.end < offset {
+            return None;
+        }
+        let mut caret_rect = *rect;
+        if let Some(preceding) = self.rect_for_text_range(rect, &(self.text_range.start..offset)) {
+            caret_rect.origin.x = preceding.max_x();
+        }
+        caret_rect.size.width = Au::zero();
+        Some(caret_rect)
+    }
 }
 
 impl ImageFragment {
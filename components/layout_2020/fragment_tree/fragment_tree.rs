@@ -5,7 +5,8 @@
 use app_units::Au;
 use base::print_tree::PrintTree;
 use euclid::default::{Point2D, Rect, Size2D};
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
+use malloc_size_of::MallocSizeOfOps;
 use style::animation::AnimationSetKey;
 use style::dom::OpaqueNode;
 use webrender_api::units;
@@ -84,6 +85,17 @@ impl FragmentTree {
             .find_map(|child| child.find(&info, 0, &mut process_func))
     }
 
+    /// Measure the memory used by the fragments of this tree, not counting the memory that
+    /// they share with the box tree, such as their styles.
+    pub fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let mut size = 0;
+        self.find(|fragment, _, _| {
+            size += fragment.conditional_shallow_size_of(ops);
+            None::<()>
+        });
+        size
+    }
+
     pub fn remove_nodes_in_fragment_tree_from_set(&self, set: &mut FxHashSet<AnimationSetKey>) {
         self.find(|fragment, _, _| {
             let tag = fragment.tag()?;
@@ -124,6 +136,40 @@ impl FragmentTree {
         content_boxes
     }
 
+    /// Get the union of the border boxes of the fragments of every node in this fragment tree,
+    /// keyed by node. This is equivalent to answering `getBoundingClientRect()` for every node
+    /// in a single pass over the tree.
+    pub fn get_border_boxes_for_all_nodes(&self) -> FxHashMap<OpaqueNode, Rect<Au>> {
+        let mut border_boxes: FxHashMap<OpaqueNode, Rect<Au>> = FxHashMap::default();
+        self.find(|fragment, _, containing_block| {
+            let tag = fragment.tag()?;
+            if tag.pseudo.is_some() {
+                return None::<()>;
+            }
+
+            let fragment_relative_rect = match fragment {
+                Fragment::Box(fragment) | Fragment::Float(fragment) => {
+                    fragment.borrow().border_rect()
+                },
+                Fragment::Positioning(fragment) => fragment.borrow().rect,
+                Fragment::Text(fragment) => fragment.borrow().rect,
+                Fragment::AbsoluteOrFixedPositioned(_) |
+                Fragment::Image(_) |
+                Fragment::IFrame(_) => return None,
+            };
+
+            let rect = fragment_relative_rect
+                .translate(containing_block.origin.to_vector())
+                .to_untyped();
+            border_boxes
+                .entry(tag.node)
+                .and_modify(|border_box| *border_box = border_box.union(&rect))
+                .or_insert(rect);
+            None::<()>
+        });
+        border_boxes
+    }
+
     pub fn get_border_dimensions_for_node(&self, requested_node: OpaqueNode) -> Rect<i32> {
         let tag_to_find = Tag::new(requested_node);
         self.find(|fragment, _, _containing_block| {
@@ -4,11 +4,14 @@
 
 #![deny(unsafe_code)]
 
+pub mod accessibility_tree;
+pub mod caret_browsing;
 mod cell;
 pub mod context;
 pub mod display_list;
 pub mod dom;
 mod dom_traversal;
+pub mod find_in_page;
 mod flexbox;
 pub mod flow;
 mod formatting_contexts;
@@ -23,6 +26,7 @@ mod positioned;
 pub mod query;
 mod quotes;
 mod replaced;
+pub mod scroll_anchoring;
 mod sizing;
 mod style_ext;
 pub mod table;
@@ -143,7 +147,7 @@ impl<'a> From<&'_ DefiniteContainingBlock<'a>> for ContainingBlock<'a> {
 /// Data that is propagated from ancestors to descendants during [`crate::flow::BoxTree`]
 /// construction.  This allows data to flow in the reverse direction of the typical layout
 /// propoagation, but only during `BoxTree` construction.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 struct PropagatedBoxTreeData {
     text_decoration: TextDecorationLine,
     allow_percentage_column_in_tables: bool,
@@ -258,10 +258,19 @@ impl PositioningContext {
             style: &new_fragment.style,
         };
 
+        // Boxes in the top layer are only laid out by the initial containing block, so they
+        // keep bubbling up through containing blocks for all descendants.
         let take_hoisted_boxes_pending_layout =
             |context: &mut Self| match context.for_nearest_positioned_ancestor.as_mut() {
                 Some(fragments) => mem::take(fragments),
-                None => mem::take(&mut context.for_nearest_containing_block_for_all_descendants),
+                None => {
+                    let (in_top_layer, pending_layout) =
+                        mem::take(&mut context.for_nearest_containing_block_for_all_descendants)
+                            .into_iter()
+                            .partition(HoistedAbsolutelyPositionedBox::is_in_top_layer);
+                    context.for_nearest_containing_block_for_all_descendants = in_top_layer;
+                    pending_layout
+                },
             };
 
         // Loop because it’s possible that we discover (the static position of)
@@ -284,6 +293,11 @@ impl PositioningContext {
     }
 
     pub(crate) fn push(&mut self, box_: HoistedAbsolutelyPositionedBox) {
+        if box_.is_in_top_layer() {
+            return self
+                .for_nearest_containing_block_for_all_descendants
+                .push(box_);
+        }
         if let Some(nearest) = &mut self.for_nearest_positioned_ancestor {
             let position = box_
                 .absolutely_positioned_box
@@ -414,6 +428,19 @@ impl Zero for PositioningContextLength {
 }
 
 impl HoistedAbsolutelyPositionedBox {
+    /// Whether this box is generated by an element in the top layer, whose containing block
+    /// is always the initial containing block.
+    ///
+    /// <https://drafts.csswg.org/css-position-4/#top-styling>
+    fn is_in_top_layer(&self) -> bool {
+        self.absolutely_positioned_box
+            .borrow()
+            .context
+            .base_fragment_info()
+            .flags
+            .contains(FragmentFlags::IS_IN_TOP_LAYER)
+    }
+
     pub(crate) fn layout_many(
         layout_context: &LayoutContext,
         boxes: &mut [Self],
@@ -3,18 +3,24 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 //! Utilities for querying the layout, as needed by layout.
+use std::ops::Range;
 use std::sync::Arc;
 
 use app_units::Au;
 use euclid::default::{Point2D, Rect};
-use euclid::{SideOffsets2D, Size2D, Vector2D};
+use euclid::{SideOffsets2D, Size2D, UnknownUnit, Vector2D};
+use fxhash::FxHashMap;
 use itertools::Itertools;
 use script_layout_interface::wrapper_traits::{
     LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
 };
-use script_layout_interface::{LayoutElementType, LayoutNodeType, OffsetParentResponse};
+use script_layout_interface::{
+    LayoutElementType, LayoutNodeType, OffsetParentResponse, RangeNode, ScrollIntoViewGeometry,
+    ScrollerGeometry,
+};
 use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
+use style::computed_values::box_sizing::T as BoxSizing;
 use style::computed_values::display::T as Display;
 use style::computed_values::position::T as Position;
 use style::computed_values::visibility::T as Visibility;
@@ -36,12 +42,14 @@ use style::values::generics::font::LineHeight;
 use style::values::specified::GenericGridTemplateComponent;
 use style::values::specified::box_::DisplayInside;
 use style_traits::{ParsingMode, ToCss};
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSize, LayoutTransform};
 
 use crate::flow::inline::construct::{TextTransformation, WhitespaceCollapse};
 use crate::fragment_tree::{
     BoxFragment, Fragment, FragmentFlags, FragmentTree, SpecificLayoutInfo, Tag,
 };
 use crate::geom::{PhysicalRect, PhysicalVec};
+use crate::style_ext::ComputedValuesExt;
 use crate::taffy::SpecificTaffyGridInfo;
 
 pub fn process_content_box_request(
@@ -69,6 +77,157 @@ pub fn process_content_boxes_request(
         .unwrap_or_default()
 }
 
+/// The client rects of the nodes of a range. Elements contribute the border boxes of their
+/// fragments, and text nodes the parts of their text fragments that display the selected
+/// text, so that there is one rect for each line of text.
+///
+/// <https://drafts.csswg.org/cssom-view/#dom-range-getclientrects>
+pub fn process_range_client_rects_request(
+    fragment_tree: Option<Arc<FragmentTree>>,
+    nodes: &[RangeNode],
+) -> Vec<Rect<Au>> {
+    let Some(fragment_tree) = fragment_tree else {
+        return Vec::new();
+    };
+    let mut rects: FxHashMap<OpaqueNode, Vec<Rect<Au>>> = nodes
+        .iter()
+        .map(|range_node| (range_node.node, Vec::new()))
+        .collect();
+    let text_ranges: FxHashMap<OpaqueNode, &Range<usize>> = nodes
+        .iter()
+        .filter_map(|range_node| Some((range_node.node, range_node.text_range.as_ref()?)))
+        .collect();
+
+    // The fragment tree is traversed in preorder, so the transforms of the ancestors of a
+    // fragment are those of the last fragment that was visited at each level above it.
+    let mut transforms: Vec<Option<LayoutTransform>> = Vec::new();
+    fragment_tree.find(|fragment, level, containing_block| {
+        transforms.truncate(level);
+        let ancestor_transform = transforms.last().cloned().flatten();
+        let transform = match transform_of_fragment(fragment, containing_block) {
+            Some(transform) => Some(match &ancestor_transform {
+                Some(ancestor_transform) => transform.then(ancestor_transform),
+                None => transform,
+            }),
+            None => ancestor_transform,
+        };
+
+        if let Fragment::Text(text_fragment) = fragment {
+            let text_fragment = text_fragment.borrow();
+            let rect = text_fragment
+                .rect
+                .translate(containing_block.origin.to_vector());
+            for source in &text_fragment.text_sources {
+                let Some(text_range) = text_ranges.get(&source.node) else {
+                    continue;
+                };
+                let Some(node_rects) = rects.get_mut(&source.node) else {
+                    continue;
+                };
+                let range = source.to_text_content_offset(text_range.start)..
+                    source.to_text_content_offset(text_range.end);
+                let text_rect = if range.is_empty() {
+                    // A caret at the end of a line is also at the start of the next one, but
+                    // it is only displayed once.
+                    if !node_rects.is_empty() {
+                        continue;
+                    }
+                    text_fragment.caret_rect_for_text_offset(&rect, range.start)
+                } else {
+                    text_fragment.rect_for_text_range(&rect, &range)
+                };
+                if let Some(text_rect) = text_rect {
+                    node_rects.push(transform_rect(&text_rect, transform.as_ref()));
+                }
+            }
+        }
+
+        if let Some(tag) = fragment.tag() {
+            if tag.pseudo.is_none() && !text_ranges.contains_key(&tag.node) {
+                let fragment_relative_rect = match fragment {
+                    Fragment::Box(fragment) | Fragment::Float(fragment) => {
+                        Some(fragment.borrow().border_rect())
+                    },
+                    Fragment::Positioning(fragment) => Some(fragment.borrow().rect),
+                    Fragment::Text(fragment) => Some(fragment.borrow().rect),
+                    Fragment::AbsoluteOrFixedPositioned(_) |
+                    Fragment::Image(_) |
+                    Fragment::IFrame(_) => None,
+                };
+                if let (Some(node_rects), Some(fragment_relative_rect)) =
+                    (rects.get_mut(&tag.node), fragment_relative_rect)
+                {
+                    let rect =
+                        fragment_relative_rect.translate(containing_block.origin.to_vector());
+                    node_rects.push(transform_rect(&rect, transform.as_ref()));
+                }
+            }
+        }
+
+        transforms.push(transform);
+        None::<()>
+    });
+
+    nodes
+        .iter()
+        .flat_map(|range_node| rects.remove(&range_node.node).unwrap_or_default())
+        .collect()
+}
+
+/// The transform of `fragment`, if it has one, relative to the initial containing block
+/// rather than to its border box.
+fn transform_of_fragment(
+    fragment: &Fragment,
+    containing_block: &PhysicalRect<Au>,
+) -> Option<LayoutTransform> {
+    let (Fragment::Box(fragment) | Fragment::Float(fragment)) = fragment else {
+        return None;
+    };
+    let fragment = fragment.borrow();
+    if !fragment
+        .style
+        .has_transform_or_perspective(fragment.base.flags)
+    {
+        return None;
+    }
+    let border_rect = fragment
+        .border_rect()
+        .translate(containing_block.origin.to_vector());
+    let transform = fragment.calculate_transform_matrix(&border_rect.to_untyped())?;
+    let origin_x = border_rect.origin.x.to_f32_px();
+    let origin_y = border_rect.origin.y.to_f32_px();
+    Some(
+        LayoutTransform::translation(-origin_x, -origin_y, 0.)
+            .then(&transform)
+            .then(&LayoutTransform::translation(origin_x, origin_y, 0.)),
+    )
+}
+
+/// The bounding box of `rect` after applying `transform` to it.
+fn transform_rect(rect: &PhysicalRect<Au>, transform: Option<&LayoutTransform>) -> Rect<Au> {
+    let rect = rect.to_untyped();
+    let Some(transform) = transform else {
+        return rect;
+    };
+    let layout_rect = LayoutRect::from_origin_and_size(
+        LayoutPoint::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+        LayoutSize::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+    );
+    let Some(transformed) = transform.outer_transformed_box(&layout_rect) else {
+        return rect;
+    };
+    Rect::new(
+        Point2D::new(
+            Au::from_f32_px(transformed.min.x),
+            Au::from_f32_px(transformed.min.y),
+        ),
+        Size2D::new(
+            Au::from_f32_px(transformed.width()),
+            Au::from_f32_px(transformed.height()),
+        ),
+    )
+}
+
 pub fn process_node_geometry_request(
     requested_node: OpaqueNode,
     fragment_tree: Option<Arc<FragmentTree>>,
@@ -80,6 +239,39 @@ pub fn process_node_geometry_request(
     }
 }
 
+pub fn process_composition_rect_request(
+    requested_node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> Option<Rect<Au>> {
+    let tag_to_find = Tag::new(requested_node);
+    let mut composition_rect: Option<Rect<Au>> = None;
+    fragment_tree?.find(|fragment, _, containing_block| {
+        let Fragment::Text(text_fragment) = fragment else {
+            return None::<()>;
+        };
+        let text_fragment = text_fragment.borrow();
+        if text_fragment.base.tag != Some(tag_to_find) {
+            return None;
+        }
+        let composition = text_fragment.composition.as_ref()?;
+        let rect = text_fragment
+            .rect
+            .translate(containing_block.origin.to_vector());
+        let rect = if composition.is_empty() {
+            text_fragment.caret_rect_for_text_offset(&rect, composition.start)?
+        } else {
+            text_fragment.rect_for_text_range(&rect, composition)?
+        };
+        let rect = rect.to_untyped();
+        composition_rect = Some(match composition_rect {
+            Some(composition_rect) => composition_rect.union(&rect),
+            None => rect,
+        });
+        None
+    });
+    composition_rect
+}
+
 /// <https://drafts.csswg.org/cssom-view/#scrolling-area>
 pub fn process_node_scroll_area_request(
     requested_node: Option<OpaqueNode>,
@@ -100,6 +292,163 @@ pub fn process_node_scroll_area_request(
     .to_untyped()
 }
 
+/// The `scroll-margin` of a box, which extends the area that is scrolled into view.
+///
+/// TODO: Stylo does not support `scroll-margin` for Servo yet.
+fn scroll_margin(_style: &ComputedValues) -> SideOffsets2D<Au, UnknownUnit> {
+    SideOffsets2D::zero()
+}
+
+/// The `scroll-padding` of a scroll container, which reduces the part of its scrollport that
+/// boxes are scrolled into.
+///
+/// TODO: Stylo does not support `scroll-padding` for Servo yet.
+fn scroll_padding(_style: &ComputedValues) -> SideOffsets2D<Au, UnknownUnit> {
+    SideOffsets2D::zero()
+}
+
+/// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+pub fn process_scroll_into_view_request(
+    node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> Option<ScrollIntoViewGeometry> {
+    let fragment_tree = fragment_tree?;
+
+    // The scroll containers that contain the fragment being visited, with their depth in the
+    // tree.
+    let mut ancestors: Vec<(usize, ScrollerGeometry)> = Vec::new();
+    let mut scrollers = None;
+    let mut target_rect: Option<Rect<Au>> = None;
+    let tag_to_find = Tag::new(node);
+    fragment_tree.find(|fragment, level, containing_block| {
+        let (Fragment::Box(box_fragment) | Fragment::Float(box_fragment)) = fragment else {
+            return None::<()>;
+        };
+        let box_fragment = box_fragment.borrow();
+        while ancestors
+            .last()
+            .is_some_and(|(ancestor_level, _)| *ancestor_level >= level)
+        {
+            ancestors.pop();
+        }
+
+        // A box can have several fragments, for instance an inline box that is split across
+        // lines. The area that is scrolled into view is the union of their border boxes, and
+        // they are assumed to be in the same scroll containers as the first one.
+        if box_fragment.base.tag == Some(tag_to_find) {
+            let border_rect = box_fragment
+                .border_rect()
+                .translate(containing_block.origin.to_vector())
+                .to_untyped()
+                .outer_rect(scroll_margin(&box_fragment.style));
+            target_rect = Some(target_rect.map_or(border_rect, |rect| rect.union(&border_rect)));
+            scrollers.get_or_insert_with(|| {
+                ancestors
+                    .iter()
+                    .rev()
+                    .map(|(_, scroller)| scroller.clone())
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        if let Some(scroller) = scroller_geometry(&box_fragment, containing_block) {
+            ancestors.push((level, scroller));
+        }
+        None
+    });
+
+    Some(ScrollIntoViewGeometry {
+        target_rect: target_rect?,
+        scrollers: scrollers.unwrap_or_default(),
+    })
+}
+
+/// The geometry of `box_fragment`, positioned in `containing_block`, as a scroll container
+/// that boxes are scrolled into, or `None` if it is not one.
+fn scroller_geometry(
+    box_fragment: &BoxFragment,
+    containing_block: &PhysicalRect<Au>,
+) -> Option<ScrollerGeometry> {
+    let tag = box_fragment.base.tag.filter(|tag| !tag.is_pseudo())?;
+    if !box_fragment.is_scroll_container() {
+        return None;
+    }
+    let padding_rect = box_fragment
+        .padding_rect()
+        .translate(containing_block.origin.to_vector())
+        .to_untyped();
+    let scrollable_overflow = box_fragment.scrollable_overflow().size.to_untyped();
+    Some(ScrollerGeometry {
+        node: tag.node.into(),
+        scrollport: padding_rect.inner_rect(scroll_padding(&box_fragment.style)),
+        scroll_range: (scrollable_overflow - padding_rect.size).max(Size2D::zero()),
+    })
+}
+
+/// The geometry needed to scroll the selection of the focused text control `node` into view,
+/// which is its caret when nothing is selected. Unlike for boxes, the scroll containers
+/// include the text control itself, when its value overflows it.
+pub fn process_text_control_selection_request(
+    node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> Option<ScrollIntoViewGeometry> {
+    let fragment_tree = fragment_tree?;
+
+    let mut ancestors: Vec<(usize, ScrollerGeometry)> = Vec::new();
+    let mut scrollers = None;
+    let mut target_rect: Option<Rect<Au>> = None;
+    let tag_to_find = Tag::new(node);
+    fragment_tree.find(|fragment, level, containing_block| {
+        while ancestors
+            .last()
+            .is_some_and(|(ancestor_level, _)| *ancestor_level >= level)
+        {
+            ancestors.pop();
+        }
+
+        match fragment {
+            Fragment::Box(box_fragment) | Fragment::Float(box_fragment) => {
+                if let Some(scroller) = scroller_geometry(&box_fragment.borrow(), containing_block)
+                {
+                    ancestors.push((level, scroller));
+                }
+            },
+            Fragment::Text(text_fragment) => {
+                let text_fragment = text_fragment.borrow();
+                if text_fragment.base.tag != Some(tag_to_find) {
+                    return None::<()>;
+                }
+                let selection = text_fragment.selection.as_ref()?;
+                let rect = text_fragment
+                    .rect
+                    .translate(containing_block.origin.to_vector());
+                let rect = if selection.is_empty() {
+                    text_fragment.caret_rect_for_text_offset(&rect, selection.start)?
+                } else {
+                    text_fragment.rect_for_text_range(&rect, selection)?
+                };
+                let rect = rect.to_untyped();
+                target_rect =
+                    Some(target_rect.map_or(rect, |target_rect| target_rect.union(&rect)));
+                scrollers.get_or_insert_with(|| {
+                    ancestors
+                        .iter()
+                        .rev()
+                        .map(|(_, scroller)| scroller.clone())
+                        .collect::<Vec<_>>()
+                });
+            },
+            _ => {},
+        }
+        None
+    });
+
+    Some(ScrollIntoViewGeometry {
+        target_rect: target_rect?,
+        scrollers: scrollers.unwrap_or_default(),
+    })
+}
+
 /// Return the resolved value of property for a given (pseudo)element.
 /// <https://drafts.csswg.org/cssom/#resolved-value>
 pub fn process_resolved_style_request<'dom>(
@@ -108,6 +457,7 @@ pub fn process_resolved_style_request<'dom>(
     pseudo: &Option<PseudoElement>,
     property: &PropertyId,
     fragment_tree: Option<Arc<FragmentTree>>,
+    text_zoom: f32,
 ) -> String {
     if !node.as_element().unwrap().has_data() {
         return process_resolved_style_request_for_unstyled_node(context, node, pseudo, property);
@@ -131,7 +481,9 @@ pub fn process_resolved_style_request<'dom>(
         None => layout_element,
     };
 
-    let style = &*layout_element.resolved_style();
+    // Pseudo-elements that are not cascaded eagerly, such as the ones used for markers, have
+    // their style computed now.
+    let style = &*layout_element.style(context);
     let longhand_id = match *property {
         PropertyId::NonCustom(id) => match id.longhand_or_shorthand() {
             Ok(longhand_id) => longhand_id,
@@ -155,14 +507,15 @@ pub fn process_resolved_style_request<'dom>(
     // fall back to returning the computed value.
 
     // For line height, the resolved value is the computed value if it
-    // is "normal" and the used value otherwise.
+    // is "normal" and the used value otherwise. Like in inline layout, the
+    // used value is scaled by the text zoom.
     if longhand_id == LonghandId::LineHeight {
         let font = style.get_font();
-        let font_size = font.font_size.computed_size();
+        let used_font_size = Au::from(font.font_size.computed_size()).scale_by(text_zoom);
         return match font.line_height {
             LineHeight::Normal => computed_style(),
-            LineHeight::Number(value) => (font_size * value.0).to_css_string(),
-            LineHeight::Length(value) => value.0.to_css_string(),
+            LineHeight::Number(value) => used_font_size.scale_by(value.0).to_css_string(),
+            LineHeight::Length(value) => Au::from(value.0).scale_by(text_zoom).to_css_string(),
         };
     }
 
@@ -184,7 +537,7 @@ pub fn process_resolved_style_request<'dom>(
                 return None;
             }
 
-            let (content_rect, margins, padding, specific_layout_info) = match fragment {
+            let (content_rect, margins, padding, border, specific_layout_info) = match fragment {
                 Fragment::Box(box_fragment) | Fragment::Float(box_fragment) => {
                     let box_fragment = box_fragment.borrow();
                     if style.get_box().position != Position::Static {
@@ -207,9 +560,11 @@ pub fn process_resolved_style_request<'dom>(
                     }
                     let content_rect = box_fragment.content_rect;
                     let margins = box_fragment.margin;
-                    let padding = box_fragment.padding;
+                    // The scrollbar gutter is laid out as padding, but is not part of it.
+                    let padding = box_fragment.padding - box_fragment.style.scrollbar_gutter();
+                    let border = box_fragment.border;
                     let specific_layout_info = box_fragment.specific_layout_info.clone();
-                    (content_rect, margins, padding, specific_layout_info)
+                    (content_rect, margins, padding, border, specific_layout_info)
                 },
                 Fragment::Positioning(positioning_fragment) => {
                     let content_rect = positioning_fragment.borrow().rect;
@@ -217,6 +572,7 @@ pub fn process_resolved_style_request<'dom>(
                         content_rect,
                         SideOffsets2D::zero(),
                         SideOffsets2D::zero(),
+                        SideOffsets2D::zero(),
                         None,
                     )
                 },
@@ -243,12 +599,29 @@ pub fn process_resolved_style_request<'dom>(
             //
             // However, all browsers ignore that for margin and padding properties, and resolve to a length
             // even if the property doesn't apply: https://github.com/w3c/csswg-drafts/issues/10391
+            //
+            // The used value of a size is that of the box that `box-sizing` applies it to.
+            let size = match style.get_position().box_sizing {
+                BoxSizing::ContentBox => content_rect.size,
+                BoxSizing::BorderBox => Size2D::new(
+                    content_rect.size.width +
+                        padding.left +
+                        padding.right +
+                        border.left +
+                        border.right,
+                    content_rect.size.height +
+                        padding.top +
+                        padding.bottom +
+                        border.top +
+                        border.bottom,
+                ),
+            };
             match longhand_id {
                 LonghandId::Width if resolved_size_should_be_used_value(fragment) => {
-                    Some(content_rect.size.width)
+                    Some(size.width)
                 },
                 LonghandId::Height if resolved_size_should_be_used_value(fragment) => {
-                    Some(content_rect.size.height)
+                    Some(size.height)
                 },
                 LonghandId::MarginBottom => Some(margins.bottom),
                 LonghandId::MarginTop => Some(margins.top),
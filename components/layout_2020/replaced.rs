@@ -137,8 +137,11 @@ pub(crate) struct VideoInfo {
 pub(crate) enum ReplacedContentKind {
     Image(Option<Arc<Image>>),
     IFrame(IFrameInfo),
+    Portal(PipelineId),
     Canvas(CanvasInfo),
     Video(Option<VideoInfo>),
+    /// A checkbox or radio button, which the theme paints in place of its content.
+    Widget,
 }
 
 impl ReplacedContents {
@@ -172,11 +175,32 @@ impl ReplacedContents {
                     }),
                     None,
                 )
+            } else if let Some(pipeline_id) = element.as_portal() {
+                (ReplacedContentKind::Portal(pipeline_id), None)
+            } else if let Some((svg_source, natural_size_in_dots)) = element.as_svg() {
+                let image = svg_source.and_then(|source| {
+                    match context.get_or_request_image_or_meta(
+                        element.opaque(),
+                        source,
+                        UsePlaceholder::No,
+                    ) {
+                        Some(ImageOrMetadataAvailable::ImageAvailable { image, .. }) => Some(image),
+                        _ => None,
+                    }
+                });
+                (
+                    ReplacedContentKind::Image(image),
+                    Some(natural_size_in_dots),
+                )
             } else if let Some((image_key, natural_size_in_dots)) = element.as_video() {
                 (
                     ReplacedContentKind::Video(image_key.map(|key| VideoInfo { image_key: key })),
                     natural_size_in_dots,
                 )
+            } else if let Some(natural_size) =
+                element.as_widget().and_then(|widget| widget.natural_size())
+            {
+                (ReplacedContentKind::Widget, Some(natural_size))
             } else {
                 return None;
             }
@@ -186,7 +210,7 @@ impl ReplacedContents {
             context.handle_animated_image(element.opaque(), image.clone());
         }
 
-        let natural_size = if let Some(naturalc_size_in_dots) = natural_size_in_dots {
+        let mut natural_size = if let Some(naturalc_size_in_dots) = natural_size_in_dots {
             // FIXME: should 'image-resolution' (when implemented) be used *instead* of
             // `script::dom::htmlimageelement::ImageRequest::current_pixel_density`?
             // https://drafts.csswg.org/css-images-4/#the-image-resolution
@@ -197,6 +221,10 @@ impl ReplacedContents {
         } else {
             NaturalSizes::empty()
         };
+        if matches!(kind, ReplacedContentKind::Widget) {
+            // Form controls have a natural width and height, but no natural ratio.
+            natural_size.ratio = None;
+        }
 
         let base_fragment_info = BaseFragmentInfo::new_for_node(element.opaque());
         Some(Self {
@@ -371,6 +399,18 @@ impl ReplacedContents {
                     style: style.clone(),
                     pipeline_id: iframe.pipeline_id,
                     rect,
+                    is_portal: false,
+                }))]
+            },
+            // Portal documents are laid out at the size of the viewport they may be activated
+            // into, so unlike iframes their size is not reported back to script.
+            ReplacedContentKind::Portal(pipeline_id) => {
+                vec![Fragment::IFrame(ArcRefCell::new(IFrameFragment {
+                    base: self.base_fragment_info.into(),
+                    style: style.clone(),
+                    pipeline_id: *pipeline_id,
+                    rect,
+                    is_portal: true,
                 }))]
             },
             ReplacedContentKind::Canvas(canvas_info) => {
@@ -394,6 +434,8 @@ impl ReplacedContents {
                     image_key: Some(image_key),
                 }))]
             },
+            // The theme paints widgets as part of the box of their element.
+            ReplacedContentKind::Widget => vec![],
         }
     }
 
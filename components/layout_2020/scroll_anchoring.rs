@@ -0,0 +1,234 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scroll anchoring, which keeps the content that is visible in a scroller in place when
+//! a reflow moves it, for instance because content was inserted above it.
+//!
+//! Before a reflow, an anchor node is selected for every scroller among the boxes that are
+//! visible in it. After the reflow, the scroll offset of the scroller is adjusted by how far
+//! the anchor node moved relative to the scroller.
+//!
+//! <https://drafts.csswg.org/css-scroll-anchoring/>
+
+use std::collections::HashMap;
+
+use app_units::Au;
+use euclid::default::Vector2D;
+use fxhash::FxHashMap;
+use style::Zero;
+use style::computed_values::position::T as ComputedPosition;
+use style::dom::OpaqueNode;
+use webrender_api::units::LayoutVector2D;
+use webrender_api::{ExternalScrollId, PipelineId};
+
+use crate::FragmentTree;
+use crate::fragment_tree::{Fragment, Tag};
+use crate::geom::{PhysicalRect, PhysicalSize};
+
+/// A scroller of a [`FragmentTree`]: the viewport, or a box that is a scroll container.
+struct Scroller {
+    id: ExternalScrollId,
+    /// The index of the scroller that this one is nested in, if any.
+    parent: Option<usize>,
+    /// The scrollport, in the coordinates of the fragment tree when nothing is scrolled.
+    scrollport: PhysicalRect<Au>,
+    /// The size of the scrollable overflow of the scroller.
+    scrollable_overflow: PhysicalSize<Au>,
+    /// The boxes that are scrolled by this scroller and not by one nested in it, in tree
+    /// order, with their depth in the tree and their border box in the coordinates of the
+    /// fragment tree.
+    candidates: Vec<(Tag, usize, PhysicalRect<Au>)>,
+}
+
+impl Scroller {
+    /// The position of the border box of `tag` relative to the scrollport, if it is scrolled
+    /// by this scroller.
+    fn position_of(&self, tag: Tag) -> Option<Vector2D<Au>> {
+        let (_, _, rect) = self
+            .candidates
+            .iter()
+            .find(|(candidate, _, _)| *candidate == tag)?;
+        Some((rect.origin - self.scrollport.origin).to_untyped())
+    }
+}
+
+/// The scrollers of `fragment_tree`, starting with the viewport.
+fn scrollers(fragment_tree: &FragmentTree, pipeline_id: PipelineId) -> Vec<Scroller> {
+    let mut scrollers = vec![Scroller {
+        id: ExternalScrollId(0, pipeline_id),
+        parent: None,
+        scrollport: fragment_tree.initial_containing_block,
+        scrollable_overflow: fragment_tree.scrollable_overflow.size,
+        candidates: Vec::new(),
+    }];
+    // The scrollers that contain the fragment being visited, with their depth in the tree.
+    let mut ancestors: Vec<(usize, usize)> = vec![(0, 0)];
+    fragment_tree.find(|fragment, level, containing_block| {
+        let (Fragment::Box(box_fragment) | Fragment::Float(box_fragment)) = fragment else {
+            return None::<()>;
+        };
+        let box_fragment = box_fragment.borrow();
+        let tag = box_fragment.base.tag?;
+        while ancestors.len() > 1 && ancestors[ancestors.len() - 1].1 >= level {
+            ancestors.pop();
+        }
+
+        // Boxes that are fixed to the viewport are never moved by scrolling.
+        if box_fragment.style.get_box().position != ComputedPosition::Fixed {
+            let border_rect = box_fragment
+                .border_rect()
+                .translate(containing_block.origin.to_vector());
+            let scroller = ancestors[ancestors.len() - 1].0;
+            scrollers[scroller]
+                .candidates
+                .push((tag, level, border_rect));
+        }
+
+        if box_fragment.is_scroll_container() {
+            let parent = ancestors[ancestors.len() - 1].0;
+            ancestors.push((scrollers.len(), level));
+            scrollers.push(Scroller {
+                id: ExternalScrollId(tag.to_display_list_fragment_id(), pipeline_id),
+                parent: Some(parent),
+                scrollport: box_fragment
+                    .padding_rect()
+                    .translate(containing_block.origin.to_vector()),
+                scrollable_overflow: box_fragment.scrollable_overflow().size,
+                candidates: Vec::new(),
+            });
+        }
+        None
+    });
+    scrollers
+}
+
+/// How far `scroll_offsets` move the border box of each node of `fragment_tree` that is
+/// scrolled, accumulated over all the scrollers that it is nested in.
+pub fn scroll_translations(
+    fragment_tree: &FragmentTree,
+    scroll_offsets: &HashMap<ExternalScrollId, LayoutVector2D>,
+    pipeline_id: PipelineId,
+) -> FxHashMap<OpaqueNode, Vector2D<Au>> {
+    let scrollers = scrollers(fragment_tree, pipeline_id);
+    // Scrollers come in tree order, so the translation of the parent of a scroller is
+    // always computed before its own.
+    let mut translations: Vec<Vector2D<Au>> = Vec::with_capacity(scrollers.len());
+    let mut node_translations = FxHashMap::default();
+    for scroller in &scrollers {
+        let scroll_offset = scroll_offsets
+            .get(&scroller.id)
+            .map(|offset| Vector2D::new(Au::from_f32_px(offset.x), Au::from_f32_px(offset.y)))
+            .unwrap_or_else(Vector2D::zero);
+        let translation = scroller
+            .parent
+            .map_or(scroll_offset, |parent| translations[parent] + scroll_offset);
+        translations.push(translation);
+        if translation == Vector2D::zero() {
+            continue;
+        }
+        for (tag, _, _) in &scroller.candidates {
+            if tag.pseudo.is_none() {
+                node_translations.insert(tag.node, translation);
+            }
+        }
+    }
+    node_translations
+}
+
+/// The anchor nodes of the scrollers of a [`FragmentTree`], selected before a reflow.
+pub struct ScrollAnchors {
+    pipeline_id: PipelineId,
+    /// The anchor node of each scroller that has one, and its position relative to the
+    /// scrollport of the scroller.
+    anchors: Vec<(ExternalScrollId, Tag, Vector2D<Au>)>,
+}
+
+impl ScrollAnchors {
+    /// Select the anchor node of every scroller of `fragment_tree` that is scrolled by
+    /// `scroll_offsets`.
+    ///
+    /// <https://drafts.csswg.org/css-scroll-anchoring/#anchor-node-selection>
+    pub fn select(
+        fragment_tree: &FragmentTree,
+        scroll_offsets: &HashMap<ExternalScrollId, LayoutVector2D>,
+        pipeline_id: PipelineId,
+    ) -> Self {
+        let anchors = scrollers(fragment_tree, pipeline_id)
+            .into_iter()
+            .filter_map(|scroller| {
+                // Scrollers that are not scrolled keep showing their start, so that content
+                // inserted there is visible.
+                let scroll_offset = scroll_offsets.get(&scroller.id)?;
+                if *scroll_offset == LayoutVector2D::zero() {
+                    return None;
+                }
+                let visible_rect = scroller.scrollport.translate(
+                    Vector2D::new(
+                        Au::from_f32_px(-scroll_offset.x),
+                        Au::from_f32_px(-scroll_offset.y),
+                    )
+                    .cast_unit(),
+                );
+
+                // The anchor node is the first box that is entirely visible. If there is
+                // none, it is the deepest box that is partially visible.
+                let visible_candidates = scroller
+                    .candidates
+                    .iter()
+                    .filter(|(_, _, rect)| rect.intersects(&visible_rect));
+                let (tag, _, _) = visible_candidates
+                    .clone()
+                    .find(|(_, _, rect)| visible_rect.contains_rect(rect))
+                    .or_else(|| visible_candidates.rev().max_by_key(|(_, level, _)| *level))?;
+                Some((scroller.id, *tag, scroller.position_of(*tag)?))
+            })
+            .collect();
+        Self {
+            pipeline_id,
+            anchors,
+        }
+    }
+
+    /// The scroll offsets that keep the anchor nodes in place in `fragment_tree`, which was
+    /// laid out after they were selected, for the scrollers that need to be adjusted.
+    ///
+    /// <https://drafts.csswg.org/css-scroll-anchoring/#scroll-adjustment>
+    pub fn adjusted_scroll_offsets(
+        &self,
+        fragment_tree: &FragmentTree,
+        scroll_offsets: &HashMap<ExternalScrollId, LayoutVector2D>,
+    ) -> Vec<(ExternalScrollId, LayoutVector2D)> {
+        if self.anchors.is_empty() {
+            return Vec::new();
+        }
+        let scrollers = scrollers(fragment_tree, self.pipeline_id);
+        self.anchors
+            .iter()
+            .filter_map(|(id, tag, old_position)| {
+                let scroller = scrollers.iter().find(|scroller| scroller.id == *id)?;
+                let scroll_offset = *scroll_offsets.get(id)?;
+                let movement = scroller.position_of(*tag)? - *old_position;
+                if movement == Vector2D::zero() {
+                    return None;
+                }
+
+                // The scroll offset is the negation of the scroll position, which is clamped
+                // to the scrollable overflow of the scroller.
+                let max_position = scroller.scrollable_overflow - scroller.scrollport.size;
+                let clamp = |position: Au, max: Au| position.min(max).max(Au::zero());
+                let position_x = clamp(
+                    Au::from_f32_px(-scroll_offset.x) + movement.x,
+                    max_position.width,
+                );
+                let position_y = clamp(
+                    Au::from_f32_px(-scroll_offset.y) + movement.y,
+                    max_position.height,
+                );
+                let adjusted_scroll_offset =
+                    LayoutVector2D::new(-position_x.to_f32_px(), -position_y.to_f32_px());
+                (adjusted_scroll_offset != scroll_offset).then_some((*id, adjusted_scroll_offset))
+            })
+            .collect()
+    }
+}
@@ -17,6 +17,7 @@ use style::properties::longhands::backface_visibility::computed_value::T as Back
 use style::properties::longhands::box_sizing::computed_value::T as BoxSizing;
 use style::properties::longhands::column_span::computed_value::T as ColumnSpan;
 use style::properties::style_structs::Border;
+use style::queries::values::PrefersColorScheme;
 use style::servo::selector_parser::PseudoElement;
 use style::values::CSSFloat;
 use style::values::computed::basic_shape::ClipPath;
@@ -26,10 +27,12 @@ use style::values::generics::box_::Perspective;
 use style::values::generics::position::{GenericAspectRatio, PreferredRatio};
 use style::values::generics::transform::{GenericRotate, GenericScale, GenericTranslate};
 use style::values::specified::align::AlignFlags;
+use style::values::specified::color::ColorSchemeFlags;
 use style::values::specified::{Overflow, WillChangeBits, box_ as stylo};
 use webrender_api as wr;
 use webrender_api::units::LayoutTransform;
 
+use crate::display_list::scrollbar_thickness;
 use crate::dom_traversal::{Contents, NonReplacedContents};
 use crate::fragment_tree::FragmentFlags;
 use crate::geom::{
@@ -325,6 +328,7 @@ pub(crate) trait ComputedValuesExt {
     fn establishes_block_formatting_context(&self, fragment_flags: FragmentFlags) -> bool;
     fn establishes_stacking_context(&self, fragment_flags: FragmentFlags) -> bool;
     fn establishes_scroll_container(&self, fragment_flags: FragmentFlags) -> bool;
+    fn scrollbar_gutter(&self) -> PhysicalSides<Au>;
     fn establishes_containing_block_for_absolute_descendants(
         &self,
         fragment_flags: FragmentFlags,
@@ -339,6 +343,7 @@ pub(crate) trait ComputedValuesExt {
         padding_border_sums: &LogicalVec2<Au>,
     ) -> Option<AspectRatio>;
     fn background_is_transparent(&self) -> bool;
+    fn uses_dark_color_scheme(&self, preferred_color_scheme: PrefersColorScheme) -> bool;
     fn get_webrender_primitive_flags(&self) -> wr::PrimitiveFlags;
     fn bidi_control_chars(&self) -> (&'static str, &'static str);
     fn resolve_align_self(
@@ -660,6 +665,34 @@ impl ComputedValuesExt for ComputedValues {
         self.effective_overflow(fragment_flags).x.is_scrollable()
     }
 
+    /// The space that `scrollbar-gutter` reserves for scrollbars on each side of a scroll
+    /// container. The gutter is on the inline-end edge, parallel to the block axis, and also on
+    /// the inline-start edge with `both-edges`. It is part of the padding of the box, so that
+    /// the scrollbar is painted in it instead of over the content.
+    /// <https://drafts.csswg.org/css-overflow/#scrollbar-gutter-property>
+    fn scrollbar_gutter(&self) -> PhysicalSides<Au> {
+        let scrollbar_gutter = self.get_box().scrollbar_gutter;
+        if !scrollbar_gutter.contains(stylo::ScrollbarGutter::STABLE) ||
+            !self.establishes_scroll_container(FragmentFlags::empty())
+        {
+            return PhysicalSides::zero();
+        }
+        let Some(thickness) = scrollbar_thickness(self) else {
+            return PhysicalSides::zero();
+        };
+        let gutter = LogicalSides {
+            inline_start: if scrollbar_gutter.contains(stylo::ScrollbarGutter::BOTH_EDGES) {
+                thickness
+            } else {
+                Au::zero()
+            },
+            inline_end: thickness,
+            block_start: Au::zero(),
+            block_end: Au::zero(),
+        };
+        gutter.to_physical(self.writing_mode)
+    }
+
     /// Returns true if this fragment establishes a new stacking context and false otherwise.
     fn establishes_stacking_context(&self, fragment_flags: FragmentFlags) -> bool {
         // From <https://www.w3.org/TR/css-will-change/#valdef-will-change-custom-ident>:
@@ -889,6 +922,17 @@ impl ComputedValuesExt for ComputedValues {
                 .all(|layer| matches!(layer, ComputedImageLayer::None))
     }
 
+    /// Whether the used color scheme of this style is dark, which is the case when its
+    /// `color-scheme` supports a dark scheme and either the user prefers dark or it does
+    /// not support a light scheme.
+    /// <https://drafts.csswg.org/css-color-adjust/#color-scheme-prop>
+    fn uses_dark_color_scheme(&self, preferred_color_scheme: PrefersColorScheme) -> bool {
+        let color_scheme = self.get_inherited_ui().color_scheme.bits;
+        color_scheme.contains(ColorSchemeFlags::DARK) &&
+            (preferred_color_scheme == PrefersColorScheme::Dark ||
+                !color_scheme.contains(ColorSchemeFlags::LIGHT))
+    }
+
     /// Generate appropriate WebRender `PrimitiveFlags` that should be used
     /// for display items generated by the `Fragment` which owns this style.
     fn get_webrender_primitive_flags(&self) -> wr::PrimitiveFlags {
@@ -1057,10 +1101,11 @@ impl LayoutStyle<'_> {
         writing_mode: WritingMode,
         containing_block_inline_size: Au,
     ) -> PaddingBorderMargin {
+        let style = self.style();
         let padding = self
             .padding(writing_mode)
-            .percentages_relative_to(containing_block_inline_size);
-        let style = self.style();
+            .percentages_relative_to(containing_block_inline_size) +
+            LogicalSides::from_physical(&style.scrollbar_gutter(), writing_mode);
         let border = self.border_width(writing_mode);
         let margin = style
             .margin(writing_mode)
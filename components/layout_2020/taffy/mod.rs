@@ -7,6 +7,7 @@ use std::fmt;
 
 use app_units::Au;
 use servo_arc::Arc;
+use style::Zero;
 use style::properties::ComputedValues;
 use stylo_taffy::TaffyStyloStyle;
 
@@ -123,22 +124,8 @@ pub(crate) struct SpecificTaffyGridInfo {
 impl SpecificTaffyGridInfo {
     fn from_detailed_grid_layout(grid_info: taffy::DetailedGridInfo) -> Self {
         Self {
-            rows: SpecificTaffyGridTrackInfo {
-                sizes: grid_info
-                    .rows
-                    .sizes
-                    .iter()
-                    .map(|size| Au::from_f32_px(*size))
-                    .collect(),
-            },
-            columns: SpecificTaffyGridTrackInfo {
-                sizes: grid_info
-                    .columns
-                    .sizes
-                    .iter()
-                    .map(|size| Au::from_f32_px(*size))
-                    .collect(),
-            },
+            rows: SpecificTaffyGridTrackInfo::from_detailed_track_info(&grid_info.rows),
+            columns: SpecificTaffyGridTrackInfo::from_detailed_track_info(&grid_info.columns),
         }
     }
 }
@@ -146,4 +133,29 @@ impl SpecificTaffyGridInfo {
 #[derive(Clone, Debug)]
 pub(crate) struct SpecificTaffyGridTrackInfo {
     pub sizes: Box<[Au]>,
+    /// The sizes of the gutters before, between and after the tracks.
+    pub gutters: Box<[Au]>,
+}
+
+impl SpecificTaffyGridTrackInfo {
+    fn from_detailed_track_info(track_info: &taffy::DetailedGridTracksInfo) -> Self {
+        let to_au = |sizes: &[f32]| sizes.iter().map(|size| Au::from_f32_px(*size)).collect();
+        Self {
+            sizes: to_au(&track_info.sizes),
+            gutters: to_au(&track_info.gutters),
+        }
+    }
+
+    /// The start and end of each track, relative to the start of the content box of the
+    /// grid container. The offset of the tracks that is caused by content distribution,
+    /// such as `justify-content: center`, is not known here.
+    pub(crate) fn track_ranges(&self) -> impl Iterator<Item = (Au, Au)> + '_ {
+        let mut position = Au::zero();
+        self.sizes.iter().enumerate().map(move |(index, size)| {
+            position += self.gutters.get(index).copied().unwrap_or_default();
+            let start = position;
+            position += *size;
+            (start, position)
+        })
+    }
 }
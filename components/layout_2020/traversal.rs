@@ -9,7 +9,7 @@ use style::dom::{NodeInfo, TElement, TNode};
 use style::traversal::{DomTraversal, PerLevelTraversalData, recalc_style_at};
 
 use crate::context::LayoutContext;
-use crate::dom::DOMLayoutData;
+use crate::dom::{DOMLayoutData, NodeExt};
 
 pub struct RecalcStyle<'a> {
     context: LayoutContext<'a>,
@@ -51,6 +51,13 @@ where
                 let mut data = el.mutate_data().unwrap();
                 recalc_style_at(self, traversal_data, context, el, &mut data, note_child);
                 el.unset_dirty_descendants();
+                if !data.damage.is_empty() {
+                    self.context
+                        .damaged_nodes
+                        .lock()
+                        .push((node.opaque(), data.damage));
+                }
+                node.layout_data_mut().style_changed = !data.damage.is_empty();
             }
         }
     }
@@ -9,10 +9,12 @@
 //! painted.
 
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
 use std::fmt::Debug;
-use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, LazyLock};
+use std::{mem, process};
 
 use app_units::Au;
 use base::Epoch;
@@ -26,13 +28,25 @@ use fonts::{FontContext, FontContextWebFontMethods};
 use fonts_traits::StylesheetWebFontLoadFinishedCallback;
 use fxhash::{FxHashMap, FxHashSet};
 use ipc_channel::ipc::IpcSender;
+use layout::accessibility_tree::AccessibilityTree;
+use layout::caret_browsing::{
+    process_caret_in_adjacent_line_request, process_caret_position_from_point_request,
+    process_caret_rect_request,
+};
 use layout::context::LayoutContext;
-use layout::display_list::{DisplayList, WebRenderImageInfo};
+use layout::display_list::{
+    DisplayList, DisplayListInvalidation, RetainedDisplayItems, WebRenderImageInfo,
+};
+use layout::find_in_page::FindInPageHighlights;
 use layout::query::{
-    get_the_text_steps, process_content_box_request, process_content_boxes_request,
-    process_node_geometry_request, process_node_scroll_area_request, process_offset_parent_query,
-    process_resolved_font_style_query, process_resolved_style_request, process_text_index_request,
+    get_the_text_steps, process_composition_rect_request, process_content_box_request,
+    process_content_boxes_request, process_node_geometry_request, process_node_scroll_area_request,
+    process_offset_parent_query, process_range_client_rects_request,
+    process_resolved_font_style_query, process_resolved_style_request,
+    process_scroll_into_view_request, process_text_control_selection_request,
+    process_text_index_request,
 };
+use layout::scroll_anchoring::ScrollAnchors;
 use layout::traversal::RecalcStyle;
 use layout::{BoxTree, FragmentTree};
 use log::{debug, error};
@@ -46,13 +60,16 @@ use profile_traits::time::{
 use profile_traits::{path, time_profile};
 use script::layout_dom::{ServoLayoutElement, ServoLayoutNode};
 use script_layout_interface::{
-    ImageAnimationState, Layout, LayoutConfig, LayoutFactory, NodesFromPointQueryType,
-    OffsetParentResponse, ReflowGoal, ReflowRequest, ReflowResult, TrustedNodeAddress,
+    CaretBrowsingSelection, CaretLineDirection, ImageAnimationState, InspectorHighlight, Layout,
+    LayoutConfig, LayoutFactory, NodesFromPointQueryType, OffsetParentResponse, RangeNode,
+    ReflowGoal, ReflowRequest, ReflowResult, ScrollIntoViewGeometry, TopLayerElement,
+    TrustedNodeAddress,
 };
 use script_traits::{DrawAPaintImageResult, PaintWorkletError, Painter, ScriptThreadMessage};
 use servo_arc::Arc as ServoArc;
 use servo_config::opts::{self, DebugOptions};
 use servo_config::pref;
+use servo_config::prefs::{self, PreferenceChange, PreferencesObserver};
 use servo_url::ServoUrl;
 use style::animation::{AnimationSetKey, DocumentAnimationSet};
 use style::context::{
@@ -86,7 +103,7 @@ use stylo_atoms::Atom;
 use url::Url;
 use webrender_api::units::{DevicePixel, LayoutPixel};
 use webrender_api::{ExternalScrollId, HitTestFlags, units};
-use webrender_traits::CrossProcessCompositorApi;
+use webrender_traits::{CrossProcessCompositorApi, ScrollAnimation};
 
 // This mutex is necessary due to syncronisation issues between two different types of thread-local storage
 // which manifest themselves when the layout thread tries to layout iframes in parallel with the main page
@@ -134,6 +151,12 @@ pub struct LayoutThread {
     /// The fragment tree.
     fragment_tree: RefCell<Option<Arc<FragmentTree>>>,
 
+    /// The accessibility tree, as last sent to the embedder.
+    accessibility_tree: RefCell<AccessibilityTree>,
+
+    /// The display items that WebRender retained from the previous display lists.
+    retained_display_items: RefCell<RetainedDisplayItems>,
+
     /// A counter for epoch messages
     epoch: Cell<Epoch>,
 
@@ -141,6 +164,23 @@ pub struct LayoutThread {
     /// constraints.
     viewport_size: UntypedSize2D<Au>,
 
+    /// The factor by which the used size of fonts is multiplied for text-only zoom.
+    text_zoom: f32,
+
+    /// Whether the document is painted with the colors of forced colors mode.
+    forced_colors: bool,
+
+    /// Whether the viewport size changed outside of a reflow while the styles of the document
+    /// used viewport units, so that the next reflow has to recascade them.
+    viewport_units_need_recascade: bool,
+
+    /// The stylesheets that the embedder injects into the user origin of the cascade of
+    /// this document.
+    user_stylesheets: Vec<DocumentStyleSheet>,
+
+    /// Notes changes to the preferences that affect the style of the document.
+    style_preferences_observer: Arc<StylePreferencesObserver>,
+
     /// Scroll offsets of nodes that scroll.
     scroll_offsets: RefCell<HashMap<ExternalScrollId, Vector2D<f32, LayoutPixel>>>,
 
@@ -251,6 +291,62 @@ impl Layout for LayoutThread {
         process_node_geometry_request(node, self.fragment_tree.borrow().clone())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_composition_rect(&self, node: OpaqueNode) -> Option<UntypedRect<Au>> {
+        process_composition_rect_request(node, self.fragment_tree.borrow().clone())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_caret_rect(&self, node: OpaqueNode, offset: usize) -> Option<UntypedRect<Au>> {
+        process_caret_rect_request(self.fragment_tree.borrow().clone(), node, offset)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_caret_in_adjacent_line(
+        &self,
+        node: OpaqueNode,
+        offset: usize,
+        direction: CaretLineDirection,
+    ) -> Option<(UntrustedNodeAddress, usize)> {
+        process_caret_in_adjacent_line_request(
+            self.fragment_tree.borrow().clone(),
+            node,
+            offset,
+            direction,
+        )
+        .map(|(node, offset)| (UntrustedNodeAddress::from(node), offset))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_caret_position_from_point(
+        &self,
+        point: UntypedPoint2D<f32>,
+    ) -> Option<(UntrustedNodeAddress, usize)> {
+        let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+        process_caret_position_from_point_request(self.fragment_tree.borrow().clone(), point)
+            .map(|(node, offset)| (UntrustedNodeAddress::from(node), offset))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_range_client_rects(&self, nodes: &[RangeNode]) -> Vec<UntypedRect<Au>> {
+        process_range_client_rects_request(self.fragment_tree.borrow().clone(), nodes)
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
@@ -333,6 +429,7 @@ impl Layout for LayoutThread {
             &pseudo,
             &property_id,
             fragment_tree,
+            self.text_zoom,
         )
     }
 
@@ -380,6 +477,18 @@ impl Layout for LayoutThread {
         process_node_scroll_area_request(node, self.fragment_tree.borrow().clone())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
+    )]
+    fn query_scroll_into_view(&self, node: OpaqueNode) -> Option<ScrollIntoViewGeometry> {
+        process_scroll_into_view_request(node, self.fragment_tree.borrow().clone())
+    }
+
+    fn query_text_control_selection(&self, node: OpaqueNode) -> Option<ScrollIntoViewGeometry> {
+        process_text_control_selection_request(node, self.fragment_tree.borrow().clone())
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(skip_all, fields(servo_profiling = true), level = "trace")
@@ -398,30 +507,43 @@ impl Layout for LayoutThread {
 
     fn exit_now(&mut self) {}
 
-    fn collect_reports(&self, reports: &mut Vec<Report>) {
+    fn collect_reports(&self, path: &[String], reports: &mut Vec<Report>) {
         // Servo uses vanilla jemalloc, which doesn't have a
-        // malloc_enclosing_size_of function.
-        let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
-
-        // TODO: Measure more than just display list, stylist, and font context.
-        let formatted_url = &format!("url({})", self.url);
-        reports.push(Report {
-            path: path![formatted_url, "layout-thread", "display-list"],
-            kind: ReportKind::ExplicitJemallocHeapSize,
-            size: 0,
-        });
-
-        reports.push(Report {
-            path: path![formatted_url, "layout-thread", "stylist"],
-            kind: ReportKind::ExplicitJemallocHeapSize,
-            size: self.stylist.size_of(&mut ops),
-        });
-
-        reports.push(Report {
-            path: path![formatted_url, "layout-thread", "font-context"],
-            kind: ReportKind::ExplicitJemallocHeapSize,
-            size: self.font_context.size_of(&mut ops),
-        });
+        // malloc_enclosing_size_of function. Fragments can be shared, so they are only
+        // measured once.
+        let mut seen_pointers = HashSet::new();
+        let mut ops = MallocSizeOfOps::new(
+            servo_allocator::usable_size,
+            None,
+            Some(Box::new(move |pointer: *const c_void| {
+                !seen_pointers.insert(pointer)
+            })),
+        );
+        let mut report = |mut path_suffix, size| {
+            let mut report_path = path.to_vec();
+            report_path.append(&mut path_suffix);
+            reports.push(Report {
+                path: report_path,
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size,
+                attribution: None,
+            });
+        };
+
+        // TODO: Measure more than just display list, fragment tree, stylist, and font context.
+        report(path!["layout", "display-list"], 0);
+        report(
+            path!["layout", "fragment-tree"],
+            self.fragment_tree
+                .borrow()
+                .as_ref()
+                .map_or(0, |fragment_tree| fragment_tree.size_of(&mut ops)),
+        );
+        report(path!["layout", "stylist"], self.stylist.size_of(&mut ops));
+        report(
+            path!["layout", "font-context"],
+            self.font_context.size_of(&mut ops),
+        );
     }
 
     fn set_quirks_mode(&mut self, quirks_mode: QuirksMode) {
@@ -437,6 +559,44 @@ impl Layout for LayoutThread {
         )
     }
 
+    fn update_media_environment(
+        &mut self,
+        document_shared_lock: &SharedRwLock,
+        window_size: WindowSizeData,
+        theme: PrefersColorScheme,
+        forced_colors: bool,
+        media_type: MediaType,
+    ) {
+        if self.forced_colors != forced_colors {
+            self.forced_colors = forced_colors;
+            self.retained_display_items
+                .borrow_mut()
+                .invalidate(DisplayListInvalidation::All);
+        }
+
+        let had_used_viewport_units = self.stylist.device().used_viewport_units();
+        let viewport_size_changed = self.viewport_did_change(window_size);
+        let theme_changed = self.theme_did_change(theme);
+        let media_type_changed = self.device().media_type() != media_type;
+        if !viewport_size_changed && !theme_changed && !media_type_changed {
+            return;
+        }
+
+        let author_guard = document_shared_lock.read();
+        let ua_or_user_guard = UA_STYLESHEETS.shared_lock.read();
+        let guards = StylesheetGuards {
+            author: &author_guard,
+            ua_or_user: &ua_or_user_guard,
+        };
+        self.update_device(window_size, theme, media_type, &guards);
+        self.retained_display_items
+            .borrow_mut()
+            .invalidate(DisplayListInvalidation::All);
+        if viewport_size_changed && had_used_viewport_units {
+            self.viewport_units_need_recascade = true;
+        }
+    }
+
     fn register_paint_worklet_modules(
         &mut self,
         _name: Atom,
@@ -460,23 +620,17 @@ impl LayoutThread {
             .compositor_api
             .send_initial_transaction(config.id.into());
 
-        let mut font = Font::initial_values();
-        let default_font_size = pref!(fonts_default_size);
-        font.font_size = FontSize {
-            computed_size: NonNegativeLength::new(default_font_size as f32),
-            used_size: NonNegativeLength::new(default_font_size as f32),
-            keyword_info: KeywordInfo::medium(),
-        };
-
         // The device pixel ratio is incorrect (it does not have the hidpi value),
         // but it will be set correctly when the initial reflow takes place.
+        let style_preferences_observer = Arc::new(StylePreferencesObserver::default());
+        prefs::add_observer(Arc::downgrade(&style_preferences_observer));
         let device = Device::new(
             MediaType::screen(),
             QuirksMode::NoQuirks,
             config.window_size.initial_viewport,
             Scale::new(config.window_size.device_pixel_ratio.get()),
             Box::new(LayoutFontMetricsProvider(config.font_context.clone())),
-            ComputedValues::initial_values_with_font_override(font),
+            ComputedValues::initial_values_with_font_override(default_font()),
             // TODO: obtain preferred color scheme from embedder
             PrefersColorScheme::Light,
         );
@@ -494,12 +648,23 @@ impl LayoutThread {
             first_reflow: Cell::new(true),
             box_tree: Default::default(),
             fragment_tree: Default::default(),
+            accessibility_tree: Default::default(),
+            retained_display_items: Default::default(),
             // Epoch starts at 1 because of the initial display list for epoch 0 that we send to WR
             epoch: Cell::new(Epoch(1)),
             viewport_size: Size2D::new(
                 Au::from_f32_px(config.window_size.initial_viewport.width),
                 Au::from_f32_px(config.window_size.initial_viewport.height),
             ),
+            text_zoom: config.window_size.text_zoom,
+            forced_colors: false,
+            viewport_units_need_recascade: false,
+            user_stylesheets: config
+                .user_stylesheets
+                .iter()
+                .map(|source| parse_user_stylesheet(source, &config.url))
+                .collect(),
+            style_preferences_observer,
             compositor_api: config.compositor_api,
             scroll_offsets: Default::default(),
             stylist: Stylist::new(device, QuirksMode::NoQuirks),
@@ -562,6 +727,9 @@ impl LayoutThread {
                 &mut reflow_request.node_to_image_animation_map,
             ))),
             iframe_sizes: Mutex::default(),
+            damaged_nodes: Mutex::default(),
+            text_zoom: self.text_zoom,
+            forced_colors: self.forced_colors,
             use_rayon,
         }
     }
@@ -620,12 +788,33 @@ impl LayoutThread {
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
         let viewport_size_changed = self.viewport_did_change(reflow_request.window_size);
         let theme_changed = self.theme_did_change(reflow_request.theme);
-
-        if viewport_size_changed || theme_changed {
-            self.update_device(reflow_request.window_size, reflow_request.theme, &guards);
+        let media_type_changed = self.device().media_type() != reflow_request.media_type;
+        let text_zoom_changed = self.text_zoom != reflow_request.window_size.text_zoom;
+        self.text_zoom = reflow_request.window_size.text_zoom;
+        if self.forced_colors != reflow_request.forced_colors {
+            self.forced_colors = reflow_request.forced_colors;
+            self.retained_display_items
+                .borrow_mut()
+                .invalidate(DisplayListInvalidation::All);
+        }
+        let default_font_changed =
+            text_zoom_changed || self.style_preferences_observer.take_changed();
+
+        if viewport_size_changed || theme_changed || media_type_changed || default_font_changed {
+            self.update_device(
+                reflow_request.window_size,
+                reflow_request.theme,
+                reflow_request.media_type.clone(),
+                &guards,
+            );
+            self.retained_display_items
+                .borrow_mut()
+                .invalidate(DisplayListInvalidation::All);
         }
 
-        if viewport_size_changed && had_used_viewport_units {
+        let viewport_units_changed = (viewport_size_changed && had_used_viewport_units) ||
+            mem::take(&mut self.viewport_units_need_recascade);
+        if viewport_units_changed || default_font_changed {
             if let Some(mut data) = root_element.mutate_data() {
                 data.hint.insert(RestyleHint::recascade_subtree());
             }
@@ -638,6 +827,12 @@ impl LayoutThread {
                 self.load_all_web_fonts_from_stylesheet_with_guard(stylesheet, &ua_or_user_guard);
             }
 
+            for stylesheet in &self.user_stylesheets {
+                self.stylist
+                    .append_stylesheet(stylesheet.clone(), &ua_or_user_guard);
+                self.load_all_web_fonts_from_stylesheet_with_guard(stylesheet, &ua_or_user_guard);
+            }
+
             if self.stylist.quirks_mode() != QuirksMode::NoQuirks {
                 self.stylist.append_stylesheet(
                     ua_stylesheets.quirks_mode_stylesheet.clone(),
@@ -718,47 +913,91 @@ impl LayoutThread {
             RecalcStyle::pre_traverse(dirty_root, shared)
         };
 
+        let mut layout_changed = false;
+        let mut scroll_anchor_adjustments = Vec::new();
         if token.should_traverse() {
-            #[cfg(feature = "tracing")]
-            let _span =
-                tracing::trace_span!("driver::traverse_dom", servo_profiling = true).entered();
-            let dirty_root: ServoLayoutNode =
-                driver::traverse_dom(&traversal, token, rayon_pool).as_node();
+            let dirty_root: ServoLayoutNode = time_profile!(
+                profile_time::ProfilerCategory::LayoutStyleRecalc,
+                self.profiler_metadata(),
+                self.time_profiler_chan.clone(),
+                || driver::traverse_dom(&traversal, token, rayon_pool).as_node(),
+            );
+
+            // Select the scroll anchors in the previous layout, so that the scroll offsets can
+            // keep them in place in the new one.
+            let scroll_anchors = self
+                .fragment_tree
+                .borrow()
+                .as_ref()
+                .filter(|_| pref!(layout_scroll_anchoring_enabled))
+                .map(|fragment_tree| {
+                    ScrollAnchors::select(
+                        fragment_tree,
+                        &self.scroll_offsets.borrow(),
+                        self.id.into(),
+                    )
+                });
 
             let root_node = root_element.as_node();
             let mut box_tree = self.box_tree.borrow_mut();
             let box_tree = &mut *box_tree;
-            let mut build_box_tree = || {
-                if !BoxTree::update(traversal.context(), dirty_root) {
-                    *box_tree = Some(Arc::new(BoxTree::construct(traversal.context(), root_node)));
-                }
-            };
-            if let Some(pool) = rayon_pool {
-                pool.install(build_box_tree)
-            } else {
-                build_box_tree()
-            };
-
             let viewport_size = Size2D::new(
                 self.viewport_size.width.to_f32_px(),
                 self.viewport_size.height.to_f32_px(),
             );
-            let run_layout = || {
+            let mut run_layout = || {
+                if !BoxTree::update(traversal.context(), dirty_root) {
+                    *box_tree = Some(Arc::new(BoxTree::construct(traversal.context(), root_node)));
+                }
                 box_tree
                     .as_ref()
                     .unwrap()
                     .layout(traversal.context(), viewport_size)
             };
-            let fragment_tree = Arc::new(if let Some(pool) = rayon_pool {
-                pool.install(run_layout)
-            } else {
-                run_layout()
-            });
+            let fragment_tree = Arc::new(time_profile!(
+                profile_time::ProfilerCategory::LayoutMain,
+                self.profiler_metadata(),
+                self.time_profiler_chan.clone(),
+                || if let Some(pool) = rayon_pool {
+                    pool.install(run_layout)
+                } else {
+                    run_layout()
+                },
+            ));
+
+            layout_changed = true;
+            if let Some(scroll_anchors) = scroll_anchors {
+                scroll_anchor_adjustments = scroll_anchors
+                    .adjusted_scroll_offsets(&fragment_tree, &self.scroll_offsets.borrow())
+                    .into_iter()
+                    .map(|(scroll_id, scroll_offset)| ScrollState {
+                        scroll_id,
+                        scroll_offset,
+                    })
+                    .collect();
+            }
             *self.fragment_tree.borrow_mut() = Some(fragment_tree);
         }
 
         layout_context = traversal.destroy();
 
+        // The display items that WebRender retained can only be reused for the nodes that
+        // were not damaged. Animated images change how their nodes are painted without
+        // damaging them.
+        let damaged_nodes = std::mem::take(&mut *layout_context.damaged_nodes.lock());
+        let animated_image_nodes = layout_context
+            .node_image_animation_map
+            .read()
+            .keys()
+            .copied()
+            .collect();
+        {
+            let mut retained_display_items = self.retained_display_items.borrow_mut();
+            retained_display_items
+                .invalidate(DisplayListInvalidation::for_damaged_nodes(damaged_nodes));
+            retained_display_items.invalidate(DisplayListInvalidation::Nodes(animated_image_nodes));
+        }
+
         for element in elements_with_snapshot {
             unsafe { element.unset_snapshot_flags() }
         }
@@ -781,21 +1020,79 @@ impl LayoutThread {
         // GC the rule tree if some heuristics are met.
         layout_context.style_context.stylist.rule_tree().maybe_gc();
 
+        // Search the rendered text for the find-in-page query, so that its matches can be
+        // highlighted in the display list.
+        let find_in_page = reflow_request
+            .find_in_page_query
+            .as_ref()
+            .filter(|_| reflow_request.reflow_goal.needs_display_list())
+            .zip(self.fragment_tree.borrow().clone())
+            .map(|(query, fragment_tree)| FindInPageHighlights::new(&fragment_tree, query));
+
         // Perform post-style recalculation layout passes.
         if let Some(root) = &*self.fragment_tree.borrow() {
             self.perform_post_style_recalc_layout_passes(
                 root.clone(),
                 &reflow_request.reflow_goal,
                 &mut layout_context,
+                find_in_page.as_ref(),
+                reflow_request.caret_browsing_selection.as_ref(),
+                &reflow_request.inspector_highlights,
+                &reflow_request.top_layer,
             );
         }
 
         self.first_reflow.set(false);
 
-        if let ReflowGoal::UpdateScrollNode(scroll_state) = reflow_request.reflow_goal {
-            self.update_scroll_node_state(&scroll_state);
+        if let ReflowGoal::UpdateScrollNode(scroll_state, animation) = reflow_request.reflow_goal {
+            self.update_scroll_node_state(&scroll_state, animation);
+        }
+
+        // Scroll anchoring adjustments are sent after the display list of the new layout,
+        // so that they apply to its scroll tree.
+        for scroll_state in &scroll_anchor_adjustments {
+            self.update_scroll_node_state(scroll_state, ScrollAnimation::Instant);
         }
 
+        // The accessibility tree only changes when the DOM, its style or its layout change,
+        // or when scrolling moves the bounds of its nodes.
+        let accessibility_tree_update = self
+            .fragment_tree
+            .borrow()
+            .as_ref()
+            .filter(|_| pref!(accessibility_enabled))
+            .and_then(|fragment_tree| {
+                self.accessibility_tree.borrow_mut().update(
+                    &layout_context,
+                    root_element.as_node(),
+                    fragment_tree,
+                    &self.scroll_offsets.borrow(),
+                    self.id.into(),
+                    layout_changed,
+                )
+            });
+
+        // Report the widths of images with `sizes="auto"`, which script uses to select their
+        // source without forcing another layout.
+        let auto_sized_image_widths = reflow_request
+            .auto_sized_images
+            .iter()
+            .filter_map(|node| {
+                let content_box =
+                    process_content_box_request(*node, self.fragment_tree.borrow().clone())?;
+                Some((*node, content_box.size.width))
+            })
+            .collect();
+        let svg_root_sizes = reflow_request
+            .svg_roots
+            .iter()
+            .filter_map(|node| {
+                let content_box =
+                    process_content_box_request(*node, self.fragment_tree.borrow().clone())?;
+                Some((*node, content_box.size))
+            })
+            .collect();
+
         let pending_images = std::mem::take(&mut *layout_context.pending_images.lock());
         let iframe_sizes = std::mem::take(&mut *layout_context.iframe_sizes.lock());
         let node_to_image_animation_map =
@@ -804,10 +1101,15 @@ impl LayoutThread {
             pending_images,
             iframe_sizes,
             node_to_image_animation_map,
+            accessibility_tree_update,
+            find_in_page_matches: find_in_page.map(|find_in_page| find_in_page.to_matches()),
+            scroll_anchor_adjustments,
+            auto_sized_image_widths,
+            svg_root_sizes,
         })
     }
 
-    fn update_scroll_node_state(&self, state: &ScrollState) {
+    fn update_scroll_node_state(&self, state: &ScrollState, animation: ScrollAnimation) {
         self.scroll_offsets
             .borrow_mut()
             .insert(state.scroll_id, state.scroll_offset);
@@ -817,6 +1119,7 @@ impl LayoutThread {
             self.id.into(),
             units::LayoutPoint::from_untyped(point),
             state.scroll_id,
+            animation,
         );
     }
 
@@ -825,6 +1128,10 @@ impl LayoutThread {
         fragment_tree: Arc<FragmentTree>,
         reflow_goal: &ReflowGoal,
         context: &mut LayoutContext,
+        find_in_page: Option<&FindInPageHighlights>,
+        caret_browsing_selection: Option<&CaretBrowsingSelection>,
+        inspector_highlights: &[InspectorHighlight],
+        top_layer: &[TopLayerElement],
     ) {
         Self::cancel_animations_for_nodes_not_in_fragment_tree(
             &context.style_context.animations,
@@ -866,14 +1173,35 @@ impl LayoutThread {
             display_list.wr.dump_serialized_display_list();
         }
 
-        // Build the root stacking context. This turns the `FragmentTree` into a
-        // tree of fragments in CSS painting order and also creates all
-        // applicable spatial and clip nodes.
-        let root_stacking_context =
-            display_list.build_stacking_context_tree(&fragment_tree, &self.debug);
+        let root_stacking_context = time_profile!(
+            profile_time::ProfilerCategory::LayoutDispListBuild,
+            self.profiler_metadata(),
+            self.time_profiler_chan.clone(),
+            || {
+                // Build the root stacking context. This turns the `FragmentTree` into a
+                // tree of fragments in CSS painting order and also creates all
+                // applicable spatial and clip nodes.
+                let root_stacking_context = display_list.build_stacking_context_tree(
+                    &fragment_tree,
+                    top_layer,
+                    &self.debug,
+                );
 
-        // Build the rest of the display list which inclues all of the WebRender primitives.
-        display_list.build(context, &fragment_tree, &root_stacking_context);
+                // Build the rest of the display list which inclues all of the WebRender
+                // primitives.
+                display_list.build(
+                    context,
+                    &fragment_tree,
+                    &root_stacking_context,
+                    find_in_page,
+                    caret_browsing_selection,
+                    inspector_highlights,
+                    &mut self.retained_display_items.borrow_mut(),
+                    &self.scroll_offsets.borrow(),
+                );
+                root_stacking_context
+            },
+        );
 
         if self.debug.dump_flow_tree {
             fragment_tree.print();
@@ -977,15 +1305,16 @@ impl LayoutThread {
         &mut self,
         window_size_data: WindowSizeData,
         theme: PrefersColorScheme,
+        media_type: MediaType,
         guards: &StylesheetGuards,
     ) {
         let device = Device::new(
-            MediaType::screen(),
+            media_type,
             self.stylist.quirks_mode(),
             window_size_data.initial_viewport,
             Scale::new(window_size_data.device_pixel_ratio.get()),
             Box::new(LayoutFontMetricsProvider(self.font_context.clone())),
-            self.stylist.device().default_computed_values().to_arc(),
+            ComputedValues::initial_values_with_font_override(default_font()),
             theme,
         );
 
@@ -998,6 +1327,90 @@ impl LayoutThread {
     }
 }
 
+/// Notes that preferences that affect the style of documents, like the default fonts,
+/// changed at runtime, so that the next reflow restyles the document with them.
+#[derive(Default)]
+struct StylePreferencesObserver(AtomicBool);
+
+impl StylePreferencesObserver {
+    fn take_changed(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl PreferencesObserver for StylePreferencesObserver {
+    fn prefs_changed(&self, changes: &[PreferenceChange]) {
+        if changes
+            .iter()
+            .any(|(name, _)| name.starts_with("fonts_") || name.starts_with("layout_"))
+        {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The initial font of documents, whose size is the default font size.
+fn default_font() -> Font {
+    let mut font = Font::initial_values();
+    let default_font_size = pref!(fonts_default_size) as f32;
+    font.font_size = FontSize {
+        computed_size: NonNegativeLength::new(default_font_size),
+        used_size: NonNegativeLength::new(default_font_size),
+        keyword_info: KeywordInfo::medium(),
+    };
+    font
+}
+
+/// The [user agent style sheet defaults] of the Fullscreen API. Fullscreen elements are in the
+/// top layer, which layout paints above all other content over a black `::backdrop`.
+///
+/// [user agent style sheet defaults]: https://fullscreen.spec.whatwg.org/#user-agent-level-style-sheet-defaults
+const FULLSCREEN_CSS: &str = r#"
+@namespace "http://www.w3.org/1999/xhtml";
+
+*|*:not(:root):fullscreen {
+  position: fixed !important;
+  inset: 0 !important;
+  margin: 0 !important;
+  box-sizing: border-box !important;
+  min-width: 0 !important;
+  max-width: none !important;
+  min-height: 0 !important;
+  max-height: none !important;
+  width: 100% !important;
+  height: 100% !important;
+  transform: none !important;
+
+  /* intentionally not !important */
+  object-fit: contain;
+}
+
+iframe:fullscreen {
+  border: none !important;
+  padding: 0 !important;
+}
+"#;
+
+/// The [user agent style sheet defaults] of modal dialogs, and the styles that make inert
+/// subtrees transparent to hit testing. Like fullscreen elements, modal dialogs are in the top
+/// layer, and their translucent `::backdrop` is painted by layout.
+///
+/// [user agent style sheet defaults]: https://html.spec.whatwg.org/multipage/#flow-content-3
+const INERT_CSS: &str = r#"
+@namespace "http://www.w3.org/1999/xhtml";
+
+dialog:modal {
+  position: fixed;
+  inset-block: 0;
+  max-width: calc(100% - 6px - 2em);
+  max-height: calc(100% - 6px - 2em);
+}
+
+*|*[inert], *|*[inert] *|* {
+  pointer-events: none !important;
+}
+"#;
+
 fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
     fn parse_ua_stylesheet(
         shared_lock: &SharedRwLock,
@@ -1041,6 +1454,8 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
             "presentational-hints.css",
             &resources::read_bytes(Resource::PresentationalHintsCSS),
         )?,
+        parse_ua_stylesheet(shared_lock, "fullscreen.css", FULLSCREEN_CSS.as_bytes())?,
+        parse_ua_stylesheet(shared_lock, "inert.css", INERT_CSS.as_bytes())?,
     ];
 
     for (contents, url) in &opts::get().user_stylesheets {
@@ -1073,6 +1488,23 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
     })
 }
 
+/// Parse a stylesheet that the embedder injects into the document at `url`, in the user
+/// origin of the cascade.
+fn parse_user_stylesheet(source: &str, url: &ServoUrl) -> DocumentStyleSheet {
+    DocumentStyleSheet(ServoArc::new(Stylesheet::from_bytes(
+        source.as_bytes(),
+        UrlExtraData(url.get_arc()),
+        None,
+        None,
+        Origin::User,
+        MediaList::empty(),
+        UA_STYLESHEETS.shared_lock.clone(),
+        None,
+        Some(&RustLogReporter),
+        QuirksMode::NoQuirks,
+    )))
+}
+
 static UA_STYLESHEETS: LazyLock<UserAgentStylesheets> =
     LazyLock::new(|| match get_ua_stylesheets() {
         Ok(stylesheets) => stylesheets,
@@ -0,0 +1,270 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Privileged `about:` pages showing diagnostic information about Servo.
+//!
+//! Unlike `about:blank`, these pages are generated here from live data: the memory reports
+//! of every process (`about:memory`), the current preferences, which can be edited
+//! (`about:config`) and the panics that happened since startup (`about:crashes`). They can
+//! only be navigated to, and the constellation makes sure that web content can't navigate
+//! to them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Local};
+use embedder_traits::CrashReport;
+use headers::{ContentType, HeaderMapExt};
+use ipc_channel::ipc;
+use log::warn;
+use net_traits::request::{Request, RequestMode};
+use net_traits::response::{Response, ResponseBody};
+use net_traits::{NetworkError, ResourceFetchTiming};
+use profile_traits::mem::{ProfilerChan as MemProfilerChan, ProfilerMsg, Report, ReportKind};
+use servo_config::prefs::{self, PrefValue};
+use servo_url::ServoUrl;
+
+/// The channel used to ask the memory profiler for reports, set when the resource threads
+/// are created.
+static MEMORY_PROFILER: OnceLock<MemProfilerChan> = OnceLock::new();
+
+/// The panics that happened since startup, oldest first.
+static CRASH_REPORTS: Mutex<Vec<CrashReport>> = Mutex::new(Vec::new());
+
+/// Whether `url` is one of the privileged `about:` pages, which only the user or another
+/// privileged page can navigate to.
+pub fn is_privileged_url(url: &ServoUrl) -> bool {
+    url.scheme() == "about" && matches!(url.path(), "memory" | "config" | "crashes")
+}
+
+/// Add a panic to the list shown in `about:crashes`.
+pub fn record_crash(report: CrashReport) {
+    CRASH_REPORTS.lock().unwrap().push(report);
+}
+
+/// The panics that happened since startup, oldest first.
+pub fn crash_reports() -> Vec<CrashReport> {
+    CRASH_REPORTS.lock().unwrap().clone()
+}
+
+pub(crate) fn set_memory_profiler(mem_profiler_chan: MemProfilerChan) {
+    // Only the first set of resource threads is used to serve these pages.
+    let _ = MEMORY_PROFILER.set(mem_profiler_chan);
+}
+
+/// Generate the privileged `about:` page at `url`.
+pub(crate) async fn fetch(request: &Request, url: ServoUrl) -> Response {
+    if request.mode != RequestMode::Navigate {
+        return Response::network_error(NetworkError::Internal(
+            "Privileged about: pages can only be navigated to".into(),
+        ));
+    }
+
+    let body = match url.path() {
+        "memory" => memory_page().await,
+        "config" => config_page(),
+        "crashes" => crashes_page(),
+        _ => return Response::network_error(NetworkError::Internal("Unknown about: page".into())),
+    };
+
+    let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+    response.headers.typed_insert(ContentType::html());
+    *response.body.lock().unwrap() = ResponseBody::Done(body.into_bytes());
+    response
+}
+
+/// Ask the memory profiler for the reports of every process. This blocks until all of the
+/// reporters have answered, so it happens off the async runtime's worker threads.
+async fn memory_reports() -> Option<Vec<Report>> {
+    let mem_profiler_chan = MEMORY_PROFILER.get()?.clone();
+    let (sender, receiver) = ipc::channel().ok()?;
+    mem_profiler_chan.send(ProfilerMsg::Report(sender));
+    let result = tokio::task::spawn_blocking(move || receiver.recv())
+        .await
+        .ok()?
+        .ok()?;
+    serde_json::from_str(&result.content)
+        .inspect_err(|error| warn!("Could not parse memory report: {error}"))
+        .ok()
+}
+
+/// Explicit memory reports, arranged by path so that each node adds up the sizes of the
+/// reports under it. Since the paths of web content start with their origin and pipeline,
+/// this shows how much memory each of them uses.
+#[derive(Default)]
+struct MemoryTree {
+    size: usize,
+    children: BTreeMap<String, MemoryTree>,
+}
+
+impl MemoryTree {
+    fn insert(&mut self, path: &[String], size: usize) {
+        self.size += size;
+        if let Some((first, rest)) = path.split_first() {
+            self.children
+                .entry(first.clone())
+                .or_default()
+                .insert(rest, size);
+        }
+    }
+
+    fn write(&self, page: &mut String, name: &str) {
+        let _ = write!(
+            page,
+            "<li><span class=size>{}</span> {}",
+            format_size(self.size),
+            escape_html(name)
+        );
+        if !self.children.is_empty() {
+            let mut children: Vec<_> = self.children.iter().collect();
+            children.sort_by(|(_, a), (_, b)| b.size.cmp(&a.size));
+            page.push_str("<ul>");
+            for (name, child) in children {
+                child.write(page, name);
+            }
+            page.push_str("</ul>");
+        }
+        page.push_str("</li>");
+    }
+}
+
+async fn memory_page() -> String {
+    let mut page = page_header("Memory");
+    let Some(mut reports) = memory_reports().await else {
+        page.push_str("<p>Memory reports are not available.</p>");
+        return page;
+    };
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut explicit = MemoryTree::default();
+    let mut other = vec![];
+    for report in reports {
+        match report.kind {
+            ReportKind::NonExplicitSize => other.push(report),
+            _ => explicit.insert(&report.path, report.size),
+        }
+    }
+
+    page.push_str("<h2>Explicit allocations</h2><ul>");
+    explicit.write(&mut page, "explicit");
+    page.push_str("</ul><h2>Other measurements</h2><table>");
+    for report in other {
+        let _ = write!(
+            page,
+            "<tr><td>{}</td><td class=size>{}</td></tr>",
+            escape_html(&report.path.join("/")),
+            format_size(report.size),
+        );
+    }
+    page.push_str("</table>");
+    page
+}
+
+fn config_page() -> String {
+    let mut page = page_header("Preferences");
+    page.push_str("<table><tr><th>Name</th><th>Value</th></tr>");
+    for (name, value) in prefs::get().values() {
+        let input = match value {
+            PrefValue::Bool(value) => format!(
+                "<input type=checkbox data-pref={name}{}>",
+                if value { " checked" } else { "" }
+            ),
+            PrefValue::Int(value) => {
+                format!("<input type=number step=1 data-pref={name} value={value}>")
+            },
+            PrefValue::Float(value) => {
+                format!("<input type=number step=any data-pref={name} value={value}>")
+            },
+            PrefValue::Str(value) => format!(
+                "<input type=text data-pref={name} value=\"{}\">",
+                escape_html(&value)
+            ),
+            // Arrays can't be edited from this page.
+            PrefValue::Array(_) => escape_html(&format!("{value:?}")),
+        };
+        let _ = write!(page, "<tr><td>{name}</td><td>{input}</td></tr>");
+    }
+    page.push_str("</table>");
+    page.push_str(
+        "<script>
+for (const input of document.querySelectorAll('input[data-pref]')) {
+    input.addEventListener('change', () => {
+        const value = input.type == 'checkbox' ? String(input.checked) : input.value;
+        navigator.servo.setPreference(input.dataset.pref, value);
+    });
+}
+</script>",
+    );
+    page
+}
+
+fn crashes_page() -> String {
+    let mut page = page_header("Crashes");
+    let reports = crash_reports();
+    if reports.is_empty() {
+        page.push_str("<p>No crashes have happened since startup.</p>");
+        return page;
+    }
+
+    for report in reports.iter().rev() {
+        let _ = write!(
+            page,
+            "<section><h2>{}</h2><p>{}</p><p>{}</p><pre>{}</pre>",
+            DateTime::<Local>::from(report.time).format("%F %r"),
+            report
+                .pipeline_id
+                .map(|pipeline_id| pipeline_id.to_string())
+                .unwrap_or_default(),
+            report
+                .url
+                .as_ref()
+                .map(|url| escape_html(url.as_str()))
+                .unwrap_or_default(),
+            escape_html(&report.reason),
+        );
+        if let Some(backtrace) = &report.backtrace {
+            let _ = write!(
+                page,
+                "<details><summary>Backtrace</summary><pre>{}</pre></details>",
+                escape_html(backtrace)
+            );
+        }
+        page.push_str("</section>");
+    }
+    page
+}
+
+fn page_header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=utf-8><title>{title}</title>\
+         <style>\
+         body {{ font-family: sans-serif; }} \
+         table {{ border-collapse: collapse; }} \
+         td, th {{ padding: 2px 8px; text-align: left; border-bottom: 1px solid #ddd; }} \
+         .size {{ text-align: right; font-family: monospace; }}\
+         li .size {{ display: inline-block; min-width: 10em; }}\
+         </style></head><body><h1>{title}</h1>"
+    )
+}
+
+fn format_size(size: usize) -> String {
+    const MEBIBYTE: f64 = 1024.0 * 1024.0;
+    format!("{:.2} MiB", size as f64 / MEBIBYTE)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
@@ -4,7 +4,10 @@
 
 use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::io;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use futures::Future;
 use futures::task::{Context, Poll};
@@ -14,38 +17,136 @@ use hyper::body::Bytes;
 use hyper::rt::Executor;
 use hyper_rustls::HttpsConnector as HyperRustlsHttpsConnector;
 use hyper_util::client::legacy::Client;
-use hyper_util::client::legacy::connect::HttpConnector as HyperHttpConnector;
+use hyper_util::client::legacy::connect::{
+    Connected, Connection, HttpConnector as HyperHttpConnector,
+};
+use hyper_util::rt::TokioIo;
 use log::warn;
 use rustls::client::WebPkiServerVerifier;
 use rustls::{ClientConfig, RootCertStore};
 use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio::net::TcpStream;
 use tower_service::Service;
 
 use crate::async_runtime::HANDLE;
 use crate::hosts::replace_host;
+use crate::proxy::{Proxy, ProxyKind, connect_through_proxy, connect_to_proxy, proxy_for};
+use crate::resolver::ServoResolver;
 
 pub const BUF_SIZE: usize = 32768;
 
+/// How long to wait for a connection to an address of the preferred family before racing
+/// it with a connection to an address of the other family.
+/// <https://www.rfc-editor.org/rfc/rfc8305#section-5>
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
 #[derive(Clone)]
 pub struct ServoHttpConnector {
-    inner: HyperHttpConnector,
+    inner: HyperHttpConnector<ServoResolver>,
 }
 
 impl ServoHttpConnector {
-    fn new() -> ServoHttpConnector {
-        let mut inner = HyperHttpConnector::new();
+    fn new(resolver: ServoResolver) -> ServoHttpConnector {
+        let mut inner = HyperHttpConnector::new_with_resolver(resolver);
         inner.enforce_http(false);
-        inner.set_happy_eyeballs_timeout(None);
+        inner.set_happy_eyeballs_timeout(Some(CONNECTION_ATTEMPT_DELAY));
         ServoHttpConnector { inner }
     }
 }
 
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A connection made by [ServoHttpConnector], either to the server or to a proxy.
+pub struct ServoConnection {
+    inner: TokioIo<TcpStream>,
+    /// Whether requests are sent to an HTTP proxy that forwards them, rather than through a
+    /// tunnel, in which case their URIs are absolute.
+    is_http_proxy: bool,
+}
+
+impl Connection for ServoConnection {
+    fn connected(&self) -> Connected {
+        self.inner.connected().proxy(self.is_http_proxy)
+    }
+}
+
+impl hyper::rt::Read for ServoConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: hyper::rt::ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl hyper::rt::Write for ServoConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+    }
+}
+
+/// Connect to `dest` through `proxy`. Plain HTTP requests are forwarded by HTTP proxies,
+/// and the other connections are tunneled through the proxy.
+async fn connect_with_proxy(proxy: Proxy, dest: Destination) -> Result<ServoConnection, BoxError> {
+    let is_https = dest.scheme_str() == Some("https");
+    let host = dest
+        .host()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = dest.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+
+    let is_http_proxy = proxy.kind == ProxyKind::Http && !is_https;
+    let stream = if is_http_proxy {
+        connect_to_proxy(&proxy).await?
+    } else {
+        connect_through_proxy(&proxy, host, port).await?
+    };
+    stream.set_nodelay(true)?;
+    Ok(ServoConnection {
+        inner: TokioIo::new(stream),
+        is_http_proxy,
+    })
+}
+
 impl Service<Destination> for ServoHttpConnector {
-    type Response = <HyperHttpConnector as Service<Destination>>::Response;
-    type Error = <HyperHttpConnector as Service<Destination>>::Error;
-    type Future = <HyperHttpConnector as Service<Destination>>::Future;
+    type Response = ServoConnection;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<ServoConnection, BoxError>> + Send>>;
 
     fn call(&mut self, dest: Destination) -> Self::Future {
+        let proxy = dest
+            .host()
+            .and_then(|host| proxy_for(dest.scheme_str().unwrap_or_default(), host));
+        if let Some(proxy) = proxy {
+            return Box::pin(connect_with_proxy(proxy, dest));
+        }
+
         // Perform host replacement when making the actual TCP connection.
         let mut new_dest = dest.clone();
         let mut parts = dest.into_parts();
@@ -68,7 +169,13 @@ impl Service<Destination> for ServoHttpConnector {
             }
         }
 
-        self.inner.call(new_dest)
+        let connecting = self.inner.call(new_dest);
+        Box::pin(async move {
+            Ok(ServoConnection {
+                inner: connecting.await?,
+                is_http_proxy: false,
+            })
+        })
     }
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -268,13 +375,23 @@ impl rustls::client::danger::ServerCertVerifier for CertificateVerificationOverr
 
 pub type BoxedBody = BoxBody<Bytes, hyper::Error>;
 
+/// The body of a response, which may have been received by hyper or over HTTP/3.
+pub type BoxedResponseBody = BoxBody<Bytes, BoxError>;
+
 pub fn create_http_client(tls_config: TlsConfig) -> Client<Connector, BoxedBody> {
+    create_http_client_with_resolver(tls_config, ServoResolver::new())
+}
+
+pub(crate) fn create_http_client_with_resolver(
+    tls_config: TlsConfig,
+    resolver: ServoResolver,
+) -> Client<Connector, BoxedBody> {
     let connector = hyper_rustls::HttpsConnectorBuilder::new()
         .with_tls_config(tls_config)
         .https_or_http()
         .enable_http1()
         .enable_http2()
-        .wrap_connector(ServoHttpConnector::new());
+        .wrap_connector(ServoHttpConnector::new(resolver));
 
     Client::builder(TokioExecutor {})
         .http1_title_case_headers(true)
@@ -30,6 +30,11 @@ pub struct ServoCookie {
     pub creation_time: SystemTime,
     pub last_access: SystemTime,
     pub expiry_time: Option<SystemTime>,
+    /// The site of the top-level document that a cookie with the `Partitioned` attribute was
+    /// set in, which is the only top-level site that the cookie is sent in.
+    /// <https://github.com/privacycg/CHIPS>
+    #[serde(default)]
+    pub partition_key: Option<String>,
 }
 
 impl ServoCookie {
@@ -212,6 +217,12 @@ impl ServoCookie {
             }
         }
 
+        // Partitioned cookies must be secure.
+        // <https://github.com/privacycg/CHIPS#opt-in-cross-site-cookies>
+        if cookie.partitioned().unwrap_or(false) && !secure_only {
+            return None;
+        }
+
         // Step 22. If the cookie-name is empty and either of the following conditions are true,
         // abort these steps and ignore the cookie entirely:
         if cookie.name().is_empty() {
@@ -233,6 +244,7 @@ impl ServoCookie {
             creation_time: SystemTime::now(),
             last_access: SystemTime::now(),
             expiry_time,
+            partition_key: None,
         })
     }
 
@@ -12,7 +12,7 @@ use std::time::SystemTime;
 
 use log::{debug, info};
 use net_traits::CookieSource;
-use net_traits::pub_domains::reg_suffix;
+use net_traits::pub_domains::{reg_suffix, site};
 use serde::{Deserialize, Serialize};
 use servo_url::ServoUrl;
 
@@ -25,6 +25,34 @@ pub struct CookieStorage {
     max_per_host: usize,
 }
 
+/// The cookies that a request or document may access, which depend on the site of its top-level
+/// document and on the cookie policy. <https://github.com/privacycg/CHIPS>
+#[derive(Clone, Debug, PartialEq)]
+pub struct CookiePartition {
+    /// The key of the partitioned cookies that may be accessed, which is the site of the
+    /// top-level document. When it has no site, no partitioned cookies may be accessed.
+    pub key: Option<String>,
+    /// Whether cookies without the `Partitioned` attribute may be accessed.
+    pub allow_unpartitioned: bool,
+}
+
+impl CookiePartition {
+    /// The partition of a top-level document at `url`, which may access all of its cookies.
+    pub fn first_party(url: &ServoUrl) -> Self {
+        Self {
+            key: site(&url.origin()),
+            allow_unpartitioned: true,
+        }
+    }
+
+    fn allows(&self, cookie: &ServoCookie) -> bool {
+        match cookie.partition_key {
+            Some(ref key) => self.key.as_ref() == Some(key),
+            None => self.allow_unpartitioned,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum RemoveCookieError {
     Overlapping,
@@ -73,7 +101,8 @@ impl CookieStorage {
 
         // Step 11.1
         let position = cookies.iter().position(|c| {
-            c.cookie.domain() == cookie.cookie.domain() &&
+            c.partition_key == cookie.partition_key &&
+                c.cookie.domain() == cookie.cookie.domain() &&
                 c.cookie.path() == cookie.cookie.path() &&
                 c.cookie.name() == cookie.cookie.name()
         });
@@ -103,7 +132,29 @@ impl CookieStorage {
     }
 
     // http://tools.ietf.org/html/rfc6265#section-5.3
-    pub fn push(&mut self, mut cookie: ServoCookie, url: &ServoUrl, source: CookieSource) {
+    pub fn push(&mut self, cookie: ServoCookie, url: &ServoUrl, source: CookieSource) {
+        self.push_in_partition(cookie, url, source, &CookiePartition::first_party(url));
+    }
+
+    /// Store a cookie set in `partition`, which keys it by the partition when it has the
+    /// `Partitioned` attribute, and ignores it when it doesn't and the partition can't access
+    /// unpartitioned cookies.
+    pub fn push_in_partition(
+        &mut self,
+        mut cookie: ServoCookie,
+        url: &ServoUrl,
+        source: CookieSource,
+        partition: &CookiePartition,
+    ) {
+        if cookie.cookie.partitioned().unwrap_or(false) {
+            let Some(key) = partition.key.clone() else {
+                return;
+            };
+            cookie.partition_key = Some(key);
+        } else if !partition.allow_unpartitioned {
+            return;
+        }
+
         // https://www.ietf.org/id/draft-ietf-httpbis-cookie-alone-01.txt Step 1
         if cookie.cookie.secure().unwrap_or(false) && !url.is_secure_scheme() {
             return;
@@ -164,6 +215,16 @@ impl CookieStorage {
 
     // http://tools.ietf.org/html/rfc6265#section-5.4
     pub fn cookies_for_url(&mut self, url: &ServoUrl, source: CookieSource) -> Option<String> {
+        self.cookies_for_url_in_partition(url, source, &CookiePartition::first_party(url))
+    }
+
+    /// The cookies for `url` that `partition` may access, serialized for a `Cookie` header.
+    pub fn cookies_for_url_in_partition(
+        &mut self,
+        url: &ServoUrl,
+        source: CookieSource,
+        partition: &CookiePartition,
+    ) -> Option<String> {
         let filterer = |c: &&mut ServoCookie| -> bool {
             debug!(
                 " === SENT COOKIE : {} {} {:?} {:?}",
@@ -177,7 +238,7 @@ impl CookieStorage {
                 c.appropriate_for_url(url, source)
             );
             // Step 1
-            c.appropriate_for_url(url, source)
+            c.appropriate_for_url(url, source) && partition.allows(c)
         };
         // Step 2
         let domain = reg_host(url.host_str().unwrap_or(""));
@@ -214,10 +275,11 @@ impl CookieStorage {
     ) -> impl Iterator<Item = cookie::Cookie<'static>> + 'a {
         let domain = reg_host(url.host_str().unwrap_or(""));
         let cookies = self.cookies_map.entry(domain).or_default();
+        let partition = CookiePartition::first_party(url);
 
         cookies
             .iter_mut()
-            .filter(move |c| c.appropriate_for_url(url, source))
+            .filter(move |c| c.appropriate_for_url(url, source) && partition.allows(c))
             .map(|c| {
                 c.touch();
                 c.cookie.clone()
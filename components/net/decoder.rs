@@ -13,7 +13,9 @@ The decompressed bytes aren't guaranteed to align to the compressed ones.
 If the response is plaintext then no additional work is carried out.
 Bytes are just passed along.
 
-If the response is gzip, deflate or brotli then the bytes are decompressed.
+If the response is gzip, deflate, brotli or zstd then the bytes are decompressed, and
+decoding fails once more bytes than `network_http_decoded_body_size_limit_mb` were
+decompressed, which protects against decompression bombs.
 */
 
 use std::error::Error;
@@ -21,7 +23,7 @@ use std::fmt;
 use std::io::{self};
 use std::pin::Pin;
 
-use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder};
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
 use bytes::Bytes;
 use futures::stream::Peekable;
 use futures::task::{Context, Poll};
@@ -32,10 +34,11 @@ use http_body_util::BodyExt;
 use hyper::Response;
 use hyper::body::Body;
 use hyper::header::{CONTENT_ENCODING, HeaderValue, TRANSFER_ENCODING};
+use servo_config::pref;
 use tokio_util::codec::{BytesCodec, FramedRead};
 use tokio_util::io::StreamReader;
 
-use crate::connector::BoxedBody;
+use crate::connector::BoxedResponseBody;
 
 pub const DECODER_BUFFER_SIZE: usize = 8192;
 
@@ -44,6 +47,10 @@ pub const DECODER_BUFFER_SIZE: usize = 8192;
 /// The inner decoder may be constructed asynchronously.
 pub struct Decoder {
     inner: Inner,
+    /// The number of bytes that were decompressed so far.
+    decoded_size: u64,
+    /// The number of bytes that may be decompressed, if there is a limit.
+    max_decoded_size: Option<u64>,
 }
 
 #[derive(PartialEq)]
@@ -51,6 +58,7 @@ enum DecoderType {
     Gzip,
     Brotli,
     Deflate,
+    Zstd,
 }
 
 enum Inner {
@@ -62,6 +70,8 @@ enum Inner {
     Deflate(FramedRead<ZlibDecoder<StreamReader<Peekable<BodyStream>, Bytes>>, BytesCodec>),
     /// A `Brotli` decoder will uncompress the brotli-encoded response content before returning it.
     Brotli(FramedRead<BrotliDecoder<StreamReader<Peekable<BodyStream>, Bytes>>, BytesCodec>),
+    /// A `Zstd` decoder will uncompress the zstd-encoded response content before returning it.
+    Zstd(FramedRead<ZstdDecoder<StreamReader<Peekable<BodyStream>, Bytes>>, BytesCodec>),
     /// A decoder that doesn't have a value yet.
     Pending(Pending),
 }
@@ -84,12 +94,14 @@ impl Decoder {
     /// This decoder will emit the underlying bytes as-is.
     #[inline]
     fn plain_text(
-        body: BoxedBody,
+        body: BoxedResponseBody,
         is_secure_scheme: bool,
         content_length: Option<ContentLength>,
     ) -> Decoder {
         Decoder {
             inner: Inner::PlainText(BodyStream::new(body, is_secure_scheme, content_length)),
+            decoded_size: 0,
+            max_decoded_size: None,
         }
     }
 
@@ -98,16 +110,19 @@ impl Decoder {
     /// This decoder will buffer and decompress bytes that are encoded in the expected format.
     #[inline]
     fn pending(
-        body: BoxedBody,
+        body: BoxedResponseBody,
         type_: DecoderType,
         is_secure_scheme: bool,
         content_length: Option<ContentLength>,
+        max_decoded_size: Option<u64>,
     ) -> Decoder {
         Decoder {
             inner: Inner::Pending(Pending {
                 body: BodyStream::new(body, is_secure_scheme, content_length).peekable(),
                 type_,
             }),
+            decoded_size: 0,
+            max_decoded_size,
         }
     }
 
@@ -117,7 +132,25 @@ impl Decoder {
     /// how to decode the content body of the response.
     ///
     /// Uses the correct variant by inspecting the Content-Encoding header.
-    pub fn detect(response: Response<BoxedBody>, is_secure_scheme: bool) -> Response<Decoder> {
+    pub fn detect(
+        response: Response<BoxedResponseBody>,
+        is_secure_scheme: bool,
+    ) -> Response<Decoder> {
+        let max_decoded_size_mb = pref!(network_http_decoded_body_size_limit_mb);
+        Decoder::detect_with_max_decoded_size(
+            response,
+            is_secure_scheme,
+            (max_decoded_size_mb > 0).then(|| max_decoded_size_mb as u64 * 1024 * 1024),
+        )
+    }
+
+    /// Like [`Decoder::detect`], with a limit on the number of bytes that a compressed body
+    /// may be decompressed to instead of the one that the preference sets.
+    pub fn detect_with_max_decoded_size(
+        response: Response<BoxedResponseBody>,
+        is_secure_scheme: bool,
+        max_decoded_size: Option<u64>,
+    ) -> Response<Decoder> {
         let values = response
             .headers()
             .get_all(CONTENT_ENCODING)
@@ -131,6 +164,8 @@ impl Decoder {
                     Some(DecoderType::Brotli)
                 } else if enc == HeaderValue::from_static("deflate") {
                     Some(DecoderType::Deflate)
+                } else if enc == HeaderValue::from_static("zstd") {
+                    Some(DecoderType::Zstd)
                 } else {
                     None
                 }
@@ -138,14 +173,31 @@ impl Decoder {
         });
         let content_length = response.headers().typed_get::<ContentLength>();
         match decoder {
-            Some(type_) => {
-                response.map(|r| Decoder::pending(r, type_, is_secure_scheme, content_length))
-            },
+            Some(type_) => response.map(|r| {
+                Decoder::pending(r, type_, is_secure_scheme, content_length, max_decoded_size)
+            }),
             None => response.map(|r| Decoder::plain_text(r, is_secure_scheme, content_length)),
         }
     }
 }
 
+impl Decoder {
+    /// Count decompressed bytes towards the limit, failing once it is exceeded.
+    fn check_decoded_size(&mut self, bytes: Bytes) -> Poll<Option<Result<Bytes, io::Error>>> {
+        self.decoded_size += bytes.len() as u64;
+        if self
+            .max_decoded_size
+            .is_some_and(|max_decoded_size| self.decoded_size > max_decoded_size)
+        {
+            return Poll::Ready(Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Decoded response body exceeds the size limit",
+            ))));
+        }
+        Poll::Ready(Some(Ok(bytes)))
+    }
+}
+
 impl Stream for Decoder {
     type Item = Result<Bytes, io::Error>;
 
@@ -163,21 +215,28 @@ impl Stream for Decoder {
             Inner::PlainText(ref mut body) => Pin::new(body).poll_next(cx),
             Inner::Gzip(ref mut decoder) => {
                 match futures_core::ready!(Pin::new(decoder).poll_next(cx)) {
-                    Some(Ok(bytes)) => Poll::Ready(Some(Ok(bytes.freeze()))),
+                    Some(Ok(bytes)) => self.check_decoded_size(bytes.freeze()),
                     Some(Err(err)) => Poll::Ready(Some(Err(err))),
                     None => Poll::Ready(None),
                 }
             },
             Inner::Brotli(ref mut decoder) => {
                 match futures_core::ready!(Pin::new(decoder).poll_next(cx)) {
-                    Some(Ok(bytes)) => Poll::Ready(Some(Ok(bytes.freeze()))),
+                    Some(Ok(bytes)) => self.check_decoded_size(bytes.freeze()),
                     Some(Err(err)) => Poll::Ready(Some(Err(err))),
                     None => Poll::Ready(None),
                 }
             },
             Inner::Deflate(ref mut decoder) => {
                 match futures_core::ready!(Pin::new(decoder).poll_next(cx)) {
-                    Some(Ok(bytes)) => Poll::Ready(Some(Ok(bytes.freeze()))),
+                    Some(Ok(bytes)) => self.check_decoded_size(bytes.freeze()),
+                    Some(Err(err)) => Poll::Ready(Some(Err(err))),
+                    None => Poll::Ready(None),
+                }
+            },
+            Inner::Zstd(ref mut decoder) => {
+                match futures_core::ready!(Pin::new(decoder).poll_next(cx)) {
+                    Some(Ok(bytes)) => self.check_decoded_size(bytes.freeze()),
                     Some(Err(err)) => Poll::Ready(Some(Err(err))),
                     None => Poll::Ready(None),
                 }
@@ -223,12 +282,17 @@ impl Future for Pending {
                 BytesCodec::new(),
                 DECODER_BUFFER_SIZE,
             )))),
+            DecoderType::Zstd => Poll::Ready(Ok(Inner::Zstd(FramedRead::with_capacity(
+                ZstdDecoder::new(StreamReader::new(body)),
+                BytesCodec::new(),
+                DECODER_BUFFER_SIZE,
+            )))),
         }
     }
 }
 
 struct BodyStream {
-    body: BoxedBody,
+    body: BoxedResponseBody,
     is_secure_scheme: bool,
     content_length: Option<ContentLength>,
     total_read: u64,
@@ -246,7 +310,11 @@ impl BodyStream {
         }
     }
 
-    fn new(body: BoxedBody, is_secure_scheme: bool, content_length: Option<ContentLength>) -> Self {
+    fn new(
+        body: BoxedResponseBody,
+        is_secure_scheme: bool,
+        content_length: Option<ContentLength>,
+    ) -> Self {
         BodyStream {
             body,
             is_secure_scheme,
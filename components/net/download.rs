@@ -0,0 +1,605 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The download manager, which writes navigation responses that are not displayed as documents
+//! to disk. Downloads report their progress to the embedder, which can pause, resume, and
+//! cancel them. Resuming a download asks the server for the rest of the file with a range
+//! request, falling back to starting over when the server doesn't support one. Navigations
+//! hand their response over while it arrives, so it is only fetched again to resume it.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base::id::WebViewId;
+use embedder_traits::{
+    DownloadControl, DownloadId, DownloadInfo, DownloadState, EmbedderMsg, EmbedderProxy,
+};
+use http::header::{
+    ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use http::{HeaderMap, HeaderValue};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use log::warn;
+use net_traits::FetchTaskTarget;
+use net_traits::content_disposition;
+use net_traits::request::{CacheMode, RedirectMode, Request, RequestBuilder, RequestMode};
+use net_traits::response::Response;
+use servo_config::pref;
+use servo_url::ServoUrl;
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+use crate::fetch::methods::{CancellationListener, FetchContext, fetch};
+
+/// The minimum time between two progress reports for a download.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The longest file name, in bytes, that downloads are saved with.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+static NEXT_DOWNLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Download the response to `request_builder`, building a new [`FetchContext`] for every
+/// attempt with `new_context`. The download runs until it completes, fails, or is cancelled.
+pub(crate) async fn download(
+    request_builder: RequestBuilder,
+    embedder_proxy: EmbedderProxy,
+    new_context: impl Fn(Arc<CancellationListener>) -> FetchContext + Send,
+) {
+    if let Some(download) = Download::new(&request_builder, embedder_proxy) {
+        download.run(request_builder, new_context, true).await;
+    }
+}
+
+/// A download, along with the controls that the embedder sends it.
+pub(crate) struct Download {
+    target: DownloadTarget,
+    controls: UnboundedReceiver<DownloadControl>,
+    /// The listener that cancels the current attempt, which is shared with the route that
+    /// receives the controls so that pausing or cancelling stops the attempt right away.
+    current_attempt: Arc<Mutex<Arc<CancellationListener>>>,
+}
+
+impl Download {
+    fn new(request_builder: &RequestBuilder, embedder_proxy: EmbedderProxy) -> Option<Self> {
+        let (control_sender, control_receiver) = match ipc::channel() {
+            Ok(channel) => channel,
+            Err(error) => {
+                warn!("Failed to create a download control channel: {error}");
+                return None;
+            },
+        };
+        let current_attempt: Arc<Mutex<Arc<CancellationListener>>> = Default::default();
+        let (sender, controls) = unbounded_channel();
+        let attempt = current_attempt.clone();
+        ROUTER.add_typed_route(
+            control_receiver,
+            Box::new(move |message| {
+                let Ok(control) = message else {
+                    return;
+                };
+                if control != DownloadControl::Resume {
+                    attempt.lock().unwrap().cancel();
+                }
+                let _ = sender.send(control);
+            }),
+        );
+
+        Some(Self {
+            target: DownloadTarget::new(request_builder, embedder_proxy, control_sender),
+            controls,
+            current_attempt,
+        })
+    }
+
+    fn start_attempt(&mut self, cancellation_listener: Arc<CancellationListener>) {
+        *self.current_attempt.lock().unwrap() = cancellation_listener.clone();
+        self.target.cancellation_listener = cancellation_listener;
+    }
+
+    /// Run the download until it completes, fails, or is cancelled, building a new
+    /// [`FetchContext`] for every attempt with `new_context`. When `fetch_first` is false, the
+    /// first attempt has already received a response, which was handed over by a navigation.
+    pub(crate) async fn run(
+        mut self,
+        request_builder: RequestBuilder,
+        new_context: impl Fn(Arc<CancellationListener>) -> FetchContext + Send,
+        mut fetch_first: bool,
+    ) {
+        loop {
+            if fetch_first {
+                let cancellation_listener = Arc::new(CancellationListener::default());
+                self.start_attempt(cancellation_listener.clone());
+                let context = new_context(cancellation_listener);
+                let request = self.target.request(&request_builder).build();
+                fetch(request, &mut self.target, &context).await;
+            }
+            fetch_first = true;
+
+            match self.stopping_control() {
+                None => return self.target.finish(),
+                Some(DownloadControl::Cancel) => return self.target.cancel(),
+                Some(DownloadControl::Pause) => {
+                    self.target.pause();
+                    loop {
+                        match self.controls.recv().await {
+                            Some(DownloadControl::Resume) => break,
+                            Some(DownloadControl::Pause) => continue,
+                            Some(DownloadControl::Cancel) | None => return self.target.cancel(),
+                        }
+                    }
+                },
+                Some(DownloadControl::Resume) => {
+                    unreachable!("Resuming is never a stopping control")
+                },
+            }
+        }
+    }
+
+    /// The control that stopped the last attempt, if the embedder paused or cancelled it
+    /// rather than the attempt finishing or failing on its own.
+    fn stopping_control(&mut self) -> Option<DownloadControl> {
+        if self.target.error.is_some() || !self.target.cancellation_listener.cancelled() {
+            return None;
+        }
+        // Resuming a running download is ignored.
+        while let Ok(control) = self.controls.try_recv() {
+            if control != DownloadControl::Resume {
+                return Some(control);
+            }
+        }
+        None
+    }
+}
+
+/// The target of a navigation fetch, which hands the response over to the download manager,
+/// instead of sending its body to the script thread, when it isn't displayed as a document.
+/// The script thread still receives the response, so that it stops waiting for a new
+/// document.
+pub(crate) struct NavigationTarget<Target> {
+    target: Target,
+    /// The request that resumes a download, when the navigation allows downloads.
+    download_request: Option<RequestBuilder>,
+    embedder_proxy: Option<EmbedderProxy>,
+    cancellation_listener: Arc<CancellationListener>,
+    download: Option<Download>,
+}
+
+impl<Target: FetchTaskTarget> NavigationTarget<Target> {
+    /// Wrap `target`, downloading the response with `embedder_proxy` when it is present and
+    /// `request_builder` allows it.
+    pub(crate) fn new(
+        target: Target,
+        request_builder: &RequestBuilder,
+        embedder_proxy: Option<EmbedderProxy>,
+        cancellation_listener: Arc<CancellationListener>,
+    ) -> Self {
+        let download_request = (request_builder.allows_downloads &&
+            request_builder.mode == RequestMode::Navigate)
+            .then(|| request_builder.clone());
+        Self {
+            target,
+            download_request,
+            embedder_proxy,
+            cancellation_listener,
+            download: None,
+        }
+    }
+
+    /// The download that the response was handed over to, if any, with the request that
+    /// resumes it.
+    pub(crate) fn into_download(self) -> Option<(Download, RequestBuilder)> {
+        Some((self.download?, self.download_request?))
+    }
+}
+
+impl<Target: FetchTaskTarget> FetchTaskTarget for NavigationTarget<Target> {
+    fn process_request_body(&mut self, request: &Request) {
+        self.target.process_request_body(request);
+    }
+
+    fn process_request_eof(&mut self, request: &Request) {
+        self.target.process_request_eof(request);
+    }
+
+    fn process_response(&mut self, request: &Request, response: &Response) {
+        let is_download = response
+            .metadata()
+            .is_ok_and(|metadata| metadata.metadata().is_download());
+        if let (true, Some(request_builder), Some(embedder_proxy)) = (
+            is_download,
+            self.download_request.as_ref(),
+            self.embedder_proxy.clone(),
+        ) {
+            if let Some(mut download) = Download::new(request_builder, embedder_proxy) {
+                download.start_attempt(self.cancellation_listener.clone());
+                download.target.process_response(request, response);
+                self.download = Some(download);
+            }
+        }
+        self.target.process_response(request, response);
+    }
+
+    fn process_response_chunk(&mut self, request: &Request, chunk: Vec<u8>) {
+        match self.download.as_mut() {
+            Some(download) => download.target.process_response_chunk(request, chunk),
+            None => self.target.process_response_chunk(request, chunk),
+        }
+    }
+
+    fn process_response_eof(&mut self, request: &Request, response: &Response) {
+        if let Some(download) = self.download.as_mut() {
+            download.target.process_response_eof(request, response);
+        }
+        self.target.process_response_eof(request, response);
+    }
+}
+
+/// The state of a download, which receives the responses to each attempt at fetching it.
+struct DownloadTarget {
+    id: DownloadId,
+    url: ServoUrl,
+    webview_id: Option<WebViewId>,
+    embedder_proxy: EmbedderProxy,
+    /// The channel that the embedder controls the download with, which is handed over when the
+    /// download starts.
+    control_sender: Option<ipc::IpcSender<DownloadControl>>,
+    /// The listener that cancels the current attempt.
+    cancellation_listener: Arc<CancellationListener>,
+    path: Option<PathBuf>,
+    file: Option<File>,
+    received_bytes: u64,
+    total_bytes: Option<u64>,
+    /// The `ETag` or `Last-Modified` value that makes sure a resumed download continues the
+    /// same version of the file.
+    validator: Option<HeaderValue>,
+    /// Whether the body was sent with a content coding, which a range request can't continue
+    /// because byte ranges refer to the encoded body. Navigations don't ask for an unencoded
+    /// body.
+    encoded: bool,
+    error: Option<String>,
+    last_progress_report: Instant,
+}
+
+impl DownloadTarget {
+    fn new(
+        request_builder: &RequestBuilder,
+        embedder_proxy: EmbedderProxy,
+        control_sender: ipc::IpcSender<DownloadControl>,
+    ) -> Self {
+        Self {
+            id: DownloadId(NEXT_DOWNLOAD_ID.fetch_add(1, Ordering::Relaxed)),
+            url: request_builder
+                .url_list
+                .last()
+                .cloned()
+                .unwrap_or_else(|| request_builder.url.clone()),
+            webview_id: request_builder.target_webview_id,
+            embedder_proxy,
+            control_sender: Some(control_sender),
+            cancellation_listener: Default::default(),
+            path: None,
+            file: None,
+            received_bytes: 0,
+            total_bytes: None,
+            validator: None,
+            encoded: false,
+            error: None,
+            last_progress_report: Instant::now(),
+        }
+    }
+
+    /// The request for the next attempt, which asks for the remaining bytes when some have
+    /// already been received.
+    fn request(&self, request_builder: &RequestBuilder) -> RequestBuilder {
+        let mut request_builder = request_builder.clone();
+        request_builder.id = Default::default();
+        request_builder.cache_mode = CacheMode::NoStore;
+        request_builder.redirect_mode = RedirectMode::Follow;
+        // Byte ranges refer to the encoded body, so ask for it unencoded.
+        request_builder
+            .headers
+            .insert(ACCEPT_ENCODING, HeaderValue::from_static("identity"));
+        if self.received_bytes > 0 && !self.encoded {
+            if let Ok(range) = HeaderValue::from_str(&format!("bytes={}-", self.received_bytes)) {
+                request_builder.headers.insert(RANGE, range);
+            }
+            if let Some(validator) = self.validator.clone() {
+                request_builder.headers.insert(IF_RANGE, validator);
+            }
+        }
+        request_builder
+    }
+
+    fn start(&mut self, response: &Response) -> io::Result<()> {
+        let file_name = content_disposition::file_name(&response.headers)
+            .or_else(|| {
+                self.url
+                    .as_url()
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .and_then(|segment| {
+                        percent_encoding::percent_decode_str(segment)
+                            .decode_utf8()
+                            .ok()
+                            .map(|segment| segment.into_owned())
+                    })
+            })
+            .unwrap_or_default();
+        let directory = match pref!(network_download_directory) {
+            directory if directory.is_empty() => std::env::temp_dir(),
+            directory => PathBuf::from(directory),
+        };
+        let (path, file) = create_unique_file(&directory, &sanitize_file_name(&file_name))?;
+        self.path = Some(path.clone());
+        self.file = Some(file);
+
+        if let Some(control_sender) = self.control_sender.take() {
+            let info = DownloadInfo {
+                id: self.id,
+                url: self.url.clone().into_url(),
+                path,
+                total_bytes: self.total_bytes,
+            };
+            self.embedder_proxy.send(EmbedderMsg::DownloadStarted(
+                self.webview_id,
+                info,
+                control_sender,
+            ));
+        }
+        Ok(())
+    }
+
+    fn fail(&mut self, error: String) {
+        self.error = Some(error);
+        self.file = None;
+        self.cancellation_listener.cancel();
+    }
+
+    fn report(&self, state: DownloadState) {
+        if self.path.is_none() {
+            // The embedder doesn't know about downloads that never started.
+            if let DownloadState::Failed(error) = state {
+                warn!("Download of {} failed: {error}", self.url);
+            }
+            return;
+        }
+        self.embedder_proxy.send(EmbedderMsg::DownloadStateChanged(
+            self.webview_id,
+            self.id,
+            state,
+        ));
+    }
+
+    fn finish(mut self) {
+        self.file = None;
+        let state = match (self.error.take(), self.total_bytes) {
+            (Some(error), _) => DownloadState::Failed(error),
+            (None, Some(total_bytes)) if self.received_bytes < total_bytes => {
+                DownloadState::Failed("The download was interrupted".to_owned())
+            },
+            (None, _) if self.path.is_none() => {
+                DownloadState::Failed("The download received no response".to_owned())
+            },
+            (None, _) => DownloadState::Completed,
+        };
+        self.report(state);
+    }
+
+    fn pause(&mut self) {
+        self.file = None;
+        self.report(DownloadState::Paused {
+            received_bytes: self.received_bytes,
+        });
+    }
+
+    fn cancel(mut self) {
+        self.file = None;
+        if let Some(path) = self.path.as_ref() {
+            if let Err(error) = fs::remove_file(path) {
+                warn!("Failed to remove cancelled download {path:?}: {error}");
+            }
+        }
+        self.report(DownloadState::Cancelled);
+    }
+}
+
+impl FetchTaskTarget for DownloadTarget {
+    fn process_request_body(&mut self, _: &Request) {}
+
+    fn process_request_eof(&mut self, _: &Request) {}
+
+    fn process_response(&mut self, _: &Request, response: &Response) {
+        // The download was paused or cancelled before the response arrived.
+        if self.cancellation_listener.cancelled() {
+            return;
+        }
+        if let Some(error) = response.get_network_error() {
+            return self.fail(format!("{error:?}"));
+        }
+
+        let status = response.status.raw_code();
+        if status == 206 && self.received_bytes > 0 {
+            let Some((start, total_bytes)) = parse_content_range(&response.headers) else {
+                return self.fail("The server sent an invalid range".to_owned());
+            };
+            if start != self.received_bytes {
+                return self.fail("The server sent an unexpected range".to_owned());
+            }
+            self.total_bytes = total_bytes.or(self.total_bytes);
+            let Some(path) = self.path.as_ref() else {
+                return self.fail("The download has no file".to_owned());
+            };
+            match OpenOptions::new().append(true).open(path) {
+                Ok(file) => self.file = Some(file),
+                Err(error) => self.fail(error.to_string()),
+            }
+            return;
+        }
+
+        if !response.status.is_success() {
+            return self.fail(format!("The server responded with status {status}"));
+        }
+
+        // The whole file is sent, either because this is the first attempt or because the
+        // server can't resume the download.
+        self.received_bytes = 0;
+        self.encoded = response
+            .headers
+            .get(CONTENT_ENCODING)
+            .is_some_and(|coding| coding != "identity");
+        // The length of an encoded body says nothing about how many decoded bytes it has.
+        self.total_bytes = response
+            .headers
+            .get(CONTENT_LENGTH)
+            .filter(|_| !self.encoded)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        self.validator = response
+            .headers
+            .get(ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .or_else(|| response.headers.get(LAST_MODIFIED))
+            .cloned();
+
+        let result = match self.path.as_ref() {
+            Some(path) => File::create(path).map(|file| self.file = Some(file)),
+            None => self.start(response),
+        };
+        if let Err(error) = result {
+            self.fail(error.to_string());
+        }
+    }
+
+    fn process_response_chunk(&mut self, _: &Request, chunk: Vec<u8>) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        if let Err(error) = file.write_all(&chunk) {
+            return self.fail(error.to_string());
+        }
+        self.received_bytes += chunk.len() as u64;
+
+        if self.last_progress_report.elapsed() >= PROGRESS_INTERVAL {
+            self.last_progress_report = Instant::now();
+            self.report(DownloadState::InProgress {
+                received_bytes: self.received_bytes,
+                total_bytes: self.total_bytes,
+            });
+        }
+    }
+
+    fn process_response_eof(&mut self, _: &Request, _: &Response) {
+        if let Some(file) = self.file.as_mut() {
+            if let Err(error) = file.flush() {
+                self.fail(error.to_string());
+            }
+        }
+    }
+}
+
+/// The first byte and the complete length in a `Content-Range` header, such as
+/// `bytes 100-199/200`.
+fn parse_content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, total_bytes) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    let total_bytes = match total_bytes {
+        "*" => None,
+        total_bytes => Some(total_bytes.parse().ok()?),
+    };
+    Some((start.parse().ok()?, total_bytes))
+}
+
+/// Make a file name suggested by a server or URL safe to use on every platform: strip any
+/// directories, replace reserved characters, and avoid names that Windows reserves for
+/// devices.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let mut name = name
+        .trim_matches(|c: char| c == '.' || c.is_whitespace())
+        .to_owned();
+
+    let stem = name.split('.').next().unwrap_or_default();
+    const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+    let is_reserved = RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved)) ||
+        (stem.len() == 4 &&
+            [b"COM", b"LPT"]
+                .iter()
+                .any(|prefix| stem.as_bytes()[..3].eq_ignore_ascii_case(*prefix)) &&
+            stem.as_bytes()[3].is_ascii_digit());
+    if is_reserved {
+        name.insert(0, '_');
+    }
+
+    // Long names are shortened before their extension, unless it is unreasonably long.
+    if name.len() > MAX_FILE_NAME_LENGTH {
+        let extension = match name.rsplit_once('.') {
+            Some((stem, extension))
+                if !stem.is_empty() && extension.len() < MAX_FILE_NAME_LENGTH / 2 =>
+            {
+                format!(".{extension}")
+            },
+            _ => String::new(),
+        };
+        let length = truncated_length(&name, MAX_FILE_NAME_LENGTH - extension.len());
+        name.truncate(length);
+        name.push_str(&extension);
+    }
+
+    if name.is_empty() {
+        return "download".to_owned();
+    }
+    name
+}
+
+/// The length of the longest prefix of `string` that is at most `max_length` bytes long and
+/// ends at a character boundary.
+fn truncated_length(string: &str, max_length: usize) -> usize {
+    let mut length = string.len().min(max_length);
+    while !string.is_char_boundary(length) {
+        length -= 1;
+    }
+    length
+}
+
+/// Create a new file called `file_name` in `directory`, adding a number to the name, like
+/// `report (1).pdf`, while a file with that name exists. The name is shortened before the
+/// extension when it would be too long with the number.
+pub fn create_unique_file(directory: &Path, file_name: &str) -> io::Result<(PathBuf, File)> {
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (file_name, String::new()),
+    };
+    for index in 0.. {
+        let number = match index {
+            0 => String::new(),
+            index => format!(" ({index})"),
+        };
+        let max_stem_length = MAX_FILE_NAME_LENGTH.saturating_sub(number.len() + extension.len());
+        let stem = &stem[..truncated_length(stem, max_stem_length)];
+        let path = directory.join(format!("{stem}{number}{extension}"));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    unreachable!("There are more file names than files")
+}
@@ -12,7 +12,6 @@ use base64::engine::general_purpose;
 use content_security_policy as csp;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
-use embedder_traits::resources::{self, Resource};
 use headers::{AccessControlExposeHeaders, ContentType, HeaderMapExt};
 use http::header::{self, HeaderMap, HeaderName, RANGE};
 use http::{HeaderValue, Method, StatusCode};
@@ -35,10 +34,12 @@ use net_traits::{
 use rustls_pki_types::CertificateDer;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
-use servo_url::{Host, ServoUrl};
+use servo_config::site_pref;
+use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use tokio::sync::mpsc::{UnboundedReceiver as TokioReceiver, UnboundedSender as TokioSender};
 
 use super::fetch_params::FetchParams;
+use crate::about;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::headers::determine_nosniff;
 use crate::filemanager_thread::FileManager;
@@ -291,6 +292,11 @@ pub async fn main_fetch(
             "Request attempted on bad port".into(),
         )));
     }
+    if should_request_be_blocked_because_images_are_disabled(request) {
+        response = Some(Response::network_error(NetworkError::Internal(
+            "Images are disabled for this site".into(),
+        )));
+    }
 
     // Step 8: If request’s referrer policy is the empty string, then set request’s referrer policy
     // to request’s policy container’s referrer policy.
@@ -327,16 +333,24 @@ pub async fn main_fetch(
 
     // Step 12.
 
-    let current_url = request.current_url();
-    let current_scheme = current_url.scheme();
-
     // Intercept the request and maybe override the response.
-    context
+    let observe_response = context
         .request_interceptor
         .lock()
         .unwrap()
         .intercept_request(request, &mut response, context);
 
+    // The embedder may have redirected the request to a URL that HSTS applies to.
+    context
+        .state
+        .hsts_list
+        .read()
+        .unwrap()
+        .apply_hsts_rules(request.current_url_mut());
+
+    let current_url = request.current_url();
+    let current_scheme = current_url.scheme();
+
     let mut response = match response {
         Some(res) => res,
         None => {
@@ -351,6 +365,8 @@ pub async fn main_fetch(
             if (same_origin && request.response_tainting == ResponseTainting::Basic) ||
                 // request's current URL's scheme is "data"
                 current_scheme == "data" ||
+                // A custom protocol handler allows its resources to be fetched.
+                context.protocols.is_fetchable(current_scheme) ||
                 // request's mode is "navigate" or "websocket"
                 matches!(
                     request.mode,
@@ -425,6 +441,15 @@ pub async fn main_fetch(
         },
     };
 
+    // Let the embedder modify the response, if it asked to observe it.
+    if observe_response {
+        context
+            .request_interceptor
+            .lock()
+            .unwrap()
+            .intercept_response(&fetch_params.request, &mut response);
+    }
+
     // Step 13.
     if recursive_flag {
         return response;
@@ -709,17 +734,6 @@ fn create_blank_reply(url: ServoUrl, timing_type: ResourceTimingType) -> Respons
     response
 }
 
-fn create_about_memory(url: ServoUrl, timing_type: ResourceTimingType) -> Response {
-    let mut response = Response::new(url, ResourceFetchTiming::new(timing_type));
-    response
-        .headers
-        .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
-    *response.body.lock().unwrap() =
-        ResponseBody::Done(resources::read_bytes(Resource::AboutMemoryHTML));
-    response.status = HttpStatus::default();
-    response
-}
-
 /// Handle a request from the user interface to ignore validation errors for a certificate.
 fn handle_allowcert_request(request: &mut Request, context: &FetchContext) -> io::Result<()> {
     let error = |string| Err(io::Error::new(io::ErrorKind::Other, string));
@@ -779,7 +793,7 @@ async fn scheme_fetch(
     let scheme = url.scheme();
     match scheme {
         "about" if url.path() == "blank" => create_blank_reply(url, request.timing_type()),
-        "about" if url.path() == "memory" => create_about_memory(url, request.timing_type()),
+        "about" if about::is_privileged_url(&url) => about::fetch(request, url).await,
 
         "chrome" if url.path() == "allowcert" => {
             if let Err(error) = handle_allowcert_request(request, context) {
@@ -899,6 +913,19 @@ fn should_be_blocked_due_to_mime_type(
         }
 }
 
+/// Whether `request` is for an image, and the site that makes it has images disabled with the
+/// `network_images_enabled` preference.
+fn should_request_be_blocked_because_images_are_disabled(request: &Request) -> bool {
+    if request.destination != Destination::Image {
+        return false;
+    }
+    let host = match &request.origin {
+        Origin::Origin(ImmutableOrigin::Tuple(_, host, _)) => Some(host.to_string()),
+        _ => None,
+    };
+    !site_pref!(network_images_enabled, host.as_deref())
+}
+
 /// <https://fetch.spec.whatwg.org/#block-bad-port>
 pub fn should_request_be_blocked_due_to_a_bad_port(url: &ServoUrl) -> bool {
     // Step 1. Let url be request’s current URL.
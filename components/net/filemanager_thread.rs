@@ -11,7 +11,7 @@ use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use base::id::WebViewId;
-use embedder_traits::{EmbedderMsg, EmbedderProxy, FilterPattern};
+use embedder_traits::{EmbedderMsg, EmbedderProxy, FileSelectionMode, FilterPattern};
 use headers::{ContentLength, ContentRange, ContentType, HeaderMap, HeaderMapExt, Range};
 use http::header::{self, HeaderValue};
 use ipc_channel::ipc::{self, IpcSender};
@@ -192,6 +192,40 @@ impl FileManager {
                         );
                     });
             },
+            FileManagerThreadMsg::SelectDirectory(webview_id, sender, origin, opt_test_path) => {
+                let store = self.store.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            store.select_directory(webview_id, sender, origin, opt_test_path, embedder);
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to select a directory after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::AllowDroppedFiles(webview_id, paths) => {
+                self.store.allow_dropped_files(webview_id, paths);
+            },
+            FileManagerThreadMsg::SelectDroppedFiles(webview_id, paths, sender, origin) => {
+                let store = self.store.clone();
+                self.thread_pool
+                    .upgrade()
+                    .map(|pool| {
+                        pool.spawn(move || {
+                            let _ = sender.send(store.select_dropped_files(webview_id, paths, &origin));
+                        });
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to select dropped files after CoreResourceManager has exited."
+                        );
+                    });
+            },
             FileManagerThreadMsg::ReadFile(sender, id, origin) => {
                 self.read_file(sender, id, origin);
             },
@@ -433,12 +467,16 @@ enum BlobBounds {
 /// Access to the content is encapsulated as methods of this struct.
 struct FileManagerStore {
     entries: RwLock<HashMap<Uuid, FileStoreEntry>>,
+    /// The paths of the files that the user dropped onto each webview, which its script
+    /// is allowed to select once.
+    dropped_files: Mutex<HashMap<WebViewId, Vec<PathBuf>>>,
 }
 
 impl FileManagerStore {
     fn new() -> Self {
         FileManagerStore {
             entries: RwLock::new(HashMap::new()),
+            dropped_files: Default::default(),
         }
     }
 
@@ -580,14 +618,14 @@ impl FileManagerStore {
         &self,
         webview_id: WebViewId,
         patterns: Vec<FilterPattern>,
-        multiple_files: bool,
+        selection_mode: FileSelectionMode,
         embedder_proxy: EmbedderProxy,
     ) -> Option<Vec<PathBuf>> {
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
         embedder_proxy.send(EmbedderMsg::SelectFiles(
             webview_id,
             patterns,
-            multiple_files,
+            selection_mode,
             ipc_sender,
         ));
         match ipc_receiver.recv() {
@@ -614,8 +652,13 @@ impl FileManagerStore {
         let opt_s = if pref!(dom_testing_html_input_element_select_files_enabled) {
             opt_test_path
         } else {
-            self.query_files_from_embedder(webview_id, patterns, false, embedder_proxy)
-                .and_then(|mut x| x.pop())
+            self.query_files_from_embedder(
+                webview_id,
+                patterns,
+                FileSelectionMode::Single,
+                embedder_proxy,
+            )
+            .and_then(|mut x| x.pop())
         };
 
         match opt_s {
@@ -645,7 +688,12 @@ impl FileManagerStore {
         let opt_v = if pref!(dom_testing_html_input_element_select_files_enabled) {
             opt_test_paths
         } else {
-            self.query_files_from_embedder(webview_id, patterns, true, embedder_proxy)
+            self.query_files_from_embedder(
+                webview_id,
+                patterns,
+                FileSelectionMode::Multiple,
+                embedder_proxy,
+            )
         };
 
         match opt_v {
@@ -676,6 +724,112 @@ impl FileManagerStore {
         }
     }
 
+    fn select_directory(
+        &self,
+        webview_id: WebViewId,
+        sender: IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        origin: FileOrigin,
+        opt_test_path: Option<PathBuf>,
+        embedder_proxy: EmbedderProxy,
+    ) {
+        // Check if the select_files preference is enabled
+        // to ensure process-level security against compromised script;
+        // Then try applying opt_test_path directly for testing convenience
+        let opt_s = if pref!(dom_testing_html_input_element_select_files_enabled) {
+            opt_test_path
+        } else {
+            self.query_files_from_embedder(
+                webview_id,
+                vec![],
+                FileSelectionMode::Directory,
+                embedder_proxy,
+            )
+            .and_then(|mut x| x.pop())
+        };
+
+        let Some(directory) = opt_s else {
+            let _ = sender.send(Err(FileManagerThreadError::UserCancelled));
+            return;
+        };
+        let _ = sender.send(self.create_directory_entries(&directory, &origin));
+    }
+
+    /// Create entries for all of the files in the given directory and in its subdirectories,
+    /// whose relative paths start with the name of the directory. Symbolic links inside of the
+    /// directory are skipped, as they could point outside of it or form a cycle.
+    fn create_directory_entries(
+        &self,
+        directory: &Path,
+        origin: &str,
+    ) -> Result<Vec<SelectedFile>, FileManagerThreadError> {
+        use net_traits::filemanager_thread::FileManagerThreadError::FileSystemError;
+
+        let directory_name = directory
+            .file_name()
+            .ok_or(FileSystemError("Invalid directory path".to_string()))?
+            .to_string_lossy()
+            .into_owned();
+
+        let mut selected_files = vec![];
+        let mut pending_directories = vec![(directory.to_path_buf(), directory_name)];
+        while let Some((directory, relative_path)) = pending_directories.pop() {
+            let mut children = std::fs::read_dir(&directory)
+                .map_err(|e| FileSystemError(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| FileSystemError(e.to_string()))?;
+            children.sort_by_key(|child| child.file_name());
+
+            for child in children {
+                // Unlike `Path::is_dir`, the file type of a directory entry does not follow
+                // symbolic links.
+                let file_type = child
+                    .file_type()
+                    .map_err(|e| FileSystemError(e.to_string()))?;
+                let path = child.path();
+                let child_relative_path =
+                    format!("{}/{}", relative_path, child.file_name().to_string_lossy());
+                if file_type.is_dir() {
+                    pending_directories.push((path, child_relative_path));
+                } else if file_type.is_file() {
+                    let mut selected_file = self.create_entry(&path, origin)?;
+                    selected_file.relative_path = Some(child_relative_path);
+                    selected_files.push(selected_file);
+                }
+            }
+        }
+        Ok(selected_files)
+    }
+
+    fn allow_dropped_files(&self, webview_id: WebViewId, paths: Vec<PathBuf>) {
+        self.dropped_files.lock().unwrap().insert(webview_id, paths);
+    }
+
+    /// Create entries for the given files, if the user dropped them onto the webview. The
+    /// dropped files can only be selected once.
+    fn select_dropped_files(
+        &self,
+        webview_id: WebViewId,
+        paths: Vec<PathBuf>,
+        origin: &str,
+    ) -> Result<Vec<SelectedFile>, FileManagerThreadError> {
+        let Some(dropped_paths) = self.dropped_files.lock().unwrap().remove(&webview_id) else {
+            return Err(FileManagerThreadError::InvalidSelection);
+        };
+        if paths.iter().any(|path| !dropped_paths.contains(path)) {
+            return Err(FileManagerThreadError::InvalidSelection);
+        }
+
+        let mut selected_files = vec![];
+        for path in paths {
+            if path.is_dir() {
+                selected_files.extend(self.create_directory_entries(&path, origin)?);
+            } else {
+                selected_files.push(self.create_entry(&path, origin)?);
+            }
+        }
+        Ok(selected_files)
+    }
+
     fn create_entry(
         &self,
         file_path: &Path,
@@ -726,6 +880,7 @@ impl FileManagerStore {
             modified,
             size: file_size,
             type_string,
+            relative_path: None,
         })
     }
 
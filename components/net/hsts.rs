@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 use std::net::{Ipv4Addr, Ipv6Addr};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use base::cross_process_instant::CrossProcessInstant;
 use embedder_traits::resources::{self, Resource};
@@ -58,6 +58,76 @@ impl HstsEntry {
     fn matches_subdomain(&self, host: &str) -> bool {
         !self.is_expired() && host.ends_with(&format!(".{}", self.host))
     }
+
+    /// Whether the entry comes from the preload list. Such entries have no `max-age`, and the
+    /// headers of responses never change or remove them.
+    fn is_preloaded(&self) -> bool {
+        self.max_age.is_none()
+    }
+}
+
+/// An [HstsEntry] that was learned from a response, as it is persisted across sessions. Its
+/// expiry is a wall-clock time, since monotonic timestamps are meaningless in another process.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct PersistedHstsEntry {
+    host: String,
+    include_subdomains: bool,
+    expires: SystemTime,
+}
+
+/// The entries of an [HstsList] that were learned from responses, as they are persisted
+/// across sessions. The entries of the preload list are not persisted, so that the list that
+/// ships with a newer version is used.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(from = "PersistedHstsListFormat")]
+pub struct PersistedHstsList {
+    entries: Vec<PersistedHstsEntry>,
+}
+
+/// An entry of the whole [HstsList], which earlier versions persisted. Its timestamp is not
+/// read, since it is meaningless in another process.
+#[derive(Deserialize)]
+struct LegacyHstsEntry {
+    host: String,
+    include_subdomains: bool,
+    max_age: Option<Duration>,
+}
+
+/// The formats that a [PersistedHstsList] may have been written in.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PersistedHstsListFormat {
+    Current {
+        entries: Vec<PersistedHstsEntry>,
+    },
+    Legacy {
+        entries_map: HashMap<String, Vec<LegacyHstsEntry>>,
+    },
+}
+
+impl From<PersistedHstsListFormat> for PersistedHstsList {
+    fn from(format: PersistedHstsListFormat) -> Self {
+        let entries_map = match format {
+            PersistedHstsListFormat::Current { entries } => return PersistedHstsList { entries },
+            PersistedHstsListFormat::Legacy { entries_map } => entries_map,
+        };
+        // The age of the learned entries is unknown, so they are kept for their whole
+        // `max-age`, which errs on the side of upgrading requests.
+        info!("Migrating the HSTS list of an earlier version");
+        let now = SystemTime::now();
+        let entries = entries_map
+            .into_values()
+            .flatten()
+            .filter_map(|entry| {
+                Some(PersistedHstsEntry {
+                    expires: now + entry.max_age?,
+                    host: entry.host,
+                    include_subdomains: entry.include_subdomains,
+                })
+            })
+            .collect();
+        PersistedHstsList { entries }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -107,10 +177,14 @@ impl HstsList {
         })
     }
 
-    fn has_domain(&self, host: &str, base_domain: &str) -> bool {
-        self.entries_map
-            .get(base_domain)
-            .is_some_and(|entries| entries.iter().any(|e| e.matches_domain(host)))
+    /// Whether the list has an entry for `host` that is a preloaded entry if `preloaded`, or
+    /// a learned one otherwise.
+    fn has_domain(&self, host: &str, base_domain: &str, preloaded: bool) -> bool {
+        self.entries_map.get(base_domain).is_some_and(|entries| {
+            entries
+                .iter()
+                .any(|e| e.is_preloaded() == preloaded && e.matches_domain(host))
+        })
     }
 
     fn has_subdomain(&self, host: &str, base_domain: &str) -> bool {
@@ -119,10 +193,21 @@ impl HstsList {
             .is_some_and(|entries| entries.iter().any(|e| e.matches_subdomain(host)))
     }
 
+    /// Add an entry to the list, or update the entry of the same host and kind. An entry with
+    /// a `max-age` of zero removes the learned entry of its host instead. Learned entries
+    /// never update or remove the entries of the preload list, which are kept alongside.
+    /// <https://www.rfc-editor.org/rfc/rfc6797#section-8.1>
     pub fn push(&mut self, entry: HstsEntry) {
         let host = entry.host.clone();
         let base_domain = reg_suffix(&host);
-        let have_domain = self.has_domain(&entry.host, base_domain);
+        if entry.max_age == Some(Duration::ZERO) {
+            if let Some(entries) = self.entries_map.get_mut(base_domain) {
+                entries.retain(|e| e.is_preloaded() || e.host != entry.host);
+            }
+            return;
+        }
+        let preloaded = entry.is_preloaded();
+        let have_domain = self.has_domain(&entry.host, base_domain, preloaded);
         let have_subdomain = self.has_subdomain(&entry.host, base_domain);
 
         let entries = self.entries_map.entry(base_domain.to_owned()).or_default();
@@ -130,14 +215,56 @@ impl HstsList {
             entries.push(entry);
         } else if !have_subdomain {
             for e in entries {
-                if e.matches_domain(&entry.host) {
+                if e.is_preloaded() == preloaded && e.matches_domain(&entry.host) {
                     e.include_subdomains = entry.include_subdomains;
                     e.max_age = entry.max_age;
+                    e.timestamp = entry.timestamp;
                 }
             }
         }
     }
 
+    /// The entries that were learned from responses and have not expired, in the form that
+    /// they are persisted in.
+    pub fn to_persisted(&self) -> PersistedHstsList {
+        let now = CrossProcessInstant::now();
+        let wall_clock_now = SystemTime::now();
+        let entries = self
+            .entries_map
+            .values()
+            .flatten()
+            .filter(|entry| !entry.is_expired())
+            .filter_map(|entry| {
+                let (max_age, timestamp) = (entry.max_age?, entry.timestamp?);
+                let age = Duration::try_from(now - timestamp).unwrap_or_default();
+                Some(PersistedHstsEntry {
+                    host: entry.host.clone(),
+                    include_subdomains: entry.include_subdomains,
+                    expires: wall_clock_now + max_age.saturating_sub(age),
+                })
+            })
+            .collect();
+        PersistedHstsList { entries }
+    }
+
+    /// Add the entries that were persisted by a previous session and have not expired since.
+    pub fn extend_from_persisted(&mut self, persisted: PersistedHstsList) {
+        let now = SystemTime::now();
+        for entry in persisted.entries {
+            let Ok(max_age) = entry.expires.duration_since(now) else {
+                continue;
+            };
+            let include_subdomains = if entry.include_subdomains {
+                IncludeSubdomains::Included
+            } else {
+                IncludeSubdomains::NotIncluded
+            };
+            if let Some(entry) = HstsEntry::new(entry.host, include_subdomains, Some(max_age)) {
+                self.push(entry);
+            }
+        }
+    }
+
     /// Step 2.9 of <https://fetch.spec.whatwg.org/#concept-main-fetch>.
     pub fn apply_hsts_rules(&self, url: &mut ServoUrl) {
         if url.scheme() != "http" && url.scheme() != "ws" {
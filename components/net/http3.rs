@@ -0,0 +1,417 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An HTTP/3 client, used for the origins that advertise an HTTP/3 alternative service with
+//! an `Alt-Svc` header, as described in <https://www.rfc-editor.org/rfc/rfc9114#section-3.1.1>.
+//!
+//! Connections are pooled by the origin host, which the certificate of the server is checked
+//! against, and the authority of the alternative service, which is only used to connect to.
+//! They are shared by all the requests to it, like the HTTP/1.1 and HTTP/2 connections of the
+//! hyper client. A failure marks the alternative service as broken for a while. A request is
+//! only sent again with the hyper client if connecting to the alternative service failed, as
+//! the server may have acted on a request that was sent.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::{Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, Bytes};
+use futures::future;
+use http::header::{self, HeaderMap, HeaderName};
+use http::{Method, Request, Response};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Frame;
+use log::{debug, warn};
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{ClientConfig, Endpoint};
+use servo_config::pref;
+use servo_url::{Host, ServoUrl};
+use tokio::sync::mpsc::channel;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::async_runtime::HANDLE;
+use crate::connector::{BoxedBody, BoxedResponseBody, TlsConfig};
+use crate::hosts::replace_host;
+use crate::resolver::resolve;
+
+type Http3Error = Box<dyn Error + Send + Sync>;
+type SendRequest = h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>;
+
+/// The ALPN protocol identifier of HTTP/3.
+const H3_ALPN: &[u8] = b"h3";
+
+/// How long an alternative service is not used after a request that was sent to it failed.
+const BROKEN_ALTERNATIVE_SERVICE_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// The freshness lifetime of an alternative service without an `ma` parameter.
+/// <https://www.rfc-editor.org/rfc/rfc7838#section-3.1>
+const DEFAULT_ALTERNATIVE_SERVICE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The headers that are specific to a connection, which must not be sent over HTTP/3.
+/// <https://www.rfc-editor.org/rfc/rfc9114#section-4.2>
+const CONNECTION_SPECIFIC_HEADERS: [HeaderName; 4] = [
+    header::CONNECTION,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+    HeaderName::from_static("keep-alive"),
+];
+
+/// The host and port of an origin or of an alternative service.
+type Authority = (String, u16);
+
+/// The host of the origin, which is the name that the server is checked against, and the
+/// authority of the alternative service that a connection goes to.
+type ConnectionKey = (String, Authority);
+
+/// The HTTP/3 endpoint that an origin advertised.
+struct AlternativeService {
+    authority: Authority,
+    expires: Instant,
+    broken_until: Option<Instant>,
+}
+
+/// What the `Alt-Svc` header of a response says about HTTP/3.
+#[derive(Debug, PartialEq)]
+pub enum AltSvcAdvertisement {
+    /// The alternative services of the origin must be forgotten.
+    Clear,
+    /// HTTP/3 is available at this host, which is the origin host when it is empty, and port.
+    /// IPv6 addresses are given without the brackets that enclose them in the header.
+    Http3(String, u16, Duration),
+    /// HTTP/3 is not advertised.
+    None,
+}
+
+/// Parse the value of an `Alt-Svc` header.
+/// <https://www.rfc-editor.org/rfc/rfc7838#section-3>
+pub fn parse_alt_svc(value: &str) -> AltSvcAdvertisement {
+    if value.trim() == "clear" {
+        return AltSvcAdvertisement::Clear;
+    }
+    value
+        .split(',')
+        .find_map(|alternative| {
+            let mut parameters = alternative.split(';').map(str::trim);
+            let (protocol, authority) = parameters.next()?.split_once('=')?;
+            if protocol != "h3" {
+                return None;
+            }
+            let (host, port) = authority.trim_matches('"').rsplit_once(':')?;
+            let host = match host.strip_prefix('[') {
+                Some(address) => address
+                    .strip_suffix(']')?
+                    .parse::<Ipv6Addr>()
+                    .ok()?
+                    .to_string(),
+                None if host.contains([':', '[', ']']) => return None,
+                None => host.to_owned(),
+            };
+            let max_age = parameters
+                .filter_map(|parameter| parameter.strip_prefix("ma="))
+                .find_map(|max_age| max_age.parse().ok())
+                .map_or(DEFAULT_ALTERNATIVE_SERVICE_MAX_AGE, Duration::from_secs);
+            Some(AltSvcAdvertisement::Http3(
+                host,
+                port.parse().ok()?,
+                max_age,
+            ))
+        })
+        .unwrap_or(AltSvcAdvertisement::None)
+}
+
+/// The host and port of the origin of `url`, with IPv6 addresses written without brackets
+/// like the hosts of alternative services, so that they can be used as a server name.
+fn origin_authority(url: &ServoUrl) -> Option<Authority> {
+    let host = match url.host()? {
+        Host::Ipv6(address) => address.to_string(),
+        host => host.to_string(),
+    };
+    Some((host, url.port_or_known_default()?))
+}
+
+/// Whether `method` is safe, so that a request with it can be sent as 0-RTT data, which an
+/// attacker may replay. <https://www.rfc-editor.org/rfc/rfc9114#section-4.1>
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// The result of trying to send a request over HTTP/3.
+pub enum Http3Outcome {
+    /// The request was not sent, because its origin did not advertise a usable HTTP/3
+    /// alternative service or because connecting to it failed. It should be sent over
+    /// HTTP/1.1 or HTTP/2 instead.
+    NotSent(Request<BoxedBody>),
+    Response(Response<BoxedResponseBody>),
+    /// The request failed after it was sent, so it must not be sent again.
+    Failed(Http3Error),
+}
+
+/// A connection of the pool.
+struct PooledConnection {
+    /// The id of the connection, so that it is only removed from the pool when it is closed
+    /// if it was not replaced.
+    id: u64,
+    send_request: SendRequest,
+    /// Whether the handshake of the connection is complete, or whether requests sent over it
+    /// may still be sent as 0-RTT data.
+    handshake_complete: Arc<AtomicBool>,
+}
+
+pub struct Http3Client {
+    tls_config: TlsConfig,
+    /// The QUIC endpoint that connections are made from, created with the first one.
+    endpoint: Mutex<Option<Endpoint>>,
+    /// The HTTP/3 alternative services that were advertised, by origin.
+    alternative_services: Mutex<HashMap<Authority, AlternativeService>>,
+    /// The open connections, by the origin host and the alternative service they go to.
+    connections: Arc<Mutex<HashMap<ConnectionKey, PooledConnection>>>,
+    next_connection_id: AtomicU64,
+}
+
+impl Http3Client {
+    pub fn new(mut tls_config: TlsConfig) -> Self {
+        tls_config.alpn_protocols = vec![H3_ALPN.to_vec()];
+        tls_config.enable_early_data = true;
+        Self {
+            tls_config,
+            endpoint: Default::default(),
+            alternative_services: Default::default(),
+            connections: Default::default(),
+            next_connection_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Note the HTTP/3 alternative service advertised by the headers of a response to `url`.
+    pub fn handle_response_headers(&self, url: &ServoUrl, headers: &HeaderMap) {
+        if url.scheme() != "https" {
+            return;
+        }
+        let Some(origin) = origin_authority(url) else {
+            return;
+        };
+        let Some(value) = headers
+            .get(header::ALT_SVC)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return;
+        };
+
+        let mut alternative_services = self.alternative_services.lock().unwrap();
+        match parse_alt_svc(value) {
+            AltSvcAdvertisement::Clear => {
+                alternative_services.remove(&origin);
+            },
+            AltSvcAdvertisement::Http3(host, port, max_age) => {
+                let host = if host.is_empty() {
+                    origin.0.clone()
+                } else {
+                    host
+                };
+                let authority = (host, port);
+                // Advertising the same service again does not make it usable if it is broken.
+                let broken_until = alternative_services
+                    .get(&origin)
+                    .filter(|service| service.authority == authority)
+                    .and_then(|service| service.broken_until);
+                alternative_services.insert(
+                    origin,
+                    AlternativeService {
+                        authority,
+                        expires: Instant::now() + max_age,
+                        broken_until,
+                    },
+                );
+            },
+            AltSvcAdvertisement::None => {},
+        }
+    }
+
+    /// The origin host and the authority of the alternative service to send requests to `url`
+    /// to over HTTP/3, if HTTP/3 is enabled and its origin advertised one that is usable.
+    fn alternative_service_for(&self, url: &ServoUrl) -> Option<ConnectionKey> {
+        if !pref!(network_http3_enabled) || url.scheme() != "https" {
+            return None;
+        }
+        let origin = origin_authority(url)?;
+        let mut alternative_services = self.alternative_services.lock().unwrap();
+        let now = Instant::now();
+        let service = alternative_services.get(&origin)?;
+        if service.expires <= now {
+            alternative_services.remove(&origin);
+            return None;
+        }
+        if service
+            .broken_until
+            .is_some_and(|broken_until| broken_until > now)
+        {
+            return None;
+        }
+        Some((origin.0, service.authority.clone()))
+    }
+
+    fn mark_broken(&self, url: &ServoUrl, key: &ConnectionKey) {
+        if let Some(origin) = origin_authority(url) {
+            if let Some(service) = self.alternative_services.lock().unwrap().get_mut(&origin) {
+                service.broken_until = Some(Instant::now() + BROKEN_ALTERNATIVE_SERVICE_DURATION);
+            }
+        }
+        self.connections.lock().unwrap().remove(key);
+    }
+
+    /// Send `request` to `url` over HTTP/3, if its origin advertised an alternative service
+    /// for it. The body of the request is streamed to the server as it is produced.
+    pub async fn request(&self, url: &ServoUrl, request: Request<BoxedBody>) -> Http3Outcome {
+        let Some(key) = self.alternative_service_for(url) else {
+            return Http3Outcome::NotSent(request);
+        };
+        let send_request = match self.connection(&key, request.method()).await {
+            Ok(send_request) => send_request,
+            Err(error) => {
+                warn!("HTTP/3 connection for {url} failed, falling back to TCP: {error}");
+                self.mark_broken(url, &key);
+                return Http3Outcome::NotSent(request);
+            },
+        };
+        match send_request_over_connection(send_request, request).await {
+            Ok(response) => Http3Outcome::Response(response),
+            Err(error) => {
+                warn!("HTTP/3 request to {url} failed: {error}");
+                self.mark_broken(url, &key);
+                Http3Outcome::Failed(error)
+            },
+        }
+    }
+
+    fn endpoint(&self) -> Result<Endpoint, Http3Error> {
+        let mut endpoint = self.endpoint.lock().unwrap();
+        if let Some(endpoint) = endpoint.as_ref() {
+            return Ok(endpoint.clone());
+        }
+        let mut new_endpoint = Endpoint::client(SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))?;
+        new_endpoint.set_default_client_config(ClientConfig::new(Arc::new(
+            QuicClientConfig::try_from(self.tls_config.clone())?,
+        )));
+        Ok(endpoint.insert(new_endpoint).clone())
+    }
+
+    /// A connection to the alternative service of `key` to send a request with `method` over.
+    /// Connections are reused, except that requests with unsafe methods are never sent over a
+    /// connection whose handshake may not be complete, as they would be sent as 0-RTT data.
+    async fn connection(
+        &self,
+        key: &ConnectionKey,
+        method: &Method,
+    ) -> Result<SendRequest, Http3Error> {
+        let allow_0rtt = pref!(network_http3_0rtt_enabled) && is_safe_method(method);
+        if let Some(connection) = self.connections.lock().unwrap().get(key) {
+            if allow_0rtt || connection.handshake_complete.load(Ordering::Acquire) {
+                return Ok(connection.send_request.clone());
+            }
+        }
+
+        // The alternative service is only used to connect to. The certificate of the server
+        // must be valid for the origin. <https://www.rfc-editor.org/rfc/rfc7838#section-2.1>
+        let (server_name, (host, port)) = key;
+        let address = resolve(&replace_host(host), true)
+            .await?
+            .first()
+            .map(|address| SocketAddr::new(*address, *port))
+            .ok_or("no address for the alternative service")?;
+        let connecting = self.endpoint()?.connect(address, server_name)?;
+        let handshake_complete = Arc::new(AtomicBool::new(false));
+        let connection = if allow_0rtt {
+            match connecting.into_0rtt() {
+                Ok((connection, accepted)) => {
+                    let handshake_complete = handshake_complete.clone();
+                    HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+                        if !accepted.await {
+                            debug!("0-RTT data was rejected");
+                        }
+                        handshake_complete.store(true, Ordering::Release);
+                    });
+                    connection
+                },
+                Err(connecting) => {
+                    let connection = connecting.await?;
+                    handshake_complete.store(true, Ordering::Release);
+                    connection
+                },
+            }
+        } else {
+            let connection = connecting.await?;
+            handshake_complete.store(true, Ordering::Release);
+            connection
+        };
+
+        let (mut driver, send_request) =
+            h3::client::new(h3_quinn::Connection::new(connection)).await?;
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        self.connections.lock().unwrap().insert(
+            key.clone(),
+            PooledConnection {
+                id,
+                send_request: send_request.clone(),
+                handshake_complete,
+            },
+        );
+
+        let connections = self.connections.clone();
+        let key = key.clone();
+        HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+            let result = future::poll_fn(|context| driver.poll_close(context)).await;
+            debug!("HTTP/3 connection to {key:?} closed: {result:?}");
+            let mut connections = connections.lock().unwrap();
+            if connections
+                .get(&key)
+                .is_some_and(|connection| connection.id == id)
+            {
+                connections.remove(&key);
+            }
+        });
+        Ok(send_request)
+    }
+}
+
+/// Send `request` over a connection, streaming its body, and receive the response, whose
+/// body is streamed as it arrives.
+async fn send_request_over_connection(
+    mut send_request: SendRequest,
+    request: Request<BoxedBody>,
+) -> Result<Response<BoxedResponseBody>, Http3Error> {
+    let (mut parts, mut request_body) = request.into_parts();
+    for name in &CONNECTION_SPECIFIC_HEADERS {
+        parts.headers.remove(name);
+    }
+
+    let mut stream = send_request
+        .send_request(Request::from_parts(parts, ()))
+        .await?;
+    while let Some(frame) = request_body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            stream.send_data(data).await?;
+        }
+    }
+    stream.finish().await?;
+
+    let response = stream.recv_response().await?;
+    let (sender, receiver) = channel(1);
+    HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+        // Reading stops when the body is dropped, which resets the stream.
+        loop {
+            let frame = match stream.recv_data().await {
+                Ok(Some(mut chunk)) => Ok(Frame::data(chunk.copy_to_bytes(chunk.remaining()))),
+                Ok(None) => break,
+                Err(error) => Err(error.into()),
+            };
+            let failed = frame.is_err();
+            if sender.send(frame).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+    Ok(response.map(|()| StreamBody::new(ReceiverStream::new(receiver)).boxed()))
+}
@@ -6,25 +6,32 @@
 
 //! A memory cache implementing the logic specified in <http://tools.ietf.org/html/rfc7234>
 //! and <http://tools.ietf.org/html/rfc7232>.
+//!
+//! The cache is bounded in size, evicting the resources that were validated the longest time
+//! ago, and can be written to and read from a directory on disk, so that it persists across
+//! sessions.
 
 use std::collections::HashMap;
+use std::fs;
 use std::ops::Bound;
+use std::path::Path;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant, SystemTime};
 
 use headers::{
-    CacheControl, ContentRange, Expires, HeaderMapExt, LastModified, Pragma, Range, Vary,
+    CacheControl, ContentRange, Date, Expires, HeaderMapExt, LastModified, Pragma, Range, Vary,
 };
 use http::header::HeaderValue;
 use http::{HeaderMap, Method, StatusCode, header};
-use log::debug;
+use log::{debug, warn};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps, MallocUnconditionalSizeOf};
 use malloc_size_of_derive::MallocSizeOf;
 use net_traits::http_status::HttpStatus;
 use net_traits::request::Request;
 use net_traits::response::{HttpsState, Response, ResponseBody};
 use net_traits::{FetchMetadata, Metadata, ResourceFetchTiming};
+use serde::{Deserialize, Serialize};
 use servo_arc::Arc;
 use servo_config::pref;
 use servo_url::ServoUrl;
@@ -160,14 +167,20 @@ fn response_is_cacheable(metadata: &Metadata) -> bool {
 /// Calculating Age
 /// <https://tools.ietf.org/html/rfc7234#section-4.2.3>
 fn calculate_response_age(response: &Response) -> Duration {
-    // TODO: follow the spec more closely (Date headers, request/response lag, ...)
-    response
+    // TODO: take the delay between sending the request and receiving the response into account.
+    let age_value = response
         .headers
         .get(header::AGE)
         .and_then(|age_header| age_header.to_str().ok())
         .and_then(|age_string| age_string.parse::<u64>().ok())
         .map(Duration::from_secs)
-        .unwrap_or_default()
+        .unwrap_or_default();
+    let apparent_age = response
+        .headers
+        .typed_get::<Date>()
+        .and_then(|date| SystemTime::now().duration_since(date.into()).ok())
+        .unwrap_or_default();
+    age_value.max(apparent_age)
 }
 
 /// Determine the expiry date from relevant headers,
@@ -241,12 +254,19 @@ fn get_response_expiry(response: &Response) -> Duration {
 
 /// Request Cache-Control Directives
 /// <https://tools.ietf.org/html/rfc7234#section-5.2.1>
-fn get_expiry_adjustment_from_request_headers(request: &Request, expires: Duration) -> Duration {
+///
+/// Stale responses that must be revalidated are never used, regardless of `max-stale`.
+/// <https://tools.ietf.org/html/rfc7234#section-5.2.2.1>
+fn get_expiry_adjustment_from_request_headers(
+    request: &Request,
+    expires: Duration,
+    must_revalidate: bool,
+) -> Duration {
     let Some(directive) = request.headers.typed_get::<CacheControl>() else {
         return expires;
     };
 
-    if let Some(max_age) = directive.max_stale() {
+    if let Some(max_age) = directive.max_stale().filter(|_| !must_revalidate) {
         return expires + max_age;
     }
 
@@ -306,10 +326,13 @@ fn create_cached_response(
     response.aborted = cached_resource.aborted.clone();
 
     let expires = cached_resource.expires;
-    let adjusted_expires = get_expiry_adjustment_from_request_headers(request, expires);
+    let must_revalidate = cached_headers
+        .typed_get::<CacheControl>()
+        .is_some_and(|directives| directives.must_revalidate());
+    let adjusted_expires =
+        get_expiry_adjustment_from_request_headers(request, expires, must_revalidate);
     let time_since_validated = Instant::now() - cached_resource.last_validated;
 
-    // TODO: take must-revalidate into account <https://tools.ietf.org/html/rfc7234#section-5.2.2.1>
     // TODO: if this cache is to be considered shared, take proxy-revalidate into account
     // <https://tools.ietf.org/html/rfc7234#section-5.2.2.7>
     let has_expired = adjusted_expires <= time_since_validated;
@@ -505,6 +528,48 @@ fn handle_range_request(
     None
 }
 
+/// Whether the headers of a request match those of the original request of `cached_resource`,
+/// for the headers that its response varies on.
+/// Calculating Secondary Keys with Vary <https://tools.ietf.org/html/rfc7234#section-4.1>
+fn resource_matches_request_headers(
+    cached_resource: &CachedResource,
+    request_headers: &HeaderMap,
+) -> bool {
+    let cached_headers = cached_resource.metadata.headers.lock().unwrap();
+    let original_request_headers = cached_resource.request_headers.lock().unwrap();
+    let Some(vary_value) = cached_headers.typed_get::<Vary>() else {
+        return true;
+    };
+    if vary_value.is_any() {
+        debug!("vary value is any, not caching");
+        return false;
+    }
+    // For every header name found in the Vary header of the stored response, the value of
+    // the header in the original request must match the value in the current request. If
+    // the header is absent from the request, it can only match a stored response if it was
+    // also absent in the original request.
+    vary_value.iter_strs().all(|vary_val| {
+        let matches = match request_headers.get(vary_val) {
+            Some(header_data) => original_request_headers
+                .get(vary_val)
+                .is_none_or(|original_header_data| original_header_data == header_data),
+            None => original_request_headers.get(vary_val).is_none(),
+        };
+        if !matches {
+            debug!("vary headers don't match, not caching");
+        }
+        matches
+    })
+}
+
+/// The size of the body of `cached_resource`, if it was received completely.
+fn complete_body_size(cached_resource: &CachedResource) -> Option<usize> {
+    match *cached_resource.body.lock().unwrap() {
+        ResponseBody::Done(ref bytes) => Some(bytes.len()),
+        ResponseBody::Empty | ResponseBody::Receiving(_) => None,
+    }
+}
+
 impl HttpCache {
     /// Constructing Responses from Caches.
     /// <https://tools.ietf.org/html/rfc7234#section-4>
@@ -526,55 +591,11 @@ impl HttpCache {
             .get(&entry_key)?
             .iter()
             .filter(|r| !r.aborted.load(Ordering::Relaxed));
-        let mut candidates = vec![];
-        for cached_resource in resources {
-            let mut can_be_constructed = true;
-            let cached_headers = cached_resource.metadata.headers.lock().unwrap();
-            let original_request_headers = cached_resource.request_headers.lock().unwrap();
-            if let Some(vary_value) = cached_headers.typed_get::<Vary>() {
-                if vary_value.is_any() {
-                    debug!("vary value is any, not caching");
-                    can_be_constructed = false
-                } else {
-                    // For every header name found in the Vary header of the stored response.
-                    // Calculating Secondary Keys with Vary <https://tools.ietf.org/html/rfc7234#section-4.1>
-                    for vary_val in vary_value.iter_strs() {
-                        match request.headers.get(vary_val) {
-                            Some(header_data) => {
-                                // If the header is present in the request.
-                                if let Some(original_header_data) =
-                                    original_request_headers.get(vary_val)
-                                {
-                                    // Check that the value of the nominated header field,
-                                    // in the original request, matches the value in the current request.
-                                    if original_header_data != header_data {
-                                        debug!("headers don't match, not caching");
-                                        can_be_constructed = false;
-                                        break;
-                                    }
-                                }
-                            },
-                            None => {
-                                // If a header field is absent from a request,
-                                // it can only match a stored response if those headers,
-                                // were also absent in the original request.
-                                can_be_constructed =
-                                    original_request_headers.get(vary_val).is_none();
-                                if !can_be_constructed {
-                                    debug!("vary header present, not caching");
-                                }
-                            },
-                        }
-                        if !can_be_constructed {
-                            break;
-                        }
-                    }
-                }
-            }
-            if can_be_constructed {
-                candidates.push(cached_resource);
-            }
-        }
+        let mut candidates: Vec<_> = resources
+            .filter(|cached_resource| {
+                resource_matches_request_headers(cached_resource, &request.headers)
+            })
+            .collect();
         // Support for range requests
         if let Some(range_spec) = request.headers.typed_get::<Range>() {
             return handle_range_request(request, candidates.as_slice(), &range_spec, done_chan);
@@ -808,14 +829,204 @@ impl HttpCache {
             last_validated: Instant::now(),
         };
         let entry = self.entries.entry(entry_key).or_default();
+        // The new response replaces the complete ones that were stored for requests with
+        // the same headers that it varies on. Incomplete ones are kept, so that consumers
+        // waiting for their body can be woken up.
+        entry.retain(|resource| {
+            complete_body_size(resource).is_none() ||
+                !resource_matches_request_headers(resource, &request.headers)
+        });
         entry.push(entry_resource);
         // TODO: Complete incomplete responses, including 206 response, when stored here.
         // See A cache MAY complete a stored incomplete response by making a subsequent range request
         // https://tools.ietf.org/html/rfc7234#section-3.1
+        self.evict_to_size_limit();
+    }
+
+    /// Evict the complete resources that were validated the longest time ago, until the size
+    /// of the bodies of the cached resources is within the limit set by the
+    /// `network_http_cache_size_limit_mb` preference.
+    pub(crate) fn evict_to_size_limit(&mut self) {
+        let size_limit = pref!(network_http_cache_size_limit_mb).max(0) as usize * 1024 * 1024;
+        let mut resources: Vec<_> = self
+            .entries
+            .values()
+            .flatten()
+            .filter_map(|resource| {
+                let size = complete_body_size(resource)?;
+                Some((resource.last_validated, resource.body.clone(), size))
+            })
+            .collect();
+        let mut total_size: usize = resources.iter().map(|(_, _, size)| size).sum();
+        if total_size <= size_limit {
+            return;
+        }
+
+        resources.sort_by_key(|(last_validated, _, _)| *last_validated);
+        let mut evicted = Vec::new();
+        for (_, body, size) in resources {
+            if total_size <= size_limit {
+                break;
+            }
+            evicted.push(body);
+            total_size -= size;
+        }
+        debug!("evicting {} resources from the http cache", evicted.len());
+        self.entries.retain(|_, resources| {
+            resources
+                .retain(|resource| !evicted.iter().any(|body| Arc::ptr_eq(body, &resource.body)));
+            !resources.is_empty()
+        });
     }
 
     /// Clear the contents of this cache.
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Read the resources that were written to `directory` by [`HttpCache::write_to_disk`].
+    pub fn read_from_disk(&mut self, directory: &Path) {
+        let index = match fs::read(directory.join(DISK_CACHE_INDEX)) {
+            Ok(index) => index,
+            Err(error) => {
+                debug!("couldn't read http cache index: {error}");
+                return;
+            },
+        };
+        let stored_resources: Vec<StoredResource> = match serde_json::from_slice(&index) {
+            Ok(stored_resources) => stored_resources,
+            Err(error) => {
+                warn!("Could not decode http cache index: {error}");
+                return;
+            },
+        };
+        let now = SystemTime::now();
+        for stored_resource in stored_resources {
+            let Ok(body) = fs::read(directory.join(&stored_resource.body_file)) else {
+                continue;
+            };
+            // Resources that were validated too long ago to be represented as an `Instant`
+            // are not worth keeping.
+            let Some(last_validated) = now
+                .duration_since(stored_resource.last_validated)
+                .ok()
+                .and_then(|elapsed| Instant::now().checked_sub(elapsed))
+            else {
+                continue;
+            };
+            let resource = CachedResource {
+                request_headers: Arc::new(Mutex::new(stored_resource.request_headers)),
+                body: Arc::new(Mutex::new(ResponseBody::Done(body))),
+                aborted: Arc::new(AtomicBool::new(false)),
+                awaiting_body: Arc::new(Mutex::new(vec![])),
+                metadata: CachedMetadata {
+                    headers: Arc::new(Mutex::new(stored_resource.response_headers)),
+                    final_url: stored_resource.final_url,
+                    content_type: stored_resource.content_type,
+                    charset: stored_resource.charset,
+                    status: stored_resource.status.clone(),
+                },
+                location_url: stored_resource.location_url,
+                https_state: stored_resource.https_state,
+                status: stored_resource.status,
+                url_list: stored_resource.url_list,
+                expires: stored_resource.expires,
+                last_validated,
+            };
+            self.entries
+                .entry(CacheKey::from_servo_url(&stored_resource.url))
+                .or_default()
+                .push(resource);
+        }
+        self.evict_to_size_limit();
+    }
+
+    /// Write the complete resources of this cache to `directory`, replacing what it contained.
+    /// The metadata of the resources is written to an index, and their bodies to separate files.
+    pub fn write_to_disk(&self, directory: &Path) {
+        if directory.exists() {
+            if let Err(error) = fs::remove_dir_all(directory) {
+                warn!("Could not clear http cache directory: {error}");
+                return;
+            }
+        }
+        if let Err(error) = fs::create_dir_all(directory) {
+            warn!("Could not create http cache directory: {error}");
+            return;
+        }
+
+        let now = SystemTime::now();
+        let mut stored_resources = Vec::new();
+        for (key, resources) in &self.entries {
+            for resource in resources {
+                if resource.aborted.load(Ordering::Acquire) {
+                    continue;
+                }
+                let body_file = format!("{}.body", stored_resources.len());
+                if let ResponseBody::Done(ref bytes) = *resource.body.lock().unwrap() {
+                    if let Err(error) = fs::write(directory.join(&body_file), bytes) {
+                        warn!("Could not write http cache body: {error}");
+                        continue;
+                    }
+                } else {
+                    continue;
+                }
+                stored_resources.push(StoredResource {
+                    url: key.url.clone(),
+                    request_headers: resource.request_headers.lock().unwrap().clone(),
+                    response_headers: resource.metadata.headers.lock().unwrap().clone(),
+                    final_url: resource.metadata.final_url.clone(),
+                    content_type: resource.metadata.content_type.clone(),
+                    charset: resource.metadata.charset.clone(),
+                    status: resource.status.clone(),
+                    location_url: resource.location_url.clone(),
+                    https_state: resource.https_state,
+                    url_list: resource.url_list.clone(),
+                    expires: resource.expires,
+                    last_validated: now - resource.last_validated.elapsed(),
+                    body_file,
+                });
+            }
+        }
+
+        match serde_json::to_vec(&stored_resources) {
+            Ok(index) => {
+                if let Err(error) = fs::write(directory.join(DISK_CACHE_INDEX), index) {
+                    warn!("Could not write http cache index: {error}");
+                }
+            },
+            Err(error) => warn!("Could not encode http cache index: {error}"),
+        }
+    }
+}
+
+/// The name of the file in the directory of a cache written to disk that describes the
+/// resources that it contains.
+const DISK_CACHE_INDEX: &str = "index.json";
+
+/// A complete cached resource as it is written to disk, apart from its body, which is written
+/// to a separate file.
+#[derive(Deserialize, Serialize)]
+struct StoredResource {
+    url: ServoUrl,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    request_headers: HeaderMap,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    response_headers: HeaderMap,
+    final_url: ServoUrl,
+    content_type: Option<String>,
+    charset: Option<String>,
+    status: HttpStatus,
+    location_url: Option<Result<ServoUrl, String>>,
+    https_state: HttpsState,
+    url_list: Vec<ServoUrl>,
+    expires: Duration,
+    last_validated: SystemTime,
+    body_file: String,
 }
@@ -4,19 +4,25 @@
 
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc as StdArc, Condvar, Mutex, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder, ZstdEncoder};
 use async_recursion::async_recursion;
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::{HistoryStateId, PipelineId};
+use base::id::{PipelineId, WebViewId};
 use crossbeam_channel::Sender;
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
-    HttpResponse as DevtoolsHttpResponse, NetworkEvent,
+    HttpResponse as DevtoolsHttpResponse, HttpResponseEnd, HttpTimings,
+    MAX_RESPONSE_BODY_PREVIEW_SIZE, NetworkEvent,
 };
-use embedder_traits::{AuthenticationResponse, EmbedderMsg, EmbedderProxy};
-use futures::{TryFutureExt, TryStreamExt, future};
+use embedder_traits::{
+    AuthenticationResponse, CookiePolicy, EmbedderMsg, EmbedderProxy, WebDriverInterceptDecision,
+    WebDriverInterceptPhase, WebDriverInterceptedRequest,
+};
+use futures::{TryStreamExt, future};
 use headers::authorization::Basic;
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
@@ -41,7 +47,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use log::{debug, error, info, log_enabled, warn};
 use net_traits::http_status::HttpStatus;
-use net_traits::pub_domains::reg_suffix;
+use net_traits::pub_domains::{reg_suffix, site};
 use net_traits::request::Origin::Origin as SpecificOrigin;
 use net_traits::request::{
     BodyChunkRequest, BodyChunkResponse, CacheMode, CredentialsMode, Destination, Initiator,
@@ -52,11 +58,15 @@ use net_traits::request::{
 };
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
 use net_traits::{
-    CookieSource, DOCUMENT_ACCEPT_HEADER_VALUE, FetchMetadata, NetworkError, RedirectEndValue,
-    RedirectStartValue, ReferrerPolicy, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
+    CookieContext, CookieSource, DOCUMENT_ACCEPT_HEADER_VALUE, FetchMetadata, NetworkError,
+    RedirectEndValue, RedirectStartValue, ReferrerPolicy, ResourceAttribute, ResourceFetchTiming,
+    ResourceTimeValue,
 };
 use servo_arc::Arc;
+use servo_config::pref;
+use servo_config::prefs::{PrefValue, PreferenceChange, PreferencesObserver};
 use servo_url::{ImmutableOrigin, ServoUrl};
+use tokio::io::AsyncReadExt;
 use tokio::sync::mpsc::{
     Receiver as TokioReceiver, Sender as TokioSender, UnboundedReceiver, UnboundedSender, channel,
     unbounded_channel,
@@ -66,7 +76,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use crate::async_runtime::HANDLE;
 use crate::connector::{CertificateErrorOverrideManager, Connector};
 use crate::cookie::ServoCookie;
-use crate::cookie_storage::CookieStorage;
+use crate::cookie_storage::{CookiePartition, CookieStorage};
 use crate::decoder::Decoder;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::fetch_params::FetchParams;
@@ -74,7 +84,9 @@ use crate::fetch::headers::{SecFetchDest, SecFetchMode, SecFetchSite, SecFetchUs
 use crate::fetch::methods::{Data, DoneChannel, FetchContext, Target, main_fetch};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
-use crate::resource_thread::{AuthCache, AuthCacheEntry};
+use crate::http3::{Http3Client, Http3Outcome};
+use crate::proxy::{ProxyKind, proxy_for};
+use crate::resource_thread::{AuthCache, AuthCacheEntry, HistoryStates};
 
 /// The various states an entry of the HttpCache can be in.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -98,13 +110,194 @@ pub struct HttpState {
     /// or whether a concurrent pending store should be awaited.
     pub http_cache_state: HttpCacheState,
     pub auth_cache: RwLock<AuthCache>,
-    pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    pub history_states: RwLock<HistoryStates>,
     pub client: Client<Connector, crate::connector::BoxedBody>,
+    pub http3_client: Http3Client,
     pub override_manager: CertificateErrorOverrideManager,
     pub embedder_proxy: Mutex<EmbedderProxy>,
+    /// The content coding that each origin advertised for request bodies in the
+    /// `Accept-Encoding` header of its responses.
+    pub request_content_codings: RwLock<HashMap<ImmutableOrigin, RequestContentCoding>>,
+    /// Whether the network is reachable, as reported by the embedder.
+    pub online: AtomicBool,
+    /// The cookie policies that embedders set for their webviews, which override the
+    /// `network_cookie_policy` preference.
+    pub cookie_policies: RwLock<HashMap<WebViewId, CookiePolicy>>,
+    /// Where to send the requests and responses that WebDriver BiDi intercepts, while it
+    /// does so.
+    pub webdriver_interceptor: RwLock<Option<IpcSender<WebDriverInterceptedRequest>>>,
+}
+
+impl PreferencesObserver for HttpState {
+    fn prefs_changed(&self, changes: &[PreferenceChange]) {
+        for (name, value) in changes {
+            match (*name, value) {
+                ("network_http_cache_disabled", PrefValue::Bool(true)) => {
+                    self.http_cache.write().unwrap().clear();
+                },
+                ("network_http_cache_size_limit_mb", _) => {
+                    self.http_cache.write().unwrap().evict_to_size_limit();
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+/// A content coding that request bodies are compressed with.
+/// <https://www.rfc-editor.org/rfc/rfc7694>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RequestContentCoding {
+    Zstd,
+    Brotli,
+    Gzip,
+}
+
+impl RequestContentCoding {
+    fn name(&self) -> &'static str {
+        match self {
+            RequestContentCoding::Zstd => "zstd",
+            RequestContentCoding::Brotli => "br",
+            RequestContentCoding::Gzip => "gzip",
+        }
+    }
+
+    /// The preferred content coding among those that an `Accept-Encoding` header accepts.
+    pub fn from_accept_encoding(value: &str) -> Option<RequestContentCoding> {
+        let accepted: Vec<_> = value
+            .split(',')
+            .filter_map(|coding| {
+                let mut parts = coding.split(';').map(str::trim);
+                let name = parts.next()?.to_ascii_lowercase();
+                let rejected = parts.any(|parameter| {
+                    parameter
+                        .strip_prefix("q=")
+                        .and_then(|quality| quality.parse::<f32>().ok()) ==
+                        Some(0.0)
+                });
+                (!rejected).then_some(name)
+            })
+            .collect();
+        [
+            RequestContentCoding::Zstd,
+            RequestContentCoding::Brotli,
+            RequestContentCoding::Gzip,
+        ]
+        .into_iter()
+        .find(|coding| accepted.iter().any(|name| name == coding.name()))
+    }
+
+    async fn encode(&self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut encoded = Vec::new();
+        match self {
+            RequestContentCoding::Zstd => ZstdEncoder::new(body).read_to_end(&mut encoded).await,
+            RequestContentCoding::Brotli => {
+                BrotliEncoder::new(body).read_to_end(&mut encoded).await
+            },
+            RequestContentCoding::Gzip => GzipEncoder::new(body).read_to_end(&mut encoded).await,
+        }?;
+        Ok(encoded)
+    }
 }
 
 impl HttpState {
+    /// The cookies for `url` that may be accessed in `context`, or `None` when the cookie
+    /// policy blocks all of them.
+    pub fn cookie_partition(
+        &self,
+        url: &ServoUrl,
+        context: &CookieContext,
+    ) -> Option<CookiePartition> {
+        let policy = context
+            .webview_id
+            .and_then(|webview_id| {
+                self.cookie_policies
+                    .read()
+                    .unwrap()
+                    .get(&webview_id)
+                    .copied()
+            })
+            .or_else(|| CookiePolicy::from_pref(&pref!(network_cookie_policy)))
+            .unwrap_or_default();
+
+        let first_party = CookiePartition::first_party(url);
+        let Some(top_level_origin) = context.top_level_origin.as_ref() else {
+            return (policy != CookiePolicy::BlockAll).then_some(first_party);
+        };
+        let key = site(top_level_origin);
+        let third_party = key != first_party.key;
+        match policy {
+            CookiePolicy::BlockAll => None,
+            CookiePolicy::BlockThirdParty if third_party => Some(CookiePartition {
+                key,
+                allow_unpartitioned: false,
+            }),
+            _ => Some(CookiePartition {
+                key,
+                allow_unpartitioned: true,
+            }),
+        }
+    }
+
+    /// Remember the content coding that the origin of `url` accepts for request bodies, as
+    /// advertised by a response. A `415 Unsupported Media Type` response without an
+    /// `Accept-Encoding` header means that it accepts none.
+    fn update_request_content_coding(
+        &self,
+        url: &ServoUrl,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) {
+        let advertised = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok());
+        let mut codings = self.request_content_codings.write().unwrap();
+        match advertised {
+            Some(value) => match RequestContentCoding::from_accept_encoding(value) {
+                Some(coding) => codings.insert(url.origin(), coding),
+                None => codings.remove(&url.origin()),
+            },
+            None if status == StatusCode::UNSUPPORTED_MEDIA_TYPE => codings.remove(&url.origin()),
+            None => None,
+        };
+    }
+
+    /// Compress a request body to `url` with the content coding that its origin advertised,
+    /// if any, updating the request headers accordingly.
+    async fn encode_request_body(
+        &self,
+        url: &ServoUrl,
+        body: Vec<u8>,
+        headers: &mut HeaderMap,
+    ) -> Vec<u8> {
+        if body.is_empty() ||
+            headers.contains_key(header::CONTENT_ENCODING) ||
+            !pref!(network_http_request_body_compression_enabled)
+        {
+            return body;
+        }
+        let coding = self
+            .request_content_codings
+            .read()
+            .unwrap()
+            .get(&url.origin())
+            .copied();
+        let Some(coding) = coding else {
+            return body;
+        };
+        match coding.encode(&body).await {
+            Ok(encoded) if encoded.len() < body.len() => {
+                headers.insert(
+                    header::CONTENT_ENCODING,
+                    HeaderValue::from_static(coding.name()),
+                );
+                headers.insert(header::CONTENT_LENGTH, HeaderValue::from(encoded.len()));
+                encoded
+            },
+            _ => body,
+        }
+    }
+
     fn request_authentication(
         &self,
         request: &Request,
@@ -129,6 +322,91 @@ impl HttpState {
         ));
         ipc_receiver.recv().ok()?
     }
+
+    /// Let WebDriver decide what to do with a request, or with the start of its response,
+    /// while it intercepts the network. This waits for the decision off the async runtime's
+    /// worker threads.
+    async fn intercept_for_webdriver(
+        &self,
+        phase: WebDriverInterceptPhase,
+        request_id: &str,
+        request: &Request,
+        response: Option<&Response>,
+    ) -> Option<WebDriverInterceptDecision> {
+        let (reply, reply_receiver) = ipc::channel().ok()?;
+        let intercepted = WebDriverInterceptedRequest {
+            request_id: request_id.to_owned(),
+            webview_id: request.target_webview_id,
+            phase,
+            url: request.current_url(),
+            method: request.method.to_string(),
+            headers: header_pairs(&request.headers),
+            status: response.map(|response| {
+                (
+                    response.status.raw_code(),
+                    String::from_utf8_lossy(response.status.message()).into_owned(),
+                )
+            }),
+            response_headers: response
+                .map(|response| header_pairs(&response.headers))
+                .unwrap_or_default(),
+            reply,
+        };
+        self.webdriver_interceptor
+            .read()
+            .unwrap()
+            .as_ref()?
+            .send(intercepted)
+            .ok()?;
+        tokio::task::spawn_blocking(move || reply_receiver.recv())
+            .await
+            .ok()?
+            .ok()
+    }
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_owned(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// The headers that WebDriver gave to replace those of a request or response. Those that
+/// are not valid are left out.
+fn header_map_from_pairs(pairs: Vec<(String, String)>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) else {
+            warn!("Ignoring invalid header {name:?} from WebDriver");
+            continue;
+        };
+        headers.append(name, value);
+    }
+    headers
+}
+
+/// A response that WebDriver provided instead of the one of the network.
+fn webdriver_provided_response(
+    url: ServoUrl,
+    timing: ResourceFetchTiming,
+    status: (u16, String),
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> Response {
+    let mut response = Response::new(url, timing);
+    response.status = HttpStatus::new_raw(status.0, status.1.into_bytes());
+    response.headers = header_map_from_pairs(headers);
+    *response.body.lock().unwrap() = ResponseBody::Done(body);
+    response
 }
 
 /// Step 13 of <https://fetch.spec.whatwg.org/#concept-fetch>.
@@ -164,7 +442,7 @@ fn set_default_accept_encoding(headers: &mut HeaderMap) {
     // TODO(eijebong): Change this once typed headers are done
     headers.insert(
         header::ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
+        HeaderValue::from_static("gzip, deflate, br, zstd"),
     );
 }
 
@@ -329,10 +607,13 @@ fn set_request_cookies(
     url: &ServoUrl,
     headers: &mut HeaderMap,
     cookie_jar: &RwLock<CookieStorage>,
+    partition: &CookiePartition,
 ) {
     let mut cookie_jar = cookie_jar.write().unwrap();
     cookie_jar.remove_expired_cookies_for_url(url);
-    if let Some(cookie_list) = cookie_jar.cookies_for_url(url, CookieSource::HTTP) {
+    if let Some(cookie_list) =
+        cookie_jar.cookies_for_url_in_partition(url, CookieSource::HTTP, partition)
+    {
         headers.insert(
             header::COOKIE,
             HeaderValue::from_bytes(cookie_list.as_bytes()).unwrap(),
@@ -340,12 +621,17 @@ fn set_request_cookies(
     }
 }
 
-fn set_cookie_for_url(cookie_jar: &RwLock<CookieStorage>, request: &ServoUrl, cookie_val: &str) {
+fn set_cookie_for_url(
+    cookie_jar: &RwLock<CookieStorage>,
+    request: &ServoUrl,
+    cookie_val: &str,
+    partition: &CookiePartition,
+) {
     let mut cookie_jar = cookie_jar.write().unwrap();
     let source = CookieSource::HTTP;
 
     if let Some(cookie) = ServoCookie::from_cookie_string(cookie_val.into(), request, source) {
-        cookie_jar.push(cookie, request, source);
+        cookie_jar.push_in_partition(cookie, request, source, partition);
     }
 }
 
@@ -353,10 +639,11 @@ fn set_cookies_from_headers(
     url: &ServoUrl,
     headers: &HeaderMap,
     cookie_jar: &RwLock<CookieStorage>,
+    partition: &CookiePartition,
 ) {
     for cookie in headers.get_all(header::SET_COOKIE) {
         if let Ok(cookie_str) = std::str::from_utf8(cookie.as_bytes()) {
-            set_cookie_for_url(cookie_jar, url, cookie_str);
+            set_cookie_for_url(cookie_jar, url, cookie_str, partition);
         }
     }
 }
@@ -422,6 +709,52 @@ fn send_response_to_devtools(
     let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
 }
 
+fn send_response_end_to_devtools(
+    devtools_chan: &Sender<DevtoolsControlMsg>,
+    request_id: String,
+    body: &[u8],
+    timings: HttpTimings,
+    pipeline_id: PipelineId,
+) {
+    let preview_size = body.len().min(MAX_RESPONSE_BODY_PREVIEW_SIZE);
+    let response_end = HttpResponseEnd {
+        body_preview: body[..preview_size].to_vec(),
+        body_size: body.len(),
+        timings,
+        pipeline_id,
+    };
+    let net_event = NetworkEvent::HttpResponseEnd(response_end);
+
+    let msg = ChromeToDevtoolsControlMsg::NetworkEvent(request_id, net_event);
+    let _ = devtools_chan.send(DevtoolsControlMsg::FromChrome(msg));
+}
+
+/// Break a finished fetch down into the phases shown by the network monitor, from the
+/// moments recorded in `timing` and the moment the response headers arrived.
+///
+/// Hyper doesn't tell us when the request was written out, so everything between the end of
+/// the connection and the response headers counts as waiting.
+fn devtools_timings(
+    timing: &ResourceFetchTiming,
+    response_start: CrossProcessInstant,
+) -> HttpTimings {
+    let between =
+        |start: Option<CrossProcessInstant>, end: Option<CrossProcessInstant>| match (start, end) {
+            (Some(start), Some(end)) if end > start => (end - start).unsigned_abs(),
+            _ => Duration::ZERO,
+        };
+    let handshake_start = timing.secure_connection_start.or(timing.connect_end);
+    HttpTimings {
+        blocked: between(timing.fetch_start, timing.domain_lookup_start),
+        dns: between(timing.domain_lookup_start, timing.connect_start),
+        connect: between(timing.connect_start, handshake_start),
+        ssl: between(timing.secure_connection_start, timing.connect_end),
+        send: Duration::ZERO,
+        wait: between(timing.connect_end, Some(response_start)),
+        receive: between(Some(response_start), timing.response_end),
+    }
+}
+
 fn auth_from_cache(
     auth_cache: &RwLock<AuthCache>,
     origin: &ImmutableOrigin,
@@ -611,6 +944,10 @@ async fn obtain_response(
                             None => warn!("Failed to read all chunks from request body."),
                         }
                     }
+                    let body = context
+                        .state
+                        .encode_request_body(url, body, &mut headers)
+                        .await;
                     Full::new(body.into()).map_err(|_| unreachable!()).boxed()
                 },
             };
@@ -661,6 +998,21 @@ async fn obtain_response(
         };
         *request.headers_mut() = headers.clone();
 
+        // Plain HTTP requests are forwarded by HTTP proxies, which are authenticated with
+        // each request rather than when the tunnel is opened.
+        let proxy = url
+            .host_str()
+            .and_then(|host| proxy_for(url.scheme(), host));
+        if let Some(proxy) = &proxy {
+            if let (ProxyKind::Http, "http") = (proxy.kind, url.scheme()) {
+                if let Some(authorization) = proxy.authorization() {
+                    request
+                        .headers_mut()
+                        .insert(header::PROXY_AUTHORIZATION, authorization);
+                }
+            }
+        }
+
         let connect_end = CrossProcessInstant::now();
         context
             .timing
@@ -679,51 +1031,57 @@ async fn obtain_response(
         let headers = headers.clone();
         let is_secure_scheme = url.is_secure_scheme();
 
-        client
-            .request(request)
-            .and_then(move |res| {
-                let send_end = CrossProcessInstant::now();
-
-                // TODO(#21271) response_start: immediately after receiving first byte of response
-
-                let msg = if let Some(request_id) = request_id {
-                    if let Some(pipeline_id) = pipeline_id {
-                        Some(prepare_devtools_request(
-                            request_id,
-                            closure_url,
-                            method.clone(),
-                            headers,
-                            Some(devtools_bytes.lock().unwrap().clone()),
-                            pipeline_id,
-                            (connect_end - connect_start).unsigned_abs(),
-                            (send_end - send_start).unsigned_abs(),
-                            is_xhr,
-                        ))
-                    // TODO: ^This is not right, connect_start is taken before contructing the
-                    // request and connect_end at the end of it. send_start is takend before the
-                    // connection too. I'm not sure it's currently possible to get the time at the
-                    // point between the connection and the start of a request.
-                    } else {
-                        debug!("Not notifying devtools (no pipeline_id)");
-                        None
-                    }
-                } else {
-                    debug!("Not notifying devtools (no request_id)");
-                    None
-                };
+        // Requests through a proxy are only sent over HTTP/1.1 or HTTP/2.
+        let http3_outcome = if proxy.is_some() {
+            Http3Outcome::NotSent(request)
+        } else {
+            context.state.http3_client.request(url, request).await
+        };
+        let res = match http3_outcome {
+            Http3Outcome::Response(res) => res,
+            Http3Outcome::Failed(error) => return Err(NetworkError::Internal(error.to_string())),
+            Http3Outcome::NotSent(request) => client
+                .request(request)
+                .await
+                .map_err(|error| {
+                    NetworkError::from_hyper_error(
+                        &error,
+                        override_manager.remove_certificate_failing_verification(host.as_str()),
+                    )
+                })?
+                .map(|body| body.map_err(Into::into).boxed()),
+        };
+        let send_end = CrossProcessInstant::now();
+
+        // TODO(#21271) response_start: immediately after receiving first byte of response
+
+        let msg = if let Some(request_id) = request_id {
+            if let Some(pipeline_id) = pipeline_id {
+                Some(prepare_devtools_request(
+                    request_id,
+                    closure_url,
+                    method.clone(),
+                    headers,
+                    Some(devtools_bytes.lock().unwrap().clone()),
+                    pipeline_id,
+                    (connect_end - connect_start).unsigned_abs(),
+                    (send_end - send_start).unsigned_abs(),
+                    is_xhr,
+                ))
+            // TODO: ^This is not right, connect_start is taken before contructing the
+            // request and connect_end at the end of it. send_start is takend before the
+            // connection too. I'm not sure it's currently possible to get the time at the
+            // point between the connection and the start of a request.
+            } else {
+                debug!("Not notifying devtools (no pipeline_id)");
+                None
+            }
+        } else {
+            debug!("Not notifying devtools (no request_id)");
+            None
+        };
 
-                future::ready(Ok((
-                    Decoder::detect(res.map(|r| r.boxed()), is_secure_scheme),
-                    msg,
-                )))
-            })
-            .map_err(move |error| {
-                NetworkError::from_hyper_error(
-                    &error,
-                    override_manager.remove_certificate_failing_verification(host.as_str()),
-                )
-            })
-            .await
+        Ok((Decoder::detect(res, is_secure_scheme), msg))
     }
 }
 
@@ -1328,11 +1686,18 @@ async fn http_network_or_cache_fetch(
         // Substep 1
         // TODO http://mxr.mozilla.org/servo/source/components/net/http_loader.rs#504
         // XXXManishearth http_loader has block_cookies: support content blocking here too
-        set_request_cookies(
-            &current_url,
-            &mut http_request.headers,
-            &context.state.cookie_jar,
-        );
+        let cookie_context = CookieContext::for_request(http_request);
+        if let Some(partition) = context
+            .state
+            .cookie_partition(&current_url, &cookie_context)
+        {
+            set_request_cookies(
+                &current_url,
+                &mut http_request.headers,
+                &context.state.cookie_jar,
+                &partition,
+            );
+        }
         // Substep 2
         if !http_request.headers.contains_key(header::AUTHORIZATION) {
             // Substep 3
@@ -1823,6 +2188,10 @@ async fn http_network_fetch(
 
     // Step 3
     // TODO be able to tell if the connection is a failure
+    // Obtaining a connection fails immediately while the network is offline.
+    if !context.state.online.load(Ordering::Relaxed) {
+        return Response::network_error(NetworkError::Internal("The network is offline".into()));
+    }
 
     // Step 4
     // TODO: check whether the connection is HTTP/2
@@ -1842,6 +2211,53 @@ async fn http_network_fetch(
         }
     }
 
+    // While WebDriver intercepts the network, it may change the request before it is sent, fail
+    // it, or answer it itself.
+    let webdriver_request_id = context
+        .state
+        .webdriver_interceptor
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|_| uuid::Uuid::new_v4().simple().to_string());
+    if let Some(ref webdriver_request_id) = webdriver_request_id {
+        match context
+            .state
+            .intercept_for_webdriver(
+                WebDriverInterceptPhase::BeforeRequestSent,
+                webdriver_request_id,
+                request,
+                None,
+            )
+            .await
+        {
+            Some(WebDriverInterceptDecision::Continue {
+                method, headers, ..
+            }) => {
+                if let Some(method) = method.and_then(|method| method.parse::<Method>().ok()) {
+                    request.method = method;
+                }
+                if let Some(headers) = headers {
+                    request.headers = header_map_from_pairs(headers);
+                }
+            },
+            Some(WebDriverInterceptDecision::Fail) => {
+                return Response::network_error(NetworkError::Internal(
+                    "Request failed by WebDriver".into(),
+                ));
+            },
+            Some(WebDriverInterceptDecision::Provide {
+                status,
+                headers,
+                body,
+            }) => {
+                let timing = context.timing.lock().unwrap().clone();
+                return webdriver_provided_response(url, timing, status, headers, body);
+            },
+            None => {},
+        }
+    }
+
     // XHR uses the default destination; other kinds of fetches (which haven't been implemented yet)
     // do not. Once we support other kinds of fetches we'll need to be more fine grained here
     // since things like image fetches are classified differently by devtools
@@ -1884,6 +2300,15 @@ async fn http_network_fetch(
         Ok(wrapped_response) => wrapped_response,
         Err(error) => return Response::network_error(error),
     };
+    let response_start = CrossProcessInstant::now();
+
+    context
+        .state
+        .http3_client
+        .handle_response_headers(&url, res.headers());
+    context
+        .state
+        .update_request_content_coding(&url, res.status(), res.headers());
 
     if log_enabled!(log::Level::Info) {
         debug!("{:?} response for {}", res.version(), url);
@@ -1949,6 +2374,44 @@ async fn http_network_fetch(
     response.referrer = request.referrer.to_url().cloned();
     response.referrer_policy = request.referrer_policy;
 
+    if let Some(ref webdriver_request_id) = webdriver_request_id {
+        match context
+            .state
+            .intercept_for_webdriver(
+                WebDriverInterceptPhase::ResponseStarted,
+                webdriver_request_id,
+                request,
+                Some(&response),
+            )
+            .await
+        {
+            Some(WebDriverInterceptDecision::Continue {
+                status, headers, ..
+            }) => {
+                if let Some((code, reason)) = status {
+                    response.status = HttpStatus::new_raw(code, reason.into_bytes());
+                }
+                if let Some(headers) = headers {
+                    response.headers = header_map_from_pairs(headers);
+                }
+            },
+            Some(WebDriverInterceptDecision::Fail) => {
+                return Response::network_error(NetworkError::Internal(
+                    "Request failed by WebDriver".into(),
+                ));
+            },
+            Some(WebDriverInterceptDecision::Provide {
+                status,
+                headers,
+                body,
+            }) => {
+                let timing = context.timing.lock().unwrap().clone();
+                return webdriver_provided_response(url, timing, status, headers, body);
+            },
+            None => {},
+        }
+    }
+
     let res_body = response.body.clone();
 
     // We're about to spawn a future to be waited on here
@@ -1984,7 +2447,7 @@ async fn http_network_fetch(
         if let Some(pipeline_id) = pipeline_id {
             send_response_to_devtools(
                 &sender,
-                request_id.unwrap(),
+                request_id.clone().unwrap(),
                 meta_headers.map(Serde::into_inner),
                 meta_status,
                 pipeline_id,
@@ -1998,6 +2461,10 @@ async fn http_network_fetch(
     let timing_ptr3 = context.timing.clone();
     let url1 = request.url();
     let url2 = url1.clone();
+    let devtools_response_end = devtools_sender
+        .zip(request_id)
+        .zip(pipeline_id)
+        .map(|((sender, request_id), pipeline_id)| (sender, request_id, pipeline_id));
 
     HANDLE.lock().unwrap().as_ref().unwrap().spawn(
         res.into_body()
@@ -2024,11 +2491,21 @@ async fn http_network_fetch(
                     ResponseBody::Receiving(ref mut body) => std::mem::take(body),
                     _ => vec![],
                 };
+                let timing = {
+                    let mut timing = timing_ptr2.lock().unwrap();
+                    timing.set_attribute(ResourceAttribute::ResponseEnd);
+                    timing.clone()
+                };
+                if let Some((sender, request_id, pipeline_id)) = devtools_response_end {
+                    send_response_end_to_devtools(
+                        &sender.lock().unwrap(),
+                        request_id,
+                        &completed_body,
+                        devtools_timings(&timing, response_start),
+                        pipeline_id,
+                    );
+                }
                 *body = ResponseBody::Done(completed_body);
-                timing_ptr2
-                    .lock()
-                    .unwrap()
-                    .set_attribute(ResourceAttribute::ResponseEnd);
                 let _ = done_sender2.send(Data::Done);
                 future::ready(Ok(()))
             })
@@ -2071,7 +2548,15 @@ async fn http_network_fetch(
     // TODO this step isn't possible yet
     // Step 15
     if credentials_flag {
-        set_cookies_from_headers(&url, &response.headers, &context.state.cookie_jar);
+        let cookie_context = CookieContext::for_request(request);
+        if let Some(partition) = context.state.cookie_partition(&url, &cookie_context) {
+            set_cookies_from_headers(
+                &url,
+                &response.headers,
+                &context.state.cookie_jar,
+                &partition,
+            );
+        }
     }
     context
         .state
@@ -4,24 +4,28 @@
 
 use std::collections::HashMap;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::{mem, thread};
 
 use imsz::imsz_from_reader;
 use ipc_channel::ipc::IpcSharedMemory;
 use log::{debug, warn};
+use mime::Mime;
 use net_traits::image_cache::{
     ImageCache, ImageCacheResult, ImageOrMetadataAvailable, ImageResponder, ImageResponse,
     PendingImageId, UsePlaceholder,
 };
 use net_traits::request::CorsSettings;
 use net_traits::{FetchMetadata, FetchResponseMsg, FilteredMetadata, NetworkError};
-use pixels::{CorsStatus, Image, ImageMetadata, PixelFormat, load_from_memory};
+use pixels::{
+    ColorSpace, CorsStatus, Image, ImageFrame, ImageMetadata, PixelFormat, load_from_memory,
+};
+use resvg::{tiny_skia, usvg};
 use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use webrender_api::units::DeviceIntSize;
 use webrender_api::{ImageDescriptor, ImageDescriptorFlags, ImageFormat};
-use webrender_traits::{CrossProcessCompositorApi, SerializableImageData};
+use webrender_traits::{CrossProcessCompositorApi, ImageUpdate, SerializableImageData};
 
 use crate::resource_thread::CoreResourceThreadPool;
 
@@ -39,13 +43,100 @@ use crate::resource_thread::CoreResourceThreadPool;
 // Helper functions.
 // ======================================================================
 
+/// The color space that decoded images are converted to.
+fn output_color_space() -> ColorSpace {
+    if pref!(gfx_display_p3_enabled) {
+        ColorSpace::DisplayP3
+    } else {
+        ColorSpace::Srgb
+    }
+}
+
 fn decode_bytes_sync(key: LoadKey, bytes: &[u8], cors: CorsStatus) -> DecoderMsg {
-    let image = load_from_memory(bytes, cors);
+    let image = load_from_memory(bytes, cors, output_color_space());
+    DecoderMsg { key, image }
+}
+
+fn rasterize_svg_sync(key: LoadKey, bytes: &[u8], cors: CorsStatus) -> DecoderMsg {
+    let image = rasterize_svg(bytes, cors);
     DecoderMsg { key, image }
 }
 
+/// The fonts that `<text>` in SVG documents is shaped and rendered with. Loading the system
+/// fonts is slow, so this happens once and is shared by all decoder threads.
+static SVG_FONT_DATABASE: LazyLock<Arc<usvg::fontdb::Database>> = LazyLock::new(|| {
+    let mut database = usvg::fontdb::Database::new();
+    database.load_system_fonts();
+    Arc::new(database)
+});
+
+/// A [`usvg::FontResolver`] that only loads the system fonts once an SVG document needs a
+/// font, so that SVG documents without text never wait for them.
+fn svg_font_resolver() -> usvg::FontResolver<'static> {
+    fn load_system_fonts(fontdb: &mut Arc<usvg::fontdb::Database>) {
+        if fontdb.is_empty() {
+            *fontdb = SVG_FONT_DATABASE.clone();
+        }
+    }
+
+    let select_font = usvg::FontResolver::default_font_selector();
+    let select_fallback = usvg::FontResolver::default_fallback_selector();
+    usvg::FontResolver {
+        select_font: Box::new(move |font, fontdb| {
+            load_system_fonts(fontdb);
+            select_font(font, fontdb)
+        }),
+        select_fallback: Box::new(move |character, used_fonts, fontdb| {
+            load_system_fonts(fontdb);
+            select_fallback(character, used_fonts, fontdb)
+        }),
+    }
+}
+
+/// Parse an SVG document and rasterize it at its natural size, which `usvg` derives from
+/// the `width`, `height` and `viewBox` attributes of the root element.
+fn rasterize_svg(bytes: &[u8], cors_status: CorsStatus) -> Option<Image> {
+    let options = usvg::Options {
+        font_resolver: svg_font_resolver(),
+        ..Default::default()
+    };
+    let tree = match usvg::Tree::from_data(bytes, &options) {
+        Ok(tree) => tree,
+        Err(error) => {
+            debug!("SVG parsing error: {error}");
+            return None;
+        },
+    };
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    // `resvg` produces premultiplied sRGB RGBA, while decoded images are unpremultiplied BGRA
+    // in the output color space.
+    let mut bytes = pixmap.take();
+    pixels::unmultiply_inplace::<false>(&mut bytes);
+    pixels::rgba8_convert_to_color_space_inplace(&mut bytes, None, output_color_space());
+    pixels::rgba8_byte_swap_colors_inplace(&mut bytes);
+
+    let frame = ImageFrame {
+        delay: None,
+        bytes: IpcSharedMemory::from_bytes(&bytes),
+        width: size.width(),
+        height: size.height(),
+    };
+    Some(Image {
+        width: size.width(),
+        height: size.height(),
+        format: PixelFormat::BGRA8,
+        frames: vec![frame],
+        id: None,
+        cors_status,
+    })
+}
+
 fn get_placeholder_image(compositor_api: &CrossProcessCompositorApi, data: &[u8]) -> Arc<Image> {
-    let mut image = load_from_memory(data, CorsStatus::Unsafe).unwrap();
+    let mut image = load_from_memory(data, CorsStatus::Unsafe, output_color_space()).unwrap();
     set_webrender_image_key(compositor_api, &mut image);
     Arc::new(image)
 }
@@ -149,7 +240,10 @@ impl AllPendingLoads {
         {
             Occupied(url_entry) => {
                 let load_key = url_entry.get();
-                CacheResult::Hit(*load_key, self.loads.get_mut(load_key).unwrap())
+                let pending_load = self.loads.get_mut(load_key).unwrap();
+                // The image is wanted again, so keep it once it has loaded.
+                pending_load.evicted = false;
+                CacheResult::Hit(*load_key, pending_load)
             },
             Vacant(url_entry) => {
                 let load_key = self.keygen.next();
@@ -286,6 +380,13 @@ struct PendingLoad {
 
     /// The URL of the final response that contains a body.
     final_url: Option<ServoUrl>,
+
+    /// Whether the response is an SVG document, which is rasterized rather than decoded.
+    is_svg: bool,
+
+    /// Whether the image was evicted while it was loading, so that it should be forgotten
+    /// instead of cached once the load completes.
+    evicted: bool,
 }
 
 impl PendingLoad {
@@ -304,6 +405,8 @@ impl PendingLoad {
             final_url: None,
             cors_setting,
             cors_status: CorsStatus::Unsafe,
+            is_svg: false,
+            evicted: false,
         }
     }
 
@@ -357,7 +460,16 @@ impl ImageCacheStore {
             LoadResult::None => ImageResponse::None,
         };
 
-        let completed_load = CompletedLoad::new(image_response.clone(), key);
+        for listener in &pending_load.listeners {
+            listener.respond(image_response.clone());
+        }
+
+        if pending_load.evicted {
+            self.delete_webrender_image(&image_response);
+            return;
+        }
+
+        let completed_load = CompletedLoad::new(image_response, key);
         self.completed_loads.insert(
             (
                 pending_load.url,
@@ -366,9 +478,16 @@ impl ImageCacheStore {
             ),
             completed_load,
         );
+    }
 
-        for listener in pending_load.listeners {
-            listener.respond(image_response.clone());
+    /// Release the WebRender image of an image that is no longer cached. The placeholder
+    /// image is shared by all failed loads, so it is kept.
+    fn delete_webrender_image(&self, image_response: &ImageResponse) {
+        if let ImageResponse::Loaded(image, _) = image_response {
+            if let Some(image_key) = image.id {
+                self.compositor_api
+                    .update_images(vec![ImageUpdate::DeleteImage(image_key)]);
+            }
         }
     }
 
@@ -397,8 +516,7 @@ impl ImageCacheStore {
             )
     }
 
-    /// Handle a message from one of the decoder worker threads or from a sync
-    /// decoding operation.
+    /// Handle a message from one of the decoder worker threads.
     fn handle_decoder(&mut self, msg: DecoderMsg) {
         let image = match msg.image {
             None => LoadResult::None,
@@ -485,53 +603,38 @@ impl ImageCache for ImageCacheImpl {
             }
         }
 
-        let decoded = {
-            let result = store
-                .pending_loads
-                .get_cached(url.clone(), origin.clone(), cors_setting);
-            match result {
-                CacheResult::Hit(key, pl) => match (&pl.result, &pl.metadata) {
-                    (&Some(Ok(_)), _) => {
-                        debug!("Sync decoding {} ({:?})", url, key);
-                        decode_bytes_sync(key, pl.bytes.as_slice(), pl.cors_status)
-                    },
-                    (&None, Some(meta)) => {
-                        debug!("Metadata available for {} ({:?})", url, key);
-                        return ImageCacheResult::Available(
-                            ImageOrMetadataAvailable::MetadataAvailable(meta.clone(), key),
-                        );
-                    },
-                    (&Some(Err(_)), _) | (&None, &None) => {
-                        debug!("{} ({:?}) is still pending", url, key);
-                        return ImageCacheResult::Pending(key);
-                    },
+        let result = store
+            .pending_loads
+            .get_cached(url.clone(), origin.clone(), cors_setting);
+        match result {
+            CacheResult::Hit(key, pl) => match (&pl.result, &pl.metadata) {
+                (&Some(Ok(_)), _) => {
+                    // The full response is available, but decoding happens on one of the
+                    // decoder worker threads. Callers are notified once it finishes rather
+                    // than blocking them on a synchronous decode.
+                    debug!("{} ({:?}) is being decoded", url, key);
+                    ImageCacheResult::Pending(key)
                 },
-                CacheResult::Miss(Some((key, _pl))) => {
-                    debug!("Should be requesting {} ({:?})", url, key);
-                    return ImageCacheResult::ReadyForRequest(key);
+                (&None, Some(meta)) => {
+                    debug!("Metadata available for {} ({:?})", url, key);
+                    ImageCacheResult::Available(ImageOrMetadataAvailable::MetadataAvailable(
+                        meta.clone(),
+                        key,
+                    ))
                 },
-                CacheResult::Miss(None) => {
-                    debug!("Couldn't find an entry for {}", url);
-                    return ImageCacheResult::LoadError;
+                (&Some(Err(_)), _) | (&None, &None) => {
+                    debug!("{} ({:?}) is still pending", url, key);
+                    ImageCacheResult::Pending(key)
                 },
-            }
-        };
-
-        // In the case where a decode is ongoing (or waiting in a queue) but we
-        // have the full response available, we decode the bytes synchronously
-        // and ignore the async decode when it finishes later.
-        // TODO: make this behaviour configurable according to the caller's needs.
-        store.handle_decoder(decoded);
-        match store.get_completed_image_if_available(url, origin, cors_setting, use_placeholder) {
-            Some(Ok((image, image_url))) => {
-                let is_placeholder = image_url == store.placeholder_url;
-                ImageCacheResult::Available(ImageOrMetadataAvailable::ImageAvailable {
-                    image,
-                    url: image_url,
-                    is_placeholder,
-                })
             },
-            _ => ImageCacheResult::LoadError,
+            CacheResult::Miss(Some((key, _pl))) => {
+                debug!("Should be requesting {} ({:?})", url, key);
+                ImageCacheResult::ReadyForRequest(key)
+            },
+            CacheResult::Miss(None) => {
+                debug!("Couldn't find an entry for {}", url);
+                ImageCacheResult::LoadError
+            },
         }
     }
 
@@ -569,8 +672,16 @@ impl ImageCache for ImageCacheImpl {
                     Err(_) => (CorsStatus::Unsafe, None),
                 };
                 let final_url = metadata.as_ref().map(|m| m.final_url.clone());
+                let is_svg = metadata
+                    .as_ref()
+                    .and_then(|m| m.content_type.clone())
+                    .is_some_and(|content_type| {
+                        let mime: Mime = content_type.into_inner().into();
+                        mime.essence_str() == "image/svg+xml"
+                    });
                 pending_load.final_url = final_url;
                 pending_load.cors_status = cors_status;
+                pending_load.is_svg = is_svg;
             },
             (FetchResponseMsg::ProcessResponseChunk(_, data), _) => {
                 debug!("Got some data for {:?}", id);
@@ -595,17 +706,25 @@ impl ImageCache for ImageCacheImpl {
                 debug!("Received EOF for {:?}", key);
                 match result {
                     Ok(_) => {
-                        let (bytes, cors_status) = {
+                        let (bytes, cors_status, is_svg) = {
                             let mut store = self.store.lock().unwrap();
                             let pending_load = store.pending_loads.get_by_key_mut(&id).unwrap();
                             pending_load.result = Some(Ok(()));
                             debug!("Async decoding {} ({:?})", pending_load.url, key);
-                            (pending_load.bytes.mark_complete(), pending_load.cors_status)
+                            (
+                                pending_load.bytes.mark_complete(),
+                                pending_load.cors_status,
+                                pending_load.is_svg,
+                            )
                         };
 
                         let local_store = self.store.clone();
                         self.thread_pool.spawn(move || {
-                            let msg = decode_bytes_sync(key, &bytes, cors_status);
+                            let msg = if is_svg {
+                                rasterize_svg_sync(key, &bytes, cors_status)
+                            } else {
+                                decode_bytes_sync(key, &bytes, cors_status)
+                            };
                             debug!("Image decoded");
                             local_store.lock().unwrap().handle_decoder(msg);
                         });
@@ -620,6 +739,24 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    fn evict_image(
+        &self,
+        url: ServoUrl,
+        origin: ImmutableOrigin,
+        cors_setting: Option<CorsSettings>,
+    ) {
+        let mut store = self.store.lock().unwrap();
+        let key = (url, origin, cors_setting);
+        if let Some(load_key) = store.pending_loads.url_to_load_key.get(&key).copied() {
+            if let Some(pending_load) = store.pending_loads.get_by_key_mut(&load_key) {
+                pending_load.evicted = true;
+            }
+        }
+        if let Some(completed_load) = store.completed_loads.remove(&key) {
+            store.delete_webrender_image(&completed_load.image_response);
+        }
+    }
 }
 
 impl ImageCacheImpl {
@@ -4,25 +4,32 @@
 
 #![deny(unsafe_code)]
 
+pub mod about;
 pub mod async_runtime;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
 mod decoder;
+mod download;
 pub mod filemanager_thread;
 mod hosts;
 pub mod hsts;
+pub mod http3;
 pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
 pub mod local_directory_listing;
 pub mod mime_classifier;
 pub mod protocols;
+mod proxy;
 pub mod request_interceptor;
+mod resolver;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
+mod websocket_deflate;
 mod websocket_loader;
+mod webtransport_loader;
 
 /// An implementation of the [Fetch specification](https://fetch.spec.whatwg.org/)
 pub mod fetch {
@@ -34,7 +41,19 @@ pub mod fetch {
 
 /// A module for re-exports of items used in unit tests.
 pub mod test {
-    pub use crate::decoder::DECODER_BUFFER_SIZE;
+    pub use crate::decoder::{DECODER_BUFFER_SIZE, Decoder};
+    pub use crate::download::{create_unique_file, sanitize_file_name};
     pub use crate::hosts::{parse_hostsfile, replace_host_table};
     pub use crate::http_loader::HttpState;
+    pub use crate::proxy::{Proxy, ProxyKind, bypasses_proxy, http_connect, socks5_connect};
+    pub use crate::resolver::{
+        DnsCache, RecordType, encode_dns_query, interleave_address_families, is_resolved_by_system,
+        parse_dns_response,
+    };
+    pub use crate::storage_thread::migrate_legacy_local_data;
+    pub use crate::websocket_deflate::{DeflateParameters, Deflater, InflatingStream, negotiate};
+    pub use crate::webtransport_loader::{
+        decode_capsule, decode_huffman, decode_response_status, decode_varint,
+        encode_connect_request, encode_varint, from_http3_error_code, to_http3_error_code,
+    };
 }
@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A protocol handler for custom schemes, such as `app://`, whose resources are provided by
+//! the embedder.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use headers::{ContentType, HeaderMapExt};
+use mime::Mime;
+use net_traits::http_status::HttpStatus;
+use net_traits::request::Request;
+use net_traits::response::{Response, ResponseBody};
+use net_traits::{NetworkError, ResourceFetchTiming};
+use servo_url::ServoUrl;
+
+use crate::fetch::methods::{DoneChannel, FetchContext};
+use crate::protocols::ProtocolHandler;
+
+/// A resource served by a [`CustomProtocolHandler`].
+pub struct CustomProtocolResponse {
+    pub body: Vec<u8>,
+    pub mime_type: Mime,
+}
+
+impl CustomProtocolResponse {
+    pub fn new(body: Vec<u8>, mime_type: Mime) -> Self {
+        Self { body, mime_type }
+    }
+}
+
+type LoadFunction = dyn Fn(&ServoUrl) -> Option<CustomProtocolResponse> + Send + Sync;
+
+/// A [`ProtocolHandler`] that serves the resources returned by a function of their URL. The
+/// function is called on the network thread, so it should return quickly. URLs for which it
+/// returns `None` fail to load with a network error.
+pub struct CustomProtocolHandler {
+    load: Box<LoadFunction>,
+    fetchable: bool,
+    secure: bool,
+}
+
+impl CustomProtocolHandler {
+    pub fn new(
+        load: impl Fn(&ServoUrl) -> Option<CustomProtocolResponse> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            load: Box::new(load),
+            fetchable: false,
+            secure: false,
+        }
+    }
+
+    /// Whether the resources can be read with `fetch()` in CORS mode, as the resources of
+    /// `data:` URLs can.
+    pub fn fetchable(mut self, fetchable: bool) -> Self {
+        self.fetchable = fetchable;
+        self
+    }
+
+    /// Whether the URLs are potentially trustworthy, so that documents loaded from them are
+    /// secure contexts.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+}
+
+impl ProtocolHandler for CustomProtocolHandler {
+    fn load(
+        &self,
+        request: &mut Request,
+        _done_chan: &mut DoneChannel,
+        _context: &FetchContext,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let url = request.current_url();
+        let response = match (self.load)(&url) {
+            Some(resource) => {
+                let mut response =
+                    Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+                *response.body.lock().unwrap() = ResponseBody::Done(resource.body);
+                response
+                    .headers
+                    .typed_insert(ContentType::from(resource.mime_type));
+                response.status = HttpStatus::default();
+                response
+            },
+            None => {
+                Response::network_error(NetworkError::Internal(format!("No resource for {url}")))
+            },
+        };
+
+        Box::pin(std::future::ready(response))
+    }
+
+    fn is_fetchable(&self) -> bool {
+        self.fetchable
+    }
+
+    fn is_secure(&self) -> bool {
+        self.secure
+    }
+}
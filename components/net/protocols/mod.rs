@@ -18,10 +18,12 @@ use net_traits::response::Response;
 use crate::fetch::methods::{DoneChannel, FetchContext, RangeRequestBounds};
 
 mod blob;
+mod custom;
 mod data;
 mod file;
 
 use blob::BlobProtocolHander;
+pub use custom::{CustomProtocolHandler, CustomProtocolResponse};
 use data::DataProtocolHander;
 use file::FileProtocolHander;
 
@@ -47,6 +49,12 @@ pub trait ProtocolHandler: Send + Sync {
     fn is_fetchable(&self) -> bool {
         false
     }
+
+    /// Specify if URLs of that protocol are potentially trustworthy, so that
+    /// documents loaded from them are secure contexts.
+    fn is_secure(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -99,6 +107,14 @@ impl ProtocolRegistry {
             .map(|handler| handler.is_fetchable())
             .unwrap_or(false)
     }
+
+    /// The registered schemes whose URLs are potentially trustworthy.
+    pub fn secure_schemes(&self) -> impl Iterator<Item = &str> {
+        self.handlers
+            .iter()
+            .filter(|(_, handler)| handler.is_secure())
+            .map(|(scheme, _)| scheme.as_str())
+    }
 }
 
 pub fn range_not_satisfiable_error(response: &mut Response) {
@@ -3,13 +3,16 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use content_security_policy::Destination;
-use embedder_traits::{EmbedderMsg, EmbedderProxy, WebResourceRequest, WebResourceResponseMsg};
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, WebResourceRequest, WebResourceResponse, WebResourceResponseMsg,
+};
 use ipc_channel::ipc;
 use log::error;
 use net_traits::NetworkError;
 use net_traits::http_status::HttpStatus;
 use net_traits::request::Request;
 use net_traits::response::{Response, ResponseBody};
+use servo_url::ServoUrl;
 
 use crate::fetch::methods::FetchContext;
 
@@ -23,25 +26,18 @@ impl RequestInterceptor {
         RequestInterceptor { embedder_proxy }
     }
 
+    /// Give the embedder the chance to serve, block or modify `request`. Returns whether the
+    /// embedder asked to observe the response to the request.
     pub fn intercept_request(
         &self,
         request: &mut Request,
         response: &mut Option<Response>,
         context: &FetchContext,
-    ) {
+    ) -> bool {
         let (sender, receiver) = ipc::channel().unwrap();
-        let is_for_main_frame = matches!(request.destination, Destination::Document);
-        let web_resource_request = WebResourceRequest {
-            method: request.method.clone(),
-            url: request.url().into_url(),
-            headers: request.headers.clone(),
-            is_for_main_frame,
-            is_redirect: request.redirect_count > 0,
-        };
-
         self.embedder_proxy.send(EmbedderMsg::WebResourceRequested(
             request.target_webview_id,
-            web_resource_request,
+            web_resource_request(request),
             sender,
         ));
 
@@ -80,7 +76,56 @@ impl RequestInterceptor {
                     break;
                 },
                 WebResourceResponseMsg::DoNotIntercept => break,
+                WebResourceResponseMsg::ModifyRequest(modification) => {
+                    request.headers.extend(modification.headers);
+                    if let Some(redirect_url) = modification.redirect_url {
+                        request.url_list.push(ServoUrl::from_url(redirect_url));
+                    }
+                    return modification.observe_response;
+                },
             }
         }
+        false
+    }
+
+    /// Give the embedder the chance to replace the status and headers of the response to
+    /// `request`.
+    pub fn intercept_response(&self, request: &Request, response: &mut Response) {
+        if response.is_network_error() {
+            return;
+        }
+        let (Some(url), Some(status_code)) = (response.url(), response.status.try_code()) else {
+            return;
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        let web_resource_response = WebResourceResponse::new(url.clone().into_url())
+            .headers(response.headers.clone())
+            .status_code(status_code)
+            .status_message(response.status.message().to_vec());
+        self.embedder_proxy
+            .send(EmbedderMsg::WebResourceResponseReceived(
+                request.target_webview_id,
+                web_resource_request(request),
+                web_resource_response,
+                sender,
+            ));
+
+        if let Ok(Some(modified_response)) = receiver.recv() {
+            response.headers = modified_response.headers;
+            response.status = HttpStatus::new(
+                modified_response.status_code,
+                modified_response.status_message,
+            );
+        }
+    }
+}
+
+fn web_resource_request(request: &Request) -> WebResourceRequest {
+    WebResourceRequest {
+        method: request.method.clone(),
+        url: request.url().into_url(),
+        headers: request.headers.clone(),
+        is_for_main_frame: matches!(request.destination, Destination::Document),
+        is_redirect: request.redirect_count > 0,
     }
 }
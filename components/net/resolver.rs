@@ -0,0 +1,429 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Resolution of host names into addresses, either with the resolver of the system or with
+//! DNS over HTTPS (<https://www.rfc-editor.org/rfc/rfc8484>), and a cache of the results that
+//! respects their time to live.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{LazyLock, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::{env, fs, io};
+
+use futures::future::join;
+use http::header::{ACCEPT, CONTENT_TYPE};
+use http::{Method, Request, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::dns::Name;
+use log::{debug, warn};
+use servo_config::pref;
+use tower_service::Service;
+
+use crate::connector::{
+    BoxedBody, CACertificates, CertificateErrorOverrideManager, Connector,
+    create_http_client_with_resolver, create_tls_config,
+};
+use crate::hosts::parse_hostsfile;
+
+/// How long the addresses returned by the resolver of the system are cached, since it does
+/// not say how long they are valid for.
+const SYSTEM_RESOLVER_TTL: Duration = Duration::from_secs(60);
+
+/// The longest time that addresses are cached for, whatever their time to live.
+const MAX_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The number of host names that are cached at most.
+const MAX_CACHE_ENTRIES: usize = 1000;
+
+/// How long to wait for a response from the DNS over HTTPS server before falling back to
+/// the resolver of the system.
+const DNS_OVER_HTTPS_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DNS_MESSAGE_MIME_TYPE: &str = "application/dns-message";
+
+static DNS_CACHE: LazyLock<Mutex<DnsCache>> = LazyLock::new(|| Mutex::new(DnsCache::new()));
+
+/// The host names in the hosts file of the system, which a DNS server does not know about.
+static SYSTEM_HOST_NAMES: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    let path = if cfg!(windows) {
+        let system_root = env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+        PathBuf::from(system_root).join("System32\\drivers\\etc\\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    };
+    fs::read_to_string(path)
+        .map(|hosts_file| {
+            parse_hostsfile(&hosts_file)
+                .into_keys()
+                .map(|host| host.to_ascii_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+});
+
+/// The client that sends queries to the DNS over HTTPS server, whose own host name is
+/// resolved by the resolver of the system.
+static DNS_OVER_HTTPS_CLIENT: LazyLock<Client<Connector, BoxedBody>> = LazyLock::new(|| {
+    let tls_config = create_tls_config(
+        CACertificates::Default,
+        false,
+        CertificateErrorOverrideManager::new(),
+    );
+    create_http_client_with_resolver(tls_config, ServoResolver::system())
+});
+
+/// The type of the records that are queried.
+/// <https://www.rfc-editor.org/rfc/rfc1035#section-3.2.2>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordType {
+    A = 1,
+    Aaaa = 28,
+}
+
+struct DnsCacheEntry {
+    addresses: Vec<IpAddr>,
+    expires: Instant,
+}
+
+/// A cache of resolved addresses, which are forgotten once their time to live has passed.
+#[derive(Default)]
+pub struct DnsCache {
+    entries: HashMap<String, DnsCacheEntry>,
+}
+
+impl DnsCache {
+    pub fn new() -> DnsCache {
+        Default::default()
+    }
+
+    /// The addresses of `host` if they were cached and have not expired at `now`.
+    pub fn get(&self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        self.entries
+            .get(host)
+            .filter(|entry| entry.expires > now)
+            .map(|entry| entry.addresses.clone())
+    }
+
+    /// Cache the addresses of `host` for `ttl` from `now`, evicting the entries that expire
+    /// first if the cache is full.
+    pub fn insert(&mut self, host: &str, addresses: Vec<IpAddr>, ttl: Duration, now: Instant) {
+        let ttl = ttl.min(MAX_TTL);
+        if ttl.is_zero() || addresses.is_empty() {
+            return;
+        }
+        if self.entries.len() >= MAX_CACHE_ENTRIES && !self.entries.contains_key(host) {
+            self.entries.retain(|_, entry| entry.expires > now);
+        }
+        if self.entries.len() >= MAX_CACHE_ENTRIES {
+            let first_to_expire = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires)
+                .map(|(host, _)| host.clone());
+            if let Some(first_to_expire) = first_to_expire {
+                self.entries.remove(&first_to_expire);
+            }
+        }
+        self.entries.insert(
+            host.to_owned(),
+            DnsCacheEntry {
+                addresses,
+                expires: now + ttl,
+            },
+        );
+    }
+}
+
+/// The resolver that the HTTP and WebSocket connections use.
+#[derive(Clone)]
+pub struct ServoResolver {
+    /// Whether host names may be resolved with DNS over HTTPS, which is not the case when
+    /// connecting to the DNS over HTTPS server itself.
+    allow_dns_over_https: bool,
+}
+
+impl ServoResolver {
+    pub fn new() -> ServoResolver {
+        ServoResolver {
+            allow_dns_over_https: true,
+        }
+    }
+
+    fn system() -> ServoResolver {
+        ServoResolver {
+            allow_dns_over_https: false,
+        }
+    }
+}
+
+impl Default for ServoResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Service<Name> for ServoResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Ok(()).into()
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let allow_dns_over_https = self.allow_dns_over_https;
+        Box::pin(async move {
+            // The port is set by the connector.
+            let addresses = resolve(name.as_str(), allow_dns_over_https).await?;
+            Ok(addresses
+                .into_iter()
+                .map(|address| SocketAddr::new(address, 0))
+                .collect::<Vec<_>>()
+                .into_iter())
+        })
+    }
+}
+
+/// Resolve `host` into addresses, ordered so that the connection attempts alternate between
+/// IPv6 and IPv4 addresses. <https://www.rfc-editor.org/rfc/rfc8305#section-4>
+pub(crate) async fn resolve(host: &str, allow_dns_over_https: bool) -> io::Result<Vec<IpAddr>> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(address) = host.parse::<IpAddr>() {
+        return Ok(vec![address]);
+    }
+    let host = host.to_ascii_lowercase();
+
+    if let Some(addresses) = DNS_CACHE.lock().unwrap().get(&host, Instant::now()) {
+        return Ok(addresses);
+    }
+
+    let mut resolved = None;
+    if allow_dns_over_https &&
+        pref!(network_dns_over_https_enabled) &&
+        !is_resolved_by_system(&host)
+    {
+        match resolve_with_dns_over_https(&host).await {
+            Ok(result) => resolved = Some(result),
+            Err(error) => warn!("DNS over HTTPS failed for {host}, using the system: {error}"),
+        }
+    }
+    let (addresses, ttl) = match resolved {
+        Some(resolved) => resolved,
+        None => {
+            let addresses = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .map(|address| address.ip())
+                .collect();
+            (addresses, SYSTEM_RESOLVER_TTL)
+        },
+    };
+    if addresses.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No addresses found for {host}"),
+        ));
+    }
+
+    let addresses = interleave_address_families(addresses);
+    DNS_CACHE
+        .lock()
+        .unwrap()
+        .insert(&host, addresses.clone(), ttl, Instant::now());
+    Ok(addresses)
+}
+
+/// Whether `host` is always resolved by the resolver of the system, because it is a loopback
+/// name or it is in the hosts file of the system, which a DNS server cannot answer for.
+/// <https://www.rfc-editor.org/rfc/rfc6761#section-6.3>
+pub fn is_resolved_by_system(host: &str) -> bool {
+    host == "localhost" || host.ends_with(".localhost") || SYSTEM_HOST_NAMES.contains(host)
+}
+
+/// Order `addresses` so that IPv6 and IPv4 addresses alternate, starting with IPv6.
+pub fn interleave_address_families(addresses: Vec<IpAddr>) -> Vec<IpAddr> {
+    let (ipv6, ipv4): (Vec<_>, Vec<_>) = addresses.into_iter().partition(IpAddr::is_ipv6);
+    let mut ipv6 = ipv6.into_iter();
+    let mut ipv4 = ipv4.into_iter();
+    let mut interleaved = Vec::new();
+    loop {
+        match (ipv6.next(), ipv4.next()) {
+            (None, None) => return interleaved,
+            (ipv6, ipv4) => interleaved.extend(ipv6.into_iter().chain(ipv4)),
+        }
+    }
+}
+
+/// Query the A and AAAA records of `host` from the DNS over HTTPS server, returning the
+/// addresses and how long they may be cached for.
+async fn resolve_with_dns_over_https(host: &str) -> Result<(Vec<IpAddr>, Duration), String> {
+    let (ipv4, ipv6) = join(
+        query_dns_over_https(host, RecordType::A),
+        query_dns_over_https(host, RecordType::Aaaa),
+    )
+    .await;
+
+    // One family being unavailable is fine, as long as the other is.
+    let (addresses, ttl) = match (ipv4, ipv6) {
+        (Err(error), Err(_)) => return Err(error),
+        (Ok(result), Err(error)) | (Err(error), Ok(result)) => {
+            debug!("DNS over HTTPS query for {host} failed: {error}");
+            result
+        },
+        (Ok((mut ipv4, ipv4_ttl)), Ok((ipv6, ipv6_ttl))) => {
+            ipv4.extend(ipv6);
+            (ipv4, ipv4_ttl.min(ipv6_ttl))
+        },
+    };
+    Ok((addresses, Duration::from_secs(ttl.into())))
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc8484#section-4.1>
+async fn query_dns_over_https(
+    host: &str,
+    record_type: RecordType,
+) -> Result<(Vec<IpAddr>, u32), String> {
+    let query = encode_dns_query(host, record_type)?;
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(pref!(network_dns_over_https_url))
+        .header(CONTENT_TYPE, DNS_MESSAGE_MIME_TYPE)
+        .header(ACCEPT, DNS_MESSAGE_MIME_TYPE)
+        .body(Full::new(query.into()).map_err(|_| unreachable!()).boxed())
+        .map_err(|error| error.to_string())?;
+
+    let exchange = async {
+        let response = DNS_OVER_HTTPS_CLIENT
+            .request(request)
+            .await
+            .map_err(|error| error.to_string())?;
+        if response.status() != StatusCode::OK {
+            return Err(format!("Unexpected status {}", response.status()));
+        }
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|error| error.to_string())?;
+        Ok(body.to_bytes())
+    };
+    let response = tokio::time::timeout(DNS_OVER_HTTPS_TIMEOUT, exchange)
+        .await
+        .map_err(|_| "Timed out".to_owned())??;
+    parse_dns_response(&response, record_type)
+}
+
+/// Encode a recursive query for the records of `host` of the given type, with an ID of
+/// zero so that HTTP caches can reuse the responses.
+/// <https://www.rfc-editor.org/rfc/rfc1035#section-4.1>
+pub fn encode_dns_query(host: &str, record_type: RecordType) -> Result<Vec<u8>, String> {
+    let mut query = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in host.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid host name: {host}"));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    if query.len() > 12 + 255 {
+        return Err(format!("Invalid host name: {host}"));
+    }
+    query.extend_from_slice(&(record_type as u16).to_be_bytes());
+    // The Internet class.
+    query.extend_from_slice(&[0, 1]);
+    Ok(query)
+}
+
+/// Parse the addresses in the answers of a response of the given type, and the smallest
+/// of their times to live in seconds.
+/// <https://www.rfc-editor.org/rfc/rfc1035#section-4.1>
+pub fn parse_dns_response(
+    response: &[u8],
+    record_type: RecordType,
+) -> Result<(Vec<IpAddr>, u32), String> {
+    let truncated = || "Truncated DNS response".to_owned();
+    let read_u16 = |offset: usize| -> Result<u16, String> {
+        response
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(truncated)
+    };
+
+    let flags = read_u16(2)?;
+    if flags & 0x8000 == 0 {
+        return Err("DNS message is not a response".to_owned());
+    }
+    match flags & 0x000f {
+        0 => {},
+        3 => return Ok((Vec::new(), 0)),
+        response_code => return Err(format!("DNS response code {response_code}")),
+    }
+    let question_count = read_u16(4)?;
+    let answer_count = read_u16(6)?;
+
+    let mut offset = 12;
+    for _ in 0..question_count {
+        // The name, type and class.
+        offset = skip_name(response, offset)? + 4;
+    }
+
+    let mut addresses = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answer_count {
+        offset = skip_name(response, offset)?;
+        let answer_type = read_u16(offset)?;
+        let class = read_u16(offset + 2)?;
+        let answer_ttl = response
+            .get(offset + 4..offset + 8)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or_else(truncated)?;
+        let data_length = read_u16(offset + 8)? as usize;
+        offset += 10;
+        let data = response
+            .get(offset..offset + data_length)
+            .ok_or_else(truncated)?;
+        offset += data_length;
+
+        // Other answers, such as the CNAME records that lead to the addresses, are skipped.
+        if answer_type != record_type as u16 || class != 1 {
+            continue;
+        }
+        let address = match (record_type, data.len()) {
+            (RecordType::A, 4) => IpAddr::from(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (RecordType::Aaaa, 16) => {
+                IpAddr::from(Ipv6Addr::from(<[u8; 16]>::try_from(data).unwrap()))
+            },
+            _ => return Err("Invalid address in DNS response".to_owned()),
+        };
+        addresses.push(address);
+        ttl = ttl.min(answer_ttl);
+    }
+
+    if addresses.is_empty() {
+        ttl = 0;
+    }
+    Ok((addresses, ttl))
+}
+
+/// The offset after the possibly compressed name at `offset`.
+fn skip_name(message: &[u8], mut offset: usize) -> Result<usize, String> {
+    loop {
+        let length = *message
+            .get(offset)
+            .ok_or_else(|| "Truncated DNS response".to_owned())?;
+        match length {
+            0 => return Ok(offset + 1),
+            // A pointer to a name earlier in the message ends the name.
+            length if length & 0xc0 == 0xc0 => return Ok(offset + 2),
+            length => offset += 1 + length as usize,
+        }
+    }
+}
@@ -5,15 +5,17 @@
 //! A thread that takes a URL and streams back the binary data.
 
 use std::borrow::{Cow, ToOwned};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
 use std::thread;
 use std::time::Duration;
 
+use base::id::HistoryStateId;
 use cookie::Cookie;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
@@ -30,7 +32,8 @@ use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::{
     CookieSource, CoreResourceMsg, CoreResourceThread, CustomResponseMediator, DiscardFetch,
     FetchChannels, FetchTaskTarget, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
-    WebSocketDomAction, WebSocketNetworkEvent,
+    WebSocketDomAction, WebSocketNetworkEvent, WebTransportDomAction, WebTransportNetworkEvent,
+    WebTransportSessionOptions,
 };
 use profile_traits::mem::{ProfilerChan as MemProfilerChan, Report, ReportKind, ReportsChan};
 use profile_traits::path;
@@ -38,25 +41,29 @@ use profile_traits::time::ProfilerChan;
 use rustls::RootCertStore;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
+use servo_config::prefs;
 use servo_url::{ImmutableOrigin, ServoUrl};
 
+use crate::about;
 use crate::async_runtime::HANDLE;
 use crate::connector::{
     CACertificates, CertificateErrorOverrideManager, create_http_client, create_tls_config,
 };
 use crate::cookie::ServoCookie;
-use crate::cookie_storage::CookieStorage;
+use crate::cookie_storage::{CookiePartition, CookieStorage};
+use crate::download::{NavigationTarget, download};
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::fetch_params::FetchParams;
 use crate::fetch::methods::{CancellationListener, FetchContext, fetch};
 use crate::filemanager_thread::FileManager;
-use crate::hsts::HstsList;
+use crate::hsts::{HstsList, PersistedHstsList};
 use crate::http_cache::HttpCache;
 use crate::http_loader::{HttpState, http_redirect_fetch};
+use crate::http3::Http3Client;
 use crate::protocols::ProtocolRegistry;
 use crate::request_interceptor::RequestInterceptor;
 use crate::storage_thread::StorageThreadFactory;
-use crate::websocket_loader;
+use crate::{websocket_loader, webtransport_loader};
 
 /// Load a file with CA certificate and produce a RootCertStore with the results.
 fn load_root_cert_store_from_file(file_path: String) -> io::Result<RootCertStore> {
@@ -147,6 +154,7 @@ pub fn new_core_resource_thread(
                 cancellation_listeners: Default::default(),
             };
 
+            about::set_memory_profiler(mem_profiler_chan.clone());
             mem_profiler_chan.run_with_memory_reporting(
                 || {
                     channel_manager.start(
@@ -182,11 +190,14 @@ fn create_http_states(
 ) -> (Arc<HttpState>, Arc<HttpState>) {
     let mut hsts_list = HstsList::from_servo_preload();
     let mut auth_cache = AuthCache::default();
-    let http_cache = HttpCache::default();
+    let mut http_cache = HttpCache::default();
     let mut cookie_jar = CookieStorage::new(150);
     if let Some(config_dir) = config_dir {
+        http_cache.read_from_disk(&config_dir.join("http_cache"));
         read_json_from_file(&mut auth_cache, config_dir, "auth_cache.json");
-        read_json_from_file(&mut hsts_list, config_dir, "hsts_list.json");
+        let mut persisted_hsts_list = PersistedHstsList::default();
+        read_json_from_file(&mut persisted_hsts_list, config_dir, "hsts_list.json");
+        hsts_list.extend_from_persisted(persisted_hsts_list);
         read_json_from_file(&mut cookie_jar, config_dir, "cookie_jar.json");
     }
 
@@ -195,7 +206,7 @@ fn create_http_states(
         hsts_list: RwLock::new(hsts_list),
         cookie_jar: RwLock::new(cookie_jar),
         auth_cache: RwLock::new(auth_cache),
-        history_states: RwLock::new(HashMap::new()),
+        history_states: RwLock::new(HistoryStates::default()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
         client: create_http_client(create_tls_config(
@@ -203,8 +214,17 @@ fn create_http_states(
             ignore_certificate_errors,
             override_manager.clone(),
         )),
+        http3_client: Http3Client::new(create_tls_config(
+            ca_certificates.clone(),
+            ignore_certificate_errors,
+            override_manager.clone(),
+        )),
         override_manager,
         embedder_proxy: Mutex::new(embedder_proxy.clone()),
+        request_content_codings: Default::default(),
+        online: AtomicBool::new(true),
+        cookie_policies: Default::default(),
+        webdriver_interceptor: Default::default(),
     };
 
     let override_manager = CertificateErrorOverrideManager::new();
@@ -212,19 +232,31 @@ fn create_http_states(
         hsts_list: RwLock::new(HstsList::from_servo_preload()),
         cookie_jar: RwLock::new(CookieStorage::new(150)),
         auth_cache: RwLock::new(AuthCache::default()),
-        history_states: RwLock::new(HashMap::new()),
+        history_states: RwLock::new(HistoryStates::default()),
         http_cache: RwLock::new(HttpCache::default()),
         http_cache_state: Mutex::new(HashMap::new()),
         client: create_http_client(create_tls_config(
+            ca_certificates.clone(),
+            ignore_certificate_errors,
+            override_manager.clone(),
+        )),
+        http3_client: Http3Client::new(create_tls_config(
             ca_certificates,
             ignore_certificate_errors,
             override_manager.clone(),
         )),
         override_manager,
         embedder_proxy: Mutex::new(embedder_proxy),
+        request_content_codings: Default::default(),
+        online: AtomicBool::new(true),
+        cookie_policies: Default::default(),
+        webdriver_interceptor: Default::default(),
     };
 
-    (Arc::new(http_state), Arc::new(private_http_state))
+    let (http_state, private_http_state) = (Arc::new(http_state), Arc::new(private_http_state));
+    prefs::add_observer(Arc::downgrade(&http_state));
+    prefs::add_observer(Arc::downgrade(&private_http_state));
+    (http_state, private_http_state)
 }
 
 impl ResourceChannelManager {
@@ -293,12 +325,14 @@ impl ResourceChannelManager {
             path: path!["memory-cache", "public"],
             kind: ReportKind::ExplicitJemallocHeapSize,
             size: public_cache.size_of(&mut ops),
+            attribution: None,
         };
 
         let private_report = Report {
             path: path!["memory-cache", "private"],
             kind: ReportKind::ExplicitJemallocHeapSize,
             size: private_cache.size_of(&mut ops),
+            attribution: None,
         };
 
         msg.send(vec![public_report, private_report]);
@@ -358,6 +392,17 @@ impl ResourceChannelManager {
                     action_receiver,
                     http_state,
                 ),
+                FetchChannels::WebTransport {
+                    options,
+                    event_sender,
+                    action_receiver,
+                } => self.resource_manager.webtransport_connect(
+                    request_builder,
+                    options,
+                    event_sender,
+                    action_receiver,
+                    http_state,
+                ),
                 FetchChannels::Prefetch => self.resource_manager.fetch(
                     request_builder,
                     None,
@@ -367,6 +412,10 @@ impl ResourceChannelManager {
                     protocols,
                 ),
             },
+            CoreResourceMsg::Download(request_builder) => {
+                self.resource_manager
+                    .download(request_builder, http_state, protocols)
+            },
             CoreResourceMsg::Cancel(request_ids) => {
                 for cancellation_listener in request_ids
                     .into_iter()
@@ -395,25 +444,38 @@ impl ResourceChannelManager {
                     protocols,
                 )
             },
-            CoreResourceMsg::SetCookieForUrl(request, cookie, source) => self
-                .resource_manager
-                .set_cookie_for_url(&request, cookie.into_inner().to_owned(), source, http_state),
-            CoreResourceMsg::SetCookiesForUrl(request, cookies, source) => {
+            CoreResourceMsg::SetCookieForUrl(request, cookie, source) => {
+                self.resource_manager.set_cookie_for_url(
+                    &request,
+                    cookie.into_inner().to_owned(),
+                    source,
+                    &CookiePartition::first_party(&request),
+                    http_state,
+                )
+            },
+            CoreResourceMsg::SetCookiesForUrl(request, cookies, source, context) => {
+                let Some(partition) = http_state.cookie_partition(&request, &context) else {
+                    return true;
+                };
                 for cookie in cookies {
                     self.resource_manager.set_cookie_for_url(
                         &request,
                         cookie.into_inner(),
                         source,
+                        &partition,
                         http_state,
                     );
                 }
             },
-            CoreResourceMsg::GetCookiesForUrl(url, consumer, source) => {
-                let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-                cookie_jar.remove_expired_cookies_for_url(&url);
-                consumer
-                    .send(cookie_jar.cookies_for_url(&url, source))
-                    .unwrap();
+            CoreResourceMsg::GetCookiesForUrl(url, consumer, source, context) => {
+                let cookies = http_state
+                    .cookie_partition(&url, &context)
+                    .and_then(|partition| {
+                        let mut cookie_jar = http_state.cookie_jar.write().unwrap();
+                        cookie_jar.remove_expired_cookies_for_url(&url);
+                        cookie_jar.cookies_for_url_in_partition(&url, source, &partition)
+                    });
+                consumer.send(cookies).unwrap();
             },
             CoreResourceMsg::NetworkMediator(mediator_chan, origin) => {
                 self.resource_manager
@@ -451,6 +513,19 @@ impl ResourceChannelManager {
             CoreResourceMsg::ClearCache => {
                 http_state.http_cache.write().unwrap().clear();
             },
+            CoreResourceMsg::SetOnline(online) => {
+                http_state.online.store(online, Ordering::Relaxed);
+            },
+            CoreResourceMsg::SetWebDriverInterceptor(sender) => {
+                *http_state.webdriver_interceptor.write().unwrap() = sender;
+            },
+            CoreResourceMsg::SetCookiePolicy(webview_id, policy) => {
+                let mut cookie_policies = http_state.cookie_policies.write().unwrap();
+                match policy {
+                    Some(policy) => cookie_policies.insert(webview_id, policy),
+                    None => cookie_policies.remove(&webview_id),
+                };
+            },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
@@ -465,9 +540,15 @@ impl ResourceChannelManager {
                         Err(_) => warn!("Error writing cookie jar to disk"),
                     }
                     match http_state.hsts_list.read() {
-                        Ok(hsts) => write_json_to_file(&*hsts, config_dir, "hsts_list.json"),
+                        Ok(hsts) => {
+                            write_json_to_file(&hsts.to_persisted(), config_dir, "hsts_list.json")
+                        },
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
+                    match http_state.http_cache.read() {
+                        Ok(http_cache) => http_cache.write_to_disk(&config_dir.join("http_cache")),
+                        Err(_) => warn!("Error writing http cache to disk"),
+                    }
                 }
                 self.resource_manager.exit();
                 let _ = sender.send(());
@@ -548,6 +629,77 @@ pub struct AuthCache {
     pub entries: HashMap<String, AuthCacheEntry>,
 }
 
+/// The default limit on the combined size, in bytes, of the serialized state objects of all
+/// session history entries.
+const DEFAULT_HISTORY_STATES_MAX_SIZE: usize = 16 * 1024 * 1024;
+
+/// The serialized state objects of session history entries, set by `history.pushState()` and
+/// `history.replaceState()`. To bound memory usage, the least recently set states are evicted
+/// once the combined size of all states exceeds a limit. Traversing to an entry whose state was
+/// evicted results in a null `history.state`.
+pub struct HistoryStates {
+    states: HashMap<HistoryStateId, Vec<u8>>,
+    /// The ids of all stored states, from least to most recently set.
+    order: VecDeque<HistoryStateId>,
+    /// The combined size of all stored states, in bytes.
+    size: usize,
+    /// The maximum combined size of all stored states, in bytes.
+    max_size: usize,
+}
+
+impl Default for HistoryStates {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_STATES_MAX_SIZE)
+    }
+}
+
+impl HistoryStates {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            states: HashMap::new(),
+            order: VecDeque::new(),
+            size: 0,
+            max_size,
+        }
+    }
+
+    pub fn get(&self, history_state_id: &HistoryStateId) -> Option<&Vec<u8>> {
+        self.states.get(history_state_id)
+    }
+
+    /// Store the state for the given entry, evicting the least recently set states if the limit
+    /// is exceeded. The most recently set state is never evicted, even if it exceeds the limit on
+    /// its own.
+    pub fn insert(&mut self, history_state_id: HistoryStateId, state: Vec<u8>) {
+        self.remove(&history_state_id);
+        self.size += state.len();
+        self.states.insert(history_state_id, state);
+        self.order.push_back(history_state_id);
+
+        while self.size > self.max_size && self.order.len() > 1 {
+            let Some(evicted) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted_state) = self.states.remove(&evicted) {
+                debug!("Evicting history state {evicted:?} to limit memory usage");
+                self.size -= evicted_state.len();
+            }
+        }
+    }
+
+    pub fn remove(&mut self, history_state_id: &HistoryStateId) {
+        if let Some(state) = self.states.remove(history_state_id) {
+            self.size -= state.len();
+            self.order.retain(|id| id != history_state_id);
+        }
+    }
+
+    /// The combined size of all stored states, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
 pub struct CoreResourceManager {
     user_agent: Cow<'static, str>,
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
@@ -731,11 +883,12 @@ impl CoreResourceManager {
         request: &ServoUrl,
         cookie: Cookie<'static>,
         source: CookieSource,
+        partition: &CookiePartition,
         http_state: &Arc<HttpState>,
     ) {
         if let Some(cookie) = ServoCookie::new_wrapped(cookie, request, source) {
             let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-            cookie_jar.push(cookie, request, source)
+            cookie_jar.push_in_partition(cookie, request, source, partition)
         }
     }
 
@@ -743,7 +896,7 @@ impl CoreResourceManager {
         &self,
         request_builder: RequestBuilder,
         res_init_: Option<ResponseInit>,
-        mut sender: Target,
+        sender: Target,
         http_state: &Arc<HttpState>,
         cancellation_listener: Arc<CancellationListener>,
         protocols: Arc<ProtocolRegistry>,
@@ -759,6 +912,18 @@ impl CoreResourceManager {
             _ => ResourceTimingType::Resource,
         };
 
+        let embedder_proxy = request_builder
+            .allows_downloads
+            .then(|| http_state.embedder_proxy.lock().unwrap().clone());
+        let download_context = request_builder
+            .allows_downloads
+            .then(|| self.download_context(&http_state, protocols.clone()));
+        let mut target = NavigationTarget::new(
+            sender,
+            &request_builder,
+            embedder_proxy,
+            cancellation_listener.clone(),
+        );
         let request = request_builder.build();
         let url = request.current_url();
 
@@ -810,14 +975,14 @@ impl CoreResourceManager {
                         &mut CorsCache::default(),
                         response,
                         true,
-                        &mut sender,
+                        &mut target,
                         &mut None,
                         &context,
                     )
                     .await;
                 },
                 None => {
-                    fetch(request, &mut sender, &context).await;
+                    fetch(request, &mut target, &context).await;
                 },
             };
 
@@ -829,9 +994,59 @@ impl CoreResourceManager {
                     .unwrap()
                     .invalidate_token(&context.file_token, id);
             }
+
+            // A navigation response that was handed over to the download manager keeps
+            // downloading after the navigation is done with it, until it completes, fails, or
+            // is cancelled.
+            if let (Some((download, request_builder)), Some(download_context)) =
+                (target.into_download(), download_context)
+            {
+                download.run(request_builder, download_context, false).await;
+            }
         });
     }
 
+    fn download(
+        &self,
+        request_builder: RequestBuilder,
+        http_state: &Arc<HttpState>,
+        protocols: Arc<ProtocolRegistry>,
+    ) {
+        let embedder_proxy = http_state.embedder_proxy.lock().unwrap().clone();
+        let new_context = self.download_context(http_state, protocols);
+        HANDLE.lock().unwrap().as_ref().unwrap().spawn(download(
+            request_builder,
+            embedder_proxy,
+            new_context,
+        ));
+    }
+
+    /// A function that builds the [`FetchContext`] of each attempt at fetching a download.
+    fn download_context(
+        &self,
+        http_state: &Arc<HttpState>,
+        protocols: Arc<ProtocolRegistry>,
+    ) -> impl Fn(Arc<CancellationListener>) -> FetchContext + Send + 'static {
+        let http_state = http_state.clone();
+        let user_agent = self.user_agent.clone();
+        let filemanager = self.filemanager.clone();
+        let request_interceptor = self.request_interceptor.clone();
+
+        move |cancellation_listener| FetchContext {
+            state: http_state.clone(),
+            user_agent: user_agent.clone(),
+            devtools_chan: None,
+            filemanager: Arc::new(Mutex::new(filemanager.clone())),
+            file_token: FileTokenCheck::NotRequired,
+            request_interceptor: Arc::new(Mutex::new(request_interceptor.clone())),
+            cancellation_listener,
+            timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
+                ResourceTimingType::Navigation,
+            ))),
+            protocols: protocols.clone(),
+        }
+    }
+
     fn websocket_connect(
         &self,
         request: RequestBuilder,
@@ -848,4 +1063,23 @@ impl CoreResourceManager {
             self.ignore_certificate_errors,
         );
     }
+
+    fn webtransport_connect(
+        &self,
+        request: RequestBuilder,
+        options: WebTransportSessionOptions,
+        event_sender: IpcSender<WebTransportNetworkEvent>,
+        action_receiver: IpcReceiver<WebTransportDomAction>,
+        http_state: &Arc<HttpState>,
+    ) {
+        webtransport_loader::init(
+            request,
+            options,
+            event_sender,
+            action_receiver,
+            http_state.clone(),
+            self.ca_certificates.clone(),
+            self.ignore_certificate_errors,
+        );
+    }
 }
@@ -8,13 +8,25 @@ use std::path::PathBuf;
 use std::thread;
 
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use log::warn;
+use net_traits::pub_domains::site;
+use net_traits::storage_thread::{StorageKey, StorageThreadMsg, StorageType};
 use servo_url::ServoUrl;
 
 use crate::resource_thread;
 
 const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
+/// The file that local storage is saved to, as a list of storage keys and their data.
+const LOCAL_DATA_FILE: &str = "partitioned_local_data.json";
+
+/// The file that local storage was saved to before storage was partitioned by top-level
+/// site, as a map from origins to their data. It is read once to migrate older profiles.
+const LEGACY_LOCAL_DATA_FILE: &str = "local_data.json";
+
+/// The total size of the keys and values of a storage area, and the key-value pairs.
+pub type StorageData = (usize, BTreeMap<String, String>);
+
 pub trait StorageThreadFactory {
     fn new(config_dir: Option<PathBuf>) -> Self;
 }
@@ -35,52 +47,92 @@ impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
 
 struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
-    session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
-    local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    session_data: HashMap<StorageKey, StorageData>,
+    local_data: HashMap<StorageKey, StorageData>,
     config_dir: Option<PathBuf>,
 }
 
 impl StorageManager {
     fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
         let mut local_data = HashMap::new();
+        let mut migrated = false;
         if let Some(ref config_dir) = config_dir {
-            resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            if config_dir.join(LOCAL_DATA_FILE).exists() {
+                let mut partitions: Vec<(StorageKey, StorageData)> = vec![];
+                resource_thread::read_json_from_file(&mut partitions, config_dir, LOCAL_DATA_FILE);
+                local_data = partitions.into_iter().collect();
+            } else if config_dir.join(LEGACY_LOCAL_DATA_FILE).exists() {
+                let mut legacy_data = HashMap::new();
+                resource_thread::read_json_from_file(
+                    &mut legacy_data,
+                    config_dir,
+                    LEGACY_LOCAL_DATA_FILE,
+                );
+                local_data = migrate_legacy_local_data(legacy_data);
+                migrated = true;
+            }
         }
-        StorageManager {
+        let manager = StorageManager {
             port,
             session_data: HashMap::new(),
             local_data,
             config_dir,
+        };
+        if migrated {
+            manager.save_state();
         }
+        manager
     }
 }
 
+/// Convert local storage saved before storage was partitioned, which is keyed by origin, to
+/// the storage of top-level documents of those origins. Origins that can't be parsed are
+/// dropped.
+pub fn migrate_legacy_local_data(
+    legacy_data: HashMap<String, StorageData>,
+) -> HashMap<StorageKey, StorageData> {
+    legacy_data
+        .into_iter()
+        .filter_map(|(origin, data)| match ServoUrl::parse(&origin) {
+            Ok(url) => Some((StorageKey::first_party(&url.origin()), data)),
+            Err(error) => {
+                warn!("Dropping local storage of unparsable origin {origin:?}: {error}");
+                None
+            },
+        })
+        .collect()
+}
+
 impl StorageManager {
     fn start(&mut self) {
         loop {
             match self.port.recv().unwrap() {
-                StorageThreadMsg::Length(sender, url, storage_type) => {
-                    self.length(sender, url, storage_type)
+                StorageThreadMsg::Length(sender, storage_key, storage_type) => {
+                    self.length(sender, storage_key, storage_type)
                 },
-                StorageThreadMsg::Key(sender, url, storage_type, index) => {
-                    self.key(sender, url, storage_type, index)
+                StorageThreadMsg::Key(sender, storage_key, storage_type, index) => {
+                    self.key(sender, storage_key, storage_type, index)
                 },
-                StorageThreadMsg::Keys(sender, url, storage_type) => {
-                    self.keys(sender, url, storage_type)
+                StorageThreadMsg::Keys(sender, storage_key, storage_type) => {
+                    self.keys(sender, storage_key, storage_type)
                 },
-                StorageThreadMsg::SetItem(sender, url, storage_type, name, value) => {
-                    self.set_item(sender, url, storage_type, name, value);
+                StorageThreadMsg::SetItem(sender, storage_key, storage_type, name, value) => {
+                    self.set_item(sender, storage_key, storage_type, name, value);
                     self.save_state()
                 },
-                StorageThreadMsg::GetItem(sender, url, storage_type, name) => {
-                    self.request_item(sender, url, storage_type, name)
+                StorageThreadMsg::GetItem(sender, storage_key, storage_type, name) => {
+                    self.request_item(sender, storage_key, storage_type, name)
                 },
-                StorageThreadMsg::RemoveItem(sender, url, storage_type, name) => {
-                    self.remove_item(sender, url, storage_type, name);
+                StorageThreadMsg::RemoveItem(sender, storage_key, storage_type, name) => {
+                    self.remove_item(sender, storage_key, storage_type, name);
                     self.save_state()
                 },
-                StorageThreadMsg::Clear(sender, url, storage_type) => {
-                    self.clear(sender, url, storage_type);
+                StorageThreadMsg::Clear(sender, storage_key, storage_type) => {
+                    self.clear(sender, storage_key, storage_type);
+                    self.save_state()
+                },
+                StorageThreadMsg::ClearSite(url) => {
+                    self.clear_site(url);
                     self.save_state()
                 },
                 StorageThreadMsg::Exit(sender) => {
@@ -94,14 +146,12 @@ impl StorageManager {
 
     fn save_state(&self) {
         if let Some(ref config_dir) = self.config_dir {
-            resource_thread::write_json_to_file(&self.local_data, config_dir, "local_data.json");
+            let partitions: Vec<_> = self.local_data.iter().collect();
+            resource_thread::write_json_to_file(&partitions, config_dir, LOCAL_DATA_FILE);
         }
     }
 
-    fn select_data(
-        &self,
-        storage_type: StorageType,
-    ) -> &HashMap<String, (usize, BTreeMap<String, String>)> {
+    fn select_data(&self, storage_type: StorageType) -> &HashMap<StorageKey, StorageData> {
         match storage_type {
             StorageType::Session => &self.session_data,
             StorageType::Local => &self.local_data,
@@ -111,42 +161,44 @@ impl StorageManager {
     fn select_data_mut(
         &mut self,
         storage_type: StorageType,
-    ) -> &mut HashMap<String, (usize, BTreeMap<String, String>)> {
+    ) -> &mut HashMap<StorageKey, StorageData> {
         match storage_type {
             StorageType::Session => &mut self.session_data,
             StorageType::Local => &mut self.local_data,
         }
     }
 
-    fn length(&self, sender: IpcSender<usize>, url: ServoUrl, storage_type: StorageType) {
-        let origin = self.origin_as_string(url);
+    fn length(&self, sender: IpcSender<usize>, storage_key: StorageKey, storage_type: StorageType) {
         let data = self.select_data(storage_type);
         sender
-            .send(data.get(&origin).map_or(0, |(_, entry)| entry.len()))
+            .send(data.get(&storage_key).map_or(0, |(_, entry)| entry.len()))
             .unwrap();
     }
 
     fn key(
         &self,
         sender: IpcSender<Option<String>>,
-        url: ServoUrl,
+        storage_key: StorageKey,
         storage_type: StorageType,
         index: u32,
     ) {
-        let origin = self.origin_as_string(url);
         let data = self.select_data(storage_type);
         let key = data
-            .get(&origin)
+            .get(&storage_key)
             .and_then(|(_, entry)| entry.keys().nth(index as usize))
             .cloned();
         sender.send(key).unwrap();
     }
 
-    fn keys(&self, sender: IpcSender<Vec<String>>, url: ServoUrl, storage_type: StorageType) {
-        let origin = self.origin_as_string(url);
+    fn keys(
+        &self,
+        sender: IpcSender<Vec<String>>,
+        storage_key: StorageKey,
+        storage_type: StorageType,
+    ) {
         let data = self.select_data(storage_type);
         let keys = data
-            .get(&origin)
+            .get(&storage_key)
             .map_or(vec![], |(_, entry)| entry.keys().cloned().collect());
 
         sender.send(keys).unwrap();
@@ -159,18 +211,18 @@ impl StorageManager {
     fn set_item(
         &mut self,
         sender: IpcSender<Result<(bool, Option<String>), ()>>,
-        url: ServoUrl,
+        storage_key: StorageKey,
         storage_type: StorageType,
         name: String,
         value: String,
     ) {
-        let origin = self.origin_as_string(url);
-
         let (this_storage_size, other_storage_size) = {
             let local_data = self.select_data(StorageType::Local);
             let session_data = self.select_data(StorageType::Session);
-            let local_data_size = local_data.get(&origin).map_or(0, |&(total, _)| total);
-            let session_data_size = session_data.get(&origin).map_or(0, |&(total, _)| total);
+            let local_data_size = local_data.get(&storage_key).map_or(0, |&(total, _)| total);
+            let session_data_size = session_data
+                .get(&storage_key)
+                .map_or(0, |&(total, _)| total);
             match storage_type {
                 StorageType::Local => (local_data_size, session_data_size),
                 StorageType::Session => (session_data_size, local_data_size),
@@ -178,12 +230,12 @@ impl StorageManager {
         };
 
         let data = self.select_data_mut(storage_type);
-        if !data.contains_key(&origin) {
-            data.insert(origin.clone(), (0, BTreeMap::new()));
+        if !data.contains_key(&storage_key) {
+            data.insert(storage_key.clone(), (0, BTreeMap::new()));
         }
 
         let message = data
-            .get_mut(&origin)
+            .get_mut(&storage_key)
             .map(|&mut (ref mut total, ref mut entry)| {
                 let mut new_total_size = this_storage_size + value.len();
                 if let Some(old_value) = entry.get(&name) {
@@ -216,15 +268,14 @@ impl StorageManager {
     fn request_item(
         &self,
         sender: IpcSender<Option<String>>,
-        url: ServoUrl,
+        storage_key: StorageKey,
         storage_type: StorageType,
         name: String,
     ) {
-        let origin = self.origin_as_string(url);
         let data = self.select_data(storage_type);
         sender
             .send(
-                data.get(&origin)
+                data.get(&storage_key)
                     .and_then(|(_, entry)| entry.get(&name))
                     .cloned(),
             )
@@ -235,28 +286,31 @@ impl StorageManager {
     fn remove_item(
         &mut self,
         sender: IpcSender<Option<String>>,
-        url: ServoUrl,
+        storage_key: StorageKey,
         storage_type: StorageType,
         name: String,
     ) {
-        let origin = self.origin_as_string(url);
         let data = self.select_data_mut(storage_type);
-        let old_value = data
-            .get_mut(&origin)
-            .and_then(|&mut (ref mut total, ref mut entry)| {
-                entry.remove(&name).inspect(|old| {
-                    *total -= name.len() + old.len();
-                })
-            });
+        let old_value =
+            data.get_mut(&storage_key)
+                .and_then(|&mut (ref mut total, ref mut entry)| {
+                    entry.remove(&name).inspect(|old| {
+                        *total -= name.len() + old.len();
+                    })
+                });
         sender.send(old_value).unwrap();
     }
 
-    fn clear(&mut self, sender: IpcSender<bool>, url: ServoUrl, storage_type: StorageType) {
-        let origin = self.origin_as_string(url);
+    fn clear(
+        &mut self,
+        sender: IpcSender<bool>,
+        storage_key: StorageKey,
+        storage_type: StorageType,
+    ) {
         let data = self.select_data_mut(storage_type);
         sender
             .send(
-                data.get_mut(&origin)
+                data.get_mut(&storage_key)
                     .is_some_and(|&mut (ref mut total, ref mut entry)| {
                         if !entry.is_empty() {
                             entry.clear();
@@ -270,7 +324,15 @@ impl StorageManager {
             .unwrap();
     }
 
-    fn origin_as_string(&self, url: ServoUrl) -> String {
-        url.origin().ascii_serialization()
+    /// Clear the local and session storage of every storage key that belongs to the site of
+    /// `url`, whether as the top-level site or as the site of the origin.
+    fn clear_site(&mut self, url: ServoUrl) {
+        let Some(site) = site(&url.origin()) else {
+            return;
+        };
+        self.local_data
+            .retain(|storage_key, _| !storage_key.belongs_to_site(&site));
+        self.session_data
+            .retain(|storage_key, _| !storage_key.belongs_to_site(&site));
     }
 }
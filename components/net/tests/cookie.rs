@@ -3,8 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use net::cookie::ServoCookie;
-use net::cookie_storage::CookieStorage;
+use net::cookie_storage::{CookiePartition, CookieStorage};
 use net_traits::CookieSource;
+use net_traits::pub_domains::site;
 use servo_url::ServoUrl;
 
 #[test]
@@ -473,3 +474,60 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+#[test]
+fn test_partitioned_cookie_requires_secure() {
+    let url = ServoUrl::parse("https://embedded.com/").unwrap();
+    let cookie = cookie::Cookie::parse("foo=bar; Partitioned").unwrap();
+    assert!(ServoCookie::new_wrapped(cookie, &url, CookieSource::HTTP).is_none());
+}
+
+#[test]
+fn test_partitioned_cookies_are_keyed_by_top_level_site() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("https://embedded.com/").unwrap();
+    let source = CookieSource::HTTP;
+    let partition = |top_level: &str| CookiePartition {
+        key: site(&ServoUrl::parse(top_level).unwrap().origin()),
+        allow_unpartitioned: true,
+    };
+
+    let cookie = cookie::Cookie::parse("foo=bar; Secure; Partitioned").unwrap();
+    let cookie = ServoCookie::new_wrapped(cookie, &url, source).unwrap();
+    storage.push_in_partition(cookie, &url, source, &partition("https://a.top.com/"));
+
+    assert_eq!(
+        storage.cookies_for_url_in_partition(&url, source, &partition("https://b.top.com/")),
+        Some("foo=bar".to_owned())
+    );
+    assert_eq!(
+        storage.cookies_for_url_in_partition(&url, source, &partition("https://other.com/")),
+        None
+    );
+    assert_eq!(storage.cookies_for_url(&url, source), None);
+}
+
+#[test]
+fn test_third_party_partition_ignores_unpartitioned_cookies() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("https://embedded.com/").unwrap();
+    let source = CookieSource::HTTP;
+    let blocked = CookiePartition {
+        key: site(&ServoUrl::parse("https://top.com/").unwrap().origin()),
+        allow_unpartitioned: false,
+    };
+
+    add_cookie_to_storage(&mut storage, &url, "first=party");
+    let cookie = cookie::Cookie::parse("third=party").unwrap();
+    let cookie = ServoCookie::new_wrapped(cookie, &url, source).unwrap();
+    storage.push_in_partition(cookie, &url, source, &blocked);
+
+    assert_eq!(
+        storage.cookies_for_url_in_partition(&url, source, &blocked),
+        None
+    );
+    assert_eq!(
+        storage.cookies_for_url(&url, source),
+        Some("first=party".to_owned())
+    );
+}
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::fs;
+
+use net::test::{create_unique_file, sanitize_file_name};
+
+#[test]
+fn test_sanitize_file_name() {
+    assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+    assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+    assert_eq!(sanitize_file_name("C:\\Windows\\system.ini"), "system.ini");
+    assert_eq!(
+        sanitize_file_name("a<b>c:d\"e|f?g*h.txt"),
+        "a_b_c_d_e_f_g_h.txt"
+    );
+    assert_eq!(sanitize_file_name("tab\there\n.txt"), "tab_here_.txt");
+    assert_eq!(sanitize_file_name("  .hidden.  "), "hidden");
+    assert_eq!(sanitize_file_name("con.txt"), "_con.txt");
+    assert_eq!(sanitize_file_name("LPT1"), "_LPT1");
+    assert_eq!(sanitize_file_name("COMPANY.txt"), "COMPANY.txt");
+    assert_eq!(sanitize_file_name(".."), "download");
+    assert_eq!(sanitize_file_name(""), "download");
+    assert_eq!(sanitize_file_name(&"é".repeat(200)).len(), 254);
+}
+
+#[test]
+fn test_create_unique_file() {
+    let directory = std::env::temp_dir().join("servo_test_download");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+
+    let paths: Vec<_> = (0..3)
+        .map(|_| create_unique_file(&directory, "report.pdf").unwrap().0)
+        .collect();
+    let no_extension = create_unique_file(&directory, "README").unwrap().0;
+    let second_no_extension = create_unique_file(&directory, "README").unwrap().0;
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(paths[0], directory.join("report.pdf"));
+    assert_eq!(paths[1], directory.join("report (1).pdf"));
+    assert_eq!(paths[2], directory.join("report (2).pdf"));
+    assert_eq!(no_extension, directory.join("README"));
+    assert_eq!(second_no_extension, directory.join("README (1)"));
+}
+
+#[test]
+fn test_create_unique_file_with_long_name() {
+    let directory = std::env::temp_dir().join("servo_test_download_long_name");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+
+    let file_name = sanitize_file_name(&format!("{}.pdf", "a".repeat(300)));
+    let first = create_unique_file(&directory, &file_name).unwrap().0;
+    let second = create_unique_file(&directory, &file_name).unwrap().0;
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(file_name.len(), 255);
+    assert_eq!(first, directory.join(&file_name));
+    let second = second.file_name().unwrap().to_str().unwrap().to_owned();
+    assert_eq!(second.len(), 255);
+    assert!(second.ends_with("a (1).pdf"), "{second}");
+}
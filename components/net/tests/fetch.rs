@@ -13,7 +13,10 @@ use std::time::{Duration, SystemTime};
 
 use base::id::TEST_PIPELINE_ID;
 use crossbeam_channel::{Sender, unbounded};
-use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
+use devtools_traits::{
+    HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse,
+    MAX_RESPONSE_BODY_PREVIEW_SIZE,
+};
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
     AccessControlAllowOrigin, AccessControlMaxAge, CacheControl, ContentLength, ContentType,
@@ -29,7 +32,7 @@ use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, FetchContext};
 use net::filemanager_thread::FileManager;
 use net::hsts::HstsEntry;
-use net::protocols::ProtocolRegistry;
+use net::protocols::{CustomProtocolHandler, CustomProtocolResponse, ProtocolRegistry};
 use net::request_interceptor::RequestInterceptor;
 use net::resource_thread::CoreResourceThreadPool;
 use net_traits::filemanager_thread::FileTokenCheck;
@@ -46,7 +49,9 @@ use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
 use uuid::Uuid;
 
-use crate::http_loader::{expect_devtools_http_request, expect_devtools_http_response};
+use crate::http_loader::{
+    expect_devtools_http_request, expect_devtools_http_response, expect_devtools_http_response_end,
+};
 use crate::{
     DEFAULT_USER_AGENT, create_embedder_proxy, create_embedder_proxy_and_receiver,
     create_http_state, fetch, fetch_with_context, fetch_with_cors_cache, make_body, make_server,
@@ -141,6 +146,30 @@ fn test_fetch_aboutblank() {
     assert_eq!(*resp_body, ResponseBody::Done(vec![]));
 }
 
+#[test]
+fn test_fetch_privileged_about_page_requires_navigation() {
+    let url = ServoUrl::parse("about:config").unwrap();
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .build();
+
+    let fetch_response = fetch(request, None);
+    assert!(fetch_response.is_network_error());
+
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .mode(RequestMode::Navigate)
+        .destination(Destination::Document)
+        .build();
+
+    let fetch_response = fetch(request, None);
+    assert!(!fetch_response.is_network_error());
+    assert!(net::about::is_privileged_url(&url));
+    assert!(!net::about::is_privileged_url(
+        &ServoUrl::parse("about:blank").unwrap()
+    ));
+}
+
 #[test]
 fn test_fetch_blob() {
     use net_traits::blob_url_store::BlobBuf;
@@ -1289,6 +1318,7 @@ fn test_fetch_with_devtools() {
     // notification received from devtools
     let devhttprequest = expect_devtools_http_request(&devtools_port);
     let mut devhttpresponse = expect_devtools_http_response(&devtools_port);
+    let devhttpresponseend = expect_devtools_http_response_end(&devtools_port);
 
     //Creating default headers for request
     let mut headers = HeaderMap::new();
@@ -1304,7 +1334,7 @@ fn test_fetch_with_devtools() {
 
     headers.insert(
         header::ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
+        HeaderValue::from_static("gzip, deflate, br, zstd"),
     );
 
     // Append fetch metadata headers
@@ -1352,6 +1382,41 @@ fn test_fetch_with_devtools() {
 
     assert_eq!(devhttprequest, httprequest);
     assert_eq!(devhttpresponse, httpresponse);
+    assert_eq!(devhttpresponseend.body_preview, MESSAGE);
+    assert_eq!(devhttpresponseend.body_size, MESSAGE.len());
+    assert_eq!(devhttpresponseend.pipeline_id, TEST_PIPELINE_ID);
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn test_fetch_with_devtools_limits_response_body_preview() {
+    let body = vec![b'a'; MAX_RESPONSE_BODY_PREVIEW_SIZE + 1];
+    let handler_body = body.clone();
+    let handler =
+        move |_: HyperRequest<Incoming>,
+              response: &mut HyperResponse<BoxBody<Bytes, hyper::Error>>| {
+            *response.body_mut() = make_body(handler_body.clone());
+        };
+    let (server, url) = make_server(handler);
+
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let (devtools_chan, devtools_port) = unbounded();
+    let _ = fetch(request, Some(devtools_chan));
+    let _ = server.close();
+
+    let _ = expect_devtools_http_request(&devtools_port);
+    let _ = expect_devtools_http_response(&devtools_port);
+    let devhttpresponseend = expect_devtools_http_response_end(&devtools_port);
+
+    assert_eq!(devhttpresponseend.body_size, body.len());
+    assert_eq!(
+        devhttpresponseend.body_preview,
+        &body[..MAX_RESPONSE_BODY_PREVIEW_SIZE]
+    );
 }
 
 #[test]
@@ -1449,3 +1514,109 @@ fn test_fetch_request_intercepted() {
         "The status_message was not set correctly!"
     );
 }
+
+#[test]
+fn test_fetch_request_modified_and_response_observed() {
+    static HEADERNAME: &str = "custom-header";
+    static HEADERVALUE: &str = "custom-value";
+
+    let handler =
+        move |request: HyperRequest<Incoming>,
+              response: &mut HyperResponse<BoxBody<Bytes, hyper::Error>>| {
+            if let Some(value) = request.headers().get(HEADERNAME) {
+                response.headers_mut().insert(HEADERNAME, value.clone());
+            }
+            *response.body_mut() = make_body(b"Yay!".to_vec());
+        };
+    let (server, url) = make_server(handler);
+
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    std::thread::spawn(move || {
+        match embedder_receiver.recv().unwrap() {
+            embedder_traits::EmbedderMsg::WebResourceRequested(_, _, response_sender) => {
+                let modification = embedder_traits::WebResourceRequestModification::default()
+                    .header(
+                        HeaderName::from_static(HEADERNAME),
+                        HeaderValue::from_static(HEADERVALUE),
+                    )
+                    .observe_response();
+                let _ = response_sender.send(
+                    embedder_traits::WebResourceResponseMsg::ModifyRequest(modification),
+                );
+            },
+            _ => unreachable!(),
+        }
+        match embedder_receiver.recv().unwrap() {
+            embedder_traits::EmbedderMsg::WebResourceResponseReceived(
+                _,
+                _,
+                web_resource_response,
+                response_sender,
+            ) => {
+                assert_eq!(web_resource_response.status_code, StatusCode::OK);
+                let response = web_resource_response.status_code(StatusCode::IM_A_TEAPOT);
+                let _ = response_sender.send(Some(response));
+            },
+            _ => unreachable!(),
+        }
+    });
+
+    let mut context = new_fetch_context(None, Some(embedder_proxy), None);
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .origin(url.origin())
+        .build();
+    let response = fetch_with_context(request, &mut context);
+    let _ = server.close();
+
+    assert_eq!(response.status.code(), StatusCode::IM_A_TEAPOT);
+    assert_eq!(
+        response.headers.get(HEADERNAME).unwrap(),
+        HeaderValue::from_static(HEADERVALUE)
+    );
+}
+
+#[test]
+fn test_fetch_custom_protocol() {
+    let handler = |fetchable| {
+        CustomProtocolHandler::new(|url| {
+            (url.path() == "/index.html").then(|| {
+                CustomProtocolResponse::new(b"Hello from app".to_vec(), mime::TEXT_HTML_UTF_8)
+            })
+        })
+        .fetchable(fetchable)
+    };
+    let cors_request = |path| {
+        let url = ServoUrl::parse(&format!("app://bundle{path}")).unwrap();
+        let origin = ServoUrl::parse("https://example.com").unwrap().origin();
+        RequestBuilder::new(None, url, Referrer::NoReferrer)
+            .origin(origin)
+            .mode(RequestMode::CorsMode)
+            .build()
+    };
+
+    let mut context = new_fetch_context(None, None, None);
+    let mut protocols = ProtocolRegistry::default();
+    assert!(protocols.register("app", handler(true)));
+    context.protocols = Arc::new(protocols);
+
+    let response = fetch_with_context(cors_request("/index.html"), &mut context);
+    assert!(!response.is_network_error());
+    assert_eq!(
+        response.headers.typed_get::<ContentType>(),
+        Some(ContentType::from(mime::TEXT_HTML_UTF_8))
+    );
+    match *response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, b"Hello from app"),
+        _ => panic!(),
+    };
+
+    let response = fetch_with_context(cors_request("/missing.html"), &mut context);
+    assert!(response.is_network_error());
+
+    // Resources of schemes that are not fetchable can't be read in CORS mode.
+    let mut protocols = ProtocolRegistry::default();
+    assert!(protocols.register("app", handler(false)));
+    context.protocols = Arc::new(protocols);
+    let response = fetch_with_context(cors_request("/index.html"), &mut context);
+    assert!(response.is_network_error());
+}
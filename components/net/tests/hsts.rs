@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use std::time::Duration as StdDuration;
 
 use base::cross_process_instant::CrossProcessInstant;
-use net::hsts::{HstsEntry, HstsList};
+use net::hsts::{HstsEntry, HstsList, PersistedHstsList};
 use net_traits::IncludeSubdomains;
 use time::Duration;
 
@@ -394,3 +394,105 @@ fn test_preload_hsts_domains_well_formed() {
     let hsts_list = HstsList::from_servo_preload();
     assert!(!hsts_list.entries_map.is_empty());
 }
+
+#[test]
+fn test_push_entry_with_0_max_age_removes_entry_from_entries_map() {
+    let mut list = HstsList::default();
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(StdDuration::from_secs(500000)),
+        )
+        .unwrap(),
+    );
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(StdDuration::ZERO),
+        )
+        .unwrap(),
+    );
+
+    assert!(list.entries_map.get("mozilla.org").unwrap().is_empty());
+}
+
+#[test]
+fn test_persisted_hsts_list_only_contains_learned_unexpired_entries() {
+    let mut list = HstsList::default();
+    list.push(
+        HstsEntry::new(
+            "preloaded.org".to_owned(),
+            IncludeSubdomains::Included,
+            None,
+        )
+        .unwrap(),
+    );
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::Included,
+            Some(StdDuration::from_secs(500000)),
+        )
+        .unwrap(),
+    );
+    list.push(HstsEntry {
+        host: "expired.org".to_owned(),
+        include_subdomains: false,
+        max_age: Some(StdDuration::from_secs(20)),
+        timestamp: Some(CrossProcessInstant::now() - Duration::seconds(100)),
+    });
+
+    let persisted = serde_json::to_string(&list.to_persisted()).unwrap();
+    let persisted: PersistedHstsList = serde_json::from_str(&persisted).unwrap();
+    let mut restored = HstsList::default();
+    restored.extend_from_persisted(persisted);
+
+    assert!(restored.is_host_secure("mozilla.org"));
+    assert!(restored.is_host_secure("servo.mozilla.org"));
+    assert!(!restored.is_host_secure("preloaded.org"));
+    assert!(!restored.is_host_secure("expired.org"));
+}
+
+#[test]
+fn test_push_entry_with_0_max_age_keeps_preloaded_entry() {
+    let mut list = HstsList::default();
+    list.push(HstsEntry::new("mozilla.org".to_owned(), IncludeSubdomains::Included, None).unwrap());
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(StdDuration::from_secs(500000)),
+        )
+        .unwrap(),
+    );
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(StdDuration::ZERO),
+        )
+        .unwrap(),
+    );
+
+    assert_eq!(list.entries_map.get("mozilla.org").unwrap().len(), 1);
+    assert!(list.is_host_secure("mozilla.org"));
+    assert!(list.is_host_secure("servo.mozilla.org"));
+}
+
+#[test]
+fn test_persisted_hsts_list_migrates_legacy_format() {
+    let legacy = r#"{"entries_map": {"mozilla.org": [
+        {"host": "mozilla.org", "include_subdomains": true,
+         "max_age": {"secs": 500000, "nanos": 0}, "timestamp": null},
+        {"host": "preloaded.mozilla.org", "include_subdomains": false,
+         "max_age": null, "timestamp": null}
+    ]}}"#;
+    let persisted: PersistedHstsList = serde_json::from_str(legacy).unwrap();
+    let mut restored = HstsList::default();
+    restored.extend_from_persisted(persisted);
+
+    assert!(restored.is_host_secure("servo.mozilla.org"));
+    assert_eq!(restored.entries_map.get("mozilla.org").unwrap().len(), 1);
+}
@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::time::Duration;
+
+use http::header::{ALT_SVC, HeaderMap, HeaderValue};
+use http_body_util::{BodyExt, Full};
+use hyper::Request as HyperRequest;
+use hyper::body::Bytes;
+use net::connector::{CACertificates, CertificateErrorOverrideManager, create_tls_config};
+use net::http3::{AltSvcAdvertisement, Http3Client, Http3Outcome, parse_alt_svc};
+use servo_url::ServoUrl;
+
+use crate::HANDLE;
+
+#[test]
+fn test_parse_alt_svc() {
+    assert_eq!(parse_alt_svc("clear"), AltSvcAdvertisement::Clear);
+    assert_eq!(parse_alt_svc(" clear "), AltSvcAdvertisement::Clear);
+    assert_eq!(
+        parse_alt_svc(r#"h3=":443""#),
+        AltSvcAdvertisement::Http3(String::new(), 443, Duration::from_secs(24 * 60 * 60))
+    );
+    assert_eq!(
+        parse_alt_svc(r#"h2="alt.example.com:443", h3="alt.example.com:8443"; ma=3600"#),
+        AltSvcAdvertisement::Http3(
+            "alt.example.com".to_owned(),
+            8443,
+            Duration::from_secs(3600)
+        )
+    );
+    assert_eq!(
+        parse_alt_svc(r#"h3="[::1]:443"; persist=1; ma=60"#),
+        AltSvcAdvertisement::Http3("::1".to_owned(), 443, Duration::from_secs(60))
+    );
+    assert_eq!(
+        parse_alt_svc(r#"h3="[2001:db8::1]:8443""#),
+        AltSvcAdvertisement::Http3(
+            "2001:db8::1".to_owned(),
+            8443,
+            Duration::from_secs(24 * 60 * 60)
+        )
+    );
+}
+
+#[test]
+fn test_parse_alt_svc_with_invalid_ipv6_address() {
+    assert_eq!(parse_alt_svc(r#"h3="::1:443""#), AltSvcAdvertisement::None);
+    assert_eq!(parse_alt_svc(r#"h3="[::1:443""#), AltSvcAdvertisement::None);
+    assert_eq!(
+        parse_alt_svc(r#"h3="[alt.example.com]:443""#),
+        AltSvcAdvertisement::None
+    );
+}
+
+#[test]
+fn test_parse_alt_svc_without_http3() {
+    assert_eq!(parse_alt_svc(r#"h2=":443""#), AltSvcAdvertisement::None);
+    assert_eq!(parse_alt_svc(r#"h3-29=":443""#), AltSvcAdvertisement::None);
+    assert_eq!(parse_alt_svc(r#"h3=":port""#), AltSvcAdvertisement::None);
+    assert_eq!(parse_alt_svc(r#"h3="443""#), AltSvcAdvertisement::None);
+    assert_eq!(parse_alt_svc(""), AltSvcAdvertisement::None);
+}
+
+#[test]
+fn test_http3_request_is_returned_when_connecting_fails() {
+    let mut preferences = servo_config::prefs::get().clone();
+    preferences.network_http3_enabled = true;
+    servo_config::prefs::set(preferences);
+
+    let client = Http3Client::new(create_tls_config(
+        CACertificates::Default,
+        false, /* ignore_certificate_errors */
+        CertificateErrorOverrideManager::new(),
+    ));
+    let url = ServoUrl::parse("https://127.0.0.1:8443/").unwrap();
+    // Port 0 cannot be connected to, so the request must not be sent over HTTP/3.
+    let mut headers = HeaderMap::new();
+    headers.insert(ALT_SVC, HeaderValue::from_static(r#"h3=":0""#));
+    client.handle_response_headers(&url, &headers);
+
+    for _ in 0..2 {
+        let request = HyperRequest::post(url.as_str())
+            .body(
+                Full::new(Bytes::from_static(b"body"))
+                    .map_err(|_| unreachable!())
+                    .boxed(),
+            )
+            .unwrap();
+        let Http3Outcome::NotSent(request) = HANDLE.block_on(client.request(&url, request)) else {
+            panic!("The request should be sent over TCP");
+        };
+        let body = HANDLE.block_on(request.into_body().collect()).unwrap();
+        assert_eq!(body.to_bytes(), Bytes::from_static(b"body"));
+    }
+}
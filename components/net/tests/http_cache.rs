@@ -4,7 +4,7 @@
 
 use base::id::TEST_PIPELINE_ID;
 use http::StatusCode;
-use http::header::{EXPIRES, HeaderValue};
+use http::header::{CACHE_CONTROL, EXPIRES, HeaderValue};
 use net::http_cache::HttpCache;
 use net_traits::request::{Referrer, RequestBuilder};
 use net_traits::response::{Response, ResponseBody};
@@ -48,3 +48,36 @@ fn test_refreshing_resource_sets_done_chan_the_appropriate_value() {
         }
     })
 }
+
+#[test]
+fn test_cache_written_to_disk_can_be_read_back() {
+    let url = ServoUrl::parse("https://servo.org/cached").unwrap();
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .origin(url.origin())
+        .build();
+    let timing = ResourceFetchTiming::new(ResourceTimingType::Navigation);
+    let mut response = Response::new(url.clone(), timing);
+    response
+        .headers
+        .insert(CACHE_CONTROL, HeaderValue::from_static("max-age=3600"));
+    *response.body.lock().unwrap() = ResponseBody::Done(b"cached body".to_vec());
+    let mut cache = HttpCache::default();
+    cache.store(&request, &response);
+
+    let directory = std::env::temp_dir().join("servo_test_http_cache");
+    cache.write_to_disk(&directory);
+    let mut read_cache = HttpCache::default();
+    read_cache.read_from_disk(&directory);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let mut done_chan = None;
+    let cached_response = read_cache
+        .construct_response(&request, &mut done_chan)
+        .unwrap();
+    assert!(!cached_response.needs_validation);
+    match *cached_response.response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(body, b"cached body"),
+        _ => panic!("Expected the body to be complete"),
+    }
+}
@@ -5,7 +5,7 @@
 #![cfg(not(target_os = "windows"))]
 
 use std::collections::HashMap;
-use std::io::Write;
+use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
@@ -15,11 +15,12 @@ use cookie::Cookie as CookiePair;
 use crossbeam_channel::{Receiver, unbounded};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
-    HttpResponse as DevtoolsHttpResponse, NetworkEvent,
+    HttpResponse as DevtoolsHttpResponse, HttpResponseEnd, NetworkEvent,
 };
 use embedder_traits::AuthenticationResponse;
 use flate2::Compression;
 use flate2::write::{GzEncoder, ZlibEncoder};
+use futures::StreamExt;
 use headers::authorization::Basic;
 use headers::{
     Authorization, ContentLength, Date, HeaderMapExt, Host, StrictTransportSecurity, UserAgent,
@@ -27,6 +28,7 @@ use headers::{
 use http::header::{self, HeaderMap, HeaderValue};
 use http::uri::Authority;
 use http::{HeaderName, Method, StatusCode};
+use http_body_util::BodyExt;
 use http_body_util::combinators::BoxBody;
 use hyper::body::{Body, Bytes, Incoming};
 use hyper::{Request as HyperRequest, Response as HyperResponse};
@@ -35,9 +37,9 @@ use ipc_channel::router::ROUTER;
 use net::cookie::ServoCookie;
 use net::cookie_storage::CookieStorage;
 use net::fetch::methods::{self};
-use net::http_loader::{determine_requests_referrer, serialize_origin};
+use net::http_loader::{RequestContentCoding, determine_requests_referrer, serialize_origin};
 use net::resource_thread::AuthCacheEntry;
-use net::test::{DECODER_BUFFER_SIZE, replace_host_table};
+use net::test::{DECODER_BUFFER_SIZE, Decoder, replace_host_table};
 use net_traits::http_status::HttpStatus;
 use net_traits::request::{
     BodyChunkRequest, BodyChunkResponse, BodySource, CredentialsMode, Destination, Referrer,
@@ -68,17 +70,29 @@ fn assert_cookie_for_domain(
     assert_eq!(cookies.as_ref().map(|c| &**c), cookie);
 }
 
+/// Receive the next network event sent to devtools, skipping the end of response bodies,
+/// which are streamed independently of the requests and responses that follow.
+fn next_devtools_network_event(devtools_port: &Receiver<DevtoolsControlMsg>) -> NetworkEvent {
+    loop {
+        match devtools_port.recv().unwrap() {
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
+                _,
+                NetworkEvent::HttpResponseEnd(_),
+            )) => continue,
+            DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
+                _,
+                net_event,
+            )) => return net_event,
+            _ => panic!("No NetworkEvent Received"),
+        }
+    }
+}
+
 pub fn expect_devtools_http_request(
     devtools_port: &Receiver<DevtoolsControlMsg>,
 ) -> DevtoolsHttpRequest {
-    match devtools_port.recv().unwrap() {
-        DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(_, net_event)) => {
-            match net_event {
-                NetworkEvent::HttpRequest(httprequest) => httprequest,
-
-                _ => panic!("No HttpRequest Received"),
-            }
-        },
+    match next_devtools_network_event(devtools_port) {
+        NetworkEvent::HttpRequest(httprequest) => httprequest,
         _ => panic!("No HttpRequest Received"),
     }
 }
@@ -86,16 +100,21 @@ pub fn expect_devtools_http_request(
 pub fn expect_devtools_http_response(
     devtools_port: &Receiver<DevtoolsControlMsg>,
 ) -> DevtoolsHttpResponse {
+    match next_devtools_network_event(devtools_port) {
+        NetworkEvent::HttpResponse(httpresponse) => httpresponse,
+        _ => panic!("No HttpResponse Received"),
+    }
+}
+
+pub fn expect_devtools_http_response_end(
+    devtools_port: &Receiver<DevtoolsControlMsg>,
+) -> HttpResponseEnd {
     match devtools_port.recv().unwrap() {
         DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
             _,
-            net_event_response,
-        )) => match net_event_response {
-            NetworkEvent::HttpResponse(httpresponse) => httpresponse,
-
-            _ => panic!("No HttpResponse Received"),
-        },
-        _ => panic!("No HttpResponse Received"),
+            NetworkEvent::HttpResponseEnd(response_end),
+        )) => response_end,
+        _ => panic!("No HttpResponseEnd Received"),
     }
 }
 
@@ -134,7 +153,7 @@ fn test_check_default_headers_loaded_in_every_request() {
 
     headers.insert(
         header::ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
+        HeaderValue::from_static("gzip, deflate, br, zstd"),
     );
 
     headers.typed_insert(Host::from(
@@ -314,7 +333,7 @@ fn test_request_and_response_data_with_network_messages() {
 
     headers.insert(
         header::ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
+        HeaderValue::from_static("gzip, deflate, br, zstd"),
     );
 
     // Append fetch metadata headers
@@ -566,6 +585,107 @@ fn test_load_should_decode_the_response_as_gzip_when_response_headers_have_conte
     );
 }
 
+#[test]
+fn test_load_should_decode_the_response_as_zstd_when_response_headers_have_content_encoding_zstd() {
+    let handler =
+        move |_: HyperRequest<Incoming>,
+              response: &mut HyperResponse<BoxBody<Bytes, hyper::Error>>| {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+            let encoded_content = zstd::encode_all(&b"Yay!"[..], 0).unwrap();
+            *response.body_mut() = make_body(encoded_content);
+        };
+    let (server, url) = make_server(handler);
+
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let response = fetch(request, None);
+
+    let _ = server.close();
+
+    let internal_response = response.internal_response.unwrap();
+    assert!(internal_response.status.clone().code().is_success());
+    assert_eq!(
+        *internal_response.body.lock().unwrap(),
+        ResponseBody::Done(b"Yay!".to_vec())
+    );
+}
+
+fn decode_with_max_decoded_size(
+    content_encoding: Option<&'static str>,
+    body: Vec<u8>,
+    max_decoded_size: Option<u64>,
+) -> Vec<Result<Bytes, io::Error>> {
+    let mut response = HyperResponse::new(make_body(body).map_err(Into::into).boxed());
+    if let Some(content_encoding) = content_encoding {
+        response.headers_mut().insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(content_encoding),
+        );
+    }
+    let decoder =
+        Decoder::detect_with_max_decoded_size(response, false, max_decoded_size).into_body();
+    crate::HANDLE.block_on(decoder.collect())
+}
+
+#[test]
+fn test_decoded_size_within_the_limit() {
+    let content = vec![0; 100_000];
+    let encoded_content = zstd::encode_all(&content[..], 0).unwrap();
+
+    for max_decoded_size in [None, Some(100_000)] {
+        let decoded: Vec<u8> =
+            decode_with_max_decoded_size(Some("zstd"), encoded_content.clone(), max_decoded_size)
+                .into_iter()
+                .flat_map(|chunk| chunk.unwrap())
+                .collect();
+        assert_eq!(decoded, content);
+    }
+}
+
+#[test]
+fn test_decoded_size_beyond_the_limit() {
+    let content = vec![0; 100_000];
+    let mut e = GzEncoder::new(Vec::new(), Compression::default());
+    e.write_all(&content).unwrap();
+    let zstd_content = zstd::encode_all(&content[..], 0).unwrap();
+
+    for (content_encoding, encoded_content) in
+        [("gzip", e.finish().unwrap()), ("zstd", zstd_content)]
+    {
+        let chunks =
+            decode_with_max_decoded_size(Some(content_encoding), encoded_content, Some(99_999));
+        let decoded_size: usize = chunks
+            .iter()
+            .map_while(|chunk| chunk.as_ref().ok())
+            .map(Bytes::len)
+            .sum();
+        assert!(decoded_size <= 99_999);
+        let error = chunks
+            .iter()
+            .find_map(|chunk| chunk.as_ref().err())
+            .expect("Decoding should fail once the limit is exceeded");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn test_decoded_size_limit_does_not_apply_to_plain_text() {
+    let content = vec![0; 100];
+    let decoded: Vec<u8> = decode_with_max_decoded_size(None, content.clone(), Some(10))
+        .into_iter()
+        .flat_map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(decoded, content);
+}
+
 #[test]
 fn test_load_doesnt_send_request_body_on_any_redirect() {
     let post_handler =
@@ -1062,7 +1182,7 @@ fn test_load_sets_default_accept_encoding_to_gzip_and_deflate() {
                     .unwrap()
                     .to_str()
                     .unwrap(),
-                "gzip, deflate, br"
+                "gzip, deflate, br, zstd"
             );
             *response.body_mut() = make_body(b"Yay!".to_vec());
         };
@@ -1829,3 +1949,54 @@ fn test_prompt_credentials_user_input_incorrect_mode() {
 
     assert!(response.internal_response.is_none());
 }
+
+#[test]
+fn test_request_content_coding_from_accept_encoding() {
+    assert_eq!(
+        RequestContentCoding::from_accept_encoding("gzip, br"),
+        Some(RequestContentCoding::Brotli)
+    );
+    assert_eq!(
+        RequestContentCoding::from_accept_encoding("gzip;q=0.5, ZSTD"),
+        Some(RequestContentCoding::Zstd)
+    );
+    assert_eq!(
+        RequestContentCoding::from_accept_encoding("zstd;q=0, gzip"),
+        Some(RequestContentCoding::Gzip)
+    );
+    assert_eq!(RequestContentCoding::from_accept_encoding("identity"), None);
+}
+
+#[test]
+fn test_fetch_fails_immediately_while_offline() {
+    let server_hit = Arc::new(AtomicBool::new(false));
+    let hit = server_hit.clone();
+    let handler =
+        move |_: HyperRequest<Incoming>,
+              response: &mut HyperResponse<BoxBody<Bytes, hyper::Error>>| {
+            hit.store(true, Ordering::SeqCst);
+            *response.body_mut() = make_body(b"Yay!".to_vec());
+        };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    context.state.online.store(false, Ordering::Relaxed);
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .method(Method::GET)
+        .origin(url.origin())
+        .build();
+    let response = fetch_with_context(request, &mut context);
+    assert!(response.is_network_error());
+    assert!(!server_hit.load(Ordering::SeqCst));
+
+    context.state.online.store(true, Ordering::Relaxed);
+    let request = RequestBuilder::new(None, url.clone(), Referrer::NoReferrer)
+        .method(Method::GET)
+        .origin(url.origin())
+        .build();
+    let response = fetch_with_context(request, &mut context);
+    let _ = server.close();
+
+    assert!(!response.is_network_error());
+    assert!(server_hit.load(Ordering::SeqCst));
+}
@@ -8,15 +8,22 @@
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
+mod download;
 mod fetch;
 mod file_loader;
 mod filemanager_thread;
 mod hsts;
+mod http3;
 mod http_cache;
 mod http_loader;
 mod mime_classifier;
+mod proxy;
+mod resolver;
 mod resource_thread;
+mod storage_thread;
 mod subresource_integrity;
+mod websocket_deflate;
+mod webtransport;
 
 use core::convert::Infallible;
 use std::collections::HashMap;
@@ -24,6 +31,7 @@ use std::fs::File;
 use std::io::{self, BufReader};
 use std::net::TcpListener as StdTcpListener;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, LazyLock, Mutex, RwLock, Weak};
 
 use crossbeam_channel::{Receiver, Sender, unbounded};
@@ -150,7 +158,7 @@ fn create_http_state(fc: Option<EmbedderProxy>) -> HttpState {
         hsts_list: RwLock::new(net::hsts::HstsList::default()),
         cookie_jar: RwLock::new(net::cookie_storage::CookieStorage::new(150)),
         auth_cache: RwLock::new(net::resource_thread::AuthCache::default()),
-        history_states: RwLock::new(HashMap::new()),
+        history_states: RwLock::new(net::resource_thread::HistoryStates::default()),
         http_cache: RwLock::new(net::http_cache::HttpCache::default()),
         http_cache_state: Mutex::new(HashMap::new()),
         client: create_http_client(create_tls_config(
@@ -158,8 +166,17 @@ fn create_http_state(fc: Option<EmbedderProxy>) -> HttpState {
             false, /* ignore_certificate_errors */
             override_manager.clone(),
         )),
+        http3_client: net::http3::Http3Client::new(create_tls_config(
+            net::connector::CACertificates::Default,
+            false, /* ignore_certificate_errors */
+            override_manager.clone(),
+        )),
         override_manager,
         embedder_proxy: Mutex::new(fc.unwrap_or_else(|| create_embedder_proxy())),
+        request_content_codings: Default::default(),
+        online: AtomicBool::new(true),
+        cookie_policies: Default::default(),
+        webdriver_interceptor: Default::default(),
     }
 }
 
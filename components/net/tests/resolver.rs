@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use net::test::{
+    DnsCache, RecordType, encode_dns_query, interleave_address_families, is_resolved_by_system,
+    parse_dns_response,
+};
+
+#[test]
+fn test_encode_dns_query() {
+    let query = encode_dns_query("example.com", RecordType::Aaaa).unwrap();
+    let mut expected = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    expected.extend(b"\x07example\x03com\x00");
+    expected.extend([0, 28, 0, 1]);
+    assert_eq!(query, expected);
+
+    assert!(encode_dns_query("example..com", RecordType::A).is_err());
+    assert!(encode_dns_query(&"a".repeat(64), RecordType::A).is_err());
+}
+
+#[test]
+fn test_parse_dns_response() {
+    let mut response = vec![0, 0, 0x81, 0x80, 0, 1, 0, 3, 0, 0, 0, 0];
+    response.extend(b"\x07example\x03com\x00");
+    response.extend([0, 1, 0, 1]);
+    // A CNAME record for the name of the question, which is skipped.
+    response.extend([0xc0, 12, 0, 5, 0, 1, 0, 0, 1, 0, 0, 2, 0xc0, 12]);
+    // Two A records with different times to live.
+    response.extend([0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 120, 0, 4, 93, 184, 216, 34]);
+    response.extend([0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 93, 184, 216, 35]);
+
+    let (addresses, ttl) = parse_dns_response(&response, RecordType::A).unwrap();
+    assert_eq!(
+        addresses,
+        vec![
+            "93.184.216.34".parse::<IpAddr>().unwrap(),
+            "93.184.216.35".parse().unwrap()
+        ]
+    );
+    assert_eq!(ttl, 60);
+
+    assert!(parse_dns_response(&response[..response.len() - 1], RecordType::A).is_err());
+}
+
+#[test]
+fn test_parse_dns_response_errors() {
+    // NXDOMAIN has no addresses.
+    let nxdomain = [0, 0, 0x81, 0x83, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(
+        parse_dns_response(&nxdomain, RecordType::A),
+        Ok((Vec::new(), 0))
+    );
+
+    let server_failure = [0, 0, 0x81, 0x82, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert!(parse_dns_response(&server_failure, RecordType::A).is_err());
+
+    let query = encode_dns_query("example.com", RecordType::A).unwrap();
+    assert!(parse_dns_response(&query, RecordType::A).is_err());
+}
+
+#[test]
+fn test_dns_cache_respects_ttl() {
+    let mut cache = DnsCache::new();
+    let now = Instant::now();
+    let addresses = vec!["127.0.0.1".parse::<IpAddr>().unwrap()];
+
+    cache.insert(
+        "example.com",
+        addresses.clone(),
+        Duration::from_secs(30),
+        now,
+    );
+    assert_eq!(cache.get("example.com", now), Some(addresses.clone()));
+    assert_eq!(
+        cache.get("example.com", now + Duration::from_secs(29)),
+        Some(addresses.clone())
+    );
+    assert_eq!(
+        cache.get("example.com", now + Duration::from_secs(30)),
+        None
+    );
+
+    cache.insert("example.org", addresses, Duration::ZERO, now);
+    assert_eq!(cache.get("example.org", now), None);
+}
+
+#[test]
+fn test_loopback_names_are_resolved_by_system() {
+    assert!(is_resolved_by_system("localhost"));
+    assert!(is_resolved_by_system("app.localhost"));
+    assert!(!is_resolved_by_system("localhost.example"));
+    assert!(!is_resolved_by_system("example.invalid"));
+}
+
+#[test]
+fn test_interleave_address_families() {
+    let addresses = [
+        "1.1.1.1",
+        "1.0.0.1",
+        "2606:4700::1111",
+        "2606:4700::1001",
+        "::1",
+    ]
+    .iter()
+    .map(|address| address.parse::<IpAddr>().unwrap())
+    .collect();
+    let interleaved: Vec<_> = interleave_address_families(addresses)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        interleaved,
+        [
+            "2606:4700::1111",
+            "1.1.1.1",
+            "2606:4700::1001",
+            "1.0.0.1",
+            "::1"
+        ]
+    );
+}
@@ -3,11 +3,13 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::net::IpAddr;
+use std::num::NonZeroU32;
 
+use base::id::{HistoryStateId, HistoryStateIndex, TEST_NAMESPACE};
 use ipc_channel::ipc;
 use net::connector::CACertificates;
 use net::protocols::ProtocolRegistry;
-use net::resource_thread::new_core_resource_thread;
+use net::resource_thread::{HistoryStates, new_core_resource_thread};
 use net::test::parse_hostsfile;
 use net_traits::CoreResourceMsg;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
@@ -172,3 +174,41 @@ fn test_parse_hostsfile_with_end_of_line_whitespace() {
         *hosts_table.get("servo.test.server").unwrap()
     );
 }
+
+fn history_state_id(index: u32) -> HistoryStateId {
+    HistoryStateId {
+        namespace_id: TEST_NAMESPACE,
+        index: HistoryStateIndex(NonZeroU32::new(index).unwrap()),
+    }
+}
+
+#[test]
+fn test_history_states_evict_least_recently_set() {
+    let mut history_states = HistoryStates::new(10);
+    history_states.insert(history_state_id(1), vec![0; 4]);
+    history_states.insert(history_state_id(2), vec![0; 4]);
+    history_states.insert(history_state_id(1), vec![0; 4]);
+    assert_eq!(history_states.size(), 8);
+
+    history_states.insert(history_state_id(3), vec![0; 4]);
+    assert!(history_states.get(&history_state_id(2)).is_none());
+    assert!(history_states.get(&history_state_id(1)).is_some());
+    assert!(history_states.get(&history_state_id(3)).is_some());
+    assert_eq!(history_states.size(), 8);
+
+    history_states.remove(&history_state_id(1));
+    assert_eq!(history_states.size(), 4);
+}
+
+#[test]
+fn test_history_states_keep_most_recent_oversized_state() {
+    let mut history_states = HistoryStates::new(10);
+    history_states.insert(history_state_id(1), vec![0; 4]);
+    history_states.insert(history_state_id(2), vec![0; 20]);
+    assert!(history_states.get(&history_state_id(1)).is_none());
+    assert_eq!(
+        history_states.get(&history_state_id(2)).map(Vec::len),
+        Some(20)
+    );
+    assert_eq!(history_states.size(), 20);
+}
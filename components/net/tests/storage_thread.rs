@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::collections::{BTreeMap, HashMap};
+
+use net::test::migrate_legacy_local_data;
+use net_traits::storage_thread::StorageKey;
+use servo_url::ServoUrl;
+
+#[test]
+fn test_legacy_local_data_is_migrated_to_first_party_storage() {
+    let data = (6, BTreeMap::from([("foo".to_owned(), "bar".to_owned())]));
+    let legacy_data = HashMap::from([
+        ("https://www.example.com".to_owned(), data.clone()),
+        ("not an origin".to_owned(), data.clone()),
+    ]);
+
+    let migrated = migrate_legacy_local_data(legacy_data);
+
+    let origin = ServoUrl::parse("https://www.example.com").unwrap().origin();
+    let storage_key = StorageKey::first_party(&origin);
+    assert_eq!(storage_key.top_level_site, "https://example.com");
+    assert_eq!(storage_key.origin, "https://www.example.com");
+    assert_eq!(migrated, HashMap::from([(storage_key, data)]));
+}
+
+#[test]
+fn test_storage_key_belongs_to_site() {
+    let top_level = ServoUrl::parse("https://top.com").unwrap().origin();
+    let embedded = ServoUrl::parse("https://sub.embedded.com")
+        .unwrap()
+        .origin();
+    let storage_key = StorageKey::new(&top_level, &embedded);
+
+    assert!(storage_key.belongs_to_site("https://top.com"));
+    assert!(storage_key.belongs_to_site("https://embedded.com"));
+    assert!(!storage_key.belongs_to_site("http://embedded.com"));
+    assert!(!storage_key.belongs_to_site("https://other.com"));
+}
@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::test::{DeflateParameters, Deflater, InflatingStream, negotiate};
+use tokio::io::AsyncReadExt;
+use tungstenite::Message;
+
+const HANDSHAKE_RESPONSE: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\n\
+    Upgrade: websocket\r\n\
+    Connection: Upgrade\r\n\
+    Sec-WebSocket-Extensions: permessage-deflate; server_no_context_takeover\r\n\r\n";
+
+fn formatted(message: Message) -> Vec<u8> {
+    let Message::Frame(frame) = message else {
+        panic!("Compressed messages are sent as frames");
+    };
+    let mut bytes = Vec::new();
+    frame.format(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_negotiate_declined() {
+    assert_eq!(negotiate(None), Ok(None));
+    assert_eq!(negotiate(Some("")), Ok(None));
+}
+
+#[test]
+fn test_negotiate_parameters() {
+    assert_eq!(
+        negotiate(Some("permessage-deflate")),
+        Ok(Some(DeflateParameters::default()))
+    );
+    assert_eq!(
+        negotiate(Some(
+            "permessage-deflate; client_no_context_takeover; server_max_window_bits=10"
+        )),
+        Ok(Some(DeflateParameters {
+            client_no_context_takeover: true,
+            server_no_context_takeover: false,
+        }))
+    );
+}
+
+#[test]
+fn test_negotiate_invalid() {
+    assert!(negotiate(Some("x-webkit-deflate-frame")).is_err());
+    assert!(negotiate(Some("permessage-deflate, permessage-deflate")).is_err());
+    assert!(negotiate(Some("permessage-deflate; client_max_window_bits=10")).is_err());
+    assert!(negotiate(Some("permessage-deflate; server_max_window_bits=16")).is_err());
+    assert!(
+        negotiate(Some(
+            "permessage-deflate; server_no_context_takeover; server_no_context_takeover"
+        ))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_inflating_stream_decompresses_messages() {
+    let parameters = negotiate(Some("permessage-deflate; server_no_context_takeover"))
+        .unwrap()
+        .unwrap();
+    let mut deflater = Deflater::new(DeflateParameters {
+        client_no_context_takeover: parameters.server_no_context_takeover,
+        server_no_context_takeover: false,
+    });
+
+    let text = "hello hello hello hello".repeat(100);
+    let mut input = HANDSHAKE_RESPONSE.to_vec();
+    input.extend(formatted(deflater.compress(Message::text(&text)).unwrap()));
+    input.extend(formatted(deflater.compress(Message::text(&text)).unwrap()));
+    assert!(input.len() < HANDSHAKE_RESPONSE.len() + text.len());
+
+    let mut output = Vec::new();
+    crate::HANDLE
+        .block_on(InflatingStream::new(&input[..], 1 << 20).read_to_end(&mut output))
+        .unwrap();
+
+    let mut expected = HANDSHAKE_RESPONSE.to_vec();
+    for _ in 0..2 {
+        expected.extend([0x81, 126]);
+        expected.extend((text.len() as u16).to_be_bytes());
+        expected.extend(text.as_bytes());
+    }
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_inflating_stream_passes_through_without_extension() {
+    let response = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+    let mut input = response.to_vec();
+    input.extend([0x82, 3, 1, 2, 3]);
+
+    let mut output = Vec::new();
+    crate::HANDLE
+        .block_on(InflatingStream::new(&input[..], 1 << 20).read_to_end(&mut output))
+        .unwrap();
+    assert_eq!(output, input);
+}
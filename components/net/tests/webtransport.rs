@@ -0,0 +1,182 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::test::{
+    decode_capsule, decode_huffman, decode_response_status, decode_varint, encode_connect_request,
+    encode_varint, from_http3_error_code, to_http3_error_code,
+};
+use quinn::VarInt;
+
+fn hex(string: &str) -> Vec<u8> {
+    (0..string.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&string[index..index + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_decode_huffman() {
+    // The examples of <https://www.rfc-editor.org/rfc/rfc7541#appendix-C.4>.
+    let examples = [
+        ("f1e3c2e5f23a6ba0ab90f4ff", "www.example.com"),
+        ("a8eb10649cbf", "no-cache"),
+        ("25a849e95ba97d7f", "custom-key"),
+        ("25a849e95bb8e8b4bf", "custom-value"),
+        ("6402", "302"),
+        ("aec3771a4b", "private"),
+        (
+            "d07abe941054d444a8200595040b8166e082a62d1bff",
+            "Mon, 21 Oct 2013 20:13:21 GMT",
+        ),
+        (
+            "9d29ad171863c78f0b97c8e9ae82ae43d3",
+            "https://www.example.com",
+        ),
+    ];
+    for (encoded, decoded) in examples {
+        assert_eq!(decode_huffman(&hex(encoded)).unwrap(), decoded.as_bytes());
+    }
+    assert_eq!(decode_huffman(&[]).unwrap(), b"");
+}
+
+#[test]
+fn test_decode_huffman_errors() {
+    // Padding that is longer than seven bits.
+    assert_eq!(decode_huffman(&hex("6402ff")), None);
+    // Padding that is not made of the most significant bits of EOS.
+    assert_eq!(decode_huffman(&hex("64")), None);
+    // An encoded EOS symbol.
+    assert_eq!(decode_huffman(&hex("fffffffc")), None);
+}
+
+#[test]
+fn test_varint() {
+    for (value, length) in [
+        (0, 1),
+        (63, 1),
+        (64, 2),
+        (16383, 2),
+        (16384, 4),
+        ((1 << 30) - 1, 4),
+        (1 << 30, 8),
+        ((1 << 62) - 1, 8),
+    ] {
+        let mut buffer = Vec::new();
+        encode_varint(value, &mut buffer);
+        assert_eq!(buffer.len(), length);
+        assert_eq!(decode_varint(&buffer), Some((value, length)));
+    }
+    // The example of <https://www.rfc-editor.org/rfc/rfc9000#appendix-A.1>.
+    assert_eq!(decode_varint(&hex("7bbd")), Some((15293, 2)));
+    assert_eq!(decode_varint(&hex("7b")), None);
+}
+
+#[test]
+fn test_encode_connect_request() {
+    let mut expected = vec![0, 0, 0xcf, 0xd7];
+    expected.extend([0x27, 0x02]);
+    expected.extend(b":protocol");
+    expected.push(12);
+    expected.extend(b"webtransport");
+    expected.push(0x50);
+    expected.push(16);
+    expected.extend(b"example.com:4433");
+    expected.push(0x51);
+    expected.push(5);
+    expected.extend(b"/chat");
+    // The `origin` entry of the static table has an index that does not fit in four bits.
+    expected.extend([0x5f, 90 - 15]);
+    expected.push(19);
+    expected.extend(b"https://example.com");
+
+    assert_eq!(
+        encode_connect_request("example.com:4433", "/chat", "https://example.com"),
+        expected
+    );
+}
+
+#[test]
+fn test_decode_response_status() {
+    // An indexed field line for `:status: 200`.
+    assert_eq!(decode_response_status(&[0, 0, 0xc0 | 25]), Some(200));
+    // A literal field line with the name of `:status: 103` and a Huffman-encoded value.
+    assert_eq!(
+        decode_response_status(&[0, 0, 0x5f, 24 - 15, 0x82, 0x64, 0x02]),
+        Some(302)
+    );
+    // A Huffman-encoded `cache-control: private` field before a literal `:status`.
+    let mut field_section = vec![0, 0, 0x5f, 36 - 15, 0x85];
+    field_section.extend(hex("aec3771a4b"));
+    field_section.extend([0x27, 0x00]);
+    field_section.extend(b":status");
+    field_section.push(3);
+    field_section.extend(b"429");
+    assert_eq!(decode_response_status(&field_section), Some(429));
+
+    // Field sections that refer to the dynamic table.
+    assert_eq!(decode_response_status(&[1, 0, 0xc0 | 25]), None);
+    assert_eq!(decode_response_status(&[0, 0, 0x80]), None);
+    // A field section without a status.
+    assert_eq!(decode_response_status(&[0, 0, 0xc0 | 15]), None);
+}
+
+#[test]
+fn test_decode_capsule() {
+    // Two capsules in one buffer, the second of which is incomplete.
+    let mut bytes = Vec::new();
+    encode_varint(0x2843, &mut bytes);
+    encode_varint(6, &mut bytes);
+    bytes.extend([0, 0, 0, 1]);
+    bytes.extend(b"ok");
+    encode_varint(0x78ae, &mut bytes);
+    encode_varint(2, &mut bytes);
+    bytes.push(0);
+
+    let (capsule_type, payload, length) = decode_capsule(&bytes).unwrap().unwrap();
+    assert_eq!(capsule_type, 0x2843);
+    assert_eq!(payload, &[0, 0, 0, 1, b'o', b'k']);
+    assert_eq!(length, 9);
+    assert!(decode_capsule(&bytes[length..]).unwrap().is_none());
+
+    bytes.push(0);
+    let (capsule_type, payload, _) = decode_capsule(&bytes[length..]).unwrap().unwrap();
+    assert_eq!(capsule_type, 0x78ae);
+    assert_eq!(payload, &[0, 0]);
+
+    assert!(decode_capsule(&[]).unwrap().is_none());
+}
+
+#[test]
+fn test_decode_capsule_too_large() {
+    let mut bytes = Vec::new();
+    encode_varint(0x2843, &mut bytes);
+    encode_varint(64 * 1024, &mut bytes);
+    assert!(decode_capsule(&bytes).unwrap().is_none());
+
+    let mut bytes = Vec::new();
+    encode_varint(0x2843, &mut bytes);
+    encode_varint(64 * 1024 + 1, &mut bytes);
+    assert!(decode_capsule(&bytes).is_err());
+}
+
+#[test]
+fn test_http3_error_codes() {
+    for code in [0, 1, 0x1d, 0x1e, 0x1f, 1000, u32::MAX] {
+        assert_eq!(from_http3_error_code(to_http3_error_code(code)), code);
+    }
+    assert_eq!(
+        to_http3_error_code(0),
+        VarInt::from_u64(0x52e4a40fa8db).unwrap()
+    );
+    assert_eq!(
+        to_http3_error_code(u32::MAX),
+        VarInt::from_u64(0x52e5ac983162).unwrap()
+    );
+    // Codes outside of the range, and the reserved codes within it, map to 0.
+    assert_eq!(from_http3_error_code(VarInt::from_u32(0x100)), 0);
+    assert_eq!(
+        from_http3_error_code(VarInt::from_u64(0x52e4a40fa8db + 0x1e).unwrap()),
+        0
+    );
+}
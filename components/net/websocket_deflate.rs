@@ -0,0 +1,419 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The permessage-deflate WebSocket extension, which compresses the payload of messages.
+//! <https://www.rfc-editor.org/rfc/rfc7692>
+//!
+//! tungstenite does not support extensions, so the extension is implemented around it:
+//! [`InflatingStream`] sits between tungstenite and the connection and turns compressed
+//! messages into uncompressed frames before tungstenite reads them, and [`Deflater`]
+//! compresses messages into frames that tungstenite sends as they are.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tungstenite::Message;
+use tungstenite::protocol::frame::Frame;
+use tungstenite::protocol::frame::coding::{Data, OpCode};
+
+/// The extension offer that is sent in the `Sec-WebSocket-Extensions` header of requests.
+/// Window sizes are not offered, so both sides use windows of 32KiB.
+pub const EXTENSION_OFFER: &str = "permessage-deflate";
+
+/// The bytes that end the payload of every compressed message, which are removed from
+/// messages that are sent and added back to messages that are received.
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// How much the output buffer of compression and decompression grows at once.
+const BUFFER_CHUNK_SIZE: usize = 16 * 1024;
+
+/// The size of the buffer that is read from the connection into.
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// The longest HTTP response to the opening handshake.
+const MAX_HANDSHAKE_RESPONSE_SIZE: usize = 64 * 1024;
+
+/// The parameters of the permessage-deflate extension that were negotiated with the server.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DeflateParameters {
+    /// Whether the compression context must be reset after each message that is sent.
+    pub client_no_context_takeover: bool,
+    /// Whether the server resets its compression context after each message that it sends.
+    pub server_no_context_takeover: bool,
+}
+
+/// Negotiate the permessage-deflate extension given the value of the
+/// `Sec-WebSocket-Extensions` header of the response. Returns `None` if the server declined
+/// the extension, and an error if it accepted it or another extension in a way that was not
+/// offered, in which case the connection must be failed.
+///
+/// <https://www.rfc-editor.org/rfc/rfc7692#section-7.1>
+pub fn negotiate(extensions: Option<&str>) -> Result<Option<DeflateParameters>, String> {
+    let Some(extensions) = extensions.filter(|extensions| !extensions.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let mut negotiated = None;
+    for extension in extensions.split(',') {
+        let mut parts = extension.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        if !name.eq_ignore_ascii_case("permessage-deflate") {
+            return Err(format!(
+                "Server accepted an extension that was not offered: {name}"
+            ));
+        }
+        if negotiated.is_some() {
+            return Err("Server accepted permessage-deflate more than once".to_owned());
+        }
+
+        let mut parameters = DeflateParameters::default();
+        let mut server_max_window_bits = None;
+        for parameter in parts {
+            let (name, value) = match parameter.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (parameter, None),
+            };
+            let duplicate = match (name, value) {
+                ("client_no_context_takeover", None) => {
+                    std::mem::replace(&mut parameters.client_no_context_takeover, true)
+                },
+                ("server_no_context_takeover", None) => {
+                    std::mem::replace(&mut parameters.server_no_context_takeover, true)
+                },
+                // A window that is smaller than 32KiB does not change how messages are
+                // decompressed.
+                ("server_max_window_bits", Some(value)) => {
+                    let bits = value
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|bits| (8..=15).contains(bits))
+                        .ok_or_else(|| format!("Invalid server_max_window_bits: {value}"))?;
+                    server_max_window_bits.replace(bits).is_some()
+                },
+                _ => return Err(format!("Invalid permessage-deflate parameter: {parameter}")),
+            };
+            if duplicate {
+                return Err(format!("Duplicate permessage-deflate parameter: {name}"));
+            }
+        }
+        negotiated = Some(parameters);
+    }
+    Ok(negotiated)
+}
+
+/// Compresses the messages that are sent over a connection that negotiated permessage-deflate.
+pub struct Deflater {
+    compressor: Compress,
+    no_context_takeover: bool,
+}
+
+impl Deflater {
+    pub fn new(parameters: DeflateParameters) -> Deflater {
+        Deflater {
+            compressor: Compress::new(Compression::default(), false),
+            no_context_takeover: parameters.client_no_context_takeover,
+        }
+    }
+
+    /// Compress a text or binary message into a frame that is sent as it is. Other messages
+    /// are returned unchanged.
+    ///
+    /// <https://www.rfc-editor.org/rfc/rfc7692#section-7.2.1>
+    pub fn compress(&mut self, message: Message) -> io::Result<Message> {
+        let (data_type, payload) = match message {
+            Message::Text(text) => (Data::Text, text.into_bytes()),
+            Message::Binary(data) => (Data::Binary, data),
+            message => return Ok(message),
+        };
+
+        let mut compressed = Vec::with_capacity(payload.len() / 2 + DEFLATE_TRAILER.len());
+        let mut consumed = 0;
+        loop {
+            if compressed.len() == compressed.capacity() {
+                compressed.reserve(BUFFER_CHUNK_SIZE);
+            }
+            let total_in = self.compressor.total_in();
+            self.compressor
+                .compress_vec(&payload[consumed..], &mut compressed, FlushCompress::Sync)
+                .map_err(io::Error::other)?;
+            consumed += (self.compressor.total_in() - total_in) as usize;
+            if consumed == payload.len() && compressed.len() < compressed.capacity() {
+                break;
+            }
+        }
+        if compressed.ends_with(&DEFLATE_TRAILER) {
+            compressed.truncate(compressed.len() - DEFLATE_TRAILER.len());
+        }
+        if self.no_context_takeover {
+            self.compressor.reset();
+        }
+
+        let mut frame = Frame::message(compressed, OpCode::Data(data_type), true);
+        frame.header_mut().rsv1 = true;
+        Ok(Message::Frame(frame))
+    }
+}
+
+enum InflatingState {
+    /// The HTTP response to the opening handshake is being read.
+    Handshake,
+    /// Frames are being read, and compressed messages are decompressed if the server
+    /// accepted permessage-deflate.
+    Frames(Option<DeflateParameters>),
+}
+
+/// A connection that decompresses the messages that the server compressed with
+/// permessage-deflate, so that tungstenite reads them as uncompressed frames.
+///
+/// The HTTP response to the opening handshake is read through the connection too, which is
+/// how it knows whether the extension was negotiated before the first frame is read.
+pub struct InflatingStream<S> {
+    inner: S,
+    state: InflatingState,
+    decompressor: Decompress,
+    /// Bytes that were read from the connection and not processed yet.
+    input: Vec<u8>,
+    /// Processed bytes that are ready to be read.
+    output: Vec<u8>,
+    output_position: usize,
+    /// The opcode and the payload so far of the compressed message being received.
+    message: Option<(u8, Vec<u8>)>,
+    max_message_size: usize,
+}
+
+impl<S> InflatingStream<S> {
+    pub fn new(inner: S, max_message_size: usize) -> InflatingStream<S> {
+        InflatingStream {
+            inner,
+            state: InflatingState::Handshake,
+            decompressor: Decompress::new(false),
+            input: Vec::new(),
+            output: Vec::new(),
+            output_position: 0,
+            message: None,
+            max_message_size,
+        }
+    }
+
+    /// Move what can be processed from the input to the output.
+    fn process(&mut self) -> io::Result<()> {
+        if let InflatingState::Handshake = self.state {
+            let Some(end) = self
+                .input
+                .windows(4)
+                .position(|window| window == b"\r\n\r\n")
+            else {
+                if self.input.len() > MAX_HANDSHAKE_RESPONSE_SIZE {
+                    return Err(io::Error::other("WebSocket handshake response too large"));
+                }
+                return Ok(());
+            };
+            let response: Vec<u8> = self.input.drain(..end + 4).collect();
+            // An invalid negotiation fails the connection once the response is processed,
+            // and no compressed message is accepted until then.
+            let parameters = negotiate(extensions_header(&response).as_deref())
+                .ok()
+                .flatten();
+            self.output.extend_from_slice(&response);
+            self.state = InflatingState::Frames(parameters);
+        }
+
+        let InflatingState::Frames(Some(parameters)) = self.state else {
+            self.output.append(&mut self.input);
+            return Ok(());
+        };
+
+        while let Some((header_length, payload_length)) = self.next_frame_length()? {
+            let frame_length = header_length + payload_length;
+            if self.input.len() < frame_length {
+                break;
+            }
+            let first_byte = self.input[0];
+            let (is_final, compressed, opcode) = (
+                first_byte & 0x80 != 0,
+                first_byte & 0x40 != 0,
+                first_byte & 0x0f,
+            );
+            let is_control = opcode & 0x08 != 0;
+
+            // Control frames and frames of uncompressed messages are passed through.
+            if is_control || (opcode != 0 && !compressed) || (opcode == 0 && self.message.is_none())
+            {
+                self.output.extend(self.input.drain(..frame_length));
+                continue;
+            }
+
+            if self.input[1] & 0x80 != 0 {
+                return Err(io::Error::other("Masked WebSocket frame from the server"));
+            }
+            let payload = self.input.drain(..frame_length).skip(header_length);
+            match &mut self.message {
+                Some((_, message)) if opcode == 0 && !compressed => {
+                    message.extend(payload);
+                    if message.len() > self.max_message_size {
+                        return Err(io::Error::other("WebSocket message too large"));
+                    }
+                },
+                None if opcode != 0 => self.message = Some((opcode, payload.collect())),
+                _ => return Err(io::Error::other("Invalid compressed WebSocket frame")),
+            }
+
+            if is_final {
+                let (opcode, message) = self.message.take().unwrap_or_default();
+                let message = self.inflate(message, parameters)?;
+                write_frame_header(&mut self.output, opcode, message.len());
+                self.output.extend_from_slice(&message);
+            }
+        }
+        Ok(())
+    }
+
+    /// The length of the header and of the payload of the next frame in the input, if
+    /// enough of it was read to know them.
+    fn next_frame_length(&self) -> io::Result<Option<(usize, usize)>> {
+        let Some(&[_, second_byte]) = self.input.get(..2) else {
+            return Ok(None);
+        };
+        let masked = second_byte & 0x80 != 0;
+        let (length_size, short_length) = match second_byte & 0x7f {
+            126 => (2, None),
+            127 => (8, None),
+            length => (0, Some(length as u64)),
+        };
+        let header_length = 2 + length_size + if masked { 4 } else { 0 };
+        let Some(extended_length) = self.input.get(2..2 + length_size) else {
+            return Ok(None);
+        };
+        let payload_length = short_length.unwrap_or_else(|| {
+            extended_length
+                .iter()
+                .fold(0, |length, byte| (length << 8) | *byte as u64)
+        });
+        if payload_length > self.max_message_size as u64 {
+            return Err(io::Error::other("WebSocket frame too large"));
+        }
+        Ok(Some((header_length, payload_length as usize)))
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc7692#section-7.2.2>
+    fn inflate(
+        &mut self,
+        mut payload: Vec<u8>,
+        parameters: DeflateParameters,
+    ) -> io::Result<Vec<u8>> {
+        payload.extend_from_slice(&DEFLATE_TRAILER);
+        let mut message = Vec::with_capacity(payload.len() * 2);
+        let mut consumed = 0;
+        loop {
+            if message.len() == message.capacity() {
+                message.reserve(BUFFER_CHUNK_SIZE);
+            }
+            let (total_in, total_out) =
+                (self.decompressor.total_in(), self.decompressor.total_out());
+            self.decompressor
+                .decompress_vec(&payload[consumed..], &mut message, FlushDecompress::Sync)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            consumed += (self.decompressor.total_in() - total_in) as usize;
+            if message.len() > self.max_message_size {
+                return Err(io::Error::other("WebSocket message too large"));
+            }
+            if consumed == payload.len() && message.len() < message.capacity() {
+                break;
+            }
+            if self.decompressor.total_in() == total_in &&
+                self.decompressor.total_out() == total_out
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Truncated compressed WebSocket message",
+                ));
+            }
+        }
+        if parameters.server_no_context_takeover {
+            self.decompressor.reset(false);
+        }
+        Ok(message)
+    }
+}
+
+/// The value of the `Sec-WebSocket-Extensions` header of an HTTP response, with the values
+/// of all the headers of that name joined.
+fn extensions_header(response: &[u8]) -> Option<String> {
+    let response = String::from_utf8_lossy(response);
+    let values: Vec<_> = response
+        .split("\r\n")
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+        .map(|(_, value)| value.trim())
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Write the header of a final unmasked frame with `payload_length` bytes of payload.
+fn write_frame_header(output: &mut Vec<u8>, opcode: u8, payload_length: usize) {
+    output.push(0x80 | opcode);
+    if payload_length < 126 {
+        output.push(payload_length as u8);
+    } else if payload_length <= u16::MAX as usize {
+        output.push(126);
+        output.extend_from_slice(&(payload_length as u16).to_be_bytes());
+    } else {
+        output.push(127);
+        output.extend_from_slice(&(payload_length as u64).to_be_bytes());
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflatingStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.output_position < this.output.len() {
+                let available = &this.output[this.output_position..];
+                let length = available.len().min(buf.remaining());
+                buf.put_slice(&available[..length]);
+                this.output_position += length;
+                if this.output_position == this.output.len() {
+                    this.output.clear();
+                    this.output_position = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut chunk = [0; READ_BUFFER_SIZE];
+            let mut chunk = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk))?;
+            if chunk.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            this.input.extend_from_slice(chunk.filled());
+            this.process()?;
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflatingStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
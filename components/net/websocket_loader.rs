@@ -11,11 +11,12 @@
 //! over events from the network and events from the DOM, using async/await to avoid
 //! the need for a dedicated thread per websocket.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use async_tungstenite::WebSocketStream;
-use async_tungstenite::tokio::{ConnectStream, client_async_tls_with_connector_and_config};
+use async_tungstenite::tokio::{TokioAdapter, client_async_with_config};
 use base64::Engine;
 use futures::future::TryFutureExt;
 use futures::stream::StreamExt;
@@ -24,8 +25,13 @@ use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use log::{debug, trace, warn};
 use net_traits::request::{RequestBuilder, RequestMode};
-use net_traits::{CookieSource, MessageData, WebSocketDomAction, WebSocketNetworkEvent};
+use net_traits::{
+    CookieContext, CookieSource, MessageData, WebSocketDomAction, WebSocketNetworkEvent,
+};
+use rustls_pki_types::ServerName;
+use servo_config::pref;
 use servo_url::ServoUrl;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio::select;
 use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
@@ -33,18 +39,30 @@ use tokio_rustls::TlsConnector;
 use tungstenite::Message;
 use tungstenite::error::{Error, ProtocolError, Result as WebSocketResult, UrlError};
 use tungstenite::handshake::client::{Request, Response};
-use tungstenite::protocol::CloseFrame;
+use tungstenite::protocol::{CloseFrame, WebSocketConfig};
 use url::Url;
 
 use crate::async_runtime::HANDLE;
 use crate::connector::{CACertificates, TlsConfig, create_tls_config};
 use crate::cookie::ServoCookie;
+use crate::cookie_storage::CookiePartition;
 use crate::fetch::methods::should_request_be_blocked_due_to_a_bad_port;
 use crate::hosts::replace_host;
 use crate::http_loader::HttpState;
+use crate::proxy::{connect_through_proxy, proxy_for};
+use crate::resolver::resolve;
+use crate::websocket_deflate::{self, DeflateParameters, Deflater, InflatingStream};
+
+/// A connection that a WebSocket runs over, which is encrypted for `wss:` URLs.
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+type ServoWebSocketStream = WebSocketStream<TokioAdapter<InflatingStream<Box<dyn Transport>>>>;
+
 /// Create a tungstenite Request object for the initial HTTP request.
-/// This request contains `Origin`, `Sec-WebSocket-Protocol`, `Authorization`,
-/// and `Cookie` headers as appropriate.
+/// This request contains `Origin`, `Sec-WebSocket-Protocol`, `Sec-WebSocket-Extensions`,
+/// `Authorization`, and `Cookie` headers as appropriate.
 /// Returns an error if any header values are invalid or tungstenite cannot create
 /// the desired request.
 fn create_request(
@@ -52,6 +70,7 @@ fn create_request(
     origin: &str,
     protocols: &[String],
     http_state: &HttpState,
+    cookie_partition: Option<&CookiePartition>,
 ) -> WebSocketResult<Request> {
     let mut builder = Request::get(resource_url.as_str());
     let headers = builder.headers_mut().unwrap();
@@ -77,10 +96,23 @@ fn create_request(
         headers.insert("Sec-WebSocket-Protocol", HeaderValue::from_str(&protocols)?);
     }
 
-    let mut cookie_jar = http_state.cookie_jar.write().unwrap();
-    cookie_jar.remove_expired_cookies_for_url(resource_url);
-    if let Some(cookie_list) = cookie_jar.cookies_for_url(resource_url, CookieSource::HTTP) {
-        headers.insert("Cookie", HeaderValue::from_str(&cookie_list)?);
+    if pref!(network_websocket_permessage_deflate_enabled) {
+        headers.insert(
+            "Sec-WebSocket-Extensions",
+            HeaderValue::from_static(websocket_deflate::EXTENSION_OFFER),
+        );
+    }
+
+    if let Some(cookie_partition) = cookie_partition {
+        let mut cookie_jar = http_state.cookie_jar.write().unwrap();
+        cookie_jar.remove_expired_cookies_for_url(resource_url);
+        if let Some(cookie_list) = cookie_jar.cookies_for_url_in_partition(
+            resource_url,
+            CookieSource::HTTP,
+            cookie_partition,
+        ) {
+            headers.insert("Cookie", HeaderValue::from_str(&cookie_list)?);
+        }
     }
 
     if resource_url.password().is_some() || resource_url.username() != "" {
@@ -102,13 +134,16 @@ fn create_request(
 /// Process an HTTP response resulting from a WS handshake.
 /// This ensures that any `Cookie` or HSTS headers are recognized.
 /// Returns an error if the protocol selected by the handshake doesn't
-/// match the list of provided protocols in the original request.
+/// match the list of provided protocols in the original request, or if
+/// the extensions it accepted weren't offered. Otherwise, returns the
+/// protocol in use and the negotiated permessage-deflate parameters.
 fn process_ws_response(
     http_state: &HttpState,
     response: &Response,
     resource_url: &ServoUrl,
     protocols: &[String],
-) -> Result<Option<String>, Error> {
+    cookie_partition: Option<&CookiePartition>,
+) -> Result<(Option<String>, Option<DeflateParameters>), Error> {
     trace!("processing websocket http response for {}", resource_url);
     let mut protocol_in_use = None;
     if let Some(protocol_name) = response.headers().get("Sec-WebSocket-Protocol") {
@@ -121,14 +156,35 @@ fn process_ws_response(
         protocol_in_use = Some(protocol_name.to_string());
     }
 
-    let mut jar = http_state.cookie_jar.write().unwrap();
-    // TODO(eijebong): Replace thise once typed headers settled on a cookie impl
-    for cookie in response.headers().get_all(header::SET_COOKIE) {
-        if let Ok(s) = std::str::from_utf8(cookie.as_bytes()) {
-            if let Some(cookie) =
-                ServoCookie::from_cookie_string(s.into(), resource_url, CookieSource::HTTP)
-            {
-                jar.push(cookie, resource_url, CookieSource::HTTP);
+    let extensions = response
+        .headers()
+        .get_all("Sec-WebSocket-Extensions")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let deflate_parameters = websocket_deflate::negotiate(Some(&extensions)).map_err(|error| {
+        warn!("Invalid WebSocket extension negotiation: {error}");
+        Error::Protocol(ProtocolError::InvalidHeader(HeaderName::from_static(
+            "sec-websocket-extensions",
+        )))
+    })?;
+
+    if let Some(cookie_partition) = cookie_partition {
+        let mut jar = http_state.cookie_jar.write().unwrap();
+        // TODO(eijebong): Replace thise once typed headers settled on a cookie impl
+        for cookie in response.headers().get_all(header::SET_COOKIE) {
+            if let Ok(s) = std::str::from_utf8(cookie.as_bytes()) {
+                if let Some(cookie) =
+                    ServoCookie::from_cookie_string(s.into(), resource_url, CookieSource::HTTP)
+                {
+                    jar.push_in_partition(
+                        cookie,
+                        resource_url,
+                        CookieSource::HTTP,
+                        cookie_partition,
+                    );
+                }
             }
         }
     }
@@ -139,7 +195,7 @@ fn process_ws_response(
         .unwrap()
         .update_hsts_list_from_response(resource_url, response.headers());
 
-    Ok(protocol_in_use)
+    Ok((protocol_in_use, deflate_parameters))
 }
 
 #[derive(Debug)]
@@ -195,8 +251,10 @@ fn setup_dom_listener(
 async fn run_ws_loop(
     mut dom_receiver: UnboundedReceiver<DomMsg>,
     resource_event_sender: IpcSender<WebSocketNetworkEvent>,
-    mut stream: WebSocketStream<ConnectStream>,
+    mut stream: ServoWebSocketStream,
+    deflate_parameters: Option<DeflateParameters>,
 ) {
+    let mut deflater = deflate_parameters.map(Deflater::new);
     loop {
         select! {
             dom_msg = dom_receiver.recv() => {
@@ -207,9 +265,28 @@ async fn run_ws_loop(
                 };
                 match dom_msg {
                     DomMsg::Send(m) => {
+                        // The DOM counts the uncompressed bytes towards `bufferedAmount`.
+                        let sent = m.len() as u64;
+                        let m = match deflater.as_mut() {
+                            Some(deflater) => match deflater.compress(m) {
+                                Ok(m) => m,
+                                Err(e) => {
+                                    warn!("error compressing websocket message: {:?}", e);
+                                    let _ = resource_event_sender.send(WebSocketNetworkEvent::Fail);
+                                    break;
+                                },
+                            },
+                            None => m,
+                        };
                         if let Err(e) = stream.send(m).await {
                             warn!("error sending websocket message: {:?}", e);
                         }
+                        if resource_event_sender
+                            .send(WebSocketNetworkEvent::MessageSent(sent))
+                            .is_err()
+                        {
+                            break;
+                        }
                     },
                     DomMsg::Close(frame) => {
                         if let Err(e) = stream.close(frame.map(|(code, reason)| {
@@ -294,6 +371,7 @@ async fn start_websocket(
     client: Request,
     tls_config: TlsConfig,
     dom_action_receiver: IpcReceiver<WebSocketDomAction>,
+    cookie_partition: Option<CookiePartition>,
 ) -> Result<(), Error> {
     trace!("starting WS connection to {}", url);
 
@@ -318,14 +396,44 @@ async fn start_websocket(
         .port_or_known_default()
         .ok_or_else(|| Error::Url(UrlError::UnableToConnect("Unknown port".into())))?;
 
-    let try_socket = TcpStream::connect((&*domain.to_string(), port)).await;
-    let socket = try_socket.map_err(Error::Io)?;
-    let connector = TlsConnector::from(Arc::new(tls_config));
+    // WebSockets are always tunneled through proxies, which see the original host.
+    let socket = match proxy_for(url.scheme(), host_str) {
+        Some(proxy) => connect_through_proxy(&proxy, host_str, port).await?,
+        None => {
+            let addresses: Vec<_> = resolve(&domain.to_string(), true)
+                .await?
+                .into_iter()
+                .map(|address| SocketAddr::new(address, port))
+                .collect();
+            let try_socket = TcpStream::connect(&*addresses).await;
+            try_socket.map_err(Error::Io)?
+        },
+    };
+    let transport: Box<dyn Transport> = if url.scheme() == "wss" {
+        let server_name = ServerName::try_from(host_str.to_owned())
+            .map_err(|e| Error::Url(UrlError::UnableToConnect(e.to_string())))?;
+        let connector = TlsConnector::from(Arc::new(tls_config));
+        Box::new(connector.connect(server_name, socket).await?)
+    } else {
+        Box::new(socket)
+    };
 
-    let (stream, response) =
-        client_async_tls_with_connector_and_config(client, socket, Some(connector), None).await?;
+    let config = WebSocketConfig::default();
+    let max_message_size = config.max_message_size.unwrap_or(usize::MAX);
+    let (stream, response) = client_async_with_config(
+        client,
+        InflatingStream::new(transport, max_message_size),
+        Some(config),
+    )
+    .await?;
 
-    let protocol_in_use = process_ws_response(&http_state, &response, &url, &protocols)?;
+    let (protocol_in_use, deflate_parameters) = process_ws_response(
+        &http_state,
+        &response,
+        &url,
+        &protocols,
+        cookie_partition.as_ref(),
+    )?;
 
     if !initiated_close.load(Ordering::SeqCst) {
         if resource_event_sender
@@ -336,7 +444,13 @@ async fn start_websocket(
         }
 
         trace!("about to start ws loop for {}", url);
-        run_ws_loop(dom_receiver, resource_event_sender, stream).await;
+        run_ws_loop(
+            dom_receiver,
+            resource_event_sender,
+            stream,
+            deflate_parameters,
+        )
+        .await;
     } else {
         trace!("client closed connection for {}, not running loop", url);
     }
@@ -375,11 +489,17 @@ fn connect(
         return Err("Port blocked".to_string());
     }
 
+    let cookie_context = CookieContext {
+        webview_id: req_builder.target_webview_id,
+        top_level_origin: req_builder.top_level_origin.clone(),
+    };
+    let cookie_partition = http_state.cookie_partition(&req_url, &cookie_context);
     let client = match create_request(
         &req_url,
         &req_builder.origin.ascii_serialization(),
         &protocols,
         &http_state,
+        cookie_partition.as_ref(),
     ) {
         Ok(c) => c,
         Err(e) => return Err(e.to_string()),
@@ -403,6 +523,7 @@ fn connect(
                 client,
                 tls_config,
                 dom_action_receiver,
+                cookie_partition,
             )
             .map_err(move |e| {
                 warn!("Failed to establish a WebSocket connection: {:?}", e);
@@ -0,0 +1,1100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! WebTransport sessions over HTTP/3, as described in
+//! <https://datatracker.ietf.org/doc/html/draft-ietf-webtrans-http3>.
+//!
+//! A session is established with an extended CONNECT request on a QUIC connection of its
+//! own. The h3 crate cannot establish WebTransport sessions as a client, so the parts of
+//! HTTP/3 and QPACK that this needs are implemented here: the session sends a single request,
+//! only reads the status of the response, and never lets the server use the QPACK dynamic
+//! table.
+//!
+//! Like the WebSocket handler, a session is driven by a task that selects over actions from
+//! the DOM and events from the network. Each stream is read and written by tasks of its own,
+//! so that a stream that is blocked by flow control does not hold back the others.
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use bytes::Bytes;
+use ipc_channel::ipc::{IpcReceiver, IpcSender};
+use ipc_channel::router::ROUTER;
+use log::{debug, trace, warn};
+use net_traits::request::RequestBuilder;
+use net_traits::{
+    WebTransportDomAction, WebTransportNetworkEvent, WebTransportSessionOptions,
+    WebTransportStreamId,
+};
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{
+    ClientConfig, Connection, Endpoint, ReadError, ReadExactError, RecvStream, SendStream, VarInt,
+    WriteError,
+};
+use servo_url::ServoUrl;
+use tokio::select;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::sync::oneshot;
+
+use crate::async_runtime::HANDLE;
+use crate::connector::{CACertificates, TlsConfig, create_tls_config};
+use crate::fetch::methods::should_request_be_blocked_due_to_a_bad_port;
+use crate::hosts::replace_host;
+use crate::http_loader::HttpState;
+use crate::resolver::resolve;
+
+type Error = Box<dyn StdError + Send + Sync>;
+
+/// The ALPN protocol identifier of HTTP/3.
+const H3_ALPN: &[u8] = b"h3";
+
+/// HTTP/3 stream types. <https://www.rfc-editor.org/rfc/rfc9114#section-6.2>
+const STREAM_TYPE_CONTROL: u64 = 0x00;
+const STREAM_TYPE_WEBTRANSPORT: u64 = 0x54;
+
+/// The signal value that starts a bidirectional WebTransport stream.
+const SIGNAL_WEBTRANSPORT_STREAM: u64 = 0x41;
+
+/// HTTP/3 frame types. <https://www.rfc-editor.org/rfc/rfc9114#section-7.2>
+const FRAME_DATA: u64 = 0x00;
+const FRAME_HEADERS: u64 = 0x01;
+const FRAME_SETTINGS: u64 = 0x04;
+
+/// The largest frame that is read, which is more than enough for the frames of a session.
+const MAX_FRAME_LENGTH: u64 = 64 * 1024;
+
+/// HTTP/3 settings. <https://www.rfc-editor.org/rfc/rfc9114#section-7.2.4.1>
+const SETTINGS_ENABLE_CONNECT_PROTOCOL: u64 = 0x08;
+const SETTINGS_H3_DATAGRAM: u64 = 0x33;
+const SETTINGS_ENABLE_WEBTRANSPORT: u64 = 0x2b603742;
+const SETTINGS_WEBTRANSPORT_MAX_SESSIONS: u64 = 0xc671706a;
+
+/// The capsule that closes a session. <https://datatracker.ietf.org/doc/html/draft-ietf-webtrans-http3#section-5>
+const CAPSULE_CLOSE_WEBTRANSPORT_SESSION: u64 = 0x2843;
+
+/// The largest capsule that is accepted, which bounds how much of the CONNECT stream of a
+/// session is buffered.
+const MAX_CAPSULE_LENGTH: u64 = 64 * 1024;
+
+/// The range of HTTP/3 error codes that WebTransport application error codes are mapped to.
+/// <https://datatracker.ietf.org/doc/html/draft-ietf-webtrans-http3#section-4.4>
+const WEBTRANSPORT_ERROR_FIRST: u64 = 0x52e4a40fa8db;
+const WEBTRANSPORT_ERROR_LAST: u64 = 0x52e5ac983162;
+
+/// H3_NO_ERROR, which closes the connection of a session once it is closed.
+const H3_NO_ERROR: u32 = 0x100;
+
+/// How long to wait for the peer to receive the capsule that closes a session before the
+/// connection is closed.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The largest chunk of data that is read from a stream at once.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Indices of the QPACK static table. <https://www.rfc-editor.org/rfc/rfc9204#appendix-A>
+const QPACK_AUTHORITY: u64 = 0;
+const QPACK_PATH: u64 = 1;
+const QPACK_METHOD_CONNECT: u64 = 15;
+const QPACK_SCHEME_HTTPS: u64 = 23;
+const QPACK_ORIGIN: u64 = 90;
+
+/// The `:status` entries of the QPACK static table, with their value.
+const QPACK_STATUS_ENTRIES: [(u64, u16); 14] = [
+    (24, 103),
+    (25, 200),
+    (26, 304),
+    (27, 404),
+    (28, 503),
+    (63, 100),
+    (64, 204),
+    (65, 206),
+    (66, 302),
+    (67, 400),
+    (68, 403),
+    (69, 421),
+    (70, 425),
+    (71, 500),
+];
+
+/// The length of the Huffman code of each symbol, the last one being EOS. The code is
+/// canonical, so the codes themselves follow from their lengths.
+/// <https://www.rfc-editor.org/rfc/rfc7541#appendix-B>
+const HUFFMAN_CODE_LENGTHS: [u8; 257] = [
+    13, 23, 28, 28, 28, 28, 28, 28, 28, 24, 30, 28, 28, 30, 28, 28, 28, 28, 28, 28, 28, 28, 30, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 6, 10, 10, 12, 13, 6, 8, 11, 10, 10, 8, 11, 8, 6, 6, 6, 5, 5,
+    5, 6, 6, 6, 6, 6, 6, 6, 7, 8, 15, 6, 12, 10, 13, 6, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+    7, 7, 7, 7, 7, 7, 7, 7, 8, 7, 8, 13, 19, 13, 14, 6, 15, 5, 6, 5, 6, 5, 6, 6, 6, 5, 7, 7, 6, 6,
+    6, 5, 6, 7, 6, 5, 5, 6, 7, 7, 7, 7, 7, 15, 11, 14, 13, 28, 20, 22, 20, 20, 22, 22, 22, 23, 22,
+    23, 23, 23, 23, 23, 24, 23, 24, 24, 22, 23, 24, 23, 23, 23, 23, 21, 22, 23, 22, 23, 23, 24, 22,
+    21, 20, 22, 22, 23, 23, 21, 23, 22, 22, 24, 21, 22, 23, 23, 21, 21, 22, 21, 23, 22, 23, 23, 20,
+    22, 22, 22, 23, 22, 22, 23, 26, 26, 20, 19, 22, 23, 22, 25, 26, 26, 26, 27, 27, 26, 24, 25, 19,
+    21, 26, 27, 27, 26, 27, 24, 21, 21, 26, 26, 28, 27, 27, 27, 20, 24, 20, 21, 22, 21, 21, 23, 22,
+    22, 25, 25, 24, 24, 26, 23, 26, 27, 26, 26, 27, 27, 27, 27, 27, 28, 27, 27, 27, 27, 27, 26, 30,
+];
+
+/// The longest Huffman code.
+const MAX_HUFFMAN_CODE_LENGTH: usize = 30;
+
+/// The symbol that ends a Huffman-encoded string, which must never be decoded.
+const HUFFMAN_EOS: u16 = 256;
+
+/// The Huffman code, by length: the number of codes of each length, their first code and the
+/// index of the symbol of the first code in `symbols`.
+struct HuffmanCode {
+    counts: [u32; MAX_HUFFMAN_CODE_LENGTH + 1],
+    first_codes: [u32; MAX_HUFFMAN_CODE_LENGTH + 1],
+    first_indices: [usize; MAX_HUFFMAN_CODE_LENGTH + 1],
+    /// The symbols, ordered by the length of their code and then by their value.
+    symbols: Vec<u16>,
+}
+
+static HUFFMAN_CODE: LazyLock<HuffmanCode> = LazyLock::new(|| {
+    let mut counts = [0; MAX_HUFFMAN_CODE_LENGTH + 1];
+    for length in HUFFMAN_CODE_LENGTHS {
+        counts[length as usize] += 1;
+    }
+    let mut first_codes = [0; MAX_HUFFMAN_CODE_LENGTH + 1];
+    let mut first_indices = [0; MAX_HUFFMAN_CODE_LENGTH + 1];
+    for length in 1..=MAX_HUFFMAN_CODE_LENGTH {
+        first_codes[length] = (first_codes[length - 1] + counts[length - 1]) << 1;
+        first_indices[length] = first_indices[length - 1] + counts[length - 1] as usize;
+    }
+    let mut symbols: Vec<u16> = (0..=HUFFMAN_EOS).collect();
+    symbols.sort_by_key(|symbol| HUFFMAN_CODE_LENGTHS[*symbol as usize]);
+    HuffmanCode {
+        counts,
+        first_codes,
+        first_indices,
+        symbols,
+    }
+});
+
+/// Decode a Huffman-encoded string. <https://www.rfc-editor.org/rfc/rfc7541#section-5.2>
+pub fn decode_huffman(bytes: &[u8]) -> Option<Vec<u8>> {
+    let huffman_code = &*HUFFMAN_CODE;
+    let mut decoded = Vec::new();
+    let mut code: u32 = 0;
+    let mut length = 0;
+    for byte in bytes {
+        for shift in (0..8).rev() {
+            code = (code << 1) | ((byte >> shift) & 1) as u32;
+            length += 1;
+            let offset = code.wrapping_sub(huffman_code.first_codes[length]);
+            if offset < huffman_code.counts[length] {
+                let symbol =
+                    huffman_code.symbols[huffman_code.first_indices[length] + offset as usize];
+                if symbol == HUFFMAN_EOS {
+                    return None;
+                }
+                decoded.push(symbol as u8);
+                code = 0;
+                length = 0;
+            } else if length == MAX_HUFFMAN_CODE_LENGTH {
+                return None;
+            }
+        }
+    }
+    // What remains must be the padding, which is fewer than eight of the most significant
+    // bits of the EOS code, all of which are ones.
+    (length < 8 && code == (1 << length) - 1).then_some(decoded)
+}
+
+/// Map a WebTransport application error code to an HTTP/3 error code.
+/// <https://datatracker.ietf.org/doc/html/draft-ietf-webtrans-http3#section-4.4>
+pub fn to_http3_error_code(code: u32) -> VarInt {
+    let code = code as u64;
+    VarInt::from_u64(WEBTRANSPORT_ERROR_FIRST + code + code / 0x1e)
+        .expect("WebTransport error codes are mapped to valid variable-length integers")
+}
+
+/// Map an HTTP/3 error code back to a WebTransport application error code, which is 0 for
+/// the codes that no application error code maps to.
+pub fn from_http3_error_code(code: VarInt) -> u32 {
+    let code = code.into_inner();
+    if !(WEBTRANSPORT_ERROR_FIRST..=WEBTRANSPORT_ERROR_LAST).contains(&code) ||
+        (code - 0x21) % 0x1f == 0
+    {
+        return 0;
+    }
+    let shifted = code - WEBTRANSPORT_ERROR_FIRST;
+    (shifted - shifted / 0x1f) as u32
+}
+
+fn stream_id_of(id: quinn::StreamId) -> WebTransportStreamId {
+    VarInt::from(id).into_inner()
+}
+
+/// Encode a variable-length integer. <https://www.rfc-editor.org/rfc/rfc9000#section-16>
+pub fn encode_varint(value: u64, buffer: &mut Vec<u8>) {
+    if value < 1 << 6 {
+        buffer.push(value as u8);
+    } else if value < 1 << 14 {
+        buffer.extend_from_slice(&(value as u16 | 0x4000).to_be_bytes());
+    } else if value < 1 << 30 {
+        buffer.extend_from_slice(&(value as u32 | 0x8000_0000).to_be_bytes());
+    } else {
+        buffer.extend_from_slice(&(value | 0xc000_0000_0000_0000).to_be_bytes());
+    }
+}
+
+/// Decode a variable-length integer, returning it with its length.
+pub fn decode_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let first = *bytes.first()?;
+    let length = 1 << (first >> 6);
+    let bytes = bytes.get(..length)?;
+    let value = bytes[1..]
+        .iter()
+        .fold((first & 0x3f) as u64, |value, byte| {
+            (value << 8) | *byte as u64
+        });
+    Some((value, length))
+}
+
+/// Read a variable-length integer from `stream`, or `None` if it ends before one.
+async fn read_varint(stream: &mut RecvStream) -> Result<Option<u64>, Error> {
+    let mut bytes = [0; 8];
+    match stream.read_exact(&mut bytes[..1]).await {
+        Ok(()) => {},
+        Err(ReadExactError::FinishedEarly(_)) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+    let length = 1 << (bytes[0] >> 6);
+    stream.read_exact(&mut bytes[1..length]).await?;
+    Ok(decode_varint(&bytes[..length]).map(|(value, _)| value))
+}
+
+fn encode_frame(frame_type: u64, payload: &[u8], buffer: &mut Vec<u8>) {
+    encode_varint(frame_type, buffer);
+    encode_varint(payload.len() as u64, buffer);
+    buffer.extend_from_slice(payload);
+}
+
+/// Read an HTTP/3 frame from `stream`, or `None` if it ends before one.
+async fn read_frame(stream: &mut RecvStream) -> Result<Option<(u64, Vec<u8>)>, Error> {
+    let Some(frame_type) = read_varint(stream).await? else {
+        return Ok(None);
+    };
+    let length = read_varint(stream).await?.ok_or("truncated frame")?;
+    if length > MAX_FRAME_LENGTH {
+        return Err("frame too large".into());
+    }
+    let mut payload = vec![0; length as usize];
+    stream.read_exact(&mut payload).await?;
+    Ok(Some((frame_type, payload)))
+}
+
+/// Decode the capsule at the start of `bytes`, returning its type, its payload and its length,
+/// or `None` if it is incomplete. <https://www.rfc-editor.org/rfc/rfc9297#section-3.2>
+pub fn decode_capsule(bytes: &[u8]) -> Result<Option<(u64, &[u8], usize)>, Error> {
+    let Some((capsule_type, type_length)) = decode_varint(bytes) else {
+        return Ok(None);
+    };
+    let Some((length, length_length)) = decode_varint(&bytes[type_length..]) else {
+        return Ok(None);
+    };
+    if length > MAX_CAPSULE_LENGTH {
+        return Err("capsule too large".into());
+    }
+    let start = type_length + length_length;
+    let end = start + length as usize;
+    Ok(bytes
+        .get(start..end)
+        .map(|payload| (capsule_type, payload, end)))
+}
+
+/// Encode an integer with an N-bit prefix. <https://www.rfc-editor.org/rfc/rfc7541#section-5.1>
+fn encode_prefixed_integer(value: u64, prefix_bits: u32, flags: u8, buffer: &mut Vec<u8>) {
+    let max_prefix = (1 << prefix_bits) - 1;
+    if value < max_prefix {
+        buffer.push(flags | value as u8);
+        return;
+    }
+    buffer.push(flags | max_prefix as u8);
+    let mut rest = value - max_prefix;
+    while rest >= 0x80 {
+        buffer.push(0x80 | (rest & 0x7f) as u8);
+        rest >>= 7;
+    }
+    buffer.push(rest as u8);
+}
+
+/// Decode an integer with an N-bit prefix, returning it with its length.
+fn decode_prefixed_integer(bytes: &[u8], prefix_bits: u32) -> Option<(u64, usize)> {
+    let max_prefix = (1 << prefix_bits) - 1;
+    let value = *bytes.first()? as u64 & max_prefix;
+    if value < max_prefix {
+        return Some((value, 1));
+    }
+    let mut value = max_prefix;
+    for (index, byte) in bytes.iter().enumerate().skip(1).take(8) {
+        value = value.checked_add(((byte & 0x7f) as u64) << (7 * (index - 1)))?;
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+/// Encode a string literal, without Huffman encoding.
+fn encode_string(value: &[u8], prefix_bits: u32, flags: u8, buffer: &mut Vec<u8>) {
+    encode_prefixed_integer(value.len() as u64, prefix_bits, flags, buffer);
+    buffer.extend_from_slice(value);
+}
+
+/// Decode a string literal, returning it with its length.
+fn decode_string(bytes: &[u8], prefix_bits: u32) -> Option<(Vec<u8>, usize)> {
+    let huffman_encoded = bytes.first()? & (1 << prefix_bits) != 0;
+    let (length, prefix_length) = decode_prefixed_integer(bytes, prefix_bits)?;
+    let end = prefix_length.checked_add(length as usize)?;
+    let value = bytes.get(prefix_length..end)?;
+    let value = if huffman_encoded {
+        decode_huffman(value)?
+    } else {
+        value.to_vec()
+    };
+    Some((value, end))
+}
+
+/// Encode the QPACK field section of the extended CONNECT request that establishes a session.
+/// <https://datatracker.ietf.org/doc/html/draft-ietf-webtrans-http3#section-3.3>
+pub fn encode_connect_request(authority: &str, path: &str, origin: &str) -> Vec<u8> {
+    // The required insert count and the base are zero, as the dynamic table is not used.
+    let mut field_section = vec![0, 0];
+    // Indexed field lines that refer to the static table.
+    encode_prefixed_integer(QPACK_METHOD_CONNECT, 6, 0b1100_0000, &mut field_section);
+    encode_prefixed_integer(QPACK_SCHEME_HTTPS, 6, 0b1100_0000, &mut field_section);
+    // A literal field line with a literal name.
+    encode_string(b":protocol", 3, 0b0010_0000, &mut field_section);
+    encode_string(b"webtransport", 7, 0, &mut field_section);
+    // Literal field lines with a name that refers to the static table.
+    for (name, value) in [
+        (QPACK_AUTHORITY, authority),
+        (QPACK_PATH, path),
+        (QPACK_ORIGIN, origin),
+    ] {
+        encode_prefixed_integer(name, 4, 0b0101_0000, &mut field_section);
+        encode_string(value.as_bytes(), 7, 0, &mut field_section);
+    }
+    field_section
+}
+
+/// The status of the response whose QPACK field section is `field_section`.
+pub fn decode_response_status(field_section: &[u8]) -> Option<u16> {
+    let status_of_entry = |index: u64| {
+        QPACK_STATUS_ENTRIES
+            .iter()
+            .find(|(entry, _)| *entry == index)
+            .map(|(_, status)| *status)
+    };
+    let parse_status = |value: &[u8]| std::str::from_utf8(value).ok()?.parse().ok();
+
+    // The field section cannot refer to the dynamic table, as its capacity is zero.
+    let (required_insert_count, mut position) = decode_prefixed_integer(field_section, 8)?;
+    if required_insert_count != 0 {
+        return None;
+    }
+    position += decode_prefixed_integer(field_section.get(position..)?, 7)?.1;
+
+    while let Some(bytes) = field_section
+        .get(position..)
+        .filter(|bytes| !bytes.is_empty())
+    {
+        let first = bytes[0];
+        if first & 0b1000_0000 != 0 {
+            // An indexed field line, which must refer to the static table.
+            if first & 0b0100_0000 == 0 {
+                return None;
+            }
+            let (index, length) = decode_prefixed_integer(bytes, 6)?;
+            if let Some(status) = status_of_entry(index) {
+                return Some(status);
+            }
+            position += length;
+        } else if first & 0b1100_0000 == 0b0100_0000 {
+            // A literal field line with a name that must refer to the static table.
+            if first & 0b0001_0000 == 0 {
+                return None;
+            }
+            let (index, name_length) = decode_prefixed_integer(bytes, 4)?;
+            let (value, value_length) = decode_string(&bytes[name_length..], 7)?;
+            if status_of_entry(index).is_some() {
+                return parse_status(&value);
+            }
+            position += name_length + value_length;
+        } else if first & 0b1110_0000 == 0b0010_0000 {
+            // A literal field line with a literal name.
+            let (name, name_length) = decode_string(bytes, 3)?;
+            let (value, value_length) = decode_string(&bytes[name_length..], 7)?;
+            if name == b":status" {
+                return parse_status(&value);
+            }
+            position += name_length + value_length;
+        } else {
+            // Field lines with post-base indices refer to the dynamic table.
+            return None;
+        }
+    }
+    None
+}
+
+/// Read what remains of a stream that is not used, so that the peer is never blocked on it.
+fn drain(mut stream: RecvStream) {
+    tokio::spawn(async move {
+        while let Ok(Some(_)) = stream.read_chunk(READ_CHUNK_SIZE, false).await {}
+    });
+}
+
+/// Wait for the control stream of the server, and check that its settings allow WebTransport.
+/// Returns whether they allow HTTP datagrams.
+async fn accept_server_settings(connection: &Connection) -> Result<bool, Error> {
+    loop {
+        let mut stream = connection.accept_uni().await?;
+        if read_varint(&mut stream).await? != Some(STREAM_TYPE_CONTROL) {
+            // The QPACK streams, which are not used, or streams of unknown types.
+            drain(stream);
+            continue;
+        }
+
+        let (frame_type, payload) = read_frame(&mut stream)
+            .await?
+            .ok_or("control stream closed")?;
+        if frame_type != FRAME_SETTINGS {
+            return Err("control stream does not start with settings".into());
+        }
+        let mut settings = HashMap::new();
+        let mut position = 0;
+        while position < payload.len() {
+            let (identifier, length) = decode_varint(&payload[position..]).ok_or("bad settings")?;
+            position += length;
+            let (value, length) = decode_varint(&payload[position..]).ok_or("bad settings")?;
+            position += length;
+            settings.insert(identifier, value);
+        }
+        let enabled = |setting| settings.get(&setting).is_some_and(|value| *value > 0);
+        if !enabled(SETTINGS_ENABLE_CONNECT_PROTOCOL) ||
+            !(enabled(SETTINGS_ENABLE_WEBTRANSPORT) ||
+                enabled(SETTINGS_WEBTRANSPORT_MAX_SESSIONS))
+        {
+            return Err("server does not support WebTransport".into());
+        }
+        drain(stream);
+        return Ok(enabled(SETTINGS_H3_DATAGRAM));
+    }
+}
+
+/// Open the control stream of the client, which must stay open as long as the connection.
+async fn open_control_stream(connection: &Connection) -> Result<SendStream, Error> {
+    let mut settings = Vec::new();
+    for setting in [
+        SETTINGS_ENABLE_CONNECT_PROTOCOL,
+        SETTINGS_H3_DATAGRAM,
+        SETTINGS_ENABLE_WEBTRANSPORT,
+        SETTINGS_WEBTRANSPORT_MAX_SESSIONS,
+    ] {
+        encode_varint(setting, &mut settings);
+        encode_varint(1, &mut settings);
+    }
+    let mut buffer = Vec::new();
+    encode_varint(STREAM_TYPE_CONTROL, &mut buffer);
+    encode_frame(FRAME_SETTINGS, &settings, &mut buffer);
+
+    let mut stream = connection.open_uni().await?;
+    stream.write_all(&buffer).await?;
+    Ok(stream)
+}
+
+#[derive(Debug)]
+enum WriteCommand {
+    Data(Vec<u8>),
+    Finish,
+    Reset(u32),
+}
+
+/// Events from the tasks of a session to the task that drives it.
+enum SessionEvent {
+    OutgoingStream {
+        request_id: u64,
+        streams: Option<(SendStream, Option<RecvStream>)>,
+    },
+    IncomingStream {
+        send: Option<SendStream>,
+        recv: RecvStream,
+    },
+    /// The server closed the session, or its CONNECT stream.
+    Closed { code: u32, reason: String },
+}
+
+struct Session {
+    connection: Connection,
+    /// The id of the CONNECT stream of the session.
+    session_id: u64,
+    /// Whether both QUIC and the server support datagrams, so that they can be sent.
+    datagrams_enabled: bool,
+    /// The CONNECT stream of the session, over which the session is closed.
+    connect_stream: SendStream,
+    /// The control stream of the client, which is kept open as long as the connection.
+    _control_stream: SendStream,
+    event_sender: IpcSender<WebTransportNetworkEvent>,
+    session_event_sender: UnboundedSender<SessionEvent>,
+    /// The senders of commands to the tasks that write to the sending side of streams.
+    writers: HashMap<WebTransportStreamId, UnboundedSender<WriteCommand>>,
+    /// The senders that stop the tasks that read the receiving side of streams, with an
+    /// error code for the peer.
+    readers: HashMap<WebTransportStreamId, oneshot::Sender<u32>>,
+}
+
+impl Session {
+    /// The header of the streams of this session, which associates them with it.
+    fn stream_header(&self, bidirectional: bool) -> Vec<u8> {
+        let mut header = Vec::new();
+        encode_varint(
+            if bidirectional {
+                SIGNAL_WEBTRANSPORT_STREAM
+            } else {
+                STREAM_TYPE_WEBTRANSPORT
+            },
+            &mut header,
+        );
+        encode_varint(self.session_id, &mut header);
+        header
+    }
+
+    fn start_writing(&mut self, mut stream: SendStream, header: Vec<u8>) -> WebTransportStreamId {
+        let stream_id = stream_id_of(stream.id());
+        let (sender, mut receiver) = unbounded_channel();
+        self.writers.insert(stream_id, sender);
+        let event_sender = self.event_sender.clone();
+        tokio::spawn(async move {
+            let report_error = |error: WriteError| match error {
+                WriteError::Stopped(code) => {
+                    let _ = event_sender.send(WebTransportNetworkEvent::StopSending(
+                        stream_id,
+                        from_http3_error_code(code),
+                    ));
+                },
+                error => debug!("Error writing to WebTransport stream: {error}"),
+            };
+            if !header.is_empty() {
+                if let Err(error) = stream.write_all(&header).await {
+                    return report_error(error);
+                }
+            }
+            while let Some(command) = receiver.recv().await {
+                match command {
+                    WriteCommand::Data(data) => {
+                        if let Err(error) = stream.write_all(&data).await {
+                            return report_error(error);
+                        }
+                    },
+                    WriteCommand::Finish => {
+                        let _ = stream.finish();
+                        return;
+                    },
+                    WriteCommand::Reset(code) => {
+                        let _ = stream.reset(to_http3_error_code(code));
+                        return;
+                    },
+                }
+            }
+        });
+        stream_id
+    }
+
+    fn start_reading(&mut self, mut stream: RecvStream) {
+        let stream_id = stream_id_of(stream.id());
+        let (stop_sender, mut stop_receiver) = oneshot::channel();
+        self.readers.insert(stream_id, stop_sender);
+        let event_sender = self.event_sender.clone();
+        tokio::spawn(async move {
+            loop {
+                select! {
+                    code = &mut stop_receiver => {
+                        if let Ok(code) = code {
+                            let _ = stream.stop(to_http3_error_code(code));
+                        }
+                        return;
+                    },
+                    chunk = stream.read_chunk(READ_CHUNK_SIZE, true) => {
+                        let (event, done) = match chunk {
+                            Ok(Some(chunk)) => (
+                                WebTransportNetworkEvent::StreamData(stream_id, chunk.bytes.to_vec()),
+                                false,
+                            ),
+                            Ok(None) => (WebTransportNetworkEvent::StreamFinished(stream_id), true),
+                            Err(ReadError::Reset(code)) => (
+                                WebTransportNetworkEvent::StreamReset(
+                                    stream_id,
+                                    from_http3_error_code(code),
+                                ),
+                                true,
+                            ),
+                            Err(error) => {
+                                debug!("Error reading from WebTransport stream: {error}");
+                                (WebTransportNetworkEvent::StreamReset(stream_id, 0), true)
+                            },
+                        };
+                        if event_sender.send(event).is_err() || done {
+                            return;
+                        }
+                    },
+                }
+            }
+        });
+    }
+
+    /// Open an outgoing stream in a task, as it may have to wait for the peer to allow it.
+    fn open_stream(&self, request_id: u64, bidirectional: bool) {
+        let connection = self.connection.clone();
+        let session_event_sender = self.session_event_sender.clone();
+        tokio::spawn(async move {
+            let streams = if bidirectional {
+                connection
+                    .open_bi()
+                    .await
+                    .ok()
+                    .map(|(send, recv)| (send, Some(recv)))
+            } else {
+                connection.open_uni().await.ok().map(|send| (send, None))
+            };
+            let _ = session_event_sender.send(SessionEvent::OutgoingStream {
+                request_id,
+                streams,
+            });
+        });
+    }
+
+    /// Read the header of a stream opened by the server in a task, and hand it over to the
+    /// session if it belongs to it.
+    fn accept_stream(&self, send: Option<SendStream>, mut recv: RecvStream) {
+        let session_id = self.session_id;
+        let session_event_sender = self.session_event_sender.clone();
+        tokio::spawn(async move {
+            let expected_type = if send.is_some() {
+                SIGNAL_WEBTRANSPORT_STREAM
+            } else {
+                STREAM_TYPE_WEBTRANSPORT
+            };
+            match read_varint(&mut recv).await {
+                Ok(Some(stream_type)) if stream_type == expected_type => {},
+                _ => return drain(recv),
+            }
+            if !matches!(read_varint(&mut recv).await, Ok(Some(id)) if id == session_id) {
+                return drain(recv);
+            }
+            let _ = session_event_sender.send(SessionEvent::IncomingStream { send, recv });
+        });
+    }
+
+    /// Read the capsules that the server sends over the CONNECT stream in a task, until it
+    /// closes the session.
+    fn read_connect_stream(&self, mut stream: RecvStream) {
+        let session_event_sender = self.session_event_sender.clone();
+        tokio::spawn(async move {
+            let mut capsules = Vec::new();
+            let (code, reason) = 'session: loop {
+                let payload = match read_frame(&mut stream).await {
+                    Ok(Some((FRAME_DATA, payload))) => payload,
+                    Ok(Some(_)) => continue,
+                    Ok(None) | Err(_) => break (0, String::new()),
+                };
+                // A DATA frame may hold several capsules, and a capsule may span several frames.
+                capsules.extend_from_slice(&payload);
+                let mut decoded_length = 0;
+                loop {
+                    match decode_capsule(&capsules[decoded_length..]) {
+                        Ok(Some((CAPSULE_CLOSE_WEBTRANSPORT_SESSION, capsule, _)))
+                            if capsule.len() >= 4 =>
+                        {
+                            let code = u32::from_be_bytes([
+                                capsule[0], capsule[1], capsule[2], capsule[3],
+                            ]);
+                            break 'session (
+                                code,
+                                String::from_utf8_lossy(&capsule[4..]).into_owned(),
+                            );
+                        },
+                        Ok(Some((_, _, length))) => decoded_length += length,
+                        Ok(None) => break,
+                        Err(error) => {
+                            debug!("Closing WebTransport session: {error}");
+                            break 'session (0, String::new());
+                        },
+                    }
+                }
+                capsules.drain(..decoded_length);
+            };
+            let _ = session_event_sender.send(SessionEvent::Closed { code, reason });
+        });
+    }
+
+    fn handle_dom_action(&mut self, action: WebTransportDomAction) {
+        match action {
+            WebTransportDomAction::OpenStream {
+                request_id,
+                bidirectional,
+            } => self.open_stream(request_id, bidirectional),
+            WebTransportDomAction::SendData(stream_id, data) => {
+                if let Some(writer) = self.writers.get(&stream_id) {
+                    let _ = writer.send(WriteCommand::Data(data));
+                }
+            },
+            WebTransportDomAction::Finish(stream_id) => {
+                if let Some(writer) = self.writers.remove(&stream_id) {
+                    let _ = writer.send(WriteCommand::Finish);
+                }
+            },
+            WebTransportDomAction::Reset(stream_id, code) => {
+                if let Some(writer) = self.writers.remove(&stream_id) {
+                    let _ = writer.send(WriteCommand::Reset(code));
+                }
+            },
+            WebTransportDomAction::StopSending(stream_id, code) => {
+                if let Some(reader) = self.readers.remove(&stream_id) {
+                    let _ = reader.send(code);
+                }
+            },
+            WebTransportDomAction::SendDatagram(_) if !self.datagrams_enabled => {
+                debug!("Dropping WebTransport datagram, as datagrams are not supported");
+            },
+            WebTransportDomAction::SendDatagram(data) => {
+                // The datagrams of a session start with the quarter of its id.
+                let mut datagram = Vec::with_capacity(data.len() + 8);
+                encode_varint(self.session_id / 4, &mut datagram);
+                datagram.extend_from_slice(&data);
+                if let Err(error) = self.connection.send_datagram(Bytes::from(datagram)) {
+                    debug!("Dropping WebTransport datagram: {error}");
+                }
+            },
+            WebTransportDomAction::Close { .. } => {
+                unreachable!("Closing is handled by the session loop")
+            },
+        }
+    }
+
+    fn handle_session_event(&mut self, event: SessionEvent) -> bool {
+        match event {
+            SessionEvent::OutgoingStream {
+                request_id,
+                streams,
+            } => {
+                let stream_id = streams.map(|(send, recv)| {
+                    let header = self.stream_header(recv.is_some());
+                    let stream_id = self.start_writing(send, header);
+                    if let Some(recv) = recv {
+                        self.start_reading(recv);
+                    }
+                    stream_id
+                });
+                let _ = self
+                    .event_sender
+                    .send(WebTransportNetworkEvent::StreamOpened {
+                        request_id,
+                        stream_id,
+                    });
+            },
+            SessionEvent::IncomingStream { send, recv } => {
+                let stream_id = stream_id_of(recv.id());
+                let bidirectional = send.is_some();
+                if let Some(send) = send {
+                    self.start_writing(send, Vec::new());
+                }
+                self.start_reading(recv);
+                let _ = self
+                    .event_sender
+                    .send(WebTransportNetworkEvent::IncomingStream {
+                        stream_id,
+                        bidirectional,
+                    });
+            },
+            SessionEvent::Closed { code, reason } => {
+                debug!("WebTransport session closed by the server: {code} {reason}");
+                let _ = self
+                    .event_sender
+                    .send(WebTransportNetworkEvent::SessionClosed { code, reason });
+                self.connection
+                    .close(VarInt::from_u32(H3_NO_ERROR), b"session closed");
+                return false;
+            },
+        }
+        true
+    }
+
+    /// Close the session by sending a capsule over its CONNECT stream.
+    async fn close(mut self, code: u32, reason: String) {
+        let mut capsule = code.to_be_bytes().to_vec();
+        capsule.extend_from_slice(reason.as_bytes());
+        let mut data = Vec::new();
+        encode_varint(CAPSULE_CLOSE_WEBTRANSPORT_SESSION, &mut data);
+        encode_varint(capsule.len() as u64, &mut data);
+        data.extend_from_slice(&capsule);
+        let mut frame = Vec::new();
+        encode_frame(FRAME_DATA, &data, &mut frame);
+
+        if self.connect_stream.write_all(&frame).await.is_ok() &&
+            self.connect_stream.finish().is_ok()
+        {
+            let _ = tokio::time::timeout(CLOSE_TIMEOUT, self.connect_stream.stopped()).await;
+        }
+        self.connection
+            .close(VarInt::from_u32(H3_NO_ERROR), b"session closed");
+    }
+
+    /// Handle actions from the DOM and events from the network until the session is closed.
+    async fn run(
+        mut self,
+        mut dom_receiver: UnboundedReceiver<WebTransportDomAction>,
+        mut session_event_receiver: UnboundedReceiver<SessionEvent>,
+    ) {
+        let datagram_prefix = {
+            let mut prefix = Vec::new();
+            encode_varint(self.session_id / 4, &mut prefix);
+            prefix
+        };
+        loop {
+            select! {
+                action = dom_receiver.recv() => {
+                    trace!("processing WebTransport DOM action: {:?}", action);
+                    match action {
+                        Some(WebTransportDomAction::Close { code, reason }) => {
+                            return self.close(code, reason).await;
+                        },
+                        Some(action) => self.handle_dom_action(action),
+                        None => return self.close(0, String::new()).await,
+                    }
+                },
+                event = session_event_receiver.recv() => {
+                    if let Some(event) = event {
+                        if !self.handle_session_event(event) {
+                            return;
+                        }
+                    }
+                },
+                stream = self.connection.accept_bi() => {
+                    match stream {
+                        Ok((send, recv)) => self.accept_stream(Some(send), recv),
+                        Err(error) => return self.fail(error.into()),
+                    }
+                },
+                stream = self.connection.accept_uni() => {
+                    match stream {
+                        Ok(recv) => self.accept_stream(None, recv),
+                        Err(error) => return self.fail(error.into()),
+                    }
+                },
+                datagram = self.connection.read_datagram() => {
+                    match datagram {
+                        Ok(datagram) if datagram.starts_with(&datagram_prefix) => {
+                            let data = datagram[datagram_prefix.len()..].to_vec();
+                            let _ = self
+                                .event_sender
+                                .send(WebTransportNetworkEvent::DatagramReceived(data));
+                        },
+                        Ok(_) => {},
+                        Err(error) => return self.fail(error.into()),
+                    }
+                },
+            }
+        }
+    }
+
+    fn fail(self, error: Error) {
+        warn!("WebTransport session failed: {error}");
+        let _ = self
+            .event_sender
+            .send(WebTransportNetworkEvent::SessionFailed);
+    }
+}
+
+/// Route the actions from the DOM to a tokio channel that the session task receives them on.
+fn setup_dom_listener(
+    action_receiver: IpcReceiver<WebTransportDomAction>,
+) -> UnboundedReceiver<WebTransportDomAction> {
+    let (sender, receiver) = unbounded_channel();
+    ROUTER.add_typed_route(
+        action_receiver,
+        Box::new(move |message| match message {
+            Ok(action) => {
+                let _ = sender.send(action);
+            },
+            Err(error) => warn!("Error receiving WebTransport DOM action: {error:?}"),
+        }),
+    );
+    receiver
+}
+
+/// Establish a session with the server at `url`, and run it until it is closed.
+async fn start_session(
+    url: ServoUrl,
+    origin: String,
+    tls_config: TlsConfig,
+    options: WebTransportSessionOptions,
+    event_sender: IpcSender<WebTransportNetworkEvent>,
+    action_receiver: IpcReceiver<WebTransportDomAction>,
+) -> Result<(), Error> {
+    trace!("starting WebTransport session with {}", url);
+    let dom_receiver = setup_dom_listener(action_receiver);
+
+    let host = url.host_str().ok_or("no host")?;
+    let port = url.port_or_known_default().ok_or("no port")?;
+    let address = resolve(&replace_host(host), true)
+        .await?
+        .first()
+        .map(|address| SocketAddr::new(*address, port))
+        .ok_or("no address")?;
+    // Sessions are never pooled. A dedicated connection is what a session must have when
+    // `allow_pooling` is false, and what it may have when it is true.
+    let local_address: SocketAddr = if address.is_ipv6() {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    };
+    let endpoint = Endpoint::client(local_address)?;
+    let client_config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(tls_config)?));
+    let connection = endpoint.connect_with(client_config, address, host)?.await?;
+
+    let control_stream = open_control_stream(&connection).await?;
+    let datagrams_enabled =
+        accept_server_settings(&connection).await? && connection.max_datagram_size().is_some();
+    if options.require_unreliable && !datagrams_enabled {
+        return Err("the session requires datagrams, which are not supported".into());
+    }
+
+    let (mut connect_stream, mut connect_response_stream) = connection.open_bi().await?;
+    let authority = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    };
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_owned(),
+    };
+    let mut request = Vec::new();
+    encode_frame(
+        FRAME_HEADERS,
+        &encode_connect_request(&authority, &path, &origin),
+        &mut request,
+    );
+    connect_stream.write_all(&request).await?;
+
+    let status = loop {
+        match read_frame(&mut connect_response_stream).await? {
+            Some((FRAME_HEADERS, field_section)) => {
+                break decode_response_status(&field_section).ok_or("bad response")?;
+            },
+            Some(_) => continue,
+            None => return Err("no response".into()),
+        }
+    };
+    if !(200..300).contains(&status) {
+        return Err(format!("server refused the session with status {status}").into());
+    }
+
+    let session_id = stream_id_of(connect_stream.id());
+    let mut datagram_prefix = Vec::new();
+    encode_varint(session_id / 4, &mut datagram_prefix);
+    let max_datagram_size = connection
+        .max_datagram_size()
+        .filter(|_| datagrams_enabled)
+        .map_or(0, |size| size.saturating_sub(datagram_prefix.len()));
+    if event_sender
+        .send(WebTransportNetworkEvent::SessionEstablished {
+            max_datagram_size: max_datagram_size as u32,
+        })
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let (session_event_sender, session_event_receiver) = unbounded_channel();
+    let session = Session {
+        connection,
+        session_id,
+        datagrams_enabled,
+        connect_stream,
+        _control_stream: control_stream,
+        event_sender,
+        session_event_sender,
+        writers: Default::default(),
+        readers: Default::default(),
+    };
+    session.read_connect_stream(connect_response_stream);
+    session.run(dom_receiver, session_event_receiver).await;
+    endpoint.wait_idle().await;
+    Ok(())
+}
+
+fn connect(
+    req_builder: RequestBuilder,
+    options: WebTransportSessionOptions,
+    event_sender: IpcSender<WebTransportNetworkEvent>,
+    action_receiver: IpcReceiver<WebTransportDomAction>,
+    http_state: Arc<HttpState>,
+    ca_certificates: CACertificates,
+    ignore_certificate_errors: bool,
+) -> Result<(), String> {
+    let url = req_builder.url.clone();
+    if url.scheme() != "https" {
+        return Err("WebTransport requires an https URL".to_string());
+    }
+    if should_request_be_blocked_due_to_a_bad_port(&url) {
+        return Err("Port blocked".to_string());
+    }
+
+    let mut tls_config = create_tls_config(
+        ca_certificates,
+        ignore_certificate_errors,
+        http_state.override_manager.clone(),
+    );
+    tls_config.alpn_protocols = vec![H3_ALPN.to_vec()];
+    let origin = req_builder.origin.ascii_serialization();
+
+    let event_sender2 = event_sender.clone();
+    match HANDLE.lock().unwrap().as_mut() {
+        Some(handle) => handle.spawn(async move {
+            if let Err(error) = start_session(
+                url,
+                origin,
+                tls_config,
+                options,
+                event_sender,
+                action_receiver,
+            )
+            .await
+            {
+                warn!("Failed to establish a WebTransport session: {error}");
+                let _ = event_sender2.send(WebTransportNetworkEvent::SessionFailed);
+            }
+        }),
+        None => return Err("No runtime available".to_string()),
+    };
+    Ok(())
+}
+
+/// Establish a new WebTransport session for the given request.
+pub fn init(
+    req_builder: RequestBuilder,
+    options: WebTransportSessionOptions,
+    event_sender: IpcSender<WebTransportNetworkEvent>,
+    action_receiver: IpcReceiver<WebTransportDomAction>,
+    http_state: Arc<HttpState>,
+    ca_certificates: CACertificates,
+    ignore_certificate_errors: bool,
+) {
+    let event_sender2 = event_sender.clone();
+    if let Err(error) = connect(
+        req_builder,
+        options,
+        event_sender,
+        action_receiver,
+        http_state,
+        ca_certificates,
+        ignore_certificate_errors,
+    ) {
+        warn!("Error starting WebTransport session: {error}");
+        let _ = event_sender2.send(WebTransportNetworkEvent::SessionFailed);
+    }
+}
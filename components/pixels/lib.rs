@@ -4,12 +4,16 @@
 
 use std::borrow::Cow;
 use std::io::Cursor;
+use std::sync::LazyLock;
 use std::time::Duration;
 use std::{cmp, fmt, vec};
 
 use euclid::default::{Point2D, Rect, Size2D};
 use image::codecs::gif::GifDecoder;
-use image::{AnimationDecoder as _, ImageFormat};
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageDecoder, ImageFormat, ImageResult};
 use ipc_channel::ipc::IpcSharedMemory;
 use log::debug;
 use malloc_size_of_derive::MallocSizeOf;
@@ -107,6 +111,101 @@ pub fn clip(
         .filter(|rect| !rect.is_empty())
 }
 
+/// The color space that decoded images are converted to, which is the one that the rendering
+/// context presents its contents in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum ColorSpace {
+    Srgb,
+    DisplayP3,
+}
+
+/// Convert RGBA pixels that are in the color space described by `icc_profile`, or in sRGB if
+/// there is none or it is invalid, to `color_space`.
+pub fn rgba8_convert_to_color_space_inplace(
+    pixels: &mut [u8],
+    icc_profile: Option<&[u8]>,
+    color_space: ColorSpace,
+) {
+    let input =
+        icc_profile.and_then(|icc_profile| qcms::Profile::new_from_slice(icc_profile, false));
+    if input.is_none() && color_space == ColorSpace::Srgb {
+        return;
+    }
+    let input = input.unwrap_or_else(qcms::Profile::new_sRGB);
+    match color_space_transform(&input, color_space, qcms::DataType::RGBA8) {
+        Some(transform) => transform.apply(pixels),
+        None => debug!("Unsupported ICC profile, not converting image to {color_space:?}"),
+    }
+}
+
+/// Convert premultiplied BGRA pixels in sRGB, such as those that a 2D canvas renders, to
+/// `color_space`.
+pub fn bgra8_premultiplied_convert_to_color_space_inplace(
+    pixels: &mut [u8],
+    color_space: ColorSpace,
+) {
+    // The transform is only created once, since this is done every time a canvas changes.
+    static SRGB_TO_DISPLAY_P3: LazyLock<Option<qcms::Transform>> = LazyLock::new(|| {
+        color_space_transform(
+            &qcms::Profile::new_sRGB(),
+            ColorSpace::DisplayP3,
+            qcms::DataType::BGRA8,
+        )
+    });
+    let transform = match color_space {
+        ColorSpace::Srgb => return,
+        ColorSpace::DisplayP3 => SRGB_TO_DISPLAY_P3.as_ref(),
+    };
+    let Some(transform) = transform else {
+        return;
+    };
+    // Color transforms apply to unpremultiplied colors.
+    unmultiply_inplace::<false>(pixels);
+    transform.apply(pixels);
+    rgba8_premultiply_inplace(pixels);
+}
+
+fn color_space_transform(
+    input: &qcms::Profile,
+    color_space: ColorSpace,
+    data_type: qcms::DataType,
+) -> Option<qcms::Transform> {
+    let mut output = match color_space {
+        ColorSpace::Srgb => qcms::Profile::new_sRGB(),
+        ColorSpace::DisplayP3 => display_p3_profile()?,
+    };
+    output.precache_output_transform();
+    qcms::Transform::new(input, &output, data_type, qcms::Intent::Perceptual)
+}
+
+/// The Display P3 color space, which has the primaries of DCI-P3, the D65 white point and
+/// the transfer function of sRGB.
+///
+/// <https://www.w3.org/TR/css-color-4/#valdef-color-display-p3>
+fn display_p3_profile() -> Option<Box<qcms::Profile>> {
+    let srgb_transfer_table: Vec<u16> = (0..1024)
+        .map(|index| {
+            let value = index as f64 / 1023.;
+            let linear = if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            };
+            (linear * 65535.).round() as u16
+        })
+        .collect();
+    let chromaticity = |x, y| qcms::CIE_xyY { x, y, Y: 1. };
+    qcms::Profile::new_rgb_with_table(
+        chromaticity(0.3127, 0.3290),
+        qcms::CIE_xyYTRIPLE {
+            red: chromaticity(0.680, 0.320),
+            green: chromaticity(0.265, 0.690),
+            blue: chromaticity(0.150, 0.060),
+        },
+        &srgb_transfer_table,
+    )
+}
+
 /// Whether this response passed any CORS checks, and is thus safe to read from
 /// in cross-origin environments.
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
@@ -171,7 +270,12 @@ pub struct ImageMetadata {
 // FIXME: Images must not be copied every frame. Instead we should atomically
 // reference count them.
 
-pub fn load_from_memory(buffer: &[u8], cors_status: CorsStatus) -> Option<Image> {
+/// Decode an image, converting it to `color_space`.
+pub fn load_from_memory(
+    buffer: &[u8],
+    cors_status: CorsStatus,
+    color_space: ColorSpace,
+) -> Option<Image> {
     if buffer.is_empty() {
         return None;
     }
@@ -183,10 +287,18 @@ pub fn load_from_memory(buffer: &[u8], cors_status: CorsStatus) -> Option<Image>
             None
         },
         Ok(format) => match format {
-            ImageFormat::Gif => decode_gif(buffer, cors_status),
-            _ => match image::load_from_memory(buffer) {
-                Ok(image) => {
+            ImageFormat::Gif => decode_gif(buffer, cors_status, color_space),
+            ImageFormat::WebP if is_animated_webp(buffer) => {
+                decode_webp(buffer, cors_status, color_space)
+            },
+            _ => match decode_with_icc_profile(buffer, format) {
+                Ok((image, icc_profile)) => {
                     let mut rgba = image.into_rgba8();
+                    rgba8_convert_to_color_space_inplace(
+                        &mut rgba,
+                        icc_profile.as_deref(),
+                        color_space,
+                    );
                     rgba8_byte_swap_colors_inplace(&mut rgba);
                     let frame = ImageFrame {
                         delay: None,
@@ -212,6 +324,28 @@ pub fn load_from_memory(buffer: &[u8], cors_status: CorsStatus) -> Option<Image>
     }
 }
 
+/// Decode a still image, along with the ICC profile that describes its color space for the
+/// formats that can embed one.
+fn decode_with_icc_profile(
+    buffer: &[u8],
+    format: ImageFormat,
+) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+    fn decode<'a>(
+        mut decoder: impl ImageDecoder<'a>,
+    ) -> ImageResult<(DynamicImage, Option<Vec<u8>>)> {
+        let icc_profile = decoder.icc_profile();
+        Ok((DynamicImage::from_decoder(decoder)?, icc_profile))
+    }
+
+    let cursor = Cursor::new(buffer);
+    match format {
+        ImageFormat::Png => decode(PngDecoder::new(cursor)?),
+        ImageFormat::Jpeg => decode(JpegDecoder::new(cursor)?),
+        ImageFormat::WebP => decode(WebPDecoder::new(cursor)?),
+        _ => Ok((image::load_from_memory_with_format(buffer, format)?, None)),
+    }
+}
+
 // https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img
 pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
     if is_gif(buffer) {
@@ -226,11 +360,30 @@ pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
         Ok(ImageFormat::Bmp)
     } else if is_ico(buffer) {
         Ok(ImageFormat::Ico)
+    } else if cfg!(feature = "avif") && is_avif(buffer) {
+        Ok(ImageFormat::Avif)
     } else {
         Err("Image Format Not Supported")
     }
 }
 
+/// Whether images with the given MIME type essence, such as `image/png`, can be decoded
+/// or, for SVG, rasterized by the image cache.
+pub fn is_supported_image_mime_type(essence: &str) -> bool {
+    match essence.to_ascii_lowercase().as_str() {
+        "image/gif" |
+        "image/jpeg" |
+        "image/png" |
+        "image/webp" |
+        "image/bmp" |
+        "image/x-icon" |
+        "image/vnd.microsoft.icon" |
+        "image/svg+xml" => true,
+        "image/avif" => cfg!(feature = "avif"),
+        _ => false,
+    }
+}
+
 pub fn unmultiply_inplace<const SWAP_RB: bool>(pixels: &mut [u8]) {
     for rgba in pixels.chunks_mut(4) {
         let a = rgba[3] as u32;
@@ -291,26 +444,55 @@ fn is_webp(buffer: &[u8]) -> bool {
     buffer[8..].len() >= len && &buffer[8..12] == b"WEBP"
 }
 
-fn decode_gif(buffer: &[u8], cors_status: CorsStatus) -> Option<Image> {
+fn is_animated_webp(buffer: &[u8]) -> bool {
+    WebPDecoder::new(Cursor::new(buffer)).is_ok_and(|decoder| decoder.has_animation())
+}
+
+fn is_avif(buffer: &[u8]) -> bool {
+    // https://aomediacodec.github.io/av1-avif/#brands-overview
+    // An AVIF file starts with an ISOBMFF `ftyp` box, whose major brand is
+    // either `avif` for still images or `avis` for image sequences.
+    buffer.len() >= 12 && &buffer[4..8] == b"ftyp" && matches!(&buffer[8..12], b"avif" | b"avis")
+}
+
+fn decode_gif(buffer: &[u8], cors_status: CorsStatus, color_space: ColorSpace) -> Option<Image> {
     let Ok(decoded_gif) = GifDecoder::new(Cursor::new(buffer)) else {
         return None;
     };
+    decode_animated_image(decoded_gif, cors_status, color_space)
+}
+
+fn decode_webp(buffer: &[u8], cors_status: CorsStatus, color_space: ColorSpace) -> Option<Image> {
+    let Ok(decoded_webp) = WebPDecoder::new(Cursor::new(buffer)) else {
+        return None;
+    };
+    decode_animated_image(decoded_webp, cors_status, color_space)
+}
+
+/// Decode every frame of an animated image up front, so that no decoding has to
+/// happen on the thread that ends up presenting the animation.
+fn decode_animated_image<'a>(
+    decoder: impl AnimationDecoder<'a>,
+    cors_status: CorsStatus,
+    color_space: ColorSpace,
+) -> Option<Image> {
     let mut width = 0;
     let mut height = 0;
 
     // This uses `map_while`, because the first non-decodable frame seems to
     // send the frame iterator into an infinite loop. See
     // <https://github.com/image-rs/image/issues/2442>.
-    let frames: Vec<ImageFrame> = decoded_gif
+    let frames: Vec<ImageFrame> = decoder
         .into_frames()
         .map_while(|decoded_frame| {
             let mut frame = match decoded_frame {
                 Ok(decoded_frame) => decoded_frame,
                 Err(error) => {
-                    debug!("decode GIF frame error: {error}");
+                    debug!("decode animated image frame error: {error}");
                     return None;
                 },
             };
+            rgba8_convert_to_color_space_inplace(frame.buffer_mut(), None, color_space);
             rgba8_byte_swap_colors_inplace(frame.buffer_mut());
 
             let frame = ImageFrame {
@@ -344,7 +526,10 @@ fn decode_gif(buffer: &[u8], cors_status: CorsStatus) -> Option<Image> {
 
 #[cfg(test)]
 mod test {
-    use super::detect_image_format;
+    use super::{
+        ColorSpace, bgra8_premultiplied_convert_to_color_space_inplace, detect_image_format,
+        is_supported_image_mime_type,
+    };
 
     #[test]
     fn test_supported_images() {
@@ -357,6 +542,12 @@ mod test {
         ];
         let bmp = [0x42, 0x4D];
         let ico = [0x00, 0x00, 0x01, 0x00];
+        let avif = [
+            0x00, 0x00, 0x00, 0x1C, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f',
+        ];
+        let avis = [
+            0x00, 0x00, 0x00, 0x1C, b'f', b't', b'y', b'p', b'a', b'v', b'i', b's',
+        ];
         let junk_format = [0x01, 0x02, 0x03, 0x04, 0x05];
 
         assert!(detect_image_format(&gif1).is_ok());
@@ -366,6 +557,62 @@ mod test {
         assert!(detect_image_format(&webp).is_ok());
         assert!(detect_image_format(&bmp).is_ok());
         assert!(detect_image_format(&ico).is_ok());
+        assert_eq!(detect_image_format(&avif).is_ok(), cfg!(feature = "avif"));
+        assert_eq!(detect_image_format(&avis).is_ok(), cfg!(feature = "avif"));
         assert!(detect_image_format(&junk_format).is_err());
+
+        assert!(is_supported_image_mime_type("image/png"));
+        assert_eq!(
+            is_supported_image_mime_type("image/avif"),
+            cfg!(feature = "avif")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "avif")]
+    fn test_decode_avif() {
+        use super::{CorsStatus, PixelFormat, load_from_memory};
+
+        // A 2x2 opaque red image.
+        let avif = include_bytes!("tests/test.avif");
+        let image = load_from_memory(avif, CorsStatus::Safe, ColorSpace::Srgb)
+            .expect("AVIF image should decode");
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.format, PixelFormat::BGRA8);
+        assert!(!image.should_animate());
+
+        let bytes = image.bytes();
+        assert_eq!(bytes.len(), 2 * 2 * 4);
+        for bgra in bytes.chunks(4) {
+            assert!(bgra[0] < 8 && bgra[1] < 8 && bgra[2] > 247, "{bgra:?}");
+            assert_eq!(bgra[3], 255);
+        }
+    }
+
+    #[test]
+    fn test_convert_canvas_pixels_to_display_p3() {
+        // Opaque sRGB red, half transparent sRGB red and a transparent pixel.
+        let srgb = [0, 0, 255, 255, 0, 0, 128, 128, 0, 0, 0, 0];
+
+        let mut pixels = srgb;
+        bgra8_premultiplied_convert_to_color_space_inplace(&mut pixels, ColorSpace::Srgb);
+        assert_eq!(pixels, srgb);
+
+        // sRGB red is about (234, 51, 35) in Display P3.
+        let mut pixels = srgb;
+        bgra8_premultiplied_convert_to_color_space_inplace(&mut pixels, ColorSpace::DisplayP3);
+        let close = |value: u8, expected: u8| value.abs_diff(expected) <= 3;
+        assert!(
+            close(pixels[0], 35) && close(pixels[1], 51) && close(pixels[2], 234),
+            "{pixels:?}"
+        );
+        assert_eq!(pixels[3], 255);
+        // The colors of translucent pixels stay premultiplied.
+        assert!(
+            close(pixels[4], 17) && close(pixels[5], 25) && close(pixels[6], 117),
+            "{pixels:?}"
+        );
+        assert_eq!(pixels[7], 128);
+        assert_eq!(&pixels[8..], &[0, 0, 0, 0]);
     }
 }
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A module for writing time profiler events out in the Chrome trace event format, which can
+//! be loaded in Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing`.
+//!
+//! See <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU>.
+
+use std::collections::HashSet;
+use std::io::{self, BufWriter, Write};
+use std::{fs, path};
+
+use base::cross_process_instant::CrossProcessInstant;
+use profile_traits::time::{
+    ProfilerCategory, ProfilerThread, TimerMetadata, TimerMetadataFrameType,
+    TimerMetadataReflowType,
+};
+use serde_json::{Value, json};
+
+/// An RAII class for writing a Chrome trace file.
+#[derive(Debug)]
+pub struct ChromeTrace {
+    file: BufWriter<fs::File>,
+    /// Whether no event has been written yet, so the next one needs no separator.
+    empty: bool,
+    /// The threads, by process and thread identifier, whose name was written already.
+    named_threads: HashSet<(u32, u64)>,
+}
+
+impl ChromeTrace {
+    /// Create a new ChromeTrace and write the start of the trace file out to disk.
+    pub fn new<P>(trace_file_path: P) -> io::Result<ChromeTrace>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut file = BufWriter::new(fs::File::create(trace_file_path)?);
+        write!(file, "{{\"traceEvents\":[")?;
+        Ok(ChromeTrace {
+            file,
+            empty: true,
+            named_threads: HashSet::new(),
+        })
+    }
+
+    /// Write one complete event to the trace file, preceded by the name of its thread if
+    /// this is the first event of that thread.
+    pub fn write_one(
+        &mut self,
+        category: &(ProfilerCategory, Option<TimerMetadata>),
+        start_time: CrossProcessInstant,
+        end_time: CrossProcessInstant,
+        thread: &ProfilerThread,
+    ) {
+        if self
+            .named_threads
+            .insert((thread.process_id, thread.thread_id))
+        {
+            if let Some(name) = &thread.name {
+                self.write_event(json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": thread.process_id,
+                    "tid": thread.thread_id,
+                    "args": { "name": name },
+                }));
+            }
+        }
+
+        let args = match &category.1 {
+            Some(metadata) => json!({
+                "url": metadata.url,
+                "iframe": metadata.iframe == TimerMetadataFrameType::IFrame,
+                "incremental": metadata.incremental == TimerMetadataReflowType::Incremental,
+            }),
+            None => json!({}),
+        };
+        self.write_event(json!({
+            "name": category.0.variant_name(),
+            "cat": category.0.trace_category(),
+            "ph": "X",
+            "ts": microseconds(start_time - CrossProcessInstant::epoch()),
+            "dur": microseconds(end_time - start_time),
+            "pid": thread.process_id,
+            "tid": thread.thread_id,
+            "args": args,
+        }));
+    }
+
+    fn write_event(&mut self, event: Value) {
+        if !self.empty {
+            self.file.write_all(b",\n").unwrap();
+        }
+        self.empty = false;
+        serde_json::to_writer(&mut self.file, &event).unwrap();
+    }
+}
+
+impl Drop for ChromeTrace {
+    /// Write the end of the trace file out to disk on destruction.
+    fn drop(&mut self) {
+        writeln!(self.file, "]}}").unwrap();
+        self.file.flush().unwrap();
+    }
+}
+
+/// Trace event timestamps and durations are in microseconds.
+fn microseconds(duration: time::Duration) -> f64 {
+    duration.whole_nanoseconds() as f64 / 1000.
+}
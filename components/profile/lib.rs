@@ -4,6 +4,7 @@
 
 #![deny(unsafe_code)]
 
+pub mod chrome_trace;
 #[allow(unsafe_code)]
 pub mod mem;
 pub mod time;
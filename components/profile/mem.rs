@@ -150,6 +150,7 @@ mod system_reporter {
                         path,
                         kind: ReportKind::NonExplicitSize,
                         size,
+                        attribution: None,
                     });
                 }
             };
@@ -12,6 +12,7 @@ use std::path::Path;
 use std::thread;
 
 use ipc_channel::ipc::{self, IpcReceiver};
+use log::warn;
 use profile_traits::time::{
     ProfilerCategory, ProfilerChan, ProfilerData, ProfilerMsg, TimerMetadata,
     TimerMetadataFrameType, TimerMetadataReflowType,
@@ -19,6 +20,7 @@ use profile_traits::time::{
 use servo_config::opts::OutputOptions;
 use time::Duration;
 
+use crate::chrome_trace::ChromeTrace;
 use crate::trace_dump::TraceDump;
 
 pub trait Formattable {
@@ -99,75 +101,47 @@ pub struct Profiler {
     output: Option<OutputOptions>,
     pub last_msg: Option<ProfilerMsg>,
     trace: Option<TraceDump>,
+    chrome_trace: Option<ChromeTrace>,
     blocked_layout_queries: HashMap<String, u32>,
 }
 
 impl Profiler {
-    pub fn create(output: &Option<OutputOptions>, file_path: Option<String>) -> ProfilerChan {
+    pub fn create(
+        output: &Option<OutputOptions>,
+        file_path: Option<String>,
+        chrome_trace_path: Option<String>,
+    ) -> ProfilerChan {
         let (chan, port) = ipc::channel().unwrap();
-        match *output {
-            Some(ref option) => {
-                // Spawn the time profiler thread
-                let outputoption = option.clone();
-                thread::Builder::new()
-                    .name("TimeProfiler".to_owned())
-                    .spawn(move || {
-                        let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
-                        let mut profiler = Profiler::new(port, trace, Some(outputoption));
-                        profiler.start();
-                    })
-                    .expect("Thread spawning failed");
-                // decide if we need to spawn the timer thread
-                match *option {
-                    OutputOptions::FileName(_) => { /* no timer thread needed */ },
-                    OutputOptions::Stdout(period) => {
-                        // Spawn a timer thread
-                        let chan = chan.clone();
-                        thread::Builder::new()
-                            .name("TimeProfTimer".to_owned())
-                            .spawn(move || {
-                                loop {
-                                    thread::sleep(std::time::Duration::from_secs_f64(period));
-                                    if chan.send(ProfilerMsg::Print).is_err() {
-                                        break;
-                                    }
-                                }
-                            })
-                            .expect("Thread spawning failed");
-                    },
-                }
-            },
-            None => {
-                // this is when the -p option hasn't been specified
-                if file_path.is_some() {
-                    // Spawn the time profiler
-                    thread::Builder::new()
-                        .name("TimeProfiler".to_owned())
-                        .spawn(move || {
-                            let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
-                            let mut profiler = Profiler::new(port, trace, None);
-                            profiler.start();
-                        })
-                        .expect("Thread spawning failed");
-                } else {
-                    // No-op to handle messages when the time profiler is not printing:
-                    thread::Builder::new()
-                        .name("TimeProfiler".to_owned())
-                        .spawn(move || {
-                            loop {
-                                match port.recv() {
-                                    Err(_) => break,
-                                    Ok(ProfilerMsg::Exit(chan)) => {
-                                        let _ = chan.send(());
-                                        break;
-                                    },
-                                    _ => {},
-                                }
-                            }
-                        })
-                        .expect("Thread spawning failed");
-                }
-            },
+        // The profiler thread is spawned even when nothing is being measured, since the
+        // embedder can start recording a Chrome trace at any time.
+        let outputoption = output.clone();
+        thread::Builder::new()
+            .name("TimeProfiler".to_owned())
+            .spawn(move || {
+                let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
+                let chrome_trace = chrome_trace_path
+                    .as_ref()
+                    .and_then(|p| ChromeTrace::new(p).ok());
+                let mut profiler = Profiler::new(port, trace, chrome_trace, outputoption);
+                profiler.start();
+            })
+            .expect("Thread spawning failed");
+
+        // decide if we need to spawn the timer thread
+        if let Some(OutputOptions::Stdout(period)) = *output {
+            // Spawn a timer thread
+            let chan = chan.clone();
+            thread::Builder::new()
+                .name("TimeProfTimer".to_owned())
+                .spawn(move || {
+                    loop {
+                        thread::sleep(std::time::Duration::from_secs_f64(period));
+                        if chan.send(ProfilerMsg::Print).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .expect("Thread spawning failed");
         }
 
         ProfilerChan(chan)
@@ -176,6 +150,7 @@ impl Profiler {
     pub fn new(
         port: IpcReceiver<ProfilerMsg>,
         trace: Option<TraceDump>,
+        chrome_trace: Option<ChromeTrace>,
         output: Option<OutputOptions>,
     ) -> Profiler {
         Profiler {
@@ -184,6 +159,7 @@ impl Profiler {
             output,
             last_msg: None,
             trace,
+            chrome_trace,
             blocked_layout_queries: HashMap::new(),
         }
     }
@@ -202,11 +178,17 @@ impl Profiler {
 
     fn handle_msg(&mut self, msg: ProfilerMsg) -> bool {
         match msg.clone() {
-            ProfilerMsg::Time(category_and_metadata, (start_time, end_time)) => {
+            ProfilerMsg::Time(category_and_metadata, (start_time, end_time), thread) => {
                 if let Some(ref mut trace) = self.trace {
                     trace.write_one(&category_and_metadata, start_time, end_time);
                 }
-                self.find_or_insert(category_and_metadata, end_time - start_time);
+                if let Some(ref mut chrome_trace) = self.chrome_trace {
+                    chrome_trace.write_one(&category_and_metadata, start_time, end_time, &thread);
+                }
+                // The buckets are only needed to print statistics.
+                if self.output.is_some() {
+                    self.find_or_insert(category_and_metadata, end_time - start_time);
+                }
             },
             ProfilerMsg::Print => {
                 if let Some(ProfilerMsg::Time(..)) = self.last_msg {
@@ -226,8 +208,25 @@ impl Profiler {
             ProfilerMsg::BlockedLayoutQuery(url) => {
                 *self.blocked_layout_queries.entry(url).or_insert(0) += 1;
             },
+            ProfilerMsg::StartChromeTrace(path) => {
+                // Finish the previous recording before starting a new one, in case they
+                // share a file.
+                self.chrome_trace = None;
+                self.chrome_trace = ChromeTrace::new(&path)
+                    .inspect_err(|error| {
+                        warn!("Couldn't create trace file {}: {error}", path.display())
+                    })
+                    .ok();
+            },
+            ProfilerMsg::StopChromeTrace(chan) => {
+                self.chrome_trace = None;
+                let _ = chan.send(());
+            },
             ProfilerMsg::Exit(chan) => {
                 self.print_buckets();
+                // Finish writing the trace files before Servo is allowed to exit.
+                self.trace = None;
+                self.chrome_trace = None;
                 let _ = chan.send(());
                 return false;
             },
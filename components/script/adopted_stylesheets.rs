@@ -0,0 +1,450 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The observable array returned by `adoptedStyleSheets`: a proxy around a JS array whose
+//! changes are applied to the adopted stylesheets of its document or shadow root.
+//!
+//! <https://webidl.spec.whatwg.org/#es-observable-array>
+
+use std::ptr;
+use std::sync::LazyLock;
+
+use js::glue::{
+    CreateWrapperProxyHandler, GetProxyPrivate, GetProxyReservedSlot, NewProxyObject, ProxyTraps,
+    SetProxyReservedSlot,
+};
+use js::jsapi::{
+    Handle as RawHandle, HandleId as RawHandleId, HandleObject as RawHandleObject,
+    HandleValue as RawHandleValue, HandleValueArray, Heap, JS_DefinePropertyById,
+    JS_DeletePropertyById, JS_ForwardSetPropertyTo, JSCLASS_DELAY_METADATA_BUILDER,
+    JSCLASS_IS_PROXY, JSCLASS_RESERVED_SLOTS_MASK, JSCLASS_RESERVED_SLOTS_SHIFT, JSClass,
+    JSClass_NON_NATIVE, JSContext, JSErrNum, JSObject, NewArrayObject, ObjectOpResult,
+    PropertyDescriptor, ProxyClassExtension, ProxyClassOps, ProxyObjectOps,
+};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{GetArrayLength, JS_GetElement, JS_SetElement, SetArrayLength};
+use js::rust::{Handle, HandleObject, HandleValue, MutableHandleValue};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::conversions::{jsid_to_string, root_from_handlevalue, root_from_object};
+use crate::dom::bindings::error::{Error, throw_dom_exception};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::proxyhandler::is_accessor_descriptor;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::utils::get_array_index_from_id;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::document::Document;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::shadowroot::ShadowRoot;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+
+/// The cached observable array of the adopted stylesheets of a document or shadow root.
+#[derive(JSTraceable)]
+pub(crate) struct AdoptedStyleSheetArray {
+    proxy: DomRefCell<Option<Heap<*mut JSObject>>>,
+}
+
+impl AdoptedStyleSheetArray {
+    pub(crate) fn new() -> AdoptedStyleSheetArray {
+        AdoptedStyleSheetArray {
+            proxy: DomRefCell::new(None),
+        }
+    }
+
+    /// Return the observable array of `owner`, which is a document or a shadow root, creating
+    /// it with the stylesheets given by `f` the first time.
+    #[allow(unsafe_code)]
+    pub(crate) fn get_or_init<F: FnOnce() -> Vec<DomRoot<CSSStyleSheet>>, T: DomObject>(
+        &self,
+        owner: &T,
+        f: F,
+        cx: SafeJSContext,
+        mut retval: MutableHandleValue,
+    ) {
+        if let Some(proxy) = &*self.proxy.borrow() {
+            retval.set(ObjectValue(proxy.get()));
+            return;
+        }
+
+        rooted!(in(*cx) let target = unsafe { NewArrayObject(*cx, &HandleValueArray::empty()) });
+        assert!(!target.is_null());
+        set_elements(cx, target.handle(), &f());
+
+        rooted!(in(*cx) let target_value = ObjectValue(target.get()));
+        rooted!(in(*cx) let proxy = unsafe {
+            NewProxyObject(
+                *cx,
+                HANDLER.0,
+                target_value.handle().into(),
+                ptr::null_mut(),
+                &CLASS,
+                true,
+            )
+        });
+        assert!(!proxy.is_null());
+        unsafe {
+            SetProxyReservedSlot(
+                proxy.get(),
+                0,
+                &ObjectValue(owner.reflector().get_jsobject().get()),
+            );
+        }
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.proxy.borrow_mut() = Some(Heap::default());
+        self.proxy.borrow().as_ref().unwrap().set(proxy.get());
+        retval.set(ObjectValue(proxy.get()));
+    }
+
+    /// Replace the contents of the observable array, if it was created, after its owner was
+    /// given new adopted stylesheets.
+    #[allow(unsafe_code)]
+    pub(crate) fn replace(&self, cx: SafeJSContext, sheets: &[DomRoot<CSSStyleSheet>]) {
+        let Some(proxy) = self.proxy.borrow().as_ref().map(Heap::get) else {
+            return;
+        };
+        rooted!(in(*cx) let target = unsafe { proxy_target(proxy) });
+        unsafe {
+            assert!(SetArrayLength(*cx, target.handle(), 0));
+        }
+        set_elements(cx, target.handle(), sheets);
+    }
+}
+
+#[allow(unsafe_code)]
+fn set_elements(cx: SafeJSContext, target: HandleObject, sheets: &[DomRoot<CSSStyleSheet>]) {
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    for (index, sheet) in sheets.iter().enumerate() {
+        value.set(ObjectValue(sheet.reflector().get_jsobject().get()));
+        unsafe {
+            assert!(JS_SetElement(*cx, target, index as u32, value.handle()));
+        }
+    }
+}
+
+/// The document or shadow root that an observable array belongs to.
+enum Owner {
+    Document(DomRoot<Document>),
+    ShadowRoot(DomRoot<ShadowRoot>),
+}
+
+impl Owner {
+    #[allow(unsafe_code)]
+    unsafe fn from_proxy(cx: *mut JSContext, proxy: *mut JSObject) -> Owner {
+        let mut slot = UndefinedValue();
+        unsafe { GetProxyReservedSlot(proxy, 0, &mut slot) };
+        let object = slot.to_object();
+        if let Ok(document) = unsafe { root_from_object::<Document>(object, cx) } {
+            return Owner::Document(document);
+        }
+        Owner::ShadowRoot(
+            unsafe { root_from_object::<ShadowRoot>(object, cx) }
+                .expect("Adopted stylesheets belong to a document or a shadow root"),
+        )
+    }
+
+    fn document(&self) -> DomRoot<Document> {
+        match self {
+            Owner::Document(document) => document.clone(),
+            Owner::ShadowRoot(shadow_root) => shadow_root.upcast::<Node>().owner_doc(),
+        }
+    }
+
+    fn set_adopted_stylesheets(&self, sheets: Vec<DomRoot<CSSStyleSheet>>) {
+        match self {
+            Owner::Document(document) => document.set_adopted_stylesheets(sheets),
+            Owner::ShadowRoot(shadow_root) => shadow_root.set_adopted_stylesheets(sheets),
+        }
+    }
+}
+
+/// The properties of the observable array that are backed by the adopted stylesheets.
+enum Key {
+    Index(u32),
+    Length,
+}
+
+#[allow(unsafe_code)]
+unsafe fn key_from_id(cx: *mut JSContext, id: Handle<js::jsapi::jsid>) -> Option<Key> {
+    if let Some(index) = get_array_index_from_id(id) {
+        return Some(Key::Index(index));
+    }
+    if id.is_string() && unsafe { jsid_to_string(cx, id) }.is_some_and(|name| &*name == "length") {
+        return Some(Key::Length);
+    }
+    None
+}
+
+#[allow(unsafe_code)]
+unsafe fn proxy_target(proxy: *mut JSObject) -> *mut JSObject {
+    let mut slot = UndefinedValue();
+    unsafe { GetProxyPrivate(proxy, &mut slot) };
+    slot.to_object()
+}
+
+/// Apply the contents of the array of `proxy` to the adopted stylesheets of its owner.
+#[allow(unsafe_code)]
+unsafe fn apply(cx: *mut JSContext, proxy: *mut JSObject, target: HandleObject) -> bool {
+    let mut length = 0;
+    if !unsafe { GetArrayLength(cx, target, &mut length) } {
+        return false;
+    }
+    let mut sheets = Vec::with_capacity(length as usize);
+    rooted!(in(cx) let mut value = UndefinedValue());
+    for index in 0..length {
+        if !unsafe { JS_GetElement(cx, target, index, value.handle_mut()) } {
+            return false;
+        }
+        // Every element was checked to be a stylesheet when it was set.
+        sheets.push(
+            unsafe { root_from_handlevalue::<CSSStyleSheet>(value.handle(), cx) }
+                .expect("Adopted stylesheets are CSSStyleSheets"),
+        );
+    }
+    unsafe { Owner::from_proxy(cx, proxy) }.set_adopted_stylesheets(sheets);
+    true
+}
+
+/// Set `key` of the array of `proxy` to `value`, following "set the indexed value" and
+/// "set the length".
+///
+/// <https://webidl.spec.whatwg.org/#observable-array-exotic-object-set-the-indexed-value>
+/// <https://webidl.spec.whatwg.org/#observable-array-exotic-object-set-the-length>
+#[allow(unsafe_code)]
+unsafe fn set_value(
+    cx: *mut JSContext,
+    proxy: *mut JSObject,
+    key: Key,
+    value: HandleValue,
+    result: *mut ObjectOpResult,
+) -> bool {
+    rooted!(in(cx) let target = unsafe { proxy_target(proxy) });
+    let mut length = 0;
+    if !unsafe { GetArrayLength(cx, target.handle(), &mut length) } {
+        return false;
+    }
+    match key {
+        Key::Index(index) => {
+            if index > length {
+                unsafe { (*result).code_ = JSErrNum::JSMSG_BAD_INDEX as usize };
+                return true;
+            }
+            let safe_cx = unsafe { SafeJSContext::from_ptr(cx) };
+            let global = unsafe { GlobalScope::from_object(proxy) };
+            let Ok(sheet) = (unsafe { root_from_handlevalue::<CSSStyleSheet>(value, cx) }) else {
+                throw_dom_exception(
+                    safe_cx,
+                    &global,
+                    Error::Type("Value is not a CSSStyleSheet".to_owned()),
+                    CanGc::note(),
+                );
+                return false;
+            };
+            let document = unsafe { Owner::from_proxy(cx, proxy) }.document();
+            if !sheet.is_constructed_in(&document) {
+                throw_dom_exception(safe_cx, &global, Error::NotAllowed, CanGc::note());
+                return false;
+            }
+            if !unsafe { JS_SetElement(cx, target.handle(), index, value) } {
+                return false;
+            }
+        },
+        Key::Length => {
+            let new_length = if value.is_int32() && value.to_int32() >= 0 {
+                value.to_int32() as u32
+            } else if value.is_double() && value.to_double() as u32 as f64 == value.to_double() {
+                value.to_double() as u32
+            } else {
+                unsafe { (*result).code_ = JSErrNum::JSMSG_BAD_ARRAY_LENGTH as usize };
+                return true;
+            };
+            // Stylesheets can be removed by shortening the array, but not added by lengthening
+            // it, as that would leave holes.
+            if new_length > length {
+                unsafe { (*result).code_ = JSErrNum::JSMSG_BAD_ARRAY_LENGTH as usize };
+                return true;
+            }
+            if !unsafe { SetArrayLength(cx, target.handle(), new_length) } {
+                return false;
+            }
+        },
+    }
+    if !unsafe { apply(cx, proxy, target.handle()) } {
+        return false;
+    }
+    unsafe {
+        (*result).code_ = 0 /* OkCode */
+    };
+    true
+}
+
+static HANDLER: LazyLock<SyncWrapper> = LazyLock::new(|| {
+    let traps = ProxyTraps {
+        enter: None,
+        getOwnPropertyDescriptor: None,
+        defineProperty: Some(define_property),
+        ownPropertyKeys: None,
+        delete_: Some(delete),
+        enumerate: None,
+        getPrototypeIfOrdinary: None,
+        getPrototype: None,
+        setPrototype: None,
+        setImmutablePrototype: None,
+        preventExtensions: Some(prevent_extensions),
+        isExtensible: None,
+        has: None,
+        get: None,
+        set: Some(set),
+        call: None,
+        construct: None,
+        hasOwn: None,
+        getOwnEnumerablePropertyKeys: None,
+        nativeCall: None,
+        objectClassIs: None,
+        className: None,
+        fun_toString: None,
+        boxedValue_unbox: None,
+        defaultValue: None,
+        trace: None,
+        finalize: None,
+        objectMoved: None,
+        isCallable: None,
+        isConstructor: None,
+    };
+
+    // The traps that are not given here are forwarded to the array.
+    #[allow(unsafe_code)]
+    unsafe {
+        SyncWrapper(CreateWrapperProxyHandler(&traps))
+    }
+});
+
+struct SyncWrapper(*const libc::c_void);
+#[allow(unsafe_code)]
+unsafe impl Sync for SyncWrapper {}
+#[allow(unsafe_code)]
+unsafe impl Send for SyncWrapper {}
+
+/// <https://webidl.spec.whatwg.org/#es-observable-array-defineProperty>
+#[allow(unsafe_code)]
+unsafe extern "C" fn define_property(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    desc: RawHandle<PropertyDescriptor>,
+    result: *mut ObjectOpResult,
+) -> bool {
+    let Some(key) = (unsafe { key_from_id(cx, Handle::from_raw(id)) }) else {
+        rooted!(in(cx) let target = unsafe { proxy_target(proxy.get()) });
+        return unsafe { JS_DefinePropertyById(cx, target.handle().into(), id, desc, result) };
+    };
+    let desc = unsafe { &*desc.ptr };
+    let attributes_match = match key {
+        Key::Index(_) => {
+            !(desc.hasConfigurable_() && !desc.configurable_()) &&
+                !(desc.hasEnumerable_() && !desc.enumerable_()) &&
+                !(desc.hasWritable_() && !desc.writable_())
+        },
+        Key::Length => {
+            !(desc.hasConfigurable_() && desc.configurable_()) &&
+                !(desc.hasEnumerable_() && desc.enumerable_()) &&
+                !(desc.hasWritable_() && !desc.writable_())
+        },
+    };
+    if is_accessor_descriptor(desc) || !attributes_match {
+        unsafe { (*result).code_ = JSErrNum::JSMSG_CANT_REDEFINE_PROP as usize };
+        return true;
+    }
+    if !desc.hasValue_() {
+        unsafe {
+            (*result).code_ = 0 /* OkCode */
+        };
+        return true;
+    }
+    rooted!(in(cx) let value = desc.value_);
+    unsafe { set_value(cx, proxy.get(), key, value.handle(), result) }
+}
+
+/// <https://webidl.spec.whatwg.org/#es-observable-array-deleteProperty>
+#[allow(unsafe_code)]
+unsafe extern "C" fn delete(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    result: *mut ObjectOpResult,
+) -> bool {
+    rooted!(in(cx) let target = unsafe { proxy_target(proxy.get()) });
+    let index = match unsafe { key_from_id(cx, Handle::from_raw(id)) } {
+        Some(Key::Index(index)) => index,
+        Some(Key::Length) => {
+            unsafe { (*result).code_ = JSErrNum::JSMSG_CANT_DELETE as usize };
+            return true;
+        },
+        None => return unsafe { JS_DeletePropertyById(cx, target.handle().into(), id, result) },
+    };
+
+    // Only the last stylesheet can be deleted, so that the array never has holes.
+    let mut length = 0;
+    if !unsafe { GetArrayLength(cx, target.handle(), &mut length) } {
+        return false;
+    }
+    if length == 0 || index != length - 1 {
+        unsafe { (*result).code_ = JSErrNum::JSMSG_CANT_DELETE as usize };
+        return true;
+    }
+    if !unsafe { SetArrayLength(cx, target.handle(), index) } ||
+        !unsafe { apply(cx, proxy.get(), target.handle()) }
+    {
+        return false;
+    }
+    unsafe {
+        (*result).code_ = 0 /* OkCode */
+    };
+    true
+}
+
+/// <https://webidl.spec.whatwg.org/#es-observable-array-set>
+#[allow(unsafe_code)]
+unsafe extern "C" fn set(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    value: RawHandleValue,
+    receiver: RawHandleValue,
+    result: *mut ObjectOpResult,
+) -> bool {
+    if let Some(key) = unsafe { key_from_id(cx, Handle::from_raw(id)) } {
+        return unsafe { set_value(cx, proxy.get(), key, HandleValue::from_raw(value), result) };
+    }
+    rooted!(in(cx) let target = unsafe { proxy_target(proxy.get()) });
+    unsafe { JS_ForwardSetPropertyTo(cx, target.handle().into(), id, value, receiver, result) }
+}
+
+/// <https://webidl.spec.whatwg.org/#es-observable-array-preventExtensions>
+#[allow(unsafe_code)]
+unsafe extern "C" fn prevent_extensions(
+    _cx: *mut JSContext,
+    _proxy: RawHandleObject,
+    result: *mut ObjectOpResult,
+) -> bool {
+    unsafe {
+        (*result).code_ = JSErrNum::JSMSG_CANT_PREVENT_EXTENSIONS as usize;
+    }
+    true
+}
+
+#[allow(unsafe_code)]
+static CLASS: JSClass = JSClass {
+    name: c"Array".as_ptr(),
+    flags: JSClass_NON_NATIVE |
+        JSCLASS_IS_PROXY |
+        JSCLASS_DELAY_METADATA_BUILDER |
+        ((1 & JSCLASS_RESERVED_SLOTS_MASK) << JSCLASS_RESERVED_SLOTS_SHIFT), /* JSCLASS_HAS_RESERVED_SLOTS(1) */
+    cOps: unsafe { &ProxyClassOps },
+    spec: ptr::null(),
+    ext: unsafe { &ProxyClassExtension },
+    oOps: unsafe { &ProxyObjectOps },
+};
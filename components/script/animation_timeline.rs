@@ -16,6 +16,12 @@ use jstraceable_derive::JSTraceable;
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
 pub(crate) struct AnimationTimeline {
     current_value: f64,
+    /// The number of seconds that the timeline is ahead of the clock time, which changes
+    /// whenever a paused timeline is resumed.
+    offset: f64,
+    /// Whether the timeline is paused, so that its value only changes when it is advanced or
+    /// set explicitly.
+    paused: bool,
 }
 
 impl AnimationTimeline {
@@ -23,17 +29,20 @@ impl AnimationTimeline {
     #[inline]
     pub(crate) fn new() -> Self {
         Self {
-            current_value: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs_f64(),
+            current_value: Self::clock_time(),
+            offset: 0.,
+            paused: false,
         }
     }
 
-    /// Creates a new "test mode" timeline, with initial time 0.
+    /// Creates a new "test mode" timeline, with initial time 0, which is paused.
     #[inline]
     pub(crate) fn new_for_testing() -> Self {
-        Self { current_value: 0. }
+        Self {
+            current_value: 0.,
+            offset: 0.,
+            paused: true,
+        }
     }
 
     /// Returns the current value of the timeline in seconds.
@@ -41,12 +50,40 @@ impl AnimationTimeline {
         self.current_value
     }
 
-    /// Updates the value of the `AnimationTimeline` to the current clock time.
+    /// Updates the value of the `AnimationTimeline` to the current clock time, unless it is
+    /// paused.
     pub(crate) fn update(&mut self) {
-        self.current_value = SystemTime::now()
+        if self.paused {
+            return;
+        }
+        self.current_value = Self::clock_time() + self.offset;
+    }
+
+    /// Stops the timeline, so that its value no longer follows the clock time.
+    pub(crate) fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lets the timeline follow the clock time again, from the value that it has now.
+    pub(crate) fn resume(&mut self) {
+        if !self.paused {
+            return;
+        }
+        self.paused = false;
+        self.offset = self.current_value - Self::clock_time();
+    }
+
+    /// Sets the current value of the timeline, in seconds.
+    pub(crate) fn set_current_value(&mut self, value: f64) {
+        self.current_value = value;
+    }
+
+    /// The current clock time in seconds.
+    fn clock_time() -> f64 {
+        SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
-            .as_secs_f64();
+            .as_secs_f64()
     }
 
     /// Increments the current value of the timeline by a specific number of seconds.
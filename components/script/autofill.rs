@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Form autofill, with which the embedder fills in the form controls of the top-level document
+//! of a `WebView`, for instance with the credentials stored by a password manager.
+//!
+//! The embedder is told about the form controls that can be filled in when it asks for them
+//! and whenever one of them is focused. Each of them is identified by the pipeline of its
+//! document and an index that the document keeps for it while it is connected, which the
+//! embedder uses to fill them in later.
+//!
+//! <https://html.spec.whatwg.org/multipage/#autofill>
+
+use embedder_traits::{AutofillField, AutofillFieldId, AutofillRequest, EmbedderMsg};
+use euclid::{Point2D, Rect, Size2D};
+use html5ever::{local_name, namespace_url, ns};
+use script_bindings::str::DOMString;
+use webrender_api::units::DeviceIntRect;
+
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLSelectElementBinding::HTMLSelectElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlformelement::{FormControlElementHelpers, HTMLFormElement};
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmlselectelement::HTMLSelectElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::script_runtime::CanGc;
+
+/// Answer a request of the embedder to find or fill in the form controls of `document`.
+pub(crate) fn handle_autofill_request(
+    document: &Document,
+    request: AutofillRequest,
+    can_gc: CanGc,
+) {
+    prune_autofill_fields(document);
+    match request {
+        AutofillRequest::GetFields => {
+            let fields = document
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<Element>)
+                .filter(|element| is_autofillable(element))
+                .map(|element| autofill_field(document, &element, can_gc))
+                .collect();
+            let window = document.window();
+            window.send_to_embedder(EmbedderMsg::AutofillFields(window.webview_id(), fields));
+        },
+        AutofillRequest::Fill(values) => {
+            let pipeline_id = document.window().pipeline_id();
+            for (id, value) in values {
+                // The document that the embedder saw may have been replaced since.
+                if id.pipeline_id != pipeline_id {
+                    warn!("Ignoring autofill of a form control of another document");
+                    continue;
+                }
+                let element = document
+                    .autofill_fields()
+                    .borrow()
+                    .get(&id.index)
+                    .map(|element| DomRoot::from_ref(&**element));
+                if let Some(element) = element.filter(|element| is_autofillable(element)) {
+                    fill(&element, value, can_gc);
+                }
+            }
+        },
+    }
+}
+
+/// Tell the embedder that `element` was focused, if it is a form control of the top-level
+/// document that can be filled in.
+pub(crate) fn notify_autofill_field_focused(document: &Document, element: &Element, can_gc: CanGc) {
+    let window = document.window();
+    if !window.is_top_level() || !is_autofillable(element) {
+        return;
+    }
+    prune_autofill_fields(document);
+    let field = autofill_field(document, element, can_gc);
+    window.send_to_embedder(EmbedderMsg::AutofillFieldFocused(
+        window.webview_id(),
+        field,
+    ));
+}
+
+/// Whether `element` is a form control that is connected to its document and whose value the
+/// user can edit, so that the embedder can fill it in.
+fn is_autofillable(element: &Element) -> bool {
+    if !element.upcast::<Node>().is_connected() {
+        return false;
+    }
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        input.input_type().is_autofillable() && element.read_write_state()
+    } else if element.is::<HTMLTextAreaElement>() {
+        element.read_write_state()
+    } else if element.is::<HTMLSelectElement>() {
+        !element.is_actually_disabled()
+    } else {
+        false
+    }
+}
+
+/// Forget the form controls that were reported to the embedder but are no longer connected to
+/// `document`, so that they can be collected. They get a new identifier if they come back.
+fn prune_autofill_fields(document: &Document) {
+    document
+        .autofill_fields()
+        .borrow_mut()
+        .0
+        .retain(|_, element| element.upcast::<Node>().is_connected());
+}
+
+/// The identifier of `element` for the embedder, which is added to the form controls of
+/// `document` that were reported to the embedder if it wasn't already.
+fn autofill_field_id(document: &Document, element: &Element) -> AutofillFieldId {
+    let mut fields = document.autofill_fields().borrow_mut();
+    let index = fields
+        .iter()
+        .find(|(_, field)| ***field == *element)
+        .map(|(index, _)| *index)
+        .unwrap_or_else(|| {
+            let index = document.next_autofill_field_index();
+            fields.insert(index, Dom::from_ref(element));
+            index
+        });
+    AutofillFieldId {
+        pipeline_id: document.window().pipeline_id(),
+        index,
+    }
+}
+
+/// Describe the form control `element` of `document` for the embedder.
+fn autofill_field(document: &Document, element: &Element, can_gc: CanGc) -> AutofillField {
+    let (control_type, value) = if let Some(input) = element.downcast::<HTMLInputElement>() {
+        (input.Type(), input.Value())
+    } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+        (textarea.Type(), textarea.Value())
+    } else if let Some(select) = element.downcast::<HTMLSelectElement>() {
+        (select.Type(), select.Value())
+    } else {
+        unreachable!("Only form controls can be filled in");
+    };
+
+    let form = element
+        .as_maybe_form_control()
+        .and_then(|control| control.form_owner())
+        .and_then(|owner| {
+            document
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<HTMLFormElement>)
+                .position(|form| form == owner)
+        });
+
+    let label = element
+        .downcast::<HTMLElement>()
+        .and_then(|element| element.label_at(0))
+        .and_then(|label| label.GetTextContent())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    let attribute = |name| {
+        element
+            .get_attribute(&ns!(), name)
+            .map(|attribute| String::from(attribute.value().as_ref()))
+    };
+
+    let rect = element
+        .upcast::<Node>()
+        .bounding_content_box_or_zero(can_gc);
+    let rect = DeviceIntRect::from_untyped(
+        &Rect::new(
+            Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+            Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+        )
+        .to_box2d(),
+    );
+
+    AutofillField {
+        id: autofill_field_id(document, element),
+        form,
+        control_type: control_type.into(),
+        name: attribute(&local_name!("name")).unwrap_or_default(),
+        element_id: attribute(&local_name!("id")).unwrap_or_default(),
+        autocomplete: attribute(&local_name!("autocomplete"))
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect(),
+        label,
+        placeholder: attribute(&local_name!("placeholder")),
+        value: value.into(),
+        rect,
+    }
+}
+
+/// Set the value of the form control `element` as if the user had entered it, firing `input`
+/// and `change` events if it changed.
+fn fill(element: &Element, value: String, can_gc: CanGc) {
+    if let Some(select) = element.downcast::<HTMLSelectElement>() {
+        // Selecting an option fires the events itself.
+        select.select_value_from_user(&value, can_gc);
+        return;
+    }
+
+    let value = DOMString::from(value);
+    let changed = if let Some(input) = element.downcast::<HTMLInputElement>() {
+        let changed = input.Value() != value;
+        let _ = input.SetValue(value, can_gc);
+        changed
+    } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+        let changed = textarea.Value() != value;
+        textarea.SetValue(value);
+        changed
+    } else {
+        false
+    };
+
+    if changed {
+        let target = element.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"), can_gc);
+        target.fire_bubbling_event(atom!("change"), can_gc);
+    }
+}
@@ -0,0 +1,254 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Caret browsing, where the user moves a caret through the text of a document with the
+//! arrow keys, and selects text by moving the caret with the shift key held.
+//!
+//! The caret is the focus of the selection of the document. Moving it collapses the
+//! selection, unless the shift key is held, in which case the selection is extended to it.
+//! Layout paints the caret and the selected text, and finds where the caret moves to on the
+//! line above or below it.
+
+use std::iter::once;
+
+use fxhash::FxHashMap;
+use keyboard_types::{Key, KeyState, KeyboardEvent, Modifiers};
+use script_layout_interface::{CaretBrowsingSelection, CaretLineDirection};
+use servo_config::pref;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::text::Text;
+use crate::script_runtime::CanGc;
+
+/// The text of `node`, if it is a text node.
+pub(crate) fn text_of(node: &Node) -> Option<String> {
+    if !node.is::<Text>() {
+        return None;
+    }
+    Some(node.downcast::<CharacterData>()?.data().to_string())
+}
+
+/// Convert an offset in UTF-16 code units, as used by the selection, to a byte offset.
+pub(crate) fn utf16_offset_to_utf8(text: &str, offset: u32) -> usize {
+    let mut utf16_offset = 0;
+    text.char_indices()
+        .find(|(_, character)| {
+            utf16_offset += character.len_utf16() as u32;
+            utf16_offset > offset
+        })
+        .map_or(text.len(), |(index, _)| index)
+}
+
+/// Convert a byte offset to an offset in UTF-16 code units, as used by the selection.
+pub(crate) fn utf8_offset_to_utf16(text: &str, offset: usize) -> u32 {
+    text[..offset].encode_utf16().count() as u32
+}
+
+/// The caret and the selected text of `document` for layout to paint, if caret browsing
+/// is enabled.
+pub(crate) fn caret_browsing_selection(document: &Document) -> Option<CaretBrowsingSelection> {
+    if !pref!(accessibility_caret_browsing_enabled) {
+        return None;
+    }
+    let mut caret_browsing_selection = CaretBrowsingSelection::default();
+    let Some(selection) = document.selection() else {
+        return Some(caret_browsing_selection);
+    };
+
+    caret_browsing_selection.caret = selection.GetFocusNode().and_then(|node| {
+        let text = text_of(&node)?;
+        Some((
+            node.to_opaque(),
+            utf16_offset_to_utf8(&text, selection.FocusOffset()),
+        ))
+    });
+
+    let Ok(range) = selection.GetRangeAt(0) else {
+        return Some(caret_browsing_selection);
+    };
+    if range.collapsed() {
+        return Some(caret_browsing_selection);
+    }
+    let start = range.start_container();
+    let end = range.end_container();
+    let mut selected_text = FxHashMap::default();
+    for node in once(start.clone()).chain(start.following_nodes(document.upcast())) {
+        if node != start && !range.contains(&node) && !range.partially_contains(&node) {
+            break;
+        }
+        let Some(text) = text_of(&node) else {
+            continue;
+        };
+        let start_offset = if node == start {
+            utf16_offset_to_utf8(&text, range.start_offset())
+        } else {
+            0
+        };
+        let end_offset = if node == end {
+            utf16_offset_to_utf8(&text, range.end_offset())
+        } else {
+            text.len()
+        };
+        if start_offset < end_offset {
+            selected_text.insert(node.to_opaque(), start_offset..end_offset);
+        }
+    }
+    caret_browsing_selection.selected_text = selected_text;
+    Some(caret_browsing_selection)
+}
+
+/// The byte offset in `text` that a caret at `offset` moves to when moved by one character
+/// in the given direction, if it does not leave the text. A run of white space counts as a
+/// single character, as it is usually collapsed when rendered.
+fn adjacent_offset(text: &str, offset: usize, forward: bool) -> Option<usize> {
+    let is_whitespace = |grapheme: &&str| grapheme.chars().all(char::is_whitespace);
+    if forward {
+        let mut graphemes = text[offset..].graphemes(true);
+        let first = graphemes.next()?;
+        let mut length = first.len();
+        if is_whitespace(&first) {
+            length += graphemes
+                .take_while(is_whitespace)
+                .map(str::len)
+                .sum::<usize>();
+        }
+        Some(offset + length)
+    } else {
+        let mut graphemes = text[..offset].graphemes(true).rev();
+        let first = graphemes.next()?;
+        let mut length = first.len();
+        if is_whitespace(&first) {
+            length += graphemes
+                .take_while(is_whitespace)
+                .map(str::len)
+                .sum::<usize>();
+        }
+        Some(offset - length)
+    }
+}
+
+/// The position that a caret at the byte offset `offset` of the text of `node` moves to when
+/// moved by one character in the given direction, crossing into the next or previous
+/// rendered text node at the edges of the text.
+fn adjacent_position(
+    document: &Document,
+    node: &Node,
+    offset: usize,
+    forward: bool,
+    can_gc: CanGc,
+) -> Option<(DomRoot<Node>, usize)> {
+    let text = text_of(node)?;
+    if let Some(offset) = adjacent_offset(&text, offset, forward) {
+        return Some((DomRoot::from_ref(node), offset));
+    }
+
+    // Text nodes that are not rendered, like those of hidden elements or those that only
+    // contain collapsed white space, have no caret position.
+    let root = document.upcast::<Node>();
+    let candidates: Box<dyn Iterator<Item = DomRoot<Node>>> = if forward {
+        Box::new(node.following_nodes(root))
+    } else {
+        Box::new(node.preceding_nodes(root))
+    };
+    candidates.find_map(|candidate| {
+        let text = text_of(&candidate)?;
+        let edge = if forward { 0 } else { text.len() };
+        document
+            .window()
+            .caret_rect_query(&candidate, edge, can_gc)?;
+        let offset = adjacent_offset(&text, edge, forward).unwrap_or(edge);
+        Some((candidate, offset))
+    })
+}
+
+/// The position of the start of the first rendered text of `document`, where the caret is
+/// placed when there is none yet.
+fn initial_position(document: &Document, can_gc: CanGc) -> Option<(DomRoot<Node>, usize)> {
+    let root = document.upcast::<Node>();
+    root.following_nodes(root).find_map(|node| {
+        text_of(&node)?;
+        document.window().caret_rect_query(&node, 0, can_gc)?;
+        Some((node, 0))
+    })
+}
+
+/// Move the caret of `document` in response to `event`, if caret browsing is enabled and it
+/// is an arrow key that is not handled by a focused text control. Returns whether the key
+/// moved the caret.
+pub(crate) fn handle_key(document: &Document, event: &KeyboardEvent, can_gc: CanGc) -> bool {
+    if !pref!(accessibility_caret_browsing_enabled) ||
+        event.state != KeyState::Down ||
+        event
+            .modifiers
+            .intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::META)
+    {
+        return false;
+    }
+    if document
+        .get_focused_element()
+        .is_some_and(|element| element.input_method_type().is_some())
+    {
+        return false;
+    }
+    if !matches!(
+        event.key,
+        Key::ArrowLeft | Key::ArrowRight | Key::ArrowUp | Key::ArrowDown
+    ) {
+        return false;
+    }
+    let Some(selection) = document.GetSelection() else {
+        return false;
+    };
+
+    let caret = selection.GetFocusNode().and_then(|node| {
+        let text = text_of(&node)?;
+        let offset = utf16_offset_to_utf8(&text, selection.FocusOffset());
+        Some((node, offset))
+    });
+    let window = document.window();
+    let new_caret = match caret {
+        None => initial_position(document, can_gc),
+        Some((node, offset)) => match event.key {
+            Key::ArrowLeft => adjacent_position(document, &node, offset, false, can_gc),
+            Key::ArrowRight => adjacent_position(document, &node, offset, true, can_gc),
+            Key::ArrowUp => {
+                window.caret_in_adjacent_line_query(&node, offset, CaretLineDirection::Up, can_gc)
+            },
+            _ => {
+                window.caret_in_adjacent_line_query(&node, offset, CaretLineDirection::Down, can_gc)
+            },
+        },
+    };
+    let Some((node, offset)) = new_caret else {
+        return false;
+    };
+
+    let Some(text) = text_of(&node) else {
+        return false;
+    };
+    let utf16_offset = utf8_offset_to_utf16(&text, offset);
+    let extended = event.modifiers.contains(Modifiers::SHIFT) &&
+        selection.RangeCount() > 0 &&
+        selection.Extend(&node, utf16_offset, can_gc).is_ok();
+    if !extended &&
+        selection
+            .Collapse(Some(&node), utf16_offset, can_gc)
+            .is_err()
+    {
+        return false;
+    }
+
+    document.set_needs_paint(true);
+    if let Some(rect) = window.caret_rect_query(&node, offset, can_gc) {
+        window.scroll_rect_into_view(rect, can_gc);
+    }
+    true
+}
@@ -0,0 +1,231 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The context menu of a document, which is shown by the embedder.
+//!
+//! When a `contextmenu` event isn't canceled, the embedder is told about the link, image,
+//! media element and selected text under the pointer, so that it can offer actions that
+//! apply to them. The action chosen by the user is then performed here, in the document.
+
+use embedder_traits::{
+    ClipboardImage, ContextMenuAction, ContextMenuElementInformation, ContextMenuMediaInformation,
+    ContextMenuResult, EmbedderMsg,
+};
+use html5ever::local_name;
+use ipc_channel::ipc;
+use net_traits::request::{CredentialsMode, RequestBuilder};
+use net_traits::{CoreResourceMsg, IpcSend};
+use pixels::PixelFormat;
+use servo_url::ServoUrl;
+
+use crate::devtools;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlareaelement::HTMLAreaElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::htmlvideoelement::HTMLVideoElement;
+use crate::dom::node::{Node, NodeTraits, ShadowIncluding};
+use crate::realms::{InRealm, enter_realm};
+use crate::script_runtime::CanGc;
+
+/// The elements that a context menu was opened for.
+struct ContextMenuTargets {
+    node: DomRoot<Node>,
+    link: Option<DomRoot<Element>>,
+    image: Option<DomRoot<HTMLImageElement>>,
+    media: Option<DomRoot<HTMLMediaElement>>,
+}
+
+impl ContextMenuTargets {
+    fn new(target: &Node) -> Self {
+        let link = target
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .filter(|node| node.is::<HTMLAnchorElement>() || node.is::<HTMLAreaElement>())
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| element.has_attribute(&local_name!("href")));
+        Self {
+            node: DomRoot::from_ref(target),
+            link,
+            image: DomRoot::downcast(DomRoot::from_ref(target)),
+            media: DomRoot::downcast(DomRoot::from_ref(target)),
+        }
+    }
+
+    fn link_url(&self, document: &Document) -> Option<ServoUrl> {
+        let href = self
+            .link
+            .as_ref()?
+            .get_string_attribute(&local_name!("href"));
+        document.base_url().join(&href).ok()
+    }
+
+    fn image_url(&self) -> Option<ServoUrl> {
+        self.image.as_ref()?.get_url()
+    }
+
+    fn media_url(&self) -> Option<ServoUrl> {
+        let source = self.media.as_ref()?.CurrentSrc();
+        ServoUrl::parse(&source).ok()
+    }
+}
+
+/// The text selected in `document`, if the selection isn't empty.
+fn selected_text(document: &Document) -> Option<String> {
+    let selection = document.GetSelection()?;
+    if selection.IsCollapsed() {
+        return None;
+    }
+    Some(String::from(selection.Stringifier()))
+}
+
+/// Ask the embedder to show the context menu for `target`, and perform the action that the
+/// user chose, if any.
+pub(crate) fn show_context_menu(document: &Document, target: &Node, can_gc: CanGc) {
+    let targets = ContextMenuTargets::new(target);
+    let element_info = ContextMenuElementInformation {
+        link_url: targets.link_url(document),
+        image_url: targets.image_url(),
+        media: targets
+            .media
+            .as_ref()
+            .map(|media| ContextMenuMediaInformation {
+                is_video: media.is::<HTMLVideoElement>(),
+                source_url: targets.media_url(),
+                paused: media.Paused(),
+                muted: media.Muted(),
+                has_controls: media
+                    .upcast::<Element>()
+                    .has_attribute(&local_name!("controls")),
+            }),
+        selected_text: selected_text(document),
+        editable: target
+            .downcast::<Element>()
+            .is_some_and(Element::read_write_state),
+    };
+
+    let window = document.window();
+    let (sender, receiver) =
+        ipc::channel::<ContextMenuResult>().expect("Failed to create IPC channel.");
+    window.send_to_embedder(EmbedderMsg::ShowContextMenu(
+        window.webview_id(),
+        sender,
+        None,
+        vec![],
+        element_info,
+    ));
+    if let Ok(ContextMenuResult::Action(action)) = receiver.recv() {
+        perform_action(document, &targets, action, can_gc);
+    }
+}
+
+fn perform_action(
+    document: &Document,
+    targets: &ContextMenuTargets,
+    action: ContextMenuAction,
+    can_gc: CanGc,
+) {
+    let window = document.window();
+    match action {
+        ContextMenuAction::CopyLink => {
+            if let Some(url) = targets.link_url(document) {
+                copy_text(document, url.into_string());
+            }
+        },
+        ContextMenuAction::SaveLink => {
+            if let Some(url) = targets.link_url(document) {
+                download(document, url);
+            }
+        },
+        ContextMenuAction::CopyImage => {
+            if let Some(image) = targets.image.as_ref().and_then(|image| image.image_data()) {
+                // Decoded images are not premultiplied, so they only need to be in RGBA order.
+                let mut bytes = image.bytes().to_vec();
+                match image.format {
+                    PixelFormat::BGRA8 => pixels::rgba8_byte_swap_colors_inplace(&mut bytes),
+                    PixelFormat::RGBA8 => {},
+                    format => return warn!("Can't copy an image in {format:?} format"),
+                }
+                window.send_to_embedder(EmbedderMsg::SetClipboardImage(
+                    window.webview_id(),
+                    ClipboardImage {
+                        width: image.width,
+                        height: image.height,
+                        bytes,
+                    },
+                ));
+            }
+        },
+        ContextMenuAction::CopyImageAddress => {
+            if let Some(url) = targets.image_url() {
+                copy_text(document, url.into_string());
+            }
+        },
+        ContextMenuAction::SaveImage => {
+            if let Some(url) = targets.image_url() {
+                download(document, url);
+            }
+        },
+        ContextMenuAction::CopySelection => {
+            if let Some(text) = selected_text(document) {
+                copy_text(document, text);
+            }
+        },
+        ContextMenuAction::ToggleMediaPlayback => {
+            if let Some(media) = targets.media.as_ref() {
+                if media.Paused() {
+                    let realm = enter_realm(&**media);
+                    media.Play(InRealm::Entered(&realm), can_gc);
+                } else {
+                    media.Pause(can_gc);
+                }
+            }
+        },
+        ContextMenuAction::ToggleMediaMute => {
+            if let Some(media) = targets.media.as_ref() {
+                media.SetMuted(!media.Muted());
+            }
+        },
+        ContextMenuAction::ToggleMediaControls => {
+            if let Some(media) = targets.media.as_ref() {
+                let element = media.upcast::<Element>();
+                let has_controls = element.has_attribute(&local_name!("controls"));
+                element.set_bool_attribute(&local_name!("controls"), !has_controls, can_gc);
+            }
+        },
+        ContextMenuAction::SaveMedia => {
+            if let Some(url) = targets.media_url() {
+                download(document, url);
+            }
+        },
+        ContextMenuAction::InspectElement => devtools::inspect_node(&targets.node),
+    }
+}
+
+fn copy_text(document: &Document, text: String) {
+    let window = document.window();
+    window.send_to_embedder(EmbedderMsg::SetClipboardText(window.webview_id(), text));
+}
+
+/// Hand `url` to the download manager, as if a navigation to it had been answered with an
+/// attachment.
+fn download(document: &Document, url: ServoUrl) {
+    let window = document.window();
+    let global = window.as_global_scope();
+    let request = RequestBuilder::new(Some(window.webview_id()), url, global.get_referrer())
+        .origin(document.origin().immutable().clone())
+        .pipeline_id(Some(window.pipeline_id()))
+        .referrer_policy(document.get_referrer_policy())
+        .insecure_requests_policy(document.insecure_requests_policy())
+        .credentials_mode(CredentialsMode::Include);
+    let _ = global
+        .resource_threads()
+        .send(CoreResourceMsg::Download(request));
+}
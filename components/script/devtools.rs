@@ -8,13 +8,18 @@ use std::str;
 
 use base::id::PipelineId;
 use devtools_traits::{
-    AttrModification, AutoMargins, ComputedNodeLayout, CssDatabaseProperty, EvaluateJSReply,
-    NodeInfo, NodeStyle, RuleModification, TimelineMarker, TimelineMarkerType,
+    AttrModification, AutoMargins, ComputedNodeLayout, CssDatabaseProperty, DomMutation,
+    EvaluateJSReply, HighlighterKind, NodeInfo, NodeStyle, RuleModification,
+    ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType,
 };
+use html5ever::{LocalName, Namespace};
 use ipc_channel::ipc::IpcSender;
 use js::jsval::UndefinedValue;
 use js::rust::ToString;
+use script_layout_interface::ReflowGoal;
+use script_traits::NavigationHistoryBehavior;
 use servo_config::pref;
+use servo_url::ServoUrl;
 use uuid::Uuid;
 
 use crate::document_collection::DocumentCollection;
@@ -30,14 +35,17 @@ use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::{ConversionResult, FromJSValConvertible, jsstring_to_str};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::cssstyledeclaration::ENABLED_LONGHAND_PROPERTIES;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::cssstyledeclaration::{CSSStyleDeclaration, ENABLED_LONGHAND_PROPERTIES};
 use crate::dom::cssstylerule::CSSStyleRule;
-use crate::dom::document::AnimationFrameCallback;
+use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlscriptelement::SourceCode;
+use crate::dom::location::NavigationType;
+use crate::dom::mutationobserver::Mutation;
 use crate::dom::node::{Node, NodeTraits, ShadowIncluding};
 use crate::dom::types::HTMLElement;
 use crate::realms::enter_realm;
@@ -45,6 +53,140 @@ use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::CanGc;
 
 #[allow(unsafe_code)]
+/// A mutation of a DOM that the inspector watches. Mutations are queued before they happen,
+/// so they are only reported at the next microtask checkpoint, once their result is known.
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) enum PendingDomMutation {
+    Attribute {
+        target: Dom<Element>,
+        #[no_trace]
+        name: LocalName,
+        #[no_trace]
+        namespace: Namespace,
+    },
+    CharacterData {
+        target: Dom<Node>,
+    },
+    ChildList {
+        target: Dom<Node>,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+/// Remember a mutation of the DOM of the document of `target`, which the inspector watches.
+pub(crate) fn queue_dom_mutation(target: &Node, mutation: &Mutation) {
+    let document = target.owner_doc();
+    let mut pending_mutations = document.devtools_dom_mutations().borrow_mut();
+    let Some(pending_mutations) = pending_mutations.as_mut() else {
+        return;
+    };
+    let unique_ids = |nodes: Option<&[&Node]>| -> Vec<String> {
+        nodes
+            .unwrap_or_default()
+            .iter()
+            .map(|node| node.unique_id())
+            .collect()
+    };
+    match *mutation {
+        Mutation::Attribute {
+            ref name,
+            ref namespace,
+            ..
+        } => {
+            if let Some(element) = target.downcast::<Element>() {
+                pending_mutations.push(PendingDomMutation::Attribute {
+                    target: Dom::from_ref(element),
+                    name: name.clone(),
+                    namespace: namespace.clone(),
+                });
+            }
+        },
+        Mutation::CharacterData { .. } => {
+            pending_mutations.push(PendingDomMutation::CharacterData {
+                target: Dom::from_ref(target),
+            });
+        },
+        Mutation::ChildList { added, removed, .. } => {
+            pending_mutations.push(PendingDomMutation::ChildList {
+                target: Dom::from_ref(target),
+                added: unique_ids(added),
+                removed: unique_ids(removed),
+            });
+        },
+    }
+}
+
+/// Select `node` in the inspector of the devtools clients, on request from the user.
+pub(crate) fn inspect_node(node: &Node) {
+    let window = node.owner_window();
+    let Some(chan) = window.as_global_scope().devtools_chan() else {
+        return;
+    };
+    let mut nodes: Vec<_> = node
+        .inclusive_ancestors(ShadowIncluding::Yes)
+        .map(|node| node.summarize())
+        .collect();
+    nodes.reverse();
+    let _ = chan.send(ScriptToDevtoolsControlMsg::InspectNode(
+        window.pipeline_id(),
+        nodes,
+    ));
+}
+
+/// Report the mutations remembered by [`queue_dom_mutation`] to the inspector.
+#[cfg_attr(crown, allow(crown::unrooted_must_root))]
+pub(crate) fn send_dom_mutations(document: &Document) {
+    let pending_mutations = match document.devtools_dom_mutations().borrow_mut().as_mut() {
+        Some(pending_mutations) if !pending_mutations.is_empty() => {
+            std::mem::take(pending_mutations)
+        },
+        _ => return,
+    };
+    let Some(chan) = document.window().as_global_scope().devtools_chan() else {
+        return;
+    };
+
+    let mutations = pending_mutations
+        .into_iter()
+        .map(|mutation| match mutation {
+            PendingDomMutation::Attribute {
+                target,
+                name,
+                namespace,
+            } => DomMutation::Attribute {
+                target: target.upcast::<Node>().unique_id(),
+                new_value: target
+                    .get_attribute(&namespace, &name)
+                    .map(|attr| String::from(&**attr.value())),
+                attribute_name: name.to_string(),
+            },
+            PendingDomMutation::CharacterData { target } => DomMutation::CharacterData {
+                target: target.unique_id(),
+                new_value: target
+                    .downcast::<CharacterData>()
+                    .map(|data| data.Data().into())
+                    .unwrap_or_default(),
+            },
+            PendingDomMutation::ChildList {
+                target,
+                added,
+                removed,
+            } => DomMutation::ChildList {
+                target: target.unique_id(),
+                added,
+                removed,
+                num_children: target.children_count() as usize,
+            },
+        })
+        .collect();
+    let _ = chan.send(ScriptToDevtoolsControlMsg::DomMutations(
+        document.window().pipeline_id(),
+        mutations,
+    ));
+}
+
 pub(crate) fn handle_evaluate_js(
     global: &GlobalScope,
     eval: String,
@@ -442,10 +584,76 @@ pub(crate) fn handle_modify_attribute(
     }
 }
 
+pub(crate) fn handle_get_node_value(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<String>>,
+) {
+    let value = find_node_by_unique_id(documents, pipeline, &node_id)
+        .and_then(|node| node.GetNodeValue())
+        .map(String::from);
+    reply.send(value).unwrap();
+}
+
+pub(crate) fn handle_get_outer_html(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<String>>,
+    can_gc: CanGc,
+) {
+    let html = find_node_by_unique_id(documents, pipeline, &node_id).and_then(|node| {
+        if let Some(element) = node.downcast::<Element>() {
+            element.GetOuterHTML(can_gc).ok().map(String::from)
+        } else if node.is::<Document>() {
+            Some(node.fragment_serialization_algorithm(false, can_gc).into())
+        } else {
+            node.GetNodeValue().map(String::from)
+        }
+    });
+    reply.send(html).unwrap();
+}
+
+pub(crate) fn handle_query_selector_all(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    node_id: String,
+    selector: String,
+    reply: IpcSender<Option<Vec<NodeInfo>>>,
+) {
+    let nodes = find_node_by_unique_id(documents, pipeline, &node_id)
+        .and_then(|node| node.query_selector_iter(selector.into()).ok())
+        .map(|matches| matches.map(|node| node.summarize()).collect());
+    reply.send(nodes).unwrap();
+}
+
+pub(crate) fn handle_modify_node_value(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    node_id: String,
+    value: String,
+) {
+    let Some(document) = documents.find_document(pipeline) else {
+        return warn!("Document for pipeline id {} is not found", &pipeline);
+    };
+    let _realm = enter_realm(document.window());
+
+    let Some(node) = find_node_by_unique_id(documents, pipeline, &node_id) else {
+        return warn!(
+            "Node id {} for pipeline id {} is not found",
+            &node_id, &pipeline
+        );
+    };
+    node.SetNodeValue(Some(value.into()));
+}
+
+#[cfg_attr(crown, allow(crown::unrooted_must_root))]
 pub(crate) fn handle_modify_rule(
     documents: &DocumentCollection,
     pipeline: PipelineId,
     node_id: String,
+    selector: Option<(String, usize)>,
     modifications: Vec<RuleModification>,
     can_gc: CanGc,
 ) {
@@ -461,18 +669,75 @@ pub(crate) fn handle_modify_rule(
         );
     };
 
-    let elem = node
-        .downcast::<HTMLElement>()
-        .expect("This should be an HTMLElement");
-    let style = elem.Style();
+    // The declarations to modify are either those of the style attribute, or those of every
+    // rule with the selector in the stylesheet.
+    let styles: Vec<DomRoot<CSSStyleDeclaration>> = match selector {
+        Some((selector, stylesheet)) => {
+            let Some(list) = node
+                .stylesheet_list_owner()
+                .stylesheet_at(stylesheet)
+                .and_then(|stylesheet| stylesheet.GetCssRules().ok())
+            else {
+                return warn!("Stylesheet {stylesheet} for pipeline id {pipeline} is not found");
+            };
+            (0..list.Length())
+                .filter_map(|i| {
+                    let rule = list.Item(i, can_gc)?;
+                    let style = rule.downcast::<CSSStyleRule>()?;
+                    (*selector == *style.SelectorText()).then(|| style.Style())
+                })
+                .collect()
+        },
+        None => match node.downcast::<HTMLElement>() {
+            Some(elem) => vec![elem.Style()],
+            None => return warn!("The style attribute only works on HTML elements"),
+        },
+    };
 
-    for modification in modifications {
-        let _ = style.SetProperty(
-            modification.name.into(),
-            modification.value.into(),
-            modification.priority.into(),
-            can_gc,
-        );
+    for style in styles {
+        for modification in &modifications {
+            let name = DOMString::from(modification.name.clone());
+            let _ = match &*modification.type_ {
+                "remove" => style.RemoveProperty(name, can_gc).map(|_| ()),
+                _ => style.SetProperty(
+                    name,
+                    modification.value.clone().into(),
+                    modification.priority.clone().into(),
+                    can_gc,
+                ),
+            };
+        }
+    }
+}
+
+pub(crate) fn handle_highlight_dom_node(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    kind: HighlighterKind,
+    node_id: Option<String>,
+    can_gc: CanGc,
+) {
+    let Some(document) = documents.find_document(pipeline) else {
+        return warn!("Document for pipeline id {} is not found", &pipeline);
+    };
+    let node = node_id.and_then(|node_id| find_node_by_unique_id(documents, pipeline, &node_id));
+    document.set_devtools_highlighted_node(kind, node.as_deref());
+
+    // Update the rendering right away, so that the overlays follow the mouse of the user.
+    document.set_needs_paint(true);
+    document
+        .window()
+        .reflow(ReflowGoal::UpdateTheRendering, can_gc);
+}
+
+pub(crate) fn handle_watch_dom_mutations(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    watch: bool,
+) {
+    match documents.find_document(pipeline) {
+        Some(document) => document.set_devtools_watches_dom_mutations(watch),
+        None => warn!("Document for pipeline id {} is not found", &pipeline),
     }
 }
 
@@ -518,6 +783,22 @@ pub(crate) fn handle_reload(documents: &DocumentCollection, id: PipelineId, can_
     }
 }
 
+pub(crate) fn handle_navigate_to(
+    documents: &DocumentCollection,
+    id: PipelineId,
+    url: ServoUrl,
+    can_gc: CanGc,
+) {
+    if let Some(win) = documents.find_window(id) {
+        win.Location().navigate(
+            url,
+            NavigationHistoryBehavior::Push,
+            NavigationType::Devtools,
+            can_gc,
+        );
+    }
+}
+
 pub(crate) fn handle_get_css_database(reply: IpcSender<HashMap<String, CssDatabaseProperty>>) {
     let database: HashMap<_, _> = ENABLED_LONGHAND_PROPERTIES
         .iter()
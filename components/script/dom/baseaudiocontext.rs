@@ -17,7 +17,13 @@ use servo_media::audio::context::{
     RealTimeAudioContextOptions,
 };
 use servo_media::audio::decoder::AudioDecoderCallbacks;
+use servo_media::audio::gain_node::GainNodeOptions;
 use servo_media::audio::graph::NodeId;
+use servo_media::audio::node::{
+    AudioNodeInit, AudioNodeMessage, ChannelCountMode as ServoMediaChannelCountMode, ChannelInfo,
+    ChannelInterpretation as ServoMediaChannelInterpretation,
+};
+use servo_media::audio::param::{ParamType, UserAutomationEvent};
 use servo_media::{ClientContextId, ServoMedia};
 use uuid::Uuid;
 
@@ -89,6 +95,12 @@ pub(crate) struct BaseAudioContext {
     #[ignore_malloc_size_of = "servo_media"]
     #[no_trace]
     audio_context_impl: Arc<Mutex<AudioContext>>,
+    /// A gain node between the graph and the output device of a realtime context, which the
+    /// embedder mutes along with the rest of the webview. Nodes that are connected to the
+    /// destination are connected to it instead.
+    #[ignore_malloc_size_of = "servo_media"]
+    #[no_trace]
+    muting_node: Option<NodeId>,
     /// <https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-destination>
     destination: MutNullableDom<AudioDestinationNode>,
     listener: MutNullableDom<AudioListener>,
@@ -124,15 +136,33 @@ impl BaseAudioContext {
             },
         };
 
+        let is_offline = matches!(options, BaseAudioContextOptions::OfflineAudioContext(_));
+
         let client_context_id =
             ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
         let audio_context_impl = ServoMedia::get()
             .create_audio_context(&client_context_id, options.convert())
             .map_err(|_| Error::NotSupported)?;
 
+        let muting_node = (!is_offline).then(|| {
+            let audio_context = audio_context_impl.lock().unwrap();
+            let node = audio_context.create_node(
+                AudioNodeInit::GainNode(GainNodeOptions { gain: 1. }),
+                ChannelInfo {
+                    count: channel_count,
+                    mode: ServoMediaChannelCountMode::Max,
+                    interpretation: ServoMediaChannelInterpretation::Speakers,
+                    context_channel_count: channel_count,
+                },
+            );
+            audio_context.connect_ports(node.output(0), audio_context.dest_node().input(0));
+            node
+        });
+
         Ok(BaseAudioContext {
             eventtarget: EventTarget::new_inherited(),
             audio_context_impl,
+            muting_node,
             destination: Default::default(),
             listener: Default::default(),
             in_flight_resume_promises_queue: Default::default(),
@@ -154,7 +184,20 @@ impl BaseAudioContext {
     }
 
     pub(crate) fn destination_node(&self) -> NodeId {
-        self.audio_context_impl.lock().unwrap().dest_node()
+        self.muting_node
+            .unwrap_or_else(|| self.audio_context_impl.lock().unwrap().dest_node())
+    }
+
+    /// Silence or restore the output of a realtime context, without the page noticing.
+    pub(crate) fn set_muted(&self, muted: bool) {
+        let Some(muting_node) = self.muting_node else {
+            return;
+        };
+        let gain = if muted { 0. } else { 1. };
+        self.audio_context_impl.lock().unwrap().message_node(
+            muting_node,
+            AudioNodeMessage::SetParam(ParamType::Gain, UserAutomationEvent::SetValue(gain)),
+        );
     }
 
     pub(crate) fn listener(&self) -> NodeId {
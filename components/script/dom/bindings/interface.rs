@@ -4,6 +4,7 @@
 
 //! Machinery to initialise interface prototype objects and interface objects.
 
+use std::cell::Cell;
 use std::convert::TryFrom;
 use std::ffi::CStr;
 use std::ptr;
@@ -13,12 +14,12 @@ use js::glue::UncheckedUnwrapObject;
 use js::jsapi::JS::CompartmentIterResult;
 use js::jsapi::{
     CallArgs, CheckedUnwrapStatic, Compartment, CompartmentSpecifier, CurrentGlobalOrNull,
-    GetFunctionRealm, GetNonCCWObjectGlobal, GetRealmGlobalOrNull, GetWellKnownSymbol,
-    HandleObject as RawHandleObject, IsSharableCompartment, IsSystemCompartment,
-    JS_AtomizeAndPinString, JS_GetFunctionObject, JS_GetProperty, JS_IterateCompartments,
-    JS_NewFunction, JS_NewGlobalObject, JS_NewObject, JS_NewPlainObject, JS_NewStringCopyN,
-    JS_SetReservedSlot, JS_WrapObject, JSAutoRealm, JSClass, JSClassOps, JSContext,
-    JSFUN_CONSTRUCTOR, JSFunctionSpec, JSObject, JSPROP_PERMANENT, JSPROP_READONLY,
+    GetCompartmentForRealm, GetFunctionRealm, GetNonCCWObjectGlobal, GetRealmGlobalOrNull,
+    GetWellKnownSymbol, HandleObject as RawHandleObject, IsSharableCompartment,
+    IsSystemCompartment, JS_AtomizeAndPinString, JS_GetFunctionObject, JS_GetProperty,
+    JS_IterateCompartments, JS_NewFunction, JS_NewGlobalObject, JS_NewObject, JS_NewPlainObject,
+    JS_NewStringCopyN, JS_SetReservedSlot, JS_WrapObject, JSAutoRealm, JSClass, JSClassOps,
+    JSContext, JSFUN_CONSTRUCTOR, JSFunctionSpec, JSObject, JSPROP_PERMANENT, JSPROP_READONLY,
     JSPROP_RESOLVING, JSPropertySpec, JSString, JSTracer, ObjectOps, OnNewGlobalHookOption,
     SymbolCode, TrueHandleValue, Value, jsid,
 };
@@ -30,7 +31,7 @@ use js::rust::wrappers::{
 };
 use js::rust::{
     HandleObject, HandleValue, MutableHandleObject, RealmOptions, define_methods,
-    define_properties, get_object_class, is_dom_class, maybe_wrap_object,
+    define_properties, get_object_class, get_object_realm, is_dom_class, maybe_wrap_object,
 };
 use script_bindings::constant::{ConstantSpec, define_constants};
 use servo_url::MutableOrigin;
@@ -134,6 +135,12 @@ impl InterfaceConstructorBehavior {
 /// A trace hook.
 pub(crate) type TraceHook = unsafe extern "C" fn(trc: *mut JSTracer, obj: *mut JSObject);
 
+thread_local! {
+    /// The compartment of the debugger global of this thread, if any. SpiderMonkey's Debugger
+    /// API only observes globals of other compartments, so no global may share it.
+    static DEBUGGER_COMPARTMENT: Cell<*mut Compartment> = const { Cell::new(ptr::null_mut()) };
+}
+
 /// Create a global object with the given class.
 pub(crate) unsafe fn create_global_object<D: DomTypes>(
     cx: SafeJSContext,
@@ -148,7 +155,13 @@ pub(crate) unsafe fn create_global_object<D: DomTypes>(
     let mut options = RealmOptions::default();
     options.creationOptions_.traceGlobal_ = Some(trace);
     options.creationOptions_.sharedMemoryAndAtomics_ = false;
-    select_compartment(cx, &mut options);
+    let is_debugger_global = CStr::from_ptr(class.name) == c"DebuggerGlobalScope";
+    if is_debugger_global {
+        options.creationOptions_.compSpec_ = CompartmentSpecifier::NewCompartmentAndZone;
+        options.creationOptions_.invisibleToDebugger_ = true;
+    } else {
+        select_compartment(cx, &mut options);
+    }
 
     let principal = ServoJSPrincipals::new::<D>(origin);
 
@@ -160,6 +173,10 @@ pub(crate) unsafe fn create_global_object<D: DomTypes>(
         &*options,
     ));
     assert!(!rval.is_null());
+    if is_debugger_global {
+        let compartment = GetCompartmentForRealm(get_object_realm(rval.get()));
+        DEBUGGER_COMPARTMENT.with(|debugger_compartment| debugger_compartment.set(compartment));
+    }
 
     // Initialize the reserved slots before doing anything that can GC, to
     // avoid getting trace hooks called on a partially initialized object.
@@ -184,7 +201,10 @@ fn select_compartment(cx: SafeJSContext, options: &mut RealmOptions) {
     ) -> CompartmentIterResult {
         let data = data as *mut Data;
 
-        if !IsSharableCompartment(compartment) || IsSystemCompartment(compartment) {
+        if !IsSharableCompartment(compartment) ||
+            IsSystemCompartment(compartment) ||
+            DEBUGGER_COMPARTMENT.with(Cell::get) == compartment
+        {
             return CompartmentIterResult::KeepGoing;
         }
 
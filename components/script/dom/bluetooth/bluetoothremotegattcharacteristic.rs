@@ -2,13 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
 use std::rc::Rc;
 
 use bluetooth_traits::blocklist::{Blocklist, uuid_is_blocklisted};
-use bluetooth_traits::{BluetoothRequest, BluetoothResponse, GATTType};
+use bluetooth_traits::{
+    BluetoothNotificationRequest, BluetoothRequest, BluetoothResponse, GATTType,
+};
 use dom_struct::dom_struct;
-use ipc_channel::ipc::IpcSender;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::typedarray::{ArrayBufferView, ArrayBufferViewU8};
 
+use crate::dom::bindings::buffer_source::{
+    Constructor, HeapBufferSource, create_array_buffer_with_size,
+    create_buffer_source_with_constructor,
+};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BluetoothCharacteristicPropertiesBinding::BluetoothCharacteristicPropertiesMethods;
 use crate::dom::bindings::codegen::Bindings::BluetoothRemoteGATTCharacteristicBinding::BluetoothRemoteGATTCharacteristicMethods;
@@ -18,10 +27,12 @@ use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
 use crate::dom::bindings::error::Error::{
     self, InvalidModification, Network, NotSupported, Security,
 };
+use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
 use crate::dom::bindings::root::{Dom, DomRoot};
-use crate::dom::bindings::str::{ByteString, DOMString};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bluetooth::{AsyncBluetoothListener, get_gatt_children, response_async};
 use crate::dom::bluetoothcharacteristicproperties::BluetoothCharacteristicProperties;
 use crate::dom::bluetoothremotegattservice::BluetoothRemoteGATTService;
@@ -29,8 +40,8 @@ use crate::dom::bluetoothuuid::{BluetoothDescriptorUUID, BluetoothUUID};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
-use crate::realms::InRealm;
-use crate::script_runtime::CanGc;
+use crate::realms::{InRealm, enter_realm};
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
 // Maximum length of an attribute value.
 // https://www.bluetooth.org/DocMan/handlers/DownloadDoc.ashx?doc_id=286439 (Vol. 3, page 2169)
@@ -43,8 +54,13 @@ pub(crate) struct BluetoothRemoteGATTCharacteristic {
     service: Dom<BluetoothRemoteGATTService>,
     uuid: DOMString,
     properties: Dom<BluetoothCharacteristicProperties>,
-    value: DomRefCell<Option<ByteString>>,
+    /// A DataView over the last value that was read, written or notified.
+    #[ignore_malloc_size_of = "mozjs"]
+    value: DomRefCell<HeapBufferSource<ArrayBufferViewU8>>,
     instance_id: String,
+    /// Whether the notifications of this characteristic are started for its document, which
+    /// is whether its active notification context set contains `navigator.bluetooth`.
+    notifications_started: Cell<bool>,
 }
 
 impl BluetoothRemoteGATTCharacteristic {
@@ -59,8 +75,9 @@ impl BluetoothRemoteGATTCharacteristic {
             service: Dom::from_ref(service),
             uuid,
             properties: Dom::from_ref(properties),
-            value: DomRefCell::new(None),
+            value: DomRefCell::new(HeapBufferSource::default()),
             instance_id,
+            notifications_started: Cell::new(false),
         }
     }
 
@@ -91,6 +108,64 @@ impl BluetoothRemoteGATTCharacteristic {
     fn get_instance_id(&self) -> String {
         self.instance_id.clone()
     }
+
+    /// A channel on which the bluetooth thread sends the new values of this characteristic,
+    /// while its notifications are started.
+    fn notification_sender(&self) -> IpcSender<Vec<u8>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let task_source = self
+            .global()
+            .task_manager()
+            .networking_task_source()
+            .to_sendable();
+        let this = Trusted::new(self);
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |message| {
+                let Ok(value) = message else {
+                    return;
+                };
+                let this = this.clone();
+                task_source.queue(task!(characteristic_value_changed: move || {
+                    this.root().handle_notification(value, CanGc::note());
+                }));
+            }),
+        );
+        sender
+    }
+
+    /// <https://webbluetoothcg.github.io/web-bluetooth/#notification-events>
+    fn handle_notification(&self, value: Vec<u8>, can_gc: CanGc) {
+        // The values that arrive after the notifications were stopped are ignored.
+        if !self.notifications_started.get() {
+            return;
+        }
+
+        if self.set_value(&value, can_gc).is_err() {
+            return;
+        }
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(atom!("characteristicvaluechanged"), can_gc);
+    }
+
+    /// Set `value` to a DataView over a new ArrayBuffer holding the given bytes.
+    fn set_value(&self, value: &[u8], can_gc: CanGc) -> Fallible<()> {
+        let cx = GlobalScope::get_cx();
+        let _ac = enter_realm(self);
+        let buffer = create_array_buffer_with_size(cx, value.len())?;
+        buffer
+            .set_data(cx, value, can_gc)
+            .map_err(|_| Error::JSFailed)?;
+        let view = create_buffer_source_with_constructor(
+            cx,
+            &Constructor::DataView,
+            &buffer,
+            0,
+            value.len(),
+        )?;
+        *self.value.borrow_mut() = view;
+        Ok(())
+    }
 }
 
 impl BluetoothRemoteGATTCharacteristicMethods<crate::DomTypeHolder>
@@ -144,8 +219,8 @@ impl BluetoothRemoteGATTCharacteristicMethods<crate::DomTypeHolder>
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-value
-    fn GetValue(&self) -> Option<ByteString> {
-        self.value.borrow().clone()
+    fn GetValue(&self, _cx: SafeJSContext) -> Option<ArrayBufferView> {
+        self.value.borrow().typed_array_to_option()
     }
 
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-readvalue
@@ -259,15 +334,20 @@ impl BluetoothRemoteGATTCharacteristicMethods<crate::DomTypeHolder>
             return p;
         }
 
-        // TODO: Step 6: Implement `active notification context set` for BluetoothRemoteGATTCharacteristic.
+        // Step 6.
+        if self.notifications_started.get() {
+            p.resolve_native(self, can_gc);
+            return p;
+        }
 
         // Note: Steps 3 - 4, 7 - 11 are implemented in components/bluetooth/lib.rs in enable_notification function
         // and in handle_response function.
         let sender = response_async(&p, self);
+        let pipeline_id = self.global().pipeline_id();
         self.get_bluetooth_thread()
             .send(BluetoothRequest::EnableNotification(
                 self.get_instance_id(),
-                true,
+                BluetoothNotificationRequest::Start(pipeline_id, self.notification_sender()),
                 sender,
             ))
             .unwrap();
@@ -279,14 +359,16 @@ impl BluetoothRemoteGATTCharacteristicMethods<crate::DomTypeHolder>
         let p = Promise::new_in_current_realm(comp, can_gc);
         let sender = response_async(&p, self);
 
-        // TODO: Step 3 - 4: Implement `active notification context set` for BluetoothRemoteGATTCharacteristic,
+        // Step 3.
+        self.notifications_started.set(false);
 
-        // Note: Steps 1 - 2, and part of Step 4 and Step 5 are implemented in components/bluetooth/lib.rs
+        // Note: Steps 1 - 2, and Step 4 - 5 are implemented in components/bluetooth/lib.rs
         // in enable_notification function and in handle_response function.
+        let pipeline_id = self.global().pipeline_id();
         self.get_bluetooth_thread()
             .send(BluetoothRequest::EnableNotification(
                 self.get_instance_id(),
-                false,
+                BluetoothNotificationRequest::Stop(pipeline_id),
                 sender,
             ))
             .unwrap();
@@ -327,15 +409,17 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTCharacteristic {
                 // TODO: Step 5.5.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
 
                 // Step 5.5.2.
-                // TODO(#5014): Replace ByteString with ArrayBuffer when it is implemented.
-                let value = ByteString::new(result);
-                *self.value.borrow_mut() = Some(value.clone());
+                if let Err(error) = self.set_value(&result, can_gc) {
+                    promise.reject_error(error, can_gc);
+                    return;
+                }
 
                 // Step 5.5.3.
                 self.upcast::<EventTarget>()
                     .fire_bubbling_event(atom!("characteristicvaluechanged"), can_gc);
 
                 // Step 5.5.4.
+                let value = self.value.borrow().typed_array_to_option();
                 promise.resolve_native(&value, can_gc);
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-writevalue
@@ -343,17 +427,19 @@ impl AsyncBluetoothListener for BluetoothRemoteGATTCharacteristic {
                 // TODO: Step 7.5.1: Implement activeAlgorithms internal slot for BluetoothRemoteGATTServer.
 
                 // Step 7.5.2.
-                // TODO(#5014): Replace ByteString with an ArrayBuffer wrapped in a DataView.
-                *self.value.borrow_mut() = Some(ByteString::new(result));
+                if let Err(error) = self.set_value(&result, can_gc) {
+                    promise.reject_error(error, can_gc);
+                    return;
+                }
 
                 // Step 7.5.3.
                 promise.resolve_native(&(), can_gc);
             },
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
             // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
-            BluetoothResponse::EnableNotification(_result) => {
-                // (StartNotification) TODO: Step 10:  Implement `active notification context set`
-                // for BluetoothRemoteGATTCharacteristic.
+            BluetoothResponse::EnableNotification(started) => {
+                // (StartNotification) Step 10.
+                self.notifications_started.set(started);
 
                 // (StartNotification) Step 11.
                 // (StopNotification)  Step 5.
@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::caret_browsing::{text_of, utf16_offset_to_utf8};
+use crate::dom::bindings::codegen::Bindings::CaretPositionBinding::CaretPositionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrect::DOMRect;
+use crate::dom::node::{Node, NodeTraits};
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// A position in the text of a document, as returned by `document.caretPositionFromPoint()`.
+/// <https://drafts.csswg.org/cssom-view/#caretposition>
+#[dom_struct]
+pub(crate) struct CaretPosition {
+    reflector: Reflector,
+    offset_node: Dom<Node>,
+    /// The offset in `offset_node`, in UTF-16 code units for text nodes.
+    offset: u32,
+}
+
+impl CaretPosition {
+    pub(crate) fn new(
+        window: &Window,
+        offset_node: &Node,
+        offset: u32,
+        can_gc: CanGc,
+    ) -> DomRoot<CaretPosition> {
+        reflect_dom_object(
+            Box::new(CaretPosition {
+                reflector: Reflector::new(),
+                offset_node: Dom::from_ref(offset_node),
+                offset,
+            }),
+            window,
+            can_gc,
+        )
+    }
+}
+
+impl CaretPositionMethods<crate::DomTypeHolder> for CaretPosition {
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-offsetnode>
+    fn OffsetNode(&self) -> DomRoot<Node> {
+        DomRoot::from_ref(&*self.offset_node)
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-offset>
+    fn Offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-caretposition-getclientrect>
+    fn GetClientRect(&self, can_gc: CanGc) -> Option<DomRoot<DOMRect>> {
+        // Only carets in text are rendered, as the caret of a collapsed range in the text.
+        // TODO: Return the rect of the caret in text controls.
+        let text = text_of(&self.offset_node)?;
+        let offset = utf16_offset_to_utf8(&text, self.offset);
+        let window = self.offset_node.owner_window();
+        let rect = window.caret_rect_query(&self.offset_node, offset, can_gc)?;
+
+        // Layout positions the caret relative to the initial containing block, while client
+        // rects are relative to the viewport.
+        let viewport_origin = window.current_viewport().origin;
+        Some(DOMRect::new(
+            window.upcast(),
+            (rect.origin.x - viewport_origin.x).to_f64_px(),
+            (rect.origin.y - viewport_origin.y).to_f64_px(),
+            rect.size.width.to_f64_px(),
+            rect.size.height.to_f64_px(),
+            can_gc,
+        ))
+    }
+}
@@ -61,6 +61,7 @@ use crate::dom::htmloutputelement::HTMLOutputElement;
 use crate::dom::htmlparagraphelement::HTMLParagraphElement;
 use crate::dom::htmlparamelement::HTMLParamElement;
 use crate::dom::htmlpictureelement::HTMLPictureElement;
+use crate::dom::htmlportalelement::HTMLPortalElement;
 use crate::dom::htmlpreelement::HTMLPreElement;
 use crate::dom::htmlprogresselement::HTMLProgressElement;
 use crate::dom::htmlquoteelement::HTMLQuoteElement;
@@ -84,7 +85,15 @@ use crate::dom::htmltrackelement::HTMLTrackElement;
 use crate::dom::htmlulistelement::HTMLUListElement;
 use crate::dom::htmlunknownelement::HTMLUnknownElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
+use crate::dom::svgcircleelement::SVGCircleElement;
 use crate::dom::svgelement::SVGElement;
+use crate::dom::svgellipseelement::SVGEllipseElement;
+use crate::dom::svggelement::SVGGElement;
+use crate::dom::svglineelement::SVGLineElement;
+use crate::dom::svgpathelement::SVGPathElement;
+use crate::dom::svgpolygonelement::SVGPolygonElement;
+use crate::dom::svgpolylineelement::SVGPolylineElement;
+use crate::dom::svgrectelement::SVGRectElement;
 use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::realms::{InRealm, enter_realm};
 use crate::script_runtime::CanGc;
@@ -114,6 +123,14 @@ fn create_svg_element(
     }
 
     match name.local {
+        local_name!("circle") => make!(SVGCircleElement),
+        local_name!("ellipse") => make!(SVGEllipseElement),
+        local_name!("g") => make!(SVGGElement),
+        local_name!("line") => make!(SVGLineElement),
+        local_name!("path") => make!(SVGPathElement),
+        local_name!("polygon") => make!(SVGPolygonElement),
+        local_name!("polyline") => make!(SVGPolylineElement),
+        local_name!("rect") => make!(SVGRectElement),
         local_name!("svg") => make!(SVGSVGElement),
         _ => make!(SVGElement),
     }
@@ -424,6 +441,7 @@ pub(crate) fn create_native_html_element(
         local_name!("video") => make!(HTMLVideoElement),
         local_name!("wbr") => make!(HTMLElement),
         local_name!("xmp") => make!(HTMLPreElement),
+        _ if &*name.local == "portal" && pref!(dom_portals_enabled) => make!(HTMLPortalElement),
         _ if is_valid_custom_element_name(&name.local) => make!(HTMLElement),
         _ => make!(HTMLUnknownElement),
     }
@@ -45,6 +45,7 @@ impl CSSGroupingRule {
             CSSRuleList::new(
                 self.global().as_window(),
                 parent_stylesheet,
+                Some(self.upcast()),
                 RulesSource::Rules(self.rules.clone()),
                 can_gc,
             )
@@ -69,13 +70,21 @@ impl CSSGroupingRuleMethods<crate::DomTypeHolder> for CSSGroupingRule {
 
     // https://drafts.csswg.org/cssom/#dom-cssgroupingrule-insertrule
     fn InsertRule(&self, rule: DOMString, index: u32, can_gc: CanGc) -> Fallible<u32> {
-        // TODO: this should accumulate the rule types of all ancestors.
-        let rule_type = self.cssrule.as_specific().ty();
-        let containing_rule_types = CssRuleTypes::from(rule_type);
-        let parse_relative_rule_type = match rule_type {
-            CssRuleType::Style | CssRuleType::Scope => Some(rule_type),
-            _ => None,
-        };
+        // The rules that can be inserted depend on the types of this rule and of all of its
+        // ancestors, and rules in a style or scope rule are relative to the closest one.
+        let mut containing_rule_types = CssRuleTypes::default();
+        let mut parse_relative_rule_type = None;
+        let mut ancestor = Some(DomRoot::from_ref(&self.cssrule));
+        while let Some(rule) = ancestor {
+            let rule_type = rule.as_specific().ty();
+            containing_rule_types.insert(rule_type);
+            if parse_relative_rule_type.is_none() &&
+                matches!(rule_type, CssRuleType::Style | CssRuleType::Scope)
+            {
+                parse_relative_rule_type = Some(rule_type);
+            }
+            ancestor = rule.parent_rule();
+        }
         self.rulelist(can_gc).insert_rule(
             &rule,
             index,
@@ -67,6 +67,7 @@ impl CSSKeyframesRule {
             CSSRuleList::new(
                 self.global().as_window(),
                 parent_stylesheet,
+                Some(self.upcast()),
                 RulesSource::Keyframes(self.keyframesrule.clone()),
                 can_gc,
             )
@@ -113,7 +114,9 @@ impl CSSKeyframesRuleMethods<crate::DomTypeHolder> for CSSKeyframesRule {
                 .write_with(&mut guard)
                 .keyframes
                 .push(rule);
+            drop(guard);
             self.rulelist(can_gc).append_lazy_dom_rule();
+            self.cssrule.parent_stylesheet().notify_invalidations();
         }
     }
 
@@ -143,8 +146,11 @@ impl CSSKeyframesRuleMethods<crate::DomTypeHolder> for CSSKeyframesRule {
         // Setting this property to a CSS-wide keyword or `none` does not throw,
         // it stores a value that serializes as a quoted string.
         let name = KeyframesName::from_ident(&value);
-        let mut guard = self.cssrule.shared_lock().write();
-        self.keyframesrule.write_with(&mut guard).name = name;
+        {
+            let mut guard = self.cssrule.shared_lock().write();
+            self.keyframesrule.write_with(&mut guard).name = name;
+        }
+        self.cssrule.parent_stylesheet().notify_invalidations();
         Ok(())
     }
 }
@@ -11,7 +11,7 @@ use style::stylesheets::{CssRule as StyleCssRule, CssRuleType};
 use crate::dom::bindings::codegen::Bindings::CSSRuleBinding::CSSRuleMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::Reflector;
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssfontfacerule::CSSFontFaceRule;
 use crate::dom::cssimportrule::CSSImportRule;
@@ -36,6 +36,9 @@ pub(crate) struct CSSRule {
     /// We keep parent_stylesheet in that case because insertRule needs it
     /// for the stylesheet’s base URL and namespace prefixes.
     parent_stylesheet_removed: Cell<bool>,
+
+    /// <https://drafts.csswg.org/cssom/#concept-css-rule-parent-css-rule>
+    parent_rule: MutNullableDom<CSSRule>,
 }
 
 impl CSSRule {
@@ -45,6 +48,7 @@ impl CSSRule {
             reflector_: Reflector::new(),
             parent_stylesheet: Dom::from_ref(parent_stylesheet),
             parent_stylesheet_removed: Cell::new(false),
+            parent_rule: MutNullableDom::new(None),
         }
     }
 
@@ -132,7 +136,7 @@ impl CSSRule {
     /// Sets owner sheet/rule to null
     pub(crate) fn detach(&self) {
         self.deparent();
-        // should set parent rule to None when we add parent rule support
+        self.parent_rule.set(None);
     }
 
     /// Sets owner sheet to null (and does the same for all children)
@@ -148,6 +152,14 @@ impl CSSRule {
         &self.parent_stylesheet
     }
 
+    pub(crate) fn parent_rule(&self) -> Option<DomRoot<CSSRule>> {
+        self.parent_rule.get()
+    }
+
+    pub(crate) fn set_parent_rule(&self, parent_rule: Option<&CSSRule>) {
+        self.parent_rule.set(parent_rule);
+    }
+
     pub(crate) fn shared_lock(&self) -> &SharedRwLock {
         &self.parent_stylesheet.style_stylesheet().shared_lock
     }
@@ -162,6 +174,11 @@ impl CSSRuleMethods<crate::DomTypeHolder> for CSSRule {
         if rule_type > 15 { 0 } else { rule_type }
     }
 
+    // https://drafts.csswg.org/cssom/#dom-cssrule-parentrule
+    fn GetParentRule(&self) -> Option<DomRoot<CSSRule>> {
+        self.parent_rule()
+    }
+
     // https://drafts.csswg.org/cssom/#dom-cssrule-parentstylesheet
     fn GetParentStyleSheet(&self) -> Option<DomRoot<CSSStyleSheet>> {
         if self.parent_stylesheet_removed.get() {
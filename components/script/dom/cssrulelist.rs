@@ -43,6 +43,8 @@ impl Convert<Error> for RulesMutateError {
 pub(crate) struct CSSRuleList {
     reflector_: Reflector,
     parent_stylesheet: Dom<CSSStyleSheet>,
+    /// The grouping or keyframes rule that contains the rules of this list, if any.
+    parent_rule: Option<Dom<CSSRule>>,
     #[ignore_malloc_size_of = "Arc"]
     rules: RulesSource,
     dom_rules: DomRefCell<Vec<MutNullableDom<CSSRule>>>,
@@ -57,6 +59,7 @@ impl CSSRuleList {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn new_inherited(
         parent_stylesheet: &CSSStyleSheet,
+        parent_rule: Option<&CSSRule>,
         rules: RulesSource,
     ) -> CSSRuleList {
         let guard = parent_stylesheet.shared_lock().read();
@@ -78,6 +81,7 @@ impl CSSRuleList {
         CSSRuleList {
             reflector_: Reflector::new(),
             parent_stylesheet: Dom::from_ref(parent_stylesheet),
+            parent_rule: parent_rule.map(Dom::from_ref),
             rules,
             dom_rules: DomRefCell::new(dom_rules),
         }
@@ -87,11 +91,16 @@ impl CSSRuleList {
     pub(crate) fn new(
         window: &Window,
         parent_stylesheet: &CSSStyleSheet,
+        parent_rule: Option<&CSSRule>,
         rules: RulesSource,
         can_gc: CanGc,
     ) -> DomRoot<CSSRuleList> {
         reflect_dom_object(
-            Box::new(CSSRuleList::new_inherited(parent_stylesheet, rules)),
+            Box::new(CSSRuleList::new_inherited(
+                parent_stylesheet,
+                parent_rule,
+                rules,
+            )),
             window,
             can_gc,
         )
@@ -125,6 +134,12 @@ impl CSSRuleList {
         let loader = owner
             .as_ref()
             .map(|element| StylesheetLoader::for_element(element));
+        // Constructed stylesheets can not have `@import` rules.
+        let allow_import_rules = if self.parent_stylesheet.is_constructed() {
+            AllowImportRules::No
+        } else {
+            AllowImportRules::Yes
+        };
         let new_rule = css_rules
             .insert_rule(
                 &parent_stylesheet.shared_lock,
@@ -134,15 +149,17 @@ impl CSSRuleList {
                 containing_rule_types,
                 parse_relative_rule_type,
                 loader.as_ref().map(|l| l as &dyn StyleStylesheetLoader),
-                AllowImportRules::Yes,
+                allow_import_rules,
             )
             .map_err(Convert::convert)?;
 
         let parent_stylesheet = &*self.parent_stylesheet;
         let dom_rule = CSSRule::new_specific(window, parent_stylesheet, new_rule, can_gc);
+        dom_rule.set_parent_rule(self.parent_rule.as_deref());
         self.dom_rules
             .borrow_mut()
             .insert(index, MutNullableDom::new(Some(&*dom_rule)));
+        parent_stylesheet.notify_invalidations();
         Ok(idx)
     }
 
@@ -162,7 +179,6 @@ impl CSSRuleList {
                     r.detach()
                 }
                 dom_rules.remove(index);
-                Ok(())
             },
             RulesSource::Keyframes(ref kf) => {
                 // https://drafts.csswg.org/css-animations/#dom-csskeyframesrule-deleterule
@@ -172,9 +188,32 @@ impl CSSRuleList {
                 }
                 dom_rules.remove(index);
                 kf.write_with(&mut guard).keyframes.remove(index);
-                Ok(())
             },
         }
+        drop(guard);
+        self.parent_stylesheet.notify_invalidations();
+        Ok(())
+    }
+
+    /// Detach the DOM objects of all the rules in this list, after the rules themselves
+    /// were replaced.
+    pub(crate) fn rules_replaced(&self) {
+        let RulesSource::Rules(ref rules) = self.rules else {
+            panic!("Can only replace the rules of CssRules-backed CSSRuleLists");
+        };
+        let mut dom_rules = self.dom_rules.borrow_mut();
+        for rule in dom_rules.iter() {
+            if let Some(rule) = rule.get() {
+                rule.detach();
+            }
+        }
+        let guard = self.parent_stylesheet.shared_lock().read();
+        *dom_rules = rules
+            .read_with(&guard)
+            .0
+            .iter()
+            .map(|_| MutNullableDom::new(None))
+            .collect();
     }
 
     /// Remove parent stylesheets from all children
@@ -191,7 +230,7 @@ impl CSSRuleList {
             rule.or_init(|| {
                 let parent_stylesheet = &self.parent_stylesheet;
                 let guard = parent_stylesheet.shared_lock().read();
-                match self.rules {
+                let dom_rule = match self.rules {
                     RulesSource::Rules(ref rules) => CSSRule::new_specific(
                         self.global().as_window(),
                         parent_stylesheet,
@@ -204,7 +243,9 @@ impl CSSRuleList {
                         rules.read_with(&guard).keyframes[idx as usize].clone(),
                         can_gc,
                     )),
-                }
+                };
+                dom_rule.set_parent_rule(self.parent_rule.as_deref());
+                dom_rule
             })
         })
     }
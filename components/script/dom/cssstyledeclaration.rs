@@ -45,6 +45,9 @@ pub(crate) struct CSSStyleDeclaration {
 #[derive(JSTraceable, MallocSizeOf)]
 #[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
 pub(crate) enum CSSStyleOwner {
+    /// The owner of the empty style that `getComputedStyle()` returns for pseudo-elements that
+    /// are valid but not supported yet. Its style declarations are always read-only.
+    Null,
     Element(Dom<Element>),
     CSSRule(
         Dom<CSSRule>,
@@ -121,12 +124,11 @@ impl CSSStyleOwner {
                 if changed {
                     // If this is changed, see also
                     // CSSStyleRule::SetSelectorText, which does the same thing.
-                    if let Some(owner) = rule.parent_stylesheet().get_owner() {
-                        owner.stylesheet_list_owner().invalidate_stylesheets();
-                    }
+                    rule.parent_stylesheet().notify_invalidations();
                 }
                 result
             },
+            CSSStyleOwner::Null => unreachable!("An empty style is never mutated"),
         }
     }
 
@@ -150,6 +152,7 @@ impl CSSStyleOwner {
                 let guard = rule.shared_lock().read();
                 f(pdb.read_with(&guard))
             },
+            CSSStyleOwner::Null => f(&PropertyDeclarationBlock::new()),
         }
     }
 
@@ -157,6 +160,7 @@ impl CSSStyleOwner {
         match *self {
             CSSStyleOwner::Element(ref el) => el.owner_window(),
             CSSStyleOwner::CSSRule(ref rule, _) => DomRoot::from_ref(rule.global().as_window()),
+            CSSStyleOwner::Null => unreachable!("An empty style is never mutated"),
         }
     }
 
@@ -173,6 +177,7 @@ impl CSSStyleOwner {
                     .clone(),
             )
             .clone(),
+            CSSStyleOwner::Null => unreachable!("An empty style is never mutated"),
         }
     }
 }
@@ -253,6 +258,7 @@ impl CSSStyleDeclaration {
             CSSStyleOwner::CSSRule(..) => {
                 panic!("get_computed_style called on CSSStyleDeclaration with a CSSRule owner")
             },
+            CSSStyleOwner::Null => DOMString::new(),
             CSSStyleOwner::Element(ref el) => {
                 let node = el.upcast::<Node>();
                 if !node.is_connected() {
@@ -388,6 +394,9 @@ pub(crate) static ENABLED_LONGHAND_PROPERTIES: LazyLock<Vec<LonghandId>> = LazyL
 impl CSSStyleDeclarationMethods<crate::DomTypeHolder> for CSSStyleDeclaration {
     // https://dev.w3.org/csswg/cssom/#dom-cssstyledeclaration-length
     fn Length(&self) -> u32 {
+        if matches!(self.owner, CSSStyleOwner::Null) {
+            return 0;
+        }
         if self.readonly {
             // Readonly style declarations are used for getComputedStyle.
             // TODO: include custom properties whose computed value is not the guaranteed-invalid value.
@@ -489,6 +498,9 @@ impl CSSStyleDeclarationMethods<crate::DomTypeHolder> for CSSStyleDeclaration {
 
     // https://dev.w3.org/csswg/cssom/#the-cssstyledeclaration-interface
     fn IndexedGetter(&self, index: u32) -> Option<DOMString> {
+        if matches!(self.owner, CSSStyleOwner::Null) {
+            return None;
+        }
         if self.readonly {
             // Readonly style declarations are used for getComputedStyle.
             // TODO: include custom properties whose computed value is not the guaranteed-invalid value.
@@ -516,13 +528,13 @@ impl CSSStyleDeclarationMethods<crate::DomTypeHolder> for CSSStyleDeclaration {
 
     // https://drafts.csswg.org/cssom/#dom-cssstyledeclaration-csstext
     fn SetCssText(&self, value: DOMString, can_gc: CanGc) -> ErrorResult {
-        let window = self.owner.window();
-
         // Step 1
         if self.readonly {
             return Err(Error::NoModificationAllowed);
         }
 
+        let window = self.owner.window();
+
         let quirks_mode = window.Document().quirks_mode();
         self.owner.mutate_associated_block(
             |pdb, _changed| {
@@ -3,22 +3,39 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::rc::Rc;
 
 use dom_struct::dom_struct;
+use js::rust::HandleObject;
 use servo_arc::Arc;
+use servo_url::ServoUrl;
 use style::shared_lock::SharedRwLock;
-use style::stylesheets::{CssRuleTypes, Stylesheet as StyleStyleSheet};
+use style::stylesheets::{
+    AllowImportRules, CssRuleTypes, Origin, Stylesheet as StyleStyleSheet, UrlExtraData,
+};
 
-use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::{
+    CSSStyleSheetInit, CSSStyleSheetMethods,
+};
+use crate::dom::bindings::codegen::Bindings::MediaListBinding::MediaListMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::MediaListOrString;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
-use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{
+    DomGlobal, reflect_dom_object, reflect_dom_object_with_proto,
+};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::medialist::MediaList;
 use crate::dom::node::NodeTraits;
+use crate::dom::promise::Promise;
 use crate::dom::stylesheet::StyleSheet;
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
@@ -31,22 +48,45 @@ pub(crate) struct CSSStyleSheet {
     #[no_trace]
     style_stylesheet: Arc<StyleStyleSheet>,
     origin_clean: Cell<bool>,
+
+    /// The document that a stylesheet created with `new CSSStyleSheet()` was constructed in,
+    /// which is the only one where it can be adopted.
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructor-document>
+    constructor_document: Option<Dom<Document>>,
+
+    /// The URL that the rules of a constructed stylesheet are resolved against.
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-stylesheet-base-url>
+    #[no_trace]
+    base_url: Option<ServoUrl>,
+
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-disallow-modification-flag>
+    disallow_modification: Cell<bool>,
+
+    /// The documents and shadow roots that adopted this stylesheet, which have to be restyled
+    /// when it changes.
+    adopters: DomRefCell<Vec<StyleSheetListOwner>>,
 }
 
 impl CSSStyleSheet {
     fn new_inherited(
-        owner: &Element,
+        owner: Option<&Element>,
+        constructor_document: Option<&Document>,
         type_: DOMString,
         href: Option<DOMString>,
         title: Option<DOMString>,
         stylesheet: Arc<StyleStyleSheet>,
+        base_url: Option<ServoUrl>,
     ) -> CSSStyleSheet {
         CSSStyleSheet {
             stylesheet: StyleSheet::new_inherited(type_, href, title),
-            owner: MutNullableDom::new(Some(owner)),
+            owner: MutNullableDom::new(owner),
             rulelist: MutNullableDom::new(None),
             style_stylesheet: stylesheet,
             origin_clean: Cell::new(true),
+            constructor_document: constructor_document.map(Dom::from_ref),
+            base_url,
+            disallow_modification: Cell::new(false),
+            adopters: DomRefCell::new(Vec::new()),
         }
     }
 
@@ -62,9 +102,41 @@ impl CSSStyleSheet {
     ) -> DomRoot<CSSStyleSheet> {
         reflect_dom_object(
             Box::new(CSSStyleSheet::new_inherited(
-                owner, type_, href, title, stylesheet,
+                Some(owner),
+                None,
+                type_,
+                href,
+                title,
+                stylesheet,
+                None,
+            )),
+            window,
+            can_gc,
+        )
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn new_constructed(
+        window: &Window,
+        proto: Option<HandleObject>,
+        document: &Document,
+        location: &ServoUrl,
+        base_url: ServoUrl,
+        stylesheet: Arc<StyleStyleSheet>,
+        can_gc: CanGc,
+    ) -> DomRoot<CSSStyleSheet> {
+        reflect_dom_object_with_proto(
+            Box::new(CSSStyleSheet::new_inherited(
+                None,
+                Some(document),
+                DOMString::from("text/css"),
+                Some(DOMString::from(location.as_str())),
+                None,
+                stylesheet,
+                Some(base_url),
             )),
             window,
+            proto,
             can_gc,
         )
     }
@@ -75,6 +147,7 @@ impl CSSStyleSheet {
             CSSRuleList::new(
                 self.global().as_window(),
                 self,
+                None,
                 RulesSource::Rules(rules),
                 CanGc::note(),
             )
@@ -90,12 +163,65 @@ impl CSSStyleSheet {
     }
 
     pub(crate) fn set_disabled(&self, disabled: bool) {
-        if self.style_stylesheet.set_disabled(disabled) && self.get_owner().is_some() {
-            self.get_owner()
-                .unwrap()
-                .stylesheet_list_owner()
-                .invalidate_stylesheets();
+        if self.style_stylesheet.set_disabled(disabled) {
+            self.notify_invalidations();
+        }
+    }
+
+    /// Mark the stylesheets of the document or shadow root of the owner of this stylesheet,
+    /// and of those that adopted it, as changed, after this stylesheet changed.
+    pub(crate) fn notify_invalidations(&self) {
+        if let Some(owner) = self.get_owner() {
+            owner.stylesheet_list_owner().invalidate_stylesheets();
+        }
+        for adopter in self.adopters.borrow().iter() {
+            adopter.invalidate_stylesheets();
+        }
+    }
+
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructed-flag>
+    pub(crate) fn is_constructed(&self) -> bool {
+        self.constructor_document.is_some()
+    }
+
+    /// Whether this stylesheet was constructed in `document`, so that it can be adopted there.
+    pub(crate) fn is_constructed_in(&self, document: &Document) -> bool {
+        self.constructor_document.as_deref() == Some(document)
+    }
+
+    pub(crate) fn add_adopter(&self, adopter: &StyleSheetListOwner) {
+        let mut adopters = self.adopters.borrow_mut();
+        if !adopters.contains(adopter) {
+            adopters.push(adopter.clone());
+        }
+    }
+
+    pub(crate) fn remove_adopter(&self, adopter: &StyleSheetListOwner) {
+        self.adopters
+            .borrow_mut()
+            .retain(|candidate| candidate != adopter);
+    }
+
+    /// Replace the rules of a constructed stylesheet with those parsed from `text`, leaving out
+    /// any `@import` rule.
+    /// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-replacesync>
+    fn replace_rules(&self, text: &str) {
+        let base_url = self
+            .base_url
+            .as_ref()
+            .expect("Only constructed stylesheets can have their rules replaced");
+        StyleStyleSheet::update_from_str(
+            &self.style_stylesheet,
+            text,
+            UrlExtraData(base_url.get_arc()),
+            None,
+            self.global().as_window().css_error_reporter(),
+            AllowImportRules::No,
+        );
+        if let Some(rulelist) = self.rulelist.get() {
+            rulelist.rules_replaced();
         }
+        self.notify_invalidations();
     }
 
     pub(crate) fn set_owner(&self, value: Option<&Element>) {
@@ -110,6 +236,10 @@ impl CSSStyleSheet {
         &self.style_stylesheet
     }
 
+    pub(crate) fn style_stylesheet_arc(&self) -> &Arc<StyleStyleSheet> {
+        &self.style_stylesheet
+    }
+
     pub(crate) fn set_origin_clean(&self, origin_clean: bool) {
         self.origin_clean.set(origin_clean);
     }
@@ -125,6 +255,63 @@ impl CSSStyleSheet {
 }
 
 impl CSSStyleSheetMethods<crate::DomTypeHolder> for CSSStyleSheet {
+    /// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssstylesheet>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        options: &CSSStyleSheetInit,
+    ) -> Fallible<DomRoot<CSSStyleSheet>> {
+        let document = window.Document();
+
+        // Step 2. Set sheet’s location to the base URL of the associated Document for the
+        // current global object.
+        let location = document.base_url();
+
+        // Step 3. Set sheet’s stylesheet base URL to the baseURL attribute value from options.
+        // If it is relative, it is resolved against the location, and if it can not be parsed,
+        // a NotAllowedError is thrown.
+        let base_url = match options.baseURL {
+            Some(ref base_url) => location.join(base_url).map_err(|_| Error::NotAllowed)?,
+            None => location.clone(),
+        };
+
+        // Step 5. If the media attribute of options is a string, create a MediaList object
+        // from the string and assign it as sheet’s media. Otherwise, serialize a media query
+        // list from the attribute and then create a MediaList object from the resulting
+        // string and set it as sheet’s media.
+        let media = match options.media {
+            MediaListOrString::MediaList(ref media) => media.MediaText(),
+            MediaListOrString::String(ref media) => media.clone(),
+        };
+        let shared_lock = document.style_shared_lock().clone();
+        let media = Arc::new(shared_lock.wrap(MediaList::parse_media_list(&media, window)));
+        let stylesheet = Arc::new(StyleStyleSheet::from_str(
+            "",
+            UrlExtraData(base_url.get_arc()),
+            Origin::Author,
+            media,
+            shared_lock,
+            None,
+            window.css_error_reporter(),
+            document.quirks_mode(),
+            AllowImportRules::No,
+        ));
+
+        // Step 1 and 4. Construct a new CSSStyleSheet object sheet, with no parent, owner node
+        // or owner rule, a null title, its origin-clean and constructed flags set, and the
+        // associated Document as its constructor document.
+        let sheet = CSSStyleSheet::new_constructed(
+            window, proto, &document, &location, base_url, stylesheet, can_gc,
+        );
+
+        // Step 6. If the disabled attribute of options is true, set sheet’s disabled flag.
+        sheet.set_disabled(options.disabled);
+
+        // Step 7. Return sheet.
+        Ok(sheet)
+    }
+
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssrules
     fn GetCssRules(&self) -> Fallible<DomRoot<CSSRuleList>> {
         if !self.origin_clean.get() {
@@ -138,6 +325,9 @@ impl CSSStyleSheetMethods<crate::DomTypeHolder> for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
+        if self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
         self.rulelist()
             .insert_rule(&rule, index, CssRuleTypes::default(), None, CanGc::note())
     }
@@ -147,6 +337,59 @@ impl CSSStyleSheetMethods<crate::DomTypeHolder> for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
+        if self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
         self.rulelist().remove_rule(index)
     }
+
+    /// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-replace>
+    fn Replace(&self, text: USVString, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1. Let promise be a promise.
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 2. If the constructed flag is not set, or the disallow modification flag is
+        // set, reject promise with a NotAllowedError DOMException and return promise.
+        if !self.is_constructed() || self.disallow_modification.get() {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return promise;
+        }
+
+        // Step 3. Set the disallow modification flag.
+        self.disallow_modification.set(true);
+
+        // Step 4. In parallel, parse the rules from text, leaving out any @import rule, and
+        // queue a task to replace the rules of the stylesheet with them.
+        let sheet = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        self.global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task!(replace_stylesheet: move || {
+                let sheet = sheet.root();
+                // Step 4.3.1. Replace the rules of sheet with rules.
+                sheet.replace_rules(&text);
+                // Step 4.3.2. Unset sheet’s disallow modification flag.
+                sheet.disallow_modification.set(false);
+                // Step 4.3.3. Resolve promise with sheet.
+                trusted_promise.root().resolve_native(&sheet, CanGc::note());
+            }));
+
+        // Step 5. Return promise.
+        promise
+    }
+
+    /// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-replacesync>
+    fn ReplaceSync(&self, text: USVString) -> ErrorResult {
+        // Step 1. If the constructed flag is not set, or the disallow modification flag is
+        // set, throw a NotAllowedError DOMException.
+        if !self.is_constructed() || self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
+
+        // Step 2 to 4. Parse the rules from text, leaving out any @import rule, and replace
+        // the rules of the stylesheet with them.
+        self.replace_rules(&text);
+        Ok(())
+    }
 }
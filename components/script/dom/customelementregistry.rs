@@ -172,6 +172,7 @@ impl CustomElementRegistry {
         // Step 4
         Ok(LifecycleCallbacks {
             connected_callback: get_callback(cx, prototype, c"connectedCallback")?,
+            connected_move_callback: get_callback(cx, prototype, c"connectedMoveCallback")?,
             disconnected_callback: get_callback(cx, prototype, c"disconnectedCallback")?,
             adopted_callback: get_callback(cx, prototype, c"adoptedCallback")?,
             attribute_changed_callback: get_callback(cx, prototype, c"attributeChangedCallback")?,
@@ -633,6 +634,9 @@ pub(crate) struct LifecycleCallbacks {
     #[ignore_malloc_size_of = "Rc"]
     connected_callback: Option<Rc<Function>>,
 
+    #[ignore_malloc_size_of = "Rc"]
+    connected_move_callback: Option<Rc<Function>>,
+
     #[ignore_malloc_size_of = "Rc"]
     disconnected_callback: Option<Rc<Function>>,
 
@@ -1043,6 +1047,7 @@ impl CustomElementReaction {
 
 pub(crate) enum CallbackReaction {
     Connected,
+    ConnectedMove,
     Disconnected,
     Adopted(DomRoot<Document>, DomRoot<Document>),
     AttributeChanged(LocalName, Option<DOMString>, Option<DOMString>, Namespace),
@@ -1146,6 +1151,25 @@ impl CustomElementReactionStack {
             CallbackReaction::Connected => {
                 (definition.callbacks.connected_callback.clone(), Vec::new())
             },
+            CallbackReaction::ConnectedMove => {
+                let callback = definition.callbacks.connected_move_callback.clone();
+                // Step 3. If reaction is "connectedMoveCallback" and callback is null, then
+                // enqueue the disconnected and then the connected reaction instead.
+                if callback.is_none() {
+                    self.enqueue_callback_reaction(
+                        element,
+                        CallbackReaction::Disconnected,
+                        Some(definition.clone()),
+                    );
+                    self.enqueue_callback_reaction(
+                        element,
+                        CallbackReaction::Connected,
+                        Some(definition),
+                    );
+                    return;
+                }
+                (callback, Vec::new())
+            },
             CallbackReaction::Disconnected => (
                 definition.callbacks.disconnected_callback.clone(),
                 Vec::new(),
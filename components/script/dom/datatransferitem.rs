@@ -86,7 +86,7 @@ impl DataTransferItemMethods<crate::DomTypeHolder> for DataTransferItem {
         self.item_kind()
             .map_or(DOMString::new(), |item| match *item {
                 Kind::Text { .. } => DOMString::from("string"),
-                Kind::File { .. } => DOMString::from("file"),
+                Kind::File { .. } | Kind::DroppedFile { .. } => DOMString::from("file"),
             })
     }
 
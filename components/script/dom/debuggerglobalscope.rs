@@ -0,0 +1,311 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+#[cfg(feature = "webgpu")]
+use std::sync::Arc;
+
+use base::id::{PipelineId, PipelineIndex, PipelineNamespaceId};
+use devtools_traits::{
+    BreakpointLocation, EvaluateJSReply, FrameInfo, PauseInfo, PauseReason, ResumeLimit, ScopeInfo,
+    ScriptToDevtoolsControlMsg, SourceInfo, SourceLocation,
+};
+use dom_struct::dom_struct;
+use embedder_traits::resources::{self, Resource};
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::JS_DefineDebuggerObject;
+use js::jsval::UndefinedValue;
+use js::rust::Runtime;
+use net_traits::ResourceThreads;
+use profile_traits::{mem, time};
+use script_traits::ScriptToConstellationChan;
+use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
+use uuid::Uuid;
+
+use crate::dom::bindings::callback::ExceptionHandling::Report;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DebuggerGlobalScopeBinding;
+use crate::dom::bindings::codegen::Bindings::DebuggerGlobalScopeBinding::{
+    DebuggerGlobalScopeMethods, DebuggerHooks, DebuggerPause, DebuggerResumeLimit, DebuggerSource,
+    DebuggerValue, PipelineIdInit,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+#[cfg(feature = "webgpu")]
+use crate::dom::webgpu::identityhub::IdentityHub;
+use crate::dom::window::Window;
+use crate::realms::enter_realm;
+use crate::script_module::ScriptFetchOptions;
+use crate::script_runtime::{CanGc, JSContext};
+use crate::script_thread::ScriptThread;
+
+// check-tidy: no specs after this line
+
+/// The global of `debugger.js`, which drives SpiderMonkey's Debugger API for the devtools
+/// debugger. There is one per script thread, and every window of the thread is one of its
+/// debuggees.
+#[dom_struct]
+pub(crate) struct DebuggerGlobalScope {
+    global_scope: GlobalScope,
+    /// The hooks that `debugger.js` registered to be told about the requests of the client.
+    #[ignore_malloc_size_of = "Rc<T> is hard"]
+    hooks: DomRefCell<Option<Rc<DebuggerHooks>>>,
+}
+
+impl DebuggerGlobalScope {
+    #[allow(unsafe_code, clippy::too_many_arguments)]
+    pub(crate) fn new(
+        runtime: &Runtime,
+        pipeline_id: PipelineId,
+        devtools_chan: IpcSender<ScriptToDevtoolsControlMsg>,
+        mem_profiler_chan: mem::ProfilerChan,
+        time_profiler_chan: time::ProfilerChan,
+        script_to_constellation_chan: ScriptToConstellationChan,
+        resource_threads: ResourceThreads,
+        user_agent: Cow<'static, str>,
+        #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
+        can_gc: CanGc,
+    ) -> DomRoot<DebuggerGlobalScope> {
+        let global = Box::new(DebuggerGlobalScope {
+            global_scope: GlobalScope::new_inherited(
+                pipeline_id,
+                Some(devtools_chan),
+                mem_profiler_chan,
+                time_profiler_chan,
+                script_to_constellation_chan,
+                resource_threads,
+                MutableOrigin::new(ImmutableOrigin::new_opaque()),
+                None,
+                Default::default(),
+                user_agent,
+                #[cfg(feature = "webgpu")]
+                gpu_id_hub,
+                None,
+                false,
+            ),
+            hooks: Default::default(),
+        });
+        let cx = unsafe { JSContext::from_ptr(runtime.cx()) };
+        let global =
+            unsafe { DebuggerGlobalScopeBinding::Wrap::<crate::DomTypeHolder>(cx, global) };
+
+        let _realm = enter_realm(&*global);
+        rooted!(in(*cx) let global_object = global.reflector().get_jsobject().get());
+        assert!(unsafe { JS_DefineDebuggerObject(*cx, global_object.handle().into()) });
+
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        let global_scope = global.upcast::<GlobalScope>();
+        if !global_scope.evaluate_js_on_global_with_result(
+            &resources::read_string(Resource::DebuggerJS),
+            rval.handle_mut(),
+            ScriptFetchOptions::default_classic_script(global_scope),
+            global_scope.api_base_url(),
+            can_gc,
+        ) {
+            warn!("Failed to run the debugger script.");
+        }
+        global
+    }
+
+    pub(crate) fn get_url(&self) -> ServoUrl {
+        ServoUrl::parse("about:internal/debugger").unwrap()
+    }
+
+    fn hooks(&self) -> Option<Rc<DebuggerHooks>> {
+        self.hooks.borrow().clone()
+    }
+
+    /// Let the debugger see the scripts that run in a new window.
+    pub(crate) fn add_debuggee(&self, window: &Window, can_gc: CanGc) {
+        let Some(hooks) = self.hooks() else {
+            return;
+        };
+        let pipeline_id = window.pipeline_id();
+        let pipeline_id = PipelineIdInit {
+            namespaceId: pipeline_id.namespace_id.0,
+            index: pipeline_id.index.0.get(),
+        };
+        let global = window.reflector().get_jsobject().get();
+        let _ = hooks.AddDebuggee__(global, &pipeline_id, Report, can_gc);
+    }
+
+    pub(crate) fn set_breakpoint(&self, breakpoint: BreakpointLocation, can_gc: CanGc) {
+        let Some(hooks) = self.hooks() else {
+            return;
+        };
+        let _ = hooks.SetBreakpoint__(
+            breakpoint.url.into(),
+            breakpoint.line,
+            breakpoint.column,
+            Report,
+            can_gc,
+        );
+    }
+
+    pub(crate) fn clear_breakpoint(&self, breakpoint: BreakpointLocation, can_gc: CanGc) {
+        let Some(hooks) = self.hooks() else {
+            return;
+        };
+        let _ = hooks.ClearBreakpoint__(
+            breakpoint.url.into(),
+            breakpoint.line,
+            breakpoint.column,
+            Report,
+            can_gc,
+        );
+    }
+
+    /// The positions where breakpoints can be set in a source, sorted by line and column.
+    pub(crate) fn possible_breakpoints(
+        &self,
+        spidermonkey_id: u32,
+        can_gc: CanGc,
+    ) -> Vec<SourceLocation> {
+        let Some(hooks) = self.hooks() else {
+            return vec![];
+        };
+        hooks
+            .PossibleBreakpoints__(spidermonkey_id, Report, can_gc)
+            .map(|locations| {
+                locations
+                    .into_iter()
+                    .map(|location| SourceLocation {
+                        line: location.line,
+                        column: location.column,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Pause as soon as any debuggee runs JS code.
+    pub(crate) fn interrupt(&self, can_gc: CanGc) {
+        let Some(hooks) = self.hooks() else {
+            return;
+        };
+        let _ = hooks.Interrupt__(Report, can_gc);
+    }
+
+    fn send_to_devtools(&self, msg: ScriptToDevtoolsControlMsg) {
+        if let Some(chan) = self.global_scope.devtools_chan() {
+            let _ = chan.send(msg);
+        }
+    }
+}
+
+fn pipeline_id_from_init(init: &PipelineIdInit) -> Option<PipelineId> {
+    Some(PipelineId {
+        namespace_id: PipelineNamespaceId(init.namespaceId),
+        index: PipelineIndex(NonZeroU32::new(init.index)?),
+    })
+}
+
+fn value_from_debugger_value(value: &DebuggerValue) -> EvaluateJSReply {
+    match &*value.type_ {
+        "undefined" => EvaluateJSReply::VoidValue,
+        "null" => EvaluateJSReply::NullValue,
+        "boolean" => EvaluateJSReply::BooleanValue(value.booleanValue.unwrap_or_default()),
+        "number" => EvaluateJSReply::NumberValue(value.numberValue.unwrap_or(f64::NAN)),
+        "object" => EvaluateJSReply::ActorValue {
+            class: value
+                .objectClass
+                .as_ref()
+                .map(|class| class.to_string())
+                .unwrap_or_default(),
+            uuid: Uuid::new_v4().to_string(),
+        },
+        // Symbols and big integers are shown as their description.
+        _ => EvaluateJSReply::StringValue(
+            value
+                .stringValue
+                .as_ref()
+                .map(|string| string.to_string())
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+impl DebuggerGlobalScopeMethods<crate::DomTypeHolder> for DebuggerGlobalScope {
+    fn SetHooks(&self, hooks: Rc<DebuggerHooks>) {
+        *self.hooks.borrow_mut() = Some(hooks);
+    }
+
+    fn NotifyNewSource(&self, source: &DebuggerSource) {
+        let Some(pipeline_id) = pipeline_id_from_init(&source.pipelineId) else {
+            return;
+        };
+        // Sources without a URL, such as the ones of `eval`, are named after their id.
+        let url = ServoUrl::parse(&source.url).unwrap_or_else(|_| {
+            ServoUrl::parse(&format!("about:internal/source/{}", source.spidermonkeyId)).unwrap()
+        });
+        self.send_to_devtools(ScriptToDevtoolsControlMsg::CreateSourceActor(
+            pipeline_id,
+            SourceInfo {
+                url,
+                introduction_type: source.introductionType.as_ref().map(|t| t.to_string()),
+                source_map_url: source.sourceMapUrl.as_ref().map(|url| url.to_string()),
+                content: source.text.to_string(),
+                spidermonkey_id: source.spidermonkeyId,
+            },
+        ));
+    }
+
+    fn Pause(&self, pause: &DebuggerPause, can_gc: CanGc) -> DebuggerResumeLimit {
+        let Some(pipeline_id) = pipeline_id_from_init(&pause.pipelineId) else {
+            return DebuggerResumeLimit::Resume;
+        };
+        let reason = match &*pause.reason {
+            "breakpoint" => PauseReason::Breakpoint,
+            "debuggerStatement" => PauseReason::DebuggerStatement,
+            "interrupted" => PauseReason::Interrupted,
+            _ => PauseReason::ResumeLimit,
+        };
+        let frames = pause
+            .frames
+            .iter()
+            .map(|frame| FrameInfo {
+                display_name: frame.displayName.to_string(),
+                source_id: frame.sourceId,
+                location: SourceLocation {
+                    line: frame.line,
+                    column: frame.column,
+                },
+                scopes: frame
+                    .scopes
+                    .iter()
+                    .map(|scope| ScopeInfo {
+                        kind: scope.kind.to_string(),
+                        function_name: scope.functionName.as_ref().map(|name| name.to_string()),
+                        bindings: scope
+                            .bindings
+                            .iter()
+                            .map(|binding| {
+                                (
+                                    binding.name.to_string(),
+                                    value_from_debugger_value(&binding.value),
+                                )
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        self.send_to_devtools(ScriptToDevtoolsControlMsg::DebuggerPaused(
+            pipeline_id,
+            PauseInfo { reason, frames },
+        ));
+
+        match ScriptThread::run_paused_debugger(pipeline_id, can_gc) {
+            Some(ResumeLimit::None) => DebuggerResumeLimit::Resume,
+            Some(ResumeLimit::StepOver) => DebuggerResumeLimit::StepOver,
+            Some(ResumeLimit::StepIn) => DebuggerResumeLimit::StepIn,
+            Some(ResumeLimit::StepOut) => DebuggerResumeLimit::StepOut,
+            None => DebuggerResumeLimit::Terminate,
+        }
+    }
+}
@@ -10,11 +10,13 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
 use std::f64::consts::PI;
 use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::slice::from_ref;
 use std::sync::{LazyLock, Mutex};
 use std::time::{Duration, Instant};
 
+use app_units::Au;
 use base::cross_process_instant::CrossProcessInstant;
 use base::id::WebViewId;
 use canvas_traits::canvas::CanvasId;
@@ -24,51 +26,59 @@ use constellation_traits::{AnimationTickType, CompositorHitTestResult};
 use content_security_policy::{self as csp, CspList, PolicyDisposition};
 use cookie::Cookie;
 use cssparser::match_ignore_ascii_case;
-use devtools_traits::ScriptToDevtoolsControlMsg;
+use devtools_traits::{HighlighterKind, ReadyState, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
 use embedder_traits::{
-    AllowOrDeny, ContextMenuResult, EditingActionEvent, EmbedderMsg, ImeEvent, InputEvent,
-    LoadStatus, MouseButton, MouseButtonAction, MouseButtonEvent, TouchEvent, TouchEventType,
-    TouchId, WheelEvent,
+    AllowOrDeny, EditingActionEvent, EmbedderMsg, FileDropEvent, ImeEvent, InputEvent, LoadStatus,
+    MouseButton, MouseButtonAction, MouseButtonEvent, MouseMoveEvent, ScreenId, TouchEvent,
+    TouchEventType, TouchId, WheelEvent,
 };
 use encoding_rs::{Encoding, UTF_8};
 use euclid::default::{Point2D, Rect, Size2D};
 use html5ever::{LocalName, Namespace, QualName, local_name, namespace_url, ns};
 use hyper_serde::Serde;
 use ipc_channel::ipc;
-use js::rust::{HandleObject, HandleValue};
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
 use keyboard_types::{Code, Key, KeyState, Modifiers};
 use metrics::{InteractiveFlag, InteractiveWindow, ProgressiveWebMetrics};
 use mime::{self, Mime};
 use net_traits::CookieSource::NonHTTP;
 use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::policy_container::PolicyContainer;
 use net_traits::pub_domains::is_pub_domain;
 use net_traits::request::{InsecureRequestsPolicy, RequestBuilder};
 use net_traits::response::HttpsState;
-use net_traits::{FetchResponseListener, IpcSend, ReferrerPolicy};
+use net_traits::{CookieContext, CoreResourceMsg, FetchResponseListener, IpcSend, ReferrerPolicy};
 use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
 use profile_traits::ipc as profile_ipc;
 use profile_traits::time::TimerMetadataFrameType;
 use script_bindings::interfaces::DocumentHelpers;
-use script_layout_interface::{PendingRestyle, TrustedNodeAddress};
+use script_layout_interface::{
+    InspectorHighlight, PendingRestyle, TopLayerElement, TrustedNodeAddress,
+};
 use script_traits::{
-    AnimationState, ConstellationInputEvent, DocumentActivity, ProgressiveWebMetricType, ScriptMsg,
+    AnimationState, ConstellationInputEvent, DocumentActivity, LoadData, LoadOrigin,
+    ProgressiveWebMetricType, SandboxingFlagSet, ScriptMsg,
 };
 use servo_arc::Arc;
-use servo_config::pref;
+use servo_config::{pref, site_pref};
 use servo_media::{ClientContextId, ServoMedia};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::attr::AttrValue;
 use style::context::QuirksMode;
+use style::dom::OpaqueNode;
+use style::dom_apis;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::selector_parser::Snapshot;
-use style::shared_lock::SharedRwLock as StyleSharedRwLock;
+use style::shared_lock::{SharedRwLock as StyleSharedRwLock, ToCssWithGuard};
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
-use style::stylesheets::{Origin, OriginSet, Stylesheet};
+use style::stylesheets::{CssRule, OriginSet, Stylesheet};
 use stylo_atoms::Atom;
+use stylo_dom::ElementState;
 use url::Host;
 use uuid::Uuid;
 #[cfg(feature = "webgpu")]
@@ -79,15 +89,24 @@ use super::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathEvaluatorMet
 use super::canvasrenderingcontext2d::CanvasRenderingContext2D;
 use super::clipboardevent::ClipboardEventType;
 use super::performancepainttiming::PerformancePaintTiming;
+use crate::adopted_stylesheets::AdoptedStyleSheetArray;
 use crate::animation_timeline::AnimationTimeline;
 use crate::animations::Animations;
+use crate::autofill::notify_autofill_field_focused;
 use crate::canvas_context::CanvasContext as _;
+use crate::caret_browsing;
+use crate::context_menu::show_context_menu;
+use crate::devtools::PendingDomMutation;
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEvent_Binding::BeforeUnloadEventMethods;
+use crate::dom::bindings::codegen::Bindings::CaretPositionBinding::{
+    CaretPositionFromPointOptions, CaretPositionMethods,
+};
+use crate::dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState, DocumentVisibilityState, NamedPropertyValue,
 };
@@ -95,6 +114,7 @@ use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventM
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElement_Binding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::IntersectionObserverBinding::IntersectionObserverMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::Navigator_Binding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
@@ -120,6 +140,8 @@ use crate::dom::bindings::weakref::WeakRef;
 use crate::dom::bindings::xmlname::{
     matches_name_production, namespace_from_domstring, validate_and_extract,
 };
+use crate::dom::blob::Blob;
+use crate::dom::caretposition::CaretPosition;
 use crate::dom::cdatasection::CDATASection;
 use crate::dom::clipboardevent::ClipboardEvent;
 use crate::dom::comment::Comment;
@@ -129,15 +151,19 @@ use crate::dom::customelementregistry::CustomElementDefinition;
 use crate::dom::customevent::CustomEvent;
 use crate::dom::datatransfer::DataTransfer;
 use crate::dom::documentfragment::DocumentFragment;
-use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
+use crate::dom::documentorshadowroot::{
+    DocumentOrShadowRoot, StyleSheetInDocument, StylesheetSource,
+};
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::element::{
     CustomElementCreationMode, Element, ElementCreator, ElementPerformFullscreenEnter,
-    ElementPerformFullscreenExit,
+    ElementPerformFullscreenExit, SelectorWrapper,
 };
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::file::File;
 use crate::dom::focusevent::FocusEvent;
 use crate::dom::fontfaceset::FontFaceSet;
 use crate::dom::globalscope::GlobalScope;
@@ -147,6 +173,7 @@ use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
@@ -171,7 +198,7 @@ use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
 use crate::dom::performanceentry::PerformanceEntry;
-use crate::dom::pointerevent::PointerEvent;
+use crate::dom::pointerevent::{MOUSE_POINTER_ID, PointerEvent, PointerInput};
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
@@ -181,6 +208,7 @@ use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::storageevent::StorageEvent;
 use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
+use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::dom::text::Text;
 use crate::dom::touch::Touch;
 use crate::dom::touchevent::TouchEvent as DomTouchEvent;
@@ -188,6 +216,7 @@ use crate::dom::touchlist::TouchList;
 use crate::dom::treewalker::TreeWalker;
 use crate::dom::types::VisibilityStateEntry;
 use crate::dom::uievent::UIEvent;
+use crate::dom::userscripts;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 #[cfg(feature = "webgpu")]
@@ -198,17 +227,20 @@ use crate::dom::windowproxy::WindowProxy;
 use crate::dom::xpathevaluator::XPathEvaluator;
 use crate::drag_data_store::{DragDataStore, Kind, Mode};
 use crate::fetch::FetchCanceller;
+use crate::focus_navigation;
 use crate::iframe_collection::IFrameCollection;
 use crate::image_animation::ImageAnimationManager;
 use crate::messaging::{CommonScriptMsg, MainThreadScriptMsg};
 use crate::network_listener::{NetworkListener, PreInvoke};
 use crate::realms::{AlreadyInRealm, InRealm, enter_realm};
-use crate::script_runtime::{CanGc, ScriptThreadEventCategory};
+use crate::script_runtime::{CanGc, JSContext, ScriptThreadEventCategory};
 use crate::script_thread::{ScriptThread, with_script_thread};
+use crate::speculation_rules::{SpeculationRuleSet, SpeculativeLoads, prefetch};
+use crate::spellcheck::show_spelling_context_menu;
 use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::TaskSourceName;
-use crate::timers::OneshotTimerCallback;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
 
 /// The number of times we are allowed to see spurious `requestAnimationFrame()` calls before
 /// falling back to fake ones.
@@ -219,6 +251,9 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// How long a touch point has to be held down without moving to show the context menu.
+const LONG_PRESS_DELAY: Duration = Duration::from_millis(500);
+
 pub(crate) enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -245,6 +280,46 @@ impl FireMouseEventType {
     }
 }
 
+/// The nodes that the `mouseenter` or `mouseleave` events, or their pointer event counterparts,
+/// are fired at when the pointer enters or leaves `event_target` from or to `related_target`, in
+/// the order in which they are fired.
+fn enter_leave_targets(
+    event_target: &Node,
+    related_target: Option<&Node>,
+    event_type: FireMouseEventType,
+) -> Vec<DomRoot<Node>> {
+    assert!(matches!(
+        event_type,
+        FireMouseEventType::Enter | FireMouseEventType::Leave
+    ));
+
+    let common_ancestor = match related_target {
+        Some(related_target) => event_target
+            .common_ancestor(related_target, ShadowIncluding::No)
+            .unwrap_or_else(|| DomRoot::from_ref(event_target)),
+        None => DomRoot::from_ref(event_target),
+    };
+
+    // We need to create a target chain in case the event target shares
+    // its boundaries with its ancestors.
+    let mut targets = vec![];
+    let mut current = Some(DomRoot::from_ref(event_target));
+    while let Some(node) = current {
+        if node == common_ancestor {
+            break;
+        }
+        current = node.GetParentNode();
+        targets.push(node);
+    }
+
+    // The order for dispatching mouseenter events starts from the topmost
+    // common ancestor of the event target and the related target.
+    if event_type == FireMouseEventType::Enter {
+        targets.reverse();
+    }
+    targets
+}
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub(crate) enum IsHTMLDocument {
     HTMLDocument,
@@ -261,6 +336,21 @@ enum FocusTransaction {
     InTransaction(Option<Dom<Element>>),
 }
 
+/// The state of a pointer that has been used in a document.
+/// <https://w3c.github.io/pointerevents/#dfn-active-pointer>
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct ActivePointer {
+    /// The buttons of the pointer that were pressed as of its last input.
+    buttons: u16,
+    /// <https://w3c.github.io/pointerevents/#dfn-pointer-capture-target-override>
+    capture_target_override: Option<Dom<Element>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pending_capture_target_override: Option<Dom<Element>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-prevent-mouse-event-flag>
+    prevent_mouse_event: bool,
+}
+
 /// Information about a declarative refresh
 #[derive(JSTraceable, MallocSizeOf)]
 pub(crate) enum DeclarativeRefresh {
@@ -320,6 +410,10 @@ pub(crate) struct Document {
     #[custom_trace]
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopted_stylesheets: DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    adopted_stylesheets_array: AdoptedStyleSheetArray,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -327,6 +421,16 @@ pub(crate) struct Document {
     focus_transaction: DomRefCell<FocusTransaction>,
     /// The element that currently has the document focus context.
     focused: MutNullableDom<Element>,
+    /// <https://html.spec.whatwg.org/multipage/#sequential-focus-navigation-starting-point>
+    sequential_focus_navigation_starting_point: MutNullableDom<Node>,
+    /// Whether the user last interacted with this document with the keyboard rather than
+    /// with a pointer, which decides whether focused elements match `:focus-visible`.
+    had_keyboard_interaction: Cell<bool>,
+    /// The connected form controls that were reported to the embedder for autofill, by the
+    /// index that identifies them when the embedder fills them in.
+    autofill_fields: DomRefCell<HashMapTracedValues<usize, Dom<Element>>>,
+    /// The index of the next form control that is reported to the embedder for autofill.
+    next_autofill_field_index: Cell<usize>,
     /// The script element that is currently executing.
     current_script: MutNullableDom<HTMLScriptElement>,
     /// <https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script>
@@ -342,6 +446,9 @@ pub(crate) struct Document {
     /// <https://html.spec.whatwg.org/multipage/#concept-n-noscript>
     /// True if scripting is enabled for all scripts in this document
     scripting_enabled: bool,
+    /// <https://html.spec.whatwg.org/multipage/#active-sandboxing-flag-set>
+    #[no_trace]
+    active_sandboxing_flag_set: Cell<SandboxingFlagSet>,
     /// <https://html.spec.whatwg.org/multipage/#animation-frame-callback-identifier>
     /// Current identifier of animation frame callback
     animation_frame_ident: Cell<u32>,
@@ -365,6 +472,12 @@ pub(crate) struct Document {
     /// Information on elements needing restyle to ship over to layout when the
     /// time comes.
     pending_restyles: DomRefCell<HashMap<Dom<Element>, NoTrace<PendingRestyle>>>,
+    /// The mutations of this document's DOM that the devtools inspector has not been told
+    /// about yet, or `None` if it doesn't watch this document.
+    devtools_dom_mutations: DomRefCell<Option<Vec<PendingDomMutation>>>,
+    /// The nodes that the devtools inspector highlights, by the kind of overlays drawn over
+    /// them.
+    devtools_highlighted_nodes: DomRefCell<HashMapTracedValues<HighlighterKind, Dom<Node>>>,
     /// This flag will be true if the `Document` needs to be painted again
     /// during the next full layout attempt due to some external change such as
     /// the web view changing size, or because the previous layout was only for
@@ -372,6 +485,11 @@ pub(crate) struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
+    /// The timer for the long press of the only active touch point, if it has not moved.
+    long_press_timer: Cell<Option<OneshotTimerHandle>>,
+    /// Whether the active touch point showed the context menu with a long press, so that
+    /// lifting it does not also click.
+    long_pressed: Cell<bool>,
     /// Navigation Timing properties:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     #[no_trace]
@@ -424,8 +542,12 @@ pub(crate) struct Document {
     ///
     /// See also: <https://github.com/servo/servo/issues/10110>
     dom_count: Cell<u32>,
-    /// Entry node for fullscreen.
-    fullscreen_element: MutNullableDom<Element>,
+    /// <https://fullscreen.spec.whatwg.org/#top-layer>
+    top_layer: DomRefCell<Vec<Dom<Element>>>,
+    /// <https://fullscreen.spec.whatwg.org/#list-of-pending-fullscreen-events>
+    pending_fullscreen_events: DomRefCell<Vec<PendingFullscreenEvent>>,
+    /// <https://drafts.csswg.org/cssom-view/#pending-scroll-event-targets>
+    pending_scroll_event_targets: DomRefCell<Vec<Dom<EventTarget>>>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -459,6 +581,10 @@ pub(crate) struct Document {
     delayed_tasks: DomRefCell<Vec<Box<dyn TaskBox>>>,
     /// <https://html.spec.whatwg.org/multipage/#completely-loaded>
     completely_loaded: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#print-when-loaded>
+    print_when_loaded: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#ready-for-post-load-tasks>
+    ready_for_post_load_tasks: Cell<bool>,
     /// Set of shadow roots connected to the document tree.
     shadow_roots: DomRefCell<HashSet<Dom<ShadowRoot>>>,
     /// Whether any of the shadow roots need the stylesheets flushed.
@@ -471,6 +597,12 @@ pub(crate) struct Document {
     media_controls: DomRefCell<HashMap<String, Dom<ShadowRoot>>>,
     /// List of all context 2d IDs that need flushing.
     dirty_2d_contexts: DomRefCell<HashMapTracedValues<CanvasId, Dom<CanvasRenderingContext2D>>>,
+    /// List of all outermost `<svg>` elements whose subtree needs to be serialized
+    /// again before the next reflow.
+    dirty_svg_roots: DomRefCell<Vec<Dom<SVGSVGElement>>>,
+    /// List of all outermost `<svg>` elements that are rendered from a serialization of their
+    /// subtree, whose sizes layout reports so that they are rasterized at their used size.
+    svg_roots: DomRefCell<Vec<Dom<SVGSVGElement>>>,
     /// List of all WebGL context IDs that need flushing.
     dirty_webgl_contexts:
         DomRefCell<HashMapTracedValues<WebGLContextId, Dom<WebGLRenderingContext>>>,
@@ -497,6 +629,13 @@ pub(crate) struct Document {
     pending_input_events: DomRefCell<Vec<ConstellationInputEvent>>,
     /// The index of the last mouse move event in the pending compositor events queue.
     mouse_move_event_index: DomRefCell<Option<usize>>,
+    /// The mouse move events that were replaced by the pending mouse move event, which become
+    /// the coalesced events of its `pointermove` event.
+    #[no_trace]
+    #[ignore_malloc_size_of = "CompositorEvent contains data from outside crates"]
+    coalesced_mouse_move_events: DomRefCell<Vec<ConstellationInputEvent>>,
+    /// The pointers that have been used in this document, by their `pointerId`.
+    active_pointers: DomRefCell<HashMapTracedValues<i32, ActivePointer>>,
     /// Pending animation ticks, to be handled at the next rendering opportunity.
     #[no_trace]
     #[ignore_malloc_size_of = "AnimationTickType contains data from an outside crate"]
@@ -515,6 +654,13 @@ pub(crate) struct Document {
     fonts: MutNullableDom<FontFaceSet>,
     /// <https://html.spec.whatwg.org/multipage/#visibility-state>
     visibility_state: Cell<DocumentVisibilityState>,
+    /// Whether the page containing this document is visible to the user, as reported by the
+    /// embedder. This is the [system visibility state] of the top-level traversable.
+    ///
+    /// [system visibility state]: https://html.spec.whatwg.org/multipage/#system-visibility-state
+    page_visible: Cell<bool>,
+    /// <https://wicg.github.io/page-lifecycle/#frozenness-state>
+    frozen: Cell<bool>,
     /// <https://www.iana.org/assignments/http-status-codes/http-status-codes.xhtml>
     status_code: Option<u16>,
     /// <https://html.spec.whatwg.org/multipage/#is-initial-about:blank>
@@ -538,6 +684,14 @@ pub(crate) struct Document {
     /// The lifetime of an intersection observer is specified at
     /// <https://github.com/w3c/IntersectionObserver/issues/525>.
     intersection_observers: DomRefCell<Vec<Dom<IntersectionObserver>>>,
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    lazy_load_intersection_observer: MutNullableDom<IntersectionObserver>,
+    /// The `<img>` elements that allow auto-sizes, whose used widths layout reports after every
+    /// reflow.
+    auto_sized_images: DomRefCell<Vec<Dom<HTMLImageElement>>>,
+    /// The URLs that this document's speculation rules have already prefetched or prerendered.
+    #[no_trace]
+    speculative_loads: DomRefCell<SpeculativeLoads>,
     /// The active keyboard modifiers for the WebView. This is updated when receiving any input event.
     #[no_trace]
     active_keyboard_modifiers: Cell<Modifiers>,
@@ -724,11 +878,14 @@ impl Document {
         // Set the document's activity level, reflow if necessary, and suspend or resume timers.
         self.activity.set(activity);
         let media = ServoMedia::get();
-        let pipeline_id = self.window().pipeline_id();
-        let client_context_id =
-            ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+        let client_context_id = self.media_client_context_id();
 
         if activity != DocumentActivity::FullyActive {
+            // Documents that are navigated away from are kept alive in the back/forward cache.
+            // They are frozen, with their timers and media suspended, until they are either
+            // reactivated by a history traversal or discarded.
+            self.freeze(can_gc);
+            self.window().History().persist_entry_state();
             self.window().suspend(can_gc);
             media.suspend(&client_context_id);
             return;
@@ -738,6 +895,7 @@ impl Document {
         self.dirty_all_nodes();
         self.window().resume(can_gc);
         media.resume(&client_context_id);
+        self.resume_from_frozen(can_gc);
 
         if self.ready_state.get() != DocumentReadyState::Complete {
             return;
@@ -760,7 +918,16 @@ impl Document {
                 // Step 4.6.2 Set document's page showing flag to true.
                 document.page_showing.set(true);
                 // Step 4.6.3 Update the visibility state of document to "visible".
-                document.update_visibility_state(DocumentVisibilityState::Visible, CanGc::note());
+                // Note: If the page is hidden in the embedder, the document stays hidden.
+                if document.page_visible.get() {
+                    document.update_visibility_state(
+                        DocumentVisibilityState::Visible,
+                        CanGc::note(),
+                    );
+                }
+                // Restore the scroll position persisted when the document was deactivated.
+                // <https://html.spec.whatwg.org/multipage/#restore-persisted-state>
+                window.History().restore_scroll_position(CanGc::note());
                 // Step 4.6.4 Fire a page transition event named pageshow at document's relevant
                 // global object with true.
                 let event = PageTransitionEvent::new(
@@ -829,6 +996,12 @@ impl Document {
         self.needs_paint.set(value)
     }
 
+    /// The selection of this document, if script or the user has made one, without
+    /// creating it like [`DocumentMethods::GetSelection`] does.
+    pub(crate) fn selection(&self) -> Option<DomRoot<Selection>> {
+        self.selection.get()
+    }
+
     pub(crate) fn needs_reflow(&self) -> Option<ReflowTriggerCondition> {
         // FIXME: This should check the dirty bit on the document,
         // not the document element. Needs some layout changes to make
@@ -1086,13 +1259,39 @@ impl Document {
 
         self.ready_state.set(state);
 
+        if let Some(chan) = self.window.as_global_scope().devtools_chan() {
+            let state = match state {
+                DocumentReadyState::Loading => ReadyState::Loading,
+                DocumentReadyState::Interactive => ReadyState::Interactive,
+                DocumentReadyState::Complete => ReadyState::Complete,
+            };
+            let _ = chan.send(ScriptToDevtoolsControlMsg::ReadyStateChanged(
+                self.window.pipeline_id(),
+                state,
+            ));
+        }
+
         self.upcast::<EventTarget>()
             .fire_event(atom!("readystatechange"), can_gc);
     }
 
     /// Return whether scripting is enabled or not
     pub(crate) fn is_scripting_enabled(&self) -> bool {
-        self.scripting_enabled
+        self.scripting_enabled && !self.has_active_sandboxing_flag(SandboxingFlagSet::SCRIPTS)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#active-sandboxing-flag-set>
+    pub(crate) fn active_sandboxing_flag_set(&self) -> SandboxingFlagSet {
+        self.active_sandboxing_flag_set.get()
+    }
+
+    pub(crate) fn set_active_sandboxing_flag_set(&self, flags: SandboxingFlagSet) {
+        self.active_sandboxing_flag_set.set(flags);
+    }
+
+    /// Whether this document was sandboxed without the keyword that lifts `flag`.
+    pub(crate) fn has_active_sandboxing_flag(&self, flag: SandboxingFlagSet) -> bool {
+        self.active_sandboxing_flag_set.get().contains(flag)
     }
 
     /// Return the element that currently has focus.
@@ -1101,6 +1300,23 @@ impl Document {
         self.focused.get()
     }
 
+    pub(crate) fn sequential_focus_navigation_starting_point(&self) -> Option<DomRoot<Node>> {
+        self.sequential_focus_navigation_starting_point.get()
+    }
+
+    pub(crate) fn set_sequential_focus_navigation_starting_point(&self, node: Option<&Node>) {
+        self.sequential_focus_navigation_starting_point.set(node);
+    }
+
+    /// Whether the focused element `element` should match `:focus-visible`, which is the
+    /// case for text controls, and for other elements unless the user last interacted with
+    /// the document with a pointer.
+    ///
+    /// <https://drafts.csswg.org/selectors/#the-focus-visible-pseudo>
+    fn should_indicate_focus(&self, element: &Element) -> bool {
+        self.had_keyboard_interaction.get() || element.input_method_type().is_some()
+    }
+
     /// Initiate a new round of checking for elements requesting focus. The last element to call
     /// `request_focus` before `commit_focus_transaction` is called will receive focus.
     fn begin_focus_transaction(&self) {
@@ -1160,6 +1376,7 @@ impl Document {
         if let Some(ref elem) = self.focused.get() {
             let node = elem.upcast::<Node>();
             elem.set_focus_state(false);
+            elem.set_focus_visible_state(false);
             // FIXME: pass appropriate relatedTarget
             self.fire_focus_event(FocusEventType::Blur, node, None, can_gc);
 
@@ -1172,7 +1389,10 @@ impl Document {
         self.focused.set(possibly_focused.as_deref());
 
         if let Some(ref elem) = self.focused.get() {
+            elem.set_focus_visible_state(self.should_indicate_focus(elem));
             elem.set_focus_state(true);
+            self.sequential_focus_navigation_starting_point
+                .set(Some(elem.upcast()));
             let node = elem.upcast::<Node>();
             // FIXME: pass appropriate relatedTarget
             self.fire_focus_event(FocusEventType::Focus, node, None, can_gc);
@@ -1184,11 +1404,7 @@ impl Document {
 
             // Notify the embedder to display an input method.
             if let Some(kind) = elem.input_method_type() {
-                let rect = elem.upcast::<Node>().bounding_content_box_or_zero(can_gc);
-                let rect = Rect::new(
-                    Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
-                    Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
-                );
+                let rect = ime_rect(elem.upcast::<Node>().bounding_content_box_or_zero(can_gc));
                 let (text, multiline) = if let Some(input) = elem.downcast::<HTMLInputElement>() {
                     (
                         Some((
@@ -1213,12 +1429,76 @@ impl Document {
                     kind,
                     text,
                     multiline,
-                    DeviceIntRect::from_untyped(&rect.to_box2d()),
+                    rect,
                 ));
             }
+
+            notify_autofill_field_focused(self, elem, can_gc);
         }
     }
 
+    /// Tell the embedder where the IME composition in the focused text control `node` is,
+    /// so that it can place the candidate window of the IME next to it.
+    pub(crate) fn send_composition_rect_to_embedder(&self, node: &Node, can_gc: CanGc) {
+        let rect = self
+            .window
+            .composition_rect_query(node, can_gc)
+            .unwrap_or_else(|| node.bounding_content_box_or_zero(can_gc));
+        self.send_to_embedder(EmbedderMsg::UpdateImeCaretRect(
+            self.webview_id(),
+            ime_rect(rect),
+        ));
+    }
+
+    /// The form controls that were reported to the embedder for autofill.
+    pub(crate) fn autofill_fields(&self) -> &DomRefCell<HashMapTracedValues<usize, Dom<Element>>> {
+        &self.autofill_fields
+    }
+
+    /// Take the index for the next form control that is reported to the embedder for autofill.
+    pub(crate) fn next_autofill_field_index(&self) -> usize {
+        let index = self.next_autofill_field_index.get();
+        self.next_autofill_field_index.set(index + 1);
+        index
+    }
+
+    /// Start or stop remembering the mutations of this document's DOM for the devtools
+    /// inspector.
+    pub(crate) fn set_devtools_watches_dom_mutations(&self, watch: bool) {
+        *self.devtools_dom_mutations.borrow_mut() = watch.then(Vec::new);
+    }
+
+    pub(crate) fn devtools_watches_dom_mutations(&self) -> bool {
+        self.devtools_dom_mutations.borrow().is_some()
+    }
+
+    pub(crate) fn devtools_dom_mutations(&self) -> &DomRefCell<Option<Vec<PendingDomMutation>>> {
+        &self.devtools_dom_mutations
+    }
+
+    /// Draw the overlays of `kind` over `node` for the devtools inspector, replacing the
+    /// overlays of that kind that were drawn over another node, or remove them if there is
+    /// no node.
+    pub(crate) fn set_devtools_highlighted_node(&self, kind: HighlighterKind, node: Option<&Node>) {
+        let mut highlighted_nodes = self.devtools_highlighted_nodes.borrow_mut();
+        match node {
+            Some(node) => highlighted_nodes.insert(kind, Dom::from_ref(node)),
+            None => highlighted_nodes.remove(&kind),
+        };
+    }
+
+    /// The nodes that the devtools inspector highlights, for layout to draw overlays over.
+    pub(crate) fn devtools_inspector_highlights(&self) -> Vec<InspectorHighlight> {
+        self.devtools_highlighted_nodes
+            .borrow()
+            .iter()
+            .map(|(kind, node)| InspectorHighlight {
+                node: node.to_opaque(),
+                kind: *kind,
+            })
+            .collect()
+    }
+
     /// Handles any updates when the document's title has changed.
     pub(crate) fn title_changed(&self) {
         if self.browsing_context().is_some() {
@@ -1319,6 +1599,12 @@ impl Document {
             return;
         };
 
+        // Inert elements, such as those blocked by a modal dialog, are not the target of user
+        // interaction events.
+        if el.is_inert() {
+            return;
+        }
+
         let node = el.upcast::<Node>();
         debug!("{:?} on {:?}", event.action, node.debug_str());
         // Prevent click event if form control element is disabled.
@@ -1329,7 +1615,14 @@ impl Document {
             }
 
             self.begin_focus_transaction();
-            self.request_focus(Some(&*el), FocusType::Element, can_gc);
+            let focus_target = focus_navigation::click_focus_target(&el, can_gc);
+            self.request_focus(
+                Some(focus_target.as_deref().unwrap_or(&el)),
+                FocusType::Element,
+                can_gc,
+            );
+            self.sequential_focus_navigation_starting_point
+                .set(Some(node));
         }
 
         let dom_event = DomRoot::upcast::<Event>(MouseEvent::for_platform_mouse_event(
@@ -1339,6 +1632,12 @@ impl Document {
             &hit_test_result,
             can_gc,
         ));
+        let pointer_input = PointerInput {
+            pen: event.pen,
+            client_point: hit_test_result.point_in_viewport,
+            button: event.button.into(),
+            buttons: pressed_mouse_buttons,
+        };
 
         // https://html.spec.whatwg.org/multipage/#run-authentic-click-activation-steps
         let activatable = el.as_maybe_activatable();
@@ -1349,20 +1648,28 @@ impl Document {
                 el.set_click_in_progress(false);
             },
             MouseButtonAction::Down => {
+                self.had_keyboard_interaction.set(false);
+                self.window.notify_activation();
                 if let Some(a) = activatable {
                     a.enter_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                dom_event.fire(target, can_gc);
+                let (target, prevent_mouse_event) =
+                    self.dispatch_pointer_button_event(&pointer_input, &el, can_gc);
+                if !prevent_mouse_event {
+                    dom_event.fire(target.upcast(), can_gc);
+                }
             },
             MouseButtonAction::Up => {
                 if let Some(a) = activatable {
                     a.exit_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                dom_event.fire(target, can_gc);
+                let (target, prevent_mouse_event) =
+                    self.dispatch_pointer_button_event(&pointer_input, &el, can_gc);
+                if !prevent_mouse_event {
+                    dom_event.fire(target.upcast(), can_gc);
+                }
             },
         }
 
@@ -1420,22 +1727,20 @@ impl Document {
             pressed_mouse_buttons,          // buttons
             None,                           // related_target
             None,                           // point_in_target
-            // TODO: decide generic pointer id
-            // <https://www.w3.org/TR/pointerevents3/#dom-pointerevent-pointerid>
-            0,                        // pointer_id
-            1,                        // width
-            1,                        // height
-            0.5,                      // pressure
-            0.0,                      // tangential_pressure
-            0,                        // tilt_x
-            0,                        // tilt_y
-            0,                        // twist
-            PI / 2.0,                 // altitude_angle
-            0.0,                      // azimuth_angle
-            DOMString::from("mouse"), // pointer_type
-            true,                     // is_primary
-            vec![],                   // coalesced_events
-            vec![],                   // predicted_events
+            MOUSE_POINTER_ID,               // pointer_id
+            1,                              // width
+            1,                              // height
+            0.5,                            // pressure
+            0.0,                            // tangential_pressure
+            0,                              // tilt_x
+            0,                              // tilt_y
+            0,                              // twist
+            PI / 2.0,                       // altitude_angle
+            0.0,                            // azimuth_angle
+            DOMString::from("mouse"),       // pointer_type
+            true,                           // is_primary
+            vec![],                         // coalesced_events
+            vec![],                         // predicted_events
             can_gc,
         );
         let event = menu_event.upcast::<Event>();
@@ -1443,15 +1748,20 @@ impl Document {
 
         // if the event was not canceled, notify the embedder to show the context menu
         if event.status() == EventStatus::NotCanceled {
-            let (sender, receiver) =
-                ipc::channel::<ContextMenuResult>().expect("Failed to create IPC channel.");
-            self.send_to_embedder(EmbedderMsg::ShowContextMenu(
-                self.webview_id(),
-                sender,
-                None,
-                vec![],
-            ));
-            let _ = receiver.recv().unwrap();
+            // The context menu of a text control offers corrections for a misspelled word.
+            let showed_spelling_context_menu =
+                if let Some(input) = target.downcast::<HTMLInputElement>() {
+                    show_spelling_context_menu(input)
+                } else if let Some(textarea) = target.downcast::<HTMLTextAreaElement>() {
+                    show_spelling_context_menu(textarea)
+                } else {
+                    false
+                };
+            if showed_spelling_context_menu {
+                return;
+            }
+
+            show_context_menu(self, target, can_gc);
         };
     }
 
@@ -1747,7 +2057,7 @@ impl Document {
                             data.to_string(),
                         ));
                     },
-                    Kind::File { .. } => {
+                    Kind::File { .. } | Kind::DroppedFile { .. } => {
                         // Step 1.2.2 If data is of a type listed in the mandatory data types list, then
                         // Step 1.2.2.1 Place part on clipboard with the appropriate OS clipboard format description
                         // Step 1.2.3 Else this is left to the implementation
@@ -1768,18 +2078,22 @@ impl Document {
     #[allow(unsafe_code)]
     pub(crate) unsafe fn handle_mouse_move_event(
         &self,
+        event: MouseMoveEvent,
         hit_test_result: Option<CompositorHitTestResult>,
         pressed_mouse_buttons: u16,
         prev_mouse_over_target: &MutNullableDom<Element>,
         can_gc: CanGc,
     ) {
+        let coalesced_mouse_move_events =
+            mem::take(&mut *self.coalesced_mouse_move_events.borrow_mut());
+
         // Ignore all incoming events without a hit test.
         let Some(hit_test_result) = hit_test_result else {
             return;
         };
 
         let node = unsafe { node::from_untrusted_compositor_node_address(hit_test_result.node) };
-        let Some(new_target) = node
+        let Some(hit_test_target) = node
             .inclusive_ancestors(ShadowIncluding::No)
             .filter_map(DomRoot::downcast::<Element>)
             .next()
@@ -1787,6 +2101,55 @@ impl Document {
             return;
         };
 
+        let pointer_input = PointerInput {
+            pen: event.pen,
+            client_point: hit_test_result.point_in_viewport,
+            button: -1,
+            buttons: pressed_mouse_buttons,
+        };
+        self.update_active_pointer(&pointer_input);
+        self.process_pending_pointer_capture(&pointer_input, can_gc);
+
+        // While the pointer is captured, its events and the compatibility mouse events
+        // are targeted at the capturing element.
+        let new_target = self
+            .pointer_capture_target(pointer_input.pointer_id())
+            .unwrap_or(hit_test_target);
+
+        // Each of the inputs that were coalesced into this one is a raw update of the pointer.
+        // They are targeted at the current target, as their own hit test results may refer to
+        // nodes that have since been removed.
+        let pointer_inputs: Vec<PointerInput> = coalesced_mouse_move_events
+            .iter()
+            .filter_map(|coalesced_event| {
+                let InputEvent::MouseMove(coalesced_mouse_move_event) = coalesced_event.event
+                else {
+                    return None;
+                };
+                Some(PointerInput {
+                    pen: coalesced_mouse_move_event.pen,
+                    client_point: coalesced_event.hit_test_result.as_ref()?.point_in_viewport,
+                    button: -1,
+                    buttons: coalesced_event.pressed_mouse_buttons,
+                })
+            })
+            .chain(std::iter::once(pointer_input))
+            .collect();
+
+        // <https://w3c.github.io/pointerevents/#the-pointerrawupdate-event>
+        if self.window.as_global_scope().is_secure_context() {
+            for raw_input in &pointer_inputs {
+                self.fire_pointer_event(
+                    "pointerrawupdate",
+                    new_target.upcast(),
+                    raw_input,
+                    None,
+                    from_ref(raw_input),
+                    can_gc,
+                );
+            }
+        }
+
         let target_has_changed = prev_mouse_over_target
             .get()
             .as_ref()
@@ -1814,6 +2177,33 @@ impl Document {
                     }
                 }
 
+                // The pointer events for leaving the old target come before the mouse events.
+                self.fire_pointer_event(
+                    "pointerout",
+                    old_target.upcast(),
+                    &pointer_input,
+                    Some(new_target.upcast()),
+                    &[],
+                    can_gc,
+                );
+                if !old_target_is_ancestor_of_new_target {
+                    let targets = enter_leave_targets(
+                        old_target.upcast(),
+                        Some(new_target.upcast()),
+                        FireMouseEventType::Leave,
+                    );
+                    for target in targets {
+                        self.fire_pointer_event(
+                            "pointerleave",
+                            target.upcast(),
+                            &pointer_input,
+                            Some(new_target.upcast()),
+                            &[],
+                            can_gc,
+                        );
+                    }
+                }
+
                 self.fire_mouse_event(
                     hit_test_result.point_in_viewport,
                     old_target.upcast(),
@@ -1850,6 +2240,32 @@ impl Document {
                 element.set_hover_state(true);
             }
 
+            // The pointer events for entering the new target come before the mouse events.
+            let old_target = prev_mouse_over_target.get();
+            let old_target = old_target
+                .as_ref()
+                .map(|old_target| old_target.upcast::<Node>());
+            self.fire_pointer_event(
+                "pointerover",
+                new_target.upcast(),
+                &pointer_input,
+                old_target.map(|old_target| old_target.upcast()),
+                &[],
+                can_gc,
+            );
+            let targets =
+                enter_leave_targets(new_target.upcast(), old_target, FireMouseEventType::Enter);
+            for target in targets {
+                self.fire_pointer_event(
+                    "pointerenter",
+                    target.upcast(),
+                    &pointer_input,
+                    old_target.map(|old_target| old_target.upcast()),
+                    &[],
+                    can_gc,
+                );
+            }
+
             self.fire_mouse_event(
                 hit_test_result.point_in_viewport,
                 new_target.upcast(),
@@ -1874,18 +2290,29 @@ impl Document {
             );
         }
 
-        // Send mousemove event to topmost target, unless it's an iframe, in which case the
-        // compositor should have also sent an event to the inner document.
-        self.fire_mouse_event(
-            hit_test_result.point_in_viewport,
+        self.fire_pointer_event(
+            "pointermove",
             new_target.upcast(),
-            FireMouseEventType::Move,
-            EventBubbles::Bubbles,
-            EventCancelable::Cancelable,
-            pressed_mouse_buttons,
+            &pointer_input,
+            None,
+            &pointer_inputs,
             can_gc,
         );
 
+        // Send mousemove event to topmost target, unless it's an iframe, in which case the
+        // compositor should have also sent an event to the inner document.
+        if !self.prevent_mouse_event(pointer_input.pointer_id()) {
+            self.fire_mouse_event(
+                hit_test_result.point_in_viewport,
+                new_target.upcast(),
+                FireMouseEventType::Move,
+                EventBubbles::Bubbles,
+                EventCancelable::Cancelable,
+                pressed_mouse_buttons,
+                can_gc,
+            );
+        }
+
         // If the target has changed then store the current mouse over target for next frame.
         if target_has_changed {
             prev_mouse_over_target.set(Some(&new_target));
@@ -1901,47 +2328,407 @@ impl Document {
         pressed_mouse_buttons: u16,
         can_gc: CanGc,
     ) {
-        assert!(matches!(
+        let targets = enter_leave_targets(&event_target, related_target.as_deref(), event_type);
+        for target in targets {
+            self.fire_mouse_event(
+                client_point,
+                target.upcast(),
+                event_type,
+                EventBubbles::DoesNotBubble,
+                EventCancelable::NotCancelable,
+                pressed_mouse_buttons,
+                can_gc,
+            );
+        }
+    }
+
+    /// Fire a pointer event of the given type at `target` for an input of the mouse or of a
+    /// pen, whose coalesced events are created from `coalesced_inputs`.
+    fn fire_pointer_event(
+        &self,
+        event_type: &str,
+        target: &EventTarget,
+        input: &PointerInput,
+        related_target: Option<&EventTarget>,
+        coalesced_inputs: &[PointerInput],
+        can_gc: CanGc,
+    ) -> EventStatus {
+        // The coalesced events are never dispatched, but they share the target of the event.
+        let coalesced_events = coalesced_inputs
+            .iter()
+            .map(|coalesced_input| {
+                let coalesced_event = PointerEvent::for_pointer_input(
+                    &self.window,
+                    event_type,
+                    coalesced_input,
+                    related_target,
+                    vec![],
+                    can_gc,
+                );
+                coalesced_event.upcast::<Event>().set_target(Some(target));
+                coalesced_event
+            })
+            .collect();
+        let pointer_event = PointerEvent::for_pointer_input(
+            &self.window,
             event_type,
-            FireMouseEventType::Enter | FireMouseEventType::Leave
-        ));
+            input,
+            related_target,
+            coalesced_events,
+            can_gc,
+        );
+        let event = pointer_event.upcast::<Event>();
+        event.fire(target, can_gc);
+        event.status()
+    }
 
-        let common_ancestor = match related_target.as_ref() {
-            Some(related_target) => event_target
-                .common_ancestor(related_target, ShadowIncluding::No)
-                .unwrap_or_else(|| DomRoot::from_ref(&*event_target)),
-            None => DomRoot::from_ref(&*event_target),
+    /// Fire the pointer event for a button of the mouse or of a pen being pressed or released,
+    /// targeted at `hit_test_target` unless the pointer is captured. This is `pointerdown` or
+    /// `pointerup` when the first button is pressed or the last one released, and a
+    /// `pointermove` when the other buttons change.
+    ///
+    /// Returns the target of the compatibility mouse event, and whether it is prevented.
+    /// <https://w3c.github.io/pointerevents/#chorded-button-interactions>
+    fn dispatch_pointer_button_event(
+        &self,
+        input: &PointerInput,
+        hit_test_target: &Element,
+        can_gc: CanGc,
+    ) -> (DomRoot<Element>, bool) {
+        let pointer_id = input.pointer_id();
+        let previous_buttons = self.update_active_pointer(input);
+        self.process_pending_pointer_capture(input, can_gc);
+        let target = self
+            .pointer_capture_target(pointer_id)
+            .unwrap_or_else(|| DomRoot::from_ref(hit_test_target));
+
+        let event_type = match (previous_buttons, input.buttons) {
+            (0, buttons) if buttons != 0 => "pointerdown",
+            (_, 0) => "pointerup",
+            _ => "pointermove",
         };
+        let coalesced_inputs = if event_type == "pointermove" {
+            from_ref(input)
+        } else {
+            &[]
+        };
+        let status = self.fire_pointer_event(
+            event_type,
+            target.upcast(),
+            input,
+            None,
+            coalesced_inputs,
+            can_gc,
+        );
 
-        // We need to create a target chain in case the event target shares
-        // its boundaries with its ancestors.
-        let mut targets = vec![];
-        let mut current = Some(event_target);
-        while let Some(node) = current {
-            if node == common_ancestor {
-                break;
+        // Canceling `pointerdown` prevents the compatibility mouse events until all of the
+        // buttons are released.
+        // <https://w3c.github.io/pointerevents/#the-pointerdown-event>
+        if event_type == "pointerdown" && status == EventStatus::Canceled {
+            if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+                pointer.prevent_mouse_event = true;
             }
-            current = node.GetParentNode();
-            targets.push(node);
         }
+        let prevent_mouse_event = self.prevent_mouse_event(pointer_id);
 
-        // The order for dispatching mouseenter events starts from the topmost
-        // common ancestor of the event target and the related target.
-        if event_type == FireMouseEventType::Enter {
-            targets = targets.into_iter().rev().collect();
+        if event_type == "pointerup" {
+            // <https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture>
+            if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+                pointer.pending_capture_target_override = None;
+                pointer.prevent_mouse_event = false;
+            }
+            self.process_pending_pointer_capture(input, can_gc);
         }
 
-        for target in targets {
-            self.fire_mouse_event(
-                client_point,
-                target.upcast(),
-                event_type,
-                EventBubbles::DoesNotBubble,
-                EventCancelable::NotCancelable,
-                pressed_mouse_buttons,
+        (target, prevent_mouse_event)
+    }
+
+    /// Record the buttons of the pointer of `input`, returning the buttons that were pressed
+    /// before it.
+    fn update_active_pointer(&self, input: &PointerInput) -> u16 {
+        let mut active_pointers = self.active_pointers.borrow_mut();
+        let pointer = active_pointers.entry(input.pointer_id()).or_default();
+        mem::replace(&mut pointer.buttons, input.buttons)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-prevent-mouse-event-flag>
+    fn prevent_mouse_event(&self, pointer_id: i32) -> bool {
+        self.active_pointers
+            .borrow()
+            .get(&pointer_id)
+            .is_some_and(|pointer| pointer.prevent_mouse_event)
+    }
+
+    /// The element that captures the pointer with the given `pointerId`, if any.
+    fn pointer_capture_target(&self, pointer_id: i32) -> Option<DomRoot<Element>> {
+        self.active_pointers
+            .borrow()
+            .get(&pointer_id)?
+            .capture_target_override
+            .as_ref()
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    /// <https://w3c.github.io/pointerevents/#setting-pointer-capture>
+    pub(crate) fn set_pointer_capture(&self, element: &Element, pointer_id: i32) -> ErrorResult {
+        let mut active_pointers = self.active_pointers.borrow_mut();
+        let pointer = active_pointers.get_mut(&pointer_id);
+
+        // Step 1. If the pointerId provided as the method's argument does not match any of the
+        // active pointers, then throw a "NotFoundError" DOMException. The mouse is always an
+        // active pointer.
+        if pointer.is_none() && pointer_id != MOUSE_POINTER_ID {
+            return Err(Error::NotFound);
+        }
+
+        // Step 3. If the element is not connected, throw an "InvalidStateError" DOMException.
+        if !element.upcast::<Node>().is_connected() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5. If the pointer is not in the active buttons state or the element's node
+        // document is not the active document of the pointer, then terminate these steps.
+        let Some(pointer) = pointer.filter(|pointer| pointer.buttons != 0) else {
+            return Ok(());
+        };
+
+        // Step 6. For the specified pointerId, set the pending pointer capture target override
+        // to the Element on which this method was invoked.
+        pointer.pending_capture_target_override = Some(Dom::from_ref(element));
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/pointerevents/#releasing-pointer-capture>
+    pub(crate) fn release_pointer_capture(
+        &self,
+        element: &Element,
+        pointer_id: i32,
+    ) -> ErrorResult {
+        // Step 1. If the pointerId provided as the method's argument does not match any of the
+        // active pointers and these steps are not being invoked as a result of the implicit
+        // release of pointer capture, then throw a "NotFoundError" DOMException.
+        if !self.active_pointers.borrow().contains_key(&pointer_id) &&
+            pointer_id != MOUSE_POINTER_ID
+        {
+            return Err(Error::NotFound);
+        }
+
+        // Step 2. If hasPointerCapture is false for the Element with the specified pointerId,
+        // then terminate these steps.
+        if !self.has_pointer_capture(element, pointer_id) {
+            return Ok(());
+        }
+
+        // Step 3. For the specified pointerId, clear the pending pointer capture target
+        // override, if set.
+        if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            pointer.pending_capture_target_override = None;
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-haspointercapture>
+    pub(crate) fn has_pointer_capture(&self, element: &Element, pointer_id: i32) -> bool {
+        self.active_pointers
+            .borrow()
+            .get(&pointer_id)
+            .and_then(|pointer| pointer.pending_capture_target_override.as_ref())
+            .is_some_and(|target| **target == *element)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#process-pending-pointer-capture>
+    fn process_pending_pointer_capture(&self, input: &PointerInput, can_gc: CanGc) {
+        let pointer_id = input.pointer_id();
+        let (capture_target, pending_capture_target) = {
+            let mut active_pointers = self.active_pointers.borrow_mut();
+            let Some(pointer) = active_pointers.get_mut(&pointer_id) else {
+                return;
+            };
+
+            // An element that is removed from its document can no longer capture the pointer.
+            // <https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture>
+            if pointer
+                .pending_capture_target_override
+                .as_ref()
+                .is_some_and(|target| !target.upcast::<Node>().is_connected())
+            {
+                pointer.pending_capture_target_override = None;
+            }
+
+            let root = |target: &Option<Dom<Element>>| {
+                target.as_ref().map(|target| DomRoot::from_ref(&**target))
+            };
+            (
+                root(&pointer.capture_target_override),
+                root(&pointer.pending_capture_target_override),
+            )
+        };
+        if capture_target == pending_capture_target {
+            return;
+        }
+
+        // Step 1. If the pointer capture target override for this pointer is set and is not
+        // equal to the pending pointer capture target override, then fire a pointer event named
+        // lostpointercapture at the pointer capture target override node. When that node was
+        // removed from its document, the event is fired at the document instead.
+        if let Some(capture_target) = capture_target {
+            let target = if capture_target.upcast::<Node>().is_connected() {
+                capture_target.upcast::<EventTarget>()
+            } else {
+                self.upcast::<EventTarget>()
+            };
+            self.fire_pointer_event("lostpointercapture", target, input, None, &[], can_gc);
+        }
+
+        // Step 2. If the pending pointer capture target override for this pointer is set and
+        // is not equal to the pointer capture target override, then fire a pointer event named
+        // gotpointercapture at the pending pointer capture target override.
+        if let Some(pending_capture_target) = pending_capture_target {
+            self.fire_pointer_event(
+                "gotpointercapture",
+                pending_capture_target.upcast(),
+                input,
+                None,
+                &[],
                 can_gc,
             );
         }
+
+        // Step 3. Set the pointer capture target override to the pending pointer capture
+        // target override, if set. Otherwise, clear the pointer capture target override.
+        if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            pointer.capture_target_override = pointer
+                .pending_capture_target_override
+                .as_ref()
+                .map(|target| Dom::from_ref(&**target));
+        }
+    }
+
+    /// Fire the drag and drop events for files that the user dropped onto the page from
+    /// outside of it. The page sees the files through the `DataTransfer` of the events,
+    /// and unless it cancels the `drop` event, a file input under the drop selects them.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    #[allow(unsafe_code)]
+    pub(crate) fn handle_file_drop_event(
+        &self,
+        event: FileDropEvent,
+        hit_test_result: Option<CompositorHitTestResult>,
+        can_gc: CanGc,
+    ) {
+        let Some(hit_test_result) = hit_test_result else {
+            return;
+        };
+
+        let node = unsafe { node::from_untrusted_compositor_node_address(hit_test_result.node) };
+        let Some(target) = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .find_map(DomRoot::downcast::<Element>)
+        else {
+            return;
+        };
+        let input = node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .find_map(DomRoot::downcast::<HTMLInputElement>)
+            .filter(|input| input.accepts_file_drop());
+
+        debug!(
+            "file drop: on {:?} at {:?}",
+            target.upcast::<Node>().debug_str(),
+            hit_test_result.point_in_viewport
+        );
+
+        let files = self.select_dropped_files(event.paths, can_gc);
+        if files.is_empty() {
+            return;
+        }
+
+        let mut drag_data_store = DragDataStore::new();
+        for file in &files {
+            let type_ = file.upcast::<Blob>().type_string();
+            let _ = drag_data_store.add(Kind::DroppedFile {
+                file: Trusted::new(&**file),
+                type_,
+            });
+        }
+        let drag_data_store = Rc::new(RefCell::new(Some(drag_data_store)));
+        let data_transfer = DataTransfer::new(&self.window, Rc::clone(&drag_data_store), can_gc);
+
+        let fire_drag_event = |type_: &str, cancelable: EventCancelable| {
+            DragEvent::new_trusted(
+                &self.window,
+                DOMString::from(type_),
+                cancelable,
+                hit_test_result.point_in_viewport,
+                hit_test_result.point_relative_to_item,
+                &data_transfer,
+                can_gc,
+            )
+            .upcast::<Event>()
+            .fire(target.upcast(), can_gc)
+        };
+
+        // The data store stays in protected mode until the drop, so the page only learns
+        // that files are being dragged, not which ones.
+        data_transfer.SetDropEffect(DOMString::from("copy"));
+        fire_drag_event("dragenter", EventCancelable::Cancelable);
+        let dragover_status = fire_drag_event("dragover", EventCancelable::Cancelable);
+
+        // Without a page that accepts the drop, only file inputs take dropped files.
+        let accepted = if dragover_status == EventStatus::Canceled {
+            data_transfer.DropEffect() != "none"
+        } else {
+            input.is_some()
+        };
+        if !accepted {
+            fire_drag_event("dragleave", EventCancelable::NotCancelable);
+            *drag_data_store.borrow_mut() = None;
+            return;
+        }
+
+        if let Some(drag_data_store) = drag_data_store.borrow_mut().as_mut() {
+            drag_data_store.set_mode(Mode::ReadOnly);
+        }
+        let drop_status = fire_drag_event("drop", EventCancelable::Cancelable);
+
+        // The `DataTransfer` of the events no longer gives access to the files afterwards.
+        *drag_data_store.borrow_mut() = None;
+
+        if drop_status == EventStatus::NotCanceled {
+            if let Some(input) = input {
+                input.handle_file_drop(files, can_gc);
+            }
+        }
+    }
+
+    /// Ask the file manager for access to the files at the paths that the user dropped, where a
+    /// directory stands for all of the files in it.
+    fn select_dropped_files(&self, paths: Vec<PathBuf>, can_gc: CanGc) -> Vec<DomRoot<File>> {
+        if paths.is_empty() {
+            return Vec::new();
+        }
+
+        let origin = get_blob_origin(&self.window.get_url());
+        let (chan, recv) =
+            profile_ipc::channel(self.window.as_global_scope().time_profiler_chan().clone())
+                .expect("Error initializing channel");
+        let msg = FileManagerThreadMsg::SelectDroppedFiles(self.webview_id(), paths, chan, origin);
+        self.window
+            .as_global_scope()
+            .resource_threads()
+            .send(CoreResourceMsg::ToFileManager(msg))
+            .unwrap();
+
+        match recv.recv().expect("IpcSender side error") {
+            Ok(selected_files) => selected_files
+                .into_iter()
+                .map(|selected| File::new_from_selected(&self.window, selected, can_gc))
+                .collect(),
+            Err(err) => {
+                debug!("File drop error: {:?}", err);
+                Vec::new()
+            },
+        }
     }
 
     #[allow(unsafe_code)]
@@ -2009,7 +2796,8 @@ impl Document {
         };
 
         let TouchId(identifier) = event.id;
-        let event_name = match event.event_type {
+        let event_type = event.event_type;
+        let event_name = match event_type {
             TouchEventType::Down => "touchstart",
             TouchEventType::Move => "touchmove",
             TouchEventType::Up => "touchend",
@@ -2025,8 +2813,17 @@ impl Document {
             return TouchEventResult::Forwarded;
         };
 
-        let target = DomRoot::upcast::<EventTarget>(el);
+        // Inert elements, such as those blocked by a modal dialog, are not the target of user
+        // interaction events.
+        if el.is_inert() {
+            return TouchEventResult::Forwarded;
+        }
+
+        let target = DomRoot::upcast::<EventTarget>(el.clone());
         let window = &*self.window;
+        if matches!(event.event_type, TouchEventType::Up) {
+            window.notify_activation();
+        }
 
         let client_x = Finite::wrap(hit_test_result.point_in_viewport.x as f64);
         let client_y = Finite::wrap(hit_test_result.point_in_viewport.y as f64);
@@ -2101,6 +2898,38 @@ impl Document {
         let event = event.upcast::<Event>();
         let result = event.fire(&target, can_gc);
 
+        // Holding down a single touch point without moving it shows the context menu, unless
+        // its touchstart event was canceled. Touch moves only arrive once the touch point left
+        // its touch slop.
+        if let Some(timer) = self.long_press_timer.take() {
+            self.window.as_global_scope().unschedule_callback(timer);
+        }
+        match event_type {
+            TouchEventType::Down => {
+                self.had_keyboard_interaction.set(false);
+                self.long_pressed.set(false);
+                if result == EventStatus::NotCanceled &&
+                    self.active_touch_points.borrow().len() == 1
+                {
+                    let callback = LongPressCallback {
+                        document: Trusted::new(self),
+                        target: Trusted::new(&*el),
+                        client_point: hit_test_result.point_in_viewport,
+                    };
+                    let timer = self.window.as_global_scope().schedule_callback(
+                        OneshotTimerCallback::LongPress(callback),
+                        LONG_PRESS_DELAY,
+                    );
+                    self.long_press_timer.set(Some(timer));
+                }
+            },
+            // A touch point that showed the context menu does not click when it is lifted.
+            TouchEventType::Up if self.long_pressed.take() => {
+                return TouchEventResult::Processed(false);
+            },
+            TouchEventType::Move | TouchEventType::Up | TouchEventType::Cancel => {},
+        }
+
         match result {
             EventStatus::Canceled => TouchEventResult::Processed(false),
             EventStatus::NotCanceled => TouchEventResult::Processed(true),
@@ -2140,6 +2969,16 @@ impl Document {
             can_gc,
         );
         let event = keyevent.upcast::<Event>();
+        if keyboard_event.state == KeyState::Down && keyboard_event.key != Key::Escape {
+            self.window.notify_activation();
+        }
+        if keyboard_event.state == KeyState::Down &&
+            !keyboard_event
+                .modifiers
+                .intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::META)
+        {
+            self.had_keyboard_interaction.set(true);
+        }
         event.fire(target, can_gc);
         let mut cancel_state = event.get_cancel_state();
 
@@ -2173,8 +3012,13 @@ impl Document {
         }
 
         if cancel_state == EventDefault::Allowed {
-            let msg = EmbedderMsg::Keyboard(self.webview_id(), keyboard_event.clone());
-            self.send_to_embedder(msg);
+            // Keys that move the caret in caret browsing mode do not scroll the page.
+            if !caret_browsing::handle_key(self, &keyboard_event, can_gc) &&
+                !focus_navigation::handle_key(self, &keyboard_event, can_gc)
+            {
+                let msg = EmbedderMsg::Keyboard(self.webview_id(), keyboard_event.clone());
+                self.send_to_embedder(msg);
+            }
 
             // This behavior is unspecced
             // We are supposed to dispatch synthetic click activation for Space and/or Return,
@@ -2472,13 +3316,24 @@ impl Document {
             .https_state(self.https_state.get())
     }
 
+    /// The context in which `document.cookie` accesses cookies, which determines the cookie
+    /// partition and policy that apply.
+    fn cookie_context(&self) -> CookieContext {
+        CookieContext {
+            webview_id: Some(self.webview_id()),
+            top_level_origin: Some(self.window.as_global_scope().top_level_origin()),
+        }
+    }
+
     pub(crate) fn fetch<Listener: FetchResponseListener + PreInvoke + Send + 'static>(
         &self,
         load: LoadType,
         mut request: RequestBuilder,
         listener: Listener,
     ) {
-        request = request.insecure_requests_policy(self.insecure_requests_policy());
+        request = request
+            .insecure_requests_policy(self.insecure_requests_policy())
+            .top_level_origin(Some(self.window.as_global_scope().top_level_origin()));
         let callback = NetworkListener {
             context: std::sync::Arc::new(Mutex::new(listener)),
             task_source: self
@@ -2497,7 +3352,9 @@ impl Document {
         mut request: RequestBuilder,
         listener: Listener,
     ) {
-        request = request.insecure_requests_policy(self.insecure_requests_policy());
+        request = request
+            .insecure_requests_policy(self.insecure_requests_policy())
+            .top_level_origin(Some(self.window.as_global_scope().top_level_origin()));
         let callback = NetworkListener {
             context: std::sync::Arc::new(Mutex::new(listener)),
             task_source: self
@@ -2588,14 +3445,16 @@ impl Document {
             self.salvageable.set(false);
         }
         let mut can_unload = true;
-        // TODO: Step 8, also check sandboxing modals flag.
+        // Step 8. Documents sandboxed without `allow-modals` can't prompt.
         let default_prevented = event.DefaultPrevented();
         let return_value_not_empty = !event
             .downcast::<BeforeUnloadEvent>()
             .unwrap()
             .ReturnValue()
             .is_empty();
-        if default_prevented || return_value_not_empty {
+        if (default_prevented || return_value_not_empty) &&
+            !self.has_active_sandboxing_flag(SandboxingFlagSet::MODALS)
+        {
             let (chan, port) = ipc::channel().expect("Failed to create IPC channel!");
             let msg = EmbedderMsg::AllowUnload(self.webview_id(), chan);
             self.send_to_embedder(msg);
@@ -2691,8 +3550,16 @@ impl Document {
         let global_scope = self.window.as_global_scope();
         // Step 10, 14
         // https://html.spec.whatwg.org/multipage/#unloading-document-cleanup-steps
+        // https://fullscreen.spec.whatwg.org/#unloading-document-cleanup-steps
+        self.fully_exit_fullscreen();
+        // Step 2 of clean-up steps. For each WebSocket object webSocket whose relevant global
+        // object is window, make disappear webSocket. If this affected any WebSocket objects,
+        // then set document's salvageable state to false.
+        if global_scope.make_websockets_disappear() {
+            self.salvageable.set(false);
+        }
         if !self.salvageable.get() {
-            // Step 1 of clean-up steps.
+            // Step 4 of clean-up steps.
             global_scope.close_event_sources();
             let msg = ScriptMsg::DiscardDocument;
             let _ = global_scope.script_to_constellation_chan().send(msg);
@@ -2770,6 +3637,15 @@ impl Document {
                 if let Some(fragment) = document.url().fragment() {
                     document.check_and_scroll_fragment(fragment, CanGc::note());
                 }
+
+                // Step 10. If the Document's print when loaded flag is set, then run the
+                // printing steps.
+                if document.print_when_loaded.get() {
+                    window.run_printing_steps(CanGc::note());
+                }
+
+                // Step 11. The Document is now ready for post-load tasks.
+                document.ready_for_post_load_tasks.set(true);
             }));
 
         // Step 8.
@@ -2787,6 +3663,28 @@ impl Document {
 
                     document.page_showing.set(true);
 
+                    // A document that reloads a discarded session history entry restores the
+                    // scroll position persisted for it.
+                    // <https://html.spec.whatwg.org/multipage/#restore-persisted-state>
+                    window.History().restore_scroll_position(CanGc::note());
+
+                    // Update the visibility state of document, as in Step 4.6.3 of
+                    // <https://html.spec.whatwg.org/multipage/#reactivate-a-document>, since the
+                    // embedder may have shown or hidden the page while the document loaded.
+                    // Documents of hidden pages are frozen like the other documents of the page.
+                    if document.page_visible.get() {
+                        document.update_visibility_state(
+                            DocumentVisibilityState::Visible,
+                            CanGc::note(),
+                        );
+                    } else {
+                        document.update_visibility_state(
+                            DocumentVisibilityState::Hidden,
+                            CanGc::note(),
+                        );
+                        document.freeze_hidden_page(CanGc::note());
+                    }
+
                     let event = PageTransitionEvent::new(
                         window,
                         atom!("pageshow"),
@@ -2805,11 +3703,7 @@ impl Document {
         // Step 9.
         // TODO: pending application cache download process tasks.
 
-        // Step 10.
-        // TODO: printing steps.
-
-        // Step 11.
-        // TODO: ready for post-load tasks.
+        // Steps 10-11 are run after the load event is fired, above.
 
         // The dom.webxr.sessionavailable pref allows webxr
         // content to immediately begin a session without waiting for a user gesture.
@@ -2852,10 +3746,55 @@ impl Document {
         }
     }
 
+    /// The caret position in the text that is closest to the point at `x` and `y` in the
+    /// viewport, as a text node and an offset in UTF-16 code units, or `None` if the point is
+    /// outside of the viewport or no text is rendered.
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#caret-position>
+    fn caret_position_from_point(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> Option<(DomRoot<Node>, u32)> {
+        if !self.has_browsing_context {
+            return None;
+        }
+        let x = *x as f32;
+        let y = *y as f32;
+        let viewport = self.window.window_size().initial_viewport;
+        if x < 0.0 || y < 0.0 || x > viewport.width || y > viewport.height {
+            return None;
+        }
+
+        // TODO: Return positions in the value of text controls.
+        let viewport_origin = self.window.current_viewport().origin;
+        let point = Point2D::new(
+            x + viewport_origin.x.to_f32_px(),
+            y + viewport_origin.y.to_f32_px(),
+        );
+        let (node, offset) = self.window.caret_position_from_point_query(point, can_gc)?;
+        let text = caret_browsing::text_of(&node)?;
+        let offset = caret_browsing::utf8_offset_to_utf16(&text, offset);
+        Some((node, offset))
+    }
+
     pub(crate) fn completely_loaded(&self) -> bool {
         self.completely_loaded.get()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#ready-for-post-load-tasks>
+    pub(crate) fn is_ready_for_post_load_tasks(&self) -> bool {
+        self.ready_for_post_load_tasks.get()
+    }
+
+    /// Print this document once it is loaded, when `print()` is called before.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#print-when-loaded>
+    pub(crate) fn set_print_when_loaded(&self) {
+        self.print_when_loaded.set(true);
+    }
+
     // https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script
     pub(crate) fn set_pending_parsing_blocking_script(
         &self,
@@ -3010,8 +3949,10 @@ impl Document {
             .queue(
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
+                userscripts::run_document_end_scripts(&document, CanGc::note());
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"), CanGc::note());
                 update_with_current_instant(&document.dom_content_loaded_event_end);
+                document.window().send_to_constellation(ScriptMsg::DOMContentLoaded);
                 })
             );
 
@@ -3320,6 +4261,125 @@ impl Document {
             .for_each(|(_, context)| context.update_rendering());
     }
 
+    pub(crate) fn add_dirty_svg_root(&self, svg_element: &SVGSVGElement) {
+        let mut dirty_svg_roots = self.dirty_svg_roots.borrow_mut();
+        if !dirty_svg_roots.iter().any(|root| &**root == svg_element) {
+            dirty_svg_roots.push(Dom::from_ref(svg_element));
+        }
+    }
+
+    /// Serialize the subtrees of all `<svg>` elements that changed since the last reflow,
+    /// so that layout can render them.
+    pub(crate) fn flush_dirty_svg_roots(&self, stylesheets_changed: bool, can_gc: CanGc) {
+        // The serializations include the author style rules of this document that apply to
+        // them, so serialize the subtrees again whose rules changed.
+        if stylesheets_changed {
+            let svg_roots: Vec<_> = self
+                .svg_roots
+                .borrow()
+                .iter()
+                .map(|root| DomRoot::from_ref(&**root))
+                .collect();
+            for svg_element in svg_roots {
+                svg_element.handle_stylesheets_changed();
+            }
+        }
+
+        let dirty_svg_roots: Vec<_> = self
+            .dirty_svg_roots
+            .borrow_mut()
+            .drain(..)
+            .map(|root| DomRoot::from_ref(&*root))
+            .collect();
+        for svg_element in dirty_svg_roots {
+            if svg_element.upcast::<Node>().is_connected() {
+                svg_element.serialize_and_cache_subtree(can_gc);
+                self.add_svg_root(&svg_element);
+            }
+        }
+    }
+
+    fn add_svg_root(&self, svg_element: &SVGSVGElement) {
+        let mut svg_roots = self.svg_roots.borrow_mut();
+        if !svg_roots.iter().any(|root| &**root == svg_element) {
+            svg_roots.push(Dom::from_ref(svg_element));
+        }
+    }
+
+    /// The nodes of the `<svg>` elements that are rendered from a serialization of their
+    /// subtree, for layout to report their sizes. Elements that are no longer outermost
+    /// `<svg>` elements of this document are forgotten, along with their serialization.
+    pub(crate) fn svg_roots_for_layout(&self) -> Vec<OpaqueNode> {
+        let mut svg_roots = self.svg_roots.borrow_mut();
+        svg_roots.retain(|svg_element| {
+            let keep = svg_element.upcast::<Node>().is_connected() &&
+                *svg_element.owner_document() == *self &&
+                svg_element.is_outermost();
+            if !keep {
+                svg_element.release_serialized_subtree();
+            }
+            keep
+        });
+        svg_roots
+            .iter()
+            .map(|svg_element| svg_element.upcast::<Node>().to_opaque())
+            .collect()
+    }
+
+    /// Update the used sizes of the `<svg>` elements rendered from a serialization of their
+    /// subtree after a reflow. Elements without a size are not being rendered.
+    pub(crate) fn handle_svg_root_sizes(&self, sizes: &[(OpaqueNode, Size2D<Au>)]) {
+        let svg_roots: Vec<DomRoot<SVGSVGElement>> = self
+            .svg_roots
+            .borrow()
+            .iter()
+            .map(|svg_element| DomRoot::from_ref(&**svg_element))
+            .collect();
+        for svg_element in svg_roots {
+            let node = svg_element.upcast::<Node>().to_opaque();
+            let size = sizes
+                .iter()
+                .find_map(|(other, size)| (*other == node).then_some(*size));
+            svg_element.set_used_size(size);
+        }
+    }
+
+    /// The style rules of the enabled author style sheets of this document that match an
+    /// element of the subtree rooted at `root`, serialized as CSS, for content that is
+    /// rendered outside of the styles of this document.
+    pub(crate) fn author_style_rules_as_css(&self, root: &Node) -> String {
+        let elements: Vec<DomRoot<Element>> = root
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .collect();
+        let quirks_mode = self.quirks_mode();
+        let guard = self.style_shared_lock.read();
+        let mut css = String::new();
+        for (sheet, _origin) in self.stylesheets.borrow().iter() {
+            if sheet.sheet.disabled() {
+                continue;
+            }
+            for rule in sheet.sheet.contents.rules.read_with(&guard).0.iter() {
+                let CssRule::Style(style_rule) = rule else {
+                    continue;
+                };
+                let selectors = &style_rule.read_with(&guard).selectors;
+                let applies = elements.iter().any(|element| {
+                    dom_apis::element_matches(
+                        &SelectorWrapper::Borrowed(element),
+                        selectors,
+                        quirks_mode,
+                    )
+                });
+                if applies {
+                    css.push_str(&rule.to_css_string(&guard));
+                    css.push('\n');
+                }
+            }
+        }
+        css
+    }
+
     #[cfg(feature = "webgpu")]
     pub(crate) fn webgpu_contexts(&self) -> WebGPUContextsMap {
         self.webgpu_contexts.clone()
@@ -3478,6 +4538,96 @@ impl Document {
             .retain(|observer| *observer != intersection_observer)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#start-intersection-observing-a-lazy-loading-element>
+    pub(crate) fn start_intersection_observing_a_lazy_loading_element(
+        &self,
+        element: &Element,
+        can_gc: CanGc,
+    ) {
+        // Step 1. Let doc be element's node document.
+        // Step 2. If doc's lazy load intersection observer is null, set it to a new
+        // IntersectionObserver instance, initialized as follows: The callback is the lazy load
+        // steps, and the rootMargin is the lazy load root margin.
+        let observer = self
+            .lazy_load_intersection_observer
+            .or_init(|| IntersectionObserver::new_lazy_load_observer(&self.window, can_gc));
+
+        // Step 3. Call doc's lazy load intersection observer's observe method with element as
+        // the argument.
+        observer.Observe(element);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#stop-intersection-observing-a-lazy-loading-element>
+    pub(crate) fn stop_intersection_observing_a_lazy_loading_element(&self, element: &Element) {
+        // Step 1. Let doc be element's node document.
+        // Step 2. Assert: doc's lazy load intersection observer is not null.
+        // Step 3. Call doc's lazy load intersection observer unobserve method with element as the
+        // argument.
+        if let Some(observer) = self.lazy_load_intersection_observer.get() {
+            observer.Unobserve(element);
+        }
+    }
+
+    /// Register a rule set from one of this document's `<script type="speculationrules">`
+    /// elements and start the speculative loads it asks for.
+    ///
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#consider-speculative-loads>
+    pub(crate) fn register_speculation_rule_set(&self, rule_set: SpeculationRuleSet) {
+        // Speculative loads are limited to same-origin URLs, since cross-site prefetches would
+        // need to be isolated from the user's cookies and network state.
+        let origin = self.origin().immutable().clone();
+        let global = self.window.as_global_scope();
+
+        for rule in rule_set.prefetch_rules() {
+            for url in rule.immediate_urls() {
+                if url.origin() != origin ||
+                    !self
+                        .speculative_loads
+                        .borrow_mut()
+                        .prefetched_urls
+                        .insert(url.clone())
+                {
+                    continue;
+                }
+                prefetch(self, url.clone(), rule.referrer_policy());
+            }
+        }
+
+        // Only a top-level document can be replaced by a prerendered document.
+        if !self.window.is_top_level() {
+            return;
+        }
+        for rule in rule_set.prerender_rules() {
+            let referrer_policy = match rule.referrer_policy() {
+                ReferrerPolicy::EmptyString => self.get_referrer_policy(),
+                referrer_policy => referrer_policy,
+            };
+            for url in rule.immediate_urls() {
+                if url.origin() != origin ||
+                    *url == self.url() ||
+                    !self
+                        .speculative_loads
+                        .borrow_mut()
+                        .prerendered_urls
+                        .insert(url.clone())
+                {
+                    continue;
+                }
+                let load_data = LoadData::new(
+                    LoadOrigin::Script(origin.clone()),
+                    url.clone(),
+                    Some(global.pipeline_id()),
+                    global.get_referrer(),
+                    referrer_policy,
+                    Some(global.is_secure_context()),
+                    Some(self.insecure_requests_policy()),
+                );
+                self.window
+                    .send_to_constellation(ScriptMsg::Prerender(load_data));
+            }
+        }
+    }
+
     /// <https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo>
     pub(crate) fn update_intersection_observer_steps(
         &self,
@@ -3588,6 +4738,15 @@ impl Document {
     }
 }
 
+/// The area of the page at `rect`, as reported to the embedder for placing an IME.
+fn ime_rect(rect: Rect<Au>) -> DeviceIntRect {
+    let rect = Rect::new(
+        Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+        Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+    );
+    DeviceIntRect::from_untyped(&rect.to_box2d())
+}
+
 fn is_character_value_key(key: &Key) -> bool {
     matches!(key, Key::Character(_) | Key::Enter)
 }
@@ -3767,6 +4926,8 @@ impl Document {
             .unwrap_or(UTF_8);
 
         let has_browsing_context = has_browsing_context == HasBrowsingContext::Yes;
+        let scripting_enabled =
+            has_browsing_context && site_pref!(dom_script_enabled, url.host_str());
 
         Document {
             node: Node::new_document_node(),
@@ -3811,17 +4972,24 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            adopted_stylesheets: DomRefCell::new(Vec::new()),
+            adopted_stylesheets_array: AdoptedStyleSheetArray::new(),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             focus_transaction: DomRefCell::new(FocusTransaction::NotInTransaction),
             focused: Default::default(),
+            sequential_focus_navigation_starting_point: Default::default(),
+            had_keyboard_interaction: Cell::new(true),
+            autofill_fields: Default::default(),
+            next_autofill_field_index: Default::default(),
             current_script: Default::default(),
             pending_parsing_blocking_script: Default::default(),
             script_blocking_stylesheets_count: Cell::new(0u32),
             deferred_scripts: Default::default(),
             asap_in_order_scripts_list: Default::default(),
             asap_scripts_set: Default::default(),
-            scripting_enabled: has_browsing_context,
+            scripting_enabled,
+            active_sandboxing_flag_set: Cell::new(SandboxingFlagSet::empty()),
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(VecDeque::new()),
             running_animation_callbacks: Cell::new(false),
@@ -3830,8 +4998,12 @@ impl Document {
             base_element: Default::default(),
             appropriate_template_contents_owner_document: Default::default(),
             pending_restyles: DomRefCell::new(HashMap::new()),
+            devtools_dom_mutations: Default::default(),
+            devtools_highlighted_nodes: DomRefCell::new(HashMapTracedValues::new()),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
+            long_press_timer: Default::default(),
+            long_pressed: Cell::new(false),
             dom_interactive: Cell::new(Default::default()),
             dom_content_loaded_event_start: Cell::new(Default::default()),
             dom_content_loaded_event_end: Cell::new(Default::default()),
@@ -3851,7 +5023,9 @@ impl Document {
             ignore_opens_during_unload_counter: Default::default(),
             spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
-            fullscreen_element: MutNullableDom::new(None),
+            top_layer: Default::default(),
+            pending_fullscreen_events: Default::default(),
+            pending_scroll_event_targets: Default::default(),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::default()),
@@ -3864,12 +5038,16 @@ impl Document {
             responsive_images: Default::default(),
             redirect_count: Cell::new(0),
             completely_loaded: Cell::new(false),
+            print_when_loaded: Cell::new(false),
+            ready_for_post_load_tasks: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
             delayed_tasks: Default::default(),
             shadow_roots: DomRefCell::new(HashSet::new()),
             shadow_roots_styles_changed: Cell::new(false),
             media_controls: DomRefCell::new(HashMap::new()),
             dirty_2d_contexts: DomRefCell::new(HashMapTracedValues::new()),
+            dirty_svg_roots: Default::default(),
+            svg_roots: Default::default(),
             dirty_webgl_contexts: DomRefCell::new(HashMapTracedValues::new()),
             #[cfg(feature = "webgpu")]
             webgpu_contexts: Rc::new(RefCell::new(HashMapTracedValues::new())),
@@ -3886,15 +5064,22 @@ impl Document {
             pending_animation_ticks: Default::default(),
             pending_input_events: Default::default(),
             mouse_move_event_index: Default::default(),
+            coalesced_mouse_move_events: Default::default(),
+            active_pointers: Default::default(),
             resize_observers: Default::default(),
             fonts: Default::default(),
             visibility_state: Cell::new(DocumentVisibilityState::Hidden),
+            page_visible: Cell::new(true),
+            frozen: Cell::new(false),
             status_code,
             is_initial_about_blank: Cell::new(is_initial_about_blank),
             allow_declarative_shadow_roots: Cell::new(allow_declarative_shadow_roots),
             inherited_insecure_requests_policy: Cell::new(inherited_insecure_requests_policy),
             intersection_observer_task_queued: Cell::new(false),
             intersection_observers: Default::default(),
+            lazy_load_intersection_observer: Default::default(),
+            auto_sized_images: Default::default(),
+            speculative_loads: Default::default(),
             active_keyboard_modifiers: Cell::new(Modifiers::empty()),
         }
     }
@@ -3946,7 +5131,10 @@ impl Document {
                 .borrow()
                 .and_then(|index| pending_compositor_events.get_mut(index))
             {
-                *mouse_move_event = event;
+                let coalesced_event = mem::replace(mouse_move_event, event);
+                self.coalesced_mouse_move_events
+                    .borrow_mut()
+                    .push(coalesced_event);
                 return;
             }
 
@@ -4382,7 +5570,12 @@ impl Document {
     }
 
     // https://fullscreen.spec.whatwg.org/#dom-element-requestfullscreen
-    pub(crate) fn enter_fullscreen(&self, pending: &Element, can_gc: CanGc) -> Rc<Promise> {
+    pub(crate) fn enter_fullscreen(
+        &self,
+        pending: &Element,
+        screen: Option<ScreenId>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
         // Step 1
         let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
         let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
@@ -4424,7 +5617,7 @@ impl Document {
         let window = self.window();
         // Step 6
         if !error {
-            let event = EmbedderMsg::NotifyFullscreenStateChanged(self.webview_id(), true);
+            let event = EmbedderMsg::NotifyFullscreenStateChanged(self.webview_id(), true, screen);
             self.send_to_embedder(event);
         }
 
@@ -4455,18 +5648,17 @@ impl Document {
         let in_realm_proof = AlreadyInRealm::assert::<crate::DomTypeHolder>();
         let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof), can_gc);
         // Step 2
-        if self.fullscreen_element.get().is_none() {
+        let Some(element) = self.fullscreen_element() else {
             promise.reject_error(Error::Type(String::from("fullscreen is null")), can_gc);
             return promise;
-        }
+        };
         // TODO Step 3-6
-        let element = self.fullscreen_element.get().unwrap();
 
         // Step 7 Parallel start
 
         let window = self.window();
         // Step 8
-        let event = EmbedderMsg::NotifyFullscreenStateChanged(self.webview_id(), false);
+        let event = EmbedderMsg::NotifyFullscreenStateChanged(self.webview_id(), false, None);
         self.send_to_embedder(event);
 
         // Step 9
@@ -4488,8 +5680,236 @@ impl Document {
         promise
     }
 
-    pub(crate) fn set_fullscreen_element(&self, element: Option<&Element>) {
-        self.fullscreen_element.set(element);
+    /// <https://fullscreen.spec.whatwg.org/#fully-exit-fullscreen>
+    ///
+    /// This is run as part of the unloading document cleanup steps, at which point the document
+    /// will not render again, so no events are fired and fullscreen is left immediately.
+    pub(crate) fn fully_exit_fullscreen(&self) {
+        // Step 1. If document's fullscreen element is null, terminate these steps.
+        if self.fullscreen_element().is_none() {
+            return;
+        }
+
+        // Step 2. Unfullscreen elements whose fullscreen flag is set, within document's top
+        // layer, except for document's fullscreen element.
+        // Step 3. Exit fullscreen document.
+        let fullscreen_elements: Vec<DomRoot<Element>> = self
+            .top_layer
+            .borrow()
+            .iter()
+            .filter(|element| element.fullscreen_state())
+            .map(|element| DomRoot::from_ref(&**element))
+            .collect();
+        for element in fullscreen_elements {
+            element.set_fullscreen_state(false);
+            self.remove_from_top_layer(&element);
+        }
+        self.pending_fullscreen_events.borrow_mut().clear();
+
+        let event = EmbedderMsg::NotifyFullscreenStateChanged(self.webview_id(), false, None);
+        self.send_to_embedder(event);
+    }
+
+    /// <https://fullscreen.spec.whatwg.org/#fullscreen-element>
+    pub(crate) fn fullscreen_element(&self) -> Option<DomRoot<Element>> {
+        self.top_layer
+            .borrow()
+            .iter()
+            .rev()
+            .find(|element| element.fullscreen_state())
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    /// The topmost modal dialog of this document, if any, which makes every node that is
+    /// not one of its descendants inert.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    pub(crate) fn modal_dialog(&self) -> Option<DomRoot<Element>> {
+        self.top_layer
+            .borrow()
+            .iter()
+            .rev()
+            .find(|element| {
+                element.is::<HTMLDialogElement>() && element.state().contains(ElementState::MODAL)
+            })
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    /// Ask layout for the width of `image` after every reflow, so that it can be used as the
+    /// source size of its `sizes="auto"` attribute.
+    pub(crate) fn add_auto_sized_image(&self, image: &HTMLImageElement) {
+        let mut auto_sized_images = self.auto_sized_images.borrow_mut();
+        if !auto_sized_images.iter().any(|other| &**other == image) {
+            auto_sized_images.push(Dom::from_ref(image));
+        }
+    }
+
+    /// The nodes of the images that allow auto-sizes, for layout to report their widths. Images
+    /// that no longer allow auto-sizes or were removed from the document are forgotten.
+    pub(crate) fn auto_sized_images_for_layout(&self) -> Vec<OpaqueNode> {
+        let mut auto_sized_images = self.auto_sized_images.borrow_mut();
+        auto_sized_images.retain(|image| {
+            image.upcast::<Node>().is_connected() &&
+                *image.owner_document() == *self &&
+                image.allows_auto_sizes()
+        });
+        auto_sized_images
+            .iter()
+            .map(|image| image.upcast::<Node>().to_opaque())
+            .collect()
+    }
+
+    /// Update the widths of the images that allow auto-sizes after a reflow. Images without a
+    /// width are not being rendered.
+    pub(crate) fn handle_auto_sized_image_widths(&self, widths: &[(OpaqueNode, Au)]) {
+        let auto_sized_images: Vec<DomRoot<HTMLImageElement>> = self
+            .auto_sized_images
+            .borrow()
+            .iter()
+            .map(|image| DomRoot::from_ref(&**image))
+            .collect();
+        for image in auto_sized_images {
+            let node = image.upcast::<Node>().to_opaque();
+            let width = widths
+                .iter()
+                .find_map(|(other, width)| (*other == node).then_some(*width));
+            image.set_auto_sizes_width(width);
+        }
+    }
+
+    /// The elements of the top layer, for layout to paint above the rest of the document.
+    pub(crate) fn top_layer_for_layout(&self) -> Vec<TopLayerElement> {
+        self.top_layer
+            .borrow()
+            .iter()
+            .map(|element| TopLayerElement {
+                node: element.upcast::<Node>().to_opaque(),
+                is_fullscreen: element.fullscreen_state(),
+            })
+            .collect()
+    }
+
+    /// <https://fullscreen.spec.whatwg.org/#top-layer-add>
+    pub(crate) fn add_to_top_layer(&self, element: &Element) {
+        {
+            let mut top_layer = self.top_layer.borrow_mut();
+            top_layer.retain(|top_layer_element| &**top_layer_element != element);
+            top_layer.push(Dom::from_ref(element));
+        }
+        element.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// <https://fullscreen.spec.whatwg.org/#remove-from-the-top-layer-immediately>
+    pub(crate) fn remove_from_top_layer(&self, element: &Element) {
+        let removed = {
+            let mut top_layer = self.top_layer.borrow_mut();
+            let length = top_layer.len();
+            top_layer.retain(|top_layer_element| &**top_layer_element != element);
+            top_layer.len() != length
+        };
+        if removed {
+            element.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
+    /// Append an event to this document's
+    /// [list of pending fullscreen events](https://fullscreen.spec.whatwg.org/#list-of-pending-fullscreen-events),
+    /// to be fired the next time the rendering is updated.
+    pub(crate) fn queue_fullscreen_event(
+        &self,
+        event_type: FullscreenEventType,
+        element: &Element,
+    ) {
+        self.pending_fullscreen_events
+            .borrow_mut()
+            .push(PendingFullscreenEvent {
+                event_type,
+                element: Dom::from_ref(element),
+            });
+    }
+
+    /// Append `target`, this document or an element of it that was scrolled, to this
+    /// document's [pending scroll event targets], unless it already is in them, so that a
+    /// `scroll` event is fired at it the next time the rendering is updated.
+    ///
+    /// [pending scroll event targets]: https://drafts.csswg.org/cssom-view/#pending-scroll-event-targets
+    pub(crate) fn queue_scroll_event(&self, target: &EventTarget) {
+        let mut targets = self.pending_scroll_event_targets.borrow_mut();
+        if !targets
+            .iter()
+            .any(|pending_target| &**pending_target == target)
+        {
+            targets.push(Dom::from_ref(target));
+        }
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#document-run-the-scroll-steps>
+    pub(crate) fn run_the_scroll_steps(&self, can_gc: CanGc) {
+        // > 2. Empty doc’s pending scroll event targets.
+        let targets: Vec<DomRoot<EventTarget>> = self
+            .pending_scroll_event_targets
+            .borrow_mut()
+            .drain(..)
+            .map(|target| DomRoot::from_ref(&*target))
+            .collect();
+
+        // > 1. For each item target in doc’s pending scroll event targets, in the order they
+        // > were added to the list, run these substeps:
+        for target in targets {
+            // > 1. If target is a Document, fire an event named scroll that bubbles at target.
+            // > 2. Otherwise, fire an event named scroll at target.
+            if target.is::<Document>() {
+                target.fire_bubbling_event(Atom::from("scroll"), can_gc);
+            } else {
+                target.fire_event(Atom::from("scroll"), can_gc);
+            }
+        }
+
+        // TODO: Fire `scrollend` events, which also requires knowing when a scroll ends.
+        if let Some(visual_viewport) = self.window.visual_viewport() {
+            visual_viewport.run_the_scroll_steps(can_gc);
+        }
+    }
+
+    /// <https://fullscreen.spec.whatwg.org/#run-the-fullscreen-steps>
+    pub(crate) fn run_the_fullscreen_steps(&self, can_gc: CanGc) {
+        // Step 1. Let pendingEvents be document's list of pending fullscreen events.
+        // Step 2. Empty document's list of pending fullscreen events.
+        let pending_events: Vec<(FullscreenEventType, DomRoot<Element>)> = self
+            .pending_fullscreen_events
+            .borrow_mut()
+            .drain(..)
+            .map(|pending_event| {
+                (
+                    pending_event.event_type,
+                    DomRoot::from_ref(&*pending_event.element),
+                )
+            })
+            .collect();
+
+        // Step 3. For each (type, element) in pendingEvents:
+        for (event_type, element) in pending_events {
+            // Step 3.1. Let target be element.
+            // Step 3.2. If target is disconnected or its node document is not document, then
+            // set target to document.
+            let target = if element.is_connected() && *element.owner_document() == *self {
+                element.upcast::<EventTarget>()
+            } else {
+                self.upcast::<EventTarget>()
+            };
+
+            // Step 3.3. Fire an event named type, with its bubbles and composed attributes set
+            // to true, at target.
+            let event = Event::new(
+                &self.global(),
+                event_type.name(),
+                EventBubbles::Bubbles,
+                EventCancelable::NotCancelable,
+                can_gc,
+            );
+            event.set_composed(true);
+            event.fire(target, can_gc);
+        }
     }
 
     pub(crate) fn get_allow_fullscreen(&self) -> bool {
@@ -4552,15 +5972,21 @@ impl Document {
     }
 
     pub(crate) fn stylesheet_count(&self) -> usize {
-        self.stylesheets.borrow().len()
+        self.stylesheets
+            .borrow()
+            .iter()
+            .filter(|(sheet, _origin)| sheet.owner.is_element())
+            .count()
     }
 
     pub(crate) fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
         let stylesheets = self.stylesheets.borrow();
 
         stylesheets
-            .get(Origin::Author, index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .iter()
+            .filter(|(sheet, _origin)| sheet.owner.is_element())
+            .nth(index)
+            .and_then(|(sheet, _origin)| sheet.owner.get_cssom_object())
     }
 
     /// Add a stylesheet owned by `owner` to the list of document sheets, in the
@@ -4571,11 +5997,7 @@ impl Document {
         let insertion_point = stylesheets
             .iter()
             .map(|(sheet, _origin)| sheet)
-            .find(|sheet_in_doc| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_doc.owner.upcast())
-            })
+            .find(|sheet_in_doc| sheet_in_doc.owner.is_after(owner))
             .cloned();
 
         if self.has_browsing_context() {
@@ -4586,7 +6008,7 @@ impl Document {
         }
 
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            StylesheetSource::Element(Dom::from_ref(owner)),
             StylesheetSetRef::Document(stylesheets),
             sheet,
             insertion_point,
@@ -4611,12 +6033,51 @@ impl Document {
         }
 
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            StylesheetSource::Element(Dom::from_ref(owner)),
             stylesheet,
             StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
         )
     }
 
+    /// Replace the adopted stylesheets of this document, which come after the stylesheets of
+    /// its elements.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn set_adopted_stylesheets(&self, sheets: Vec<DomRoot<CSSStyleSheet>>) {
+        let (old_sheets, new_sheets) = DocumentOrShadowRoot::replace_adopted_stylesheets(
+            &self.adopted_stylesheets,
+            sheets,
+            &StyleSheetListOwner::Document(Dom::from_ref(self)),
+        );
+
+        for sheet in old_sheets {
+            if self.has_browsing_context() {
+                self.window
+                    .layout_mut()
+                    .remove_stylesheet(sheet.style_stylesheet_arc().clone());
+            }
+            DocumentOrShadowRoot::remove_stylesheet(
+                StylesheetSource::Constructed(Dom::from_ref(&*sheet)),
+                sheet.style_stylesheet_arc(),
+                StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+            );
+        }
+        for sheet in new_sheets {
+            if self.has_browsing_context() {
+                self.window
+                    .layout_mut()
+                    .add_stylesheet(sheet.style_stylesheet_arc().clone(), None);
+            }
+            DocumentOrShadowRoot::add_stylesheet(
+                StylesheetSource::Constructed(Dom::from_ref(&*sheet)),
+                StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+                sheet.style_stylesheet_arc().clone(),
+                None,
+                self.style_shared_lock(),
+            );
+        }
+        self.invalidate_stylesheets();
+    }
+
     pub(crate) fn get_elements_with_id(&self, id: &Atom) -> Ref<[Dom<Element>]> {
         Ref::map(self.id_map.borrow(), |map| {
             map.get(id).map(|vec| &**vec).unwrap_or_default()
@@ -4665,6 +6126,26 @@ impl Document {
             .update_for_new_timeline_value(&self.window, current_timeline_value);
     }
 
+    /// Stop the animation timeline, so that it only changes when advanced or set by a test.
+    pub(crate) fn pause_animation_timeline(&self) {
+        self.animation_timeline.borrow_mut().pause();
+    }
+
+    /// Let the animation timeline follow the clock again, from its current value.
+    pub(crate) fn resume_animation_timeline(&self) {
+        self.animation_timeline.borrow_mut().resume();
+    }
+
+    /// Set the value of the animation timeline, in seconds, and update the animations for it.
+    pub(crate) fn set_animation_timeline_value(&self, value: f64) {
+        self.animation_timeline
+            .borrow_mut()
+            .set_current_value(value);
+        self.animations
+            .borrow()
+            .update_for_new_timeline_value(&self.window, value);
+    }
+
     pub(crate) fn maybe_mark_animating_nodes_as_dirty(&self) {
         let current_timeline_value = self.current_animation_timeline_value();
         self.animations
@@ -4692,10 +6173,8 @@ impl Document {
 
     /// An implementation of <https://drafts.csswg.org/web-animations-1/#update-animations-and-send-events>.
     pub(crate) fn update_animations_and_send_events(&self, can_gc: CanGc) {
-        // Only update the time if it isn't being managed by a test.
-        if !pref!(layout_animations_test_enabled) {
-            self.animation_timeline.borrow_mut().update();
-        }
+        // The time is not updated if the timeline is paused, because it is managed by a test.
+        self.animation_timeline.borrow_mut().update();
 
         // > 1. Update the current time of all timelines associated with doc passing now
         // > as the timestamp.
@@ -4733,6 +6212,112 @@ impl Document {
         *self.declarative_refresh.borrow_mut() = Some(refresh);
     }
 
+    /// Set the initial visibility state of this newly created document from whether its page
+    /// is visible in the embedder. Unlike later changes, this does not fire a
+    /// `visibilitychange` event.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#make-active>
+    pub(crate) fn set_initial_page_visibility(&self, visible: bool) {
+        self.page_visible.set(visible);
+        // Set document's visibility state to document's node navigable's traversable
+        // navigable's system visibility state.
+        self.visibility_state.set(if visible {
+            DocumentVisibilityState::Visible
+        } else {
+            DocumentVisibilityState::Hidden
+        });
+    }
+
+    /// Handle the page containing this document becoming visible or hidden in the embedder,
+    /// updating the visibility state of the document. When the [Page Lifecycle API] is enabled,
+    /// hidden documents are also frozen, pausing their timers and media until they are visible
+    /// again.
+    ///
+    /// [Page Lifecycle API]: https://wicg.github.io/page-lifecycle/
+    pub(crate) fn set_page_visibility(&self, visible: bool, can_gc: CanGc) {
+        if self.page_visible.get() == visible {
+            return;
+        }
+        self.page_visible.set(visible);
+
+        // Documents that are not showing, such as those in the session history, are already
+        // hidden and have their timers suspended.
+        if !self.is_fully_active() || !self.page_showing.get() {
+            return;
+        }
+
+        if visible {
+            if self.frozen.get() {
+                self.window.as_global_scope().resume();
+                ServoMedia::get().resume(&self.media_client_context_id());
+                self.resume_from_frozen(can_gc);
+            }
+            self.update_visibility_state(DocumentVisibilityState::Visible, can_gc);
+        } else {
+            self.update_visibility_state(DocumentVisibilityState::Hidden, can_gc);
+            self.freeze_hidden_page(can_gc);
+        }
+    }
+
+    /// Freeze this document because the page containing it is hidden from the user, if the
+    /// [Page Lifecycle API] is enabled.
+    ///
+    /// [Page Lifecycle API]: https://wicg.github.io/page-lifecycle/
+    fn freeze_hidden_page(&self, can_gc: CanGc) {
+        if !pref!(dom_page_lifecycle_enabled) {
+            return;
+        }
+        // Pause all media of the document. Servo suspends all media playback of the
+        // pipeline instead, which is resumed when the document is resumed.
+        // Note: Queued tasks such as timers are also paused while the document is frozen.
+        self.freeze(can_gc);
+        self.window.as_global_scope().suspend();
+        ServoMedia::get().suspend(&self.media_client_context_id());
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#freeze-steps>
+    ///
+    /// This only updates the frozenness state of the document. Callers are responsible for
+    /// suspending its timers and media.
+    fn freeze(&self, can_gc: CanGc) {
+        if self.frozen.get() {
+            return;
+        }
+
+        // Step 1. Set document's frozenness state to true.
+        self.frozen.set(true);
+
+        // Step 2. Fire an event named freeze at document.
+        if pref!(dom_page_lifecycle_enabled) {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("freeze"), can_gc);
+        }
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#resume-steps>
+    ///
+    /// This only updates the frozenness state of the document. Callers are responsible for
+    /// resuming its timers and media.
+    fn resume_from_frozen(&self, can_gc: CanGc) {
+        if !self.frozen.get() {
+            return;
+        }
+
+        // Step 2. Fire an event named resume at document.
+        if pref!(dom_page_lifecycle_enabled) {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("resume"), can_gc);
+        }
+
+        // Step 3. Set document's frozenness state to false.
+        self.frozen.set(false);
+    }
+
+    fn media_client_context_id(&self) -> ClientContextId {
+        let pipeline_id = self.window().pipeline_id();
+        ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#visibility-state>
     fn update_visibility_state(&self, visibility_state: DocumentVisibilityState, can_gc: CanGc) {
         // Step 1 If document's visibility state equals visibilityState, then return.
@@ -4843,6 +6428,30 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         })
     }
 
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    fn AdoptedStyleSheets(&self, cx: JSContext, _can_gc: CanGc, retval: MutableHandleValue) {
+        self.adopted_stylesheets_array.get_or_init(
+            self,
+            || {
+                self.adopted_stylesheets
+                    .borrow()
+                    .iter()
+                    .map(|sheet| DomRoot::from_ref(&**sheet))
+                    .collect()
+            },
+            cx,
+            retval,
+        );
+    }
+
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        let sheets = DocumentOrShadowRoot::adoptable_stylesheets(cx, value, self)?;
+        self.adopted_stylesheets_array.replace(cx, &sheets);
+        self.set_adopted_stylesheets(sheets);
+        Ok(())
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation
@@ -4897,9 +6506,11 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             return Err(Error::Security);
         }
 
-        // TODO: Step 2. "If this Document object's active sandboxing
-        // flag set has its sandboxed document.domain browsing context
-        // flag set, then throw a "SecurityError" DOMException."
+        // Step 2. If this Document object's active sandboxing flag set has its sandboxed
+        // document.domain browsing context flag set, then throw a "SecurityError" DOMException.
+        if self.has_active_sandboxing_flag(SandboxingFlagSet::DOCUMENT_DOMAIN) {
+            return Err(Error::Security);
+        }
 
         // Steps 3-4.
         let effective_domain = match self.origin.effective_domain() {
@@ -5634,6 +7245,11 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         self.upcast::<Node>().replace_children(nodes, can_gc)
     }
 
+    // https://dom.spec.whatwg.org/#dom-parentnode-movebefore
+    fn MoveBefore(&self, node: &Node, child: Option<&Node>, can_gc: CanGc) -> ErrorResult {
+        self.upcast::<Node>().move_before(node, child, can_gc)
+    }
+
     // https://dom.spec.whatwg.org/#dom-parentnode-queryselector
     fn QuerySelector(&self, selectors: DOMString) -> Fallible<Option<DomRoot<Element>>> {
         let root = self.upcast::<Node>();
@@ -5676,7 +7292,7 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             .window
             .as_global_scope()
             .resource_threads()
-            .send(GetCookiesForUrl(url, tx, NonHTTP));
+            .send(GetCookiesForUrl(url, tx, NonHTTP, self.cookie_context()));
         let cookies = rx.recv().unwrap();
         Ok(cookies.map_or(DOMString::new(), DOMString::from))
     }
@@ -5701,7 +7317,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
             .window
             .as_global_scope()
             .resource_threads()
-            .send(SetCookiesForUrl(self.url(), cookies, NonHTTP));
+            .send(SetCookiesForUrl(
+                self.url(),
+                cookies,
+                NonHTTP,
+                self.cookie_context(),
+            ));
         Ok(())
     }
 
@@ -5910,6 +7531,52 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint
+    fn CaretPositionFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        options: &CaretPositionFromPointOptions,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<CaretPosition>> {
+        let (mut node, mut offset) = self.caret_position_from_point(x, y, can_gc)?;
+
+        // A position in a shadow tree that is not one of the given shadow roots is moved to
+        // the host of that shadow tree, so that closed shadow trees are not exposed.
+        while let Some(shadow_root) = node.containing_shadow_root() {
+            if options
+                .shadowRoots
+                .iter()
+                .any(|root| **root == *shadow_root)
+            {
+                break;
+            }
+            let host = shadow_root.Host();
+            let host = host.upcast::<Node>();
+            offset = host.index();
+            node = host.GetParentNode()?;
+        }
+
+        Some(CaretPosition::new(&self.window, &node, offset, can_gc))
+    }
+
+    // Non-standard counterpart of `caretPositionFromPoint()` from WebKit and Blink, which
+    // returns a collapsed range and does not expose the contents of shadow trees.
+    fn CaretRangeFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<Range>> {
+        let options = CaretPositionFromPointOptions {
+            shadowRoots: Vec::new(),
+        };
+        let position = self.CaretPositionFromPoint(x, y, &options, can_gc)?;
+        let node = position.OffsetNode();
+        let offset = position.Offset();
+        Some(Range::new(self, &node, offset, &node, offset, can_gc))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-open
     fn Open(
         &self,
@@ -6126,6 +7793,12 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
     // https://html.spec.whatwg.org/multipage/#documentandelementeventhandlers
     document_and_element_event_handlers!();
 
+    // https://wicg.github.io/page-lifecycle/#dom-document-onfreeze
+    event_handler!(freeze, GetOnfreeze, SetOnfreeze);
+
+    // https://wicg.github.io/page-lifecycle/#dom-document-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
     // https://fullscreen.spec.whatwg.org/#handler-document-onfullscreenerror
     event_handler!(fullscreenerror, GetOnfullscreenerror, SetOnfullscreenerror);
 
@@ -6143,13 +7816,13 @@ impl DocumentMethods<crate::DomTypeHolder> for Document {
 
     // https://fullscreen.spec.whatwg.org/#dom-document-fullscreen
     fn Fullscreen(&self) -> bool {
-        self.fullscreen_element.get().is_some()
+        self.fullscreen_element().is_some()
     }
 
     // https://fullscreen.spec.whatwg.org/#dom-document-fullscreenelement
     fn GetFullscreenElement(&self) -> Option<DomRoot<Element>> {
         // TODO ShadowRoot
-        self.fullscreen_element.get()
+        self.fullscreen_element()
     }
 
     // https://fullscreen.spec.whatwg.org/#dom-document-exitfullscreen
@@ -6264,6 +7937,30 @@ pub(crate) fn determine_policy_for_token(token: &str) -> ReferrerPolicy {
     }
 }
 
+/// The type of an event in a document's
+/// [list of pending fullscreen events](https://fullscreen.spec.whatwg.org/#list-of-pending-fullscreen-events).
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+pub(crate) enum FullscreenEventType {
+    Change,
+    Error,
+}
+
+impl FullscreenEventType {
+    fn name(&self) -> Atom {
+        match self {
+            FullscreenEventType::Change => atom!("fullscreenchange"),
+            FullscreenEventType::Error => atom!("fullscreenerror"),
+        }
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct PendingFullscreenEvent {
+    event_type: FullscreenEventType,
+    element: Dom<Element>,
+}
+
 /// Specifies the type of focus event that is sent to a pipeline
 #[derive(Clone, Copy, PartialEq)]
 pub(crate) enum FocusType {
@@ -6290,6 +7987,27 @@ pub(crate) struct FakeRequestAnimationFrameCallback {
     document: Trusted<Document>,
 }
 
+/// The timer callback for a touch point that is held down without moving, which shows the
+/// context menu for the element under it.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) struct LongPressCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    document: Trusted<Document>,
+    #[ignore_malloc_size_of = "non-owning"]
+    target: Trusted<Element>,
+    #[no_trace]
+    client_point: Point2D<f32>,
+}
+
+impl LongPressCallback {
+    pub(crate) fn invoke(self, can_gc: CanGc) {
+        let document = self.document.root();
+        document.long_press_timer.set(None);
+        document.long_pressed.set(true);
+        document.maybe_show_context_menu(self.target.root().upcast(), 0, self.client_point, can_gc);
+    }
+}
+
 impl FakeRequestAnimationFrameCallback {
     pub(crate) fn invoke(self, can_gc: CanGc) {
         // TODO: Once there is a more generic mechanism to trigger `update_the_rendering` when
@@ -6,6 +6,7 @@ use std::fmt;
 
 use constellation_traits::UntrustedNodeAddress;
 use euclid::default::Point2D;
+use js::rust::HandleValue;
 use script_layout_interface::{NodesFromPointQueryType, QueryMsg};
 use servo_arc::Arc;
 use style::invalidation::media_queries::{MediaListKey, ToMediaListKey};
@@ -19,24 +20,63 @@ use super::bindings::trace::HashMapTracedValues;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::Node_Binding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::conversions::{ConversionResult, FromJSValConvertible};
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{self, Node, VecPreOrderInsertionHelper};
 use crate::dom::shadowroot::ShadowRoot;
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
-use crate::script_runtime::CanGc;
+use crate::script_runtime::{CanGc, JSContext};
 use crate::stylesheet_set::StylesheetSetRef;
 
+/// Where a stylesheet of a document or shadow root comes from.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) enum StylesheetSource {
+    /// The `<style>` or `<link>` element that owns the stylesheet.
+    Element(Dom<Element>),
+    /// A constructed stylesheet in the `adoptedStyleSheets` of the document or shadow root.
+    Constructed(Dom<CSSStyleSheet>),
+}
+
+impl StylesheetSource {
+    pub(crate) fn get_cssom_object(&self) -> Option<DomRoot<CSSStyleSheet>> {
+        match self {
+            StylesheetSource::Element(element) => element.upcast::<Node>().get_cssom_stylesheet(),
+            StylesheetSource::Constructed(sheet) => Some(DomRoot::from_ref(&**sheet)),
+        }
+    }
+
+    /// Whether a stylesheet from this source comes after those of `element`. Adopted
+    /// stylesheets come after all of the stylesheets of elements.
+    pub(crate) fn is_after(&self, element: &Element) -> bool {
+        match self {
+            StylesheetSource::Element(other) => element.upcast::<Node>().is_before(other.upcast()),
+            StylesheetSource::Constructed(_) => true,
+        }
+    }
+
+    /// Whether this is the `<style>` or `<link>` element of a stylesheet, which are the
+    /// stylesheets listed in `styleSheets`.
+    pub(crate) fn is_element(&self) -> bool {
+        matches!(self, StylesheetSource::Element(_))
+    }
+}
+
 #[derive(Clone, JSTraceable, MallocSizeOf)]
 #[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
 pub(crate) struct StyleSheetInDocument {
     #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
     pub(crate) sheet: Arc<Stylesheet>,
-    pub(crate) owner: Dom<Element>,
+    pub(crate) owner: StylesheetSource,
 }
 
 // This is necessary because this type is contained within a Stylo type which needs
@@ -115,7 +155,21 @@ impl DocumentOrShadowRoot {
             .query_nodes_from_point(*client_point, query_type)
     }
 
+    /// The element that was hit when a hit test hit the node at `address`: the node itself
+    /// if it is an element, or otherwise the host of its shadow root or its parent element.
     #[allow(unsafe_code)]
+    fn element_for_hit_node(address: UntrustedNodeAddress) -> Option<DomRoot<Element>> {
+        let node = unsafe { node::from_untrusted_node_address(address) };
+        if let Some(element) = node.downcast::<Element>() {
+            return Some(DomRoot::from_ref(element));
+        }
+        let parent_node = node.GetParentNode()?;
+        if let Some(shadow_root) = parent_node.downcast::<ShadowRoot>() {
+            return Some(shadow_root.Host());
+        }
+        DomRoot::downcast::<Element>(parent_node)
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     pub(crate) fn element_from_point(
         &self,
@@ -142,28 +196,11 @@ impl DocumentOrShadowRoot {
             .nodes_from_point(point, NodesFromPointQueryType::Topmost, can_gc)
             .first()
         {
-            Some(address) => {
-                let node = unsafe { node::from_untrusted_node_address(*address) };
-                let parent_node = node.GetParentNode().unwrap();
-                let shadow_host = parent_node
-                    .downcast::<ShadowRoot>()
-                    .map(ShadowRootMethods::Host);
-                let element_ref = node
-                    .downcast::<Element>()
-                    .or(shadow_host.as_deref())
-                    .unwrap_or_else(|| {
-                        parent_node
-                            .downcast::<Element>()
-                            .expect("Hit node should have an element or shadowroot parent")
-                    });
-
-                Some(DomRoot::from_ref(element_ref))
-            },
+            Some(address) => Self::element_for_hit_node(*address),
             None => document_element,
         }
     }
 
-    #[allow(unsafe_code)]
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
     pub(crate) fn elements_from_point(
         &self,
@@ -188,14 +225,15 @@ impl DocumentOrShadowRoot {
         }
 
         // Step 1 and Step 3
+        // The hit nodes are mapped to elements in the same way as for `elementFromPoint`,
+        // and an element is only listed once, even if several of its boxes were hit.
         let nodes = self.nodes_from_point(point, NodesFromPointQueryType::All, can_gc);
-        let mut elements: Vec<DomRoot<Element>> = nodes
-            .iter()
-            .flat_map(|&untrusted_node_address| {
-                let node = unsafe { node::from_untrusted_node_address(untrusted_node_address) };
-                DomRoot::downcast::<Element>(node)
-            })
-            .collect();
+        let mut elements: Vec<DomRoot<Element>> = vec![];
+        for element in nodes.into_iter().filter_map(Self::element_for_hit_node) {
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
 
         // Step 4
         if let Some(root_element) = document_element {
@@ -227,10 +265,10 @@ impl DocumentOrShadowRoot {
         }
     }
 
-    /// Remove a stylesheet owned by `owner` from the list of document sheets.
+    /// Remove a stylesheet from `owner` from the list of document sheets.
     #[cfg_attr(crown, allow(crown::unrooted_must_root))] // Owner needs to be rooted already necessarily.
     pub(crate) fn remove_stylesheet(
-        owner: &Element,
+        owner: StylesheetSource,
         s: &Arc<Stylesheet>,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
     ) {
@@ -241,28 +279,31 @@ impl DocumentOrShadowRoot {
             None,
             StyleSheetInDocument {
                 sheet: s.clone(),
-                owner: Dom::from_ref(owner),
+                owner,
             },
             &guard,
         );
     }
 
-    /// Add a stylesheet owned by `owner` to the list of document sheets, in the
+    /// Add a stylesheet from `owner` to the list of document sheets, in the
     /// correct tree position.
     #[cfg_attr(crown, allow(crown::unrooted_must_root))] // Owner needs to be rooted already necessarily.
     pub(crate) fn add_stylesheet(
-        owner: &Element,
+        owner: StylesheetSource,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
         sheet: Arc<Stylesheet>,
         insertion_point: Option<StyleSheetInDocument>,
         style_shared_lock: &StyleSharedRwLock,
     ) {
-        debug_assert!(owner.as_stylesheet_owner().is_some(), "Wat");
+        debug_assert!(
+            match owner {
+                StylesheetSource::Element(ref element) => element.as_stylesheet_owner().is_some(),
+                StylesheetSource::Constructed(ref sheet) => sheet.is_constructed(),
+            },
+            "Wat"
+        );
 
-        let sheet = StyleSheetInDocument {
-            sheet,
-            owner: Dom::from_ref(owner),
-        };
+        let sheet = StyleSheetInDocument { sheet, owner };
 
         let guard = style_shared_lock.read();
 
@@ -276,6 +317,63 @@ impl DocumentOrShadowRoot {
         }
     }
 
+    /// Convert the value that `adoptedStyleSheets` is set to into the stylesheets that it
+    /// lists, which all have to be constructed in `document`.
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    #[allow(unsafe_code)]
+    pub(crate) fn adoptable_stylesheets(
+        cx: JSContext,
+        value: HandleValue,
+        document: &Document,
+    ) -> Fallible<Vec<DomRoot<CSSStyleSheet>>> {
+        let conversion = unsafe { FromJSValConvertible::from_jsval(*cx, value, ()) };
+        let sheets: Vec<DomRoot<CSSStyleSheet>> = match conversion {
+            Ok(ConversionResult::Success(sheets)) => sheets,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into())),
+            _ => return Err(Error::JSFailed),
+        };
+        if sheets
+            .iter()
+            .any(|sheet| !sheet.is_constructed_in(document))
+        {
+            return Err(Error::NotAllowed);
+        }
+        Ok(sheets)
+    }
+
+    /// Replace the adopted stylesheets of `adopter` with `sheets`, and return the stylesheets
+    /// that applied to it before and those that apply now, in order. A stylesheet that was
+    /// adopted more than once only applies at its last place.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn replace_adopted_stylesheets(
+        adopted_stylesheets: &DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+        sheets: Vec<DomRoot<CSSStyleSheet>>,
+        adopter: &StyleSheetListOwner,
+    ) -> (Vec<DomRoot<CSSStyleSheet>>, Vec<DomRoot<CSSStyleSheet>>) {
+        fn applying(sheets: &[Dom<CSSStyleSheet>]) -> Vec<DomRoot<CSSStyleSheet>> {
+            sheets
+                .iter()
+                .enumerate()
+                .filter(|&(index, sheet)| !sheets[index + 1..].contains(sheet))
+                .map(|(_, sheet)| DomRoot::from_ref(&**sheet))
+                .collect()
+        }
+
+        let sheets: Vec<_> = sheets.iter().map(|sheet| Dom::from_ref(&**sheet)).collect();
+        let old_sheets = applying(&std::mem::replace(
+            &mut *adopted_stylesheets.borrow_mut(),
+            sheets,
+        ));
+        let new_sheets = applying(&adopted_stylesheets.borrow());
+        for sheet in &old_sheets {
+            sheet.remove_adopter(adopter);
+        }
+        for sheet in &new_sheets {
+            sheet.add_adopter(adopter);
+        }
+        (old_sheets, new_sheets)
+    }
+
     /// Remove any existing association between the provided id/name and any elements in this document.
     pub(crate) fn unregister_named_element(
         &self,
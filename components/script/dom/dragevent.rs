@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use euclid::default::Point2D;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::DragEventBinding;
+use crate::dom::bindings::codegen::Bindings::DragEventBinding::DragEventMethods;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://html.spec.whatwg.org/multipage/#the-dragevent-interface>
+#[dom_struct]
+pub(crate) struct DragEvent {
+    mouseevent: MouseEvent,
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-dragevent-datatransfer>
+    data_transfer: MutNullableDom<DataTransfer>,
+}
+
+impl DragEvent {
+    fn new_inherited() -> DragEvent {
+        DragEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            data_transfer: MutNullableDom::new(None),
+        }
+    }
+
+    fn new_uninitialized_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<DragEvent> {
+        reflect_dom_object_with_proto(Box::new(DragEvent::new_inherited()), window, proto, can_gc)
+    }
+
+    /// Create a trusted [DragEvent] for a drag that the user does outside of the page,
+    /// like dropping files from the file manager of the system.
+    pub(crate) fn new_trusted(
+        window: &Window,
+        type_: DOMString,
+        cancelable: EventCancelable,
+        point_in_viewport: Point2D<f32>,
+        point_in_target: Point2D<f32>,
+        data_transfer: &DataTransfer,
+        can_gc: CanGc,
+    ) -> DomRoot<DragEvent> {
+        let client_x = point_in_viewport.x as i32;
+        let client_y = point_in_viewport.y as i32;
+        let event = DragEvent::new_with_proto(
+            window,
+            None,
+            type_,
+            EventBubbles::Bubbles,
+            cancelable,
+            Some(window),
+            0,
+            client_x,
+            client_y,
+            client_x,
+            client_y, // TODO: Get real screen coordinates?
+            false,
+            false,
+            false,
+            false,
+            0,
+            0,
+            None,
+            Some(point_in_target),
+            Some(data_transfer),
+            can_gc,
+        );
+
+        event.upcast::<Event>().set_trusted(true);
+        event.upcast::<Event>().set_composed(true);
+
+        event
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        point_in_target: Option<Point2D<f32>>,
+        data_transfer: Option<&DataTransfer>,
+        can_gc: CanGc,
+    ) -> DomRoot<DragEvent> {
+        let ev = DragEvent::new_uninitialized_with_proto(window, proto, can_gc);
+        ev.mouseevent.initialize_mouse_event(
+            type_,
+            can_bubble,
+            cancelable,
+            view,
+            detail,
+            screen_x,
+            screen_y,
+            client_x,
+            client_y,
+            ctrl_key,
+            alt_key,
+            shift_key,
+            meta_key,
+            button,
+            buttons,
+            related_target,
+            point_in_target,
+        );
+        ev.data_transfer.set(data_transfer);
+        ev
+    }
+}
+
+impl DragEventMethods<crate::DomTypeHolder> for DragEvent {
+    /// <https://html.spec.whatwg.org/multipage/#dom-dragevent-dragevent>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        type_: DOMString,
+        init: &DragEventBinding::DragEventInit,
+    ) -> Fallible<DomRoot<DragEvent>> {
+        let event = DragEvent::new_with_proto(
+            window,
+            proto,
+            type_,
+            EventBubbles::from(init.parent.parent.parent.parent.bubbles),
+            EventCancelable::from(init.parent.parent.parent.parent.cancelable),
+            init.parent.parent.parent.view.as_deref(),
+            init.parent.parent.parent.detail,
+            init.parent.screenX,
+            init.parent.screenY,
+            init.parent.clientX,
+            init.parent.clientY,
+            init.parent.parent.ctrlKey,
+            init.parent.parent.altKey,
+            init.parent.parent.shiftKey,
+            init.parent.parent.metaKey,
+            init.parent.button,
+            init.parent.buttons,
+            init.parent.relatedTarget.as_deref(),
+            None,
+            init.dataTransfer.as_deref(),
+            can_gc,
+        );
+        event
+            .upcast::<Event>()
+            .set_composed(init.parent.parent.parent.parent.composed);
+        Ok(event)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-dragevent-datatransfer>
+    fn GetDataTransfer(&self) -> Option<DomRoot<DataTransfer>> {
+        self.data_transfer.get()
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-event-istrusted>
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -12,11 +12,12 @@ use std::rc::Rc;
 use std::str::FromStr;
 use std::{fmt, mem};
 
+use app_units::Au;
 use cssparser::match_ignore_ascii_case;
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
 use embedder_traits::InputMethodType;
-use euclid::default::{Rect, Size2D};
+use euclid::default::{Rect, Size2D, Vector2D};
 use html5ever::serialize::TraversalScope;
 use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use html5ever::{
@@ -27,12 +28,14 @@ use js::jsval::JSVal;
 use js::rust::HandleObject;
 use net_traits::ReferrerPolicy;
 use net_traits::request::CorsSettings;
+use script_layout_interface::ScrollIntoViewGeometry;
 use selectors::Element as SelectorsElement;
 use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
 use selectors::bloom::{BLOOM_HASH_MASK, BloomFilter};
 use selectors::matching::{ElementSelectorFlags, MatchingContext};
 use selectors::sink::Push;
 use servo_arc::Arc;
+use servo_config::pref;
 use style::applicable_declarations::ApplicableDeclarationBlock;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 use style::context::QuirksMode;
@@ -69,18 +72,21 @@ use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut, ref_filter_map};
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::{
-    ElementMethods, GetHTMLOptions, ShadowRootInit,
+    ElementMethods, FullscreenOptions, GetHTMLOptions, ScrollLogicalPosition, ShadowRootInit,
 };
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
     ShadowRootMethods, ShadowRootMode, SlotAssignmentMode,
 };
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     ScrollBehavior, ScrollToOptions, WindowMethods,
 };
-use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::{BooleanOrScrollIntoViewOptions, NodeOrString};
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -98,14 +104,14 @@ use crate::dom::customelementregistry::{
     is_valid_custom_element_name,
 };
 use crate::dom::document::{
-    Document, LayoutDocumentHelpers, ReflowTriggerCondition, determine_policy_for_token,
+    Document, FullscreenEventType, LayoutDocumentHelpers, ReflowTriggerCondition,
+    determine_policy_for_token,
 };
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domrect::DOMRect;
 use crate::dom::domrectlist::DOMRectList;
 use crate::dom::domtokenlist::DOMTokenList;
 use crate::dom::elementinternals::ElementInternals;
-use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlbodyelement::{HTMLBodyElement, HTMLBodyElementLayoutHelpers};
@@ -118,7 +124,7 @@ use crate::dom::htmlformelement::FormControlElementHelpers;
 use crate::dom::htmlhrelement::{HTMLHRElement, HTMLHRLayoutHelpers};
 use crate::dom::htmliframeelement::{HTMLIFrameElement, HTMLIFrameElementLayoutMethods};
 use crate::dom::htmlimageelement::{HTMLImageElement, LayoutHTMLImageElementHelpers};
-use crate::dom::htmlinputelement::{HTMLInputElement, LayoutHTMLInputElementHelpers};
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType, LayoutHTMLInputElementHelpers};
 use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::htmllegendelement::HTMLLegendElement;
 use crate::dom::htmllinkelement::HTMLLinkElement;
@@ -143,9 +149,10 @@ use crate::dom::mutationobserver::{Mutation, MutationObserver};
 use crate::dom::namednodemap::NamedNodeMap;
 use crate::dom::node::{
     BindContext, ChildrenMutation, LayoutNodeHelpers, Node, NodeDamage, NodeFlags, NodeTraits,
-    ShadowIncluding, UnbindContext,
+    ShadowIncluding, UnbindContext, from_untrusted_node_address,
 };
 use crate::dom::nodelist::NodeList;
+use crate::dom::permissions::descriptor_permission_state;
 use crate::dom::promise::Promise;
 use crate::dom::raredata::ElementRareData;
 use crate::dom::servoparser::ServoParser;
@@ -154,6 +161,7 @@ use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::{VirtualMethods, vtable_for};
+use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
@@ -344,6 +352,18 @@ impl Element {
         }
     }
 
+    /// Restyle this element and its descendants after they were moved with `moveBefore()`.
+    /// They keep their style and layout data, so layout can tell whether their style changed
+    /// and reuse their boxes if it didn't.
+    pub(crate) fn restyle_after_move(&self) {
+        self.upcast::<Node>().set_flag(NodeFlags::WAS_MOVED, true);
+        self.node
+            .owner_doc()
+            .ensure_pending_restyle(self)
+            .hint
+            .insert(RestyleHint::restyle_subtree());
+    }
+
     pub(crate) fn set_is(&self, is: LocalName) {
         *self.is.borrow_mut() = Some(is);
     }
@@ -620,6 +640,7 @@ impl Element {
             tree_connected: self.upcast::<Node>().is_connected(),
             tree_is_in_a_document_tree: self.upcast::<Node>().is_in_a_document_tree(),
             tree_is_in_a_shadow_tree: true,
+            is_move: false,
         };
         shadow_root.bind_to_tree(&bind_context, can_gc);
 
@@ -661,6 +682,31 @@ impl Element {
         true
     }
 
+    // https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking
+    pub(crate) fn is_spellcheck_enabled(&self) -> bool {
+        let name = &html5ever::local_name!("spellcheck");
+        if self.has_attribute(name) {
+            match_ignore_ascii_case! { &*self.get_string_attribute(name),
+                "true" | "" => return true,
+                "false" => return false,
+                _ => {},
+            }
+        }
+        // Text controls are true-by-default, and other elements are inherit-by-default.
+        if self.is::<HTMLTextAreaElement>() ||
+            self.downcast::<HTMLInputElement>()
+                .is_some_and(|input| input.input_type().is_spellcheckable())
+        {
+            return true;
+        }
+        if let Some(parent) = self.upcast::<Node>().GetParentNode() {
+            if let Some(elem) = parent.downcast::<Element>() {
+                return elem.is_spellcheck_enabled();
+            }
+        }
+        false
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-directionality
     pub(crate) fn directionality(&self) -> String {
         self.downcast::<HTMLElement>()
@@ -734,6 +780,37 @@ impl Element {
             .registered_intersection_observers
             .retain(|reg_obs| *reg_obs.observer != *observer)
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#will-lazy-load-element-steps>
+    pub(crate) fn will_lazy_load(&self) -> bool {
+        if !pref!(dom_lazy_loading_enabled) {
+            return false;
+        }
+
+        // Step 1. If scripting is disabled for element, then return false.
+        // Note: This is an anti-tracking measure, because if a user agent supported lazy loading
+        // when scripting is disabled, it would still be possible for a site to track a user's
+        // approximate scroll position throughout a session.
+        if !self.owner_document().is_scripting_enabled() {
+            return false;
+        }
+
+        // Step 2. If element's lazy loading attribute is in the Lazy state, then return true.
+        // Step 3. Return false.
+        self.get_string_attribute(&local_name!("loading"))
+            .eq_ignore_ascii_case("lazy")
+    }
+
+    /// Run the [lazy load resumption steps] of this element, if it has any.
+    ///
+    /// [lazy load resumption steps]: https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps
+    pub(crate) fn run_lazy_load_resumption_steps(&self, can_gc: CanGc) {
+        if let Some(image) = self.downcast::<HTMLImageElement>() {
+            image.run_lazy_load_resumption_steps(can_gc);
+        } else if let Some(iframe) = self.downcast::<HTMLIFrameElement>() {
+            iframe.run_lazy_load_resumption_steps(can_gc);
+        }
+    }
 }
 
 /// <https://dom.spec.whatwg.org/#valid-shadow-host-name>
@@ -1561,7 +1638,7 @@ impl Element {
     }
 
     pub(crate) fn is_focusable_area(&self) -> bool {
-        if self.is_actually_disabled() {
+        if self.is_actually_disabled() || self.is_inert() {
             return false;
         }
         let node = self.upcast::<Node>();
@@ -1584,6 +1661,30 @@ impl Element {
         )
     }
 
+    /// Whether this element is inert, because it is in a subtree with the `inert` attribute or
+    /// because a modal dialog that does not contain it blocks its document. Inert elements
+    /// can't be focused, are not the target of user interaction events and are hidden from
+    /// assistive technologies.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#inert>
+    pub(crate) fn is_inert(&self) -> bool {
+        let node = self.upcast::<Node>();
+        let has_inert_ancestor = node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+            .any(|element| element.has_attribute(&local_name!("inert")));
+        if has_inert_ancestor {
+            return true;
+        }
+
+        // <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+        self.owner_document().modal_dialog().is_some_and(|dialog| {
+            !dialog
+                .upcast::<Node>()
+                .is_shadow_including_inclusive_ancestor_of(node)
+        })
+    }
+
     pub(crate) fn is_actually_disabled(&self) -> bool {
         let node = self.upcast::<Node>();
         match node.type_id() {
@@ -2150,6 +2251,21 @@ impl Element {
         win.scroll_node(node, x, y, behavior, can_gc);
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+    pub(crate) fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+        can_gc: CanGc,
+    ) {
+        let window = self.owner_window();
+        let Some(geometry) = window.scroll_into_view_query(self.upcast(), can_gc) else {
+            return;
+        };
+        scroll_geometry_into_view(&window, geometry, behavior, block, inline, can_gc);
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#fragment-parsing-algorithm-steps>
     pub(crate) fn parse_fragment(
         &self,
@@ -2674,6 +2790,29 @@ impl ElementMethods<crate::DomTypeHolder> for Element {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions, can_gc: CanGc) {
+        // Steps 1-4
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+            BooleanOrScrollIntoViewOptions::Boolean(true) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::Start,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::Boolean(false) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::End,
+                ScrollLogicalPosition::Nearest,
+            ),
+        };
+
+        // Steps 5-6
+        self.scroll_into_view(behavior, block, inline, can_gc);
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions, can_gc: CanGc) {
         // Step 1
@@ -3153,6 +3292,11 @@ impl ElementMethods<crate::DomTypeHolder> for Element {
         self.upcast::<Node>().replace_children(nodes, can_gc)
     }
 
+    // https://dom.spec.whatwg.org/#dom-parentnode-movebefore
+    fn MoveBefore(&self, node: &Node, child: Option<&Node>, can_gc: CanGc) -> ErrorResult {
+        self.upcast::<Node>().move_before(node, child, can_gc)
+    }
+
     // https://dom.spec.whatwg.org/#dom-parentnode-queryselector
     fn QuerySelector(&self, selectors: DOMString) -> Fallible<Option<DomRoot<Element>>> {
         let root = self.upcast::<Node>();
@@ -3317,9 +3461,37 @@ impl ElementMethods<crate::DomTypeHolder> for Element {
     }
 
     // https://fullscreen.spec.whatwg.org/#dom-element-requestfullscreen
-    fn RequestFullscreen(&self, can_gc: CanGc) -> Rc<Promise> {
+    fn RequestFullscreen(&self, options: &FullscreenOptions, can_gc: CanGc) -> Rc<Promise> {
         let doc = self.owner_document();
-        doc.enter_fullscreen(self, can_gc)
+        // https://w3c.github.io/window-management/#usage-overview-place-fullscreen-content-on-a-specific-screen
+        // The requested screen is only honoured when the page may see the other screens.
+        let screen = options
+            .screen
+            .as_ref()
+            .filter(|_| {
+                descriptor_permission_state(
+                    PermissionName::Window_management,
+                    Some(doc.window().as_global_scope()),
+                ) == PermissionState::Granted
+            })
+            .map(|screen| screen.id());
+        doc.enter_fullscreen(self, screen, can_gc)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-setpointercapture>
+    fn SetPointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        self.owner_document().set_pointer_capture(self, pointer_id)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-releasepointercapture>
+    fn ReleasePointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        self.owner_document()
+            .release_pointer_capture(self, pointer_id)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-haspointercapture>
+    fn HasPointerCapture(&self, pointer_id: i32) -> bool {
+        self.owner_document().has_pointer_capture(self, pointer_id)
     }
 
     // https://dom.spec.whatwg.org/#dom-element-attachshadow
@@ -3959,9 +4131,30 @@ impl VirtualMethods for Element {
 
         let doc = self.owner_document();
 
-        let fullscreen = doc.GetFullscreenElement();
-        if fullscreen.as_deref() == Some(self) {
-            doc.exit_fullscreen(CanGc::note());
+        // https://html.spec.whatwg.org/multipage/#sequential-focus-navigation-starting-point
+        // Sequential focus navigation continues from where the removed starting point was.
+        if doc
+            .sequential_focus_navigation_starting_point()
+            .is_some_and(|starting_point| *starting_point == *self.upcast::<Node>())
+        {
+            let starting_point = context
+                .prev_sibling
+                .and_then(|sibling| sibling.traverse_preorder(ShadowIncluding::No).last())
+                .unwrap_or_else(|| DomRoot::from_ref(context.parent));
+            doc.set_sequential_focus_navigation_starting_point(Some(&starting_point));
+        }
+
+        // https://fullscreen.spec.whatwg.org/#removing-steps
+        // Moving an element does not run these steps, so it stays in fullscreen.
+        if !context.is_move {
+            if self.fullscreen_state() {
+                if doc.fullscreen_element().as_deref() == Some(self) {
+                    doc.exit_fullscreen(CanGc::note());
+                } else {
+                    self.set_fullscreen_state(false);
+                }
+            }
+            doc.remove_from_top_layer(self);
         }
         if let Some(ref value) = *self.id_attribute.borrow() {
             if let Some(ref shadow_root) = self.containing_shadow_root() {
@@ -4379,6 +4572,12 @@ impl Element {
                 let element = self.downcast::<HTMLLabelElement>().unwrap();
                 Some(element as &dyn Activatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLSelectElement,
+            )) => {
+                let element = self.downcast::<HTMLSelectElement>().unwrap();
+                Some(element as &dyn Activatable)
+            },
             NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
                 let element = self.downcast::<HTMLElement>().unwrap();
                 Some(element as &dyn Activatable)
@@ -4553,6 +4752,32 @@ impl Element {
         let node = self.upcast::<Node>();
         node.owner_doc().element_state_will_change(self);
         self.state.set(state);
+
+        // The theme of layout paints form controls differently in these states, even when no
+        // selector depends on them.
+        let themed_states = ElementState::HOVER |
+            ElementState::ACTIVE |
+            ElementState::CHECKED |
+            ElementState::INDETERMINATE |
+            ElementState::DISABLED;
+        if which.intersects(themed_states) && self.is_themed_form_control() {
+            node.dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
+    /// Whether layout paints this element with the theme of form controls.
+    fn is_themed_form_control(&self) -> bool {
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return matches!(
+                input.input_type(),
+                InputType::Checkbox |
+                    InputType::Radio |
+                    InputType::Button |
+                    InputType::Submit |
+                    InputType::Reset
+            );
+        }
+        self.is::<HTMLButtonElement>() || self.is::<HTMLSelectElement>()
     }
 
     /// <https://html.spec.whatwg.org/multipage/#concept-selector-active>
@@ -4573,6 +4798,11 @@ impl Element {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    /// Set whether this element matches `:focus-visible`, which indicates its focus.
+    pub(crate) fn set_focus_visible_state(&self, value: bool) {
+        self.set_state(ElementState::FOCUSRING, value);
+    }
+
     pub(crate) fn hover_state(&self) -> bool {
         self.state.get().contains(ElementState::HOVER)
     }
@@ -4620,6 +4850,10 @@ impl Element {
         self.set_state(ElementState::URLTARGET, value)
     }
 
+    pub(crate) fn fullscreen_state(&self) -> bool {
+        self.state.get().contains(ElementState::FULLSCREEN)
+    }
+
     pub(crate) fn set_fullscreen_state(&self, value: bool) {
         self.set_state(ElementState::FULLSCREEN, value)
     }
@@ -4794,9 +5028,7 @@ impl TaskOnce for ElementPerformFullscreenEnter {
 
         // Step 7.1
         if self.error || !element.fullscreen_element_ready_check() {
-            document
-                .upcast::<EventTarget>()
-                .fire_event(atom!("fullscreenerror"), CanGc::note());
+            document.queue_fullscreen_event(FullscreenEventType::Error, &element);
             promise.reject_error(
                 Error::Type(String::from("fullscreen is not connected")),
                 CanGc::note(),
@@ -4805,14 +5037,16 @@ impl TaskOnce for ElementPerformFullscreenEnter {
         }
 
         // TODO Step 7.2-4
-        // Step 7.5
-        element.set_fullscreen_state(true);
-        document.set_fullscreen_element(Some(&element));
-
-        // Step 7.6
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"), CanGc::note());
+        // Step 7.5 If element is already the fullscreen element of its document, there is
+        // nothing to do. Otherwise, set its fullscreen flag, move it to the top of the top layer
+        // and append (fullscreenchange, element) to the list of pending fullscreen events.
+        // Firing the event happens during the next rendering update, when the fullscreen steps
+        // are run.
+        if document.fullscreen_element().as_deref() != Some(&*element) {
+            element.set_fullscreen_state(true);
+            document.add_to_top_layer(&element);
+            document.queue_fullscreen_event(FullscreenEventType::Change, &element);
+        }
 
         // Step 7.7
         promise.resolve_native(&(), CanGc::note());
@@ -4839,14 +5073,12 @@ impl TaskOnce for ElementPerformFullscreenExit {
         let element = self.element.root();
         let document = element.owner_document();
         // TODO Step 9.1-5
-        // Step 9.6
+        // Step 9.6 Unfullscreen the element, removing it from the top layer.
         element.set_fullscreen_state(false);
-        document.set_fullscreen_element(None);
+        document.remove_from_top_layer(&element);
 
-        // Step 9.8
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"), CanGc::note());
+        // Step 9.8 Append (fullscreenchange, element) to the list of pending fullscreen events.
+        document.queue_fullscreen_event(FullscreenEventType::Change, &element);
 
         // Step 9.10
         self.promise.root().resolve_native(&(), CanGc::note());
@@ -4900,6 +5132,110 @@ pub(crate) fn reflect_referrer_policy_attribute(element: &Element) -> DOMString
     DOMString::new()
 }
 
+/// Scroll the target area of `geometry` into view in each of its scrolling boxes, from the
+/// innermost one to the viewport.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+#[allow(unsafe_code)]
+pub(crate) fn scroll_geometry_into_view(
+    window: &Window,
+    geometry: ScrollIntoViewGeometry,
+    behavior: ScrollBehavior,
+    block: ScrollLogicalPosition,
+    inline: ScrollLogicalPosition,
+    can_gc: CanGc,
+) {
+    // TODO: This assumes a horizontal writing mode, where the block axis is vertical.
+    let mut target_rect = geometry.target_rect;
+    for scroller in geometry.scrollers {
+        let node = unsafe { from_untrusted_node_address(scroller.node) };
+        let current_position = window.scroll_offset_query(&node);
+        let x = scroll_into_view_position(
+            target_rect.min_x(),
+            target_rect.max_x(),
+            scroller.scrollport.min_x(),
+            scroller.scrollport.max_x(),
+            current_position.x as f64,
+            inline,
+        )
+        .clamp(0., scroller.scroll_range.width.to_f64_px());
+        let y = scroll_into_view_position(
+            target_rect.min_y(),
+            target_rect.max_y(),
+            scroller.scrollport.min_y(),
+            scroller.scrollport.max_y(),
+            current_position.y as f64,
+            block,
+        )
+        .clamp(0., scroller.scroll_range.height.to_f64_px());
+        if x != current_position.x as f64 || y != current_position.y as f64 {
+            window.scroll_node(&node, x, y, behavior, can_gc);
+        }
+
+        // Continue with the area of the scroller that the target is scrolled into, as seen
+        // by the scrollers that contain it.
+        target_rect =
+            target_rect.translate(Vector2D::new(Au::from_f64_px(-x), Au::from_f64_px(-y)));
+    }
+
+    // Finally, scroll the viewport.
+    let viewport = window.window_size().initial_viewport;
+    let x = scroll_into_view_position(
+        target_rect.min_x(),
+        target_rect.max_x(),
+        Au::default(),
+        Au::from_f32_px(viewport.width),
+        window.ScrollX() as f64,
+        inline,
+    );
+    let y = scroll_into_view_position(
+        target_rect.min_y(),
+        target_rect.max_y(),
+        Au::default(),
+        Au::from_f32_px(viewport.height),
+        window.ScrollY() as f64,
+        block,
+    );
+    window.scroll(x, y, behavior, can_gc);
+}
+
+/// The scroll position on one axis that scrolls the area from `start` to `end` into the part
+/// of a scrolling box from `scrollport_start` to `scrollport_end` when it is not scrolled,
+/// given its current scroll position and the requested alignment.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+fn scroll_into_view_position(
+    start: Au,
+    end: Au,
+    scrollport_start: Au,
+    scrollport_end: Au,
+    current_position: f64,
+    alignment: ScrollLogicalPosition,
+) -> f64 {
+    let (start, end) = (start.to_f64_px(), end.to_f64_px());
+    let (scrollport_start, scrollport_end) =
+        (scrollport_start.to_f64_px(), scrollport_end.to_f64_px());
+    let align_start = start - scrollport_start;
+    let align_end = end - scrollport_end;
+    match alignment {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => (align_start + align_end) / 2.,
+        ScrollLogicalPosition::Nearest => {
+            let start_outside = start < scrollport_start + current_position;
+            let end_outside = end > scrollport_end + current_position;
+            if start_outside == end_outside {
+                // The area is either entirely visible, or it covers the whole scrolling box.
+                current_position
+            } else if start_outside == (end - start <= scrollport_end - scrollport_start) {
+                align_start
+            } else {
+                align_end
+            }
+        },
+    }
+}
+
 pub(crate) fn referrer_policy_for_element(element: &Element) -> ReferrerPolicy {
     element
         .get_attribute_by_name(DOMString::from_string(String::from("referrerpolicy")))
@@ -93,6 +93,10 @@ pub(crate) struct Event {
     /// <https://dom.spec.whatwg.org/#event-path>
     path: DomRefCell<Vec<EventPathSegment>>,
 
+    /// The result of `composedPath()` for the current target, which listeners along the event
+    /// path may ask for many times while the event is dispatched.
+    composed_path_cache: DomRefCell<Option<ComposedPathCache>>,
+
     /// <https://dom.spec.whatwg.org/#event-relatedtarget>
     related_target: MutNullableDom<EventTarget>,
 
@@ -113,6 +117,12 @@ pub(crate) struct EventPathSegment {
     /// <https://dom.spec.whatwg.org/#event-path-shadow-adjusted-target>
     shadow_adjusted_target: Option<Dom<EventTarget>>,
 
+    /// The shadow-adjusted target of the last struct that is either this struct or a preceding
+    /// one, whose shadow-adjusted target is non-null. This is the target of the event while
+    /// invoking the listeners of this struct, and is found once when the path is built rather
+    /// than each time that they are invoked.
+    event_target: Option<Dom<EventTarget>>,
+
     /// <https://dom.spec.whatwg.org/#event-path-relatedtarget>
     related_target: Option<Dom<EventTarget>>,
 
@@ -123,6 +133,15 @@ pub(crate) struct EventPathSegment {
     slot_in_closed_tree: bool,
 }
 
+/// The [composed path](https://dom.spec.whatwg.org/#dom-event-composedpath) of an event that
+/// is being dispatched, as seen from one of the targets on its path.
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct ComposedPathCache {
+    current_target: Dom<EventTarget>,
+    composed_path: Vec<Dom<EventTarget>>,
+}
+
 impl Event {
     pub(crate) fn new_inherited() -> Event {
         Event {
@@ -142,6 +161,7 @@ impl Event {
             initialized: Cell::new(false),
             time_stamp: CrossProcessInstant::now(),
             path: DomRefCell::default(),
+            composed_path_cache: DomRefCell::default(),
             related_target: Default::default(),
             in_passive_listener: Cell::new(false),
         }
@@ -261,15 +281,21 @@ impl Event {
         // invocation-target-in-shadow-tree is invocationTargetInShadowTree, shadow-adjusted target is
         // shadowAdjustedTarget, relatedTarget is relatedTarget, touch target list is touchTargets,
         // root-of-closed-tree is root-of-closed-tree, and slot-in-closed-tree is slot-in-closed-tree.
+        let mut path = self.path.borrow_mut();
+        let event_target = match shadow_adjusted_target {
+            Some(shadow_adjusted_target) => Some(Dom::from_ref(shadow_adjusted_target)),
+            None => path.last().and_then(|segment| segment.event_target.clone()),
+        };
         let event_path_segment = EventPathSegment {
             invocation_target: Dom::from_ref(invocation_target),
             shadow_adjusted_target: shadow_adjusted_target.map(Dom::from_ref),
+            event_target,
             related_target: related_target.map(Dom::from_ref),
             invocation_target_in_shadow_tree,
             root_of_closed_tree,
             slot_in_closed_tree,
         };
-        self.path.borrow_mut().push(event_path_segment);
+        path.push(event_path_segment);
     }
 
     /// <https://dom.spec.whatwg.org/#concept-event-dispatch>
@@ -395,7 +421,7 @@ impl Event {
                 let related_target = self
                     .related_target
                     .get()
-                    .map(|related_target| related_target.retarget(&target));
+                    .map(|related_target| related_target.retarget(&parent));
 
                 // TODO: Step 5.9.4 Let touchTargets be a new list.
                 // Step 5.9.5 For each touchTarget of event’s touch target list, append the result of retargeting
@@ -514,7 +540,7 @@ impl Event {
                 .filter(|window| window.need_emit_timeline_marker(TimelineMarkerType::DOMEvent));
 
             // Step 5.13 For each struct in event’s path, in reverse order:
-            for (index, segment) in self.path.borrow().iter().enumerate().rev() {
+            for segment in self.path.borrow().iter().rev() {
                 // Step 5.13.1 If struct’s shadow-adjusted target is non-null, then set event’s
                 // eventPhase attribute to AT_TARGET.
                 if segment.shadow_adjusted_target.is_some() {
@@ -528,7 +554,6 @@ impl Event {
                 // Step 5.13.3 Invoke with struct, event, "capturing", and legacyOutputDidListenersThrowFlag if given.
                 invoke(
                     segment,
-                    index,
                     self,
                     ListenerPhase::Capturing,
                     timeline_window.as_deref(),
@@ -537,7 +562,7 @@ impl Event {
             }
 
             // Step 5.14 For each struct in event’s path:
-            for (index, segment) in self.path.borrow().iter().enumerate() {
+            for segment in self.path.borrow().iter() {
                 // Step 5.14.1 If struct’s shadow-adjusted target is non-null, then set event’s
                 // eventPhase attribute to AT_TARGET.
                 if segment.shadow_adjusted_target.is_some() {
@@ -557,7 +582,6 @@ impl Event {
                 // Step 5.14.3 Invoke with struct, event, "bubbling", and legacyOutputDidListenersThrowFlag if given.
                 invoke(
                     segment,
-                    index,
                     self,
                     ListenerPhase::Bubbling,
                     timeline_window.as_deref(),
@@ -594,6 +618,7 @@ impl Event {
 
         // Step 8. Set event’s path to the empty list.
         self.path.borrow_mut().clear();
+        *self.composed_path_cache.borrow_mut() = None;
 
         // Step 9. Unset event’s dispatch flag, stop propagation flag, and stop immediate propagation flag.
         self.dispatch.set(false);
@@ -791,7 +816,20 @@ impl EventMethods<crate::DomTypeHolder> for Event {
     }
 
     /// <https://dom.spec.whatwg.org/#dom-event-composedpath>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     fn ComposedPath(&self) -> Vec<DomRoot<EventTarget>> {
+        // The composed path only depends on the current target while the event is dispatched.
+        let current_target = self.GetCurrentTarget();
+        if let Some(cache) = self.composed_path_cache.borrow().as_ref() {
+            if current_target.as_deref() == Some(&*cache.current_target) {
+                return cache
+                    .composed_path
+                    .iter()
+                    .map(|target| target.as_rooted())
+                    .collect();
+            }
+        }
+
         // Step 1. Let composedPath be an empty list.
         let mut composed_path = vec![];
 
@@ -804,7 +842,7 @@ impl EventMethods<crate::DomTypeHolder> for Event {
         }
 
         // Step 4. Let currentTarget be this’s currentTarget attribute value.
-        let current_target = self.GetCurrentTarget();
+        // NOTE: This was found above, to look up the cached composed path.
 
         // Step 5. Append currentTarget to composedPath.
         // TODO: https://github.com/whatwg/dom/issues/1343
@@ -852,6 +890,7 @@ impl EventMethods<crate::DomTypeHolder> for Event {
         let mut max_hidden_level = current_target_hidden_subtree_level;
 
         // Step 11. Set index to currentTargetIndex − 1.
+        let mut preceding_targets = vec![];
         // Step 12. While index is greater than or equal to 0:
         // NOTE: This is just iterating part of the path in reverse
         for element in path.iter().take(current_target_index).rev() {
@@ -863,7 +902,7 @@ impl EventMethods<crate::DomTypeHolder> for Event {
             // Step 12.2 If currentHiddenLevel is less than or equal to maxHiddenLevel,
             // then prepend path[index]'s invocation target to composedPath.
             if current_hidden_level <= max_hidden_level {
-                composed_path.insert(0, element.invocation_target.as_rooted());
+                preceding_targets.push(element.invocation_target.as_rooted());
             }
 
             // Step 12.3 If path[index]'s slot-in-closed-tree is true:
@@ -881,6 +920,11 @@ impl EventMethods<crate::DomTypeHolder> for Event {
             // Step 12.4 Decrease index by 1.
         }
 
+        // The preceding targets were found in reverse order, and are prepended at once.
+        preceding_targets.reverse();
+        preceding_targets.append(&mut composed_path);
+        let mut composed_path = preceding_targets;
+
         // Step 13. Set currentHiddenLevel and maxHiddenLevel to currentTargetHiddenSubtreeLevel.
         current_hidden_level = current_target_hidden_subtree_level;
         max_hidden_level = current_target_hidden_subtree_level;
@@ -916,6 +960,15 @@ impl EventMethods<crate::DomTypeHolder> for Event {
             // Step 15.4 Increase index by 1.
         }
 
+        *self.composed_path_cache.borrow_mut() =
+            current_target.map(|current_target| ComposedPathCache {
+                current_target: current_target.as_traced(),
+                composed_path: composed_path
+                    .iter()
+                    .map(|target| target.as_traced())
+                    .collect(),
+            });
+
         // Step 16. Return composedPath.
         composed_path
     }
@@ -1119,7 +1172,6 @@ impl TaskOnce for SimpleEventTask {
 /// <https://dom.spec.whatwg.org/#concept-event-listener-invoke>
 fn invoke(
     segment: &EventPathSegment,
-    segment_index_in_path: usize,
     event: &Event,
     phase: ListenerPhase,
     timeline_window: Option<&Window>,
@@ -1128,14 +1180,7 @@ fn invoke(
 ) {
     // Step 1. Set event’s target to the shadow-adjusted target of the last struct in event’s path,
     // that is either struct or preceding struct, whose shadow-adjusted target is non-null.
-    event.target.set(
-        event.path.borrow()[..segment_index_in_path + 1]
-            .iter()
-            .rev()
-            .flat_map(|segment| segment.shadow_adjusted_target.clone())
-            .next()
-            .as_deref(),
-    );
+    event.target.set(segment.event_target.as_deref());
 
     // Step 2. Set event’s relatedTarget to struct’s relatedTarget.
     event.related_target.set(segment.related_target.as_deref());
@@ -1265,7 +1310,7 @@ fn inner_invoke(
         }
 
         // Step 2.9 If listener’s passive is true, then set event's in passive listener flag.
-        event.set_in_passive_listener(event_target.is_passive(&event.type_(), listener));
+        event.set_in_passive_listener(listener.borrow().passive());
 
         // Step 2.10 If global is a Window object, then record timing info for event listener
         // given event and listener.
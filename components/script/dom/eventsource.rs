@@ -563,6 +563,7 @@ impl EventSourceMethods<crate::DomTypeHolder> for EventSource {
             global.insecure_requests_policy(),
         )
         .origin(global.origin().immutable().clone())
+        .top_level_origin(Some(global.top_level_origin()))
         .pipeline_id(Some(global.pipeline_id()));
 
         // Step 10
@@ -22,7 +22,6 @@ use js::rust::{
 };
 use libc::c_char;
 use servo_url::ServoUrl;
-use style::str::HTML_SPACE_CHARACTERS;
 use stylo_atoms::Atom;
 
 use crate::conversions::Convert;
@@ -311,7 +310,10 @@ pub(crate) struct EventListenerEntry {
     phase: ListenerPhase,
     listener: EventListenerType,
     once: bool,
-    passive: Option<bool>,
+    /// Whether the listener is passive, which is resolved against the
+    /// [default passive value](https://dom.spec.whatwg.org/#default-passive-value)
+    /// when the listener is added rather than each time it is invoked.
+    passive: bool,
     removed: bool,
 }
 
@@ -328,6 +330,10 @@ impl EventListenerEntry {
         self.removed
     }
 
+    pub(crate) fn passive(&self) -> bool {
+        self.passive
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#getting-the-current-value-of-the-event-handler>
     pub(crate) fn get_compiled_listener(
         &self,
@@ -449,13 +455,9 @@ impl EventTarget {
     /// <https://dom.spec.whatwg.org/#default-passive-value>
     fn default_passive_value(&self, ty: &Atom) -> bool {
         // Return true if all of the following are true:
-        let event_type = ty.to_ascii_lowercase();
-
         // type is one of "touchstart", "touchmove", "wheel", or "mousewheel"
-        let matches_event_type = matches!(
-            event_type.trim_matches(HTML_SPACE_CHARACTERS),
-            "touchstart" | "touchmove" | "wheel" | "mousewheel"
-        );
+        let matches_event_type =
+            matches!(&**ty, "touchstart" | "touchmove" | "wheel" | "mousewheel");
 
         if !matches_event_type {
             return false;
@@ -484,6 +486,7 @@ impl EventTarget {
 
     /// <https://html.spec.whatwg.org/multipage/#event-handler-attributes:event-handlers-11>
     fn set_inline_event_listener(&self, ty: Atom, listener: Option<InlineEventListener>) {
+        let passive = self.default_passive_value(&ty);
         let mut handlers = self.handlers.borrow_mut();
         let entries = match handlers.entry(ty) {
             Occupied(entry) => entry.into_mut(),
@@ -511,7 +514,7 @@ impl EventTarget {
                         phase: ListenerPhase::Bubbling,
                         listener: EventListenerType::Inline(listener.into()),
                         once: false,
-                        passive: None,
+                        passive,
                         removed: false,
                     })));
                 }
@@ -529,14 +532,6 @@ impl EventTarget {
         }
     }
 
-    /// Determines the `passive` attribute of an associated event listener
-    pub(crate) fn is_passive(&self, ty: &Atom, listener: &Rc<RefCell<EventListenerEntry>>) -> bool {
-        listener
-            .borrow()
-            .passive
-            .unwrap_or(self.default_passive_value(ty))
-    }
-
     fn get_inline_event_listener(&self, ty: &Atom, can_gc: CanGc) -> Option<CommonEventHandler> {
         let handlers = self.handlers.borrow();
         handlers
@@ -812,8 +807,16 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
+
+        // Step 4. If listener’s passive is null, then set it to the default passive value
+        // given listener’s type and eventTarget.
+        let passive = options
+            .passive
+            .unwrap_or_else(|| self.default_passive_value(&ty));
+
         let mut handlers = self.handlers.borrow_mut();
-        let entries = match handlers.entry(Atom::from(ty)) {
+        let entries = match handlers.entry(ty) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -827,7 +830,7 @@ impl EventTarget {
             phase,
             listener: EventListenerType::Additive(listener),
             once: options.once,
-            passive: options.passive,
+            passive,
             removed: false,
         }));
 
@@ -857,7 +860,7 @@ impl EventTarget {
                 phase,
                 listener: EventListenerType::Additive(listener.clone()),
                 once: false,
-                passive: None,
+                passive: false,
                 removed: false,
             }));
             if let Some(position) = entries.iter().position(|e| *e == old_entry) {
@@ -17,7 +17,7 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::{Blob, blob_parts_to_bytes, normalize_type_string};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -28,16 +28,24 @@ pub(crate) struct File {
     blob: Blob,
     name: DOMString,
     modified: SystemTime,
+    /// <https://wicg.github.io/entries-api/#dom-file-webkitrelativepath>
+    relative_path: DOMString,
 }
 
 impl File {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
-    fn new_inherited(blob_impl: &BlobImpl, name: DOMString, modified: Option<SystemTime>) -> File {
+    fn new_inherited(
+        blob_impl: &BlobImpl,
+        name: DOMString,
+        modified: Option<SystemTime>,
+        relative_path: DOMString,
+    ) -> File {
         File {
             blob: Blob::new_inherited(blob_impl),
             name,
             // https://w3c.github.io/FileAPI/#dfn-lastModified
             modified: modified.unwrap_or_else(SystemTime::now),
+            relative_path,
         }
     }
 
@@ -48,7 +56,15 @@ impl File {
         modified: Option<SystemTime>,
         can_gc: CanGc,
     ) -> DomRoot<File> {
-        Self::new_with_proto(global, None, blob_impl, name, modified, can_gc)
+        Self::new_with_proto(
+            global,
+            None,
+            blob_impl,
+            name,
+            modified,
+            DOMString::new(),
+            can_gc,
+        )
     }
 
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
@@ -58,10 +74,16 @@ impl File {
         blob_impl: BlobImpl,
         name: DOMString,
         modified: Option<SystemTime>,
+        relative_path: DOMString,
         can_gc: CanGc,
     ) -> DomRoot<File> {
         let file = reflect_dom_object_with_proto(
-            Box::new(File::new_inherited(&blob_impl, name, modified)),
+            Box::new(File::new_inherited(
+                &blob_impl,
+                name,
+                modified,
+                relative_path,
+            )),
             global,
             proto,
             can_gc,
@@ -83,8 +105,9 @@ impl File {
                 .expect("File name encoding error"),
         );
 
-        File::new(
+        File::new_with_proto(
             window.upcast(),
+            None,
             BlobImpl::new_from_file(
                 selected.id,
                 selected.filename,
@@ -93,6 +116,7 @@ impl File {
             ),
             name,
             Some(selected.modified),
+            DOMString::from(selected.relative_path.unwrap_or_default()),
             can_gc,
         )
     }
@@ -142,6 +166,7 @@ impl FileMethods<crate::DomTypeHolder> for File {
             BlobImpl::new_from_bytes(bytes, type_string),
             replaced_filename,
             modified,
+            DOMString::new(),
             can_gc,
         ))
     }
@@ -158,4 +183,9 @@ impl FileMethods<crate::DomTypeHolder> for File {
         (OffsetDateTime::from(self.modified) - OffsetDateTime::UNIX_EPOCH).whole_milliseconds()
             as i64
     }
+
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    fn WebkitRelativePath(&self) -> USVString {
+        USVString(self.relative_path.to_string())
+    }
 }
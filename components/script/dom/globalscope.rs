@@ -14,6 +14,7 @@ use std::thread::JoinHandle;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{mem, ptr};
 
+use base::cross_process_instant::CrossProcessInstant;
 use base::id::{
     BlobId, BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineId,
     ServiceWorkerId, ServiceWorkerRegistrationId, WebViewId,
@@ -103,6 +104,7 @@ use crate::dom::bindings::weakref::{DOMTracker, WeakRef};
 use crate::dom::blob::Blob;
 use crate::dom::broadcastchannel::BroadcastChannel;
 use crate::dom::crypto::Crypto;
+use crate::dom::debuggerglobalscope::DebuggerGlobalScope;
 use crate::dom::dedicatedworkerglobalscope::{
     DedicatedWorkerControlMsg, DedicatedWorkerGlobalScope,
 };
@@ -129,6 +131,7 @@ use crate::dom::underlyingsourcecontainer::UnderlyingSourceType;
 use crate::dom::webgpu::gpudevice::GPUDevice;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::identityhub::IdentityHub;
+use crate::dom::websocket::WebSocket;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
@@ -294,6 +297,9 @@ pub(crate) struct GlobalScope {
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
 
+    /// Vector storing references of all websockets.
+    websocket_tracker: DOMTracker<WebSocket>,
+
     /// Storage for watching rejected promises waiting for some client to
     /// consume their rejection.
     /// Promises in this list have been rejected in the last turn of the
@@ -752,6 +758,7 @@ impl GlobalScope {
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            websocket_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
             user_agent,
@@ -2018,6 +2025,23 @@ impl GlobalScope {
         canceled_any_fetch
     }
 
+    pub(crate) fn track_websocket(&self, websocket: &WebSocket) {
+        self.websocket_tracker.track(websocket);
+    }
+
+    /// Make all of the WebSocket objects of this global disappear, returning whether any of
+    /// them were connecting or open.
+    ///
+    /// <https://websockets.spec.whatwg.org/#make-disappear>
+    pub(crate) fn make_websockets_disappear(&self) -> bool {
+        let mut affected_any_websocket = false;
+        self.websocket_tracker
+            .for_each(|websocket: DomRoot<WebSocket>| {
+                affected_any_websocket |= websocket.make_disappear();
+            });
+        affected_any_websocket
+    }
+
     /// Returns the global scope of the realm that the given DOM object's reflector
     /// was created in.
     #[allow(unsafe_code)]
@@ -2305,6 +2329,9 @@ impl GlobalScope {
             // https://drafts.css-houdini.org/worklets/#script-settings-for-worklets
             return worklet.base_url();
         }
+        if let Some(debugger_global) = self.downcast::<DebuggerGlobalScope>() {
+            return debugger_global.get_url();
+        }
         unreachable!();
     }
 
@@ -2320,6 +2347,9 @@ impl GlobalScope {
             // TODO: is this the right URL to return?
             return worklet.base_url();
         }
+        if let Some(debugger_global) = self.downcast::<DebuggerGlobalScope>() {
+            return debugger_global.get_url();
+        }
         unreachable!();
     }
 
@@ -2396,6 +2426,20 @@ impl GlobalScope {
         InsecureRequestsPolicy::DoNotUpgrade
     }
 
+    /// The origin of the top-level document that this global belongs to, which keys the
+    /// cookies and storage that fetches initiated from this global may access.
+    pub(crate) fn top_level_origin(&self) -> ImmutableOrigin {
+        if let Some(window) = self.downcast::<Window>() {
+            if let Some(top_level_origin) = window.top_level_origin() {
+                return top_level_origin;
+            }
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.top_level_origin();
+        }
+        self.origin().immutable().clone()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#report-the-error>
     pub(crate) fn report_an_error(&self, error_info: ErrorInfo, value: HandleValue, can_gc: CanGc) {
         // Step 1.
@@ -2769,6 +2813,22 @@ impl GlobalScope {
         self.timers().suspend();
     }
 
+    pub(crate) fn pause_timer_clock(&self) {
+        self.timers().pause_clock();
+    }
+
+    pub(crate) fn resume_timer_clock(&self) {
+        self.timers().resume_clock();
+    }
+
+    pub(crate) fn advance_timer_clock(&self, by: Duration, can_gc: CanGc) {
+        self.timers().advance_clock(by, self, can_gc);
+    }
+
+    pub(crate) fn controlled_timer_clock_time(&self) -> Option<CrossProcessInstant> {
+        self.timers().controlled_clock_time()
+    }
+
     pub(crate) fn slow_down_timers(&self) {
         self.timers().slow_down();
     }
@@ -3239,6 +3299,7 @@ impl GlobalScope {
         request_builder: RequestBuilder,
         network_listener: NetworkListener<Listener>,
     ) {
+        let request_builder = request_builder.top_level_origin(Some(self.top_level_origin()));
         fetch_async(
             &self.core_resource_thread(),
             request_builder,
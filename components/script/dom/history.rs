@@ -14,12 +14,14 @@ use js::rust::{HandleValue, MutableHandleValue};
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use profile_traits::ipc::channel;
-use script_traits::{ScriptMsg, StructuredSerializedData};
+use script_traits::{
+    ScriptMsg, ScrollRestorationMode, SessionHistoryEntryState, StructuredSerializedData,
+};
 use servo_url::ServoUrl;
 
-use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::HistoryBinding::{HistoryMethods, ScrollRestoration};
 use crate::dom::bindings::codegen::Bindings::LocationBinding::Location_Binding::LocationMethods;
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
@@ -48,6 +50,10 @@ pub(crate) struct History {
     state: Heap<JSVal>,
     #[no_trace]
     state_id: Cell<Option<HistoryStateId>>,
+    /// The persisted state of the current session history entry. The states of the other
+    /// entries are kept by the constellation, which hands them back on traversal.
+    #[no_trace]
+    entry_state: Cell<SessionHistoryEntryState>,
 }
 
 impl History {
@@ -59,6 +65,7 @@ impl History {
             window: Dom::from_ref(window),
             state,
             state_id: Cell::new(None),
+            entry_state: Default::default(),
         }
     }
 
@@ -68,6 +75,47 @@ impl History {
 }
 
 impl History {
+    /// Set the persisted state of the current session history entry, when this document
+    /// reloads an entry whose document was discarded.
+    pub(crate) fn set_entry_state(&self, entry_state: SessionHistoryEntryState) {
+        self.entry_state.set(entry_state);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#persisted-user-state-restoration>
+    ///
+    /// Save the scroll position of the current session history entry, and store the state of
+    /// the entry in the session history of the constellation, so that it can be restored when
+    /// the user traverses back to it.
+    pub(crate) fn persist_entry_state(&self) {
+        let mut entry_state = self.entry_state.get();
+        entry_state.scroll_position = Some((self.window.ScrollX(), self.window.ScrollY()));
+        self.entry_state.set(entry_state);
+
+        let msg = ScriptMsg::SetSessionHistoryEntryState(self.state_id.get(), entry_state);
+        let _ = self
+            .window
+            .as_global_scope()
+            .script_to_constellation_chan()
+            .send(msg);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#restore-persisted-state>
+    ///
+    /// Restore the scroll position of the current session history entry, if one was saved and
+    /// its scroll restoration mode is "auto". Returns whether the scroll position was restored.
+    pub(crate) fn restore_scroll_position(&self, can_gc: CanGc) -> bool {
+        let entry_state = self.entry_state.get();
+        if entry_state.scroll_restoration_mode != ScrollRestorationMode::Auto {
+            return false;
+        }
+        let Some((x, y)) = entry_state.scroll_position else {
+            return false;
+        };
+        self.window
+            .scroll(x as f64, y as f64, ScrollBehavior::Instant, can_gc);
+        true
+    }
+
     fn traverse_history(&self, direction: TraversalDirection) -> ErrorResult {
         if !self.window.Document().is_fully_active() {
             return Err(Error::Security);
@@ -88,8 +136,12 @@ impl History {
         &self,
         state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        entry_state: SessionHistoryEntryState,
         can_gc: CanGc,
     ) {
+        // Persist the state of the entry that is being traversed away from.
+        self.persist_entry_state();
+
         // Steps 5
         let document = self.window.Document();
         let old_url = document.url().clone();
@@ -98,14 +150,19 @@ impl History {
         // Step 6
         let hash_changed = old_url.fragment() != url.fragment();
 
-        // Step 8
-        if let Some(fragment) = url.fragment() {
-            document.check_and_scroll_fragment(fragment, can_gc);
-        }
-
         // Step 11
         let state_changed = state_id != self.state_id.get();
         self.state_id.set(state_id);
+        self.entry_state.set(entry_state);
+
+        // Step 8
+        // Note: Restoring the persisted scroll position of the entry takes precedence over
+        // scrolling to the fragment.
+        if !self.restore_scroll_position(can_gc) {
+            if let Some(fragment) = url.fragment() {
+                document.check_and_scroll_fragment(fragment, can_gc);
+            }
+        }
         let serialized_data = match state_id {
             Some(state_id) => {
                 let (tx, rx) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
@@ -225,6 +282,14 @@ impl History {
         // Step 8
         let state_id = match push_or_replace {
             PushOrReplace::Push => {
+                // The new entry inherits the scroll restoration mode of the current entry,
+                // whose state is persisted so that it can be restored on traversal.
+                self.persist_entry_state();
+                self.entry_state.set(SessionHistoryEntryState {
+                    scroll_restoration_mode: self.entry_state.get().scroll_restoration_mode,
+                    scroll_position: None,
+                });
+
                 let state_id = HistoryStateId::new();
                 self.state_id.set(Some(state_id));
                 let msg = ScriptMsg::PushHistoryState(state_id, new_url.clone());
@@ -332,6 +397,39 @@ impl HistoryMethods<crate::DomTypeHolder> for History {
         Ok(())
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration>
+    fn GetScrollRestoration(&self) -> Fallible<ScrollRestoration> {
+        // Step 1. If this's relevant global object's associated Document is not fully active,
+        // then throw a "SecurityError" DOMException.
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        // Step 2. Return this's node navigable's active session history entry's scroll
+        // restoration mode.
+        Ok(match self.entry_state.get().scroll_restoration_mode {
+            ScrollRestorationMode::Auto => ScrollRestoration::Auto,
+            ScrollRestorationMode::Manual => ScrollRestoration::Manual,
+        })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration>
+    fn SetScrollRestoration(&self, value: ScrollRestoration) -> ErrorResult {
+        // Step 1. If this's relevant global object's associated Document is not fully active,
+        // then throw a "SecurityError" DOMException.
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        // Step 2. Set this's node navigable's active session history entry's scroll restoration
+        // mode to the given value.
+        let mut entry_state = self.entry_state.get();
+        entry_state.scroll_restoration_mode = match value {
+            ScrollRestoration::Auto => ScrollRestorationMode::Auto,
+            ScrollRestoration::Manual => ScrollRestorationMode::Manual,
+        };
+        self.entry_state.set(entry_state);
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-history-length>
     fn GetLength(&self) -> Fallible<u32> {
         if !self.window.Document().is_fully_active() {
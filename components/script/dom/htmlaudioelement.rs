@@ -44,14 +44,16 @@ impl HTMLAudioElement {
         proto: Option<HandleObject>,
         can_gc: CanGc,
     ) -> DomRoot<HTMLAudioElement> {
-        Node::reflect_node_with_proto(
+        let element = Node::reflect_node_with_proto(
             Box::new(HTMLAudioElement::new_inherited(
                 local_name, prefix, document,
             )),
             document,
             proto,
             can_gc,
-        )
+        );
+        document.window().track_media_element(element.upcast());
+        element
     }
 }
 
@@ -5,13 +5,14 @@
 use std::cell::{Cell, Ref};
 
 use dom_struct::dom_struct;
-use html5ever::{LocalName, Prefix, local_name};
+use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
 use js::rust::HandleObject;
 
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLDetailsElementBinding::HTMLDetailsElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLSlotElementBinding::HTMLSlotElement_Binding::HTMLSlotElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::GetRootNodeOptions;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::Node_Binding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
     ShadowRootMode, SlotAssignmentMode,
@@ -20,20 +21,51 @@ use crate::dom::bindings::codegen::UnionTypes::ElementOrText;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
-use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::element::{AttributeMutation, Element, ElementCreator};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlslotelement::HTMLSlotElement;
-use crate::dom::node::{BindContext, ChildrenMutation, Node, NodeDamage, NodeTraits};
+use crate::dom::htmlstyleelement::HTMLStyleElement;
+use crate::dom::node::{
+    BindContext, ChildrenMutation, Node, NodeDamage, NodeTraits, ShadowIncluding,
+};
 use crate::dom::shadowroot::IsUserAgentWidget;
 use crate::dom::text::Text;
+use crate::dom::toggleevent::ToggleEvent;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
 /// The summary that should be presented if no `<summary>` element is present
 const DEFAULT_SUMMARY: &str = "Details";
 
+/// The style of the UA shadow tree. The summary is a list item whose marker shows whether the
+/// details are open, and the content is hidden while they are closed, so that opening and
+/// closing the details only restyles them. Styles of the page that apply to its `<summary>`
+/// element take precedence, so it can change or hide the marker with `list-style-type`.
+const DETAILS_STYLE: &str = "
+    #summary > summary,
+    #summary::slotted(summary) {
+        display: list-item;
+        counter-increment: list-item 0;
+        list-style: disclosure-closed inside;
+    }
+    :host([open]) #summary > summary,
+    :host([open]) #summary::slotted(summary) {
+        list-style-type: disclosure-open;
+    }
+    #details-content {
+        display: block;
+    }
+    /* TODO: This should hide the content with content-visibility, which Servo does not
+       support yet. */
+    :host(:not([open])) #details-content {
+        display: none;
+    }
+";
+
 /// Holds handles to all slots in the UA shadow tree
 ///
 /// The composition of the tree is described in
@@ -43,8 +75,6 @@ const DEFAULT_SUMMARY: &str = "Details";
 struct ShadowTree {
     summary: Dom<HTMLSlotElement>,
     descendants: Dom<HTMLSlotElement>,
-    /// The summary that is displayed if no other summary exists
-    implicit_summary: Dom<HTMLElement>,
 }
 
 #[dom_struct]
@@ -52,6 +82,11 @@ pub(crate) struct HTMLDetailsElement {
     htmlelement: HTMLElement,
     toggle_counter: Cell<u32>,
 
+    /// Whether the details were open before the pending `toggle` event was queued, if there
+    /// is one.
+    /// <https://html.spec.whatwg.org/multipage/#details-toggle-task-tracker>
+    pending_toggle_old_state: Cell<Option<bool>>,
+
     /// Represents the UA widget for the details element
     shadow_tree: DomRefCell<Option<ShadowTree>>,
 }
@@ -65,6 +100,7 @@ impl HTMLDetailsElement {
         HTMLDetailsElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             toggle_counter: Cell::new(0),
+            pending_toggle_old_state: Cell::new(None),
             shadow_tree: Default::default(),
         }
     }
@@ -116,7 +152,23 @@ impl HTMLDetailsElement {
             )
             .expect("Attaching UA shadow root failed");
 
+        let style = HTMLStyleElement::new(
+            local_name!("style"),
+            None,
+            &document,
+            None,
+            ElementCreator::ScriptCreated,
+            can_gc,
+        );
+        style
+            .upcast::<Node>()
+            .SetTextContent(Some(DOMString::from(DETAILS_STYLE)), can_gc);
+        root.upcast::<Node>()
+            .AppendChild(style.upcast::<Node>())
+            .unwrap();
+
         let summary = HTMLSlotElement::new(local_name!("slot"), None, &document, None, can_gc);
+        summary.upcast::<Element>().SetId("summary".into(), can_gc);
         root.upcast::<Node>()
             .AppendChild(summary.upcast::<Node>())
             .unwrap();
@@ -132,6 +184,9 @@ impl HTMLDetailsElement {
             .unwrap();
 
         let descendants = HTMLSlotElement::new(local_name!("slot"), None, &document, None, can_gc);
+        descendants
+            .upcast::<Element>()
+            .SetId("details-content".into(), can_gc);
         root.upcast::<Node>()
             .AppendChild(descendants.upcast::<Node>())
             .unwrap();
@@ -139,10 +194,8 @@ impl HTMLDetailsElement {
         let _ = self.shadow_tree.borrow_mut().insert(ShadowTree {
             summary: summary.as_traced(),
             descendants: descendants.as_traced(),
-            implicit_summary: fallback_summary.as_traced(),
         });
-        self.upcast::<Node>()
-            .dirty(crate::dom::node::NodeDamage::OtherNodeDamage);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
     pub(crate) fn find_corresponding_summary_element(&self) -> Option<DomRoot<HTMLElement>> {
@@ -157,16 +210,23 @@ impl HTMLDetailsElement {
     fn update_shadow_tree_contents(&self, can_gc: CanGc) {
         let shadow_tree = self.shadow_tree(can_gc);
 
-        if let Some(summary) = self.find_corresponding_summary_element() {
-            shadow_tree
-                .summary
-                .Assign(vec![ElementOrText::Element(DomRoot::upcast(summary))]);
-        }
+        let summary = self.find_corresponding_summary_element();
+        shadow_tree.summary.Assign(
+            summary
+                .iter()
+                .map(|summary| ElementOrText::Element(DomRoot::upcast(summary.clone())))
+                .collect(),
+        );
 
+        // All of the other children are slotted into the content, including other
+        // `<summary>` elements.
         let mut slottable_children = vec![];
         for child in self.upcast::<Node>().children() {
             if let Some(element) = child.downcast::<Element>() {
-                if element.local_name() == &local_name!("summary") {
+                if summary
+                    .as_ref()
+                    .is_some_and(|summary| summary.upcast::<Element>() == element)
+                {
                     continue;
                 }
 
@@ -182,44 +242,88 @@ impl HTMLDetailsElement {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
-    fn update_shadow_tree_styles(&self, can_gc: CanGc) {
-        let shadow_tree = self.shadow_tree(can_gc);
-
-        let value = if self.Open() {
-            "display: block;"
-        } else {
-            // TODO: This should be "display: block; content-visibility: hidden;",
-            // but servo does not support content-visibility yet
-            "display: none;"
+    /// <https://html.spec.whatwg.org/multipage/#details-name-group>
+    fn details_name_group(&self) -> Vec<DomRoot<HTMLDetailsElement>> {
+        let Some(name) = self.upcast::<Element>().get_name() else {
+            return vec![];
         };
-        shadow_tree
-            .descendants
-            .upcast::<Element>()
-            .set_string_attribute(&local_name!("style"), value.into(), can_gc);
-
-        // Manually update the list item style of the implicit summary element.
-        // Unlike the other summaries, this summary is in the shadow tree and
-        // can't be styled with UA sheets
-        let implicit_summary_list_item_style = if self.Open() {
-            "disclosure-open"
-        } else {
-            "disclosure-closed"
-        };
-        let implicit_summary_style = format!(
-            "display: list-item;
-            counter-increment: list-item 0;
-            list-style: {implicit_summary_list_item_style} inside;"
-        );
-        shadow_tree
-            .implicit_summary
-            .upcast::<Element>()
-            .set_string_attribute(&local_name!("style"), implicit_summary_style.into(), can_gc);
+        self.upcast::<Node>()
+            .GetRootNode(&GetRootNodeOptions::empty())
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLDetailsElement>)
+            .filter(|other| {
+                **other != *self && other.upcast::<Element>().get_name().as_ref() == Some(&name)
+            })
+            .collect()
+    }
 
-        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    /// <https://html.spec.whatwg.org/multipage/#ensure-details-exclusivity-by-closing-other-elements-if-needed>
+    fn close_other_details_in_group(&self, can_gc: CanGc) {
+        if let Some(other) = self
+            .details_name_group()
+            .into_iter()
+            .find(|other| other.Open())
+        {
+            other
+                .upcast::<Element>()
+                .remove_attribute(&ns!(), &local_name!("open"), can_gc);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#ensure-details-exclusivity-by-closing-the-given-element-if-needed>
+    fn close_if_other_details_in_group_open(&self, can_gc: CanGc) {
+        if !self.Open() {
+            return;
+        }
+        if self.details_name_group().iter().any(|other| other.Open()) {
+            self.upcast::<Element>()
+                .remove_attribute(&ns!(), &local_name!("open"), can_gc);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#queue-a-details-toggle-event-task>
+    fn queue_toggle_event(&self, old_state: bool, new_state: bool) {
+        // A pending event is replaced, but its old state is kept, so that toggling the details
+        // several times before the event is fired only fires a single event.
+        let old_state = self.pending_toggle_old_state.take().unwrap_or(old_state);
+        self.pending_toggle_old_state.set(Some(old_state));
+
+        let counter = self.toggle_counter.get() + 1;
+        self.toggle_counter.set(counter);
+
+        let state = |open| DOMString::from(if open { "open" } else { "closed" });
+        let (old_state, new_state) = (state(old_state), state(new_state));
+        let this = Trusted::new(self);
+        self.owner_global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task!(details_notification_task_steps: move || {
+                let this = this.root();
+                if counter != this.toggle_counter.get() {
+                    return;
+                }
+                this.pending_toggle_old_state.set(None);
+                let event = ToggleEvent::new(
+                    &this.owner_window(),
+                    atom!("toggle"),
+                    false,
+                    false,
+                    old_state,
+                    new_state,
+                    CanGc::note(),
+                );
+                event.upcast::<Event>().fire(this.upcast::<EventTarget>(), CanGc::note());
+            }));
     }
 }
 
 impl HTMLDetailsElementMethods<crate::DomTypeHolder> for HTMLDetailsElement {
+    // https://html.spec.whatwg.org/multipage/#dom-details-name
+    make_getter!(Name, "name");
+
+    // https://html.spec.whatwg.org/multipage/#dom-details-name
+    make_atomic_setter!(SetName, "name");
+
     // https://html.spec.whatwg.org/multipage/#dom-details-open
     make_bool_getter!(Open, "open");
 
@@ -232,28 +336,26 @@ impl VirtualMethods for HTMLDetailsElement {
         Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#the-details-element:concept-element-attributes-change-ext>
     fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation, can_gc: CanGc) {
         self.super_type()
             .unwrap()
             .attribute_mutated(attr, mutation, can_gc);
 
-        if attr.local_name() == &local_name!("open") {
-            self.update_shadow_tree_styles(CanGc::note());
-
-            let counter = self.toggle_counter.get() + 1;
-            self.toggle_counter.set(counter);
-
-            let this = Trusted::new(self);
-            self.owner_global()
-                .task_manager()
-                .dom_manipulation_task_source()
-                .queue(task!(details_notification_task_steps: move || {
-                    let this = this.root();
-                    if counter == this.toggle_counter.get() {
-                        this.upcast::<EventTarget>().fire_event(atom!("toggle"), CanGc::note());
-                    }
-                }));
-            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        if *attr.namespace() != ns!() {
+            return;
+        }
+        match *attr.local_name() {
+            local_name!("name") => self.close_if_other_details_in_group_open(can_gc),
+            local_name!("open") => match mutation {
+                AttributeMutation::Set(None) => {
+                    self.queue_toggle_event(false, true);
+                    self.close_other_details_in_group(can_gc);
+                },
+                AttributeMutation::Removed => self.queue_toggle_event(true, false),
+                AttributeMutation::Set(Some(_)) => {},
+            },
+            _ => {},
         }
     }
 
@@ -267,6 +369,6 @@ impl VirtualMethods for HTMLDetailsElement {
         self.super_type().unwrap().bind_to_tree(context, can_gc);
 
         self.update_shadow_tree_contents(CanGc::note());
-        self.update_shadow_tree_styles(CanGc::note());
+        self.close_if_other_details_in_group_open(can_gc);
     }
 }
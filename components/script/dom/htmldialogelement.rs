@@ -5,23 +5,28 @@
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
 use js::rust::HandleObject;
+use stylo_dom::ElementState;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::Document;
+use crate::dom::document::{Document, FocusType};
 use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{Node, NodeTraits};
+use crate::dom::node::{Node, NodeTraits, ShadowIncluding, UnbindContext};
+use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
 #[dom_struct]
 pub(crate) struct HTMLDialogElement {
     htmlelement: HTMLElement,
     return_value: DomRefCell<DOMString>,
+    /// <https://html.spec.whatwg.org/multipage/#previously-focused-element>
+    previously_focused_element: MutNullableDom<Element>,
 }
 
 impl HTMLDialogElement {
@@ -33,6 +38,7 @@ impl HTMLDialogElement {
         HTMLDialogElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             return_value: DomRefCell::new(DOMString::new()),
+            previously_focused_element: Default::default(),
         }
     }
 
@@ -53,6 +59,61 @@ impl HTMLDialogElement {
             can_gc,
         )
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#is-modal>
+    ///
+    /// The flag is kept in the element state, so that the dialog matches `:modal`.
+    fn is_modal(&self) -> bool {
+        self.upcast::<Element>()
+            .state()
+            .contains(ElementState::MODAL)
+    }
+
+    fn set_modal(&self, modal: bool) {
+        self.upcast::<Element>()
+            .set_state(ElementState::MODAL, modal);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dialog-focusing-steps>
+    fn run_dialog_focusing_steps(&self, can_gc: CanGc) {
+        let element = self.upcast::<Element>();
+
+        // Step 1. If subject is inert, return.
+        if element.is_inert() {
+            return;
+        }
+
+        // Step 2-3. Let control be the dialog itself if it has the autofocus attribute, or
+        // else its focus delegate, which is its first focusable descendant, preferring one
+        // with the autofocus attribute.
+        let control = if element.has_attribute(&local_name!("autofocus")) {
+            None
+        } else {
+            let focusable_descendants: Vec<_> = self
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::Yes)
+                .skip(1)
+                .filter_map(DomRoot::downcast::<Element>)
+                .filter(|descendant| descendant.is_focusable_area())
+                .collect();
+            focusable_descendants
+                .iter()
+                .find(|descendant| descendant.has_attribute(&local_name!("autofocus")))
+                .or(focusable_descendants.first())
+                .cloned()
+        };
+
+        // Step 4. Run the focusing steps for control. If neither the dialog nor any of its
+        // descendants can be focused, the focus moves to the viewport, so that it doesn't
+        // stay on an element that the dialog may have made inert.
+        let control = control.or_else(|| {
+            element
+                .is_focusable_area()
+                .then(|| DomRoot::from_ref(element))
+        });
+        self.owner_document()
+            .request_focus(control.as_deref(), FocusType::Element, can_gc);
+    }
 }
 
 impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
@@ -74,20 +135,27 @@ impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-dialog-show>
-    fn Show(&self, can_gc: CanGc) {
+    fn Show(&self, can_gc: CanGc) -> ErrorResult {
         let element = self.upcast::<Element>();
+        let is_open = element.has_attribute(&local_name!("open"));
 
-        // Step 1 TODO: Check is modal flag is false
-        if element.has_attribute(&local_name!("open")) {
-            return;
+        // Step 1. If this has an open attribute and the is modal flag of this is false, then
+        // return.
+        if is_open && !self.is_modal() {
+            return Ok(());
         }
 
-        // TODO: Step 2 If this has an open attribute, then throw an "InvalidStateError" DOMException.
+        // Step 2. If this has an open attribute, then throw an "InvalidStateError" DOMException.
+        if is_open {
+            return Err(Error::InvalidState);
+        }
 
-        // Step 3
+        // Step 3. Add an open attribute to this, whose value is the empty string.
         element.set_bool_attribute(&local_name!("open"), true, can_gc);
 
-        // TODO: Step 4 Set this's previously focused element to the focused element.
+        // Step 4. Set this's previously focused element to the focused element.
+        self.previously_focused_element
+            .set(self.owner_document().get_focused_element().as_deref());
 
         // TODO: Step 5 Let hideUntil be the result of running topmost popover ancestor given this, null, and false.
 
@@ -95,33 +163,126 @@ impl HTMLDialogElementMethods<crate::DomTypeHolder> for HTMLDialogElement {
 
         // TODO: Step 7 Run hide all popovers until given hideUntil, false, and true.
 
-        // TODO(Issue #32702): Step 8 Run the dialog focusing steps given this.
+        // Step 8. Run the dialog focusing steps given this.
+        self.run_dialog_focusing_steps(can_gc);
+        Ok(())
     }
 
-    // https://html.spec.whatwg.org/multipage/#dom-dialog-close
-    fn Close(&self, return_value: Option<DOMString>) {
+    /// <https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal>
+    fn ShowModal(&self, can_gc: CanGc) -> ErrorResult {
+        let element = self.upcast::<Element>();
+        let is_open = element.has_attribute(&local_name!("open"));
+
+        // Step 1. If this has an open attribute and the is modal flag of this is true, then
+        // return.
+        if is_open && self.is_modal() {
+            return Ok(());
+        }
+
+        // Step 2. If this has an open attribute, then throw an "InvalidStateError" DOMException.
+        if is_open {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3. If this is not connected, then throw an "InvalidStateError" DOMException.
+        if !self.upcast::<Node>().is_connected() {
+            return Err(Error::InvalidState);
+        }
+
+        // TODO: Step 4. If this is in the popover showing state, then throw an
+        // "InvalidStateError" DOMException.
+
+        // TODO: Step 5. If the result of firing an event named beforetoggle is false, then
+        // return.
+
+        // Step 6. Add an open attribute to this, whose value is the empty string.
+        element.set_bool_attribute(&local_name!("open"), true, can_gc);
+
+        // Step 7. Set the is modal flag of this to true, which makes this's node document
+        // blocked by the modal dialog this.
+        self.set_modal(true);
+
+        // Step 8. If this's node document's top layer does not already contain this, then add
+        // an element to the top layer given this.
+        let document = self.owner_document();
+        document.add_to_top_layer(element);
+
+        // Step 9. Set this's previously focused element to the focused element.
+        self.previously_focused_element
+            .set(document.get_focused_element().as_deref());
+
+        // TODO: Step 10-12. Hide the popovers that are not ancestors of this.
+
+        // Step 13. Run the dialog focusing steps given this.
+        self.run_dialog_focusing_steps(can_gc);
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-dialog-close>
+    fn Close(&self, return_value: Option<DOMString>, can_gc: CanGc) {
         let element = self.upcast::<Element>();
         let target = self.upcast::<EventTarget>();
 
         // Step 1 & 2
         if element
-            .remove_attribute(&ns!(), &local_name!("open"), CanGc::note())
+            .remove_attribute(&ns!(), &local_name!("open"), can_gc)
             .is_none()
         {
             return;
         }
 
-        // Step 3
+        // Step 3. If is modal of subject is true, then remove it from its node document's top
+        // layer and set its is modal flag to false.
+        let document = self.owner_document();
+        if self.is_modal() {
+            document.remove_from_top_layer(element);
+            self.set_modal(false);
+        }
+
+        // Step 4
         if let Some(new_value) = return_value {
             *self.return_value.borrow_mut() = new_value;
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 5. If subject's previously focused element is not null, then set it to null and
+        // run the focusing steps for it, if the focus is within the dialog.
+        if let Some(previously_focused_element) = self.previously_focused_element.take() {
+            let focus_is_within_dialog = document.get_focused_element().is_none_or(|focused| {
+                self.upcast::<Node>()
+                    .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+            });
+            if focus_is_within_dialog {
+                document.request_focus(
+                    Some(&previously_focused_element),
+                    FocusType::Element,
+                    can_gc,
+                );
+            }
+        }
+
+        // TODO: Step 6 implement pending dialog stack removal
 
-        // Step 5
+        // Step 7
         self.owner_global()
             .task_manager()
             .dom_manipulation_task_source()
             .queue_simple_event(target, atom!("close"));
     }
 }
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-dialog-element:html-element-removing-steps>
+    fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
+        self.super_type().unwrap().unbind_from_tree(context, can_gc);
+
+        // A dialog that is removed from its document no longer blocks it.
+        if self.is_modal() {
+            self.owner_document().remove_from_top_layer(self.upcast());
+            self.set_modal(false);
+        }
+    }
+}
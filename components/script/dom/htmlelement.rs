@@ -52,6 +52,7 @@ use crate::dom::node::{BindContext, Node, NodeTraits, ShadowIncluding, UnbindCon
 use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::focus_navigation;
 use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 
@@ -178,6 +179,11 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_setter!(SetHidden, "hidden");
 
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    make_bool_getter!(Inert, "inert");
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    make_bool_setter!(SetInert, "inert");
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!(NoOnload);
 
@@ -415,8 +421,15 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
     fn Focus(&self, can_gc: CanGc) {
         // TODO: Mark the element as locked for focus and run the focusing steps.
         // https://html.spec.whatwg.org/multipage/#focusing-steps
+        // A shadow host that delegates its focus focuses its first focusable element instead.
+        let element = self.upcast::<Element>();
+        let focus_target = focus_navigation::focus_delegate(element, can_gc);
         let document = self.owner_document();
-        document.request_focus(Some(self.upcast()), FocusType::Element, can_gc);
+        document.request_focus(
+            Some(focus_target.as_deref().unwrap_or(element)),
+            FocusType::Element,
+            can_gc,
+        );
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-blur
@@ -571,6 +584,20 @@ impl HTMLElementMethods<crate::DomTypeHolder> for HTMLElement {
         );
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn Spellcheck(&self) -> bool {
+        self.as_element().is_spellcheck_enabled()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn SetSpellcheck(&self, value: bool, can_gc: CanGc) {
+        self.as_element().set_string_attribute(
+            &local_name!("spellcheck"),
+            DOMString::from(if value { "true" } else { "false" }),
+            can_gc,
+        );
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-contenteditable
     fn ContentEditable(&self) -> DOMString {
         // TODO: https://github.com/servo/servo/issues/12776
@@ -1131,6 +1158,18 @@ impl VirtualMethods for HTMLElement {
                     },
                 }
             },
+            // Making a subtree inert makes its focused element, if any, lose the focus.
+            (&local_name!("inert"), AttributeMutation::Set(None)) => {
+                let document = element.owner_document();
+                if let Some(focused) = document.get_focused_element() {
+                    if self
+                        .upcast::<Node>()
+                        .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+                    {
+                        document.perform_focus_fixup_rule(&focused, can_gc);
+                    }
+                }
+            },
             _ => {},
         }
     }
@@ -14,7 +14,7 @@ use js::rust::HandleObject;
 use mime::{self, Mime};
 use net_traits::http_percent_encode;
 use net_traits::request::Referrer;
-use script_traits::{LoadData, LoadOrigin, NavigationHistoryBehavior};
+use script_traits::{LoadData, LoadOrigin, NavigationHistoryBehavior, SandboxingFlagSet};
 use servo_rand::random;
 use style::attr::AttrValue;
 use style::str::split_html_space_chars;
@@ -743,7 +743,12 @@ impl HTMLFormElement {
         // Step 3
         let doc = self.owner_document();
         let base = doc.base_url();
-        // TODO: Handle browsing contexts (Step 4, 5)
+        // TODO: Handle browsing contexts (Step 4)
+        // Step 5. If form document's active sandboxing flag set has its sandboxed forms browsing
+        // context flag set, then return.
+        if doc.has_active_sandboxing_flag(SandboxingFlagSet::FORMS) {
+            return;
+        }
         // Step 6
         if submit_method_flag == SubmittedFrom::NotFromForm {
             // Step 6.1
@@ -845,8 +850,11 @@ impl HTMLFormElement {
 
         // Step 19
         let source = doc.browsing_context().unwrap();
-        let (maybe_chosen, _new) = source
-            .choose_browsing_context(target_attribute_value.unwrap_or(DOMString::new()), noopener);
+        let (maybe_chosen, _new) = source.choose_browsing_context(
+            target_attribute_value.unwrap_or(DOMString::new()),
+            noopener,
+            Default::default(),
+        );
 
         // Step 20
         let chosen = match maybe_chosen {
@@ -5,7 +5,6 @@
 use std::cell::Cell;
 
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
-use bitflags::bitflags;
 use constellation_traits::WindowSizeData;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
@@ -15,7 +14,7 @@ use profile_traits::ipc as ProfiledIpc;
 use script_traits::IFrameSandboxState::{IFrameSandboxed, IFrameUnsandboxed};
 use script_traits::{
     IFrameLoadInfo, IFrameLoadInfoWithData, JsEvalResult, LoadData, LoadOrigin,
-    NavigationHistoryBehavior, NewLayoutInfo, ScriptMsg, UpdatePipelineIdReason,
+    NavigationHistoryBehavior, NewLayoutInfo, SandboxingFlagSet, ScriptMsg, UpdatePipelineIdReason,
 };
 use servo_url::ServoUrl;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
@@ -44,21 +43,6 @@ use crate::dom::windowproxy::WindowProxy;
 use crate::script_runtime::CanGc;
 use crate::script_thread::ScriptThread;
 
-#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
-struct SandboxAllowance(u8);
-
-bitflags! {
-    impl SandboxAllowance: u8 {
-        const ALLOW_NOTHING = 0x00;
-        const ALLOW_SAME_ORIGIN = 0x01;
-        const ALLOW_TOP_NAVIGATION = 0x02;
-        const ALLOW_FORMS = 0x04;
-        const ALLOW_SCRIPTS = 0x08;
-        const ALLOW_POINTER_LOCK = 0x10;
-        const ALLOW_POPUPS = 0x20;
-    }
-}
-
 #[derive(PartialEq)]
 enum PipelineType {
     InitialAboutBlank,
@@ -85,14 +69,31 @@ pub(crate) struct HTMLIFrameElement {
     #[no_trace]
     about_blank_pipeline_id: Cell<Option<PipelineId>>,
     sandbox: MutNullableDom<DOMTokenList>,
-    sandbox_allowance: Cell<Option<SandboxAllowance>>,
+    /// <https://html.spec.whatwg.org/multipage/#iframe-sandboxing-flag-set>
+    #[no_trace]
+    sandboxing_flag_set: Cell<Option<SandboxingFlagSet>>,
     load_blocker: DomRefCell<Option<LoadBlocker>>,
     throttled: Cell<bool>,
+    /// The [`LoadData`] of the navigation deferred until this iframe approaches the viewport,
+    /// which is all that is needed to run its lazy load resumption steps.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    #[ignore_malloc_size_of = "Defined in script_traits"]
+    #[no_trace]
+    lazy_load_resumption_steps: DomRefCell<Option<LoadData>>,
 }
 
 impl HTMLIFrameElement {
     pub(crate) fn is_sandboxed(&self) -> bool {
-        self.sandbox_allowance.get().is_some()
+        self.sandboxing_flag_set.get().is_some()
+    }
+
+    /// The sandboxing flags of documents created in this iframe's browsing context, which
+    /// include those of the iframe's own document.
+    /// <https://html.spec.whatwg.org/multipage/#determining-the-creation-sandboxing-flags>
+    fn creation_sandboxing_flag_set(&self) -> SandboxingFlagSet {
+        self.sandboxing_flag_set.get().unwrap_or_default() |
+            self.owner_document().active_sandboxing_flag_set()
     }
 
     /// <https://html.spec.whatwg.org/multipage/#otherwise-steps-for-iframe-or-frame-elements>,
@@ -150,6 +151,7 @@ impl HTMLIFrameElement {
         };
 
         let document = self.owner_document();
+        load_data.sandboxing_flag_set = self.creation_sandboxing_flag_set();
 
         {
             let load_blocker = &self.load_blocker;
@@ -201,6 +203,7 @@ impl HTMLIFrameElement {
                 .get_iframe_size_if_known(browsing_context_id, can_gc)
                 .unwrap_or_default(),
             device_pixel_ratio: window.device_pixel_ratio(),
+            text_zoom: window.window_size().text_zoom,
         };
 
         match pipeline_type {
@@ -252,6 +255,9 @@ impl HTMLIFrameElement {
 
     /// <https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes>
     fn process_the_iframe_attributes(&self, mode: ProcessingMode, can_gc: CanGc) {
+        // Any previously deferred navigation is superseded by this processing of the attributes.
+        self.cancel_lazy_load_resumption_steps();
+
         // > 1. If `element`'s `srcdoc` attribute is specified, then:
         if self
             .upcast::<Element>()
@@ -362,6 +368,25 @@ impl HTMLIFrameElement {
             Some(document.insecure_requests_policy()),
         );
 
+        // Step 2.3: If the will lazy load element steps given element return true, then:
+        if self.upcast::<Element>().will_lazy_load() {
+            // Step 2.3.1: Set element's lazy load resumption steps to the rest of this algorithm
+            // starting with the step labeled navigate.
+            *self.lazy_load_resumption_steps.borrow_mut() = Some(load_data);
+
+            // Step 2.3.2: Start intersection-observing a lazy loading element for element.
+            document.start_intersection_observing_a_lazy_loading_element(self.upcast(), can_gc);
+
+            // Step 2.3.3: Return.
+            return;
+        }
+
+        self.navigate_to_the_resource(load_data, can_gc);
+    }
+
+    /// The steps of <https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes>
+    /// starting with the step labeled "navigate", which are deferred for lazy loading iframes.
+    fn navigate_to_the_resource(&self, load_data: LoadData, can_gc: CanGc) {
         let pipeline_id = self.pipeline_id();
         // If the initial `about:blank` page is the current page, load with replacement enabled,
         // see https://html.spec.whatwg.org/multipage/#the-iframe-element:about:blank-3
@@ -377,6 +402,25 @@ impl HTMLIFrameElement {
         self.navigate_or_reload_child_browsing_context(load_data, history_handling, can_gc);
     }
 
+    /// Run the lazy load resumption steps of this iframe, if it is waiting to approach the
+    /// viewport before navigating.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    pub(crate) fn run_lazy_load_resumption_steps(&self, can_gc: CanGc) {
+        if let Some(load_data) = self.cancel_lazy_load_resumption_steps() {
+            self.navigate_to_the_resource(load_data, can_gc);
+        }
+    }
+
+    /// Stop waiting for this iframe to approach the viewport, returning the [`LoadData`] of the
+    /// deferred navigation, if any.
+    fn cancel_lazy_load_resumption_steps(&self) -> Option<LoadData> {
+        let load_data = self.lazy_load_resumption_steps.borrow_mut().take()?;
+        self.owner_document()
+            .stop_intersection_observing_a_lazy_loading_element(self.upcast());
+        Some(load_data)
+    }
+
     fn create_nested_browsing_context(&self, can_gc: CanGc) {
         // Synchronously create a new browsing context, which will present
         // `about:blank`. (This is not a navigation.)
@@ -465,9 +509,10 @@ impl HTMLIFrameElement {
             pending_pipeline_id: Cell::new(None),
             about_blank_pipeline_id: Cell::new(None),
             sandbox: Default::default(),
-            sandbox_allowance: Cell::new(None),
+            sandboxing_flag_set: Cell::new(None),
             load_blocker: DomRefCell::new(None),
             throttled: Cell::new(false),
+            lazy_load_resumption_steps: DomRefCell::new(None),
         }
     }
 
@@ -640,6 +685,18 @@ impl HTMLIFrameElementMethods<crate::DomTypeHolder> for HTMLIFrameElement {
     // https://html.spec.whatwg.org/multipage/#attr-iframe-referrerpolicy
     make_setter!(SetReferrerPolicy, "referrerpolicy");
 
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    make_enumerated_getter!(
+        Loading,
+        "loading",
+        "lazy" | "eager",
+        missing => "eager",
+        invalid => "eager"
+    );
+
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    make_setter!(SetLoading, "loading");
+
     // https://html.spec.whatwg.org/multipage/#attr-iframe-allowfullscreen
     make_bool_getter!(AllowFullscreen, "allowfullscreen");
     // https://html.spec.whatwg.org/multipage/#attr-iframe-allowfullscreen
@@ -682,21 +739,11 @@ impl VirtualMethods for HTMLIFrameElement {
             .attribute_mutated(attr, mutation, can_gc);
         match *attr.local_name() {
             local_name!("sandbox") => {
-                self.sandbox_allowance
+                self.sandboxing_flag_set
                     .set(mutation.new_value(attr).map(|value| {
-                        let mut modes = SandboxAllowance::ALLOW_NOTHING;
-                        for token in value.as_tokens() {
-                            modes |= match &*token.to_ascii_lowercase() {
-                                "allow-same-origin" => SandboxAllowance::ALLOW_SAME_ORIGIN,
-                                "allow-forms" => SandboxAllowance::ALLOW_FORMS,
-                                "allow-pointer-lock" => SandboxAllowance::ALLOW_POINTER_LOCK,
-                                "allow-popups" => SandboxAllowance::ALLOW_POPUPS,
-                                "allow-scripts" => SandboxAllowance::ALLOW_SCRIPTS,
-                                "allow-top-navigation" => SandboxAllowance::ALLOW_TOP_NAVIGATION,
-                                _ => SandboxAllowance::ALLOW_NOTHING,
-                            };
-                        }
-                        modes
+                        SandboxingFlagSet::parse_sandboxing_directive(
+                            value.as_tokens().iter().map(|token| &**token),
+                        )
                     }));
             },
             local_name!("srcdoc") => {
@@ -729,6 +776,15 @@ impl VirtualMethods for HTMLIFrameElement {
                     self.process_the_iframe_attributes(ProcessingMode::NotFirstTime, CanGc::note());
                 }
             },
+            // > When the loading attribute's state is changed to the Eager state, the user agent
+            // > must run these steps:
+            // > 1. Let resumptionSteps be the element's lazy load resumption steps.
+            // > 2. If resumptionSteps is null, then return.
+            // > 3. Set the element's lazy load resumption steps to null.
+            // > 4. Invoke resumptionSteps.
+            local_name!("loading") if !self.upcast::<Element>().will_lazy_load() => {
+                self.run_lazy_load_resumption_steps(can_gc);
+            },
             _ => {},
         }
     }
@@ -766,8 +822,15 @@ impl VirtualMethods for HTMLIFrameElement {
     fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
         self.super_type().unwrap().unbind_from_tree(context, can_gc);
 
+        // A moved iframe keeps its nested browsing context, so that its document is not
+        // unloaded and loaded again.
+        if context.is_move {
+            return;
+        }
+
         let blocker = &self.load_blocker;
         LoadBlocker::terminate(blocker, CanGc::note());
+        self.cancel_lazy_load_resumption_steps();
 
         // https://html.spec.whatwg.org/multipage/#a-browsing-context-is-discarded
         let window = self.owner_window();
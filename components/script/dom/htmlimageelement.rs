@@ -28,7 +28,7 @@ use net_traits::{
     ResourceFetchTiming, ResourceTimingType,
 };
 use num_traits::ToPrimitive;
-use pixels::{CorsStatus, Image, ImageMetadata};
+use pixels::{CorsStatus, Image, ImageMetadata, is_supported_image_mime_type};
 use servo_url::ServoUrl;
 use servo_url::origin::MutableOrigin;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto, parse_integer, parse_length};
@@ -99,6 +99,9 @@ enum ParseState {
 pub(crate) struct SourceSet {
     image_sources: Vec<ImageSource>,
     source_size: SourceSizeList,
+    /// The width of the image's concrete object size, used as the source size instead of
+    /// `source_size` when the image uses `sizes="auto"`.
+    auto_source_size: Option<Au>,
 }
 
 impl SourceSet {
@@ -106,6 +109,7 @@ impl SourceSet {
         SourceSet {
             image_sources: Vec::new(),
             source_size: SourceSizeList::empty(),
+            auto_source_size: None,
         }
     }
 }
@@ -153,6 +157,19 @@ struct ImageRequest {
     final_url: Option<ServoUrl>,
     current_pixel_density: Option<f64>,
 }
+
+/// The state needed to run the [lazy load resumption steps] of an image, which are the rest of
+/// the update the image data algorithm starting with the step labeled "fetch the image".
+///
+/// [lazy load resumption steps]: https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps
+#[derive(JSTraceable, MallocSizeOf)]
+struct LazyLoadResumptionSteps {
+    #[no_trace]
+    url: ServoUrl,
+    src: USVString,
+    selected_pixel_density: f64,
+}
+
 #[dom_struct]
 pub(crate) struct HTMLImageElement {
     htmlelement: HTMLElement,
@@ -166,6 +183,13 @@ pub(crate) struct HTMLImageElement {
     last_selected_source: DomRefCell<Option<USVString>>,
     #[ignore_malloc_size_of = "promises are hard"]
     image_decode_promises: DomRefCell<Vec<Rc<Promise>>>,
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    lazy_load_resumption_steps: DomRefCell<Option<LazyLoadResumptionSteps>>,
+    /// The width of the concrete object size of this image in the last layout, if it allows
+    /// auto-sizes and is being rendered.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in app_units"]
+    auto_sizes_width: Cell<Option<Au>>,
 }
 
 impl HTMLImageElement {
@@ -587,6 +611,17 @@ impl HTMLImageElement {
             None => vec![DomRoot::from_ref(elem)],
         };
 
+        // An image that allows auto-sizes uses the width of its concrete object size as the
+        // source size of a `sizes` attribute starting with "auto", as long as it is being
+        // rendered. Otherwise the "auto" entry is ignored. Layout reports that width after
+        // every reflow, so that it is never necessary to force a layout here.
+        let auto_source_size = if self.allows_auto_sizes() {
+            self.owner_document().add_auto_sized_image(self);
+            self.auto_sizes_width.get()
+        } else {
+            None
+        };
+
         // Step 3
         let width = match elem.get_attribute(&ns!(), &local_name!("width")) {
             Some(x) => match parse_length(&x.value()) {
@@ -611,6 +646,9 @@ impl HTMLImageElement {
 
                 // Step 4.1.2
                 if let Some(x) = element.get_attribute(&ns!(), &local_name!("sizes")) {
+                    if sizes_attribute_starts_with_auto(&x.value()) {
+                        source_set.auto_source_size = auto_source_size;
+                    }
                     source_set.source_size =
                         parse_a_sizes_attribute(DOMString::from_string(x.value().to_string()));
                 }
@@ -673,20 +711,22 @@ impl HTMLImageElement {
 
             // Step 4.7
             if let Some(x) = element.get_attribute(&ns!(), &local_name!("sizes")) {
+                if sizes_attribute_starts_with_auto(&x.value()) {
+                    source_set.auto_source_size = auto_source_size;
+                }
                 source_set.source_size =
                     parse_a_sizes_attribute(DOMString::from_string(x.value().to_string()));
             }
 
-            // Step 4.8
+            // Step 4.8. If child has a type attribute, and its value is an unknown or
+            // unsupported MIME type, continue to the next child.
             if let Some(x) = element.get_attribute(&ns!(), &local_name!("type")) {
-                // TODO Handle unsupported mime type
-                let mime = x.value().parse::<Mime>();
-                match mime {
-                    Ok(m) => match m.type_() {
-                        mime::IMAGE => (),
-                        _ => continue,
-                    },
-                    _ => continue,
+                let is_supported = x
+                    .value()
+                    .parse::<Mime>()
+                    .is_ok_and(|mime| is_supported_image_mime_type(mime.essence_str()));
+                if !is_supported {
+                    continue;
                 }
             }
 
@@ -743,7 +783,10 @@ impl HTMLImageElement {
         let source_size = &mut source_set.source_size;
 
         // Find source_size_length for Step 2.2
-        let source_size_length = self.evaluate_source_size_list(source_size, width);
+        let source_size_length = match source_set.auto_source_size {
+            Some(auto_source_size) => auto_source_size,
+            None => self.evaluate_source_size_list(source_size, width),
+        };
 
         // Step 2
         for imgsource in &mut source_set.image_sources {
@@ -848,6 +891,36 @@ impl HTMLImageElement {
         src: &USVString,
         selected_pixel_density: f64,
         can_gc: CanGc,
+    ) {
+        // If the will lazy load element steps given the img return true, then:
+        if self.upcast::<Element>().will_lazy_load() {
+            // Set the img's lazy load resumption steps to the rest of this algorithm starting
+            // with the step labeled fetch the image.
+            *self.lazy_load_resumption_steps.borrow_mut() = Some(LazyLoadResumptionSteps {
+                url: url.clone(),
+                src: src.clone(),
+                selected_pixel_density,
+            });
+
+            // Start intersection-observing a lazy loading element for the img element.
+            self.owner_document()
+                .start_intersection_observing_a_lazy_loading_element(self.upcast(), can_gc);
+
+            // Return.
+            return;
+        }
+
+        self.fetch_the_image(url, src, selected_pixel_density, can_gc);
+    }
+
+    /// The steps of html.spec.whatwg.org/multipage/#update-the-image-data starting with the step
+    /// labeled "fetch the image", which are deferred for lazy loading images.
+    fn fetch_the_image(
+        &self,
+        url: &ServoUrl,
+        src: &USVString,
+        selected_pixel_density: f64,
+        can_gc: CanGc,
     ) {
         match self.image_request.get() {
             ImageRequestPhase::Pending => {
@@ -894,8 +967,45 @@ impl HTMLImageElement {
         self.fetch_image(url, can_gc);
     }
 
+    /// Run the lazy load resumption steps of this image, if it is waiting to approach the
+    /// viewport before being fetched.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    pub(crate) fn run_lazy_load_resumption_steps(&self, can_gc: CanGc) {
+        let Some(mut steps) = self.cancel_lazy_load_resumption_steps() else {
+            return;
+        };
+
+        // The concrete object size of an image that allows auto-sizes is only known once it is
+        // rendered, so select the image source again now that it is about to be displayed.
+        if self.allows_auto_sizes() {
+            if let Some((src, selected_pixel_density)) = self.select_image_source() {
+                if let Ok(url) = self.owner_document().base_url().join(&src.0) {
+                    steps = LazyLoadResumptionSteps {
+                        url,
+                        src,
+                        selected_pixel_density,
+                    };
+                }
+            }
+        }
+
+        self.fetch_the_image(&steps.url, &steps.src, steps.selected_pixel_density, can_gc);
+    }
+
+    /// Stop waiting for this image to approach the viewport, returning its lazy load resumption
+    /// steps, if any.
+    fn cancel_lazy_load_resumption_steps(&self) -> Option<LazyLoadResumptionSteps> {
+        let steps = self.lazy_load_resumption_steps.borrow_mut().take()?;
+        self.owner_document()
+            .stop_intersection_observing_a_lazy_loading_element(self.upcast());
+        Some(steps)
+    }
+
     /// Step 8-12 of html.spec.whatwg.org/multipage/#update-the-image-data
     fn update_the_image_data_sync_steps(&self, can_gc: CanGc) {
+        // Any previously deferred fetch is superseded by this update of the image data.
+        self.cancel_lazy_load_resumption_steps();
         let document = self.owner_document();
         let global = self.owner_global();
         let task_manager = global.task_manager();
@@ -996,6 +1106,12 @@ impl HTMLImageElement {
             .borrow_mut()
             .clone_from(&selected_source);
 
+        // Step 2.2 of <https://html.spec.whatwg.org/multipage/#dom-img-decode>:
+        // pending decodes are rejected once the current request changes.
+        if self.current_request.borrow().source_url != selected_source {
+            self.reject_image_decode_promises(can_gc);
+        }
+
         // Step 6, check the list of available images
         if let Some(src) = selected_source {
             if let Ok(img_url) = base_url.join(&src) {
@@ -1184,7 +1300,9 @@ impl HTMLImageElement {
             self.current_request.borrow().state,
             State::CompletelyAvailable
         ) {
-            // this doesn't follow the spec, but it's been discussed in <https://github.com/whatwg/html/issues/4217>
+            // The image cache decodes images on its worker threads before the request
+            // becomes completely available, so there is nothing left to decode here.
+            // See <https://github.com/whatwg/html/issues/4217>.
             promise.resolve_native(&(), can_gc);
         } else {
             self.image_decode_promises
@@ -1256,6 +1374,43 @@ impl HTMLImageElement {
         );
     }
 
+    /// Record the width of the concrete object size of this image, as reported by layout. A
+    /// change of that width is a relevant mutation of an image that allows auto-sizes.
+    pub(crate) fn set_auto_sizes_width(&self, width: Option<Au>) {
+        if self.auto_sizes_width.replace(width) == width || width.is_none() {
+            return;
+        }
+
+        // An image that is waiting to be lazily loaded selects its source again when it is
+        // fetched, so there is nothing to update yet.
+        if self.lazy_load_resumption_steps.borrow().is_some() {
+            return;
+        }
+
+        let this = Trusted::new(self);
+        self.owner_global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task!(update_auto_sized_image: move || {
+                this.root().update_the_image_data(CanGc::note());
+            }));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#allows-auto-sizes>
+    pub(crate) fn allows_auto_sizes(&self) -> bool {
+        // > An img element allows auto-sizes if: its loading attribute is in the Lazy state, and
+        // > its sizes attribute's value is "auto" (ASCII case-insensitive), or starts with
+        // > "auto," (ASCII case-insensitive).
+        let element = self.upcast::<Element>();
+        let is_lazy = element
+            .get_attribute(&ns!(), &local_name!("loading"))
+            .is_some_and(|loading| loading.value().eq_ignore_ascii_case("lazy"));
+        is_lazy &&
+            element
+                .get_attribute(&ns!(), &local_name!("sizes"))
+                .is_some_and(|sizes| sizes_attribute_starts_with_auto(&sizes.value()))
+    }
+
     fn uses_srcset_or_picture(elem: &Element) -> bool {
         let has_src = elem.has_attribute(&local_name!("srcset"));
         let is_parent_picture = elem
@@ -1298,6 +1453,8 @@ impl HTMLImageElement {
             source_set: DomRefCell::new(SourceSet::new()),
             last_selected_source: DomRefCell::new(None),
             image_decode_promises: DomRefCell::new(vec![]),
+            lazy_load_resumption_steps: DomRefCell::new(None),
+            auto_sizes_width: Cell::new(None),
         }
     }
 
@@ -1469,6 +1626,14 @@ impl LayoutHTMLImageElementHelpers for LayoutDom<'_, HTMLImageElement> {
     }
 }
 
+/// Whether the value of a `sizes` attribute is "auto" or starts with "auto,", ignoring ASCII case.
+fn sizes_attribute_starts_with_auto(value: &str) -> bool {
+    value.eq_ignore_ascii_case("auto") ||
+        value
+            .get(.."auto,".len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("auto,"))
+}
+
 //https://html.spec.whatwg.org/multipage/#parse-a-sizes-attribute
 pub(crate) fn parse_a_sizes_attribute(value: DOMString) -> SourceSizeList {
     let mut input = ParserInput::new(&value);
@@ -1552,9 +1717,28 @@ impl HTMLImageElementMethods<crate::DomTypeHolder> for HTMLImageElement {
     make_url_setter!(SetSrc, "src");
 
     // https://html.spec.whatwg.org/multipage/#dom-img-srcset
-    make_url_getter!(Srcset, "srcset");
-    // https://html.spec.whatwg.org/multipage/#dom-img-src
-    make_url_setter!(SetSrcset, "srcset");
+    fn Srcset(&self) -> USVString {
+        USVString(
+            self.upcast::<Element>()
+                .get_string_attribute(&local_name!("srcset"))
+                .into(),
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-srcset
+    fn SetSrcset(&self, value: USVString) {
+        self.upcast::<Element>().set_string_attribute(
+            &local_name!("srcset"),
+            DOMString::from(value.0),
+            CanGc::note(),
+        );
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_getter!(Sizes, "sizes");
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_setter!(SetSizes, "sizes");
 
     // https://html.spec.whatwg.org/multipage/#dom-img-crossOrigin
     fn GetCrossOrigin(&self) -> Option<DOMString> {
@@ -1685,6 +1869,18 @@ impl HTMLImageElementMethods<crate::DomTypeHolder> for HTMLImageElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    make_enumerated_getter!(
+        Loading,
+        "loading",
+        "lazy" | "eager",
+        missing => "eager",
+        invalid => "eager"
+    );
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    make_setter!(SetLoading, "loading");
+
     /// <https://html.spec.whatwg.org/multipage/#dom-img-decode>
     fn Decode(&self, can_gc: CanGc) -> Rc<Promise> {
         // Step 1
@@ -1759,6 +1955,15 @@ impl VirtualMethods for HTMLImageElement {
             &local_name!("crossorigin") |
             &local_name!("sizes") |
             &local_name!("referrerpolicy") => self.update_the_image_data(CanGc::note()),
+            // > When the loading attribute's state is changed to the Eager state, the user agent
+            // > must run these steps:
+            // > 1. Let resumptionSteps be the element's lazy load resumption steps.
+            // > 2. If resumptionSteps is null, then return.
+            // > 3. Set the element's lazy load resumption steps to null.
+            // > 4. Invoke resumptionSteps.
+            &local_name!("loading") if !self.upcast::<Element>().will_lazy_load() => {
+                self.run_lazy_load_resumption_steps(can_gc)
+            },
             _ => {},
         }
     }
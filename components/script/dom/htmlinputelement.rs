@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::borrow::Cow;
-use std::cell::Cell;
+use std::cell::{Cell, Ref};
 use std::cmp::Ordering;
 use std::ops::Range;
 use std::path::PathBuf;
@@ -22,6 +22,7 @@ use js::jsapi::{
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::{CheckRegExpSyntax, ExecuteRegExpNoStatics, ObjectIsRegExp};
 use js::rust::{HandleObject, MutableHandleObject};
+use keyboard_types::Key;
 use net_traits::blob_url_store::get_blob_origin;
 use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::{CoreResourceMsg, IpcSend};
@@ -44,40 +45,50 @@ use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::FileListBinding::FileListMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
+    ShadowRootMode, SlotAssignmentMode,
+};
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomGlobal;
-use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::clipboardevent::ClipboardEvent;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
-use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
+use crate::dom::element::{AttributeMutation, Element, ElementCreator, LayoutElementHelpers};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::file::File;
 use crate::dom::filelist::{FileList, LayoutFileListHelpers};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmldatalistelement::HTMLDataListElement;
+use crate::dom::htmldivelement::HTMLDivElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{
     FormControl, FormDatum, FormDatumValue, FormSubmitterElement, HTMLFormElement, ResetFrom,
     SubmittedFrom,
 };
+use crate::dom::htmloptionelement::HTMLOptionElement;
+use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{
     BindContext, CloneChildrenFlag, Node, NodeDamage, NodeTraits, ShadowIncluding, UnbindContext,
 };
 use crate::dom::nodelist::NodeList;
+use crate::dom::shadowroot::IsUserAgentWidget;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
 use crate::dom::validation::{Validatable, is_barred_by_datalist_ancestor};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::realms::enter_realm;
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+use crate::spellcheck::{SpellcheckedTextControl, Spelling, check_spelling};
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
 };
@@ -193,6 +204,33 @@ impl InputType {
         self.is_textual() || *self == InputType::Password
     }
 
+    // https://html.spec.whatwg.org/multipage/#autofill
+    //
+    // The input types whose values the embedder can fill in, for instance with credentials,
+    // addresses or payment details.
+    pub(crate) fn is_autofillable(&self) -> bool {
+        matches!(
+            *self,
+            InputType::Date |
+                InputType::Email |
+                InputType::Month |
+                InputType::Number |
+                InputType::Password |
+                InputType::Search |
+                InputType::Tel |
+                InputType::Text |
+                InputType::Url
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking
+    //
+    // The values of URL and E-mail inputs are checkable text as well, but they are rarely made
+    // up of words, so they are not checked.
+    pub(crate) fn is_spellcheckable(&self) -> bool {
+        matches!(*self, InputType::Text | InputType::Search)
+    }
+
     // https://html.spec.whatwg.org/multipage/#has-a-periodic-domain
     fn has_periodic_domain(&self) -> bool {
         *self == InputType::Time
@@ -303,6 +341,8 @@ pub(crate) struct HTMLInputElement {
     #[ignore_malloc_size_of = "TextInput contains an IPCSender which cannot be measured"]
     #[no_trace]
     textinput: DomRefCell<TextInput<EmbedderClipboardProvider>>,
+    #[no_trace]
+    spelling: DomRefCell<Spelling>,
     // https://html.spec.whatwg.org/multipage/#concept-input-value-dirty-flag
     value_dirty: Cell<bool>,
     // not specified explicitly, but implied by the fact that sanitization can't
@@ -314,8 +354,78 @@ pub(crate) struct HTMLInputElement {
     form_owner: MutNullableDom<HTMLFormElement>,
     labels_node_list: MutNullableDom<NodeList>,
     validity_state: MutNullableDom<ValidityState>,
+
+    /// The UA shadow tree that shows the slider of range inputs.
+    range_shadow_tree: DomRefCell<Option<RangeShadowTree>>,
+    /// The value of a range input when the user started to drag its thumb, while they do.
+    range_value_before_drag: DomRefCell<Option<DOMString>>,
+}
+
+/// Holds handles to the parts of the slider of a range input. The ids of the parts are the
+/// hooks that style them, in the UA shadow tree, until there are pseudo-elements for them.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct RangeShadowTree {
+    slider: Dom<HTMLDivElement>,
+    progress: Dom<HTMLDivElement>,
+    ticks: Dom<HTMLDivElement>,
+    thumb: Dom<HTMLDivElement>,
 }
 
+/// The style of the parts of the slider of range inputs. The track and the tick marks are
+/// inset by half of the width of the thumb, so that the center of the thumb is over the
+/// position of the value on them.
+const RANGE_SLIDER_STYLE: &str = "
+    #-servo-range-slider {
+        position: relative;
+        min-width: 129px;
+        height: 16px;
+    }
+    #-servo-range-track {
+        position: absolute;
+        top: 6px;
+        left: 8px;
+        right: 8px;
+        height: 4px;
+        border-radius: 2px;
+        overflow: hidden;
+        background-color: rgb(206, 206, 206);
+    }
+    #-servo-range-progress {
+        height: 100%;
+        background-color: rgb(0, 117, 255);
+    }
+    #-servo-range-ticks {
+        position: absolute;
+        top: 16px;
+        left: 8px;
+        right: 8px;
+        height: 5px;
+    }
+    #-servo-range-ticks > div {
+        position: absolute;
+        top: 0;
+        width: 1px;
+        height: 100%;
+        background-color: rgb(118, 118, 118);
+    }
+    #-servo-range-thumb {
+        position: absolute;
+        top: 0;
+        width: 16px;
+        height: 16px;
+        border-radius: 50%;
+        background-color: rgb(0, 117, 255);
+    }
+";
+
+/// The width of the thumb of range inputs, in CSS pixels.
+const RANGE_THUMB_WIDTH: f64 = 16.0;
+
+/// The number of steps that the Page Up and Page Down keys move range inputs by, as a
+/// fraction of their range.
+const RANGE_PAGE_FRACTION: f64 = 0.1;
+
 #[derive(JSTraceable)]
 pub(crate) struct InputActivationState {
     indeterminate: bool,
@@ -366,12 +476,15 @@ impl HTMLInputElement {
                 None,
                 SelectionDirection::None,
             )),
+            spelling: Default::default(),
             value_dirty: Cell::new(false),
             sanitization_flag: Cell::new(true),
             filelist: MutNullableDom::new(None),
             form_owner: Default::default(),
             labels_node_list: MutNullableDom::new(None),
             validity_state: Default::default(),
+            range_shadow_tree: Default::default(),
+            range_value_before_drag: Default::default(),
         }
     }
 
@@ -803,6 +916,269 @@ impl HTMLInputElement {
             .map(DomRoot::from_ref)
     }
 
+    /// The value of a range input as a fraction of its range, from 0 at its minimum to 1 at
+    /// its maximum.
+    fn range_fraction(&self, value: f64) -> f64 {
+        let min = self.minimum().unwrap_or(0.0);
+        let max = self.maximum().unwrap_or(100.0);
+        if max <= min {
+            return 0.0;
+        }
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    /// The values of the options of the `list` of a range input that are within its range,
+    /// which the slider shows tick marks for.
+    fn range_tick_values(&self) -> Vec<f64> {
+        let Some(datalist) = self.suggestions_source_element() else {
+            return vec![];
+        };
+        let min = self.minimum().unwrap_or(0.0);
+        let max = self.maximum().unwrap_or(100.0);
+        datalist
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+            .filter(|option| !option.Disabled())
+            .filter_map(|option| self.convert_string_to_number(&option.Value()))
+            .filter(|value| (min..=max).contains(value))
+            .collect()
+    }
+
+    fn create_range_shadow_tree(&self, can_gc: CanGc) {
+        let document = self.owner_document();
+        let root = self
+            .upcast::<Element>()
+            .attach_shadow(
+                IsUserAgentWidget::Yes,
+                ShadowRootMode::Closed,
+                false,
+                false,
+                false,
+                SlotAssignmentMode::Manual,
+                can_gc,
+            )
+            .expect("Attaching UA shadow root failed");
+
+        let style = HTMLStyleElement::new(
+            local_name!("style"),
+            None,
+            &document,
+            None,
+            ElementCreator::ScriptCreated,
+            can_gc,
+        );
+        style
+            .upcast::<Node>()
+            .SetTextContent(Some(DOMString::from(RANGE_SLIDER_STYLE)), can_gc);
+        root.upcast::<Node>()
+            .AppendChild(style.upcast::<Node>())
+            .unwrap();
+
+        // FIXME: The track and the thumb should use ::-moz-range-track and ::-moz-range-thumb.
+        let create_part = |parent: &Node, id: &str| {
+            let part = HTMLDivElement::new(local_name!("div"), None, &document, None, can_gc);
+            part.upcast::<Element>().SetId(id.into(), can_gc);
+            parent.AppendChild(part.upcast::<Node>()).unwrap();
+            part
+        };
+        let slider = create_part(root.upcast::<Node>(), "-servo-range-slider");
+        let track = create_part(slider.upcast::<Node>(), "-servo-range-track");
+        let progress = create_part(track.upcast::<Node>(), "-servo-range-progress");
+        let ticks = create_part(slider.upcast::<Node>(), "-servo-range-ticks");
+        let thumb = create_part(slider.upcast::<Node>(), "-servo-range-thumb");
+
+        let _ = self.range_shadow_tree.borrow_mut().insert(RangeShadowTree {
+            slider: slider.as_traced(),
+            progress: progress.as_traced(),
+            ticks: ticks.as_traced(),
+            thumb: thumb.as_traced(),
+        });
+    }
+
+    fn range_shadow_tree(&self, can_gc: CanGc) -> Ref<'_, RangeShadowTree> {
+        if !self.upcast::<Element>().is_shadow_host() {
+            self.create_range_shadow_tree(can_gc);
+        }
+
+        Ref::filter_map(self.range_shadow_tree.borrow(), Option::as_ref)
+            .ok()
+            .expect("UA shadow tree was not created")
+    }
+
+    /// Show the value and the tick marks of a range input on its slider, or remove the
+    /// slider when the input is no longer a range input.
+    fn update_range_shadow_tree(&self, can_gc: CanGc) {
+        if self.input_type() != InputType::Range {
+            if self.range_shadow_tree.borrow_mut().take().is_some() {
+                self.upcast::<Element>().detach_shadow(can_gc);
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+            }
+            return;
+        }
+
+        let value = self
+            .convert_string_to_number(&self.Value())
+            .unwrap_or_else(|| self.default_range_value());
+        let percentage = self.range_fraction(value) * 100.0;
+        let tick_percentages: Vec<f64> = self
+            .range_tick_values()
+            .into_iter()
+            .map(|value| self.range_fraction(value) * 100.0)
+            .collect();
+        let (accent_color, tick_color) = if self.upcast::<Element>().disabled_state() {
+            ("rgb(160, 160, 160)", "rgb(192, 192, 192)")
+        } else {
+            ("rgb(0, 117, 255)", "rgb(118, 118, 118)")
+        };
+
+        let document = self.owner_document();
+        let shadow_tree = self.range_shadow_tree(can_gc);
+        let height = if tick_percentages.is_empty() { 16 } else { 22 };
+        shadow_tree.slider.upcast::<Element>().set_string_attribute(
+            &local_name!("style"),
+            format!("height: {height}px").into(),
+            can_gc,
+        );
+        shadow_tree
+            .progress
+            .upcast::<Element>()
+            .set_string_attribute(
+                &local_name!("style"),
+                format!("width: {percentage}%; background-color: {accent_color}").into(),
+                can_gc,
+            );
+        // The thumb moves along the inset track, so it is offset by the same fraction of
+        // its own width as its center is of the width of the track.
+        let thumb_offset = percentage * RANGE_THUMB_WIDTH / 100.0;
+        shadow_tree.thumb.upcast::<Element>().set_string_attribute(
+            &local_name!("style"),
+            format!(
+                "left: calc({percentage}% - {thumb_offset}px); background-color: {accent_color}"
+            )
+            .into(),
+            can_gc,
+        );
+
+        let ticks = shadow_tree.ticks.upcast::<Node>();
+        while let Some(child) = ticks.GetFirstChild() {
+            ticks.RemoveChild(&child, can_gc).unwrap();
+        }
+        for tick_percentage in tick_percentages {
+            let tick = HTMLDivElement::new(local_name!("div"), None, &document, None, can_gc);
+            tick.upcast::<Element>().set_string_attribute(
+                &local_name!("style"),
+                format!("left: {tick_percentage}%; background-color: {tick_color}").into(),
+                can_gc,
+            );
+            ticks.AppendChild(tick.upcast::<Node>()).unwrap();
+        }
+
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Set the value of a range input to the value at the given horizontal offset from the
+    /// left edge of its content box, as the user points at it, and fire an `input` event
+    /// if the value changes.
+    fn set_range_value_from_offset(&self, offset_x: f64, can_gc: CanGc) {
+        let width = self
+            .upcast::<Node>()
+            .bounding_content_box_or_zero(can_gc)
+            .size
+            .width
+            .to_f64_px();
+        let track_width = width - RANGE_THUMB_WIDTH;
+        if track_width <= 0.0 {
+            return;
+        }
+        let fraction = ((offset_x - RANGE_THUMB_WIDTH / 2.0) / track_width).clamp(0.0, 1.0);
+        let min = self.minimum().unwrap_or(0.0);
+        let max = self.maximum().unwrap_or(100.0);
+        if max <= min {
+            return;
+        }
+
+        let old_value = self.Value();
+        let _ = self.SetValueAsNumber(min + fraction * (max - min), can_gc);
+        if self.Value() != old_value {
+            self.upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("input"), can_gc);
+        }
+    }
+
+    /// Finish dragging the thumb of a range input, and fire a `change` event if the value
+    /// differs from the one it had when the user started to.
+    fn finish_range_drag(&self, can_gc: CanGc) {
+        let Some(value_before_drag) = self.range_value_before_drag.borrow_mut().take() else {
+            return;
+        };
+        if self.Value() != value_before_drag {
+            self.upcast::<EventTarget>()
+                .fire_bubbling_event(atom!("change"), can_gc);
+        }
+    }
+
+    /// Handle pointer and keyboard interaction with the slider of a range input. Returns
+    /// whether the event was handled.
+    fn handle_range_event(&self, event: &Event, can_gc: CanGc) -> bool {
+        if let Some(mouse_event) = event.downcast::<MouseEvent>() {
+            match event.type_() {
+                atom!("mousedown") if mouse_event.Button() == 0 => {
+                    *self.range_value_before_drag.borrow_mut() = Some(self.Value());
+                    self.set_range_value_from_offset(mouse_event.OffsetX(can_gc) as f64, can_gc);
+                    true
+                },
+                atom!("mousemove") if self.range_value_before_drag.borrow().is_some() => {
+                    // The primary button may have been released outside of the input.
+                    if mouse_event.Buttons() & 1 == 0 {
+                        self.finish_range_drag(can_gc);
+                    } else {
+                        self.set_range_value_from_offset(
+                            mouse_event.OffsetX(can_gc) as f64,
+                            can_gc,
+                        );
+                    }
+                    true
+                },
+                atom!("mouseup") if mouse_event.Button() == 0 => {
+                    self.finish_range_drag(can_gc);
+                    true
+                },
+                _ => false,
+            }
+        } else if let Some(keyboard_event) = event.downcast::<KeyboardEvent>() {
+            if event.type_() != atom!("keydown") {
+                return false;
+            }
+            let min = self.minimum().unwrap_or(0.0);
+            let max = self.maximum().unwrap_or(100.0);
+            let page = (max - min).max(0.0) * RANGE_PAGE_FRACTION;
+            let value = self.ValueAsNumber();
+            let old_value = self.Value();
+            let _ = match keyboard_event.key() {
+                Key::ArrowUp | Key::ArrowRight => {
+                    self.step_up_or_down(1, StepDirection::Up, can_gc)
+                },
+                Key::ArrowDown | Key::ArrowLeft => {
+                    self.step_up_or_down(1, StepDirection::Down, can_gc)
+                },
+                Key::PageUp => self.SetValueAsNumber(value + page, can_gc),
+                Key::PageDown => self.SetValueAsNumber(value - page, can_gc),
+                Key::Home => self.SetValueAsNumber(min, can_gc),
+                Key::End => self.SetValueAsNumber(max, can_gc),
+                _ => return false,
+            };
+            if self.Value() != old_value {
+                let target = self.upcast::<EventTarget>();
+                target.fire_bubbling_event(atom!("input"), can_gc);
+                target.fire_bubbling_event(atom!("change"), can_gc);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
     fn suffers_from_being_missing(&self, value: &DOMString) -> bool {
         match self.input_type() {
@@ -1011,6 +1387,8 @@ pub(crate) trait LayoutHTMLInputElementHelpers<'dom> {
     fn value_for_layout(self) -> Cow<'dom, str>;
     fn size_for_layout(self) -> u32;
     fn selection_for_layout(self) -> Option<Range<usize>>;
+    fn spelling_errors_for_layout(self) -> Vec<Range<usize>>;
+    fn composition_for_layout(self) -> Option<Range<usize>>;
 }
 
 #[allow(unsafe_code)]
@@ -1031,6 +1409,10 @@ impl<'dom> LayoutDom<'dom, HTMLInputElement> {
         unsafe { self.unsafe_get().placeholder.borrow_for_layout() }
     }
 
+    fn spelling(self) -> &'dom Spelling {
+        unsafe { self.unsafe_get().spelling.borrow_for_layout() }
+    }
+
     fn input_type(self) -> InputType {
         self.unsafe_get().input_type.get()
     }
@@ -1043,6 +1425,15 @@ impl<'dom> LayoutDom<'dom, HTMLInputElement> {
                 .sorted_selection_offsets_range()
         }
     }
+
+    fn textinput_composition(self) -> Option<Range<usize>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .composition()
+        }
+    }
 }
 
 impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElement> {
@@ -1134,6 +1525,21 @@ impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElem
             _ => None,
         }
     }
+
+    fn spelling_errors_for_layout(self) -> Vec<Range<usize>> {
+        if !self.input_type().is_spellcheckable() {
+            return Vec::new();
+        }
+        self.spelling()
+            .misspelled_words(&self.get_raw_textinput_value())
+    }
+
+    fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !self.upcast::<Element>().focus_state() || !self.input_type().is_textual() {
+            return None;
+        }
+        self.textinput_composition()
+    }
 }
 
 impl TextControlElement for HTMLInputElement {
@@ -1189,6 +1595,22 @@ impl TextControlElement for HTMLInputElement {
     }
 }
 
+impl SpellcheckedTextControl for HTMLInputElement {
+    fn textinput(&self) -> &DomRefCell<TextInput<EmbedderClipboardProvider>> {
+        &self.textinput
+    }
+
+    fn spelling(&self) -> &DomRefCell<Spelling> {
+        &self.spelling
+    }
+
+    fn is_spellchecked(&self) -> bool {
+        self.input_type().is_spellcheckable() &&
+            self.is_mutable() &&
+            self.upcast::<Element>().is_spellcheck_enabled()
+    }
+}
+
 #[allow(non_snake_case)]
 impl HTMLInputElementMethods<crate::DomTypeHolder> for HTMLInputElement {
     // https://html.spec.whatwg.org/multipage/#dom-input-accept
@@ -1229,9 +1651,27 @@ impl HTMLInputElementMethods<crate::DomTypeHolder> for HTMLInputElement {
     fn SetFiles(&self, files: Option<&FileList>) {
         if self.input_type() == InputType::File && files.is_some() {
             self.filelist.set(files);
+            self.validity_state()
+                .perform_validation_and_update(ValidationFlags::all(), CanGc::note());
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         }
     }
 
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn Webkitdirectory(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("webkitdirectory"))
+    }
+
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn SetWebkitdirectory(&self, value: bool) {
+        self.upcast::<Element>().set_bool_attribute(
+            &LocalName::from("webkitdirectory"),
+            value,
+            CanGc::note(),
+        );
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-input-defaultchecked
     make_bool_getter!(DefaultChecked, "checked");
 
@@ -1341,6 +1781,7 @@ impl HTMLInputElementMethods<crate::DomTypeHolder> for HTMLInputElement {
 
         self.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        self.update_range_shadow_tree(can_gc);
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         Ok(())
     }
@@ -1874,11 +2315,16 @@ impl HTMLInputElement {
                 self.update_checked_state(self.DefaultChecked(), false);
                 self.checked_changed.set(false);
             },
+            InputType::File => {
+                let filelist = FileList::new(&self.owner_window(), vec![], CanGc::note());
+                self.filelist.set(Some(&filelist));
+            },
             InputType::Image => (),
             _ => (),
         }
         self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.update_range_shadow_tree(CanGc::note());
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
@@ -1906,9 +2352,28 @@ impl HTMLInputElement {
 
         let webview_id = window.webview_id();
         let filter = filter_from_accept(&self.Accept());
-        let target = self.upcast::<EventTarget>();
 
-        if self.Multiple() {
+        if self.Webkitdirectory() {
+            let opt_test_path = opt_test_paths
+                .and_then(|paths| paths.first().map(|path| PathBuf::from(path.to_string())));
+
+            let (chan, recv) = ipc::channel(self.global().time_profiler_chan().clone())
+                .expect("Error initializing channel");
+            let msg =
+                FileManagerThreadMsg::SelectDirectory(webview_id, chan, origin, opt_test_path);
+            resource_threads
+                .send(CoreResourceMsg::ToFileManager(msg))
+                .unwrap();
+
+            match recv.recv().expect("IpcSender side error") {
+                Ok(selected_files) => {
+                    for selected in selected_files {
+                        files.push(File::new_from_selected(&window, selected, can_gc));
+                    }
+                },
+                Err(err) => error = Some(err),
+            };
+        } else if self.Multiple() {
             let opt_test_paths = opt_test_paths.map(|paths| {
                 paths
                     .iter()
@@ -1963,12 +2428,47 @@ impl HTMLInputElement {
         if let Some(err) = error {
             debug!("Input file select error: {:?}", err);
         } else {
-            let filelist = FileList::new(&window, files, can_gc);
-            self.filelist.set(Some(&filelist));
+            self.set_selected_files(files, can_gc);
+        }
+    }
 
-            target.fire_bubbling_event(atom!("input"), can_gc);
-            target.fire_bubbling_event(atom!("change"), can_gc);
+    /// Select the files that the user dropped onto a file input, as if they had chosen them
+    /// from the file selection dialog.
+    pub(crate) fn handle_file_drop(&self, files: Vec<DomRoot<File>>, can_gc: CanGc) {
+        if !self.accepts_file_drop() {
+            return;
         }
+
+        // Without `multiple`, only the first of the dropped files is selected.
+        let files = if self.Multiple() || self.Webkitdirectory() {
+            files
+        } else {
+            files.into_iter().take(1).collect()
+        };
+        if files.is_empty() {
+            return;
+        }
+
+        self.set_selected_files(files, can_gc);
+    }
+
+    /// Whether files that the user drops onto this element can become its selected files.
+    pub(crate) fn accepts_file_drop(&self) -> bool {
+        self.input_type() == InputType::File && self.is_mutable()
+    }
+
+    /// Replace the selected files of a file input with files that the user chose, and fire
+    /// the events that tell the page about it.
+    fn set_selected_files(&self, files: Vec<DomRoot<File>>, can_gc: CanGc) {
+        let filelist = FileList::new(&self.owner_window(), files, can_gc);
+        self.filelist.set(Some(&filelist));
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"), can_gc);
+        target.fire_bubbling_event(atom!("change"), can_gc);
     }
 
     // https://html.spec.whatwg.org/multipage/#value-sanitization-algorithm
@@ -2382,10 +2882,13 @@ impl VirtualMethods for HTMLInputElement {
                             el.set_read_write_state(false);
                         }
 
+                        // Only file inputs have selected files.
                         if new_type == InputType::File {
                             let window = self.owner_window();
                             let filelist = FileList::new(&window, vec![], CanGc::note());
                             self.filelist.set(Some(&filelist));
+                        } else {
+                            self.filelist.set(None);
                         }
 
                         let new_value_mode = self.value_mode();
@@ -2520,8 +3023,22 @@ impl VirtualMethods for HTMLInputElement {
             _ => {},
         }
 
+        if matches!(
+            *attr.local_name(),
+            local_name!("type") |
+                local_name!("value") |
+                local_name!("min") |
+                local_name!("max") |
+                local_name!("step") |
+                local_name!("list") |
+                local_name!("disabled")
+        ) {
+            self.update_range_shadow_tree(can_gc);
+        }
+
         self.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        check_spelling(self);
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -2553,6 +3070,9 @@ impl VirtualMethods for HTMLInputElement {
             r.validity_state()
                 .perform_validation_and_update(ValidationFlags::all(), can_gc);
         }
+
+        // The `list` of a range input may only be found once it is in a tree.
+        self.update_range_shadow_tree(can_gc);
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
@@ -2583,7 +3103,11 @@ impl VirtualMethods for HTMLInputElement {
             s.handle_event(event, can_gc);
         }
 
-        if event.type_() == atom!("click") && !event.DefaultPrevented() {
+        if self.input_type() == InputType::Range && self.is_mutable() {
+            if !event.DefaultPrevented() && self.handle_range_event(event, can_gc) {
+                event.PreventDefault();
+            }
+        } else if event.type_() == atom!("click") && !event.DefaultPrevented() {
             // WHATWG-specified activation behaviors are handled elsewhere;
             // this is for all the other things a UI click might do
 
@@ -2629,10 +3153,12 @@ impl VirtualMethods for HTMLInputElement {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        self.selection().scroll_into_view(can_gc);
                         event.mark_as_handled();
                     },
                     RedrawSelection => {
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        self.selection().scroll_into_view(can_gc);
                         event.mark_as_handled();
                     },
                     Nothing => (),
@@ -2659,19 +3185,32 @@ impl VirtualMethods for HTMLInputElement {
             self.input_type().is_textual_or_password()
         {
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
-                } else if event.type_() == atom!("compositionupdate") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionupdate(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                {
+                    let mut textinput = self.textinput.borrow_mut();
+                    let _ = match event.type_() {
+                        atom!("compositionstart") => textinput.handle_compositionstart(),
+                        atom!("compositionupdate") => {
+                            textinput.handle_compositionupdate(compositionevent.data())
+                        },
+                        _ => textinput.handle_compositionend(compositionevent.data()),
+                    };
+                }
+                self.value_dirty.set(true);
+                self.update_placeholder_shown_state();
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                if event.type_() != atom!("compositionstart") {
+                    self.owner_global()
+                        .task_manager()
+                        .user_interaction_task_source()
+                        .queue_event(
+                            self.upcast(),
+                            atom!("input"),
+                            EventBubbles::Bubbles,
+                            EventCancelable::NotCancelable,
+                        );
                 }
+                self.owner_document()
+                    .send_composition_rect_to_embedder(self.upcast(), can_gc);
                 event.mark_as_handled();
             }
         } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
@@ -2682,6 +3221,7 @@ impl VirtualMethods for HTMLInputElement {
 
         self.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        check_spelling(self);
     }
 
     // https://html.spec.whatwg.org/multipage/#the-input-element%3Aconcept-node-clone-ext
@@ -2703,6 +3243,7 @@ impl VirtualMethods for HTMLInputElement {
         elem.textinput
             .borrow_mut()
             .set_content(self.textinput.borrow().get_content());
+        elem.update_range_shadow_tree(can_gc);
         elem.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
     }
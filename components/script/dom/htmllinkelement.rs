@@ -226,8 +226,18 @@ impl VirtualMethods for HTMLLinkElement {
 
         match *attr.local_name() {
             local_name!("rel") | local_name!("rev") => {
+                let previous_relations = self.relations.get();
                 self.relations
                     .set(LinkRelations::for_element(self.upcast()));
+
+                // A prefetch link is fetched when it is created on a connected element.
+                if self.relations.get().contains(LinkRelations::PREFETCH) &&
+                    !previous_relations.contains(LinkRelations::PREFETCH)
+                {
+                    if let Some(href) = get_attr(self.upcast(), &local_name!("href")) {
+                        self.fetch_and_process_prefetch_link(&href);
+                    }
+                }
             },
             local_name!("href") => {
                 if self.relations.get().contains(LinkRelations::STYLESHEET) {
@@ -250,7 +260,9 @@ impl VirtualMethods for HTMLLinkElement {
             },
             local_name!("crossorigin") => {
                 if self.relations.get().contains(LinkRelations::PREFETCH) {
-                    self.fetch_and_process_prefetch_link(&attr.value());
+                    if let Some(href) = get_attr(self.upcast(), &local_name!("href")) {
+                        self.fetch_and_process_prefetch_link(&href);
+                    }
                 }
             },
             _ => {},
@@ -375,19 +387,25 @@ impl HTMLLinkElement {
         options.destination = Some(Destination::None);
 
         // Step 4. Let request be the result of creating a link request given options.
-        let url = options.base_url.clone();
         let Some(request) = options.create_link_request(self.owner_window().webview_id()) else {
             // Step 5. If request is null, then return.
             return;
         };
 
         // Step 6. Set request's initiator to "prefetch".
-        let request = request.initiator(Initiator::Prefetch);
+        // The request is made on behalf of the document, so that the response is stored in the
+        // HTTP cache under the same partition that a later navigation or subresource load uses.
+        let document = self.upcast::<Node>().owner_doc();
+        let mut request = request
+            .initiator(Initiator::Prefetch)
+            .origin(document.origin().immutable().clone())
+            .pipeline_id(Some(document.global().pipeline_id()));
+        request.referrer = document.global().get_referrer();
+        let url = request.url.clone();
 
         // (Step 7, firing load/error events is handled in the FetchResponseListener impl for PrefetchContext)
 
         // Step 8. The user agent should fetch request, with processResponseConsumeBody set to processPrefetchResponse.
-        let document = self.upcast::<Node>().owner_doc();
         let fetch_context = PrefetchContext {
             url,
             link: Trusted::new(self),
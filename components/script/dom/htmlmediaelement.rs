@@ -31,6 +31,7 @@ use script_bindings::codegen::InheritTypes::{
     ElementTypeId, HTMLElementTypeId, HTMLMediaElementTypeId, NodeTypeId,
 };
 use script_layout_interface::MediaFrame;
+use script_traits::SandboxingFlagSet;
 use servo_config::pref;
 use servo_media::player::audio::AudioRenderer;
 use servo_media::player::video::{VideoFrame, VideoFrameRenderer};
@@ -161,6 +162,9 @@ pub(crate) struct MediaFrameRenderer {
     player_id: Option<u64>,
     compositor_api: CrossProcessCompositorApi,
     current_frame: Option<MediaFrame>,
+    /// Whether the image of the current frame is an external image that WebRender reads from
+    /// the GL texture that the player decoded the frame into.
+    current_frame_is_external: bool,
     old_frame: Option<ImageKey>,
     very_old_frame: Option<ImageKey>,
     current_frame_holder: Option<FrameHolder>,
@@ -173,6 +177,7 @@ impl MediaFrameRenderer {
             player_id: None,
             compositor_api,
             current_frame: None,
+            current_frame_is_external: false,
             old_frame: None,
             very_old_frame: None,
             current_frame_holder: None,
@@ -187,6 +192,7 @@ impl MediaFrameRenderer {
                 width: image.width as i32,
                 height: image.height as i32,
             });
+            self.current_frame_is_external = false;
             self.show_poster = true;
         }
     }
@@ -199,6 +205,16 @@ impl VideoFrameRenderer for MediaFrameRenderer {
             return;
         }
 
+        // Frames that the player decoded into GL textures are presented by WebRender directly
+        // from the texture, as an external image, which avoids copying the pixels of every
+        // frame through the CPU. That needs the player to be registered with the GLPlayer
+        // thread, without which such frames cannot be presented at all.
+        let is_external = frame.is_gl_texture();
+        if is_external && self.player_id.is_none() {
+            warn!("Dropping GL video frame, as GL accelerated media playback is not available");
+            return;
+        }
+
         let mut updates = vec![];
 
         if let Some(old_image_key) = mem::replace(&mut self.very_old_frame, self.old_frame.take()) {
@@ -213,11 +229,15 @@ impl VideoFrameRenderer for MediaFrameRenderer {
         );
 
         match &mut self.current_frame {
+            // The image of the previous frame can be reused if it has the same size and is of
+            // the same kind. External images don't need to be updated, as WebRender locks the
+            // texture of the current frame whenever it draws it.
             Some(current_frame)
                 if current_frame.width == frame.get_width() &&
-                    current_frame.height == frame.get_height() =>
+                    current_frame.height == frame.get_height() &&
+                    self.current_frame_is_external == is_external =>
             {
-                if !frame.is_gl_texture() {
+                if !is_external {
                     updates.push(ImageUpdate::UpdateImage(
                         current_frame.image_key,
                         descriptor,
@@ -233,70 +253,41 @@ impl VideoFrameRenderer for MediaFrameRenderer {
                     updates.push(ImageUpdate::DeleteImage(old_image_key));
                 }
             },
-            Some(current_frame) => {
-                self.old_frame = Some(current_frame.image_key);
-
-                let Some(new_image_key) = self.compositor_api.generate_image_key() else {
-                    return;
-                };
-
-                /* update current_frame */
-                current_frame.image_key = new_image_key;
-                current_frame.width = frame.get_width();
-                current_frame.height = frame.get_height();
-
-                let image_data = if frame.is_gl_texture() && self.player_id.is_some() {
-                    let texture_target = if frame.is_external_oes() {
-                        ImageBufferKind::TextureExternal
-                    } else {
-                        ImageBufferKind::Texture2D
-                    };
-
-                    SerializableImageData::External(ExternalImageData {
-                        id: ExternalImageId(self.player_id.unwrap()),
-                        channel_index: 0,
-                        image_type: ExternalImageType::TextureHandle(texture_target),
-                        normalized_uvs: false,
-                    })
-                } else {
-                    SerializableImageData::Raw(IpcSharedMemory::from_bytes(&frame.get_data()))
-                };
-
-                self.current_frame_holder
-                    .get_or_insert_with(|| FrameHolder::new(frame.clone()))
-                    .set(frame);
-
-                updates.push(ImageUpdate::AddImage(new_image_key, descriptor, image_data));
-            },
-            None => {
+            current_frame => {
                 let Some(image_key) = self.compositor_api.generate_image_key() else {
                     return;
                 };
-
-                self.current_frame = Some(MediaFrame {
+                if let Some(current_frame) = current_frame {
+                    self.old_frame = Some(current_frame.image_key);
+                }
+                *current_frame = Some(MediaFrame {
                     image_key,
                     width: frame.get_width(),
                     height: frame.get_height(),
                 });
+                self.current_frame_is_external = is_external;
 
-                let image_data = if frame.is_gl_texture() && self.player_id.is_some() {
-                    let texture_target = if frame.is_external_oes() {
-                        ImageBufferKind::TextureExternal
-                    } else {
-                        ImageBufferKind::Texture2D
-                    };
-
-                    SerializableImageData::External(ExternalImageData {
-                        id: ExternalImageId(self.player_id.unwrap()),
-                        channel_index: 0,
-                        image_type: ExternalImageType::TextureHandle(texture_target),
-                        normalized_uvs: false,
-                    })
-                } else {
-                    SerializableImageData::Raw(IpcSharedMemory::from_bytes(&frame.get_data()))
+                let image_data = match self.player_id {
+                    Some(player_id) if is_external => {
+                        let texture_target = if frame.is_external_oes() {
+                            ImageBufferKind::TextureExternal
+                        } else {
+                            ImageBufferKind::Texture2D
+                        };
+                        SerializableImageData::External(ExternalImageData {
+                            id: ExternalImageId(player_id),
+                            channel_index: 0,
+                            image_type: ExternalImageType::TextureHandle(texture_target),
+                            normalized_uvs: false,
+                        })
+                    },
+                    _ => SerializableImageData::Raw(IpcSharedMemory::from_bytes(&frame.get_data())),
                 };
 
-                self.current_frame_holder = Some(FrameHolder::new(frame));
+                match self.current_frame_holder.as_mut() {
+                    Some(holder) => holder.set(frame),
+                    None => self.current_frame_holder = Some(FrameHolder::new(frame)),
+                }
 
                 updates.push(ImageUpdate::AddImage(image_key, descriptor, image_data));
             },
@@ -685,11 +676,14 @@ impl HTMLMediaElement {
         }
 
         if ready_state == ReadyState::HaveEnoughData {
-            // TODO: Check sandboxed automatic features browsing context flag.
-            // FIXME(nox): I have no idea what this TODO is about.
-
             // FIXME(nox): Review this block.
-            if self.autoplaying.get() && self.Paused() && self.Autoplay() {
+            if self.autoplaying.get() &&
+                self.Paused() &&
+                self.Autoplay() &&
+                !self
+                    .owner_document()
+                    .has_active_sandboxing_flag(SandboxingFlagSet::AUTOMATIC_FEATURES)
+            {
                 // Step 1
                 self.paused.set(false);
                 // Step 2
@@ -1376,6 +1370,7 @@ impl HTMLMediaElement {
         );
 
         *self.player.borrow_mut() = Some(player);
+        self.update_player_mute();
 
         let trusted_node = Trusted::new(self);
         let task_source = self
@@ -1412,45 +1407,31 @@ impl HTMLMediaElement {
             .unwrap_or((0, None));
 
         self.id.set(player_id);
-        self.video_renderer.lock().unwrap().player_id = Some(player_id);
+        self.video_renderer.lock().unwrap().player_id =
+            image_receiver.is_some().then_some(player_id);
 
         if let Some(image_receiver) = image_receiver {
-            let trusted_node = Trusted::new(self);
-            let task_source = self
-                .owner_global()
-                .task_manager()
-                .media_element_task_source()
-                .to_sendable();
+            // WebRender blocks on these messages while it draws the external image of the
+            // current frame, so they are answered right away instead of waiting for the
+            // script thread, which may be busy running script.
+            let video_renderer = self.video_renderer.clone();
             ROUTER.add_typed_route(
                 image_receiver,
                 Box::new(move |message| {
                     let msg = message.unwrap();
-                    let this = trusted_node.clone();
-                    task_source.queue(task!(handle_glplayer_message: move || {
-                        trace!("GLPlayer message {:?}", msg);
-                        let video_renderer = this.root().video_renderer.clone();
-
-                        match msg {
-                            GLPlayerMsgForward::Lock(sender) => {
-                                if let Some(holder) = video_renderer
-                                    .lock()
-                                    .unwrap()
-                                    .current_frame_holder
-                                    .as_mut() {
-                                        holder.lock();
-                                        sender.send(holder.get()).unwrap();
-                                    };
-                            },
-                            GLPlayerMsgForward::Unlock() => {
-                                if let Some(holder) = video_renderer
-                                    .lock()
-                                    .unwrap()
-                                    .current_frame_holder
-                                    .as_mut() { holder.unlock() }
-                            },
-                            _ => (),
-                        }
-                    }));
+                    trace!("GLPlayer message {:?}", msg);
+                    let mut video_renderer = video_renderer.lock().unwrap();
+                    let Some(holder) = video_renderer.current_frame_holder.as_mut() else {
+                        return;
+                    };
+                    match msg {
+                        GLPlayerMsgForward::Lock(sender) => {
+                            holder.lock();
+                            let _ = sender.send(holder.get());
+                        },
+                        GLPlayerMsgForward::Unlock() => holder.unlock(),
+                        _ => (),
+                    }
                 }),
             );
         }
@@ -1962,6 +1943,15 @@ impl HTMLMediaElement {
         }
     }
 
+    /// Mute the media player if either the page or the embedder muted this element.
+    pub(crate) fn update_player_mute(&self) {
+        let muted =
+            self.muted.get() || ScriptThread::is_webview_muted(self.owner_window().webview_id());
+        if let Some(ref player) = *self.player.borrow() {
+            let _ = player.lock().unwrap().set_mute(muted);
+        }
+    }
+
     pub(crate) fn get_current_frame(&self) -> Option<VideoFrame> {
         self.video_renderer
             .lock()
@@ -2116,11 +2106,8 @@ impl HTMLMediaElementMethods<crate::DomTypeHolder> for HTMLMediaElement {
             return;
         }
 
-        if let Some(ref player) = *self.player.borrow() {
-            let _ = player.lock().unwrap().set_mute(value);
-        }
-
         self.muted.set(value);
+        self.update_player_mute();
         self.owner_global()
             .task_manager()
             .media_element_task_source()
@@ -2513,6 +2500,17 @@ impl VirtualMethods for HTMLMediaElement {
         };
     }
 
+    fn adopting_steps(&self, old_doc: &Document, can_gc: CanGc) {
+        self.super_type().unwrap().adopting_steps(old_doc, can_gc);
+
+        // The element is now muted along with the webview of its new document.
+        let window = self.owner_window();
+        if *old_doc.window() != *window {
+            window.track_media_element(self);
+        }
+        self.update_player_mute();
+    }
+
     // https://html.spec.whatwg.org/multipage/#playing-the-media-resource:remove-an-element-from-a-document
     fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
         self.super_type().unwrap().unbind_from_tree(context, can_gc);
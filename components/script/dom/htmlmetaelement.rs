@@ -10,7 +10,7 @@ use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use js::rust::HandleObject;
 use regex::bytes::Regex;
-use script_traits::NavigationHistoryBehavior;
+use script_traits::{NavigationHistoryBehavior, SandboxingFlagSet};
 use servo_url::ServoUrl;
 use style::str::HTML_SPACE_CHARACTERS;
 
@@ -202,9 +202,13 @@ impl HTMLMetaElement {
                 return;
             }
         }
+        // 11. If document's active sandboxing flag set has the sandboxed automatic features
+        // browsing context flag set, then return.
+        if document.has_active_sandboxing_flag(SandboxingFlagSet::AUTOMATIC_FEATURES) {
+            return;
+        }
         // 12-13
         if document.completely_loaded() {
-            // TODO: handle active sandboxing flag
             let window = self.owner_window();
             window.as_global_scope().schedule_callback(
                 OneshotTimerCallback::RefreshRedirectDue(RefreshRedirectDue {
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Ref;
+
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix, local_name};
 use js::rust::HandleObject;
@@ -9,25 +11,46 @@ use script_bindings::str::DOMString;
 use stylo_dom::ElementState;
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLOptGroupElementBinding::HTMLOptGroupElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLSlotElementBinding::HTMLSlotElement_Binding::HTMLSlotElementMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
+    ShadowRootMode, SlotAssignmentMode,
+};
 use crate::dom::bindings::codegen::GenericBindings::NodeBinding::Node_Binding::NodeMethods;
+use crate::dom::bindings::codegen::UnionTypes::ElementOrText;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::htmldivelement::HTMLDivElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmloptionelement::HTMLOptionElement;
-use crate::dom::htmlselectelement::HTMLSelectElement;
-use crate::dom::node::{BindContext, Node, UnbindContext};
+use crate::dom::htmlselectelement::{HTMLSelectElement, create_list_box_style};
+use crate::dom::htmlslotelement::HTMLSlotElement;
+use crate::dom::node::{
+    BindContext, ChildrenMutation, Node, NodeDamage, NodeTraits, UnbindContext,
+};
+use crate::dom::shadowroot::IsUserAgentWidget;
 use crate::dom::validation::Validatable;
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
+/// Holds handles to the elements of the UA shadow tree, which shows the label of the group
+/// above its options when it is in a `<select>` element that is shown as a list box.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct ShadowTree {
+    label: Dom<HTMLDivElement>,
+    options: Dom<HTMLSlotElement>,
+}
+
 /// <https://html.spec.whatwg.org/multipage/#htmloptgroupelement>
 #[dom_struct]
 pub(crate) struct HTMLOptGroupElement {
     htmlelement: HTMLElement,
+    shadow_tree: DomRefCell<Option<ShadowTree>>,
 }
 
 impl HTMLOptGroupElement {
@@ -43,6 +66,7 @@ impl HTMLOptGroupElement {
                 prefix,
                 document,
             ),
+            shadow_tree: Default::default(),
         }
     }
 
@@ -64,6 +88,77 @@ impl HTMLOptGroupElement {
         )
     }
 
+    fn shadow_tree(&self, can_gc: CanGc) -> Ref<'_, ShadowTree> {
+        if !self.upcast::<Element>().is_shadow_host() {
+            self.create_shadow_tree(can_gc);
+        }
+
+        Ref::filter_map(self.shadow_tree.borrow(), Option::as_ref)
+            .ok()
+            .expect("UA shadow tree was not created")
+    }
+
+    fn create_shadow_tree(&self, can_gc: CanGc) {
+        let document = self.owner_document();
+        let root = self
+            .upcast::<Element>()
+            .attach_shadow(
+                IsUserAgentWidget::Yes,
+                ShadowRootMode::Closed,
+                false,
+                false,
+                false,
+                SlotAssignmentMode::Manual,
+                can_gc,
+            )
+            .expect("Attaching UA shadow root failed");
+
+        let style = create_list_box_style(&document, can_gc);
+        root.upcast::<Node>()
+            .AppendChild(style.upcast::<Node>())
+            .unwrap();
+
+        let label = HTMLDivElement::new(local_name!("div"), None, &document, None, can_gc);
+        label.upcast::<Element>().set_string_attribute(
+            &local_name!("style"),
+            "font-weight: bold; white-space: nowrap;".into(),
+            can_gc,
+        );
+        root.upcast::<Node>()
+            .AppendChild(label.upcast::<Node>())
+            .unwrap();
+
+        let options = HTMLSlotElement::new(local_name!("slot"), None, &document, None, can_gc);
+        root.upcast::<Node>()
+            .AppendChild(options.upcast::<Node>())
+            .unwrap();
+
+        let _ = self.shadow_tree.borrow_mut().insert(ShadowTree {
+            label: label.as_traced(),
+            options: options.as_traced(),
+        });
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Show the label of the group, and the options in it.
+    fn update_shadow_tree(&self, can_gc: CanGc) {
+        let shadow_tree = self.shadow_tree(can_gc);
+        shadow_tree
+            .label
+            .upcast::<Node>()
+            .SetTextContent(Some(self.Label()), can_gc);
+
+        let options = self
+            .upcast::<Node>()
+            .children()
+            .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+            .map(|option| ElementOrText::Element(DomRoot::upcast(option)))
+            .collect();
+        shadow_tree.options.Assign(options);
+
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
     fn update_select_validity(&self, can_gc: CanGc) {
         if let Some(select) = self.owner_select_element() {
             select
@@ -131,14 +226,23 @@ impl VirtualMethods for HTMLOptGroupElement {
                     el.check_disabled_attribute();
                 }
             }
+        } else if attr.local_name() == &local_name!("label") {
+            self.update_shadow_tree(can_gc);
         }
     }
 
+    fn children_changed(&self, mutation: &ChildrenMutation) {
+        self.super_type().unwrap().children_changed(mutation);
+
+        self.update_shadow_tree(CanGc::note());
+    }
+
     fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
         if let Some(s) = self.super_type() {
             s.bind_to_tree(context, can_gc);
         }
 
+        self.update_shadow_tree(can_gc);
         self.update_select_validity(can_gc);
     }
 
@@ -29,7 +29,7 @@ use crate::dom::htmlformelement::HTMLFormElement;
 use crate::dom::htmloptgroupelement::HTMLOptGroupElement;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
-use crate::dom::node::{BindContext, Node, ShadowIncluding, UnbindContext};
+use crate::dom::node::{BindContext, ChildrenMutation, Node, ShadowIncluding, UnbindContext};
 use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::validitystate::ValidationFlags;
@@ -85,13 +85,31 @@ impl HTMLOptionElement {
     }
 
     pub(crate) fn set_selectedness(&self, selected: bool) {
+        if self.selectedness.get() == selected {
+            return;
+        }
         self.selectedness.set(selected);
+        self.upcast::<Element>()
+            .set_state(ElementState::CHECKED, selected);
+        if let Some(select) = self.owner_select_element() {
+            select.update_selected_option_label(CanGc::note());
+        }
     }
 
     pub(crate) fn set_dirtiness(&self, dirtiness: bool) {
         self.dirtiness.set(dirtiness);
     }
 
+    /// The `<select>` element whose list of options this option is in, if any.
+    fn owner_select_element(&self) -> Option<DomRoot<HTMLSelectElement>> {
+        let parent = self.upcast::<Node>().GetParentNode()?;
+        if parent.is::<HTMLOptGroupElement>() {
+            parent.GetParentNode().and_then(DomRoot::downcast)
+        } else {
+            DomRoot::downcast(parent)
+        }
+    }
+
     fn pick_if_selected_and_reset(&self) {
         if let Some(select) = self
             .upcast::<Node>()
@@ -284,7 +302,7 @@ impl HTMLOptionElementMethods<crate::DomTypeHolder> for HTMLOptionElement {
     // https://html.spec.whatwg.org/multipage/#dom-option-selected
     fn SetSelected(&self, selected: bool) {
         self.dirtiness.set(true);
-        self.selectedness.set(selected);
+        self.set_selectedness(selected);
         self.pick_if_selected_and_reset();
         self.update_select_validity(CanGc::note());
     }
@@ -325,22 +343,37 @@ impl VirtualMethods for HTMLOptionElement {
                     AttributeMutation::Set(_) => {
                         // https://html.spec.whatwg.org/multipage/#concept-option-selectedness
                         if !self.dirtiness.get() {
-                            self.selectedness.set(true);
+                            self.set_selectedness(true);
                         }
                     },
                     AttributeMutation::Removed => {
                         // https://html.spec.whatwg.org/multipage/#concept-option-selectedness
                         if !self.dirtiness.get() {
-                            self.selectedness.set(false);
+                            self.set_selectedness(false);
                         }
                     },
                 }
                 self.update_select_validity(can_gc);
             },
+            local_name!("label") => {
+                if let Some(select) = self.owner_select_element() {
+                    select.update_selected_option_label(can_gc);
+                }
+            },
             _ => {},
         }
     }
 
+    fn children_changed(&self, mutation: &ChildrenMutation) {
+        if let Some(s) = self.super_type() {
+            s.children_changed(mutation);
+        }
+
+        if let Some(select) = self.owner_select_element() {
+            select.update_selected_option_label(CanGc::note());
+        }
+    }
+
     fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
         if let Some(s) = self.super_type() {
             s.bind_to_tree(context, can_gc);
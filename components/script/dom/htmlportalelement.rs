@@ -0,0 +1,388 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use base::id::PipelineId;
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix, local_name};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleObject, HandleValue};
+use net_traits::ReferrerPolicy;
+use script_traits::{LoadData, LoadOrigin, ScriptMsg, StructuredSerializedData};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::HTMLPortalElementBinding::{
+    HTMLPortalElementMethods, PortalActivateOptions,
+};
+use crate::dom::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::DomGlobal;
+use crate::dom::bindings::root::{DomRoot, LayoutDom};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::document::{Document, determine_policy_for_token};
+use crate::dom::element::{AttributeMutation, Element, reflect_referrer_policy_attribute};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::node::{Node, NodeDamage, NodeTraits, UnbindContext};
+use crate::dom::promise::Promise;
+use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// A `<portal>` element, which shows a prerendered document in place that can later become the
+/// top-level document of the page. The hosted document is isolated from input and from the
+/// host's scripts until it is activated.
+///
+/// <https://wicg.github.io/portals/#the-portal-element>
+#[dom_struct]
+pub(crate) struct HTMLPortalElement {
+    htmlelement: HTMLElement,
+    /// The pipeline of the document hosted by this portal, if any.
+    #[no_trace]
+    pipeline_id: Cell<Option<PipelineId>>,
+}
+
+impl HTMLPortalElement {
+    fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> HTMLPortalElement {
+        HTMLPortalElement {
+            htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
+            pipeline_id: Cell::new(None),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<HTMLPortalElement> {
+        Node::reflect_node_with_proto(
+            Box::new(HTMLPortalElement::new_inherited(
+                local_name, prefix, document,
+            )),
+            document,
+            proto,
+            can_gc,
+        )
+    }
+
+    #[inline]
+    pub(crate) fn pipeline_id(&self) -> Option<PipelineId> {
+        self.pipeline_id.get()
+    }
+
+    /// Start loading the document named by the `src` attribute in a new portal pipeline,
+    /// replacing the current one.
+    fn create_portal_document(&self) {
+        self.discard_portal_document();
+
+        // Portals can only be activated into the top-level browsing context, so only
+        // top-level documents can host them.
+        let window = self.owner_window();
+        if !window.is_top_level() {
+            return;
+        }
+
+        let element = self.upcast::<Element>();
+        if !element.has_attribute(&local_name!("src")) {
+            return;
+        }
+        let url = match ServoUrl::parse(&element.get_url_attribute(&local_name!("src"))) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            _ => return,
+        };
+        let document = self.owner_document();
+
+        let referrer_policy = match determine_policy_for_token(self.ReferrerPolicy().str()) {
+            ReferrerPolicy::EmptyString => document.get_referrer_policy(),
+            policy => policy,
+        };
+        let global = window.as_global_scope();
+        let load_data = LoadData::new(
+            LoadOrigin::Script(document.origin().immutable().clone()),
+            url,
+            Some(window.pipeline_id()),
+            global.get_referrer(),
+            referrer_policy,
+            Some(global.is_secure_context()),
+            Some(document.insecure_requests_policy()),
+        );
+
+        let new_pipeline_id = PipelineId::new();
+        self.pipeline_id.set(Some(new_pipeline_id));
+        window.send_to_constellation(ScriptMsg::CreatePortal(new_pipeline_id, load_data));
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Handle the constellation's answer to an activation of this portal's document, which
+    /// fails when the host document is navigating. The portal keeps its document then.
+    fn activation_finished(
+        &self,
+        pipeline_id: PipelineId,
+        activated: bool,
+        promise: &Promise,
+        can_gc: CanGc,
+    ) {
+        if activated {
+            return promise.resolve_native(&(), can_gc);
+        }
+        if self.pipeline_id.get().is_none() &&
+            self.upcast::<Node>().is_connected_with_browsing_context()
+        {
+            self.pipeline_id.set(Some(pipeline_id));
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        } else {
+            // The portal was removed or given another document in the meantime.
+            self.owner_window()
+                .send_to_constellation(ScriptMsg::DiscardPortal(pipeline_id));
+        }
+        promise.reject_error(Error::InvalidState, can_gc);
+    }
+
+    /// Close the document hosted by this portal, if there is one.
+    fn discard_portal_document(&self) {
+        let Some(pipeline_id) = self.pipeline_id.take() else {
+            return;
+        };
+        self.owner_window()
+            .send_to_constellation(ScriptMsg::DiscardPortal(pipeline_id));
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+}
+
+pub(crate) trait HTMLPortalElementLayoutMethods {
+    fn pipeline_id(self) -> Option<PipelineId>;
+}
+
+impl HTMLPortalElementLayoutMethods for LayoutDom<'_, HTMLPortalElement> {
+    #[inline]
+    fn pipeline_id(self) -> Option<PipelineId> {
+        (self.unsafe_get()).pipeline_id.get()
+    }
+}
+
+impl HTMLPortalElementMethods<crate::DomTypeHolder> for HTMLPortalElement {
+    // https://wicg.github.io/portals/#dom-htmlportalelement-src
+    make_url_getter!(Src, "src");
+
+    // https://wicg.github.io/portals/#dom-htmlportalelement-src
+    make_url_setter!(SetSrc, "src");
+
+    /// <https://wicg.github.io/portals/#dom-htmlportalelement-referrerpolicy>
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://wicg.github.io/portals/#dom-htmlportalelement-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
+    /// <https://wicg.github.io/portals/#dom-htmlportalelement-activate>
+    fn Activate(
+        &self,
+        cx: JSContext,
+        options: RootedTraceableBox<PortalActivateOptions>,
+        can_gc: CanGc,
+    ) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // Step 2. If portalBrowsingContext is null, throw an "InvalidStateError" DOMException.
+        // Step 4. If element's node document is not fully active, throw an "InvalidStateError"
+        // DOMException.
+        let document = self.owner_document();
+        let pipeline_id = match self.pipeline_id.get() {
+            Some(pipeline_id) if document.is_fully_active() => pipeline_id,
+            _ => {
+                promise.reject_error(Error::InvalidState, can_gc);
+                return promise;
+            },
+        };
+
+        // Step 6. Let serializeWithTransferResult be StructuredSerializeWithTransfer(options's
+        // data member, options's transfer member). Rethrow any exceptions.
+        let data = match serialize_with_transfer(cx, options.data.handle(), &options.parent) {
+            Ok(data) => data,
+            Err(error) => {
+                promise.reject_error(error, can_gc);
+                return promise;
+            },
+        };
+
+        // Activation swaps the documents in a single frame. There is no built-in transition
+        // from the bounds of the element to the viewport: like the specification, this
+        // leaves it to the page to animate the element before calling `activate()`.
+        //
+        // The hosted document now belongs to the top-level browsing context, and this
+        // element will not show it anymore.
+        self.pipeline_id.set(None);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        let task_source = self
+            .owner_global()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .to_sendable();
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |activated| {
+                let Some(trusted_promise) = trusted_promise.take() else {
+                    return;
+                };
+                let activated = activated.unwrap_or(false);
+                let this = this.clone();
+                task_source.queue(task!(portal_activation_finished: move || {
+                    this.root().activation_finished(
+                        pipeline_id,
+                        activated,
+                        &trusted_promise.root(),
+                        CanGc::note(),
+                    );
+                }));
+            }),
+        );
+        self.owner_window()
+            .send_to_constellation(ScriptMsg::ActivatePortal(pipeline_id, data, sender));
+        promise
+    }
+
+    /// <https://wicg.github.io/portals/#dom-htmlportalelement-postmessage>
+    fn PostMessage(
+        &self,
+        cx: JSContext,
+        message: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> ErrorResult {
+        // Step 2. If portalBrowsingContext is null, throw an "InvalidStateError" DOMException.
+        let Some(pipeline_id) = self.pipeline_id.get() else {
+            return Err(Error::InvalidState);
+        };
+
+        // Step 4. Let origin be the serialization of settings's origin.
+        let source_origin = self.owner_document().origin().immutable().clone();
+
+        // Step 6. Let serializeWithTransferResult be StructuredSerializeWithTransfer(message,
+        // options's transfer member). Rethrow any exceptions.
+        let data = serialize_with_transfer(cx, message, &options)?;
+
+        // Step 7. Queue a global task to post the message to the portal browsing context.
+        self.owner_window()
+            .send_to_constellation(ScriptMsg::PostMessageToPortal(
+                pipeline_id,
+                source_origin,
+                data,
+            ));
+        Ok(())
+    }
+
+    // https://wicg.github.io/portals/#dom-htmlportalelement-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://wicg.github.io/portals/#dom-htmlportalelement-onmessageerror
+    event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+}
+
+/// Serialize `message` for posting across a portal, with the objects to transfer given by
+/// `options`.
+pub(crate) fn serialize_with_transfer(
+    cx: JSContext,
+    message: HandleValue,
+    options: &StructuredSerializeOptions,
+) -> Fallible<StructuredSerializedData> {
+    let mut rooted = CustomAutoRooter::new(
+        options
+            .transfer
+            .iter()
+            .map(|js: &RootedTraceableBox<Heap<*mut JSObject>>| js.get())
+            .collect(),
+    );
+    let guard = CustomAutoRooterGuard::new(*cx, &mut rooted);
+    structuredclone::write(cx, message, Some(guard))
+}
+
+/// Queue a task to fire a `message` event, with the data that was posted across a portal by a
+/// document of `source_origin`, at `target`: a `<portal>` element or a `PortalHost`.
+///
+/// <https://wicg.github.io/portals/#dom-htmlportalelement-postmessage>
+pub(crate) fn post_portal_message(
+    target: &EventTarget,
+    source_origin: ImmutableOrigin,
+    data: StructuredSerializedData,
+) {
+    let trusted_target = Trusted::new(target);
+    let task = task!(post_portal_message: move || {
+        let target = trusted_target.root();
+        let global = target.global();
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut message_clone = UndefinedValue());
+        match structuredclone::read(&global, data, message_clone.handle_mut()) {
+            Ok(ports) => MessageEvent::dispatch_jsval(
+                &target,
+                &global,
+                message_clone.handle(),
+                Some(&source_origin.ascii_serialization()),
+                None,
+                ports,
+                CanGc::note(),
+            ),
+            Err(()) => MessageEvent::dispatch_error(&target, &global, CanGc::note()),
+        }
+    });
+    // TODO(#12718): Use the "posted message task source".
+    target
+        .global()
+        .task_manager()
+        .dom_manipulation_task_source()
+        .queue(task);
+}
+
+impl VirtualMethods for HTMLPortalElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation, can_gc: CanGc) {
+        self.super_type()
+            .unwrap()
+            .attribute_mutated(attr, mutation, can_gc);
+        if *attr.local_name() == local_name!("src") &&
+            self.upcast::<Node>().is_connected_with_browsing_context()
+        {
+            self.create_portal_document();
+        }
+    }
+
+    fn post_connection_steps(&self) {
+        if let Some(s) = self.super_type() {
+            s.post_connection_steps();
+        }
+
+        if self.upcast::<Node>().is_connected_with_browsing_context() {
+            self.create_portal_document();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
+        self.super_type().unwrap().unbind_from_tree(context, can_gc);
+        self.discard_portal_document();
+    }
+}
@@ -49,6 +49,7 @@ use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::AutoEntryScript;
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::trace::NoTrace;
+use crate::dom::console::Console;
 use crate::dom::document::Document;
 use crate::dom::element::{
     AttributeMutation, Element, ElementCreator, cors_setting_for_element,
@@ -68,6 +69,7 @@ use crate::script_module::{
     ModuleOwner, ScriptFetchOptions, fetch_external_module_script, fetch_inline_module_script,
 };
 use crate::script_runtime::CanGc;
+use crate::speculation_rules::SpeculationRuleSet;
 use crate::task_source::{SendableTaskSource, TaskSourceName};
 use crate::unminify::{ScriptSource, unminify_js};
 
@@ -266,6 +268,8 @@ pub(crate) static SCRIPT_JS_MIMES: StaticStringVec = &[
 pub(crate) enum ScriptType {
     Classic,
     Module,
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set>
+    SpeculationRules,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -282,6 +286,13 @@ pub(crate) enum SourceCode {
     Compiled(CompiledSourceCode),
 }
 
+/// The script types that can be fetched from a `src` URL.
+#[derive(Clone, Copy)]
+enum ExternalScriptType {
+    Classic,
+    Module,
+}
+
 #[derive(JSTraceable, MallocSizeOf)]
 pub(crate) struct ScriptOrigin {
     #[ignore_malloc_size_of = "Rc is hard"]
@@ -744,7 +755,9 @@ impl HTMLScriptElement {
 
         // Step 23. Module script credentials mode.
         let module_credentials_mode = match script_type {
-            ScriptType::Classic => CredentialsMode::CredentialsSameOrigin,
+            ScriptType::Classic | ScriptType::SpeculationRules => {
+                CredentialsMode::CredentialsSameOrigin
+            },
             ScriptType::Module => reflect_cross_origin_attribute(element).map_or(
                 CredentialsMode::CredentialsSameOrigin,
                 |attr| match &*attr {
@@ -792,7 +805,18 @@ impl HTMLScriptElement {
         if let Some(src) = element.get_attribute(&ns!(), &local_name!("src")) {
             // Step 31. If el has a src content attribute, then:
 
-            // TODO: Step 31.1. If el's type is "importmap".
+            // Step 31.1. If el's type is "importmap" or "speculationrules", then queue an element
+            // task on the DOM manipulation task source given el to fire an event named error at
+            // el, and return.
+            // TODO: Handle "importmap".
+            let script_type = match script_type {
+                ScriptType::Classic => ExternalScriptType::Classic,
+                ScriptType::Module => ExternalScriptType::Module,
+                ScriptType::SpeculationRules => {
+                    self.queue_error_event();
+                    return;
+                },
+            };
 
             // Step 31.2. Let src be the value of el's src attribute.
             let src = src.value();
@@ -823,7 +847,7 @@ impl HTMLScriptElement {
 
             // Step 31.11. Switch on el's type:
             match script_type {
-                ScriptType::Classic => {
+                ExternalScriptType::Classic => {
                     let kind = if element.has_attribute(&local_name!("defer")) &&
                         was_parser_inserted &&
                         !asynch
@@ -854,7 +878,7 @@ impl HTMLScriptElement {
                         ExternalScriptKind::Asap => doc.add_asap_script(self),
                     }
                 },
-                ScriptType::Module => {
+                ExternalScriptType::Module => {
                     // Step 31.11. Fetch an external module script graph.
                     fetch_external_module_script(
                         ModuleOwner::Window(Trusted::new(self)),
@@ -929,6 +953,11 @@ impl HTMLScriptElement {
                         can_gc,
                     );
                 },
+                ScriptType::SpeculationRules => {
+                    // Step 34.6. Otherwise, immediately execute the script element el. The rule
+                    // set is parsed and registered when the element is executed.
+                    self.execute(result, can_gc);
+                },
             }
         }
     }
@@ -1014,6 +1043,7 @@ impl HTMLScriptElement {
                 }
             },
             ScriptType::Module => document.set_current_script(None),
+            ScriptType::SpeculationRules => {},
         }
 
         match script.type_ {
@@ -1025,6 +1055,7 @@ impl HTMLScriptElement {
                 assert!(document.GetCurrentScript().is_none());
                 self.run_a_module_script(&script, false, can_gc);
             },
+            ScriptType::SpeculationRules => self.register_speculation_rules(&script),
         }
 
         // Step 7.
@@ -1129,6 +1160,24 @@ impl HTMLScriptElement {
         }
     }
 
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#register-speculation-rules>
+    fn register_speculation_rules(&self, script: &ScriptOrigin) {
+        let SourceCode::Text(ref text) = script.code else {
+            return warn!("Speculation rules cannot be compiled");
+        };
+
+        // Let result be the result of parsing a speculation rule set string given source text,
+        // el's node document, and el's node document's document base URL.
+        let document = self.owner_document();
+        match SpeculationRuleSet::parse(text, &document, &script.url) {
+            Ok(rule_set) => document.register_speculation_rule_set(rule_set),
+            Err(error) => Console::internal_warn(
+                &self.global(),
+                DOMString::from(format!("Failed to parse speculation rules: {error}")),
+            ),
+        }
+    }
+
     pub(crate) fn queue_error_event(&self) {
         self.owner_global()
             .task_manager()
@@ -1194,6 +1243,13 @@ impl HTMLScriptElement {
                     return Some(ScriptType::Module);
                 }
 
+                if pref!(dom_speculation_rules_enabled) &&
+                    ty.to_ascii_lowercase().trim_matches(HTML_SPACE_CHARACTERS) ==
+                        "speculationrules"
+                {
+                    return Some(ScriptType::SpeculationRules);
+                }
+
                 if SCRIPT_JS_MIMES
                     .contains(&ty.to_ascii_lowercase().trim_matches(HTML_SPACE_CHARACTERS))
                 {
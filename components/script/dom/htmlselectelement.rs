@@ -2,45 +2,117 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Ref;
 use std::default::Default;
 use std::iter;
 
 use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, SelectElementOption, SelectElementOptionOrOptgroup};
+use euclid::{Point2D, Rect, Size2D};
 use html5ever::{LocalName, Prefix, local_name};
+use ipc_channel::ipc;
 use js::rust::HandleObject;
+use keyboard_types::{Key, Modifiers};
 use style::attr::AttrValue;
 use stylo_dom::ElementState;
+use webrender_api::units::DeviceIntRect;
 
+use crate::dom::activation::Activatable;
 use crate::dom::attr::Attr;
-use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::{
+    ElementMethods, ScrollLogicalPosition,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLCollectionBinding::HTMLCollectionMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptGroupElementBinding::HTMLOptGroupElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLOptionsCollectionBinding::HTMLOptionsCollectionMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLSelectElementBinding::HTMLSelectElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLSlotElementBinding::HTMLSlotElement_Binding::HTMLSlotElementMethods;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
+    ShadowRootMode, SlotAssignmentMode,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
 use crate::dom::bindings::codegen::UnionTypes::{
-    HTMLElementOrLong, HTMLOptionElementOrHTMLOptGroupElement,
+    ElementOrText, HTMLElementOrLong, HTMLOptionElementOrHTMLOptGroupElement,
 };
 use crate::dom::bindings::error::ErrorResult;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
-use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::element::{AttributeMutation, Element, ElementCreator};
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlcollection::CollectionFilter;
+use crate::dom::htmldivelement::HTMLDivElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, FormDatum, FormDatumValue, HTMLFormElement};
 use crate::dom::htmloptgroupelement::HTMLOptGroupElement;
 use crate::dom::htmloptionelement::HTMLOptionElement;
 use crate::dom::htmloptionscollection::HTMLOptionsCollection;
-use crate::dom::node::{BindContext, Node, NodeTraits, UnbindContext};
+use crate::dom::htmlslotelement::HTMLSlotElement;
+use crate::dom::htmlstyleelement::HTMLStyleElement;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::node::{
+    BindContext, ChildrenMutation, Node, NodeDamage, NodeTraits, ShadowIncluding, UnbindContext,
+};
 use crate::dom::nodelist::NodeList;
+use crate::dom::shadowroot::IsUserAgentWidget;
 use crate::dom::validation::{Validatable, is_barred_by_datalist_ancestor};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
+/// The style of the options, and the labels of option groups, of a `<select>` element that is
+/// shown as a list box. Options that are in an `<optgroup>` are slotted into the UA shadow
+/// tree of the group, so the group uses this style as well.
+const LIST_BOX_STYLE: &str = "
+    ::slotted(option) {
+        display: block;
+        white-space: nowrap;
+        padding: 0 2px;
+    }
+    ::slotted(option:checked) {
+        background-color: rgb(0, 117, 255);
+        color: white;
+    }
+    ::slotted(option:disabled) {
+        color: rgb(118, 118, 118);
+    }
+    ::slotted(optgroup) {
+        display: block;
+    }
+";
+
+/// The approximate height of an option in a list box, relative to the font size, which is
+/// used to make the list box as tall as its display size in rows.
+const LIST_BOX_ROW_HEIGHT: f32 = 1.2;
+
+/// Create the `<style>` element that styles the options of a list box in a UA shadow tree.
+pub(crate) fn create_list_box_style(
+    document: &Document,
+    can_gc: CanGc,
+) -> DomRoot<HTMLStyleElement> {
+    let style = HTMLStyleElement::new(
+        local_name!("style"),
+        None,
+        document,
+        None,
+        ElementCreator::ScriptCreated,
+        can_gc,
+    );
+    style
+        .upcast::<Node>()
+        .SetTextContent(Some(DOMString::from(LIST_BOX_STYLE)), can_gc);
+    style
+}
+
 #[derive(JSTraceable, MallocSizeOf)]
 struct OptionsFilter;
 impl CollectionFilter for OptionsFilter {
@@ -61,6 +133,16 @@ impl CollectionFilter for OptionsFilter {
     }
 }
 
+/// Holds handles to the elements of the UA shadow tree. A `<select>` element that is shown as
+/// a drop-down box shows the label of its selected option, and one that is shown as a list box
+/// shows its options and option groups, by slotting them.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+struct ShadowTree {
+    selected_option_label: Dom<HTMLDivElement>,
+    options: Dom<HTMLSlotElement>,
+}
+
 #[dom_struct]
 pub(crate) struct HTMLSelectElement {
     htmlelement: HTMLElement,
@@ -68,6 +150,12 @@ pub(crate) struct HTMLSelectElement {
     form_owner: MutNullableDom<HTMLFormElement>,
     labels_node_list: MutNullableDom<NodeList>,
     validity_state: MutNullableDom<ValidityState>,
+    shadow_tree: DomRefCell<Option<ShadowTree>>,
+    /// The option of a list box that the keyboard moves from, which is the one that the user
+    /// last clicked or moved to.
+    active_option: MutNullableDom<HTMLOptionElement>,
+    /// The option of a list box that a range selection, made with the shift key, starts at.
+    selection_anchor: MutNullableDom<HTMLOptionElement>,
 }
 
 static DEFAULT_SELECT_SIZE: u32 = 0;
@@ -89,6 +177,9 @@ impl HTMLSelectElement {
             form_owner: Default::default(),
             labels_node_list: Default::default(),
             validity_state: Default::default(),
+            shadow_tree: Default::default(),
+            active_option: Default::default(),
+            selection_anchor: Default::default(),
         }
     }
 
@@ -215,6 +306,413 @@ impl HTMLSelectElement {
             self.Size()
         }
     }
+
+    /// Whether the element is shown as a list box, rather than as a drop-down box.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#the-select-element-2>
+    fn is_list_box(&self) -> bool {
+        self.Multiple() || self.display_size() > 1
+    }
+
+    fn shadow_tree(&self, can_gc: CanGc) -> Ref<'_, ShadowTree> {
+        if !self.upcast::<Element>().is_shadow_host() {
+            self.create_shadow_tree(can_gc);
+        }
+
+        Ref::filter_map(self.shadow_tree.borrow(), Option::as_ref)
+            .ok()
+            .expect("UA shadow tree was not created")
+    }
+
+    fn create_shadow_tree(&self, can_gc: CanGc) {
+        let document = self.owner_document();
+        let root = self
+            .upcast::<Element>()
+            .attach_shadow(
+                IsUserAgentWidget::Yes,
+                ShadowRootMode::Closed,
+                false,
+                false,
+                false,
+                SlotAssignmentMode::Manual,
+                can_gc,
+            )
+            .expect("Attaching UA shadow root failed");
+
+        let style = create_list_box_style(&document, can_gc);
+        root.upcast::<Node>()
+            .AppendChild(style.upcast::<Node>())
+            .unwrap();
+
+        let selected_option_label =
+            HTMLDivElement::new(local_name!("div"), None, &document, None, can_gc);
+        root.upcast::<Node>()
+            .AppendChild(selected_option_label.upcast::<Node>())
+            .unwrap();
+
+        let options = HTMLSlotElement::new(local_name!("slot"), None, &document, None, can_gc);
+        root.upcast::<Node>()
+            .AppendChild(options.upcast::<Node>())
+            .unwrap();
+
+        let _ = self.shadow_tree.borrow_mut().insert(ShadowTree {
+            selected_option_label: selected_option_label.as_traced(),
+            options: options.as_traced(),
+        });
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Show either the label of the selected option, or the options and option groups, and
+    /// size the element to show as many options as its display size when it is a list box.
+    fn update_shadow_tree(&self, can_gc: CanGc) {
+        let is_list_box = self.is_list_box();
+        {
+            let shadow_tree = self.shadow_tree(can_gc);
+            let slottables = if is_list_box {
+                self.upcast::<Node>()
+                    .children()
+                    .filter(|child| {
+                        child.is::<HTMLOptionElement>() || child.is::<HTMLOptGroupElement>()
+                    })
+                    .filter_map(DomRoot::downcast::<Element>)
+                    .map(ElementOrText::Element)
+                    .collect()
+            } else {
+                vec![]
+            };
+            shadow_tree.options.Assign(slottables);
+
+            // The slot scrolls the options of a list box, and is as tall as the display size
+            // in rows. The drop-down arrow is painted over the end of a drop-down box, so its
+            // label leaves space for it.
+            let (options_style, label_style) = if is_list_box {
+                (
+                    format!(
+                        "display: block; height: {}em; overflow-y: auto;",
+                        self.display_size() as f32 * LIST_BOX_ROW_HEIGHT
+                    ),
+                    "display: none;",
+                )
+            } else {
+                (
+                    "display: none;".to_owned(),
+                    "white-space: pre; overflow: hidden; padding-inline-end: 16px;",
+                )
+            };
+            shadow_tree
+                .options
+                .upcast::<Element>()
+                .set_string_attribute(&local_name!("style"), options_style.into(), can_gc);
+            shadow_tree
+                .selected_option_label
+                .upcast::<Element>()
+                .set_string_attribute(&local_name!("style"), label_style.into(), can_gc);
+        }
+
+        self.update_selected_option_label(can_gc);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Show the label of the selected option of a drop-down box, after its selectedness or
+    /// its label changed.
+    pub(crate) fn update_selected_option_label(&self, can_gc: CanGc) {
+        if self.is_list_box() || !self.upcast::<Element>().is_shadow_host() {
+            return;
+        }
+        let label = self
+            .list_of_options()
+            .find(|option| option.Selected())
+            .map(|option| option.Label())
+            .unwrap_or_default();
+        // An empty label would collapse the line, so that the box would be shorter than
+        // one with a label.
+        let label = if label.is_empty() {
+            DOMString::from("\u{200B}")
+        } else {
+            label
+        };
+
+        let shadow_tree = self.shadow_tree(can_gc);
+        let label_node = shadow_tree.selected_option_label.upcast::<Node>();
+        if label_node.GetTextContent().as_ref() != Some(&label) {
+            label_node.SetTextContent(Some(label), can_gc);
+        }
+    }
+
+    /// Select the options for which `is_selected` is true, and deselect the others, as if the
+    /// user did, firing `input` and `change` events if the selection changed.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#send-select-update-notifications>
+    fn set_selection_from_user(
+        &self,
+        is_selected: impl Fn(&HTMLOptionElement) -> bool,
+        can_gc: CanGc,
+    ) {
+        let mut changed = false;
+        for option in self.list_of_options() {
+            let selected = is_selected(&option);
+            if option.Selected() != selected {
+                option.set_selectedness(selected);
+                option.set_dirtiness(true);
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+
+        self.validity_state()
+            .perform_validation_and_update(ValidationFlags::VALUE_MISSING, can_gc);
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"), can_gc);
+        target.fire_bubbling_event(atom!("change"), can_gc);
+    }
+
+    /// Select the first option whose value is `value`, as if the user did, when the embedder
+    /// fills in this element. Nothing changes if there is no such option.
+    pub(crate) fn select_value_from_user(&self, value: &str, can_gc: CanGc) {
+        let Some(chosen_option) = self
+            .list_of_options()
+            .find(|option| option.Value() == value)
+        else {
+            return;
+        };
+        self.set_selection_from_user(|option| *option == *chosen_option, can_gc);
+    }
+
+    /// Show the menu of a drop-down box with the embedder, and select the option that the
+    /// user chooses from it.
+    fn show_menu(&self, can_gc: CanGc) {
+        let options: Vec<_> = self.list_of_options().collect();
+        let menu_option = |option: &HTMLOptionElement| {
+            let id = options
+                .iter()
+                .position(|candidate| &**candidate == option)?;
+            Some(SelectElementOption {
+                id,
+                label: option.Label().into(),
+                is_disabled: option.upcast::<Element>().disabled_state(),
+            })
+        };
+        let menu_options = self
+            .upcast::<Node>()
+            .children()
+            .filter_map(|child| {
+                if let Some(option) = child.downcast::<HTMLOptionElement>() {
+                    menu_option(option).map(SelectElementOptionOrOptgroup::Option)
+                } else if let Some(optgroup) = child.downcast::<HTMLOptGroupElement>() {
+                    Some(SelectElementOptionOrOptgroup::Optgroup {
+                        label: optgroup.Label().into(),
+                        options: child
+                            .children()
+                            .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+                            .filter_map(|option| menu_option(&option))
+                            .collect(),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let selected_option = options.iter().position(|option| option.Selected());
+
+        let rect = self.upcast::<Node>().bounding_content_box_or_zero(can_gc);
+        let position = DeviceIntRect::from_untyped(
+            &Rect::new(
+                Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
+                Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
+            )
+            .to_box2d(),
+        );
+
+        let window = self.owner_window();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        window.send_to_embedder(EmbedderMsg::ShowSelectElementMenu(
+            window.webview_id(),
+            menu_options,
+            selected_option,
+            position,
+            sender,
+        ));
+        let Ok(Some(chosen_option)) = receiver.recv() else {
+            return;
+        };
+        let Some(chosen_option) = options.get(chosen_option) else {
+            return;
+        };
+        if chosen_option.upcast::<Element>().disabled_state() {
+            return;
+        }
+        self.set_selection_from_user(|option| option == &**chosen_option, can_gc);
+    }
+
+    /// Change the selection of a list box after the user clicked on one of its options. The
+    /// control (or command) key toggles the option, and the shift key selects the range of
+    /// options from the selection anchor, in a list box that allows multiple selections.
+    fn click_list_box_option(
+        &self,
+        clicked: &HTMLOptionElement,
+        toggle: bool,
+        extend: bool,
+        can_gc: CanGc,
+    ) {
+        if clicked.upcast::<Element>().disabled_state() {
+            return;
+        }
+        if !self.Multiple() {
+            self.selection_anchor.set(Some(clicked));
+            self.active_option.set(Some(clicked));
+            self.set_selection_from_user(|option| option == clicked, can_gc);
+            return;
+        }
+
+        let anchor = self
+            .selection_anchor
+            .get()
+            .filter(|anchor| self.list_of_options().any(|option| option == *anchor));
+        match anchor {
+            Some(anchor) if extend => {
+                let range = self.range_of_options(&anchor, clicked);
+                self.set_selection_from_user(
+                    |option| {
+                        let in_range = range.iter().any(|candidate| &**candidate == option);
+                        (in_range && !option.upcast::<Element>().disabled_state()) ||
+                            (toggle && !in_range && option.Selected())
+                    },
+                    can_gc,
+                );
+            },
+            _ if toggle => {
+                let selected = !clicked.Selected();
+                self.selection_anchor.set(Some(clicked));
+                self.set_selection_from_user(
+                    |option| {
+                        if option == clicked {
+                            selected
+                        } else {
+                            option.Selected()
+                        }
+                    },
+                    can_gc,
+                );
+            },
+            _ => {
+                self.selection_anchor.set(Some(clicked));
+                self.set_selection_from_user(|option| option == clicked, can_gc);
+            },
+        }
+        self.active_option.set(Some(clicked));
+    }
+
+    /// The options between `first` and `last`, inclusive, in either order.
+    fn range_of_options(
+        &self,
+        first: &HTMLOptionElement,
+        last: &HTMLOptionElement,
+    ) -> Vec<DomRoot<HTMLOptionElement>> {
+        let mut in_range = false;
+        let mut range = vec![];
+        for option in self.list_of_options() {
+            let is_end = &*option == first || &*option == last;
+            if is_end || in_range {
+                range.push(option);
+            }
+            if is_end && (in_range || first == last) {
+                break;
+            }
+            if is_end {
+                in_range = true;
+            }
+        }
+        range
+    }
+
+    /// The enabled option that the given key moves to from `current`, in a drop-down box or
+    /// a list box.
+    fn option_for_key(
+        &self,
+        key: &Key,
+        current: Option<&HTMLOptionElement>,
+    ) -> Option<DomRoot<HTMLOptionElement>> {
+        let enabled_options: Vec<_> = self
+            .list_of_options()
+            .filter(|option| !option.upcast::<Element>().disabled_state())
+            .collect();
+        let current = current.and_then(|current| {
+            enabled_options
+                .iter()
+                .position(|option| &**option == current)
+        });
+        let index = match (key, current) {
+            (Key::Home, _) | (Key::ArrowDown, None) => 0,
+            (Key::End, _) | (Key::ArrowUp, None) => enabled_options.len().checked_sub(1)?,
+            (Key::ArrowDown, Some(current)) => (current + 1).min(enabled_options.len() - 1),
+            (Key::ArrowUp, Some(current)) => current.saturating_sub(1),
+            _ => return None,
+        };
+        enabled_options.get(index).cloned()
+    }
+
+    /// Handle the keys that change the selection of a drop-down box, or open its menu.
+    fn handle_drop_down_keydown(&self, event: &KeyboardEvent, can_gc: CanGc) -> bool {
+        let key = event.key();
+        let opens_menu = match key {
+            Key::ArrowDown | Key::ArrowUp => event.modifiers().contains(Modifiers::ALT),
+            Key::F4 => true,
+            Key::Character(ref character) => character == " ",
+            _ => false,
+        };
+        if opens_menu {
+            self.show_menu(can_gc);
+            return true;
+        }
+
+        let selected = self.list_of_options().find(|option| option.Selected());
+        let Some(option) = self.option_for_key(&key, selected.as_deref()) else {
+            return false;
+        };
+        self.set_selection_from_user(|candidate| candidate == &*option, can_gc);
+        true
+    }
+
+    /// Handle the keys that move through the options of a list box. In a list box that allows
+    /// multiple selections, the shift key extends the selection from the selection anchor,
+    /// and the control (or command) key moves without selecting, so that the space key can
+    /// toggle the option that was moved to.
+    fn handle_list_box_keydown(&self, event: &KeyboardEvent, can_gc: CanGc) -> bool {
+        let key = event.key();
+        let modifiers = event.modifiers();
+        let toggle = modifiers.intersects(Modifiers::CONTROL | Modifiers::META);
+        let extend = modifiers.contains(Modifiers::SHIFT);
+        let active = self
+            .active_option
+            .get()
+            .filter(|active| self.list_of_options().any(|option| option == *active))
+            .or_else(|| self.list_of_options().find(|option| option.Selected()));
+
+        if self.Multiple() && toggle && key == Key::Character(" ".into()) {
+            if let Some(active) = active {
+                self.click_list_box_option(&active, true, false, can_gc);
+            }
+            return true;
+        }
+
+        let Some(option) = self.option_for_key(&key, active.as_deref()) else {
+            return false;
+        };
+        if self.Multiple() && toggle && !extend {
+            self.active_option.set(Some(&*option));
+        } else {
+            self.click_list_box_option(&option, false, extend, can_gc);
+        }
+        option.upcast::<Element>().scroll_into_view(
+            ScrollBehavior::Auto,
+            ScrollLogicalPosition::Nearest,
+            ScrollLogicalPosition::Nearest,
+            can_gc,
+        );
+        true
+    }
 }
 
 impl HTMLSelectElementMethods<crate::DomTypeHolder> for HTMLSelectElement {
@@ -450,10 +948,21 @@ impl VirtualMethods for HTMLSelectElement {
             local_name!("form") => {
                 self.form_attribute_mutated(mutation, can_gc);
             },
+            local_name!("multiple") | local_name!("size") => {
+                self.update_shadow_tree(can_gc);
+            },
             _ => {},
         }
     }
 
+    fn children_changed(&self, mutation: &ChildrenMutation) {
+        if let Some(s) = self.super_type() {
+            s.children_changed(mutation);
+        }
+
+        self.update_shadow_tree(CanGc::note());
+    }
+
     fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
         if let Some(s) = self.super_type() {
             s.bind_to_tree(context, can_gc);
@@ -461,6 +970,31 @@ impl VirtualMethods for HTMLSelectElement {
 
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.update_shadow_tree(can_gc);
+    }
+
+    fn handle_event(&self, event: &Event, can_gc: CanGc) {
+        if let Some(s) = self.super_type() {
+            s.handle_event(event, can_gc);
+        }
+
+        if event.type_() != atom!("keydown") ||
+            event.DefaultPrevented() ||
+            self.upcast::<Element>().disabled_state()
+        {
+            return;
+        }
+        let Some(keyboard_event) = event.downcast::<KeyboardEvent>() else {
+            return;
+        };
+        let handled = if self.is_list_box() {
+            self.handle_list_box_keydown(keyboard_event, can_gc)
+        } else {
+            self.handle_drop_down_keydown(keyboard_event, can_gc)
+        };
+        if handled {
+            event.PreventDefault();
+        }
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext, can_gc: CanGc) {
@@ -489,6 +1023,50 @@ impl VirtualMethods for HTMLSelectElement {
     }
 }
 
+impl Activatable for HTMLSelectElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn is_instance_activatable(&self) -> bool {
+        !self.upcast::<Element>().disabled_state()
+    }
+
+    /// Open the menu of a drop-down box, or select the option of a list box that was clicked.
+    /// Only clicks by the user open the menu, as the embedder shows it in a dialog that the
+    /// page should not be able to open by itself.
+    fn activation_behavior(&self, event: &Event, _target: &EventTarget, can_gc: CanGc) {
+        if !self.is_list_box() {
+            if event.IsTrusted() {
+                self.show_menu(can_gc);
+            }
+            return;
+        }
+
+        let Some(clicked) = event
+            .GetTarget()
+            .and_then(DomRoot::downcast::<Node>)
+            .and_then(|node| {
+                node.inclusive_ancestors(ShadowIncluding::No)
+                    .find_map(DomRoot::downcast::<HTMLOptionElement>)
+            })
+            .filter(|clicked| self.list_of_options().any(|option| option == *clicked))
+        else {
+            return;
+        };
+        let (toggle, extend) =
+            event
+                .downcast::<MouseEvent>()
+                .map_or((false, false), |mouse_event| {
+                    (
+                        mouse_event.CtrlKey() || mouse_event.MetaKey(),
+                        mouse_event.ShiftKey(),
+                    )
+                });
+        self.click_list_box_option(&clicked, toggle, extend, can_gc);
+    }
+}
+
 impl FormControl for HTMLSelectElement {
     fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
         self.form_owner.get()
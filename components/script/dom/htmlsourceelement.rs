@@ -5,6 +5,7 @@
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix, local_name};
 use js::rust::HandleObject;
+use style::attr::AttrValue;
 
 use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::HTMLSourceElementBinding::HTMLSourceElementMethods;
@@ -91,6 +92,16 @@ impl VirtualMethods for HTMLSourceElement {
         }
     }
 
+    fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
+        match name {
+            &local_name!("width") | &local_name!("height") => AttrValue::from_u32(value.into(), 0),
+            _ => self
+                .super_type()
+                .unwrap()
+                .parse_plain_attribute(name, value),
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#the-source-element:nodes-are-inserted>
     fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
         self.super_type().unwrap().bind_to_tree(context, can_gc);
@@ -147,4 +158,16 @@ impl HTMLSourceElementMethods<crate::DomTypeHolder> for HTMLSourceElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-source-media
     make_setter!(SetMedia, "media");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-width
+    make_uint_getter!(Width, "width");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-width
+    make_uint_setter!(SetWidth, "width");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-height
+    make_uint_getter!(Height, "height");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-height
+    make_uint_setter!(SetHeight, "height");
 }
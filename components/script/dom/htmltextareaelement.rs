@@ -42,6 +42,7 @@ use crate::dom::validation::{Validatable, is_barred_by_datalist_ancestor};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
+use crate::spellcheck::{SpellcheckedTextControl, Spelling, check_spelling};
 use crate::textinput::{
     Direction, KeyReaction, Lines, SelectionDirection, TextInput, UTF8Bytes, UTF16CodeUnits,
     handle_text_clipboard_action,
@@ -53,6 +54,8 @@ pub(crate) struct HTMLTextAreaElement {
     #[ignore_malloc_size_of = "TextInput contains an IPCSender which cannot be measured"]
     #[no_trace]
     textinput: DomRefCell<TextInput<EmbedderClipboardProvider>>,
+    #[no_trace]
+    spelling: DomRefCell<Spelling>,
     placeholder: DomRefCell<DOMString>,
     // https://html.spec.whatwg.org/multipage/#concept-textarea-dirty
     value_dirty: Cell<bool>,
@@ -64,6 +67,8 @@ pub(crate) struct HTMLTextAreaElement {
 pub(crate) trait LayoutHTMLTextAreaElementHelpers {
     fn value_for_layout(self) -> String;
     fn selection_for_layout(self) -> Option<Range<usize>>;
+    fn spelling_errors_for_layout(self) -> Vec<Range<usize>>;
+    fn composition_for_layout(self) -> Option<Range<usize>>;
     fn get_cols(self) -> u32;
     fn get_rows(self) -> u32;
 }
@@ -88,9 +93,22 @@ impl<'dom> LayoutDom<'dom, HTMLTextAreaElement> {
         }
     }
 
+    fn textinput_composition(self) -> Option<Range<usize>> {
+        unsafe {
+            self.unsafe_get()
+                .textinput
+                .borrow_for_layout()
+                .composition()
+        }
+    }
+
     fn placeholder(self) -> &'dom str {
         unsafe { self.unsafe_get().placeholder.borrow_for_layout() }
     }
+
+    fn spelling(self) -> &'dom Spelling {
+        unsafe { self.unsafe_get().spelling.borrow_for_layout() }
+    }
 }
 
 impl LayoutHTMLTextAreaElementHelpers for LayoutDom<'_, HTMLTextAreaElement> {
@@ -114,6 +132,17 @@ impl LayoutHTMLTextAreaElementHelpers for LayoutDom<'_, HTMLTextAreaElement> {
         ))
     }
 
+    fn spelling_errors_for_layout(self) -> Vec<Range<usize>> {
+        self.spelling().misspelled_words(&self.textinput_content())
+    }
+
+    fn composition_for_layout(self) -> Option<Range<usize>> {
+        if !self.upcast::<Element>().focus_state() {
+            return None;
+        }
+        self.textinput_composition()
+    }
+
     fn get_cols(self) -> u32 {
         self.upcast::<Element>()
             .get_attr_for_layout(&ns!(), &local_name!("cols"))
@@ -166,6 +195,7 @@ impl HTMLTextAreaElement {
                 None,
                 SelectionDirection::None,
             )),
+            spelling: Default::default(),
             value_dirty: Cell::new(false),
             form_owner: Default::default(),
             labels_node_list: Default::default(),
@@ -225,6 +255,20 @@ impl TextControlElement for HTMLTextAreaElement {
     }
 }
 
+impl SpellcheckedTextControl for HTMLTextAreaElement {
+    fn textinput(&self) -> &DomRefCell<TextInput<EmbedderClipboardProvider>> {
+        &self.textinput
+    }
+
+    fn spelling(&self) -> &DomRefCell<Spelling> {
+        &self.spelling
+    }
+
+    fn is_spellchecked(&self) -> bool {
+        self.is_mutable() && self.upcast::<Element>().is_spellcheck_enabled()
+    }
+}
+
 impl HTMLTextAreaElementMethods<crate::DomTypeHolder> for HTMLTextAreaElement {
     // TODO A few of these attributes have default values and additional
     // constraints
@@ -547,6 +591,7 @@ impl VirtualMethods for HTMLTextAreaElement {
 
         self.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        check_spelling(self);
     }
 
     fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
@@ -646,10 +691,12 @@ impl VirtualMethods for HTMLTextAreaElement {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        self.selection().scroll_into_view(can_gc);
                         event.mark_as_handled();
                     },
                     KeyReaction::RedrawSelection => {
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        self.selection().scroll_into_view(can_gc);
                         event.mark_as_handled();
                     },
                     KeyReaction::Nothing => (),
@@ -672,19 +719,32 @@ impl VirtualMethods for HTMLTextAreaElement {
             event.type_() == atom!("compositionend")
         {
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionend(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
-                } else if event.type_() == atom!("compositionupdate") {
-                    let _ = self
-                        .textinput
-                        .borrow_mut()
-                        .handle_compositionupdate(compositionevent);
-                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                {
+                    let mut textinput = self.textinput.borrow_mut();
+                    let _ = match event.type_() {
+                        atom!("compositionstart") => textinput.handle_compositionstart(),
+                        atom!("compositionupdate") => {
+                            textinput.handle_compositionupdate(compositionevent.data())
+                        },
+                        _ => textinput.handle_compositionend(compositionevent.data()),
+                    };
+                }
+                self.value_dirty.set(true);
+                self.update_placeholder_shown_state();
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                if event.type_() != atom!("compositionstart") {
+                    self.owner_global()
+                        .task_manager()
+                        .user_interaction_task_source()
+                        .queue_event(
+                            self.upcast(),
+                            atom!("input"),
+                            EventBubbles::Bubbles,
+                            EventCancelable::NotCancelable,
+                        );
                 }
+                self.owner_document()
+                    .send_composition_rect_to_embedder(self.upcast(), can_gc);
                 event.mark_as_handled();
             }
         } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
@@ -695,6 +755,7 @@ impl VirtualMethods for HTMLTextAreaElement {
 
         self.validity_state()
             .perform_validation_and_update(ValidationFlags::all(), can_gc);
+        check_spelling(self);
     }
 
     fn pop(&self) {
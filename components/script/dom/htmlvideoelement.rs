@@ -89,14 +89,16 @@ impl HTMLVideoElement {
         proto: Option<HandleObject>,
         can_gc: CanGc,
     ) -> DomRoot<HTMLVideoElement> {
-        Node::reflect_node_with_proto(
+        let element = Node::reflect_node_with_proto(
             Box::new(HTMLVideoElement::new_inherited(
                 local_name, prefix, document,
             )),
             document,
             proto,
             can_gc,
-        )
+        );
+        document.window().track_media_element(element.upcast());
+        element
     }
 
     pub(crate) fn get_video_width(&self) -> Option<u32> {
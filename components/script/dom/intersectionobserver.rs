@@ -12,6 +12,7 @@ use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
 use euclid::default::{Rect, Size2D};
 use js::rust::{HandleObject, MutableHandleValue};
+use servo_config::pref;
 use style::context::QuirksMode;
 use style::parser::{Parse, ParserContext};
 use style::stylesheets::{CssRuleType, Origin};
@@ -47,6 +48,19 @@ use crate::script_runtime::{CanGc, JSContext};
 /// <https://w3c.github.io/IntersectionObserver/#intersectionobserver-intersection-root>
 pub type IntersectionRoot = Option<ElementOrDocument>;
 
+/// The callback invoked by an [`IntersectionObserver`] when there are changes to the
+/// intersection of its targets.
+#[derive(JSTraceable, MallocSizeOf)]
+pub(crate) enum IntersectionObserverCallbackType {
+    /// A callback provided by script to the `IntersectionObserver` constructor.
+    Script(#[ignore_malloc_size_of = "Rc are hard"] Rc<IntersectionObserverCallback>),
+    /// The callback of the lazy load intersection observer of a [`Document`], which runs the
+    /// lazy load resumption steps of elements approaching the viewport.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    LazyLoad,
+}
+
 /// The Intersection Observer interface
 ///
 /// > The IntersectionObserver interface can be used to observe changes in the intersection
@@ -70,8 +84,7 @@ pub(crate) struct IntersectionObserver {
     /// > with the intersection root, as per the processing model.
     ///
     /// <https://w3c.github.io/IntersectionObserver/#intersection-observer-callback>
-    #[ignore_malloc_size_of = "Rc are hard"]
-    callback: Rc<IntersectionObserverCallback>,
+    callback: IntersectionObserverCallbackType,
 
     /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-queuedentries-slot>
     queued_entries: DomRefCell<Vec<Dom<IntersectionObserverEntry>>>,
@@ -102,7 +115,7 @@ pub(crate) struct IntersectionObserver {
 impl IntersectionObserver {
     fn new_inherited(
         window: &Window,
-        callback: Rc<IntersectionObserverCallback>,
+        callback: IntersectionObserverCallbackType,
         root: IntersectionRoot,
         root_margin: IntersectionObserverRootMargin,
         scroll_margin: IntersectionObserverRootMargin,
@@ -156,7 +169,7 @@ impl IntersectionObserver {
         let observer = reflect_dom_object_with_proto(
             Box::new(Self::new_inherited(
                 window,
-                callback,
+                IntersectionObserverCallbackType::Script(callback),
                 init.root.clone(),
                 root_margin,
                 scroll_margin,
@@ -172,6 +185,30 @@ impl IntersectionObserver {
         Ok(observer)
     }
 
+    /// Create the lazy load intersection observer of the [`Document`] of the given [`Window`],
+    /// whose root margin is the lazy load root margin.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    pub(crate) fn new_lazy_load_observer(window: &Window, can_gc: CanGc) -> DomRoot<Self> {
+        let root_margin = DOMString::from(format!("{}px", lazy_load_root_margin()));
+        let root_margin =
+            parse_a_margin(Some(&root_margin)).expect("Lazy load root margin should be valid");
+        let observer = reflect_dom_object_with_proto(
+            Box::new(Self::new_inherited(
+                window,
+                IntersectionObserverCallbackType::LazyLoad,
+                None,
+                root_margin,
+                parse_a_margin(None).expect("Default scroll margin should be valid"),
+            )),
+            window,
+            None,
+            can_gc,
+        );
+        observer.thresholds.replace(vec![Finite::wrap(0.)]);
+        observer
+    }
+
     /// Step 5-13 of <https://w3c.github.io/IntersectionObserver/#initialize-new-intersection-observer>
     fn init_observer(&self, init: &IntersectionObserverInit) -> Fallible<()> {
         // Step 5
@@ -382,13 +419,42 @@ impl IntersectionObserver {
             .collect();
 
         // Step 4-5
-        let _ = self.callback.Call_(
-            self,
-            queued_entries,
-            self,
-            ExceptionHandling::Report,
-            can_gc,
-        );
+        match &self.callback {
+            IntersectionObserverCallbackType::Script(callback) => {
+                let _ = callback.Call_(
+                    self,
+                    queued_entries,
+                    self,
+                    ExceptionHandling::Report,
+                    can_gc,
+                );
+            },
+            IntersectionObserverCallbackType::LazyLoad => {
+                Self::run_the_lazy_load_steps(queued_entries, can_gc);
+            },
+        }
+    }
+
+    /// The callback of the lazy load intersection observer.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    fn run_the_lazy_load_steps(entries: Vec<DomRoot<IntersectionObserverEntry>>, can_gc: CanGc) {
+        // > For each entry in entries using a method of iteration which does not trigger
+        // > developer-modifiable array accessors or iteration hooks:
+        for entry in entries {
+            // > 1. Let resumptionSteps be null.
+            // > 2. If entry.isIntersecting is true, then set resumptionSteps to entry.target's
+            // >    lazy load resumption steps.
+            // > 3. If resumptionSteps is null, then return.
+            if !entry.IsIntersecting() {
+                continue;
+            }
+
+            // > 4. Stop intersection-observing a lazy loading element for entry.target.
+            // > 5. Set entry.target's lazy load resumption steps to null.
+            // > 6. Invoke resumptionSteps.
+            entry.Target().run_lazy_load_resumption_steps(can_gc);
+        }
     }
 
     /// Connect the observer itself into owner doc if it is unconnected.
@@ -714,10 +780,9 @@ impl IntersectionObserverMethods<crate::DomTypeHolder> for IntersectionObserver
     ///
     /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-observe>
     fn Observe(&self, target: &Element) {
+        // Note: This connects the observer to the owner doc, to be accessed in the event loop,
+        // when observing the first target.
         self.observe_target_element(target);
-
-        // Connect to owner doc to be accessed in the event loop.
-        self.connect_to_owner_unchecked();
     }
 
     /// > Run the unobserve a target Element algorithm, providing this and target.
@@ -793,6 +858,24 @@ impl IntersectionObserverRegistration {
     }
 }
 
+/// > The lazy load root margin is an implementation-defined value, but with the following
+/// > recommendations to consider: Set a minimum value that most often results in the resources
+/// > being loaded before they intersect the viewport under normal usage patterns for the given
+/// > device. The typical scrolling speed and latency of the device could increase this value.
+/// > The network quality, such as the effective connection type, could increase this value.
+///
+/// This returns the margin in CSS pixels, loading resources earlier on slower connections.
+///
+/// <https://html.spec.whatwg.org/multipage/#lazy-load-root-margin>
+fn lazy_load_root_margin() -> u32 {
+    match &*pref!(network_effective_connection_type) {
+        "slow-2g" => 8000,
+        "2g" => 6000,
+        "3g" => 4000,
+        _ => 3000,
+    }
+}
+
 /// <https://w3c.github.io/IntersectionObserver/#parse-a-margin>
 fn parse_a_margin(value: Option<&DOMString>) -> Result<IntersectionObserverRootMargin, ()> {
     // <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserverinit-rootmargin> &&
@@ -0,0 +1,224 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, KeyboardLock};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use keyboard_types::Code;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::KeyboardBinding::KeyboardMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::keyboardlayoutmap::KeyboardLayoutMap;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::script_runtime::CanGc;
+use crate::task_source::SendableTaskSource;
+
+/// <https://wicg.github.io/keyboard-lock/#keyboard-interface>
+#[dom_struct]
+pub(crate) struct Keyboard {
+    eventtarget: EventTarget,
+    /// The promise of the last call to `lock()`, until the keyboard is locked.
+    #[ignore_malloc_size_of = "Rc"]
+    keyboard_lock_promise: DomRefCell<Option<Rc<Promise>>>,
+    /// Whether the embedder was asked to lock the keyboard.
+    keyboard_lock_enabled: Cell<bool>,
+    /// The promise of the pending calls to `getLayoutMap()`, until the embedder responds
+    /// with the keyboard layout.
+    #[ignore_malloc_size_of = "Rc"]
+    layout_map_promise: DomRefCell<Option<Rc<Promise>>>,
+}
+
+impl Keyboard {
+    fn new_inherited() -> Keyboard {
+        Keyboard {
+            eventtarget: EventTarget::new_inherited(),
+            keyboard_lock_promise: Default::default(),
+            keyboard_lock_enabled: Default::default(),
+            layout_map_promise: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<Keyboard> {
+        reflect_dom_object(Box::new(Keyboard::new_inherited()), window, can_gc)
+    }
+
+    /// Whether script is currently executing in the currently active top-level browsing
+    /// context, which is the only one that may lock the keyboard.
+    fn is_in_active_top_level_browsing_context(&self) -> bool {
+        let global = self.global();
+        let window = global.as_window();
+        window.is_top_level() && window.Document().is_fully_active()
+    }
+
+    fn handle_layout_map(&self, layout: Vec<(Code, String)>, can_gc: CanGc) {
+        let Some(promise) = self.layout_map_promise.borrow_mut().take() else {
+            return;
+        };
+        let layout = layout
+            .into_iter()
+            .map(|(code, character)| {
+                (
+                    DOMString::from(code.to_string()),
+                    DOMString::from(character),
+                )
+            })
+            .collect();
+        let map = KeyboardLayoutMap::new(self.global().as_window(), layout, can_gc);
+        promise.resolve_native(&map, can_gc);
+    }
+}
+
+impl KeyboardMethods<crate::DomTypeHolder> for Keyboard {
+    /// <https://wicg.github.io/keyboard-lock/#dom-keyboard-lock>
+    fn Lock(&self, key_codes: Vec<DOMString>, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1. Let p be a new promise in the current realm.
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 2. If not currently executing in the currently active top-level browsing
+        // context, then reject p with an "InvalidStateError" DOMException.
+        if !self.is_in_active_top_level_browsing_context() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 3. Append the keyCodes to the reserved key codes, rejecting p with an
+        // "InvalidAccessError" DOMException if any of them is not a valid key code.
+        let mut reserved_key_codes = Vec::with_capacity(key_codes.len());
+        for key_code in key_codes {
+            match key_code.parse::<Code>() {
+                Ok(code) if code != Code::Unidentified => reserved_key_codes.push(code),
+                _ => {
+                    promise.reject_error(Error::InvalidAccess, can_gc);
+                    return promise;
+                },
+            }
+        }
+
+        // Step 4. If [[keyboardLockPromise]] is not null, reject it with an "AbortError"
+        // DOMException.
+        if let Some(previous_promise) = self.keyboard_lock_promise.borrow_mut().take() {
+            previous_promise.reject_error(Error::Abort, can_gc);
+        }
+
+        // Step 5. Set [[keyboardLockPromise]] to p.
+        *self.keyboard_lock_promise.borrow_mut() = Some(promise.clone());
+
+        // Step 6. Enable the keyboard lock in parallel. If no key codes were given, all of
+        // the keys are locked.
+        let lock = if reserved_key_codes.is_empty() {
+            KeyboardLock::AllKeys
+        } else {
+            KeyboardLock::Keys(reserved_key_codes)
+        };
+        let global = self.global();
+        let window = global.as_window();
+        window.send_to_embedder(EmbedderMsg::SetKeyboardLock(
+            window.webview_id(),
+            Some(lock),
+        ));
+        self.keyboard_lock_enabled.set(true);
+
+        // Step 7. Queue a task to resolve [[keyboardLockPromise]] and set it to null.
+        let this = Trusted::new(self);
+        global.task_manager().user_interaction_task_source().queue(
+            task!(resolve_keyboard_lock: move || {
+                let keyboard = this.root();
+                let promise = keyboard.keyboard_lock_promise.borrow_mut().take();
+                if let Some(promise) = promise {
+                    promise.resolve_native(&(), CanGc::note());
+                }
+            }),
+        );
+
+        promise
+    }
+
+    /// <https://wicg.github.io/keyboard-lock/#dom-keyboard-unlock>
+    fn Unlock(&self, can_gc: CanGc) {
+        // Step 1. If not currently executing in the currently active top-level browsing
+        // context, then terminate these steps.
+        if !self.is_in_active_top_level_browsing_context() {
+            return;
+        }
+
+        // Step 2. If [[keyboardLockPromise]] is not null, reject it with an "AbortError"
+        // DOMException and set it to null.
+        if let Some(promise) = self.keyboard_lock_promise.borrow_mut().take() {
+            promise.reject_error(Error::Abort, can_gc);
+        }
+
+        // Step 3. If enableKeyboardLock is true, release the keyboard lock.
+        if self.keyboard_lock_enabled.replace(false) {
+            let global = self.global();
+            let window = global.as_window();
+            window.send_to_embedder(EmbedderMsg::SetKeyboardLock(window.webview_id(), None));
+        }
+    }
+
+    /// <https://wicg.github.io/keyboard-map/#dom-keyboard-getlayoutmap>
+    fn GetLayoutMap(&self, comp: InRealm, can_gc: CanGc) -> Rc<Promise> {
+        // Step 1. If [[layoutMapPromise]] is not null, return it.
+        if let Some(promise) = self.layout_map_promise.borrow().as_ref() {
+            return promise.clone();
+        }
+
+        // Step 2. Let p be a new promise in the current realm.
+        let promise = Promise::new_in_current_realm(comp, can_gc);
+
+        // Step 3. If not currently executing in the currently active top-level browsing
+        // context, or a fully active nested browsing context, then reject p with an
+        // "InvalidStateError" DOMException.
+        let global = self.global();
+        let window = global.as_window();
+        if !window.Document().is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Step 4. Set [[layoutMapPromise]] to p.
+        *self.layout_map_promise.borrow_mut() = Some(promise.clone());
+
+        // Step 5. In parallel, ask the embedder for the current keyboard layout, then queue a
+        // task to resolve [[layoutMapPromise]] with a new KeyboardLayoutMap.
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let this = Trusted::new(self);
+        let task_source: SendableTaskSource =
+            global.task_manager().user_interaction_task_source().into();
+        ROUTER.add_typed_route(
+            receiver,
+            Box::new(move |message| {
+                let layout = match message {
+                    Ok(layout) => layout,
+                    Err(error) => {
+                        warn!("Error receiving keyboard layout map: {error:?}");
+                        Vec::new()
+                    },
+                };
+                let this = this.clone();
+                task_source.queue(task!(resolve_keyboard_layout_map: move || {
+                    this.root().handle_layout_map(layout, CanGc::note());
+                }));
+            }),
+        );
+        window.send_to_embedder(EmbedderMsg::GetKeyboardLayoutMap(
+            window.webview_id(),
+            sender,
+        ));
+
+        promise
+    }
+}
@@ -0,0 +1,56 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use indexmap::IndexMap;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::KeyboardLayoutMapBinding::KeyboardLayoutMapMethods;
+use crate::dom::bindings::like::Maplike;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use crate::maplike;
+use crate::script_runtime::CanGc;
+
+/// A map from the physical code of each key of the keyboard layout to the character it
+/// produces without modifiers.
+/// <https://wicg.github.io/keyboard-map/#keyboardlayoutmap-interface>
+#[dom_struct]
+pub(crate) struct KeyboardLayoutMap {
+    reflector: Reflector,
+    #[custom_trace]
+    internal: DomRefCell<IndexMap<DOMString, DOMString>>,
+}
+
+impl KeyboardLayoutMap {
+    pub(crate) fn new(
+        window: &Window,
+        layout: IndexMap<DOMString, DOMString>,
+        can_gc: CanGc,
+    ) -> DomRoot<KeyboardLayoutMap> {
+        reflect_dom_object(
+            Box::new(KeyboardLayoutMap {
+                reflector: Reflector::new(),
+                internal: DomRefCell::new(layout),
+            }),
+            window,
+            can_gc,
+        )
+    }
+}
+
+impl KeyboardLayoutMapMethods<crate::DomTypeHolder> for KeyboardLayoutMap {
+    fn Size(&self) -> u32 {
+        self.internal.size()
+    }
+}
+
+impl Maplike for KeyboardLayoutMap {
+    type Key = DOMString;
+    type Value = DOMString;
+
+    maplike!(self, internal);
+}
@@ -41,6 +41,9 @@ pub(crate) enum NavigationType {
     ///
     /// [1]: https://html.spec.whatwg.org/multipage/#shared-declarative-refresh-steps
     DeclarativeRefresh,
+
+    /// Navigation requested by a devtools client.
+    Devtools,
 }
 
 #[dom_struct]
@@ -79,7 +82,8 @@ impl Location {
         let source_window = match navigation_type {
             NavigationType::ReloadByScript |
             NavigationType::ReloadByConstellation |
-            NavigationType::DeclarativeRefresh => {
+            NavigationType::DeclarativeRefresh |
+            NavigationType::Devtools => {
                 // > Navigate the browsing context [...] the source browsing context
                 // > set to the browsing context being navigated.
                 DomRoot::from_ref(&*self.window)
@@ -92,6 +96,14 @@ impl Location {
         };
         let source_document = source_window.Document();
 
+        // TODO: throw a "SecurityError" DOMException once exceptions are rethrown.
+        if !source_window
+            .window_proxy()
+            .allowed_by_sandboxing_to_navigate(&self.window.window_proxy())
+        {
+            return;
+        }
+
         let referrer = Referrer::ReferrerUrl(source_document.url());
         let referrer_policy = source_document.get_referrer_policy();
 
@@ -101,9 +113,9 @@ impl Location {
         // > node document of the element that initiated the navigation.
         let navigation_origin_window = match navigation_type {
             NavigationType::Normal | NavigationType::ReloadByScript => incumbent_window(),
-            NavigationType::ReloadByConstellation | NavigationType::DeclarativeRefresh => {
-                DomRoot::from_ref(&*self.window)
-            },
+            NavigationType::ReloadByConstellation |
+            NavigationType::DeclarativeRefresh |
+            NavigationType::Devtools => DomRoot::from_ref(&*self.window),
         };
         let (load_origin, creator_pipeline_id) = (
             navigation_origin_window.origin().immutable().clone(),
@@ -113,7 +125,9 @@ impl Location {
         // Is `historyHandling` `reload`?
         let reload_triggered = match navigation_type {
             NavigationType::ReloadByScript | NavigationType::ReloadByConstellation => true,
-            NavigationType::Normal | NavigationType::DeclarativeRefresh => false,
+            NavigationType::Normal |
+            NavigationType::DeclarativeRefresh |
+            NavigationType::Devtools => false,
         };
 
         // Initiate navigation
@@ -526,6 +526,7 @@ macro_rules! global_event_handlers(
         event_handler!(emptied, GetOnemptied, SetOnemptied);
         event_handler!(ended, GetOnended, SetOnended);
         event_handler!(formdata, GetOnformdata, SetOnformdata);
+        event_handler!(gotpointercapture, GetOngotpointercapture, SetOngotpointercapture);
         event_handler!(input, GetOninput, SetOninput);
         event_handler!(invalid, GetOninvalid, SetOninvalid);
         event_handler!(keydown, GetOnkeydown, SetOnkeydown);
@@ -534,6 +535,7 @@ macro_rules! global_event_handlers(
         event_handler!(loadeddata, GetOnloadeddata, SetOnloadeddata);
         event_handler!(loadedmetadata, GetOnloadedmetadata, SetOnloadedmetadata);
         event_handler!(loadstart, GetOnloadstart, SetOnloadstart);
+        event_handler!(lostpointercapture, GetOnlostpointercapture, SetOnlostpointercapture);
         event_handler!(mousedown, GetOnmousedown, SetOnmousedown);
         event_handler!(mouseenter, GetOnmouseenter, SetOnmouseenter);
         event_handler!(mouseleave, GetOnmouseleave, SetOnmouseleave);
@@ -545,6 +547,15 @@ macro_rules! global_event_handlers(
         event_handler!(pause, GetOnpause, SetOnpause);
         event_handler!(play, GetOnplay, SetOnplay);
         event_handler!(playing, GetOnplaying, SetOnplaying);
+        event_handler!(pointercancel, GetOnpointercancel, SetOnpointercancel);
+        event_handler!(pointerdown, GetOnpointerdown, SetOnpointerdown);
+        event_handler!(pointerenter, GetOnpointerenter, SetOnpointerenter);
+        event_handler!(pointerleave, GetOnpointerleave, SetOnpointerleave);
+        event_handler!(pointermove, GetOnpointermove, SetOnpointermove);
+        event_handler!(pointerout, GetOnpointerout, SetOnpointerout);
+        event_handler!(pointerover, GetOnpointerover, SetOnpointerover);
+        event_handler!(pointerrawupdate, GetOnpointerrawupdate, SetOnpointerrawupdate);
+        event_handler!(pointerup, GetOnpointerup, SetOnpointerup);
         event_handler!(progress, GetOnprogress, SetOnprogress);
         event_handler!(ratechange, GetOnratechange, SetOnratechange);
         event_handler!(reset, GetOnreset, SetOnreset);
@@ -59,30 +59,14 @@ impl MediaList {
     fn shared_lock(&self) -> &SharedRwLock {
         &self.parent_stylesheet.style_stylesheet().shared_lock
     }
-}
-
-impl MediaListMethods<crate::DomTypeHolder> for MediaList {
-    /// <https://drafts.csswg.org/cssom/#dom-medialist-mediatext>
-    fn MediaText(&self) -> DOMString {
-        let guard = self.shared_lock().read();
-        DOMString::from(self.media_queries.read_with(&guard).to_css_string())
-    }
 
-    /// <https://drafts.csswg.org/cssom/#dom-medialist-mediatext>
-    fn SetMediaText(&self, value: DOMString) {
-        let mut guard = self.shared_lock().write();
-        let media_queries = self.media_queries.write_with(&mut guard);
-        // Step 2
+    /// Parse a list of media queries, as given to `mediaText`.
+    pub(crate) fn parse_media_list(value: &str, window: &Window) -> StyleMediaList {
         if value.is_empty() {
-            // Step 1
-            *media_queries = StyleMediaList::empty();
-            return;
+            return StyleMediaList::empty();
         }
-        // Step 3
-        let mut input = ParserInput::new(&value);
+        let mut input = ParserInput::new(value);
         let mut parser = Parser::new(&mut input);
-        let global = self.global();
-        let window = global.as_window();
         let url_data = UrlExtraData(window.get_url().get_arc());
         let quirks_mode = window.Document().quirks_mode();
         let context = ParserContext::new(
@@ -95,7 +79,26 @@ impl MediaListMethods<crate::DomTypeHolder> for MediaList {
             window.css_error_reporter(),
             None,
         );
-        *media_queries = StyleMediaList::parse(&context, &mut parser);
+        StyleMediaList::parse(&context, &mut parser)
+    }
+}
+
+impl MediaListMethods<crate::DomTypeHolder> for MediaList {
+    /// <https://drafts.csswg.org/cssom/#dom-medialist-mediatext>
+    fn MediaText(&self) -> DOMString {
+        let guard = self.shared_lock().read();
+        DOMString::from(self.media_queries.read_with(&guard).to_css_string())
+    }
+
+    /// <https://drafts.csswg.org/cssom/#dom-medialist-mediatext>
+    fn SetMediaText(&self, value: DOMString) {
+        // Step 1 to 3
+        let media_list = MediaList::parse_media_list(&value, self.global().as_window());
+        {
+            let mut guard = self.shared_lock().write();
+            *self.media_queries.write_with(&mut guard) = media_list;
+        }
+        self.parent_stylesheet.notify_invalidations();
     }
 
     // https://drafts.csswg.org/cssom/#dom-medialist-length
@@ -145,17 +148,20 @@ impl MediaListMethods<crate::DomTypeHolder> for MediaList {
         }
         // Step 3
         let m_serialized = m.clone().unwrap().to_css_string();
-        let mut guard = self.shared_lock().write();
-        let mq = self.media_queries.write_with(&mut guard);
-        let any = mq
-            .media_queries
-            .iter()
-            .any(|q| m_serialized == q.to_css_string());
-        if any {
-            return;
+        {
+            let mut guard = self.shared_lock().write();
+            let mq = self.media_queries.write_with(&mut guard);
+            let any = mq
+                .media_queries
+                .iter()
+                .any(|q| m_serialized == q.to_css_string());
+            if any {
+                return;
+            }
+            // Step 4
+            mq.media_queries.push(m.unwrap());
         }
-        // Step 4
-        mq.media_queries.push(m.unwrap());
+        self.parent_stylesheet.notify_invalidations();
     }
 
     /// <https://drafts.csswg.org/cssom/#dom-medialist-deletemedium>
@@ -184,13 +190,16 @@ impl MediaListMethods<crate::DomTypeHolder> for MediaList {
         }
         // Step 3
         let m_serialized = m.unwrap().to_css_string();
-        let mut guard = self.shared_lock().write();
-        let media_list = self.media_queries.write_with(&mut guard);
-        let new_vec = media_list
-            .media_queries
-            .drain(..)
-            .filter(|q| m_serialized != q.to_css_string())
-            .collect();
-        media_list.media_queries = new_vec;
+        {
+            let mut guard = self.shared_lock().write();
+            let media_list = self.media_queries.write_with(&mut guard);
+            let new_vec = media_list
+                .media_queries
+                .drain(..)
+                .filter(|q| m_serialized != q.to_css_string())
+                .collect();
+            media_list.media_queries = new_vec;
+        }
+        self.parent_stylesheet.notify_invalidations();
     }
 }
@@ -5,9 +5,11 @@
 use std::cell::Cell;
 use std::rc::Rc;
 
+use cssparser::{Parser, ParserInput, Token, match_ignore_ascii_case};
 use dom_struct::dom_struct;
+use embedder_traits::{ForcedColors, HoverCapability, PointerCapability, PrefersReducedMotion};
 use style::media_queries::MediaList;
-use style_traits::ToCss;
+use style_traits::{ParseError, ToCss};
 
 use crate::dom::bindings::codegen::Bindings::EventListenerBinding::EventListener;
 use crate::dom::bindings::codegen::Bindings::EventTargetBinding::{
@@ -20,6 +22,7 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
 pub(crate) enum MediaQueryListMatchState {
@@ -31,16 +34,23 @@ pub(crate) enum MediaQueryListMatchState {
 pub(crate) struct MediaQueryList {
     eventtarget: EventTarget,
     document: Dom<Document>,
+    /// The media query list as it was given to `matchMedia()`.
+    query: String,
     #[no_trace]
     media_query_list: MediaList,
     last_match_state: Cell<Option<bool>>,
 }
 
 impl MediaQueryList {
-    fn new_inherited(document: &Document, media_query_list: MediaList) -> MediaQueryList {
+    fn new_inherited(
+        document: &Document,
+        query: String,
+        media_query_list: MediaList,
+    ) -> MediaQueryList {
         MediaQueryList {
             eventtarget: EventTarget::new_inherited(),
             document: Dom::from_ref(document),
+            query,
             media_query_list,
             last_match_state: Cell::new(None),
         }
@@ -48,11 +58,16 @@ impl MediaQueryList {
 
     pub(crate) fn new(
         document: &Document,
+        query: String,
         media_query_list: MediaList,
         can_gc: CanGc,
     ) -> DomRoot<MediaQueryList> {
         reflect_dom_object(
-            Box::new(MediaQueryList::new_inherited(document, media_query_list)),
+            Box::new(MediaQueryList::new_inherited(
+                document,
+                query,
+                media_query_list,
+            )),
             document.window(),
             can_gc,
         )
@@ -79,14 +94,204 @@ impl MediaQueryList {
 
     pub(crate) fn evaluate(&self) -> bool {
         let quirks_mode = self.document.quirks_mode();
+        let window = self.document.window();
+        if uses_features_unknown_to_stylo(&self.query) {
+            return evaluate_with_features_unknown_to_stylo(&self.query, window);
+        }
         self.media_query_list
-            .evaluate(self.document.window().layout().device(), quirks_mode)
+            .evaluate(window.layout().device(), quirks_mode)
+    }
+}
+
+/// The media features that Stylo does not evaluate for Servo. Media queries that use them are
+/// evaluated by [`evaluate_with_features_unknown_to_stylo`], which leaves the rest of their
+/// conditions to Stylo.
+const FEATURES_UNKNOWN_TO_STYLO: [&str; 4] = [
+    "prefers-reduced-motion",
+    "hover",
+    "pointer",
+    "forced-colors",
+];
+
+fn uses_features_unknown_to_stylo(query: &str) -> bool {
+    let query = query.to_ascii_lowercase();
+    FEATURES_UNKNOWN_TO_STYLO
+        .iter()
+        .any(|feature| query.contains(feature))
+}
+
+/// Evaluate a media query list that uses media features that Stylo does not know about, with
+/// the values that `window` has for them. Its media types and other media features are
+/// evaluated by Stylo. Media queries that fail to parse do not match.
+/// <https://drafts.csswg.org/mediaqueries-4/#mq-syntax>
+fn evaluate_with_features_unknown_to_stylo(query: &str, window: &Window) -> bool {
+    let mut input = ParserInput::new(query);
+    let mut parser = Parser::new(&mut input);
+    let Ok(matches) = parser.parse_comma_separated(|input| {
+        Ok(input
+            .parse_entirely(|input| parse_media_query(input, window))
+            .unwrap_or(false))
+    }) else {
+        return false;
+    };
+    matches.into_iter().any(|matches| matches)
+}
+
+/// Evaluate a part of a media query list, which is a media type or a media condition in
+/// parentheses, with Stylo.
+fn evaluate_with_stylo(query: &str, window: &Window) -> bool {
+    let document = window.Document();
+    window
+        .parse_media_query_list(query)
+        .evaluate(window.layout().device(), document.quirks_mode())
+}
+
+fn parse_media_query<'i>(
+    input: &mut Parser<'i, '_>,
+    window: &Window,
+) -> Result<bool, ParseError<'i>> {
+    if let Ok(matches) = input.try_parse(|input| parse_media_condition(input, window, true)) {
+        return Ok(matches);
     }
+
+    let negated = input
+        .try_parse(|input| input.expect_ident_matching("not"))
+        .is_ok();
+    if !negated {
+        let _ = input.try_parse(|input| input.expect_ident_matching("only"));
+    }
+    let media_type = input.expect_ident()?.clone();
+    let mut matches = evaluate_with_stylo(&media_type, window);
+    if input
+        .try_parse(|input| input.expect_ident_matching("and"))
+        .is_ok()
+    {
+        matches &= parse_media_condition(input, window, false)?;
+    }
+    Ok(matches != negated)
+}
+
+fn parse_media_condition<'i>(
+    input: &mut Parser<'i, '_>,
+    window: &Window,
+    allow_or: bool,
+) -> Result<bool, ParseError<'i>> {
+    if input
+        .try_parse(|input| input.expect_ident_matching("not"))
+        .is_ok()
+    {
+        return Ok(!parse_media_in_parens(input, window)?);
+    }
+
+    let mut matches = parse_media_in_parens(input, window)?;
+    let Ok(is_and) = input.try_parse(|input| {
+        let location = input.current_source_location();
+        let operator = input.expect_ident()?.clone();
+        if operator.eq_ignore_ascii_case("and") {
+            Ok(true)
+        } else if allow_or && operator.eq_ignore_ascii_case("or") {
+            Ok(false)
+        } else {
+            Err(location.new_unexpected_token_error(Token::Ident(operator)))
+        }
+    }) else {
+        return Ok(matches);
+    };
+    loop {
+        let next_matches = parse_media_in_parens(input, window)?;
+        matches = if is_and {
+            matches && next_matches
+        } else {
+            matches || next_matches
+        };
+        let operator = if is_and { "and" } else { "or" };
+        if input
+            .try_parse(|input| input.expect_ident_matching(operator))
+            .is_err()
+        {
+            return Ok(matches);
+        }
+    }
+}
+
+fn parse_media_in_parens<'i>(
+    input: &mut Parser<'i, '_>,
+    window: &Window,
+) -> Result<bool, ParseError<'i>> {
+    let start = input.position();
+    input.expect_parenthesis_block()?;
+    let matches = input.parse_nested_block(|input| {
+        if let Ok(matches) = input.try_parse(|input| {
+            input.parse_entirely(|input| parse_media_condition(input, window, true))
+        }) {
+            return Ok(Some(matches));
+        }
+        Ok(input
+            .try_parse(|input| input.parse_entirely(|input| parse_media_feature(input, window)))
+            .ok())
+    })?;
+    Ok(matches.unwrap_or_else(|| evaluate_with_stylo(input.slice_from(start), window)))
+}
+
+/// Evaluate one of [`FEATURES_UNKNOWN_TO_STYLO`], failing for other media features.
+fn parse_media_feature<'i>(
+    input: &mut Parser<'i, '_>,
+    window: &Window,
+) -> Result<bool, ParseError<'i>> {
+    let location = input.current_source_location();
+    let name = input.expect_ident()?.clone();
+    let value = match_ignore_ascii_case! { &*name,
+        "prefers-reduced-motion" => match window.prefers_reduced_motion() {
+            PrefersReducedMotion::NoPreference => "no-preference",
+            PrefersReducedMotion::Reduce => "reduce",
+        },
+        "hover" | "any-hover" => match window.hover_capability() {
+            HoverCapability::None => "none",
+            HoverCapability::Hover => "hover",
+        },
+        "pointer" | "any-pointer" => match window.pointer_capability() {
+            PointerCapability::None => "none",
+            PointerCapability::Coarse => "coarse",
+            PointerCapability::Fine => "fine",
+        },
+        "forced-colors" => match window.forced_colors() {
+            ForcedColors::None => "none",
+            ForcedColors::Active => "active",
+        },
+        _ => return Err(location.new_unexpected_token_error(Token::Ident(name.clone()))),
+    };
+
+    // In a boolean context, media features match unless their value is `none` or
+    // `no-preference`.
+    if input.is_exhausted() {
+        return Ok(value != "none" && value != "no-preference");
+    }
+    input.expect_colon()?;
+    let location = input.current_source_location();
+    let keyword = input.expect_ident()?.clone();
+    let keywords: &[&str] = match_ignore_ascii_case! { &*name,
+        "prefers-reduced-motion" => &["no-preference", "reduce"],
+        "hover" | "any-hover" => &["none", "hover"],
+        "forced-colors" => &["none", "active"],
+        _ => &["none", "coarse", "fine"],
+    };
+    if !keywords
+        .iter()
+        .any(|candidate| keyword.eq_ignore_ascii_case(candidate))
+    {
+        return Err(location.new_unexpected_token_error(Token::Ident(keyword.clone())));
+    }
+    Ok(keyword.eq_ignore_ascii_case(value))
 }
 
 impl MediaQueryListMethods<crate::DomTypeHolder> for MediaQueryList {
     // https://drafts.csswg.org/cssom-view/#dom-mediaquerylist-media
     fn Media(&self) -> DOMString {
+        // Stylo serializes media queries that use media features that it does not know about
+        // as `not all`.
+        if uses_features_unknown_to_stylo(&self.query) {
+            return self.query.trim().into();
+        }
         self.media_query_list.to_css_string().into()
     }
 
@@ -245,6 +245,7 @@ pub(crate) mod canvasgradient;
 pub(crate) mod canvaspattern;
 #[allow(dead_code)]
 pub(crate) mod canvasrenderingcontext2d;
+pub(crate) mod caretposition;
 pub(crate) mod cdatasection;
 pub(crate) mod channelmergernode;
 pub(crate) mod channelsplitternode;
@@ -283,6 +284,7 @@ pub(crate) mod customevent;
 pub(crate) mod datatransfer;
 pub(crate) mod datatransferitem;
 pub(crate) mod datatransferitemlist;
+pub(crate) mod debuggerglobalscope;
 pub(crate) mod dedicatedworkerglobalscope;
 pub(crate) mod defaultteereadrequest;
 pub(crate) mod defaultteeunderlyingsource;
@@ -307,6 +309,7 @@ pub(crate) mod domrectreadonly;
 pub(crate) mod domstringlist;
 pub(crate) mod domstringmap;
 pub(crate) mod domtokenlist;
+pub(crate) mod dragevent;
 pub(crate) mod dynamicmoduleowner;
 #[allow(dead_code)]
 pub(crate) mod element;
@@ -390,6 +393,7 @@ pub(crate) mod htmloutputelement;
 pub(crate) mod htmlparagraphelement;
 pub(crate) mod htmlparamelement;
 pub(crate) mod htmlpictureelement;
+pub(crate) mod htmlportalelement;
 pub(crate) mod htmlpreelement;
 pub(crate) mod htmlprogresselement;
 pub(crate) mod htmlquoteelement;
@@ -421,7 +425,9 @@ pub(crate) mod inputevent;
 pub(crate) mod intersectionobserver;
 pub(crate) mod intersectionobserverentry;
 pub(crate) mod intersectionobserverrootmargin;
+pub(crate) mod keyboard;
 pub(crate) mod keyboardevent;
+pub(crate) mod keyboardlayoutmap;
 pub(crate) mod location;
 pub(crate) mod mediadeviceinfo;
 pub(crate) mod mediadevices;
@@ -451,6 +457,7 @@ pub(crate) mod namednodemap;
 pub(crate) mod navigationpreloadmanager;
 pub(crate) mod navigator;
 pub(crate) mod navigatorinfo;
+pub(crate) mod networkinformation;
 #[allow(dead_code)]
 pub(crate) mod node;
 pub(crate) mod nodeiterator;
@@ -487,6 +494,8 @@ pub(crate) mod pluginarray;
 #[allow(dead_code)]
 pub(crate) mod pointerevent;
 pub(crate) mod popstateevent;
+pub(crate) mod portalactivateevent;
+pub(crate) mod portalhost;
 pub(crate) mod processinginstruction;
 pub(crate) mod progressevent;
 #[allow(dead_code)]
@@ -521,6 +530,8 @@ pub(crate) mod rtcrtptransceiver;
 pub(crate) mod rtcsessiondescription;
 pub(crate) mod rtctrackevent;
 pub(crate) mod screen;
+pub(crate) mod screendetailed;
+pub(crate) mod screendetails;
 pub(crate) mod securitypolicyviolationevent;
 pub(crate) mod selection;
 #[allow(dead_code)]
@@ -542,8 +553,20 @@ pub(crate) mod stylesheet;
 pub(crate) mod stylesheetlist;
 pub(crate) mod submitevent;
 pub(crate) mod subtlecrypto;
+pub(crate) mod svganimatedlength;
+pub(crate) mod svganimatednumber;
+pub(crate) mod svgcircleelement;
 pub(crate) mod svgelement;
+pub(crate) mod svgellipseelement;
+pub(crate) mod svggelement;
+pub(crate) mod svggeometryelement;
 pub(crate) mod svggraphicselement;
+pub(crate) mod svglength;
+pub(crate) mod svglineelement;
+pub(crate) mod svgpathelement;
+pub(crate) mod svgpolygonelement;
+pub(crate) mod svgpolylineelement;
+pub(crate) mod svgrectelement;
 pub(crate) mod svgsvgelement;
 pub(crate) mod testbinding;
 pub(crate) mod testbindingiterable;
@@ -567,6 +590,7 @@ pub(crate) mod texttrackcuelist;
 pub(crate) mod texttracklist;
 #[allow(dead_code)]
 pub(crate) mod timeranges;
+pub(crate) mod toggleevent;
 pub(crate) mod touch;
 pub(crate) mod touchevent;
 pub(crate) mod touchlist;
@@ -588,6 +612,7 @@ pub(crate) mod videotrack;
 pub(crate) mod videotracklist;
 pub(crate) mod virtualmethods;
 pub(crate) mod visibilitystateentry;
+pub(crate) mod visualviewport;
 pub(crate) mod vttcue;
 pub(crate) mod vttregion;
 pub(crate) mod webgl2renderingcontext;
@@ -612,6 +637,9 @@ pub(crate) mod webgluniformlocation;
 pub(crate) mod webglvertexarrayobject;
 pub(crate) mod webglvertexarrayobjectoes;
 pub(crate) mod websocket;
+pub(crate) mod webtransport;
+pub(crate) mod webtransportbidirectionalstream;
+pub(crate) mod webtransportdatagramduplexstream;
 #[cfg(feature = "webxr")]
 mod webxr;
 #[cfg(feature = "webxr")]
@@ -8,7 +8,9 @@ use std::rc::Rc;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Namespace, namespace_url, ns};
 use js::rust::HandleObject;
+use smallvec::SmallVec;
 
+use crate::devtools;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::MutationObserverBinding::MutationObserver_Binding::MutationObserverMethods;
@@ -18,7 +20,7 @@ use crate::dom::bindings::codegen::Bindings::MutationObserverBinding::{
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::mutationrecord::MutationRecord;
@@ -33,8 +35,8 @@ pub(crate) struct MutationObserver {
     reflector_: Reflector,
     #[ignore_malloc_size_of = "can't measure Rc values"]
     callback: Rc<MutationCallback>,
-    record_queue: DomRefCell<Vec<DomRoot<MutationRecord>>>,
-    node_list: DomRefCell<Vec<DomRoot<Node>>>,
+    record_queue: DomRefCell<Vec<Dom<MutationRecord>>>,
+    node_list: DomRefCell<Vec<Dom<Node>>>,
 }
 
 pub(crate) enum Mutation<'a> {
@@ -55,8 +57,9 @@ pub(crate) enum Mutation<'a> {
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
 pub(crate) struct RegisteredObserver {
-    pub(crate) observer: DomRoot<MutationObserver>,
+    pub(crate) observer: Dom<MutationObserver>,
     options: ObserverOptions,
 }
 
@@ -68,7 +71,9 @@ pub(crate) struct ObserverOptions {
     character_data_old_value: bool,
     child_list: bool,
     subtree: bool,
-    attribute_filter: Vec<DOMString>,
+    /// The local names of the attributes to observe, interned once when observing starts, or
+    /// `None` to observe all attributes.
+    attribute_filter: Option<Box<[LocalName]>>,
 }
 
 impl MutationObserver {
@@ -91,6 +96,15 @@ impl MutationObserver {
         }
     }
 
+    /// Empty the record queue of this observer, returning the records that were in it.
+    fn take_record_queue(&self) -> Vec<DomRoot<MutationRecord>> {
+        self.record_queue
+            .borrow_mut()
+            .drain(..)
+            .map(|record| DomRoot::from_ref(&*record))
+            .collect()
+    }
+
     /// <https://dom.spec.whatwg.org/#queue-a-mutation-observer-compound-microtask>
     pub(crate) fn queue_mutation_observer_microtask() {
         // Step 1. If the surrounding agent’s mutation observer microtask queued is true, then return.
@@ -111,8 +125,8 @@ impl MutationObserver {
         ScriptThread::set_mutation_observer_microtask_queued(false);
 
         // Step 2. Let notifySet be a clone of the surrounding agent’s pending mutation observers.
-        // TODO Step 3. Empty the surrounding agent’s pending mutation observers.
-        let notify_list = ScriptThread::get_mutation_observers();
+        // Step 3. Empty the surrounding agent’s pending mutation observers.
+        let notify_list = ScriptThread::take_pending_mutation_observers();
 
         // Step 4. Let signalSet be a clone of the surrounding agent’s signal slots.
         // Step 5. Empty the surrounding agent’s signal slots.
@@ -121,10 +135,8 @@ impl MutationObserver {
         // Step 6. For each mo of notifySet:
         for mo in &notify_list {
             // Step 6.1 Let records be a clone of mo’s record queue.
-            let queue: Vec<DomRoot<MutationRecord>> = mo.record_queue.borrow().clone();
-
             // Step 6.2 Empty mo’s record queue.
-            mo.record_queue.borrow_mut().clear();
+            let queue = mo.take_record_queue();
 
             // TODO Step 6.3 For each node of mo’s node list, remove all transient registered observers
             // whose observer is mo from node’s registered observer list.
@@ -144,6 +156,9 @@ impl MutationObserver {
             slot.upcast::<EventTarget>()
                 .fire_event(atom!("slotchange"), can_gc);
         }
+
+        // Now that their results are known, report the mutations to the devtools inspector.
+        ScriptThread::send_devtools_dom_mutations();
     }
 
     /// <https://dom.spec.whatwg.org/#queueing-a-mutation-record>
@@ -153,96 +168,96 @@ impl MutationObserver {
     ) where
         F: FnOnce() -> Mutation<'a>,
     {
+        // The devtools inspector watches mutations regardless of mutation observers.
+        if target.owner_doc().devtools_watches_dom_mutations() {
+            devtools::queue_dom_mutation(target, &attr_type);
+            MutationObserver::queue_mutation_observer_microtask();
+        }
+
         if !target.global().as_window().get_exists_mut_observer() {
             return;
         }
-        // Step 1
-        let mut interested_observers: Vec<(DomRoot<MutationObserver>, Option<DOMString>)> = vec![];
-
-        // Step 2 & 3
+        // Step 1. Let interestedObservers be an empty map.
+        // Most mutations have a handful of interested observers at most, which are kept on the stack.
+        let mut interested_observers: SmallVec<
+            [(DomRoot<MutationObserver>, Option<DOMString>); 4],
+        > = SmallVec::new();
+
+        // Step 2. Let nodes be the inclusive ancestors of target.
+        // Step 3. For each node in nodes, and then for each registered of node’s registered observer list:
         for node in target.inclusive_ancestors(ShadowIncluding::No) {
-            let registered = node.registered_mutation_observers();
-            if registered.is_none() {
+            let Some(registered_observers) = node.registered_mutation_observers() else {
                 continue;
-            }
+            };
+
+            for registered in &*registered_observers {
+                // Step 3.1. Let options be registered’s options.
+                let options = &registered.options;
 
-            for registered in &*registered.unwrap() {
-                if &*node != target && !registered.options.subtree {
+                // Step 3.2. Skip registered if node is not target and options["subtree"] is false,
+                // or if options do not ask for mutations of this type or of this attribute.
+                if &*node != target && !options.subtree {
                     continue;
                 }
-
-                match *attr_type {
+                let paired_string = match *attr_type {
                     Mutation::Attribute {
                         ref name,
                         ref namespace,
                         ref old_value,
                     } => {
-                        // Step 3.1
-                        if !registered.options.attributes {
+                        if !options.attributes {
                             continue;
                         }
-                        if !registered.options.attribute_filter.is_empty() {
-                            if *namespace != ns!() {
-                                continue;
-                            }
-                            if !registered
-                                .options
-                                .attribute_filter
-                                .iter()
-                                .any(|s| **s == **name)
-                            {
+                        if let Some(attribute_filter) = &options.attribute_filter {
+                            if *namespace != ns!() || !attribute_filter.contains(name) {
                                 continue;
                             }
                         }
-                        // Step 3.1.2
-                        let paired_string = if registered.options.attribute_old_value {
-                            old_value.clone()
-                        } else {
+                        if !options.attribute_old_value {
                             None
-                        };
-                        // Step 3.1.1
-                        let idx = interested_observers
-                            .iter()
-                            .position(|(o, _)| std::ptr::eq(&**o, &*registered.observer));
-                        if let Some(idx) = idx {
-                            interested_observers[idx].1 = paired_string;
                         } else {
-                            interested_observers
-                                .push((DomRoot::from_ref(&*registered.observer), paired_string));
+                            old_value.clone()
                         }
                     },
                     Mutation::CharacterData { ref old_value } => {
-                        if !registered.options.character_data {
+                        if !options.character_data {
                             continue;
                         }
-                        // Step 3.1.2
-                        let paired_string = if registered.options.character_data_old_value {
-                            Some(old_value.clone())
-                        } else {
+                        if !options.character_data_old_value {
                             None
-                        };
-                        // Step 3.1.1
-                        let idx = interested_observers
-                            .iter()
-                            .position(|(o, _)| std::ptr::eq(&**o, &*registered.observer));
-                        if let Some(idx) = idx {
-                            interested_observers[idx].1 = paired_string;
                         } else {
-                            interested_observers
-                                .push((DomRoot::from_ref(&*registered.observer), paired_string));
+                            Some(old_value.clone())
                         }
                     },
                     Mutation::ChildList { .. } => {
-                        if !registered.options.child_list {
+                        if !options.child_list {
                             continue;
                         }
-                        interested_observers.push((DomRoot::from_ref(&*registered.observer), None));
+                        None
                     },
+                };
+
+                // Step 3.2.2. If interestedObservers[mo] does not exist, then set
+                // interestedObservers[mo] to null.
+                // Step 3.2.3. If either type is "attributes" and options["attributeOldValue"] is
+                // true, or type is "characterData" and options["characterDataOldValue"] is true,
+                // then set interestedObservers[mo] to oldValue.
+                match interested_observers
+                    .iter_mut()
+                    .find(|(observer, _)| **observer == *registered.observer)
+                {
+                    Some((_, interested_paired_string)) => {
+                        if paired_string.is_some() {
+                            *interested_paired_string = paired_string;
+                        }
+                    },
+                    None => interested_observers
+                        .push((DomRoot::from_ref(&*registered.observer), paired_string)),
                 }
             }
         }
 
-        // Step 4
+        // Step 4. For each observer → mappedOldValue of interestedObservers:
         for (observer, paired_string) in interested_observers {
             // Steps 4.1-4.7
             let record = match *attr_type {
@@ -281,8 +296,14 @@ impl MutationObserver {
                     CanGc::note(),
                 ),
             };
-            // Step 4.8
-            observer.record_queue.borrow_mut().push(record);
+            // Step 4.8. Enqueue record to observer’s record queue.
+            observer
+                .record_queue
+                .borrow_mut()
+                .push(Dom::from_ref(&*record));
+
+            // Step 4.9. Append observer to the surrounding agent’s pending mutation observers.
+            ScriptThread::add_pending_mutation_observer(&observer);
         }
 
         // Step 5
@@ -299,14 +320,20 @@ impl MutationObserverMethods<crate::DomTypeHolder> for MutationObserver {
         callback: Rc<MutationCallback>,
     ) -> Fallible<DomRoot<MutationObserver>> {
         global.set_exists_mut_observer();
-        let observer = MutationObserver::new_with_proto(global, proto, callback, can_gc);
-        ScriptThread::add_mutation_observer(&observer);
-        Ok(observer)
+        Ok(MutationObserver::new_with_proto(
+            global, proto, callback, can_gc,
+        ))
     }
 
     /// <https://dom.spec.whatwg.org/#dom-mutationobserver-observe>
     fn Observe(&self, target: &Node, options: &MutationObserverInit) -> Fallible<()> {
-        let attribute_filter = options.attributeFilter.clone().unwrap_or_default();
+        let attribute_filter: Option<Box<[LocalName]>> =
+            options.attributeFilter.as_ref().map(|attribute_filter| {
+                attribute_filter
+                    .iter()
+                    .map(|name| LocalName::from(&**name))
+                    .collect()
+            });
         let attribute_old_value = options.attributeOldValue.unwrap_or(false);
         let mut attributes = options.attributes.unwrap_or(false);
         let mut character_data = options.characterData.unwrap_or(false);
@@ -382,7 +409,7 @@ impl MutationObserverMethods<crate::DomTypeHolder> for MutationObserver {
         // Step 8
         if add_new_observer {
             target.add_mutation_observer(RegisteredObserver {
-                observer: DomRoot::from_ref(self),
+                observer: Dom::from_ref(self),
                 options: ObserverOptions {
                     attributes,
                     attribute_old_value,
@@ -394,7 +421,7 @@ impl MutationObserverMethods<crate::DomTypeHolder> for MutationObserver {
                 },
             });
 
-            self.node_list.borrow_mut().push(DomRoot::from_ref(target));
+            self.node_list.borrow_mut().push(Dom::from_ref(target));
         }
 
         Ok(())
@@ -402,16 +429,19 @@ impl MutationObserverMethods<crate::DomTypeHolder> for MutationObserver {
 
     /// <https://dom.spec.whatwg.org/#dom-mutationobserver-takerecords>
     fn TakeRecords(&self) -> Vec<DomRoot<MutationRecord>> {
-        let records: Vec<DomRoot<MutationRecord>> = self.record_queue.borrow().clone();
-        self.record_queue.borrow_mut().clear();
-        records
+        self.take_record_queue()
     }
 
     /// <https://dom.spec.whatwg.org/#dom-mutationobserver-disconnect>
     fn Disconnect(&self) {
         // Step 1
-        let mut nodes = self.node_list.borrow_mut();
-        for node in nodes.drain(..) {
+        let nodes: Vec<DomRoot<Node>> = self
+            .node_list
+            .borrow_mut()
+            .drain(..)
+            .map(|node| DomRoot::from_ref(&*node))
+            .collect();
+        for node in nodes {
             node.remove_mutation_observer(self);
         }
 
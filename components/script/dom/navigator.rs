@@ -20,10 +20,12 @@ use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bluetooth::Bluetooth;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gamepadevent::GamepadEventType;
+use crate::dom::keyboard::Keyboard;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::networkinformation::NetworkInformation;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
@@ -61,6 +63,8 @@ pub(crate) struct Navigator {
     /// <https://www.w3.org/TR/gamepad/#dfn-hasgamepadgesture>
     has_gamepad_gesture: Cell<bool>,
     servo_internals: MutNullableDom<ServoInternals>,
+    connection: MutNullableDom<NetworkInformation>,
+    keyboard: MutNullableDom<Keyboard>,
 }
 
 impl Navigator {
@@ -82,6 +86,8 @@ impl Navigator {
             gpu: Default::default(),
             has_gamepad_gesture: Cell::new(false),
             servo_internals: Default::default(),
+            connection: Default::default(),
+            keyboard: Default::default(),
         }
     }
 
@@ -147,6 +153,11 @@ impl Navigator {
     pub(crate) fn set_has_gamepad_gesture(&self, has_gamepad_gesture: bool) {
         self.has_gamepad_gesture.set(has_gamepad_gesture);
     }
+
+    /// The [`NetworkInformation`] of `navigator.connection`, if content has accessed it.
+    pub(crate) fn connection(&self) -> Option<DomRoot<NetworkInformation>> {
+        self.connection.get()
+    }
 }
 
 impl NavigatorMethods<crate::DomTypeHolder> for Navigator {
@@ -246,6 +257,23 @@ impl NavigatorMethods<crate::DomTypeHolder> for Navigator {
         true
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-navigator-online>
+    fn OnLine(&self) -> bool {
+        self.global().as_window().network_status().online
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-navigator-connection>
+    fn Connection(&self) -> DomRoot<NetworkInformation> {
+        self.connection
+            .or_init(|| NetworkInformation::new(self.global().as_window(), CanGc::note()))
+    }
+
+    /// <https://wicg.github.io/keyboard-lock/#dom-navigator-keyboard>
+    fn Keyboard(&self) -> DomRoot<Keyboard> {
+        self.keyboard
+            .or_init(|| Keyboard::new(self.global().as_window(), CanGc::note()))
+    }
+
     /// <https://www.w3.org/TR/gamepad/#dom-navigator-getgamepads>
     fn GetGamepads(&self) -> Vec<Option<DomRoot<Gamepad>>> {
         let global = self.global();
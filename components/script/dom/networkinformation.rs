@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::NetworkStatus;
+
+use crate::dom::bindings::codegen::Bindings::NetworkInformationBinding::{
+    EffectiveConnectionType, NetworkInformationMethods,
+};
+use crate::dom::bindings::reflector::{DomGlobal, reflect_dom_object};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// The largest downlink, in megabits per second, that is exposed to content, so that the
+/// attributes can't be used to fingerprint fast connections.
+const MAX_DOWNLINK: f64 = 10.0;
+
+/// The largest round-trip time, in milliseconds, that is exposed to content.
+const MAX_RTT: u64 = 3000;
+
+/// <https://wicg.github.io/netinfo/#networkinformation-interface>
+#[dom_struct]
+pub(crate) struct NetworkInformation {
+    eventtarget: EventTarget,
+}
+
+impl NetworkInformation {
+    fn new_inherited() -> NetworkInformation {
+        NetworkInformation {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<NetworkInformation> {
+        reflect_dom_object(
+            Box::new(NetworkInformation::new_inherited()),
+            window,
+            can_gc,
+        )
+    }
+
+    fn network_status(&self) -> NetworkStatus {
+        self.global().as_window().network_status()
+    }
+}
+
+impl NetworkInformationMethods<crate::DomTypeHolder> for NetworkInformation {
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-effectivetype>
+    fn EffectiveType(&self) -> EffectiveConnectionType {
+        match self.network_status().effective_type {
+            embedder_traits::EffectiveConnectionType::Slow2G => EffectiveConnectionType::Slow_2g,
+            embedder_traits::EffectiveConnectionType::TwoG => EffectiveConnectionType::_2g,
+            embedder_traits::EffectiveConnectionType::ThreeG => EffectiveConnectionType::_3g,
+            embedder_traits::EffectiveConnectionType::FourG => EffectiveConnectionType::_4g,
+        }
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-downlink>
+    fn Downlink(&self) -> f64 {
+        // The value is rounded to the nearest multiple of 25 kilobits per second.
+        let downlink = self.network_status().downlink.clamp(0., MAX_DOWNLINK);
+        (downlink * 40.).round() / 40.
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-rtt>
+    fn Rtt(&self) -> u64 {
+        // The value is rounded to the nearest multiple of 25 milliseconds.
+        let rtt = self.network_status().rtt.min(MAX_RTT);
+        (rtt + 12) / 25 * 25
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-savedata>
+    fn SaveData(&self) -> bool {
+        self.network_status().save_data
+    }
+
+    // https://wicg.github.io/netinfo/#dom-networkinformation-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
@@ -26,7 +26,7 @@ use html5ever::{Namespace, Prefix, QualName, namespace_url, ns, serialize as htm
 use js::jsapi::JSObject;
 use js::rust::HandleObject;
 use libc::{self, c_void, uintptr_t};
-use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use malloc_size_of::{MallocConditionalShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use pixels::{Image, ImageMetadata};
 use script_bindings::codegen::InheritTypes::DocumentFragmentTypeId;
 use script_layout_interface::{
@@ -101,6 +101,7 @@ use crate::dom::htmliframeelement::{HTMLIFrameElement, HTMLIFrameElementLayoutMe
 use crate::dom::htmlimageelement::{HTMLImageElement, LayoutHTMLImageElementHelpers};
 use crate::dom::htmlinputelement::{HTMLInputElement, LayoutHTMLInputElementHelpers};
 use crate::dom::htmllinkelement::HTMLLinkElement;
+use crate::dom::htmlportalelement::{HTMLPortalElement, HTMLPortalElementLayoutMethods};
 use crate::dom::htmlslotelement::{HTMLSlotElement, Slottable};
 use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
@@ -206,7 +207,11 @@ bitflags! {
         /// to be reachable with using sequential focus navigation."]
         const SEQUENTIALLY_FOCUSABLE = 1 << 3;
 
-        // There are two free bits here.
+        /// Whether this node was moved with `moveBefore()` since layout last built its boxes,
+        /// so that layout can reuse them if nothing else changed.
+        const WAS_MOVED = 1 << 4;
+
+        // There is one free bit here.
 
         /// Specifies whether the parser has set an associated form owner for
         /// this element. Only applicable for form-associatable elements.
@@ -248,7 +253,7 @@ impl Node {
     /// Adds a new child to the end of this node's list of children.
     ///
     /// Fails unless `new_child` is disconnected from the tree.
-    fn add_child(&self, new_child: &Node, before: Option<&Node>, can_gc: CanGc) {
+    fn add_child(&self, new_child: &Node, before: Option<&Node>, is_move: bool, can_gc: CanGc) {
         assert!(new_child.parent_node.get().is_none());
         assert!(new_child.prev_sibling.get().is_none());
         assert!(new_child.next_sibling.get().is_none());
@@ -312,6 +317,7 @@ impl Node {
                     tree_connected: parent_is_connected,
                     tree_is_in_a_document_tree: parent_is_in_a_document_tree,
                     tree_is_in_a_shadow_tree: parent_in_shadow_tree,
+                    is_move,
                 },
                 can_gc,
             );
@@ -353,7 +359,12 @@ impl Node {
         let is_parent_connected = context.parent.is_connected();
 
         for node in root.traverse_preorder(ShadowIncluding::Yes) {
-            node.clean_up_style_and_layout_data();
+            // A moved node keeps its style and layout data, so that its animations and
+            // transitions keep running and layout can reuse its boxes. It is restyled once it
+            // is inserted again.
+            if !context.is_move {
+                node.clean_up_style_and_layout_data();
+            }
 
             // Step 11 & 14.1. Run the removing steps.
             // This needs to be in its own loop, because unbind_from_tree may
@@ -361,8 +372,9 @@ impl Node {
             // e.g. when removing a <form>.
             vtable_for(&node).unbind_from_tree(context, can_gc);
 
-            // Step 12 & 14.2. Enqueue disconnected custom element reactions.
-            if is_parent_connected {
+            // Step 12 & 14.2. Enqueue disconnected custom element reactions. Moved custom
+            // elements get a single reaction once they are inserted again.
+            if is_parent_connected && !context.is_move {
                 if let Some(element) = node.as_custom_element() {
                     ScriptThread::enqueue_callback_reaction(
                         &element,
@@ -377,7 +389,7 @@ impl Node {
     /// Removes the given child from this node's list of children.
     ///
     /// Fails unless `child` is a child of this node.
-    fn remove_child(&self, child: &Node, cached_index: Option<u32>, can_gc: CanGc) {
+    fn remove_child(&self, child: &Node, cached_index: Option<u32>, is_move: bool, can_gc: CanGc) {
         assert!(child.parent_node.get().as_deref() == Some(self));
         self.note_dirty_descendants();
 
@@ -404,12 +416,15 @@ impl Node {
             },
         }
 
-        let context = UnbindContext::new(
-            self,
-            prev_sibling.as_deref(),
-            next_sibling.as_deref(),
-            cached_index,
-        );
+        let context = UnbindContext {
+            is_move,
+            ..UnbindContext::new(
+                self,
+                prev_sibling.as_deref(),
+                next_sibling.as_deref(),
+                cached_index,
+            )
+        };
 
         child.prev_sibling.set(None);
         child.next_sibling.set(None);
@@ -1047,6 +1062,28 @@ impl Node {
         Ok(())
     }
 
+    /// <https://dom.spec.whatwg.org/#dom-parentnode-movebefore>
+    pub(crate) fn move_before(
+        &self,
+        node: &Node,
+        child: Option<&Node>,
+        can_gc: CanGc,
+    ) -> ErrorResult {
+        // Step 1. Let referenceChild be child.
+        // Step 2. If referenceChild is node, then set referenceChild to node’s next sibling.
+        let reference_child_root;
+        let reference_child = match child {
+            Some(child) if child == node => {
+                reference_child_root = node.GetNextSibling();
+                reference_child_root.as_deref()
+            },
+            _ => child,
+        };
+
+        // Step 3. Move node into this before referenceChild.
+        Node::move_node(node, self, reference_child, can_gc)
+    }
+
     /// <https://dom.spec.whatwg.org/#dom-parentnode-queryselector>
     pub(crate) fn query_selector(
         &self,
@@ -1374,6 +1411,24 @@ impl Node {
         })
     }
 
+    /// The memory used by the computed styles of this node, not counting the styles that
+    /// were already measured with `ops` for other nodes that share them.
+    pub(crate) fn computed_styles_size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let style_data = self.style_data.borrow();
+        let Some(style_data) = style_data.as_ref() else {
+            return 0;
+        };
+        let element_data = style_data.element_data.borrow();
+        let pseudos = element_data.styles.pseudos.as_array().into_iter().flatten();
+        element_data
+            .styles
+            .primary
+            .iter()
+            .chain(pseudos.flatten())
+            .map(|style| style.conditional_shallow_size_of(ops))
+            .sum()
+    }
+
     pub(crate) fn style(&self, can_gc: CanGc) -> Option<Arc<ComputedValues>> {
         if !self
             .owner_window()
@@ -1555,6 +1610,8 @@ pub(crate) trait LayoutNodeHelpers<'dom> {
 
     fn text_content(self) -> Cow<'dom, str>;
     fn selection(self) -> Option<Range<usize>>;
+    fn spelling_errors(self) -> Vec<Range<usize>>;
+    fn composition(self) -> Option<Range<usize>>;
     fn image_url(self) -> Option<ServoUrl>;
     fn image_density(self) -> Option<f64>;
     fn image_data(self) -> Option<(Option<StdArc<Image>>, Option<ImageMetadata>)>;
@@ -1563,6 +1620,7 @@ pub(crate) trait LayoutNodeHelpers<'dom> {
     fn svg_data(self) -> Option<SVGSVGData>;
     fn iframe_browsing_context_id(self) -> Option<BrowsingContextId>;
     fn iframe_pipeline_id(self) -> Option<PipelineId>;
+    fn portal_pipeline_id(self) -> Option<PipelineId>;
     fn opaque(self) -> OpaqueNode;
 }
 
@@ -1745,6 +1803,30 @@ impl<'dom> LayoutNodeHelpers<'dom> for LayoutDom<'dom, Node> {
         None
     }
 
+    fn spelling_errors(self) -> Vec<Range<usize>> {
+        if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
+            return area.spelling_errors_for_layout();
+        }
+
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return input.spelling_errors_for_layout();
+        }
+
+        Vec::new()
+    }
+
+    fn composition(self) -> Option<Range<usize>> {
+        if let Some(area) = self.downcast::<HTMLTextAreaElement>() {
+            return area.composition_for_layout();
+        }
+
+        if let Some(input) = self.downcast::<HTMLInputElement>() {
+            return input.composition_for_layout();
+        }
+
+        None
+    }
+
     fn image_url(self) -> Option<ServoUrl> {
         self.downcast::<HTMLImageElement>()
             .expect("not an image!")
@@ -1785,6 +1867,11 @@ impl<'dom> LayoutNodeHelpers<'dom> for LayoutDom<'dom, Node> {
             .and_then(|iframe_element| iframe_element.pipeline_id())
     }
 
+    fn portal_pipeline_id(self) -> Option<PipelineId> {
+        self.downcast::<HTMLPortalElement>()
+            .and_then(|portal_element| portal_element.pipeline_id())
+    }
+
     #[allow(unsafe_code)]
     fn opaque(self) -> OpaqueNode {
         unsafe { OpaqueNode(self.get_jsobject() as usize) }
@@ -2317,7 +2404,7 @@ impl Node {
         // Step 7.
         for kid in new_nodes {
             // Step 7.1.
-            parent.add_child(kid, child, can_gc);
+            parent.add_child(kid, child, false, can_gc);
 
             // Step 7.4 If parent is a shadow host whose shadow root’s slot assignment is "named"
             // and node is a slottable, then assign a slot for node.
@@ -2414,6 +2501,236 @@ impl Node {
         node.owner_doc().remove_script_and_layout_blocker();
     }
 
+    /// <https://dom.spec.whatwg.org/#move>
+    ///
+    /// Unlike removing and inserting the node again, moving it keeps its state: the removing and
+    /// insertion steps are run with [`UnbindContext::is_move`] and [`BindContext::is_move`], so
+    /// that iframes keep their nested browsing contexts and animations keep running.
+    fn move_node(
+        node: &Node,
+        new_parent: &Node,
+        child: Option<&Node>,
+        can_gc: CanGc,
+    ) -> ErrorResult {
+        // Step 1. If newParent’s shadow-including root is not the same as node’s shadow-including
+        // root, then throw a "HierarchyRequestError" DOMException.
+        let shadow_including_root = GetRootNodeOptions { composed: true };
+        if new_parent.GetRootNode(&shadow_including_root) !=
+            node.GetRootNode(&shadow_including_root)
+        {
+            return Err(Error::HierarchyRequest);
+        }
+
+        // Step 2. If node is a host-including inclusive ancestor of newParent, then throw a
+        // "HierarchyRequestError" DOMException.
+        if node.is_shadow_including_inclusive_ancestor_of(new_parent) {
+            return Err(Error::HierarchyRequest);
+        }
+
+        // Step 3. If child is non-null and its parent is not newParent, then throw a
+        // "NotFoundError" DOMException.
+        if child.is_some_and(|child| !new_parent.is_parent_of(child)) {
+            return Err(Error::NotFound);
+        }
+
+        // Step 4. If node is not an Element or a CharacterData node, then throw a
+        // "HierarchyRequestError" DOMException.
+        // Step 5. If node is a Text node and newParent is a document, then throw a
+        // "HierarchyRequestError" DOMException.
+        match node.type_id() {
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(_))
+                if new_parent.is::<Document>() =>
+            {
+                return Err(Error::HierarchyRequest);
+            },
+            NodeTypeId::Element(_) | NodeTypeId::CharacterData(_) => {},
+            _ => return Err(Error::HierarchyRequest),
+        }
+
+        // Step 6. If newParent is a document, node is an Element node, and either newParent has
+        // an element child, child is a doctype, or child is non-null and a doctype is following
+        // child then throw a "HierarchyRequestError" DOMException.
+        if new_parent.is::<Document>() &&
+            node.is::<Element>() &&
+            (new_parent.child_elements().next().is_some() ||
+                child.is_some_and(|child| {
+                    child
+                        .inclusively_following_siblings()
+                        .any(|sibling| sibling.is_doctype())
+                }))
+        {
+            return Err(Error::HierarchyRequest);
+        }
+
+        // Step 7. Let oldParent be node’s parent.
+        // Step 8. Assert: oldParent is non-null.
+        let old_parent = node
+            .GetParentNode()
+            .expect("Nodes without a parent can't be moved");
+
+        let document = new_parent.owner_doc();
+        document.add_script_and_layout_blocker();
+
+        // Step 9. Run the live range pre-remove steps, given node.
+        let cached_index = if old_parent.ranges_is_empty() {
+            None
+        } else {
+            let index = node.index();
+            old_parent.ranges().decrease_above(&old_parent, index, 1);
+            Some(index)
+        };
+
+        // TODO: Step 10. For each NodeIterator object iterator whose root’s node document is
+        // node’s node document, run the NodeIterator pre-remove steps given node and iterator.
+
+        // Step 11. Let oldPreviousSibling be node’s previous sibling.
+        let old_previous_sibling = node.GetPreviousSibling();
+
+        // Step 12. Let oldNextSibling be node’s next sibling.
+        let old_next_sibling = node.GetNextSibling();
+
+        // Step 13. Remove node from oldParent’s children.
+        old_parent.remove_child(node, cached_index, true, can_gc);
+
+        // Step 14. If node is assigned, then run assign slottables for node’s assigned slot.
+        if let Some(slot) = node.assigned_slot() {
+            slot.assign_slottables();
+        }
+
+        // Step 15. If oldParent’s root is a shadow root, and oldParent is a slot whose assigned
+        // nodes is empty, then run signal a slot change for oldParent.
+        if old_parent.is_in_a_shadow_tree() {
+            if let Some(slot_element) = old_parent.downcast::<HTMLSlotElement>() {
+                if !slot_element.has_assigned_nodes() {
+                    slot_element.signal_a_slot_change();
+                }
+            }
+        }
+
+        // Step 16. If node has an inclusive descendant that is a slot:
+        if node
+            .traverse_preorder(ShadowIncluding::No)
+            .any(|descendant| descendant.is::<HTMLSlotElement>())
+        {
+            // Step 16.1. Run assign slottables for a tree with oldParent’s root.
+            old_parent
+                .GetRootNode(&GetRootNodeOptions::empty())
+                .assign_slottables_for_a_tree();
+
+            // Step 16.2. Run assign slottables for a tree with node.
+            node.assign_slottables_for_a_tree();
+        }
+
+        // Step 17. If child is non-null:
+        if let Some(child) = child {
+            // Steps 17.1-2. Increase the offsets of the live ranges after child by 1.
+            if !new_parent.ranges_is_empty() {
+                new_parent
+                    .ranges()
+                    .increase_above(new_parent, child.index(), 1);
+            }
+        }
+
+        // Step 18. Let newPreviousSibling be child’s previous sibling if child is non-null, and
+        // newParent’s last child otherwise.
+        let new_previous_sibling = match child {
+            Some(child) => child.GetPreviousSibling(),
+            None => new_parent.GetLastChild(),
+        };
+
+        // Step 19. If child is null, then append node to newParent’s children. Otherwise, insert
+        // node into newParent’s children before child’s index.
+        new_parent.add_child(node, child, true, can_gc);
+        if let Some(element) = node.downcast::<Element>() {
+            element.restyle_after_move();
+        }
+
+        // Step 20. If newParent is a shadow host whose shadow root’s slot assignment is "named"
+        // and node is a slottable, then assign a slot for node.
+        if let Some(shadow_root) = new_parent
+            .downcast::<Element>()
+            .and_then(Element::shadow_root)
+        {
+            if shadow_root.SlotAssignment() == SlotAssignmentMode::Named &&
+                (node.is::<Element>() || node.is::<Text>())
+            {
+                let cx = GlobalScope::get_cx();
+                rooted!(in(*cx) let slottable = Slottable(Dom::from_ref(node)));
+                slottable.assign_a_slot();
+            }
+        }
+
+        // Step 21. If newParent’s root is a shadow root, and newParent is a slot whose assigned
+        // nodes is empty, then run signal a slot change for newParent.
+        if new_parent.is_in_a_shadow_tree() {
+            if let Some(slot_element) = new_parent.downcast::<HTMLSlotElement>() {
+                if !slot_element.has_assigned_nodes() {
+                    slot_element.signal_a_slot_change();
+                }
+            }
+        }
+
+        // Step 22. Run assign slottables for a tree with node’s root.
+        node.GetRootNode(&GetRootNodeOptions::empty())
+            .assign_slottables_for_a_tree();
+
+        // Step 23. For each shadow-including inclusive descendant inclusiveDescendant of node, in
+        // shadow-including tree order:
+        for descendant in node
+            .traverse_preorder(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            // Step 23.1. The moving steps were run by the removing and insertion steps above.
+            // Step 23.2. If inclusiveDescendant is custom and newParent is connected, then
+            // enqueue a custom element callback reaction with inclusiveDescendant, callback
+            // name "connectedMoveCallback", and « ».
+            if descendant.is_custom() && new_parent.is_connected() {
+                ScriptThread::enqueue_callback_reaction(
+                    &descendant,
+                    CallbackReaction::ConnectedMove,
+                    None,
+                );
+            }
+        }
+
+        vtable_for(&old_parent).children_changed(&ChildrenMutation::replace(
+            old_previous_sibling.as_deref(),
+            &Some(node),
+            &[],
+            old_next_sibling.as_deref(),
+        ));
+        vtable_for(new_parent).children_changed(&ChildrenMutation::insert(
+            new_previous_sibling.as_deref(),
+            &[node],
+            child,
+        ));
+
+        // Step 24. Queue a tree mutation record for oldParent with « », « node »,
+        // oldPreviousSibling, and oldNextSibling.
+        let removed = [node];
+        let mutation = LazyCell::new(|| Mutation::ChildList {
+            added: None,
+            removed: Some(&removed),
+            prev: old_previous_sibling.as_deref(),
+            next: old_next_sibling.as_deref(),
+        });
+        MutationObserver::queue_a_mutation_record(&old_parent, mutation);
+
+        // Step 25. Queue a tree mutation record for newParent with « node », « »,
+        // newPreviousSibling, and child.
+        let added = [node];
+        let mutation = LazyCell::new(|| Mutation::ChildList {
+            added: Some(&added),
+            removed: None,
+            prev: new_previous_sibling.as_deref(),
+            next: child,
+        });
+        MutationObserver::queue_a_mutation_record(new_parent, mutation);
+
+        document.remove_script_and_layout_blocker();
+        Ok(())
+    }
+
     /// <https://dom.spec.whatwg.org/#concept-node-replace-all>
     pub(crate) fn replace_all(node: Option<&Node>, parent: &Node, can_gc: CanGc) {
         parent.owner_doc().add_script_and_layout_blocker();
@@ -2525,7 +2842,7 @@ impl Node {
 
         // Step 7. Remove node from its parent's children.
         // Step 11-14. Run removing steps and enqueue disconnected custom element reactions for the subtree.
-        parent.remove_child(node, cached_index, can_gc);
+        parent.remove_child(node, cached_index, false, can_gc);
 
         // Step 8. If node is assigned, then run assign slottables for node’s assigned slot.
         if let Some(slot) = node.assigned_slot() {
@@ -3935,6 +4252,12 @@ pub(crate) struct BindContext {
 
     /// Whether the tree's root is a shadow root
     pub(crate) tree_is_in_a_shadow_tree: bool,
+
+    /// Whether the node is inserted again after being moved within its shadow-including root,
+    /// in which case it keeps its state, such as the nested browsing context of an iframe.
+    ///
+    /// <https://dom.spec.whatwg.org/#concept-node-move-ext>
+    pub(crate) is_move: bool,
 }
 
 impl BindContext {
@@ -3952,7 +4275,7 @@ pub(crate) struct UnbindContext<'a> {
     /// The parent of the inclusive ancestor that was removed.
     pub(crate) parent: &'a Node,
     /// The previous sibling of the inclusive ancestor that was removed.
-    prev_sibling: Option<&'a Node>,
+    pub(crate) prev_sibling: Option<&'a Node>,
     /// The next sibling of the inclusive ancestor that was removed.
     pub(crate) next_sibling: Option<&'a Node>,
 
@@ -3968,6 +4291,12 @@ pub(crate) struct UnbindContext<'a> {
 
     /// Whether the tree's root is a shadow root
     pub(crate) tree_is_in_a_shadow_tree: bool,
+
+    /// Whether the node is removed to be moved within its shadow-including root, in which
+    /// case it keeps its state, such as the nested browsing context of an iframe.
+    ///
+    /// <https://dom.spec.whatwg.org/#concept-node-move-ext>
+    pub(crate) is_move: bool,
 }
 
 impl<'a> UnbindContext<'a> {
@@ -3986,6 +4315,7 @@ impl<'a> UnbindContext<'a> {
             tree_connected: parent.is_connected(),
             tree_is_in_a_document_tree: parent.is_in_a_document_tree(),
             tree_is_in_a_shadow_tree: parent.is_in_a_shadow_tree(),
+            is_move: false,
         }
     }
 
@@ -452,7 +452,12 @@ impl PerformanceMethods<crate::DomTypeHolder> for Performance {
 
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/HighResolutionTime/Overview.html#dom-performance-now
     fn Now(&self) -> DOMHighResTimeStamp {
-        self.to_dom_high_res_time_stamp(CrossProcessInstant::now())
+        // When a test controls the clock of the timers, the time only advances with it.
+        let now = self
+            .global()
+            .controlled_timer_clock_time()
+            .unwrap_or_else(CrossProcessInstant::now);
+        self.to_dom_high_res_time_stamp(now)
     }
 
     // https://www.w3.org/TR/hr-time-2/#dom-performance-timeorigin
@@ -3,8 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::f64::consts::PI;
 
 use dom_struct::dom_struct;
+use embedder_traits::PenState;
 use euclid::default::Point2D;
 use js::rust::HandleObject;
 
@@ -13,16 +15,87 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PointerEventBinding::{
     PointerEventInit, PointerEventMethods,
 };
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
+/// The `pointerId` of the mouse, which is always an active pointer.
+/// <https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid>
+pub(crate) const MOUSE_POINTER_ID: i32 = 1;
+
+/// The `pointerId` of a pen, whose input is delivered along with the mouse input of the
+/// platform.
+pub(crate) const PEN_POINTER_ID: i32 = 2;
+
+/// The state of the mouse, or of a pen, for one of its input events, from which the pointer
+/// events for that input are created.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PointerInput {
+    /// The state of the pen, or `None` when the input comes from the mouse.
+    pub(crate) pen: Option<PenState>,
+    /// The position of the pointer in the viewport.
+    pub(crate) client_point: Point2D<f32>,
+    /// The button whose state changed with this input, or -1 if none did.
+    pub(crate) button: i16,
+    /// The buttons that are pressed after this input, as a bitmask.
+    pub(crate) buttons: u16,
+}
+
+impl PointerInput {
+    pub(crate) fn pointer_id(&self) -> i32 {
+        match self.pen {
+            Some(_) => PEN_POINTER_ID,
+            None => MOUSE_POINTER_ID,
+        }
+    }
+}
+
+/// Convert the tilt of a pen to its altitude and azimuth angles, in radians.
+/// <https://w3c.github.io/pointerevents/#converting-between-tiltx-tilty-and-altitudeangle-azimuthangle>
+fn tilt_to_spherical(tilt_x: i32, tilt_y: i32) -> (f64, f64) {
+    let tilt_x_radians = (tilt_x as f64).to_radians();
+    let tilt_y_radians = (tilt_y as f64).to_radians();
+    let is_flat = tilt_x.abs() == 90 || tilt_y.abs() == 90;
+
+    let azimuth_angle = if tilt_x == 0 {
+        match tilt_y.signum() {
+            1 => PI / 2.0,
+            -1 => 3.0 * PI / 2.0,
+            _ => 0.0,
+        }
+    } else if tilt_y == 0 {
+        if tilt_x < 0 { PI } else { 0.0 }
+    } else if is_flat {
+        0.0
+    } else {
+        let azimuth_angle = tilt_y_radians.tan().atan2(tilt_x_radians.tan());
+        if azimuth_angle < 0.0 {
+            azimuth_angle + 2.0 * PI
+        } else {
+            azimuth_angle
+        }
+    };
+
+    let altitude_angle = if is_flat {
+        0.0
+    } else if tilt_x == 0 {
+        PI / 2.0 - tilt_y_radians.abs()
+    } else if tilt_y == 0 {
+        PI / 2.0 - tilt_x_radians.abs()
+    } else {
+        (1.0 / (tilt_x_radians.tan().powi(2) + tilt_y_radians.tan().powi(2)).sqrt()).atan()
+    };
+
+    (altitude_angle, azimuth_angle)
+}
+
 #[dom_struct]
 pub(crate) struct PointerEvent {
     mouseevent: MouseEvent,
@@ -228,6 +301,89 @@ impl PointerEvent {
         *ev.predicted_events.borrow_mut() = predicted_events;
         ev
     }
+
+    /// Create a trusted pointer event of the given type for an input of the mouse or of a pen.
+    /// Whether it bubbles, can be canceled and is composed follows the type of the event.
+    /// <https://w3c.github.io/pointerevents/#pointer-event-types>
+    pub(crate) fn for_pointer_input(
+        window: &Window,
+        type_: &str,
+        input: &PointerInput,
+        related_target: Option<&EventTarget>,
+        coalesced_events: Vec<DomRoot<PointerEvent>>,
+        can_gc: CanGc,
+    ) -> DomRoot<PointerEvent> {
+        let (can_bubble, cancelable, composed) = match type_ {
+            "pointerenter" | "pointerleave" => (
+                EventBubbles::DoesNotBubble,
+                EventCancelable::NotCancelable,
+                false,
+            ),
+            "pointerrawupdate" | "pointercancel" | "gotpointercapture" | "lostpointercapture" => {
+                (EventBubbles::Bubbles, EventCancelable::NotCancelable, true)
+            },
+            _ => (EventBubbles::Bubbles, EventCancelable::Cancelable, true),
+        };
+
+        let (pointer_type, pen) = match input.pen {
+            Some(pen) => ("pen", pen),
+            // The mouse has no pressure of its own, so it is 0.5 while a button is pressed.
+            // <https://w3c.github.io/pointerevents/#dom-pointerevent-pressure>
+            None => (
+                "mouse",
+                PenState {
+                    pressure: if input.buttons != 0 { 0.5 } else { 0.0 },
+                    ..Default::default()
+                },
+            ),
+        };
+        let (altitude_angle, azimuth_angle) = tilt_to_spherical(pen.tilt_x, pen.tilt_y);
+
+        // TODO: Get real screen coordinates?
+        let client_x = input.client_point.x as i32;
+        let client_y = input.client_point.y as i32;
+        let pointer_event = PointerEvent::new(
+            window,
+            None,
+            DOMString::from(type_),
+            can_bubble,
+            cancelable,
+            Some(window),
+            0,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            input.button,
+            input.buttons,
+            related_target,
+            None,
+            input.pointer_id(),
+            1,
+            1,
+            pen.pressure,
+            pen.tangential_pressure,
+            pen.tilt_x,
+            pen.tilt_y,
+            pen.twist,
+            altitude_angle,
+            azimuth_angle,
+            DOMString::from(pointer_type),
+            true,
+            coalesced_events,
+            vec![],
+            can_gc,
+        );
+
+        let event = pointer_event.upcast::<Event>();
+        event.set_trusted(true);
+        event.set_composed(composed);
+        pointer_event
+    }
 }
 
 impl PointerEventMethods<crate::DomTypeHolder> for PointerEvent {
@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::{HandleObject, HandleValue, MutableHandleValue};
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PortalActivateEventBinding;
+use crate::dom::bindings::codegen::Bindings::PortalActivateEventBinding::PortalActivateEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::Event;
+use crate::dom::window::Window;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// The event that is fired at the window of a document hosted by a `<portal>` element when the
+/// document is activated, with the data given to `activate()`.
+///
+/// <https://wicg.github.io/portals/#the-portalactivateevent-interface>
+#[dom_struct]
+pub(crate) struct PortalActivateEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    data: Heap<JSVal>,
+}
+
+impl PortalActivateEvent {
+    fn new_inherited() -> PortalActivateEvent {
+        PortalActivateEvent {
+            event: Event::new_inherited(),
+            data: Heap::default(),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        data: HandleValue,
+        can_gc: CanGc,
+    ) -> DomRoot<PortalActivateEvent> {
+        let event = reflect_dom_object_with_proto(
+            Box::new(PortalActivateEvent::new_inherited()),
+            window,
+            proto,
+            can_gc,
+        );
+        event.data.set(data.get());
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+
+    /// Fire a `portalactivate` event with `data` at `window`.
+    pub(crate) fn dispatch_jsval(window: &Window, data: HandleValue, can_gc: CanGc) {
+        let event = PortalActivateEvent::new(
+            window,
+            None,
+            Atom::from("portalactivate"),
+            false,
+            false,
+            data,
+            can_gc,
+        );
+        event.upcast::<Event>().fire(window.upcast(), can_gc);
+    }
+}
+
+impl PortalActivateEventMethods<crate::DomTypeHolder> for PortalActivateEvent {
+    /// <https://wicg.github.io/portals/#dom-portalactivateevent-portalactivateevent>
+    fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        type_: DOMString,
+        init: RootedTraceableBox<PortalActivateEventBinding::PortalActivateEventInit>,
+    ) -> Fallible<DomRoot<PortalActivateEvent>> {
+        Ok(PortalActivateEvent::new(
+            window,
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            init.data.handle(),
+            can_gc,
+        ))
+    }
+
+    /// <https://wicg.github.io/portals/#dom-portalactivateevent-data>
+    fn Data(&self, _cx: JSContext, mut retval: MutableHandleValue) {
+        retval.set(self.data.get())
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-event-istrusted>
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
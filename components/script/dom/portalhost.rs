@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleValue;
+use script_traits::ScriptMsg;
+
+use crate::dom::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
+use crate::dom::bindings::codegen::Bindings::PortalHostBinding::PortalHostMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlportalelement::serialize_with_transfer;
+use crate::dom::window::Window;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// The object that the document hosted by a `<portal>` element uses to exchange messages with
+/// the document that hosts it.
+///
+/// <https://wicg.github.io/portals/#the-portalhost-interface>
+#[dom_struct]
+pub(crate) struct PortalHost {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+}
+
+impl PortalHost {
+    fn new_inherited(window: &Window) -> PortalHost {
+        PortalHost {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<PortalHost> {
+        reflect_dom_object(Box::new(PortalHost::new_inherited(window)), window, can_gc)
+    }
+}
+
+impl PortalHostMethods<crate::DomTypeHolder> for PortalHost {
+    /// <https://wicg.github.io/portals/#dom-portalhost-postmessage>
+    fn PostMessage(
+        &self,
+        cx: JSContext,
+        message: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> ErrorResult {
+        // Step 2. If browsingContext is not a portal browsing context, throw an
+        // "InvalidStateError" DOMException.
+        if !self.window.is_portal() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3. Let origin be the serialization of settings's origin.
+        let source_origin = self.window.Document().origin().immutable().clone();
+
+        // Step 5. Let serializeWithTransferResult be StructuredSerializeWithTransfer(message,
+        // options's transfer member). Rethrow any exceptions.
+        let data = serialize_with_transfer(cx, message, &options)?;
+
+        // Step 6. Queue a global task to fire the message at the host element.
+        self.window
+            .send_to_constellation(ScriptMsg::PostMessageToPortalHost(source_origin, data));
+        Ok(())
+    }
+
+    // https://wicg.github.io/portals/#dom-portalhost-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://wicg.github.io/portals/#dom-portalhost-onmessageerror
+    event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+}
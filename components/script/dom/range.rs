@@ -4,13 +4,16 @@
 
 use std::cell::UnsafeCell;
 use std::cmp::{Ordering, PartialOrd};
-use std::iter;
 
+use app_units::Au;
 use dom_struct::dom_struct;
+use euclid::default::Rect;
 use js::jsapi::JSTracer;
 use js::rust::HandleObject;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use script_layout_interface::RangeNode;
 
+use crate::caret_browsing::{text_of, utf16_offset_to_utf8};
 use crate::dom::abstractrange::{AbstractRange, BoundaryPoint, bp_position};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AbstractRangeBinding::AbstractRangeMethods;
@@ -137,7 +140,7 @@ impl Range {
     }
 
     /// <https://dom.spec.whatwg.org/#contained>
-    fn contains(&self, node: &Node) -> bool {
+    pub(crate) fn contains(&self, node: &Node) -> bool {
         matches!(
             (
                 bp_position(node, 0, &self.start_container(), self.start_offset()),
@@ -148,7 +151,7 @@ impl Range {
     }
 
     /// <https://dom.spec.whatwg.org/#partially-contained>
-    fn partially_contains(&self, node: &Node) -> bool {
+    pub(crate) fn partially_contains(&self, node: &Node) -> bool {
         self.start_container()
             .inclusive_ancestors(ShadowIncluding::No)
             .any(|n| &*n == node) !=
@@ -328,21 +331,67 @@ impl Range {
         self.abstract_range().Collapsed()
     }
 
-    fn client_rects(
-        &self,
-        can_gc: CanGc,
-    ) -> impl Iterator<Item = euclid::Rect<app_units::Au, euclid::UnknownUnit>> {
-        // FIXME: For text nodes that are only partially selected, this should return the client
-        // rect of the selected part, not the whole text node.
+    /// The client rects of the nodes that this range selects, in tree order. These are the
+    /// border boxes of the elements that it contains and whose parent it does not contain, and
+    /// the selected part of each line of the text nodes that it contains or partially contains.
+    ///
+    /// A range never crosses a shadow boundary, so the text of the shadow trees of the elements
+    /// that it contains is covered by the border boxes of their hosts.
+    fn client_rects(&self, can_gc: CanGc) -> Vec<Rect<Au>> {
         let start = self.start_container();
         let end = self.end_container();
-        let document = start.owner_doc();
-        let end_clone = end.clone();
+        let selected_text = |node: &Node, start_offset: u32, end_offset: u32| {
+            let text = text_of(node)?;
+            Some(RangeNode {
+                node: node.to_opaque(),
+                text_range: Some(
+                    utf16_offset_to_utf8(&text, start_offset)..
+                        utf16_offset_to_utf8(&text, end_offset),
+                ),
+            })
+        };
+
+        let mut nodes = Vec::new();
+        let start_end_offset = if start == end {
+            self.end_offset()
+        } else {
+            start.len()
+        };
+        nodes.extend(selected_text(&start, self.start_offset(), start_end_offset));
+
+        // The nodes that the range contains follow its start container in tree order, until the
+        // first node that starts after the end of the range.
+        let root = start
+            .inclusive_ancestors(ShadowIncluding::No)
+            .last()
+            .expect("A node always has an inclusive ancestor");
+        for node in start.following_nodes(&root).take_while(|node| {
+            bp_position(node, 0, &end, self.end_offset()) == Some(Ordering::Less)
+        }) {
+            if !self.contains(&node) {
+                continue;
+            }
+            if node.is::<Element>() &&
+                !node
+                    .GetParentNode()
+                    .is_some_and(|parent| self.contains(&parent))
+            {
+                nodes.push(RangeNode {
+                    node: node.to_opaque(),
+                    text_range: None,
+                });
+            } else {
+                nodes.extend(selected_text(&node, 0, node.len()));
+            }
+        }
+
+        if start != end {
+            nodes.extend(selected_text(&end, 0, self.end_offset()));
+        }
+
         start
-            .following_nodes(document.upcast::<Node>())
-            .take_while(move |node| node != &end)
-            .chain(iter::once(end_clone))
-            .flat_map(move |node| node.content_boxes(can_gc))
+            .owner_window()
+            .range_client_rects_query(&nodes, can_gc)
     }
 }
 
@@ -1133,6 +1182,7 @@ impl RangeMethods<crate::DomTypeHolder> for Range {
 
         let client_rects = self
             .client_rects(can_gc)
+            .into_iter()
             .map(|rect| {
                 DOMRect::new(
                     window.upcast(),
@@ -1159,7 +1209,14 @@ impl RangeMethods<crate::DomTypeHolder> for Range {
         // Step 3. If all rectangles in list have zero width or height, return the first rectangle in list.
         // Step 4. Otherwise, return a DOMRect object describing the smallest rectangle that includes all
         // of the rectangles in list of which the height or width is not zero.
-        let bounding_rect = list.fold(euclid::Rect::zero(), |acc, rect| acc.union(&rect));
+        let bounding_rect = list
+            .iter()
+            .filter(|rect| !rect.is_empty())
+            .fold(None, |bounding_rect: Option<Rect<Au>>, rect| {
+                Some(bounding_rect.map_or(*rect, |bounding_rect| bounding_rect.union(rect)))
+            })
+            .or_else(|| list.first().copied())
+            .unwrap_or_else(Rect::zero);
 
         DOMRect::new(
             window.upcast(),
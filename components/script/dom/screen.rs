@@ -3,12 +3,14 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
+use embedder_traits::ScreenInfo;
 use euclid::Size2D;
 use profile_traits::ipc;
 use servo_geometry::DeviceIndependentIntSize;
 use style_traits::CSSPixel;
 use webrender_traits::CrossProcessCompositorMessage;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ScreenBinding::ScreenMethods;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
@@ -20,21 +22,44 @@ use crate::script_runtime::CanGc;
 pub(crate) struct Screen {
     reflector_: Reflector,
     window: Dom<Window>,
+    /// When this [`Screen`] is the base of a `ScreenDetailed`, the details of the particular
+    /// screen it describes. Otherwise, this describes the screen the window is currently on.
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    info: DomRefCell<Option<ScreenInfo>>,
 }
 
 impl Screen {
-    fn new_inherited(window: &Window) -> Screen {
+    pub(crate) fn new_inherited(window: &Window, info: Option<ScreenInfo>) -> Screen {
         Screen {
             reflector_: Reflector::new(),
             window: Dom::from_ref(window),
+            info: DomRefCell::new(info),
         }
     }
 
     pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<Screen> {
-        reflect_dom_object(Box::new(Screen::new_inherited(window)), window, can_gc)
+        reflect_dom_object(
+            Box::new(Screen::new_inherited(window, None)),
+            window,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn info(&self) -> Option<ScreenInfo> {
+        self.info.borrow().clone()
+    }
+
+    pub(crate) fn set_info(&self, info: ScreenInfo) {
+        *self.info.borrow_mut() = Some(info);
     }
 
     fn screen_size(&self) -> Size2D<u32, CSSPixel> {
+        if let Some(info) = self.info.borrow().as_ref() {
+            let size = info.rect.size().to_u32();
+            return Size2D::new(size.width, size.height);
+        }
+
         let (send, recv) =
             ipc::channel::<DeviceIndependentIntSize>(self.global().time_profiler_chan().clone())
                 .unwrap();
@@ -48,6 +73,11 @@ impl Screen {
     }
 
     fn screen_avail_size(&self) -> Size2D<u32, CSSPixel> {
+        if let Some(info) = self.info.borrow().as_ref() {
+            let size = info.available_rect.size().to_u32();
+            return Size2D::new(size.width, size.height);
+        }
+
         let (send, recv) =
             ipc::channel::<DeviceIndependentIntSize>(self.global().time_profiler_chan().clone())
                 .unwrap();
@@ -59,6 +89,13 @@ impl Screen {
         let size = recv.recv().unwrap_or(Size2D::zero()).to_u32();
         Size2D::new(size.width, size.height)
     }
+
+    fn color_depth(&self) -> u32 {
+        self.info
+            .borrow()
+            .as_ref()
+            .map_or(24, |info| info.color_depth)
+    }
 }
 
 impl ScreenMethods<crate::DomTypeHolder> for Screen {
@@ -84,11 +121,16 @@ impl ScreenMethods<crate::DomTypeHolder> for Screen {
 
     // https://drafts.csswg.org/cssom-view/#dom-screen-colordepth
     fn ColorDepth(&self) -> u32 {
-        24
+        self.color_depth()
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-screen-pixeldepth
     fn PixelDepth(&self) -> u32 {
-        24
+        self.color_depth()
+    }
+
+    // https://w3c.github.io/window-management/#dom-screen-isextended
+    fn IsExtended(&self) -> bool {
+        self.window.screen_topology().screens.len() > 1
     }
 }
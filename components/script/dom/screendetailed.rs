@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::{ScreenId, ScreenInfo};
+
+use crate::dom::bindings::codegen::Bindings::ScreenDetailsBinding::ScreenDetailedMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::screen::Screen;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/window-management/#screendetailed>
+#[dom_struct]
+pub(crate) struct ScreenDetailed {
+    screen: Screen,
+    #[no_trace]
+    id: ScreenId,
+}
+
+impl ScreenDetailed {
+    fn new_inherited(window: &Window, info: ScreenInfo) -> ScreenDetailed {
+        ScreenDetailed {
+            id: info.id,
+            screen: Screen::new_inherited(window, Some(info)),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, info: ScreenInfo, can_gc: CanGc) -> DomRoot<ScreenDetailed> {
+        reflect_dom_object(
+            Box::new(ScreenDetailed::new_inherited(window, info)),
+            window,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn id(&self) -> ScreenId {
+        self.id
+    }
+
+    /// Update the details of this screen.
+    pub(crate) fn update(&self, info: ScreenInfo) {
+        self.screen.set_info(info);
+    }
+
+    fn info(&self) -> ScreenInfo {
+        self.screen
+            .info()
+            .expect("ScreenDetailed should always have screen information")
+    }
+}
+
+impl ScreenDetailedMethods<crate::DomTypeHolder> for ScreenDetailed {
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-availleft>
+    fn AvailLeft(&self) -> i32 {
+        self.info().available_rect.min.x
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-availtop>
+    fn AvailTop(&self) -> i32 {
+        self.info().available_rect.min.y
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-left>
+    fn Left(&self) -> i32 {
+        self.info().rect.min.x
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-top>
+    fn Top(&self) -> i32 {
+        self.info().rect.min.y
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-isprimary>
+    fn IsPrimary(&self) -> bool {
+        self.info().is_primary
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-isinternal>
+    fn IsInternal(&self) -> bool {
+        self.info().is_internal
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-devicepixelratio>
+    fn DevicePixelRatio(&self) -> Finite<f32> {
+        Finite::wrap(self.info().device_pixel_ratio)
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetailed-label>
+    fn Label(&self) -> DOMString {
+        DOMString::from(self.info().label)
+    }
+}
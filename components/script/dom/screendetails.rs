@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::ScreenTopology;
+use js::rust::MutableHandleValue;
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ScreenDetailsBinding::ScreenDetailsMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::screendetailed::ScreenDetailed;
+use crate::dom::window::Window;
+use crate::script_runtime::{CanGc, JSContext};
+
+/// <https://w3c.github.io/window-management/#screendetails>
+#[dom_struct]
+pub(crate) struct ScreenDetails {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+    /// <https://w3c.github.io/window-management/#dom-screendetails-screens>
+    screens: DomRefCell<Vec<Dom<ScreenDetailed>>>,
+    /// The [`ScreenTopology`] that `screens` was last built from.
+    #[no_trace]
+    topology: DomRefCell<ScreenTopology>,
+}
+
+impl ScreenDetails {
+    fn new_inherited(window: &Window) -> ScreenDetails {
+        ScreenDetails {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+            screens: Default::default(),
+            topology: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        topology: ScreenTopology,
+        can_gc: CanGc,
+    ) -> DomRoot<ScreenDetails> {
+        let screen_details = reflect_dom_object(
+            Box::new(ScreenDetails::new_inherited(window)),
+            window,
+            can_gc,
+        );
+        screen_details.update(topology, can_gc);
+        screen_details
+    }
+
+    /// Update the set of screens from a new [`ScreenTopology`], reusing existing
+    /// [`ScreenDetailed`] objects for screens that are still connected. Returns whether the set
+    /// of screens changed and whether the current screen changed, in that order.
+    fn update(&self, topology: ScreenTopology, can_gc: CanGc) -> (bool, bool) {
+        let previous_screens: Vec<DomRoot<ScreenDetailed>> = self
+            .screens
+            .borrow()
+            .iter()
+            .map(|screen| DomRoot::from_ref(&**screen))
+            .collect();
+        let changes = topology.changes_since(&self.topology.borrow());
+        let screens: Vec<DomRoot<ScreenDetailed>> = topology
+            .screens
+            .iter()
+            .map(|info| {
+                match previous_screens
+                    .iter()
+                    .find(|screen| screen.id() == info.id)
+                {
+                    Some(screen) => {
+                        screen.update(info.clone());
+                        screen.clone()
+                    },
+                    None => ScreenDetailed::new(&self.window, info.clone(), can_gc),
+                }
+            })
+            .collect();
+
+        *self.screens.borrow_mut() = screens
+            .iter()
+            .map(|screen| Dom::from_ref(&**screen))
+            .collect();
+        *self.topology.borrow_mut() = topology;
+        (changes.screens_changed, changes.current_screen_changed)
+    }
+
+    /// Handle a change to the screens connected to the system, firing `screenschange` and
+    /// `currentscreenchange` events as appropriate.
+    pub(crate) fn handle_screens_changed(&self, topology: ScreenTopology, can_gc: CanGc) {
+        let (screens_changed, current_screen_changed) = self.update(topology, can_gc);
+        if screens_changed {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("screenschange"), can_gc);
+        }
+        if current_screen_changed {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("currentscreenchange"), can_gc);
+        }
+    }
+}
+
+impl ScreenDetailsMethods<crate::DomTypeHolder> for ScreenDetails {
+    /// <https://w3c.github.io/window-management/#dom-screendetails-screens>
+    fn Screens(&self, cx: JSContext, can_gc: CanGc, retval: MutableHandleValue) {
+        let screens: Vec<DomRoot<ScreenDetailed>> = self
+            .screens
+            .borrow()
+            .iter()
+            .map(|screen| DomRoot::from_ref(&**screen))
+            .collect();
+        to_frozen_array(screens.as_slice(), cx, retval, can_gc)
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-screendetails-currentscreen>
+    fn CurrentScreen(&self) -> DomRoot<ScreenDetailed> {
+        let screens = self.screens.borrow();
+        let current_screen = self.topology.borrow().current_screen;
+        screens
+            .iter()
+            .find(|screen| Some(screen.id()) == current_screen)
+            .or_else(|| screens.first())
+            .map(|screen| DomRoot::from_ref(&**screen))
+            .expect("ScreenDetails should always describe at least one screen")
+    }
+
+    // https://w3c.github.io/window-management/#dom-screendetails-onscreenschange
+    event_handler!(screenschange, GetOnscreenschange, SetOnscreenschange);
+
+    // https://w3c.github.io/window-management/#dom-screendetails-oncurrentscreenchange
+    event_handler!(
+        currentscreenchange,
+        GetOncurrentscreenchange,
+        SetOncurrentscreenchange
+    );
+}
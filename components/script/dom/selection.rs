@@ -5,6 +5,7 @@
 use std::cell::Cell;
 
 use dom_struct::dom_struct;
+use servo_config::pref;
 
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
@@ -81,6 +82,10 @@ impl Selection {
     }
 
     pub(crate) fn queue_selectionchange_task(&self) {
+        // In caret browsing mode, layout paints the caret and the selected text.
+        if pref!(accessibility_caret_browsing_enabled) {
+            self.document.set_needs_paint(true);
+        }
         if self.task_queued.get() {
             // Spec doesn't specify not to queue multiple tasks,
             // but it's much easier to code range operations if
@@ -15,6 +15,7 @@ use crate::dom::bindings::codegen::Bindings::ServoInternalsBinding::ServoInterna
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::realms::{AlreadyInRealm, InRealm};
@@ -53,6 +54,14 @@ impl ServoInternalsMethods<crate::DomTypeHolder> for ServoInternals {
         }
         promise
     }
+
+    /// <https://servo.org/internal-no-spec>
+    fn SetPreference(&self, name: DOMString, value: DOMString) {
+        let _ = self
+            .global()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::SetPreference(name.into(), value.into()));
+    }
 }
 
 impl RoutedPromiseListener<MemoryReportResult> for ServoInternals {
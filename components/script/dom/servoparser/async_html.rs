@@ -267,7 +267,7 @@ impl Tokenizer {
         // Create new thread for HtmlTokenizer. This is where parser actions
         // will be generated from the input provided. These parser actions are then passed
         // onto the main thread to be executed.
-        let scripting_enabled = document.has_browsing_context();
+        let scripting_enabled = document.is_scripting_enabled();
         thread::Builder::new()
             .name(format!("Parse:{}", tokenizer.url.debug_compact()))
             .spawn(move || {
@@ -59,7 +59,7 @@ impl Tokenizer {
 
         let options = TreeBuilderOpts {
             ignore_missing_rules: true,
-            scripting_enabled: document.has_browsing_context(),
+            scripting_enabled: document.is_scripting_enabled(),
             ..Default::default()
         };
 
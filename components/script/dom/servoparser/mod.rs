@@ -24,7 +24,7 @@ use mime::{self, Mime};
 use net_traits::request::RequestId;
 use net_traits::{
     FetchMetadata, FetchResponseListener, Metadata, NetworkError, ResourceFetchTiming,
-    ResourceTimingType,
+    ResourceTimingType, is_text_document_mime_type,
 };
 use profile_traits::time::{
     ProfilerCategory, ProfilerChan, TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType,
@@ -923,7 +923,7 @@ impl FetchResponseListener for ParserContext {
                     .AppendChild(&DomRoot::upcast::<Node>(img))
                     .expect("Appending failed");
             },
-            (mime::TEXT, mime::PLAIN, _) => {
+            _ if is_text_document_mime_type(content_type) => {
                 // https://html.spec.whatwg.org/multipage/#read-text
                 let page = "<pre>\n".into();
                 parser.push_string_input_chunk(page);
@@ -61,6 +61,7 @@ impl Tokenizer {
     pub(crate) fn new(document: &Document) -> Self {
         let sink = PrefetchSink {
             origin: document.origin().immutable().clone(),
+            top_level_origin: document.window().as_global_scope().top_level_origin(),
             pipeline_id: document.global().pipeline_id(),
             webview_id: document.webview_id(),
             base_url: RefCell::new(None),
@@ -89,6 +90,8 @@ struct PrefetchSink {
     #[no_trace]
     origin: ImmutableOrigin,
     #[no_trace]
+    top_level_origin: ImmutableOrigin,
+    #[no_trace]
     pipeline_id: PipelineId,
     #[no_trace]
     webview_id: WebViewId,
@@ -146,7 +149,8 @@ impl TokenSink for PrefetchSink {
                             parser_metadata: ParserMetadata::ParserInserted,
                         },
                         self.insecure_requests_policy,
-                    );
+                    )
+                    .top_level_origin(Some(self.top_level_origin.clone()));
                     let _ = self
                         .resource_threads
                         .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
@@ -166,6 +170,7 @@ impl TokenSink for PrefetchSink {
                         self.insecure_requests_policy,
                     )
                     .origin(self.origin.clone())
+                    .top_level_origin(Some(self.top_level_origin.clone()))
                     .pipeline_id(Some(self.pipeline_id))
                     .referrer_policy(self.get_referrer_policy(tag, local_name!("referrerpolicy")));
 
@@ -200,6 +205,7 @@ impl TokenSink for PrefetchSink {
                                 self.insecure_requests_policy,
                             )
                             .origin(self.origin.clone())
+                            .top_level_origin(Some(self.top_level_origin.clone()))
                             .pipeline_id(Some(self.pipeline_id))
                             .referrer_policy(referrer_policy)
                             .integrity_metadata(integrity_metadata);
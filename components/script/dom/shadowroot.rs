@@ -8,6 +8,7 @@ use std::collections::hash_map::Entry;
 
 use dom_struct::dom_struct;
 use html5ever::serialize::TraversalScope;
+use js::rust::{HandleValue, MutableHandleValue};
 use servo_arc::Arc;
 use style::author_styles::AuthorStyles;
 use style::dom::TElement;
@@ -16,6 +17,7 @@ use style::stylesheets::Stylesheet;
 use style::stylist::{CascadeData, Stylist};
 use stylo_atoms::Atom;
 
+use crate::adopted_stylesheets::AdoptedStyleSheetArray;
 use crate::conversions::Convert;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::GetHTMLOptions;
@@ -24,6 +26,7 @@ use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRoot_Bindi
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{
     ShadowRootMode, SlotAssignmentMode,
 };
+use crate::dom::bindings::error::ErrorResult;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object;
@@ -32,7 +35,9 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::document::Document;
 use crate::dom::documentfragment::DocumentFragment;
-use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
+use crate::dom::documentorshadowroot::{
+    DocumentOrShadowRoot, StyleSheetInDocument, StylesheetSource,
+};
 use crate::dom::element::Element;
 use crate::dom::htmlslotelement::HTMLSlotElement;
 use crate::dom::node::{
@@ -43,7 +48,7 @@ use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
 use crate::dom::types::EventTarget;
 use crate::dom::virtualmethods::{VirtualMethods, vtable_for};
 use crate::dom::window::Window;
-use crate::script_runtime::CanGc;
+use crate::script_runtime::{CanGc, JSContext};
 use crate::stylesheet_set::StylesheetSetRef;
 
 /// Whether a shadow root hosts an User Agent widget.
@@ -64,6 +69,10 @@ pub(crate) struct ShadowRoot {
     #[custom_trace]
     author_styles: DomRefCell<AuthorStyles<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopted_stylesheets: DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    adopted_stylesheets_array: AdoptedStyleSheetArray,
     window: Dom<Window>,
 
     /// <https://dom.spec.whatwg.org/#dom-shadowroot-mode>
@@ -117,6 +126,8 @@ impl ShadowRoot {
             host: MutNullableDom::new(Some(host)),
             author_styles: DomRefCell::new(AuthorStyles::new()),
             stylesheet_list: MutNullableDom::new(None),
+            adopted_stylesheets: DomRefCell::new(Vec::new()),
+            adopted_stylesheets_array: AdoptedStyleSheetArray::new(),
             window: Dom::from_ref(document.window()),
             mode,
             slot_assignment_mode,
@@ -167,15 +178,22 @@ impl ShadowRoot {
     }
 
     pub(crate) fn stylesheet_count(&self) -> usize {
-        self.author_styles.borrow().stylesheets.len()
+        self.author_styles
+            .borrow()
+            .stylesheets
+            .iter()
+            .filter(|sheet| sheet.owner.is_element())
+            .count()
     }
 
     pub(crate) fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
         let stylesheets = &self.author_styles.borrow().stylesheets;
 
         stylesheets
-            .get(index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .iter()
+            .filter(|sheet| sheet.owner.is_element())
+            .nth(index)
+            .and_then(|sheet| sheet.owner.get_cssom_object())
     }
 
     /// Add a stylesheet owned by `owner` to the list of shadow root sheets, in the
@@ -185,14 +203,10 @@ impl ShadowRoot {
         let stylesheets = &mut self.author_styles.borrow_mut().stylesheets;
         let insertion_point = stylesheets
             .iter()
-            .find(|sheet_in_shadow| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_shadow.owner.upcast())
-            })
+            .find(|sheet_in_shadow| sheet_in_shadow.owner.is_after(owner))
             .cloned();
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            StylesheetSource::Element(Dom::from_ref(owner)),
             StylesheetSetRef::Author(stylesheets),
             sheet,
             insertion_point,
@@ -204,12 +218,41 @@ impl ShadowRoot {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))] // Owner needs to be rooted already necessarily.
     pub(crate) fn remove_stylesheet(&self, owner: &Element, s: &Arc<Stylesheet>) {
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            StylesheetSource::Element(Dom::from_ref(owner)),
             s,
             StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
         )
     }
 
+    /// Replace the adopted stylesheets of this shadow root, which come after the stylesheets
+    /// of its elements.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn set_adopted_stylesheets(&self, sheets: Vec<DomRoot<CSSStyleSheet>>) {
+        let (old_sheets, new_sheets) = DocumentOrShadowRoot::replace_adopted_stylesheets(
+            &self.adopted_stylesheets,
+            sheets,
+            &StyleSheetListOwner::ShadowRoot(Dom::from_ref(self)),
+        );
+
+        for sheet in old_sheets {
+            DocumentOrShadowRoot::remove_stylesheet(
+                StylesheetSource::Constructed(Dom::from_ref(&*sheet)),
+                sheet.style_stylesheet_arc(),
+                StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
+            );
+        }
+        for sheet in new_sheets {
+            DocumentOrShadowRoot::add_stylesheet(
+                StylesheetSource::Constructed(Dom::from_ref(&*sheet)),
+                StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
+                sheet.style_stylesheet_arc().clone(),
+                None,
+                self.document.style_shared_lock(),
+            );
+        }
+        self.invalidate_stylesheets();
+    }
+
     pub(crate) fn invalidate_stylesheets(&self) {
         self.document.invalidate_shadow_roots_stylesheets();
         self.author_styles.borrow_mut().stylesheets.force_dirty();
@@ -407,6 +450,30 @@ impl ShadowRootMethods<crate::DomTypeHolder> for ShadowRoot {
         })
     }
 
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    fn AdoptedStyleSheets(&self, cx: JSContext, _can_gc: CanGc, retval: MutableHandleValue) {
+        self.adopted_stylesheets_array.get_or_init(
+            self,
+            || {
+                self.adopted_stylesheets
+                    .borrow()
+                    .iter()
+                    .map(|sheet| DomRoot::from_ref(&**sheet))
+                    .collect()
+            },
+            cx,
+            retval,
+        );
+    }
+
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        let sheets = DocumentOrShadowRoot::adoptable_stylesheets(cx, value, &self.document)?;
+        self.adopted_stylesheets_array.replace(cx, &sheets);
+        self.set_adopted_stylesheets(sheets);
+        Ok(())
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-shadowroot-gethtml>
     fn GetHTML(&self, options: &GetHTMLOptions, can_gc: CanGc) -> DOMString {
         // > ShadowRoot's getHTML(options) method steps are to return the result of HTML fragment serialization
@@ -487,6 +554,7 @@ impl VirtualMethods for ShadowRoot {
                     tree_connected: context.tree_connected,
                     tree_is_in_a_document_tree: false,
                     tree_is_in_a_shadow_tree: true,
+                    is_move: context.is_move,
                 },
                 can_gc,
             );
@@ -5,7 +5,7 @@
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
 use net_traits::IpcSend;
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::storage_thread::{StorageKey, StorageThreadMsg, StorageType};
 use profile_traits::ipc;
 use script_traits::ScriptMsg;
 use servo_url::ServoUrl;
@@ -53,6 +53,13 @@ impl Storage {
         self.global().get_url()
     }
 
+    /// The key of the storage that this object accesses, which is partitioned by the site of the
+    /// top-level document.
+    fn storage_key(&self) -> StorageKey {
+        let global = self.global();
+        StorageKey::new(&global.top_level_origin(), global.origin().immutable())
+    }
+
     fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
         self.global().resource_threads().sender()
     }
@@ -66,7 +73,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         self.get_storage_thread()
             .send(StorageThreadMsg::Length(
                 sender,
-                self.get_url(),
+                self.storage_key(),
                 self.storage_type,
             ))
             .unwrap();
@@ -80,7 +87,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         self.get_storage_thread()
             .send(StorageThreadMsg::Key(
                 sender,
-                self.get_url(),
+                self.storage_key(),
                 self.storage_type,
                 index,
             ))
@@ -93,7 +100,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let name = String::from(name);
 
-        let msg = StorageThreadMsg::GetItem(sender, self.get_url(), self.storage_type, name);
+        let msg = StorageThreadMsg::GetItem(sender, self.storage_key(), self.storage_type, name);
         self.get_storage_thread().send(msg).unwrap();
         receiver.recv().unwrap().map(DOMString::from)
     }
@@ -106,7 +113,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
 
         let msg = StorageThreadMsg::SetItem(
             sender,
-            self.get_url(),
+            self.storage_key(),
             self.storage_type,
             name.clone(),
             value.clone(),
@@ -128,8 +135,12 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let name = String::from(name);
 
-        let msg =
-            StorageThreadMsg::RemoveItem(sender, self.get_url(), self.storage_type, name.clone());
+        let msg = StorageThreadMsg::RemoveItem(
+            sender,
+            self.storage_key(),
+            self.storage_type,
+            name.clone(),
+        );
         self.get_storage_thread().send(msg).unwrap();
         if let Some(old_value) = receiver.recv().unwrap() {
             self.broadcast_change_notification(Some(name), Some(old_value), None);
@@ -143,7 +154,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         self.get_storage_thread()
             .send(StorageThreadMsg::Clear(
                 sender,
-                self.get_url(),
+                self.storage_key(),
                 self.storage_type,
             ))
             .unwrap();
@@ -159,7 +170,7 @@ impl StorageMethods<crate::DomTypeHolder> for Storage {
         self.get_storage_thread()
             .send(StorageThreadMsg::Keys(
                 sender,
-                self.get_url(),
+                self.storage_key(),
                 self.storage_type,
             ))
             .unwrap();
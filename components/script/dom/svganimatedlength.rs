@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use html5ever::LocalName;
+
+use crate::dom::bindings::codegen::Bindings::SVGAnimatedLengthBinding::SVGAnimatedLengthMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::element::Element;
+use crate::dom::node::NodeTraits;
+use crate::dom::svglength::SVGLength;
+use crate::script_runtime::CanGc;
+
+/// <https://svgwg.org/svg2-draft/types.html#InterfaceSVGAnimatedLength>
+///
+/// Animations are not supported, so `animVal` always reflects the same value as `baseVal`.
+#[dom_struct]
+pub(crate) struct SVGAnimatedLength {
+    reflector_: Reflector,
+    element: Dom<Element>,
+    #[no_trace]
+    attribute: LocalName,
+    base_val: MutNullableDom<SVGLength>,
+    anim_val: MutNullableDom<SVGLength>,
+}
+
+impl SVGAnimatedLength {
+    fn new_inherited(element: &Element, attribute: LocalName) -> SVGAnimatedLength {
+        SVGAnimatedLength {
+            reflector_: Reflector::new(),
+            element: Dom::from_ref(element),
+            attribute,
+            base_val: Default::default(),
+            anim_val: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        element: &Element,
+        attribute: LocalName,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGAnimatedLength> {
+        reflect_dom_object(
+            Box::new(SVGAnimatedLength::new_inherited(element, attribute)),
+            &*element.owner_window(),
+            can_gc,
+        )
+    }
+}
+
+impl SVGAnimatedLengthMethods<crate::DomTypeHolder> for SVGAnimatedLength {
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGAnimatedLength__baseVal>
+    fn BaseVal(&self) -> DomRoot<SVGLength> {
+        self.base_val
+            .or_init(|| SVGLength::new(&self.element, self.attribute.clone(), false, CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGAnimatedLength__animVal>
+    fn AnimVal(&self) -> DomRoot<SVGLength> {
+        self.anim_val
+            .or_init(|| SVGLength::new(&self.element, self.attribute.clone(), true, CanGc::note()))
+    }
+}
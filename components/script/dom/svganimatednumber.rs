@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::str::FromStr;
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, namespace_url, ns};
+use svgtypes::Number;
+
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::SVGAnimatedNumberBinding::SVGAnimatedNumberMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::node::NodeTraits;
+use crate::script_runtime::CanGc;
+
+/// Parse the value of a number attribute, returning `None` if it is not a valid number.
+pub(crate) fn number_attribute(element: &Element, name: &LocalName) -> Option<f64> {
+    let value = element.get_attribute(&ns!(), name)?.Value();
+    Number::from_str(value.trim()).ok().map(|number| number.0)
+}
+
+/// <https://svgwg.org/svg2-draft/types.html#InterfaceSVGAnimatedNumber>
+///
+/// Animations are not supported, so `animVal` always reflects the same value as `baseVal`.
+#[dom_struct]
+pub(crate) struct SVGAnimatedNumber {
+    reflector_: Reflector,
+    element: Dom<Element>,
+    #[no_trace]
+    attribute: LocalName,
+    /// The value used when the attribute is missing or invalid.
+    initial_value: f64,
+}
+
+impl SVGAnimatedNumber {
+    fn new_inherited(
+        element: &Element,
+        attribute: LocalName,
+        initial_value: f64,
+    ) -> SVGAnimatedNumber {
+        SVGAnimatedNumber {
+            reflector_: Reflector::new(),
+            element: Dom::from_ref(element),
+            attribute,
+            initial_value,
+        }
+    }
+
+    pub(crate) fn new(
+        element: &Element,
+        attribute: LocalName,
+        initial_value: f64,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGAnimatedNumber> {
+        reflect_dom_object(
+            Box::new(SVGAnimatedNumber::new_inherited(
+                element,
+                attribute,
+                initial_value,
+            )),
+            &*element.owner_window(),
+            can_gc,
+        )
+    }
+
+    fn value(&self) -> Finite<f32> {
+        let value = number_attribute(&self.element, &self.attribute).unwrap_or(self.initial_value);
+        // Numbers that do not fit in a float, like `1e39`, are treated as zero.
+        Finite::new(value as f32).unwrap_or_default()
+    }
+}
+
+impl SVGAnimatedNumberMethods<crate::DomTypeHolder> for SVGAnimatedNumber {
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGAnimatedNumber__baseVal>
+    fn BaseVal(&self) -> Finite<f32> {
+        self.value()
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGAnimatedNumber__baseVal>
+    fn SetBaseVal(&self, value: Finite<f32>, can_gc: CanGc) {
+        // Number attributes can have mixed-case names, such as `pathLength`, which
+        // `Element::set_string_attribute` does not accept.
+        self.element
+            .SetAttribute(
+                DOMString::from(&*self.attribute),
+                DOMString::from((*value).to_string()),
+                can_gc,
+            )
+            .expect("SVG number attribute names are valid attribute names");
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGAnimatedNumber__animVal>
+    fn AnimVal(&self) -> Finite<f32> {
+        self.value()
+    }
+}
@@ -8,13 +8,15 @@ use js::rust::HandleObject;
 use script_bindings::str::DOMString;
 use stylo_dom::ElementState;
 
+use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::SVGElementBinding::SVGElementMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::document::Document;
-use crate::dom::element::Element;
-use crate::dom::node::{Node, NodeTraits};
+use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::node::{ChildrenMutation, Node, NodeTraits, ShadowIncluding};
+use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
@@ -59,12 +61,43 @@ impl SVGElement {
             can_gc,
         )
     }
+
+    /// Invalidate the rendering of the outermost `<svg>` element containing this element,
+    /// after a change to this element or to its children.
+    fn invalidate_outermost_svg_element(&self) {
+        let node = self.upcast::<Node>();
+        if !node.is_connected() {
+            return;
+        }
+        let outermost_svg_element = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .take_while(|ancestor| ancestor.is::<SVGElement>())
+            .filter_map(DomRoot::downcast::<SVGSVGElement>)
+            .last();
+        if let Some(svg_element) = outermost_svg_element {
+            svg_element.invalidate_cached_serialized_subtree();
+        }
+    }
 }
 
 impl VirtualMethods for SVGElement {
     fn super_type(&self) -> Option<&dyn VirtualMethods> {
         Some(self.upcast::<Element>() as &dyn VirtualMethods)
     }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation, can_gc: CanGc) {
+        self.super_type()
+            .unwrap()
+            .attribute_mutated(attr, mutation, can_gc);
+        self.invalidate_outermost_svg_element();
+    }
+
+    fn children_changed(&self, mutation: &ChildrenMutation) {
+        if let Some(super_type) = self.super_type() {
+            super_type.children_changed(mutation);
+        }
+        self.invalidate_outermost_svg_element();
+    }
 }
 
 impl SVGElementMethods<crate::DomTypeHolder> for SVGElement {
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix, local_name};
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::SVGEllipseElementBinding::SVGEllipseElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::svganimatedlength::SVGAnimatedLength;
+use crate::dom::svggeometryelement::SVGGeometryElement;
+use crate::script_runtime::CanGc;
+
+/// <https://svgwg.org/svg2-draft/shapes.html#InterfaceSVGEllipseElement>
+#[dom_struct]
+pub(crate) struct SVGEllipseElement {
+    svggeometryelement: SVGGeometryElement,
+    cx: MutNullableDom<SVGAnimatedLength>,
+    cy: MutNullableDom<SVGAnimatedLength>,
+    rx: MutNullableDom<SVGAnimatedLength>,
+    ry: MutNullableDom<SVGAnimatedLength>,
+}
+
+impl SVGEllipseElement {
+    fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> SVGEllipseElement {
+        SVGEllipseElement {
+            svggeometryelement: SVGGeometryElement::new_inherited(local_name, prefix, document),
+            cx: Default::default(),
+            cy: Default::default(),
+            rx: Default::default(),
+            ry: Default::default(),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGEllipseElement> {
+        Node::reflect_node_with_proto(
+            Box::new(SVGEllipseElement::new_inherited(
+                local_name, prefix, document,
+            )),
+            document,
+            proto,
+            can_gc,
+        )
+    }
+}
+
+impl SVGEllipseElementMethods<crate::DomTypeHolder> for SVGEllipseElement {
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGEllipseElement__cx>
+    fn Cx(&self) -> DomRoot<SVGAnimatedLength> {
+        self.cx
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("cx"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGEllipseElement__cy>
+    fn Cy(&self) -> DomRoot<SVGAnimatedLength> {
+        self.cy
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("cy"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGEllipseElement__rx>
+    fn Rx(&self) -> DomRoot<SVGAnimatedLength> {
+        self.rx
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("rx"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGEllipseElement__ry>
+    fn Ry(&self) -> DomRoot<SVGAnimatedLength> {
+        self.ry
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("ry"), CanGc::note()))
+    }
+}
@@ -0,0 +1,218 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
+use kurbo::{BezPath, Circle, Ellipse, ParamCurve, ParamCurveArclen, PathEl, Point, Rect, Shape};
+use svgtypes::PointsParser;
+
+use crate::dom::bindings::codegen::Bindings::SVGGeometryElementBinding::SVGGeometryElementMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::document::Document;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::element::Element;
+use crate::dom::node::NodeTraits;
+use crate::dom::svganimatednumber::SVGAnimatedNumber;
+use crate::dom::svggraphicselement::SVGGraphicsElement;
+use crate::dom::svglength::length_attribute_in_user_units;
+use crate::script_runtime::CanGc;
+
+/// The tolerance used when flattening curved shapes, and the accuracy of path length
+/// computations, in user units.
+const ACCURACY: f64 = 1e-3;
+
+/// The geometry of the basic shape or path described by the attributes of `element`, or
+/// `None` if the element is not a shape or its geometry is invalid or disabled.
+///
+/// <https://svgwg.org/svg2-draft/shapes.html>
+pub(crate) fn shape_path(element: &Element) -> Option<BezPath> {
+    let length = |name: &LocalName| length_attribute_in_user_units(element, name);
+    match *element.local_name() {
+        local_name!("path") => {
+            let data = element.get_attribute(&ns!(), &local_name!("d"))?.Value();
+            BezPath::from_svg(&data).ok()
+        },
+        local_name!("rect") => {
+            let x = length(&local_name!("x")).unwrap_or(0.);
+            let y = length(&local_name!("y")).unwrap_or(0.);
+            let width = length(&local_name!("width")).unwrap_or(0.);
+            let height = length(&local_name!("height")).unwrap_or(0.);
+            if width <= 0. || height <= 0. {
+                return None;
+            }
+
+            // <https://svgwg.org/svg2-draft/shapes.html#RectElement>
+            let (rx, ry) = match (length(&local_name!("rx")), length(&local_name!("ry"))) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(radius), None) | (None, Some(radius)) => (radius, radius),
+                (None, None) => (0., 0.),
+            };
+            let rx = rx.clamp(0., width / 2.);
+            let ry = ry.clamp(0., height / 2.);
+            if rx == 0. || ry == 0. {
+                return Some(Rect::new(x, y, x + width, y + height).to_path(ACCURACY));
+            }
+
+            // The path of a rounded rectangle starts at the end of the top-left corner and
+            // goes clockwise, which matters for `getPointAtLength()`.
+            let right = x + width;
+            let bottom = y + height;
+            let data = format!(
+                "M {} {y} H {} A {rx} {ry} 0 0 1 {right} {} V {} A {rx} {ry} 0 0 1 {} {bottom} \
+                 H {} A {rx} {ry} 0 0 1 {x} {} V {} A {rx} {ry} 0 0 1 {} {y} Z",
+                x + rx,
+                right - rx,
+                y + ry,
+                bottom - ry,
+                right - rx,
+                x + rx,
+                bottom - ry,
+                y + ry,
+                x + rx,
+            );
+            BezPath::from_svg(&data).ok()
+        },
+        local_name!("circle") => {
+            let center = Point::new(
+                length(&local_name!("cx")).unwrap_or(0.),
+                length(&local_name!("cy")).unwrap_or(0.),
+            );
+            let radius = length(&local_name!("r")).unwrap_or(0.);
+            if radius <= 0. {
+                return None;
+            }
+            Some(Circle::new(center, radius).to_path(ACCURACY))
+        },
+        local_name!("ellipse") => {
+            let center = Point::new(
+                length(&local_name!("cx")).unwrap_or(0.),
+                length(&local_name!("cy")).unwrap_or(0.),
+            );
+            let (rx, ry) = match (length(&local_name!("rx")), length(&local_name!("ry"))) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(radius), None) | (None, Some(radius)) => (radius, radius),
+                (None, None) => return None,
+            };
+            if rx <= 0. || ry <= 0. {
+                return None;
+            }
+            Some(Ellipse::new(center, (rx, ry), 0.).to_path(ACCURACY))
+        },
+        local_name!("line") => {
+            let mut path = BezPath::new();
+            path.move_to((
+                length(&local_name!("x1")).unwrap_or(0.),
+                length(&local_name!("y1")).unwrap_or(0.),
+            ));
+            path.line_to((
+                length(&local_name!("x2")).unwrap_or(0.),
+                length(&local_name!("y2")).unwrap_or(0.),
+            ));
+            Some(path)
+        },
+        local_name!("polyline") | local_name!("polygon") => {
+            let points = element
+                .get_attribute(&ns!(), &local_name!("points"))?
+                .Value();
+            let mut path = BezPath::new();
+            for point in PointsParser::from(&*points) {
+                if path.elements().is_empty() {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+            if *element.local_name() == local_name!("polygon") && !path.elements().is_empty() {
+                path.close_path();
+            }
+            Some(path)
+        },
+        _ => None,
+    }
+}
+
+/// <https://svgwg.org/svg2-draft/types.html#InterfaceSVGGeometryElement>
+#[dom_struct]
+pub(crate) struct SVGGeometryElement {
+    svggraphicselement: SVGGraphicsElement,
+    path_length: MutNullableDom<SVGAnimatedNumber>,
+}
+
+impl SVGGeometryElement {
+    pub(crate) fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> SVGGeometryElement {
+        SVGGeometryElement {
+            svggraphicselement: SVGGraphicsElement::new_inherited(local_name, prefix, document),
+            path_length: Default::default(),
+        }
+    }
+
+    /// The geometry of this element, or an `InvalidStateError` if it has none, because its
+    /// path is empty or its geometry is invalid or disabled.
+    fn path(&self) -> Fallible<BezPath> {
+        shape_path(self.upcast())
+            .filter(|path| path.segments().next().is_some())
+            .ok_or(Error::InvalidState)
+    }
+}
+
+impl SVGGeometryElementMethods<crate::DomTypeHolder> for SVGGeometryElement {
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGeometryElement__pathLength>
+    fn PathLength(&self) -> DomRoot<SVGAnimatedNumber> {
+        self.path_length.or_init(|| {
+            SVGAnimatedNumber::new(self.upcast(), local_name!("pathLength"), 0., CanGc::note())
+        })
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGeometryElement__getTotalLength>
+    fn GetTotalLength(&self) -> Fallible<Finite<f32>> {
+        let length: f64 = self
+            .path()?
+            .segments()
+            .map(|segment| segment.arclen(ACCURACY))
+            .sum();
+        Ok(Finite::new(length as f32).unwrap_or_default())
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGeometryElement__getPointAtLength>
+    fn GetPointAtLength(
+        &self,
+        distance: Finite<f32>,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<DOMPoint>> {
+        let path = self.path()?;
+
+        // The distance is clamped to the length of the path, so a point past the end of
+        // the path is its last point.
+        let mut point = match path.elements().first() {
+            Some(PathEl::MoveTo(start)) => *start,
+            _ => Point::ZERO,
+        };
+        let mut remaining = (*distance as f64).max(0.);
+        for segment in path.segments() {
+            let length = segment.arclen(ACCURACY);
+            if remaining <= length {
+                point = segment.eval(segment.inv_arclen(remaining, ACCURACY));
+                break;
+            }
+            remaining -= length;
+            point = segment.end();
+        }
+
+        Ok(DOMPoint::new(
+            &self.owner_global(),
+            point.x,
+            point.y,
+            0.,
+            1.,
+            can_gc,
+        ))
+    }
+}
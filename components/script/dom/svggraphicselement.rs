@@ -3,13 +3,80 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use html5ever::{LocalName, Prefix};
+use euclid::default::Transform2D;
+use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
+use kurbo::{Affine, Rect, Shape};
 use stylo_dom::ElementState;
 
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::SVGGraphicsElementBinding::{
+    SVGBoundingBoxOptions, SVGGraphicsElementMethods,
+};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::document::Document;
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::domrect::DOMRect;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, NodeTraits};
 use crate::dom::svgelement::SVGElement;
+use crate::dom::svggeometryelement::shape_path;
+use crate::dom::svgsvgelement::SVGSVGElement;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_runtime::CanGc;
+
+/// The transform from the coordinate system that `element` establishes for its children to
+/// the user space of its parent, given by its `transform` attribute and, for `<svg>`
+/// elements, its viewport.
+fn transform_to_parent(element: &Element) -> Affine {
+    let transform = element
+        .get_attribute(&ns!(), &local_name!("transform"))
+        .and_then(|attr| attr.Value().parse::<svgtypes::Transform>().ok())
+        .map_or(Affine::IDENTITY, |transform| {
+            Affine::new([
+                transform.a,
+                transform.b,
+                transform.c,
+                transform.d,
+                transform.e,
+                transform.f,
+            ])
+        });
+    match element.downcast::<SVGSVGElement>() {
+        Some(svg_element) => transform * svg_element.viewport_transform(),
+        None => transform,
+    }
+}
+
+/// The bounding box of the geometry of `element` and its descendants, in the coordinate
+/// system that `element` establishes for its children, or `None` if there is no geometry.
+///
+/// <https://svgwg.org/svg2-draft/coords.html#BoundingBoxes>
+fn bounding_box(element: &Element) -> Option<Rect> {
+    if let Some(path) = shape_path(element) {
+        return Some(path.bounding_box());
+    }
+    element
+        .upcast::<Node>()
+        .children()
+        .filter_map(DomRoot::downcast::<SVGGraphicsElement>)
+        .filter_map(|child| {
+            let child = child.upcast::<Element>();
+            let child_bounding_box = bounding_box(child)?;
+            Some(transform_to_parent(child).transform_rect_bbox(child_bounding_box))
+        })
+        .reduce(|union, child_bounding_box| union.union(child_bounding_box))
+}
+
+fn to_dom_matrix(element: &Element, transform: Affine, can_gc: CanGc) -> DomRoot<DOMMatrix> {
+    let [a, b, c, d, e, f] = transform.as_coeffs();
+    DOMMatrix::new(
+        &element.owner_global(),
+        true,
+        Transform2D::new(a, b, c, d, e, f).to_3d(),
+        can_gc,
+    )
+}
 
 #[dom_struct]
 pub(crate) struct SVGGraphicsElement {
@@ -40,6 +107,63 @@ impl SVGGraphicsElement {
             svgelement: SVGElement::new_inherited_with_state(state, tag_name, prefix, document),
         }
     }
+
+    /// The transform from the user space of this element to the coordinate system of its
+    /// nearest viewport, or of its outermost viewport if `to_outermost_viewport` is true,
+    /// along with the `<svg>` element that establishes that viewport. Returns `None` if this
+    /// element is not inside an `<svg>` element.
+    fn transform_to_viewport(
+        &self,
+        to_outermost_viewport: bool,
+    ) -> Option<(Affine, DomRoot<SVGSVGElement>)> {
+        let mut transform = Affine::IDENTITY;
+        let mut current = DomRoot::from_ref(self.upcast::<Element>());
+        loop {
+            transform = transform_to_parent(&current) * transform;
+            if let Some(svg_element) = current.downcast::<SVGSVGElement>() {
+                if !to_outermost_viewport || svg_element.is_outermost() {
+                    return Some((transform, DomRoot::from_ref(svg_element)));
+                }
+            }
+            current = current
+                .upcast::<Node>()
+                .GetParentElement()
+                .filter(|parent| parent.is::<SVGElement>())?;
+        }
+    }
+}
+
+impl SVGGraphicsElementMethods<crate::DomTypeHolder> for SVGGraphicsElement {
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGraphicsElement__getBBox>
+    fn GetBBox(&self, _options: &SVGBoundingBoxOptions, can_gc: CanGc) -> DomRoot<DOMRect> {
+        // TODO: Only the fill bounding box is supported, so the options are ignored.
+        let rect = bounding_box(self.upcast()).unwrap_or(Rect::ZERO);
+        DOMRect::new(
+            &self.owner_global(),
+            rect.x0,
+            rect.y0,
+            rect.width(),
+            rect.height(),
+            can_gc,
+        )
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGraphicsElement__getCTM>
+    fn GetCTM(&self, can_gc: CanGc) -> Option<DomRoot<DOMMatrix>> {
+        let (transform, _) = self.transform_to_viewport(false)?;
+        Some(to_dom_matrix(self.upcast(), transform, can_gc))
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGGraphicsElement__getScreenCTM>
+    fn GetScreenCTM(&self, can_gc: CanGc) -> Option<DomRoot<DOMMatrix>> {
+        let (transform, outermost_svg_element) = self.transform_to_viewport(true)?;
+        let origin = outermost_svg_element
+            .upcast::<Node>()
+            .bounding_content_box(can_gc)?
+            .origin;
+        let transform = Affine::translate((origin.x.to_f64_px(), origin.y.to_f64_px())) * transform;
+        Some(to_dom_matrix(self.upcast(), transform, can_gc))
+    }
 }
 
 impl VirtualMethods for SVGGraphicsElement {
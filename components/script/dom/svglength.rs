@@ -0,0 +1,309 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::str::FromStr;
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, local_name, namespace_url, ns};
+use svgtypes::{Length, LengthUnit};
+
+use crate::dom::bindings::codegen::Bindings::SVGLengthBinding::{
+    SVGLengthConstants, SVGLengthMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, NodeTraits};
+use crate::dom::svgelement::SVGElement;
+use crate::dom::svgsvgelement::SVGSVGElement;
+use crate::script_runtime::CanGc;
+
+/// The font size that font-relative units are resolved against.
+///
+/// TODO: Use the computed font size of the element.
+const DEFAULT_FONT_SIZE: f64 = 16.;
+
+/// The axis of the SVG viewport that a percentage length is relative to.
+///
+/// <https://svgwg.org/svg2-draft/coords.html#Units>
+#[derive(Clone, Copy)]
+pub(crate) enum LengthDirection {
+    Horizontal,
+    Vertical,
+    Other,
+}
+
+impl LengthDirection {
+    pub(crate) fn for_attribute(name: &LocalName) -> LengthDirection {
+        match *name {
+            local_name!("x") |
+            local_name!("x1") |
+            local_name!("x2") |
+            local_name!("cx") |
+            local_name!("rx") |
+            local_name!("width") => LengthDirection::Horizontal,
+            local_name!("y") |
+            local_name!("y1") |
+            local_name!("y2") |
+            local_name!("cy") |
+            local_name!("ry") |
+            local_name!("height") => LengthDirection::Vertical,
+            _ => LengthDirection::Other,
+        }
+    }
+}
+
+/// Parse the value of a length attribute, returning `None` if it is not a valid length.
+pub(crate) fn parse_length(value: &str) -> Option<Length> {
+    Length::from_str(value.trim()).ok()
+}
+
+/// The number of user units in one unit of the given type, for a length on `element`.
+fn user_units_per_unit(unit: LengthUnit, element: &Element, direction: LengthDirection) -> f64 {
+    match unit {
+        LengthUnit::None | LengthUnit::Px => 1.,
+        LengthUnit::In => 96.,
+        LengthUnit::Cm => 96. / 2.54,
+        LengthUnit::Mm => 96. / 25.4,
+        LengthUnit::Pt => 96. / 72.,
+        LengthUnit::Pc => 16.,
+        LengthUnit::Em => DEFAULT_FONT_SIZE,
+        LengthUnit::Ex => DEFAULT_FONT_SIZE / 2.,
+        LengthUnit::Percent => {
+            let viewport = nearest_viewport_size(element);
+            let base = match direction {
+                LengthDirection::Horizontal => viewport.0,
+                LengthDirection::Vertical => viewport.1,
+                LengthDirection::Other => {
+                    (viewport.0 * viewport.0 + viewport.1 * viewport.1).sqrt() /
+                        std::f64::consts::SQRT_2
+                },
+            };
+            base / 100.
+        },
+    }
+}
+
+/// The size of the SVG viewport that establishes the coordinate system of `element`, which
+/// is the one of the nearest ancestor `<svg>` element.
+fn nearest_viewport_size(element: &Element) -> (f64, f64) {
+    element
+        .upcast::<Node>()
+        .ancestors()
+        .take_while(|ancestor| ancestor.is::<SVGElement>())
+        .find_map(DomRoot::downcast::<SVGSVGElement>)
+        .map_or((0., 0.), |svg_element| svg_element.viewport_size())
+}
+
+/// Resolve a length on `element` into user units.
+pub(crate) fn length_to_user_units(
+    length: Length,
+    element: &Element,
+    direction: LengthDirection,
+) -> f64 {
+    length.number * user_units_per_unit(length.unit, element, direction)
+}
+
+/// The value of the length attribute `name` of `element` in user units, or `None` if the
+/// attribute is missing or not a valid length.
+pub(crate) fn length_attribute_in_user_units(element: &Element, name: &LocalName) -> Option<f64> {
+    let value = element.get_attribute(&ns!(), name)?.Value();
+    let length = parse_length(&value)?;
+    Some(length_to_user_units(
+        length,
+        element,
+        LengthDirection::for_attribute(name),
+    ))
+}
+
+fn unit_type(unit: LengthUnit) -> u16 {
+    match unit {
+        LengthUnit::None => SVGLengthConstants::SVG_LENGTHTYPE_NUMBER,
+        LengthUnit::Percent => SVGLengthConstants::SVG_LENGTHTYPE_PERCENTAGE,
+        LengthUnit::Em => SVGLengthConstants::SVG_LENGTHTYPE_EMS,
+        LengthUnit::Ex => SVGLengthConstants::SVG_LENGTHTYPE_EXS,
+        LengthUnit::Px => SVGLengthConstants::SVG_LENGTHTYPE_PX,
+        LengthUnit::Cm => SVGLengthConstants::SVG_LENGTHTYPE_CM,
+        LengthUnit::Mm => SVGLengthConstants::SVG_LENGTHTYPE_MM,
+        LengthUnit::In => SVGLengthConstants::SVG_LENGTHTYPE_IN,
+        LengthUnit::Pt => SVGLengthConstants::SVG_LENGTHTYPE_PT,
+        LengthUnit::Pc => SVGLengthConstants::SVG_LENGTHTYPE_PC,
+    }
+}
+
+fn unit_for_type(unit_type: u16) -> Option<LengthUnit> {
+    Some(match unit_type {
+        SVGLengthConstants::SVG_LENGTHTYPE_NUMBER => LengthUnit::None,
+        SVGLengthConstants::SVG_LENGTHTYPE_PERCENTAGE => LengthUnit::Percent,
+        SVGLengthConstants::SVG_LENGTHTYPE_EMS => LengthUnit::Em,
+        SVGLengthConstants::SVG_LENGTHTYPE_EXS => LengthUnit::Ex,
+        SVGLengthConstants::SVG_LENGTHTYPE_PX => LengthUnit::Px,
+        SVGLengthConstants::SVG_LENGTHTYPE_CM => LengthUnit::Cm,
+        SVGLengthConstants::SVG_LENGTHTYPE_MM => LengthUnit::Mm,
+        SVGLengthConstants::SVG_LENGTHTYPE_IN => LengthUnit::In,
+        SVGLengthConstants::SVG_LENGTHTYPE_PT => LengthUnit::Pt,
+        SVGLengthConstants::SVG_LENGTHTYPE_PC => LengthUnit::Pc,
+        _ => return None,
+    })
+}
+
+fn serialize_length(length: Length) -> String {
+    let suffix = match length.unit {
+        LengthUnit::None => "",
+        LengthUnit::Percent => "%",
+        LengthUnit::Em => "em",
+        LengthUnit::Ex => "ex",
+        LengthUnit::Px => "px",
+        LengthUnit::Cm => "cm",
+        LengthUnit::Mm => "mm",
+        LengthUnit::In => "in",
+        LengthUnit::Pt => "pt",
+        LengthUnit::Pc => "pc",
+    };
+    format!("{}{}", length.number, suffix)
+}
+
+/// <https://svgwg.org/svg2-draft/types.html#InterfaceSVGLength>
+///
+/// An [`SVGLength`] reflects the length attribute of its element, so reading it always
+/// reflects the current attribute value and modifying it updates the attribute.
+#[dom_struct]
+pub(crate) struct SVGLength {
+    reflector_: Reflector,
+    element: Dom<Element>,
+    #[no_trace]
+    attribute: LocalName,
+    /// Whether this is the `animVal` of an `SVGAnimatedLength`, which can't be modified.
+    read_only: bool,
+}
+
+impl SVGLength {
+    fn new_inherited(element: &Element, attribute: LocalName, read_only: bool) -> SVGLength {
+        SVGLength {
+            reflector_: Reflector::new(),
+            element: Dom::from_ref(element),
+            attribute,
+            read_only,
+        }
+    }
+
+    pub(crate) fn new(
+        element: &Element,
+        attribute: LocalName,
+        read_only: bool,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGLength> {
+        reflect_dom_object(
+            Box::new(SVGLength::new_inherited(element, attribute, read_only)),
+            &*element.owner_window(),
+            can_gc,
+        )
+    }
+
+    fn length(&self) -> Length {
+        self.element
+            .get_attribute(&ns!(), &self.attribute)
+            .and_then(|attribute| parse_length(&attribute.Value()))
+            .unwrap_or_else(Length::zero)
+    }
+
+    fn user_units_per_unit(&self, unit: LengthUnit) -> f64 {
+        user_units_per_unit(
+            unit,
+            &self.element,
+            LengthDirection::for_attribute(&self.attribute),
+        )
+    }
+
+    fn set_length(&self, length: Length, can_gc: CanGc) -> ErrorResult {
+        if self.read_only {
+            return Err(Error::NoModificationAllowed);
+        }
+        self.element.set_string_attribute(
+            &self.attribute,
+            DOMString::from(serialize_length(length)),
+            can_gc,
+        );
+        Ok(())
+    }
+}
+
+impl SVGLengthMethods<crate::DomTypeHolder> for SVGLength {
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__unitType>
+    fn UnitType(&self) -> u16 {
+        unit_type(self.length().unit)
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__value>
+    fn Value(&self) -> Finite<f32> {
+        let length = self.length();
+        let value = length.number * self.user_units_per_unit(length.unit);
+        // Lengths that do not fit in a float, like `1e39`, are treated as zero.
+        Finite::new(value as f32).unwrap_or_default()
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__value>
+    fn SetValue(&self, value: Finite<f32>, can_gc: CanGc) -> ErrorResult {
+        let unit = self.length().unit;
+        let user_units_per_unit = self.user_units_per_unit(unit);
+        if user_units_per_unit == 0. {
+            return Ok(());
+        }
+        self.set_length(
+            Length::new(*value as f64 / user_units_per_unit, unit),
+            can_gc,
+        )
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__valueInSpecifiedUnits>
+    fn ValueInSpecifiedUnits(&self) -> Finite<f32> {
+        Finite::new(self.length().number as f32).unwrap_or_default()
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__valueInSpecifiedUnits>
+    fn SetValueInSpecifiedUnits(&self, value: Finite<f32>, can_gc: CanGc) -> ErrorResult {
+        let unit = self.length().unit;
+        self.set_length(Length::new(*value as f64, unit), can_gc)
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__valueAsString>
+    fn ValueAsString(&self) -> DOMString {
+        DOMString::from(serialize_length(self.length()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__valueAsString>
+    fn SetValueAsString(&self, value: DOMString, can_gc: CanGc) -> ErrorResult {
+        let length = parse_length(&value).ok_or(Error::Syntax)?;
+        self.set_length(length, can_gc)
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__newValueSpecifiedUnits>
+    fn NewValueSpecifiedUnits(
+        &self,
+        unit_type: u16,
+        value: Finite<f32>,
+        can_gc: CanGc,
+    ) -> ErrorResult {
+        let unit = unit_for_type(unit_type).ok_or(Error::NotSupported)?;
+        self.set_length(Length::new(*value as f64, unit), can_gc)
+    }
+
+    /// <https://svgwg.org/svg2-draft/types.html#__svg__SVGLength__convertToSpecifiedUnits>
+    fn ConvertToSpecifiedUnits(&self, unit_type: u16, can_gc: CanGc) -> ErrorResult {
+        let unit = unit_for_type(unit_type).ok_or(Error::NotSupported)?;
+        let length = self.length();
+        let user_units = length.number * self.user_units_per_unit(length.unit);
+        let user_units_per_unit = self.user_units_per_unit(unit);
+        let number = if user_units_per_unit == 0. {
+            0.
+        } else {
+            user_units / user_units_per_unit
+        };
+        self.set_length(Length::new(number, unit), can_gc)
+    }
+}
@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix, local_name};
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::SVGLineElementBinding::SVGLineElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::svganimatedlength::SVGAnimatedLength;
+use crate::dom::svggeometryelement::SVGGeometryElement;
+use crate::script_runtime::CanGc;
+
+/// <https://svgwg.org/svg2-draft/shapes.html#InterfaceSVGLineElement>
+#[dom_struct]
+pub(crate) struct SVGLineElement {
+    svggeometryelement: SVGGeometryElement,
+    x1: MutNullableDom<SVGAnimatedLength>,
+    y1: MutNullableDom<SVGAnimatedLength>,
+    x2: MutNullableDom<SVGAnimatedLength>,
+    y2: MutNullableDom<SVGAnimatedLength>,
+}
+
+impl SVGLineElement {
+    fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> SVGLineElement {
+        SVGLineElement {
+            svggeometryelement: SVGGeometryElement::new_inherited(local_name, prefix, document),
+            x1: Default::default(),
+            y1: Default::default(),
+            x2: Default::default(),
+            y2: Default::default(),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGLineElement> {
+        Node::reflect_node_with_proto(
+            Box::new(SVGLineElement::new_inherited(local_name, prefix, document)),
+            document,
+            proto,
+            can_gc,
+        )
+    }
+}
+
+impl SVGLineElementMethods<crate::DomTypeHolder> for SVGLineElement {
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGLineElement__x1>
+    fn X1(&self) -> DomRoot<SVGAnimatedLength> {
+        self.x1
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("x1"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGLineElement__y1>
+    fn Y1(&self) -> DomRoot<SVGAnimatedLength> {
+        self.y1
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("y1"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGLineElement__x2>
+    fn X2(&self) -> DomRoot<SVGAnimatedLength> {
+        self.x2
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("x2"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGLineElement__y2>
+    fn Y2(&self) -> DomRoot<SVGAnimatedLength> {
+        self.y2
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("y2"), CanGc::note()))
+    }
+}
@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Prefix, local_name};
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::SVGRectElementBinding::SVGRectElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::svganimatedlength::SVGAnimatedLength;
+use crate::dom::svggeometryelement::SVGGeometryElement;
+use crate::script_runtime::CanGc;
+
+/// <https://svgwg.org/svg2-draft/shapes.html#InterfaceSVGRectElement>
+#[dom_struct]
+pub(crate) struct SVGRectElement {
+    svggeometryelement: SVGGeometryElement,
+    x: MutNullableDom<SVGAnimatedLength>,
+    y: MutNullableDom<SVGAnimatedLength>,
+    width: MutNullableDom<SVGAnimatedLength>,
+    height: MutNullableDom<SVGAnimatedLength>,
+    rx: MutNullableDom<SVGAnimatedLength>,
+    ry: MutNullableDom<SVGAnimatedLength>,
+}
+
+impl SVGRectElement {
+    fn new_inherited(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+    ) -> SVGRectElement {
+        SVGRectElement {
+            svggeometryelement: SVGGeometryElement::new_inherited(local_name, prefix, document),
+            x: Default::default(),
+            y: Default::default(),
+            width: Default::default(),
+            height: Default::default(),
+            rx: Default::default(),
+            ry: Default::default(),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new(
+        local_name: LocalName,
+        prefix: Option<Prefix>,
+        document: &Document,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+    ) -> DomRoot<SVGRectElement> {
+        Node::reflect_node_with_proto(
+            Box::new(SVGRectElement::new_inherited(local_name, prefix, document)),
+            document,
+            proto,
+            can_gc,
+        )
+    }
+}
+
+impl SVGRectElementMethods<crate::DomTypeHolder> for SVGRectElement {
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__x>
+    fn X(&self) -> DomRoot<SVGAnimatedLength> {
+        self.x
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("x"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__y>
+    fn Y(&self) -> DomRoot<SVGAnimatedLength> {
+        self.y
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("y"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__width>
+    fn Width(&self) -> DomRoot<SVGAnimatedLength> {
+        self.width
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("width"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__height>
+    fn Height(&self) -> DomRoot<SVGAnimatedLength> {
+        self.height
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("height"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__rx>
+    fn Rx(&self) -> DomRoot<SVGAnimatedLength> {
+        self.rx
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("rx"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/shapes.html#__svg__SVGRectElement__ry>
+    fn Ry(&self) -> DomRoot<SVGAnimatedLength> {
+        self.ry
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("ry"), CanGc::note()))
+    }
+}
@@ -2,29 +2,67 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use app_units::Au;
+use base64::Engine as _;
 use dom_struct::dom_struct;
+use euclid::default::Size2D;
+use html5ever::serialize::TraversalScope;
 use html5ever::{LocalName, Prefix, local_name, namespace_url, ns};
 use js::rust::HandleObject;
+use kurbo::Affine;
 use script_layout_interface::SVGSVGData;
+use servo_url::ServoUrl;
 use style::attr::AttrValue;
+use svgtypes::{Align, AspectRatio, ViewBox};
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::SVGSVGElementBinding::SVGSVGElementMethods;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::{DomRoot, LayoutDom};
+use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
-use crate::dom::node::Node;
+use crate::dom::node::{BindContext, Node, NodeDamage, NodeTraits};
+use crate::dom::svganimatedlength::SVGAnimatedLength;
+use crate::dom::svgelement::SVGElement;
 use crate::dom::svggraphicselement::SVGGraphicsElement;
+use crate::dom::svglength::length_attribute_in_user_units;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::CanGc;
 
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
 
+/// The source of the identifiers that make the serializations of different `<svg>` elements
+/// distinct entries of the image cache, even when their subtrees are identical.
+static NEXT_SERIALIZATION_ID: AtomicU64 = AtomicU64::new(0);
+
 #[dom_struct]
 pub(crate) struct SVGSVGElement {
     svggraphicselement: SVGGraphicsElement,
+    /// The subtree rooted at this element, serialized into a `data:` URL so that it can be
+    /// rendered like an SVG image. This is cleared whenever the subtree changes and filled
+    /// in again before the next reflow.
+    #[no_trace]
+    cached_serialized_data_url: DomRefCell<Option<ServoUrl>>,
+    /// The identifier that is added to the `data:` URLs of the serializations of this
+    /// element, so that its entry in the image cache is its own.
+    serialization_id: u64,
+    /// The size of the content box of this element in device pixels after the last reflow
+    /// that rendered it, which its subtree is rasterized at.
+    used_size_in_device_pixels: Cell<Option<(u32, u32)>>,
+    /// The author style rules that apply to the subtree, which were included in its last
+    /// serialization.
+    serialized_author_css: DomRefCell<String>,
+    x: MutNullableDom<SVGAnimatedLength>,
+    y: MutNullableDom<SVGAnimatedLength>,
+    width: MutNullableDom<SVGAnimatedLength>,
+    height: MutNullableDom<SVGAnimatedLength>,
 }
 
 impl SVGSVGElement {
@@ -35,6 +73,14 @@ impl SVGSVGElement {
     ) -> SVGSVGElement {
         SVGSVGElement {
             svggraphicselement: SVGGraphicsElement::new_inherited(local_name, prefix, document),
+            cached_serialized_data_url: Default::default(),
+            serialization_id: NEXT_SERIALIZATION_ID.fetch_add(1, Ordering::Relaxed),
+            used_size_in_device_pixels: Default::default(),
+            serialized_author_css: Default::default(),
+            x: Default::default(),
+            y: Default::default(),
+            width: Default::default(),
+            height: Default::default(),
         }
     }
 
@@ -53,6 +99,247 @@ impl SVGSVGElement {
             can_gc,
         )
     }
+
+    /// Whether this element is an outermost `<svg>` element, rather than one nested inside
+    /// another SVG element.
+    pub(crate) fn is_outermost(&self) -> bool {
+        !self
+            .upcast::<Node>()
+            .GetParentNode()
+            .is_some_and(|parent| parent.is::<SVGElement>())
+    }
+
+    /// The size of the SVG viewport of this element, in the user units of its parent.
+    fn size(&self) -> (f64, f64) {
+        let element = self.upcast::<Element>();
+        if self.is_outermost() {
+            // Match the size that layout uses for outermost `<svg>` elements.
+            let width = element
+                .get_attribute(&ns!(), &local_name!("width"))
+                .map_or(DEFAULT_WIDTH, |attr| attr.value().as_uint());
+            let height = element
+                .get_attribute(&ns!(), &local_name!("height"))
+                .map_or(DEFAULT_HEIGHT, |attr| attr.value().as_uint());
+            return (width as f64, height as f64);
+        }
+
+        // Nested `<svg>` elements fill the viewport of their parent by default.
+        let parent_viewport = self
+            .upcast::<Node>()
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| ancestor.is::<SVGElement>())
+            .find_map(DomRoot::downcast::<SVGSVGElement>)
+            .map_or((0., 0.), |svg_element| svg_element.viewport_size());
+        (
+            length_attribute_in_user_units(element, &local_name!("width"))
+                .unwrap_or(parent_viewport.0),
+            length_attribute_in_user_units(element, &local_name!("height"))
+                .unwrap_or(parent_viewport.1),
+        )
+    }
+
+    fn view_box(&self) -> Option<ViewBox> {
+        let value = self
+            .upcast::<Element>()
+            .get_attribute(&ns!(), &local_name!("viewBox"))?
+            .Value();
+        value
+            .parse::<ViewBox>()
+            .ok()
+            .filter(|view_box| view_box.w > 0. && view_box.h > 0.)
+    }
+
+    /// The size of the coordinate system that this element establishes for its descendants,
+    /// which percentage lengths of its descendants are relative to.
+    ///
+    /// <https://svgwg.org/svg2-draft/coords.html#Units>
+    pub(crate) fn viewport_size(&self) -> (f64, f64) {
+        match self.view_box() {
+            Some(view_box) => (view_box.w, view_box.h),
+            None => self.size(),
+        }
+    }
+
+    /// The transform from the user space established by this element for its descendants to
+    /// the user space of its parent.
+    ///
+    /// <https://svgwg.org/svg2-draft/coords.html#ComputingAViewportsTransform>
+    pub(crate) fn viewport_transform(&self) -> Affine {
+        let element = self.upcast::<Element>();
+        let translation = if self.is_outermost() {
+            Affine::IDENTITY
+        } else {
+            Affine::translate((
+                length_attribute_in_user_units(element, &local_name!("x")).unwrap_or(0.),
+                length_attribute_in_user_units(element, &local_name!("y")).unwrap_or(0.),
+            ))
+        };
+
+        let Some(view_box) = self.view_box() else {
+            return translation;
+        };
+        let (width, height) = self.size();
+        let aspect_ratio = element
+            .get_attribute(&ns!(), &local_name!("preserveAspectRatio"))
+            .and_then(|attr| attr.Value().parse::<AspectRatio>().ok())
+            .unwrap_or_default();
+
+        let mut scale_x = width / view_box.w;
+        let mut scale_y = height / view_box.h;
+        if aspect_ratio.align != Align::None {
+            let scale = if aspect_ratio.slice {
+                scale_x.max(scale_y)
+            } else {
+                scale_x.min(scale_y)
+            };
+            scale_x = scale;
+            scale_y = scale;
+        }
+
+        let mut translate_x = -view_box.x * scale_x;
+        let mut translate_y = -view_box.y * scale_y;
+        let extra_width = width - view_box.w * scale_x;
+        let extra_height = height - view_box.h * scale_y;
+        match aspect_ratio.align {
+            Align::XMidYMin | Align::XMidYMid | Align::XMidYMax => translate_x += extra_width / 2.,
+            Align::XMaxYMin | Align::XMaxYMid | Align::XMaxYMax => translate_x += extra_width,
+            _ => {},
+        }
+        match aspect_ratio.align {
+            Align::XMinYMid | Align::XMidYMid | Align::XMaxYMid => translate_y += extra_height / 2.,
+            Align::XMinYMax | Align::XMidYMax | Align::XMaxYMax => translate_y += extra_height,
+            _ => {},
+        }
+
+        translation * Affine::new([scale_x, 0., 0., scale_y, translate_x, translate_y])
+    }
+
+    /// Drop the serialized subtree and schedule it to be serialized again before the next
+    /// reflow.
+    pub(crate) fn invalidate_cached_serialized_subtree(&self) {
+        self.release_serialized_subtree();
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        self.owner_document().add_dirty_svg_root(self);
+    }
+
+    /// Drop the serialized subtree and the image that the image cache rasterized from it,
+    /// which is never requested again.
+    pub(crate) fn release_serialized_subtree(&self) {
+        let Some(url) = self.cached_serialized_data_url.borrow_mut().take() else {
+            return;
+        };
+        let window = self.owner_window();
+        window
+            .image_cache()
+            .evict_image(url, window.origin().immutable().clone(), None);
+    }
+
+    /// Serialize the subtree again if the style sheets of the document changed the author style
+    /// rules that apply to it.
+    pub(crate) fn handle_stylesheets_changed(&self) {
+        // Subtrees that have not been serialized yet will pick up the new rules anyway.
+        if self.cached_serialized_data_url.borrow().is_none() {
+            return;
+        }
+        let author_css = self
+            .owner_document()
+            .author_style_rules_as_css(self.upcast());
+        if *self.serialized_author_css.borrow() != author_css {
+            self.invalidate_cached_serialized_subtree();
+        }
+    }
+
+    /// Record the size of the content box of this element after a reflow, so that its subtree
+    /// is rasterized at the size that it is rendered at.
+    pub(crate) fn set_used_size(&self, size: Option<Size2D<Au>>) {
+        let Some(size) = size else {
+            return;
+        };
+        let device_pixel_ratio = self.owner_window().device_pixel_ratio().get();
+        let to_device_pixels =
+            |length: Au| ((length.to_f32_px() * device_pixel_ratio).ceil() as u32).max(1);
+        let size = (to_device_pixels(size.width), to_device_pixels(size.height));
+        if self.used_size_in_device_pixels.replace(Some(size)) != Some(size) {
+            self.invalidate_cached_serialized_subtree();
+        }
+    }
+
+    /// Serialize the subtree rooted at this element into a `data:` URL, if it has changed
+    /// since it was last serialized.
+    pub(crate) fn serialize_and_cache_subtree(&self, can_gc: CanGc) {
+        if self.cached_serialized_data_url.borrow().is_some() {
+            return;
+        }
+
+        let subtree = String::from(self.upcast::<Node>().html_serialize(
+            TraversalScope::IncludeNode,
+            false,
+            vec![],
+            can_gc,
+        ));
+
+        // `&nbsp;` is the only named character reference the HTML serializer produces that
+        // is not predefined in XML.
+        let subtree = subtree.replace("&nbsp;", "&#160;");
+
+        // The subtree is wrapped in an `<svg>` element that scales its natural size to the
+        // used size, so that it is rasterized at the resolution it is rendered at. The HTML
+        // parser does not require namespace declarations on inline SVG, but the result is
+        // parsed as XML, so the wrapper declares the namespaces that the serialization uses.
+        // It also carries the author style rules of the document, which would otherwise not
+        // apply to the serialization.
+        let (natural_width, natural_height) = self.size();
+        let (width, height) = self
+            .used_size_in_device_pixels
+            .get()
+            .unwrap_or((natural_width as u32, natural_height as u32));
+        let author_css = self
+            .owner_document()
+            .author_style_rules_as_css(self.upcast());
+        let escaped_author_css = author_css.replace("]]>", "]]]]><![CDATA[>");
+        let source = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {natural_width} {natural_height}\" preserveAspectRatio=\"none\">\
+             <style><![CDATA[{escaped_author_css}]]></style>{subtree}</svg>"
+        );
+        *self.serialized_author_css.borrow_mut() = author_css;
+
+        let data_url = format!(
+            "data:image/svg+xml;base64,{}#{}",
+            base64::engine::general_purpose::STANDARD.encode(source),
+            self.serialization_id,
+        );
+        *self.cached_serialized_data_url.borrow_mut() = ServoUrl::parse(&data_url).ok();
+    }
+}
+
+impl SVGSVGElementMethods<crate::DomTypeHolder> for SVGSVGElement {
+    /// <https://svgwg.org/svg2-draft/geometry.html#__svg__SVGSVGElement__x>
+    fn X(&self) -> DomRoot<SVGAnimatedLength> {
+        self.x
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("x"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/geometry.html#__svg__SVGSVGElement__y>
+    fn Y(&self) -> DomRoot<SVGAnimatedLength> {
+        self.y
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("y"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/geometry.html#__svg__SVGSVGElement__width>
+    fn Width(&self) -> DomRoot<SVGAnimatedLength> {
+        self.width
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("width"), CanGc::note()))
+    }
+
+    /// <https://svgwg.org/svg2-draft/geometry.html#__svg__SVGSVGElement__height>
+    fn Height(&self) -> DomRoot<SVGAnimatedLength> {
+        self.height
+            .or_init(|| SVGAnimatedLength::new(self.upcast(), local_name!("height"), CanGc::note()))
+    }
 }
 
 pub(crate) trait LayoutSVGSVGElementHelpers {
@@ -60,6 +347,7 @@ pub(crate) trait LayoutSVGSVGElementHelpers {
 }
 
 impl LayoutSVGSVGElementHelpers for LayoutDom<'_, SVGSVGElement> {
+    #[allow(unsafe_code)]
     fn data(self) -> SVGSVGData {
         let width_attr = self
             .upcast::<Element>()
@@ -67,7 +355,14 @@ impl LayoutSVGSVGElementHelpers for LayoutDom<'_, SVGSVGElement> {
         let height_attr = self
             .upcast::<Element>()
             .get_attr_for_layout(&ns!(), &local_name!("height"));
+        let source = unsafe {
+            self.unsafe_get()
+                .cached_serialized_data_url
+                .borrow_for_layout()
+                .clone()
+        };
         SVGSVGData {
+            source,
             width: width_attr.map_or(DEFAULT_WIDTH, |val| val.as_uint()),
             height: height_attr.map_or(DEFAULT_HEIGHT, |val| val.as_uint()),
         }
@@ -85,6 +380,17 @@ impl VirtualMethods for SVGSVGElement {
             .attribute_mutated(attr, mutation, can_gc);
     }
 
+    fn bind_to_tree(&self, context: &BindContext, can_gc: CanGc) {
+        if let Some(super_type) = self.super_type() {
+            super_type.bind_to_tree(context, can_gc);
+        }
+
+        // Nested `<svg>` elements are rendered as part of their outermost `<svg>` ancestor.
+        if context.tree_connected && self.is_outermost() {
+            self.invalidate_cached_serialized_subtree();
+        }
+    }
+
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
         match *name {
             local_name!("width") => AttrValue::from_u32(value.into(), DEFAULT_WIDTH),
@@ -9,13 +9,17 @@
 
 use crate::clipboard_provider::EmbedderClipboardProvider;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::element::scroll_geometry_into_view;
 use crate::dom::event::{EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::node::{Node, NodeDamage, NodeTraits};
+use crate::script_runtime::CanGc;
 use crate::textinput::{SelectionDirection, SelectionState, TextInput, UTF8Bytes};
 
 pub(crate) trait TextControlElement: DerivedFrom<EventTarget> + DerivedFrom<Node> {
@@ -267,6 +271,25 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
         Ok(())
     }
 
+    /// Scroll the selection, or the caret when nothing is selected, into view after it moved,
+    /// both in the text control when its value overflows it and in the scrolling boxes that
+    /// contain the text control.
+    pub(crate) fn scroll_into_view(&self, can_gc: CanGc) {
+        let node = self.element.upcast::<Node>();
+        let window = node.owner_window();
+        let Some(geometry) = window.text_control_selection_query(node, can_gc) else {
+            return;
+        };
+        scroll_geometry_into_view(
+            &window,
+            geometry,
+            ScrollBehavior::Auto,
+            ScrollLogicalPosition::Nearest,
+            ScrollLogicalPosition::Nearest,
+            can_gc,
+        );
+    }
+
     fn start(&self) -> u32 {
         let UTF8Bytes(offset) = self.textinput.borrow().selection_start_offset();
         offset as u32
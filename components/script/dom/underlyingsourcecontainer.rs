@@ -9,6 +9,7 @@ use dom_struct::dom_struct;
 use js::jsapi::{Heap, IsPromiseObject, JSObject};
 use js::jsval::JSVal;
 use js::rust::{Handle as SafeHandle, HandleObject, HandleValue as SafeHandleValue, IntoHandle};
+use net_traits::WebTransportStreamId;
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::codegen::Bindings::UnderlyingSourceBinding::UnderlyingSource as JsUnderlyingSource;
@@ -19,6 +20,7 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::defaultteeunderlyingsource::DefaultTeeUnderlyingSource;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
+use crate::dom::webtransport::WebTransport;
 use crate::script_runtime::CanGc;
 
 /// <https://streams.spec.whatwg.org/#underlying-source-api>
@@ -40,6 +42,9 @@ pub(crate) enum UnderlyingSourceType {
     Js(JsUnderlyingSource, Heap<*mut JSObject>),
     /// Tee
     Tee(Dom<DefaultTeeUnderlyingSource>),
+    /// The receiving side of a stream of a WebTransport session, or the datagrams and the
+    /// incoming streams of the session if there is no stream id.
+    WebTransport(Dom<WebTransport>, Option<WebTransportStreamId>),
 }
 
 impl UnderlyingSourceType {
@@ -49,7 +54,8 @@ impl UnderlyingSourceType {
             self,
             UnderlyingSourceType::Memory(_) |
                 UnderlyingSourceType::Blob(_) |
-                UnderlyingSourceType::FetchResponse
+                UnderlyingSourceType::FetchResponse |
+                UnderlyingSourceType::WebTransport(..)
         )
     }
 
@@ -128,6 +134,10 @@ impl UnderlyingSourceContainer {
                 // Call the cancel algorithm for the appropriate branch.
                 tee_underlyin_source.cancel_algorithm(reason, can_gc)
             },
+            UnderlyingSourceType::WebTransport(transport, Some(stream_id)) => {
+                transport.stop_receiving(*stream_id);
+                None
+            },
             _ => None,
         }
     }
@@ -4,42 +4,71 @@
 
 use std::rc::Rc;
 
+use embedder_traits::user_content_manager::{UserScript, UserScriptInjectionTime};
 use js::jsval::UndefinedValue;
 
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
 use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlscriptelement::SourceCode;
 use crate::dom::node::NodeTraits;
+use crate::dom::window::Window;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::CanGc;
 
+/// The user scripts that should run in `document` at the given time.
+fn user_scripts_for_document(
+    document: &Document,
+    run_at: UserScriptInjectionTime,
+) -> Vec<UserScript> {
+    document
+        .window()
+        .user_content_manager()
+        .scripts_for_url(&document.url(), run_at)
+        .cloned()
+        .collect()
+}
+
 pub(crate) fn load_script(head: &HTMLHeadElement) {
     let doc = head.owner_document();
-    let userscripts = doc.window().userscripts().to_owned();
+    let userscripts = user_scripts_for_document(&doc, UserScriptInjectionTime::DocumentStart);
     if userscripts.is_empty() {
         return;
     }
     let window = Trusted::new(doc.window());
     doc.add_delayed_task(task!(UserScriptExecute: move || {
-        let win = window.root();
-        let cx = win.get_cx();
-        rooted!(in(*cx) let mut rval = UndefinedValue());
-
-        for user_script in userscripts {
-            let script_text = SourceCode::Text(
-                Rc::new(DOMString::from_string(user_script.script))
-            );
-            let global_scope = win.as_global_scope();
-            global_scope.evaluate_script_on_global_with_result(
-                &script_text,
-                &user_script.source_file.map(|path| path.to_string_lossy().to_string()).unwrap_or_default(),
-                rval.handle_mut(),
-                1,
-                ScriptFetchOptions::default_classic_script(global_scope),
-                global_scope.api_base_url(),
-                CanGc::note(),
-            );
-        }
+        run_user_scripts(&window.root(), userscripts, CanGc::note());
     }));
 }
+
+/// Run the user scripts that are injected once `document` is parsed, right before its
+/// `DOMContentLoaded` event fires.
+pub(crate) fn run_document_end_scripts(document: &Document, can_gc: CanGc) {
+    let userscripts = user_scripts_for_document(document, UserScriptInjectionTime::DocumentEnd);
+    if !userscripts.is_empty() {
+        run_user_scripts(document.window(), userscripts, can_gc);
+    }
+}
+
+fn run_user_scripts(win: &Window, userscripts: Vec<UserScript>, can_gc: CanGc) {
+    let cx = win.get_cx();
+    rooted!(in(*cx) let mut rval = UndefinedValue());
+
+    for user_script in userscripts {
+        let script_text = SourceCode::Text(Rc::new(DOMString::from_string(user_script.script)));
+        let global_scope = win.as_global_scope();
+        global_scope.evaluate_script_on_global_with_result(
+            &script_text,
+            &user_script
+                .source_file
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            rval.handle_mut(),
+            1,
+            ScriptFetchOptions::default_classic_script(global_scope),
+            global_scope.api_base_url(),
+            can_gc,
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use constellation_traits::VisualViewportState;
+use dom_struct::dom_struct;
+use euclid::{Size2D, Vector2D};
+use style_traits::CSSPixel;
+use stylo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::VisualViewportBinding::VisualViewportMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
+
+/// <https://drafts.csswg.org/cssom-view/#visualviewport>
+#[dom_struct]
+pub(crate) struct VisualViewport {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+    /// Whether the scale or the size of the visual viewport changed since the last time that
+    /// the resize steps were run.
+    resize_pending: Cell<bool>,
+    /// Whether the visual viewport moved relative to the layout viewport since the last time
+    /// that the scroll steps were run.
+    scroll_pending: Cell<bool>,
+}
+
+impl VisualViewport {
+    fn new_inherited(window: &Window) -> VisualViewport {
+        VisualViewport {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+            resize_pending: Cell::new(false),
+            scroll_pending: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, can_gc: CanGc) -> DomRoot<VisualViewport> {
+        reflect_dom_object(
+            Box::new(VisualViewport::new_inherited(window)),
+            window,
+            can_gc,
+        )
+    }
+
+    /// Note that the visual viewport changed from `old_state` to `new_state`, so that the
+    /// appropriate events are fired during the next *update the rendering*.
+    pub(crate) fn handle_state_change(
+        &self,
+        old_state: VisualViewportState,
+        new_state: VisualViewportState,
+    ) {
+        if old_state.scale != new_state.scale {
+            self.resize_pending.set(true);
+        }
+        if old_state.offset != new_state.offset {
+            self.scroll_pending.set(true);
+        }
+    }
+
+    /// Note that the layout viewport was resized, which also resizes the visual viewport.
+    pub(crate) fn handle_layout_viewport_resize(&self) {
+        self.resize_pending.set(true);
+    }
+
+    /// The part of <https://drafts.csswg.org/cssom-view/#document-run-the-resize-steps> that
+    /// fires a `resize` event at the visual viewport if it was resized.
+    pub(crate) fn run_the_resize_steps(&self, can_gc: CanGc) {
+        if self.resize_pending.replace(false) {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("resize"), can_gc);
+        }
+    }
+
+    /// The part of <https://drafts.csswg.org/cssom-view/#document-run-the-scroll-steps> that
+    /// fires a `scroll` event at the visual viewport if it was scrolled.
+    pub(crate) fn run_the_scroll_steps(&self, can_gc: CanGc) {
+        if self.scroll_pending.replace(false) {
+            self.upcast::<EventTarget>()
+                .fire_event(Atom::from("scroll"), can_gc);
+        }
+    }
+
+    /// The offset of the visual viewport from the layout viewport, or `None` if the document
+    /// of the window is not fully active.
+    fn offset(&self) -> Option<Vector2D<f32, CSSPixel>> {
+        if !self.window.Document().is_fully_active() {
+            return None;
+        }
+        Some(self.window.visual_viewport_state().offset)
+    }
+
+    /// The size of the visual viewport, or `None` if the document of the window is not fully
+    /// active.
+    fn size(&self) -> Option<Size2D<f32, CSSPixel>> {
+        if !self.window.Document().is_fully_active() {
+            return None;
+        }
+        let scale = self.window.visual_viewport_state().scale;
+        Some(self.window.window_size().initial_viewport / scale)
+    }
+}
+
+impl VisualViewportMethods<crate::DomTypeHolder> for VisualViewport {
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-offsetleft>
+    fn OffsetLeft(&self) -> Finite<f64> {
+        Finite::wrap(self.offset().map_or(0., |offset| offset.x as f64))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-offsettop>
+    fn OffsetTop(&self) -> Finite<f64> {
+        Finite::wrap(self.offset().map_or(0., |offset| offset.y as f64))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-pageleft>
+    fn PageLeft(&self) -> Finite<f64> {
+        Finite::wrap(self.offset().map_or(0., |offset| {
+            self.window.current_viewport().origin.x.to_f64_px() + offset.x as f64
+        }))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-pagetop>
+    fn PageTop(&self) -> Finite<f64> {
+        Finite::wrap(self.offset().map_or(0., |offset| {
+            self.window.current_viewport().origin.y.to_f64_px() + offset.y as f64
+        }))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-width>
+    fn Width(&self) -> Finite<f64> {
+        Finite::wrap(self.size().map_or(0., |size| size.width as f64))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-height>
+    fn Height(&self) -> Finite<f64> {
+        Finite::wrap(self.size().map_or(0., |size| size.height as f64))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-visualviewport-scale>
+    fn Scale(&self) -> Finite<f64> {
+        if !self.window.Document().is_fully_active() {
+            return Finite::wrap(0.);
+        }
+        Finite::wrap(self.window.visual_viewport_state().scale as f64)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-onresize
+    event_handler!(resize, GetOnresize, SetOnresize);
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-onscroll
+    event_handler!(scroll, GetOnscroll, SetOnscroll);
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-onscrollend
+    event_handler!(scrollend, GetOnscrollend, SetOnscrollend);
+}
@@ -101,7 +101,6 @@ pub(crate) struct WebSocket {
     url: ServoUrl,
     ready_state: Cell<WebSocketRequestState>,
     buffered_amount: Cell<u64>,
-    clearing_buffer: Cell<bool>, //Flag to tell if there is a running thread to clear buffered_amount
     #[ignore_malloc_size_of = "Defined in std"]
     #[no_trace]
     sender: IpcSender<WebSocketDomAction>,
@@ -116,7 +115,6 @@ impl WebSocket {
             url,
             ready_state: Cell::new(WebSocketRequestState::Connecting),
             buffered_amount: Cell::new(0),
-            clearing_buffer: Cell::new(false),
             sender,
             binary_type: Cell::new(BinaryType::Blob),
             protocol: DomRefCell::new("".to_owned()),
@@ -148,8 +146,6 @@ impl WebSocket {
             WebSocketRequestState::Closing | WebSocketRequestState::Closed => true,
         };
 
-        let address = Trusted::new(self);
-
         match data_byte_len.checked_add(self.buffered_amount.get()) {
             None => panic!(),
             Some(new_amount) => self.buffered_amount.set(new_amount),
@@ -159,22 +155,42 @@ impl WebSocket {
             return Ok(false);
         }
 
-        if !self.clearing_buffer.get() && self.ready_state.get() == WebSocketRequestState::Open {
-            self.clearing_buffer.set(true);
-
-            // TODO(mrobinson): Should this task be cancellable?
-            self.global()
-                .task_manager()
-                .websocket_task_source()
-                .queue_unconditionally(BufferedAmountTask { address });
-        }
-
         Ok(true)
     }
 
     pub(crate) fn origin(&self) -> ImmutableOrigin {
         self.url.origin()
     }
+
+    /// Make this WebSocket disappear when its document is unloaded, returning whether the
+    /// connection was still connecting or open.
+    ///
+    /// <https://websockets.spec.whatwg.org/#make-disappear>
+    pub(crate) fn make_disappear(&self) -> bool {
+        let affected = match self.ready_state.get() {
+            WebSocketRequestState::Closed => return false,
+            WebSocketRequestState::Closing => false,
+            // Step 1. If the WebSocket connection is not yet established, fail the WebSocket
+            // connection.
+            // Step 2. If the WebSocket closing handshake has not yet been started, start the
+            // WebSocket closing handshake, with the status code to use in the WebSocket Close
+            // message being 1001.
+            // Note: The resource thread closes connections that are not yet established as soon
+            // as they are.
+            WebSocketRequestState::Connecting | WebSocketRequestState::Open => {
+                let _ = self.sender.send(WebSocketDomAction::Close(
+                    Some(close_code::GOING_AWAY),
+                    None,
+                ));
+                true
+            },
+        };
+
+        // Step 3. If the WebSocket connection is closed, then set this's ready state to CLOSED.
+        // Note: No close event is fired, as the document is going away.
+        self.ready_state.set(WebSocketRequestState::Closed);
+        affected
+    }
 }
 
 impl WebSocketMethods<crate::DomTypeHolder> for WebSocket {
@@ -256,10 +272,12 @@ impl WebSocketMethods<crate::DomTypeHolder> for WebSocket {
 
         // Step 12. Establish a WebSocket connection given urlRecord, protocols, and client.
         let ws = WebSocket::new(global, proto, url_record.clone(), dom_action_sender, can_gc);
+        global.track_websocket(&ws);
         let address = Trusted::new(&*ws);
 
         let request = RequestBuilder::new(global.webview_id(), url_record, Referrer::NoReferrer)
             .origin(global.origin().immutable().clone())
+            .top_level_origin(Some(global.top_level_origin()))
             .insecure_requests_policy(global.insecure_requests_policy())
             .mode(RequestMode::WebSocket { protocols })
             .service_workers_mode(ServiceWorkersMode::None)
@@ -293,6 +311,13 @@ impl WebSocketMethods<crate::DomTypeHolder> for WebSocket {
                     };
                     task_source.queue(message_thread);
                 },
+                WebSocketNetworkEvent::MessageSent(amount) => {
+                    let buffered_amount_task = BufferedAmountTask {
+                        address: address.clone(),
+                        amount,
+                    };
+                    task_source.queue(buffered_amount_task);
+                },
                 WebSocketNetworkEvent::Fail => {
                     fail_the_websocket_connection(address.clone(), &task_source);
                 },
@@ -483,19 +508,20 @@ impl TaskOnce for ConnectionEstablishedTask {
 
 struct BufferedAmountTask {
     address: Trusted<WebSocket>,
+    amount: u64,
 }
 
 impl TaskOnce for BufferedAmountTask {
     // See https://html.spec.whatwg.org/multipage/#dom-websocket-bufferedamount
     //
-    // To be compliant with standards, we need to reset bufferedAmount only when the event loop
-    // reaches step 1.  In our implementation, the bytes will already have been sent on a background
-    // thread.
+    // The bytes of a message stop counting towards bufferedAmount once the network
+    // reports that they were transmitted, so that pages which wait for it to drain
+    // before sending more don't queue an unbounded amount of data.
     fn run_once(self) {
         let ws = self.address.root();
 
-        ws.buffered_amount.set(0);
-        ws.clearing_buffer.set(false);
+        ws.buffered_amount
+            .set(ws.buffered_amount.get().saturating_sub(self.amount));
     }
 }
 
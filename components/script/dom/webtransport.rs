@@ -0,0 +1,719 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::jsval::UndefinedValue;
+use js::rust::{HandleObject, HandleValue as SafeHandleValue};
+use net_traits::request::{
+    CacheMode, CredentialsMode, RedirectMode, Referrer, RequestBuilder, RequestMode,
+    ServiceWorkersMode,
+};
+use net_traits::{
+    CoreResourceMsg, FetchChannels, WebTransportDomAction, WebTransportNetworkEvent,
+    WebTransportSessionOptions, WebTransportStreamId,
+};
+use profile_traits::ipc as ProfiledIpc;
+use servo_url::ServoUrl;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebTransportBinding::{
+    WebTransportCloseInfo, WebTransportMethods, WebTransportOptions,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, ToJSValConvertible,
+};
+use crate::dom::bindings::error::{Error, ErrorToJsval, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::USVString;
+use crate::dom::bindings::trace::HashMapTracedValues;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::underlyingsourcecontainer::UnderlyingSourceType;
+use crate::dom::webtransportbidirectionalstream::WebTransportBidirectionalStream;
+use crate::dom::webtransportdatagramduplexstream::WebTransportDatagramDuplexStream;
+use crate::dom::writablestream::WritableStream;
+use crate::dom::writablestreamdefaultcontroller::UnderlyingSinkType;
+use crate::realms::enter_realm;
+use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
+use crate::task::TaskOnce;
+
+/// The longest reason that a session can be closed with, in bytes.
+/// <https://w3c.github.io/webtransport/#dom-webtransport-close>
+const MAX_CLOSE_REASON_LENGTH: usize = 1024;
+
+/// <https://w3c.github.io/webtransport/#dom-webtransport-state-slot>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum WebTransportState {
+    Connecting,
+    Connected,
+    Closed,
+    Failed,
+}
+
+/// What a native writable stream of a [`WebTransport`] writes to.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
+pub(crate) enum WebTransportSink {
+    /// The sending side of the stream with this id.
+    Stream(WebTransportStreamId),
+    /// The datagrams of the session.
+    Datagrams,
+}
+
+/// Whether the stream with `stream_id` is bidirectional.
+/// <https://www.rfc-editor.org/rfc/rfc9000#section-2.1>
+fn is_bidirectional(stream_id: WebTransportStreamId) -> bool {
+    stream_id & 0x2 == 0
+}
+
+/// <https://w3c.github.io/webtransport/#webtransport>
+#[dom_struct]
+pub(crate) struct WebTransport {
+    reflector_: Reflector,
+    #[no_trace]
+    sender: IpcSender<WebTransportDomAction>,
+    state: Cell<WebTransportState>,
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-ready-slot>
+    #[ignore_malloc_size_of = "Rc"]
+    ready: Rc<Promise>,
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-closed-slot>
+    #[ignore_malloc_size_of = "Rc"]
+    closed: Rc<Promise>,
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-datagrams-slot>
+    datagrams: MutNullableDom<WebTransportDatagramDuplexStream>,
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-incomingbidirectionalstreams-slot>
+    incoming_bidirectional_streams: MutNullableDom<ReadableStream>,
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-incomingunidirectionalstreams-slot>
+    incoming_unidirectional_streams: MutNullableDom<ReadableStream>,
+    /// The readable streams of the receiving side of the streams of the session, which data
+    /// received by the network is enqueued into.
+    receive_streams: DomRefCell<HashMapTracedValues<WebTransportStreamId, Dom<ReadableStream>>>,
+    /// The writable streams of the sending side of the streams of the session.
+    send_streams: DomRefCell<HashMapTracedValues<WebTransportStreamId, Dom<WritableStream>>>,
+    /// The promises of the streams that are being opened, by the id of their request.
+    #[ignore_malloc_size_of = "Rc"]
+    pending_stream_requests: DomRefCell<HashMapTracedValues<u64, Rc<Promise>>>,
+    next_stream_request_id: Cell<u64>,
+}
+
+impl WebTransport {
+    fn new_inherited(
+        sender: IpcSender<WebTransportDomAction>,
+        ready: Rc<Promise>,
+        closed: Rc<Promise>,
+    ) -> WebTransport {
+        WebTransport {
+            reflector_: Reflector::new(),
+            sender,
+            state: Cell::new(WebTransportState::Connecting),
+            ready,
+            closed,
+            datagrams: Default::default(),
+            incoming_bidirectional_streams: Default::default(),
+            incoming_unidirectional_streams: Default::default(),
+            receive_streams: DomRefCell::new(HashMapTracedValues::new()),
+            send_streams: DomRefCell::new(HashMapTracedValues::new()),
+            pending_stream_requests: DomRefCell::new(HashMapTracedValues::new()),
+            next_stream_request_id: Cell::new(0),
+        }
+    }
+
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn new(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        sender: IpcSender<WebTransportDomAction>,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<WebTransport>> {
+        // Rejections of these promises are reported by the streams of the session instead.
+        let ready = Promise::new(global, can_gc);
+        ready.set_promise_is_handled();
+        let closed = Promise::new(global, can_gc);
+        closed.set_promise_is_handled();
+
+        let transport = reflect_dom_object_with_proto(
+            Box::new(WebTransport::new_inherited(sender, ready, closed)),
+            global,
+            proto,
+            can_gc,
+        );
+
+        // <https://w3c.github.io/webtransport/#webtransportdatagramduplexstream-create>
+        let readable = ReadableStream::new_with_external_underlying_source(
+            global,
+            UnderlyingSourceType::WebTransport(Dom::from_ref(&*transport), None),
+            can_gc,
+        )?;
+        let writable = WritableStream::new_with_external_underlying_sink(
+            cx,
+            global,
+            UnderlyingSinkType::WebTransport(
+                Dom::from_ref(&*transport),
+                WebTransportSink::Datagrams,
+            ),
+            can_gc,
+        )?;
+        transport
+            .datagrams
+            .set(Some(&*WebTransportDatagramDuplexStream::new(
+                global, &readable, &writable, can_gc,
+            )));
+
+        for incoming_streams in [
+            &transport.incoming_bidirectional_streams,
+            &transport.incoming_unidirectional_streams,
+        ] {
+            incoming_streams.set(Some(&*ReadableStream::new_with_external_underlying_source(
+                global,
+                UnderlyingSourceType::WebTransport(Dom::from_ref(&*transport), None),
+                can_gc,
+            )?));
+        }
+
+        Ok(transport)
+    }
+
+    fn datagrams(&self) -> DomRoot<WebTransportDatagramDuplexStream> {
+        self.datagrams
+            .get()
+            .expect("A WebTransport always has datagrams.")
+    }
+
+    fn is_closed_or_failed(&self) -> bool {
+        matches!(
+            self.state.get(),
+            WebTransportState::Closed | WebTransportState::Failed
+        )
+    }
+
+    /// Create the readable stream of the receiving side of the stream with `stream_id`.
+    /// <https://w3c.github.io/webtransport/#webtransportreceivestream-create>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn create_receive_stream(
+        &self,
+        stream_id: WebTransportStreamId,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<ReadableStream>> {
+        let stream = ReadableStream::new_with_external_underlying_source(
+            &self.global(),
+            UnderlyingSourceType::WebTransport(Dom::from_ref(self), Some(stream_id)),
+            can_gc,
+        )?;
+        self.receive_streams
+            .borrow_mut()
+            .insert(stream_id, Dom::from_ref(&*stream));
+        Ok(stream)
+    }
+
+    /// Create the writable stream of the sending side of the stream with `stream_id`.
+    /// <https://w3c.github.io/webtransport/#webtransportsendstream-create>
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    fn create_send_stream(
+        &self,
+        cx: SafeJSContext,
+        stream_id: WebTransportStreamId,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<WritableStream>> {
+        let stream = WritableStream::new_with_external_underlying_sink(
+            cx,
+            &self.global(),
+            UnderlyingSinkType::WebTransport(
+                Dom::from_ref(self),
+                WebTransportSink::Stream(stream_id),
+            ),
+            can_gc,
+        )?;
+        self.send_streams
+            .borrow_mut()
+            .insert(stream_id, Dom::from_ref(&*stream));
+        Ok(stream)
+    }
+
+    /// Create the [`WebTransportBidirectionalStream`] of the stream with `stream_id`.
+    /// <https://w3c.github.io/webtransport/#webtransportbidirectionalstream-create>
+    fn create_bidirectional_stream(
+        &self,
+        cx: SafeJSContext,
+        stream_id: WebTransportStreamId,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<WebTransportBidirectionalStream>> {
+        let readable = self.create_receive_stream(stream_id, can_gc)?;
+        let writable = self.create_send_stream(cx, stream_id, can_gc)?;
+        Ok(WebTransportBidirectionalStream::new(
+            &self.global(),
+            &readable,
+            &writable,
+            can_gc,
+        ))
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-createbidirectionalstream>
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-createunidirectionalstream>
+    fn create_outgoing_stream(&self, bidirectional: bool, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+
+        // If transport.[[State]] is "closed" or "failed", return a new rejected promise with
+        // an InvalidStateError.
+        if self.is_closed_or_failed() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // The stream is opened once the session is established and the server allows it.
+        let request_id = self.next_stream_request_id.get();
+        self.next_stream_request_id.set(request_id + 1);
+        self.pending_stream_requests
+            .borrow_mut()
+            .insert(request_id, promise.clone());
+        let _ = self.sender.send(WebTransportDomAction::OpenStream {
+            request_id,
+            bidirectional,
+        });
+        promise
+    }
+
+    /// The write algorithm of the native writable streams of this session.
+    #[allow(unsafe_code)]
+    pub(crate) fn write_to_sink(
+        &self,
+        cx: SafeJSContext,
+        sink: WebTransportSink,
+        chunk: SafeHandleValue,
+    ) -> Fallible<()> {
+        // Let bytes be a copy of the byte sequence which chunk represents.
+        let bytes = match unsafe { ArrayBufferViewOrArrayBuffer::from_jsval(*cx, chunk, ()) } {
+            Ok(ConversionResult::Success(ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer))) => {
+                buffer.to_vec()
+            },
+            Ok(ConversionResult::Success(ArrayBufferViewOrArrayBuffer::ArrayBufferView(view))) => {
+                view.to_vec()
+            },
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.to_string())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        if self.is_closed_or_failed() {
+            return Err(Error::InvalidState);
+        }
+        let action = match sink {
+            WebTransportSink::Stream(stream_id) => {
+                WebTransportDomAction::SendData(stream_id, bytes)
+            },
+            WebTransportSink::Datagrams => {
+                // Datagrams that are too large to be sent are dropped.
+                // <https://w3c.github.io/webtransport/#datagram-duplex-stream-procedures>
+                let max_datagram_size = self.datagrams().max_datagram_size();
+                if max_datagram_size != 0 && bytes.len() > max_datagram_size as usize {
+                    return Ok(());
+                }
+                WebTransportDomAction::SendDatagram(bytes)
+            },
+        };
+        self.sender.send(action).map_err(|_| Error::InvalidState)
+    }
+
+    /// The close algorithm of the native writable streams of this session.
+    pub(crate) fn close_sink(&self, sink: WebTransportSink) {
+        if let WebTransportSink::Stream(stream_id) = sink {
+            self.send_streams.borrow_mut().remove(&stream_id);
+            let _ = self.sender.send(WebTransportDomAction::Finish(stream_id));
+        }
+    }
+
+    /// The abort algorithm of the native writable streams of this session.
+    pub(crate) fn abort_sink(&self, sink: WebTransportSink) {
+        if let WebTransportSink::Stream(stream_id) = sink {
+            self.send_streams.borrow_mut().remove(&stream_id);
+            let _ = self.sender.send(WebTransportDomAction::Reset(stream_id, 0));
+        }
+    }
+
+    /// The cancel algorithm of the readable stream of the stream with `stream_id`.
+    pub(crate) fn stop_receiving(&self, stream_id: WebTransportStreamId) {
+        self.receive_streams.borrow_mut().remove(&stream_id);
+        let _ = self
+            .sender
+            .send(WebTransportDomAction::StopSending(stream_id, 0));
+    }
+
+    fn error_writable_stream(&self, stream: &WritableStream, error: Error, can_gc: CanGc) {
+        let Some(controller) = stream.get_controller() else {
+            return;
+        };
+        let cx = GlobalScope::get_cx();
+        let global = self.global();
+        rooted!(in(*cx) let mut error_val = UndefinedValue());
+        error.to_jsval(cx, &global, error_val.handle_mut(), can_gc);
+        controller.error_if_needed(cx, error_val.handle(), &global, can_gc);
+    }
+
+    #[allow(unsafe_code)]
+    fn handle_incoming_stream(
+        &self,
+        cx: SafeJSContext,
+        stream_id: WebTransportStreamId,
+        bidirectional: bool,
+        can_gc: CanGc,
+    ) -> Fallible<()> {
+        rooted!(in(*cx) let mut chunk = UndefinedValue());
+        let incoming_streams = if bidirectional {
+            let stream = self.create_bidirectional_stream(cx, stream_id, can_gc)?;
+            unsafe { stream.to_jsval(*cx, chunk.handle_mut()) };
+            &self.incoming_bidirectional_streams
+        } else {
+            let stream = self.create_receive_stream(stream_id, can_gc)?;
+            unsafe { stream.to_jsval(*cx, chunk.handle_mut()) };
+            &self.incoming_unidirectional_streams
+        };
+        let incoming_streams = incoming_streams
+            .get()
+            .expect("A WebTransport always has incoming streams.");
+        incoming_streams
+            .get_default_controller()
+            .enqueue(cx, chunk.handle(), can_gc)
+    }
+
+    fn handle_network_event(&self, event: WebTransportNetworkEvent, can_gc: CanGc) {
+        if self.is_closed_or_failed() {
+            return;
+        }
+        let _ac = enter_realm(self);
+        let cx = GlobalScope::get_cx();
+
+        match event {
+            WebTransportNetworkEvent::SessionEstablished { max_datagram_size } => {
+                self.state.set(WebTransportState::Connected);
+                self.datagrams().set_max_datagram_size(max_datagram_size);
+                self.ready.resolve_native(&(), can_gc);
+            },
+            WebTransportNetworkEvent::StreamOpened {
+                request_id,
+                stream_id,
+            } => {
+                let Some(promise) = self
+                    .pending_stream_requests
+                    .borrow_mut()
+                    .remove(&request_id)
+                else {
+                    return;
+                };
+                let Some(stream_id) = stream_id else {
+                    return promise.reject_error(Error::InvalidState, can_gc);
+                };
+                let result = if is_bidirectional(stream_id) {
+                    self.create_bidirectional_stream(cx, stream_id, can_gc)
+                        .map(|stream| promise.resolve_native(&stream, can_gc))
+                } else {
+                    self.create_send_stream(cx, stream_id, can_gc)
+                        .map(|stream| promise.resolve_native(&stream, can_gc))
+                };
+                if let Err(error) = result {
+                    promise.reject_error(error, can_gc);
+                }
+            },
+            WebTransportNetworkEvent::IncomingStream {
+                stream_id,
+                bidirectional,
+            } => {
+                if let Err(error) =
+                    self.handle_incoming_stream(cx, stream_id, bidirectional, can_gc)
+                {
+                    warn!("Failed to handle incoming WebTransport stream: {error:?}");
+                }
+            },
+            WebTransportNetworkEvent::StreamData(stream_id, data) => {
+                let stream = self
+                    .receive_streams
+                    .borrow()
+                    .get(&stream_id)
+                    .map(|stream| stream.as_rooted());
+                if let Some(stream) = stream.filter(|stream| stream.is_readable()) {
+                    stream.enqueue_native(data, can_gc);
+                }
+            },
+            WebTransportNetworkEvent::StreamFinished(stream_id) => {
+                let stream = self.receive_streams.borrow_mut().remove(&stream_id);
+                if let Some(stream) = stream.filter(|stream| stream.is_readable()) {
+                    stream.controller_close_native(can_gc);
+                }
+            },
+            WebTransportNetworkEvent::StreamReset(stream_id, _code) => {
+                let stream = self.receive_streams.borrow_mut().remove(&stream_id);
+                if let Some(stream) = stream.filter(|stream| stream.is_readable()) {
+                    stream.error_native(Error::Network, can_gc);
+                }
+            },
+            WebTransportNetworkEvent::StopSending(stream_id, _code) => {
+                let stream = self.send_streams.borrow_mut().remove(&stream_id);
+                if let Some(stream) = stream {
+                    self.error_writable_stream(&stream, Error::Network, can_gc);
+                }
+            },
+            WebTransportNetworkEvent::DatagramReceived(data) => {
+                let datagrams = self.datagrams();
+                if datagrams.readable().is_readable() {
+                    datagrams.readable().enqueue_native(data, can_gc);
+                }
+            },
+            WebTransportNetworkEvent::SessionClosed { code, reason } => {
+                let close_info = WebTransportCloseInfo {
+                    closeCode: code,
+                    reason: USVString(reason),
+                };
+                self.cleanup(Some(close_info), can_gc);
+            },
+            WebTransportNetworkEvent::SessionFailed => self.cleanup(None, can_gc),
+        }
+    }
+
+    /// <https://w3c.github.io/webtransport/#webtransport-cleanup>
+    fn cleanup(&self, close_info: Option<WebTransportCloseInfo>, can_gc: CanGc) {
+        let _ac = enter_realm(self);
+
+        // Set transport.[[State]] to "closed" if closeInfo is given, or "failed" otherwise.
+        self.state.set(if close_info.is_some() {
+            WebTransportState::Closed
+        } else {
+            WebTransportState::Failed
+        });
+
+        // Error the streams of the session, and reject the promises of the streams that
+        // are still being opened.
+        let receive_streams: Vec<_> = self
+            .receive_streams
+            .borrow_mut()
+            .drain()
+            .map(|(_, stream)| stream.as_rooted())
+            .collect();
+        for stream in receive_streams {
+            if stream.is_readable() {
+                stream.error_native(Error::Network, can_gc);
+            }
+        }
+        let send_streams: Vec<_> = self
+            .send_streams
+            .borrow_mut()
+            .drain()
+            .map(|(_, stream)| stream.as_rooted())
+            .collect();
+        for stream in send_streams {
+            self.error_writable_stream(&stream, Error::Network, can_gc);
+        }
+        let pending_stream_requests: Vec<_> = self
+            .pending_stream_requests
+            .borrow_mut()
+            .drain()
+            .map(|(_, promise)| promise)
+            .collect();
+        for promise in pending_stream_requests {
+            promise.reject_error(Error::InvalidState, can_gc);
+        }
+
+        let datagrams = self.datagrams();
+        self.error_writable_stream(datagrams.writable(), Error::Network, can_gc);
+        let readables = [
+            DomRoot::from_ref(datagrams.readable()),
+            self.incoming_bidirectional_streams
+                .get()
+                .expect("A WebTransport always has incoming streams."),
+            self.incoming_unidirectional_streams
+                .get()
+                .expect("A WebTransport always has incoming streams."),
+        ];
+        match close_info {
+            Some(close_info) => {
+                // Resolve closed with closeInfo, and close the incoming streams and datagrams.
+                self.closed.resolve_native(&close_info, can_gc);
+                if self.ready.is_pending() {
+                    self.ready.reject_error(Error::Network, can_gc);
+                }
+                for readable in readables {
+                    if readable.is_readable() {
+                        readable.controller_close_native(can_gc);
+                    }
+                }
+            },
+            None => {
+                // Reject ready and closed, and error the incoming streams and datagrams.
+                self.ready.reject_error(Error::Network, can_gc);
+                self.closed.reject_error(Error::Network, can_gc);
+                for readable in readables {
+                    if readable.is_readable() {
+                        readable.error_native(Error::Network, can_gc);
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl WebTransportMethods<crate::DomTypeHolder> for WebTransport {
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-webtransport>
+    fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        can_gc: CanGc,
+        url: USVString,
+        options: &WebTransportOptions,
+    ) -> Fallible<DomRoot<WebTransport>> {
+        // Let parsedURL be the URL record resulting from parsing url.
+        // If parsedURL is a failure, throw a SyntaxError exception.
+        let url = ServoUrl::parse(&url.0).map_err(|_| Error::Syntax)?;
+
+        // If parsedURL scheme is not https, throw a SyntaxError exception.
+        // If parsedURL fragment is not null, throw a SyntaxError exception.
+        if url.scheme() != "https" || url.fragment().is_some() {
+            return Err(Error::Syntax);
+        }
+
+        // Create the interface for communication with the resource thread.
+        let (dom_action_sender, resource_action_receiver): (
+            IpcSender<WebTransportDomAction>,
+            IpcReceiver<WebTransportDomAction>,
+        ) = ipc::channel().unwrap();
+        let (resource_event_sender, dom_event_receiver): (
+            IpcSender<WebTransportNetworkEvent>,
+            ProfiledIpc::IpcReceiver<WebTransportNetworkEvent>,
+        ) = ProfiledIpc::channel(global.time_profiler_chan().clone()).unwrap();
+
+        let cx = GlobalScope::get_cx();
+        let transport = WebTransport::new(cx, global, proto, dom_action_sender, can_gc)?;
+
+        // Initialize WebTransport over HTTP.
+        // <https://w3c.github.io/webtransport/#initialize-webtransport-over-http>
+        let request = RequestBuilder::new(global.webview_id(), url, Referrer::NoReferrer)
+            .origin(global.origin().immutable().clone())
+            .top_level_origin(Some(global.top_level_origin()))
+            .insecure_requests_policy(global.insecure_requests_policy())
+            .mode(RequestMode::CorsMode)
+            .service_workers_mode(ServiceWorkersMode::None)
+            .credentials_mode(CredentialsMode::Omit)
+            .cache_mode(CacheMode::NoStore)
+            .redirect_mode(RedirectMode::Error);
+        let channels = FetchChannels::WebTransport {
+            options: WebTransportSessionOptions {
+                allow_pooling: options.allowPooling,
+                require_unreliable: options.requireUnreliable,
+            },
+            event_sender: resource_event_sender,
+            action_receiver: resource_action_receiver,
+        };
+        let _ = global
+            .core_resource_thread()
+            .send(CoreResourceMsg::Fetch(request, channels));
+
+        let address = Trusted::new(&*transport);
+        let task_source = global.task_manager().networking_task_source().to_sendable();
+        ROUTER.add_typed_route(
+            dom_event_receiver.to_ipc_receiver(),
+            Box::new(move |message| {
+                let Ok(event) = message else {
+                    return;
+                };
+                task_source.queue(NetworkEventTask {
+                    address: address.clone(),
+                    event,
+                });
+            }),
+        );
+
+        Ok(transport)
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-ready>
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready.clone()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-closed>
+    fn Closed(&self) -> Rc<Promise> {
+        self.closed.clone()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-close>
+    fn Close(&self, close_info: &WebTransportCloseInfo, can_gc: CanGc) {
+        // If transport.[[State]] is "closed" or "failed", then abort these steps.
+        if self.is_closed_or_failed() {
+            return;
+        }
+
+        // Let reason be the longest prefix of closeInfo.reason whose UTF-8 encoding is no
+        // longer than 1024 bytes.
+        let mut reason = close_info.reason.0.clone();
+        if reason.len() > MAX_CLOSE_REASON_LENGTH {
+            let mut end = MAX_CLOSE_REASON_LENGTH;
+            while !reason.is_char_boundary(end) {
+                end -= 1;
+            }
+            reason.truncate(end);
+        }
+        let _ = self.sender.send(WebTransportDomAction::Close {
+            code: close_info.closeCode,
+            reason: reason.clone(),
+        });
+
+        // If transport.[[State]] is "connecting", cleanup transport with an error.
+        if self.state.get() == WebTransportState::Connecting {
+            return self.cleanup(None, can_gc);
+        }
+
+        // Cleanup transport with closeInfo.
+        let close_info = WebTransportCloseInfo {
+            closeCode: close_info.closeCode,
+            reason: USVString(reason),
+        };
+        self.cleanup(Some(close_info), can_gc);
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-datagrams>
+    fn Datagrams(&self) -> DomRoot<WebTransportDatagramDuplexStream> {
+        self.datagrams()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-createbidirectionalstream>
+    fn CreateBidirectionalStream(&self, can_gc: CanGc) -> Rc<Promise> {
+        self.create_outgoing_stream(true, can_gc)
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-incomingbidirectionalstreams>
+    fn IncomingBidirectionalStreams(&self) -> DomRoot<ReadableStream> {
+        self.incoming_bidirectional_streams
+            .get()
+            .expect("A WebTransport always has incoming streams.")
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-createunidirectionalstream>
+    fn CreateUnidirectionalStream(&self, can_gc: CanGc) -> Rc<Promise> {
+        self.create_outgoing_stream(false, can_gc)
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransport-incomingunidirectionalstreams>
+    fn IncomingUnidirectionalStreams(&self) -> DomRoot<ReadableStream> {
+        self.incoming_unidirectional_streams
+            .get()
+            .expect("A WebTransport always has incoming streams.")
+    }
+}
+
+/// Task queued when the session of a [`WebTransport`] reports an event.
+struct NetworkEventTask {
+    address: Trusted<WebTransport>,
+    event: WebTransportNetworkEvent,
+}
+
+impl TaskOnce for NetworkEventTask {
+    fn run_once(self) {
+        let transport = self.address.root();
+        transport.handle_network_event(self.event, CanGc::note());
+    }
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::WebTransportBidirectionalStreamBinding::WebTransportBidirectionalStreamMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::writablestream::WritableStream;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/webtransport/#webtransportbidirectionalstream>
+#[dom_struct]
+pub(crate) struct WebTransportBidirectionalStream {
+    reflector_: Reflector,
+    readable: Dom<ReadableStream>,
+    writable: Dom<WritableStream>,
+}
+
+impl WebTransportBidirectionalStream {
+    fn new_inherited(
+        readable: &ReadableStream,
+        writable: &WritableStream,
+    ) -> WebTransportBidirectionalStream {
+        WebTransportBidirectionalStream {
+            reflector_: Reflector::new(),
+            readable: Dom::from_ref(readable),
+            writable: Dom::from_ref(writable),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        readable: &ReadableStream,
+        writable: &WritableStream,
+        can_gc: CanGc,
+    ) -> DomRoot<WebTransportBidirectionalStream> {
+        reflect_dom_object(
+            Box::new(WebTransportBidirectionalStream::new_inherited(
+                readable, writable,
+            )),
+            global,
+            can_gc,
+        )
+    }
+}
+
+impl WebTransportBidirectionalStreamMethods<crate::DomTypeHolder>
+    for WebTransportBidirectionalStream
+{
+    /// <https://w3c.github.io/webtransport/#dom-webtransportbidirectionalstream-readable>
+    fn Readable(&self) -> DomRoot<ReadableStream> {
+        self.readable.as_rooted()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransportbidirectionalstream-writable>
+    fn Writable(&self) -> DomRoot<WritableStream> {
+        self.writable.as_rooted()
+    }
+}
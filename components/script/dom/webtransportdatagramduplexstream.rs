@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::WebTransportDatagramDuplexStreamBinding::WebTransportDatagramDuplexStreamMethods;
+use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::readablestream::ReadableStream;
+use crate::dom::writablestream::WritableStream;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/webtransport/#webtransportdatagramduplexstream>
+#[dom_struct]
+pub(crate) struct WebTransportDatagramDuplexStream {
+    reflector_: Reflector,
+    readable: Dom<ReadableStream>,
+    writable: Dom<WritableStream>,
+    /// The largest datagram that can be sent, which is only known once the session is
+    /// established.
+    max_datagram_size: Cell<u32>,
+}
+
+impl WebTransportDatagramDuplexStream {
+    fn new_inherited(
+        readable: &ReadableStream,
+        writable: &WritableStream,
+    ) -> WebTransportDatagramDuplexStream {
+        WebTransportDatagramDuplexStream {
+            reflector_: Reflector::new(),
+            readable: Dom::from_ref(readable),
+            writable: Dom::from_ref(writable),
+            max_datagram_size: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        readable: &ReadableStream,
+        writable: &WritableStream,
+        can_gc: CanGc,
+    ) -> DomRoot<WebTransportDatagramDuplexStream> {
+        reflect_dom_object(
+            Box::new(WebTransportDatagramDuplexStream::new_inherited(
+                readable, writable,
+            )),
+            global,
+            can_gc,
+        )
+    }
+
+    pub(crate) fn readable(&self) -> &ReadableStream {
+        &self.readable
+    }
+
+    pub(crate) fn writable(&self) -> &WritableStream {
+        &self.writable
+    }
+
+    pub(crate) fn max_datagram_size(&self) -> u32 {
+        self.max_datagram_size.get()
+    }
+
+    pub(crate) fn set_max_datagram_size(&self, max_datagram_size: u32) {
+        self.max_datagram_size.set(max_datagram_size);
+    }
+}
+
+impl WebTransportDatagramDuplexStreamMethods<crate::DomTypeHolder>
+    for WebTransportDatagramDuplexStream
+{
+    /// <https://w3c.github.io/webtransport/#dom-webtransportdatagramduplexstream-readable>
+    fn Readable(&self) -> DomRoot<ReadableStream> {
+        self.readable.as_rooted()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransportdatagramduplexstream-writable>
+    fn Writable(&self) -> DomRoot<WritableStream> {
+        self.writable.as_rooted()
+    }
+
+    /// <https://w3c.github.io/webtransport/#dom-webtransportdatagramduplexstream-maxdatagramsize>
+    fn MaxDatagramSize(&self) -> u32 {
+        self.max_datagram_size.get()
+    }
+}
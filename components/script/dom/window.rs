@@ -21,15 +21,17 @@ use base64::Engine;
 #[cfg(feature = "bluetooth")]
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLChan;
-use constellation_traits::{ScrollState, WindowSizeData, WindowSizeType};
+use constellation_traits::{ScrollState, VisualViewportState, WindowSizeData, WindowSizeType};
 use crossbeam_channel::{Sender, unbounded};
 use cssparser::{Parser, ParserInput, SourceLocation};
 use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType};
 use dom_struct::dom_struct;
-use embedder_traits::user_content_manager::{UserContentManager, UserScript};
+use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    AlertResponse, ConfirmResponse, EmbedderMsg, PromptResponse, SimpleDialog, Theme,
-    WebDriverJSError, WebDriverJSResult,
+    AlertResponse, AnimationClockRequest, ConfirmResponse, EmbedderMsg, EmulatedMediaFeatures,
+    FindInPageRequest, FindInPageResult, ForcedColors, HoverCapability, NetworkStatus,
+    PointerCapability, PrefersReducedMotion, PromptResponse, ScreenTopology, SimpleDialog, Theme,
+    WebDriverJSError, WebDriverJSResult, WebDriverJSValue,
 };
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
 use euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
@@ -59,12 +61,14 @@ use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
 use script_bindings::interfaces::WindowHelpers;
 use script_layout_interface::{
-    FragmentType, Layout, PendingImageState, QueryMsg, Reflow, ReflowGoal, ReflowRequest,
-    TrustedNodeAddress, combine_id_with_fragment_type,
+    CaretLineDirection, FindInPageMatches, FindInPageQuery, FragmentType, Layout,
+    PendingImageState, QueryMsg, RangeNode, Reflow, ReflowGoal, ReflowRequest,
+    ScrollIntoViewGeometry, TrustedNodeAddress, combine_id_with_fragment_type,
+    node_id_from_scroll_id,
 };
 use script_traits::{
-    DocumentState, LoadData, LoadOrigin, NavigationHistoryBehavior, ScriptMsg, ScriptThreadMessage,
-    ScriptToConstellationChan, StructuredSerializedData,
+    DocumentState, LoadData, LoadOrigin, NavigationHistoryBehavior, SandboxingFlagSet, ScriptMsg,
+    ScriptThreadMessage, ScriptToConstellationChan, StructuredSerializedData,
 };
 use selectors::attr::CaseSensitivity;
 use servo_arc::Arc as ServoArc;
@@ -73,7 +77,7 @@ use servo_geometry::{DeviceIndependentIntRect, MaxRect, f32_rect_to_au_rect};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
-use style::media_queries;
+use style::media_queries::{self, MediaType};
 use style::parser::ParserContext as CssParserContext;
 use style::properties::PropertyId;
 use style::properties::style_structs::Font;
@@ -86,10 +90,12 @@ use stylo_atoms::Atom;
 use url::Position;
 use webrender_api::units::{DevicePixel, LayoutPixel};
 use webrender_api::{DocumentId, ExternalScrollId};
-use webrender_traits::CrossProcessCompositorApi;
+use webrender_traits::{CrossProcessCompositorApi, CrossProcessCompositorMessage, ScrollAnimation};
 
 use super::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
 use super::bindings::trace::HashMapTracedValues;
+use crate::caret_browsing::caret_browsing_selection;
+use crate::dom::audiocontext::AudioContext;
 use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState, NamedPropertyValue,
@@ -100,6 +106,10 @@ use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryList_Binding::MediaQueryListMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionStatus_Binding::PermissionStatusMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionDescriptor, PermissionName, PermissionState,
+};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -130,8 +140,11 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::htmlaudioelement::HTMLAudioElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
@@ -139,13 +152,19 @@ use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
 use crate::dom::node::{Node, NodeDamage, NodeTraits, from_untrusted_node_address};
 use crate::dom::performance::Performance;
+use crate::dom::permissions::{PermissionAlgorithm, Permissions};
+use crate::dom::permissionstatus::PermissionStatus;
+use crate::dom::portalactivateevent::PortalActivateEvent;
+use crate::dom::portalhost::PortalHost;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
+use crate::dom::screendetails::ScreenDetails;
 use crate::dom::selection::Selection;
 use crate::dom::storage::Storage;
 #[cfg(feature = "bluetooth")]
 use crate::dom::testrunner::TestRunner;
 use crate::dom::types::UIEvent;
+use crate::dom::visualviewport::VisualViewport;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
 #[cfg(feature = "webgpu")]
 use crate::dom::webgpu::identityhub::IdentityHub;
@@ -160,7 +179,7 @@ use crate::script_runtime::{CanGc, JSContext, Runtime};
 use crate::script_thread::ScriptThread;
 use crate::timers::{IsInterval, TimerCallback};
 use crate::unminify::unminified_path;
-use crate::webdriver_handlers::jsval_to_webdriver;
+use crate::webdriver_handlers::{find_node_in_document, jsval_to_webdriver};
 use crate::{fetch, window_named_properties};
 
 /// A callback to call when a response comes back from the `ImageCache`.
@@ -184,6 +203,9 @@ enum WindowState {
 /// assuming that `<body>` take this long to parse.
 const INITIAL_REFLOW_DELAY: Duration = Duration::from_millis(200);
 
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>
+const TRANSIENT_ACTIVATION_DURATION: Duration = Duration::from_secs(5);
+
 /// During loading and parsing, layouts are suppressed to avoid flashing incomplete page
 /// contents.
 ///
@@ -244,6 +266,20 @@ pub(crate) struct Window {
     #[no_trace]
     navigation_start: Cell<CrossProcessInstant>,
     screen: MutNullableDom<Screen>,
+    /// <https://w3c.github.io/window-management/#screendetails>
+    screen_details: MutNullableDom<ScreenDetails>,
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-visualviewport>
+    visual_viewport: MutNullableDom<VisualViewport>,
+    /// Whether this window's document is hosted by a `<portal>` element and has not been
+    /// activated yet.
+    is_portal: Cell<bool>,
+    /// Whether this window's document is prerendered, or hosted by a `<portal>` element, and
+    /// hidden from the user until it is activated.
+    ///
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#document-prerendering>
+    is_prerendering: Cell<bool>,
+    /// <https://wicg.github.io/portals/#dom-window-portalhost>
+    portal_host: MutNullableDom<PortalHost>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
     status: DomRefCell<DOMString>,
@@ -261,7 +297,45 @@ pub(crate) struct Window {
 
     /// Platform theme.
     #[no_trace]
-    theme: Cell<PrefersColorScheme>,
+    theme: Cell<Theme>,
+
+    /// The media features that the embedder emulates for this window's webview, which take
+    /// precedence over the platform values.
+    #[no_trace]
+    emulated_media_features: Cell<EmulatedMediaFeatures>,
+
+    /// Whether the values of media features changed for a reason other than a resize since
+    /// media queries were last evaluated.
+    media_environment_changed: Cell<bool>,
+
+    /// Whether the document is being printed, in which case it is laid out with print media.
+    printing: Cell<bool>,
+
+    /// The connectivity and quality of the network, as last reported by the embedder.
+    #[no_trace]
+    network_status: Cell<NetworkStatus>,
+
+    /// The origin of the top-level document of this window's browsing context, or `None` when
+    /// this window's browsing context is itself top-level.
+    #[no_trace]
+    top_level_origin: Option<ImmutableOrigin>,
+
+    /// The find-in-page search requested by the embedder, if one is in progress.
+    #[no_trace]
+    find_in_page_query: DomRefCell<Option<FindInPageQuery>>,
+
+    /// The state of the find-in-page search, as last reported to the embedder.
+    #[no_trace]
+    find_in_page_result: Cell<Option<FindInPageResult>>,
+
+    /// The bounding box of the active find-in-page match found by the last reflow.
+    #[no_trace]
+    find_in_page_active_match_rect: Cell<Option<UntypedRect<Au>>>,
+
+    /// The time of the last activation-triggering input event, used to compute
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>.
+    #[no_trace]
+    last_activation_timestamp: Cell<Option<Instant>>,
 
     /// Parent id associated with this page, if any.
     #[no_trace]
@@ -307,6 +381,11 @@ pub(crate) struct Window {
     #[no_trace]
     current_viewport: Cell<UntypedRect<Au>>,
 
+    /// Where the visual viewport of the top-level document is in its layout viewport, and how
+    /// far it is pinch-zoomed. This stays at its default for other documents.
+    #[no_trace]
+    visual_viewport_state: Cell<VisualViewportState>,
+
     error_reporter: CSSErrorReporter,
 
     /// A list of scroll offsets for each scrollable element.
@@ -316,6 +395,14 @@ pub(crate) struct Window {
     /// All the MediaQueryLists we need to update
     media_query_lists: DOMTracker<MediaQueryList>,
 
+    /// The audio and video elements created for a document of this window, whether they are in
+    /// the document or not, which are muted along with the webview.
+    audio_elements: DOMTracker<HTMLAudioElement>,
+    video_elements: DOMTracker<HTMLVideoElement>,
+
+    /// The audio contexts of this window, which are muted along with the webview.
+    audio_contexts: DOMTracker<AudioContext>,
+
     #[cfg(feature = "bluetooth")]
     test_runner: MutNullableDom<TestRunner>,
 
@@ -531,14 +618,32 @@ impl Window {
         Some(&self.error_reporter)
     }
 
-    /// Sets a new list of scroll offsets.
+    /// Sets a new list of scroll offsets, queuing a `scroll` event at the nodes that were
+    /// scrolled.
     ///
     /// This is called when layout gives us new ones and WebRender is in use.
+    #[allow(unsafe_code)]
     pub(crate) fn set_scroll_offsets(
         &self,
         offsets: HashMap<OpaqueNode, Vector2D<f32, LayoutPixel>>,
     ) {
-        *self.scroll_offsets.borrow_mut() = offsets
+        let scrolled_nodes: Vec<_> = {
+            let old_offsets = self.scroll_offsets.borrow();
+            offsets
+                .iter()
+                .filter(|(node, offset)| {
+                    old_offsets.get(node).copied().unwrap_or_default() != **offset
+                })
+                .map(|(node, _)| *node)
+                .collect()
+        };
+        *self.scroll_offsets.borrow_mut() = offsets;
+
+        let document = self.Document();
+        for node in scrolled_nodes {
+            let node = unsafe { from_untrusted_node_address(node.into()) };
+            document.queue_scroll_event(node.upcast());
+        }
     }
 
     pub(crate) fn current_viewport(&self) -> UntypedRect<Au> {
@@ -625,8 +730,8 @@ impl Window {
         &self.compositor_api
     }
 
-    pub(crate) fn userscripts(&self) -> &[UserScript] {
-        self.user_content_manager.scripts()
+    pub(crate) fn user_content_manager(&self) -> &UserContentManager {
+        &self.user_content_manager
     }
 
     pub(crate) fn get_player_context(&self) -> WindowGLContext {
@@ -730,6 +835,9 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-alert
     fn Alert(&self, s: DOMString) {
+        if self.cannot_show_simple_dialogs() {
+            return;
+        }
         // Print to the console.
         // Ensure that stderr doesn't trample through the alert() we use to
         // communicate test results (see executorservo.py in wptrunner).
@@ -755,6 +863,9 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-confirm
     fn Confirm(&self, s: DOMString) -> bool {
+        if self.cannot_show_simple_dialogs() {
+            return false;
+        }
         let (sender, receiver) =
             ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let dialog = SimpleDialog::Confirm {
@@ -768,6 +879,9 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-prompt
     fn Prompt(&self, message: DOMString, default: DOMString) -> Option<DOMString> {
+        if self.cannot_show_simple_dialogs() {
+            return None;
+        }
         let (sender, receiver) =
             ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let dialog = SimpleDialog::Prompt {
@@ -783,6 +897,31 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-print>
+    fn Print(&self, can_gc: CanGc) {
+        // Step 1. Let document be this's associated Document.
+        let document = self.Document();
+
+        // Step 2. If document is not fully active, then return.
+        if !document.is_fully_active() {
+            return;
+        }
+
+        // Step 3. If document's unload counter is greater than 0, then return.
+        if document.is_prompting_or_unloading() {
+            return;
+        }
+
+        // Step 4. If document is ready for post-load tasks, then run the printing steps for
+        // document.
+        // Step 5. Otherwise, set document's print when loaded flag.
+        if document.is_ready_for_post_load_tasks() {
+            self.run_printing_steps(can_gc);
+        } else {
+            document.set_print_when_loaded();
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-stop
     fn Stop(&self, can_gc: CanGc) {
         // TODO: Cancel ongoing navigation.
@@ -837,6 +976,7 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         if value.is_null() {
             if let Some(proxy) = self.window_proxy.get() {
                 proxy.disown();
+                self.send_to_constellation(ScriptMsg::DisownOpener);
             }
             return Ok(());
         }
@@ -1113,11 +1253,67 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
     // https://html.spec.whatwg.org/multipage/#windoweventhandlers
     window_event_handlers!();
 
+    // https://wicg.github.io/portals/#dom-window-onportalactivate
+    event_handler!(portalactivate, GetOnportalactivate, SetOnportalactivate);
+
+    /// <https://wicg.github.io/portals/#dom-window-portalhost>
+    fn GetPortalHost(&self) -> Option<DomRoot<PortalHost>> {
+        if !self.is_portal.get() {
+            return None;
+        }
+        Some(
+            self.portal_host
+                .or_init(|| PortalHost::new(self, CanGc::note())),
+        )
+    }
+
     // https://developer.mozilla.org/en-US/docs/Web/API/Window/screen
     fn Screen(&self) -> DomRoot<Screen> {
         self.screen.or_init(|| Screen::new(self, CanGc::note()))
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-visualviewport>
+    fn GetVisualViewport(&self) -> Option<DomRoot<VisualViewport>> {
+        if !self.Document().is_fully_active() {
+            return None;
+        }
+        Some(
+            self.visual_viewport
+                .or_init(|| VisualViewport::new(self, CanGc::note())),
+        )
+    }
+
+    /// <https://w3c.github.io/window-management/#dom-window-getscreendetails>
+    fn GetScreenDetails(&self, can_gc: CanGc) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), can_gc);
+        if !self.Document().is_fully_active() {
+            promise.reject_error(Error::InvalidState, can_gc);
+            return promise;
+        }
+
+        // Screens other than the one the window is on are only exposed with the user's consent.
+        let descriptor = PermissionDescriptor {
+            name: PermissionName::Window_management,
+        };
+        let status = PermissionStatus::new(&self.global(), &descriptor, can_gc);
+        Permissions::permission_request(GlobalScope::get_cx(), &promise, &descriptor, &status);
+        if status.State() != PermissionState::Granted {
+            promise.reject_error(Error::NotAllowed, can_gc);
+            return promise;
+        }
+
+        let screen_details = match self.screen_details.get() {
+            Some(screen_details) => screen_details,
+            None => {
+                let screen_details = ScreenDetails::new(self, self.screen_topology(), can_gc);
+                self.screen_details.set(Some(&screen_details));
+                screen_details
+            },
+        };
+        promise.resolve_native(&screen_details, can_gc);
+        promise
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -1235,6 +1431,16 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
         }
     }
 
+    fn WebdriverChannelMessage(&self, cx: JSContext, channel: DOMString, message: HandleValue) {
+        let message = unsafe { jsval_to_webdriver(*cx, &self.globalscope, message) }
+            .unwrap_or(WebDriverJSValue::Null);
+        self.send_to_constellation(ScriptMsg::WebDriverChannelMessage(channel.into(), message));
+    }
+
+    fn WebdriverNode(&self, id: DOMString) -> Fallible<DomRoot<Node>> {
+        find_node_in_document(&self.Document(), &id).ok_or(Error::NotFound)
+    }
+
     // https://drafts.csswg.org/cssom/#dom-window-getcomputedstyle
     fn GetComputedStyle(
         &self,
@@ -1253,6 +1459,18 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
             Some(ref pseudo) if pseudo == ":after" || pseudo == "::after" => {
                 Some(PseudoElement::After)
             },
+            Some(ref pseudo) if pseudo == "::selection" => Some(PseudoElement::Selection),
+            // Stylo does not support `::marker` and `::placeholder` rules yet, so these
+            // pseudo-elements have an empty style instead of the one of their element.
+            Some(ref pseudo) if pseudo == "::marker" || pseudo == "::placeholder" => {
+                return CSSStyleDeclaration::new(
+                    self,
+                    CSSStyleOwner::Null,
+                    None,
+                    CSSModificationAccess::Readonly,
+                    CanGc::note(),
+                );
+            },
             _ => None,
         };
 
@@ -1428,27 +1646,44 @@ impl WindowMethods<crate::DomTypeHolder> for Window {
 
     // https://drafts.csswg.org/cssom-view/#dom-window-matchmedia
     fn MatchMedia(&self, query: DOMString) -> DomRoot<MediaQueryList> {
-        let mut input = ParserInput::new(&query);
-        let mut parser = Parser::new(&mut input);
-        let url_data = UrlExtraData(self.get_url().get_arc());
-        let quirks_mode = self.Document().quirks_mode();
-        let context = CssParserContext::new(
-            Origin::Author,
-            &url_data,
-            Some(CssRuleType::Media),
-            ParsingMode::DEFAULT,
-            quirks_mode,
-            /* namespaces = */ Default::default(),
-            self.css_error_reporter(),
-            None,
-        );
-        let media_query_list = media_queries::MediaList::parse(&context, &mut parser);
+        let media_query_list = self.parse_media_query_list(&query);
         let document = self.Document();
-        let mql = MediaQueryList::new(&document, media_query_list, CanGc::note());
+        let mql = MediaQueryList::new(&document, query.into(), media_query_list, CanGc::note());
         self.media_query_lists.track(&*mql);
         mql
     }
 
+    /// Keep track of a media element that belongs to a document of this window, so that it is
+    /// muted along with the webview even when it is not in the document.
+    pub(crate) fn track_media_element(&self, element: &HTMLMediaElement) {
+        if let Some(audio_element) = element.downcast::<HTMLAudioElement>() {
+            self.audio_elements.track(audio_element);
+        } else if let Some(video_element) = element.downcast::<HTMLVideoElement>() {
+            self.video_elements.track(video_element);
+        }
+    }
+
+    /// Keep track of an audio context of this window, and mute it if the webview is muted.
+    pub(crate) fn track_audio_context(&self, audio_context: &AudioContext) {
+        self.audio_contexts.track(audio_context);
+        audio_context
+            .base()
+            .set_muted(ScriptThread::is_webview_muted(self.webview_id()));
+    }
+
+    /// Apply the muting of the webview to every media element and audio context of this
+    /// window.
+    pub(crate) fn update_audio_muting(&self) {
+        self.audio_elements
+            .for_each(|element| element.upcast::<HTMLMediaElement>().update_player_mute());
+        self.video_elements
+            .for_each(|element| element.upcast::<HTMLMediaElement>().update_player_mute());
+
+        let muted = ScriptThread::is_webview_muted(self.webview_id());
+        self.audio_contexts
+            .for_each(|audio_context| audio_context.base().set_muted(muted));
+    }
+
     // https://fetch.spec.whatwg.org/#fetch-method
     fn Fetch(
         &self,
@@ -1776,6 +2011,12 @@ impl Window {
         if let Some(performance) = self.performance.get() {
             performance.clear_and_disable_performance_entry_buffer();
         }
+
+        #[cfg(feature = "bluetooth")]
+        let _ = self
+            .bluetooth_thread
+            .send(BluetoothRequest::PipelineExited(self.pipeline_id()));
+
         self.as_global_scope()
             .task_manager()
             .cancel_all_tasks_and_ignore_future_tasks();
@@ -1831,30 +2072,113 @@ impl Window {
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
+        behavior: ScrollBehavior,
         _element: Option<&Element>,
         can_gc: CanGc,
     ) {
-        // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
+        // TODO Step 1: The `scroll-behavior` property of the element is not supported, so
+        // scrolls with an "auto" behavior are always instant.
+        let animation = match behavior {
+            ScrollBehavior::Smooth => ScrollAnimation::Smooth,
+            ScrollBehavior::Auto | ScrollBehavior::Instant => ScrollAnimation::Instant,
+        };
         self.reflow(
-            ReflowGoal::UpdateScrollNode(ScrollState {
-                scroll_id,
-                scroll_offset: Vector2D::new(-x, -y),
-            }),
+            ReflowGoal::UpdateScrollNode(
+                ScrollState {
+                    scroll_id,
+                    scroll_offset: Vector2D::new(-x, -y),
+                },
+                animation,
+            ),
             can_gc,
         );
     }
 
+    pub(crate) fn is_portal(&self) -> bool {
+        self.is_portal.get()
+    }
+
+    pub(crate) fn set_is_portal(&self, is_portal: bool) {
+        self.is_portal.set(is_portal);
+    }
+
+    pub(crate) fn set_is_prerendering(&self, is_prerendering: bool) {
+        self.is_prerendering.set(is_prerendering);
+    }
+
+    /// The `PortalHost` of this window, if its document is hosted by a `<portal>` element.
+    pub(crate) fn portal_host(&self) -> Option<DomRoot<PortalHost>> {
+        self.GetPortalHost()
+    }
+
+    /// Note that this window's document, which was hosted by a `<portal>` element, became the
+    /// active document of the top-level browsing context, and fire `portalactivate` with the
+    /// data given to `activate()`.
+    ///
+    /// <https://wicg.github.io/portals/#activate-a-portal-browsing-context>
+    pub(crate) fn activate_portal(&self, data: StructuredSerializedData) {
+        self.is_portal.set(false);
+        self.portal_host.set(None);
+
+        let this = Trusted::new(self);
+        let task = task!(fire_portalactivate_event: move || {
+            let this = this.root();
+            let cx = this.get_cx();
+            let obj = this.reflector().get_jsobject();
+            let _ac = JSAutoRealm::new(*cx, obj.get());
+            rooted!(in(*cx) let mut data_clone = UndefinedValue());
+            // If deserializing the data throws, the event's data is null.
+            if structuredclone::read(this.upcast(), data, data_clone.handle_mut()).is_err() {
+                data_clone.set(NullValue());
+            }
+            PortalActivateEvent::dispatch_jsval(&this, data_clone.handle(), CanGc::note());
+        });
+        self.as_global_scope()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task);
+    }
+
+    /// The [`VisualViewport`] of this window, if script has accessed it.
+    pub(crate) fn visual_viewport(&self) -> Option<DomRoot<VisualViewport>> {
+        self.visual_viewport.get()
+    }
+
+    pub(crate) fn visual_viewport_state(&self) -> VisualViewportState {
+        self.visual_viewport_state.get()
+    }
+
+    /// Handle the compositor pinch-zooming or panning the visual viewport.
+    pub(crate) fn set_visual_viewport_state(&self, new_state: VisualViewportState) {
+        let old_state = self.visual_viewport_state.replace(new_state);
+        if let Some(visual_viewport) = self.visual_viewport.get() {
+            visual_viewport.handle_state_change(old_state, new_state);
+        }
+    }
+
     pub(crate) fn update_viewport_for_scroll(&self, x: f32, y: f32) {
         let size = self.current_viewport.get().size;
         let new_viewport = Rect::new(Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)), size);
         self.current_viewport.set(new_viewport)
     }
 
+    /// The number of device pixels per CSS pixel, which is the emulated resolution if the
+    /// embedder set one.
     pub(crate) fn device_pixel_ratio(&self) -> Scale<f32, CSSPixel, DevicePixel> {
-        self.window_size.get().device_pixel_ratio
+        self.emulated_media_features
+            .get()
+            .resolution
+            .map(Scale::new)
+            .unwrap_or(self.window_size.get().device_pixel_ratio)
+    }
+
+    /// The size of the window that layout evaluates media queries against and lays out the
+    /// document for.
+    fn window_size_for_layout(&self) -> WindowSizeData {
+        WindowSizeData {
+            device_pixel_ratio: self.device_pixel_ratio(),
+            ..self.window_size.get()
+        }
     }
 
     fn client_window(&self) -> (Size2D<u32, CSSPixel>, Point2D<i32, CSSPixel>) {
@@ -1881,6 +2205,54 @@ impl Window {
         ScriptThread::handle_tick_all_animations_for_testing(self.pipeline_id());
     }
 
+    /// Pause, resume, step or set the clock of the animations and timers of this window, and
+    /// return the value of the animation timeline afterwards, in milliseconds.
+    pub(crate) fn handle_animation_clock_request(
+        &self,
+        request: AnimationClockRequest,
+        can_gc: CanGc,
+    ) -> f64 {
+        let document = self.Document();
+        let global = self.as_global_scope();
+        if let AnimationClockRequest::Step(time_ms) | AnimationClockRequest::SetTime(time_ms) =
+            request
+        {
+            if !time_ms.is_finite() {
+                warn!("Ignoring animation clock request for a time of {time_ms}ms.");
+                return document.current_animation_timeline_value() * 1000.;
+            }
+        }
+        match request {
+            AnimationClockRequest::Pause => {
+                document.pause_animation_timeline();
+                global.pause_timer_clock();
+            },
+            AnimationClockRequest::Resume => {
+                document.resume_animation_timeline();
+                global.resume_timer_clock();
+            },
+            AnimationClockRequest::Step(delta_ms) => {
+                let delta_ms = delta_ms.max(0.);
+                document.pause_animation_timeline();
+                global.pause_timer_clock();
+                document.advance_animation_timeline_for_testing(delta_ms / 1000.);
+                ScriptThread::handle_tick_all_animations_for_testing(self.pipeline_id());
+                global.advance_timer_clock(Duration::from_secs_f64(delta_ms / 1000.), can_gc);
+            },
+            AnimationClockRequest::SetTime(time_ms) => {
+                let delta_ms = time_ms - document.current_animation_timeline_value() * 1000.;
+                document.pause_animation_timeline();
+                global.pause_timer_clock();
+                document.set_animation_timeline_value(time_ms / 1000.);
+                ScriptThread::handle_tick_all_animations_for_testing(self.pipeline_id());
+                if delta_ms > 0. {
+                    global.advance_timer_clock(Duration::from_secs_f64(delta_ms / 1000.), can_gc);
+                }
+            },
+        }
+        document.current_animation_timeline_value() * 1000.
+    }
+
     /// Reflows the page unconditionally if possible and not suppressed. This method will wait for
     /// the layout to complete. If there is no window size yet, the page is presumed invisible and
     /// no reflow is performed. If reflow is suppressed, no reflow will be performed for ForDisplay
@@ -1945,6 +2317,9 @@ impl Window {
             document.flush_dirty_2d_canvases();
         }
 
+        // Inline `<svg>` elements are rendered from a serialization of their subtree.
+        document.flush_dirty_svg_roots(stylesheets_changed, CanGc::note());
+
         let pending_restyles = document.drain_pending_restyles();
 
         let dirty_root = document
@@ -1961,7 +2336,7 @@ impl Window {
             document: document.upcast::<Node>().to_trusted_node_address(),
             dirty_root,
             stylesheets_changed,
-            window_size: self.window_size.get(),
+            window_size: self.window_size_for_layout(),
             origin: self.origin().immutable().clone(),
             reflow_goal,
             dom_count: document.dom_count(),
@@ -1971,7 +2346,15 @@ impl Window {
             node_to_image_animation_map: document
                 .image_animation_manager_mut()
                 .take_image_animate_set(),
-            theme: self.theme.get(),
+            theme: self.prefers_color_scheme(),
+            forced_colors: self.forced_colors() == ForcedColors::Active,
+            media_type: self.media_type(),
+            find_in_page_query: self.find_in_page_query.borrow().clone(),
+            caret_browsing_selection: caret_browsing_selection(&document),
+            inspector_highlights: document.devtools_inspector_highlights(),
+            top_layer: document.top_layer_for_layout(),
+            auto_sized_images: document.auto_sized_images_for_layout(),
+            svg_roots: document.svg_roots_for_layout(),
         };
 
         let Some(results) = self.layout.borrow_mut().reflow(reflow) else {
@@ -2017,13 +2400,42 @@ impl Window {
         let size_messages = self
             .Document()
             .iframes_mut()
-            .handle_new_iframe_sizes_after_layout(results.iframe_sizes, self.device_pixel_ratio());
+            .handle_new_iframe_sizes_after_layout(results.iframe_sizes, self.window_size());
         if !size_messages.is_empty() {
             self.send_to_constellation(ScriptMsg::IFrameSizes(size_messages));
         }
         document
             .image_animation_manager_mut()
             .restore_image_animate_set(results.node_to_image_animation_map);
+        document.handle_auto_sized_image_widths(&results.auto_sized_image_widths);
+        document.handle_svg_root_sizes(&results.svg_root_sizes);
+
+        if let Some(find_in_page_matches) = results.find_in_page_matches {
+            self.handle_find_in_page_matches(find_in_page_matches);
+        }
+
+        for scroll_state in results.scroll_anchor_adjustments {
+            let scroll_offset = scroll_state.scroll_offset;
+            if scroll_state.scroll_id.is_root() {
+                self.update_viewport_for_scroll(-scroll_offset.x, -scroll_offset.y);
+            } else if let Some(node_id) = node_id_from_scroll_id(scroll_state.scroll_id.0 as usize)
+            {
+                self.scroll_offsets
+                    .borrow_mut()
+                    .insert(OpaqueNode(node_id), -scroll_offset);
+            }
+        }
+
+        // Only the accessibility tree of the top-level document is exposed to the embedder,
+        // as the trees of nested documents would need to be grafted into it.
+        if let Some(tree_update) = results.accessibility_tree_update {
+            if self.is_top_level() {
+                self.send_to_embedder(EmbedderMsg::AccessibilityTreeUpdate(
+                    self.webview_id(),
+                    tree_update,
+                ));
+            }
+        }
         document.update_animations_post_reflow();
         self.update_constellation_epoch();
 
@@ -2247,6 +2659,17 @@ impl Window {
         self.layout.borrow().query_content_boxes(node.to_opaque())
     }
 
+    pub(crate) fn range_client_rects_query(
+        &self,
+        nodes: &[RangeNode],
+        can_gc: CanGc,
+    ) -> Vec<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::ContentBoxes, can_gc) {
+            return vec![];
+        }
+        self.layout.borrow().query_range_client_rects(nodes)
+    }
+
     pub(crate) fn client_rect_query(&self, node: &Node, can_gc: CanGc) -> UntypedRect<i32> {
         if !self.layout_reflow(QueryMsg::ClientRectQuery, can_gc) {
             return Rect::zero();
@@ -2254,6 +2677,74 @@ impl Window {
         self.layout.borrow().query_client_rect(node.to_opaque())
     }
 
+    pub(crate) fn composition_rect_query(
+        &self,
+        node: &Node,
+        can_gc: CanGc,
+    ) -> Option<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::CompositionRectQuery, can_gc) {
+            return None;
+        }
+        self.layout
+            .borrow()
+            .query_composition_rect(node.to_opaque())
+    }
+
+    /// The area of a caret at the byte offset `offset` of the text of the text node `node`,
+    /// for caret browsing.
+    pub(crate) fn caret_rect_query(
+        &self,
+        node: &Node,
+        offset: usize,
+        can_gc: CanGc,
+    ) -> Option<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::CaretQuery, can_gc) {
+            return None;
+        }
+        self.layout
+            .borrow()
+            .query_caret_rect(node.to_opaque(), offset)
+    }
+
+    /// The position that a caret at the byte offset `offset` of the text of the text node
+    /// `node` moves to on the line above or below it, for caret browsing.
+    #[allow(unsafe_code)]
+    pub(crate) fn caret_in_adjacent_line_query(
+        &self,
+        node: &Node,
+        offset: usize,
+        direction: CaretLineDirection,
+        can_gc: CanGc,
+    ) -> Option<(DomRoot<Node>, usize)> {
+        if !self.layout_reflow(QueryMsg::CaretQuery, can_gc) {
+            return None;
+        }
+        let (address, offset) = self.layout.borrow().query_caret_in_adjacent_line(
+            node.to_opaque(),
+            offset,
+            direction,
+        )?;
+        Some((unsafe { from_untrusted_node_address(address) }, offset))
+    }
+
+    /// The position in the rendered text that is closest to `point`, relative to the initial
+    /// containing block, as a text node and a byte offset in its text.
+    #[allow(unsafe_code)]
+    pub(crate) fn caret_position_from_point_query(
+        &self,
+        point: UntypedPoint2D<f32>,
+        can_gc: CanGc,
+    ) -> Option<(DomRoot<Node>, usize)> {
+        if !self.layout_reflow(QueryMsg::CaretQuery, can_gc) {
+            return None;
+        }
+        let (address, offset) = self
+            .layout
+            .borrow()
+            .query_caret_position_from_point(point)?;
+        Some((unsafe { from_untrusted_node_address(address) }, offset))
+    }
+
     /// Find the scroll area of the given node, if it is not None. If the node
     /// is None, find the scroll area of the viewport.
     pub(crate) fn scrolling_area_query(
@@ -2268,6 +2759,35 @@ impl Window {
         self.layout.borrow().query_scrolling_area(opaque)
     }
 
+    /// The geometry needed to scroll the box of `node` into view, or `None` if it has no box.
+    pub(crate) fn scroll_into_view_query(
+        &self,
+        node: &Node,
+        can_gc: CanGc,
+    ) -> Option<ScrollIntoViewGeometry> {
+        if !self.layout_reflow(QueryMsg::ScrollIntoViewQuery, can_gc) {
+            return None;
+        }
+        self.layout
+            .borrow()
+            .query_scroll_into_view(node.to_opaque())
+    }
+
+    /// The geometry needed to scroll the selection of the focused text control `node` into
+    /// view, or its caret if nothing is selected.
+    pub(crate) fn text_control_selection_query(
+        &self,
+        node: &Node,
+        can_gc: CanGc,
+    ) -> Option<ScrollIntoViewGeometry> {
+        if !self.layout_reflow(QueryMsg::ScrollIntoViewQuery, can_gc) {
+            return None;
+        }
+        self.layout
+            .borrow()
+            .query_text_control_selection(node.to_opaque())
+    }
+
     pub(crate) fn scroll_offset_query(&self, node: &Node) -> Vector2D<f32, LayoutPixel> {
         if let Some(scroll_offset) = self.scroll_offsets.borrow().get(&node.to_opaque()) {
             return *scroll_offset;
@@ -2382,6 +2902,12 @@ impl Window {
         self.window_proxy.set(Some(window_proxy));
     }
 
+    /// Move this window into another browsing context, which happens when a prerendered document
+    /// is activated. The new `WindowProxy` points at this window once it is resumed.
+    pub(crate) fn set_window_proxy(&self, window_proxy: &WindowProxy) {
+        self.window_proxy.set(Some(window_proxy));
+    }
+
     #[allow(unsafe_code)]
     pub(crate) fn init_document(&self, document: &Document) {
         assert!(self.document.get().is_none());
@@ -2508,18 +3034,365 @@ impl Window {
         self.window_size.get()
     }
 
-    /// Handle a theme change request, triggering a reflow is any actual change occured.
-    pub(crate) fn handle_theme_change(&self, new_theme: Theme) {
-        let new_theme = match new_theme {
+    /// The value of the `prefers-color-scheme` media feature, which is the emulated one if
+    /// the embedder set one and the platform theme otherwise.
+    fn prefers_color_scheme(&self) -> PrefersColorScheme {
+        match self.color_scheme_theme() {
             Theme::Light => PrefersColorScheme::Light,
             Theme::Dark => PrefersColorScheme::Dark,
-        };
+        }
+    }
 
-        if self.theme.get() == new_theme {
-            return;
+    fn color_scheme_theme(&self) -> Theme {
+        self.emulated_media_features
+            .get()
+            .prefers_color_scheme
+            .unwrap_or(self.theme.get())
+    }
+
+    /// The value of the `prefers-reduced-motion` media feature.
+    pub(crate) fn prefers_reduced_motion(&self) -> PrefersReducedMotion {
+        self.emulated_media_features
+            .get()
+            .prefers_reduced_motion
+            .unwrap_or(PrefersReducedMotion::NoPreference)
+    }
+
+    /// The value of the `hover` and `any-hover` media features.
+    pub(crate) fn hover_capability(&self) -> HoverCapability {
+        self.emulated_media_features
+            .get()
+            .hover
+            .unwrap_or(HoverCapability::Hover)
+    }
+
+    /// The value of the `pointer` and `any-pointer` media features.
+    pub(crate) fn pointer_capability(&self) -> PointerCapability {
+        self.emulated_media_features
+            .get()
+            .pointer
+            .unwrap_or(PointerCapability::Fine)
+    }
+
+    /// The value of the `forced-colors` media feature.
+    pub(crate) fn forced_colors(&self) -> ForcedColors {
+        self.emulated_media_features
+            .get()
+            .forced_colors
+            .unwrap_or(ForcedColors::None)
+    }
+
+    /// The values of all the media features that the embedder can emulate, whether it does
+    /// or not.
+    fn media_feature_values(&self) -> EmulatedMediaFeatures {
+        EmulatedMediaFeatures {
+            prefers_color_scheme: Some(self.color_scheme_theme()),
+            prefers_reduced_motion: Some(self.prefers_reduced_motion()),
+            hover: Some(self.hover_capability()),
+            pointer: Some(self.pointer_capability()),
+            forced_colors: Some(self.forced_colors()),
+            resolution: Some(self.device_pixel_ratio().get()),
         }
+    }
+
+    /// Handle a theme change request, triggering a reflow is any actual change occured.
+    pub(crate) fn handle_theme_change(&self, new_theme: Theme) {
+        let old_values = self.media_feature_values();
         self.theme.set(new_theme);
+        self.media_features_did_change(old_values);
+    }
+
+    /// Handle a change to the media features that the embedder emulates, triggering a reflow
+    /// and the evaluation of media queries if any media feature actually changed.
+    pub(crate) fn set_emulated_media_features(&self, features: EmulatedMediaFeatures) {
+        let old_values = self.media_feature_values();
+        self.emulated_media_features.set(features);
+        self.media_features_did_change(old_values);
+    }
+
+    fn media_features_did_change(&self, old_values: EmulatedMediaFeatures) {
+        if self.media_feature_values() == old_values {
+            return;
+        }
+        self.media_environment_changed.set(true);
+        self.Document().set_needs_paint(true);
+    }
+
+    /// Parse a media query list in the context of the document of this window.
+    pub(crate) fn parse_media_query_list(&self, query: &str) -> media_queries::MediaList {
+        let mut input = ParserInput::new(query);
+        let mut parser = Parser::new(&mut input);
+        let url_data = UrlExtraData(self.get_url().get_arc());
+        let quirks_mode = self.Document().quirks_mode();
+        let context = CssParserContext::new(
+            Origin::Author,
+            &url_data,
+            Some(CssRuleType::Media),
+            ParsingMode::DEFAULT,
+            quirks_mode,
+            /* namespaces = */ Default::default(),
+            self.css_error_reporter(),
+            None,
+        );
+        media_queries::MediaList::parse(&context, &mut parser)
+    }
+
+    /// The media type that media queries are evaluated against.
+    fn media_type(&self) -> MediaType {
+        if self.printing.get() {
+            MediaType::print()
+        } else {
+            MediaType::screen()
+        }
+    }
+
+    /// Lay out the document with print media while it is being printed, and with screen media
+    /// otherwise.
+    fn set_printing(&self, printing: bool, can_gc: CanGc) {
+        if self.printing.replace(printing) == printing {
+            return;
+        }
+        self.media_environment_changed.set(true);
         self.Document().set_needs_paint(true);
+        self.reflow(ReflowGoal::UpdateTheRendering, can_gc);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#printing-steps>
+    pub(crate) fn run_printing_steps(&self, can_gc: CanGc) {
+        // Step 1. If the active sandboxing flag set of document has the sandboxed modals flag
+        // set, then return.
+        if self.cannot_show_simple_dialogs() {
+            return;
+        }
+
+        // Step 2. Fire an event named beforeprint at the relevant global object of document.
+        // TODO: Also fire it at the windows of the child navigables of document.
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("beforeprint"), can_gc);
+
+        // Step 3. Offer the user the opportunity to obtain a physical form of document, using
+        // the state of document as it is at this point. The embedder captures the document,
+        // which is laid out with print media, and script waits until it is done.
+        // TODO: Layout does not fragment the document into pages yet, so it is printed as a
+        // single continuous page.
+        self.set_printing(true, can_gc);
+        let (sender, receiver) =
+            ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.send_to_embedder(EmbedderMsg::ShowPrintDialog(self.webview_id(), sender));
+        if let Err(error) = receiver.recv() {
+            warn!("Error waiting for the embedder to print: {error:?}");
+        }
+        self.set_printing(false, can_gc);
+
+        // Step 4. Fire an event named afterprint at the relevant global object of document.
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("afterprint"), can_gc);
+    }
+
+    /// Whether the values of media features changed since the last call, for a reason other
+    /// than a resize of the viewport.
+    pub(crate) fn take_media_environment_change(&self) -> bool {
+        self.media_environment_changed.replace(false)
+    }
+
+    pub(crate) fn top_level_origin(&self) -> Option<ImmutableOrigin> {
+        self.top_level_origin.clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    pub(crate) fn notify_activation(&self) {
+        self.last_activation_timestamp.set(Some(Instant::now()));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    pub(crate) fn has_transient_activation(&self) -> bool {
+        self.last_activation_timestamp
+            .get()
+            .is_some_and(|timestamp| timestamp.elapsed() < TRANSIENT_ACTIVATION_DURATION)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#cannot-show-simple-dialogs>
+    fn cannot_show_simple_dialogs(&self) -> bool {
+        // Prerendered documents cannot show dialogs, since the user cannot see them.
+        // <https://wicg.github.io/nav-speculation/prerendering.html#patch-modals>
+        self.is_prerendering.get() ||
+            self.Document()
+                .has_active_sandboxing_flag(SandboxingFlagSet::MODALS)
+    }
+
+    pub(crate) fn network_status(&self) -> NetworkStatus {
+        self.network_status.get()
+    }
+
+    /// Handle a change to the network, firing the `online` or `offline` event at the window
+    /// (<https://html.spec.whatwg.org/multipage/#dom-navigator-online>) and the `change` event
+    /// at `navigator.connection` (<https://wicg.github.io/netinfo/#handling-changes-to-the-underlying-connection>).
+    pub(crate) fn handle_network_status_change(&self, network_status: NetworkStatus) {
+        let old_network_status = self.network_status.replace(network_status);
+        let online_changed = old_network_status.online != network_status.online;
+        let connection_changed = NetworkStatus {
+            online: network_status.online,
+            ..old_network_status
+        } != network_status;
+        if !online_changed && !connection_changed {
+            return;
+        }
+
+        let this = Trusted::new(self);
+        let task = task!(network_status_change: move || {
+            let window = this.root();
+            if online_changed {
+                let name = match window.network_status().online {
+                    true => "online",
+                    false => "offline",
+                };
+                window
+                    .upcast::<EventTarget>()
+                    .fire_event(Atom::from(name), CanGc::note());
+            }
+            let connection = window
+                .navigator
+                .get()
+                .and_then(|navigator| navigator.connection());
+            if let Some(connection) = connection.filter(|_| connection_changed) {
+                connection
+                    .upcast::<EventTarget>()
+                    .fire_event(atom!("change"), CanGc::note());
+            }
+        });
+        self.as_global_scope()
+            .task_manager()
+            .networking_task_source()
+            .queue(task);
+    }
+
+    /// Handle a find-in-page request from the embedder, updating the highlighted matches and
+    /// scrolling the active match into view.
+    pub(crate) fn handle_find_in_page_request(&self, request: FindInPageRequest, can_gc: CanGc) {
+        let result = self.find_in_page_result.get().unwrap_or_default();
+        let scroll_to_active_match = {
+            let mut query = self.find_in_page_query.borrow_mut();
+            match request {
+                FindInPageRequest::Start(text, _) if text.is_empty() => {
+                    *query = None;
+                    false
+                },
+                FindInPageRequest::Start(text, options) => {
+                    // Keep the index of the active match, so that it moves as little as
+                    // possible while the user types.
+                    let active_match_index = result.active_match_index.unwrap_or_default();
+                    *query = Some(FindInPageQuery {
+                        text,
+                        case_sensitive: options.case_sensitive,
+                        active_match_index,
+                    });
+                    true
+                },
+                FindInPageRequest::Next | FindInPageRequest::Previous => {
+                    let (Some(query), Some(active_match_index)) =
+                        (query.as_mut(), result.active_match_index)
+                    else {
+                        return;
+                    };
+                    let number_of_matches = result.number_of_matches;
+                    query.active_match_index = match request {
+                        FindInPageRequest::Next => (active_match_index + 1) % number_of_matches,
+                        _ => (active_match_index + number_of_matches - 1) % number_of_matches,
+                    };
+                    true
+                },
+                FindInPageRequest::Stop => {
+                    *query = None;
+                    false
+                },
+            }
+        };
+
+        if self.find_in_page_query.borrow().is_none() {
+            self.find_in_page_active_match_rect.set(None);
+            if self.find_in_page_result.take().is_some() {
+                self.send_to_embedder(EmbedderMsg::FindInPageResult(
+                    self.webview_id(),
+                    FindInPageResult::default(),
+                ));
+            }
+        }
+
+        // Update the rendering right away, so that the matches are highlighted and counted.
+        self.Document().set_needs_paint(true);
+        self.reflow(ReflowGoal::UpdateTheRendering, can_gc);
+
+        if scroll_to_active_match {
+            self.scroll_find_in_page_match_into_view(can_gc);
+        }
+    }
+
+    /// Record the find-in-page matches found by layout, notifying the embedder if the number
+    /// of matches or the active match changed.
+    fn handle_find_in_page_matches(&self, matches: FindInPageMatches) {
+        self.find_in_page_active_match_rect
+            .set(matches.active_match_rect);
+        let result = FindInPageResult {
+            number_of_matches: matches.number_of_matches,
+            active_match_index: matches.active_match_index,
+        };
+        if self.find_in_page_result.replace(Some(result)) != Some(result) {
+            self.send_to_embedder(EmbedderMsg::FindInPageResult(self.webview_id(), result));
+        }
+    }
+
+    /// Scroll the viewport so that the active find-in-page match is centered in it, unless
+    /// the match is already entirely visible.
+    fn scroll_find_in_page_match_into_view(&self, can_gc: CanGc) {
+        if let Some(rect) = self.find_in_page_active_match_rect.get() {
+            self.scroll_rect_into_view(rect, can_gc);
+        }
+    }
+
+    /// Scroll the viewport so that `rect`, in document coordinates, is centered in it,
+    /// unless it is already entirely visible.
+    pub(crate) fn scroll_rect_into_view(&self, rect: UntypedRect<Au>, can_gc: CanGc) {
+        let viewport = self.window_size.get().initial_viewport;
+        let scroll_position = |start: Au, size: Au, scroll: i32, viewport_size: f32| {
+            let (start, size) = (start.to_f64_px(), size.to_f64_px());
+            let (scroll, viewport_size) = (scroll as f64, viewport_size as f64);
+            if start >= scroll && start + size <= scroll + viewport_size {
+                return scroll;
+            }
+            start + (size - viewport_size) / 2.
+        };
+        let x = scroll_position(
+            rect.origin.x,
+            rect.size.width,
+            self.ScrollX(),
+            viewport.width,
+        );
+        let y = scroll_position(
+            rect.origin.y,
+            rect.size.height,
+            self.ScrollY(),
+            viewport.height,
+        );
+        self.scroll(x, y, ScrollBehavior::Auto, can_gc);
+    }
+
+    /// Ask the compositor for the current arrangement of screens connected to the system.
+    pub(crate) fn screen_topology(&self) -> ScreenTopology {
+        let (sender, receiver) =
+            ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
+        self.compositor_api()
+            .sender()
+            .send(CrossProcessCompositorMessage::GetScreenTopology(sender))
+            .unwrap();
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// Handle a change to the screens connected to the system, updating the
+    /// [`ScreenDetails`] exposed to script, if any.
+    pub(crate) fn handle_screens_changed(&self, can_gc: CanGc) {
+        if let Some(screen_details) = self.screen_details.get() {
+            screen_details.handle_screens_changed(self.screen_topology(), can_gc);
+        }
     }
 
     pub(crate) fn get_url(&self) -> ServoUrl {
@@ -2564,7 +3437,7 @@ impl Window {
         self.page_clip_rect.set(proposed_clip_rect);
 
         // The document needs to be repainted, because the initial containing block
-        // is now a different size.
+        // is now a different size, or its contents are rasterized at a different scale.
         self.Document().set_needs_paint(true);
 
         // If we didn't have a clip rect, the previous display doesn't need rebuilding
@@ -2662,10 +3535,19 @@ impl Window {
             self.pipeline_id(),
             self.window_size(),
         );
+        // When only the device pixel ratio changed, for instance because the window moved to a
+        // screen with a different pixel density, the viewport keeps its size in CSS pixels and
+        // is not resized. The document is still laid out and painted again at the new ratio.
+        let viewport_resized = self.window_size().initial_viewport != new_size.initial_viewport;
         self.set_window_size(new_size);
+        if viewport_resized {
+            if let Some(visual_viewport) = self.visual_viewport.get() {
+                visual_viewport.handle_layout_viewport_resize();
+            }
+        }
 
         // http://dev.w3.org/csswg/cssom-view/#resizing-viewports
-        if size_type == WindowSizeType::Resize {
+        if viewport_resized && size_type == WindowSizeType::Resize {
             let uievent = UIEvent::new(
                 self,
                 DOMString::from("resize"),
@@ -2679,7 +3561,7 @@ impl Window {
         }
 
         // The document needs to be repainted, because the initial containing block
-        // is now a different size.
+        // is now a different size, or its contents are rasterized at a different scale.
         self.Document().set_needs_paint(true);
 
         true
@@ -2688,6 +3570,17 @@ impl Window {
     /// Evaluate media query lists and report changes
     /// <https://drafts.csswg.org/cssom-view/#evaluate-media-queries-and-report-changes>
     pub(crate) fn evaluate_media_queries_and_report_changes(&self, can_gc: CanGc) {
+        // Media queries are evaluated against the device of layout, which has to be told about
+        // the new viewport size and media features first.
+        let document = self.Document();
+        self.layout_mut().update_media_environment(
+            document.style_shared_lock(),
+            self.window_size_for_layout(),
+            self.prefers_color_scheme(),
+            self.forced_colors() == ForcedColors::Active,
+            self.media_type(),
+        );
+
         let _realm = enter_realm(self);
 
         rooted_vec!(let mut mql_list);
@@ -2806,8 +3699,12 @@ impl Window {
         user_content_manager: UserContentManager,
         user_agent: Cow<'static, str>,
         player_context: WindowGLContext,
+        network_status: NetworkStatus,
+        theme: Theme,
+        emulated_media_features: EmulatedMediaFeatures,
         #[cfg(feature = "webgpu")] gpu_id_hub: Arc<IdentityHub>,
         inherited_secure_context: Option<bool>,
+        top_level_origin: Option<ImmutableOrigin>,
     ) -> DomRoot<Self> {
         let error_reporter = CSSErrorReporter {
             pipelineid: pipeline_id,
@@ -2851,6 +3748,11 @@ impl Window {
             performance: Default::default(),
             navigation_start: Cell::new(navigation_start),
             screen: Default::default(),
+            screen_details: Default::default(),
+            visual_viewport: Default::default(),
+            is_portal: Default::default(),
+            is_prerendering: Default::default(),
+            portal_host: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
             status: DomRefCell::new(DOMString::new()),
@@ -2865,6 +3767,7 @@ impl Window {
             unhandled_resize_event: Default::default(),
             window_size: Cell::new(window_size),
             current_viewport: Cell::new(initial_viewport.to_untyped()),
+            visual_viewport_state: Default::default(),
             layout_blocker: Cell::new(LayoutBlocker::WaitingForParse),
             current_state: Cell::new(WindowState::Alive),
             devtools_marker_sender: Default::default(),
@@ -2873,6 +3776,9 @@ impl Window {
             error_reporter,
             scroll_offsets: Default::default(),
             media_query_lists: DOMTracker::new(),
+            audio_elements: DOMTracker::new(),
+            video_elements: DOMTracker::new(),
+            audio_contexts: DOMTracker::new(),
             #[cfg(feature = "bluetooth")]
             test_runner: Default::default(),
             webgl_chan,
@@ -2895,7 +3801,16 @@ impl Window {
             throttled: Cell::new(false),
             layout_marker: DomRefCell::new(Rc::new(Cell::new(true))),
             current_event: DomRefCell::new(None),
-            theme: Cell::new(PrefersColorScheme::Light),
+            theme: Cell::new(theme),
+            emulated_media_features: Cell::new(emulated_media_features),
+            media_environment_changed: Cell::new(false),
+            printing: Cell::new(false),
+            network_status: Cell::new(network_status),
+            top_level_origin,
+            find_in_page_query: Default::default(),
+            find_in_page_result: Default::default(),
+            find_in_page_active_match_rect: Default::default(),
+            last_activation_timestamp: Cell::new(None),
         });
 
         unsafe {
@@ -2978,13 +3893,16 @@ fn should_move_clip_rect(clip_rect: UntypedRect<Au>, new_viewport: UntypedRect<f
 fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal) {
     let goal_string = match *reflow_goal {
         ReflowGoal::UpdateTheRendering => "\tFull",
-        ReflowGoal::UpdateScrollNode(_) => "\tUpdateScrollNode",
+        ReflowGoal::UpdateScrollNode(..) => "\tUpdateScrollNode",
         ReflowGoal::LayoutQuery(ref query_msg) => match *query_msg {
             QueryMsg::ContentBox => "\tContentBoxQuery",
             QueryMsg::ContentBoxes => "\tContentBoxesQuery",
             QueryMsg::NodesFromPointQuery => "\tNodesFromPointQuery",
             QueryMsg::ClientRectQuery => "\tClientRectQuery",
+            QueryMsg::CompositionRectQuery => "\tCompositionRectQuery",
+            QueryMsg::CaretQuery => "\tCaretQuery",
             QueryMsg::ScrollingAreaQuery => "\tNodeScrollGeometryQuery",
+            QueryMsg::ScrollIntoViewQuery => "\tScrollIntoViewQuery",
             QueryMsg::ResolvedStyleQuery => "\tResolvedStyleQuery",
             QueryMsg::ResolvedFontStyleQuery => "\nResolvedFontStyleQuery",
             QueryMsg::OffsetParentQuery => "\tOffsetParentQuery",
@@ -7,6 +7,7 @@ use std::ptr;
 
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use dom_struct::dom_struct;
+use embedder_traits::AuxiliaryWebViewFeatures;
 use html5ever::local_name;
 use indexmap::map::IndexMap;
 use ipc_channel::ipc;
@@ -31,7 +32,7 @@ use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use net_traits::request::Referrer;
 use script_traits::{
     AuxiliaryWebViewCreationRequest, LoadData, LoadOrigin, NavigationHistoryBehavior,
-    NewLayoutInfo, ScriptMsg,
+    NewLayoutInfo, SandboxingFlagSet, ScriptMsg,
 };
 use serde::{Deserialize, Serialize};
 use servo_url::{ImmutableOrigin, ServoUrl};
@@ -283,6 +284,7 @@ impl WindowProxy {
         &self,
         name: DOMString,
         noopener: bool,
+        mut features: AuxiliaryWebViewFeatures,
     ) -> Option<DomRoot<WindowProxy>> {
         let (response_sender, response_receiver) = ipc::channel().unwrap();
         let window = self
@@ -291,6 +293,7 @@ impl WindowProxy {
             .and_then(ScriptThread::find_document)
             .map(|doc| DomRoot::from_ref(doc.window()))
             .unwrap();
+        features.user_activated = window.has_transient_activation();
 
         let document = self
             .currently_active
@@ -298,7 +301,7 @@ impl WindowProxy {
             .and_then(ScriptThread::find_document)
             .expect("A WindowProxy creating an auxiliary to have an active document");
         let blank_url = ServoUrl::parse("about:blank").ok().unwrap();
-        let load_data = LoadData::new(
+        let mut load_data = LoadData::new(
             LoadOrigin::Script(document.origin().immutable().clone()),
             blank_url,
             None,
@@ -307,10 +310,20 @@ impl WindowProxy {
             None, // Doesn't inherit secure context
             None,
         );
+        // Popups opened from a document sandboxed without `allow-popups-to-escape-sandbox`
+        // inherit its sandboxing flags.
+        let sandboxing_flag_set = document.active_sandboxing_flag_set();
+        if sandboxing_flag_set
+            .contains(SandboxingFlagSet::PROPAGATES_TO_AUXILIARY_BROWSING_CONTEXTS)
+        {
+            load_data.sandboxing_flag_set = sandboxing_flag_set;
+        }
         let load_info = AuxiliaryWebViewCreationRequest {
             load_data: load_data.clone(),
             opener_webview_id: window.webview_id(),
             opener_pipeline_id: self.currently_active.get().unwrap(),
+            features,
+            noopener,
             response_sender,
         };
         let constellation_msg = ScriptMsg::CreateAuxiliaryWebView(load_info);
@@ -323,7 +336,7 @@ impl WindowProxy {
             new_pipeline_id: response.new_pipeline_id,
             browsing_context_id: new_browsing_context_id,
             webview_id: response.new_webview_id,
-            opener: Some(self.browsing_context_id),
+            opener: (!noopener).then_some(self.browsing_context_id),
             load_data,
             window_size: window.window_size(),
         };
@@ -335,9 +348,6 @@ impl WindowProxy {
         if name.to_lowercase() != "_blank" {
             new_window_proxy.set_name(name);
         }
-        if noopener {
-            new_window_proxy.disown();
-        }
         Some(new_window_proxy)
     }
 
@@ -468,18 +478,21 @@ impl WindowProxy {
         // Step 5
         let tokenized_features = tokenize_open_features(features);
         // Step 7-9
-        let noreferrer = parse_open_feature_boolean(&tokenized_features, "noreferrer");
+        let noreferrer = check_if_open_feature_is_set(&tokenized_features, "noreferrer", false);
         let noopener = if noreferrer {
             true
         } else {
-            parse_open_feature_boolean(&tokenized_features, "noopener")
+            check_if_open_feature_is_set(&tokenized_features, "noopener", false)
         };
+        // Step 12, the features are handed to the embedder, which sets up the new browsing
+        // context when it is created below.
+        let features = parse_auxiliary_webview_features(&tokenized_features);
         // Step 10, 11
-        let (chosen, new) = match self.choose_browsing_context(non_empty_target, noopener) {
+        let (chosen, new) = match self.choose_browsing_context(non_empty_target, noopener, features)
+        {
             (Some(chosen), new) => (chosen, new),
             (None, _) => return Ok(None),
         };
-        // TODO Step 12, set up browsing context features.
         let target_document = match chosen.document() {
             Some(target_document) => target_document,
             None => return Ok(None),
@@ -526,7 +539,8 @@ impl WindowProxy {
             target_window.load_url(history_handling, false, load_data, can_gc);
         }
         if noopener {
-            // Step 15 (Dis-owning has been done in create_auxiliary_browsing_context).
+            // Step 15 (the new browsing context was created without an opener in
+            // create_auxiliary_browsing_context).
             return Ok(None);
         }
         // Step 17.
@@ -538,35 +552,118 @@ impl WindowProxy {
         &self,
         name: DOMString,
         noopener: bool,
+        features: AuxiliaryWebViewFeatures,
     ) -> (Option<DomRoot<WindowProxy>>, bool) {
-        match name.to_lowercase().as_ref() {
+        let chosen = match name.to_lowercase().as_ref() {
             "" | "_self" => {
                 // Step 3.
-                (Some(DomRoot::from_ref(self)), false)
+                return (Some(DomRoot::from_ref(self)), false);
             },
             "_parent" => {
                 // Step 4
-                if let Some(parent) = self.parent() {
-                    return (Some(DomRoot::from_ref(parent)), false);
+                match self.parent() {
+                    Some(parent) => DomRoot::from_ref(parent),
+                    None => return (None, false),
                 }
-                (None, false)
             },
             "_top" => {
                 // Step 5
-                (Some(DomRoot::from_ref(self.top())), false)
+                DomRoot::from_ref(self.top())
+            },
+            "_blank" => {
+                return self.create_auxiliary_browsing_context_if_allowed(name, noopener, features);
             },
-            "_blank" => (self.create_auxiliary_browsing_context(name, noopener), true),
             _ => {
                 // Step 6.
                 // TODO: expand the search to all 'familiar' bc,
                 // including auxiliaries familiar by way of their opener.
                 // See https://html.spec.whatwg.org/multipage/#familiar-with
                 match ScriptThread::find_window_proxy_by_name(&name) {
-                    Some(proxy) => (Some(proxy), false),
-                    None => (self.create_auxiliary_browsing_context(name, noopener), true),
+                    Some(proxy) => proxy,
+                    None => {
+                        return self.create_auxiliary_browsing_context_if_allowed(
+                            name, noopener, features,
+                        );
+                    },
                 }
             },
+        };
+        if !self.allowed_by_sandboxing_to_navigate(&chosen) {
+            return (None, false);
+        }
+        (Some(chosen), false)
+    }
+
+    /// Step 8 of <https://html.spec.whatwg.org/multipage/#the-rules-for-choosing-a-navigable>:
+    /// documents sandboxed without `allow-popups` can't create auxiliary browsing contexts.
+    fn create_auxiliary_browsing_context_if_allowed(
+        &self,
+        name: DOMString,
+        noopener: bool,
+        features: AuxiliaryWebViewFeatures,
+    ) -> (Option<DomRoot<WindowProxy>>, bool) {
+        if self.document().is_some_and(|document| {
+            document.has_active_sandboxing_flag(SandboxingFlagSet::AUXILIARY_NAVIGATION)
+        }) {
+            warn!("Blocked a popup from a document sandboxed without allow-popups");
+            return (None, false);
+        }
+        (
+            self.create_auxiliary_browsing_context(name, noopener, features),
+            true,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#allowed-by-sandboxing-to-navigate>
+    pub(crate) fn allowed_by_sandboxing_to_navigate(&self, target: &WindowProxy) -> bool {
+        let Some(document) = self.document() else {
+            return true;
+        };
+        let is_same = self.browsing_context_id == target.browsing_context_id;
+        let is_top_level = target.parent().is_none();
+
+        // Step 1. If A is not the same navigable as B, and A is not one of the ancestor
+        // navigables of B, and B is not a top-level traversable, and A's active document's active
+        // sandboxing flag set has its sandboxed navigation browsing context flag set, then return
+        // false.
+        let is_ancestor = || {
+            let mut current = target.parent();
+            while let Some(ancestor) = current {
+                if ancestor.browsing_context_id == self.browsing_context_id {
+                    return true;
+                }
+                current = ancestor.parent();
+            }
+            false
+        };
+        if !is_same &&
+            !is_top_level &&
+            !is_ancestor() &&
+            document.has_active_sandboxing_flag(SandboxingFlagSet::NAVIGATION)
+        {
+            return false;
+        }
+
+        // Step 2. If B is a top-level traversable, then:
+        if is_top_level && !is_same {
+            // Step 2.2. If A's active window has transient activation and A's active document's
+            // active sandboxing flag set has its sandboxed top-level navigation with user
+            // activation browsing context flag set, then return false.
+            // Step 2.3. If A's active window does not have transient activation and A's active
+            // document's active sandboxing flag set has its sandboxed top-level navigation
+            // without user activation browsing context flag set, then return false.
+            let flag = if document.window().has_transient_activation() {
+                SandboxingFlagSet::TOP_LEVEL_NAVIGATION_WITH_USER_ACTIVATION
+            } else {
+                SandboxingFlagSet::TOP_LEVEL_NAVIGATION_WITHOUT_USER_ACTIVATION
+            };
+            if document.has_active_sandboxing_flag(flag) {
+                return false;
+            }
         }
+
+        // Step 3. Return true.
+        true
     }
 
     pub(crate) fn is_auxiliary(&self) -> bool {
@@ -828,19 +925,76 @@ fn tokenize_open_features(features: DOMString) -> IndexMap<String, String> {
 }
 
 // https://html.spec.whatwg.org/multipage/#concept-window-open-features-parse-boolean
-fn parse_open_feature_boolean(tokenized_features: &IndexMap<String, String>, name: &str) -> bool {
-    if let Some(value) = tokenized_features.get(name) {
-        // Step 1 & 2
-        if value.is_empty() || value == "yes" {
-            return true;
-        }
-        // Step 3 & 4
-        if let Ok(int) = parse_integer(value.chars()) {
-            return int != 0;
-        }
+fn parse_open_feature_boolean(value: &str) -> bool {
+    // Step 1 & 2
+    if value.is_empty() || value == "yes" || value == "true" {
+        return true;
+    }
+    // Step 3-5
+    parse_integer(value.chars()).unwrap_or(0) != 0
+}
+
+// https://html.spec.whatwg.org/multipage/#window-feature-is-set
+fn check_if_open_feature_is_set(
+    tokenized_features: &IndexMap<String, String>,
+    name: &str,
+    default: bool,
+) -> bool {
+    // Step 1 & 2
+    tokenized_features
+        .get(name)
+        .map_or(default, |value| parse_open_feature_boolean(value))
+}
+
+// https://html.spec.whatwg.org/multipage/#popup-window-is-requested
+fn popup_window_is_requested(tokenized_features: &IndexMap<String, String>) -> bool {
+    // Step 1
+    if tokenized_features.is_empty() {
+        return false;
+    }
+    // Step 2
+    if let Some(popup) = tokenized_features.get("popup") {
+        return parse_open_feature_boolean(popup);
+    }
+    // Step 3-4
+    let location = check_if_open_feature_is_set(tokenized_features, "location", false);
+    let toolbar = check_if_open_feature_is_set(tokenized_features, "toolbar", false);
+    if !location && !toolbar {
+        return true;
+    }
+    // Step 5-9
+    !check_if_open_feature_is_set(tokenized_features, "menubar", false) ||
+        !check_if_open_feature_is_set(tokenized_features, "resizable", true) ||
+        !check_if_open_feature_is_set(tokenized_features, "scrollbars", false) ||
+        !check_if_open_feature_is_set(tokenized_features, "status", false)
+}
+
+// https://html.spec.whatwg.org/multipage/#set-up-browsing-context-features
+fn parse_auxiliary_webview_features(
+    tokenized_features: &IndexMap<String, String>,
+) -> AuxiliaryWebViewFeatures {
+    let parse_feature = |name: &str| {
+        tokenized_features
+            .get(name)
+            .and_then(|value| parse_integer(value.chars()).ok())
+    };
+    // Positions are clamped to the screen by the embedder, which knows where it is.
+    // Step 5-6, 8-9
+    let left = parse_feature("left");
+    let top = parse_feature("top");
+    // Step 11-12, 14-15: the new viewport can't be smaller than 100 CSS pixels.
+    let width = parse_feature("width").map(|width| width.max(100));
+    let height = parse_feature("height").map(|height| height.max(100));
+
+    AuxiliaryWebViewFeatures {
+        popup: popup_window_is_requested(tokenized_features),
+        left,
+        top,
+        width,
+        height,
+        // Filled in when the browsing context is actually created.
+        user_activated: false,
     }
-    // Step 5
-    false
 }
 
 // This is only called from extern functions,
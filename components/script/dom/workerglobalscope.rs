@@ -24,8 +24,9 @@ use net_traits::request::{
     CredentialsMode, Destination, InsecureRequestsPolicy, ParserMetadata,
     RequestBuilder as NetRequestInit,
 };
+use profile_traits::mem::ReportAttribution;
 use script_traits::WorkerGlobalScopeInit;
-use servo_url::{MutableOrigin, ServoUrl};
+use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use timers::TimerScheduler;
 use uuid::Uuid;
 
@@ -59,7 +60,7 @@ use crate::dom::workernavigator::WorkerNavigator;
 use crate::fetch;
 use crate::messaging::{CommonScriptMsg, ScriptEventLoopReceiver, ScriptEventLoopSender};
 use crate::realms::{InRealm, enter_realm};
-use crate::script_runtime::{CanGc, JSContext, JSContextHelper, Runtime};
+use crate::script_runtime::{CanGc, JSContext, JSContextHelper, Runtime, start_memory_measurement};
 use crate::task::TaskCanceller;
 use crate::timers::{IsInterval, TimerCallback};
 
@@ -81,6 +82,7 @@ pub(crate) fn prepare_workerscope_init(
         creation_url: global.creation_url().clone(),
         user_agent: global.get_user_agent(),
         inherited_secure_context: Some(global.is_secure_context()),
+        top_level_origin: global.top_level_origin(),
     };
 
     init
@@ -130,6 +132,10 @@ pub(crate) struct WorkerGlobalScope {
 
     #[no_trace]
     insecure_requests_policy: InsecureRequestsPolicy,
+
+    /// The origin of the top-level document of this worker's owner.
+    #[no_trace]
+    top_level_origin: ImmutableOrigin,
 }
 
 impl WorkerGlobalScope {
@@ -185,6 +191,7 @@ impl WorkerGlobalScope {
             performance: Default::default(),
             timer_scheduler: RefCell::default(),
             insecure_requests_policy,
+            top_level_origin: init.top_level_origin,
         }
     }
 
@@ -193,6 +200,10 @@ impl WorkerGlobalScope {
         self.insecure_requests_policy
     }
 
+    pub(crate) fn top_level_origin(&self) -> ImmutableOrigin {
+        self.top_level_origin.clone()
+    }
+
     /// Clear various items when the worker event-loop shuts-down.
     pub(crate) fn clear_js_runtime(&self) {
         self.upcast::<GlobalScope>()
@@ -533,7 +544,15 @@ impl WorkerGlobalScope {
             CommonScriptMsg::Task(_, task, _, _) => task.run_box(),
             CommonScriptMsg::CollectReports(reports_chan) => {
                 let cx = self.get_cx();
-                let reports = cx.get_reports(format!("url({})", self.get_url()));
+                let origin = self.upcast::<GlobalScope>().origin().immutable();
+                let attribution = ReportAttribution::Origins(vec![origin.ascii_serialization()]);
+                let mut path = attribution.path();
+                path.push(format!("worker({})", self.get_url()));
+                start_memory_measurement();
+                let mut reports = cx.get_reports(path);
+                for report in &mut reports {
+                    report.attribution = Some(attribution.clone());
+                }
                 reports_chan.send(reports);
             },
         }
@@ -28,7 +28,9 @@ use crate::dom::countqueuingstrategy::{extract_high_water_mark, extract_size_alg
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
-use crate::dom::writablestreamdefaultcontroller::WritableStreamDefaultController;
+use crate::dom::writablestreamdefaultcontroller::{
+    UnderlyingSinkType, WritableStreamDefaultController,
+};
 use crate::dom::writablestreamdefaultwriter::WritableStreamDefaultWriter;
 use crate::realms::{InRealm, enter_realm};
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
@@ -186,6 +188,28 @@ impl WritableStream {
         )
     }
 
+    /// Build a stream backed by a Rust underlying sink.
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
+    pub(crate) fn new_with_external_underlying_sink(
+        cx: SafeJSContext,
+        global: &GlobalScope,
+        sink: UnderlyingSinkType,
+        can_gc: CanGc,
+    ) -> Fallible<DomRoot<WritableStream>> {
+        assert!(!matches!(sink, UnderlyingSinkType::Js));
+        let stream = WritableStream::new_with_proto(global, None, can_gc);
+        let controller = WritableStreamDefaultController::new(
+            global,
+            &UnderlyingSink::empty(),
+            sink,
+            1.0,
+            extract_size_algorithm(&QueuingStrategy::empty(), can_gc),
+            can_gc,
+        );
+        controller.setup(cx, global, &stream, &None, can_gc)?;
+        Ok(stream)
+    }
+
     /// Used as part of
     /// <https://streams.spec.whatwg.org/#set-up-writable-stream-default-controller>
     pub(crate) fn assert_no_controller(&self) {
@@ -880,6 +904,7 @@ impl WritableStreamMethods<crate::DomTypeHolder> for WritableStream {
         let controller = WritableStreamDefaultController::new(
             global,
             &underlying_sink_dict,
+            UnderlyingSinkType::Js,
             high_water_mark,
             size_algorithm,
             can_gc,
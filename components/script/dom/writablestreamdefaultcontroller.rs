@@ -25,10 +25,24 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
 use crate::dom::readablestreamdefaultcontroller::{EnqueuedValue, QueueWithSizes, ValueWithSize};
+use crate::dom::webtransport::{WebTransport, WebTransportSink};
 use crate::dom::writablestream::WritableStream;
 use crate::realms::{InRealm, enter_realm};
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 
+/// The underlying sink of a controller.
+/// The `Js` variant corresponds to a JavaScript object,
+/// whose algorithms are held by the controller.
+/// The other variants are native sinks in Rust.
+#[derive(JSTraceable, MallocSizeOf)]
+#[cfg_attr(crown, crown::unrooted_must_root_lint::must_root)]
+pub(crate) enum UnderlyingSinkType {
+    /// A JS object as underlying sink.
+    Js,
+    /// The sending side of a stream of a WebTransport session, or its datagrams.
+    WebTransport(Dom<WebTransport>, WebTransportSink),
+}
+
 impl js::gc::Rootable for CloseAlgorithmFulfillmentHandler {}
 
 /// The fulfillment handler for
@@ -231,6 +245,9 @@ pub struct WritableStreamDefaultController {
     #[ignore_malloc_size_of = "Rc is hard"]
     write: RefCell<Option<Rc<UnderlyingSinkWriteCallback>>>,
 
+    /// The underlying sink, which is either the JS object below or a native sink.
+    underlying_sink_type: UnderlyingSinkType,
+
     /// The JS object used as `this` when invoking sink algorithms.
     #[ignore_malloc_size_of = "mozjs"]
     underlying_sink_obj: Heap<*mut JSObject>,
@@ -257,6 +274,7 @@ impl WritableStreamDefaultController {
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     fn new_inherited(
         underlying_sink: &UnderlyingSink,
+        underlying_sink_type: UnderlyingSinkType,
         strategy_hwm: f64,
         strategy_size: Rc<QueuingStrategySize>,
     ) -> WritableStreamDefaultController {
@@ -267,6 +285,7 @@ impl WritableStreamDefaultController {
             abort: RefCell::new(underlying_sink.abort.clone()),
             close: RefCell::new(underlying_sink.close.clone()),
             write: RefCell::new(underlying_sink.write.clone()),
+            underlying_sink_type,
             underlying_sink_obj: Default::default(),
             strategy_hwm,
             strategy_size: RefCell::new(Some(strategy_size)),
@@ -274,9 +293,11 @@ impl WritableStreamDefaultController {
         }
     }
 
+    #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn new(
         global: &GlobalScope,
         underlying_sink: &UnderlyingSink,
+        underlying_sink_type: UnderlyingSinkType,
         strategy_hwm: f64,
         strategy_size: Rc<QueuingStrategySize>,
         can_gc: CanGc,
@@ -284,6 +305,7 @@ impl WritableStreamDefaultController {
         reflect_dom_object(
             Box::new(WritableStreamDefaultController::new_inherited(
                 underlying_sink,
+                underlying_sink_type,
                 strategy_hwm,
                 strategy_size,
             )),
@@ -439,6 +461,10 @@ impl WritableStreamDefaultController {
         reason: SafeHandleValue,
         can_gc: CanGc,
     ) -> Rc<Promise> {
+        if let UnderlyingSinkType::WebTransport(transport, sink) = &self.underlying_sink_type {
+            transport.abort_sink(*sink);
+            return Promise::new_resolved(global, cx, (), can_gc);
+        }
         rooted!(in(*cx) let this_object = self.underlying_sink_obj.get());
         let algo = self.abort.borrow().clone();
         let result = if let Some(algo) = algo {
@@ -465,6 +491,14 @@ impl WritableStreamDefaultController {
         global: &GlobalScope,
         can_gc: CanGc,
     ) -> Rc<Promise> {
+        if let UnderlyingSinkType::WebTransport(transport, sink) = &self.underlying_sink_type {
+            let promise = Promise::new(global, can_gc);
+            match transport.write_to_sink(cx, *sink, chunk) {
+                Ok(()) => promise.resolve_native(&(), can_gc),
+                Err(error) => promise.reject_error(error, can_gc),
+            }
+            return promise;
+        }
         rooted!(in(*cx) let this_object = self.underlying_sink_obj.get());
         let algo = self.write.borrow().clone();
         let result = if let Some(algo) = algo {
@@ -491,6 +525,10 @@ impl WritableStreamDefaultController {
         global: &GlobalScope,
         can_gc: CanGc,
     ) -> Rc<Promise> {
+        if let UnderlyingSinkType::WebTransport(transport, sink) = &self.underlying_sink_type {
+            transport.close_sink(*sink);
+            return Promise::new_resolved(global, cx, (), can_gc);
+        }
         rooted!(in(*cx) let mut this_object = ptr::null_mut::<JSObject>());
         this_object.set(self.underlying_sink_obj.get());
         let algo = self.close.borrow().clone();
@@ -9,6 +9,7 @@ use pixels::Image;
 use script_traits::serializable::BlobImpl;
 
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::file::File;
@@ -26,20 +27,28 @@ pub(crate) enum Kind {
         name: DOMString,
         type_: String,
     },
+    /// A file that the user dropped onto the page. It is backed by the file manager, so
+    /// every list of files hands out this same `File` instead of a copy of its contents.
+    DroppedFile {
+        file: Trusted<File>,
+        type_: String,
+    },
 }
 
 impl Kind {
     pub(crate) fn type_(&self) -> DOMString {
         match self {
             Kind::Text { type_, .. } => type_.clone(),
-            Kind::File { type_, .. } => DOMString::from(type_.clone()),
+            Kind::File { type_, .. } | Kind::DroppedFile { type_, .. } => {
+                DOMString::from(type_.clone())
+            },
         }
     }
 
     pub(crate) fn as_string(&self) -> Option<String> {
         match self {
             Kind::Text { data, .. } => Some(data.to_string()),
-            Kind::File { .. } => None,
+            Kind::File { .. } | Kind::DroppedFile { .. } => None,
         }
     }
 
@@ -55,6 +64,7 @@ impl Kind {
                 None,
                 can_gc,
             )),
+            Kind::DroppedFile { file, .. } => Some(file.root()),
         }
     }
 
@@ -63,7 +73,7 @@ impl Kind {
     }
 
     fn is_file(&self) -> bool {
-        matches!(self, Kind::File { .. })
+        matches!(self, Kind::File { .. } | Kind::DroppedFile { .. })
     }
 }
 
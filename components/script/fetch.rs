@@ -115,6 +115,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         referrer_policy: request.referrer_policy,
         pipeline_id: request.pipeline_id,
         target_webview_id: request.target_webview_id,
+        top_level_origin: request.top_level_origin.clone(),
         redirect_mode: request.redirect_mode,
         integrity_metadata: request.integrity_metadata.clone(),
         cryptographic_nonce_metadata: request.cryptographic_nonce_metadata.clone(),
@@ -126,6 +127,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         https_state: request.https_state,
         response_tainting: request.response_tainting,
         crash: None,
+        allows_downloads: false,
     }
 }
 
@@ -337,7 +339,9 @@ pub(crate) fn load_whole_resource(
     global: &GlobalScope,
     can_gc: CanGc,
 ) -> Result<(Metadata, Vec<u8>), NetworkError> {
-    let request = request.https_state(global.get_https_state());
+    let request = request
+        .https_state(global.get_https_state())
+        .top_level_origin(Some(global.top_level_origin()));
     let (action_sender, action_receiver) = ipc::channel().unwrap();
     let url = request.url.clone();
     core_resource_thread
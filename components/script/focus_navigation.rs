@@ -0,0 +1,242 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Sequential focus navigation, where the user moves the focus between the focusable
+//! elements of a document with the Tab key, or backwards with Shift+Tab.
+//!
+//! Elements are visited in the order of their `tabindex`: first those with a positive
+//! `tabindex`, in increasing order, and then the others in tree order. Each shadow tree is a
+//! separate focus navigation scope, whose elements are visited right after its host, or
+//! instead of it if the host itself is not focusable or delegates its focus. Elements in
+//! `inert` subtrees and elements that are not being rendered are skipped.
+//!
+//! <https://html.spec.whatwg.org/multipage/#sequential-focus-navigation>
+
+use html5ever::{local_name, namespace_url, ns};
+use keyboard_types::{Key, KeyState, KeyboardEvent, Modifiers};
+use style::attr::parse_integer;
+
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::{Document, FocusType};
+use crate::dom::element::Element;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::script_runtime::CanGc;
+
+/// The direction in which the focus moves.
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+/// Move the focus of `document` in response to `event`, if it is a Tab key press. Returns
+/// whether the key was handled.
+pub(crate) fn handle_key(document: &Document, event: &KeyboardEvent, can_gc: CanGc) -> bool {
+    if event.state != KeyState::Down ||
+        event.key != Key::Tab ||
+        event
+            .modifiers
+            .intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::META)
+    {
+        return false;
+    }
+    let direction = if event.modifiers.contains(Modifiers::SHIFT) {
+        Direction::Backward
+    } else {
+        Direction::Forward
+    };
+    navigate(document, direction, can_gc);
+    true
+}
+
+/// <https://html.spec.whatwg.org/multipage/#sequential-navigation-search-algorithm>
+fn navigate(document: &Document, direction: Direction, can_gc: CanGc) {
+    let order = focus_navigation_order(document.upcast(), can_gc);
+
+    // The focused element takes precedence over the starting point, which is only used
+    // when the focus was lost, for instance because the focused element was removed.
+    let starting_point = document
+        .get_focused_element()
+        .map(DomRoot::upcast::<Node>)
+        .or_else(|| document.sequential_focus_navigation_starting_point());
+
+    let candidate = match starting_point {
+        None => match direction {
+            Direction::Forward => order.first(),
+            Direction::Backward => order.last(),
+        },
+        Some(starting_point) => {
+            match order
+                .iter()
+                .position(|element| *element.upcast::<Node>() == *starting_point)
+            {
+                Some(index) => match direction {
+                    Direction::Forward => order.get(index + 1),
+                    Direction::Backward => index.checked_sub(1).and_then(|index| order.get(index)),
+                },
+                None => candidate_around(document, &order, &starting_point, direction),
+            }
+        },
+    };
+
+    // After the last element, the focus moves back to the document, so that the next
+    // navigation starts over from the first element.
+    let Some(candidate) = candidate.cloned() else {
+        document.request_focus(None, FocusType::Element, can_gc);
+        document.set_sequential_focus_navigation_starting_point(None);
+        return;
+    };
+    document.request_focus(Some(&candidate), FocusType::Element, can_gc);
+    candidate.scroll_into_view(
+        ScrollBehavior::Auto,
+        ScrollLogicalPosition::Nearest,
+        ScrollLogicalPosition::Nearest,
+        can_gc,
+    );
+}
+
+/// The element of `order` that follows, or precedes, `starting_point` in shadow-including
+/// tree order, when the starting point itself is not in the order.
+fn candidate_around<'a>(
+    document: &Document,
+    order: &'a [DomRoot<Element>],
+    starting_point: &Node,
+    direction: Direction,
+) -> Option<&'a DomRoot<Element>> {
+    let tree_order: Vec<_> = document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::Yes)
+        .collect();
+    let tree_index = |node: &Node| tree_order.iter().position(|other| **other == *node);
+    let start = tree_index(starting_point)?;
+    match direction {
+        Direction::Forward => order
+            .iter()
+            .find(|element| tree_index(element.upcast()).is_some_and(|index| index > start)),
+        Direction::Backward => order
+            .iter()
+            .rev()
+            .find(|element| tree_index(element.upcast()).is_some_and(|index| index < start)),
+    }
+}
+
+/// The value of the `tabindex` attribute of `element`, if it is a valid integer.
+fn tab_index(element: &Element) -> Option<i32> {
+    let attribute = element.get_attribute(&ns!(), &local_name!("tabindex"))?;
+    parse_integer(attribute.value().chars()).ok()
+}
+
+/// Whether `element` can be reached with sequential focus navigation.
+fn is_sequentially_focusable(element: &Element, can_gc: CanGc) -> bool {
+    let tab_index = tab_index(element);
+    if tab_index.is_some_and(|tab_index| tab_index < 0) {
+        return false;
+    }
+    // Links without an `href` are only focusable with a `tabindex`.
+    if tab_index.is_none() &&
+        element.is::<HTMLAnchorElement>() &&
+        !element.has_attribute(&local_name!("href"))
+    {
+        return false;
+    }
+    element.is_focusable_area() && element.has_css_layout_box(can_gc)
+}
+
+/// The elements of the focus navigation scope of `root`, which is a document or a shadow
+/// root, in tree order. Shadow trees are separate scopes, and `inert` subtrees are skipped.
+fn collect_focus_navigation_scope(root: &Node, scope: &mut Vec<DomRoot<Element>>) {
+    for child in root.children() {
+        let Some(element) = child.downcast::<Element>() else {
+            continue;
+        };
+        if element.has_attribute(&local_name!("inert")) {
+            continue;
+        }
+        scope.push(DomRoot::from_ref(element));
+        collect_focus_navigation_scope(&child, scope);
+    }
+}
+
+/// The focusable elements of the focus navigation scope of `root` in the order in which
+/// they are visited, including the elements of the nested shadow trees.
+///
+/// <https://html.spec.whatwg.org/multipage/#flattened-tabindex-ordered-focus-navigation-scope>
+fn focus_navigation_order(root: &Node, can_gc: CanGc) -> Vec<DomRoot<Element>> {
+    let mut scope = Vec::new();
+    collect_focus_navigation_scope(root, &mut scope);
+
+    // Shadow hosts are kept in the scope even if they are not focusable themselves, so
+    // that their shadow trees are visited in their place.
+    scope.retain(|element| {
+        element.shadow_root().is_some() || is_sequentially_focusable(element, can_gc)
+    });
+
+    // Elements with a positive `tabindex` come first, in increasing order. The sort is
+    // stable, so elements with the same `tabindex` stay in tree order.
+    scope.sort_by_key(|element| match tab_index(element) {
+        Some(tab_index) if tab_index > 0 => tab_index,
+        _ => i32::MAX,
+    });
+
+    let mut order = Vec::with_capacity(scope.len());
+    for element in scope {
+        let Some(shadow_root) = element.shadow_root() else {
+            order.push(element);
+            continue;
+        };
+        if !shadow_root.DelegatesFocus() && is_sequentially_focusable(&element, can_gc) {
+            order.push(element.clone());
+        }
+        if element.has_css_layout_box(can_gc) {
+            order.extend(focus_navigation_order(shadow_root.upcast(), can_gc));
+        }
+    }
+    order
+}
+
+/// The element that is focused instead of the shadow host `element`, if its shadow root
+/// delegates its focus, which is the first focusable element of its shadow tree.
+///
+/// <https://html.spec.whatwg.org/multipage/#focus-delegate>
+pub(crate) fn focus_delegate(element: &Element, can_gc: CanGc) -> Option<DomRoot<Element>> {
+    let shadow_root = element.shadow_root()?;
+    if !shadow_root.DelegatesFocus() {
+        return None;
+    }
+
+    // If the focus is already in the shadow tree, it stays where it is.
+    let document = element.owner_document();
+    if let Some(focused) = document.get_focused_element() {
+        if element
+            .upcast::<Node>()
+            .is_shadow_including_inclusive_ancestor_of(focused.upcast())
+        {
+            return Some(focused);
+        }
+    }
+
+    focus_navigation_order(shadow_root.upcast(), can_gc)
+        .into_iter()
+        .next()
+}
+
+/// The element to focus when the user clicks `target`, which is the nearest inclusive
+/// ancestor of it that is focusable, or the focus delegate of a shadow host.
+pub(crate) fn click_focus_target(target: &Element, can_gc: CanGc) -> Option<DomRoot<Element>> {
+    target
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::Yes)
+        .filter_map(DomRoot::downcast::<Element>)
+        .find_map(|element| {
+            if element.is_focusable_area() {
+                return Some(element);
+            }
+            focus_delegate(&element, can_gc)
+        })
+}
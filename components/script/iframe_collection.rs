@@ -7,12 +7,11 @@ use std::default::Default;
 
 use base::id::BrowsingContextId;
 use constellation_traits::{WindowSizeData, WindowSizeType};
-use euclid::{Scale, Size2D};
+use euclid::Size2D;
 use fnv::FnvHashMap;
 use script_layout_interface::IFrameSizes;
 use script_traits::IFrameSizeMsg;
 use style_traits::CSSPixel;
-use webrender_api::units::DevicePixel;
 
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{Dom, DomRoot};
@@ -115,7 +114,7 @@ impl IFrameCollection {
     pub(crate) fn handle_new_iframe_sizes_after_layout(
         &mut self,
         new_iframe_sizes: IFrameSizes,
-        device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+        parent_window_size: WindowSizeData,
     ) -> Vec<IFrameSizeMsg> {
         if new_iframe_sizes.is_empty() {
             return vec![];
@@ -133,7 +132,7 @@ impl IFrameCollection {
                         size.pipeline_id,
                         WindowSizeData {
                             initial_viewport: new_size,
-                            device_pixel_ratio,
+                            ..parent_window_size
                         },
                         WindowSizeType::Resize,
                     );
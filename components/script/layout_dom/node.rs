@@ -219,6 +219,16 @@ impl<'dom> LayoutNode<'dom> for ServoLayoutNode<'dom> {
     fn layout_data(&self) -> Option<&'dom GenericLayoutData> {
         self.get_jsmanaged().layout_data()
     }
+
+    unsafe fn take_was_moved(&self) -> bool {
+        unsafe {
+            let was_moved = self.node.get_flag(NodeFlags::WAS_MOVED);
+            if was_moved {
+                self.node.set_flag(NodeFlags::WAS_MOVED, false);
+            }
+            was_moved
+        }
+    }
 }
 
 /// A wrapper around a `ServoLayoutNode` that can be used safely on different threads.
@@ -357,6 +367,16 @@ impl<'dom> ThreadSafeLayoutNode<'dom> for ServoThreadSafeLayoutNode<'dom> {
         })
     }
 
+    fn spelling_errors(&self) -> Vec<std::ops::Range<usize>> {
+        let this = unsafe { self.get_jsmanaged() };
+        this.spelling_errors()
+    }
+
+    fn composition(&self) -> Option<std::ops::Range<usize>> {
+        let this = unsafe { self.get_jsmanaged() };
+        this.composition()
+    }
+
     fn image_url(&self) -> Option<ServoUrl> {
         let this = unsafe { self.get_jsmanaged() };
         this.image_url()
@@ -399,6 +419,11 @@ impl<'dom> ThreadSafeLayoutNode<'dom> for ServoThreadSafeLayoutNode<'dom> {
         this.iframe_pipeline_id()
     }
 
+    fn portal_pipeline_id(&self) -> Option<PipelineId> {
+        let this = unsafe { self.get_jsmanaged() };
+        this.portal_pipeline_id()
+    }
+
     fn get_span(&self) -> Option<u32> {
         unsafe {
             self.get_jsmanaged()
@@ -24,12 +24,15 @@ extern crate malloc_size_of_derive;
 #[macro_use]
 extern crate stylo_atoms;
 
+mod adopted_stylesheets;
 mod animation_timeline;
 mod animations;
+mod autofill;
 #[macro_use]
 mod task;
 mod body;
 pub(crate) mod clipboard_provider;
+mod context_menu;
 pub(crate) mod conversions;
 mod devtools;
 pub(crate) mod document_loader;
@@ -37,7 +40,9 @@ pub(crate) mod document_loader;
 mod dom;
 mod canvas_context;
 mod canvas_state;
+mod caret_browsing;
 pub(crate) mod fetch;
+mod focus_navigation;
 mod init;
 mod layout_image;
 
@@ -61,6 +66,8 @@ pub(crate) mod script_runtime;
 pub(crate) mod script_thread;
 pub(crate) mod security_manager;
 pub(crate) mod serviceworker_manager;
+mod speculation_rules;
+mod spellcheck;
 mod stylesheet_loader;
 mod stylesheet_set;
 mod task_manager;
@@ -383,7 +383,8 @@ pub(crate) fn follow_hyperlink(
     let source = document.browsing_context().unwrap();
     let (maybe_chosen, history_handling) = match target_attribute_value {
         Some(name) => {
-            let (maybe_chosen, new) = source.choose_browsing_context(name, noopener);
+            let (maybe_chosen, new) =
+                source.choose_browsing_context(name, noopener, Default::default());
             let history_handling = if new {
                 NavigationHistoryBehavior::Replace
             } else {
@@ -2,11 +2,21 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-//! Routines for handling measuring the memory usage of arbitrary DOM nodes.
+//! Routines for handling measuring the memory usage of arbitrary DOM nodes and documents.
 
 use std::os::raw::c_void;
+use std::sync::Arc;
 
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use profile_traits::mem::{Report, ReportAttribution, ReportKind};
+use profile_traits::path;
+
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::document::Document;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::script_runtime::{dom_object_size, new_malloc_size_of_ops};
 
 /// Used by codegen to include the pointer to the `MallocSizeOf` implementation of each
 /// IDL interface. This way we don't have to find the most-derived interface of DOM
@@ -18,3 +28,64 @@ pub(crate) unsafe fn malloc_size_of_including_raw_self<T: MallocSizeOf>(
 ) -> usize {
     ops.malloc_size_of(obj) + (*(obj as *const T)).size_of(ops)
 }
+
+/// Measure the memory used on behalf of `document`: its nodes, their computed styles, the
+/// images that they show and its layout. Each report is attributed to the document.
+pub(crate) fn collect_document_reports(document: &Document, reports: &mut Vec<Report>) {
+    let window = document.window();
+    let attribution = ReportAttribution::Pipeline {
+        origin: document.origin().immutable().ascii_serialization(),
+        pipeline_id: window.pipeline_id(),
+        url: document.url().into_string(),
+    };
+    let path = attribution.path();
+    let first_report = reports.len();
+    let mut report = |mut path_suffix, kind, size| {
+        let mut report_path = path.clone();
+        report_path.append(&mut path_suffix);
+        reports.push(Report {
+            path: report_path,
+            kind,
+            size,
+            attribution: None,
+        });
+    };
+
+    let mut ops = new_malloc_size_of_ops();
+    let (mut nodes, mut computed_styles, mut images) = (0, 0, 0);
+    for node in document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::Yes)
+    {
+        nodes += dom_object_size(node.reflector());
+        computed_styles += node.computed_styles_size_of(&mut ops);
+        // Images are shared by all of the elements that show them.
+        if let Some(image) = node
+            .downcast::<HTMLImageElement>()
+            .and_then(HTMLImageElement::image_data)
+        {
+            if !ops.have_seen_ptr(Arc::as_ptr(&image)) {
+                images += image.bytes().len();
+            }
+        }
+    }
+
+    report(
+        path!["dom", "nodes"],
+        ReportKind::ExplicitJemallocHeapSize,
+        nodes,
+    );
+    report(
+        path!["dom", "computed-styles"],
+        ReportKind::ExplicitJemallocHeapSize,
+        computed_styles,
+    );
+    // Decoded images are kept in shared memory.
+    report(path!["images"], ReportKind::ExplicitNonHeapSize, images);
+
+    window.layout().collect_reports(&path, reports);
+
+    for report in &mut reports[first_report..] {
+        report.attribution = Some(attribution.clone());
+    }
+}
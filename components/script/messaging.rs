@@ -55,6 +55,11 @@ impl MixedMessage {
                     .or(Some(new_layout_info.new_pipeline_id)),
                 ScriptThreadMessage::Resize(id, ..) => Some(*id),
                 ScriptThreadMessage::ThemeChange(id, ..) => Some(*id),
+                ScriptThreadMessage::ScreensChanged(id) => Some(*id),
+                ScriptThreadMessage::NetworkStatusChanged(id, ..) => Some(*id),
+                ScriptThreadMessage::FindInPage(id, ..) => Some(*id),
+                ScriptThreadMessage::Autofill(id, ..) => Some(*id),
+                ScriptThreadMessage::ControlAnimationClock(id, ..) => Some(*id),
                 ScriptThreadMessage::ResizeInactive(id, ..) => Some(*id),
                 ScriptThreadMessage::UnloadDocument(id) => Some(*id),
                 ScriptThreadMessage::ExitPipeline(id, ..) => Some(*id),
@@ -64,9 +69,16 @@ impl MixedMessage {
                 ScriptThreadMessage::GetTitle(id) => Some(*id),
                 ScriptThreadMessage::SetDocumentActivity(id, ..) => Some(*id),
                 ScriptThreadMessage::SetThrottled(id, ..) => Some(*id),
+                ScriptThreadMessage::ActivatePrerenderedDocument(id, ..) => Some(*id),
+                ScriptThreadMessage::SetWebViewVisibility(..) => None,
+                ScriptThreadMessage::SetWebViewMuted(..) => None,
+                ScriptThreadMessage::SetEmulatedMediaFeatures(..) => None,
+                ScriptThreadMessage::SetUserContentManager(..) => None,
+                ScriptThreadMessage::PreferencesUpdated(..) => None,
                 ScriptThreadMessage::SetThrottledInContainingIframe(id, ..) => Some(*id),
                 ScriptThreadMessage::NavigateIframe(id, ..) => Some(*id),
                 ScriptThreadMessage::PostMessage { target: id, .. } => Some(*id),
+                ScriptThreadMessage::PostPortalMessage { target: id, .. } => Some(*id),
                 ScriptThreadMessage::UpdatePipelineId(_, _, _, id, _) => Some(*id),
                 ScriptThreadMessage::UpdateHistoryState(id, ..) => Some(*id),
                 ScriptThreadMessage::RemoveHistoryStates(id, ..) => Some(*id),
@@ -88,6 +100,7 @@ impl MixedMessage {
                 #[cfg(feature = "webgpu")]
                 ScriptThreadMessage::SetWebGPUPort(..) => None,
                 ScriptThreadMessage::SetScrollStates(id, ..) => Some(*id),
+                ScriptThreadMessage::SetVisualViewport(id, ..) => Some(*id),
             },
             MixedMessage::FromScript(inner_msg) => match inner_msg {
                 MainThreadScriptMsg::Common(CommonScriptMsg::Task(_, _, pipeline_id, _)) => {
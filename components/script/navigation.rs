@@ -22,7 +22,7 @@ use net_traits::{
     BoxedFetchCallback, CoreResourceThread, DOCUMENT_ACCEPT_HEADER_VALUE, FetchResponseMsg,
     Metadata, fetch_async, set_default_accept_language,
 };
-use script_traits::{DocumentActivity, LoadData};
+use script_traits::{DocumentActivity, LoadData, StructuredSerializedData};
 use servo_url::{MutableOrigin, ServoUrl};
 
 use crate::fetch::FetchCanceller;
@@ -159,6 +159,10 @@ pub(crate) struct InProgressLoad {
     /// this load.
     #[no_trace]
     pub(crate) url_list: Vec<ServoUrl>,
+    /// The data given to `activate()` when this document, hosted by a `<portal>` element, was
+    /// activated before it finished loading.
+    #[no_trace]
+    pub(crate) portal_activation_data: Option<StructuredSerializedData>,
 }
 
 impl InProgressLoad {
@@ -189,6 +193,7 @@ impl InProgressLoad {
             canceller: Default::default(),
             load_data,
             url_list: vec![url],
+            portal_activation_data: None,
         }
     }
 
@@ -216,6 +221,7 @@ impl InProgressLoad {
         .body(self.load_data.data.clone())
         .redirect_mode(RedirectMode::Manual)
         .origin(self.origin.immutable().clone())
+        .top_level_origin(self.load_data.top_level_origin.clone())
         .crash(self.load_data.crash.clone());
         request_builder.url_list = self.url_list.clone();
 
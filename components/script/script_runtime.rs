@@ -69,7 +69,7 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{
     LiveDOMReferences, Trusted, TrustedPromise, trace_refcounted_objects,
 };
-use crate::dom::bindings::reflector::{DomGlobal, DomObject};
+use crate::dom::bindings::reflector::{DomGlobal, DomObject, Reflector};
 use crate::dom::bindings::root::trace_roots;
 use crate::dom::bindings::utils::DOM_CALLBACKS;
 use crate::dom::bindings::{principals, settings_stack};
@@ -815,23 +815,32 @@ thread_local!(static SEEN_POINTERS: LazyCell<RefCell<HashSet<*const c_void>>> =
     LazyCell::new(|| RefCell::new(HashSet::new()))
 });
 
+fn have_seen_pointer(ptr: *const c_void) -> bool {
+    SEEN_POINTERS.with(|pointers| !pointers.borrow_mut().insert(ptr))
+}
+
+/// Create the operations used to measure the memory of this thread's DOM objects, which
+/// don't count twice the memory that they share.
+pub(crate) fn new_malloc_size_of_ops() -> MallocSizeOfOps {
+    MallocSizeOfOps::new(
+        servo_allocator::usable_size,
+        None,
+        Some(Box::new(have_seen_pointer)),
+    )
+}
+
 #[allow(unsafe_code)]
 unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
     match get_dom_class(obj) {
         Ok(v) => {
             let dom_object = private_from_object(obj) as *const c_void;
 
-            if dom_object.is_null() {
+            // DOM objects that were attributed to a document are not counted again in the
+            // JavaScript heap.
+            if dom_object.is_null() || have_seen_pointer(dom_object) {
                 return 0;
             }
-            let seen_pointer =
-                move |ptr| SEEN_POINTERS.with(|pointers| !pointers.borrow_mut().insert(ptr));
-            let mut ops = MallocSizeOfOps::new(
-                servo_allocator::usable_size,
-                None,
-                Some(Box::new(seen_pointer)),
-            );
-            (v.malloc_size_of)(&mut ops, dom_object)
+            (v.malloc_size_of)(&mut new_malloc_size_of_ops(), dom_object)
         },
         Err(_e) => 0,
     }
@@ -936,13 +945,26 @@ pub(crate) use script_bindings::script_runtime::JSContext;
 /// Extra methods for the JSContext type defined in script_bindings, when
 /// the methods are only called by code in the script crate.
 pub(crate) trait JSContextHelper {
-    fn get_reports(&self, path_seg: String) -> Vec<Report>;
+    fn get_reports(&self, path: Vec<String>) -> Vec<Report>;
+}
+
+/// Start measuring the memory of this thread's DOM objects, forgetting the objects that
+/// were measured before. Until the next call, each object is only counted once, either by
+/// [`dom_object_size`] or in the JavaScript heap by [`JSContextHelper::get_reports`].
+pub(crate) fn start_memory_measurement() {
+    SEEN_POINTERS.with(|pointers| pointers.borrow_mut().clear());
+}
+
+/// The memory used by the DOM object reflected by `reflector`, or zero if it was already
+/// measured since [`start_memory_measurement`] was called.
+#[allow(unsafe_code)]
+pub(crate) fn dom_object_size(reflector: &Reflector) -> usize {
+    unsafe { get_size(reflector.get_jsobject().get()) }
 }
 
 impl JSContextHelper for JSContext {
     #[allow(unsafe_code)]
-    fn get_reports(&self, path_seg: String) -> Vec<Report> {
-        SEEN_POINTERS.with(|pointers| pointers.borrow_mut().clear());
+    fn get_reports(&self, path: Vec<String>) -> Vec<Report> {
         let stats = unsafe {
             let mut stats = ::std::mem::zeroed();
             if !CollectServoSizes(**self, &mut stats, Some(get_size)) {
@@ -953,9 +975,15 @@ impl JSContextHelper for JSContext {
 
         let mut reports = vec![];
         let mut report = |mut path_suffix, kind, size| {
-            let mut path = path![path_seg, "js"];
+            let mut path = path.clone();
+            path.push("js".to_owned());
             path.append(&mut path_suffix);
-            reports.push(Report { path, kind, size })
+            reports.push(Report {
+                path,
+                kind,
+                size,
+                attribution: None,
+            })
         };
 
         // A note about possibly confusing terminology: the JS GC "heap" is allocated via
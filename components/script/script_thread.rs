@@ -19,7 +19,7 @@
 
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::default::Default;
 use std::option::Option;
 use std::rc::Rc;
@@ -37,15 +37,19 @@ use base::cross_process_instant::CrossProcessInstant;
 use base::id::{BrowsingContextId, HistoryStateId, PipelineId, PipelineNamespace, WebViewId};
 use canvas_traits::webgl::WebGLPipeline;
 use chrono::{DateTime, Local};
-use constellation_traits::{CompositorHitTestResult, ScrollState, WindowSizeData, WindowSizeType};
-use crossbeam_channel::unbounded;
+use constellation_traits::{
+    CompositorHitTestResult, ScrollState, VisualViewportState, WindowSizeData, WindowSizeType,
+};
+use crossbeam_channel::{select, unbounded};
 use devtools_traits::{
-    CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState,
+    CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState, ResumeLimit,
     ScriptToDevtoolsControlMsg, WorkerId,
 };
 use embedder_traits::user_content_manager::UserContentManager;
 use embedder_traits::{
-    EmbedderMsg, InputEvent, MediaSessionActionType, Theme, WebDriverScriptCommand,
+    AnimationClockRequest, AutofillRequest, EmbedderMsg, EmulatedMediaFeatures, FindInPageRequest,
+    InputEvent, MediaSessionActionType, MouseMoveEvent, NetworkStatus, Theme,
+    WebDriverScriptCommand,
 };
 use euclid::default::Rect;
 use fonts::{FontContext, SystemFontServiceProxy};
@@ -72,8 +76,8 @@ use net_traits::{
     ResourceFetchTiming, ResourceThreads, ResourceTimingType,
 };
 use percent_encoding::percent_decode;
-use profile_traits::mem::ReportsChan;
-use profile_traits::time::ProfilerCategory;
+use profile_traits::mem::{ReportAttribution, ReportsChan};
+use profile_traits::time::{ProfilerCategory, send_profile_data};
 use profile_traits::time_profile;
 use script_layout_interface::{
     LayoutConfig, LayoutFactory, ReflowGoal, ScriptThreadFactory, node_id_from_scroll_id,
@@ -81,10 +85,12 @@ use script_layout_interface::{
 use script_traits::{
     ConstellationInputEvent, DiscardBrowsingContext, DocumentActivity, InitialScriptState,
     JsEvalResult, LoadData, LoadOrigin, NavigationHistoryBehavior, NewLayoutInfo, Painter,
-    ProgressiveWebMetricType, ScriptMsg, ScriptThreadMessage, ScriptToConstellationChan,
-    StructuredSerializedData, UpdatePipelineIdReason,
+    ProgressiveWebMetricType, SandboxingFlagSet, ScriptMsg, ScriptThreadMessage,
+    ScriptToConstellationChan, SessionHistoryEntryState, StructuredSerializedData,
+    UpdatePipelineIdReason,
 };
-use servo_config::opts;
+use servo_config::prefs::{self, Preferences, SitePreferences};
+use servo_config::{opts, pref};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
@@ -96,6 +102,7 @@ use webgpu::{WebGPUDevice, WebGPUMsg};
 use webrender_api::DocumentId;
 use webrender_traits::CrossProcessCompositorApi;
 
+use crate::autofill::handle_autofill_request;
 use crate::document_collection::DocumentCollection;
 use crate::document_loader::DocumentLoader;
 use crate::dom::bindings::cell::DomRefCell;
@@ -119,6 +126,7 @@ use crate::dom::bindings::trace::{HashMapTracedValues, JSTraceable};
 use crate::dom::customelementregistry::{
     CallbackReaction, CustomElementDefinition, CustomElementReactionStack,
 };
+use crate::dom::debuggerglobalscope::DebuggerGlobalScope;
 use crate::dom::document::{
     Document, DocumentSource, FocusType, HasBrowsingContext, IsHTMLDocument, TouchEventResult,
 };
@@ -126,6 +134,7 @@ use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlportalelement::{HTMLPortalElement, post_portal_message};
 use crate::dom::htmlslotelement::HTMLSlotElement;
 use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{Node, NodeTraits, ShadowIncluding};
@@ -147,10 +156,11 @@ use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{
     CanGc, JSContext, JSContextHelper, Runtime, ScriptThreadEventCategory, ThreadSafeJSContext,
+    start_memory_measurement,
 };
 use crate::task_queue::TaskQueue;
 use crate::task_source::{SendableTaskSource, TaskSourceName};
-use crate::{devtools, webdriver_handlers};
+use crate::{devtools, mem, webdriver_handlers};
 
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = const { Cell::new(None) });
 
@@ -227,6 +237,9 @@ pub struct ScriptThread {
     background_hang_monitor: Box<dyn BackgroundHangMonitor>,
     /// A flag set to `true` by the BHM on exit, and checked from within the interrupt handler.
     closing: Arc<AtomicBool>,
+    /// A flag set to `true` by the BHM when the embedder asks to stop the script that is
+    /// currently running, and checked from within the interrupt handler.
+    interrupting: Arc<AtomicBool>,
 
     /// A [`TimerScheduler`] used to schedule timers for this [`ScriptThread`]. Timers are handled
     /// in the [`ScriptThread`] event loop.
@@ -243,6 +256,10 @@ pub struct ScriptThread {
     /// The topmost element over the mouse.
     topmost_mouse_over_target: MutNullableDom<Element>,
 
+    /// The global of the script that drives the devtools debugger, created along with the
+    /// first window of this thread when devtools are enabled.
+    debugger_global: MutNullableDom<DebuggerGlobalScope>,
+
     /// List of pipelines that have been owned and closed by this script thread.
     #[no_trace]
     closed_pipelines: DomRefCell<HashSet<PipelineId>>,
@@ -253,8 +270,9 @@ pub struct ScriptThread {
     /// Microtask Queue for adding support for mutation observer microtasks
     mutation_observer_microtask_queued: Cell<bool>,
 
-    /// The unit of related similar-origin browsing contexts' list of MutationObserver objects
-    mutation_observers: DomRefCell<Vec<Dom<MutationObserver>>>,
+    /// <https://dom.spec.whatwg.org/#mutation-observer-list>
+    /// The observers with records to deliver at the next mutation observer microtask.
+    pending_mutation_observers: DomRefCell<Vec<Dom<MutationObserver>>>,
 
     /// <https://dom.spec.whatwg.org/#signal-slot-list>
     signal_slots: DomRefCell<Vec<Dom<HTMLSlotElement>>>,
@@ -304,9 +322,15 @@ pub struct ScriptThread {
     /// Unminify Css.
     unminify_css: bool,
 
-    /// User content manager
+    /// The user scripts and stylesheets that are injected into the documents loaded by this
+    /// script thread.
     #[no_trace]
-    user_content_manager: UserContentManager,
+    user_content_manager: RefCell<UserContentManager>,
+
+    /// The connectivity and quality of the network, used for the windows that are created
+    /// in this script thread.
+    #[no_trace]
+    network_status: Cell<NetworkStatus>,
 
     /// An optional string allowing the user agent to be set for testing.
     user_agent: Cow<'static, str>,
@@ -321,6 +345,23 @@ pub struct ScriptThread {
     /// Code is running as a consequence of a user interaction
     is_user_interacting: Cell<bool>,
 
+    /// The webviews whose audio was muted by the embedder.
+    #[no_trace]
+    muted_webviews: RefCell<HashSet<WebViewId>>,
+
+    /// The webviews that the embedder reported as hidden from the user, whose documents are
+    /// created hidden.
+    #[no_trace]
+    hidden_webviews: RefCell<HashSet<WebViewId>>,
+
+    /// The platform theme, as last reported by the embedder.
+    #[no_trace]
+    theme: Cell<Theme>,
+
+    /// The media features that the embedder emulates for each of its webviews, if any.
+    #[no_trace]
+    emulated_media_features: RefCell<HashMap<WebViewId, EmulatedMediaFeatures>>,
+
     /// Identity manager for WebGPU resources
     #[no_trace]
     #[cfg(feature = "webgpu")]
@@ -336,6 +377,7 @@ pub struct ScriptThread {
 
 struct BHMExitSignal {
     closing: Arc<AtomicBool>,
+    interrupting: Arc<AtomicBool>,
     js_context: ThreadSafeJSContext,
 }
 
@@ -344,10 +386,20 @@ impl BackgroundHangMonitorExitSignal for BHMExitSignal {
         self.closing.store(true, Ordering::SeqCst);
         self.js_context.request_interrupt_callback();
     }
+
+    fn signal_to_interrupt(&self) {
+        self.interrupting.store(true, Ordering::SeqCst);
+        self.js_context.request_interrupt_callback();
+    }
 }
 
 #[allow(unsafe_code)]
 unsafe extern "C" fn interrupt_callback(_cx: *mut UnsafeJSContext) -> bool {
+    // Stop the script that is running, as an uncatchable exception would, but keep the
+    // pages of this script-thread alive.
+    if ScriptThread::take_interrupt_request() {
+        return false;
+    }
     let res = ScriptThread::can_continue_running();
     if !res {
         ScriptThread::prepare_for_shutdown();
@@ -461,6 +513,11 @@ impl ScriptThread {
         with_script_thread(|script_thread| script_thread.can_continue_running_inner())
     }
 
+    /// Whether the script that is running was asked to stop, clearing the request.
+    pub(crate) fn take_interrupt_request() -> bool {
+        with_script_thread(|script_thread| script_thread.interrupting.swap(false, Ordering::SeqCst))
+    }
+
     pub(crate) fn prepare_for_shutdown() {
         with_script_thread(|script_thread| {
             script_thread.prepare_for_shutdown_inner();
@@ -477,22 +534,24 @@ impl ScriptThread {
         with_script_thread(|script_thread| script_thread.mutation_observer_microtask_queued.get())
     }
 
-    pub(crate) fn add_mutation_observer(observer: &MutationObserver) {
+    /// Add `observer` to the pending mutation observers, unless it is already one of them.
+    pub(crate) fn add_pending_mutation_observer(observer: &MutationObserver) {
         with_script_thread(|script_thread| {
-            script_thread
-                .mutation_observers
-                .borrow_mut()
-                .push(Dom::from_ref(observer));
+            let mut pending = script_thread.pending_mutation_observers.borrow_mut();
+            if !pending.iter().any(|pending| &**pending == observer) {
+                pending.push(Dom::from_ref(observer));
+            }
         })
     }
 
-    pub(crate) fn get_mutation_observers() -> Vec<DomRoot<MutationObserver>> {
+    /// Empty the pending mutation observers, returning the observers that were pending.
+    pub(crate) fn take_pending_mutation_observers() -> Vec<DomRoot<MutationObserver>> {
         with_script_thread(|script_thread| {
             script_thread
-                .mutation_observers
-                .borrow()
-                .iter()
-                .map(|o| DomRoot::from_ref(&**o))
+                .pending_mutation_observers
+                .borrow_mut()
+                .drain(..)
+                .map(|observer| DomRoot::from_ref(&*observer))
                 .collect()
         })
     }
@@ -506,6 +565,15 @@ impl ScriptThread {
         })
     }
 
+    /// Report the mutations of the DOMs that the devtools inspector watches.
+    pub(crate) fn send_devtools_dom_mutations() {
+        with_script_thread(|script_thread| {
+            for (_, document) in script_thread.documents.borrow().iter() {
+                devtools::send_dom_mutations(&document);
+            }
+        })
+    }
+
     pub(crate) fn take_signal_slots() -> Vec<DomRoot<HTMLSlotElement>> {
         with_script_thread(|script_thread| {
             script_thread
@@ -677,6 +745,13 @@ impl ScriptThread {
         with_script_thread(|script_thread| script_thread.is_user_interacting.get())
     }
 
+    /// Whether the embedder muted the audio of the given webview.
+    pub(crate) fn is_webview_muted(webview_id: WebViewId) -> bool {
+        with_script_thread(|script_thread| {
+            script_thread.muted_webviews.borrow().contains(&webview_id)
+        })
+    }
+
     pub(crate) fn get_fully_active_document_ids() -> HashSet<PipelineId> {
         with_script_thread(|script_thread| {
             script_thread
@@ -857,8 +932,10 @@ impl ScriptThread {
         let task_queue = TaskQueue::new(self_receiver, self_sender.clone());
 
         let closing = Arc::new(AtomicBool::new(false));
+        let interrupting = Arc::new(AtomicBool::new(false));
         let background_hang_monitor_exit_signal = BHMExitSignal {
             closing: closing.clone(),
+            interrupting: interrupting.clone(),
             js_context: runtime.thread_safe_js_context(),
         };
 
@@ -915,13 +992,15 @@ impl ScriptThread {
             task_queue,
             background_hang_monitor,
             closing,
+            interrupting,
             timer_scheduler: Default::default(),
             microtask_queue: runtime.microtask_queue.clone(),
             js_runtime: Rc::new(runtime),
             topmost_mouse_over_target: MutNullableDom::new(Default::default()),
+            debugger_global: Default::default(),
             closed_pipelines: DomRefCell::new(HashSet::new()),
             mutation_observer_microtask_queued: Default::default(),
-            mutation_observers: Default::default(),
+            pending_mutation_observers: Default::default(),
             signal_slots: Default::default(),
             system_font_service,
             webgl_chan: state.webgl_chan,
@@ -939,10 +1018,15 @@ impl ScriptThread {
             local_script_source: opts.local_script_source.clone(),
             unminify_css: opts.unminify_css,
             user_agent,
-            user_content_manager: state.user_content_manager,
+            user_content_manager: RefCell::new(state.user_content_manager),
+            network_status: Cell::new(state.network_status),
             player_context: state.player_context,
             node_ids: Default::default(),
             is_user_interacting: Cell::new(false),
+            muted_webviews: Default::default(),
+            hidden_webviews: Default::default(),
+            theme: Cell::new(Theme::Light),
+            emulated_media_features: Default::default(),
             #[cfg(feature = "webgpu")]
             gpu_id_hub: Arc::new(IdentityHub::default()),
             inherited_secure_context: state.inherited_secure_context,
@@ -989,6 +1073,7 @@ impl ScriptThread {
     fn process_mouse_move_event(
         &self,
         document: &Document,
+        event: MouseMoveEvent,
         hit_test_result: Option<CompositorHitTestResult>,
         pressed_mouse_buttons: u16,
         can_gc: CanGc,
@@ -998,6 +1083,7 @@ impl ScriptThread {
 
         unsafe {
             document.handle_mouse_move_event(
+                event,
                 hit_test_result,
                 pressed_mouse_buttons,
                 &self.topmost_mouse_over_target,
@@ -1070,6 +1156,10 @@ impl ScriptThread {
         let _realm = enter_realm(document.window());
         for event in document.take_pending_input_events().into_iter() {
             document.update_active_keyboard_modifiers(event.active_keyboard_modifiers);
+            let hit_test_time = event
+                .hit_test_result
+                .as_ref()
+                .map(|hit_test_result| hit_test_result.hit_test_time);
 
             match event.event {
                 InputEvent::MouseButton(mouse_button_event) => {
@@ -1080,10 +1170,10 @@ impl ScriptThread {
                         can_gc,
                     );
                 },
-                InputEvent::MouseMove(_) => {
-                    // The event itself is unecessary here, because the point in the viewport is in the hit test.
+                InputEvent::MouseMove(mouse_move_event) => {
                     self.process_mouse_move_event(
                         &document,
+                        mouse_move_event,
                         event.hit_test_result,
                         event.pressed_mouse_buttons,
                         can_gc,
@@ -1129,6 +1219,21 @@ impl ScriptThread {
                 InputEvent::EditingAction(editing_action_event) => {
                     document.handle_editing_action(editing_action_event, can_gc);
                 },
+                InputEvent::FileDrop(file_drop_event) => {
+                    document.handle_file_drop_event(file_drop_event, event.hit_test_result, can_gc);
+                },
+            }
+
+            // Measure how long it took from finding the target of the event until its DOM events
+            // were dispatched.
+            if let Some(hit_test_time) = hit_test_time {
+                send_profile_data(
+                    ProfilerCategory::ScriptInputEventDispatch,
+                    None,
+                    &self.senders.time_profiler_sender,
+                    hit_test_time,
+                    CrossProcessInstant::now(),
+                );
             }
         }
         ScriptThread::set_user_interacting(false);
@@ -1202,6 +1307,7 @@ impl ScriptThread {
             if !document.is_fully_active() {
                 continue;
             }
+            self.install_crash_reporting_ids(Some(*pipeline_id));
 
             // TODO(#31581): The steps in the "Revealing the document" section need to be implemented
             // `process_pending_input_events` handles the focusing steps as well as other events
@@ -1211,11 +1317,13 @@ impl ScriptThread {
             // https://html.spec.whatwg.org/multipage/#flush-autofocus-candidates.
             self.process_pending_input_events(*pipeline_id, can_gc);
 
-            // TODO(#31665): Implement the "run the scroll steps" from
-            // https://drafts.csswg.org/cssom-view/#document-run-the-scroll-steps.
-
             // > 8. For each doc of docs, run the resize steps for doc. [CSSOMVIEW]
-            if document.window().run_the_resize_steps(can_gc) {
+            let resized = document.window().run_the_resize_steps(can_gc);
+            if let Some(visual_viewport) = document.window().visual_viewport() {
+                visual_viewport.run_the_resize_steps(can_gc);
+            }
+            let media_environment_changed = document.window().take_media_environment_change();
+            if resized || media_environment_changed {
                 // Evaluate media queries and report changes.
                 document
                     .window()
@@ -1226,13 +1334,18 @@ impl ScriptThread {
                 document.react_to_environment_changes()
             }
 
+            // > 9. For each doc of docs, run the scroll steps for doc. [CSSOMVIEW]
+            document.run_the_scroll_steps(can_gc);
+
             // > 11. For each doc of docs, update animations and send events for doc, passing
             // > in relative high resolution time given frameTimestamp and doc's relevant
             // > global object as the timestamp [WEBANIMATIONS]
             document.update_animations_and_send_events(can_gc);
 
-            // TODO(#31866): Implement "run the fullscreen steps" from
-            // https://fullscreen.spec.whatwg.org/multipage/#run-the-fullscreen-steps.
+            // > 12. For each doc of docs, run the fullscreen steps for doc, passing in the
+            // > relative high resolution time given frameTimestamp and doc's relevant global
+            // > object as the timestamp [FULLSCREEN]
+            document.run_the_fullscreen_steps(can_gc);
 
             // TODO(#31868): Implement the "context lost steps" from
             // https://html.spec.whatwg.org/multipage/#context-lost-steps.
@@ -1359,6 +1472,7 @@ impl ScriptThread {
                 .borrow_mut()
                 .dispatch_completed_timers();
 
+            self.install_crash_reporting_ids(event.pipeline_id());
             let _realm = event.pipeline_id().map(|id| {
                 let global = self.documents.borrow().find_global(id);
                 global.map(|global| enter_realm(&*global))
@@ -1473,6 +1587,7 @@ impl ScriptThread {
             debug!("Processing event {:?}.", msg);
             let category = self.categorize_msg(&msg);
             let pipeline_id = msg.pipeline_id();
+            self.install_crash_reporting_ids(pipeline_id);
             let _realm = pipeline_id.and_then(|id| {
                 let global = self.documents.borrow().find_global(id);
                 global.map(|global| enter_realm(&*global))
@@ -1593,7 +1708,7 @@ impl ScriptThread {
         F: FnOnce() -> R,
     {
         self.background_hang_monitor
-            .notify_activity(HangAnnotation::Script(category.into()));
+            .notify_activity(HangAnnotation::Script(category.into(), pipeline_id));
         let start = Instant::now();
         let value = if self.profile_script_events {
             let profiler_chan = self.senders.time_profiler_sender.clone();
@@ -1768,6 +1883,21 @@ impl ScriptThread {
             ScriptThreadMessage::ThemeChange(_, theme) => {
                 self.handle_theme_change_msg(theme);
             },
+            ScriptThreadMessage::ScreensChanged(pipeline_id) => {
+                self.handle_screens_changed_msg(pipeline_id, can_gc);
+            },
+            ScriptThreadMessage::NetworkStatusChanged(pipeline_id, network_status) => {
+                self.handle_network_status_changed_msg(pipeline_id, network_status);
+            },
+            ScriptThreadMessage::FindInPage(pipeline_id, request) => {
+                self.handle_find_in_page_msg(pipeline_id, request, can_gc);
+            },
+            ScriptThreadMessage::Autofill(pipeline_id, request) => {
+                self.handle_autofill_msg(pipeline_id, request, can_gc);
+            },
+            ScriptThreadMessage::ControlAnimationClock(pipeline_id, request) => {
+                self.handle_animation_clock_msg(pipeline_id, request, can_gc);
+            },
             ScriptThreadMessage::GetTitle(pipeline_id) => self.handle_get_title_msg(pipeline_id),
             ScriptThreadMessage::SetDocumentActivity(pipeline_id, activity) => {
                 self.handle_set_document_activity_msg(pipeline_id, activity, can_gc)
@@ -1775,6 +1905,31 @@ impl ScriptThread {
             ScriptThreadMessage::SetThrottled(pipeline_id, throttled) => {
                 self.handle_set_throttled_msg(pipeline_id, throttled)
             },
+            ScriptThreadMessage::ActivatePrerenderedDocument(
+                pipeline_id,
+                browsing_context_id,
+                portal_activation_data,
+            ) => self.handle_activate_prerendered_document_msg(
+                pipeline_id,
+                browsing_context_id,
+                portal_activation_data,
+                can_gc,
+            ),
+            ScriptThreadMessage::SetWebViewVisibility(webview_id, visible) => {
+                self.handle_set_webview_visibility_msg(webview_id, visible, can_gc)
+            },
+            ScriptThreadMessage::SetWebViewMuted(webview_id, muted) => {
+                self.handle_set_webview_muted_msg(webview_id, muted)
+            },
+            ScriptThreadMessage::SetEmulatedMediaFeatures(webview_id, features) => {
+                self.handle_set_emulated_media_features_msg(webview_id, features)
+            },
+            ScriptThreadMessage::SetUserContentManager(user_content_manager) => {
+                *self.user_content_manager.borrow_mut() = user_content_manager;
+            },
+            ScriptThreadMessage::PreferencesUpdated(preferences, site_preferences) => {
+                self.handle_preferences_updated_msg(*preferences, site_preferences)
+            },
             ScriptThreadMessage::SetThrottledInContainingIframe(
                 parent_pipeline_id,
                 browsing_context_id,
@@ -1799,6 +1954,12 @@ impl ScriptThread {
                 source_origin,
                 data,
             ),
+            ScriptThreadMessage::PostPortalMessage {
+                target,
+                portal,
+                source_origin,
+                data,
+            } => self.handle_post_portal_message_msg(target, portal, source_origin, data),
             ScriptThreadMessage::UpdatePipelineId(
                 parent_pipeline_id,
                 browsing_context_id,
@@ -1813,9 +1974,18 @@ impl ScriptThread {
                 reason,
                 can_gc,
             ),
-            ScriptThreadMessage::UpdateHistoryState(pipeline_id, history_state_id, url) => {
-                self.handle_update_history_state_msg(pipeline_id, history_state_id, url, can_gc)
-            },
+            ScriptThreadMessage::UpdateHistoryState(
+                pipeline_id,
+                history_state_id,
+                url,
+                entry_state,
+            ) => self.handle_update_history_state_msg(
+                pipeline_id,
+                history_state_id,
+                url,
+                entry_state,
+                can_gc,
+            ),
             ScriptThreadMessage::RemoveHistoryStates(pipeline_id, history_states) => {
                 self.handle_remove_history_states(pipeline_id, history_states)
             },
@@ -1880,6 +2050,9 @@ impl ScriptThread {
             ScriptThreadMessage::SetScrollStates(pipeline_id, scroll_states) => {
                 self.handle_set_scroll_states(pipeline_id, scroll_states)
             },
+            ScriptThreadMessage::SetVisualViewport(pipeline_id, visual_viewport) => {
+                self.handle_set_visual_viewport(pipeline_id, visual_viewport)
+            },
         }
     }
 
@@ -1894,12 +2067,22 @@ impl ScriptThread {
             Some(pipeline_id),
             || {
                 window.layout_mut().set_scroll_offsets(&scroll_states);
+                // The compositor moves scrollbar thumbs by itself, but the bounds of the nodes
+                // of the accessibility tree depend on the scroll offsets.
+                if pref!(accessibility_enabled) {
+                    window.Document().set_needs_paint(true);
+                }
 
                 let mut scroll_offsets = HashMap::new();
                 for scroll_state in scroll_states.into_iter() {
                     let scroll_offset = scroll_state.scroll_offset;
                     if scroll_state.scroll_id.is_root() {
+                        let old_origin = window.current_viewport().origin;
                         window.update_viewport_for_scroll(-scroll_offset.x, -scroll_offset.y);
+                        if window.current_viewport().origin != old_origin {
+                            let document = window.Document();
+                            document.queue_scroll_event(document.upcast());
+                        }
                     } else if let Some(node_id) =
                         node_id_from_scroll_id(scroll_state.scroll_id.0 as usize)
                     {
@@ -1911,6 +2094,18 @@ impl ScriptThread {
         )
     }
 
+    fn handle_set_visual_viewport(
+        &self,
+        pipeline_id: PipelineId,
+        visual_viewport: VisualViewportState,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            warn!("Received visual viewport for closed pipeline {pipeline_id}");
+            return;
+        };
+        window.set_visual_viewport_state(visual_viewport);
+    }
+
     #[cfg(feature = "webgpu")]
     fn handle_msg_from_webgpu_server(&self, msg: WebGPUMsg, can_gc: CanGc) {
         match msg {
@@ -2041,8 +2236,33 @@ impl ScriptThread {
             DevtoolScriptControlMsg::ModifyAttribute(id, node_id, modifications) => {
                 devtools::handle_modify_attribute(&documents, id, node_id, modifications, can_gc)
             },
-            DevtoolScriptControlMsg::ModifyRule(id, node_id, modifications) => {
-                devtools::handle_modify_rule(&documents, id, node_id, modifications, can_gc)
+            DevtoolScriptControlMsg::GetNodeValue(id, node_id, reply) => {
+                devtools::handle_get_node_value(&documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::GetOuterHtml(id, node_id, reply) => {
+                devtools::handle_get_outer_html(&documents, id, node_id, reply, can_gc)
+            },
+            DevtoolScriptControlMsg::QuerySelectorAll(id, node_id, selector, reply) => {
+                devtools::handle_query_selector_all(&documents, id, node_id, selector, reply)
+            },
+            DevtoolScriptControlMsg::ModifyNodeValue(id, node_id, value) => {
+                devtools::handle_modify_node_value(&documents, id, node_id, value)
+            },
+            DevtoolScriptControlMsg::ModifyRule(id, node_id, selector, modifications) => {
+                devtools::handle_modify_rule(
+                    &documents,
+                    id,
+                    node_id,
+                    selector,
+                    modifications,
+                    can_gc,
+                )
+            },
+            DevtoolScriptControlMsg::HighlightDomNode(id, kind, node_id) => {
+                devtools::handle_highlight_dom_node(&documents, id, kind, node_id, can_gc)
+            },
+            DevtoolScriptControlMsg::WatchDomMutations(id, watch) => {
+                devtools::handle_watch_dom_mutations(&documents, id, watch)
             },
             DevtoolScriptControlMsg::WantsLiveNotifications(id, to_send) => match documents
                 .find_window(id)
@@ -2060,12 +2280,145 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&documents, id, name)
             },
             DevtoolScriptControlMsg::Reload(id) => devtools::handle_reload(&documents, id, can_gc),
+            DevtoolScriptControlMsg::NavigateTo(id, url) => {
+                devtools::handle_navigate_to(&documents, id, url, can_gc)
+            },
             DevtoolScriptControlMsg::GetCssDatabase(reply) => {
                 devtools::handle_get_css_database(reply)
             },
+            DevtoolScriptControlMsg::AttachDebugger(id) => self.handle_attach_debugger(id, can_gc),
+            DevtoolScriptControlMsg::GetPossibleBreakpoints(spidermonkey_id, reply) => {
+                let locations = self
+                    .debugger_global
+                    .get()
+                    .map(|debugger_global| {
+                        debugger_global.possible_breakpoints(spidermonkey_id, can_gc)
+                    })
+                    .unwrap_or_default();
+                let _ = reply.send(locations);
+            },
+            DevtoolScriptControlMsg::SetBreakpoint(breakpoint) => {
+                if let Some(debugger_global) = self.debugger_global.get() {
+                    debugger_global.set_breakpoint(breakpoint, can_gc);
+                }
+            },
+            DevtoolScriptControlMsg::ClearBreakpoint(breakpoint) => {
+                if let Some(debugger_global) = self.debugger_global.get() {
+                    debugger_global.clear_breakpoint(breakpoint, can_gc);
+                }
+            },
+            DevtoolScriptControlMsg::Interrupt => {
+                if let Some(debugger_global) = self.debugger_global.get() {
+                    debugger_global.interrupt(can_gc);
+                }
+            },
+            // The thread is not paused, so there is nothing to resume.
+            DevtoolScriptControlMsg::Resume(_) => {},
+        }
+    }
+
+    /// Block the script thread while the debugger is paused in `pipeline_id`, handling the
+    /// messages of the devtools client until it resumes the thread. Returns how far the thread
+    /// should run before pausing again, or `None` if the paused script must be terminated
+    /// because its pipeline or the whole thread is closing.
+    pub(crate) fn run_paused_debugger(
+        pipeline_id: PipelineId,
+        can_gc: CanGc,
+    ) -> Option<ResumeLimit> {
+        with_script_thread(|script_thread| {
+            script_thread.background_hang_monitor.notify_wait();
+            let receivers = &script_thread.receivers;
+            let mut deferred_messages = vec![];
+            let limit = loop {
+                select! {
+                    recv(receivers.devtools_server_receiver) -> msg => match msg {
+                        Ok(DevtoolScriptControlMsg::Resume(limit)) => break Some(limit),
+                        Ok(msg) => script_thread.handle_msg_from_devtools(msg, can_gc),
+                        Err(_) => break Some(ResumeLimit::None),
+                    },
+                    recv(receivers.constellation_receiver) -> msg => {
+                        let Ok(msg) = msg else {
+                            break None;
+                        };
+                        // A pipeline can't be closed while its script is on the stack, so the
+                        // script is terminated first.
+                        let closing = match msg {
+                            ScriptThreadMessage::ExitScriptThread => true,
+                            ScriptThreadMessage::ExitPipeline(exiting_pipeline_id, _) => {
+                                exiting_pipeline_id == pipeline_id
+                            },
+                            _ => false,
+                        };
+                        deferred_messages.push(msg);
+                        if closing {
+                            break None;
+                        }
+                    },
+                }
+            };
+            // The messages of the constellation are handled in the order in which they were
+            // received once the thread is back in its event loop.
+            for msg in deferred_messages {
+                let _ = script_thread.senders.constellation_sender.send(msg);
+            }
+            script_thread
+                .background_hang_monitor
+                .notify_activity(HangAnnotation::Script(
+                    ScriptThreadEventCategory::DevtoolsMsg.into(),
+                    None,
+                ));
+            limit
+        })
+    }
+
+    /// Start debugging all of the documents of this thread, once a devtools client attaches to
+    /// the one of `pipeline_id`. Scripts run slower while they are debugged, so documents are
+    /// only debugged from then on.
+    fn handle_attach_debugger(&self, pipeline_id: PipelineId, can_gc: CanGc) {
+        if self.debugger_global.get().is_some() {
+            return;
+        }
+        let Some(debugger_global) = self.debugger_global(pipeline_id, can_gc) else {
+            return;
+        };
+        let windows: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .collect();
+        for window in windows {
+            debugger_global.add_debuggee(&window, can_gc);
         }
     }
 
+    /// The debugger global of this thread, which is created when a devtools client attaches.
+    fn debugger_global(
+        &self,
+        pipeline_id: PipelineId,
+        can_gc: CanGc,
+    ) -> Option<DomRoot<DebuggerGlobalScope>> {
+        let devtools_chan = self.senders.devtools_server_sender.clone()?;
+        Some(self.debugger_global.or_init(|| {
+            DebuggerGlobalScope::new(
+                &self.js_runtime,
+                pipeline_id,
+                devtools_chan,
+                self.senders.memory_profiler_sender.clone(),
+                self.senders.time_profiler_sender.clone(),
+                ScriptToConstellationChan {
+                    sender: self.senders.pipeline_to_constellation_sender.clone(),
+                    pipeline_id,
+                },
+                self.resource_threads.clone(),
+                self.user_agent.clone(),
+                #[cfg(feature = "webgpu")]
+                self.gpu_id_hub.clone(),
+                can_gc,
+            )
+        }))
+    }
+
     fn handle_msg_from_image_cache(&self, response: PendingImageResponse) {
         let window = self.documents.borrow().find_window(response.pipeline_id);
         if let Some(ref window) = window {
@@ -2080,7 +2433,7 @@ impl ScriptThread {
         can_gc: CanGc,
     ) {
         // https://github.com/servo/servo/issues/23535
-        // These two messages need different treatment since the JS script might mutate
+        // These messages need different treatment since the JS script might mutate
         // `self.documents`, which would conflict with the immutable borrow of it that
         // occurs for the rest of the messages
         match msg {
@@ -2094,6 +2447,13 @@ impl ScriptThread {
                     window, script, reply, can_gc,
                 );
             },
+            // Advancing the clock runs the timers that become due.
+            WebDriverScriptCommand::ControlAnimationClock(request, reply) => {
+                let window = self.documents.borrow().find_window(pipeline_id);
+                return webdriver_handlers::handle_control_animation_clock(
+                    window, request, reply, can_gc,
+                );
+            },
             _ => (),
         }
 
@@ -2221,6 +2581,38 @@ impl ScriptThread {
                     can_gc,
                 )
             },
+            WebDriverScriptCommand::FindShadowElementsCSS(selector, shadow_root_id, reply) => {
+                webdriver_handlers::handle_find_shadow_elements_css(
+                    &documents,
+                    pipeline_id,
+                    shadow_root_id,
+                    selector,
+                    reply,
+                )
+            },
+            WebDriverScriptCommand::FindShadowElementsLinkText(
+                selector,
+                shadow_root_id,
+                partial,
+                reply,
+            ) => webdriver_handlers::handle_find_shadow_elements_link_text(
+                &documents,
+                pipeline_id,
+                shadow_root_id,
+                selector,
+                partial,
+                reply,
+            ),
+            WebDriverScriptCommand::FindShadowElementsTagName(selector, shadow_root_id, reply) => {
+                webdriver_handlers::handle_find_shadow_elements_tag_name(
+                    &documents,
+                    pipeline_id,
+                    shadow_root_id,
+                    selector,
+                    reply,
+                    can_gc,
+                )
+            },
             WebDriverScriptCommand::FocusElement(element_id, reply) => {
                 webdriver_handlers::handle_focus_element(
                     &documents,
@@ -2297,6 +2689,14 @@ impl ScriptThread {
             WebDriverScriptCommand::GetElementText(node_id, reply) => {
                 webdriver_handlers::handle_get_text(&documents, pipeline_id, node_id, reply)
             },
+            WebDriverScriptCommand::GetElementShadowRoot(node_id, reply) => {
+                webdriver_handlers::handle_get_element_shadow_root(
+                    &documents,
+                    pipeline_id,
+                    node_id,
+                    reply,
+                )
+            },
             WebDriverScriptCommand::GetElementInViewCenterPoint(node_id, reply) => {
                 webdriver_handlers::handle_get_element_in_view_center_point(
                     &documents,
@@ -2351,13 +2751,90 @@ impl ScriptThread {
         })
     }
 
+    /// Handle preferences that the embedder changed at runtime. In a content process this
+    /// updates its own copy of them, which notifies the observers in this process. Documents
+    /// are laid out again, so that layout can react to the change.
+    fn handle_preferences_updated_msg(
+        &self,
+        preferences: Preferences,
+        site_preferences: SitePreferences,
+    ) {
+        prefs::set_site_preferences(site_preferences);
+        prefs::set(preferences);
+        for (_, document) in self.documents.borrow().iter() {
+            document.set_needs_paint(true);
+        }
+    }
+
     /// Handle changes to the theme, triggering reflow if the theme actually changed.
     fn handle_theme_change_msg(&self, theme: Theme) {
+        self.theme.set(theme);
         for (_, document) in self.documents.borrow().iter() {
             document.window().handle_theme_change(theme);
         }
     }
 
+    /// Handle a find-in-page request from the embedder for the document of a pipeline.
+    fn handle_find_in_page_msg(
+        &self,
+        pipeline_id: PipelineId,
+        request: FindInPageRequest,
+        can_gc: CanGc,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            return warn!("Find-in-page request sent to nonexistent pipeline {pipeline_id}");
+        };
+        window.handle_find_in_page_request(request, can_gc);
+    }
+
+    /// Handle an autofill request from the embedder for the document of a pipeline.
+    fn handle_autofill_msg(
+        &self,
+        pipeline_id: PipelineId,
+        request: AutofillRequest,
+        can_gc: CanGc,
+    ) {
+        let Some(document) = self.documents.borrow().find_document(pipeline_id) else {
+            return warn!("Autofill request sent to nonexistent pipeline {pipeline_id}");
+        };
+        handle_autofill_request(&document, request, can_gc);
+    }
+
+    /// Handle a request from the embedder to control the animation clock of the document of a
+    /// pipeline.
+    fn handle_animation_clock_msg(
+        &self,
+        pipeline_id: PipelineId,
+        request: AnimationClockRequest,
+        can_gc: CanGc,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            return warn!("Animation clock request sent to nonexistent pipeline {pipeline_id}");
+        };
+        window.handle_animation_clock_request(request, can_gc);
+    }
+
+    /// Handle changes to the connected screens, updating any exposed `ScreenDetails`.
+    fn handle_screens_changed_msg(&self, pipeline_id: PipelineId, can_gc: CanGc) {
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            return;
+        };
+        window.handle_screens_changed(can_gc);
+    }
+
+    /// Handle a change to the network, which the window of the pipeline tells its page about.
+    fn handle_network_status_changed_msg(
+        &self,
+        pipeline_id: PipelineId,
+        network_status: NetworkStatus,
+    ) {
+        self.network_status.set(network_status);
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            return;
+        };
+        window.handle_network_status_change(network_status);
+    }
+
     // exit_fullscreen creates a new JS promise object, so we need to have entered a realm
     fn handle_exit_fullscreen(&self, id: PipelineId, can_gc: CanGc) {
         let document = self.documents.borrow().find_document(id);
@@ -2414,12 +2891,24 @@ impl ScriptThread {
 
     fn collect_reports(&self, reports_chan: ReportsChan) {
         let documents = self.documents.borrow();
-        let urls = itertools::join(documents.iter().map(|(_, d)| d.url().to_string()), ", ");
-
-        let mut reports = self.get_cx().get_reports(format!("url({})", urls));
+        start_memory_measurement();
+        let mut reports = vec![];
         for (_, document) in documents.iter() {
-            document.window().layout().collect_reports(&mut reports);
+            mem::collect_document_reports(&document, &mut reports);
+        }
+
+        // The JavaScript heap is shared by all of the documents of this event loop, so it is
+        // only attributed to their origins. It doesn't include the DOM objects measured above.
+        let origins: BTreeSet<_> = documents
+            .iter()
+            .map(|(_, document)| document.origin().immutable().ascii_serialization())
+            .collect();
+        let attribution = ReportAttribution::Origins(origins.into_iter().collect());
+        let mut js_reports = self.get_cx().get_reports(attribution.path());
+        for report in &mut js_reports {
+            report.attribution = Some(attribution.clone());
         }
+        reports.append(&mut js_reports);
 
         reports_chan.send(reports);
     }
@@ -2440,6 +2929,165 @@ impl ScriptThread {
         }
     }
 
+    /// Move a prerendered document out of the hidden browsing context it was loaded in and into
+    /// `browsing_context_id`, whose `WindowProxy` it takes over once it becomes fully active.
+    /// `portal_activation_data` is the data given to `activate()` when the document was hosted
+    /// by a `<portal>` element.
+    fn handle_activate_prerendered_document_msg(
+        &self,
+        pipeline_id: PipelineId,
+        browsing_context_id: BrowsingContextId,
+        portal_activation_data: Option<StructuredSerializedData>,
+        can_gc: CanGc,
+    ) {
+        // A document that is still loading is created in the new browsing context, and takes
+        // over its `WindowProxy` when its window is resumed.
+        if let Some(load) = self
+            .incomplete_loads
+            .borrow_mut()
+            .iter_mut()
+            .find(|load| load.pipeline_id == pipeline_id)
+        {
+            load.browsing_context_id = browsing_context_id;
+            load.load_data.is_portal = false;
+            load.load_data.is_prerendering = false;
+            load.portal_activation_data = portal_activation_data;
+            return;
+        }
+
+        let Some(window) = self.documents.borrow().find_window(pipeline_id) else {
+            return warn!("{pipeline_id}: Activating prerender of closed pipeline");
+        };
+        let prerender_window_proxy = window.window_proxy();
+        let window_proxy = self.local_window_proxy(
+            &window,
+            browsing_context_id,
+            window.webview_id(),
+            None,
+            None,
+        );
+        window.set_window_proxy(&window_proxy);
+        window.set_is_prerendering(false);
+        if window.Document().is_fully_active() {
+            window_proxy.set_currently_active(&window, can_gc);
+        }
+
+        // Nothing can navigate or reach the hidden browsing context anymore.
+        prerender_window_proxy.discard_browsing_context();
+        self.window_proxies
+            .borrow_mut()
+            .remove(&prerender_window_proxy.browsing_context_id());
+
+        if let Some(data) = portal_activation_data {
+            window.activate_portal(data);
+        }
+    }
+
+    /// Deliver a message sent between a `<portal>` element and the document it hosts, which
+    /// the constellation has checked is still hosted by that portal.
+    fn handle_post_portal_message_msg(
+        &self,
+        target: PipelineId,
+        portal: PipelineId,
+        source_origin: ImmutableOrigin,
+        data: StructuredSerializedData,
+    ) {
+        let Some(window) = self.documents.borrow().find_window(target) else {
+            return warn!("Portal message sent to closed pipeline {target}");
+        };
+
+        // The document hosted by the portal receives messages on its `PortalHost`, and the
+        // host document on the `<portal>` element itself.
+        if target == portal {
+            if let Some(portal_host) = window.portal_host() {
+                post_portal_message(portal_host.upcast(), source_origin, data);
+            }
+            return;
+        }
+        let portal_element = window
+            .Document()
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<HTMLPortalElement>)
+            .find(|element| element.pipeline_id() == Some(portal));
+        if let Some(portal_element) = portal_element {
+            post_portal_message(portal_element.upcast(), source_origin, data);
+        }
+    }
+
+    fn handle_set_webview_visibility_msg(
+        &self,
+        webview_id: WebViewId,
+        visible: bool,
+        can_gc: CanGc,
+    ) {
+        {
+            let mut hidden_webviews = self.hidden_webviews.borrow_mut();
+            if visible {
+                hidden_webviews.remove(&webview_id);
+            } else {
+                hidden_webviews.insert(webview_id);
+            }
+        }
+
+        let documents: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .filter(|document| document.webview_id() == webview_id)
+            .collect();
+        for document in documents {
+            document.set_page_visibility(visible, can_gc);
+        }
+    }
+
+    fn handle_set_webview_muted_msg(&self, webview_id: WebViewId, muted: bool) {
+        {
+            let mut muted_webviews = self.muted_webviews.borrow_mut();
+            if muted {
+                muted_webviews.insert(webview_id);
+            } else {
+                muted_webviews.remove(&webview_id);
+            }
+        }
+
+        // Media elements that are not in a document and audio contexts are only known to their
+        // window, which also tracks the elements adopted into one of its documents.
+        let windows: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .filter(|window| window.webview_id() == webview_id)
+            .collect();
+        for window in windows {
+            window.update_audio_muting();
+        }
+    }
+
+    fn handle_set_emulated_media_features_msg(
+        &self,
+        webview_id: WebViewId,
+        features: EmulatedMediaFeatures,
+    ) {
+        if features == EmulatedMediaFeatures::default() {
+            self.emulated_media_features
+                .borrow_mut()
+                .remove(&webview_id);
+        } else {
+            self.emulated_media_features
+                .borrow_mut()
+                .insert(webview_id, features);
+        }
+
+        for (_, document) in self.documents.borrow().iter() {
+            if document.webview_id() == webview_id {
+                document.window().set_emulated_media_features(features);
+            }
+        }
+    }
+
     fn handle_set_throttled_msg(&self, id: PipelineId, throttled: bool) {
         // Separate message sent since parent script thread could be different (Iframe of different
         // domain)
@@ -2611,6 +3259,7 @@ impl ScriptThread {
         pipeline_id: PipelineId,
         history_state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        entry_state: SessionHistoryEntryState,
         can_gc: CanGc,
     ) {
         let window = self.documents.borrow().find_window(pipeline_id);
@@ -2621,9 +3270,11 @@ impl ScriptThread {
                     pipeline_id
                 );
             },
-            Some(window) => window
-                .History()
-                .activate_state(history_state_id, url, can_gc),
+            Some(window) => {
+                window
+                    .History()
+                    .activate_state(history_state_id, url, entry_state, can_gc)
+            },
         }
     }
 
@@ -2675,7 +3326,23 @@ impl ScriptThread {
                     _ => false,
                 };
 
-                if is20x {
+                // Responses that aren't displayed as documents are downloaded instead, which
+                // also leaves the current document in place.
+                let should_download = metadata.as_ref().is_some_and(Metadata::is_download);
+                if should_download {
+                    let mut incomplete_loads = self.incomplete_loads.borrow_mut();
+                    let load = &mut incomplete_loads[idx];
+                    if self.allows_downloads(&load.load_data) {
+                        // The resource thread hands the rest of the response to the download
+                        // manager, so the fetch must keep running.
+                        load.canceller.ignore();
+                    } else {
+                        warn!("Download of {} blocked by sandboxing", load.load_data.url);
+                        load.canceller.cancel();
+                    }
+                }
+
+                if is20x || should_download {
                     // If we have an existing window that is being navigated:
                     if let Some(window) = self.documents.borrow().find_window(*id) {
                         let window_proxy = window.window_proxy();
@@ -3018,7 +3685,7 @@ impl ScriptThread {
     fn load(
         &self,
         metadata: Metadata,
-        incomplete: InProgressLoad,
+        mut incomplete: InProgressLoad,
         can_gc: CanGc,
     ) -> DomRoot<ServoParser> {
         let final_url = metadata.final_url.clone();
@@ -3036,8 +3703,11 @@ impl ScriptThread {
             incomplete.load_data.url, incomplete.pipeline_id
         );
 
-        let origin = if final_url.as_str() == "about:blank" || final_url.as_str() == "about:srcdoc"
-        {
+        // Documents that were sandboxed without `allow-same-origin` get a unique opaque origin.
+        let sandboxing_flag_set = incomplete.load_data.sandboxing_flag_set;
+        let origin = if sandboxing_flag_set.contains(SandboxingFlagSet::ORIGIN) {
+            MutableOrigin::new(ImmutableOrigin::new_opaque())
+        } else if final_url.as_str() == "about:blank" || final_url.as_str() == "about:srcdoc" {
             incomplete.origin.clone()
         } else {
             MutableOrigin::new(final_url.origin())
@@ -3065,6 +3735,12 @@ impl ScriptThread {
             time_profiler_chan: self.senders.time_profiler_sender.clone(),
             compositor_api: self.compositor_api.clone(),
             window_size: incomplete.window_size,
+            user_stylesheets: self
+                .user_content_manager
+                .borrow()
+                .stylesheets_for_url(&final_url)
+                .map(|stylesheet| stylesheet.source.clone())
+                .collect(),
         };
 
         // Create the window and document objects.
@@ -3100,14 +3776,32 @@ impl ScriptThread {
             self.unminify_js,
             self.unminify_css,
             self.local_script_source.clone(),
-            self.user_content_manager.clone(),
+            self.user_content_manager.borrow().clone(),
             self.user_agent.clone(),
             self.player_context.clone(),
+            self.network_status.get(),
+            self.theme.get(),
+            self.emulated_media_features
+                .borrow()
+                .get(&incomplete.webview_id)
+                .copied()
+                .unwrap_or_default(),
             #[cfg(feature = "webgpu")]
             self.gpu_id_hub.clone(),
             incomplete.load_data.inherited_secure_context,
+            incomplete.load_data.top_level_origin.clone(),
         );
 
+        if let Some(debugger_global) = self.debugger_global.get() {
+            debugger_global.add_debuggee(&window, can_gc);
+        }
+
+        window.set_is_portal(incomplete.load_data.is_portal);
+        window.set_is_prerendering(incomplete.load_data.is_prerendering);
+        if let Some(data) = incomplete.portal_activation_data.take() {
+            window.activate_portal(data);
+        }
+
         let _realm = enter_realm(&*window);
 
         // Initialize the browsing context for the window.
@@ -3193,13 +3887,23 @@ impl ScriptThread {
             .into();
 
         document.set_referrer_policy(referrer_policy);
+        document.set_active_sandboxing_flag_set(sandboxing_flag_set);
         document.set_ready_state(DocumentReadyState::Loading, can_gc);
+        document.set_initial_page_visibility(
+            !self
+                .hidden_webviews
+                .borrow()
+                .contains(&incomplete.webview_id),
+        );
 
         self.documents
             .borrow_mut()
             .insert(incomplete.pipeline_id, &document);
 
         window.init_document(&document);
+        window
+            .History()
+            .set_entry_state(incomplete.load_data.session_history_entry_state);
 
         // For any similar-origin iframe, ensure that the contentWindow/contentDocument
         // APIs resolve to the new window/document as soon as parsing starts.
@@ -3364,6 +4068,19 @@ impl ScriptThread {
         load_data.url = ServoUrl::parse("about:blank").unwrap();
     }
 
+    /// Remember which pipeline, and which webview, the task being run belongs to, so that a
+    /// panic while running it is attributed to them.
+    fn install_crash_reporting_ids(&self, pipeline_id: Option<PipelineId>) {
+        // Tasks that don't belong to a pipeline must not be blamed on the pipeline of the
+        // previous task.
+        PipelineId::install(pipeline_id);
+        if let Some(document) =
+            pipeline_id.and_then(|pipeline_id| self.documents.borrow().find_document(pipeline_id))
+        {
+            WebViewId::install(document.webview_id());
+        }
+    }
+
     /// Instructs the constellation to fetch the document that will be loaded. Stores the InProgressLoad
     /// argument until a notification is received that the fetch is complete.
     fn pre_page_load(&self, mut incomplete: InProgressLoad) {
@@ -3373,7 +4090,9 @@ impl ScriptThread {
             .borrow_mut()
             .push((incomplete.pipeline_id, context));
 
-        let request_builder = incomplete.request_builder();
+        let request_builder = incomplete
+            .request_builder()
+            .allows_downloads(self.allows_downloads(&incomplete.load_data));
         incomplete.canceller = FetchCanceller::new(request_builder.id);
         NavigationListener::new(request_builder, self.senders.self_sender.clone())
             .initiate_fetch(&self.resource_threads.core_thread, None);
@@ -3471,7 +4190,9 @@ impl ScriptThread {
         // in the new `RequestBuilder` as well.
         incomplete_load.url_list.push(metadata.final_url.clone());
 
-        let mut request_builder = incomplete_load.request_builder();
+        let mut request_builder = incomplete_load
+            .request_builder()
+            .allows_downloads(self.allows_downloads(&incomplete_load.load_data));
         request_builder.referrer = metadata
             .referrer
             .clone()
@@ -3502,6 +4223,20 @@ impl ScriptThread {
             .initiate_fetch(&self.resource_threads.core_thread, response_init);
     }
 
+    /// Whether a navigation with `load_data` may hand its response to the download manager.
+    /// Documents that were sandboxed without `allow-downloads` can't start downloads, whether
+    /// in their own browsing context or another one.
+    /// <https://html.spec.whatwg.org/multipage/#allowed-to-download>
+    fn allows_downloads(&self, load_data: &LoadData) -> bool {
+        let source_sandboxing_flag_set = load_data
+            .creator_pipeline_id
+            .and_then(|creator| self.documents.borrow().find_document(creator))
+            .map(|document| document.active_sandboxing_flag_set())
+            .unwrap_or_default();
+        let sandboxing_flag_set = source_sandboxing_flag_set | load_data.sandboxing_flag_set;
+        !sandboxing_flag_set.contains(SandboxingFlagSet::DOWNLOADS)
+    }
+
     /// Synchronously fetch `about:blank`. Stores the `InProgressLoad`
     /// argument until a notification is received that the fetch is complete.
     fn start_page_load_about_blank(&self, mut incomplete: InProgressLoad) {
@@ -0,0 +1,548 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Parsing and processing of [speculation rules](https://wicg.github.io/nav-speculation/speculation-rules.html),
+//! which allow a document to ask for likely navigations to be prefetched or prerendered.
+
+use std::collections::HashSet;
+
+use malloc_size_of_derive::MallocSizeOf;
+use net_traits::request::{Destination, Initiator, RequestId};
+use net_traits::{
+    FetchMetadata, FetchResponseListener, NetworkError, ReferrerPolicy, ResourceFetchTiming,
+    ResourceTimingType,
+};
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomGlobal;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::console::Console;
+use crate::dom::document::{Document, determine_policy_for_token};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::fetch::create_a_potential_cors_request;
+use crate::network_listener::{PreInvoke, ResourceTimingListener, submit_timing};
+use crate::script_runtime::CanGc;
+
+/// The keys that are allowed in a speculation rule.
+const SPECULATION_RULE_KEYS: &[&str] = &[
+    "source",
+    "urls",
+    "where",
+    "relative_to",
+    "eagerness",
+    "referrer_policy",
+    "tag",
+    "requires",
+    "expects_no_vary_search",
+    "target_hint",
+];
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-eagerness>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SpeculationRuleEagerness {
+    Immediate,
+    Eager,
+    Moderate,
+    Conservative,
+}
+
+impl SpeculationRuleEagerness {
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "immediate" => Some(Self::Immediate),
+            "eager" => Some(Self::Eager),
+            "moderate" => Some(Self::Moderate),
+            "conservative" => Some(Self::Conservative),
+            _ => None,
+        }
+    }
+
+    /// Whether rules with this eagerness are acted upon as soon as they are registered, rather
+    /// than waiting for the user to show interest in a link.
+    fn is_immediate(self) -> bool {
+        // TODO: "moderate" and "conservative" rules should be triggered by hovering or pressing
+        // on a matching link.
+        matches!(self, Self::Immediate | Self::Eager)
+    }
+}
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule>
+pub(crate) struct SpeculationRule {
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-urls>
+    urls: Vec<ServoUrl>,
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-eagerness>
+    eagerness: SpeculationRuleEagerness,
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-referrer-policy>
+    referrer_policy: ReferrerPolicy,
+}
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set>
+#[derive(Default)]
+pub(crate) struct SpeculationRuleSet {
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set-prefetch-rules>
+    prefetch_rules: Vec<SpeculationRule>,
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set-prerender-rules>
+    prerender_rules: Vec<SpeculationRule>,
+}
+
+impl SpeculationRuleSet {
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule-set-string>
+    pub(crate) fn parse(
+        input: &str,
+        document: &Document,
+        base_url: &ServoUrl,
+    ) -> Result<SpeculationRuleSet, String> {
+        // Step 1. Let parsed be the result of parsing a JSON string to an Infra value given input.
+        let parsed: Value = serde_json::from_str(input).map_err(|error| error.to_string())?;
+
+        // Step 2. If parsed is not a map, then throw a TypeError indicating that the top-level
+        // value needs to be a JSON object.
+        let Value::Object(parsed) = parsed else {
+            return Err("Speculation rules must be a JSON object".to_owned());
+        };
+
+        // Step 3. Let result be a new speculation rule set.
+        let mut result = SpeculationRuleSet::default();
+
+        // Step 4. Let tag be null.
+        // Step 5. If parsed["tag"] exists:
+        if let Some(tag) = parsed.get("tag") {
+            // Step 5.1. If parsed["tag"] is not a speculation rule tag, then throw a TypeError
+            // indicating that the speculation rule tag is invalid.
+            // Step 5.2. Set tag to parsed["tag"].
+            // TODO: Tags are only used for the `Sec-Speculation-Tags` header, which is not sent.
+            if !is_a_speculation_rule_tag(tag) {
+                return Err("Invalid speculation rule tag".to_owned());
+            }
+        }
+
+        // Step 6. Let typesToTreatAsPrefetch be « "prefetch" ».
+        // Step 7. The user agent may append "prerender" to typesToTreatAsPrefetch.
+        // Step 8. For each type of typesToTreatAsPrefetch:
+        result.prefetch_rules = parse_speculation_rules(&parsed, "prefetch", document, base_url);
+
+        // Step 9. If parsed["prerender"] exists:
+        result.prerender_rules = parse_speculation_rules(&parsed, "prerender", document, base_url);
+
+        // Step 10. Return result.
+        Ok(result)
+    }
+
+    pub(crate) fn prefetch_rules(&self) -> &[SpeculationRule] {
+        &self.prefetch_rules
+    }
+
+    pub(crate) fn prerender_rules(&self) -> &[SpeculationRule] {
+        &self.prerender_rules
+    }
+}
+
+impl SpeculationRule {
+    /// The URLs that should be loaded now for this rule, or nothing if this rule is only
+    /// triggered by user interaction.
+    pub(crate) fn immediate_urls(&self) -> &[ServoUrl] {
+        if self.eagerness.is_immediate() {
+            &self.urls
+        } else {
+            &[]
+        }
+    }
+
+    pub(crate) fn referrer_policy(&self) -> ReferrerPolicy {
+        self.referrer_policy
+    }
+}
+
+/// The URLs a document has already prefetched or prerendered due to its speculation rules, so
+/// that a URL mentioned by several rules is only loaded once.
+#[derive(Default, MallocSizeOf)]
+pub(crate) struct SpeculativeLoads {
+    pub(crate) prefetched_urls: HashSet<ServoUrl>,
+    pub(crate) prerendered_urls: HashSet<ServoUrl>,
+}
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-tag>
+fn is_a_speculation_rule_tag(value: &Value) -> bool {
+    // > A speculation rule tag is either an ASCII string whose code points are all in the range
+    // > U+0020 to U+007E inclusive, or null.
+    match value {
+        Value::Null => true,
+        Value::String(tag) => tag.chars().all(|c| (' '..='~').contains(&c)),
+        _ => false,
+    }
+}
+
+fn report_a_warning(document: &Document, message: String) {
+    Console::internal_warn(
+        document.window().upcast::<GlobalScope>(),
+        DOMString::from(message),
+    );
+}
+
+/// Steps 8 and 9 of <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule-set-string>
+fn parse_speculation_rules(
+    parsed: &Map<String, Value>,
+    rule_type: &str,
+    document: &Document,
+    base_url: &ServoUrl,
+) -> Vec<SpeculationRule> {
+    // If parsed[type] exists:
+    let Some(rules) = parsed.get(rule_type) else {
+        return vec![];
+    };
+
+    // If parsed[type] is a list, then for each rule of parsed[type]:
+    let Value::Array(rules) = rules else {
+        // Otherwise, the user agent may report a warning to the console indicating that the
+        // rules list for type needs to be a JSON array.
+        report_a_warning(
+            document,
+            format!("The \"{rule_type}\" speculation rules must be a JSON array"),
+        );
+        return vec![];
+    };
+
+    // Let rule be the result of parsing a speculation rule given rule, tag, document, and
+    // baseURL. If rule is null, then continue. Append rule to result's rules.
+    rules
+        .iter()
+        .filter_map(|rule| parse_a_speculation_rule(rule, document, base_url))
+        .collect()
+}
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule>
+fn parse_a_speculation_rule(
+    input: &Value,
+    document: &Document,
+    base_url: &ServoUrl,
+) -> Option<SpeculationRule> {
+    // Step 1. If input is not a map, then the user agent may report a warning to the console
+    // indicating that the rule needs to be a JSON object, and return null.
+    let Value::Object(input) = input else {
+        report_a_warning(
+            document,
+            "A speculation rule must be a JSON object".to_owned(),
+        );
+        return None;
+    };
+
+    // Step 2. If input has any key other than those allowed, then the user agent may report a
+    // warning to the console indicating that the rule has unrecognized keys, and return null.
+    if let Some(key) = input
+        .keys()
+        .find(|key| !SPECULATION_RULE_KEYS.contains(&key.as_str()))
+    {
+        report_a_warning(
+            document,
+            format!("Unrecognized key \"{key}\" in speculation rule"),
+        );
+        return None;
+    }
+
+    // Step 3. Let source be null.
+    // Step 4. If input["source"] exists, then set source to input["source"].
+    // Step 5. Otherwise, if input["urls"] exists and input["where"] does not exist, then set
+    // source to "list".
+    // Step 6. Otherwise, if input["where"] exists and input["urls"] does not exist, then set
+    // source to "document".
+    let source = match input.get("source") {
+        Some(source) => source.as_str(),
+        None if input.contains_key("urls") && !input.contains_key("where") => Some("list"),
+        None if input.contains_key("where") && !input.contains_key("urls") => Some("document"),
+        None => None,
+    };
+
+    // Step 7. If source is neither "list" nor "document", then the user agent may report a
+    // warning to the console indicating that a source could not be inferred or an invalid
+    // source was specified, and return null.
+    let urls = match source {
+        Some("list") => parse_list_rule_urls(input, document, base_url)?,
+        Some("document") => {
+            // TODO: Document rules match links in the document against a predicate.
+            report_a_warning(
+                document,
+                "Document speculation rules are not supported".to_owned(),
+            );
+            return None;
+        },
+        _ => {
+            report_a_warning(
+                document,
+                "A speculation rule must have a \"list\" or \"document\" source".to_owned(),
+            );
+            return None;
+        },
+    };
+
+    // Step 10. Let eagerness be "immediate" if source is "list"; otherwise, "conservative".
+    // Step 11. If input["eagerness"] exists:
+    let eagerness = match input.get("eagerness") {
+        // Step 11.1. If input["eagerness"] is not a speculation rule eagerness, then the user agent
+        // may report a warning to the console indicating that the eagerness was invalid, and
+        // return null.
+        // Step 11.2. Set eagerness to input["eagerness"].
+        Some(eagerness) => {
+            let Some(eagerness) = eagerness
+                .as_str()
+                .and_then(SpeculationRuleEagerness::from_str)
+            else {
+                report_a_warning(document, "Invalid speculation rule eagerness".to_owned());
+                return None;
+            };
+            eagerness
+        },
+        None => SpeculationRuleEagerness::Immediate,
+    };
+
+    // Step 12. Let referrerPolicy be the empty string.
+    // Step 13. If input["referrer_policy"] exists:
+    let referrer_policy = match input.get("referrer_policy") {
+        // Step 13.1. If input["referrer_policy"] is not a referrer policy, then the user agent may
+        // report a warning to the console indicating that the referrer policy was invalid, and
+        // return null.
+        // Step 13.2. Set referrerPolicy to input["referrer_policy"].
+        Some(referrer_policy) => {
+            let policy = referrer_policy.as_str().map(determine_policy_for_token);
+            match (referrer_policy.as_str(), policy) {
+                (Some(""), _) => ReferrerPolicy::EmptyString,
+                (_, Some(policy)) if policy != ReferrerPolicy::EmptyString => policy,
+                _ => {
+                    report_a_warning(
+                        document,
+                        "Invalid speculation rule referrer policy".to_owned(),
+                    );
+                    return None;
+                },
+            }
+        },
+        None => ReferrerPolicy::EmptyString,
+    };
+
+    // Step 14-15. Validate the rule's tag.
+    if input
+        .get("tag")
+        .is_some_and(|tag| !is_a_speculation_rule_tag(tag))
+    {
+        report_a_warning(document, "Invalid speculation rule tag".to_owned());
+        return None;
+    }
+
+    // Step 16. Let requirements be an empty ordered set.
+    // Step 17. If input["requires"] exists:
+    if let Some(requirements) = input.get("requires") {
+        // Step 17.1. If input["requires"] is not a list, then the user agent may report a
+        // warning to the console indicating that the requirements were not understood, and
+        // return null.
+        // Step 17.2. For each requirement of input["requires"]: If requirement is not a
+        // speculation rule requirement, then the user agent may report a warning to the console
+        // indicating that the requirement was not understood, and return null.
+        let Value::Array(requirements) = requirements else {
+            report_a_warning(document, "Invalid speculation rule requirements".to_owned());
+            return None;
+        };
+        if requirements.iter().any(|requirement| {
+            requirement.as_str() != Some("anonymous-client-ip-when-cross-origin")
+        }) {
+            report_a_warning(document, "Invalid speculation rule requirement".to_owned());
+            return None;
+        }
+
+        // Servo cannot hide the client's IP address, so rules that require it never apply.
+        if !requirements.is_empty() {
+            return None;
+        }
+    }
+
+    // Step 18-19. Validate the No-Vary-Search hint.
+    // TODO: The hint should be used to match navigations against prefetched responses.
+    if input
+        .get("expects_no_vary_search")
+        .is_some_and(|hint| !hint.is_string())
+    {
+        report_a_warning(document, "Invalid No-Vary-Search hint".to_owned());
+        return None;
+    }
+
+    // Step 20-21. Validate the target navigable name hint.
+    if input
+        .get("target_hint")
+        .is_some_and(|hint| !hint.is_string())
+    {
+        report_a_warning(document, "Invalid speculation rule target hint".to_owned());
+        return None;
+    }
+
+    // Step 22. Return a speculation rule.
+    Some(SpeculationRule {
+        urls,
+        eagerness,
+        referrer_policy,
+    })
+}
+
+/// Step 8 of <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule>
+fn parse_list_rule_urls(
+    input: &Map<String, Value>,
+    document: &Document,
+    base_url: &ServoUrl,
+) -> Option<Vec<ServoUrl>> {
+    // Step 8.1. If input["where"] exists, then the user agent may report a warning to the
+    // console indicating that there were conflicting sources for this rule, and return null.
+    if input.contains_key("where") {
+        report_a_warning(
+            document,
+            "A list speculation rule cannot have a \"where\" key".to_owned(),
+        );
+        return None;
+    }
+
+    // Step 8.2. If input["relative_to"] exists:
+    let base_url = match input.get("relative_to").map(Value::as_str) {
+        None | Some(Some("ruleset")) => base_url.clone(),
+        // Step 8.2.2. If input["relative_to"] is "document", then set baseURL to document's
+        // document base URL.
+        Some(Some("document")) => document.base_url(),
+        // Step 8.2.1. If input["relative_to"] is neither "ruleset" nor "document", then the user
+        // agent may report a warning to the console indicating that the supplied relative-to
+        // value was invalid, and return null.
+        Some(_) => {
+            report_a_warning(
+                document,
+                "Invalid speculation rule \"relative_to\" value".to_owned(),
+            );
+            return None;
+        },
+    };
+
+    // Step 8.3. If input["urls"] does not exist or is not a list, then the user agent may report
+    // a warning to the console indicating that the supplied URL list was invalid, and return
+    // null.
+    let Some(Value::Array(url_strings)) = input.get("urls") else {
+        report_a_warning(document, "Invalid speculation rule URL list".to_owned());
+        return None;
+    };
+
+    // Step 8.4. For each urlString of input["urls"]:
+    let mut urls = Vec::with_capacity(url_strings.len());
+    for url_string in url_strings {
+        // Step 8.4.1. If urlString is not a string, then the user agent may report a warning to
+        // the console indicating that the supplied URL must be a string, and return null.
+        let Some(url_string) = url_string.as_str() else {
+            report_a_warning(document, "Speculation rule URLs must be strings".to_owned());
+            return None;
+        };
+
+        // Step 8.4.2. Let parsedURL be the result of URL parsing urlString with baseURL.
+        // Step 8.4.3. If parsedURL is failure, or parsedURL's scheme is not an HTTP(S) scheme,
+        // then continue.
+        let Ok(parsed_url) = base_url.join(url_string) else {
+            continue;
+        };
+        if !matches!(parsed_url.scheme(), "http" | "https") {
+            continue;
+        }
+
+        // Step 8.4.4. Append parsedURL to urls.
+        urls.push(parsed_url);
+    }
+    Some(urls)
+}
+
+/// Prefetch `url` on behalf of `document`'s speculation rules.
+///
+/// <https://wicg.github.io/nav-speculation/prefetch.html#prefetch>
+pub(crate) fn prefetch(document: &Document, url: ServoUrl, referrer_policy: ReferrerPolicy) {
+    let global = document.global();
+    let referrer_policy = match referrer_policy {
+        ReferrerPolicy::EmptyString => document.get_referrer_policy(),
+        referrer_policy => referrer_policy,
+    };
+    let request = create_a_potential_cors_request(
+        Some(document.webview_id()),
+        url.clone(),
+        Destination::Document,
+        None,
+        None,
+        global.get_referrer(),
+        document.insecure_requests_policy(),
+    )
+    .origin(document.origin().immutable().clone())
+    .pipeline_id(Some(global.pipeline_id()))
+    .policy_container(document.policy_container().to_owned())
+    .referrer_policy(referrer_policy)
+    .initiator(Initiator::Prefetch);
+
+    let context = SpeculativePrefetchContext {
+        document: Trusted::new(document),
+        resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+        url,
+    };
+    document.fetch_background(request, context);
+}
+
+/// The fetch listener for a prefetch started by speculation rules. The response is only fetched
+/// so that it ends up in the HTTP cache for the navigation that follows.
+struct SpeculativePrefetchContext {
+    /// The document whose speculation rules caused this prefetch.
+    document: Trusted<Document>,
+
+    resource_timing: ResourceFetchTiming,
+
+    /// The url being prefetched
+    url: ServoUrl,
+}
+
+impl FetchResponseListener for SpeculativePrefetchContext {
+    fn process_request_body(&mut self, _: RequestId) {}
+
+    fn process_request_eof(&mut self, _: RequestId) {}
+
+    fn process_response(&mut self, _: RequestId, _: Result<FetchMetadata, NetworkError>) {}
+
+    fn process_response_chunk(&mut self, _: RequestId, _: Vec<u8>) {}
+
+    fn process_response_eof(
+        &mut self,
+        _: RequestId,
+        response: Result<ResourceFetchTiming, NetworkError>,
+    ) {
+        if let Err(error) = response {
+            debug!("Speculative prefetch of {} failed: {:?}", self.url, error);
+        }
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        submit_timing(self, CanGc::note())
+    }
+}
+
+impl ResourceTimingListener for SpeculativePrefetchContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (InitiatorType::Other, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.document.root().global()
+    }
+}
+
+impl PreInvoke for SpeculativePrefetchContext {
+    fn should_invoke(&self) -> bool {
+        // Prefetch requests are never aborted.
+        true
+    }
+}
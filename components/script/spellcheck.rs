@@ -0,0 +1,251 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Spellchecking of the values of text controls, using the dictionaries of the embedder.
+//!
+//! The words of the value of a text control are checked by the embedder whenever the value
+//! changes while the control is focused. Layout then marks the misspelled words, and the
+//! context menu of the control offers the embedder's suggestions for correcting the
+//! misspelled word at the caret.
+//!
+//! <https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking>
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use embedder_traits::{ContextMenuElementInformation, ContextMenuResult, EmbedderMsg};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+
+use crate::clipboard_provider::EmbedderClipboardProvider;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::conversions::DerivedFrom;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::node::{Node, NodeDamage, NodeTraits};
+use crate::dom::textcontrol::TextControlElement;
+use crate::task_source::SendableTaskSource;
+use crate::textinput::{SelectionDirection, TextInput, UTF8Bytes, word_segments};
+
+/// The words of `text` whose spelling can be checked, along with their byte ranges.
+fn words(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    word_segments(text)
+        .into_iter()
+        .filter(|(_, is_word)| *is_word)
+        .map(|(range, _)| (range.clone(), &text[range]))
+        .filter(|(_, word)| word.chars().any(char::is_alphabetic))
+}
+
+/// The state of spellchecking for the value of a text control.
+#[derive(Default, MallocSizeOf)]
+pub(crate) struct Spelling {
+    /// Whether each of the words whose spelling was checked by the embedder is misspelled.
+    checked_words: HashMap<String, bool>,
+    /// The value whose misspelled words are known.
+    checked_text: String,
+    /// The byte ranges of the misspelled words in [`Self::checked_text`].
+    misspelled_words: Vec<Range<usize>>,
+    /// The value whose words are being checked by the embedder, if any.
+    pending_text: Option<String>,
+}
+
+impl Spelling {
+    /// The byte ranges of the misspelled words in `text`, which are only known if it is the
+    /// value that was checked most recently.
+    pub(crate) fn misspelled_words(&self, text: &str) -> Vec<Range<usize>> {
+        if text != self.checked_text {
+            return Vec::new();
+        }
+        self.misspelled_words.clone()
+    }
+
+    fn set_checked_text(&mut self, text: String) {
+        self.misspelled_words = words(&text)
+            .filter(|(_, word)| self.checked_words.get(*word).copied().unwrap_or_default())
+            .map(|(range, _)| range)
+            .collect();
+        self.checked_text = text;
+        self.pending_text = None;
+    }
+
+    /// Forget the misspelled words of the value, returning whether any were known.
+    fn clear(&mut self) -> bool {
+        self.checked_text.clear();
+        self.pending_text = None;
+        !std::mem::take(&mut self.misspelled_words).is_empty()
+    }
+}
+
+/// A text control whose value can be spellchecked.
+pub(crate) trait SpellcheckedTextControl:
+    TextControlElement + DerivedFrom<Element> + DomObject
+{
+    fn textinput(&self) -> &DomRefCell<TextInput<EmbedderClipboardProvider>>;
+    fn spelling(&self) -> &DomRefCell<Spelling>;
+
+    /// Whether the value of this control is checkable text and spellchecking is enabled for
+    /// it.
+    fn is_spellchecked(&self) -> bool;
+}
+
+/// Check the spelling of the value of `element` if it changed since it was last checked.
+pub(crate) fn check_spelling<E: SpellcheckedTextControl>(element: &E) {
+    let node = element.upcast::<Node>();
+    let mut spelling = element.spelling().borrow_mut();
+    if !element.is_spellchecked() {
+        if spelling.clear() {
+            node.dirty(NodeDamage::OtherNodeDamage);
+        }
+        return;
+    }
+
+    // Only the values that the user is editing are checked.
+    if !element.upcast::<Element>().focus_state() {
+        return;
+    }
+
+    let text = String::from(element.textinput().borrow().get_content());
+    if text == spelling.checked_text || spelling.pending_text.as_ref() == Some(&text) {
+        return;
+    }
+
+    let mut unchecked_words: Vec<String> = words(&text)
+        .filter(|(_, word)| !spelling.checked_words.contains_key(*word))
+        .map(|(_, word)| word.to_owned())
+        .collect();
+    unchecked_words.sort_unstable();
+    unchecked_words.dedup();
+    if unchecked_words.is_empty() {
+        spelling.set_checked_text(text);
+        node.dirty(NodeDamage::OtherNodeDamage);
+        return;
+    }
+    spelling.pending_text = Some(text.clone());
+
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    let trusted_element = Trusted::new(element);
+    let task_source: SendableTaskSource = element
+        .owner_global()
+        .task_manager()
+        .user_interaction_task_source()
+        .into();
+    let words = unchecked_words.clone();
+    ROUTER.add_typed_route(
+        receiver,
+        Box::new(move |message| {
+            let misspelled = match message {
+                Ok(misspelled) => misspelled,
+                Err(error) => return warn!("Error receiving spellcheck result: {error:?}"),
+            };
+            let element = trusted_element.clone();
+            let words = words.clone();
+            let text = text.clone();
+            task_source.queue(task!(handle_spellcheck_result: move || {
+                handle_spellcheck_result(&*element.root(), words, misspelled, text);
+            }));
+        }),
+    );
+
+    let window = element.owner_window();
+    window.send_to_embedder(EmbedderMsg::CheckSpelling(
+        window.webview_id(),
+        unchecked_words,
+        sender,
+    ));
+}
+
+fn handle_spellcheck_result<E: SpellcheckedTextControl>(
+    element: &E,
+    words: Vec<String>,
+    misspelled: Vec<bool>,
+    text: String,
+) {
+    let mut spelling = element.spelling().borrow_mut();
+    spelling
+        .checked_words
+        .extend(words.into_iter().zip(misspelled));
+
+    // The value may have changed again while its words were being checked.
+    if spelling.pending_text.as_ref() != Some(&text) {
+        return;
+    }
+    spelling.set_checked_text(text);
+    element.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+}
+
+/// Show the context menu of `element` with the embedder's suggestions for correcting the
+/// misspelled word at its caret, returning `false` if there is no misspelled word there.
+pub(crate) fn show_spelling_context_menu<E: SpellcheckedTextControl>(element: &E) -> bool {
+    let (text, selection) = {
+        let textinput = element.textinput().borrow();
+        (
+            String::from(textinput.get_content()),
+            UTF8Bytes::unwrap_range(textinput.sorted_selection_offsets_range()),
+        )
+    };
+    let Some(word) = element
+        .spelling()
+        .borrow()
+        .misspelled_words(&text)
+        .into_iter()
+        .find(|word| word.start <= selection.start && selection.end <= word.end)
+    else {
+        return false;
+    };
+
+    let window = element.owner_window();
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    window.send_to_embedder(EmbedderMsg::GetSpellingSuggestions(
+        window.webview_id(),
+        text[word.clone()].to_owned(),
+        sender,
+    ));
+    let suggestions = receiver.recv().unwrap_or_default();
+
+    let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+    window.send_to_embedder(EmbedderMsg::ShowContextMenu(
+        window.webview_id(),
+        sender,
+        None,
+        suggestions.clone(),
+        ContextMenuElementInformation {
+            editable: true,
+            ..Default::default()
+        },
+    ));
+    if let Ok(ContextMenuResult::Selected(index)) = receiver.recv() {
+        if let Some(suggestion) = suggestions.get(index) {
+            replace_word(element, word, suggestion);
+        }
+    }
+    true
+}
+
+/// Replace a misspelled word in the value of `element` with a correction, as if the user
+/// typed it.
+fn replace_word<E: SpellcheckedTextControl>(element: &E, word: Range<usize>, correction: &str) {
+    {
+        let mut textinput = element.textinput().borrow_mut();
+        textinput.set_selection_range(word.start as u32, word.end as u32, SelectionDirection::None);
+        textinput.replace_selection(DOMString::from(correction));
+    }
+    element.set_dirty_value_flag(true);
+    element.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    element
+        .owner_global()
+        .task_manager()
+        .user_interaction_task_source()
+        .queue_event(
+            element.upcast::<EventTarget>(),
+            atom!("input"),
+            EventBubbles::Bubbles,
+            EventCancelable::NotCancelable,
+        );
+    check_spelling(element);
+}
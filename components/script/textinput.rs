@@ -9,6 +9,7 @@ use std::cmp::min;
 use std::default::Default;
 use std::ops::{Add, AddAssign, Range};
 
+use icu_segmenter::WordSegmenter;
 use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -17,7 +18,6 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::event::Event;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::NodeTraits;
@@ -196,6 +196,11 @@ pub struct TextInput<T: ClipboardProvider> {
 
     /// Was last change made by set_content?
     was_last_change_by_set_content: bool,
+
+    /// The byte range of the content that is the composition string of the active IME
+    /// composition, if any.
+    #[no_trace]
+    composition: Option<Range<usize>>,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -262,6 +267,22 @@ fn len_of_first_n_code_units(text: &str, n: UTF16CodeUnits) -> UTF8Bytes {
     utf8_len
 }
 
+/// The byte ranges of the segments of `text` between its word boundaries, along with whether
+/// each of them is a word rather than white space or punctuation. These are found by the same
+/// ICU4X word segmenter that layout uses, so that words are the same in script and layout.
+pub(crate) fn word_segments(text: &str) -> Vec<(Range<usize>, bool)> {
+    let segmenter = WordSegmenter::new_auto();
+    let mut boundaries = segmenter.segment_str(text);
+    // There is always a boundary at the start of the text.
+    let mut start = boundaries.next().unwrap_or_default();
+    let mut segments = Vec::new();
+    while let Some(end) = boundaries.next() {
+        segments.push((start..end, boundaries.is_word_like()));
+        start = end;
+    }
+    segments
+}
+
 impl<T: ClipboardProvider> TextInput<T> {
     /// Instantiate a new text input control
     pub fn new(
@@ -282,6 +303,7 @@ impl<T: ClipboardProvider> TextInput<T> {
             min_length,
             selection_direction,
             was_last_change_by_set_content: true,
+            composition: None,
         };
         i.set_content(initial);
         i
@@ -326,6 +348,15 @@ impl<T: ClipboardProvider> TextInput<T> {
         self.replace_selection(DOMString::new());
     }
 
+    /// Remove the selection, or the text from the edit point to the next word boundary in the
+    /// given direction when nothing is selected.
+    pub fn delete_word(&mut self, dir: Direction) {
+        if self.selection_origin.is_none() || self.selection_origin == Some(self.edit_point) {
+            self.adjust_horizontal_by_word(dir, Selection::Selected);
+        }
+        self.replace_selection(DOMString::new());
+    }
+
     /// Insert a character at the current editing point
     pub fn insert_char(&mut self, ch: char) {
         self.insert_string(ch.to_string());
@@ -767,16 +798,10 @@ impl<T: ClipboardProvider> TextInput<T> {
                         input = &self.lines[current_line][..remaining];
                     }
 
-                    let mut iter = input.split_word_bounds().rev();
-                    loop {
-                        match iter.next() {
-                            None => break,
-                            Some(x) => {
-                                shift_temp += UTF8Bytes(x.len());
-                                if x.chars().any(|x| x.is_alphabetic() || x.is_numeric()) {
-                                    break;
-                                }
-                            },
+                    for (segment, is_word) in word_segments(input).into_iter().rev() {
+                        shift_temp += UTF8Bytes(segment.len());
+                        if is_word {
+                            break;
                         }
                     }
                 },
@@ -792,16 +817,10 @@ impl<T: ClipboardProvider> TextInput<T> {
                         input = &self.lines[current_line][current_offset..];
                     }
 
-                    let mut iter = input.split_word_bounds();
-                    loop {
-                        match iter.next() {
-                            None => break,
-                            Some(x) => {
-                                shift_temp += UTF8Bytes(x.len());
-                                if x.chars().any(|x| x.is_alphabetic() || x.is_numeric()) {
-                                    break;
-                                }
-                            },
+                    for (segment, is_word) in word_segments(input) {
+                        shift_temp += UTF8Bytes(segment.len());
+                        if is_word {
+                            break;
                         }
                     }
                 },
@@ -842,6 +861,8 @@ impl<T: ClipboardProvider> TextInput<T> {
                 self.edit_point.index = (self.lines[&self.lines.len() - 1]).len_utf8();
             },
         }
+        self.update_selection_direction();
+        self.assert_ok_selection();
     }
 
     /// Process a given `KeyboardEvent` and return an action for the caller to execute.
@@ -865,6 +886,12 @@ impl<T: ClipboardProvider> TextInput<T> {
             Selection::NotSelected
         };
         mods.remove(Modifiers::SHIFT);
+        // Whole words are deleted with Alt on macOS, and with Control elsewhere.
+        let word_modifier = if macos {
+            Modifiers::ALT
+        } else {
+            Modifiers::CONTROL
+        };
         ShortcutMatcher::new(KeyState::Down, key.clone(), mods)
             .shortcut(Modifiers::CONTROL | Modifiers::ALT, 'B', || {
                 self.adjust_horizontal_by_word(Direction::Backward, maybe_select);
@@ -913,6 +940,14 @@ impl<T: ClipboardProvider> TextInput<T> {
                 }
                 KeyReaction::DispatchInput
             })
+            .shortcut(word_modifier, Key::Delete, || {
+                self.delete_word(Direction::Forward);
+                KeyReaction::DispatchInput
+            })
+            .shortcut(word_modifier, Key::Backspace, || {
+                self.delete_word(Direction::Backward);
+                KeyReaction::DispatchInput
+            })
             .shortcut(Modifiers::empty(), Key::Delete, || {
                 self.delete_char(Direction::Forward);
                 KeyReaction::DispatchInput
@@ -945,6 +980,14 @@ impl<T: ClipboardProvider> TextInput<T> {
                 self.adjust_horizontal_by_word(Direction::Forward, maybe_select);
                 KeyReaction::RedrawSelection
             })
+            .optional_shortcut(!macos, Modifiers::CONTROL, Key::ArrowLeft, || {
+                self.adjust_horizontal_by_word(Direction::Backward, maybe_select);
+                KeyReaction::RedrawSelection
+            })
+            .optional_shortcut(!macos, Modifiers::CONTROL, Key::ArrowRight, || {
+                self.adjust_horizontal_by_word(Direction::Forward, maybe_select);
+                KeyReaction::RedrawSelection
+            })
             .shortcut(Modifiers::empty(), Key::ArrowLeft, || {
                 self.adjust_horizontal_by_one(Direction::Backward, maybe_select);
                 KeyReaction::RedrawSelection
@@ -962,13 +1005,20 @@ impl<T: ClipboardProvider> TextInput<T> {
                 KeyReaction::RedrawSelection
             })
             .shortcut(Modifiers::empty(), Key::Enter, || self.handle_return())
-            .optional_shortcut(macos, Modifiers::empty(), Key::Home, || {
-                self.edit_point.index = UTF8Bytes::zero();
+            .optional_shortcut(!macos, Modifiers::CONTROL, Key::Home, || {
+                self.adjust_horizontal_to_limit(Direction::Backward, maybe_select);
+                KeyReaction::RedrawSelection
+            })
+            .optional_shortcut(!macos, Modifiers::CONTROL, Key::End, || {
+                self.adjust_horizontal_to_limit(Direction::Forward, maybe_select);
                 KeyReaction::RedrawSelection
             })
-            .optional_shortcut(macos, Modifiers::empty(), Key::End, || {
-                self.edit_point.index = self.current_line_length();
-                self.assert_ok_selection();
+            .shortcut(Modifiers::empty(), Key::Home, || {
+                self.adjust_horizontal_to_line_end(Direction::Backward, maybe_select);
+                KeyReaction::RedrawSelection
+            })
+            .shortcut(Modifiers::empty(), Key::End, || {
+                self.adjust_horizontal_to_line_end(Direction::Forward, maybe_select);
                 KeyReaction::RedrawSelection
             })
             .shortcut(Modifiers::empty(), Key::PageUp, || {
@@ -989,22 +1039,54 @@ impl<T: ClipboardProvider> TextInput<T> {
             .unwrap()
     }
 
-    pub(crate) fn handle_compositionend(&mut self, event: &CompositionEvent) -> KeyReaction {
-        self.insert_string(event.data());
+    /// <https://w3c.github.io/uievents/#compositionstart>
+    pub fn handle_compositionstart(&mut self) -> KeyReaction {
+        // The composition string replaces the selection, and is empty until it is updated.
+        let start = self.selection_start_offset().0;
+        self.insert_string("");
+        self.composition = Some(start..start);
         KeyReaction::DispatchInput
     }
 
-    pub(crate) fn handle_compositionupdate(&mut self, event: &CompositionEvent) -> KeyReaction {
-        let start = self.selection_start_offset().0;
-        self.insert_string(event.data());
-        self.set_selection_range(
-            start as u32,
-            (start + event.data().len_utf8().0) as u32,
-            SelectionDirection::Forward,
-        );
+    /// <https://w3c.github.io/uievents/#compositionupdate>
+    pub fn handle_compositionupdate(&mut self, data: &str) -> KeyReaction {
+        let start = self.replace_composition(data);
+        self.composition = Some(start..self.selection_end_offset().0);
         KeyReaction::DispatchInput
     }
 
+    /// <https://w3c.github.io/uievents/#compositionend>
+    pub fn handle_compositionend(&mut self, data: &str) -> KeyReaction {
+        self.replace_composition(data);
+        self.composition = None;
+        KeyReaction::DispatchInput
+    }
+
+    /// Replace the composition string, or the selection if there is no active composition,
+    /// with `text`, leaving the edit point after it. Returns the offset where `text` starts.
+    fn replace_composition(&mut self, text: &str) -> usize {
+        let start = match self.composition.clone() {
+            Some(composition) => {
+                let end = composition.end.min(self.len_utf8().0);
+                self.set_selection_range(
+                    composition.start as u32,
+                    end as u32,
+                    SelectionDirection::Forward,
+                );
+                composition.start.min(end)
+            },
+            None => self.selection_start_offset().0,
+        };
+        self.insert_string(text);
+        start
+    }
+
+    /// The byte range of the content that is the composition string of the active IME
+    /// composition, if any.
+    pub fn composition(&self) -> Option<Range<usize>> {
+        self.composition.clone()
+    }
+
     /// Whether the content is empty.
     pub(crate) fn is_empty(&self) -> bool {
         self.lines.len() <= 1 && self.lines.first().is_none_or(|line| line.is_empty())
@@ -1071,6 +1153,7 @@ impl<T: ClipboardProvider> TextInput<T> {
         };
 
         self.was_last_change_by_set_content = true;
+        self.composition = None;
         self.edit_point = self.edit_point.constrain_to(&self.lines);
 
         if let Some(origin) = self.selection_origin {
@@ -9,11 +9,12 @@ use std::default::Default;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
 
+use base::cross_process_instant::CrossProcessInstant;
 use deny_public_fields::DenyPublicFields;
 use js::jsapi::Heap;
 use js::jsval::{JSVal, UndefinedValue};
 use js::rust::HandleValue;
-use servo_config::pref;
+use servo_config::{opts, pref};
 use timers::{BoxedTimerCallback, TimerEvent, TimerEventId, TimerEventRequest, TimerSource};
 
 use crate::dom::bindings::callback::ExceptionHandling::Report;
@@ -24,7 +25,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{DomGlobal, DomObject};
 use crate::dom::bindings::root::Dom;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::FakeRequestAnimationFrameCallback;
+use crate::dom::document::{FakeRequestAnimationFrameCallback, LongPressCallback};
 use crate::dom::eventsource::EventSourceTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlmetaelement::RefreshRedirectDue;
@@ -52,6 +53,15 @@ pub(crate) struct OneshotTimers {
     /// offset back by this amount for a coherent time across document
     /// activations.
     suspension_offset: Cell<Duration>,
+    /// The time that the clock of the timers was stopped at, while it is controlled by
+    /// tests instead of following the system clock.
+    paused_clock: Cell<Option<Instant>>,
+    /// How far the clock of the timers was moved ahead of the system clock while it was
+    /// paused.
+    clock_advance: Cell<Duration>,
+    /// Whether the clock of the timers was ever paused, after which `performance.now()`
+    /// follows it instead of the system clock.
+    clock_controlled: Cell<bool>,
     /// Calls to `fire_timer` with a different argument than this get ignored.
     /// They were previously scheduled and got invalidated when
     ///  - timers were suspended,
@@ -82,6 +92,7 @@ pub(crate) enum OneshotTimerCallback {
     TestBindingCallback(TestBindingCallback),
     FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
     RefreshRedirectDue(RefreshRedirectDue),
+    LongPress(LongPressCallback),
 }
 
 impl OneshotTimerCallback {
@@ -93,6 +104,7 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(can_gc),
             OneshotTimerCallback::RefreshRedirectDue(callback) => callback.invoke(can_gc),
+            OneshotTimerCallback::LongPress(callback) => callback.invoke(can_gc),
         }
     }
 }
@@ -121,6 +133,9 @@ impl PartialEq for OneshotTimer {
 
 impl OneshotTimers {
     pub(crate) fn new(global_scope: &GlobalScope) -> OneshotTimers {
+        // When rendering deterministically, the clock of a window only advances when a test
+        // asks for it, from the time that the window was created.
+        let deterministic = opts::get().deterministic && global_scope.is::<Window>();
         OneshotTimers {
             global_scope: Dom::from_ref(global_scope),
             js_timers: JsTimers::default(),
@@ -128,6 +143,9 @@ impl OneshotTimers {
             timers: DomRefCell::new(Vec::new()),
             suspended_since: Cell::new(None),
             suspension_offset: Cell::new(Duration::ZERO),
+            paused_clock: Cell::new(deterministic.then(Instant::now)),
+            clock_advance: Cell::new(Duration::ZERO),
+            clock_controlled: Cell::new(deterministic),
             expected_event_id: Cell::new(TimerEventId(0)),
         }
     }
@@ -200,6 +218,11 @@ impl OneshotTimers {
             return;
         }
 
+        self.run_due_timers(base_time, global, can_gc);
+    }
+
+    /// Run the timers that are due at `base_time`, and schedule the next one.
+    fn run_due_timers(&self, base_time: Instant, global: &GlobalScope, can_gc: CanGc) {
         // select timers to run to prevent firing timers
         // that were installed during fire of another timer
         let mut timers_to_run = Vec::new();
@@ -230,10 +253,73 @@ impl OneshotTimers {
     }
 
     fn base_time(&self) -> Instant {
+        if let Some(paused_clock) = self.paused_clock.get() {
+            return paused_clock;
+        }
         let offset = self.suspension_offset.get();
-        match self.suspended_since.get() {
+        let base_time = match self.suspended_since.get() {
             Some(suspend_time) => suspend_time - offset,
             None => Instant::now() - offset,
+        };
+        base_time + self.clock_advance.get()
+    }
+
+    /// Stop the clock of the timers, so that they only run once it is advanced with
+    /// [`Self::advance_clock`].
+    pub(crate) fn pause_clock(&self) {
+        if self.paused_clock.get().is_some() {
+            return;
+        }
+        debug!("Pausing the clock of timers.");
+        self.paused_clock.set(Some(self.base_time()));
+        self.clock_controlled.set(true);
+        self.invalidate_expected_event_id();
+    }
+
+    /// Let the clock of the timers follow the system clock again, from the time that it was
+    /// paused at.
+    pub(crate) fn resume_clock(&self) {
+        let Some(paused_clock) = self.paused_clock.take() else {
+            return;
+        };
+        debug!("Resuming the clock of timers.");
+        let base_time = self.base_time();
+        if paused_clock > base_time {
+            self.clock_advance
+                .set(self.clock_advance.get() + (paused_clock - base_time));
+        } else {
+            self.suspension_offset
+                .set(self.suspension_offset.get() + (base_time - paused_clock));
+        }
+        self.schedule_timer_call();
+    }
+
+    /// The current time of the clock of the timers, if it was ever controlled by tests, so
+    /// that `performance.now()` and the timestamps of animation frames agree with it.
+    pub(crate) fn controlled_clock_time(&self) -> Option<CrossProcessInstant> {
+        if !self.clock_controlled.get() {
+            return None;
+        }
+        let now = Instant::now();
+        let base_time = self.base_time();
+        let cross_process_now = CrossProcessInstant::now();
+        Some(if base_time >= now {
+            cross_process_now + (base_time - now)
+        } else {
+            cross_process_now - (now - base_time)
+        })
+    }
+
+    /// Move the paused clock of the timers ahead, and run the timers that become due, unless
+    /// they are suspended.
+    pub(crate) fn advance_clock(&self, by: Duration, global: &GlobalScope, can_gc: CanGc) {
+        let Some(paused_clock) = self.paused_clock.get() else {
+            return warn!("Advancing the clock of timers that is not paused.");
+        };
+        let base_time = paused_clock + by;
+        self.paused_clock.set(Some(base_time));
+        if self.suspended_since.get().is_none() {
+            self.run_due_timers(base_time, global, can_gc);
         }
     }
 
@@ -278,6 +364,10 @@ impl OneshotTimers {
             // The timer will be scheduled when the pipeline is fully activated.
             return;
         }
+        if self.paused_clock.get().is_some() {
+            // The timer will run when the clock is advanced or resumed.
+            return;
+        }
 
         let timers = self.timers.borrow();
         let Some(timer) = timers.last() else {
@@ -299,7 +389,7 @@ impl OneshotTimers {
             callback,
             source: timer.source,
             id: expected_event_id,
-            duration: timer.scheduled_for - Instant::now(),
+            duration: timer.scheduled_for - self.base_time(),
         };
 
         self.global_scope.schedule_timer(event_request);
@@ -10,9 +10,11 @@ use std::ptr::NonNull;
 use base::id::{BrowsingContextId, PipelineId};
 use cookie::Cookie;
 use embedder_traits::{
-    WebDriverCookieError, WebDriverFrameId, WebDriverJSError, WebDriverJSResult, WebDriverJSValue,
+    AnimationClockRequest, WebDriverCookieError, WebDriverFrameId, WebDriverJSError,
+    WebDriverJSResult, WebDriverJSValue,
 };
 use euclid::default::{Point2D, Rect, Size2D};
+use html5ever::LocalName;
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{
@@ -26,7 +28,7 @@ use net_traits::CookieSource::{HTTP, NonHTTP};
 use net_traits::CoreResourceMsg::{DeleteCookies, GetCookiesDataForUrl, SetCookieForUrl};
 use net_traits::IpcSend;
 use servo_url::ServoUrl;
-use webdriver::common::{WebElement, WebFrame, WebWindow};
+use webdriver::common::{ShadowRoot as WebShadowRoot, WebElement, WebFrame, WebWindow};
 use webdriver::error::ErrorStatus;
 
 use crate::document_collection::DocumentCollection;
@@ -51,9 +53,11 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomGlobal, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlcollection::HTMLCollection;
 use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
@@ -62,6 +66,7 @@ use crate::dom::htmloptionelement::HTMLOptionElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
 use crate::dom::node::{Node, NodeTraits, ShadowIncluding};
 use crate::dom::nodelist::NodeList;
+use crate::dom::shadowroot::ShadowRoot;
 use crate::dom::window::Window;
 use crate::dom::xmlserializer::XMLSerializer;
 use crate::realms::enter_realm;
@@ -69,25 +74,49 @@ use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{CanGc, JSContext as SafeJSContext};
 use crate::script_thread::ScriptThread;
 
+/// Find the node with the given id in the shadow-including tree of a document.
+pub(crate) fn find_node_in_document(document: &Document, node_id: &str) -> Option<DomRoot<Node>> {
+    document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::Yes)
+        .find(|node| node.unique_id() == node_id)
+}
+
+/// <https://w3c.github.io/webdriver/#dfn-get-a-known-element>
 fn find_node_by_unique_id(
     documents: &DocumentCollection,
     pipeline: PipelineId,
     node_id: String,
 ) -> Result<DomRoot<Node>, ErrorStatus> {
-    match documents.find_document(pipeline).and_then(|document| {
-        document
-            .upcast::<Node>()
-            .traverse_preorder(ShadowIncluding::Yes)
-            .find(|node| node.unique_id() == node_id)
-    }) {
-        Some(node) => Ok(node),
-        None => {
-            if ScriptThread::has_node_id(&node_id) {
-                Err(ErrorStatus::StaleElementReference)
-            } else {
-                Err(ErrorStatus::NoSuchElement)
-            }
-        },
+    if let Some(node) = documents
+        .find_document(pipeline)
+        .and_then(|document| find_node_in_document(&document, &node_id))
+    {
+        return Ok(node);
+    }
+
+    // A node that is still in the document of another browsing context is not stale, it is
+    // just not one of the current browsing context.
+    let in_other_document = documents
+        .iter()
+        .any(|(_, document)| find_node_in_document(&document, &node_id).is_some());
+    if !in_other_document && ScriptThread::has_node_id(&node_id) {
+        Err(ErrorStatus::StaleElementReference)
+    } else {
+        Err(ErrorStatus::NoSuchElement)
+    }
+}
+
+/// <https://w3c.github.io/webdriver/#dfn-get-a-known-shadow-root>
+fn find_shadow_root_by_unique_id(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    shadow_root_id: String,
+) -> Result<DomRoot<ShadowRoot>, ErrorStatus> {
+    match find_node_by_unique_id(documents, pipeline, shadow_root_id) {
+        Ok(node) => DomRoot::downcast::<ShadowRoot>(node).ok_or(ErrorStatus::NoSuchShadowRoot),
+        Err(ErrorStatus::StaleElementReference) => Err(ErrorStatus::DetachedShadowRoot),
+        Err(_) => Err(ErrorStatus::NoSuchShadowRoot),
     }
 }
 
@@ -272,6 +301,10 @@ pub(crate) unsafe fn jsval_to_webdriver(
             Ok(WebDriverJSValue::Element(WebElement(
                 element.upcast::<Node>().unique_id(),
             )))
+        } else if let Ok(shadow_root) = root_from_object::<ShadowRoot>(*object, cx) {
+            Ok(WebDriverJSValue::ShadowRoot(WebShadowRoot(
+                shadow_root.upcast::<Node>().unique_id(),
+            )))
         } else if let Ok(window) = root_from_object::<Window>(*object, cx) {
             let window_proxy = window.window_proxy();
             if window_proxy.is_browsing_context_discarded() {
@@ -423,6 +456,19 @@ pub(crate) fn handle_execute_async_script(
     }
 }
 
+pub(crate) fn handle_control_animation_clock(
+    window: Option<DomRoot<Window>>,
+    request: AnimationClockRequest,
+    reply: IpcSender<Result<f64, ErrorStatus>>,
+    can_gc: CanGc,
+) {
+    let result = match window {
+        Some(window) => Ok(window.handle_animation_clock_request(request, can_gc)),
+        None => Err(ErrorStatus::NoSuchWindow),
+    };
+    reply.send(result).unwrap();
+}
+
 pub(crate) fn handle_get_browsing_context_id(
     documents: &DocumentCollection,
     pipeline: PipelineId,
@@ -431,10 +477,16 @@ pub(crate) fn handle_get_browsing_context_id(
 ) {
     reply
         .send(match webdriver_frame_id {
-            WebDriverFrameId::Short(_) => {
-                // This isn't supported yet
-                Err(ErrorStatus::UnsupportedOperation)
-            },
+            WebDriverFrameId::Short(index) => documents
+                .find_document(pipeline)
+                .and_then(|document| {
+                    document
+                        .iframes()
+                        .iter()
+                        .nth(index as usize)
+                        .and_then(|iframe| iframe.browsing_context_id())
+                })
+                .ok_or(ErrorStatus::NoSuchFrame),
             WebDriverFrameId::Element(element_id) => {
                 find_node_by_unique_id(documents, pipeline, element_id).and_then(|node| {
                     node.downcast::<HTMLIFrameElement>()
@@ -442,13 +494,15 @@ pub(crate) fn handle_get_browsing_context_id(
                         .ok_or(ErrorStatus::NoSuchFrame)
                 })
             },
+            // The parent of a top-level browsing context is itself.
             WebDriverFrameId::Parent => documents
                 .find_window(pipeline)
-                .and_then(|window| {
-                    window
-                        .window_proxy()
+                .map(|window| {
+                    let window_proxy = window.window_proxy();
+                    window_proxy
                         .parent()
-                        .map(|parent| parent.browsing_context_id())
+                        .unwrap_or(&window_proxy)
+                        .browsing_context_id()
                 })
                 .ok_or(ErrorStatus::NoSuchFrame),
         })
@@ -758,6 +812,98 @@ pub(crate) fn handle_find_element_elements_tag_name(
         .unwrap();
 }
 
+pub(crate) fn handle_find_shadow_elements_css(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    shadow_root_id: String,
+    selector: String,
+    reply: IpcSender<Result<Vec<String>, ErrorStatus>>,
+) {
+    reply
+        .send(
+            find_shadow_root_by_unique_id(documents, pipeline, shadow_root_id).and_then(
+                |shadow_root| {
+                    shadow_root
+                        .upcast::<Node>()
+                        .query_selector_all(DOMString::from(selector))
+                        .map_err(|_| ErrorStatus::InvalidSelector)
+                        .map(|nodes| {
+                            nodes
+                                .iter()
+                                .map(|x| x.upcast::<Node>().unique_id())
+                                .collect()
+                        })
+                },
+            ),
+        )
+        .unwrap();
+}
+
+pub(crate) fn handle_find_shadow_elements_link_text(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    shadow_root_id: String,
+    selector: String,
+    partial: bool,
+    reply: IpcSender<Result<Vec<String>, ErrorStatus>>,
+) {
+    reply
+        .send(
+            find_shadow_root_by_unique_id(documents, pipeline, shadow_root_id).and_then(
+                |shadow_root| {
+                    all_matching_links(shadow_root.upcast::<Node>(), selector.clone(), partial)
+                },
+            ),
+        )
+        .unwrap();
+}
+
+pub(crate) fn handle_find_shadow_elements_tag_name(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    shadow_root_id: String,
+    selector: String,
+    reply: IpcSender<Result<Vec<String>, ErrorStatus>>,
+    can_gc: CanGc,
+) {
+    reply
+        .send(
+            find_shadow_root_by_unique_id(documents, pipeline, shadow_root_id).map(|shadow_root| {
+                HTMLCollection::by_qualified_name(
+                    &shadow_root.owner_window(),
+                    shadow_root.upcast::<Node>(),
+                    LocalName::from(selector),
+                    can_gc,
+                )
+                .elements_iter()
+                .map(|x| x.upcast::<Node>().unique_id())
+                .collect::<Vec<String>>()
+            }),
+        )
+        .unwrap();
+}
+
+// https://w3c.github.io/webdriver/#get-element-shadow-root
+pub(crate) fn handle_get_element_shadow_root(
+    documents: &DocumentCollection,
+    pipeline: PipelineId,
+    element_id: String,
+    reply: IpcSender<Result<Option<String>, ErrorStatus>>,
+) {
+    reply
+        .send(
+            find_node_by_unique_id(documents, pipeline, element_id).and_then(|node| match node
+                .downcast::<Element>(
+            ) {
+                Some(element) => Ok(element
+                    .shadow_root()
+                    .map(|shadow_root| shadow_root.upcast::<Node>().unique_id())),
+                None => Err(ErrorStatus::NoSuchElement),
+            }),
+        )
+        .unwrap();
+}
+
 pub(crate) fn handle_focus_element(
     documents: &DocumentCollection,
     pipeline: PipelineId,
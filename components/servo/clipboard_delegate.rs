@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use image::RgbaImage;
 use ipc_channel::ipc::IpcSender;
 
 use crate::WebView;
@@ -57,6 +58,10 @@ pub trait ClipboardDelegate {
 
     /// A request to set the text contents of the system clipboard to `new_contents`.
     fn set_text(&self, _webview: WebView, _new_contents: String) {}
+
+    /// A request to set the contents of the system clipboard to an image, for instance when
+    /// the user copies an image with the context menu.
+    fn set_image(&self, _webview: WebView, _image: RgbaImage) {}
 }
 
 pub(crate) struct DefaultClipboardDelegate;
@@ -73,6 +78,10 @@ impl ClipboardDelegate for DefaultClipboardDelegate {
     fn set_text(&self, _webview: WebView, new_contents: String) {
         clipboard::set_text(new_contents);
     }
+
+    fn set_image(&self, _webview: WebView, image: RgbaImage) {
+        clipboard::set_image(image);
+    }
 }
 
 #[cfg(all(
@@ -80,9 +89,11 @@ impl ClipboardDelegate for DefaultClipboardDelegate {
     not(any(target_os = "android", target_env = "ohos"))
 ))]
 mod clipboard {
+    use std::borrow::Cow;
     use std::sync::OnceLock;
 
-    use arboard::Clipboard;
+    use arboard::{Clipboard, ImageData};
+    use image::RgbaImage;
     use parking_lot::Mutex;
 
     use super::StringRequest;
@@ -119,13 +130,26 @@ mod clipboard {
             let _ = clipboard.set_text(new_contents);
         });
     }
+
+    pub(super) fn set_image(image: RgbaImage) {
+        with_shared_clipboard(move |clipboard| {
+            let _ = clipboard.set_image(ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: Cow::Owned(image.into_raw()),
+            });
+        });
+    }
 }
 
 #[cfg(any(not(feature = "clipboard"), target_os = "android", target_env = "ohos"))]
 mod clipboard {
+    use image::RgbaImage;
+
     use super::StringRequest;
 
     pub(super) fn clear() {}
     pub(super) fn get_text(_: StringRequest) {}
     pub(super) fn set_text(_: String) {}
+    pub(super) fn set_image(_: RgbaImage) {}
 }
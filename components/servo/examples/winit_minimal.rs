@@ -8,7 +8,10 @@ use std::rc::Rc;
 
 use compositing::windowing::{AnimationState, EmbedderMethods, WindowMethods};
 use euclid::{Point2D, Scale, Size2D};
-use servo::{RenderingContext, Servo, TouchEventType, WebView, WindowRenderingContext};
+use servo::{
+    AuxiliaryWebViewFeatures, RenderingContext, Servo, TouchEventType, WebView,
+    WindowRenderingContext,
+};
 use servo_geometry::DeviceIndependentPixel;
 use tracing::warn;
 use url::Url;
@@ -53,7 +56,11 @@ impl ::servo::WebViewDelegate for AppState {
         self.window_delegate.window.request_redraw();
     }
 
-    fn request_open_auxiliary_webview(&self, parent_webview: WebView) -> Option<WebView> {
+    fn request_open_auxiliary_webview(
+        &self,
+        parent_webview: WebView,
+        _features: AuxiliaryWebViewFeatures,
+    ) -> Option<WebView> {
         let webview = self.servo.new_auxiliary_webview();
         webview.set_delegate(parent_webview.delegate());
         webview.focus();
@@ -77,6 +77,7 @@ use fonts::SystemFontService;
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods};
 pub use gleam::gl;
 use gleam::gl::RENDERER;
+pub use image::RgbaImage;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 pub use keyboard_types::*;
@@ -89,7 +90,7 @@ use profile_traits::{mem, time};
 use script::{JSEngineSetup, ServiceWorkerManager};
 use script_traits::ScriptToConstellationChan;
 use servo_config::opts::Opts;
-use servo_config::prefs::Preferences;
+use servo_config::prefs::{PrefValue, PreferenceError, Preferences};
 use servo_config::{opts, pref, prefs};
 use servo_delegate::DefaultServoDelegate;
 use servo_media::ServoMedia;
@@ -99,7 +100,9 @@ use servo_url::ServoUrl;
 pub use webgpu;
 #[cfg(feature = "webgpu")]
 use webgpu::swapchain::WGPUImageMap;
-use webrender::{ONE_TIME_USAGE_HINT, RenderApiSender, ShaderPrecacheFlags, UploadMethod};
+use webrender::{
+    CompositorConfig, ONE_TIME_USAGE_HINT, RenderApiSender, ShaderPrecacheFlags, UploadMethod,
+};
 use webrender_api::{ColorF, DocumentId, FramePublishId};
 pub use webrender_traits::rendering_context::{
     OffscreenRenderingContext, RenderingContext, SoftwareRenderingContext, WindowRenderingContext,
@@ -108,7 +111,7 @@ use webrender_traits::{
     CrossProcessCompositorApi, WebrenderExternalImageHandlers, WebrenderExternalImageRegistry,
     WebrenderImageHandlerType,
 };
-use webview::WebViewInner;
+use webview::{SiteZoomLevels, WebViewInner};
 #[cfg(feature = "webxr")]
 pub use webxr;
 pub use {
@@ -123,10 +126,11 @@ pub use {bluetooth, bluetooth_traits};
 use crate::proxies::ConstellationProxy;
 use crate::responders::ServoErrorChannel;
 pub use crate::servo_delegate::{ServoDelegate, ServoError};
-pub use crate::webview::WebView;
+pub use crate::webview::{WebView, ZoomLevels};
 pub use crate::webview_delegate::{
-    AllowOrDenyRequest, AuthenticationRequest, NavigationRequest, PermissionRequest,
-    WebResourceLoad, WebViewDelegate,
+    AllowOrDenyRequest, AuthenticationRequest, Download, NavigationRequest, PermissionRequest,
+    PrintRequest, UnresponsiveScriptRequest, WebResourceLoad, WebResourceResponseLoad,
+    WebViewDelegate,
 };
 
 #[cfg(feature = "webdriver")]
@@ -209,7 +213,15 @@ pub struct Servo {
     /// When accessed, `Servo` will be reponsible for cleaning up the invalid `Weak`
     /// references.
     webviews: RefCell<HashMap<WebViewId, Weak<RefCell<WebViewInner>>>>,
+    /// The zoom levels chosen for each site, which are shared by all [`WebView`]s.
+    site_zoom_levels: SiteZoomLevels,
     servo_errors: ServoErrorChannel,
+    /// The connectivity and quality of the platform's network, as last reported by the embedder.
+    network_status: Cell<NetworkStatus>,
+    /// Whether the embedder forces offline mode, regardless of the platform's connectivity.
+    offline_mode: Cell<bool>,
+    /// The channel to the time profiler, used to record traces on request of the embedder.
+    time_profiler_chan: time::ProfilerChan,
     /// For single-process Servo instances, this field controls the initialization
     /// and deinitialization of the JS Engine. Multiprocess Servo instances have their
     /// own instance that exists in the content process instead.
@@ -260,7 +272,7 @@ impl Servo {
     )]
     pub fn new(
         opts: Opts,
-        preferences: Preferences,
+        mut preferences: Preferences,
         rendering_context: Rc<dyn RenderingContext>,
         mut embedder: Box<dyn EmbedderMethods>,
         window: Rc<dyn WindowMethods>,
@@ -271,6 +283,18 @@ impl Servo {
         opts::set_options(opts);
         let opts = opts::get();
 
+        // Deterministic rendering turns off the settings whose output depends on the display or
+        // on timing.
+        if opts.deterministic {
+            preferences.gfx_subpixel_text_antialiasing_enabled = false;
+            preferences.layout_animations_test_enabled = true;
+        }
+
+        if preferences.gfx_display_p3_enabled && !rendering_context.supports_display_p3() {
+            warn!("Rendering context does not present in Display P3, rendering in sRGB instead.");
+            preferences.gfx_display_p3_enabled = false;
+        }
+
         // Set the preferences globally.
         // TODO: It would be better to make these private to a particular Servo instance.
         servo_config::prefs::set(preferences);
@@ -329,6 +353,7 @@ impl Servo {
         let time_profiler_chan = profile_time::Profiler::create(
             &opts.time_profiling,
             opts.time_profiler_trace_path.clone(),
+            opts.time_profiler_chrome_trace_path.clone(),
         );
         let mem_profiler_chan = profile_mem::Profiler::create();
 
@@ -336,6 +361,8 @@ impl Servo {
             Some(devtools::start_server(
                 pref!(devtools_server_port) as u16,
                 embedder_proxy.clone(),
+                time_profiler_chan.clone(),
+                mem_profiler_chan.clone(),
             ))
         } else {
             None
@@ -402,6 +429,16 @@ impl Servo {
                     clear_color,
                     upload_method,
                     workers,
+                    size_of_op: Some(servo_allocator::usable_size),
+                    compositor_config: CompositorConfig::Draw {
+                        max_partial_present_rects: if pref!(gfx_partial_present_enabled) {
+                            1
+                        } else {
+                            0
+                        },
+                        draw_previous_partial_present_regions: false,
+                        partial_present: None,
+                    },
                     ..Default::default()
                 },
                 None,
@@ -478,12 +515,16 @@ impl Servo {
         let window_size = WindowSizeData {
             initial_viewport: scaled_viewport_size / Scale::new(1.0),
             device_pixel_ratio: Scale::new(device_pixel_ratio),
+            text_zoom: 1.0,
         };
 
         // Create the constellation, which maintains the engine pipelines, including script and
         // layout, as well as the navigation context.
         let mut protocols = ProtocolRegistry::with_internal_protocols();
         protocols.merge(embedder.get_protocol_handlers());
+        for scheme in protocols.secure_schemes() {
+            servo_url::register_secure_scheme(scheme);
+        }
 
         let constellation_chan = create_constellation(
             user_agent,
@@ -521,7 +562,7 @@ impl Servo {
                 sender: compositor_proxy,
                 receiver: compositor_receiver,
                 constellation_chan: constellation_chan.clone(),
-                time_profiler_chan,
+                time_profiler_chan: time_profiler_chan.clone(),
                 mem_profiler_chan,
                 webrender,
                 webrender_document,
@@ -543,7 +584,11 @@ impl Servo {
             embedder_receiver,
             shutdown_state,
             webviews: Default::default(),
+            site_zoom_levels: Default::default(),
             servo_errors: ServoErrorChannel::default(),
+            network_status: Cell::new(NetworkStatus::default()),
+            time_profiler_chan,
+            offline_mode: Cell::new(false),
             _js_engine_setup: js_engine_setup,
         }
     }
@@ -668,12 +713,84 @@ impl Servo {
         self.compositor.borrow_mut().finish_shutting_down();
     }
 
+    /// Notify Servo that the system is low on memory, so that it frees the memory of caches
+    /// that can be filled again later.
+    pub fn notify_memory_pressure(&self) {
+        self.compositor.borrow_mut().notify_memory_pressure();
+    }
+
+    /// Notify Servo that the connectivity or quality of the platform's network changed. Pages
+    /// are told about the change through `navigator.onLine` and `navigator.connection`.
+    pub fn notify_network_status_changed(&self, network_status: NetworkStatus) {
+        self.network_status.set(network_status);
+        self.send_network_status();
+    }
+
+    /// Force Servo to behave as if the network were unreachable, or stop doing so. While
+    /// offline, requests that need the network fail immediately.
+    pub fn set_offline_mode(&self, offline_mode: bool) {
+        self.offline_mode.set(offline_mode);
+        self.send_network_status();
+    }
+
+    fn send_network_status(&self) {
+        let network_status = self.network_status.get();
+        let network_status = NetworkStatus {
+            online: network_status.online && !self.offline_mode.get(),
+            ..network_status
+        };
+        self.constellation_proxy
+            .send(ConstellationMsg::NetworkStatusChanged(network_status));
+    }
+
+    /// Clear the `localStorage` and `sessionStorage` of the site of `url`, including the
+    /// storage of other sites embedded in it, and its storage when embedded in other sites.
+    pub fn clear_storage_for_site(&self, url: url::Url) {
+        self.constellation_proxy
+            .send(ConstellationMsg::ClearStorageForSite(url.into()));
+    }
+
+    /// The reports of every panic that happened since startup, oldest first. These are also
+    /// shown in `about:crashes`.
+    pub fn crash_reports(&self) -> Vec<CrashReport> {
+        net::about::crash_reports()
+    }
+
+    /// Measure the memory used by Servo, attributing it to the origins and documents it is
+    /// used for. The report is given to [`ServoDelegate::notify_memory_report`] once every
+    /// process has been measured. A detailed report is also shown in `about:memory`.
+    pub fn create_memory_report(&self) {
+        self.constellation_proxy
+            .send(ConstellationMsg::CreateMemoryReport);
+    }
+
+    /// Start recording the layout, style, display list, script, IPC and compositing work of
+    /// every process into a file at `path`, in the Chrome trace event format that Perfetto
+    /// and `chrome://tracing` can load. This replaces the recording in progress, if any.
+    pub fn start_trace_recording(&self, path: PathBuf) {
+        self.time_profiler_chan
+            .send(time::ProfilerMsg::StartChromeTrace(path));
+    }
+
+    /// Stop the recording started with [`Self::start_trace_recording`], returning once its
+    /// file is complete.
+    pub fn stop_trace_recording(&self) {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        self.time_profiler_chan
+            .send(time::ProfilerMsg::StopChromeTrace(sender));
+        let _ = receiver.recv();
+    }
+
     pub fn deinit(&self) {
         self.compositor.borrow_mut().deinit();
     }
 
     pub fn new_webview(&self, url: url::Url) -> WebView {
-        let webview = WebView::new(&self.constellation_proxy, self.compositor.clone());
+        let webview = WebView::new(
+            &self.constellation_proxy,
+            self.compositor.clone(),
+            self.site_zoom_levels.clone(),
+        );
         self.webviews
             .borrow_mut()
             .insert(webview.id(), webview.weak_handle());
@@ -683,13 +800,107 @@ impl Servo {
     }
 
     pub fn new_auxiliary_webview(&self) -> WebView {
-        let webview = WebView::new(&self.constellation_proxy, self.compositor.clone());
+        let webview = WebView::new(
+            &self.constellation_proxy,
+            self.compositor.clone(),
+            self.site_zoom_levels.clone(),
+        );
         self.webviews
             .borrow_mut()
             .insert(webview.id(), webview.weak_handle());
         webview
     }
 
+    /// Return handles to all [`WebView`]s of this Servo instance that are still open, in no
+    /// particular order.
+    pub fn webviews(&self) -> Vec<WebView> {
+        self.webviews
+            .borrow()
+            .values()
+            .filter_map(WebView::from_weak_handle)
+            .collect()
+    }
+
+    /// The [`ZoomLevels`] that were chosen for each site, keyed by host. Embedders can
+    /// store them in order to restore them with [`Self::set_site_zoom_levels`] later.
+    pub fn site_zoom_levels(&self) -> HashMap<String, ZoomLevels> {
+        self.site_zoom_levels.borrow().clone()
+    }
+
+    /// Replace the [`ZoomLevels`] of each site, keyed by host. They apply to [`WebView`]s
+    /// the next time that they navigate to a different site.
+    pub fn set_site_zoom_levels(&self, site_zoom_levels: HashMap<String, ZoomLevels>) {
+        *self.site_zoom_levels.borrow_mut() = site_zoom_levels;
+    }
+
+    /// Change the value of the preference called `name` at runtime. The parts of Servo that
+    /// use it react to the change without a restart, although some preferences only affect
+    /// documents that are loaded afterwards.
+    ///
+    /// Fails, without changing anything, when there is no preference called `name` or
+    /// when `value` does not have the type of the preference.
+    pub fn set_preference(&self, name: &str, value: PrefValue) -> Result<(), PreferenceError> {
+        let mut preferences = prefs::get().clone();
+        preferences.try_set_value(name, value)?;
+        self.set_preferences(preferences);
+        Ok(())
+    }
+
+    /// Replace all of the preferences at runtime, like [`Self::set_preference`].
+    pub fn set_preferences(&self, preferences: Preferences) {
+        prefs::set(preferences);
+        self.send_preferences_to_constellation();
+    }
+
+    /// Override the value of the preference called `name` for the site of `host`, for
+    /// instance to disable scripts with `dom_script_enabled` or images with
+    /// `network_images_enabled`.
+    ///
+    /// Fails like [`Self::set_preference`].
+    pub fn set_site_preference(
+        &self,
+        host: &str,
+        name: &str,
+        value: PrefValue,
+    ) -> Result<(), PreferenceError> {
+        prefs::get().clone().try_set_value(name, value.clone())?;
+        let mut site_preferences = prefs::site_preferences();
+        site_preferences
+            .entry(host.to_owned())
+            .or_default()
+            .insert(name.to_owned(), value);
+        prefs::set_site_preferences(site_preferences);
+        self.send_preferences_to_constellation();
+        Ok(())
+    }
+
+    /// Remove the overrides of all preferences for the site of `host`.
+    pub fn clear_site_preferences(&self, host: &str) {
+        let mut site_preferences = prefs::site_preferences();
+        if site_preferences.remove(host).is_none() {
+            return;
+        }
+        prefs::set_site_preferences(site_preferences);
+        self.send_preferences_to_constellation();
+    }
+
+    fn send_preferences_to_constellation(&self) {
+        self.constellation_proxy
+            .send(ConstellationMsg::PreferencesUpdated(
+                Box::new(prefs::get().clone()),
+                prefs::site_preferences(),
+            ));
+    }
+
+    /// Replace the user scripts and stylesheets that are injected into documents, including
+    /// the documents of iframes. The change applies to documents that are loaded afterwards.
+    pub fn set_user_content_manager(&self, user_content_manager: UserContentManager) {
+        self.constellation_proxy
+            .send(ConstellationMsg::SetUserContentManager(
+                user_content_manager,
+            ));
+    }
+
     fn get_webview_handle(&self, id: WebViewId) -> Option<WebView> {
         self.webviews
             .borrow()
@@ -727,11 +938,15 @@ impl Servo {
                         .show_simple_dialog(webview, prompt_definition);
                 }
             },
-            EmbedderMsg::ShowContextMenu(webview_id, ipc_sender, title, items) => {
+            EmbedderMsg::ShowContextMenu(webview_id, ipc_sender, title, items, element_info) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    webview
-                        .delegate()
-                        .show_context_menu(webview, ipc_sender, title, items);
+                    webview.delegate().show_context_menu(
+                        webview,
+                        ipc_sender,
+                        title,
+                        items,
+                        element_info,
+                    );
                 }
             },
             EmbedderMsg::AllowNavigationRequest(webview_id, pipeline_id, servo_url) => {
@@ -745,9 +960,11 @@ impl Servo {
                     webview.delegate().request_navigation(webview, request);
                 }
             },
-            EmbedderMsg::AllowOpeningWebView(webview_id, response_sender) => {
+            EmbedderMsg::AllowOpeningWebView(webview_id, features, response_sender) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    let new_webview = webview.delegate().request_open_auxiliary_webview(webview);
+                    let new_webview = webview
+                        .delegate()
+                        .request_open_auxiliary_webview(webview, features);
                     let _ = response_sender.send(new_webview.map(|webview| webview.id()));
                 }
             },
@@ -805,6 +1022,15 @@ impl Servo {
                     webview.clipboard_delegate().set_text(webview, string);
                 }
             },
+            EmbedderMsg::SetClipboardImage(webview_id, image) => {
+                let Some(webview) = self.get_webview_handle(webview_id) else {
+                    return;
+                };
+                match RgbaImage::from_raw(image.width, image.height, image.bytes) {
+                    Some(image) => webview.clipboard_delegate().set_image(webview, image),
+                    None => warn!("Tried to copy an image with the wrong number of pixels"),
+                }
+            },
             EmbedderMsg::SetCursor(webview_id, cursor) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.set_cursor(cursor);
@@ -831,11 +1057,16 @@ impl Servo {
                     webview.set_url(current_url);
                 }
             },
-            EmbedderMsg::NotifyFullscreenStateChanged(webview_id, fullscreen) => {
+            EmbedderMsg::NotifyFullscreenStateChanged(webview_id, fullscreen, screen) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
-                    webview
-                        .delegate()
-                        .notify_fullscreen_state_changed(webview, fullscreen);
+                    match screen {
+                        Some(screen) if fullscreen => webview
+                            .delegate()
+                            .request_fullscreen_on_screen(webview, screen),
+                        _ => webview
+                            .delegate()
+                            .notify_fullscreen_state_changed(webview, fullscreen),
+                    }
                 }
             },
             EmbedderMsg::WebResourceRequested(
@@ -863,11 +1094,78 @@ impl Servo {
                     self.delegate().load_web_resource(web_resource_load);
                 }
             },
-            EmbedderMsg::Panic(webview_id, reason, backtrace) => {
+            EmbedderMsg::WebResourceResponseReceived(
+                webview_id,
+                web_resource_request,
+                web_resource_response,
+                response_sender,
+            ) => {
+                let web_resource_response_load = WebResourceResponseLoad::new(
+                    web_resource_request,
+                    web_resource_response,
+                    response_sender,
+                    self.servo_errors.sender(),
+                );
+                if let Some(webview) =
+                    webview_id.and_then(|webview_id| self.get_webview_handle(webview_id))
+                {
+                    webview
+                        .delegate()
+                        .load_web_resource_response(webview, web_resource_response_load);
+                } else {
+                    self.delegate()
+                        .load_web_resource_response(web_resource_response_load);
+                }
+            },
+            EmbedderMsg::DownloadStarted(webview_id, info, control_sender) => {
+                if let Some(webview) =
+                    webview_id.and_then(|webview_id| self.get_webview_handle(webview_id))
+                {
+                    let download = Download::new(info, control_sender);
+                    webview
+                        .delegate()
+                        .notify_download_started(webview, download);
+                }
+            },
+            EmbedderMsg::DownloadStateChanged(webview_id, id, state) => {
+                if let Some(webview) =
+                    webview_id.and_then(|webview_id| self.get_webview_handle(webview_id))
+                {
+                    webview
+                        .delegate()
+                        .notify_download_state_changed(webview, id, state);
+                }
+            },
+            EmbedderMsg::Panic(report) => {
+                if let Some(webview) = report
+                    .webview_id
+                    .and_then(|webview_id| self.get_webview_handle(webview_id))
+                {
+                    webview.delegate().notify_crashed(
+                        webview,
+                        report.reason.clone(),
+                        report.backtrace.clone(),
+                    );
+                }
+                self.delegate().notify_crash_report(self, report);
+            },
+            EmbedderMsg::MemoryReportCreated(report) => {
+                self.delegate().notify_memory_report(self, report);
+            },
+            EmbedderMsg::ScriptUnresponsive(webview_id, pipeline_id) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
+                    let request = UnresponsiveScriptRequest {
+                        pipeline_id,
+                        constellation_proxy: self.constellation_proxy.clone(),
+                    };
                     webview
                         .delegate()
-                        .notify_crashed(webview, reason, backtrace);
+                        .notify_script_unresponsive(webview, request);
+                }
+            },
+            EmbedderMsg::ScriptResponsive(webview_id, _) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().notify_script_responsive(webview);
                 }
             },
             EmbedderMsg::GetSelectedBluetoothDevice(webview_id, items, response_sender) => {
@@ -882,14 +1180,14 @@ impl Servo {
             EmbedderMsg::SelectFiles(
                 webview_id,
                 filter_patterns,
-                allow_select_multiple,
+                selection_mode,
                 response_sender,
             ) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.delegate().show_file_selection_dialog(
                         webview,
                         filter_patterns,
-                        allow_select_multiple,
+                        selection_mode,
                         response_sender,
                     );
                 }
@@ -933,11 +1231,35 @@ impl Servo {
                     );
                 }
             },
+            EmbedderMsg::ShowSelectElementMenu(
+                webview_id,
+                options,
+                selected_option,
+                position,
+                response_sender,
+            ) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().show_select_element_menu(
+                        webview,
+                        options,
+                        selected_option,
+                        position,
+                        response_sender,
+                    );
+                } else {
+                    let _ = response_sender.send(None);
+                }
+            },
             EmbedderMsg::HideIME(webview_id) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
                     webview.delegate().hide_ime(webview);
                 }
             },
+            EmbedderMsg::UpdateImeCaretRect(webview_id, rect) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().update_ime_caret_rect(webview, rect);
+                }
+            },
             EmbedderMsg::ReportProfile(_items) => {},
             EmbedderMsg::MediaSessionEvent(webview_id, media_session_event) => {
                 if let Some(webview) = self.get_webview_handle(webview_id) {
@@ -994,6 +1316,64 @@ impl Servo {
                     None => self.delegate().show_notification(notification),
                 }
             },
+            EmbedderMsg::FindInPageResult(webview_id, result) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .notify_find_in_page_result(webview, result);
+                }
+            },
+            EmbedderMsg::AccessibilityTreeUpdate(webview_id, tree_update) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .notify_accessibility_tree_update(webview, tree_update);
+                }
+            },
+            EmbedderMsg::CheckSpelling(webview_id, words, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .check_spelling(webview, words, response_sender);
+                }
+            },
+            EmbedderMsg::GetSpellingSuggestions(webview_id, word, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .get_spelling_suggestions(webview, word, response_sender);
+                }
+            },
+            EmbedderMsg::AutofillFields(webview_id, fields) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.delegate().notify_autofill_fields(webview, fields);
+                }
+            },
+            EmbedderMsg::AutofillFieldFocused(webview_id, field) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .notify_autofill_field_focused(webview, field);
+                }
+            },
+            EmbedderMsg::GetKeyboardLayoutMap(webview_id, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview
+                        .delegate()
+                        .get_keyboard_layout_map(webview, response_sender);
+                }
+            },
+            EmbedderMsg::SetKeyboardLock(webview_id, lock) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    webview.set_keyboard_lock(lock);
+                }
+            },
+            EmbedderMsg::ShowPrintDialog(webview_id, response_sender) => {
+                if let Some(webview) = self.get_webview_handle(webview_id) {
+                    let print_request = PrintRequest::new(response_sender);
+                    webview.delegate().show_print_dialog(webview, print_request);
+                }
+            },
         }
     }
 }
@@ -1175,6 +1555,10 @@ pub fn run_content_process(token: String) {
     let unprivileged_content = unprivileged_content_receiver.recv().unwrap();
     opts::set_options(unprivileged_content.opts());
     prefs::set(unprivileged_content.prefs().clone());
+    prefs::set_site_preferences(unprivileged_content.site_preferences().clone());
+    for scheme in unprivileged_content.secure_schemes() {
+        servo_url::register_secure_scheme(scheme);
+    }
 
     // Enter the sandbox if necessary.
     if opts::get().sandbox {
@@ -2,10 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use embedder_traits::Notification;
+use embedder_traits::{CrashReport, MemoryReport, Notification};
 
 use crate::Servo;
-use crate::webview_delegate::{AllowOrDenyRequest, WebResourceLoad};
+use crate::webview_delegate::{AllowOrDenyRequest, WebResourceLoad, WebResourceResponseLoad};
 
 #[derive(Debug)]
 pub enum ServoError {
@@ -22,6 +22,13 @@ pub enum ServoError {
 pub trait ServoDelegate {
     /// Notification that Servo has received a major error.
     fn notify_error(&self, _servo: &Servo, _error: ServoError) {}
+    /// Report that one of Servo's threads panicked. If the panic happened while handling the
+    /// content of a [`WebView`], its [`crate::WebViewDelegate::notify_crashed`] is also called
+    /// and the affected document is replaced by an error page.
+    fn notify_crash_report(&self, _servo: &Servo, _report: CrashReport) {}
+    /// Report the memory used by Servo, by origin and by document, after it was requested
+    /// with [`Servo::create_memory_report`].
+    fn notify_memory_report(&self, _servo: &Servo, _report: MemoryReport) {}
     /// Report that the DevTools server has started on the given `port`. The `token` that
     /// be used to bypass the permission prompt from the DevTools client.
     fn notify_devtools_server_started(&self, _servo: &Servo, _port: u16, _token: String) {}
@@ -37,6 +44,11 @@ pub trait ServoDelegate {
     /// [`crate::WebViewDelegate::load_web_resource`].
     fn load_web_resource(&self, _load: WebResourceLoad) {}
 
+    /// The response to a [`WebResourceLoad`] whose response was asked to be observed has been
+    /// received. This is called for loads not associated with a [`WebView`]; for loads associated
+    /// with a [`WebView`], Servo will call [`crate::WebViewDelegate::load_web_resource_response`].
+    fn load_web_resource_response(&self, _load: WebResourceResponseLoad) {}
+
     /// Request to display a notification.
     fn show_notification(&self, _notification: Notification) {}
 }
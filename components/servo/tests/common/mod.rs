@@ -0,0 +1,145 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Shared infrastructure for tests that run a whole [`Servo`] instance, rendering into a
+//! [`SoftwareRenderingContext`] instead of a window.
+
+// Each test file only uses some of the helpers.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use dpi::PhysicalSize;
+use euclid::Scale;
+use servo::compositing::windowing::{
+    AnimationState, EmbedderCoordinates, EmbedderMethods, WindowMethods,
+};
+use servo::config::prefs::Preferences;
+use servo::servo_geometry::DeviceIndependentIntRect;
+use servo::{
+    EventLoopWaker, RenderingContext, Servo, SoftwareRenderingContext, WebView, WebViewDelegate,
+};
+
+/// The size of the [`SoftwareRenderingContext`] that tests render into.
+const SIZE: PhysicalSize<u32> = PhysicalSize::new(500, 500);
+
+/// A [`Servo`] instance for a test. Only one of these can exist per process, so each test
+/// file should only contain a single test.
+pub struct ServoTest {
+    servo: Servo,
+}
+
+impl ServoTest {
+    pub fn new() -> Self {
+        Self::new_with_preferences(Default::default())
+    }
+
+    pub fn new_with_preferences(preferences: Preferences) -> Self {
+        let rendering_context = Rc::new(
+            SoftwareRenderingContext::new(SIZE).expect("Could not create SoftwareRenderingContext"),
+        );
+        rendering_context
+            .make_current()
+            .expect("Could not make SoftwareRenderingContext current");
+
+        let servo = Servo::new(
+            Default::default(),
+            preferences,
+            rendering_context,
+            Box::new(EmbedderDelegate),
+            Rc::new(WindowDelegate),
+            Default::default(),
+            Default::default(),
+        );
+        Self { servo }
+    }
+
+    pub fn servo(&self) -> &Servo {
+        &self.servo
+    }
+
+    /// Spin the [`Servo`] event loop until `callback` returns true, failing if that takes
+    /// longer than `timeout`.
+    pub fn spin(&self, callback: impl Fn() -> bool, timeout: Duration) -> Result<(), String> {
+        let start = Instant::now();
+        while !callback() {
+            if start.elapsed() > timeout {
+                return Err(format!("Timed out after {timeout:?}"));
+            }
+            self.servo.spin_event_loop();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    /// Spin the [`Servo`] event loop for `duration`, to give pending work a chance to run.
+    pub fn spin_for(&self, duration: Duration) {
+        let _ = self.spin(|| false, duration);
+    }
+}
+
+impl Drop for ServoTest {
+    fn drop(&mut self) {
+        self.servo.start_shutting_down();
+        let _ = self.spin(|| !self.servo.spin_event_loop(), Duration::from_secs(5));
+        self.servo.deinit();
+    }
+}
+
+/// A [`WebViewDelegate`] that records the title of the page, which test pages use to report
+/// their results.
+#[derive(Default)]
+pub struct TitleDelegate {
+    title: RefCell<Option<String>>,
+}
+
+impl TitleDelegate {
+    pub fn title(&self) -> Option<String> {
+        self.title.borrow().clone()
+    }
+}
+
+impl WebViewDelegate for TitleDelegate {
+    fn notify_page_title_changed(&self, _webview: WebView, title: Option<String>) {
+        *self.title.borrow_mut() = title;
+    }
+}
+
+struct EmbedderDelegate;
+
+impl EmbedderMethods for EmbedderDelegate {
+    fn create_event_loop_waker(&mut self) -> Box<dyn EventLoopWaker> {
+        Box::new(Waker)
+    }
+}
+
+/// Tests poll the event loop, so there is nothing to wake.
+#[derive(Clone)]
+struct Waker;
+
+impl EventLoopWaker for Waker {
+    fn clone_box(&self) -> Box<dyn EventLoopWaker> {
+        Box::new(Waker)
+    }
+
+    fn wake(&self) {}
+}
+
+struct WindowDelegate;
+
+impl WindowMethods for WindowDelegate {
+    fn get_coordinates(&self) -> EmbedderCoordinates {
+        let size = euclid::Size2D::new(SIZE.width as i32, SIZE.height as i32);
+        EmbedderCoordinates {
+            hidpi_factor: Scale::new(1.0),
+            screen_size: size,
+            available_screen_size: size,
+            window_rect: DeviceIndependentIntRect::from_size(size),
+        }
+    }
+
+    fn set_animation_state(&self, _state: AnimationState) {}
+}
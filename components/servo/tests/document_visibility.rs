@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+mod common;
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use common::{ServoTest, TitleDelegate};
+use url::Url;
+
+/// The page reports the visibility states it has seen in its title.
+const PAGE: &str = "data:text/html,<script>
+    const states = [document.visibilityState];
+    const report = () => document.title = states.join(' ');
+    document.addEventListener('visibilitychange', () => {
+        states.push(document.visibilityState);
+        report();
+    });
+    addEventListener('pageshow', () => {
+        states.push('pageshow');
+        report();
+    });
+</script>";
+
+#[test]
+fn test_hiding_a_loaded_page_fires_one_visibilitychange() {
+    let servo_test = ServoTest::new();
+    let delegate = Rc::new(TitleDelegate::default());
+
+    let webview = servo_test
+        .servo()
+        .new_webview(Url::parse(PAGE).expect("Page URL should be valid"));
+    webview.set_delegate(delegate.clone());
+    webview.focus();
+    webview.raise_to_top(true);
+
+    // A page that loads in a visible webview is visible from the start, without a
+    // visibilitychange event.
+    let timeout = Duration::from_secs(10);
+    let expected = "visible pageshow";
+    let result = servo_test.spin(|| delegate.title().as_deref() == Some(expected), timeout);
+    assert_eq!(result, Ok(()), "title is {:?}", delegate.title());
+
+    webview.notify_visibility_change(false);
+    let expected = "visible pageshow hidden";
+    let result = servo_test.spin(|| delegate.title().as_deref() == Some(expected), timeout);
+    assert_eq!(result, Ok(()), "title is {:?}", delegate.title());
+
+    // Give any duplicate events a chance to fire.
+    servo_test.spin_for(Duration::from_millis(500));
+    assert_eq!(delegate.title().as_deref(), Some(expected));
+}
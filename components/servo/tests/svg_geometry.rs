@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+mod common;
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use common::{ServoTest, TitleDelegate};
+use servo::config::prefs::Preferences;
+use url::Url;
+
+/// The page reports the results of the SVG geometry APIs in its title, with the names of the
+/// exceptions that calls threw.
+const PAGE: &str = "data:text/html,<svg>
+    <path id='empty' d=''/>
+    <rect id='disabled' width='0' height='10'/>
+    <path id='line' d='M 0 0 L 30 40' pathLength='1e39'/>
+    <rect id='huge' width='1e39' height='10'/>
+</svg>
+<script>
+    const get = (id) => document.getElementById(id);
+    const run = (f) => {
+        try {
+            return String(f());
+        } catch (error) {
+            return error.name;
+        }
+    };
+    document.title = [
+        run(() => get('empty').getTotalLength()),
+        run(() => get('empty').getPointAtLength(1).x),
+        run(() => get('disabled').getTotalLength()),
+        run(() => get('line').getTotalLength()),
+        run(() => get('line').getPointAtLength(25).x),
+        run(() => get('line').getPointAtLength(25).y),
+        run(() => get('line').pathLength.baseVal),
+        run(() => get('huge').width.baseVal.value),
+        run(() => get('huge').width.baseVal.valueInSpecifiedUnits),
+    ].join(' ');
+</script>";
+
+#[test]
+fn test_svg_geometry_of_empty_paths_and_huge_lengths() {
+    let servo_test = ServoTest::new_with_preferences(Preferences {
+        dom_svg_enabled: true,
+        ..Default::default()
+    });
+    let delegate = Rc::new(TitleDelegate::default());
+
+    let webview = servo_test
+        .servo()
+        .new_webview(Url::parse(PAGE).expect("Page URL should be valid"));
+    webview.set_delegate(delegate.clone());
+
+    // Geometry APIs throw for empty paths and shapes whose rendering is disabled, and
+    // numbers that do not fit in a float are zero rather than a crash.
+    let expected = "InvalidStateError InvalidStateError InvalidStateError 50 15 20 0 0 0";
+    let result = servo_test.spin(
+        || delegate.title().as_deref() == Some(expected),
+        Duration::from_secs(10),
+    );
+    assert_eq!(result, Ok(()), "title is {:?}", delegate.title());
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
 use std::hash::Hash;
 use std::rc::{Rc, Weak};
 use std::time::Duration;
@@ -13,8 +14,11 @@ use compositing::windowing::WebRenderDebugOption;
 use constellation_traits::{ConstellationMsg, TraversalDirection};
 use dpi::PhysicalSize;
 use embedder_traits::{
-    Cursor, InputEvent, LoadStatus, MediaSessionActionType, Theme, TouchEventType,
+    AnimationClockRequest, AutofillFieldId, AutofillRequest, CookiePolicy, Cursor,
+    EmulatedMediaFeatures, FindInPageOptions, FindInPageRequest, InputEvent, KeyboardLock,
+    LoadStatus, MediaSessionActionType, Theme, TouchEventType,
 };
+use image::RgbaImage;
 use url::Url;
 use webrender_api::ScrollLocation;
 use webrender_api::units::{DeviceIntPoint, DeviceRect};
@@ -53,6 +57,30 @@ use crate::webview_delegate::{DefaultWebViewDelegate, WebViewDelegate};
 #[derive(Clone)]
 pub struct WebView(Rc<RefCell<WebViewInner>>);
 
+/// The page zoom and text zoom that were chosen for the pages of a site. A [`WebView`]
+/// restores them whenever it navigates to that site.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoomLevels {
+    /// The factor by which pages are zoomed, affecting the size of everything on them.
+    pub page_zoom: f32,
+    /// The factor by which the size of the text of pages is multiplied, without affecting
+    /// the size of anything else.
+    pub text_zoom: f32,
+}
+
+impl Default for ZoomLevels {
+    fn default() -> Self {
+        Self {
+            page_zoom: 1.0,
+            text_zoom: 1.0,
+        }
+    }
+}
+
+/// The [`ZoomLevels`] of each site, keyed by host, that are shared by all [`WebView`]s of
+/// a `Servo` instance.
+pub(crate) type SiteZoomLevels = Rc<RefCell<HashMap<String, ZoomLevels>>>;
+
 impl PartialEq for WebView {
     fn eq(&self, other: &Self) -> bool {
         self.inner().id == other.inner().id
@@ -72,6 +100,7 @@ pub(crate) struct WebViewInner {
     pub(crate) compositor: Rc<RefCell<IOCompositor>>,
     pub(crate) delegate: Rc<dyn WebViewDelegate>,
     pub(crate) clipboard_delegate: Rc<dyn ClipboardDelegate>,
+    site_zoom_levels: SiteZoomLevels,
 
     rect: DeviceRect,
     load_status: LoadStatus,
@@ -81,6 +110,8 @@ pub(crate) struct WebViewInner {
     favicon_url: Option<Url>,
     focused: bool,
     cursor: Cursor,
+    muted: bool,
+    keyboard_lock: Option<KeyboardLock>,
 }
 
 impl Drop for WebViewInner {
@@ -94,6 +125,7 @@ impl WebView {
     pub(crate) fn new(
         constellation_proxy: &ConstellationProxy,
         compositor: Rc<RefCell<IOCompositor>>,
+        site_zoom_levels: SiteZoomLevels,
     ) -> Self {
         let id = WebViewId::new();
         compositor.borrow_mut().add_webview(id);
@@ -103,6 +135,7 @@ impl WebView {
             compositor,
             delegate: Rc::new(DefaultWebViewDelegate),
             clipboard_delegate: Rc::new(DefaultClipboardDelegate),
+            site_zoom_levels,
             rect: DeviceRect::zero(),
             load_status: LoadStatus::Complete,
             url: None,
@@ -111,6 +144,8 @@ impl WebView {
             favicon_url: None,
             focused: false,
             cursor: Cursor::Pointer,
+            muted: false,
+            keyboard_lock: None,
         })))
     }
 
@@ -159,7 +194,13 @@ impl WebView {
             return;
         }
         self.inner_mut().load_status = new_value;
-        self.delegate().notify_load_status_changed(self, new_value);
+        self.delegate()
+            .notify_load_status_changed(self.clone(), new_value);
+
+        // The keyboard lock of a page is released when navigating away from it.
+        if new_value == LoadStatus::Started {
+            self.set_keyboard_lock(None);
+        }
     }
 
     pub fn url(&self) -> Option<Url> {
@@ -175,7 +216,10 @@ impl WebView {
         {
             return;
         }
-        self.inner_mut().url = Some(new_value.clone());
+        let old_url = self.inner_mut().url.replace(new_value.clone());
+        if old_url.as_ref().and_then(Url::host_str) != new_value.host_str() {
+            self.restore_zoom_levels_for_site(&new_value);
+        }
         self.delegate().notify_url_changed(self, new_value);
     }
 
@@ -244,6 +288,22 @@ impl WebView {
         self.delegate().notify_cursor_changed(self, new_value);
     }
 
+    /// The keys that the page of this [`WebView`] locked with `navigator.keyboard.lock()`,
+    /// if any. While the [`WebView`] is fullscreen, these keys should be delivered to the
+    /// page rather than handled by the embedder.
+    pub fn keyboard_lock(&self) -> Option<KeyboardLock> {
+        self.inner().keyboard_lock.clone()
+    }
+
+    pub(crate) fn set_keyboard_lock(self, new_value: Option<KeyboardLock>) {
+        if self.inner().keyboard_lock == new_value {
+            return;
+        }
+        self.inner_mut().keyboard_lock = new_value.clone();
+        self.delegate()
+            .notify_keyboard_lock_changed(self, new_value);
+    }
+
     pub fn focus(&self) {
         self.inner()
             .constellation_proxy
@@ -302,6 +362,19 @@ impl WebView {
             .send(ConstellationMsg::ThemeChange(theme))
     }
 
+    /// Emulate the given media features for the documents of this [`WebView`], including
+    /// those of nested browsing contexts and of documents loaded later. Media queries are
+    /// evaluated again and `MediaQueryList`s whose result changes fire a `change` event.
+    /// Pass `EmulatedMediaFeatures::default()` to go back to the platform values.
+    pub fn set_emulated_media_features(&self, features: EmulatedMediaFeatures) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::SetEmulatedMediaFeatures(
+                self.id(),
+                features,
+            ));
+    }
+
     pub fn load(&self, url: Url) {
         self.inner()
             .constellation_proxy
@@ -371,6 +444,10 @@ impl WebView {
             .send(ConstellationMsg::MediaSessionAction(event));
     }
 
+    /// Notify this [`WebView`] of a vsync of the display that it is shown on. Once this is
+    /// called, animations and `requestAnimationFrame` callbacks are ticked on vsync rather than
+    /// after every paint, so embedders should keep calling it for as long as Servo reports that
+    /// it is animating.
     pub fn notify_vsync(&self) {
         self.inner().compositor.borrow_mut().on_vsync(self.id());
     }
@@ -389,18 +466,98 @@ impl WebView {
             .on_embedder_window_moved();
     }
 
-    pub fn set_zoom(&self, new_zoom: f32) {
+    /// Notify this [`WebView`] that the device pixel ratio of its window changed, for
+    /// instance because the window was moved to a screen with a different pixel density.
+    pub fn notify_hidpi_factor_changed(&self) {
         self.inner()
             .compositor
             .borrow_mut()
-            .on_zoom_window_event(new_zoom);
+            .on_hidpi_factor_changed();
+    }
+
+    /// Notify this [`WebView`] that the screens connected to the system have changed, for
+    /// instance because a display was connected, disconnected, or rearranged.
+    pub fn notify_screens_changed(&self) {
+        self.inner().compositor.borrow_mut().on_screens_changed();
     }
 
+    /// Multiply the page zoom of this [`WebView`] by `magnification`.
+    pub fn set_zoom(&self, magnification: f32) {
+        self.set_page_zoom(self.page_zoom() * magnification);
+    }
+
+    /// Reset both the page zoom and the text zoom of this [`WebView`].
     pub fn reset_zoom(&self) {
+        self.set_zoom_levels(ZoomLevels::default());
+        self.remember_zoom_levels_for_site();
+    }
+
+    /// The factor by which the pages of this [`WebView`] are zoomed. Page zoom changes the
+    /// size of a CSS pixel, so it affects layout, media queries and `devicePixelRatio`.
+    pub fn page_zoom(&self) -> f32 {
+        self.inner().compositor.borrow().page_zoom(self.id())
+    }
+
+    /// Set the page zoom of this [`WebView`]. It is remembered for the site of the current
+    /// page, and restored when navigating to that site again.
+    pub fn set_page_zoom(&self, page_zoom: f32) {
+        self.inner()
+            .compositor
+            .borrow_mut()
+            .set_page_zoom(self.id(), page_zoom);
+        self.remember_zoom_levels_for_site();
+    }
+
+    /// The factor by which the size of the text of the pages of this [`WebView`] is
+    /// multiplied, whatever the unit of its font size. Unlike page zoom, text zoom does not
+    /// change the size of anything but text and the height of its lines.
+    pub fn text_zoom(&self) -> f32 {
+        self.inner().compositor.borrow().text_zoom(self.id())
+    }
+
+    /// Set the text zoom of this [`WebView`]. It is remembered for the site of the current
+    /// page, and restored when navigating to that site again.
+    pub fn set_text_zoom(&self, text_zoom: f32) {
         self.inner()
             .compositor
             .borrow_mut()
-            .on_zoom_reset_window_event();
+            .set_text_zoom(self.id(), text_zoom);
+        self.remember_zoom_levels_for_site();
+    }
+
+    fn set_zoom_levels(&self, zoom_levels: ZoomLevels) {
+        let id = self.id();
+        let inner = self.inner();
+        let mut compositor = inner.compositor.borrow_mut();
+        compositor.set_page_zoom(id, zoom_levels.page_zoom);
+        compositor.set_text_zoom(id, zoom_levels.text_zoom);
+    }
+
+    /// Record the current zoom levels for the site of the current page, forgetting them
+    /// when they are the default ones.
+    fn remember_zoom_levels_for_site(&self) {
+        let Some(host) = self.url().and_then(|url| url.host_str().map(str::to_owned)) else {
+            return;
+        };
+        let zoom_levels = ZoomLevels {
+            page_zoom: self.page_zoom(),
+            text_zoom: self.text_zoom(),
+        };
+        let inner = self.inner();
+        let mut site_zoom_levels = inner.site_zoom_levels.borrow_mut();
+        if zoom_levels == ZoomLevels::default() {
+            site_zoom_levels.remove(&host);
+        } else {
+            site_zoom_levels.insert(host, zoom_levels);
+        }
+    }
+
+    fn restore_zoom_levels_for_site(&self, url: &Url) {
+        let zoom_levels = url
+            .host_str()
+            .and_then(|host| self.inner().site_zoom_levels.borrow().get(host).copied())
+            .unwrap_or_default();
+        self.set_zoom_levels(zoom_levels);
     }
 
     pub fn set_pinch_zoom(&self, new_pinch_zoom: f32) {
@@ -410,6 +567,61 @@ impl WebView {
             .set_pinch_zoom(self.id(), new_pinch_zoom);
     }
 
+    /// Search the text of the top-level document of this [`WebView`] for `text`, highlighting
+    /// all of the matches and scrolling the active one into view. Calling this again while a
+    /// search is in progress updates the search, for instance as the user types. The number
+    /// of matches is reported to [`WebViewDelegate::notify_find_in_page_result`].
+    pub fn find_in_page(&self, text: String, options: FindInPageOptions) {
+        self.send_find_in_page_request(FindInPageRequest::Start(text, options));
+    }
+
+    /// Make the next match of the current find-in-page search the active one.
+    pub fn find_next(&self) {
+        self.send_find_in_page_request(FindInPageRequest::Next);
+    }
+
+    /// Make the previous match of the current find-in-page search the active one.
+    pub fn find_previous(&self) {
+        self.send_find_in_page_request(FindInPageRequest::Previous);
+    }
+
+    /// Stop the current find-in-page search and remove the highlights of its matches.
+    pub fn stop_finding(&self) {
+        self.send_find_in_page_request(FindInPageRequest::Stop);
+    }
+
+    fn send_find_in_page_request(&self, request: FindInPageRequest) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::FindInPage(self.id(), request));
+    }
+
+    /// Ask the top-level document of this [`WebView`] for its form controls that can be filled
+    /// in, which are reported to [`WebViewDelegate::notify_autofill_fields`].
+    pub fn request_autofill_fields(&self) {
+        self.send_autofill_request(AutofillRequest::GetFields);
+    }
+
+    /// Fill in form controls of the top-level document of this [`WebView`] that were reported
+    /// to the [`WebViewDelegate`], as if the user had entered the given values.
+    pub fn autofill(&self, values: Vec<(AutofillFieldId, String)>) {
+        self.send_autofill_request(AutofillRequest::Fill(values));
+    }
+
+    fn send_autofill_request(&self, request: AutofillRequest) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::Autofill(self.id(), request));
+    }
+
+    /// Pause, resume, step or set the clock that drives the animations and timers of the
+    /// top-level document of this [`WebView`], so that rendering it does not depend on timing.
+    pub fn control_animation_clock(&self, request: AnimationClockRequest) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::ControlAnimationClock(self.id(), request));
+    }
+
     pub fn exit_fullscreen(&self) {
         self.inner()
             .constellation_proxy
@@ -422,6 +634,41 @@ impl WebView {
             .send(ConstellationMsg::SetWebViewThrottled(self.id(), throttled));
     }
 
+    /// Override the cookie policy of this [`WebView`], or restore the one from the
+    /// `network_cookie_policy` preference when `policy` is `None`.
+    pub fn set_cookie_policy(&self, policy: Option<CookiePolicy>) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::SetCookiePolicy(self.id(), policy));
+    }
+
+    /// Notify this [`WebView`] that it has become visible or hidden to the user, for instance
+    /// because the window containing it was minimized, restored, or occluded. Hidden pages have
+    /// their `document.visibilityState` set to `"hidden"` and may be frozen.
+    pub fn notify_visibility_change(&self, visible: bool) {
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::SetWebViewVisibility(self.id(), visible));
+    }
+
+    /// Whether the audio of this [`WebView`] was muted with [`WebView::set_muted`].
+    pub fn muted(&self) -> bool {
+        self.inner().muted
+    }
+
+    /// Mute or unmute all audio played by the documents of this [`WebView`], including those
+    /// of nested browsing contexts and of documents loaded later. This does not change the
+    /// `muted` attribute that pages see on their media elements.
+    pub fn set_muted(&self, muted: bool) {
+        if self.inner().muted == muted {
+            return;
+        }
+        self.inner_mut().muted = muted;
+        self.inner()
+            .constellation_proxy
+            .send(ConstellationMsg::SetWebViewMuted(self.id(), muted));
+    }
+
     pub fn toggle_webrender_debugging(&self, debugging: WebRenderDebugOption) {
         self.inner()
             .compositor
@@ -445,9 +692,22 @@ impl WebView {
             .send(ConstellationMsg::SendError(Some(self.id()), message));
     }
 
+    /// Render the current contents of this [`WebView`] and read back its pixels, without
+    /// presenting them. If `rect` is given, only that part of the [`WebView`], relative to its
+    /// origin, is read back. The image is scaled by `scale`, which is useful for thumbnails.
+    /// Returns `None` if `rect` is outside of the [`WebView`] or if rendering failed.
+    pub fn capture_pixels(&self, rect: Option<DeviceRect>, scale: f32) -> Option<RgbaImage> {
+        self.inner()
+            .compositor
+            .borrow_mut()
+            .capture_pixels(self.id(), rect, scale)
+    }
+
     /// Paint the contents of this [`WebView`] into its `RenderingContext`. This will
-    /// always paint, unless the `Opts::wait_for_stable_image` option is enabled. In
-    /// that case, this might do nothing. Returns true if a paint was actually performed.
+    /// always paint, unless the `Opts::wait_for_stable_image` option is enabled or partial
+    /// present is enabled and nothing changed. In those cases, this might do nothing. Returns
+    /// true if a paint was actually performed, in which case the `RenderingContext` should be
+    /// presented.
     pub fn paint(&self) -> bool {
         self.inner().compositor.borrow_mut().render()
     }
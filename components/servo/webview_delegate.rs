@@ -7,13 +7,16 @@ use std::path::PathBuf;
 use base::id::PipelineId;
 use constellation_traits::ConstellationMsg;
 use embedder_traits::{
-    AllowOrDeny, AuthenticationResponse, ContextMenuResult, Cursor, FilterPattern,
-    GamepadHapticEffectType, InputMethodType, LoadStatus, MediaSessionEvent, Notification,
-    PermissionFeature, SimpleDialog, WebResourceRequest, WebResourceResponse,
-    WebResourceResponseMsg,
+    AllowOrDeny, AuthenticationResponse, AutofillField, AuxiliaryWebViewFeatures,
+    ContextMenuElementInformation, ContextMenuResult, Cursor, DownloadControl, DownloadId,
+    DownloadInfo, DownloadState, FileSelectionMode, FilterPattern, FindInPageResult,
+    GamepadHapticEffectType, InputMethodType, KeyboardLock, LoadStatus, MediaSessionEvent,
+    Notification, PermissionFeature, ScreenId, SelectElementOptionOrOptgroup, SimpleDialog,
+    UnresponsiveScriptAction, WebResourceRequest, WebResourceRequestModification,
+    WebResourceResponse, WebResourceResponseMsg, accesskit,
 };
 use ipc_channel::ipc::IpcSender;
-use keyboard_types::KeyboardEvent;
+use keyboard_types::{Code, KeyboardEvent};
 use serde::Serialize;
 use url::Url;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
@@ -62,6 +65,34 @@ impl Drop for NavigationRequest {
     }
 }
 
+/// A script in a [`WebView`] that has been running for so long that its page is unresponsive.
+/// If not handled, the script keeps running.
+pub struct UnresponsiveScriptRequest {
+    pub(crate) pipeline_id: PipelineId,
+    pub(crate) constellation_proxy: ConstellationProxy,
+}
+
+impl UnresponsiveScriptRequest {
+    /// Stop the script that is running, leaving the page as it is.
+    pub fn interrupt(self) {
+        self.constellation_proxy
+            .send(ConstellationMsg::StopUnresponsiveScript(
+                self.pipeline_id,
+                UnresponsiveScriptAction::Interrupt,
+            ));
+    }
+
+    /// Stop the event loop running the script. Its pages, including this one, are replaced
+    /// with crash pages.
+    pub fn kill(self) {
+        self.constellation_proxy
+            .send(ConstellationMsg::StopUnresponsiveScript(
+                self.pipeline_id,
+                UnresponsiveScriptAction::Kill,
+            ));
+    }
+}
+
 /// Sends a response over an IPC channel, or a default response on [`Drop`] if no response was sent.
 pub(crate) struct IpcResponder<T: Serialize> {
     response_sender: IpcSender<T>,
@@ -197,6 +228,24 @@ impl AuthenticationRequest {
     }
 }
 
+/// A request from the page of a [`WebView`] to print its contents, made with `window.print()`.
+/// While the request is alive, the page is laid out with print media, so that the embedder can
+/// capture it with [`WebView::capture_pixels`] to print it or save it. The page continues
+/// running once the request is finished or dropped.
+///
+/// The capture is a single raster image of the viewport. Layout does not split the document
+/// into pages, and there is no vector or PDF output.
+pub struct PrintRequest(IpcResponder<()>);
+
+impl PrintRequest {
+    pub(crate) fn new(response_sender: IpcSender<()>) -> Self {
+        Self(IpcResponder::new(response_sender, ()))
+    }
+
+    /// Let the page know that its contents were printed, or that the user cancelled printing.
+    pub fn finish(self) {}
+}
+
 /// Information related to the loading of a web resource. These are created for all HTTP requests.
 /// The client may choose to intercept the load of web resources and send an alternate response
 /// by calling [`WebResourceLoad::intercept`].
@@ -236,6 +285,109 @@ impl WebResourceLoad {
             error_sender: self.error_sender,
         }
     }
+    /// Let this [`WebResourceLoad`] continue to the network after applying the given
+    /// [`WebResourceRequestModification`], which can add headers, redirect the request or ask
+    /// for the response to be passed to [`WebViewDelegate::load_web_resource_response`].
+    pub fn modify(mut self, modification: WebResourceRequestModification) {
+        if let Err(error) = self
+            .responder
+            .send(WebResourceResponseMsg::ModifyRequest(modification))
+        {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+    /// Block this [`WebResourceLoad`], which will trigger a network error.
+    pub fn block(mut self) {
+        if let Err(error) = self.responder.send(WebResourceResponseMsg::CancelLoad) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// The response to a [`WebResourceLoad`] that was modified with
+/// [`WebResourceRequestModification::observe_response`]. The client may replace the status and
+/// headers of the response by calling [`WebResourceResponseLoad::replace`]. Otherwise the response
+/// is used as it is.
+pub struct WebResourceResponseLoad {
+    pub request: WebResourceRequest,
+    pub response: WebResourceResponse,
+    pub(crate) responder: IpcResponder<Option<WebResourceResponse>>,
+    pub(crate) error_sender: ServoErrorSender,
+}
+
+impl WebResourceResponseLoad {
+    pub(crate) fn new(
+        web_resource_request: WebResourceRequest,
+        web_resource_response: WebResourceResponse,
+        response_sender: IpcSender<Option<WebResourceResponse>>,
+        error_sender: ServoErrorSender,
+    ) -> Self {
+        Self {
+            request: web_resource_request,
+            response: web_resource_response,
+            responder: IpcResponder::new(response_sender, None),
+            error_sender,
+        }
+    }
+
+    /// The [`WebResourceRequest`] associated with this [`WebResourceResponseLoad`].
+    pub fn request(&self) -> &WebResourceRequest {
+        &self.request
+    }
+    /// The [`WebResourceResponse`] that was received, without its body.
+    pub fn response(&self) -> &WebResourceResponse {
+        &self.response
+    }
+    /// Replace the status and headers of the response with those of `response`.
+    pub fn replace(mut self, response: WebResourceResponse) {
+        if let Err(error) = self.responder.send(Some(response)) {
+            self.error_sender.raise_response_send_error(error);
+        }
+    }
+}
+
+/// A download that was started by a navigation in a [`WebView`]. Its progress is reported to
+/// [`WebViewDelegate::notify_download_state_changed`]. A running download continues when every
+/// handle to it is dropped, but a paused download is cancelled.
+#[derive(Clone)]
+pub struct Download {
+    info: DownloadInfo,
+    control_sender: IpcSender<DownloadControl>,
+}
+
+impl Download {
+    pub(crate) fn new(info: DownloadInfo, control_sender: IpcSender<DownloadControl>) -> Self {
+        Self {
+            info,
+            control_sender,
+        }
+    }
+
+    pub fn id(&self) -> DownloadId {
+        self.info.id
+    }
+    /// The [`DownloadInfo`] describing where this [`Download`] comes from and where it is
+    /// written to.
+    pub fn info(&self) -> &DownloadInfo {
+        &self.info
+    }
+    /// Stop receiving data for this [`Download`] until it is resumed.
+    pub fn pause(&self) {
+        self.send(DownloadControl::Pause);
+    }
+    /// Resume this [`Download`] after it was paused.
+    pub fn resume(&self) {
+        self.send(DownloadControl::Resume);
+    }
+    /// Stop this [`Download`] and remove the file that it was written to.
+    pub fn cancel(&self) {
+        self.send(DownloadControl::Cancel);
+    }
+
+    fn send(&self, control: DownloadControl) {
+        // Downloads that have already finished no longer listen for controls.
+        let _ = self.control_sender.send(control);
+    }
 }
 
 /// An intercepted web resource load. This struct allows the client to send an alternative response
@@ -336,6 +488,11 @@ pub trait WebViewDelegate {
     fn notify_keyboard_event(&self, _webview: WebView, _: KeyboardEvent) {}
     /// A pipeline in the webview panicked. First string is the reason, second one is the backtrace.
     fn notify_crashed(&self, _webview: WebView, _reason: String, _backtrace: Option<String>) {}
+    /// A script in the webview has been running for so long that the page is unresponsive.
+    /// This is only reported when the `dom_script_watchdog_enabled` preference is set.
+    fn notify_script_unresponsive(&self, _webview: WebView, _: UnresponsiveScriptRequest) {}
+    /// A script that was reported to be unresponsive in the webview has finished by itself.
+    fn notify_script_responsive(&self, _webview: WebView) {}
     /// Notifies the embedder about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     fn notify_media_session_event(&self, _webview: WebView, _event: MediaSessionEvent) {}
@@ -346,6 +503,14 @@ pub trait WebViewDelegate {
     /// API](https://fullscreen.spec.whatwg.org/).
     fn notify_fullscreen_state_changed(&self, _webview: WebView, _: bool) {}
 
+    /// A [`WebView`] has requested to enter fullscreen on a particular screen, as specified by
+    /// the `screen` member of `FullscreenOptions` in the [Window Management
+    /// API](https://w3c.github.io/window-management/). By default, this falls back to
+    /// [`WebViewDelegate::notify_fullscreen_state_changed`], ignoring the requested screen.
+    fn request_fullscreen_on_screen(&self, webview: WebView, _screen: ScreenId) {
+        self.notify_fullscreen_state_changed(webview, true);
+    }
+
     /// Whether or not to allow a [`WebView`] to load a URL in its main frame or one of its
     /// nested `<iframe>`s. [`NavigationRequest`]s are accepted by default.
     fn request_navigation(&self, _webview: WebView, _navigation_request: NavigationRequest) {}
@@ -356,9 +521,15 @@ pub trait WebViewDelegate {
     fn request_move_to(&self, _webview: WebView, _: DeviceIntPoint) {}
     /// Resize the window to size
     fn request_resize_to(&self, _webview: WebView, _: DeviceIntSize) {}
-    /// Whether or not to allow script to open a new `WebView`. If not handled by the
-    /// embedder, these requests are automatically denied.
-    fn request_open_auxiliary_webview(&self, _parent_webview: WebView) -> Option<WebView> {
+    /// Whether or not to allow script to open a new `WebView`, for instance with
+    /// `window.open()`. The embedder can use the requested [`AuxiliaryWebViewFeatures`] to
+    /// decide whether to show the new `WebView` as a tab or a popup window, or return `None`
+    /// to block it. If not handled by the embedder, these requests are automatically denied.
+    fn request_open_auxiliary_webview(
+        &self,
+        _parent_webview: WebView,
+        _features: AuxiliaryWebViewFeatures,
+    ) -> Option<WebView> {
         None
     }
 
@@ -393,13 +564,16 @@ pub trait WebViewDelegate {
         };
     }
 
-    /// Show a context menu to the user
+    /// Show a context menu to the user. Besides the given items, which select
+    /// [`ContextMenuResult::Selected`], the menu can offer the [`ContextMenuAction`](crate::ContextMenuAction)s that apply
+    /// to the element described by `element_info`, which select [`ContextMenuResult::Action`].
     fn show_context_menu(
         &self,
         _webview: WebView,
         result_sender: IpcSender<ContextMenuResult>,
         _: Option<String>,
         _: Vec<String>,
+        _element_info: ContextMenuElementInformation,
     ) {
         let _ = result_sender.send(ContextMenuResult::Ignored);
     }
@@ -415,12 +589,13 @@ pub trait WebViewDelegate {
         let _ = response_sender.send(None);
     }
 
-    /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
+    /// Open file dialog to select files, or a directory with the files in it, as the
+    /// [`FileSelectionMode`] allows.
     fn show_file_selection_dialog(
         &self,
         _webview: WebView,
         _filter_pattern: Vec<FilterPattern>,
-        _allow_select_mutiple: bool,
+        _selection_mode: FileSelectionMode,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
     ) {
         let _ = response_sender.send(None);
@@ -443,6 +618,24 @@ pub trait WebViewDelegate {
     /// Request to hide the IME when the editable element is blurred.
     fn hide_ime(&self, _webview: WebView) {}
 
+    /// Show the menu of a `<select>` element below `position`, with `selected_option` being
+    /// the id of the option that is currently selected. Send the id of the option that the
+    /// user chooses, or `None` if they dismiss the menu.
+    fn show_select_element_menu(
+        &self,
+        _webview: WebView,
+        _options: Vec<SelectElementOptionOrOptgroup>,
+        _selected_option: Option<usize>,
+        _position: DeviceIntRect,
+        response_sender: IpcSender<Option<usize>>,
+    ) {
+        let _ = response_sender.send(None);
+    }
+
+    /// Notify the embedder that the IME composition in the focused editable element is now
+    /// at `rect`, so that the candidate window of the IME can be placed next to it.
+    fn update_ime_caret_rect(&self, _webview: WebView, _rect: DeviceIntRect) {}
+
     /// Request to play a haptic effect on a connected gamepad.
     fn play_gamepad_haptic_effect(
         &self,
@@ -464,8 +657,154 @@ pub trait WebViewDelegate {
     /// will call [`crate::ServoDelegate::load_web_resource`].
     fn load_web_resource(&self, _webview: WebView, _load: WebResourceLoad) {}
 
+    /// The response to a [`WebResourceLoad`] whose response was asked to be observed with
+    /// [`WebResourceLoad::modify`] has been received. Its status and headers may be replaced
+    /// before it is used.
+    ///
+    /// Note: For loads not associated with a [`WebView`], Servo will call
+    /// [`crate::ServoDelegate::load_web_resource_response`].
+    fn load_web_resource_response(&self, _webview: WebView, _load: WebResourceResponseLoad) {}
+
+    /// A navigation in this [`WebView`] received a response that is downloaded rather than
+    /// displayed. The [`Download`] can be used to pause, resume, or cancel it.
+    fn notify_download_started(&self, _webview: WebView, _download: Download) {}
+
+    /// The state of a download started by this [`WebView`] changed.
+    fn notify_download_state_changed(
+        &self,
+        _webview: WebView,
+        _id: DownloadId,
+        _state: DownloadState,
+    ) {
+    }
+
     /// Request to display a notification.
     fn show_notification(&self, _webview: WebView, _notification: Notification) {}
+
+    /// The accessibility tree of this [`WebView`] changed. Only nodes that changed since the
+    /// previous update are included, unless the update also contains a new
+    /// [`accesskit::Tree`], in which case it describes the entire tree. This is only called
+    /// when the `accessibility_enabled` preference is set.
+    fn notify_accessibility_tree_update(
+        &self,
+        _webview: WebView,
+        _tree_update: accesskit::TreeUpdate,
+    ) {
+    }
+
+    /// The state of the find-in-page search started with [`WebView::find_in_page`] changed,
+    /// either because the active match changed or because the content of the page changed.
+    fn notify_find_in_page_result(&self, _webview: WebView, _result: FindInPageResult) {}
+
+    /// Check the spelling of words that the user typed into a text control of this
+    /// [`WebView`], responding with whether each of them is misspelled. Misspelled words
+    /// are marked in the page. By default, no word is misspelled.
+    fn check_spelling(
+        &self,
+        _webview: WebView,
+        words: Vec<String>,
+        response_sender: IpcSender<Vec<bool>>,
+    ) {
+        let _ = response_sender.send(vec![false; words.len()]);
+    }
+
+    /// Get suggestions for correcting a misspelled word, which are offered as the items of
+    /// the context menu of the text control that contains it.
+    fn get_spelling_suggestions(
+        &self,
+        _webview: WebView,
+        _word: String,
+        response_sender: IpcSender<Vec<String>>,
+    ) {
+        let _ = response_sender.send(Vec::new());
+    }
+
+    /// The form controls of this [`WebView`] that can be filled in, as requested with
+    /// [`WebView::request_autofill_fields`].
+    fn notify_autofill_fields(&self, _webview: WebView, _fields: Vec<AutofillField>) {}
+
+    /// A form control of this [`WebView`] that can be filled in was focused, for instance so
+    /// that a password manager can offer the credentials that it stored for the page. It can
+    /// be filled in with [`WebView::autofill`].
+    fn notify_autofill_field_focused(&self, _webview: WebView, _field: AutofillField) {}
+
+    /// Get the characters that the keys of the current keyboard layout produce without
+    /// modifiers, which pages read with `navigator.keyboard.getLayoutMap()`. Only the keys
+    /// that produce characters need to be included. By default, this is a US QWERTY layout.
+    fn get_keyboard_layout_map(
+        &self,
+        _webview: WebView,
+        response_sender: IpcSender<Vec<(Code, String)>>,
+    ) {
+        let _ = response_sender.send(us_keyboard_layout_map());
+    }
+
+    /// The page of this [`WebView`] locked the keyboard with `navigator.keyboard.lock()` to
+    /// receive the given keys while it is fullscreen, instead of the embedder handling them
+    /// as shortcuts, or released that lock if `None`. The current lock is also available with
+    /// [`WebView::keyboard_lock`].
+    fn notify_keyboard_lock_changed(&self, _webview: WebView, _lock: Option<KeyboardLock>) {}
+
+    /// The page of this [`WebView`] asked to be printed with `window.print()`. The embedder
+    /// should offer the user to print it, and finish the [`PrintRequest`] once done. By
+    /// default, nothing is printed.
+    fn show_print_dialog(&self, _webview: WebView, _request: PrintRequest) {}
+}
+
+/// The characters produced by the keys of a US QWERTY keyboard without modifiers.
+fn us_keyboard_layout_map() -> Vec<(Code, String)> {
+    [
+        (Code::Backquote, "`"),
+        (Code::Digit1, "1"),
+        (Code::Digit2, "2"),
+        (Code::Digit3, "3"),
+        (Code::Digit4, "4"),
+        (Code::Digit5, "5"),
+        (Code::Digit6, "6"),
+        (Code::Digit7, "7"),
+        (Code::Digit8, "8"),
+        (Code::Digit9, "9"),
+        (Code::Digit0, "0"),
+        (Code::Minus, "-"),
+        (Code::Equal, "="),
+        (Code::KeyQ, "q"),
+        (Code::KeyW, "w"),
+        (Code::KeyE, "e"),
+        (Code::KeyR, "r"),
+        (Code::KeyT, "t"),
+        (Code::KeyY, "y"),
+        (Code::KeyU, "u"),
+        (Code::KeyI, "i"),
+        (Code::KeyO, "o"),
+        (Code::KeyP, "p"),
+        (Code::BracketLeft, "["),
+        (Code::BracketRight, "]"),
+        (Code::Backslash, "\\"),
+        (Code::KeyA, "a"),
+        (Code::KeyS, "s"),
+        (Code::KeyD, "d"),
+        (Code::KeyF, "f"),
+        (Code::KeyG, "g"),
+        (Code::KeyH, "h"),
+        (Code::KeyJ, "j"),
+        (Code::KeyK, "k"),
+        (Code::KeyL, "l"),
+        (Code::Semicolon, ";"),
+        (Code::Quote, "'"),
+        (Code::KeyZ, "z"),
+        (Code::KeyX, "x"),
+        (Code::KeyC, "c"),
+        (Code::KeyV, "v"),
+        (Code::KeyB, "b"),
+        (Code::KeyN, "n"),
+        (Code::KeyM, "m"),
+        (Code::Comma, ","),
+        (Code::Period, "."),
+        (Code::Slash, "/"),
+    ]
+    .into_iter()
+    .map(|(code, character)| (code, character.to_owned()))
+    .collect()
 }
 
 pub(crate) struct DefaultWebViewDelegate;
@@ -578,6 +917,58 @@ fn test_authentication_request() {
     drop(receiver);
     drop(request);
     assert!(errors.try_recv().is_none());
+
+    // Explicit modification yields ModifyRequest and nothing else
+    let errors = ServoErrorChannel::default();
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel");
+    let request = WebResourceLoad::new(web_resource_request(), sender, errors.sender());
+    request.modify(WebResourceRequestModification::default().observe_response());
+    assert!(matches!(
+        receiver.try_recv(),
+        Ok(WebResourceResponseMsg::ModifyRequest(modification)) if modification.observe_response
+    ));
+    assert!(matches!(receiver.try_recv(), Err(_)));
+    assert!(errors.try_recv().is_none());
+
+    // Explicit block yields CancelLoad and nothing else
+    let errors = ServoErrorChannel::default();
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel");
+    let request = WebResourceLoad::new(web_resource_request(), sender, errors.sender());
+    request.block();
+    assert!(matches!(
+        receiver.try_recv(),
+        Ok(WebResourceResponseMsg::CancelLoad)
+    ));
+    assert!(matches!(receiver.try_recv(), Err(_)));
+    assert!(errors.try_recv().is_none());
+
+    // No replacement of an observed response yields None and nothing else
+    let errors = ServoErrorChannel::default();
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel");
+    let response = WebResourceResponseLoad::new(
+        web_resource_request(),
+        web_resource_response(),
+        sender,
+        errors.sender(),
+    );
+    drop(response);
+    assert!(matches!(receiver.try_recv(), Ok(None)));
+    assert!(matches!(receiver.try_recv(), Err(_)));
+    assert!(errors.try_recv().is_none());
+
+    // Explicit replacement of an observed response yields the replacement
+    let errors = ServoErrorChannel::default();
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel");
+    let response = WebResourceResponseLoad::new(
+        web_resource_request(),
+        web_resource_response(),
+        sender,
+        errors.sender(),
+    );
+    response.replace(web_resource_response());
+    assert!(matches!(receiver.try_recv(), Ok(Some(_))));
+    assert!(matches!(receiver.try_recv(), Err(_)));
+    assert!(errors.try_recv().is_none());
 }
 
 #[test]
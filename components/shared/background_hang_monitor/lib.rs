@@ -49,7 +49,8 @@ pub enum ScriptHangAnnotation {
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum HangAnnotation {
-    Script(ScriptHangAnnotation),
+    /// A script task of the given kind, run for the given pipeline if any.
+    Script(ScriptHangAnnotation, Option<PipelineId>),
 }
 
 /// Hang-alerts are sent by the monitor to the constellation.
@@ -59,6 +60,8 @@ pub enum HangMonitorAlert {
     Hang(HangAlert),
     /// Report a completed sampled profile.
     Profile(Vec<u8>),
+    /// A component that was reported to be permanently hanging has become responsive again.
+    HangEnded(MonitoredComponentId),
 }
 
 impl fmt::Debug for HangMonitorAlert {
@@ -66,6 +69,7 @@ impl fmt::Debug for HangMonitorAlert {
         match *self {
             HangMonitorAlert::Hang(..) => write!(fmt, "Hang"),
             HangMonitorAlert::Profile(..) => write!(fmt, "Profile"),
+            HangMonitorAlert::HangEnded(..) => write!(fmt, "HangEnded"),
         }
     }
 }
@@ -200,6 +204,10 @@ pub trait BackgroundHangMonitor {
 pub trait BackgroundHangMonitorExitSignal: Send {
     /// Called by the BHM, to notify the monitored component to exit.
     fn signal_to_exit(&self);
+
+    /// Called by the BHM, to notify the monitored component to abandon the work it is
+    /// hanging on, without exiting.
+    fn signal_to_interrupt(&self) {}
 }
 
 /// Messages to control the sampling profiler.
@@ -209,4 +217,8 @@ pub enum BackgroundHangMonitorControlMsg {
     ToggleSampler(Duration, Duration),
     /// Exit, and propagate the signal to monitored components.
     Exit(IpcSender<()>),
+    /// Signal a single monitored component to abandon the work it is hanging on.
+    InterruptComponent(MonitoredComponentId),
+    /// Signal a single monitored component to exit.
+    ExitComponent(MonitoredComponentId),
 }
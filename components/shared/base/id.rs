@@ -223,6 +223,16 @@ impl PipelineId {
     pub fn root_scroll_id(&self) -> webrender_api::ExternalScrollId {
         ExternalScrollId(0, self.into())
     }
+
+    /// Script threads install the id of the pipeline whose task they are running, or `None`
+    /// for tasks that don't belong to any pipeline, since it is used by crash reporting.
+    pub fn install(id: Option<PipelineId>) {
+        INSTALLED_PIPELINE_ID.with(|tls| tls.set(id))
+    }
+
+    pub fn installed() -> Option<PipelineId> {
+        INSTALLED_PIPELINE_ID.with(|tls| tls.get())
+    }
 }
 
 impl From<WebRenderPipelineId> for PipelineId {
@@ -279,6 +289,9 @@ impl fmt::Display for BrowsingContextGroupId {
 thread_local!(pub static WEBVIEW_ID: Cell<Option<WebViewId>> =
     const { Cell::new(None) });
 
+thread_local!(static INSTALLED_PIPELINE_ID: Cell<Option<PipelineId>> =
+    const { Cell::new(None) });
+
 #[derive(
     Clone, Copy, Deserialize, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize,
 )]
@@ -5,6 +5,7 @@
 pub mod blocklist;
 pub mod scanfilter;
 
+use base::id::PipelineId;
 use ipc_channel::ipc::IpcSender;
 use serde::{Deserialize, Serialize};
 
@@ -65,6 +66,15 @@ pub struct BluetoothDescriptorMsg {
     pub instance_id: String,
 }
 
+/// Starting or stopping the notifications of a characteristic for a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum BluetoothNotificationRequest {
+    /// Start the notifications, and send each new value of the characteristic on the given
+    /// channel until they are stopped for the document.
+    Start(PipelineId, IpcSender<Vec<u8>>),
+    Stop(PipelineId),
+}
+
 pub type BluetoothServicesMsg = Vec<BluetoothServiceMsg>;
 
 pub type BluetoothCharacteristicsMsg = Vec<BluetoothCharacteristicMsg>;
@@ -89,7 +99,11 @@ pub enum BluetoothRequest {
     ),
     ReadValue(String, IpcSender<BluetoothResponseResult>),
     WriteValue(String, Vec<u8>, IpcSender<BluetoothResponseResult>),
-    EnableNotification(String, bool, IpcSender<BluetoothResponseResult>),
+    EnableNotification(
+        String,
+        BluetoothNotificationRequest,
+        IpcSender<BluetoothResponseResult>,
+    ),
     WatchAdvertisements(String, IpcSender<BluetoothResponseResult>),
     SetRepresentedToNull(Vec<String>, Vec<String>, Vec<String>),
     IsRepresentedDeviceNull(String, IpcSender<bool>),
@@ -100,6 +114,9 @@ pub enum BluetoothRequest {
         IpcSender<BluetoothResult<bool>>,
     ),
     Test(String, IpcSender<BluetoothResult<()>>),
+    /// The document of the pipeline went away, so it no longer receives the notifications of
+    /// characteristics.
+    PipelineExited(PipelineId),
     Exit,
 }
 
@@ -113,7 +130,8 @@ pub enum BluetoothResponse {
     GetDescriptors(BluetoothDescriptorsMsg, bool),
     ReadValue(Vec<u8>),
     WriteValue(Vec<u8>),
-    EnableNotification(()),
+    /// Whether the notifications of the characteristic are started for the document.
+    EnableNotification(bool),
     WatchAdvertisements(()),
     GetAvailability(bool),
 }
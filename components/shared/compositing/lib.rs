@@ -13,6 +13,7 @@ use euclid::Rect;
 use ipc_channel::ipc::IpcSender;
 use log::warn;
 use pixels::Image;
+use profile_traits::mem::ReportsChan;
 use script_traits::{AnimationState, TouchEventResult};
 use strum_macros::IntoStaticStr;
 use style_traits::CSSPixel;
@@ -66,6 +67,14 @@ pub enum CompositorMsg {
     TouchEventProcessed(WebViewId, TouchEventResult),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
+    /// Render the given rectangle of the page of a webview, relative to its viewport at its
+    /// current scroll position, or all of the page, even the parts of it beyond its viewport,
+    /// and return the Image over a passed channel.
+    CreatePagePng(
+        WebViewId,
+        Option<Rect<f32, CSSPixel>>,
+        IpcSender<Option<Image>>,
+    ),
     /// A reply to the compositor asking if the output image is stable.
     IsReadyToSaveImageReply(bool),
     /// Set whether to use less resources by stopping animations.
@@ -86,6 +95,10 @@ pub enum CompositorMsg {
     WebDriverMouseButtonEvent(WebViewId, MouseButtonAction, MouseButton, f32, f32),
     /// WebDriver mouse move event
     WebDriverMouseMoveEvent(WebViewId, f32, f32),
+    /// WebDriver mouse wheel scroll event
+    WebDriverWheelScrollEvent(WebViewId, f32, f32, f64, f64),
+    /// Measure the memory used by WebRender for textures, for the memory profiler.
+    CollectMemoryReport(ReportsChan),
 
     /// Messages forwarded to the compositor by the constellation from other crates. These
     /// messages are mainly passed on from the compositor to WebRender.
@@ -17,12 +17,18 @@ use base::Epoch;
 use base::cross_process_instant::CrossProcessInstant;
 use base::id::{PipelineId, ScrollTreeNodeId, WebViewId};
 use bitflags::bitflags;
-use embedder_traits::{Cursor, InputEvent, MediaSessionActionType, Theme, WebDriverCommandMsg};
+use embedder_traits::user_content_manager::UserContentManager;
+use embedder_traits::{
+    AnimationClockRequest, AutofillRequest, CookiePolicy, Cursor, EmulatedMediaFeatures,
+    FindInPageRequest, InputEvent, MediaSessionActionType, NetworkStatus, Theme,
+    UnresponsiveScriptAction, WebDriverCommandMsg,
+};
 use euclid::{Scale, Size2D, Vector2D};
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of::malloc_size_of_is_0;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use servo_config::prefs::{Preferences, SitePreferences};
 use servo_url::ServoUrl;
 use strum_macros::IntoStaticStr;
 use style_traits::CSSPixel;
@@ -45,20 +51,35 @@ pub enum ConstellationMsg {
     LoadUrl(WebViewId, ServoUrl),
     /// Clear the network cache.
     ClearCache,
+    /// Clear the local and session storage of the site of the given URL, including the storage
+    /// of other sites embedded in it.
+    ClearStorageForSite(ServoUrl),
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(WebViewId, TraversalDirection),
     /// Inform the constellation of a window being resized.
     WindowSize(WebViewId, WindowSizeData, WindowSizeType),
     /// Inform the constellation of a theme change.
     ThemeChange(Theme),
+    /// Inform the constellation that the set of connected screens, or the screen that the
+    /// window occupies, has changed.
+    ScreensChanged,
+    /// Inform the constellation that the connectivity or quality of the network changed.
+    NetworkStatusChanged(NetworkStatus),
+    /// Override the cookie policy of a webview, or restore the default one when `None`.
+    SetCookiePolicy(WebViewId, Option<CookiePolicy>),
     /// Requests that the constellation instruct layout to begin a new tick of the animation.
     TickAnimation(PipelineId, AnimationTickType),
     /// Dispatch a webdriver command
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
     Reload(WebViewId),
-    /// A log entry, with the top-level browsing context id and thread name
-    LogEntry(Option<WebViewId>, Option<String>, LogEntry),
+    /// A log entry, with the top-level browsing context id, pipeline id and thread name
+    LogEntry(
+        Option<WebViewId>,
+        Option<PipelineId>,
+        Option<String>,
+        LogEntry,
+    ),
     /// Create a new top level browsing context.
     NewWebView(ServoUrl, WebViewId),
     /// Close a top level browsing context.
@@ -81,11 +102,40 @@ pub enum ConstellationMsg {
     MediaSessionAction(MediaSessionActionType),
     /// Set whether to use less resources, by stopping animations and running timers at a heavily limited rate.
     SetWebViewThrottled(WebViewId, bool),
+    /// Set whether the given `WebView` is visible to the user, for instance because its window was
+    /// minimized or occluded. This drives the visibility state of its documents.
+    SetWebViewVisibility(WebViewId, bool),
+    /// Set whether all audio played by the documents of the given `WebView` is muted.
+    SetWebViewMuted(WebViewId, bool),
+    /// Set the media features that are emulated for the documents of the given `WebView`.
+    SetEmulatedMediaFeatures(WebViewId, EmulatedMediaFeatures),
+    /// Replace the user scripts and stylesheets that are injected into documents that are
+    /// loaded from now on.
+    SetUserContentManager(UserContentManager),
+    /// The embedder changed the preferences, or the values that they take for particular
+    /// sites, at runtime.
+    PreferencesUpdated(Box<Preferences>, SitePreferences),
+    /// Measure the memory used by Servo, and send the resulting report to the embedder.
+    CreateMemoryReport,
+    /// The embedder decided what to do about the unresponsive script running in the given
+    /// pipeline.
+    StopUnresponsiveScript(PipelineId, UnresponsiveScriptAction),
     /// The Servo renderer scrolled and is updating the scroll states of the nodes in the
     /// given pipeline via the constellation.
     SetScrollStates(PipelineId, Vec<ScrollState>),
+    /// The Servo renderer pinch-zoomed or panned the visual viewport of the top-level document
+    /// in the given pipeline.
+    SetVisualViewport(PipelineId, VisualViewportState),
     /// Notify the constellation that a particular paint metric event has happened for the given pipeline.
     PaintMetric(PipelineId, PaintMetricEvent),
+    /// Search the text of the top-level document of a `WebView`.
+    FindInPage(WebViewId, FindInPageRequest),
+    /// Find or fill in the form controls of the top-level document of a `WebView` that can
+    /// be filled in automatically.
+    Autofill(WebViewId, AutofillRequest),
+    /// Pause, resume, step or set the clock of the animations and timers of the top-level
+    /// document of a `WebView`.
+    ControlAnimationClock(WebViewId, AnimationClockRequest),
 }
 
 /// A description of a paint metric that is sent from the Servo renderer to the
@@ -124,6 +174,9 @@ pub struct WindowSizeData {
 
     /// The resolution of the window in dppx, not including any "pinch zoom" factor.
     pub device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+
+    /// The factor by which the size of text is multiplied for text-only zoom.
+    pub text_zoom: f32,
 }
 
 /// The type of window size change.
@@ -166,6 +219,10 @@ pub struct CompositorHitTestResult {
 
     /// The scroll tree node associated with this hit test item.
     pub scroll_tree_node: ScrollTreeNodeId,
+
+    /// When the hit test was performed, so that the time until the input event is dispatched
+    /// by script can be measured.
+    pub hit_test_time: CrossProcessInstant,
 }
 
 /// The scroll state of a stacking context.
@@ -177,6 +234,27 @@ pub struct ScrollState {
     pub scroll_offset: Vector2D<f32, LayoutPixel>,
 }
 
+/// The visual viewport of a top-level document, which is the part of its layout viewport that
+/// is visible when the renderer is pinch-zoomed.
+///
+/// <https://drafts.csswg.org/cssom-view/#visual-viewport>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct VisualViewportState {
+    /// The offset of the visual viewport from the layout viewport.
+    pub offset: Vector2D<f32, CSSPixel>,
+    /// The pinch zoom scale of the visual viewport.
+    pub scale: f32,
+}
+
+impl Default for VisualViewportState {
+    fn default() -> Self {
+        Self {
+            offset: Vector2D::zero(),
+            scale: 1.,
+        }
+    }
+}
+
 /// The address of a node. Layout sends these back. They must be validated via
 /// `from_untrusted_node_address` before they can be used, because we do not trust layout.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
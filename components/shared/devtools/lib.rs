@@ -67,6 +67,16 @@ pub enum ChromeToDevtoolsControlMsg {
     NetworkEvent(String, NetworkEvent),
 }
 
+/// How far along in loading a document is, as told by `document.readyState`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ReadyState {
+    Loading,
+    /// The document was parsed, and `DOMContentLoaded` is about to be fired.
+    Interactive,
+    /// The resources of the document were loaded, and `load` is about to be fired.
+    Complete,
+}
+
 /// The state of a page navigation.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum NavigationState {
@@ -102,11 +112,134 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page title change
     TitleChanged(PipelineId, String),
+
+    /// Report that the document of a page is further along in loading.
+    ReadyStateChanged(PipelineId, ReadyState),
+
+    /// Report changes to the DOM of a document that the inspector watches.
+    DomMutations(PipelineId, Vec<DomMutation>),
+
+    /// A new script source was compiled in the given pipeline.
+    CreateSourceActor(PipelineId, SourceInfo),
+
+    /// The debugger paused the script thread of the given pipeline, which now waits for a
+    /// [`DevtoolScriptControlMsg::Resume`].
+    DebuggerPaused(PipelineId, PauseInfo),
+
+    /// The user asked to inspect a node of the given pipeline. The nodes are its inclusive
+    /// ancestors, from the document down to the node itself.
+    InspectNode(PipelineId, Vec<NodeInfo>),
+}
+
+/// A script source seen by the debugger.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SourceInfo {
+    pub url: ServoUrl,
+    /// How the source was introduced, such as `scriptElement`, `eval` or `Function`.
+    pub introduction_type: Option<String>,
+    /// The source map of the source, from its `sourceMappingURL` comment.
+    pub source_map_url: Option<String>,
+    /// The text of the source.
+    pub content: String,
+    /// The id that SpiderMonkey gave to the source, which is unique in its script thread.
+    pub spidermonkey_id: u32,
+}
+
+/// A position in a script source, with a one-based line and a zero-based column.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SourceLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A breakpoint, which applies to every source with the given URL.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct BreakpointLocation {
+    pub url: String,
+    pub line: u32,
+    /// The column of the breakpoint, or `None` to break at the first position on the line.
+    pub column: Option<u32>,
+}
+
+/// Why the debugger paused.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum PauseReason {
+    Breakpoint,
+    DebuggerStatement,
+    Interrupted,
+    /// A step requested by the [`ResumeLimit`] of the last resumption finished.
+    ResumeLimit,
+}
+
+/// The state of the script thread when the debugger paused.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PauseInfo {
+    pub reason: PauseReason,
+    /// The frames on the stack, youngest first.
+    pub frames: Vec<FrameInfo>,
 }
 
-/// Serialized JS return values
-/// TODO: generalize this beyond the EvaluateJS message?
+/// A frame on the stack of a paused script thread.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameInfo {
+    /// The name of the function that the frame executes, which is empty for global code.
+    pub display_name: String,
+    /// The [`SourceInfo::spidermonkey_id`] of the source that the frame executes.
+    pub source_id: u32,
+    pub location: SourceLocation,
+    /// The scopes of the frame, innermost first.
+    pub scopes: Vec<ScopeInfo>,
+}
+
+/// A scope of a frame, and the variables that it binds.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScopeInfo {
+    /// The kind of environment, such as `function`, `block`, `with` or `object`.
+    pub kind: String,
+    /// The name of the function, for function scopes.
+    pub function_name: Option<String>,
+    /// The bindings of declarative scopes. The variables of object scopes, such as the
+    /// global scope, are not listed.
+    pub bindings: Vec<(String, EvaluateJSReply)>,
+}
+
+/// How far a paused script thread should run before pausing again.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum ResumeLimit {
+    /// Run until the next breakpoint.
+    #[default]
+    None,
+    /// Run until the next line of the current frame, or of its caller when it returns.
+    StepOver,
+    /// Run until the next line, entering the functions that are called.
+    StepIn,
+    /// Run until the current frame returns.
+    StepOut,
+}
+
+/// A change to the DOM of a document, whose nodes are identified by their unique id.
 #[derive(Debug, Deserialize, Serialize)]
+pub enum DomMutation {
+    /// An attribute of an element was set, or removed if there is no new value.
+    Attribute {
+        target: String,
+        attribute_name: String,
+        new_value: Option<String>,
+    },
+    /// The text of a text, comment or processing instruction node changed.
+    CharacterData { target: String, new_value: String },
+    /// Children were added to or removed from a node.
+    ChildList {
+        target: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+        num_children: usize,
+    },
+}
+
+/// Serialized JS values, as returned by the EvaluateJS message or bound in the scopes of a
+/// paused frame.
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum EvaluateJSReply {
     VoidValue,
     NullValue,
@@ -171,6 +304,17 @@ pub struct NodeStyle {
     pub priority: String,
 }
 
+/// The overlays that the inspector can draw over a node to show its layout.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub enum HighlighterKind {
+    /// The content, padding, border and margin boxes of the node.
+    BoxModel,
+    /// The flex container and its items.
+    Flexbox,
+    /// The grid container, its tracks and the gaps between them.
+    Grid,
+}
+
 /// The properties of a DOM node as computed by layout.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -239,8 +383,26 @@ pub enum DevtoolScriptControlMsg {
     GetLayout(PipelineId, String, IpcSender<Option<ComputedNodeLayout>>),
     /// Update a given node's attributes with a list of modifications.
     ModifyAttribute(PipelineId, String, Vec<AttrModification>),
-    /// Update a given node's style rules with a list of modifications.
-    ModifyRule(PipelineId, String, Vec<RuleModification>),
+    /// Retrieve the value of the given text, comment or processing instruction node.
+    GetNodeValue(PipelineId, String, IpcSender<Option<String>>),
+    /// Retrieve the markup of the given node, including the node itself.
+    GetOuterHtml(PipelineId, String, IpcSender<Option<String>>),
+    /// Retrieve the descendants of the given node that match a selector, in tree order, or
+    /// `None` if the node does not exist or the selector is invalid.
+    QuerySelectorAll(PipelineId, String, String, IpcSender<Option<Vec<NodeInfo>>>),
+    /// Replace the value of the given text, comment or processing instruction node.
+    ModifyNodeValue(PipelineId, String, String),
+    /// Update the style rules of a given node with a list of modifications. These apply to the
+    /// rules with the given selector in the stylesheet with the given index, or to the style
+    /// attribute of the node if there is no selector.
+    ModifyRule(
+        PipelineId,
+        String,
+        Option<(String, usize)>,
+        Vec<RuleModification>,
+    ),
+    /// Start or stop reporting the changes to the DOM of the document of a given pipeline.
+    WatchDomMutations(PipelineId, bool),
     /// Request live console messages for a given pipeline (true if desired, false otherwise).
     WantsLiveNotifications(PipelineId, bool),
     /// Request live notifications for a given set of timeline events for a given pipeline.
@@ -256,8 +418,27 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Direct the given pipeline to navigate to a new page.
+    NavigateTo(PipelineId, ServoUrl),
     /// Gets the list of all allowed CSS rules and possible values.
     GetCssDatabase(IpcSender<HashMap<String, CssDatabaseProperty>>),
+    /// Start debugging the documents of the script thread of the given pipeline, since a
+    /// devtools client attached to it.
+    AttachDebugger(PipelineId),
+    /// Retrieve the positions where breakpoints can be set in the source with the given
+    /// SpiderMonkey id.
+    GetPossibleBreakpoints(u32, IpcSender<Vec<SourceLocation>>),
+    /// Set a breakpoint in every source of the script thread with the given URL.
+    SetBreakpoint(BreakpointLocation),
+    /// Remove a breakpoint set with [`DevtoolScriptControlMsg::SetBreakpoint`].
+    ClearBreakpoint(BreakpointLocation),
+    /// Pause as soon as the script thread runs JS code.
+    Interrupt,
+    /// Resume the paused script thread.
+    Resume(ResumeLimit),
+    /// Draw the overlays of the given kind over the given node of the given pipeline, or
+    /// remove them if there is no node.
+    HighlightDomNode(PipelineId, HighlighterKind, Option<String>),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -274,7 +455,10 @@ pub struct RuleModification {
     pub type_: String,
     pub index: u32,
     pub name: String,
+    /// The new value of the declaration, which is missing when it is removed.
+    #[serde(default)]
     pub value: String,
+    #[serde(default)]
     pub priority: String,
 }
 
@@ -420,10 +604,44 @@ pub struct HttpResponse {
     pub pipeline_id: PipelineId,
 }
 
+/// The longest part of a response body that is kept for the network monitor. Longer bodies
+/// are only shown up to this size.
+pub const MAX_RESPONSE_BODY_PREVIEW_SIZE: usize = 1024 * 1024;
+
+/// How long each phase of a fetch took, as shown in the timings of the network monitor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpTimings {
+    pub blocked: Duration,
+    pub dns: Duration,
+    pub connect: Duration,
+    pub ssl: Duration,
+    pub send: Duration,
+    pub wait: Duration,
+    pub receive: Duration,
+}
+
+impl HttpTimings {
+    pub fn total(&self) -> Duration {
+        self.blocked + self.dns + self.connect + self.ssl + self.send + self.wait + self.receive
+    }
+}
+
+/// Sent once the body of a response has been received completely.
+#[derive(Debug, PartialEq)]
+pub struct HttpResponseEnd {
+    /// The start of the decoded body, at most [`MAX_RESPONSE_BODY_PREVIEW_SIZE`] bytes long.
+    pub body_preview: Vec<u8>,
+    /// The size of the whole decoded body.
+    pub body_size: usize,
+    pub timings: HttpTimings,
+    pub pipeline_id: PipelineId,
+}
+
 #[derive(Debug)]
 pub enum NetworkEvent {
     HttpRequest(HttpRequest),
     HttpResponse(HttpResponse),
+    HttpResponseEnd(HttpResponseEnd),
 }
 
 impl TimelineMarker {
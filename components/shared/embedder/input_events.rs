@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use keyboard_types::{CompositionEvent, KeyboardEvent};
+use std::path::PathBuf;
+
+use keyboard_types::{Code, CompositionEvent, KeyboardEvent};
 use log::error;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,7 @@ use webrender_api::units::DevicePoint;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum InputEvent {
     EditingAction(EditingActionEvent),
+    FileDrop(FileDropEvent),
     Gamepad(GamepadEvent),
     Ime(ImeEvent),
     Keyboard(KeyboardEvent),
@@ -33,6 +36,7 @@ impl InputEvent {
     pub fn point(&self) -> Option<DevicePoint> {
         match self {
             InputEvent::EditingAction(..) => None,
+            InputEvent::FileDrop(event) => Some(event.point),
             InputEvent::Gamepad(..) => None,
             InputEvent::Ime(..) => None,
             InputEvent::Keyboard(..) => None,
@@ -49,6 +53,19 @@ pub struct MouseButtonEvent {
     pub action: MouseButtonAction,
     pub button: MouseButton,
     pub point: DevicePoint,
+    /// The state of the pen, if the event comes from a pen rather than from a mouse.
+    pub pen: Option<PenState>,
+}
+
+impl MouseButtonEvent {
+    pub fn new(action: MouseButtonAction, button: MouseButton, point: DevicePoint) -> Self {
+        Self {
+            action,
+            button,
+            point,
+            pen: None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -101,6 +118,41 @@ pub enum MouseButtonAction {
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct MouseMoveEvent {
     pub point: DevicePoint,
+    /// The state of the pen, if the event comes from a pen rather than from a mouse.
+    pub pen: Option<PenState>,
+}
+
+impl MouseMoveEvent {
+    pub fn new(point: DevicePoint) -> Self {
+        Self { point, pen: None }
+    }
+}
+
+/// The state of a pen or stylus, for the mouse events that it causes when the platform reports
+/// them. They are exposed to the page with the pointer events that are fired for them.
+///
+/// <https://w3c.github.io/pointerevents/#pointerevent-interface>
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PenState {
+    /// The pressure of the tip of the pen, from 0 to 1.
+    pub pressure: f32,
+    /// The pressure of a control on the barrel of the pen, such as a finger wheel, from -1 to 1.
+    pub tangential_pressure: f32,
+    /// The angle between the pen and the plane of the Y and Z axes, from -90 to 90 degrees.
+    /// It is positive when the pen leans to the right.
+    pub tilt_x: i32,
+    /// The angle between the pen and the plane of the X and Z axes, from -90 to 90 degrees.
+    /// It is positive when the pen leans towards the user.
+    pub tilt_y: i32,
+    /// The clockwise rotation of the pen around its own axis, from 0 to 359 degrees.
+    pub twist: i32,
+}
+
+/// Files that the user dropped onto a `WebView`, from outside of it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileDropEvent {
+    pub point: DevicePoint,
+    pub paths: Vec<PathBuf>,
 }
 
 /// The type of input represented by a multi-touch event.
@@ -279,3 +331,24 @@ pub enum GamepadUpdateType {
     /// <https://www.w3.org/TR/gamepad/#dfn-represents-a-standard-gamepad-button>
     Button(usize, f64),
 }
+
+/// The keys that a page asked to receive with `navigator.keyboard.lock()`, even when the
+/// embedder would otherwise handle them itself, for instance as shortcuts.
+/// <https://wicg.github.io/keyboard-lock/>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum KeyboardLock {
+    /// All keys are locked.
+    AllKeys,
+    /// Only the keys with these physical codes are locked.
+    Keys(Vec<Code>),
+}
+
+impl KeyboardLock {
+    /// Whether the key with the physical `code` is locked.
+    pub fn includes(&self, code: Code) -> bool {
+        match self {
+            KeyboardLock::AllKeys => true,
+            KeyboardLock::Keys(codes) => codes.contains(&code),
+        }
+    }
+}
@@ -16,17 +16,21 @@ mod webdriver;
 use std::fmt::{Debug, Error, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 
+pub use accesskit;
 use base::id::{PipelineId, WebViewId};
 use crossbeam_channel::Sender;
-use http::{HeaderMap, Method, StatusCode};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
 use ipc_channel::ipc::IpcSender;
+use keyboard_types::Code;
 pub use keyboard_types::{KeyboardEvent, Modifiers};
 use log::warn;
 use malloc_size_of_derive::MallocSizeOf;
 use num_derive::FromPrimitive;
 use pixels::Image;
 use serde::{Deserialize, Serialize};
+use servo_geometry::DeviceIndependentIntRect;
 use servo_url::ServoUrl;
 use strum_macros::IntoStaticStr;
 use url::Url;
@@ -131,6 +135,75 @@ pub enum ContextMenuResult {
     Dismissed,
     Ignored,
     Selected(usize),
+    /// The user chose one of the built-in actions for the element that the context menu
+    /// was opened for. It is performed by the document.
+    Action(ContextMenuAction),
+}
+
+/// Information about what was under the pointer when a context menu was opened, so that the
+/// embedder can decide which [`ContextMenuAction`]s to offer.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ContextMenuElementInformation {
+    /// The URL of the link containing the element, if any.
+    pub link_url: Option<ServoUrl>,
+    /// The URL of the image, if the element is an image.
+    pub image_url: Option<ServoUrl>,
+    /// The state of the media element, if the element is one.
+    pub media: Option<ContextMenuMediaInformation>,
+    /// The text selected in the document, if the selection isn't empty.
+    pub selected_text: Option<String>,
+    /// Whether the element is a text control or other element that the user can type into.
+    pub editable: bool,
+}
+
+/// The state of a `<video>` or `<audio>` element that a context menu was opened for.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ContextMenuMediaInformation {
+    /// Whether the element is a `<video>` rather than an `<audio>` element.
+    pub is_video: bool,
+    /// The URL of the media resource that is playing, if any.
+    pub source_url: Option<ServoUrl>,
+    pub paused: bool,
+    pub muted: bool,
+    /// Whether the element shows its own playback controls.
+    pub has_controls: bool,
+}
+
+/// A built-in context menu action, which the document performs on the element that the
+/// context menu was opened for.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ContextMenuAction {
+    /// Copy the URL of the link to the clipboard.
+    CopyLink,
+    /// Download the target of the link.
+    SaveLink,
+    /// Copy the pixels of the image to the clipboard.
+    CopyImage,
+    /// Copy the URL of the image to the clipboard.
+    CopyImageAddress,
+    /// Download the image.
+    SaveImage,
+    /// Copy the selected text to the clipboard.
+    CopySelection,
+    /// Play the media element if it is paused, or pause it otherwise.
+    ToggleMediaPlayback,
+    /// Mute or unmute the media element.
+    ToggleMediaMute,
+    /// Show or hide the playback controls of the media element.
+    ToggleMediaControls,
+    /// Download the media resource.
+    SaveMedia,
+    /// Select the element in the inspector of the connected devtools clients.
+    InspectElement,
+}
+
+/// An image to put on the system clipboard, as RGBA pixels with 8 bits per channel that are
+/// not premultiplied by alpha.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
 }
 
 /// [Simple dialogs](https://html.spec.whatwg.org/multipage/#simple-dialogs) are synchronous dialogs
@@ -250,11 +323,16 @@ pub enum EmbedderMsg {
         IpcSender<ContextMenuResult>,
         Option<String>,
         Vec<String>,
+        ContextMenuElementInformation,
     ),
     /// Whether or not to allow a pipeline to load a url.
     AllowNavigationRequest(WebViewId, PipelineId, ServoUrl),
     /// Whether or not to allow script to open a new tab/browser
-    AllowOpeningWebView(WebViewId, IpcSender<Option<WebViewId>>),
+    AllowOpeningWebView(
+        WebViewId,
+        AuxiliaryWebViewFeatures,
+        IpcSender<Option<WebViewId>>,
+    ),
     /// A webview was destroyed.
     WebViewClosed(WebViewId),
     /// A webview gained focus for keyboard events.
@@ -271,14 +349,17 @@ pub enum EmbedderMsg {
     GetClipboardText(WebViewId, IpcSender<Result<String, String>>),
     /// Sets system clipboard contents
     SetClipboardText(WebViewId, String),
+    /// Sets system clipboard contents to an image
+    SetClipboardImage(WebViewId, ClipboardImage),
     /// Changes the cursor.
     SetCursor(WebViewId, Cursor),
     /// A favicon was detected
     NewFavicon(WebViewId, ServoUrl),
     /// The history state has changed.
     HistoryChanged(WebViewId, Vec<ServoUrl>, usize),
-    /// Entered or exited fullscreen.
-    NotifyFullscreenStateChanged(WebViewId, bool),
+    /// Entered or exited fullscreen. When entering fullscreen, content may have asked for a
+    /// particular screen via `FullscreenOptions.screen`.
+    NotifyFullscreenStateChanged(WebViewId, bool, Option<ScreenId>),
     /// The [`LoadStatus`] of the Given `WebView` has changed.
     NotifyLoadStatusChanged(WebViewId, LoadStatus),
     WebResourceRequested(
@@ -286,15 +367,37 @@ pub enum EmbedderMsg {
         WebResourceRequest,
         IpcSender<WebResourceResponseMsg>,
     ),
-    /// A pipeline panicked. First string is the reason, second one is the backtrace.
-    Panic(WebViewId, String, Option<String>),
+    /// The response to a request whose response the embedder asked to observe was received.
+    /// The embedder may reply with a response whose status and headers replace those of the
+    /// received response.
+    WebResourceResponseReceived(
+        Option<WebViewId>,
+        WebResourceRequest,
+        WebResourceResponse,
+        IpcSender<Option<WebResourceResponse>>,
+    ),
+    /// A navigation response was handed to the download manager and is being written to
+    /// disk. The sender controls the download for as long as it runs.
+    DownloadStarted(Option<WebViewId>, DownloadInfo, IpcSender<DownloadControl>),
+    /// The state of a download that has started changed.
+    DownloadStateChanged(Option<WebViewId>, DownloadId, DownloadState),
+    /// A thread panicked.
+    Panic(CrashReport),
+    /// The memory report that the embedder asked for is ready.
+    MemoryReportCreated(MemoryReport),
+    /// The script running in the given pipeline has not yielded for a long time, making its
+    /// page unresponsive.
+    ScriptUnresponsive(WebViewId, PipelineId),
+    /// The script that was reported to be unresponsive in the given pipeline has finished.
+    ScriptResponsive(WebViewId, PipelineId),
     /// Open dialog to select bluetooth device.
     GetSelectedBluetoothDevice(WebViewId, Vec<String>, IpcSender<Option<String>>),
-    /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
+    /// Open file dialog to select files, or a directory with the files in it, as the
+    /// [`FileSelectionMode`] allows.
     SelectFiles(
         WebViewId,
         Vec<FilterPattern>,
-        bool,
+        FileSelectionMode,
         IpcSender<Option<Vec<PathBuf>>>,
     ),
     /// Open interface to request permission specified by prompt.
@@ -312,6 +415,19 @@ pub enum EmbedderMsg {
     ),
     /// Request to hide the IME when the editable element is blurred.
     HideIME(WebViewId),
+    /// Show the menu of a `<select>` element, placed below the given rectangle of the element,
+    /// so that the user can choose one of its options. The response is the [`SelectElementOption::id`]
+    /// of the chosen option, or `None` when the menu was dismissed.
+    ShowSelectElementMenu(
+        WebViewId,
+        Vec<SelectElementOptionOrOptgroup>,
+        Option<usize>,
+        DeviceIntRect,
+        IpcSender<Option<usize>>,
+    ),
+    /// Notify the embedder of the area of the IME composition in the focused editable
+    /// element, which is where the candidate window of the IME should be placed.
+    UpdateImeCaretRect(WebViewId, DeviceIntRect),
     /// Report a complete sampled profile
     ReportProfile(Vec<u8>),
     /// Notifies the embedder about media session events
@@ -331,6 +447,31 @@ pub enum EmbedderMsg {
     ShutdownComplete,
     /// Request to display a notification.
     ShowNotification(Option<WebViewId>, Notification),
+    /// The accessibility tree of the top-level document of a `WebView` changed. Only the
+    /// nodes that changed since the previous update are included.
+    AccessibilityTreeUpdate(WebViewId, accesskit::TreeUpdate),
+    /// The state of the find-in-page search of a `WebView` changed.
+    FindInPageResult(WebViewId, FindInPageResult),
+    /// Check the spelling of words in editable content, responding with whether each of them
+    /// is misspelled.
+    CheckSpelling(WebViewId, Vec<String>, IpcSender<Vec<bool>>),
+    /// Get suggestions for correcting the spelling of a misspelled word.
+    GetSpellingSuggestions(WebViewId, String, IpcSender<Vec<String>>),
+    /// The form controls of the top-level document of a `WebView` that can be filled in, as
+    /// requested with [`AutofillRequest::GetFields`].
+    AutofillFields(WebViewId, Vec<AutofillField>),
+    /// A form control of the top-level document of a `WebView` that can be filled in was
+    /// focused.
+    AutofillFieldFocused(WebViewId, AutofillField),
+    /// Get the characters that the keys of the current keyboard layout produce, as pairs of
+    /// the physical code of a key and the character it produces without modifiers.
+    GetKeyboardLayoutMap(WebViewId, IpcSender<Vec<(Code, String)>>),
+    /// The page of a `WebView` locked the keyboard to receive the given keys, or released
+    /// the lock if `None`.
+    SetKeyboardLock(WebViewId, Option<KeyboardLock>),
+    /// The page of a `WebView` called `window.print()`. Its contents are laid out with print
+    /// media until the embedder responds, once it printed them or the user cancelled.
+    ShowPrintDialog(WebViewId, IpcSender<()>),
 }
 
 impl Debug for EmbedderMsg {
@@ -340,11 +481,109 @@ impl Debug for EmbedderMsg {
     }
 }
 
+impl EmbedderMsg {
+    /// Whether this message carries a sender for the response of the embedder, which the
+    /// sender of the message may be waiting on.
+    pub fn expects_response(&self) -> bool {
+        match self {
+            EmbedderMsg::ShowSimpleDialog(..) |
+            EmbedderMsg::RequestAuthentication(..) |
+            EmbedderMsg::ShowContextMenu(..) |
+            EmbedderMsg::AllowOpeningWebView(..) |
+            EmbedderMsg::AllowUnload(..) |
+            EmbedderMsg::GetClipboardText(..) |
+            EmbedderMsg::WebResourceRequested(..) |
+            EmbedderMsg::WebResourceResponseReceived(..) |
+            EmbedderMsg::DownloadStarted(..) |
+            EmbedderMsg::GetSelectedBluetoothDevice(..) |
+            EmbedderMsg::SelectFiles(..) |
+            EmbedderMsg::PromptPermission(..) |
+            EmbedderMsg::ShowSelectElementMenu(..) |
+            EmbedderMsg::RequestDevtoolsConnection(..) |
+            EmbedderMsg::PlayGamepadHapticEffect(..) |
+            EmbedderMsg::StopGamepadHapticEffect(..) |
+            EmbedderMsg::CheckSpelling(..) |
+            EmbedderMsg::GetSpellingSuggestions(..) |
+            EmbedderMsg::GetKeyboardLayoutMap(..) |
+            EmbedderMsg::ShowPrintDialog(..) => true,
+            EmbedderMsg::Status(..) |
+            EmbedderMsg::ChangePageTitle(..) |
+            EmbedderMsg::MoveTo(..) |
+            EmbedderMsg::ResizeTo(..) |
+            EmbedderMsg::AllowNavigationRequest(..) |
+            EmbedderMsg::WebViewClosed(..) |
+            EmbedderMsg::WebViewFocused(..) |
+            EmbedderMsg::WebViewBlurred |
+            EmbedderMsg::Keyboard(..) |
+            EmbedderMsg::ClearClipboard(..) |
+            EmbedderMsg::SetClipboardText(..) |
+            EmbedderMsg::SetClipboardImage(..) |
+            EmbedderMsg::SetCursor(..) |
+            EmbedderMsg::NewFavicon(..) |
+            EmbedderMsg::HistoryChanged(..) |
+            EmbedderMsg::NotifyFullscreenStateChanged(..) |
+            EmbedderMsg::NotifyLoadStatusChanged(..) |
+            EmbedderMsg::DownloadStateChanged(..) |
+            EmbedderMsg::Panic(..) |
+            EmbedderMsg::MemoryReportCreated(..) |
+            EmbedderMsg::ScriptUnresponsive(..) |
+            EmbedderMsg::ScriptResponsive(..) |
+            EmbedderMsg::ShowIME(..) |
+            EmbedderMsg::HideIME(..) |
+            EmbedderMsg::UpdateImeCaretRect(..) |
+            EmbedderMsg::ReportProfile(..) |
+            EmbedderMsg::MediaSessionEvent(..) |
+            EmbedderMsg::OnDevtoolsStarted(..) |
+            EmbedderMsg::ShutdownComplete |
+            EmbedderMsg::ShowNotification(..) |
+            EmbedderMsg::AccessibilityTreeUpdate(..) |
+            EmbedderMsg::FindInPageResult(..) |
+            EmbedderMsg::AutofillFields(..) |
+            EmbedderMsg::AutofillFieldFocused(..) |
+            EmbedderMsg::SetKeyboardLock(..) => false,
+        }
+    }
+}
+
+/// An `<option>` of a `<select>` element whose menu is shown by the embedder.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SelectElementOption {
+    /// The index of the option in the list of options of the `<select>` element.
+    pub id: usize,
+    /// The label of the option, which is shown in the menu.
+    pub label: String,
+    /// Whether the option is disabled, in which case it cannot be chosen.
+    pub is_disabled: bool,
+}
+
+/// An entry of the menu of a `<select>` element, which is either an option or an `<optgroup>`
+/// with a label and the options that it groups.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SelectElementOptionOrOptgroup {
+    Option(SelectElementOption),
+    Optgroup {
+        label: String,
+        options: Vec<SelectElementOption>,
+    },
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FilterPattern(pub String);
 
+/// What the user can select in a file selection dialog.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FileSelectionMode {
+    /// A single file.
+    Single,
+    /// Any number of files.
+    Multiple,
+    /// A single directory, which stands for all of the files in it and in its
+    /// subdirectories.
+    Directory,
+}
+
 /// <https://w3c.github.io/mediasession/#mediametadata>
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MediaMetadata {
@@ -421,6 +660,7 @@ pub enum PermissionFeature {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    WindowManagement,
 }
 
 /// Used to specify the kind of input method editor appropriate to edit a field.
@@ -491,6 +731,41 @@ pub enum WebResourceResponseMsg {
     CancelLoad,
     /// Signal that this load will not be intercepted.
     DoNotIntercept,
+    /// Signal that this load will not be intercepted, but that the request is modified before
+    /// it continues.
+    ModifyRequest(WebResourceRequestModification),
+}
+
+/// Changes that the embedder makes to a request before it continues to the network.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WebResourceRequestModification {
+    /// Headers that are set on the request, replacing the headers of the same names.
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    /// The URL that the request is redirected to, if any.
+    pub redirect_url: Option<Url>,
+    /// Whether the embedder is given the response so that it can modify it.
+    pub observe_response: bool,
+}
+
+impl WebResourceRequestModification {
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn redirect_url(mut self, url: Url) -> Self {
+        self.redirect_url = Some(url);
+        self
+    }
+
+    pub fn observe_response(mut self) -> Self {
+        self.observe_response = true;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
@@ -545,6 +820,385 @@ pub enum Theme {
     /// Dark theme.
     Dark,
 }
+
+/// Media features that the embedder emulates for the content of a `WebView`, in place of
+/// the values that Servo derives from the platform. This lets embedders and test harnesses
+/// check how pages respond to these features without changing the platform settings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct EmulatedMediaFeatures {
+    /// The theme matched by the `prefers-color-scheme` media feature, instead of the one
+    /// reported with `WebView::notify_theme_change`.
+    pub prefers_color_scheme: Option<Theme>,
+    /// The value of the `prefers-reduced-motion` media feature, which is `no-preference`
+    /// otherwise. This and the `hover` and `pointer` media features are only evaluated for
+    /// `window.matchMedia()`, as the style engine does not support them in stylesheets.
+    pub prefers_reduced_motion: Option<PrefersReducedMotion>,
+    /// The value of the `hover` and `any-hover` media features, which is `hover` otherwise.
+    pub hover: Option<HoverCapability>,
+    /// The value of the `pointer` and `any-pointer` media features, which is `fine`
+    /// otherwise.
+    pub pointer: Option<PointerCapability>,
+    /// The value of the `forced-colors` media feature, which is `none` otherwise. When it is
+    /// `active`, layout paints pages with the colors of forced colors mode. Like the features
+    /// above, it is only evaluated for `window.matchMedia()`.
+    pub forced_colors: Option<ForcedColors>,
+    /// The number of device pixels per CSS pixel matched by the `resolution` media feature,
+    /// instead of the device pixel ratio of the `WebView`. It is also the value of
+    /// `window.devicePixelRatio` and the density that images are chosen for.
+    pub resolution: Option<f32>,
+}
+
+/// The values of the `prefers-reduced-motion` media feature.
+/// <https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum PrefersReducedMotion {
+    NoPreference,
+    Reduce,
+}
+
+/// The values of the `hover` and `any-hover` media features.
+/// <https://drafts.csswg.org/mediaqueries-4/#hover>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum HoverCapability {
+    None,
+    Hover,
+}
+
+/// The values of the `pointer` and `any-pointer` media features.
+/// <https://drafts.csswg.org/mediaqueries-4/#pointer>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum PointerCapability {
+    None,
+    Coarse,
+    Fine,
+}
+
+/// The values of the `forced-colors` media feature.
+/// <https://drafts.csswg.org/mediaqueries-5/#forced-colors>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum ForcedColors {
+    None,
+    Active,
+}
+
+/// The effective type of a network connection, which reflects its measured quality rather
+/// than the technology that it uses. <https://wicg.github.io/netinfo/#effectiveconnectiontype-enum>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum EffectiveConnectionType {
+    Slow2G,
+    TwoG,
+    ThreeG,
+    FourG,
+}
+
+/// The connectivity and quality of the platform's network connection, as reported by the
+/// embedder.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct NetworkStatus {
+    /// Whether the network is reachable.
+    pub online: bool,
+    pub effective_type: EffectiveConnectionType,
+    /// The estimated bandwidth, in megabits per second.
+    pub downlink: f64,
+    /// The estimated round-trip time, in milliseconds.
+    pub rtt: u64,
+    /// Whether the user asked for reduced data usage.
+    pub save_data: bool,
+}
+
+impl Default for NetworkStatus {
+    fn default() -> Self {
+        Self {
+            online: true,
+            effective_type: EffectiveConnectionType::FourG,
+            downlink: 10.0,
+            rtt: 50,
+            save_data: false,
+        }
+    }
+}
+
+/// Which cookies web content may use. Third-party cookies are those of requests and documents
+/// whose site differs from the site of the top-level document.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CookiePolicy {
+    #[default]
+    AllowAll,
+    /// Block third-party cookies, except for those set with the `Partitioned` attribute,
+    /// which are kept separately for every top-level site.
+    BlockThirdParty,
+    BlockAll,
+}
+
+impl CookiePolicy {
+    /// Parse a policy from the value of the `network_cookie_policy` preference.
+    pub fn from_pref(value: &str) -> Option<Self> {
+        match value {
+            "allow-all" => Some(Self::AllowAll),
+            "block-third-party" => Some(Self::BlockThirdParty),
+            "block-all" => Some(Self::BlockAll),
+            _ => None,
+        }
+    }
+}
+
+/// The features that a page asked for when opening a new auxiliary webview, for instance
+/// with `window.open()`. Embedders can use them to decide whether to open the webview as a
+/// tab or a separate window, or whether to block it entirely.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AuxiliaryWebViewFeatures {
+    /// Whether the page asked for a minimal popup window rather than a new tab.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#popup-window-is-requested>
+    pub popup: bool,
+    /// The requested position of the left edge of the new window, in CSS pixels relative to
+    /// the screen.
+    pub left: Option<i32>,
+    /// The requested position of the top edge of the new window, in CSS pixels relative to
+    /// the screen.
+    pub top: Option<i32>,
+    /// The requested width of the new webview's viewport, in CSS pixels.
+    pub width: Option<i32>,
+    /// The requested height of the new webview's viewport, in CSS pixels.
+    pub height: Option<i32>,
+    /// Whether the page was handling a user interaction when it asked for the webview.
+    /// Popups opened without one are usually unwanted.
+    pub user_activated: bool,
+}
+
+/// A report about a panic in one of Servo's threads.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashReport {
+    /// When the panic happened.
+    pub time: SystemTime,
+    /// The webview whose content was being handled when the panic happened, if any.
+    pub webview_id: Option<WebViewId>,
+    /// The pipeline whose content was being handled when the panic happened, if known.
+    pub pipeline_id: Option<PipelineId>,
+    /// The URL of the document in that pipeline, if known.
+    pub url: Option<ServoUrl>,
+    /// The panic message.
+    pub reason: String,
+    /// The backtrace of the panic, if it was captured.
+    pub backtrace: Option<String>,
+}
+
+/// The memory used on behalf of a single document.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PipelineMemoryUsage {
+    /// The webview showing the document.
+    pub webview_id: Option<WebViewId>,
+    /// The pipeline of the document.
+    pub pipeline_id: Option<PipelineId>,
+    /// The URL that the document was loaded from.
+    pub url: Option<ServoUrl>,
+    /// The memory used by the nodes of the document, in bytes.
+    pub dom: usize,
+    /// The memory used by the computed styles of the nodes of the document, in bytes.
+    pub styles: usize,
+    /// The memory used by the layout of the document, in bytes.
+    pub layout: usize,
+    /// The memory used by the decoded images that the document shows, in bytes.
+    pub images: usize,
+}
+
+impl PipelineMemoryUsage {
+    /// The total memory used on behalf of the document, in bytes.
+    pub fn total(&self) -> usize {
+        self.dom + self.styles + self.layout + self.images
+    }
+}
+
+/// The memory used on behalf of the documents and workers of a single origin.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct OriginMemoryUsage {
+    /// The serialization of the origin.
+    pub origin: String,
+    /// The memory used by the JavaScript heaps of the event loops and workers that only run
+    /// the scripts of this origin, in bytes. The heaps of event loops that are shared with
+    /// other origins of the same site are in [`MemoryReport::shared_javascript`].
+    pub javascript: usize,
+    /// The memory used on behalf of each of the documents of this origin.
+    pub pipelines: Vec<PipelineMemoryUsage>,
+}
+
+impl OriginMemoryUsage {
+    /// The total memory used on behalf of the origin, in bytes.
+    pub fn total(&self) -> usize {
+        self.javascript +
+            self.pipelines
+                .iter()
+                .map(PipelineMemoryUsage::total)
+                .sum::<usize>()
+    }
+}
+
+/// The memory used by the JavaScript heap of an event loop that runs the scripts of several
+/// origins of the same site, which can't be attributed to a single one of them.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SharedJavaScriptMemoryUsage {
+    /// The serializations of the origins.
+    pub origins: Vec<String>,
+    /// The memory used by the heap, in bytes.
+    pub size: usize,
+}
+
+/// A measurement of the memory used by Servo, made on demand.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MemoryReport {
+    /// The memory used on behalf of each origin with live documents or workers.
+    pub origins: Vec<OriginMemoryUsage>,
+    /// The memory used by the JavaScript heaps that are shared by several origins.
+    pub shared_javascript: Vec<SharedJavaScriptMemoryUsage>,
+    /// The explicit allocations that are not made on behalf of a particular origin, such as
+    /// those of caches, of the renderer and of the networking code, in bytes.
+    pub unattributed: usize,
+    /// The physical memory used by the processes of Servo, in bytes, if known.
+    pub resident: Option<usize>,
+}
+
+/// What to do about a script that has made its page unresponsive.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum UnresponsiveScriptAction {
+    /// Stop the script that is running, leaving the page as it is.
+    Interrupt,
+    /// Stop the event loop running the script, replacing its pages with crash pages.
+    Kill,
+}
+
+/// An identifier for a download, unique within a Servo instance.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DownloadId(pub u64);
+
+/// A description of a download that has started.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DownloadInfo {
+    pub id: DownloadId,
+    /// The URL of the response that is being downloaded.
+    pub url: Url,
+    /// The path of the file that the download is written to.
+    pub path: PathBuf,
+    /// The size of the download, if the server reported it.
+    pub total_bytes: Option<u64>,
+}
+
+/// The progress of a download.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DownloadState {
+    InProgress {
+        received_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Paused {
+        received_bytes: u64,
+    },
+    Completed,
+    /// The download failed, for the given reason. The partially written file is left on disk.
+    Failed(String),
+    /// The download was cancelled and its file removed.
+    Cancelled,
+}
+
+/// A request from the embedder to change the state of a download.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DownloadControl {
+    Pause,
+    /// Resume a paused download, using a range request when the server supports one.
+    Resume,
+    Cancel,
+}
+
+/// An identifier for a screen connected to the system. These are assigned by the embedder and
+/// should remain stable for as long as the screen stays connected.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ScreenId(pub u32);
+
+/// A description of one of the screens connected to the system, used to implement the
+/// [Window Management API](https://w3c.github.io/window-management/). All coordinates are
+/// in device independent pixels relative to the origin of the primary screen.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScreenInfo {
+    /// The embedder-assigned identifier of this screen.
+    pub id: ScreenId,
+    /// A human-readable label for the screen, such as "Built-in Retina Display".
+    pub label: String,
+    /// The position and size of the whole screen.
+    pub rect: DeviceIndependentIntRect,
+    /// The position and size of the screen area available to windows, which excludes
+    /// system UI such as docks and taskbars.
+    pub available_rect: DeviceIndependentIntRect,
+    /// The ratio between device pixels and device independent pixels for this screen.
+    pub device_pixel_ratio: f32,
+    /// The number of bits used to represent the color of a single pixel.
+    pub color_depth: u32,
+    /// Whether this is the operating system's primary screen.
+    pub is_primary: bool,
+    /// Whether this screen is built in to the device, like a laptop display.
+    pub is_internal: bool,
+}
+
+/// The set of screens connected to the system along with the screen that currently holds the
+/// majority of the embedder's window.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ScreenTopology {
+    /// All connected screens.
+    pub screens: Vec<ScreenInfo>,
+    /// The screen that the window currently occupies, if it could be determined.
+    pub current_screen: Option<ScreenId>,
+}
+
+impl ScreenTopology {
+    /// Create a [`ScreenTopology`] from a list of screens, selecting as the current screen
+    /// the one containing the center of the given window rectangle.
+    pub fn new(screens: Vec<ScreenInfo>, window_rect: DeviceIndependentIntRect) -> Self {
+        let center = window_rect.center();
+        let current_screen = screens
+            .iter()
+            .find(|screen| screen.rect.contains(center))
+            .or_else(|| screens.iter().find(|screen| screen.is_primary))
+            .or_else(|| screens.first())
+            .map(|screen| screen.id);
+        Self {
+            screens,
+            current_screen,
+        }
+    }
+
+    /// The [`ScreenInfo`] for the current screen, if any.
+    pub fn current(&self) -> Option<&ScreenInfo> {
+        let current_screen = self.current_screen?;
+        self.screens
+            .iter()
+            .find(|screen| screen.id == current_screen)
+    }
+
+    /// Compare this topology with a previous one, to decide which of the `screenschange` and
+    /// `currentscreenchange` events should fire.
+    pub fn changes_since(&self, previous: &ScreenTopology) -> ScreenTopologyChanges {
+        let screens_changed = self.screens.len() != previous.screens.len() ||
+            self.screens.iter().any(|screen| {
+                !previous
+                    .screens
+                    .iter()
+                    .any(|previous_screen| previous_screen.id == screen.id)
+            });
+        let current_screen_changed = self.current() != previous.current();
+        ScreenTopologyChanges {
+            screens_changed,
+            current_screen_changed,
+        }
+    }
+}
+
+/// The result of [`ScreenTopology::changes_since`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScreenTopologyChanges {
+    /// Whether a screen was connected or disconnected.
+    pub screens_changed: bool,
+    /// Whether the window moved to another screen or any attribute of its screen changed.
+    pub current_screen_changed: bool,
+}
+
 // The type of MediaSession action.
 /// <https://w3c.github.io/mediasession/#enumdef-mediasessionaction>
 #[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
@@ -636,3 +1290,105 @@ pub struct NotificationAction {
     /// Icon's raw image data and metadata.
     pub icon_resource: Option<Arc<Image>>,
 }
+
+/// Options for a find-in-page search.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FindInPageOptions {
+    /// Whether matches must have the same case as the searched text.
+    pub case_sensitive: bool,
+}
+
+/// A request from the embedder to search the text of the top-level document of a `WebView`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum FindInPageRequest {
+    /// Start a search for the given text, or update the current search as the user types.
+    /// The active match stays the same, if it still matches.
+    Start(String, FindInPageOptions),
+    /// Make the next match the active match, wrapping around at the end of the document.
+    Next,
+    /// Make the previous match the active match, wrapping around at the start of the document.
+    Previous,
+    /// Stop searching and remove the highlights of the matches.
+    Stop,
+}
+
+/// The identifier of a form control that the embedder can fill in, which stays valid for as
+/// long as its document is the active document of the `WebView` and the form control is
+/// connected to it. Identifiers are not reused, and those of another document are rejected.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct AutofillFieldId {
+    /// The pipeline of the document of the form control.
+    pub pipeline_id: PipelineId,
+    /// The number of the form control among those that its document reported.
+    pub index: usize,
+}
+
+/// A form control of the top-level document of a `WebView` that the embedder can fill in,
+/// for instance with credentials or an address stored by a password manager.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AutofillField {
+    /// The identifier to fill in the form control with [`AutofillRequest::Fill`].
+    pub id: AutofillFieldId,
+    /// The index of the form that the form control belongs to, among the `<form>` elements of
+    /// the document in tree order, if it belongs to one.
+    pub form: Option<usize>,
+    /// The type of the form control, as reported by its `type` IDL attribute, for instance
+    /// `"password"`, `"textarea"` or `"select-one"`.
+    pub control_type: String,
+    /// The `name` attribute of the form control.
+    pub name: String,
+    /// The `id` attribute of the form control.
+    pub element_id: String,
+    /// The tokens of the `autocomplete` attribute of the form control, in lowercase, for
+    /// instance `["section-login", "username"]`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#autofill-detail-tokens>
+    pub autocomplete: Vec<String>,
+    /// The text of the first `<label>` of the form control, if it has one.
+    pub label: Option<String>,
+    /// The `placeholder` attribute of the form control, if it has one.
+    pub placeholder: Option<String>,
+    /// The current value of the form control.
+    pub value: String,
+    /// The area of the form control in the page.
+    pub rect: DeviceIntRect,
+}
+
+/// A request from the embedder to the top-level document of a `WebView` to find or fill in
+/// the form controls that can be filled in automatically.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum AutofillRequest {
+    /// Report the form controls that can be filled in with [`EmbedderMsg::AutofillFields`].
+    GetFields,
+    /// Set the values of the given form controls as if the user had entered them, firing
+    /// `input` and `change` events. Form controls that can no longer be filled in, for
+    /// instance because they were disabled or removed, are skipped.
+    Fill(Vec<(AutofillFieldId, String)>),
+}
+
+/// A request to control the clock that drives the animations and timers of a document, so
+/// that tests of animated content and comparisons of screenshots do not depend on timing.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum AnimationClockRequest {
+    /// Stop the clock, so that animations and timers no longer advance on their own.
+    Pause,
+    /// Let the clock follow the system clock again, from the time that it was stopped at.
+    Resume,
+    /// Stop the clock and move it ahead by the given number of milliseconds, running the
+    /// timers that become due.
+    Step(f64),
+    /// Stop the clock and set the time of the animation timeline to the given number of
+    /// milliseconds. Timers only ever move ahead, by as much as the timeline did.
+    SetTime(f64),
+}
+
+/// The state of a find-in-page search, which is reported to the embedder whenever it
+/// changes, including when the content of the document changes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub struct FindInPageResult {
+    /// The number of matches in the document.
+    pub number_of_matches: usize,
+    /// The index of the active match, which is highlighted differently from the other
+    /// matches and scrolled into view, if there are any matches.
+    pub active_match_index: Option<usize>,
+}
@@ -113,8 +113,9 @@ pub enum Resource {
     /// The page contains a js function `setData` that will then be used to build the list of directory.
     /// It can be empty but then nothing will be displayed when a directory listing is requested.
     DirectoryListingHTML,
-    /// A HTML page that is used for the about:memory url.
-    AboutMemoryHTML,
+    /// A JS file that drives SpiderMonkey's Debugger API for the devtools debugger.
+    /// It can be empty but then breakpoints, stepping and sources will not work in devtools.
+    DebuggerJS,
 }
 
 impl Resource {
@@ -134,7 +135,7 @@ impl Resource {
             Resource::MediaControlsJS => "media-controls.js",
             Resource::CrashHTML => "crash.html",
             Resource::DirectoryListingHTML => "directory-listing.html",
-            Resource::AboutMemoryHTML => "about-memory.html",
+            Resource::DebuggerJS => "debugger.js",
         }
     }
 }
@@ -192,9 +193,7 @@ fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
                 Resource::DirectoryListingHTML => {
                     &include_bytes!("../../../resources/directory-listing.html")[..]
                 },
-                Resource::AboutMemoryHTML => {
-                    &include_bytes!("../../../resources/about-memory.html")[..]
-                },
+                Resource::DebuggerJS => &include_bytes!("../../../resources/debugger.js")[..],
             }
             .to_owned()
         }
@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::{ScreenId, ScreenInfo, ScreenTopology, ScreenTopologyChanges};
+use euclid::{Box2D, Point2D};
+use servo_geometry::DeviceIndependentIntRect;
+
+fn rect(x: i32, y: i32, width: i32, height: i32) -> DeviceIndependentIntRect {
+    Box2D::new(Point2D::new(x, y), Point2D::new(x + width, y + height))
+}
+
+fn screen(id: u32, x: i32, is_primary: bool) -> ScreenInfo {
+    ScreenInfo {
+        id: ScreenId(id),
+        label: format!("Screen {id}"),
+        rect: rect(x, 0, 1920, 1080),
+        available_rect: rect(x, 0, 1920, 1040),
+        device_pixel_ratio: 1.0,
+        color_depth: 24,
+        is_primary,
+        is_internal: false,
+    }
+}
+
+fn two_screens() -> Vec<ScreenInfo> {
+    vec![screen(1, 0, true), screen(2, 1920, false)]
+}
+
+#[test]
+fn test_current_screen_contains_window_center() {
+    let topology = ScreenTopology::new(two_screens(), rect(2000, 100, 800, 600));
+    assert_eq!(topology.current_screen, Some(ScreenId(2)));
+    assert_eq!(
+        topology.current().map(|screen| screen.id),
+        Some(ScreenId(2))
+    );
+
+    // A window that straddles both screens belongs to the one holding its center.
+    let topology = ScreenTopology::new(two_screens(), rect(1500, 100, 800, 600));
+    assert_eq!(topology.current_screen, Some(ScreenId(1)));
+}
+
+#[test]
+fn test_current_screen_falls_back_to_primary() {
+    let screens = vec![screen(1, 0, false), screen(2, 1920, true)];
+    let topology = ScreenTopology::new(screens, rect(-5000, -5000, 800, 600));
+    assert_eq!(topology.current_screen, Some(ScreenId(2)));
+
+    let topology = ScreenTopology::new(vec![], rect(0, 0, 800, 600));
+    assert_eq!(topology.current_screen, None);
+    assert!(topology.current().is_none());
+}
+
+#[test]
+fn test_no_changes() {
+    let topology = ScreenTopology::new(two_screens(), rect(100, 100, 800, 600));
+    assert_eq!(
+        topology.changes_since(&topology.clone()),
+        ScreenTopologyChanges::default()
+    );
+}
+
+#[test]
+fn test_window_moved_to_other_screen() {
+    let previous = ScreenTopology::new(two_screens(), rect(100, 100, 800, 600));
+    let topology = ScreenTopology::new(two_screens(), rect(2000, 100, 800, 600));
+    assert_eq!(
+        topology.changes_since(&previous),
+        ScreenTopologyChanges {
+            screens_changed: false,
+            current_screen_changed: true,
+        }
+    );
+}
+
+#[test]
+fn test_current_screen_attributes_changed() {
+    let previous = ScreenTopology::new(two_screens(), rect(100, 100, 800, 600));
+    let mut screens = two_screens();
+    screens[0].device_pixel_ratio = 2.0;
+    let topology = ScreenTopology::new(screens, rect(100, 100, 800, 600));
+    assert_eq!(
+        topology.changes_since(&previous),
+        ScreenTopologyChanges {
+            screens_changed: false,
+            current_screen_changed: true,
+        }
+    );
+
+    // Changes to other screens do not affect the current screen.
+    let mut screens = two_screens();
+    screens[1].device_pixel_ratio = 2.0;
+    let topology = ScreenTopology::new(screens, rect(100, 100, 800, 600));
+    assert_eq!(
+        topology.changes_since(&previous),
+        ScreenTopologyChanges::default()
+    );
+}
+
+#[test]
+fn test_screen_connected_and_disconnected() {
+    let one_screen = ScreenTopology::new(vec![screen(1, 0, true)], rect(100, 100, 800, 600));
+    let both_screens = ScreenTopology::new(two_screens(), rect(100, 100, 800, 600));
+    let connected = ScreenTopologyChanges {
+        screens_changed: true,
+        current_screen_changed: false,
+    };
+    assert_eq!(both_screens.changes_since(&one_screen), connected);
+    assert_eq!(one_screen.changes_since(&both_screens), connected);
+
+    // Swapping one screen for another is a change even though the count is the same.
+    let swapped = ScreenTopology::new(
+        vec![screen(1, 0, true), screen(3, 1920, false)],
+        rect(100, 100, 800, 600),
+    );
+    assert_eq!(swapped.changes_since(&both_screens), connected);
+}
+
+#[test]
+fn test_current_screen_disconnected() {
+    let previous = ScreenTopology::new(two_screens(), rect(2000, 100, 800, 600));
+    let topology = ScreenTopology::new(vec![screen(1, 0, true)], rect(2000, 100, 800, 600));
+    assert_eq!(
+        topology.changes_since(&previous),
+        ScreenTopologyChanges {
+            screens_changed: true,
+            current_screen_changed: true,
+        }
+    );
+}
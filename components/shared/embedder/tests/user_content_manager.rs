@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::user_content_manager::{
+    UserContentManager, UserScript, UserScriptInjectionTime, UserStyleSheet,
+};
+use servo_url::ServoUrl;
+
+fn matches(pattern: &str, url: &str) -> bool {
+    let mut manager = UserContentManager::new();
+    manager.add_stylesheet(UserStyleSheet {
+        source: String::new(),
+        url_patterns: vec![pattern.to_owned()],
+    });
+    let url = ServoUrl::parse(url).unwrap();
+    manager.stylesheets_for_url(&url).count() == 1
+}
+
+#[test]
+fn test_match_pattern_scheme() {
+    assert!(matches("https://example.com/*", "https://example.com/"));
+    assert!(!matches("https://example.com/*", "http://example.com/"));
+    assert!(matches("*://example.com/*", "http://example.com/"));
+    assert!(matches("*://example.com/*", "wss://example.com/"));
+    assert!(!matches("*://example.com/*", "ftp://example.com/"));
+    assert!(matches("file:///home/*", "file:///home/user/index.html"));
+    assert!(!matches("file:///home/*", "file:///etc/passwd"));
+}
+
+#[test]
+fn test_match_pattern_host() {
+    assert!(matches("https://*/*", "https://example.com/"));
+    assert!(matches("https://*.example.com/*", "https://example.com/"));
+    assert!(matches(
+        "https://*.example.com/*",
+        "https://a.b.example.com/"
+    ));
+    assert!(!matches(
+        "https://*.example.com/*",
+        "https://badexample.com/"
+    ));
+    assert!(!matches("https://example.com/*", "https://a.example.com/"));
+    assert!(matches("https://EXAMPLE.com/*", "https://example.com/"));
+    // Wildcards are only allowed at the start of the host.
+    assert!(!matches("https://ex*.com/*", "https://example.com/"));
+    assert!(!matches("https://example.*/*", "https://example.com/"));
+}
+
+#[test]
+fn test_match_pattern_does_not_match_across_components() {
+    assert!(!matches(
+        "https://*.example.com/*",
+        "https://evil.test/x.example.com/"
+    ));
+    assert!(!matches(
+        "https://example.com/*",
+        "https://example.com@evil.test/"
+    ));
+    assert!(!matches(
+        "*://example.com/*",
+        "https://example.com.evil.test/"
+    ));
+}
+
+#[test]
+fn test_match_pattern_path() {
+    assert!(matches("https://example.com/", "https://example.com"));
+    assert!(!matches("https://example.com/", "https://example.com/a"));
+    assert!(matches(
+        "https://example.com/a/*",
+        "https://example.com/a/b/c"
+    ));
+    assert!(!matches(
+        "https://example.com/a/*",
+        "https://example.com/b/a/"
+    ));
+    assert!(matches(
+        "https://example.com/*.html",
+        "https://example.com/a.html"
+    ));
+    assert!(matches(
+        "https://example.com/*?q=*",
+        "https://example.com/a?q=1"
+    ));
+    // The fragment is never matched.
+    assert!(matches("https://example.com/a", "https://example.com/a#b"));
+}
+
+#[test]
+fn test_match_pattern_special_and_invalid_patterns() {
+    assert!(matches("<all_urls>", "https://example.com/"));
+    assert!(matches("<all_urls>", "file:///index.html"));
+    assert!(!matches("https://example.com", "https://example.com/"));
+    assert!(!matches("example.com/*", "https://example.com/"));
+    assert!(!matches("*", "https://example.com/"));
+}
+
+#[test]
+fn test_scripts_for_url() {
+    let mut manager = UserContentManager::new();
+    manager.add_script("all();");
+    manager.add_script(UserScript {
+        script: "end();".to_owned(),
+        run_at: UserScriptInjectionTime::DocumentEnd,
+        url_patterns: vec!["https://*.example.com/*".to_owned()],
+        ..Default::default()
+    });
+
+    let scripts = |url: &str, run_at| {
+        manager
+            .scripts_for_url(&ServoUrl::parse(url).unwrap(), run_at)
+            .map(|script| script.script.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        scripts(
+            "https://example.com/",
+            UserScriptInjectionTime::DocumentStart
+        ),
+        ["all();"]
+    );
+    assert_eq!(
+        scripts("https://example.com/", UserScriptInjectionTime::DocumentEnd),
+        ["end();"]
+    );
+    assert!(scripts("https://example.org/", UserScriptInjectionTime::DocumentEnd).is_empty());
+}
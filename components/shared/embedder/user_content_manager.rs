@@ -7,10 +7,15 @@ use std::path::PathBuf;
 use malloc_size_of::MallocSizeOfOps;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+use url::Position;
 
+/// The scripts and stylesheets that the embedder injects into the documents that match
+/// their URL patterns, including the documents of iframes.
 #[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, Serialize)]
 pub struct UserContentManager {
     user_scripts: Vec<UserScript>,
+    user_stylesheets: Vec<UserStyleSheet>,
 }
 
 impl UserContentManager {
@@ -25,12 +30,59 @@ impl UserContentManager {
     pub fn scripts(&self) -> &[UserScript] {
         &self.user_scripts
     }
+
+    pub fn add_stylesheet(&mut self, stylesheet: impl Into<UserStyleSheet>) {
+        self.user_stylesheets.push(stylesheet.into());
+    }
+
+    pub fn stylesheets(&self) -> &[UserStyleSheet] {
+        &self.user_stylesheets
+    }
+
+    /// The user scripts that should run in a document at `url` at the given time, in the
+    /// order in which they were added.
+    pub fn scripts_for_url<'a>(
+        &'a self,
+        url: &'a ServoUrl,
+        run_at: UserScriptInjectionTime,
+    ) -> impl Iterator<Item = &'a UserScript> + 'a {
+        self.user_scripts.iter().filter(move |script| {
+            script.run_at == run_at && url_matches_patterns(url, &script.url_patterns)
+        })
+    }
+
+    /// The user stylesheets that apply to a document at `url`, in the order in which they
+    /// were added.
+    pub fn stylesheets_for_url<'a>(
+        &'a self,
+        url: &'a ServoUrl,
+    ) -> impl Iterator<Item = &'a UserStyleSheet> + 'a {
+        self.user_stylesheets
+            .iter()
+            .filter(move |stylesheet| url_matches_patterns(url, &stylesheet.url_patterns))
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// When a [`UserScript`] runs in the documents that it is injected into.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum UserScriptInjectionTime {
+    /// As soon as the `<head>` element of the document is created, before the scripts of
+    /// the document run.
+    #[default]
+    DocumentStart,
+    /// Once the document is parsed, right before `DOMContentLoaded` fires.
+    DocumentEnd,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct UserScript {
     pub script: String,
     pub source_file: Option<PathBuf>,
+    pub run_at: UserScriptInjectionTime,
+    /// The URLs of the documents that this script is injected into, as match patterns in
+    /// the format of WebExtensions, for instance `https://*.example.com/*` or
+    /// `<all_urls>`. When empty, the script is injected into all documents.
+    pub url_patterns: Vec<String>,
 }
 
 // Maybe we should implement `MallocSizeOf` for `PathBuf` in `malloc_size_of` crate?
@@ -41,6 +93,7 @@ impl malloc_size_of::MallocSizeOf for UserScript {
         if let Some(path) = &self.source_file {
             sum += unsafe { ops.malloc_size_of(path.as_path()) };
         }
+        sum += self.url_patterns.size_of(ops);
         sum
     }
 }
@@ -49,7 +102,110 @@ impl<T: Into<String>> From<T> for UserScript {
     fn from(script: T) -> Self {
         UserScript {
             script: script.into(),
-            source_file: None,
+            ..Default::default()
+        }
+    }
+}
+
+/// A stylesheet that is added to the user origin of the cascade of the documents that it
+/// is injected into.
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, Serialize)]
+pub struct UserStyleSheet {
+    pub source: String,
+    /// The URLs of the documents that this stylesheet is injected into, in the same format
+    /// as [`UserScript::url_patterns`].
+    pub url_patterns: Vec<String>,
+}
+
+impl<T: Into<String>> From<T> for UserStyleSheet {
+    fn from(source: T) -> Self {
+        UserStyleSheet {
+            source: source.into(),
+            url_patterns: Vec::new(),
+        }
+    }
+}
+
+fn url_matches_patterns(url: &ServoUrl, patterns: &[String]) -> bool {
+    patterns.is_empty() ||
+        patterns
+            .iter()
+            .any(|pattern| url_matches_pattern(url, pattern))
+}
+
+/// Whether `url` matches `pattern`, a match pattern in the format of WebExtensions:
+/// <https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/Match_patterns>.
+/// The scheme, host and path of the URL are matched separately, so that a wildcard can
+/// never match across them. Invalid patterns match nothing.
+fn url_matches_pattern(url: &ServoUrl, pattern: &str) -> bool {
+    if pattern == "<all_urls>" {
+        return true;
+    }
+    let Some((scheme, rest)) = pattern.split_once("://") else {
+        return false;
+    };
+    let Some(path_start) = rest.find('/') else {
+        return false;
+    };
+    let (host, path) = rest.split_at(path_start);
+
+    let scheme_matches = match scheme {
+        "*" => matches!(url.scheme(), "http" | "https" | "ws" | "wss"),
+        _ => scheme.eq_ignore_ascii_case(url.scheme()),
+    };
+    scheme_matches &&
+        host_matches(host, url.host_str().unwrap_or_default()) &&
+        wildcard_match(
+            path.as_bytes(),
+            url.as_url()[Position::BeforePath..Position::AfterQuery].as_bytes(),
+        )
+}
+
+/// Whether the host of a URL matches the host part of a match pattern, which is either
+/// `*`, a host, or `*.` followed by a domain, which matches the domain and its subdomains.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    let (domain, include_subdomains) = match pattern.strip_prefix("*.") {
+        Some(domain) => (domain, true),
+        None => (pattern, false),
+    };
+    if domain.contains('*') {
+        return false;
+    }
+    if domain.eq_ignore_ascii_case(host) {
+        return true;
+    }
+    include_subdomains &&
+        host.len() > domain.len() &&
+        host.as_bytes()[host.len() - domain.len() - 1] == b'.' &&
+        host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches any sequence of characters.
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut pattern_index, mut text_index) = (0, 0);
+    let mut backtrack = None;
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some(b'*') => {
+                pattern_index += 1;
+                backtrack = Some((pattern_index, text_index));
+            },
+            Some(byte) if *byte == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            },
+            _ => {
+                let Some((star_pattern_index, star_text_index)) = backtrack else {
+                    return false;
+                };
+                pattern_index = star_pattern_index;
+                text_index = star_text_index + 1;
+                backtrack = Some((star_pattern_index, text_index));
+            },
         }
     }
+    pattern[pattern_index..].iter().all(|byte| *byte == b'*')
 }
@@ -18,11 +18,11 @@ use pixels::Image;
 use serde::{Deserialize, Serialize};
 use servo_url::ServoUrl;
 use style_traits::CSSPixel;
-use webdriver::common::{WebElement, WebFrame, WebWindow};
+use webdriver::common::{ShadowRoot, WebElement, WebFrame, WebWindow};
 use webdriver::error::ErrorStatus;
 use webrender_api::units::DeviceIntSize;
 
-use crate::{MouseButton, MouseButtonAction};
+use crate::{AnimationClockRequest, MouseButton, MouseButtonAction};
 
 /// Messages to the constellation originating from the WebDriver server.
 #[derive(Debug, Deserialize, Serialize)]
@@ -44,6 +44,9 @@ pub enum WebDriverCommandMsg {
     MouseButtonAction(WebViewId, MouseButtonAction, MouseButton, f32, f32),
     /// Act as if the mouse was moved in the browsing context with the given ID.
     MouseMoveAction(WebViewId, f32, f32),
+    /// Act as if the mouse wheel was scrolled by the given deltas, at the given point of the
+    /// browsing context with the given ID.
+    WheelScrollAction(WebViewId, f32, f32, f64, f64),
     /// Set the window size.
     SetWindowSize(WebViewId, DeviceIntSize, IpcSender<Size2D<f32, CSSPixel>>),
     /// Take a screenshot of the window.
@@ -52,6 +55,13 @@ pub enum WebDriverCommandMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<Image>>,
     ),
+    /// Take a screenshot of the given rectangle of the page of a webview, relative to its
+    /// viewport, or of the whole page, including the parts of it that are scrolled out of view.
+    TakePageScreenshot(
+        WebViewId,
+        Option<Rect<f32, CSSPixel>>,
+        IpcSender<Option<Image>>,
+    ),
     /// Create a new webview that loads about:blank. The constellation will use
     /// the provided channels to return the top level browsing context id
     /// associated with the new webview, and a notification when the initial
@@ -65,6 +75,77 @@ pub enum WebDriverCommandMsg {
     CloseWebView(WebViewId),
     /// Focus the webview associated with the provided id.
     FocusWebView(WebViewId),
+    /// Start or stop telling the WebDriver BiDi server about the browsing contexts and their
+    /// navigations.
+    SetBiDiEventSender(Option<IpcSender<WebDriverBiDiEvent>>),
+    /// Start or stop letting the WebDriver BiDi server decide what to do with the requests
+    /// and responses of the network.
+    SetNetworkInterceptor(Option<IpcSender<WebDriverInterceptedRequest>>),
+}
+
+/// Events of the browser that the WebDriver BiDi server tells its clients about.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebDriverBiDiEvent {
+    /// A top-level browsing context was created.
+    ContextCreated(WebViewId),
+    /// A top-level browsing context was closed.
+    ContextDestroyed(WebViewId),
+    /// A top-level browsing context started navigating to the given URL.
+    NavigationStarted(WebViewId, ServoUrl),
+    /// The `DOMContentLoaded` event was fired at the document of a top-level browsing context.
+    DomContentLoaded(WebViewId, ServoUrl),
+    /// The `load` event was fired at the window of a top-level browsing context.
+    Load(WebViewId, ServoUrl),
+    /// A page sent a message through a channel that it was given by `script.callFunction`.
+    ChannelMessage(WebViewId, String, WebDriverJSValue),
+}
+
+/// The points of a fetch at which the WebDriver BiDi server can intercept it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum WebDriverInterceptPhase {
+    /// The request is about to be sent.
+    BeforeRequestSent,
+    /// The headers of the response were received.
+    ResponseStarted,
+}
+
+/// A request, or the start of its response, that waits for the WebDriver BiDi server to decide
+/// what to do with it.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WebDriverInterceptedRequest {
+    /// An id that is the same in both phases of the request.
+    pub request_id: String,
+    pub webview_id: Option<WebViewId>,
+    pub phase: WebDriverInterceptPhase,
+    pub url: ServoUrl,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    /// The status code and reason phrase of the response, in the `ResponseStarted` phase.
+    pub status: Option<(u16, String)>,
+    /// The headers of the response, in the `ResponseStarted` phase.
+    pub response_headers: Vec<(String, String)>,
+    pub reply: IpcSender<WebDriverInterceptDecision>,
+}
+
+/// What to do with an intercepted request or response.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebDriverInterceptDecision {
+    /// Carry on with the fetch. In the `BeforeRequestSent` phase, the method and headers of
+    /// the request may be replaced, and in the `ResponseStarted` phase, the status and
+    /// headers of the response.
+    Continue {
+        method: Option<String>,
+        status: Option<(u16, String)>,
+        headers: Option<Vec<(String, String)>>,
+    },
+    /// Fail the fetch with a network error.
+    Fail,
+    /// Use the given response instead of the one of the network.
+    Provide {
+        status: (u16, String),
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -80,6 +161,7 @@ pub enum WebDriverScriptCommand {
     DeleteCookies(IpcSender<Result<(), ErrorStatus>>),
     ExecuteScript(String, IpcSender<WebDriverJSResult>),
     ExecuteAsyncScript(String, IpcSender<WebDriverJSResult>),
+    ControlAnimationClock(AnimationClockRequest, IpcSender<Result<f64, ErrorStatus>>),
     FindElementCSS(String, IpcSender<Result<Option<String>, ErrorStatus>>),
     FindElementLinkText(String, bool, IpcSender<Result<Option<String>, ErrorStatus>>),
     FindElementTagName(String, IpcSender<Result<Option<String>, ErrorStatus>>),
@@ -110,6 +192,14 @@ pub enum WebDriverScriptCommand {
         IpcSender<Result<Vec<String>, ErrorStatus>>,
     ),
     FindElementElementsTagName(String, String, IpcSender<Result<Vec<String>, ErrorStatus>>),
+    FindShadowElementsCSS(String, String, IpcSender<Result<Vec<String>, ErrorStatus>>),
+    FindShadowElementsLinkText(
+        String,
+        String,
+        bool,
+        IpcSender<Result<Vec<String>, ErrorStatus>>,
+    ),
+    FindShadowElementsTagName(String, String, IpcSender<Result<Vec<String>, ErrorStatus>>),
     FocusElement(String, IpcSender<Result<(), ErrorStatus>>),
     ElementClick(String, IpcSender<Result<Option<String>, ErrorStatus>>),
     GetActiveElement(IpcSender<Option<String>>),
@@ -129,6 +219,7 @@ pub enum WebDriverScriptCommand {
     GetElementRect(String, IpcSender<Result<UntypedRect<f64>, ErrorStatus>>),
     GetElementTagName(String, IpcSender<Result<String, ErrorStatus>>),
     GetElementText(String, IpcSender<Result<String, ErrorStatus>>),
+    GetElementShadowRoot(String, IpcSender<Result<Option<String>, ErrorStatus>>),
     GetElementInViewCenterPoint(String, IpcSender<Result<Option<(i64, i64)>, ErrorStatus>>),
     GetBoundingClientRect(String, IpcSender<Result<UntypedRect<f32>, ErrorStatus>>),
     GetBrowsingContextId(
@@ -157,6 +248,7 @@ pub enum WebDriverJSValue {
     Number(f64),
     String(String),
     Element(WebElement),
+    ShadowRoot(ShadowRoot),
     Frame(WebFrame),
     Window(WebWindow),
     ArrayLike(Vec<WebDriverJSValue>),
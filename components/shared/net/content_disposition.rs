@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Parsing of the `Content-Disposition` response header.
+//! <https://www.rfc-editor.org/rfc/rfc6266>
+
+use http::HeaderMap;
+use http::header::CONTENT_DISPOSITION;
+use percent_encoding::percent_decode_str;
+
+/// Whether the `Content-Disposition` header of a response asks for it to be downloaded rather
+/// than displayed.
+pub fn is_attachment(headers: &HeaderMap) -> bool {
+    content_disposition(headers).is_some_and(|value| {
+        value
+            .split(';')
+            .next()
+            .is_some_and(|disposition| disposition.trim().eq_ignore_ascii_case("attachment"))
+    })
+}
+
+/// The file name suggested by the `Content-Disposition` header of a response, if any. The
+/// extended `filename*` parameter is preferred over `filename`. The name is returned as sent,
+/// and needs sanitizing before it is used as a path.
+pub fn file_name(headers: &HeaderMap) -> Option<String> {
+    let value = content_disposition(headers)?;
+    let parameters = parameters(value);
+
+    let extended = parameters
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("filename*"))
+        .find_map(|(_, value)| decode_extended_value(value));
+    if extended.is_some() {
+        return extended;
+    }
+
+    parameters
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("filename"))
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+fn content_disposition(headers: &HeaderMap) -> Option<&str> {
+    headers.get(CONTENT_DISPOSITION)?.to_str().ok()
+}
+
+/// Split the parameters following the disposition type into name and value pairs, unquoting
+/// quoted values.
+fn parameters(value: &str) -> Vec<(String, String)> {
+    let mut parameters = vec![];
+    let mut chars = value.chars().peekable();
+
+    // Skip the disposition type.
+    for c in chars.by_ref() {
+        if c == ';' {
+            break;
+        }
+    }
+
+    loop {
+        let mut name = String::new();
+        let mut has_value = false;
+        for c in chars.by_ref() {
+            if c == '=' {
+                has_value = true;
+                break;
+            }
+            if c == ';' {
+                break;
+            }
+            name.push(c);
+        }
+        let name = name.trim().to_owned();
+        if !has_value {
+            if chars.peek().is_none() {
+                return parameters;
+            }
+            continue;
+        }
+
+        while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+
+        let mut parameter_value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => parameter_value.extend(chars.next()),
+                    c => parameter_value.push(c),
+                }
+            }
+            for c in chars.by_ref() {
+                if c == ';' {
+                    break;
+                }
+            }
+        } else {
+            for c in chars.by_ref() {
+                if c == ';' {
+                    break;
+                }
+                parameter_value.push(c);
+            }
+        }
+
+        if !name.is_empty() {
+            parameters.push((name, parameter_value.trim().to_owned()));
+        }
+        if chars.peek().is_none() {
+            return parameters;
+        }
+    }
+}
+
+/// Decode an extended parameter value, such as `UTF-8''na%C3%AFve.txt`.
+/// <https://www.rfc-editor.org/rfc/rfc8187#section-3.2>
+fn decode_extended_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+
+    let bytes = percent_decode_str(encoded);
+    let decoded = if charset.eq_ignore_ascii_case("utf-8") {
+        bytes.decode_utf8().ok()?.into_owned()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        bytes.map(char::from).collect()
+    } else {
+        return None;
+    };
+    Some(decoded).filter(|decoded| !decoded.is_empty())
+}
@@ -131,6 +131,9 @@ pub struct SelectedFile {
     pub size: u64,
     // https://w3c.github.io/FileAPI/#dfn-type
     pub type_string: String,
+    /// The path of the file from the selected directory, starting with the name of that
+    /// directory, for files that were selected as part of a directory.
+    pub relative_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -153,6 +156,29 @@ pub enum FileManagerThreadMsg {
         Option<Vec<PathBuf>>,
     ),
 
+    /// Select a directory, which stands for all of the files in it and in its subdirectories.
+    /// Last field is pre-selected directory path for testing
+    SelectDirectory(
+        WebViewId,
+        IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        FileOrigin,
+        Option<PathBuf>,
+    ),
+
+    /// Allow the given webview to select the files that the user dropped onto it, once,
+    /// with `SelectDroppedFiles`. This is only sent by the constellation, as the drop
+    /// comes from the embedder.
+    AllowDroppedFiles(WebViewId, Vec<PathBuf>),
+
+    /// Select files that the user dropped onto the given webview. Only the files that
+    /// `AllowDroppedFiles` allowed are selected.
+    SelectDroppedFiles(
+        WebViewId,
+        Vec<PathBuf>,
+        IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        FileOrigin,
+    ),
+
     /// Read FileID-indexed file in chunks, optionally check URL validity based on boolean flag
     ReadFile(
         IpcSender<FileManagerResult<ReadFileProgress>>,
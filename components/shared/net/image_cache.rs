@@ -137,4 +137,14 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// Forget the image for the given URL, releasing its memory and its WebRender image, for
+    /// callers that will never request it again. A load that is still pending is forgotten
+    /// once it completes.
+    fn evict_image(
+        &self,
+        url: ServoUrl,
+        origin: ImmutableOrigin,
+        cors_setting: Option<CorsSettings>,
+    );
 }
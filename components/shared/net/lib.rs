@@ -10,9 +10,10 @@ use std::sync::{LazyLock, OnceLock};
 use std::thread;
 
 use base::cross_process_instant::CrossProcessInstant;
-use base::id::HistoryStateId;
+use base::id::{HistoryStateId, WebViewId};
 use cookie::Cookie;
 use crossbeam_channel::{Receiver, Sender, unbounded};
+use embedder_traits::{CookiePolicy, WebDriverInterceptedRequest};
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap, HeaderValue, StatusCode, header};
 use hyper_serde::Serde;
@@ -36,9 +37,11 @@ use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
 
 pub mod blob_url_store;
+pub mod content_disposition;
 pub mod filemanager_thread;
 pub mod http_status;
 pub mod image_cache;
+pub mod local_server;
 pub mod policy_container;
 pub mod pub_domains;
 pub mod quality;
@@ -410,6 +413,29 @@ impl ResourceThreads {
     pub fn clear_cache(&self) {
         let _ = self.core_thread.send(CoreResourceMsg::ClearCache);
     }
+
+    pub fn clear_storage_for_site(&self, url: ServoUrl) {
+        let _ = self.storage_thread.send(StorageThreadMsg::ClearSite(url));
+    }
+
+    pub fn set_online(&self, online: bool) {
+        let _ = self.core_thread.send(CoreResourceMsg::SetOnline(online));
+    }
+
+    pub fn set_webdriver_interceptor(
+        &self,
+        sender: Option<IpcSender<WebDriverInterceptedRequest>>,
+    ) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::SetWebDriverInterceptor(sender));
+    }
+
+    pub fn set_cookie_policy(&self, webview_id: WebViewId, policy: Option<CookiePolicy>) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::SetCookiePolicy(webview_id, policy));
+    }
 }
 
 impl IpcSend<CoreResourceMsg> for ResourceThreads {
@@ -455,12 +481,79 @@ pub enum WebSocketDomAction {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebSocketNetworkEvent {
-    ConnectionEstablished { protocol_in_use: Option<String> },
+    ConnectionEstablished {
+        protocol_in_use: Option<String>,
+    },
     MessageReceived(MessageData),
+    /// A message of the given number of bytes was sent over the network.
+    MessageSent(u64),
     Close(Option<u16>, String),
     Fail,
 }
 
+/// The id of a stream of a WebTransport session.
+pub type WebTransportStreamId = u64;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebTransportDomAction {
+    /// Open an outgoing stream. The network replies with a `StreamOpened` event with the same
+    /// `request_id`.
+    OpenStream {
+        request_id: u64,
+        bidirectional: bool,
+    },
+    SendData(WebTransportStreamId, Vec<u8>),
+    /// Finish the sending side of a stream, after the data that was sent on it.
+    Finish(WebTransportStreamId),
+    /// Abruptly terminate the sending side of a stream with an application error code.
+    Reset(WebTransportStreamId, u32),
+    /// Ask the peer to stop sending on the receiving side of a stream.
+    StopSending(WebTransportStreamId, u32),
+    SendDatagram(Vec<u8>),
+    Close {
+        code: u32,
+        reason: String,
+    },
+}
+
+/// The options of a WebTransport session.
+/// <https://w3c.github.io/webtransport/#dictdef-webtransportoptions>
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WebTransportSessionOptions {
+    /// Whether the session may share its connection with other sessions.
+    pub allow_pooling: bool,
+    /// Whether the session must fail if datagrams cannot be sent over its connection.
+    pub require_unreliable: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebTransportNetworkEvent {
+    SessionEstablished {
+        max_datagram_size: u32,
+    },
+    /// The outgoing stream requested with `request_id` was opened, or could not be.
+    StreamOpened {
+        request_id: u64,
+        stream_id: Option<WebTransportStreamId>,
+    },
+    IncomingStream {
+        stream_id: WebTransportStreamId,
+        bidirectional: bool,
+    },
+    StreamData(WebTransportStreamId, Vec<u8>),
+    StreamFinished(WebTransportStreamId),
+    /// The peer reset the receiving side of a stream with an application error code.
+    StreamReset(WebTransportStreamId, u32),
+    /// The peer asked to stop sending on the sending side of a stream.
+    StopSending(WebTransportStreamId, u32),
+    DatagramReceived(Vec<u8>),
+    SessionClosed {
+        code: u32,
+        reason: String,
+    },
+    SessionFailed,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 /// IPC channels to communicate with the script thread about network or DOM events.
 pub enum FetchChannels {
@@ -469,6 +562,11 @@ pub enum FetchChannels {
         event_sender: IpcSender<WebSocketNetworkEvent>,
         action_receiver: IpcReceiver<WebSocketDomAction>,
     },
+    WebTransport {
+        options: WebTransportSessionOptions,
+        event_sender: IpcSender<WebTransportNetworkEvent>,
+        action_receiver: IpcReceiver<WebTransportDomAction>,
+    },
     /// If the fetch is just being done to populate the cache,
     /// not because the data is needed now.
     Prefetch,
@@ -480,12 +578,25 @@ pub enum CoreResourceMsg {
     Cancel(Vec<RequestId>),
     /// Initiate a fetch in response to processing a redirection
     FetchRedirect(RequestBuilder, ResponseInit, IpcSender<FetchResponseMsg>),
+    /// Fetch a navigation response that is not displayed as a document again, and write it to
+    /// disk as a download
+    Download(RequestBuilder),
     /// Store a cookie for a given originating URL
     SetCookieForUrl(ServoUrl, Serde<Cookie<'static>>, CookieSource),
     /// Store a set of cookies for a given originating URL
-    SetCookiesForUrl(ServoUrl, Vec<Serde<Cookie<'static>>>, CookieSource),
+    SetCookiesForUrl(
+        ServoUrl,
+        Vec<Serde<Cookie<'static>>>,
+        CookieSource,
+        CookieContext,
+    ),
     /// Retrieve the stored cookies for a given URL
-    GetCookiesForUrl(ServoUrl, IpcSender<Option<String>>, CookieSource),
+    GetCookiesForUrl(
+        ServoUrl,
+        IpcSender<Option<String>>,
+        CookieSource,
+        CookieContext,
+    ),
     /// Get a cookie by name for a given originating URL
     GetCookiesDataForUrl(
         ServoUrl,
@@ -503,6 +614,15 @@ pub enum CoreResourceMsg {
     Synchronize(IpcSender<()>),
     /// Clear the network cache.
     ClearCache,
+    /// Set whether the network is reachable. Requests that need the network fail immediately
+    /// while it is not.
+    SetOnline(bool),
+    /// Send the HTTP requests, and then their responses, to WebDriver to be intercepted, or
+    /// stop doing so.
+    SetWebDriverInterceptor(Option<IpcSender<WebDriverInterceptedRequest>>),
+    /// Set the cookie policy of a webview, or go back to the default policy of the
+    /// `network_cookie_policy` preference.
+    SetCookiePolicy(WebViewId, Option<CookiePolicy>),
     /// Send the service worker network mediator for an origin to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>, ImmutableOrigin),
     /// Message forwarded to file manager's handler
@@ -894,6 +1014,44 @@ impl Metadata {
             .unwrap()
             .typed_insert::<ReferrerPolicyHeader>(referrer_policy.into());
     }
+
+    /// Whether a navigation response is handed to the download manager instead of being
+    /// displayed as a document, either because it asks to be downloaded or because there is no
+    /// way to display its content type.
+    /// <https://html.spec.whatwg.org/multipage/#process-a-navigate-response>
+    pub fn is_download(&self) -> bool {
+        if !self.status.is_success() {
+            return false;
+        }
+        if self
+            .headers
+            .as_ref()
+            .is_some_and(|headers| content_disposition::is_attachment(headers))
+        {
+            return true;
+        }
+
+        let Some(content_type) = self.content_type.clone() else {
+            return false;
+        };
+        let content_type: Mime = content_type.into_inner().into();
+        if is_text_document_mime_type(&content_type) {
+            return false;
+        }
+        !matches!(
+            (
+                content_type.type_(),
+                content_type.subtype(),
+                content_type.suffix()
+            ),
+            (mime::IMAGE, _, _) |
+                (mime::TEXT, mime::HTML, _) |
+                (mime::TEXT, mime::XML, _) |
+                (mime::APPLICATION, mime::XML, _) |
+                (mime::APPLICATION, mime::JSON, _) |
+                (_, _, Some(mime::XML))
+        )
+    }
 }
 
 /// The creator of a given cookie
@@ -905,6 +1063,26 @@ pub enum CookieSource {
     NonHTTP,
 }
 
+/// The environment that cookies are read or written in, which decides whether they are
+/// partitioned by top-level site or blocked as third-party cookies.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CookieContext {
+    /// The webview whose cookie policy applies, if any.
+    pub webview_id: Option<WebViewId>,
+    /// The origin of the top-level document, or `None` for top-level documents and for
+    /// environments outside of any document.
+    pub top_level_origin: Option<ImmutableOrigin>,
+}
+
+impl CookieContext {
+    pub fn for_request(request: &Request) -> Self {
+        Self {
+            webview_id: request.target_webview_id,
+            top_level_origin: request.top_level_origin.clone(),
+        }
+    }
+}
+
 /// Network errors that have to be exported out of the loaders
 #[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
 pub enum NetworkError {
@@ -931,6 +1109,26 @@ impl NetworkError {
     }
 }
 
+/// Whether a document of `content_type` is displayed as plain text, like text, style sheets
+/// and scripts. <https://html.spec.whatwg.org/multipage/#navigate-text>
+pub fn is_text_document_mime_type(content_type: &Mime) -> bool {
+    match (content_type.type_(), content_type.subtype()) {
+        (mime::TEXT, mime::PLAIN | mime::CSS | mime::JAVASCRIPT) => true,
+        (mime::TEXT, subtype) => {
+            matches!(
+                subtype.as_str(),
+                "vtt" | "ecmascript" | "jscript" | "livescript" | "x-ecmascript" | "x-javascript"
+            ) || subtype.as_str().starts_with("javascript1.")
+        },
+        (mime::APPLICATION, mime::JAVASCRIPT) => true,
+        (mime::APPLICATION, subtype) => matches!(
+            subtype.as_str(),
+            "ecmascript" | "x-ecmascript" | "x-javascript"
+        ),
+        _ => false,
+    }
+}
+
 /// Normalize `slice`, as defined by
 /// [the Fetch Spec](https://fetch.spec.whatwg.org/#concept-header-value-normalize).
 pub fn trim_http_whitespace(mut slice: &[u8]) -> &[u8] {
@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Checks that keep web pages away from the servers that listen on the local machine for
+//! automation tools, like the DevTools and WebDriver BiDi servers.
+
+use std::fmt;
+use std::net::IpAddr;
+
+/// Why a request to a local automation server was rejected.
+#[derive(Debug, PartialEq)]
+pub enum RejectedRequest {
+    /// The `Host` header is missing or names a domain, which a web page can make point to
+    /// the local machine.
+    Host,
+    /// The WebSocket handshake has an `Origin` header, which web pages send, unlike the
+    /// automation tools.
+    Origin(String),
+}
+
+impl fmt::Display for RejectedRequest {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RejectedRequest::Host => write!(
+                formatter,
+                "Host header is specified and is not an IP address or localhost."
+            ),
+            RejectedRequest::Origin(origin) => write!(
+                formatter,
+                "Rejected an incoming WebSocket connection from the {origin} origin."
+            ),
+        }
+    }
+}
+
+/// Check the `Host` header of a request to a local automation server, and the `Origin`
+/// header of a WebSocket handshake, which is `None` for other requests.
+pub fn check_request(host: Option<&str>, origin: Option<&str>) -> Result<(), RejectedRequest> {
+    if !host.is_some_and(is_allowed_host) {
+        return Err(RejectedRequest::Host);
+    }
+    if let Some(origin) = origin {
+        return Err(RejectedRequest::Origin(origin.to_owned()));
+    }
+    Ok(())
+}
+
+/// Whether a `Host` header names the local machine or an IP address, which unlike a domain name
+/// cannot be made to point to this server by a web page.
+pub fn is_allowed_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.parse::<u16>().is_ok() => name,
+        _ => host,
+    };
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok()
+}
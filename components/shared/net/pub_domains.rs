@@ -146,3 +146,15 @@ pub fn reg_host(url: &ServoUrl) -> Option<Host> {
         ImmutableOrigin::Opaque(_) => None,
     }
 }
+
+/// The serialized [site](https://html.spec.whatwg.org/multipage/#obtain-a-site) of an origin,
+/// or `None` for an opaque origin.
+pub fn site(origin: &ImmutableOrigin) -> Option<String> {
+    match origin {
+        ImmutableOrigin::Opaque(_) => None,
+        ImmutableOrigin::Tuple(scheme, Host::Domain(domain), _) => {
+            Some(format!("{scheme}://{}", reg_suffix(domain).to_lowercase()))
+        },
+        ImmutableOrigin::Tuple(scheme, host, _) => Some(format!("{scheme}://{host}")),
+    }
+}
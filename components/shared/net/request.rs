@@ -300,6 +300,8 @@ pub struct RequestBuilder {
     pub referrer_policy: ReferrerPolicy,
     pub pipeline_id: Option<PipelineId>,
     pub target_webview_id: Option<WebViewId>,
+    /// <https://html.spec.whatwg.org/multipage/#concept-environment-top-level-origin>
+    pub top_level_origin: Option<ImmutableOrigin>,
 
     /// <https://fetch.spec.whatwg.org/#concept-request-redirect-mode>
     pub redirect_mode: RedirectMode,
@@ -322,6 +324,9 @@ pub struct RequestBuilder {
     pub response_tainting: ResponseTainting,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
+    /// Servo internal: whether a navigation response that isn't displayed as a document is
+    /// handed to the download manager.
+    pub allows_downloads: bool,
 }
 
 impl RequestBuilder {
@@ -348,6 +353,7 @@ impl RequestBuilder {
             referrer_policy: ReferrerPolicy::EmptyString,
             pipeline_id: None,
             target_webview_id: webview_id,
+            top_level_origin: None,
             redirect_mode: RedirectMode::Follow,
             integrity_metadata: "".to_owned(),
             cryptographic_nonce_metadata: "".to_owned(),
@@ -357,6 +363,7 @@ impl RequestBuilder {
             https_state: HttpsState::None,
             response_tainting: ResponseTainting::Basic,
             crash: None,
+            allows_downloads: false,
         }
     }
 
@@ -479,12 +486,25 @@ impl RequestBuilder {
         self
     }
 
+    pub fn allows_downloads(mut self, allows_downloads: bool) -> Self {
+        self.allows_downloads = allows_downloads;
+        self
+    }
+
     /// <https://fetch.spec.whatwg.org/#concept-request-policy-container>
     pub fn policy_container(mut self, policy_container: PolicyContainer) -> RequestBuilder {
         self.policy_container = RequestPolicyContainer::PolicyContainer(policy_container);
         self
     }
 
+    /// The origin of the top-level document of the environment that makes this request, which
+    /// partitions and blocks third-party cookies. `None` when the request is made by a top-level
+    /// document or outside of any document.
+    pub fn top_level_origin(mut self, top_level_origin: Option<ImmutableOrigin>) -> RequestBuilder {
+        self.top_level_origin = top_level_origin;
+        self
+    }
+
     pub fn insecure_requests_policy(
         mut self,
         insecure_requests_policy: InsecureRequestsPolicy,
@@ -546,6 +566,7 @@ impl RequestBuilder {
         request.crash = self.crash;
         request.policy_container = self.policy_container;
         request.insecure_requests_policy = self.insecure_requests_policy;
+        request.top_level_origin = self.top_level_origin;
         request
     }
 }
@@ -621,6 +642,8 @@ pub struct Request {
     pub policy_container: RequestPolicyContainer,
     /// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy>
     pub insecure_requests_policy: InsecureRequestsPolicy,
+    /// <https://html.spec.whatwg.org/multipage/#concept-environment-top-level-origin>
+    pub top_level_origin: Option<ImmutableOrigin>,
     pub https_state: HttpsState,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
@@ -668,6 +691,7 @@ impl Request {
             response_tainting: ResponseTainting::Basic,
             policy_container: RequestPolicyContainer::Client,
             insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
+            top_level_origin: None,
             https_state,
             crash: None,
         }
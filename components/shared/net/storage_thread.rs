@@ -5,7 +5,9 @@
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+use crate::pub_domains::site;
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum StorageType {
@@ -13,35 +15,74 @@ pub enum StorageType {
     Local,
 }
 
-/// Request operations on the storage data associated with a particular url
+/// The key that storage is partitioned by, which is the site of the top-level document together
+/// with the origin that accesses the storage. This keeps a site embedded in different top-level
+/// sites from sharing storage across them.
+/// <https://privacycg.github.io/storage-partitioning/>
+#[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct StorageKey {
+    /// The serialized site of the top-level document.
+    pub top_level_site: String,
+    /// The serialized origin that accesses the storage.
+    pub origin: String,
+}
+
+impl StorageKey {
+    pub fn new(top_level_origin: &ImmutableOrigin, origin: &ImmutableOrigin) -> StorageKey {
+        StorageKey {
+            top_level_site: site(top_level_origin).unwrap_or_else(|| "null".to_owned()),
+            origin: origin.ascii_serialization(),
+        }
+    }
+
+    /// The key of a top-level document with the given origin.
+    pub fn first_party(origin: &ImmutableOrigin) -> StorageKey {
+        StorageKey::new(origin, origin)
+    }
+
+    /// Whether the storage of this key belongs to `site`, either because it is the site of the
+    /// top-level document or because it is the site of the origin accessing the storage.
+    pub fn belongs_to_site(&self, site_to_match: &str) -> bool {
+        self.top_level_site == site_to_match ||
+            ServoUrl::parse(&self.origin)
+                .ok()
+                .and_then(|url| site(&url.origin()))
+                .is_some_and(|origin_site| origin_site == site_to_match)
+    }
+}
+
+/// Request operations on the storage data associated with a particular storage key
 #[derive(Debug, Deserialize, Serialize)]
 pub enum StorageThreadMsg {
     /// gets the number of key/value pairs present in the associated storage data
-    Length(IpcSender<usize>, ServoUrl, StorageType),
+    Length(IpcSender<usize>, StorageKey, StorageType),
 
     /// gets the name of the key at the specified index in the associated storage data
-    Key(IpcSender<Option<String>>, ServoUrl, StorageType, u32),
+    Key(IpcSender<Option<String>>, StorageKey, StorageType, u32),
 
     /// Gets the available keys in the associated storage data
-    Keys(IpcSender<Vec<String>>, ServoUrl, StorageType),
+    Keys(IpcSender<Vec<String>>, StorageKey, StorageType),
 
     /// gets the value associated with the given key in the associated storage data
-    GetItem(IpcSender<Option<String>>, ServoUrl, StorageType, String),
+    GetItem(IpcSender<Option<String>>, StorageKey, StorageType, String),
 
     /// sets the value of the given key in the associated storage data
     SetItem(
         IpcSender<Result<(bool, Option<String>), ()>>,
-        ServoUrl,
+        StorageKey,
         StorageType,
         String,
         String,
     ),
 
     /// removes the key/value pair for the given key in the associated storage data
-    RemoveItem(IpcSender<Option<String>>, ServoUrl, StorageType, String),
+    RemoveItem(IpcSender<Option<String>>, StorageKey, StorageType, String),
 
     /// clears the associated storage data by removing all the key/value pairs
-    Clear(IpcSender<bool>, ServoUrl, StorageType),
+    Clear(IpcSender<bool>, StorageKey, StorageType),
+
+    /// clears the storage data of every storage key that belongs to the site of the given url
+    ClearSite(ServoUrl),
 
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
@@ -0,0 +1,57 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::header::{CONTENT_DISPOSITION, HeaderMap, HeaderValue};
+use net_traits::content_disposition::{file_name, is_attachment};
+
+fn headers(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_DISPOSITION, HeaderValue::from_static(value));
+    headers
+}
+
+#[test]
+fn test_is_attachment() {
+    assert!(is_attachment(&headers("attachment")));
+    assert!(is_attachment(&headers("Attachment; filename=a.txt")));
+    assert!(!is_attachment(&headers("inline")));
+    assert!(!is_attachment(&headers("inline; filename=attachment")));
+    assert!(!is_attachment(&HeaderMap::new()));
+}
+
+#[test]
+fn test_file_name() {
+    assert_eq!(
+        file_name(&headers("attachment; filename=report.pdf")),
+        Some("report.pdf".to_owned())
+    );
+    assert_eq!(
+        file_name(&headers(
+            r#"attachment; filename="my \"report\"; final.pdf""#
+        )),
+        Some(r#"my "report"; final.pdf"#.to_owned())
+    );
+    assert_eq!(
+        file_name(&headers(
+            "attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve.txt"
+        )),
+        Some("naïve.txt".to_owned())
+    );
+    assert_eq!(
+        file_name(&headers(
+            "attachment; filename*=unknown''x.txt; filename=y.txt"
+        )),
+        Some("y.txt".to_owned())
+    );
+    assert_eq!(file_name(&headers("attachment")), None);
+    assert_eq!(file_name(&headers("attachment; filename=\"\"")), None);
+}
+
+#[test]
+fn test_file_name_skips_parameters_without_values() {
+    assert_eq!(
+        file_name(&headers("attachment; creation; filename=a.txt")),
+        Some("a.txt".to_owned())
+    );
+}
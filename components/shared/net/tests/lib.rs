@@ -3,7 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use base::cross_process_instant::CrossProcessInstant;
-use net_traits::{ResourceAttribute, ResourceFetchTiming, ResourceTimeValue, ResourceTimingType};
+use http::header::{CONTENT_DISPOSITION, HeaderValue};
+use net_traits::http_status::HttpStatus;
+use net_traits::{
+    Metadata, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue, ResourceTimingType,
+};
+use servo_url::ServoUrl;
 
 #[test]
 fn test_set_start_time_to_fetch_start_if_nonzero_tao() {
@@ -232,3 +237,39 @@ fn test_reset_start_time() {
         "failed to reset `start_time`"
     );
 }
+
+#[test]
+fn test_metadata_is_download() {
+    let metadata = |content_type: &str| {
+        let mut metadata = Metadata::default(ServoUrl::parse("https://example.com/").unwrap());
+        metadata.set_content_type(Some(&content_type.parse().unwrap()));
+        metadata
+    };
+    for content_type in [
+        "text/html",
+        "text/plain; charset=utf-8",
+        "text/css",
+        "text/javascript",
+        "application/javascript",
+        "image/png",
+        "application/json",
+        "image/svg+xml",
+    ] {
+        assert!(!metadata(content_type).is_download(), "{content_type}");
+    }
+    assert!(metadata("application/pdf").is_download());
+    assert!(metadata("application/octet-stream").is_download());
+    assert!(metadata("video/mp4").is_download());
+
+    let mut attachment = metadata("text/html");
+    attachment.headers.as_mut().unwrap().insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=page.html"),
+    );
+    assert!(attachment.is_download());
+
+    // Error pages are displayed, whatever their content type is.
+    let mut not_found = metadata("application/octet-stream");
+    not_found.status = HttpStatus::new_raw(404, b"Not Found".to_vec());
+    assert!(!not_found.is_download());
+}
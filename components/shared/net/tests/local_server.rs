@@ -0,0 +1,31 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net_traits::local_server::{RejectedRequest, check_request, is_allowed_host};
+
+#[test]
+fn test_is_allowed_host() {
+    assert!(is_allowed_host("localhost"));
+    assert!(is_allowed_host("LOCALHOST:9222"));
+    assert!(is_allowed_host("127.0.0.1:9222"));
+    assert!(is_allowed_host("[::1]:9222"));
+    assert!(is_allowed_host("::1"));
+    assert!(!is_allowed_host("example.com"));
+    assert!(!is_allowed_host("localhost.example.com:9222"));
+    assert!(!is_allowed_host("localhost:port"));
+}
+
+#[test]
+fn test_check_request() {
+    assert_eq!(check_request(Some("127.0.0.1:9222"), None), Ok(()));
+    assert_eq!(check_request(None, None), Err(RejectedRequest::Host));
+    assert_eq!(
+        check_request(Some("attacker.example:9222"), None),
+        Err(RejectedRequest::Host)
+    );
+    assert_eq!(
+        check_request(Some("localhost:9222"), Some("https://example.com")),
+        Err(RejectedRequest::Origin("https://example.com".to_owned()))
+    );
+}
@@ -8,6 +8,7 @@
 
 use std::marker::Send;
 
+use base::id::PipelineId;
 use crossbeam_channel::Sender;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
@@ -138,6 +139,9 @@ pub struct Report {
 
     /// The size, in bytes.
     pub size: usize,
+
+    /// The web content on whose behalf the memory is used, if any.
+    pub attribution: Option<ReportAttribution>,
 }
 
 /// A channel through which memory reports can be sent.
@@ -190,6 +194,52 @@ macro_rules! path {
     }}
 }
 
+/// The first segment of the paths of reports about memory used on behalf of web pages. These
+/// reports also have a [`ReportAttribution`].
+pub const PAGES_PATH_SEGMENT: &str = "pages";
+
+/// The web content on whose behalf the memory of a [`Report`] is used.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ReportAttribution {
+    /// The document of a pipeline.
+    Pipeline {
+        /// The serialization of the origin of the document.
+        origin: String,
+        /// The pipeline of the document.
+        pipeline_id: PipelineId,
+        /// The URL that the document was loaded from.
+        url: String,
+    },
+    /// The documents and workers of the given origins together, but not a single one of
+    /// them. This is the case of the JavaScript heap of an event loop, which can run the
+    /// scripts of several origins of the same site.
+    Origins(Vec<String>),
+}
+
+impl ReportAttribution {
+    /// The path of the reports with this attribution, which is used to show them.
+    pub fn path(&self) -> Vec<String> {
+        match self {
+            ReportAttribution::Pipeline {
+                origin,
+                pipeline_id,
+                url,
+            } => path![
+                PAGES_PATH_SEGMENT,
+                format!("origin({origin})"),
+                format!("pipeline({pipeline_id}, {url})")
+            ],
+            ReportAttribution::Origins(origins) => match origins.as_slice() {
+                [origin] => path![PAGES_PATH_SEGMENT, format!("origin({origin})")],
+                _ => path![
+                    PAGES_PATH_SEGMENT,
+                    format!("origins({})", origins.join(", "))
+                ],
+            },
+        }
+    }
+}
+
 /// The results produced by the memory reporter.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MemoryReportResult {
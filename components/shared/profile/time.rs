@@ -2,6 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::OnceCell;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use base::cross_process_instant::CrossProcessInstant;
 use ipc_channel::ipc::IpcSender;
 use log::warn;
@@ -41,6 +45,7 @@ pub enum ProfilerMsg {
     Time(
         (ProfilerCategory, Option<TimerMetadata>),
         (CrossProcessInstant, CrossProcessInstant),
+        ProfilerThread,
     ),
     /// Message used to get time spend entries for a particular ProfilerBuckets (in nanoseconds)
     Get(
@@ -53,10 +58,44 @@ pub enum ProfilerMsg {
     /// Report a layout query that could not be processed immediately for a particular URL.
     BlockedLayoutQuery(String),
 
+    /// Start recording every event into a Chrome trace file at the given path, replacing the
+    /// recording in progress, if any.
+    StartChromeTrace(PathBuf),
+
+    /// Stop recording the Chrome trace and finish writing its file, then acknowledge.
+    StopChromeTrace(IpcSender<()>),
+
     /// Tells the profiler to shut down.
     Exit(IpcSender<()>),
 }
 
+/// The thread that an event was measured on, which lets traces show the events of each
+/// process and thread on their own track.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProfilerThread {
+    pub process_id: u32,
+    /// An identifier for the thread, unique within its process.
+    pub thread_id: u64,
+    pub name: Option<String>,
+}
+
+impl ProfilerThread {
+    pub fn current() -> Self {
+        static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+        thread_local! {
+            static THREAD_ID: OnceCell<u64> = const { OnceCell::new() };
+        }
+
+        ProfilerThread {
+            process_id: std::process::id(),
+            thread_id: THREAD_ID.with(|thread_id| {
+                *thread_id.get_or_init(|| NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed))
+            }),
+            name: std::thread::current().name().map(str::to_owned),
+        }
+    }
+}
+
 /// Usage sites of variants marked “Rust tracing only” are not visible to rust-analyzer.
 #[repr(u32)]
 #[derive(
@@ -71,15 +110,23 @@ pub enum ProfilerCategory {
     /// The script thread is doing layout work.
     LayoutPerform = 0x10,
 
-    /// Events currently only used by Layout 2013.
+    /// Layout is restyling the damaged part of the DOM.
     LayoutStyleRecalc = 0x11,
+
+    /// Events currently only used by Layout 2013.
     LayoutTextShaping = 0x12,
     LayoutRestyleDamagePropagation = 0x13,
     LayoutGeneratedContent = 0x18,
     LayoutFloatPlacementSpeculation = 0x1a,
+
+    /// Layout is building the box tree and laying it out into a fragment tree.
     LayoutMain = 0x1b,
+
+    /// Events currently only used by Layout 2013.
     LayoutStoreOverflow = 0x1c,
     LayoutParallelWarmup = 0x1d,
+
+    /// Layout is building the display list of the fragment tree.
     LayoutDispListBuild = 0x1e,
 
     ImageSaving = 0x51,
@@ -125,6 +172,10 @@ pub enum ProfilerCategory {
     ScriptPortMessage = 0x7e,
     ScriptWebGPUMsg = 0x7f,
 
+    /// From the hit test of an input event in the compositor until script has dispatched the
+    /// DOM events for it, which includes the time that the event waited to be processed.
+    ScriptInputEventDispatch = 0x80,
+
     /// Web performance metrics.
     TimeToFirstPaint = 0x90,
     TimeToFirstContentfulPaint = 0x91,
@@ -180,6 +231,7 @@ impl ProfilerCategory {
             ProfilerCategory::ScriptHistoryEvent => "ScriptHistoryEvent",
             ProfilerCategory::ScriptPortMessage => "ScriptPortMessage",
             ProfilerCategory::ScriptWebGPUMsg => "ScriptWebGPUMsg",
+            ProfilerCategory::ScriptInputEventDispatch => "ScriptInputEventDispatch",
             ProfilerCategory::TimeToFirstPaint => "TimeToFirstPaint",
             ProfilerCategory::TimeToFirstContentfulPaint => "TimeToFirstContentfulPaint",
             ProfilerCategory::TimeToInteractive => "TimeToInteractive",
@@ -187,6 +239,61 @@ impl ProfilerCategory {
             ProfilerCategory::IpcBytesReceiver => "IpcBytesReceiver",
         }
     }
+
+    /// The broader kind of work that this category measures, used as the category of trace
+    /// events so that they can be filtered together.
+    pub const fn trace_category(&self) -> &'static str {
+        match self {
+            ProfilerCategory::Compositing => "compositing",
+            ProfilerCategory::LayoutStyleRecalc |
+            ProfilerCategory::LayoutRestyleDamagePropagation => "style",
+            ProfilerCategory::LayoutDispListBuild => "display-list",
+            ProfilerCategory::LayoutPerform |
+            ProfilerCategory::LayoutTextShaping |
+            ProfilerCategory::LayoutGeneratedContent |
+            ProfilerCategory::LayoutFloatPlacementSpeculation |
+            ProfilerCategory::LayoutMain |
+            ProfilerCategory::LayoutStoreOverflow |
+            ProfilerCategory::LayoutParallelWarmup => "layout",
+            ProfilerCategory::ImageSaving => "image",
+            ProfilerCategory::ScriptAttachLayout |
+            ProfilerCategory::ScriptConstellationMsg |
+            ProfilerCategory::ScriptDevtoolsMsg |
+            ProfilerCategory::ScriptDocumentEvent |
+            ProfilerCategory::ScriptEvaluate |
+            ProfilerCategory::ScriptEvent |
+            ProfilerCategory::ScriptFileRead |
+            ProfilerCategory::ScriptFontLoading |
+            ProfilerCategory::ScriptImageCacheMsg |
+            ProfilerCategory::ScriptInputEvent |
+            ProfilerCategory::ScriptNetworkEvent |
+            ProfilerCategory::ScriptParseHTML |
+            ProfilerCategory::ScriptPlannedNavigation |
+            ProfilerCategory::ScriptRendering |
+            ProfilerCategory::ScriptResize |
+            ProfilerCategory::ScriptSetScrollState |
+            ProfilerCategory::ScriptSetViewport |
+            ProfilerCategory::ScriptTimerEvent |
+            ProfilerCategory::ScriptStylesheetLoad |
+            ProfilerCategory::ScriptUpdateReplacedElement |
+            ProfilerCategory::ScriptWebSocketEvent |
+            ProfilerCategory::ScriptWorkerEvent |
+            ProfilerCategory::ScriptServiceWorkerEvent |
+            ProfilerCategory::ScriptParseXML |
+            ProfilerCategory::ScriptEnterFullscreen |
+            ProfilerCategory::ScriptExitFullscreen |
+            ProfilerCategory::ScriptWorkletEvent |
+            ProfilerCategory::ScriptPerformanceEvent |
+            ProfilerCategory::ScriptHistoryEvent |
+            ProfilerCategory::ScriptPortMessage |
+            ProfilerCategory::ScriptWebGPUMsg |
+            ProfilerCategory::ScriptInputEventDispatch => "script",
+            ProfilerCategory::TimeToFirstPaint |
+            ProfilerCategory::TimeToFirstContentfulPaint |
+            ProfilerCategory::TimeToInteractive => "paint-metrics",
+            ProfilerCategory::IpcReceiver | ProfilerCategory::IpcBytesReceiver => "ipc",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize)]
@@ -243,5 +350,9 @@ pub fn send_profile_data(
     start_time: CrossProcessInstant,
     end_time: CrossProcessInstant,
 ) {
-    profiler_chan.send(ProfilerMsg::Time((category, meta), (start_time, end_time)));
+    profiler_chan.send(ProfilerMsg::Time(
+        (category, meta),
+        (start_time, end_time),
+        ProfilerThread::current(),
+    ));
 }
@@ -24,17 +24,22 @@ use base::id::{
     BlobId, BrowsingContextId, DomPointId, HistoryStateId, MessagePortId, PipelineId,
     PipelineNamespaceId, WebViewId,
 };
+use bitflags::bitflags;
 #[cfg(feature = "bluetooth")]
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use constellation_traits::{
-    AnimationTickType, CompositorHitTestResult, ScrollState, WindowSizeData, WindowSizeType,
+    AnimationTickType, CompositorHitTestResult, ScrollState, VisualViewportState, WindowSizeData,
+    WindowSizeType,
 };
 use crossbeam_channel::{RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
 use embedder_traits::input_events::InputEvent;
 use embedder_traits::user_content_manager::UserContentManager;
-use embedder_traits::{MediaSessionActionType, Theme, WebDriverScriptCommand};
+use embedder_traits::{
+    AnimationClockRequest, AutofillRequest, AuxiliaryWebViewFeatures, EmulatedMediaFeatures,
+    FindInPageRequest, MediaSessionActionType, NetworkStatus, Theme, WebDriverScriptCommand,
+};
 use euclid::{Rect, Scale, Size2D, UnknownUnit};
 use http::{HeaderMap, Method};
 use ipc_channel::Error as IpcError;
@@ -50,6 +55,7 @@ use net_traits::{ReferrerPolicy, ResourceThreads};
 use pixels::PixelFormat;
 use profile_traits::{mem, time as profile_time};
 use serde::{Deserialize, Serialize};
+use servo_config::prefs::{Preferences, SitePreferences};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use strum::{EnumIter, IntoEnumIterator};
 use strum_macros::IntoStaticStr;
@@ -80,6 +86,29 @@ pub enum LoadOrigin {
     Script(ImmutableOrigin),
 }
 
+/// <https://html.spec.whatwg.org/multipage/#scroll-restoration-mode>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum ScrollRestorationMode {
+    /// The user agent restores the scroll position of the entry when traversing to it.
+    #[default]
+    Auto,
+    /// The page takes care of restoring the scroll position of the entry.
+    Manual,
+}
+
+/// The user state of a [session history entry] that is persisted while another entry is the
+/// current one, so that it can be restored when the entry becomes current again, even if its
+/// document was discarded in the meantime.
+///
+/// [session history entry]: https://html.spec.whatwg.org/multipage/#session-history-entry
+#[derive(Clone, Copy, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct SessionHistoryEntryState {
+    /// <https://html.spec.whatwg.org/multipage/#she-scroll-restoration-mode>
+    pub scroll_restoration_mode: ScrollRestorationMode,
+    /// <https://html.spec.whatwg.org/multipage/#she-scroll-position>
+    pub scroll_position: Option<(i32, i32)>,
+}
+
 /// can be passed to `LoadUrl` to load a page with GET/POST
 /// parameters or headers
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -117,6 +146,21 @@ pub struct LoadData {
     pub inherited_secure_context: Option<bool>,
     /// The inherited policy for upgrading insecure requests; None if not inherited.
     pub inherited_insecure_requests_policy: Option<InsecureRequestsPolicy>,
+    /// The origin of the top-level document, which is set by the constellation for nested
+    /// documents; None for top-level documents.
+    pub top_level_origin: Option<ImmutableOrigin>,
+    /// The sandboxing flags of the document that this load creates, which come from the iframe
+    /// or opener of its browsing context.
+    pub sandboxing_flag_set: SandboxingFlagSet,
+    /// The persisted state of the session history entry that this load recreates, when it
+    /// reloads a document that was discarded from the session history.
+    pub session_history_entry_state: SessionHistoryEntryState,
+    /// Whether the document that this load creates is hosted by a `<portal>` element, which
+    /// gives it a `PortalHost` to exchange messages with its host until it is activated.
+    pub is_portal: bool,
+    /// Whether the document that this load creates is prerendered, or hosted by a `<portal>`
+    /// element, and hidden from the user until it is activated.
+    pub is_prerendering: bool,
 
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
@@ -157,6 +201,11 @@ impl LoadData {
             inherited_secure_context,
             crash: None,
             inherited_insecure_requests_policy,
+            top_level_origin: None,
+            sandboxing_flag_set: SandboxingFlagSet::empty(),
+            session_history_entry_state: SessionHistoryEntryState::default(),
+            is_portal: false,
+            is_prerendering: false,
         }
     }
 }
@@ -256,6 +305,18 @@ pub enum ScriptThreadMessage {
     Resize(PipelineId, WindowSizeData, WindowSizeType),
     /// Theme changed.
     ThemeChange(PipelineId, Theme),
+    /// The set of connected screens, or the screen that the window occupies, changed.
+    ScreensChanged(PipelineId),
+    /// The connectivity or quality of the network changed.
+    NetworkStatusChanged(PipelineId, NetworkStatus),
+    /// Search the text of the document of a pipeline.
+    FindInPage(PipelineId, FindInPageRequest),
+    /// Find or fill in the form controls of the document of a pipeline that can be filled in
+    /// automatically.
+    Autofill(PipelineId, AutofillRequest),
+    /// Pause, resume, step or set the clock of the animations and timers of the document of
+    /// a pipeline.
+    ControlAnimationClock(PipelineId, AnimationClockRequest),
     /// Notifies script that window has been resized but to not take immediate action.
     ResizeInactive(PipelineId, WindowSizeData),
     /// Window switched from fullscreen mode.
@@ -278,6 +339,30 @@ pub enum ScriptThreadMessage {
     SetThrottled(PipelineId, bool),
     /// Notify the containing iframe (in PipelineId) that the nested browsing context (BrowsingContextId) is throttled.
     SetThrottledInContainingIframe(PipelineId, BrowsingContextId, bool),
+    /// Notifies the script thread that a prerendered document (PipelineId) is being activated in
+    /// the given browsing context, so that it takes over that browsing context's `WindowProxy`
+    /// instead of the hidden one it was loaded in. The data is given to the `portalactivate`
+    /// event when the document was hosted by a `<portal>` element.
+    ActivatePrerenderedDocument(
+        PipelineId,
+        BrowsingContextId,
+        Option<StructuredSerializedData>,
+    ),
+    /// Notifies the script thread that the given webview became visible or hidden to the user,
+    /// which applies to all of its documents, including those that are created later.
+    SetWebViewVisibility(WebViewId, bool),
+    /// Notifies the script thread that the embedder muted or unmuted the audio of all documents
+    /// in the given webview.
+    SetWebViewMuted(WebViewId, bool),
+    /// Notifies the script thread that the embedder changed the media features that are
+    /// emulated for all documents in the given webview.
+    SetEmulatedMediaFeatures(WebViewId, EmulatedMediaFeatures),
+    /// Notifies the script thread that the embedder replaced the user scripts and stylesheets
+    /// that are injected into new documents.
+    SetUserContentManager(UserContentManager),
+    /// Notifies the script thread that the preferences, or the values that they take for
+    /// particular sites, changed at runtime.
+    PreferencesUpdated(Box<Preferences>, SitePreferences),
     /// Notifies script thread that a url should be loaded in this iframe.
     /// PipelineId is for the parent, BrowsingContextId is for the nested browsing context
     NavigateIframe(
@@ -302,6 +387,20 @@ pub enum ScriptThreadMessage {
         /// The data to be posted.
         data: StructuredSerializedData,
     },
+    /// Post a message between the document hosted by a `<portal>` element and its host. When
+    /// the target is the portal's document, the message goes to its `PortalHost`, and otherwise
+    /// it goes to the `<portal>` element of the target that hosts the portal's document.
+    PostPortalMessage {
+        /// The pipeline that receives the message.
+        target: PipelineId,
+        /// The pipeline of the portal's document.
+        portal: PipelineId,
+        /// The origin of the document that sent the message.
+        /// <https://html.spec.whatwg.org/multipage/#dom-messageevent-origin>
+        source_origin: ImmutableOrigin,
+        /// The data to be posted.
+        data: StructuredSerializedData,
+    },
     /// Updates the current pipeline ID of a given iframe.
     /// First PipelineId is for the parent, second is the new PipelineId for the frame.
     UpdatePipelineId(
@@ -311,8 +410,14 @@ pub enum ScriptThreadMessage {
         PipelineId,
         UpdatePipelineIdReason,
     ),
-    /// Updates the history state and url of a given pipeline.
-    UpdateHistoryState(PipelineId, Option<HistoryStateId>, ServoUrl),
+    /// Updates the history state and url of a given pipeline, along with the persisted state of
+    /// the session history entry that becomes current.
+    UpdateHistoryState(
+        PipelineId,
+        Option<HistoryStateId>,
+        ServoUrl,
+        SessionHistoryEntryState,
+    ),
     /// Removes inaccesible history states.
     RemoveHistoryStates(PipelineId, Vec<HistoryStateId>),
     /// Set an iframe to be focused. Used when an element in an iframe gains focus.
@@ -363,6 +468,9 @@ pub enum ScriptThreadMessage {
     /// The compositor scrolled and is updating the scroll states of the nodes in the given
     /// pipeline via the Constellation.
     SetScrollStates(PipelineId, Vec<ScrollState>),
+    /// The compositor pinch-zoomed or panned the visual viewport of the top-level document in
+    /// the given pipeline.
+    SetVisualViewport(PipelineId, VisualViewportState),
 }
 
 impl fmt::Debug for ScriptThreadMessage {
@@ -466,6 +574,8 @@ pub struct InitialScriptState {
     pub player_context: WindowGLContext,
     /// User content manager
     pub user_content_manager: UserContentManager,
+    /// The connectivity and quality of the network when the script thread starts.
+    pub network_status: NetworkStatus,
 }
 
 /// This trait allows creating a `ServiceWorkerManager` without depending on the `script`
@@ -475,6 +585,101 @@ pub trait ServiceWorkerManagerFactory {
     fn create(sw_senders: SWManagerSenders, origin: ImmutableOrigin);
 }
 
+/// The restrictions placed on a document by the `sandbox` attribute of its iframe, or inherited
+/// from the documents that contain or opened it.
+/// <https://html.spec.whatwg.org/multipage/#sandboxing-flag-set>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub struct SandboxingFlagSet(u32);
+
+bitflags! {
+    impl SandboxingFlagSet: u32 {
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-navigation-browsing-context-flag>
+        const NAVIGATION = 1 << 0;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-auxiliary-navigation-browsing-context-flag>
+        const AUXILIARY_NAVIGATION = 1 << 1;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-top-level-navigation-without-user-activation-browsing-context-flag>
+        const TOP_LEVEL_NAVIGATION_WITHOUT_USER_ACTIVATION = 1 << 2;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-top-level-navigation-with-user-activation-browsing-context-flag>
+        const TOP_LEVEL_NAVIGATION_WITH_USER_ACTIVATION = 1 << 3;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-plugins-browsing-context-flag>
+        const PLUGINS = 1 << 4;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-origin-browsing-context-flag>
+        const ORIGIN = 1 << 5;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-forms-browsing-context-flag>
+        const FORMS = 1 << 6;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-pointer-lock-browsing-context-flag>
+        const POINTER_LOCK = 1 << 7;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-scripts-browsing-context-flag>
+        const SCRIPTS = 1 << 8;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-automatic-features-browsing-context-flag>
+        const AUTOMATIC_FEATURES = 1 << 9;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-document.domain-browsing-context-flag>
+        const DOCUMENT_DOMAIN = 1 << 10;
+        /// <https://html.spec.whatwg.org/multipage/#sandbox-propagates-to-auxiliary-browsing-contexts-flag>
+        const PROPAGATES_TO_AUXILIARY_BROWSING_CONTEXTS = 1 << 11;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-modals-flag>
+        const MODALS = 1 << 12;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-orientation-lock-browsing-context-flag>
+        const ORIENTATION_LOCK = 1 << 13;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-presentation-browsing-context-flag>
+        const PRESENTATION = 1 << 14;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-downloads-browsing-context-flag>
+        const DOWNLOADS = 1 << 15;
+        /// <https://html.spec.whatwg.org/multipage/#sandboxed-custom-protocols-navigation-browsing-context-flag>
+        const CUSTOM_PROTOCOLS_NAVIGATION = 1 << 16;
+    }
+}
+
+impl SandboxingFlagSet {
+    /// Parse the tokens of a sandboxing directive, such as the value of the `sandbox` attribute
+    /// of an iframe, into the flags it sets.
+    /// <https://html.spec.whatwg.org/multipage/#parse-a-sandboxing-directive>
+    pub fn parse_sandboxing_directive<'a>(tokens: impl Iterator<Item = &'a str>) -> Self {
+        let mut allowed = SandboxingFlagSet::empty();
+        let mut allow_top_navigation = false;
+        let mut allow_top_navigation_by_user_activation = false;
+        for token in tokens {
+            match &*token.to_ascii_lowercase() {
+                "allow-popups" => allowed |= SandboxingFlagSet::AUXILIARY_NAVIGATION,
+                "allow-top-navigation" => allow_top_navigation = true,
+                "allow-top-navigation-by-user-activation" => {
+                    allow_top_navigation_by_user_activation = true
+                },
+                "allow-same-origin" => allowed |= SandboxingFlagSet::ORIGIN,
+                "allow-forms" => allowed |= SandboxingFlagSet::FORMS,
+                "allow-pointer-lock" => allowed |= SandboxingFlagSet::POINTER_LOCK,
+                "allow-scripts" => {
+                    allowed |= SandboxingFlagSet::SCRIPTS | SandboxingFlagSet::AUTOMATIC_FEATURES
+                },
+                "allow-popups-to-escape-sandbox" => {
+                    allowed |= SandboxingFlagSet::PROPAGATES_TO_AUXILIARY_BROWSING_CONTEXTS
+                },
+                "allow-modals" => allowed |= SandboxingFlagSet::MODALS,
+                "allow-orientation-lock" => allowed |= SandboxingFlagSet::ORIENTATION_LOCK,
+                "allow-presentation" => allowed |= SandboxingFlagSet::PRESENTATION,
+                "allow-downloads" => allowed |= SandboxingFlagSet::DOWNLOADS,
+                "allow-top-navigation-to-custom-protocols" => {
+                    allowed |= SandboxingFlagSet::CUSTOM_PROTOCOLS_NAVIGATION
+                },
+                _ => {},
+            }
+        }
+
+        // `allow-top-navigation` implies `allow-top-navigation-by-user-activation`, and also
+        // allows navigating to custom protocols.
+        if allow_top_navigation {
+            allowed |= SandboxingFlagSet::TOP_LEVEL_NAVIGATION_WITHOUT_USER_ACTIVATION |
+                SandboxingFlagSet::TOP_LEVEL_NAVIGATION_WITH_USER_ACTIVATION |
+                SandboxingFlagSet::CUSTOM_PROTOCOLS_NAVIGATION;
+        } else if allow_top_navigation_by_user_activation {
+            allowed |= SandboxingFlagSet::TOP_LEVEL_NAVIGATION_WITH_USER_ACTIVATION |
+                SandboxingFlagSet::CUSTOM_PROTOCOLS_NAVIGATION;
+        }
+
+        SandboxingFlagSet::all().difference(allowed)
+    }
+}
+
 /// Whether the sandbox attribute is present for an iframe element
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum IFrameSandboxState {
@@ -493,6 +698,10 @@ pub struct AuxiliaryWebViewCreationRequest {
     pub opener_webview_id: WebViewId,
     /// The pipeline opener browsing context.
     pub opener_pipeline_id: PipelineId,
+    /// The features requested for the new webview, passed on to the embedder.
+    pub features: AuxiliaryWebViewFeatures,
+    /// Whether the new browsing context should be created without an opener.
+    pub noopener: bool,
     /// Sender for the constellation’s response to our request.
     pub response_sender: IpcSender<Option<AuxiliaryWebViewCreationResponse>>,
 }
@@ -568,6 +777,8 @@ pub struct WorkerGlobalScopeInit {
     pub user_agent: Cow<'static, str>,
     /// True if secure context
     pub inherited_secure_context: Option<bool>,
+    /// The origin of the top-level document of the worker's owner
+    pub top_level_origin: ImmutableOrigin,
 }
 
 /// Common entities representing a network load origin
@@ -13,7 +13,9 @@ use base::id::{
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use constellation_traits::{LogEntry, TraversalDirection};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent, TouchEventType, TouchSequenceId};
+use embedder_traits::{
+    EmbedderMsg, MediaSessionEvent, TouchEventType, TouchSequenceId, WebDriverJSValue,
+};
 use euclid::Size2D;
 use euclid::default::Size2D as UntypedSize2D;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
@@ -31,7 +33,8 @@ use crate::mem::MemoryReportResult;
 use crate::{
     AnimationState, AuxiliaryWebViewCreationRequest, BroadcastMsg, DocumentState,
     IFrameLoadInfoWithData, LoadData, MessagePortMsg, NavigationHistoryBehavior, PortMessageTask,
-    StructuredSerializedData, WindowSizeType, WorkerGlobalScopeInit, WorkerScriptLoadOrigin,
+    SessionHistoryEntryState, StructuredSerializedData, WindowSizeType, WorkerGlobalScopeInit,
+    WorkerScriptLoadOrigin,
 };
 
 /// An iframe sizing operation.
@@ -135,11 +138,31 @@ pub enum ScriptMsg {
     /// All pending loads are complete, and the `load` event for this pipeline
     /// has been dispatched.
     LoadComplete,
+    /// The `DOMContentLoaded` event for this pipeline has been dispatched.
+    DOMContentLoaded,
+    /// A page sent a message through a channel that WebDriver BiDi gave it as an argument of a
+    /// function.
+    WebDriverChannelMessage(String, WebDriverJSValue),
     /// A new load has been requested, with an option to replace the current entry once loaded
     /// instead of adding a new entry.
     LoadUrl(LoadData, NavigationHistoryBehavior),
     /// Abort loading after sending a LoadUrl message.
     AbortLoadUrl,
+    /// Start prerendering a same-origin document that this top-level document may navigate to.
+    Prerender(LoadData),
+    /// Start loading the document of a `<portal>` element of this top-level document in a new
+    /// pipeline with the given id.
+    CreatePortal(PipelineId, LoadData),
+    /// Make the document of a `<portal>` element the active document of this top-level
+    /// browsing context, giving it the data for its `portalactivate` event. The sender is told
+    /// whether the activation started.
+    ActivatePortal(PipelineId, StructuredSerializedData, IpcSender<bool>),
+    /// Close the document of a `<portal>` element that was removed or changed.
+    DiscardPortal(PipelineId),
+    /// Post a message from this document to the document of one of its `<portal>` elements.
+    PostMessageToPortal(PipelineId, ImmutableOrigin, StructuredSerializedData),
+    /// Post a message from the document of a `<portal>` element to the document that hosts it.
+    PostMessageToPortalHost(ImmutableOrigin, StructuredSerializedData),
     /// Post a message to the currently active window of a given browsing context.
     PostMessage {
         /// The target of the posted message.
@@ -162,6 +185,9 @@ pub enum ScriptMsg {
     PushHistoryState(HistoryStateId, ServoUrl),
     /// Inform the constellation of a replaced history state.
     ReplaceHistoryState(HistoryStateId, ServoUrl),
+    /// Store the persisted state of the given session history entry of this pipeline, which
+    /// is restored when the entry becomes current again.
+    SetSessionHistoryEntryState(Option<HistoryStateId>, SessionHistoryEntryState),
     /// Gets the length of the joint session history from the constellation.
     JointSessionHistoryLength(IpcSender<u32>),
     /// Notification that this iframe should be removed.
@@ -175,6 +201,9 @@ pub enum ScriptMsg {
     ScriptNewIFrame(IFrameLoadInfoWithData),
     /// Script has opened a new auxiliary browsing context.
     CreateAuxiliaryWebView(AuxiliaryWebViewCreationRequest),
+    /// Script has set `window.opener` to null, so documents that are later loaded in this
+    /// browsing context must not get an opener either.
+    DisownOpener,
     /// Mark a new document as active
     ActivateDocument,
     /// Set the document state for a pipeline (used by screenshot / reftests)
@@ -185,8 +214,8 @@ pub enum ScriptMsg {
     SetFinalUrl(ServoUrl),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(TouchEventResult),
-    /// A log entry, with the top-level browsing context id and thread name
-    LogEntry(Option<String>, LogEntry),
+    /// A log entry, with the pipeline id and thread name
+    LogEntry(Option<PipelineId>, Option<String>, LogEntry),
     /// Discard the document.
     DiscardDocument,
     /// Discard the browsing context.
@@ -217,6 +246,9 @@ pub enum ScriptMsg {
     IFrameSizes(Vec<IFrameSizeMsg>),
     /// Request results from the memory reporter.
     ReportMemory(IpcSender<MemoryReportResult>),
+    /// Change the value of a preference from `about:config`. The value is parsed according
+    /// to the type of the preference.
+    SetPreference(String, String),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -4,10 +4,11 @@
 
 use base::id::ScrollTreeNodeId;
 use euclid::Size2D;
-use webrender_api::units::LayoutVector2D;
-use webrender_api::{ExternalScrollId, PipelineId, ScrollLocation, SpatialId};
+use webrender_api::units::{LayoutTransform, LayoutVector2D};
+use webrender_api::{ExternalScrollId, PipelineId, PropertyBindingKey, ScrollLocation, SpatialId};
 use webrender_traits::display_list::{
-    AxesScrollSensitivity, ScrollSensitivity, ScrollTree, ScrollableNodeInfo,
+    AxesScrollSensitivity, ScrollSensitivity, ScrollTree, ScrollableNodeInfo, ScrollbarAxis,
+    ScrollbarThumb,
 };
 
 fn add_mock_scroll_node(tree: &mut ScrollTree) -> ScrollTreeNodeId {
@@ -33,6 +34,7 @@ fn add_mock_scroll_node(tree: &mut ScrollTree) -> ScrollTreeNodeId {
                 y: ScrollSensitivity::ScriptAndInputEvents,
             },
             offset: LayoutVector2D::zero(),
+            scrollbar_thumbs: Vec::new(),
         }),
     )
 }
@@ -186,3 +188,49 @@ fn test_scroll_tree_chain_through_overflow_hidden() {
         Some(LayoutVector2D::new(0.0, 0.0))
     );
 }
+
+#[test]
+fn test_scroll_tree_scrollbar_thumb_transforms() {
+    let mut scroll_tree = ScrollTree::default();
+    let pipeline_id = PipelineId(0, 0);
+    let id = add_mock_scroll_node(&mut scroll_tree);
+    let thumb = |key, axis| ScrollbarThumb {
+        transform_key: PropertyBindingKey::new(key),
+        axis,
+        travel: 50.0,
+    };
+    scroll_tree
+        .get_node_mut(&id)
+        .scroll_info
+        .as_mut()
+        .unwrap()
+        .scrollbar_thumbs = vec![
+        thumb(1, ScrollbarAxis::Vertical),
+        thumb(2, ScrollbarAxis::Horizontal),
+    ];
+
+    // The thumbs move as far along their tracks as the node is scrolled along its range.
+    scroll_tree.scroll_node_or_ancestor(
+        &id,
+        ScrollLocation::Delta(LayoutVector2D::new(-100.0, -20.0)),
+    );
+    let transforms = scroll_tree.scrollbar_thumb_transforms(ExternalScrollId(0, pipeline_id));
+    assert_eq!(transforms.len(), 2);
+    assert_eq!(transforms[0].key, PropertyBindingKey::new(1));
+    assert_eq!(
+        transforms[0].value,
+        LayoutTransform::translation(0.0, 10.0, 0.0)
+    );
+    assert_eq!(transforms[1].key, PropertyBindingKey::new(2));
+    assert_eq!(
+        transforms[1].value,
+        LayoutTransform::translation(50.0, 0.0, 0.0)
+    );
+
+    // Nodes that are not in the tree have no thumbs.
+    assert!(
+        scroll_tree
+            .scrollbar_thumb_transforms(ExternalScrollId(1, pipeline_id))
+            .is_empty()
+    );
+}
@@ -12,6 +12,7 @@ pub mod wrapper_traits;
 
 use std::any::Any;
 use std::borrow::Cow;
+use std::ops::Range;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicIsize, AtomicU64, Ordering};
 
@@ -20,8 +21,9 @@ use atomic_refcell::AtomicRefCell;
 use base::Epoch;
 use base::id::{BrowsingContextId, PipelineId, WebViewId};
 use constellation_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
+use devtools_traits::HighlighterKind;
 use euclid::Size2D;
-use euclid::default::{Point2D, Rect};
+use euclid::default::{Point2D, Rect, Size2D as UntypedSize2D};
 use fnv::FnvHashMap;
 use fonts::{FontContext, SystemFontServiceProxy};
 use fxhash::FxHashMap;
@@ -42,15 +44,16 @@ use style::context::QuirksMode;
 use style::data::ElementData;
 use style::dom::OpaqueNode;
 use style::invalidation::element::restyle_hints::RestyleHint;
-use style::media_queries::Device;
+use style::media_queries::{Device, MediaType};
 use style::properties::PropertyId;
 use style::properties::style_structs::Font;
 use style::queries::values::PrefersColorScheme;
 use style::selector_parser::{PseudoElement, RestyleDamage, Snapshot};
+use style::shared_lock::SharedRwLock;
 use style::stylesheets::Stylesheet;
 use style_traits::CSSPixel;
 use webrender_api::ImageKey;
-use webrender_traits::CrossProcessCompositorApi;
+use webrender_traits::{CrossProcessCompositorApi, ScrollAnimation};
 
 pub type GenericLayoutData = dyn Any + Send + Sync;
 
@@ -130,6 +133,9 @@ pub struct HTMLCanvasData {
 }
 
 pub struct SVGSVGData {
+    /// A `data:` URL containing the serialized subtree of the `<svg>` element, if it
+    /// has been serialized.
+    pub source: Option<ServoUrl>,
     pub width: u32,
     pub height: u32,
 }
@@ -187,6 +193,9 @@ pub struct LayoutConfig {
     pub time_profiler_chan: time::ProfilerChan,
     pub compositor_api: CrossProcessCompositorApi,
     pub window_size: WindowSizeData,
+    /// The source of the user stylesheets that apply to the document, which are added to the
+    /// user origin of the cascade.
+    pub user_stylesheets: Vec<String>,
 }
 
 pub trait LayoutFactory: Send + Sync {
@@ -217,9 +226,9 @@ pub trait Layout {
     /// Inform the layout that its ScriptThread is about to exit.
     fn exit_now(&mut self);
 
-    /// Requests that layout measure its memory usage. The resulting reports are sent back
-    /// via the supplied channel.
-    fn collect_reports(&self, reports: &mut Vec<Report>);
+    /// Requests that layout measure its memory usage. The resulting reports are added to
+    /// `reports`, under the given path.
+    fn collect_reports(&self, path: &[String], reports: &mut Vec<Report>);
 
     /// Sets quirks mode for the document, causing the quirks mode stylesheet to be used.
     fn set_quirks_mode(&mut self, quirks_mode: QuirksMode);
@@ -230,6 +239,18 @@ pub trait Layout {
     /// Requests a reflow.
     fn reflow(&mut self, reflow_request: ReflowRequest) -> Option<ReflowResult>;
 
+    /// Update the `Device` that media queries are evaluated against with the given viewport,
+    /// color scheme and media type, and whether forced colors mode is active, without a
+    /// reflow. The styles that depend on them are updated by the next reflow.
+    fn update_media_environment(
+        &mut self,
+        document_shared_lock: &SharedRwLock,
+        window_size: WindowSizeData,
+        theme: PrefersColorScheme,
+        forced_colors: bool,
+        media_type: MediaType,
+    );
+
     /// Tells layout that script has added some paint worklet modules.
     fn register_paint_worklet_modules(
         &mut self,
@@ -244,6 +265,31 @@ pub trait Layout {
     fn query_content_box(&self, node: OpaqueNode) -> Option<Rect<Au>>;
     fn query_content_boxes(&self, node: OpaqueNode) -> Vec<Rect<Au>>;
     fn query_client_rect(&self, node: OpaqueNode) -> Rect<i32>;
+    /// The area of the IME composition string of the text control `node`, or of the position
+    /// of the composition if its string is empty, or `None` if it has no active composition.
+    fn query_composition_rect(&self, node: OpaqueNode) -> Option<Rect<Au>>;
+    /// The area of a caret at the given byte offset of the text of the text node `node`, or
+    /// `None` if that text is not rendered.
+    fn query_caret_rect(&self, node: OpaqueNode, offset: usize) -> Option<Rect<Au>>;
+    /// The position in the rendered text of the line above or below a caret at the given
+    /// byte offset of the text of the text node `node` that is closest to the caret, as a text
+    /// node and a byte offset in its text.
+    fn query_caret_in_adjacent_line(
+        &self,
+        node: OpaqueNode,
+        offset: usize,
+        direction: CaretLineDirection,
+    ) -> Option<(UntrustedNodeAddress, usize)>;
+    /// The position in the rendered text that is closest to the given point, relative to the
+    /// initial containing block, as a text node and a byte offset in its text.
+    fn query_caret_position_from_point(
+        &self,
+        point: Point2D<f32>,
+    ) -> Option<(UntrustedNodeAddress, usize)>;
+    /// The client rects of a range, for the nodes that it selects, in the order of `nodes`
+    /// and relative to the initial containing block. They include the transforms of the boxes
+    /// and of their ancestors.
+    fn query_range_client_rects(&self, nodes: &[RangeNode]) -> Vec<Rect<Au>>;
     fn query_element_inner_outer_text(&self, node: TrustedNodeAddress) -> String;
     fn query_nodes_from_point(
         &self,
@@ -267,6 +313,11 @@ pub trait Layout {
         animation_timeline_value: f64,
     ) -> Option<ServoArc<Font>>;
     fn query_scrolling_area(&self, node: Option<OpaqueNode>) -> Rect<i32>;
+    /// The geometry needed to scroll the box of `node` into view, or `None` if it has no box.
+    fn query_scroll_into_view(&self, node: OpaqueNode) -> Option<ScrollIntoViewGeometry>;
+    /// The geometry needed to scroll the selection of the focused text control `node` into
+    /// view, or its caret if nothing is selected, or `None` if neither is rendered.
+    fn query_text_control_selection(&self, node: OpaqueNode) -> Option<ScrollIntoViewGeometry>;
     fn query_text_indext(&self, node: OpaqueNode, point: Point2D<f32>) -> Option<usize>;
 }
 
@@ -299,8 +350,11 @@ pub enum NodesFromPointQueryType {
 pub enum QueryMsg {
     ContentBox,
     ContentBoxes,
+    CaretQuery,
     ClientRectQuery,
+    CompositionRectQuery,
     ScrollingAreaQuery,
+    ScrollIntoViewQuery,
     OffsetParentQuery,
     TextIndexQuery,
     NodesFromPointQuery,
@@ -327,8 +381,9 @@ pub enum ReflowGoal {
     LayoutQuery(QueryMsg),
 
     /// Tells layout about a single new scrolling offset from the script. The rest will
-    /// remain untouched and layout won't forward this back to script.
-    UpdateScrollNode(ScrollState),
+    /// remain untouched and layout won't forward this back to script. The compositor
+    /// scrolls to the new offset with the given [`ScrollAnimation`].
+    UpdateScrollNode(ScrollState, ScrollAnimation),
 }
 
 impl ReflowGoal {
@@ -336,19 +391,22 @@ impl ReflowGoal {
     /// be present or false if it only needs stacking-relative positions.
     pub fn needs_display_list(&self) -> bool {
         match *self {
-            ReflowGoal::UpdateTheRendering | ReflowGoal::UpdateScrollNode(_) => true,
+            ReflowGoal::UpdateTheRendering | ReflowGoal::UpdateScrollNode(..) => true,
             ReflowGoal::LayoutQuery(ref querymsg) => match *querymsg {
                 QueryMsg::ElementInnerOuterTextQuery |
                 QueryMsg::InnerWindowDimensionsQuery |
                 QueryMsg::NodesFromPointQuery |
                 QueryMsg::ResolvedStyleQuery |
                 QueryMsg::TextIndexQuery => true,
+                QueryMsg::CaretQuery |
                 QueryMsg::ClientRectQuery |
+                QueryMsg::CompositionRectQuery |
                 QueryMsg::ContentBox |
                 QueryMsg::ContentBoxes |
                 QueryMsg::OffsetParentQuery |
                 QueryMsg::ResolvedFontStyleQuery |
                 QueryMsg::ScrollingAreaQuery |
+                QueryMsg::ScrollIntoViewQuery |
                 QueryMsg::StyleQuery => false,
             },
         }
@@ -358,15 +416,18 @@ impl ReflowGoal {
     /// false if a layout_thread display list is sufficient.
     pub fn needs_display(&self) -> bool {
         match *self {
-            ReflowGoal::UpdateTheRendering | ReflowGoal::UpdateScrollNode(_) => true,
+            ReflowGoal::UpdateTheRendering | ReflowGoal::UpdateScrollNode(..) => true,
             ReflowGoal::LayoutQuery(ref querymsg) => match *querymsg {
                 QueryMsg::NodesFromPointQuery |
                 QueryMsg::TextIndexQuery |
                 QueryMsg::ElementInnerOuterTextQuery => true,
                 QueryMsg::ContentBox |
                 QueryMsg::ContentBoxes |
+                QueryMsg::CaretQuery |
                 QueryMsg::ClientRectQuery |
+                QueryMsg::CompositionRectQuery |
                 QueryMsg::ScrollingAreaQuery |
+                QueryMsg::ScrollIntoViewQuery |
                 QueryMsg::ResolvedStyleQuery |
                 QueryMsg::ResolvedFontStyleQuery |
                 QueryMsg::OffsetParentQuery |
@@ -404,6 +465,115 @@ pub struct ReflowResult {
     pub iframe_sizes: IFrameSizes,
     /// The mapping of node to animated image, need to be returned to ImageAnimationManager
     pub node_to_image_animation_map: FxHashMap<OpaqueNode, ImageAnimationState>,
+    /// The changes to the accessibility tree of the document since the last update, if
+    /// accessibility is enabled and anything changed.
+    pub accessibility_tree_update: Option<accesskit::TreeUpdate>,
+    /// The matches of the find-in-page query of the [`ReflowRequest`], if there was one.
+    pub find_in_page_matches: Option<FindInPageMatches>,
+    /// The scroll offsets that scroll anchoring adjusted to keep the visible content of
+    /// scrollers in place, which layout already sent to the compositor.
+    pub scroll_anchor_adjustments: Vec<ScrollState>,
+    /// The widths of the content boxes of the rendered images among the
+    /// [`ReflowRequest::auto_sized_images`].
+    pub auto_sized_image_widths: Vec<(OpaqueNode, Au)>,
+    /// The sizes of the content boxes of the rendered `<svg>` elements among the
+    /// [`ReflowRequest::svg_roots`].
+    pub svg_root_sizes: Vec<(OpaqueNode, UntypedSize2D<Au>)>,
+}
+
+/// A find-in-page search of the rendered text of a document. Layout searches for matches
+/// after every reflow and highlights them in the display list.
+#[derive(Clone, Debug, MallocSizeOf, PartialEq)]
+pub struct FindInPageQuery {
+    /// The text to search for.
+    pub text: String,
+    /// Whether matches must have the same case as [`Self::text`].
+    pub case_sensitive: bool,
+    /// The index of the match to highlight as the active match. This wraps around when it
+    /// is larger than the number of matches.
+    pub active_match_index: usize,
+}
+
+/// The matches of a [`FindInPageQuery`] found by layout.
+#[derive(Debug, Default)]
+pub struct FindInPageMatches {
+    /// The number of matches in the document.
+    pub number_of_matches: usize,
+    /// The index of the active match, after wrapping around, if there are any matches.
+    pub active_match_index: Option<usize>,
+    /// The bounding box of the active match, relative to the initial containing block.
+    pub active_match_rect: Option<Rect<Au>>,
+}
+
+/// The caret and the selection of a document in caret browsing mode, as positions in the
+/// text of the text nodes that they are in.
+#[derive(Clone, Debug, Default)]
+pub struct CaretBrowsingSelection {
+    /// The text node that contains the caret, and the byte offset of the caret in its text.
+    pub caret: Option<(OpaqueNode, usize)>,
+    /// The byte ranges of the selected text in the text of each text node that has any.
+    pub selected_text: FxHashMap<OpaqueNode, Range<usize>>,
+}
+
+/// A node that the devtools inspector highlights, and the overlays that are drawn over it.
+#[derive(Clone, Copy, Debug)]
+pub struct InspectorHighlight {
+    pub node: OpaqueNode,
+    pub kind: HighlighterKind,
+}
+
+/// An element in the top layer of a document, which is painted above the rest of the document
+/// in the order of the top layer, over a `::backdrop` that covers the viewport.
+///
+/// <https://drafts.csswg.org/css-position-4/#top-layer>
+#[derive(Clone, Copy, Debug)]
+pub struct TopLayerElement {
+    pub node: OpaqueNode,
+    /// Whether the element is fullscreen rather than a modal dialog, which decides the color of
+    /// its backdrop.
+    pub is_fullscreen: bool,
+}
+
+/// The direction in which a caret moves to an adjacent line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaretLineDirection {
+    Up,
+    Down,
+}
+
+/// A node that is selected by a range, whose boxes are part of the client rects of the range.
+#[derive(Clone, Debug)]
+pub struct RangeNode {
+    pub node: OpaqueNode,
+    /// For a text node, the byte range of its text that is selected. Only the parts of its
+    /// lines that display this text are included, or the position of the caret if the range
+    /// is empty. For an element, `None`, and the border boxes of all its fragments are included.
+    pub text_range: Option<Range<usize>>,
+}
+
+/// A scroll container that contains the box that is scrolled into view.
+#[derive(Clone, Debug)]
+pub struct ScrollerGeometry {
+    /// The element that is the scroll container.
+    pub node: UntrustedNodeAddress,
+    /// The padding box of the scroll container, relative to the initial containing block
+    /// when nothing is scrolled.
+    pub scrollport: Rect<Au>,
+    /// The largest scroll position of the scroll container on each axis.
+    pub scroll_range: UntypedSize2D<Au>,
+}
+
+/// The geometry needed to scroll a box into view.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-a-target-into-view>
+#[derive(Clone, Debug)]
+pub struct ScrollIntoViewGeometry {
+    /// The border box of the box, extended by its `scroll-margin`, relative to the initial
+    /// containing block when nothing is scrolled.
+    pub target_rect: Rect<Au>,
+    /// The scroll containers that contain the box, innermost first, with their scrollports
+    /// reduced by their `scroll-padding`. This does not include the viewport.
+    pub scrollers: Vec<ScrollerGeometry>,
 }
 
 /// Information needed for a script-initiated reflow.
@@ -435,6 +605,26 @@ pub struct ReflowRequest {
     pub node_to_image_animation_map: FxHashMap<OpaqueNode, ImageAnimationState>,
     /// The theme for the window
     pub theme: PrefersColorScheme,
+    /// Whether the document is painted with the colors of forced colors mode.
+    /// <https://drafts.csswg.org/css-color-adjust/#forced>
+    pub forced_colors: bool,
+    /// The media type that media queries are evaluated against, which is `print` while the
+    /// document is being printed and `screen` otherwise.
+    pub media_type: MediaType,
+    /// The find-in-page search whose matches should be highlighted, if any.
+    pub find_in_page_query: Option<FindInPageQuery>,
+    /// The caret and selected text to paint, if caret browsing is enabled.
+    pub caret_browsing_selection: Option<CaretBrowsingSelection>,
+    /// The nodes that the devtools inspector highlights, with overlays in the display list.
+    pub inspector_highlights: Vec<InspectorHighlight>,
+    /// The elements in the top layer of the document, from the bottom to the top.
+    pub top_layer: Vec<TopLayerElement>,
+    /// The `<img>` elements that use `sizes="auto"`, whose widths are reported back in
+    /// [`ReflowResult::auto_sized_image_widths`].
+    pub auto_sized_images: Vec<OpaqueNode>,
+    /// The outermost `<svg>` elements that are rendered from a serialization of their subtree,
+    /// whose sizes are reported back in [`ReflowResult::svg_root_sizes`].
+    pub svg_roots: Vec<OpaqueNode>,
 }
 
 /// A pending restyle.
@@ -68,6 +68,16 @@ pub trait LayoutNode<'dom>: Copy + Debug + TNode + Send + Sync {
     /// Returns None if there is no layout data or it isn't of the desired type.
     fn layout_data(&self) -> Option<&'dom GenericLayoutData>;
 
+    /// Whether this node was moved with `moveBefore()` since its boxes were last built, in
+    /// which case they may be reused. Clears the flag.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it modifies the given node during
+    /// layout. Callers should ensure that no other layout thread is
+    /// attempting to read or modify the flags of this node.
+    unsafe fn take_was_moved(&self) -> bool;
+
     fn rev_children(self) -> LayoutIterator<ReverseChildrenIterator<Self>> {
         LayoutIterator(ReverseChildrenIterator {
             current: self.last_child(),
@@ -215,6 +225,14 @@ pub trait ThreadSafeLayoutNode<'dom>: Clone + Copy + Debug + NodeInfo + PartialE
     /// If the insertion point is within this node, returns it. Otherwise, returns `None`.
     fn selection(&self) -> Option<Range<ByteIndex>>;
 
+    /// If this is a text control, returns the byte ranges of the misspelled words in its
+    /// value. Otherwise, returns an empty list.
+    fn spelling_errors(&self) -> Vec<std::ops::Range<usize>>;
+
+    /// If this is a text control with an active IME composition, returns the byte range of
+    /// the composition string in its value. Otherwise, returns `None`.
+    fn composition(&self) -> Option<std::ops::Range<usize>>;
+
     /// If this is an image element, returns its URL. If this is not an image element, fails.
     fn image_url(&self) -> Option<ServoUrl>;
 
@@ -238,6 +256,10 @@ pub trait ThreadSafeLayoutNode<'dom>: Clone + Copy + Debug + NodeInfo + PartialE
     /// not an iframe element, fails. Returns None if there is no nested browsing context.
     fn iframe_pipeline_id(&self) -> Option<PipelineId>;
 
+    /// If this node is a portal element, returns the pipeline ID of its document. Returns None
+    /// if this node is not a portal element or the portal has no document.
+    fn portal_pipeline_id(&self) -> Option<PipelineId>;
+
     fn get_span(&self) -> Option<u32>;
     fn get_colspan(&self) -> Option<u32>;
     fn get_rowspan(&self) -> Option<u32>;
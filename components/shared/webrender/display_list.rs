@@ -8,8 +8,11 @@ use base::id::ScrollTreeNodeId;
 use embedder_traits::Cursor;
 use serde::{Deserialize, Serialize};
 use style::values::specified::Overflow;
-use webrender_api::units::{LayoutSize, LayoutVector2D};
-use webrender_api::{Epoch, ExternalScrollId, PipelineId, ScrollLocation, SpatialId};
+use webrender_api::units::{LayoutPoint, LayoutSize, LayoutTransform, LayoutVector2D};
+use webrender_api::{
+    Epoch, ExternalScrollId, PipelineId, PropertyBindingKey, PropertyValue, ScrollLocation,
+    SpatialId,
+};
 
 /// The scroll sensitivity of a scroll node in a particular axis ie whether it can be scrolled due to
 /// input events and script events or only script events.
@@ -43,7 +46,7 @@ pub struct AxesScrollSensitivity {
 
 /// Information that Servo keeps alongside WebRender display items
 /// in order to add more context to hit test results.
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct HitTestInfo {
     /// The id of the node of this hit test item.
     pub node: u64,
@@ -53,6 +56,34 @@ pub struct HitTestInfo {
 
     /// The id of the [ScrollTree] associated with this hit test item.
     pub scroll_tree_node: ScrollTreeNodeId,
+
+    /// The plane of this hit test item, if it is part of a 3D rendering context.
+    pub plane: Option<HitTestPlane>,
+}
+
+/// The plane of a hit test item that is part of a 3D rendering context. WebRender orders hit
+/// test results by the order in which the items are painted, but within a 3D rendering
+/// context the topmost item is the one that is closest to the viewer at the hit point.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HitTestPlane {
+    /// The 3D rendering context of the item, which is unique within its display list.
+    pub context: u32,
+
+    /// The transform from the coordinate space of the spatial node of the item to the one of
+    /// the element that establishes the 3D rendering context.
+    pub transform: LayoutTransform,
+
+    /// The origin of the rectangle of the hit test item in its spatial node.
+    pub origin: LayoutPoint,
+}
+
+impl HitTestPlane {
+    /// The depth of the point of this item that was hit, given as a point relative to the
+    /// item, in the 3D rendering context. Points with a larger depth are closer to the viewer.
+    pub fn depth_at(&self, point_relative_to_item: LayoutPoint) -> Option<f32> {
+        let point = self.origin + point_relative_to_item.to_vector();
+        Some(self.transform.transform_point3d(point.to_3d())?.z)
+    }
 }
 
 /// Data stored for nodes in the [ScrollTree] that actually scroll,
@@ -71,6 +102,71 @@ pub struct ScrollableNodeInfo {
 
     /// The current offset of this scroll node.
     pub offset: LayoutVector2D,
+
+    /// The thumbs of the scrollbars of this node, which are moved when it scrolls.
+    pub scrollbar_thumbs: Vec<ScrollbarThumb>,
+}
+
+impl ScrollableNodeInfo {
+    /// The transforms that place the scrollbar thumbs of this node along their tracks, at
+    /// its current offset.
+    pub fn scrollbar_thumb_transforms(&self) -> Vec<PropertyValue<LayoutTransform>> {
+        // Offsets are stored negated, as the offsets of the content.
+        self.scrollbar_thumbs
+            .iter()
+            .map(|thumb| PropertyValue {
+                key: thumb.transform_key,
+                value: thumb.transform(-self.offset, self.scrollable_size),
+            })
+            .collect()
+    }
+}
+
+/// The axis along which a scrollbar thumb moves.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScrollbarAxis {
+    Horizontal,
+    Vertical,
+}
+
+/// The thumb of a scrollbar that layout paints in a reference frame whose transform is bound
+/// to a WebRender property, so that the compositor can move it along its track when it
+/// scrolls the node of the scrollbar, without a new display list.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScrollbarThumb {
+    /// The key of the transform of the reference frame of the thumb.
+    pub transform_key: PropertyBindingKey<LayoutTransform>,
+
+    /// The axis along which the thumb moves.
+    pub axis: ScrollbarAxis,
+
+    /// How far the thumb moves from the start of its track when the node is scrolled to
+    /// its end.
+    pub travel: f32,
+}
+
+impl ScrollbarThumb {
+    /// The transform that moves the thumb from the start of its track to where it is when
+    /// its node, which can scroll by `scrollable_size`, is scrolled to `scroll_position`.
+    pub fn transform(
+        &self,
+        scroll_position: LayoutVector2D,
+        scrollable_size: LayoutSize,
+    ) -> LayoutTransform {
+        let (scroll_position, scroll_range) = match self.axis {
+            ScrollbarAxis::Horizontal => (scroll_position.x, scrollable_size.width),
+            ScrollbarAxis::Vertical => (scroll_position.y, scrollable_size.height),
+        };
+        let distance = if scroll_range > 0. {
+            (scroll_position / scroll_range).clamp(0., 1.) * self.travel
+        } else {
+            0.
+        };
+        match self.axis {
+            ScrollbarAxis::Horizontal => LayoutTransform::translation(distance, 0., 0.),
+            ScrollbarAxis::Vertical => LayoutTransform::translation(0., distance, 0.),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -96,6 +192,14 @@ impl ScrollTreeNode {
         self.scroll_info.as_ref().map(|info| info.offset)
     }
 
+    /// The transforms that place the scrollbar thumbs of this node at its current offset.
+    pub fn scrollbar_thumb_transforms(&self) -> Vec<PropertyValue<LayoutTransform>> {
+        self.scroll_info
+            .as_ref()
+            .map(ScrollableNodeInfo::scrollbar_thumb_transforms)
+            .unwrap_or_default()
+    }
+
     /// Set the offset for this node, returns false if this was a
     /// non-scrolling node for which you cannot set the offset.
     pub fn set_offset(&mut self, new_offset: LayoutVector2D) -> bool {
@@ -242,6 +346,19 @@ impl ScrollTree {
         parent.and_then(|parent| self.scroll_node_or_ancestor(&parent, scroll_location))
     }
 
+    /// The transforms that place the scrollbar thumbs of the scroll node with the given
+    /// [`ExternalScrollId`] at its current offset.
+    pub fn scrollbar_thumb_transforms(
+        &self,
+        external_scroll_id: ExternalScrollId,
+    ) -> Vec<PropertyValue<LayoutTransform>> {
+        self.nodes
+            .iter()
+            .find(|node| node.external_id() == Some(external_scroll_id))
+            .map(ScrollTreeNode::scrollbar_thumb_transforms)
+            .unwrap_or_default()
+    }
+
     /// Given an [`ExternalScrollId`] and an offset, update the scroll offset of the scroll node
     /// with the given id.
     pub fn set_scroll_offsets_for_node_with_external_scroll_id(
@@ -303,6 +420,10 @@ pub struct CompositorDisplayListInfo {
     /// Whether the first layout or a subsequent (incremental) layout triggered this
     /// display list creation.
     pub first_reflow: bool,
+
+    /// The pipelines of the iframes in this display list that have `pointer-events: none`.
+    /// Nothing in them, including the contents of iframes nested in them, is hit by hit tests.
+    pub iframes_without_pointer_events: Vec<PipelineId>,
 }
 
 impl CompositorDisplayListInfo {
@@ -330,6 +451,7 @@ impl CompositorDisplayListInfo {
                 scrollable_size: content_size - viewport_size,
                 scroll_sensitivity: viewport_scroll_sensitivity,
                 offset: LayoutVector2D::zero(),
+                scrollbar_thumbs: Vec::new(),
             }),
         );
 
@@ -344,6 +466,7 @@ impl CompositorDisplayListInfo {
             root_scroll_node_id,
             is_contentful: false,
             first_reflow,
+            iframes_without_pointer_events: Vec::new(),
         }
     }
 
@@ -354,11 +477,13 @@ impl CompositorDisplayListInfo {
         node: u64,
         cursor: Option<Cursor>,
         scroll_tree_node: ScrollTreeNodeId,
+        plane: Option<HitTestPlane>,
     ) -> usize {
         let hit_test_info = HitTestInfo {
             node,
             cursor,
             scroll_tree_node,
+            plane,
         };
 
         if let Some(last) = self.hit_test_info.last() {
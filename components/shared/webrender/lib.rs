@@ -14,6 +14,7 @@ use std::sync::{Arc, Mutex};
 use base::id::WebViewId;
 use constellation_traits::CompositorHitTestResult;
 use display_list::CompositorDisplayListInfo;
+use embedder_traits::ScreenTopology;
 use euclid::default::Size2D as UntypedSize2D;
 use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
 use log::warn;
@@ -27,6 +28,14 @@ use webrender_api::{
     ImageKey, NativeFontHandle, PipelineId as WebRenderPipelineId,
 };
 
+/// Whether the compositor moves a scroll node to a new scroll offset at once, or animates
+/// the scroll over a short period of time.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScrollAnimation {
+    Instant,
+    Smooth,
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum CrossProcessCompositorMessage {
     /// Inform WebRender of the existence of this pipeline.
@@ -37,6 +46,7 @@ pub enum CrossProcessCompositorMessage {
         WebRenderPipelineId,
         LayoutPoint,
         ExternalScrollId,
+        ScrollAnimation,
     ),
     /// Inform WebRender of a new display list for the given pipeline.
     SendDisplayList {
@@ -88,6 +98,9 @@ pub enum CrossProcessCompositorMessage {
     /// Get the available screen size (without toolbars and docks) for the screen
     /// the client window inhabits.
     GetAvailableScreenSize(IpcSender<DeviceIndependentIntSize>),
+    /// Get the description of all screens connected to the system and the screen that
+    /// the client window currently inhabits.
+    GetScreenTopology(IpcSender<ScreenTopology>),
 }
 
 impl fmt::Debug for CrossProcessCompositorMessage {
@@ -108,6 +121,7 @@ impl fmt::Debug for CrossProcessCompositorMessage {
             Self::GetClientWindowRect(..) => f.write_str("GetClientWindowRect"),
             Self::GetScreenSize(..) => f.write_str("GetScreenSize"),
             Self::GetAvailableScreenSize(..) => f.write_str("GetAvailableScreenSize"),
+            Self::GetScreenTopology(..) => f.write_str("GetScreenTopology"),
         }
     }
 }
@@ -148,12 +162,14 @@ impl CrossProcessCompositorApi {
         pipeline_id: WebRenderPipelineId,
         point: LayoutPoint,
         scroll_id: ExternalScrollId,
+        animation: ScrollAnimation,
     ) {
         if let Err(e) = self.0.send(CrossProcessCompositorMessage::SendScrollNode(
             webview_id,
             pipeline_id,
             point,
             scroll_id,
+            animation,
         )) {
             warn!("Error sending scroll node: {}", e);
         }
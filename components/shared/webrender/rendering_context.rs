@@ -5,6 +5,7 @@
 #![deny(unsafe_code)]
 
 use std::cell::{Cell, RefCell, RefMut};
+use std::ffi::c_void;
 use std::num::NonZeroU32;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -54,6 +55,19 @@ pub trait RenderingContext {
     /// Presents the rendered frame to the screen. In a double-buffered context, this would
     /// swap buffers.
     fn present(&self);
+    /// The number of frames since the contents of the buffer that is rendered to next were
+    /// presented, or 0 if its contents are unknown. When this is not 0, only the parts of the
+    /// frame that changed since then need to be rendered again. Default to 0.
+    fn buffer_age(&self) -> usize {
+        0
+    }
+    /// Whether the contents of this [`RenderingContext`] are presented in the Display P3
+    /// color space, for instance because its surface was configured for a wide gamut
+    /// display. Servo only renders colors outside of sRGB when this is true. Default to
+    /// `false`.
+    fn supports_display_p3(&self) -> bool {
+        false
+    }
     /// Makes the context the current OpenGL context for this thread.
     /// After calling this function, it is valid to use OpenGL rendering
     /// commands.
@@ -270,6 +284,68 @@ impl SurfmanRenderingContext {
     }
 }
 
+/// The EGL functions needed to query the age of the back buffer of a window surface, with
+/// the `EGL_EXT_buffer_age` extension.
+///
+/// <https://registry.khronos.org/EGL/extensions/EXT/EGL_EXT_buffer_age.txt>
+struct EglBufferAge {
+    get_current_display: EglGetCurrentDisplay,
+    get_current_surface: EglGetCurrentSurface,
+    query_surface: EglQuerySurface,
+}
+
+type EglGetCurrentDisplay = unsafe extern "C" fn() -> *const c_void;
+type EglGetCurrentSurface = unsafe extern "C" fn(i32) -> *const c_void;
+type EglQuerySurface = unsafe extern "C" fn(*const c_void, *const c_void, i32, *mut i32) -> u32;
+
+impl EglBufferAge {
+    const EGL_DRAW: i32 = 0x3059;
+    const EGL_BUFFER_AGE_EXT: i32 = 0x313D;
+
+    /// Load the functions from the EGL implementation of `surfman_context`, if it uses EGL.
+    #[allow(unsafe_code)]
+    fn load(surfman_context: &SurfmanRenderingContext) -> Option<Self> {
+        let device = surfman_context.device.borrow();
+        let context = surfman_context.context.borrow();
+        let load = |name| {
+            let function = device.get_proc_address(&context, name);
+            (!function.is_null()).then_some(function)
+        };
+        let get_current_display = load("eglGetCurrentDisplay")?;
+        let get_current_surface = load("eglGetCurrentSurface")?;
+        let query_surface = load("eglQuerySurface")?;
+        Some(unsafe {
+            EglBufferAge {
+                get_current_display: std::mem::transmute::<*const c_void, EglGetCurrentDisplay>(
+                    get_current_display,
+                ),
+                get_current_surface: std::mem::transmute::<*const c_void, EglGetCurrentSurface>(
+                    get_current_surface,
+                ),
+                query_surface: std::mem::transmute::<*const c_void, EglQuerySurface>(query_surface),
+            }
+        })
+    }
+
+    /// The age of the back buffer of the surface that the current context draws to, or 0
+    /// if it is unknown, for instance because the extension is not supported.
+    #[allow(unsafe_code)]
+    fn query(&self) -> usize {
+        unsafe {
+            let display = (self.get_current_display)();
+            let surface = (self.get_current_surface)(Self::EGL_DRAW);
+            if display.is_null() || surface.is_null() {
+                return 0;
+            }
+            let mut age = 0;
+            if (self.query_surface)(display, surface, Self::EGL_BUFFER_AGE_EXT, &mut age) == 0 {
+                return 0;
+            }
+            age.max(0) as usize
+        }
+    }
+}
+
 /// A software rendering context that uses a software OpenGL implementation to render
 /// Servo. This will generally have bad performance, but can be used in situations where
 /// it is more convenient to have consistent, but slower display output.
@@ -279,6 +355,8 @@ pub struct SoftwareRenderingContext {
     size: Cell<PhysicalSize<u32>>,
     surfman_rendering_info: SurfmanRenderingContext,
     swap_chain: SwapChain<Device>,
+    /// Whether the embedder presents the pixels of this context in Display P3.
+    display_p3: bool,
 }
 
 impl SoftwareRenderingContext {
@@ -298,8 +376,18 @@ impl SoftwareRenderingContext {
             size: Cell::new(size),
             surfman_rendering_info,
             swap_chain,
+            display_p3: false,
         })
     }
+
+    /// Declare that the embedder presents the pixels that it reads back from this context
+    /// with [`RenderingContext::read_to_image`] in the Display P3 color space, for instance
+    /// by tagging them with its color profile. Servo then renders wide gamut colors instead of
+    /// clipping them to sRGB, if the `gfx_display_p3_enabled` preference is set.
+    pub fn with_display_p3(mut self) -> Self {
+        self.display_p3 = true;
+        self
+    }
 }
 
 impl Drop for SoftwareRenderingContext {
@@ -344,6 +432,10 @@ impl RenderingContext for SoftwareRenderingContext {
             .swap_buffers(device, context, PreserveBuffer::No);
     }
 
+    fn supports_display_p3(&self) -> bool {
+        self.display_p3
+    }
+
     fn make_current(&self) -> Result<(), Error> {
         self.surfman_rendering_info.make_current()
     }
@@ -383,6 +475,9 @@ impl RenderingContext for SoftwareRenderingContext {
 pub struct WindowRenderingContext {
     size: Cell<PhysicalSize<u32>>,
     surfman_context: SurfmanRenderingContext,
+    /// The functions to query the age of the back buffer of the window surface, if the
+    /// platform uses EGL.
+    egl_buffer_age: Option<EglBufferAge>,
 }
 
 impl WindowRenderingContext {
@@ -406,9 +501,11 @@ impl WindowRenderingContext {
         surfman_context.bind_surface(surface)?;
         surfman_context.make_current()?;
 
+        let egl_buffer_age = EglBufferAge::load(&surfman_context);
         Ok(Self {
             size: Cell::new(size),
             surfman_context,
+            egl_buffer_age,
         })
     }
 
@@ -499,6 +596,15 @@ impl RenderingContext for WindowRenderingContext {
         }
     }
 
+    fn buffer_age(&self) -> usize {
+        // The window surface is bound to the context, so it is the current draw surface
+        // once the context is current.
+        if self.make_current().is_err() {
+            return 0;
+        }
+        self.egl_buffer_age.as_ref().map_or(0, EglBufferAge::query)
+    }
+
     fn make_current(&self) -> Result<(), Error> {
         self.surfman_context.make_current()
     }
@@ -527,6 +633,32 @@ impl RenderingContext for WindowRenderingContext {
     }
 }
 
+/// A framebuffer that the scene can be rendered to without touching the buffers of a
+/// [`RenderingContext`], for instance to read back its pixels without presenting them.
+pub struct OffscreenRenderTarget {
+    framebuffer: Framebuffer,
+}
+
+impl OffscreenRenderTarget {
+    /// Create a target of `size` that uses the OpenGL context of `rendering_context`.
+    pub fn new(rendering_context: &dyn RenderingContext, size: PhysicalSize<u32>) -> Self {
+        Self {
+            framebuffer: Framebuffer::new(rendering_context.gleam_gl_api(), size),
+        }
+    }
+
+    /// Bind the framebuffer of this target, so that it is rendered to.
+    pub fn bind(&self) {
+        self.framebuffer.bind();
+    }
+
+    /// Read back the pixels of `source_rectangle`, in the bottom-left origin coordinates
+    /// of OpenGL.
+    pub fn read_to_image(&self, source_rectangle: DeviceIntRect) -> Option<RgbaImage> {
+        self.framebuffer.read_to_image(source_rectangle)
+    }
+}
+
 struct Framebuffer {
     gl: Rc<dyn Gl>,
     framebuffer_id: gl::GLuint,
@@ -673,6 +805,9 @@ pub struct OffscreenRenderingContext {
     parent_context: Rc<WindowRenderingContext>,
     size: Cell<PhysicalSize<u32>>,
     framebuffer: RefCell<Framebuffer>,
+    /// Whether a frame was presented since the framebuffer was created, in which case it
+    /// still contains that frame.
+    presented: Cell<bool>,
 }
 
 type RenderToParentCallback = Box<dyn Fn(&glow::Context, Rect<i32>) + Send + Sync>;
@@ -684,6 +819,7 @@ impl OffscreenRenderingContext {
             parent_context,
             size: Cell::new(size),
             framebuffer,
+            presented: Cell::new(false),
         }
     }
 
@@ -767,6 +903,7 @@ impl RenderingContext for OffscreenRenderingContext {
         let old_framebuffer =
             std::mem::replace(&mut *self.framebuffer.borrow_mut(), new_framebuffer);
         self.size.set(new_size);
+        self.presented.set(false);
 
         let blit_size = new_size.min(old_size);
         let rect = Rect::new(
@@ -795,7 +932,14 @@ impl RenderingContext for OffscreenRenderingContext {
         self.framebuffer.borrow().bind();
     }
 
-    fn present(&self) {}
+    fn present(&self) {
+        self.presented.set(true);
+    }
+
+    fn buffer_age(&self) -> usize {
+        // There is a single framebuffer, so it contains the last frame that was presented.
+        self.presented.get() as usize
+    }
 
     fn make_current(&self) -> Result<(), surfman::Error> {
         self.parent_context.make_current()
@@ -14,6 +14,7 @@ use std::hash::Hasher;
 use std::net::IpAddr;
 use std::ops::{Index, Range, RangeFrom, RangeFull, RangeTo};
 use std::path::Path;
+use std::sync::{LazyLock, RwLock};
 
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,23 @@ pub use crate::origin::{ImmutableOrigin, MutableOrigin, OpaqueOrigin};
 
 const DATA_URL_DISPLAY_LENGTH: usize = 40;
 
+/// The custom schemes registered by the embedder whose URLs are potentially trustworthy.
+static SECURE_SCHEMES: LazyLock<RwLock<Vec<String>>> = LazyLock::new(Default::default);
+
+/// Register a custom `scheme` whose URLs are potentially trustworthy, so that documents loaded
+/// from it are secure contexts even though the URL parser gives them opaque origins.
+pub fn register_secure_scheme(scheme: &str) {
+    let mut schemes = SECURE_SCHEMES.write().unwrap();
+    if !schemes.iter().any(|registered| registered == scheme) {
+        schemes.push(scheme.to_owned());
+    }
+}
+
+/// The custom schemes whose URLs are potentially trustworthy.
+pub fn secure_schemes() -> Vec<String> {
+    SECURE_SCHEMES.read().unwrap().clone()
+}
+
 #[derive(Debug)]
 pub enum UrlError {
     SetUsername,
@@ -223,6 +241,16 @@ impl ServoUrl {
         if self.scheme() == "data" {
             return true;
         }
+        // URLs of custom schemes have opaque origins, so the schemes that the embedder
+        // registered as secure are checked here rather than in step 3.
+        if SECURE_SCHEMES
+            .read()
+            .unwrap()
+            .iter()
+            .any(|scheme| scheme == self.scheme())
+        {
+            return true;
+        }
         // Step 3
         self.is_origin_trustworthy()
     }
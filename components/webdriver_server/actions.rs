@@ -13,7 +13,8 @@ use keyboard_types::webdriver::KeyInputState;
 use webdriver::actions::{
     ActionSequence, ActionsType, GeneralAction, KeyAction, KeyActionItem, KeyDownAction,
     KeyUpAction, NullActionItem, PointerAction, PointerActionItem, PointerActionParameters,
-    PointerDownAction, PointerMoveAction, PointerOrigin, PointerType, PointerUpAction,
+    PointerDownAction, PointerMoveAction, PointerOrigin, PointerType, PointerUpAction, WheelAction,
+    WheelActionItem, WheelScrollAction,
 };
 use webdriver::error::ErrorStatus;
 
@@ -22,11 +23,15 @@ use crate::Handler;
 // Interval between pointerMove increments in ms, based on common vsync
 static POINTERMOVE_INTERVAL: u64 = 17;
 
+// Interval between wheel scroll increments in ms, based on common vsync
+static WHEELSCROLL_INTERVAL: u64 = 17;
+
 // https://w3c.github.io/webdriver/#dfn-input-source-state
 pub(crate) enum InputSourceState {
     Null,
     Key(KeyInputState),
     Pointer(PointerInputState),
+    Wheel,
 }
 
 // https://w3c.github.io/webdriver/#dfn-pointer-input-source
@@ -40,11 +45,7 @@ pub(crate) struct PointerInputState {
 impl PointerInputState {
     pub fn new(subtype: &PointerType) -> PointerInputState {
         PointerInputState {
-            subtype: match subtype {
-                PointerType::Mouse => PointerType::Mouse,
-                PointerType::Pen => PointerType::Pen,
-                PointerType::Touch => PointerType::Touch,
-            },
+            subtype: copy_pointer_type(subtype),
             pressed: HashSet::new(),
             x: 0,
             y: 0,
@@ -52,31 +53,51 @@ impl PointerInputState {
     }
 }
 
+fn copy_pointer_type(pointer_type: &PointerType) -> PointerType {
+    match pointer_type {
+        PointerType::Mouse => PointerType::Mouse,
+        PointerType::Pen => PointerType::Pen,
+        PointerType::Touch => PointerType::Touch,
+    }
+}
+
+/// An action of an input source, as it is found in the actions of a tick.
+enum ActionItem<'a> {
+    Null(&'a NullActionItem),
+    Key(&'a KeyActionItem),
+    Pointer(&'a PointerActionItem),
+    Wheel(&'a WheelActionItem),
+}
+
+/// The actions that are dispatched in a tick, along with the ids of their input sources.
+type TickActions<'a> = Vec<(&'a str, ActionItem<'a>)>;
+
+// https://w3c.github.io/webdriver/#dfn-actions-by-tick
+type ActionsByTick<'a> = Vec<TickActions<'a>>;
+
+fn pause_duration(action: &GeneralAction) -> Option<u64> {
+    let GeneralAction::Pause(pause_action) = action;
+    pause_action.duration
+}
+
 // https://w3c.github.io/webdriver/#dfn-computing-the-tick-duration
-fn compute_tick_duration(tick_actions: &ActionSequence) -> u64 {
+fn compute_tick_duration(tick_actions: &TickActions) -> u64 {
     let mut duration = 0;
-    match &tick_actions.actions {
-        ActionsType::Null { actions } => {
-            for action in actions.iter() {
-                let NullActionItem::General(GeneralAction::Pause(pause_action)) = action;
-                duration = cmp::max(duration, pause_action.duration.unwrap_or(0));
-            }
-        },
-        ActionsType::Pointer {
-            parameters: _,
-            actions,
-        } => {
-            for action in actions.iter() {
-                let action_duration = match action {
-                    PointerActionItem::General(GeneralAction::Pause(action)) => action.duration,
-                    PointerActionItem::Pointer(PointerAction::Move(action)) => action.duration,
-                    _ => None,
-                };
-                duration = cmp::max(duration, action_duration.unwrap_or(0));
-            }
-        },
-        ActionsType::Key { actions: _ } => (),
-        ActionsType::Wheel { .. } => todo!("Not implemented."),
+    for (_, action) in tick_actions.iter() {
+        let action_duration = match action {
+            ActionItem::Null(NullActionItem::General(action)) |
+            ActionItem::Key(KeyActionItem::General(action)) |
+            ActionItem::Pointer(PointerActionItem::General(action)) |
+            ActionItem::Wheel(WheelActionItem::General(action)) => pause_duration(action),
+            ActionItem::Pointer(PointerActionItem::Pointer(PointerAction::Move(action))) => {
+                action.duration
+            },
+            ActionItem::Wheel(WheelActionItem::Wheel(WheelAction::Scroll(action))) => {
+                action.duration
+            },
+            _ => None,
+        };
+        duration = cmp::max(duration, action_duration.unwrap_or(0));
     }
     duration
 }
@@ -85,114 +106,157 @@ impl Handler {
     // https://w3c.github.io/webdriver/#dfn-dispatch-actions
     pub(crate) fn dispatch_actions(
         &mut self,
-        actions_by_tick: &[ActionSequence],
+        actions: &[ActionSequence],
     ) -> Result<(), ErrorStatus> {
+        let actions_by_tick = self.extract_an_action_sequence(actions)?;
         for tick_actions in actions_by_tick.iter() {
+            let tick_start = Instant::now();
             let tick_duration = compute_tick_duration(tick_actions);
-            self.dispatch_tick_actions(tick_actions, tick_duration)?;
+            self.dispatch_tick_actions(tick_actions, tick_duration, tick_start)?;
+
+            // Wait for the rest of the tick duration, so that the actions of the next tick
+            // only start once every action of this one is done.
+            let remaining =
+                Duration::from_millis(tick_duration).saturating_sub(tick_start.elapsed());
+            if !remaining.is_zero() {
+                thread::sleep(remaining);
+            }
         }
         Ok(())
     }
 
-    fn dispatch_general_action(&mut self, source_id: &str) {
-        self.session_mut()
-            .unwrap()
+    // https://w3c.github.io/webdriver/#dfn-extract-an-action-sequence
+    fn extract_an_action_sequence<'a>(
+        &mut self,
+        actions: &'a [ActionSequence],
+    ) -> Result<ActionsByTick<'a>, ErrorStatus> {
+        let mut actions_by_tick: ActionsByTick = Vec::new();
+        for action_sequence in actions.iter() {
+            self.get_or_create_an_input_source(action_sequence)?;
+            let source_id = action_sequence.id.as_str();
+            let source_actions: Vec<ActionItem> = match &action_sequence.actions {
+                ActionsType::Null { actions } => actions.iter().map(ActionItem::Null).collect(),
+                ActionsType::Key { actions } => actions.iter().map(ActionItem::Key).collect(),
+                ActionsType::Pointer { actions, .. } => {
+                    actions.iter().map(ActionItem::Pointer).collect()
+                },
+                ActionsType::Wheel { actions } => actions.iter().map(ActionItem::Wheel).collect(),
+            };
+            for (tick, action) in source_actions.into_iter().enumerate() {
+                if actions_by_tick.len() <= tick {
+                    actions_by_tick.push(Vec::new());
+                }
+                actions_by_tick[tick].push((source_id, action));
+            }
+        }
+        Ok(actions_by_tick)
+    }
+
+    // https://w3c.github.io/webdriver/#dfn-get-or-create-an-input-source
+    fn get_or_create_an_input_source(
+        &mut self,
+        action_sequence: &ActionSequence,
+    ) -> Result<(), ErrorStatus> {
+        let session = self.session.as_mut().unwrap();
+        let source = session
             .input_state_table
-            .entry(source_id.to_string())
-            .or_insert(InputSourceState::Null);
-        // https://w3c.github.io/webdriver/#dfn-dispatch-a-pause-action
-        // Nothing to be done
+            .entry(action_sequence.id.clone())
+            .or_insert_with(|| match &action_sequence.actions {
+                ActionsType::Null { .. } => InputSourceState::Null,
+                ActionsType::Key { .. } => InputSourceState::Key(KeyInputState::new()),
+                ActionsType::Pointer { parameters, .. } => {
+                    InputSourceState::Pointer(PointerInputState::new(&parameters.pointer_type))
+                },
+                ActionsType::Wheel { .. } => InputSourceState::Wheel,
+            });
+
+        // An input source keeps its type, and a pointer its subtype, for the whole session.
+        let same_type = match (source, &action_sequence.actions) {
+            (InputSourceState::Null, ActionsType::Null { .. }) |
+            (InputSourceState::Key(_), ActionsType::Key { .. }) |
+            (InputSourceState::Wheel, ActionsType::Wheel { .. }) => true,
+            (
+                InputSourceState::Pointer(pointer_input_state),
+                ActionsType::Pointer { parameters, .. },
+            ) => pointer_input_state.subtype == parameters.pointer_type,
+            _ => false,
+        };
+        if !same_type {
+            return Err(ErrorStatus::InvalidArgument);
+        }
+        Ok(())
     }
 
     // https://w3c.github.io/webdriver/#dfn-dispatch-tick-actions
     fn dispatch_tick_actions(
         &mut self,
-        tick_actions: &ActionSequence,
+        tick_actions: &TickActions,
         tick_duration: u64,
+        tick_start: Instant,
     ) -> Result<(), ErrorStatus> {
-        let source_id = &tick_actions.id;
-        match &tick_actions.actions {
-            ActionsType::Null { actions } => {
-                for _action in actions.iter() {
-                    self.dispatch_general_action(source_id);
-                }
-            },
-            ActionsType::Key { actions } => {
-                for action in actions.iter() {
-                    match action {
-                        KeyActionItem::General(_action) => {
-                            self.dispatch_general_action(source_id);
-                        },
-                        KeyActionItem::Key(action) => {
-                            self.session_mut()
-                                .unwrap()
-                                .input_state_table
-                                .entry(source_id.to_string())
-                                .or_insert(InputSourceState::Key(KeyInputState::new()));
-                            match action {
-                                KeyAction::Down(action) => {
-                                    self.dispatch_keydown_action(source_id, action)
-                                },
-                                KeyAction::Up(action) => {
-                                    self.dispatch_keyup_action(source_id, action)
-                                },
-                            };
-                        },
-                    }
-                }
-            },
-            ActionsType::Pointer {
-                parameters,
-                actions,
-            } => {
-                for action in actions.iter() {
-                    match action {
-                        PointerActionItem::General(_action) => {
-                            self.dispatch_general_action(source_id);
-                        },
-                        PointerActionItem::Pointer(action) => {
-                            self.session_mut()
-                                .unwrap()
-                                .input_state_table
-                                .entry(source_id.to_string())
-                                .or_insert(InputSourceState::Pointer(PointerInputState::new(
-                                    &parameters.pointer_type,
-                                )));
-                            match action {
-                                PointerAction::Cancel => (),
-                                PointerAction::Down(action) => {
-                                    self.dispatch_pointerdown_action(source_id, action)
-                                },
-                                PointerAction::Move(action) => self.dispatch_pointermove_action(
-                                    source_id,
-                                    action,
-                                    tick_duration,
-                                )?,
-                                PointerAction::Up(action) => {
-                                    self.dispatch_pointerup_action(source_id, action)
-                                },
-                            }
-                        },
-                    }
-                }
-            },
-            ActionsType::Wheel { .. } => todo!("Not implemented."),
+        for (source_id, action) in tick_actions.iter() {
+            match action {
+                // https://w3c.github.io/webdriver/#dfn-dispatch-a-pause-action
+                // Nothing to be done
+                ActionItem::Null(_) |
+                ActionItem::Key(KeyActionItem::General(_)) |
+                ActionItem::Pointer(PointerActionItem::General(_)) |
+                ActionItem::Wheel(WheelActionItem::General(_)) => (),
+                ActionItem::Key(KeyActionItem::Key(action)) => match action {
+                    KeyAction::Down(action) => self.dispatch_keydown_action(source_id, action)?,
+                    KeyAction::Up(action) => self.dispatch_keyup_action(source_id, action)?,
+                },
+                ActionItem::Pointer(PointerActionItem::Pointer(action)) => match action {
+                    // Pointers are never canceled by the platform, so there is nothing to be done.
+                    PointerAction::Cancel => (),
+                    PointerAction::Down(action) => {
+                        self.dispatch_pointerdown_action(source_id, action)
+                    },
+                    PointerAction::Move(action) => self.dispatch_pointermove_action(
+                        source_id,
+                        action,
+                        tick_duration,
+                        tick_start,
+                    )?,
+                    PointerAction::Up(action) => self.dispatch_pointerup_action(source_id, action),
+                },
+                ActionItem::Wheel(WheelActionItem::Wheel(WheelAction::Scroll(action))) => {
+                    self.dispatch_scroll_action(action, tick_duration, tick_start)?
+                },
+            }
         }
 
         Ok(())
     }
 
-    // https://w3c.github.io/webdriver/#dfn-dispatch-a-keydown-action
-    fn dispatch_keydown_action(&mut self, source_id: &str, action: &KeyDownAction) {
-        let session = self.session.as_mut().unwrap();
-
-        let raw_key = action.value.chars().next().unwrap();
-        let key_input_state = match session.input_state_table.get_mut(source_id).unwrap() {
-            InputSourceState::Null => unreachable!(),
+    fn key_input_state(&mut self, source_id: &str) -> &mut KeyInputState {
+        match self
+            .session
+            .as_mut()
+            .unwrap()
+            .input_state_table
+            .get_mut(source_id)
+            .unwrap()
+        {
             InputSourceState::Key(key_input_state) => key_input_state,
-            InputSourceState::Pointer(_) => unreachable!(),
-        };
+            _ => unreachable!(),
+        }
+    }
 
+    // https://w3c.github.io/webdriver/#dfn-dispatch-a-keydown-action
+    fn dispatch_keydown_action(
+        &mut self,
+        source_id: &str,
+        action: &KeyDownAction,
+    ) -> Result<(), ErrorStatus> {
+        let raw_key = action
+            .value
+            .chars()
+            .next()
+            .ok_or(ErrorStatus::InvalidArgument)?;
+        let keyboard_event = self.key_input_state(source_id).dispatch_keydown(raw_key);
+
+        let session = self.session.as_mut().unwrap();
         session.input_cancel_list.push(ActionSequence {
             id: source_id.into(),
             actions: ActionsType::Key {
@@ -202,41 +266,34 @@ impl Handler {
             },
         });
 
-        let keyboard_event = key_input_state.dispatch_keydown(raw_key);
         let cmd_msg =
             WebDriverCommandMsg::KeyboardAction(session.browsing_context_id, keyboard_event);
         self.constellation_chan
             .send(ConstellationMsg::WebDriverCommand(cmd_msg))
             .unwrap();
+        Ok(())
     }
 
     // https://w3c.github.io/webdriver/#dfn-dispatch-a-keyup-action
-    fn dispatch_keyup_action(&mut self, source_id: &str, action: &KeyUpAction) {
-        let session = self.session.as_mut().unwrap();
-
-        let raw_key = action.value.chars().next().unwrap();
-        let key_input_state = match session.input_state_table.get_mut(source_id).unwrap() {
-            InputSourceState::Null => unreachable!(),
-            InputSourceState::Key(key_input_state) => key_input_state,
-            InputSourceState::Pointer(_) => unreachable!(),
-        };
-
-        session.input_cancel_list.push(ActionSequence {
-            id: source_id.into(),
-            actions: ActionsType::Key {
-                actions: vec![KeyActionItem::Key(KeyAction::Up(KeyUpAction {
-                    value: action.value.clone(),
-                }))],
-            },
-        });
-
-        if let Some(keyboard_event) = key_input_state.dispatch_keyup(raw_key) {
+    fn dispatch_keyup_action(
+        &mut self,
+        source_id: &str,
+        action: &KeyUpAction,
+    ) -> Result<(), ErrorStatus> {
+        let raw_key = action
+            .value
+            .chars()
+            .next()
+            .ok_or(ErrorStatus::InvalidArgument)?;
+        if let Some(keyboard_event) = self.key_input_state(source_id).dispatch_keyup(raw_key) {
+            let session = self.session.as_ref().unwrap();
             let cmd_msg =
                 WebDriverCommandMsg::KeyboardAction(session.browsing_context_id, keyboard_event);
             self.constellation_chan
                 .send(ConstellationMsg::WebDriverCommand(cmd_msg))
                 .unwrap();
         }
+        Ok(())
     }
 
     // https://w3c.github.io/webdriver/#dfn-dispatch-a-pointerdown-action
@@ -248,9 +305,8 @@ impl Handler {
         let session = self.session.as_mut().unwrap();
 
         let pointer_input_state = match session.input_state_table.get_mut(source_id).unwrap() {
-            InputSourceState::Null => unreachable!(),
-            InputSourceState::Key(_) => unreachable!(),
             InputSourceState::Pointer(pointer_input_state) => pointer_input_state,
+            _ => unreachable!(),
         };
 
         if pointer_input_state.pressed.contains(&action.button) {
@@ -262,11 +318,7 @@ impl Handler {
             id: source_id.into(),
             actions: ActionsType::Pointer {
                 parameters: PointerActionParameters {
-                    pointer_type: match pointer_input_state.subtype {
-                        PointerType::Mouse => PointerType::Mouse,
-                        PointerType::Pen => PointerType::Pen,
-                        PointerType::Touch => PointerType::Touch,
-                    },
+                    pointer_type: copy_pointer_type(&pointer_input_state.subtype),
                 },
                 actions: vec![PointerActionItem::Pointer(PointerAction::Up(
                     PointerUpAction {
@@ -295,9 +347,8 @@ impl Handler {
         let session = self.session.as_mut().unwrap();
 
         let pointer_input_state = match session.input_state_table.get_mut(source_id).unwrap() {
-            InputSourceState::Null => unreachable!(),
-            InputSourceState::Key(_) => unreachable!(),
             InputSourceState::Pointer(pointer_input_state) => pointer_input_state,
+            _ => unreachable!(),
         };
 
         if !pointer_input_state.pressed.contains(&action.button) {
@@ -305,25 +356,6 @@ impl Handler {
         }
         pointer_input_state.pressed.remove(&action.button);
 
-        session.input_cancel_list.push(ActionSequence {
-            id: source_id.into(),
-            actions: ActionsType::Pointer {
-                parameters: PointerActionParameters {
-                    pointer_type: match pointer_input_state.subtype {
-                        PointerType::Mouse => PointerType::Mouse,
-                        PointerType::Pen => PointerType::Pen,
-                        PointerType::Touch => PointerType::Touch,
-                    },
-                },
-                actions: vec![PointerActionItem::Pointer(PointerAction::Down(
-                    PointerDownAction {
-                        button: action.button,
-                        ..Default::default()
-                    },
-                ))],
-            },
-        });
-
         let button = (action.button as u16).into();
         let cmd_msg = WebDriverCommandMsg::MouseButtonAction(
             session.webview_id,
@@ -337,15 +369,42 @@ impl Handler {
             .unwrap();
     }
 
+    /// The in-view center point of an element, in CSS pixels relative to the viewport.
+    fn element_in_view_center_point(&self, element: &str) -> Result<(i64, i64), ErrorStatus> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.browsing_context_script_command(WebDriverScriptCommand::GetElementInViewCenterPoint(
+            element.to_string(),
+            sender,
+        ))
+        .unwrap();
+
+        receiver.recv().unwrap()?.ok_or(ErrorStatus::UnknownError)
+    }
+
+    /// Fail with `move target out of bounds` if a point is outside of the viewport.
+    fn check_in_viewport(&self, x: i64, y: i64) -> Result<(), ErrorStatus> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd_msg =
+            WebDriverCommandMsg::GetWindowSize(self.session.as_ref().unwrap().webview_id, sender);
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+
+        let viewport_size = receiver.recv().unwrap();
+        if x < 0 || x as f32 > viewport_size.width || y < 0 || y as f32 > viewport_size.height {
+            return Err(ErrorStatus::MoveTargetOutOfBounds);
+        }
+        Ok(())
+    }
+
     // https://w3c.github.io/webdriver/#dfn-dispatch-a-pointermove-action
     pub(crate) fn dispatch_pointermove_action(
         &mut self,
         source_id: &str,
         action: &PointerMoveAction,
         tick_duration: u64,
+        tick_start: Instant,
     ) -> Result<(), ErrorStatus> {
-        let tick_start = Instant::now();
-
         // Steps 1 - 2
         let x_offset = action.x;
         let y_offset = action.y;
@@ -359,43 +418,24 @@ impl Handler {
             .get(source_id)
             .unwrap()
         {
-            InputSourceState::Null => unreachable!(),
-            InputSourceState::Key(_) => unreachable!(),
             InputSourceState::Pointer(pointer_input_state) => {
                 (pointer_input_state.x, pointer_input_state.y)
             },
+            _ => unreachable!(),
         };
 
         // Step 5 - 6
         let (x, y) = match action.origin {
             PointerOrigin::Viewport => (x_offset, y_offset),
             PointerOrigin::Pointer => (start_x + x_offset, start_y + y_offset),
-            PointerOrigin::Element(ref x) => {
-                let (sender, receiver) = ipc::channel().unwrap();
-                self.browsing_context_script_command(
-                    WebDriverScriptCommand::GetElementInViewCenterPoint(x.to_string(), sender),
-                )
-                .unwrap();
-
-                let Some(point) = receiver.recv().unwrap()? else {
-                    return Err(ErrorStatus::UnknownError);
-                };
-                point
+            PointerOrigin::Element(ref element) => {
+                let (x, y) = self.element_in_view_center_point(&element.to_string())?;
+                (x + x_offset, y + y_offset)
             },
         };
 
-        let (sender, receiver) = ipc::channel().unwrap();
-        let cmd_msg =
-            WebDriverCommandMsg::GetWindowSize(self.session.as_ref().unwrap().webview_id, sender);
-        self.constellation_chan
-            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
-            .unwrap();
-
         // Steps 7 - 8
-        let viewport_size = receiver.recv().unwrap();
-        if x < 0 || x as f32 > viewport_size.width || y < 0 || y as f32 > viewport_size.height {
-            return Err(ErrorStatus::MoveTargetOutOfBounds);
-        }
+        self.check_in_viewport(x, y)?;
 
         // Step 9
         let duration = match action.duration {
@@ -429,9 +469,8 @@ impl Handler {
     ) {
         let session = self.session.as_mut().unwrap();
         let pointer_input_state = match session.input_state_table.get_mut(source_id).unwrap() {
-            InputSourceState::Null => unreachable!(),
-            InputSourceState::Key(_) => unreachable!(),
             InputSourceState::Pointer(pointer_input_state) => pointer_input_state,
+            _ => unreachable!(),
         };
 
         loop {
@@ -484,4 +523,116 @@ impl Handler {
             thread::sleep(Duration::from_millis(POINTERMOVE_INTERVAL));
         }
     }
+
+    // https://w3c.github.io/webdriver/#dfn-dispatch-a-scroll-action
+    fn dispatch_scroll_action(
+        &mut self,
+        action: &WheelScrollAction,
+        tick_duration: u64,
+        tick_start: Instant,
+    ) -> Result<(), ErrorStatus> {
+        // Steps 1 - 2
+        let (Some(x_offset), Some(y_offset), Some(delta_x), Some(delta_y)) =
+            (action.x, action.y, action.deltaX, action.deltaY)
+        else {
+            return Err(ErrorStatus::InvalidArgument);
+        };
+
+        // Steps 3 - 4
+        let (x, y) = match action.origin {
+            PointerOrigin::Viewport => (x_offset, y_offset),
+            PointerOrigin::Pointer => return Err(ErrorStatus::InvalidArgument),
+            PointerOrigin::Element(ref element) => {
+                let (x, y) = self.element_in_view_center_point(&element.to_string())?;
+                (x + x_offset, y + y_offset)
+            },
+        };
+
+        // Step 5
+        self.check_in_viewport(x, y)?;
+
+        // Step 6
+        let duration = match action.duration {
+            Some(duration) => duration,
+            None => tick_duration,
+        };
+
+        // Step 7
+        if duration > 0 {
+            thread::sleep(Duration::from_millis(WHEELSCROLL_INTERVAL));
+        }
+
+        // Step 8
+        self.perform_a_scroll(duration, x, y, delta_x, delta_y, tick_start);
+
+        // Step 9
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webdriver/#dfn-perform-a-scroll>
+    fn perform_a_scroll(
+        &self,
+        duration: u64,
+        x: i64,
+        y: i64,
+        target_delta_x: i64,
+        target_delta_y: i64,
+        tick_start: Instant,
+    ) {
+        let webview_id = self.session.as_ref().unwrap().webview_id;
+        let mut current_delta_x = 0;
+        let mut current_delta_y = 0;
+
+        loop {
+            // Step 1
+            let time_delta = tick_start.elapsed().as_millis();
+
+            // Step 2
+            let duration_ratio = if duration > 0 {
+                time_delta as f64 / duration as f64
+            } else {
+                1.0
+            };
+
+            // Step 3
+            let last = 1.0 - duration_ratio < 0.001;
+
+            // Step 4
+            let (delta_x, delta_y) = if last {
+                (
+                    target_delta_x - current_delta_x,
+                    target_delta_y - current_delta_y,
+                )
+            } else {
+                (
+                    (duration_ratio * target_delta_x as f64) as i64 - current_delta_x,
+                    (duration_ratio * target_delta_y as f64) as i64 - current_delta_y,
+                )
+            };
+
+            // Step 5
+            if delta_x != 0 || delta_y != 0 {
+                let cmd_msg = WebDriverCommandMsg::WheelScrollAction(
+                    webview_id,
+                    x as f32,
+                    y as f32,
+                    delta_x as f64,
+                    delta_y as f64,
+                );
+                self.constellation_chan
+                    .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+                    .unwrap();
+                current_delta_x += delta_x;
+                current_delta_y += delta_y;
+            }
+
+            // Step 6
+            if last {
+                return;
+            }
+
+            // Step 7
+            thread::sleep(Duration::from_millis(WHEELSCROLL_INTERVAL));
+        }
+    }
 }
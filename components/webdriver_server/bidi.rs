@@ -0,0 +1,1575 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A server for [WebDriver BiDi], the bidirectional protocol of WebDriver, which runs alongside
+//! the HTTP endpoints of the classic protocol. It listens on a WebSocket of its own, and only on
+//! the loopback interface.
+//!
+//! A client either connects to `/session` and creates a session with `session.new`, or connects
+//! to the URL that a classic session returned as its `webSocketUrl` capability, which shares that
+//! session. The commands of the `session`, `browsingContext`, `script` and `network` modules are
+//! supported as far as Servo is able to answer them, and the other commands fail with an
+//! "unknown command" error. Only top-level browsing contexts are reported, and each of them has a
+//! single realm with the same id, which stands for whatever document the context has at the time.
+//!
+//! [WebDriver BiDi]: https://w3c.github.io/webdriver-bidi/
+
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base::id::WebViewId;
+use constellation_traits::ConstellationMsg;
+use crossbeam_channel::{Sender, unbounded};
+use embedder_traits::{WebDriverBiDiEvent, WebDriverCommandMsg, WebDriverInterceptedRequest};
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use log::{info, warn};
+use net_traits::local_server::{self, RejectedRequest};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use servo_config::pref;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+use uuid::Uuid;
+
+use crate::bidi::browsing_context::LoadWaiter;
+use crate::bidi::network::{BlockedRequest, Intercept};
+use crate::capabilities::ServoCapabilities;
+
+mod browsing_context;
+mod network;
+mod script;
+
+/// How long reading from a WebSocket may block before the queued events are sent.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The events that clients are able to subscribe to, by their module.
+const EVENTS: &[(&str, &[&str])] = &[
+    (
+        "browsingContext",
+        &[
+            "browsingContext.contextCreated",
+            "browsingContext.contextDestroyed",
+            "browsingContext.navigationStarted",
+            "browsingContext.domContentLoaded",
+            "browsingContext.load",
+        ],
+    ),
+    (
+        "network",
+        &["network.beforeRequestSent", "network.responseStarted"],
+    ),
+    ("script", &["script.message"]),
+];
+
+/// A command sent by a client.
+#[derive(Deserialize)]
+struct Command {
+    id: u64,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct SuccessReply {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    id: u64,
+    result: Value,
+}
+
+#[derive(Serialize)]
+struct ErrorReply<'a> {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    id: Option<u64>,
+    error: &'static str,
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+struct Event<'a, T> {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Debug)]
+pub(crate) struct BiDiError {
+    error: &'static str,
+    message: String,
+}
+
+impl BiDiError {
+    pub(crate) fn new(error: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            error,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new("invalid argument", message)
+    }
+
+    pub(crate) fn no_such_frame(context: &str) -> Self {
+        Self::new(
+            "no such frame",
+            format!("No browsing context with id {}", context),
+        )
+    }
+
+    fn unknown_command(method: &str) -> Self {
+        Self::new("unknown command", format!("{} is not supported", method))
+    }
+}
+
+pub(crate) type CommandResult = Result<Value, BiDiError>;
+
+/// Read the parameters of a command, which may be left out when none of them are required.
+pub(crate) fn parse_params<T: DeserializeOwned>(params: &Value) -> Result<T, BiDiError> {
+    let params = match params {
+        Value::Null => Value::Object(Map::new()),
+        params => params.clone(),
+    };
+    serde_json::from_value(params).map_err(|error| BiDiError::invalid_argument(error.to_string()))
+}
+
+pub(crate) fn command_result<T: Serialize>(result: T) -> CommandResult {
+    serde_json::to_value(result).map_err(|error| BiDiError::new("unknown error", error.to_string()))
+}
+
+pub(crate) fn empty_result() -> CommandResult {
+    Ok(Value::Object(Map::new()))
+}
+
+/// The time of an event, in milliseconds since the Unix epoch.
+pub(crate) fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A subscription of a session to some events, either in every browsing context or in some of
+/// them.
+struct Subscription {
+    id: String,
+    events: Vec<&'static str>,
+    contexts: Option<Vec<WebViewId>>,
+}
+
+impl Subscription {
+    fn matches(&self, method: &str, context: Option<WebViewId>) -> bool {
+        if !self.events.iter().any(|event| *event == method) {
+            return false;
+        }
+        match (&self.contexts, context) {
+            (None, _) => true,
+            (Some(contexts), Some(context)) => contexts.contains(&context),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+struct Client {
+    sender: Sender<String>,
+    /// The session of the client, which is the classic session that it connected to, or the
+    /// one that it created with `session.new`.
+    session: Option<String>,
+    subscriptions: Vec<Subscription>,
+}
+
+/// A top-level browsing context.
+pub(crate) struct Context {
+    pub id: String,
+    pub url: String,
+    /// The id of the navigation to the current document, if it was started since the context
+    /// was created.
+    pub navigation: Option<String>,
+}
+
+pub(crate) struct BiDiState {
+    constellation_chan: Sender<ConstellationMsg>,
+    pub contexts: HashMap<WebViewId, Context>,
+    clients: HashMap<u64, Client>,
+    next_client_id: u64,
+    /// The ids of the classic sessions that asked for a WebSocket URL.
+    classic_sessions: HashSet<String>,
+    pub intercepts: HashMap<String, Intercept>,
+    /// The requests that wait for a client to decide what to do with them, by their id.
+    pub blocked_requests: HashMap<String, BlockedRequest>,
+    pub load_waiters: Vec<LoadWaiter>,
+    /// The sender that the resource threads are given while requests are intercepted.
+    interceptor: IpcSender<WebDriverInterceptedRequest>,
+    interceptor_installed: bool,
+}
+
+impl BiDiState {
+    fn new(
+        constellation_chan: Sender<ConstellationMsg>,
+        interceptor: IpcSender<WebDriverInterceptedRequest>,
+    ) -> Self {
+        Self {
+            constellation_chan,
+            contexts: HashMap::new(),
+            clients: HashMap::new(),
+            next_client_id: 0,
+            classic_sessions: HashSet::new(),
+            intercepts: HashMap::new(),
+            blocked_requests: HashMap::new(),
+            load_waiters: vec![],
+            interceptor,
+            interceptor_installed: false,
+        }
+    }
+
+    pub(crate) fn send_command(&self, msg: WebDriverCommandMsg) -> Result<(), BiDiError> {
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(msg))
+            .map_err(|_| BiDiError::new("unknown error", "The browser is shutting down"))
+    }
+
+    pub(crate) fn context_id(&self, webview_id: WebViewId) -> Option<&str> {
+        self.contexts
+            .get(&webview_id)
+            .map(|context| context.id.as_str())
+    }
+
+    pub(crate) fn webview_id(&self, context: &str) -> Result<WebViewId, BiDiError> {
+        self.contexts
+            .iter()
+            .find(|(_, candidate)| candidate.id == context)
+            .map(|(webview_id, _)| *webview_id)
+            .ok_or_else(|| BiDiError::no_such_frame(context))
+    }
+
+    /// Send an event to the sessions that subscribed to it.
+    pub(crate) fn emit<T: Serialize>(&self, method: &str, context: Option<WebViewId>, params: &T) {
+        let event = Event {
+            type_: "event",
+            method,
+            params,
+        };
+        let Ok(message) = serde_json::to_string(&event) else {
+            return;
+        };
+        for client in self.clients.values() {
+            if client.session.is_some() &&
+                client
+                    .subscriptions
+                    .iter()
+                    .any(|subscription| subscription.matches(method, context))
+            {
+                let _ = client.sender.send(message.clone());
+            }
+        }
+    }
+
+    /// Whether a session subscribed to an event in a browsing context.
+    pub(crate) fn is_subscribed(&self, method: &str, context: Option<WebViewId>) -> bool {
+        self.clients.values().any(|client| {
+            client
+                .subscriptions
+                .iter()
+                .any(|subscription| subscription.matches(method, context))
+        })
+    }
+
+    /// Intercept the network while there is something to tell the clients about it, and stop
+    /// doing so otherwise, so that requests do not wait for this server for nothing.
+    pub(crate) fn update_interceptor(&mut self) {
+        let needed = !self.intercepts.is_empty() ||
+            self.clients.values().any(|client| {
+                client.subscriptions.iter().any(|subscription| {
+                    subscription
+                        .events
+                        .iter()
+                        .any(|event| event.starts_with("network."))
+                })
+            });
+        if !needed {
+            // Requests carry on once the decision about them is dropped.
+            self.blocked_requests.clear();
+        }
+        if needed == self.interceptor_installed {
+            return;
+        }
+        self.interceptor_installed = needed;
+        let sender = needed.then(|| self.interceptor.clone());
+        let _ = self.send_command(WebDriverCommandMsg::SetNetworkInterceptor(sender));
+    }
+
+    fn add_client(&mut self, sender: Sender<String>, session: Option<String>) -> u64 {
+        let id = self.next_client_id;
+        self.next_client_id += 1;
+        self.clients.insert(
+            id,
+            Client {
+                sender,
+                session,
+                subscriptions: vec![],
+            },
+        );
+        id
+    }
+
+    fn remove_client(&mut self, id: u64) {
+        self.clients.remove(&id);
+        self.intercepts
+            .retain(|_, intercept| intercept.client != id);
+        self.update_interceptor();
+    }
+
+    fn handle_event(&mut self, event: WebDriverBiDiEvent) {
+        match event {
+            WebDriverBiDiEvent::ContextCreated(webview_id) => {
+                browsing_context::context_created(self, webview_id)
+            },
+            WebDriverBiDiEvent::ContextDestroyed(webview_id) => {
+                browsing_context::context_destroyed(self, webview_id)
+            },
+            WebDriverBiDiEvent::NavigationStarted(webview_id, url) => {
+                browsing_context::navigation_started(self, webview_id, url)
+            },
+            WebDriverBiDiEvent::DomContentLoaded(webview_id, url) => {
+                browsing_context::dom_content_loaded(self, webview_id, url)
+            },
+            WebDriverBiDiEvent::Load(webview_id, url) => {
+                browsing_context::load(self, webview_id, url)
+            },
+            WebDriverBiDiEvent::ChannelMessage(webview_id, channel, message) => {
+                script::channel_message(self, webview_id, channel, &message)
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SubscribeParams {
+    events: Vec<String>,
+    contexts: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct UnsubscribeParams {
+    events: Option<Vec<String>>,
+    subscriptions: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewSessionReply {
+    session_id: String,
+    capabilities: Value,
+}
+
+/// The events that a name given to `session.subscribe` stands for, which is either an event or
+/// a whole module.
+fn expand_event_name(name: &str) -> Result<Vec<&'static str>, BiDiError> {
+    for (module, events) in EVENTS {
+        if name == *module {
+            return Ok(events.to_vec());
+        }
+        if let Some(event) = events.iter().find(|event| **event == name) {
+            return Ok(vec![*event]);
+        }
+    }
+    Err(BiDiError::invalid_argument(format!(
+        "{} is not a known event",
+        name
+    )))
+}
+
+/// The WebDriver BiDi server, as seen by the classic WebDriver server, which registers its
+/// sessions with it.
+pub(crate) struct BiDiServer {
+    state: Arc<Mutex<BiDiState>>,
+    port: u16,
+}
+
+impl BiDiServer {
+    /// Listen for connections on the port of the `webdriver_bidi_port` preference, and start
+    /// following the browsing contexts.
+    pub(crate) fn start(constellation_chan: Sender<ConstellationMsg>) -> Option<Self> {
+        let listener = match TcpListener::bind(("127.0.0.1", pref!(webdriver_bidi_port) as u16)) {
+            Ok(listener) => listener,
+            Err(error) => {
+                warn!("Failed to start the WebDriver BiDi server: {}", error);
+                return None;
+            },
+        };
+        let port = listener.local_addr().ok()?.port();
+        info!("WebDriver BiDi server listening on port {}", port);
+
+        let (interceptor, interceptor_receiver) = ipc::channel().ok()?;
+        let (event_sender, event_receiver) = ipc::channel().ok()?;
+        let state = Arc::new(Mutex::new(BiDiState::new(constellation_chan, interceptor)));
+
+        let event_state = state.clone();
+        ROUTER.add_typed_route(
+            event_receiver,
+            Box::new(move |event| {
+                if let Ok(event) = event {
+                    event_state.lock().unwrap().handle_event(event);
+                }
+            }),
+        );
+        let interceptor_state = state.clone();
+        ROUTER.add_typed_route(
+            interceptor_receiver,
+            Box::new(move |request| {
+                if let Ok(request) = request {
+                    network::request_intercepted(&mut interceptor_state.lock().unwrap(), request);
+                }
+            }),
+        );
+        // The browsing contexts are followed from the start, so that they are known once a
+        // client connects.
+        let _ = state
+            .lock()
+            .unwrap()
+            .send_command(WebDriverCommandMsg::SetBiDiEventSender(Some(event_sender)));
+
+        let server_state = state.clone();
+        thread::Builder::new()
+            .name("WebDriverBiDiAcceptor".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let state = server_state.clone();
+                    thread::Builder::new()
+                        .name("WebDriverBiDiClient".to_owned())
+                        .spawn(move || handle_connection(state, stream))
+                        .expect("Thread spawning failed");
+                }
+            })
+            .expect("Thread spawning failed");
+
+        Some(Self { state, port })
+    }
+
+    /// Let clients connect to a classic session, at the URL that is returned.
+    pub(crate) fn register_session(&self, id: &str) -> String {
+        self.state
+            .lock()
+            .unwrap()
+            .classic_sessions
+            .insert(id.to_owned());
+        format!("ws://127.0.0.1:{}/session/{}", self.port, id)
+    }
+
+    /// A classic session was deleted, along with what its clients subscribed to.
+    pub(crate) fn unregister_session(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.classic_sessions.remove(id);
+        for client in state.clients.values_mut() {
+            if client.session.as_deref() == Some(id) {
+                client.session = None;
+                client.subscriptions.clear();
+            }
+        }
+        state.update_interceptor();
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message));
+    *response.status_mut() = status;
+    response
+}
+
+/// Open the WebSocket of a client, and serve it until it goes away.
+fn handle_connection(state: Arc<Mutex<BiDiState>>, stream: TcpStream) {
+    let mut session = None;
+    let check_request = |request: &Request, response: Response| {
+        let host = request
+            .headers()
+            .get("host")
+            .and_then(|host| host.to_str().ok());
+        let origin = request
+            .headers()
+            .get("origin")
+            .map(|origin| String::from_utf8_lossy(origin.as_bytes()));
+        if let Err(rejection) = local_server::check_request(host, origin.as_deref()) {
+            let status = match rejection {
+                RejectedRequest::Host => StatusCode::INTERNAL_SERVER_ERROR,
+                RejectedRequest::Origin(_) => StatusCode::FORBIDDEN,
+            };
+            return Err(error_response(status, rejection.to_string()));
+        }
+        let path = request.uri().path();
+        if path == "/session" {
+            return Ok(response);
+        }
+        match path.strip_prefix("/session/") {
+            Some(id) if state.lock().unwrap().classic_sessions.contains(id) => {
+                session = Some(id.to_owned());
+                Ok(response)
+            },
+            _ => Err(error_response(
+                StatusCode::NOT_FOUND,
+                "No such session".to_owned(),
+            )),
+        }
+    };
+    let Ok(socket) = tungstenite::accept_hdr(stream, check_request) else {
+        return;
+    };
+    run_client(&state, socket, session);
+}
+
+/// Exchange messages with a client until it goes away.
+fn run_client(
+    state: &Arc<Mutex<BiDiState>>,
+    mut socket: WebSocket<TcpStream>,
+    session: Option<String>,
+) {
+    info!("WebDriver BiDi connection established");
+    // Reading times out regularly, so that the events that happen in the meantime are sent.
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(EVENT_POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+    let (sender, receiver) = unbounded();
+    let client = state.lock().unwrap().add_client(sender.clone(), session);
+
+    loop {
+        if receiver
+            .try_iter()
+            .any(|message| socket.send(Message::text(message)).is_err())
+        {
+            break;
+        }
+        match socket.read() {
+            Ok(Message::Text(message)) => {
+                // Commands may wait for the page or for the network, which may in turn wait
+                // for other commands of the client, so each of them is handled on its own.
+                let state = state.clone();
+                let sender = sender.clone();
+                thread::Builder::new()
+                    .name("WebDriverBiDiCommand".to_owned())
+                    .spawn(move || {
+                        let reply = handle_message(&state, client, &message);
+                        let _ = sender.send(reply);
+                    })
+                    .expect("Thread spawning failed");
+            },
+            Ok(_) => {},
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {},
+            Err(_) => break,
+        }
+    }
+
+    info!("WebDriver BiDi connection closed");
+    state.lock().unwrap().remove_client(client);
+}
+
+fn error_reply(id: Option<u64>, error: &BiDiError) -> String {
+    serde_json::to_string(&ErrorReply {
+        type_: "error",
+        id,
+        error: error.error,
+        message: &error.message,
+    })
+    .unwrap_or_default()
+}
+
+/// Handle a command of a client, and return the reply to it.
+fn handle_message(state: &Arc<Mutex<BiDiState>>, client: u64, message: &str) -> String {
+    let command: Command = match serde_json::from_str(message) {
+        Ok(command) => command,
+        Err(error) => {
+            return error_reply(None, &BiDiError::invalid_argument(error.to_string()));
+        },
+    };
+    match handle_command(state, client, &command.method, &command.params) {
+        Ok(result) => serde_json::to_string(&SuccessReply {
+            type_: "success",
+            id: command.id,
+            result,
+        })
+        .unwrap_or_default(),
+        Err(error) => error_reply(Some(command.id), &error),
+    }
+}
+
+fn handle_command(
+    state: &Arc<Mutex<BiDiState>>,
+    client: u64,
+    method: &str,
+    params: &Value,
+) -> CommandResult {
+    let has_session = state
+        .lock()
+        .unwrap()
+        .clients
+        .get(&client)
+        .is_some_and(|client| client.session.is_some());
+    match method {
+        "session.status" => session_status(state),
+        "session.new" => new_session(state, client),
+        _ if !has_session => Err(BiDiError::new(
+            "invalid session id",
+            "The connection has no session",
+        )),
+        "session.end" => end_session(state, client),
+        "session.subscribe" => subscribe(state, client, params),
+        "session.unsubscribe" => unsubscribe(state, client, params),
+        _ if method.starts_with("browsingContext.") => {
+            browsing_context::handle(state, method, params)
+        },
+        _ if method.starts_with("script.") => script::handle(state, method, params),
+        _ if method.starts_with("network.") => network::handle(state, client, method, params),
+        _ => Err(BiDiError::unknown_command(method)),
+    }
+}
+
+// https://w3c.github.io/webdriver-bidi/#command-session-status
+fn session_status(state: &Mutex<BiDiState>) -> CommandResult {
+    let state = state.lock().unwrap();
+    let busy = !state.classic_sessions.is_empty() ||
+        state
+            .clients
+            .values()
+            .any(|client| client.session.is_some());
+    Ok(if busy {
+        json!({ "ready": false, "message": "Not ready for a new session" })
+    } else {
+        json!({ "ready": true, "message": "Ready for a new session" })
+    })
+}
+
+// https://w3c.github.io/webdriver-bidi/#command-session-new
+fn new_session(state: &Mutex<BiDiState>, client: u64) -> CommandResult {
+    let mut state = state.lock().unwrap();
+    let Some(client) = state.clients.get_mut(&client) else {
+        return Err(BiDiError::new("unknown error", "The client is gone"));
+    };
+    if client.session.is_some() {
+        return Err(BiDiError::new(
+            "session not created",
+            "The connection already has a session",
+        ));
+    }
+    let session_id = Uuid::new_v4().to_string();
+    client.session = Some(session_id.clone());
+
+    let capabilities = ServoCapabilities::new();
+    command_result(NewSessionReply {
+        session_id,
+        capabilities: json!({
+            "acceptInsecureCerts": capabilities.accept_insecure_certs,
+            "browserName": capabilities.browser_name,
+            "browserVersion": capabilities.browser_version,
+            "platformName": capabilities.platform_name.unwrap_or("unknown".to_string()),
+            "setWindowRect": capabilities.set_window_rect,
+            "proxy": {},
+        }),
+    })
+}
+
+// https://w3c.github.io/webdriver-bidi/#command-session-end
+fn end_session(state: &Mutex<BiDiState>, client: u64) -> CommandResult {
+    let mut state = state.lock().unwrap();
+    let Some(session) = state
+        .clients
+        .get(&client)
+        .and_then(|client| client.session.clone())
+    else {
+        return Err(BiDiError::new("unknown error", "The client is gone"));
+    };
+    if state.classic_sessions.contains(&session) {
+        return Err(BiDiError::new(
+            "unsupported operation",
+            "A session that was created with WebDriver classic ends with it",
+        ));
+    }
+    if let Some(client) = state.clients.get_mut(&client) {
+        client.session = None;
+        client.subscriptions.clear();
+    }
+    state
+        .intercepts
+        .retain(|_, intercept| intercept.client != client);
+    state.update_interceptor();
+    empty_result()
+}
+
+// https://w3c.github.io/webdriver-bidi/#command-session-subscribe
+fn subscribe(state: &Mutex<BiDiState>, client: u64, params: &Value) -> CommandResult {
+    let params: SubscribeParams = parse_params(params)?;
+    let mut events = vec![];
+    for name in &params.events {
+        events.extend(expand_event_name(name)?);
+    }
+    let mut state = state.lock().unwrap();
+    let contexts = params
+        .contexts
+        .map(|contexts| {
+            contexts
+                .iter()
+                .map(|context| state.webview_id(context))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+    let id = Uuid::new_v4().to_string();
+    if let Some(client) = state.clients.get_mut(&client) {
+        client.subscriptions.push(Subscription {
+            id: id.clone(),
+            events,
+            contexts,
+        });
+    }
+    state.update_interceptor();
+    Ok(json!({ "subscription": id }))
+}
+
+// https://w3c.github.io/webdriver-bidi/#command-session-unsubscribe
+fn unsubscribe(state: &Mutex<BiDiState>, client: u64, params: &Value) -> CommandResult {
+    let params: UnsubscribeParams = parse_params(params)?;
+    let mut state = state.lock().unwrap();
+    let Some(subscriptions) = state
+        .clients
+        .get_mut(&client)
+        .map(|client| &mut client.subscriptions)
+    else {
+        return Err(BiDiError::new("unknown error", "The client is gone"));
+    };
+    if let Some(ids) = params.subscriptions {
+        if let Some(id) = ids.iter().find(|id| {
+            !subscriptions
+                .iter()
+                .any(|subscription| subscription.id == **id)
+        }) {
+            return Err(BiDiError::invalid_argument(format!(
+                "No subscription with id {}",
+                id
+            )));
+        }
+        subscriptions.retain(|subscription| !ids.contains(&subscription.id));
+    } else if let Some(names) = params.events {
+        let mut events = vec![];
+        for name in &names {
+            events.extend(expand_event_name(name)?);
+        }
+        // Only the subscriptions to every browsing context are removed by their events.
+        for subscription in subscriptions.iter_mut() {
+            if subscription.contexts.is_none() {
+                subscription.events.retain(|event| !events.contains(event));
+            }
+        }
+        subscriptions.retain(|subscription| !subscription.events.is_empty());
+    } else {
+        return Err(BiDiError::invalid_argument(
+            "Either events or subscriptions are required",
+        ));
+    }
+    state.update_interceptor();
+    empty_result()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use base::id::{BrowsingContextId, PipelineNamespace, TEST_NAMESPACE, WebViewId};
+    use constellation_traits::ConstellationMsg;
+    use crossbeam_channel::{Receiver, unbounded};
+    use embedder_traits::{
+        WebDriverBiDiEvent, WebDriverCommandMsg, WebDriverInterceptDecision,
+        WebDriverInterceptPhase, WebDriverInterceptedRequest, WebDriverJSValue,
+        WebDriverScriptCommand,
+    };
+    use ipc_channel::ipc::{self, IpcReceiver};
+    use serde_json::{Value, json};
+    use servo_url::ServoUrl;
+
+    use super::{BiDiServer, BiDiState, handle_message, network};
+
+    struct TestServer {
+        state: Arc<Mutex<BiDiState>>,
+        constellation: Receiver<ConstellationMsg>,
+        _interceptor: IpcReceiver<WebDriverInterceptedRequest>,
+    }
+
+    impl TestServer {
+        fn new() -> Self {
+            PipelineNamespace::install(TEST_NAMESPACE);
+            let (constellation_chan, constellation) = unbounded();
+            let (interceptor, interceptor_receiver) = ipc::channel().unwrap();
+            Self {
+                state: Arc::new(Mutex::new(BiDiState::new(constellation_chan, interceptor))),
+                constellation,
+                _interceptor: interceptor_receiver,
+            }
+        }
+
+        fn connect(&self, session: Option<&str>) -> (u64, Receiver<String>) {
+            let (sender, receiver) = unbounded();
+            let client = self
+                .state
+                .lock()
+                .unwrap()
+                .add_client(sender, session.map(str::to_owned));
+            (client, receiver)
+        }
+
+        /// Start a session on a new connection.
+        fn new_session(&self) -> (u64, Receiver<String>) {
+            let (client, events) = self.connect(None);
+            result(
+                &self.state,
+                client,
+                json!({ "id": 0, "method": "session.new", "params": {} }),
+            );
+            (client, events)
+        }
+
+        fn create_context(&self) -> (WebViewId, String) {
+            let webview_id = WebViewId::new();
+            self.state
+                .lock()
+                .unwrap()
+                .handle_event(WebDriverBiDiEvent::ContextCreated(webview_id));
+            let context = self.state.lock().unwrap().contexts[&webview_id].id.clone();
+            (webview_id, context)
+        }
+
+        fn next_command(&self) -> WebDriverCommandMsg {
+            match self.constellation.recv_timeout(Duration::from_secs(5)) {
+                Ok(ConstellationMsg::WebDriverCommand(command)) => command,
+                _ => panic!("A WebDriver command should have been sent"),
+            }
+        }
+    }
+
+    /// Send a command, and return its reply.
+    fn send(state: &Arc<Mutex<BiDiState>>, client: u64, command: Value) -> Value {
+        serde_json::from_str(&handle_message(state, client, &command.to_string())).unwrap()
+    }
+
+    /// Send a command that must succeed, and return its result.
+    fn result(state: &Arc<Mutex<BiDiState>>, client: u64, command: Value) -> Value {
+        let reply = send(state, client, command);
+        assert_eq!(reply["type"], "success", "{reply}");
+        reply["result"].clone()
+    }
+
+    /// Send a command that must fail, and return its error.
+    fn error(state: &Arc<Mutex<BiDiState>>, client: u64, command: Value) -> Value {
+        let reply = send(state, client, command);
+        assert_eq!(reply["type"], "error", "{reply}");
+        reply["error"].clone()
+    }
+
+    fn next_event(events: &Receiver<String>) -> Value {
+        serde_json::from_str(&events.try_recv().expect("An event should have been sent")).unwrap()
+    }
+
+    fn url(url: &str) -> ServoUrl {
+        ServoUrl::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        let server = TestServer::new();
+        let (client, _events) = server.connect(None);
+
+        let reply: Value =
+            serde_json::from_str(&handle_message(&server.state, client, "not json")).unwrap();
+        assert_eq!(reply["type"], "error");
+        assert_eq!(reply["id"], Value::Null);
+        assert_eq!(reply["error"], "invalid argument");
+
+        let reply = send(
+            &server.state,
+            client,
+            json!({ "id": 1, "method": "browsingContext.getTree" }),
+        );
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["error"], "invalid session id");
+
+        let (client, _events) = server.new_session();
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 2, "method": "unknown.method" })
+            ),
+            "unknown command"
+        );
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 3, "method": "session.subscribe", "params": { "events": "load" } })
+            ),
+            "invalid argument"
+        );
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let server = TestServer::new();
+        let (client, _events) = server.connect(None);
+
+        let status = result(
+            &server.state,
+            client,
+            json!({ "id": 1, "method": "session.status" }),
+        );
+        assert_eq!(status["ready"], true);
+
+        let session = result(
+            &server.state,
+            client,
+            json!({ "id": 2, "method": "session.new", "params": {} }),
+        );
+        assert!(session["sessionId"].is_string());
+        assert_eq!(session["capabilities"]["browserName"], "servo");
+        let status = result(
+            &server.state,
+            client,
+            json!({ "id": 3, "method": "session.status" }),
+        );
+        assert_eq!(status["ready"], false);
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 4, "method": "session.new", "params": {} })
+            ),
+            "session not created"
+        );
+
+        result(
+            &server.state,
+            client,
+            json!({ "id": 5, "method": "session.end" }),
+        );
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 6, "method": "browsingContext.getTree" })
+            ),
+            "invalid session id"
+        );
+        let status = result(
+            &server.state,
+            client,
+            json!({ "id": 7, "method": "session.status" }),
+        );
+        assert_eq!(status["ready"], true);
+    }
+
+    #[test]
+    fn test_classic_session() {
+        let server = TestServer::new();
+        let bidi_server = BiDiServer {
+            state: server.state.clone(),
+            port: 4444,
+        };
+        assert_eq!(
+            bidi_server.register_session("classic"),
+            "ws://127.0.0.1:4444/session/classic"
+        );
+        let (client, events) = server.connect(Some("classic"));
+
+        result(
+            &server.state,
+            client,
+            json!({ "id": 1, "method": "session.subscribe", "params": { "events": ["browsingContext"] } }),
+        );
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 2, "method": "session.end" })
+            ),
+            "unsupported operation"
+        );
+
+        // The subscriptions of the session end with it.
+        bidi_server.unregister_session("classic");
+        server.create_context();
+        assert!(events.try_recv().is_err());
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 3, "method": "browsingContext.getTree" })
+            ),
+            "invalid session id"
+        );
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let server = TestServer::new();
+        let (client, events) = server.new_session();
+        let (_, other_events) = server.new_session();
+
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 1, "method": "session.subscribe", "params": { "events": ["unknown"] } })
+            ),
+            "invalid argument"
+        );
+        result(
+            &server.state,
+            client,
+            json!({
+                "id": 2,
+                "method": "session.subscribe",
+                "params": { "events": ["browsingContext.contextCreated"] },
+            }),
+        );
+
+        let (webview_id, context) = server.create_context();
+        let event = next_event(&events);
+        assert_eq!(event["type"], "event");
+        assert_eq!(event["method"], "browsingContext.contextCreated");
+        assert_eq!(event["params"]["context"], context);
+        assert_eq!(event["params"]["url"], "about:blank");
+        // Sessions are only told about the events they subscribed to.
+        assert!(other_events.try_recv().is_err());
+
+        // A subscription to some browsing contexts is only told about their events.
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 3,
+                    "method": "session.subscribe",
+                    "params": { "events": ["browsingContext.load"], "contexts": ["unknown"] },
+                })
+            ),
+            "no such frame"
+        );
+        let subscription = result(
+            &server.state,
+            client,
+            json!({
+                "id": 4,
+                "method": "session.subscribe",
+                "params": { "events": ["browsingContext.load"], "contexts": [context] },
+            }),
+        )["subscription"]
+            .clone();
+        let (other_webview_id, _) = server.create_context();
+        next_event(&events);
+        server
+            .state
+            .lock()
+            .unwrap()
+            .handle_event(WebDriverBiDiEvent::Load(
+                other_webview_id,
+                url("https://example.org/"),
+            ));
+        assert!(events.try_recv().is_err());
+        server
+            .state
+            .lock()
+            .unwrap()
+            .handle_event(WebDriverBiDiEvent::Load(
+                webview_id,
+                url("https://example.com/"),
+            ));
+        let event = next_event(&events);
+        assert_eq!(event["method"], "browsingContext.load");
+        assert_eq!(event["params"]["url"], "https://example.com/");
+
+        // Subscriptions to some browsing contexts are only removed by their id.
+        result(
+            &server.state,
+            client,
+            json!({
+                "id": 5,
+                "method": "session.unsubscribe",
+                "params": { "events": ["browsingContext"] },
+            }),
+        );
+        server.create_context();
+        assert!(events.try_recv().is_err());
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 6,
+                    "method": "session.unsubscribe",
+                    "params": { "subscriptions": ["unknown"] },
+                })
+            ),
+            "invalid argument"
+        );
+        result(
+            &server.state,
+            client,
+            json!({
+                "id": 7,
+                "method": "session.unsubscribe",
+                "params": { "subscriptions": [subscription] },
+            }),
+        );
+        server
+            .state
+            .lock()
+            .unwrap()
+            .handle_event(WebDriverBiDiEvent::Load(
+                webview_id,
+                url("https://example.com/"),
+            ));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_navigation_round_trip() {
+        let server = TestServer::new();
+        let (client, events) = server.new_session();
+        result(
+            &server.state,
+            client,
+            json!({ "id": 1, "method": "session.subscribe", "params": { "events": ["browsingContext"] } }),
+        );
+        let (webview_id, context) = server.create_context();
+        next_event(&events);
+
+        let tree = result(
+            &server.state,
+            client,
+            json!({ "id": 2, "method": "browsingContext.getTree" }),
+        );
+        assert_eq!(tree["contexts"][0]["context"], context);
+        assert_eq!(tree["contexts"][0]["children"], json!([]));
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 3,
+                    "method": "browsingContext.navigate",
+                    "params": { "context": "unknown", "url": "https://example.com/" },
+                })
+            ),
+            "no such frame"
+        );
+
+        // The command waits for the page to load.
+        let navigation = {
+            let state = server.state.clone();
+            let context = context.clone();
+            thread::spawn(move || {
+                result(
+                    &state,
+                    client,
+                    json!({
+                        "id": 4,
+                        "method": "browsingContext.navigate",
+                        "params": { "context": context, "url": "https://example.com/", "wait": "complete" },
+                    }),
+                )
+            })
+        };
+        let WebDriverCommandMsg::LoadUrl(loaded_webview_id, loaded_url, _) = server.next_command()
+        else {
+            panic!("The browsing context should be navigated");
+        };
+        assert_eq!(loaded_webview_id, webview_id);
+        assert_eq!(loaded_url.as_str(), "https://example.com/");
+
+        let example = url("https://example.com/");
+        for event in [
+            WebDriverBiDiEvent::NavigationStarted(webview_id, example.clone()),
+            WebDriverBiDiEvent::DomContentLoaded(webview_id, example.clone()),
+            WebDriverBiDiEvent::Load(webview_id, example),
+        ] {
+            server.state.lock().unwrap().handle_event(event);
+        }
+        let reply = navigation.join().unwrap();
+
+        let started = next_event(&events);
+        assert_eq!(started["method"], "browsingContext.navigationStarted");
+        assert_eq!(started["params"]["context"], context);
+        assert!(started["params"]["navigation"].is_string());
+        for method in ["browsingContext.domContentLoaded", "browsingContext.load"] {
+            let event = next_event(&events);
+            assert_eq!(event["method"], method);
+            assert_eq!(
+                event["params"]["navigation"],
+                started["params"]["navigation"]
+            );
+        }
+        assert_eq!(reply["navigation"], started["params"]["navigation"]);
+        assert_eq!(reply["url"], "https://example.com/");
+
+        server
+            .state
+            .lock()
+            .unwrap()
+            .handle_event(WebDriverBiDiEvent::ContextDestroyed(webview_id));
+        let event = next_event(&events);
+        assert_eq!(event["method"], "browsingContext.contextDestroyed");
+        assert_eq!(event["params"]["context"], context);
+    }
+
+    #[test]
+    fn test_script_round_trip() {
+        let server = TestServer::new();
+        let (client, events) = server.new_session();
+        let (webview_id, context) = server.create_context();
+
+        let realms = result(
+            &server.state,
+            client,
+            json!({ "id": 1, "method": "script.getRealms" }),
+        );
+        assert_eq!(
+            realms["realms"],
+            json!([{ "realm": context, "origin": "null", "type": "window", "context": context }])
+        );
+
+        for (result, expected) in [
+            (
+                vec![
+                    WebDriverJSValue::Boolean(true),
+                    WebDriverJSValue::Number(2.),
+                ],
+                json!({
+                    "type": "success",
+                    "result": { "type": "number", "value": 2. },
+                    "realm": context,
+                }),
+            ),
+            (
+                vec![
+                    WebDriverJSValue::Boolean(false),
+                    WebDriverJSValue::String("Error: failed".to_owned()),
+                ],
+                json!({
+                    "type": "exception",
+                    "exceptionDetails": {
+                        "columnNumber": 0,
+                        "exception": { "type": "error" },
+                        "lineNumber": 0,
+                        "stackTrace": { "callFrames": [] },
+                        "text": "Error: failed",
+                    },
+                    "realm": context,
+                }),
+            ),
+        ] {
+            let evaluation = {
+                let state = server.state.clone();
+                let context = context.clone();
+                thread::spawn(move || {
+                    result(
+                        &state,
+                        client,
+                        json!({
+                            "id": 2,
+                            "method": "script.evaluate",
+                            "params": {
+                                "expression": "1 + \"1\"",
+                                "target": { "context": context },
+                                "awaitPromise": false,
+                            },
+                        }),
+                    )
+                })
+            };
+            let WebDriverCommandMsg::ScriptCommand(
+                browsing_context_id,
+                WebDriverScriptCommand::ExecuteAsyncScript(script, reply),
+            ) = server.next_command()
+            else {
+                panic!("The script should be run");
+            };
+            assert_eq!(browsing_context_id, BrowsingContextId::from(webview_id));
+            assert!(script.contains(r#"(() => (0, eval)("1 + \"1\"")).apply(undefined, [])"#));
+            reply.send(Ok(WebDriverJSValue::ArrayLike(result))).unwrap();
+            assert_eq!(evaluation.join().unwrap(), expected);
+        }
+
+        // Channels are functions that send their argument to the subscribed sessions.
+        let call = {
+            let state = server.state.clone();
+            let context = context.clone();
+            thread::spawn(move || {
+                result(
+                    &state,
+                    client,
+                    json!({
+                        "id": 3,
+                        "method": "script.callFunction",
+                        "params": {
+                            "functionDeclaration": "(send, values) => send(values)",
+                            "target": { "realm": context },
+                            "awaitPromise": true,
+                            "arguments": [
+                                { "type": "channel", "value": { "channel": "channel" } },
+                                { "type": "array", "value": [
+                                    { "type": "number", "value": "-0" },
+                                    { "type": "bigint", "value": "10" },
+                                    { "type": "map", "value": [["key", { "type": "null" }]] },
+                                ] },
+                            ],
+                        },
+                    }),
+                )
+            })
+        };
+        let WebDriverCommandMsg::ScriptCommand(
+            _,
+            WebDriverScriptCommand::ExecuteAsyncScript(script, reply),
+        ) = server.next_command()
+        else {
+            panic!("The script should be run");
+        };
+        assert!(script.contains(
+            r#"((send, values) => send(values)).apply(undefined, [((message) => window.webdriverChannelMessage("channel", message)), [-0, BigInt("10"), new Map([["key", null]])]])"#
+        ));
+        reply
+            .send(Ok(WebDriverJSValue::ArrayLike(vec![
+                WebDriverJSValue::Boolean(true),
+                WebDriverJSValue::Undefined,
+            ])))
+            .unwrap();
+        assert_eq!(
+            call.join().unwrap()["result"],
+            json!({ "type": "undefined" })
+        );
+
+        result(
+            &server.state,
+            client,
+            json!({ "id": 4, "method": "session.subscribe", "params": { "events": ["script.message"] } }),
+        );
+        server
+            .state
+            .lock()
+            .unwrap()
+            .handle_event(WebDriverBiDiEvent::ChannelMessage(
+                webview_id,
+                "channel".to_owned(),
+                WebDriverJSValue::String("message".to_owned()),
+            ));
+        let event = next_event(&events);
+        assert_eq!(event["method"], "script.message");
+        assert_eq!(
+            event["params"],
+            json!({
+                "channel": "channel",
+                "data": { "type": "string", "value": "message" },
+                "source": { "realm": context, "context": context },
+            })
+        );
+
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 5,
+                    "method": "script.callFunction",
+                    "params": {
+                        "functionDeclaration": "() => {}",
+                        "target": { "context": context },
+                        "awaitPromise": false,
+                        "arguments": [{ "type": "number", "value": "eleven" }],
+                    },
+                })
+            ),
+            "invalid argument"
+        );
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 6,
+                    "method": "script.evaluate",
+                    "params": { "expression": "1", "target": {}, "awaitPromise": false },
+                })
+            ),
+            "invalid argument"
+        );
+    }
+
+    fn intercepted_request(
+        url: &str,
+        phase: WebDriverInterceptPhase,
+    ) -> (
+        WebDriverInterceptedRequest,
+        IpcReceiver<WebDriverInterceptDecision>,
+    ) {
+        let (reply, decision) = ipc::channel().unwrap();
+        let request = WebDriverInterceptedRequest {
+            request_id: "request".to_owned(),
+            webview_id: None,
+            phase,
+            url: ServoUrl::parse(url).unwrap(),
+            method: "GET".to_owned(),
+            headers: vec![("accept".to_owned(), "*/*".to_owned())],
+            status: None,
+            response_headers: vec![],
+            reply,
+        };
+        (request, decision)
+    }
+
+    #[test]
+    fn test_network_round_trip() {
+        let server = TestServer::new();
+        let (client, events) = server.new_session();
+
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 1, "method": "network.addIntercept", "params": { "phases": ["authRequired"] } })
+            ),
+            "unsupported operation"
+        );
+        let intercept = result(
+            &server.state,
+            client,
+            json!({
+                "id": 2,
+                "method": "network.addIntercept",
+                "params": {
+                    "phases": ["beforeRequestSent"],
+                    "urlPatterns": [{ "type": "pattern", "hostname": "example.com" }],
+                },
+            }),
+        )["intercept"]
+            .clone();
+        assert!(matches!(
+            server.next_command(),
+            WebDriverCommandMsg::SetNetworkInterceptor(Some(_))
+        ));
+        result(
+            &server.state,
+            client,
+            json!({ "id": 3, "method": "session.subscribe", "params": { "events": ["network"] } }),
+        );
+
+        // Requests that no intercept matches carry on.
+        let (request, decision) = intercepted_request(
+            "https://example.org/",
+            WebDriverInterceptPhase::BeforeRequestSent,
+        );
+        network::request_intercepted(&mut server.state.lock().unwrap(), request);
+        assert!(matches!(
+            decision.recv(),
+            Ok(WebDriverInterceptDecision::Continue { method: None, .. })
+        ));
+        let event = next_event(&events);
+        assert_eq!(event["method"], "network.beforeRequestSent");
+        assert_eq!(event["params"]["isBlocked"], false);
+
+        let (request, decision) = intercepted_request(
+            "https://example.com/",
+            WebDriverInterceptPhase::BeforeRequestSent,
+        );
+        network::request_intercepted(&mut server.state.lock().unwrap(), request);
+        let event = next_event(&events);
+        assert_eq!(event["params"]["isBlocked"], true);
+        assert_eq!(event["params"]["intercepts"], json!([intercept]));
+        assert_eq!(event["params"]["request"]["request"], "request");
+        assert_eq!(event["params"]["request"]["method"], "GET");
+        assert_eq!(
+            event["params"]["request"]["headers"],
+            json!([{ "name": "accept", "value": { "type": "string", "value": "*/*" } }])
+        );
+        assert!(decision.try_recv().is_err());
+
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({
+                    "id": 4,
+                    "method": "network.continueResponse",
+                    "params": { "request": "request" },
+                })
+            ),
+            "invalid argument"
+        );
+        result(
+            &server.state,
+            client,
+            json!({
+                "id": 5,
+                "method": "network.continueRequest",
+                "params": {
+                    "request": "request",
+                    "method": "POST",
+                    "headers": [{ "name": "x-test", "value": { "type": "base64", "value": "dmFsdWU=" } }],
+                },
+            }),
+        );
+        let Ok(WebDriverInterceptDecision::Continue {
+            method, headers, ..
+        }) = decision.recv()
+        else {
+            panic!("The request should carry on");
+        };
+        assert_eq!(method.as_deref(), Some("POST"));
+        assert_eq!(
+            headers,
+            Some(vec![("x-test".to_owned(), "value".to_owned())])
+        );
+        assert_eq!(
+            error(
+                &server.state,
+                client,
+                json!({ "id": 6, "method": "network.failRequest", "params": { "request": "request" } })
+            ),
+            "no such request"
+        );
+
+        // Requests are followed as long as the session is subscribed to their events.
+        result(
+            &server.state,
+            client,
+            json!({
+                "id": 7,
+                "method": "network.removeIntercept",
+                "params": { "intercept": intercept },
+            }),
+        );
+        assert!(server.constellation.try_recv().is_err());
+        result(
+            &server.state,
+            client,
+            json!({ "id": 8, "method": "session.unsubscribe", "params": { "events": ["network"] } }),
+        );
+        assert!(matches!(
+            server.next_command(),
+            WebDriverCommandMsg::SetNetworkInterceptor(None)
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `browsingContext` module, which creates, navigates and closes the top-level browsing
+//! contexts, and tells about their navigations.
+//!
+//! <https://w3c.github.io/webdriver-bidi/#module-browsingContext>
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base::id::WebViewId;
+use crossbeam_channel::{Sender, after, select, unbounded};
+use embedder_traits::{WebDriverCommandMsg, WebDriverLoadStatus};
+use ipc_channel::ipc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use servo_url::ServoUrl;
+use uuid::Uuid;
+
+use crate::bidi::{
+    BiDiError, BiDiState, CommandResult, Context, command_result, empty_result, parse_params,
+    timestamp,
+};
+
+/// How long a navigation may take before the command that waits for it fails.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How far a navigation has to get before a command that started it returns.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+enum ReadinessState {
+    #[default]
+    None,
+    Interactive,
+    Complete,
+}
+
+/// A command that waits for the navigation of a browsing context.
+pub(crate) struct LoadWaiter {
+    webview_id: WebViewId,
+    wait: ReadinessState,
+    /// Whether the navigation started, so that the end of an earlier one is not mistaken for
+    /// it.
+    started: bool,
+    /// Where to send the id of the navigation, once it got far enough.
+    sender: Sender<Option<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Info {
+    context: String,
+    url: String,
+    children: Option<Vec<Info>>,
+    parent: Option<String>,
+    user_context: &'static str,
+    original_opener: Option<String>,
+    client_window: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NavigationInfo<'a> {
+    context: &'a str,
+    navigation: Option<&'a str>,
+    timestamp: u64,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+struct NavigateReply {
+    navigation: Option<String>,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct ContextParams {
+    context: String,
+}
+
+#[derive(Deserialize)]
+struct NavigateParams {
+    context: String,
+    url: String,
+    #[serde(default)]
+    wait: ReadinessState,
+}
+
+#[derive(Deserialize)]
+struct ReloadParams {
+    context: String,
+    #[serde(default)]
+    wait: ReadinessState,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateParams {
+    reference_context: Option<String>,
+}
+
+fn info(context: &Context, children: Option<Vec<Info>>) -> Info {
+    Info {
+        context: context.id.clone(),
+        url: context.url.clone(),
+        children,
+        parent: None,
+        user_context: "default",
+        original_opener: None,
+        client_window: String::new(),
+    }
+}
+
+fn emit_navigation_event(state: &BiDiState, method: &str, webview_id: WebViewId) {
+    let Some(context) = state.contexts.get(&webview_id) else {
+        return;
+    };
+    let event = NavigationInfo {
+        context: &context.id,
+        navigation: context.navigation.as_deref(),
+        timestamp: timestamp(),
+        url: &context.url,
+    };
+    state.emit(method, Some(webview_id), &event);
+}
+
+/// Tell the commands that wait for a browsing context that its navigation got as far as
+/// `readiness`.
+fn notify_load_waiters(state: &mut BiDiState, webview_id: WebViewId, readiness: ReadinessState) {
+    let navigation = state
+        .contexts
+        .get(&webview_id)
+        .and_then(|context| context.navigation.clone());
+    state.load_waiters.retain(|waiter| {
+        if waiter.webview_id != webview_id || !waiter.started || waiter.wait > readiness {
+            return true;
+        }
+        let _ = waiter.sender.send(navigation.clone());
+        false
+    });
+}
+
+/// The browsing context of a webview, which is added once either the event of its creation or
+/// the reply to `browsingContext.create` arrives, whichever comes first.
+fn ensure_context(state: &mut BiDiState, webview_id: WebViewId) -> &Context {
+    state.contexts.entry(webview_id).or_insert_with(|| Context {
+        id: Uuid::new_v4().to_string(),
+        url: "about:blank".to_owned(),
+        navigation: None,
+    })
+}
+
+pub(crate) fn context_created(state: &mut BiDiState, webview_id: WebViewId) {
+    let created = info(ensure_context(state, webview_id), None);
+    state.emit("browsingContext.contextCreated", Some(webview_id), &created);
+}
+
+pub(crate) fn context_destroyed(state: &mut BiDiState, webview_id: WebViewId) {
+    let Some(context) = state.contexts.remove(&webview_id) else {
+        return;
+    };
+    // The commands that wait for the context fail once their sender is dropped.
+    state
+        .load_waiters
+        .retain(|waiter| waiter.webview_id != webview_id);
+    state.emit(
+        "browsingContext.contextDestroyed",
+        Some(webview_id),
+        &info(&context, None),
+    );
+}
+
+pub(crate) fn navigation_started(state: &mut BiDiState, webview_id: WebViewId, url: ServoUrl) {
+    let Some(context) = state.contexts.get_mut(&webview_id) else {
+        return;
+    };
+    context.navigation = Some(Uuid::new_v4().to_string());
+    context.url = url.into_string();
+    for waiter in &mut state.load_waiters {
+        if waiter.webview_id == webview_id {
+            waiter.started = true;
+        }
+    }
+    emit_navigation_event(state, "browsingContext.navigationStarted", webview_id);
+}
+
+pub(crate) fn dom_content_loaded(state: &mut BiDiState, webview_id: WebViewId, url: ServoUrl) {
+    let Some(context) = state.contexts.get_mut(&webview_id) else {
+        return;
+    };
+    context.url = url.into_string();
+    emit_navigation_event(state, "browsingContext.domContentLoaded", webview_id);
+    notify_load_waiters(state, webview_id, ReadinessState::Interactive);
+}
+
+pub(crate) fn load(state: &mut BiDiState, webview_id: WebViewId, url: ServoUrl) {
+    let Some(context) = state.contexts.get_mut(&webview_id) else {
+        return;
+    };
+    context.url = url.into_string();
+    emit_navigation_event(state, "browsingContext.load", webview_id);
+    notify_load_waiters(state, webview_id, ReadinessState::Complete);
+}
+
+/// Start a navigation of a browsing context, and wait for it to get as far as `wait`.
+fn navigate(
+    state: &Mutex<BiDiState>,
+    context: &str,
+    wait: ReadinessState,
+    message: impl FnOnce(WebViewId) -> WebDriverCommandMsg,
+) -> CommandResult {
+    let (sender, receiver) = unbounded();
+    let url = {
+        let mut state = state.lock().unwrap();
+        let webview_id = state.webview_id(context)?;
+        if wait != ReadinessState::None {
+            state.load_waiters.push(LoadWaiter {
+                webview_id,
+                wait,
+                started: false,
+                sender,
+            });
+        }
+        state.send_command(message(webview_id))?;
+        state.contexts[&webview_id].url.clone()
+    };
+    if wait == ReadinessState::None {
+        return command_result(NavigateReply {
+            navigation: None,
+            url,
+        });
+    }
+    select! {
+        recv(receiver) -> navigation => {
+            let navigation = navigation.map_err(|_| BiDiError::no_such_frame(context))?;
+            let url = state
+                .lock()
+                .unwrap()
+                .contexts
+                .values()
+                .find(|candidate| candidate.id == context)
+                .map_or(url, |context| context.url.clone());
+            command_result(NavigateReply { navigation, url })
+        },
+        recv(after(LOAD_TIMEOUT)) -> _ => Err(BiDiError::new("timeout", "Navigation timed out")),
+    }
+}
+
+/// The browsing context commands that are supported:
+///
+/// - `getTree`: Returns the top-level browsing contexts
+///
+/// - `navigate`, `reload`: Navigate a browsing context, and wait for the new document to be
+///   parsed or loaded, as asked for
+///
+/// - `create`: Opens a new tab, and returns its browsing context
+///
+/// - `close`: Closes a browsing context
+///
+/// - `activate`: Focuses a browsing context
+pub(crate) fn handle(state: &Mutex<BiDiState>, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "browsingContext.getTree" => {
+            let state = state.lock().unwrap();
+            let contexts: Vec<_> = state
+                .contexts
+                .values()
+                .map(|context| info(context, Some(vec![])))
+                .collect();
+            Ok(json!({ "contexts": contexts }))
+        },
+
+        "browsingContext.navigate" => {
+            let params: NavigateParams = parse_params(params)?;
+            let url = ServoUrl::parse(&params.url)
+                .map_err(|_| BiDiError::invalid_argument("Invalid URL"))?;
+            // The load is followed through the events of the browsing context instead.
+            let (load_sender, _) = ipc::channel::<WebDriverLoadStatus>().unwrap();
+            navigate(state, &params.context, params.wait, |webview_id| {
+                WebDriverCommandMsg::LoadUrl(webview_id, url, load_sender)
+            })
+        },
+
+        "browsingContext.reload" => {
+            let params: ReloadParams = parse_params(params)?;
+            let (load_sender, _) = ipc::channel::<WebDriverLoadStatus>().unwrap();
+            navigate(state, &params.context, params.wait, |webview_id| {
+                WebDriverCommandMsg::Refresh(webview_id, load_sender)
+            })
+        },
+
+        "browsingContext.create" => {
+            let params: CreateParams = parse_params(params)?;
+            let (sender, receiver) = ipc::channel().unwrap();
+            let (load_sender, _) = ipc::channel().unwrap();
+            {
+                let state = state.lock().unwrap();
+                let reference = match params.reference_context {
+                    Some(context) => state.webview_id(&context)?,
+                    None => *state.contexts.keys().next().ok_or_else(|| {
+                        BiDiError::new("unknown error", "There is no browsing context to open from")
+                    })?,
+                };
+                state.send_command(WebDriverCommandMsg::NewWebView(
+                    reference,
+                    sender,
+                    load_sender,
+                ))?;
+            }
+            let webview_id = receiver
+                .recv()
+                .map_err(|_| BiDiError::new("unknown error", "The tab was not opened"))?;
+            let context = ensure_context(&mut state.lock().unwrap(), webview_id)
+                .id
+                .clone();
+            Ok(json!({ "context": context }))
+        },
+
+        "browsingContext.close" => {
+            let params: ContextParams = parse_params(params)?;
+            let state = state.lock().unwrap();
+            let webview_id = state.webview_id(&params.context)?;
+            state.send_command(WebDriverCommandMsg::CloseWebView(webview_id))?;
+            empty_result()
+        },
+
+        "browsingContext.activate" => {
+            let params: ContextParams = parse_params(params)?;
+            let state = state.lock().unwrap();
+            let webview_id = state.webview_id(&params.context)?;
+            state.send_command(WebDriverCommandMsg::FocusWebView(webview_id))?;
+            empty_result()
+        },
+
+        _ => Err(BiDiError::unknown_command(method)),
+    }
+}
@@ -0,0 +1,541 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `network` module, which tells about the HTTP requests and the start of their responses,
+//! and lets clients block them at either point to change, fail or answer them. Requests are only
+//! followed while a session subscribed to the events of the module or added an intercept.
+//!
+//! <https://w3c.github.io/webdriver-bidi/#module-network>
+
+use std::sync::Mutex;
+
+use base::id::WebViewId;
+use base64::Engine;
+use embedder_traits::{
+    WebDriverInterceptDecision, WebDriverInterceptPhase, WebDriverInterceptedRequest,
+};
+use ipc_channel::ipc::IpcSender;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use servo_url::ServoUrl;
+use uuid::Uuid;
+
+use crate::bidi::{BiDiError, BiDiState, CommandResult, empty_result, parse_params, timestamp};
+
+/// A pattern of the URLs of the requests that an intercept blocks.
+///
+/// <https://w3c.github.io/webdriver-bidi/#type-network-UrlPattern>
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum UrlPattern {
+    String {
+        pattern: String,
+    },
+    Pattern {
+        protocol: Option<String>,
+        hostname: Option<String>,
+        port: Option<String>,
+        pathname: Option<String>,
+        search: Option<String>,
+    },
+}
+
+impl UrlPattern {
+    fn matches(&self, url: &ServoUrl) -> bool {
+        match self {
+            UrlPattern::String { pattern } => pattern == url.as_str(),
+            UrlPattern::Pattern {
+                protocol,
+                hostname,
+                port,
+                pathname,
+                search,
+            } => {
+                protocol.as_ref().is_none_or(|protocol| {
+                    protocol
+                        .trim_end_matches(':')
+                        .eq_ignore_ascii_case(url.scheme())
+                }) && hostname.as_ref().is_none_or(|hostname| {
+                    url.host_str()
+                        .is_some_and(|host| host.eq_ignore_ascii_case(hostname))
+                }) && port.as_ref().is_none_or(|port| {
+                    url.port_or_known_default()
+                        .is_some_and(|url_port| url_port.to_string() == *port)
+                }) && pathname.as_ref().is_none_or(|pathname| {
+                    url.path() == pathname || url.path().strip_prefix('/') == Some(pathname)
+                }) && search.as_ref().is_none_or(|search| {
+                    url.query().unwrap_or("") == search.trim_start_matches('?')
+                })
+            },
+        }
+    }
+}
+
+/// A rule of a session that blocks the requests that match it, at some phases.
+pub(crate) struct Intercept {
+    /// The client whose session added the intercept.
+    pub client: u64,
+    phases: Vec<WebDriverInterceptPhase>,
+    url_patterns: Vec<UrlPattern>,
+    contexts: Option<Vec<WebViewId>>,
+}
+
+impl Intercept {
+    fn matches(&self, request: &WebDriverInterceptedRequest) -> bool {
+        self.phases.contains(&request.phase) &&
+            (self.url_patterns.is_empty() ||
+                self.url_patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(&request.url))) &&
+            self.contexts.as_ref().is_none_or(|contexts| {
+                request
+                    .webview_id
+                    .is_some_and(|webview_id| contexts.contains(&webview_id))
+            })
+    }
+}
+
+/// A request that waits for a client to decide what to do with it. It carries on unchanged if
+/// it is dropped.
+pub(crate) struct BlockedRequest {
+    phase: WebDriverInterceptPhase,
+    reply: IpcSender<WebDriverInterceptDecision>,
+}
+
+/// A value of a header, or of a body.
+///
+/// <https://w3c.github.io/webdriver-bidi/#type-network-BytesValue>
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+enum BytesValue {
+    String(String),
+    Base64(String),
+}
+
+impl BytesValue {
+    fn into_bytes(self) -> Result<Vec<u8>, BiDiError> {
+        match self {
+            BytesValue::String(string) => Ok(string.into_bytes()),
+            BytesValue::Base64(encoded) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|_| BiDiError::invalid_argument("Invalid base64 value")),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Header {
+    name: String,
+    value: BytesValue,
+}
+
+fn headers(pairs: &[(String, String)]) -> Vec<Header> {
+    pairs
+        .iter()
+        .map(|(name, value)| Header {
+            name: name.clone(),
+            value: BytesValue::String(value.clone()),
+        })
+        .collect()
+}
+
+fn header_pairs(headers: Vec<Header>) -> Result<Vec<(String, String)>, BiDiError> {
+    headers
+        .into_iter()
+        .map(|header| {
+            let value = header.value.into_bytes()?;
+            Ok((header.name, String::from_utf8_lossy(&value).into_owned()))
+        })
+        .collect()
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FetchTimingInfo {
+    time_origin: f64,
+    request_time: f64,
+    redirect_start: f64,
+    redirect_end: f64,
+    fetch_start: f64,
+    dns_start: f64,
+    dns_end: f64,
+    connect_start: f64,
+    connect_end: f64,
+    tls_start: f64,
+    request_start: f64,
+    response_start: f64,
+    response_end: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestData {
+    request: String,
+    url: String,
+    method: String,
+    headers: Vec<Header>,
+    cookies: Vec<Value>,
+    headers_size: u64,
+    body_size: Option<u64>,
+    timings: FetchTimingInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseData {
+    url: String,
+    protocol: &'static str,
+    status: u16,
+    status_text: String,
+    from_cache: bool,
+    headers: Vec<Header>,
+    mime_type: String,
+    bytes_received: u64,
+    headers_size: Option<u64>,
+    body_size: Option<u64>,
+    content: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NetworkEvent {
+    context: Option<String>,
+    is_blocked: bool,
+    navigation: Option<String>,
+    redirect_count: u32,
+    request: RequestData,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    intercepts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initiator: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<ResponseData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddInterceptParams {
+    phases: Vec<String>,
+    #[serde(default)]
+    url_patterns: Vec<UrlPattern>,
+    contexts: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct RemoveInterceptParams {
+    intercept: String,
+}
+
+#[derive(Deserialize)]
+struct RequestParams {
+    request: String,
+}
+
+#[derive(Deserialize)]
+struct ContinueRequestParams {
+    request: String,
+    method: Option<String>,
+    headers: Option<Vec<Header>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContinueResponseParams {
+    request: String,
+    status_code: Option<u16>,
+    reason_phrase: Option<String>,
+    headers: Option<Vec<Header>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProvideResponseParams {
+    request: String,
+    status_code: Option<u16>,
+    reason_phrase: Option<String>,
+    headers: Option<Vec<Header>>,
+    body: Option<BytesValue>,
+}
+
+fn event_name(phase: WebDriverInterceptPhase) -> &'static str {
+    match phase {
+        WebDriverInterceptPhase::BeforeRequestSent => "network.beforeRequestSent",
+        WebDriverInterceptPhase::ResponseStarted => "network.responseStarted",
+    }
+}
+
+/// Tell the sessions about a request, or the start of its response, and block it if one of
+/// their intercepts matches it.
+pub(crate) fn request_intercepted(state: &mut BiDiState, request: WebDriverInterceptedRequest) {
+    let intercepts: Vec<_> = state
+        .intercepts
+        .iter()
+        .filter(|(_, intercept)| intercept.matches(&request))
+        .map(|(id, _)| id.clone())
+        .collect();
+    let is_blocked = !intercepts.is_empty();
+    let method = event_name(request.phase);
+
+    if state.is_subscribed(method, request.webview_id) {
+        let context = request
+            .webview_id
+            .and_then(|webview_id| state.contexts.get(&webview_id));
+        let response = request.status.as_ref().map(|(status, status_text)| {
+            let mime_type = request
+                .response_headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            ResponseData {
+                url: request.url.to_string(),
+                protocol: "http/1.1",
+                status: *status,
+                status_text: status_text.clone(),
+                from_cache: false,
+                headers: headers(&request.response_headers),
+                mime_type,
+                bytes_received: 0,
+                headers_size: None,
+                body_size: None,
+                content: json!({ "size": 0 }),
+            }
+        });
+        let event = NetworkEvent {
+            context: context.map(|context| context.id.clone()),
+            is_blocked,
+            navigation: context.and_then(|context| context.navigation.clone()),
+            redirect_count: 0,
+            request: RequestData {
+                request: request.request_id.clone(),
+                url: request.url.to_string(),
+                method: request.method.clone(),
+                headers: headers(&request.headers),
+                cookies: vec![],
+                headers_size: 0,
+                body_size: None,
+                timings: FetchTimingInfo::default(),
+            },
+            timestamp: timestamp(),
+            intercepts,
+            initiator: (request.phase == WebDriverInterceptPhase::BeforeRequestSent)
+                .then(|| json!({ "type": "other" })),
+            response,
+        };
+        state.emit(method, request.webview_id, &event);
+    }
+
+    if is_blocked {
+        state.blocked_requests.insert(
+            request.request_id,
+            BlockedRequest {
+                phase: request.phase,
+                reply: request.reply,
+            },
+        );
+    } else {
+        let _ = request.reply.send(WebDriverInterceptDecision::Continue {
+            method: None,
+            status: None,
+            headers: None,
+        });
+    }
+}
+
+/// Let a blocked request go on as the client decided.
+fn decide(
+    state: &Mutex<BiDiState>,
+    request: &str,
+    phase: Option<WebDriverInterceptPhase>,
+    decision: WebDriverInterceptDecision,
+) -> CommandResult {
+    let mut state = state.lock().unwrap();
+    let Some(blocked) = state.blocked_requests.get(request) else {
+        return Err(BiDiError::new(
+            "no such request",
+            format!("No blocked request with id {}", request),
+        ));
+    };
+    if phase.is_some_and(|phase| phase != blocked.phase) {
+        return Err(BiDiError::invalid_argument(format!(
+            "The request is blocked in the {} phase",
+            event_name(blocked.phase)
+        )));
+    }
+    if let Some(blocked) = state.blocked_requests.remove(request) {
+        let _ = blocked.reply.send(decision);
+    }
+    empty_result()
+}
+
+fn status(status_code: Option<u16>, reason_phrase: Option<String>) -> Option<(u16, String)> {
+    status_code.map(|code| {
+        let reason = reason_phrase.unwrap_or_else(|| {
+            http::StatusCode::from_u16(code)
+                .ok()
+                .and_then(|status| status.canonical_reason())
+                .unwrap_or("")
+                .to_owned()
+        });
+        (code, reason)
+    })
+}
+
+/// The network commands that are supported:
+///
+/// - `addIntercept`, `removeIntercept`: Start and stop blocking the requests that match some
+///   URL patterns, in the `beforeRequestSent` or `responseStarted` phase
+///
+/// - `continueRequest`, `continueResponse`: Let a blocked request carry on, with another method
+///   or other headers, or with another status or other headers for its response
+///
+/// - `failRequest`: Fails a blocked request with a network error
+///
+/// - `provideResponse`: Answers a blocked request with the given response
+pub(crate) fn handle(
+    state: &Mutex<BiDiState>,
+    client: u64,
+    method: &str,
+    params: &Value,
+) -> CommandResult {
+    match method {
+        "network.addIntercept" => {
+            let params: AddInterceptParams = parse_params(params)?;
+            let phases = params
+                .phases
+                .iter()
+                .map(|phase| match phase.as_str() {
+                    "beforeRequestSent" => Ok(WebDriverInterceptPhase::BeforeRequestSent),
+                    "responseStarted" => Ok(WebDriverInterceptPhase::ResponseStarted),
+                    _ => Err(BiDiError::new(
+                        "unsupported operation",
+                        format!("Requests can not be intercepted in the {} phase", phase),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if phases.is_empty() {
+                return Err(BiDiError::invalid_argument(
+                    "At least one phase is required",
+                ));
+            }
+            // The patterns match against serialized URLs.
+            let url_patterns = params
+                .url_patterns
+                .into_iter()
+                .map(|pattern| match pattern {
+                    UrlPattern::String { pattern } => ServoUrl::parse(&pattern)
+                        .map(|url| UrlPattern::String {
+                            pattern: url.into_string(),
+                        })
+                        .map_err(|_| BiDiError::invalid_argument("Invalid URL pattern")),
+                    pattern => Ok(pattern),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut state = state.lock().unwrap();
+            let contexts = params
+                .contexts
+                .map(|contexts| {
+                    contexts
+                        .iter()
+                        .map(|context| state.webview_id(context))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?;
+            let id = Uuid::new_v4().to_string();
+            state.intercepts.insert(
+                id.clone(),
+                Intercept {
+                    client,
+                    phases,
+                    url_patterns,
+                    contexts,
+                },
+            );
+            state.update_interceptor();
+            Ok(json!({ "intercept": id }))
+        },
+
+        "network.removeIntercept" => {
+            let params: RemoveInterceptParams = parse_params(params)?;
+            let mut state = state.lock().unwrap();
+            if state.intercepts.remove(&params.intercept).is_none() {
+                return Err(BiDiError::new(
+                    "no such intercept",
+                    format!("No intercept with id {}", params.intercept),
+                ));
+            }
+            state.update_interceptor();
+            empty_result()
+        },
+
+        "network.continueRequest" => {
+            let params: ContinueRequestParams = parse_params(params)?;
+            let headers = params.headers.map(header_pairs).transpose()?;
+            decide(
+                state,
+                &params.request,
+                Some(WebDriverInterceptPhase::BeforeRequestSent),
+                WebDriverInterceptDecision::Continue {
+                    method: params.method,
+                    status: None,
+                    headers,
+                },
+            )
+        },
+
+        "network.continueResponse" => {
+            let params: ContinueResponseParams = parse_params(params)?;
+            let headers = params.headers.map(header_pairs).transpose()?;
+            decide(
+                state,
+                &params.request,
+                Some(WebDriverInterceptPhase::ResponseStarted),
+                WebDriverInterceptDecision::Continue {
+                    method: None,
+                    status: status(params.status_code, params.reason_phrase),
+                    headers,
+                },
+            )
+        },
+
+        "network.failRequest" => {
+            let params: RequestParams = parse_params(params)?;
+            decide(
+                state,
+                &params.request,
+                None,
+                WebDriverInterceptDecision::Fail,
+            )
+        },
+
+        "network.provideResponse" => {
+            let params: ProvideResponseParams = parse_params(params)?;
+            let headers = params.headers.map(header_pairs).transpose()?;
+            let body = params
+                .body
+                .map(BytesValue::into_bytes)
+                .transpose()?
+                .unwrap_or_default();
+            let status = status(
+                Some(params.status_code.unwrap_or(200)),
+                params.reason_phrase,
+            )
+            .unwrap_or_default();
+            decide(
+                state,
+                &params.request,
+                None,
+                WebDriverInterceptDecision::Provide {
+                    status,
+                    headers: headers.unwrap_or_default(),
+                    body,
+                },
+            )
+        },
+
+        _ => Err(BiDiError::unknown_command(method)),
+    }
+}
@@ -0,0 +1,435 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `script` module, which runs JavaScript in the browsing contexts and passes on the
+//! messages that they send through channels. Values are sent by value, and objects are not
+//! kept alive for the client, so they have no handle.
+//!
+//! <https://w3c.github.io/webdriver-bidi/#module-script>
+
+use std::sync::Mutex;
+
+use base::id::{BrowsingContextId, WebViewId};
+use embedder_traits::{
+    WebDriverCommandMsg, WebDriverJSError, WebDriverJSValue, WebDriverScriptCommand,
+};
+use ipc_channel::ipc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use servo_url::ServoUrl;
+
+use crate::bidi::{BiDiError, BiDiState, CommandResult, command_result, parse_params};
+
+#[derive(Deserialize)]
+struct Target {
+    context: Option<String>,
+    realm: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvaluateParams {
+    expression: String,
+    target: Target,
+    await_promise: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallFunctionParams {
+    function_declaration: String,
+    target: Target,
+    await_promise: bool,
+    #[serde(default)]
+    arguments: Vec<Value>,
+    this: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct GetRealmsParams {
+    context: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RealmInfo {
+    realm: String,
+    origin: String,
+    #[serde(rename = "type")]
+    type_: &'static str,
+    context: String,
+}
+
+#[derive(Serialize)]
+struct Source<'a> {
+    realm: &'a str,
+    context: &'a str,
+}
+
+#[derive(Serialize)]
+struct MessageEvent<'a> {
+    channel: &'a str,
+    data: Value,
+    source: Source<'a>,
+}
+
+/// The JavaScript source of a number that may not be representable in JSON.
+fn number_source(value: &Value) -> Result<String, BiDiError> {
+    match value {
+        Value::Number(number) => Ok(number.to_string()),
+        Value::String(special)
+            if matches!(special.as_str(), "NaN" | "-0" | "Infinity" | "-Infinity") =>
+        {
+            Ok(special.clone())
+        },
+        _ => Err(BiDiError::invalid_argument("Invalid number")),
+    }
+}
+
+fn string_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, BiDiError> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| BiDiError::invalid_argument(format!("Expected a string {}", field)))
+}
+
+fn list_sources(value: &Value) -> Result<Vec<String>, BiDiError> {
+    value
+        .as_array()
+        .ok_or_else(|| BiDiError::invalid_argument("Expected a list of values"))?
+        .iter()
+        .map(local_value_source)
+        .collect()
+}
+
+/// The sources of the key and value of each entry of a mapping, whose keys are either strings or
+/// values themselves.
+fn entry_sources(value: &Value) -> Result<Vec<(String, String)>, BiDiError> {
+    value
+        .as_array()
+        .ok_or_else(|| BiDiError::invalid_argument("Expected a list of entries"))?
+        .iter()
+        .map(|entry| match entry.as_array().map(Vec::as_slice) {
+            Some([key @ Value::String(_), value]) => {
+                Ok((key.to_string(), local_value_source(value)?))
+            },
+            Some([key, value]) => Ok((local_value_source(key)?, local_value_source(value)?)),
+            _ => Err(BiDiError::invalid_argument("Expected a key and a value")),
+        })
+        .collect()
+}
+
+/// The JavaScript source of a value that a client gave, which is described at
+/// <https://w3c.github.io/webdriver-bidi/#type-script-LocalValue>. A channel becomes a function
+/// that sends its argument to the clients that subscribed to `script.message`.
+fn local_value_source(local_value: &Value) -> Result<String, BiDiError> {
+    if local_value.get("sharedId").is_some() {
+        return Err(BiDiError::new(
+            "no such node",
+            "Nodes are not known by their shared id",
+        ));
+    }
+    if local_value.get("handle").is_some() {
+        return Err(BiDiError::new(
+            "no such handle",
+            "Objects are not kept alive for the client",
+        ));
+    }
+    let type_ = string_field(local_value, "type")?;
+    let value = local_value.get("value").unwrap_or(&Value::Null);
+    // JSON is valid JavaScript.
+    Ok(match type_ {
+        "undefined" => "undefined".to_owned(),
+        "null" => "null".to_owned(),
+        "string" => Value::String(string_field(local_value, "value")?.to_owned()).to_string(),
+        "number" => number_source(value)?,
+        "boolean" => value
+            .as_bool()
+            .ok_or_else(|| BiDiError::invalid_argument("Expected a boolean value"))?
+            .to_string(),
+        "bigint" => format!(
+            "BigInt({})",
+            Value::String(string_field(local_value, "value")?.to_owned())
+        ),
+        "array" => format!("[{}]", list_sources(value)?.join(", ")),
+        "set" => format!("new Set([{}])", list_sources(value)?.join(", ")),
+        "object" => {
+            let entries: Vec<_> = entry_sources(value)?
+                .into_iter()
+                .map(|(key, value)| format!("[{}]: {}", key, value))
+                .collect();
+            format!("({{{}}})", entries.join(", "))
+        },
+        "map" => {
+            let entries: Vec<_> = entry_sources(value)?
+                .into_iter()
+                .map(|(key, value)| format!("[{}, {}]", key, value))
+                .collect();
+            format!("new Map([{}])", entries.join(", "))
+        },
+        "date" => format!(
+            "new Date({})",
+            Value::String(string_field(local_value, "value")?.to_owned())
+        ),
+        "regexp" => format!(
+            "new RegExp({}, {})",
+            Value::String(string_field(value, "pattern")?.to_owned()),
+            Value::String(
+                value
+                    .get("flags")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_owned()
+            ),
+        ),
+        "channel" => format!(
+            "((message) => window.webdriverChannelMessage({}, message))",
+            Value::String(string_field(value, "channel")?.to_owned()),
+        ),
+        _ => {
+            return Err(BiDiError::invalid_argument(format!(
+                "{} is not a known type of value",
+                type_
+            )));
+        },
+    })
+}
+
+fn number_remote_value(number: f64) -> Value {
+    if number.is_nan() {
+        json!({ "type": "number", "value": "NaN" })
+    } else if number == f64::INFINITY {
+        json!({ "type": "number", "value": "Infinity" })
+    } else if number == f64::NEG_INFINITY {
+        json!({ "type": "number", "value": "-Infinity" })
+    } else if number == 0. && number.is_sign_negative() {
+        json!({ "type": "number", "value": "-0" })
+    } else {
+        json!({ "type": "number", "value": number })
+    }
+}
+
+/// A value of the page, as it is described to the client.
+///
+/// <https://w3c.github.io/webdriver-bidi/#type-script-RemoteValue>
+fn remote_value(value: &WebDriverJSValue) -> Value {
+    match value {
+        WebDriverJSValue::Undefined => json!({ "type": "undefined" }),
+        WebDriverJSValue::Null => json!({ "type": "null" }),
+        WebDriverJSValue::Boolean(boolean) => json!({ "type": "boolean", "value": boolean }),
+        WebDriverJSValue::Int(integer) => json!({ "type": "number", "value": integer }),
+        WebDriverJSValue::Number(number) => number_remote_value(*number),
+        WebDriverJSValue::String(string) => json!({ "type": "string", "value": string }),
+        WebDriverJSValue::Element(element) => json!({ "type": "node", "sharedId": element.0 }),
+        WebDriverJSValue::ShadowRoot(shadow_root) => {
+            json!({ "type": "node", "sharedId": shadow_root.0 })
+        },
+        WebDriverJSValue::Frame(_) | WebDriverJSValue::Window(_) => json!({ "type": "window" }),
+        WebDriverJSValue::ArrayLike(values) => {
+            let values: Vec<_> = values.iter().map(remote_value).collect();
+            json!({ "type": "array", "value": values })
+        },
+        WebDriverJSValue::Object(entries) => {
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|(key, value)| json!([key, remote_value(value)]))
+                .collect();
+            json!({ "type": "object", "value": entries })
+        },
+    }
+}
+
+/// The browsing context that a script runs in, which is also its realm.
+fn target_context(
+    state: &Mutex<BiDiState>,
+    target: &Target,
+) -> Result<(WebViewId, String), BiDiError> {
+    let state = state.lock().unwrap();
+    match (&target.context, &target.realm) {
+        (Some(context), _) => Ok((state.webview_id(context)?, context.clone())),
+        (None, Some(realm)) => state
+            .webview_id(realm)
+            .map(|webview_id| (webview_id, realm.clone()))
+            .map_err(|_| BiDiError::new("no such frame", format!("No realm with id {}", realm))),
+        (None, None) => Err(BiDiError::invalid_argument(
+            "Either a context or a realm is required",
+        )),
+    }
+}
+
+fn exception_result(realm: &str, text: String) -> CommandResult {
+    Ok(json!({
+        "type": "exception",
+        "exceptionDetails": {
+            "columnNumber": 0,
+            "exception": { "type": "error" },
+            "lineNumber": 0,
+            "stackTrace": { "callFrames": [] },
+            "text": text,
+        },
+        "realm": realm,
+    }))
+}
+
+/// Call a function in a browsing context, and report what it returned or threw. The result is
+/// sent back through `webdriverCallback`, as a pair of whether the call succeeded and its value
+/// or the description of its exception.
+fn call_function(
+    state: &Mutex<BiDiState>,
+    target: &Target,
+    function: &str,
+    this: &str,
+    arguments: &[String],
+    await_promise: bool,
+) -> CommandResult {
+    let (webview_id, realm) = target_context(state, target)?;
+    let call = format!("({}).apply({}, [{}])", function, this, arguments.join(", "));
+    let script = if await_promise {
+        format!(
+            "Promise.resolve().then(() => {}).then(\
+                 (value) => window.webdriverCallback([true, value]), \
+                 (error) => window.webdriverCallback([false, String(error)]));",
+            call
+        )
+    } else {
+        format!(
+            "(() => {{ \
+                 let result; \
+                 try {{ result = [true, {}]; }} catch (error) {{ result = [false, String(error)]; }} \
+                 window.webdriverCallback(result); \
+             }})();",
+            call
+        )
+    };
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    state
+        .lock()
+        .unwrap()
+        .send_command(WebDriverCommandMsg::ScriptCommand(
+            BrowsingContextId::from(webview_id),
+            WebDriverScriptCommand::ExecuteAsyncScript(script, sender),
+        ))?;
+    let result = receiver
+        .recv()
+        .unwrap_or(Err(WebDriverJSError::BrowsingContextNotFound));
+    match result {
+        Ok(WebDriverJSValue::ArrayLike(values)) => match values.as_slice() {
+            [WebDriverJSValue::Boolean(true), value] => Ok(json!({
+                "type": "success",
+                "result": remote_value(value),
+                "realm": realm,
+            })),
+            [
+                WebDriverJSValue::Boolean(false),
+                WebDriverJSValue::String(text),
+            ] => exception_result(&realm, text.clone()),
+            _ => Err(BiDiError::new("unknown error", "Unexpected result")),
+        },
+        Ok(_) => Err(BiDiError::new("unknown error", "Unexpected result")),
+        // Values that can not be sent back are described by their type only.
+        Err(WebDriverJSError::UnknownType) => Ok(json!({
+            "type": "success",
+            "result": { "type": "object" },
+            "realm": realm,
+        })),
+        Err(WebDriverJSError::JSError) => {
+            exception_result(&realm, "The script is not valid".to_owned())
+        },
+        Err(WebDriverJSError::BrowsingContextNotFound) => Err(BiDiError::no_such_frame(&realm)),
+        Err(WebDriverJSError::StaleElementReference) | Err(WebDriverJSError::Timeout) => {
+            Err(BiDiError::new("unknown error", "The script did not finish"))
+        },
+    }
+}
+
+/// Tell the sessions that a page sent a message through a channel.
+pub(crate) fn channel_message(
+    state: &BiDiState,
+    webview_id: WebViewId,
+    channel: String,
+    message: &WebDriverJSValue,
+) {
+    let Some(context) = state.context_id(webview_id) else {
+        return;
+    };
+    let event = MessageEvent {
+        channel: &channel,
+        data: remote_value(message),
+        source: Source {
+            realm: context,
+            context,
+        },
+    };
+    state.emit("script.message", Some(webview_id), &event);
+}
+
+/// The script commands that are supported:
+///
+/// - `evaluate`: Evaluates an expression in a browsing context, and waits for the promise that
+///   it returns to settle, if asked to
+///
+/// - `callFunction`: Calls a function with the given arguments, which may be channels
+///
+/// - `getRealms`: Returns the realm of each browsing context
+pub(crate) fn handle(state: &Mutex<BiDiState>, method: &str, params: &Value) -> CommandResult {
+    match method {
+        "script.evaluate" => {
+            let params: EvaluateParams = parse_params(params)?;
+            let expression = Value::String(params.expression).to_string();
+            call_function(
+                state,
+                &params.target,
+                &format!("() => (0, eval)({})", expression),
+                "undefined",
+                &[],
+                params.await_promise,
+            )
+        },
+
+        "script.callFunction" => {
+            let params: CallFunctionParams = parse_params(params)?;
+            let this = params
+                .this
+                .as_ref()
+                .map_or(Ok("undefined".to_owned()), local_value_source)?;
+            let arguments = params
+                .arguments
+                .iter()
+                .map(local_value_source)
+                .collect::<Result<Vec<_>, _>>()?;
+            call_function(
+                state,
+                &params.target,
+                &params.function_declaration,
+                &this,
+                &arguments,
+                params.await_promise,
+            )
+        },
+
+        "script.getRealms" => {
+            let params: GetRealmsParams = parse_params(params)?;
+            let state = state.lock().unwrap();
+            if let Some(context) = &params.context {
+                state.webview_id(context)?;
+            }
+            let realms: Vec<_> = state
+                .contexts
+                .values()
+                .filter(|context| params.context.as_ref().is_none_or(|id| *id == context.id))
+                .map(|context| RealmInfo {
+                    realm: context.id.clone(),
+                    origin: ServoUrl::parse(&context.url)
+                        .map(|url| url.origin().ascii_serialization())
+                        .unwrap_or_default(),
+                    type_: "window",
+                    context: context.id.clone(),
+                })
+                .collect();
+            command_result(json!({ "realms": realms }))
+        },
+
+        _ => Err(BiDiError::unknown_command(method)),
+    }
+}
@@ -79,7 +79,7 @@ impl BrowserCapabilities for ServoCapabilities {
         &mut self,
         _: &serde_json::Map<std::string::String, Value>,
     ) -> Result<bool, WebDriverError> {
-        todo!()
+        Ok(true)
     }
 
     fn webauthn_virtual_authenticators(
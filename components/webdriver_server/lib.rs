@@ -7,13 +7,14 @@
 #![deny(unsafe_code)]
 
 mod actions;
+mod bidi;
 mod capabilities;
 
 use std::borrow::ToOwned;
 use std::collections::{BTreeMap, HashMap};
 use std::io::Cursor;
 use std::net::{SocketAddr, SocketAddrV4};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{env, fmt, mem, process, thread};
 
 use base::id::{BrowsingContextId, WebViewId};
@@ -23,8 +24,9 @@ use constellation_traits::{ConstellationMsg, TraversalDirection};
 use cookie::{CookieBuilder, Expiration};
 use crossbeam_channel::{Receiver, Sender, after, select, unbounded};
 use embedder_traits::{
-    WebDriverCommandMsg, WebDriverCookieError, WebDriverFrameId, WebDriverJSError,
-    WebDriverJSResult, WebDriverJSValue, WebDriverLoadStatus, WebDriverScriptCommand,
+    AnimationClockRequest, WebDriverCommandMsg, WebDriverCookieError, WebDriverFrameId,
+    WebDriverJSError, WebDriverJSResult, WebDriverJSValue, WebDriverLoadStatus,
+    WebDriverScriptCommand,
 };
 use euclid::{Rect, Size2D};
 use http::method::Method;
@@ -33,14 +35,13 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use keyboard_types::webdriver::send_keys;
 use log::{debug, info};
-use pixels::PixelFormat;
+use pixels::{Image, PixelFormat};
 use serde::de::{Deserializer, MapAccess, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use servo_config::prefs::{self, PrefValue, Preferences};
 use servo_url::ServoUrl;
-use style_traits::CSSPixel;
 use uuid::Uuid;
 use webdriver::actions::{
     ActionSequence, PointerDownAction, PointerMoveAction, PointerOrigin, PointerType,
@@ -53,7 +54,9 @@ use webdriver::command::{
     SwitchToFrameParameters, SwitchToWindowParameters, TimeoutsParameters, WebDriverCommand,
     WebDriverExtensionCommand, WebDriverMessage, WindowRectParameters,
 };
-use webdriver::common::{Cookie, Date, LocatorStrategy, Parameters, WebElement};
+use webdriver::common::{
+    Cookie, Date, ELEMENT_KEY, LocatorStrategy, Parameters, SHADOW_KEY, ShadowRoot, WebElement,
+};
 use webdriver::error::{ErrorStatus, WebDriverError, WebDriverResult};
 use webdriver::httpapi::WebDriverExtensionRoute;
 use webdriver::response::{
@@ -63,6 +66,7 @@ use webdriver::response::{
 use webdriver::server::{self, Session, SessionTeardownKind, WebDriverHandler};
 
 use crate::actions::{InputSourceState, PointerInputState};
+use crate::bidi::BiDiServer;
 
 fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
     vec![
@@ -81,6 +85,16 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::GET,
+            "/session/{sessionId}/servo/screenshot/full",
+            ServoExtensionRoute::TakeFullPageScreenshot,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/clock",
+            ServoExtensionRoute::ControlAnimationClock,
+        ),
     ]
 }
 
@@ -101,7 +115,8 @@ fn cookie_msg_to_cookie(cookie: cookie::Cookie) -> Cookie {
 }
 
 pub fn start_server(port: u16, constellation_chan: Sender<ConstellationMsg>) {
-    let handler = Handler::new(constellation_chan);
+    let bidi = BiDiServer::start(constellation_chan.clone());
+    let handler = Handler::new(constellation_chan, bidi);
     thread::Builder::new()
         .name("WebDriverHttpServer".to_owned())
         .spawn(move || {
@@ -190,6 +205,8 @@ struct Handler {
     session: Option<WebDriverSession>,
     constellation_chan: Sender<ConstellationMsg>,
     resize_timeout: u32,
+    /// The WebDriver BiDi server, which sessions may share with clients that connect to it.
+    bidi: Option<BiDiServer>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -198,6 +215,8 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    TakeFullPageScreenshot,
+    ControlAnimationClock,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -221,6 +240,14 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::TakeFullPageScreenshot => {
+                ServoExtensionCommand::TakeFullPageScreenshot
+            },
+            ServoExtensionRoute::ControlAnimationClock => {
+                let parameters: AnimationClockParameters =
+                    serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::ControlAnimationClock(parameters)
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -232,6 +259,8 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    TakeFullPageScreenshot,
+    ControlAnimationClock(AnimationClockParameters),
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -240,6 +269,8 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::TakeFullPageScreenshot => None,
+            ServoExtensionCommand::ControlAnimationClock(ref x) => serde_json::to_value(x).ok(),
         }
     }
 }
@@ -260,6 +291,7 @@ impl Serialize for SendableWebDriverJSValue {
             WebDriverJSValue::Number(x) => serializer.serialize_f64(x),
             WebDriverJSValue::String(ref x) => serializer.serialize_str(x),
             WebDriverJSValue::Element(ref x) => x.serialize(serializer),
+            WebDriverJSValue::ShadowRoot(ref x) => x.serialize(serializer),
             WebDriverJSValue::Frame(ref x) => x.serialize(serializer),
             WebDriverJSValue::Window(ref x) => x.serialize(serializer),
             WebDriverJSValue::ArrayLike(ref x) => x
@@ -363,6 +395,18 @@ struct SetPrefsParameters {
     prefs: Vec<(String, WebDriverPrefValue)>,
 }
 
+/// The parameters of the command that controls the clock of the animations and timers of the
+/// current browsing context, for instance `{"action": "step", "time": 16}`. Times are in
+/// milliseconds.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum AnimationClockParameters {
+    Pause,
+    Resume,
+    Step { time: f64 },
+    Set { time: f64 },
+}
+
 fn map_to_vec<'de, D>(de: D) -> Result<Vec<(String, WebDriverPrefValue)>, D::Error>
 where
     D: Deserializer<'de>,
@@ -400,7 +444,7 @@ impl<'de> Visitor<'de> for TupleVecMapVisitor {
 }
 
 impl Handler {
-    pub fn new(constellation_chan: Sender<ConstellationMsg>) -> Handler {
+    pub fn new(constellation_chan: Sender<ConstellationMsg>, bidi: Option<BiDiServer>) -> Handler {
         // Create a pair of both an IPC and a threaded channel,
         // keep the IPC sender to clone and pass to the constellation for each load,
         // and keep a threaded receiver to block on an incoming load-status.
@@ -415,6 +459,7 @@ impl Handler {
             session: None,
             constellation_chan,
             resize_timeout: 500,
+            bidi,
         }
     }
 
@@ -585,6 +630,19 @@ impl Handler {
                         json!(servo_capabilities.set_window_rect),
                     );
 
+                    // https://w3c.github.io/webdriver-bidi/#establishing
+                    if processed.get("webSocketUrl") == Some(&json!(true)) {
+                        match self.bidi {
+                            Some(ref bidi) => {
+                                let url = bidi.register_session(&session.id.to_string());
+                                processed.insert("webSocketUrl".to_string(), json!(url));
+                            },
+                            None => {
+                                processed.remove("webSocketUrl");
+                            },
+                        }
+                    }
+
                     let response =
                         NewSessionResponse::new(session.id.to_string(), Value::Object(processed));
                     self.session = Some(session);
@@ -602,10 +660,16 @@ impl Handler {
     }
 
     fn handle_delete_session(&mut self) -> WebDriverResult<WebDriverResponse> {
-        self.session = None;
+        self.end_session();
         Ok(WebDriverResponse::DeleteSession)
     }
 
+    fn end_session(&mut self) {
+        if let (Some(session), Some(bidi)) = (self.session.take(), &self.bidi) {
+            bidi.unregister_session(&session.id.to_string());
+        }
+    }
+
     // https://w3c.github.io/webdriver/#status
     fn handle_status(&self) -> WebDriverResult<WebDriverResponse> {
         Ok(WebDriverResponse::Generic(ValueResponse(
@@ -995,13 +1059,6 @@ impl Handler {
         &mut self,
         frame_id: WebDriverFrameId,
     ) -> WebDriverResult<WebDriverResponse> {
-        if let WebDriverFrameId::Short(_) = frame_id {
-            return Err(WebDriverError::new(
-                ErrorStatus::UnsupportedOperation,
-                "Selecting frame by id not supported",
-            ));
-        }
-
         let (sender, receiver) = ipc::channel().unwrap();
         let cmd = WebDriverScriptCommand::GetBrowsingContextId(frame_id, sender);
         self.browsing_context_script_command(cmd)?;
@@ -1171,6 +1228,87 @@ impl Handler {
         }
     }
 
+    // https://w3c.github.io/webdriver/#find-elements-from-shadow-root
+    fn handle_find_elements_from_shadow_root(
+        &self,
+        shadow_root: &ShadowRoot,
+        parameters: &LocatorParameters,
+    ) -> WebDriverResult<Vec<String>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+
+        match parameters.using {
+            LocatorStrategy::CSSSelector => {
+                let cmd = WebDriverScriptCommand::FindShadowElementsCSS(
+                    parameters.value.clone(),
+                    shadow_root.0.clone(),
+                    sender,
+                );
+                self.browsing_context_script_command(cmd)?;
+            },
+            LocatorStrategy::LinkText | LocatorStrategy::PartialLinkText => {
+                let cmd = WebDriverScriptCommand::FindShadowElementsLinkText(
+                    parameters.value.clone(),
+                    shadow_root.0.clone(),
+                    parameters.using == LocatorStrategy::PartialLinkText,
+                    sender,
+                );
+                self.browsing_context_script_command(cmd)?;
+            },
+            LocatorStrategy::TagName => {
+                let cmd = WebDriverScriptCommand::FindShadowElementsTagName(
+                    parameters.value.clone(),
+                    shadow_root.0.clone(),
+                    sender,
+                );
+                self.browsing_context_script_command(cmd)?;
+            },
+            _ => {
+                return Err(WebDriverError::new(
+                    ErrorStatus::UnsupportedOperation,
+                    "Unsupported locator strategy",
+                ));
+            },
+        }
+
+        receiver
+            .recv()
+            .unwrap()
+            .map_err(|error| WebDriverError::new(error, ""))
+    }
+
+    // https://w3c.github.io/webdriver/#find-element-from-shadow-root
+    fn handle_find_element_from_shadow_root(
+        &self,
+        shadow_root: &ShadowRoot,
+        parameters: &LocatorParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let value = self
+            .handle_find_elements_from_shadow_root(shadow_root, parameters)?
+            .into_iter()
+            .next()
+            .map(|x| serde_json::to_value(WebElement(x)).unwrap());
+        Ok(WebDriverResponse::Generic(ValueResponse(
+            serde_json::to_value(value)?,
+        )))
+    }
+
+    // https://w3c.github.io/webdriver/#get-element-shadow-root
+    fn handle_get_shadow_root(&self, element: &WebElement) -> WebDriverResult<WebDriverResponse> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd = WebDriverScriptCommand::GetElementShadowRoot(element.to_string(), sender);
+        self.browsing_context_script_command(cmd)?;
+        match receiver.recv().unwrap() {
+            Ok(Some(shadow_root)) => Ok(WebDriverResponse::Generic(ValueResponse(
+                serde_json::to_value(ShadowRoot(shadow_root))?,
+            ))),
+            Ok(None) => Err(WebDriverError::new(
+                ErrorStatus::NoSuchShadowRoot,
+                "Element has no shadow root",
+            )),
+            Err(error) => Err(WebDriverError::new(error, "")),
+        }
+    }
+
     // https://w3c.github.io/webdriver/webdriver-spec.html#get-element-rect
     fn handle_element_rect(&self, element: &WebElement) -> WebDriverResult<WebDriverResponse> {
         let (sender, receiver) = ipc::channel().unwrap();
@@ -1600,20 +1738,23 @@ impl Handler {
 
                     // Steps 8.7 - 8.8
                     let pointer_down_action = PointerDownAction {
-                        button: 1,
+                        button: 0,
                         ..Default::default()
                     };
 
                     // Steps 8.9 - 8.10
                     let pointer_up_action = PointerUpAction {
-                        button: 1,
+                        button: 0,
                         ..Default::default()
                     };
 
                     // Step 8.11
-                    if let Err(error) =
-                        self.dispatch_pointermove_action(&id, &pointer_move_action, 0)
-                    {
+                    if let Err(error) = self.dispatch_pointermove_action(
+                        &id,
+                        &pointer_move_action,
+                        0,
+                        Instant::now(),
+                    ) {
                         return Err(WebDriverError::new(error, ""));
                     }
 
@@ -1636,7 +1777,12 @@ impl Handler {
         }
     }
 
-    fn take_screenshot(&self, rect: Option<Rect<f32, CSSPixel>>) -> WebDriverResult<String> {
+    /// Take a screenshot with the message that `screenshot_message` makes, retrying until
+    /// the compositor is able to, and encode it as PNG in base64.
+    fn take_screenshot(
+        &self,
+        screenshot_message: impl Fn(IpcSender<Option<Image>>) -> WebDriverCommandMsg,
+    ) -> WebDriverResult<String> {
         let mut img = None;
 
         let interval = 1000;
@@ -1645,8 +1791,7 @@ impl Handler {
         for _ in 0..iterations {
             let (sender, receiver) = ipc::channel().unwrap();
 
-            let cmd_msg =
-                WebDriverCommandMsg::TakeScreenshot(self.session()?.webview_id, rect, sender);
+            let cmd_msg = screenshot_message(sender);
             self.constellation_chan
                 .send(ConstellationMsg::WebDriverCommand(cmd_msg))
                 .unwrap();
@@ -1686,7 +1831,10 @@ impl Handler {
     }
 
     fn handle_take_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
-        let encoded = self.take_screenshot(None)?;
+        let webview_id = self.session()?.webview_id;
+        let encoded = self.take_screenshot(|sender| {
+            WebDriverCommandMsg::TakeScreenshot(webview_id, None, sender)
+        })?;
 
         Ok(WebDriverResponse::Generic(ValueResponse(
             serde_json::to_value(encoded)?,
@@ -1704,16 +1852,61 @@ impl Handler {
 
         match receiver.recv().unwrap() {
             Ok(rect) => {
-                let encoded = self.take_screenshot(Some(Rect::from_untyped(&rect)))?;
+                // The parts of the element that are scrolled out of view are rendered too.
+                let webview_id = self.session()?.webview_id;
+                let rect = Rect::from_untyped(&rect);
+                let encoded = self.take_screenshot(|sender| {
+                    WebDriverCommandMsg::TakePageScreenshot(webview_id, Some(rect), sender)
+                })?;
 
                 Ok(WebDriverResponse::Generic(ValueResponse(
                     serde_json::to_value(encoded)?,
                 )))
             },
-            Err(_) => Err(WebDriverError::new(
-                ErrorStatus::StaleElementReference,
-                "Element not found",
-            )),
+            Err(error) => Err(WebDriverError::new(error, "Element not found")),
+        }
+    }
+
+    fn handle_take_full_page_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
+        let webview_id = self.session()?.webview_id;
+        let encoded = self.take_screenshot(|sender| {
+            WebDriverCommandMsg::TakePageScreenshot(webview_id, None, sender)
+        })?;
+
+        Ok(WebDriverResponse::Generic(ValueResponse(
+            serde_json::to_value(encoded)?,
+        )))
+    }
+
+    fn handle_control_animation_clock(
+        &self,
+        parameters: &AnimationClockParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let request = match *parameters {
+            AnimationClockParameters::Pause => AnimationClockRequest::Pause,
+            AnimationClockParameters::Resume => AnimationClockRequest::Resume,
+            AnimationClockParameters::Step { time } if time.is_finite() && time >= 0. => {
+                AnimationClockRequest::Step(time)
+            },
+            AnimationClockParameters::Set { time } if time.is_finite() => {
+                AnimationClockRequest::SetTime(time)
+            },
+            _ => {
+                return Err(WebDriverError::new(
+                    ErrorStatus::InvalidArgument,
+                    "Invalid clock time",
+                ));
+            },
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd = WebDriverScriptCommand::ControlAnimationClock(request, sender);
+        self.browsing_context_script_command(cmd)?;
+        match receiver.recv().unwrap() {
+            Ok(time) => Ok(WebDriverResponse::Generic(ValueResponse(
+                json!({ "time": time }),
+            ))),
+            Err(error) => Err(WebDriverError::new(error, "")),
         }
     }
 
@@ -1832,6 +2025,20 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
             WebDriverCommand::FindElementElements(ref element, ref parameters) => {
                 self.handle_find_elements_from_element(element, parameters)
             },
+            WebDriverCommand::GetShadowRoot(ref element) => self.handle_get_shadow_root(element),
+            WebDriverCommand::FindShadowRootElement(ref shadow_root, ref parameters) => {
+                self.handle_find_element_from_shadow_root(shadow_root, parameters)
+            },
+            WebDriverCommand::FindShadowRootElements(ref shadow_root, ref parameters) => {
+                let elements = self
+                    .handle_find_elements_from_shadow_root(shadow_root, parameters)?
+                    .into_iter()
+                    .map(|x| serde_json::to_value(WebElement(x)).unwrap())
+                    .collect::<Vec<Value>>();
+                Ok(WebDriverResponse::Generic(ValueResponse(
+                    serde_json::to_value(elements)?,
+                )))
+            },
             WebDriverCommand::GetNamedCookie(ref name) => self.handle_get_cookie(name),
             WebDriverCommand::GetCookies => self.handle_get_cookies(),
             WebDriverCommand::GetActiveElement => self.handle_active_element(),
@@ -1870,6 +2077,12 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::TakeFullPageScreenshot => {
+                    self.handle_take_full_page_screenshot()
+                },
+                ServoExtensionCommand::ControlAnimationClock(ref x) => {
+                    self.handle_control_animation_clock(x)
+                },
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
@@ -1879,7 +2092,7 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
     }
 
     fn teardown_session(&mut self, _session: SessionTeardownKind) {
-        self.session = None;
+        self.end_session();
     }
 }
 
@@ -1897,6 +2110,15 @@ fn webdriver_value_to_js_argument(v: &Value) -> String {
             format!("[{}]", elems.join(", "))
         },
         Value::Object(map) => {
+            // References to elements and shadow roots are turned back into the nodes of the
+            // document that the script runs in.
+            if let Some(id) = map
+                .get(ELEMENT_KEY)
+                .or_else(|| map.get(SHADOW_KEY))
+                .and_then(Value::as_str)
+            {
+                return format!("window.webdriverNode(\"{}\")", id);
+            }
             let elems = map
                 .iter()
                 .map(|(k, v)| format!("{}: {}", k, webdriver_value_to_js_argument(v)))
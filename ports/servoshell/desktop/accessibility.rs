@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Exposes the accessibility tree of the focused `WebView` to the platform accessibility
+//! APIs of a winit window, using AccessKit.
+//!
+//! Servo only sends the nodes that changed in each update, so the trees of all `WebView`s
+//! are kept up to date here, including those that are not focused, so that the tree of a
+//! `WebView` can be exposed in full as soon as it gains focus.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use accesskit_winit::Adapter;
+use servo::accesskit::{
+    ActionHandler, ActionRequest, ActivationHandler, Affine, DeactivationHandler, Node, NodeId,
+    Role, Tree, TreeUpdate,
+};
+use servo::base::id::WebViewId;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+
+/// The id of the node that represents the window itself. The ids of the nodes of web content
+/// are derived from the addresses of DOM nodes, so they are never zero.
+const WINDOW_NODE_ID: NodeId = NodeId(0);
+
+/// The latest known state of the accessibility tree of a `WebView`.
+#[derive(Default)]
+struct WebViewAccessibilityTree {
+    nodes: HashMap<NodeId, Node>,
+    root: Option<NodeId>,
+    focus: Option<NodeId>,
+}
+
+impl WebViewAccessibilityTree {
+    /// Apply an update received from Servo, which only contains the nodes that changed.
+    fn apply(&mut self, tree_update: &TreeUpdate) {
+        // An update with a new tree describes the entire tree of a new document.
+        if let Some(tree) = &tree_update.tree {
+            self.nodes.clear();
+            self.root = Some(tree.root);
+        }
+        self.focus = Some(tree_update.focus);
+        self.nodes.extend(tree_update.nodes.iter().cloned());
+        self.remove_unreachable_nodes();
+    }
+
+    /// Remove the nodes that are no longer children of any node of the tree, which is how
+    /// Servo signals that they were removed.
+    fn remove_unreachable_nodes(&mut self) {
+        let mut reachable = HashMap::with_capacity(self.nodes.len());
+        let mut stack: Vec<NodeId> = self.root.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            let Some(node) = self.nodes.remove(&id) else {
+                continue;
+            };
+            stack.extend(node.children().iter().copied());
+            reachable.insert(id, node);
+        }
+        self.nodes = reachable;
+    }
+}
+
+/// The latest known state of the accessibility trees of all `WebView`s, which is used to
+/// answer requests for the entire tree from assistive technologies.
+#[derive(Default)]
+struct AccessibilityTreeState {
+    webviews: HashMap<WebViewId, WebViewAccessibilityTree>,
+    /// The `WebView` whose tree is exposed, which is the focused one.
+    focused_webview: Option<WebViewId>,
+    /// The transform from the coordinates of web content to window coordinates.
+    transform: Affine,
+}
+
+impl AccessibilityTreeState {
+    fn focused_tree(&self) -> Option<&WebViewAccessibilityTree> {
+        self.webviews.get(&self.focused_webview?)
+    }
+
+    fn window_node(&self) -> Node {
+        let mut node = Node::new(Role::Window);
+        node.set_label("Servo");
+        node.set_transform(self.transform);
+        node.set_children(
+            self.focused_tree()
+                .and_then(|tree| tree.root)
+                .into_iter()
+                .collect::<Vec<_>>(),
+        );
+        node
+    }
+
+    fn focus(&self) -> NodeId {
+        self.focused_tree()
+            .and_then(|tree| tree.focus)
+            .unwrap_or(WINDOW_NODE_ID)
+    }
+
+    /// Apply an update received from Servo for `webview_id`, returning the update to apply
+    /// to the tree of the window if that `WebView` is exposed.
+    fn apply(
+        &mut self,
+        webview_id: WebViewId,
+        tree_update: TreeUpdate,
+        transform: Affine,
+    ) -> Option<TreeUpdate> {
+        self.webviews
+            .entry(webview_id)
+            .or_default()
+            .apply(&tree_update);
+        if self.focused_webview != Some(webview_id) {
+            return None;
+        }
+
+        let tree = tree_update.tree.map(|_| Tree::new(WINDOW_NODE_ID));
+        self.transform = transform;
+        let mut nodes = tree_update.nodes;
+        nodes.push((WINDOW_NODE_ID, self.window_node()));
+        Some(TreeUpdate {
+            nodes,
+            tree,
+            focus: self.focus(),
+        })
+    }
+
+    fn full_tree(&self) -> TreeUpdate {
+        let mut nodes: Vec<_> = self
+            .focused_tree()
+            .into_iter()
+            .flat_map(|tree| tree.nodes.iter())
+            .map(|(id, node)| (*id, node.clone()))
+            .collect();
+        nodes.push((WINDOW_NODE_ID, self.window_node()));
+        TreeUpdate {
+            nodes,
+            tree: Some(Tree::new(WINDOW_NODE_ID)),
+            focus: self.focus(),
+        }
+    }
+}
+
+struct InitialTreeHandler(Arc<Mutex<AccessibilityTreeState>>);
+
+impl ActivationHandler for InitialTreeHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        Some(self.0.lock().unwrap().full_tree())
+    }
+}
+
+/// Actions requested by assistive technologies are not supported yet.
+struct NullActionHandler;
+
+impl ActionHandler for NullActionHandler {
+    fn do_action(&mut self, _request: ActionRequest) {}
+}
+
+struct NullDeactivationHandler;
+
+impl DeactivationHandler for NullDeactivationHandler {
+    fn deactivate_accessibility(&mut self) {}
+}
+
+pub(crate) struct AccessibilityAdapter {
+    adapter: RefCell<Adapter>,
+    state: Arc<Mutex<AccessibilityTreeState>>,
+}
+
+impl AccessibilityAdapter {
+    /// Create an adapter for `window`, which must not have been made visible yet.
+    pub(crate) fn new(
+        event_loop: &ActiveEventLoop,
+        window: &winit::window::Window,
+    ) -> AccessibilityAdapter {
+        let state = Arc::new(Mutex::new(AccessibilityTreeState::default()));
+        let adapter = Adapter::with_direct_handlers(
+            event_loop,
+            window,
+            InitialTreeHandler(state.clone()),
+            NullActionHandler,
+            NullDeactivationHandler,
+        );
+        AccessibilityAdapter {
+            adapter: RefCell::new(adapter),
+            state,
+        }
+    }
+
+    pub(crate) fn process_event(&self, window: &winit::window::Window, event: &WindowEvent) {
+        self.adapter.borrow_mut().process_event(window, event);
+    }
+
+    /// Handle an update of the accessibility tree of a `WebView`, whose content is positioned
+    /// in the window by `transform`. Only the tree of the focused `WebView` is exposed, but
+    /// the others are kept up to date for when they gain focus.
+    pub(crate) fn update(&self, webview_id: WebViewId, tree_update: TreeUpdate, transform: Affine) {
+        let Some(tree_update) =
+            self.state
+                .lock()
+                .unwrap()
+                .apply(webview_id, tree_update, transform)
+        else {
+            return;
+        };
+        self.adapter
+            .borrow_mut()
+            .update_if_active(move || tree_update);
+    }
+
+    /// Expose the entire accessibility tree of the newly focused `WebView`, if any.
+    pub(crate) fn set_focused_webview(&self, webview_id: Option<WebViewId>) {
+        let tree_update = {
+            let mut state = self.state.lock().unwrap();
+            if state.focused_webview == webview_id {
+                return;
+            }
+            state.focused_webview = webview_id;
+            state.full_tree()
+        };
+        self.adapter
+            .borrow_mut()
+            .update_if_active(move || tree_update);
+    }
+
+    /// Forget the accessibility tree of a `WebView` that was closed.
+    pub(crate) fn remove_webview(&self, webview_id: WebViewId) {
+        self.state.lock().unwrap().webviews.remove(&webview_id);
+    }
+}
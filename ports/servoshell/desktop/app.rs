@@ -173,6 +173,14 @@ impl App {
         ));
         running_state.new_toplevel_webview(self.initial_url.clone().into_url());
 
+        // winit only reports changes of the platform theme, so tell Servo about the one that
+        // the window starts out with for `prefers-color-scheme` to match it.
+        if let Some(theme) = window.winit_window().and_then(winit::window::Window::theme) {
+            if let Some((_, webview)) = running_state.webviews().first() {
+                webview.notify_theme_change(headed_window::servo_theme_from_winit(theme));
+            }
+        }
+
         if let Some(ref mut minibrowser) = self.minibrowser {
             minibrowser.update(window.winit_window().unwrap(), &running_state, "init");
             window.set_toolbar_height(minibrowser.toolbar_height);
@@ -464,6 +472,7 @@ fn load_userscripts(userscripts_directory: Option<&Path>) -> std::io::Result<Vec
             userscripts.push(UserScript {
                 script: std::fs::read_to_string(&file)?,
                 source_file: Some(file),
+                ..Default::default()
             });
         }
     }
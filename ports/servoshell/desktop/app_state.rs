@@ -11,15 +11,17 @@ use euclid::{Point2D, Vector2D};
 use image::{DynamicImage, ImageFormat};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use log::{error, info};
+use servo::accesskit::TreeUpdate;
 use servo::base::id::WebViewId;
 use servo::config::pref;
 use servo::ipc_channel::ipc::IpcSender;
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 use servo::{
-    AllowOrDenyRequest, AuthenticationRequest, FilterPattern, GamepadHapticEffectType, LoadStatus,
-    PermissionRequest, Servo, ServoDelegate, ServoError, SimpleDialog, TouchEventType, WebView,
-    WebViewDelegate,
+    AllowOrDenyRequest, AuthenticationRequest, AuxiliaryWebViewFeatures, FileSelectionMode,
+    FilterPattern, GamepadHapticEffectType, LoadStatus, PermissionRequest,
+    SelectElementOptionOrOptgroup, Servo, ServoDelegate, ServoError, SimpleDialog, TouchEventType,
+    WebView, WebViewDelegate,
 };
 use url::Url;
 
@@ -240,8 +242,10 @@ impl RunningAppState {
         inner.webviews.retain(|&id, _| id != webview_id);
         inner.creation_order.retain(|&id| id != webview_id);
         inner.dialogs.remove(&webview_id);
+        inner.window.notify_accessibility_webview_closed(webview_id);
         if Some(webview_id) == inner.focused_webview_id {
             inner.focused_webview_id = None;
+            inner.window.notify_accessibility_focus_changed(None);
         }
 
         let last_created = inner
@@ -454,6 +458,7 @@ impl WebViewDelegate for RunningAppState {
     fn request_open_auxiliary_webview(
         &self,
         parent_webview: servo::WebView,
+        _features: AuxiliaryWebViewFeatures,
     ) -> Option<servo::WebView> {
         let webview = self.servo.new_auxiliary_webview();
         webview.set_delegate(parent_webview.delegate());
@@ -478,6 +483,9 @@ impl WebViewDelegate for RunningAppState {
         } else if inner_mut.focused_webview_id == Some(webview.id()) {
             inner_mut.focused_webview_id = None;
         }
+        inner_mut
+            .window
+            .notify_accessibility_focus_changed(inner_mut.focused_webview_id);
     }
 
     fn notify_keyboard_event(&self, webview: servo::WebView, keyboard_event: KeyboardEvent) {
@@ -496,6 +504,10 @@ impl WebViewDelegate for RunningAppState {
         self.inner().window.set_fullscreen(fullscreen_state);
     }
 
+    fn request_fullscreen_on_screen(&self, _webview: servo::WebView, screen: servo::ScreenId) {
+        self.inner().window.set_fullscreen_on_screen(screen);
+    }
+
     fn show_bluetooth_device_dialog(
         &self,
         webview: servo::WebView,
@@ -508,15 +520,28 @@ impl WebViewDelegate for RunningAppState {
         );
     }
 
+    fn show_select_element_menu(
+        &self,
+        webview: servo::WebView,
+        options: Vec<SelectElementOptionOrOptgroup>,
+        selected_option: Option<usize>,
+        position: servo::webrender_api::units::DeviceIntRect,
+        response_sender: IpcSender<Option<usize>>,
+    ) {
+        self.add_dialog(
+            webview,
+            Dialog::new_select_element_dialog(options, selected_option, position, response_sender),
+        );
+    }
+
     fn show_file_selection_dialog(
         &self,
         webview: servo::WebView,
         filter_pattern: Vec<FilterPattern>,
-        allow_select_mutiple: bool,
+        selection_mode: FileSelectionMode,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
     ) {
-        let file_dialog =
-            Dialog::new_file_dialog(allow_select_mutiple, response_sender, filter_pattern);
+        let file_dialog = Dialog::new_file_dialog(selection_mode, response_sender, filter_pattern);
         self.add_dialog(webview, file_dialog);
     }
 
@@ -579,4 +604,18 @@ impl WebViewDelegate for RunningAppState {
     fn hide_ime(&self, _webview: WebView) {
         self.inner().window.hide_ime();
     }
+
+    fn update_ime_caret_rect(
+        &self,
+        _webview: WebView,
+        rect: servo::webrender_api::units::DeviceIntRect,
+    ) {
+        self.inner().window.update_ime_caret_rect(rect);
+    }
+
+    fn notify_accessibility_tree_update(&self, webview: WebView, tree_update: TreeUpdate) {
+        self.inner()
+            .window
+            .notify_accessibility_tree_update(webview.id(), tree_update);
+    }
 }
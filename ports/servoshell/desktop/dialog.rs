@@ -9,15 +9,17 @@ use egui::Modal;
 use egui_file_dialog::{DialogState, FileDialog as EguiFileDialog};
 use log::warn;
 use servo::ipc_channel::ipc::IpcSender;
+use servo::webrender_api::units::DeviceIntRect;
 use servo::{
-    AlertResponse, AuthenticationRequest, ConfirmResponse, FilterPattern, PermissionRequest,
-    PromptResponse, SimpleDialog,
+    AlertResponse, AuthenticationRequest, ConfirmResponse, FileSelectionMode, FilterPattern,
+    PermissionRequest, PromptResponse, SelectElementOption, SelectElementOptionOrOptgroup,
+    SimpleDialog,
 };
 
 pub enum Dialog {
     File {
         dialog: EguiFileDialog,
-        multiple: bool,
+        selection_mode: FileSelectionMode,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
     },
     #[allow(clippy::enum_variant_names, reason = "spec terminology")]
@@ -36,11 +38,17 @@ pub enum Dialog {
         selected_device_index: usize,
         response_sender: IpcSender<Option<String>>,
     },
+    SelectElement {
+        options: Vec<SelectElementOptionOrOptgroup>,
+        selected_option: Option<usize>,
+        position: DeviceIntRect,
+        response_sender: IpcSender<Option<usize>>,
+    },
 }
 
 impl Dialog {
     pub fn new_file_dialog(
-        multiple: bool,
+        selection_mode: FileSelectionMode,
         response_sender: IpcSender<Option<Vec<PathBuf>>>,
         patterns: Vec<FilterPattern>,
     ) -> Self {
@@ -63,7 +71,7 @@ impl Dialog {
 
         Dialog::File {
             dialog,
-            multiple,
+            selection_mode,
             response_sender,
         }
     }
@@ -102,18 +110,32 @@ impl Dialog {
         }
     }
 
+    pub fn new_select_element_dialog(
+        options: Vec<SelectElementOptionOrOptgroup>,
+        selected_option: Option<usize>,
+        position: DeviceIntRect,
+        response_sender: IpcSender<Option<usize>>,
+    ) -> Self {
+        Dialog::SelectElement {
+            options,
+            selected_option,
+            position,
+            response_sender,
+        }
+    }
+
     pub fn update(&mut self, ctx: &egui::Context) -> bool {
         match self {
             Dialog::File {
                 dialog,
-                multiple,
+                selection_mode,
                 response_sender,
             } => {
                 if dialog.state() == DialogState::Closed {
-                    if *multiple {
-                        dialog.pick_multiple();
-                    } else {
-                        dialog.pick_file();
+                    match selection_mode {
+                        FileSelectionMode::Single => dialog.pick_file(),
+                        FileSelectionMode::Multiple => dialog.pick_multiple(),
+                        FileSelectionMode::Directory => dialog.pick_directory(),
                     }
                 }
 
@@ -373,10 +395,89 @@ impl Dialog {
                 });
                 is_open
             },
+            Dialog::SelectElement {
+                options,
+                selected_option,
+                position,
+                response_sender,
+            } => {
+                // The position is relative to the WebView, which starts below the toolbar.
+                let webview_origin = ctx.available_rect().min;
+                let menu_position =
+                    webview_origin + egui::vec2(position.min.x as f32, position.max.y as f32);
+
+                let mut chosen_option = None;
+                let area = egui::Area::new("select_element_menu".into())
+                    .order(egui::Order::Foreground)
+                    .fixed_pos(menu_position)
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.set_min_width(position.width() as f32);
+                            egui::ScrollArea::vertical()
+                                .max_height(300.0)
+                                .show(ui, |ui| {
+                                    for entry in options.iter() {
+                                        match entry {
+                                            SelectElementOptionOrOptgroup::Option(option) => {
+                                                if let Some(id) = select_element_option(
+                                                    ui,
+                                                    option,
+                                                    *selected_option,
+                                                ) {
+                                                    chosen_option = Some(id);
+                                                }
+                                            },
+                                            SelectElementOptionOrOptgroup::Optgroup {
+                                                label,
+                                                options,
+                                            } => {
+                                                ui.label(egui::RichText::new(label).strong());
+                                                ui.indent(label, |ui| {
+                                                    for option in options {
+                                                        if let Some(id) = select_element_option(
+                                                            ui,
+                                                            option,
+                                                            *selected_option,
+                                                        ) {
+                                                            chosen_option = Some(id);
+                                                        }
+                                                    }
+                                                });
+                                            },
+                                        }
+                                    }
+                                });
+                        });
+                    });
+
+                let dismissed = area.response.clicked_elsewhere() ||
+                    ctx.input(|i| i.key_pressed(egui::Key::Escape));
+                if chosen_option.is_none() && !dismissed {
+                    return true;
+                }
+                if let Err(e) = response_sender.send(chosen_option) {
+                    warn!("Failed to send select element menu response: {}", e);
+                }
+                false
+            },
         }
     }
 }
 
+/// Show an option of the menu of a `<select>` element, returning its id if the user chose it.
+fn select_element_option(
+    ui: &mut egui::Ui,
+    option: &SelectElementOption,
+    selected_option: Option<usize>,
+) -> Option<usize> {
+    let is_selected = selected_option == Some(option.id);
+    let response = ui.add_enabled(
+        !option.is_disabled,
+        egui::SelectableLabel::new(is_selected, &option.label),
+    );
+    response.clicked().then_some(option.id)
+}
+
 fn make_dialog_label(message: &str, ui: &mut egui::Ui, input_text: Option<&mut String>) {
     let mut frame = egui::Frame::default().inner_margin(10.0).begin(ui);
     frame.content_ui.set_min_width(150.0);
@@ -14,6 +14,8 @@ use euclid::{Angle, Length, Point2D, Rotation3D, Scale, Size2D, UnknownUnit, Vec
 use keyboard_types::{Modifiers, ShortcutMatcher};
 use log::{debug, info};
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawWindowHandle};
+use servo::accesskit::{Affine, TreeUpdate};
+use servo::base::id::WebViewId;
 use servo::compositing::windowing::{
     AnimationState, EmbedderCoordinates, WebRenderDebugOption, WindowMethods,
 };
@@ -22,10 +24,10 @@ use servo::servo_geometry::DeviceIndependentPixel;
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePixel};
 use servo::{
-    Cursor, ImeEvent, InputEvent, Key, KeyState, KeyboardEvent, MouseButton as ServoMouseButton,
-    MouseButtonAction, MouseButtonEvent, MouseMoveEvent, OffscreenRenderingContext,
-    RenderingContext, Theme, TouchEvent, TouchEventType, TouchId, WebView, WheelDelta, WheelEvent,
-    WheelMode, WindowRenderingContext,
+    Cursor, FileDropEvent, ImeEvent, InputEvent, Key, KeyState, KeyboardEvent,
+    MouseButton as ServoMouseButton, MouseButtonAction, MouseButtonEvent, MouseMoveEvent,
+    OffscreenRenderingContext, RenderingContext, ScreenId, ScreenInfo, Theme, TouchEvent,
+    TouchEventType, TouchId, WebView, WheelDelta, WheelEvent, WheelMode, WindowRenderingContext,
 };
 use surfman::{Context, Device};
 use url::Url;
@@ -35,6 +37,7 @@ use winit::event::{
 };
 use winit::event_loop::ActiveEventLoop;
 use winit::keyboard::{Key as LogicalKey, ModifiersState, NamedKey};
+use winit::monitor::MonitorHandle;
 #[cfg(any(target_os = "linux", target_os = "windows"))]
 use winit::window::Icon;
 #[cfg(target_os = "macos")]
@@ -43,6 +46,7 @@ use {
     objc2_foundation::MainThreadMarker,
 };
 
+use super::accessibility::AccessibilityAdapter;
 use super::app_state::RunningAppState;
 use super::geometry::{winit_position_to_euclid_point, winit_size_to_euclid_size};
 use super::keyutils::{CMD_OR_ALT, keyboard_event_from_winit};
@@ -58,7 +62,7 @@ pub struct Window {
     toolbar_height: Cell<Length<f32, DeviceIndependentPixel>>,
     mouse_down_button: Cell<Option<MouseButton>>,
     webview_relative_mouse_down_point: Cell<Point2D<f32, DevicePixel>>,
-    monitor: winit::monitor::MonitorHandle,
+    monitor: MonitorHandle,
     webview_relative_mouse_point: Cell<Point2D<f32, DevicePixel>>,
     last_pressed: Cell<Option<(KeyboardEvent, Option<LogicalKey>)>>,
     /// A map of winit's key codes to key values that are interpreted from
@@ -69,6 +73,8 @@ pub struct Window {
     device_pixel_ratio_override: Option<f32>,
     xr_window_poses: RefCell<Vec<Rc<XRWindowPose>>>,
     modifiers_state: Cell<ModifiersState>,
+    /// Whether the AltGr key is held down, which winit doesn't track as a modifier.
+    alt_graph_pressed: Cell<bool>,
 
     /// The RenderingContext that renders directly onto the Window. This is used as
     /// the target of egui rendering and also where Servo rendering results are finally
@@ -78,6 +84,10 @@ pub struct Window {
     /// The `RenderingContext` of Servo itself. This is used to render Servo results
     /// temporarily until they can be blitted into the egui scene.
     rendering_context: Rc<OffscreenRenderingContext>,
+
+    /// Exposes the accessibility tree of the focused `WebView` to assistive technologies,
+    /// if the `accessibility_enabled` preference is set.
+    accessibility_adapter: Option<AccessibilityAdapter>,
 }
 
 impl Window {
@@ -93,13 +103,18 @@ impl Window {
             .with_transparent(no_native_titlebar)
             .with_inner_size(LogicalSize::new(window_size.width, window_size.height))
             .with_min_inner_size(LogicalSize::new(1, 1))
-            .with_visible(true);
+            .with_visible(false);
 
         #[allow(deprecated)]
         let winit_window = event_loop
             .create_window(window_attr)
             .expect("Failed to create window.");
 
+        // The accessibility adapter must be created before the window is shown.
+        let accessibility_adapter = pref!(accessibility_enabled)
+            .then(|| AccessibilityAdapter::new(event_loop, &winit_window));
+        winit_window.set_visible(true);
+
         #[cfg(any(target_os = "linux", target_os = "windows"))]
         {
             let icon_bytes = include_bytes!("../../../resources/servo_64.png");
@@ -161,9 +176,11 @@ impl Window {
             device_pixel_ratio_override: servoshell_preferences.device_pixel_ratio_override,
             xr_window_poses: RefCell::new(vec![]),
             modifiers_state: Cell::new(ModifiersState::empty()),
+            alt_graph_pressed: Cell::new(false),
             toolbar_height: Cell::new(Default::default()),
             window_rendering_context,
             rendering_context,
+            accessibility_adapter,
         }
     }
 
@@ -210,10 +227,20 @@ impl Window {
     }
 
     fn handle_keyboard_input(&self, state: Rc<RunningAppState>, winit_event: KeyEvent) {
+        if winit_event.logical_key == LogicalKey::Named(NamedKey::AltGraph) {
+            self.alt_graph_pressed
+                .set(winit_event.state == ElementState::Pressed);
+        }
+
         // First, handle servoshell key bindings that are not overridable by, or visible to, the page.
         let mut keyboard_event =
             keyboard_event_from_winit(&winit_event, self.modifiers_state.get());
-        if self.handle_intercepted_key_bindings(state.clone(), &keyboard_event) {
+        keyboard_event
+            .modifiers
+            .set(Modifiers::ALT_GRAPH, self.alt_graph_pressed.get());
+        if !self.is_locked_by_page(&state, &keyboard_event) &&
+            self.handle_intercepted_key_bindings(state.clone(), &keyboard_event)
+        {
             return;
         }
 
@@ -276,11 +303,11 @@ impl Window {
             ElementState::Released => MouseButtonAction::Up,
         };
 
-        webview.notify_input_event(InputEvent::MouseButton(MouseButtonEvent {
+        webview.notify_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
             action,
-            button: mouse_button,
+            mouse_button,
             point,
-        }));
+        )));
 
         // Also send a 'click' event if this is release and the press was recorded
         // to be within a 10 pixels.
@@ -294,15 +321,29 @@ impl Window {
             let pixel_dist = self.webview_relative_mouse_down_point.get() - point;
             let pixel_dist = (pixel_dist.x * pixel_dist.x + pixel_dist.y * pixel_dist.y).sqrt();
             if mouse_down_button == button && pixel_dist < max_pixel_dist {
-                webview.notify_input_event(InputEvent::MouseButton(MouseButtonEvent {
-                    action: MouseButtonAction::Click,
-                    button: mouse_button,
+                webview.notify_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+                    MouseButtonAction::Click,
+                    mouse_button,
                     point,
-                }));
+                )));
             }
         }
     }
 
+    /// Whether the page in the focused webview locked the keyboard, with
+    /// `navigator.keyboard.lock()`, to receive this key instead of servoshell. The lock only
+    /// applies in fullscreen, and holding down Escape still exits fullscreen so that the user
+    /// can't get stuck in it.
+    fn is_locked_by_page(&self, state: &RunningAppState, key_event: &KeyboardEvent) -> bool {
+        if !self.get_fullscreen() || (key_event.key == Key::Escape && key_event.repeat) {
+            return false;
+        }
+        state
+            .focused_webview()
+            .and_then(|webview| webview.keyboard_lock())
+            .is_some_and(|lock| lock.includes(key_event.code))
+    }
+
     /// Handle key events before sending them to Servo.
     fn handle_intercepted_key_bindings(
         &self,
@@ -428,6 +469,17 @@ impl Window {
         self.rendering_context.clone()
     }
 
+    /// Whether the window is shown by a Wayland compositor, rather than by an X11 server or
+    /// another platform's window system.
+    fn is_wayland(&self) -> bool {
+        matches!(
+            self.winit_window
+                .window_handle()
+                .map(|handle| handle.as_raw()),
+            Ok(RawWindowHandle::Wayland(_))
+        )
+    }
+
     #[allow(unused_variables)]
     fn force_srgb_color_space(window_handle: RawWindowHandle) {
         #[cfg(target_os = "macos")]
@@ -501,6 +553,21 @@ impl WindowPortsMethods for Window {
         self.fullscreen.set(state);
     }
 
+    fn set_fullscreen_on_screen(&self, screen: ScreenId) {
+        let is_wayland = self.is_wayland();
+        let Some(monitor) = self
+            .winit_window
+            .available_monitors()
+            .find(|monitor| screen_id_for_monitor(monitor, is_wayland) == screen)
+        else {
+            // The screen is gone, so use the one the window is on.
+            return self.set_fullscreen(true);
+        };
+        self.winit_window
+            .set_fullscreen(Some(winit::window::Fullscreen::Borderless(Some(monitor))));
+        self.fullscreen.set(true);
+    }
+
     fn get_fullscreen(&self) -> bool {
         self.fullscreen.get()
     }
@@ -561,6 +628,10 @@ impl WindowPortsMethods for Window {
     }
 
     fn handle_winit_event(&self, state: Rc<RunningAppState>, event: WindowEvent) {
+        if let Some(accessibility_adapter) = &self.accessibility_adapter {
+            accessibility_adapter.process_event(&self.winit_window, &event);
+        }
+
         let Some(webview) = state.focused_webview() else {
             return;
         };
@@ -578,7 +649,7 @@ impl WindowPortsMethods for Window {
                 point.y -= (self.toolbar_height() * self.hidpi_factor()).0;
 
                 self.webview_relative_mouse_point.set(point);
-                webview.notify_input_event(InputEvent::MouseMove(MouseMoveEvent { point }));
+                webview.notify_input_event(InputEvent::MouseMove(MouseMoveEvent::new(point)));
             },
             WindowEvent::MouseWheel { delta, phase, .. } => {
                 let (mut dx, mut dy, mode) = match delta {
@@ -628,6 +699,14 @@ impl WindowPortsMethods for Window {
                     Point2D::new(touch.location.x as f32, touch.location.y as f32),
                 )));
             },
+            WindowEvent::DroppedFile(path) => {
+                // Each of the files that are dropped at once comes in its own event, and there
+                // is no position for them, so they are dropped where the cursor last was.
+                webview.notify_input_event(InputEvent::FileDrop(FileDropEvent {
+                    point: self.webview_relative_mouse_point.get(),
+                    paths: vec![path],
+                }));
+            },
             WindowEvent::PinchGesture { delta, .. } => {
                 webview.set_pinch_zoom(delta as f32 + 1.0);
             },
@@ -641,14 +720,17 @@ impl WindowPortsMethods for Window {
                 }
             },
             WindowEvent::ThemeChanged(theme) => {
-                webview.notify_theme_change(match theme {
-                    winit::window::Theme::Light => Theme::Light,
-                    winit::window::Theme::Dark => Theme::Dark,
-                });
+                webview.notify_theme_change(servo_theme_from_winit(theme));
             },
             WindowEvent::Moved(_new_position) => {
                 webview.notify_embedder_window_moved();
             },
+            WindowEvent::ScaleFactorChanged { .. } => {
+                webview.notify_hidpi_factor_changed();
+            },
+            WindowEvent::Occluded(occluded) => {
+                webview.notify_visibility_change(!occluded);
+            },
             WindowEvent::Ime(ime) => match ime {
                 Ime::Enabled => {
                     webview.notify_input_event(InputEvent::Ime(ImeEvent::Composition(
@@ -713,6 +795,31 @@ impl WindowPortsMethods for Window {
         self.toolbar_height.get()
     }
 
+    fn notify_accessibility_tree_update(&self, webview_id: WebViewId, tree_update: TreeUpdate) {
+        let Some(accessibility_adapter) = &self.accessibility_adapter else {
+            return;
+        };
+
+        // Web content is laid out in CSS pixels below the toolbar, while AccessKit expects
+        // bounds in physical pixels relative to the window.
+        let hidpi_factor = self.hidpi_factor().get() as f64;
+        let toolbar_height = self.toolbar_height().get() as f64 * hidpi_factor;
+        let transform = Affine::translate((0., toolbar_height)) * Affine::scale(hidpi_factor);
+        accessibility_adapter.update(webview_id, tree_update, transform);
+    }
+
+    fn notify_accessibility_focus_changed(&self, webview_id: Option<WebViewId>) {
+        if let Some(accessibility_adapter) = &self.accessibility_adapter {
+            accessibility_adapter.set_focused_webview(webview_id);
+        }
+    }
+
+    fn notify_accessibility_webview_closed(&self, webview_id: WebViewId) {
+        if let Some(accessibility_adapter) = &self.accessibility_adapter {
+            accessibility_adapter.remove_webview(webview_id);
+        }
+    }
+
     fn set_toolbar_height(&self, height: Length<f32, DeviceIndependentPixel>) {
         self.toolbar_height.set(height);
         // Prevent the inner area from being 0 pixels wide or tall
@@ -735,21 +842,22 @@ impl WindowPortsMethods for Window {
         position: servo::webrender_api::units::DeviceIntRect,
     ) {
         self.winit_window.set_ime_allowed(true);
-        self.winit_window.set_ime_cursor_area(
-            LogicalPosition::new(
-                position.min.x,
-                position.min.y + (self.toolbar_height.get().0 as i32),
-            ),
-            LogicalSize::new(
-                position.max.x - position.min.x,
-                position.max.y - position.min.y,
-            ),
-        );
+        self.update_ime_caret_rect(position);
     }
 
     fn hide_ime(&self) {
         self.winit_window.set_ime_allowed(false);
     }
+
+    fn update_ime_caret_rect(&self, rect: servo::webrender_api::units::DeviceIntRect) {
+        self.winit_window.set_ime_cursor_area(
+            LogicalPosition::new(
+                rect.min.x,
+                rect.min.y + (self.toolbar_height.get().0 as i32),
+            ),
+            LogicalSize::new(rect.max.x - rect.min.x, rect.max.y - rect.min.y),
+        );
+    }
 }
 
 impl WindowMethods for Window {
@@ -772,11 +880,78 @@ impl WindowMethods for Window {
         }
     }
 
+    fn get_screens(&self) -> Vec<ScreenInfo> {
+        let primary_monitor = self.winit_window.primary_monitor();
+        let is_wayland = self.is_wayland();
+        self.winit_window
+            .available_monitors()
+            .map(|monitor| {
+                let scale: Scale<f64, DeviceIndependentPixel, DevicePixel> =
+                    Scale::new(monitor.scale_factor());
+                let origin = winit_position_to_euclid_point(monitor.position()).to_i32();
+                let size = winit_size_to_euclid_size(monitor.size()).to_i32();
+                let rect =
+                    (DeviceIntRect::from_origin_and_size(origin, size).to_f64() / scale).to_i32();
+                ScreenInfo {
+                    id: screen_id_for_monitor(&monitor, is_wayland),
+                    label: monitor.name().unwrap_or_default(),
+                    rect,
+                    // FIXME: Winit doesn't have API for available size. Fallback to screen size
+                    available_rect: rect,
+                    device_pixel_ratio: monitor.scale_factor() as f32,
+                    color_depth: 24,
+                    is_primary: primary_monitor.as_ref() == Some(&monitor),
+                    // Winit doesn't expose whether a monitor is built into the device.
+                    is_internal: false,
+                }
+            })
+            .collect()
+    }
+
     fn set_animation_state(&self, state: AnimationState) {
         self.animation_state.set(state);
     }
 }
 
+/// The [`ScreenId`] of a monitor, derived from the identifier the platform gives it. Unlike
+/// its position in the list of monitors, this does not change when other monitors are connected
+/// or disconnected.
+#[allow(unused_variables)]
+fn screen_id_for_monitor(monitor: &MonitorHandle, is_wayland: bool) -> ScreenId {
+    #[cfg(target_os = "macos")]
+    {
+        use winit::platform::macos::MonitorHandleExtMacOS;
+        ScreenId(monitor.native_id())
+    }
+    // Wayland and X11 identify monitors differently, so ask the backend that is in use.
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland {
+            use winit::platform::wayland::MonitorHandleExtWayland;
+            ScreenId(MonitorHandleExtWayland::native_id(monitor))
+        } else {
+            use winit::platform::x11::MonitorHandleExtX11;
+            ScreenId(MonitorHandleExtX11::native_id(monitor))
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+
+        #[cfg(target_os = "windows")]
+        let native_id = {
+            use winit::platform::windows::MonitorHandleExtWindows;
+            monitor.native_id()
+        };
+        #[cfg(not(target_os = "windows"))]
+        let native_id = monitor.name().unwrap_or_default();
+
+        let mut hasher = DefaultHasher::new();
+        native_id.hash(&mut hasher);
+        ScreenId(hasher.finish() as u32)
+    }
+}
+
 fn winit_phase_to_touch_event_type(phase: TouchPhase) -> TouchEventType {
     match phase {
         TouchPhase::Started => TouchEventType::Down,
@@ -786,6 +961,13 @@ fn winit_phase_to_touch_event_type(phase: TouchPhase) -> TouchEventType {
     }
 }
 
+pub(crate) fn servo_theme_from_winit(theme: winit::window::Theme) -> Theme {
+    match theme {
+        winit::window::Theme::Light => Theme::Light,
+        winit::window::Theme::Dark => Theme::Dark,
+    }
+}
+
 #[cfg(any(target_os = "linux", target_os = "windows"))]
 fn load_icon(icon_bytes: &[u8]) -> Icon {
     let (icon_rgba, icon_width, icon_height) = {
@@ -31,7 +31,7 @@ impl FromWinitKeyEvent for Key {
             WinitKey::Named(named_key) => named_key,
             WinitKey::Character(ref string) => return Key::Character(string.to_string()),
             WinitKey::Unidentified(_) => return Key::Unidentified,
-            WinitKey::Dead(_) => return Key::Unidentified,
+            WinitKey::Dead(_) => return Key::Dead,
         };
 
         match named_key {
@@ -589,7 +589,7 @@ pub fn keyboard_event_from_winit(key_event: &KeyEvent, state: ModifiersState) ->
         code: Code::from_winit_key_event(key_event),
         location: Location::from_winit_key_event(key_event),
         modifiers: keyboard_modifiers_from_winit_modifiers(state),
-        repeat: false,
+        repeat: key_event.repeat,
         is_composing: false,
     }
 }
@@ -8,10 +8,11 @@
 use std::rc::Rc;
 
 use euclid::{Length, Scale};
+use servo::base::id::WebViewId;
 use servo::compositing::windowing::WindowMethods;
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePixel};
-use servo::{Cursor, RenderingContext, WebView};
+use servo::{Cursor, RenderingContext, ScreenId, WebView};
 
 use super::app_state::RunningAppState;
 
@@ -38,6 +39,11 @@ pub trait WindowPortsMethods: WindowMethods {
     -> Option<DeviceIntSize>;
     fn set_position(&self, _point: DeviceIntPoint) {}
     fn set_fullscreen(&self, _state: bool) {}
+    /// Enter fullscreen on the given screen. Windows that can't choose a screen enter
+    /// fullscreen where they are.
+    fn set_fullscreen_on_screen(&self, _screen: ScreenId) {
+        self.set_fullscreen(true)
+    }
     fn set_cursor(&self, _cursor: Cursor) {}
     fn new_glwindow(
         &self,
@@ -57,4 +63,22 @@ pub trait WindowPortsMethods: WindowMethods {
     }
 
     fn hide_ime(&self) {}
+
+    fn update_ime_caret_rect(&self, _rect: servo::webrender_api::units::DeviceIntRect) {}
+
+    /// Handle an update of the accessibility tree of a [`WebView`], which is exposed to
+    /// assistive technologies if the [`WebView`] is focused.
+    fn notify_accessibility_tree_update(
+        &self,
+        _webview_id: WebViewId,
+        _tree_update: servo::accesskit::TreeUpdate,
+    ) {
+    }
+
+    /// Expose the accessibility tree of the newly focused [`WebView`], if any, to assistive
+    /// technologies.
+    fn notify_accessibility_focus_changed(&self, _webview_id: Option<WebViewId>) {}
+
+    /// Forget the accessibility tree of a [`WebView`] that was closed.
+    fn notify_accessibility_webview_closed(&self, _webview_id: WebViewId) {}
 }
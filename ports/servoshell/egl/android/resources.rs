@@ -42,9 +42,7 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::DirectoryListingHTML => {
                 &include_bytes!("../../../../resources/directory-listing.html")[..]
             },
-            Resource::AboutMemoryHTML => {
-                &include_bytes!("../../../../resources/about-memory.html")[..]
-            },
+            Resource::DebuggerJS => &include_bytes!("../../../../resources/debugger.js")[..],
         })
     }
 
@@ -19,12 +19,12 @@ use servo::servo_geometry::DeviceIndependentPixel;
 use servo::webrender_api::ScrollLocation;
 use servo::webrender_api::units::{DeviceIntRect, DeviceIntSize, DevicePixel};
 use servo::{
-    AllowOrDenyRequest, ContextMenuResult, EmbedderProxy, EventLoopWaker, ImeEvent, InputEvent,
-    InputMethodType, Key, KeyState, KeyboardEvent, LoadStatus, MediaSessionActionType,
-    MediaSessionEvent, MouseButton, MouseButtonAction, MouseButtonEvent, MouseMoveEvent,
-    NavigationRequest, PermissionRequest, RenderingContext, Servo, ServoDelegate, ServoError,
-    SimpleDialog, TouchEvent, TouchEventType, TouchId, WebView, WebViewDelegate,
-    WindowRenderingContext,
+    AllowOrDenyRequest, AuxiliaryWebViewFeatures, ContextMenuElementInformation, ContextMenuResult,
+    EmbedderProxy, EventLoopWaker, ImeEvent, InputEvent, InputMethodType, Key, KeyState,
+    KeyboardEvent, LoadStatus, MediaSessionActionType, MediaSessionEvent, MouseButton,
+    MouseButtonAction, MouseButtonEvent, MouseMoveEvent, NavigationRequest, PermissionRequest,
+    RenderingContext, Servo, ServoDelegate, ServoError, SimpleDialog, TouchEvent, TouchEventType,
+    TouchId, WebView, WebViewDelegate, WindowRenderingContext,
 };
 use url::Url;
 
@@ -192,7 +192,11 @@ impl WebViewDelegate for RunningAppState {
         }
     }
 
-    fn request_open_auxiliary_webview(&self, _parent_webview: WebView) -> Option<WebView> {
+    fn request_open_auxiliary_webview(
+        &self,
+        _parent_webview: WebView,
+        _features: AuxiliaryWebViewFeatures,
+    ) -> Option<WebView> {
         let new_webview = self.servo.new_auxiliary_webview();
         self.add(new_webview.clone());
         Some(new_webview)
@@ -214,6 +218,7 @@ impl WebViewDelegate for RunningAppState {
         result_sender: IpcSender<ContextMenuResult>,
         title: Option<String>,
         items: Vec<String>,
+        _element_info: ContextMenuElementInformation,
     ) {
         if self.inner().context_menu_sender.is_some() {
             warn!("Trying to show a context menu when a context menu is already active");
@@ -491,31 +496,31 @@ impl RunningAppState {
     /// Register a mouse movement.
     pub fn mouse_move(&self, x: f32, y: f32) {
         self.active_webview()
-            .notify_input_event(InputEvent::MouseMove(MouseMoveEvent {
-                point: Point2D::new(x, y),
-            }));
+            .notify_input_event(InputEvent::MouseMove(MouseMoveEvent::new(Point2D::new(
+                x, y,
+            ))));
         self.perform_updates();
     }
 
     /// Register a mouse button press.
     pub fn mouse_down(&self, x: f32, y: f32, button: MouseButton) {
         self.active_webview()
-            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent {
-                action: MouseButtonAction::Down,
+            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+                MouseButtonAction::Down,
                 button,
-                point: Point2D::new(x, y),
-            }));
+                Point2D::new(x, y),
+            )));
         self.perform_updates();
     }
 
     /// Register a mouse button release.
     pub fn mouse_up(&self, x: f32, y: f32, button: MouseButton) {
         self.active_webview()
-            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent {
-                action: MouseButtonAction::Up,
+            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+                MouseButtonAction::Up,
                 button,
-                point: Point2D::new(x, y),
-            }));
+                Point2D::new(x, y),
+            )));
         self.perform_updates();
     }
 
@@ -543,11 +548,11 @@ impl RunningAppState {
     /// Perform a click.
     pub fn click(&self, x: f32, y: f32) {
         self.active_webview()
-            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent {
-                action: MouseButtonAction::Click,
-                button: MouseButton::Left,
-                point: Point2D::new(x, y),
-            }));
+            .notify_input_event(InputEvent::MouseButton(MouseButtonEvent::new(
+                MouseButtonAction::Click,
+                MouseButton::Left,
+                Point2D::new(x, y),
+            )));
         self.perform_updates();
     }
 
@@ -638,8 +643,9 @@ impl RunningAppState {
     pub fn present_if_needed(&self) {
         if self.inner().need_present {
             self.inner_mut().need_present = false;
-            self.active_webview().paint();
-            self.rendering_context.present();
+            if self.active_webview().paint() {
+                self.rendering_context.present();
+            }
         }
     }
 }
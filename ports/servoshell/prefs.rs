@@ -205,6 +205,13 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         "Path to dump a self-contained HTML timeline of profiler traces",
         "",
     );
+    opts.optopt(
+        "",
+        "profiler-chrome-trace-path",
+        "Path to record profiler traces into, in the Chrome trace event format that \
+         Perfetto and chrome://tracing can load",
+        "trace.json",
+    );
     opts.optflag(
         "x",
         "exit",
@@ -240,6 +247,12 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         "",
     );
     opts.optflag("z", "headless", "Headless mode");
+    opts.optflag(
+        "",
+        "deterministic",
+        "Render reproducibly for automated testing: implies headless mode and a device pixel \
+         ratio of 1, and only advances the clock of documents when a test asks for it",
+    );
     opts.optflag(
         "f",
         "hard-fail",
@@ -251,6 +264,12 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         "Display about:failure on thread failure instead of exiting",
     );
     opts.optflagopt("", "devtools", "Start remote devtools server on port", "0");
+    opts.optopt(
+        "",
+        "remote-debugging-port",
+        "Start a Chrome DevTools Protocol server on port",
+        "9222",
+    );
     opts.optflagopt(
         "",
         "webdriver",
@@ -445,7 +464,13 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         None
     };
 
-    if let Some(ref time_profiler_trace_path) = opt_match.opt_str("profiler-trace-path") {
+    for time_profiler_trace_path in [
+        opt_match.opt_str("profiler-trace-path"),
+        opt_match.opt_str("profiler-chrome-trace-path"),
+    ]
+    .iter()
+    .flatten()
+    {
         let mut path = PathBuf::from(time_profiler_trace_path);
         path.pop();
         if let Err(why) = fs::create_dir_all(&path) {
@@ -501,6 +526,18 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         preferences.devtools_server_port = port;
     }
 
+    if let Some(port) = opt_match.opt_str("remote-debugging-port") {
+        let port = port.parse().unwrap_or_else(|err| {
+            args_fail(&format!(
+                "Error parsing option: --remote-debugging-port ({})",
+                err
+            ))
+        });
+        preferences.devtools_server_enabled = true;
+        preferences.devtools_cdp_server_enabled = true;
+        preferences.devtools_cdp_server_port = port;
+    }
+
     let webdriver_port = opt_match.opt_default("webdriver", "7000").map(|port| {
         port.parse().unwrap_or_else(|err| {
             args_fail(&format!("Error parsing option: --webdriver ({})", err))
@@ -554,15 +591,19 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
             "dom_fontface_enabled",
             "dom_imagebitmap_enabled",
             "dom_intersection_observer_enabled",
+            "dom_lazy_loading_enabled",
             "dom_mouse_event_which_enabled",
             "dom_notification_enabled",
             "dom_offscreen_canvas_enabled",
+            "dom_page_lifecycle_enabled",
             "dom_permissions_enabled",
             "dom_resize_observer_enabled",
             "dom_serviceworker_enabled",
+            "dom_speculation_rules_enabled",
             "dom_svg_enabled",
             "dom_webgl2_enabled",
             "dom_webgpu_enabled",
+            "dom_window_management_enabled",
             "dom_xpath_enabled",
             "layout_columns_enabled",
             "layout_container_queries_enabled",
@@ -599,6 +640,13 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         device_pixel_ratio_override = Some(1.0);
     }
 
+    // Deterministic rendering does not depend on the scale factor of the display, unless one
+    // is asked for.
+    let deterministic = opt_match.opt_present("deterministic");
+    if deterministic {
+        device_pixel_ratio_override.get_or_insert(1.0);
+    }
+
     let url = if !opt_match.free.is_empty() {
         Some(opt_match.free[0][..].into())
     } else {
@@ -620,7 +668,7 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         no_native_titlebar,
         device_pixel_ratio_override,
         clean_shutdown: opt_match.opt_present("clean-shutdown"),
-        headless: opt_match.opt_present("z"),
+        headless: opt_match.opt_present("z") || deterministic,
         tracing_filter,
         initial_window_size,
         screen_size_override,
@@ -642,6 +690,7 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         wait_for_stable_image,
         time_profiling,
         time_profiler_trace_path: opt_match.opt_str("profiler-trace-path"),
+        time_profiler_chrome_trace_path: opt_match.opt_str("profiler-chrome-trace-path"),
         nonincremental_layout,
         user_stylesheets,
         hard_fail: opt_match.opt_present("f") && !opt_match.opt_present("F"),
@@ -659,6 +708,7 @@ pub(crate) fn parse_command_line_arguments(args: Vec<String>) -> ArgumentParsing
         local_script_source: opt_match.opt_str("local-script-source"),
         unminify_css: opt_match.opt_present("unminify-css"),
         print_pwm: opt_match.opt_present("print-pwm"),
+        deterministic,
     };
 
     ArgumentParsingResult::ChromeProcess(opts, preferences, servoshell_preferences)
@@ -793,6 +843,38 @@ fn test_parse_pref_from_command_line() {
     assert!(preferences.dom_bluetooth_enabled);
 }
 
+#[test]
+fn test_parse_deterministic_from_command_line() {
+    let args = vec!["servo".to_string(), "--deterministic".to_string()];
+    let ArgumentParsingResult::ChromeProcess(opts, _, servoshell_preferences) =
+        parse_command_line_arguments(args)
+    else {
+        unreachable!("No preferences for content process")
+    };
+    assert!(opts.deterministic);
+    assert!(servoshell_preferences.headless);
+    assert_eq!(
+        servoshell_preferences.device_pixel_ratio_override,
+        Some(1.0)
+    );
+
+    // An explicit device pixel ratio is kept.
+    let args = vec![
+        "servo".to_string(),
+        "--deterministic".to_string(),
+        "--device-pixel-ratio=2".to_string(),
+    ];
+    let ArgumentParsingResult::ChromeProcess(_, _, servoshell_preferences) =
+        parse_command_line_arguments(args)
+    else {
+        unreachable!("No preferences for content process")
+    };
+    assert_eq!(
+        servoshell_preferences.device_pixel_ratio_override,
+        Some(2.0)
+    );
+}
+
 #[test]
 fn test_invalid_prefs_from_command_line_panics() {
     let err_msg = std::panic::catch_unwind(|| {
@@ -0,0 +1,330 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+// This script runs in the DebuggerGlobalScope of a script thread, and drives
+// SpiderMonkey's Debugger API on behalf of the devtools server. Lines are
+// one-based and columns are zero-based, as in the devtools protocol.
+
+"use strict";
+
+const dbg = new Debugger();
+
+// The pipeline id of each debuggee global, keyed by its Debugger.Object.
+const debuggeePipelines = new WeakMap();
+
+// The sources seen so far, keyed by their id.
+const sources = new Map();
+
+// The breakpoints, keyed by URL and then by "line:column". Each one keeps the
+// handler that is installed in the scripts of its URL.
+const breakpoints = new Map();
+
+// The frames with onStep or onPop hooks for the current resume limit.
+let steppingFrames = [];
+
+// Whether the script thread is paused, in which case the hooks do nothing, so
+// that evaluating code in the paused frame does not pause again.
+let paused = false;
+
+function pipelineOf(script) {
+    return debuggeePipelines.get(script.global);
+}
+
+// Every script and function script nested in it.
+function allScripts(script) {
+    const scripts = [script];
+    for (const child of script.getChildScripts()) {
+        scripts.push(...allScripts(child));
+    }
+    return scripts;
+}
+
+function breakpointKey(line, column) {
+    return `${line}:${column ?? ""}`;
+}
+
+// The offsets of the script where a breakpoint at the given line and column
+// should be installed. Without a column, the first position of the line is
+// used.
+function breakpointOffsets(script, line, column) {
+    const positions = script.getPossibleBreakpoints({ line });
+    if (column !== null && column !== undefined) {
+        return positions
+            .filter(position => position.columnNumber - 1 === column)
+            .map(position => position.offset);
+    }
+    if (positions.length === 0) {
+        return [];
+    }
+    const first = positions.reduce((a, b) => (b.columnNumber < a.columnNumber ? b : a));
+    return [first.offset];
+}
+
+function installBreakpoint(script, breakpoint) {
+    for (const candidate of allScripts(script)) {
+        for (const offset of breakpointOffsets(candidate, breakpoint.line, breakpoint.column)) {
+            candidate.setBreakpoint(offset, breakpoint.handler);
+        }
+    }
+}
+
+function describeValue(value) {
+    switch (typeof value) {
+        case "undefined":
+            return { type: "undefined" };
+        case "boolean":
+            return { type: "boolean", booleanValue: value };
+        case "number":
+            return { type: "number", numberValue: value };
+        case "string":
+            return { type: "string", stringValue: value };
+        case "object":
+            if (value === null) {
+                return { type: "null" };
+            }
+            // Bindings that are optimized out, uninitialized or missing are
+            // shown as undefined.
+            if (!(value instanceof Debugger.Object)) {
+                return { type: "undefined" };
+            }
+            return { type: "object", objectClass: value.class };
+        default:
+            return { type: typeof value, stringValue: String(value) };
+    }
+}
+
+function describeScopes(frame) {
+    const scopes = [];
+    for (let env = frame.environment; env; env = env.parent) {
+        if (env.type !== "declarative") {
+            // The bindings of object environments, such as the global one, are
+            // not listed.
+            scopes.push({ kind: env.type, bindings: [] });
+            continue;
+        }
+        const functionName = env.calleeScript ? (env.calleeScript.displayName ?? "") : undefined;
+        const bindings = env.names().map(name => ({
+            name,
+            value: describeValue(env.getVariable(name)),
+        }));
+        scopes.push({
+            kind: env.calleeScript ? "function" : "block",
+            functionName,
+            bindings,
+        });
+    }
+    return scopes;
+}
+
+function describeFrames(youngest) {
+    const frames = [];
+    for (let frame = youngest; frame; frame = frame.older) {
+        if (!frame.script) {
+            continue;
+        }
+        const metadata = frame.script.getOffsetMetadata(frame.offset);
+        frames.push({
+            displayName: frame.callee ? (frame.callee.displayName ?? "") : "",
+            sourceId: frame.script.source.id,
+            line: metadata.lineNumber,
+            column: metadata.columnNumber - 1,
+            scopes: describeScopes(frame),
+        });
+    }
+    return frames;
+}
+
+function clearStepping() {
+    for (const frame of steppingFrames) {
+        if (frame.onStack) {
+            frame.onStep = undefined;
+            frame.onPop = undefined;
+        }
+    }
+    steppingFrames = [];
+    dbg.onEnterFrame = undefined;
+}
+
+function hookFrame(frame, onStep, onPop) {
+    frame.onStep = onStep;
+    frame.onPop = onPop;
+    steppingFrames.push(frame);
+}
+
+// Pause at the next position that starts a new line in the given frame.
+function stepToNextLine(frame, line) {
+    hookFrame(
+        frame,
+        function () {
+            const metadata = this.script.getOffsetMetadata(this.offset);
+            if (!metadata.isStepStart || metadata.lineNumber === line) {
+                return undefined;
+            }
+            return pauseAt(this, "resumeLimit");
+        },
+        function () {
+            stepOutOf(this);
+            return undefined;
+        },
+    );
+}
+
+// Pause at the next position of the caller once the given frame returns.
+function stepOutOf(frame) {
+    const older = frame.older;
+    if (!older || !older.script) {
+        return;
+    }
+    stepToNextLine(older, null);
+}
+
+function pauseAt(frame, reason) {
+    if (paused) {
+        return undefined;
+    }
+    const pipelineId = pipelineOf(frame.script);
+    if (!pipelineId) {
+        return undefined;
+    }
+    clearStepping();
+
+    paused = true;
+    let limit;
+    try {
+        limit = pause({ pipelineId, reason, frames: describeFrames(frame) });
+    } finally {
+        paused = false;
+    }
+
+    // Terminate the paused script, since its document is closing.
+    if (limit === "terminate") {
+        return null;
+    }
+    if (!frame.onStack) {
+        return undefined;
+    }
+    const line = frame.script.getOffsetMetadata(frame.offset).lineNumber;
+    switch (limit) {
+        case "stepOver":
+            stepToNextLine(frame, line);
+            break;
+        case "stepIn":
+            stepToNextLine(frame, line);
+            dbg.onEnterFrame = function (entered) {
+                if (entered.script) {
+                    stepToNextLine(entered, null);
+                }
+            };
+            break;
+        case "stepOut":
+            hookFrame(frame, undefined, function () {
+                stepOutOf(this);
+                return undefined;
+            });
+            break;
+    }
+    return undefined;
+}
+
+dbg.onNewScript = function (script) {
+    const pipelineId = pipelineOf(script);
+    if (!pipelineId) {
+        return;
+    }
+    const source = script.source;
+    if (!sources.has(source.id)) {
+        sources.set(source.id, source);
+        notifyNewSource({
+            pipelineId,
+            spidermonkeyId: source.id,
+            url: source.url ?? "",
+            introductionType: source.introductionType ?? undefined,
+            sourceMapUrl: source.sourceMapURL ?? undefined,
+            text: source.text,
+        });
+    }
+
+    const urlBreakpoints = breakpoints.get(script.url);
+    if (urlBreakpoints) {
+        for (const breakpoint of urlBreakpoints.values()) {
+            installBreakpoint(script, breakpoint);
+        }
+    }
+};
+
+dbg.onDebuggerStatement = function (frame) {
+    return pauseAt(frame, "debuggerStatement");
+};
+
+setHooks({
+    addDebuggee(global, pipelineId) {
+        const debuggee = dbg.addDebuggee(global);
+        debuggeePipelines.set(debuggee, pipelineId);
+        // Report the scripts that ran before the devtools client attached.
+        for (const script of dbg.findScripts({ global: debuggee })) {
+            dbg.onNewScript(script);
+        }
+    },
+
+    setBreakpoint(url, line, column) {
+        if (!breakpoints.has(url)) {
+            breakpoints.set(url, new Map());
+        }
+        const urlBreakpoints = breakpoints.get(url);
+        const key = breakpointKey(line, column);
+        if (urlBreakpoints.has(key)) {
+            return;
+        }
+        const breakpoint = {
+            line,
+            column,
+            handler: {
+                hit(frame) {
+                    return pauseAt(frame, "breakpoint");
+                },
+            },
+        };
+        urlBreakpoints.set(key, breakpoint);
+        for (const script of dbg.findScripts({ url, line })) {
+            for (const offset of breakpointOffsets(script, line, column)) {
+                script.setBreakpoint(offset, breakpoint.handler);
+            }
+        }
+    },
+
+    clearBreakpoint(url, line, column) {
+        const urlBreakpoints = breakpoints.get(url);
+        const breakpoint = urlBreakpoints?.get(breakpointKey(line, column));
+        if (!breakpoint) {
+            return;
+        }
+        urlBreakpoints.delete(breakpointKey(line, column));
+        for (const script of dbg.findScripts({ url })) {
+            script.clearBreakpoint(breakpoint.handler);
+        }
+    },
+
+    possibleBreakpoints(sourceId) {
+        const source = sources.get(sourceId);
+        if (!source) {
+            return [];
+        }
+        const locations = new Map();
+        for (const script of dbg.findScripts({ source })) {
+            for (const position of script.getPossibleBreakpoints()) {
+                const line = position.lineNumber;
+                const column = position.columnNumber - 1;
+                locations.set(breakpointKey(line, column), { line, column });
+            }
+        }
+        return [...locations.values()].sort((a, b) => a.line - b.line || a.column - b.column);
+    },
+
+    interrupt() {
+        dbg.onEnterFrame = function (frame) {
+            dbg.onEnterFrame = undefined;
+            return pauseAt(frame, "interrupted");
+        };
+    },
+});
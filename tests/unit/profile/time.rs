@@ -2,18 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::thread;
+use std::{fs, thread};
 
 use ::time::Duration;
+use base::cross_process_instant::CrossProcessInstant;
 use ipc_channel::ipc;
 use profile::time;
 use profile_traits::ipc as ProfiledIpc;
-use profile_traits::time::{ProfilerCategory, ProfilerData, ProfilerMsg};
+use profile_traits::time::{
+    ProfilerCategory, ProfilerData, ProfilerMsg, ProfilerThread, TimerMetadata,
+    TimerMetadataFrameType, TimerMetadataReflowType,
+};
 use servo_config::opts::OutputOptions;
 
 #[test]
 fn time_profiler_smoke_test() {
-    let chan = time::Profiler::create(&None, None);
+    let chan = time::Profiler::create(&None, None, None);
     assert!(true, "Can create the profiler thread");
 
     let (ipcchan, _ipcport) = ipc::channel().unwrap();
@@ -63,7 +67,7 @@ fn time_profiler_stats_test() {
 
 #[test]
 fn channel_profiler_test() {
-    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None);
+    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None, None);
     let (profiled_sender, profiled_receiver) = ProfiledIpc::channel(chan.clone()).unwrap();
     thread::spawn(move || {
         thread::sleep(std::time::Duration::from_secs(2));
@@ -88,7 +92,7 @@ fn channel_profiler_test() {
 
 #[test]
 fn bytes_channel_profiler_test() {
-    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None);
+    let chan = time::Profiler::create(&Some(OutputOptions::Stdout(5.0)), None, None);
     let (profiled_sender, profiled_receiver) = ProfiledIpc::bytes_channel(chan.clone()).unwrap();
     thread::spawn(move || {
         thread::sleep(std::time::Duration::from_secs(2));
@@ -111,6 +115,49 @@ fn bytes_channel_profiler_test() {
     };
 }
 
+#[test]
+fn chrome_trace_test() {
+    let path = std::env::temp_dir().join(format!("servo-trace-{}.json", std::process::id()));
+    let chan = time::Profiler::create(&None, None, None);
+    chan.send(ProfilerMsg::StartChromeTrace(path.clone()));
+
+    let start_time = CrossProcessInstant::now();
+    let end_time = start_time + Duration::milliseconds(3);
+    let metadata = TimerMetadata {
+        url: "http://example.com/".into(),
+        iframe: TimerMetadataFrameType::RootWindow,
+        incremental: TimerMetadataReflowType::FirstReflow,
+    };
+    chan.send(ProfilerMsg::Time(
+        (ProfilerCategory::LayoutStyleRecalc, Some(metadata)),
+        (start_time, end_time),
+        ProfilerThread {
+            process_id: 1,
+            thread_id: 2,
+            name: Some("Layout".into()),
+        },
+    ));
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    chan.send(ProfilerMsg::StopChromeTrace(sender));
+    receiver.recv().unwrap();
+
+    let trace: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+        .expect("The trace should be valid JSON");
+    let _ = fs::remove_file(&path);
+    let events = trace["traceEvents"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["ph"], "M");
+    assert_eq!(events[0]["args"]["name"], "Layout");
+    assert_eq!(events[1]["name"], "LayoutStyleRecalc");
+    assert_eq!(events[1]["cat"], "style");
+    assert_eq!(events[1]["ph"], "X");
+    assert_eq!(events[1]["pid"], 1);
+    assert_eq!(events[1]["tid"], 2);
+    assert_eq!(events[1]["dur"], 3000.0);
+    assert_eq!(events[1]["args"]["url"], "http://example.com/");
+}
+
 #[cfg(debug_assertions)]
 #[test]
 #[should_panic]
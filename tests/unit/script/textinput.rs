@@ -530,6 +530,87 @@ fn test_navigation_keyboard_shortcuts() {
     assert_eq!(textinput.edit_point().index, UTF8Bytes::zero());
 }
 
+#[test]
+fn test_navigation_keyboard_shortcuts_without_macos() {
+    let mut textinput = text_input(Lines::Multiple, "hello áéc\nfoo");
+
+    // Test that CTRL + Right moves to the end of the word.
+    textinput.handle_keydown_aux(Key::ArrowRight, Modifiers::CONTROL, false);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(5));
+    textinput.handle_keydown_aux(Key::ArrowRight, Modifiers::CONTROL, false);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(11));
+    // Test that CTRL + Left moves to the start of the word.
+    textinput.handle_keydown_aux(Key::ArrowLeft, Modifiers::CONTROL, false);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(6));
+
+    // Test that Home and End move to the ends of the current line.
+    textinput.handle_keydown_aux(Key::End, Modifiers::empty(), false);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(11));
+    textinput.handle_keydown_aux(Key::Home, Modifiers::empty(), false);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes::zero());
+
+    // Test that CTRL + End and CTRL + Home move to the ends of the text.
+    textinput.handle_keydown_aux(Key::End, Modifiers::CONTROL, false);
+    assert_eq!(textinput.edit_point().line, 1);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(3));
+    textinput.handle_keydown_aux(Key::Home, Modifiers::CONTROL, false);
+    assert_eq!(textinput.edit_point().line, 0);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes::zero());
+}
+
+#[test]
+fn test_selection_keyboard_shortcuts() {
+    let mut textinput = text_input(Lines::Single, "hello world");
+
+    // Test that SHIFT + End selects up to the end of the line, forwards.
+    textinput.handle_keydown_aux(Key::ArrowRight, Modifiers::CONTROL, false);
+    textinput.handle_keydown_aux(Key::End, Modifiers::SHIFT, false);
+    assert_eq!(textinput.selection_start_offset(), UTF8Bytes(5));
+    assert_eq!(textinput.selection_end_offset(), UTF8Bytes(11));
+    assert_eq!(textinput.selection_direction(), SelectionDirection::Forward);
+
+    // Test that SHIFT + Home selects back to the start of the line, backwards.
+    textinput.handle_keydown_aux(Key::ArrowLeft, Modifiers::empty(), false);
+    textinput.handle_keydown_aux(Key::Home, Modifiers::SHIFT, false);
+    assert_eq!(textinput.selection_start_offset(), UTF8Bytes::zero());
+    assert_eq!(textinput.selection_end_offset(), UTF8Bytes(5));
+    assert_eq!(
+        textinput.selection_direction(),
+        SelectionDirection::Backward
+    );
+
+    // Test that CTRL + SHIFT + End selects up to the end of the text.
+    textinput.handle_keydown_aux(Key::ArrowRight, Modifiers::empty(), false);
+    textinput.handle_keydown_aux(Key::End, Modifiers::CONTROL | Modifiers::SHIFT, false);
+    assert_eq!(textinput.selection_start_offset(), UTF8Bytes(5));
+    assert_eq!(textinput.selection_end_offset(), UTF8Bytes(11));
+    assert_eq!(textinput.selection_direction(), SelectionDirection::Forward);
+}
+
+#[test]
+fn test_word_deletion_keyboard_shortcuts() {
+    let mut textinput = text_input(Lines::Multiple, "hello world\nfoo");
+    textinput.handle_keydown_aux(Key::End, Modifiers::CONTROL, false);
+
+    // Test that CTRL + Backspace deletes the word before the edit point.
+    textinput.handle_keydown_aux(Key::Backspace, Modifiers::CONTROL, false);
+    assert_eq!(textinput.get_content(), "hello world\n");
+    // And the line break before it, at the start of a line.
+    textinput.handle_keydown_aux(Key::Backspace, Modifiers::CONTROL, false);
+    assert_eq!(textinput.get_content(), "hello ");
+
+    // Test that CTRL + Delete deletes the word after the edit point.
+    textinput.handle_keydown_aux(Key::Home, Modifiers::empty(), false);
+    textinput.handle_keydown_aux(Key::Delete, Modifiers::CONTROL, false);
+    assert_eq!(textinput.get_content(), " ");
+
+    // Test that ALT + Backspace deletes the word before the edit point on macOS.
+    let mut textinput = text_input(Lines::Single, "hello world");
+    textinput.handle_keydown_aux(Key::ArrowRight, Modifiers::META, true);
+    textinput.handle_keydown_aux(Key::Backspace, Modifiers::ALT, true);
+    assert_eq!(textinput.get_content(), "hello ");
+}
+
 #[test]
 fn test_textinput_handle_return() {
     let mut single_line_textinput = text_input(Lines::Single, "abcdef");
@@ -864,3 +945,82 @@ fn test_backspace_in_textarea_at_beginning_of_line() {
     textinput.handle_keydown_aux(Key::Backspace, Modifiers::empty(), false);
     assert_eq!(textinput.get_content(), DOMString::from("first line"));
 }
+
+#[test]
+fn test_composition_replaces_composition_string() {
+    let mut textinput = text_input(Lines::Single, "ab");
+    textinput.set_edit_point_index(1);
+
+    textinput.handle_compositionstart();
+    assert_eq!(textinput.composition(), Some(1..1));
+
+    textinput.handle_compositionupdate("x");
+    assert_eq!(textinput.get_content(), DOMString::from("axb"));
+    assert_eq!(textinput.composition(), Some(1..2));
+
+    textinput.handle_compositionupdate("日本");
+    assert_eq!(textinput.get_content(), DOMString::from("a日本b"));
+    assert_eq!(textinput.composition(), Some(1..7));
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(7));
+
+    textinput.handle_compositionend("日本語");
+    assert_eq!(textinput.get_content(), DOMString::from("a日本語b"));
+    assert_eq!(textinput.composition(), None);
+    assert_eq!(textinput.edit_point().index, UTF8Bytes(10));
+}
+
+#[test]
+fn test_composition_replaces_selection() {
+    let mut textinput = text_input(Lines::Single, "abcd");
+    textinput.set_selection_range(1, 3, SelectionDirection::Forward);
+
+    textinput.handle_compositionstart();
+    assert_eq!(textinput.get_content(), DOMString::from("ad"));
+    assert_eq!(textinput.composition(), Some(1..1));
+
+    textinput.handle_compositionupdate("x");
+    textinput.handle_compositionend("xy");
+    assert_eq!(textinput.get_content(), DOMString::from("axyd"));
+    assert_eq!(textinput.composition(), None);
+}
+
+#[test]
+fn test_compositionend_without_compositionstart_inserts_at_selection() {
+    let mut textinput = text_input(Lines::Single, "abc");
+    textinput.set_selection_range(1, 2, SelectionDirection::Forward);
+    textinput.handle_compositionend("x");
+    assert_eq!(textinput.get_content(), DOMString::from("axc"));
+    assert_eq!(textinput.composition(), None);
+}
+
+#[test]
+fn test_composition_empty_update_removes_composition_string() {
+    let mut textinput = text_input(Lines::Single, "ab");
+    textinput.set_edit_point_index(1);
+    textinput.handle_compositionstart();
+    textinput.handle_compositionupdate("xyz");
+    textinput.handle_compositionupdate("");
+    assert_eq!(textinput.get_content(), DOMString::from("ab"));
+    assert_eq!(textinput.composition(), Some(1..1));
+}
+
+#[test]
+fn test_composition_in_textarea() {
+    let mut textinput = text_input(Lines::Multiple, "first\nsecond");
+    textinput.handle_keydown_aux(Key::ArrowDown, Modifiers::empty(), false);
+    textinput.handle_compositionstart();
+    textinput.handle_compositionupdate("x");
+    assert_eq!(textinput.get_content(), DOMString::from("first\nxsecond"));
+    assert_eq!(textinput.composition(), Some(6..7));
+    textinput.handle_compositionend("yz");
+    assert_eq!(textinput.get_content(), DOMString::from("first\nyzsecond"));
+}
+
+#[test]
+fn test_set_content_cancels_composition() {
+    let mut textinput = text_input(Lines::Single, "");
+    textinput.handle_compositionstart();
+    textinput.handle_compositionupdate("x");
+    textinput.set_content(DOMString::from("abc"));
+    assert_eq!(textinput.composition(), None);
+}